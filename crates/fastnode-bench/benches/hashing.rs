@@ -35,5 +35,34 @@ fn bench_blake3_file(c: &mut Criterion) {
     group.finish();
 }
 
-criterion_group!(benches, bench_blake3_bytes, bench_blake3_file);
+/// Large files: chunked parallel hashing against reading the whole file and
+/// hashing it on one thread.
+fn bench_blake3_file_large(c: &mut Criterion) {
+    let mut group = c.benchmark_group("blake3_file_large");
+    group.sample_size(20);
+
+    for size in [1024 * 1024, 8 * 1024 * 1024, 64 * 1024 * 1024] {
+        let data = vec![0xABu8; size];
+        let mut file = NamedTempFile::new().unwrap();
+        file.write_all(&data).unwrap();
+        file.flush().unwrap();
+
+        group.throughput(Throughput::Bytes(size as u64));
+        group.bench_with_input(BenchmarkId::new("chunked", size), &file, |b, file| {
+            b.iter(|| fastnode_util::hash::blake3_file(black_box(file.path())));
+        });
+        group.bench_with_input(BenchmarkId::new("read_all", size), &file, |b, file| {
+            b.iter(|| blake3_bytes(&std::fs::read(black_box(file.path())).unwrap()));
+        });
+    }
+
+    group.finish();
+}
+
+criterion_group!(
+    benches,
+    bench_blake3_bytes,
+    bench_blake3_file,
+    bench_blake3_file_large
+);
 criterion_main!(benches);
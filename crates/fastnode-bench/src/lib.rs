@@ -7,3 +7,8 @@
 //!
 //! This crate is intentionally minimal - it exists to hold criterion benchmarks
 //! and establish a performance measurement culture from day one.
+//!
+//! Compare against an earlier run with Criterion's baselines:
+//! `cargo bench -p fastnode-bench -- --save-baseline main`, then
+//! `-- --baseline main` on a branch. For end-to-end numbers gated in CI, use
+//! `howth bench <kind> --save` and `--baseline <sha> --threshold <pct>`.
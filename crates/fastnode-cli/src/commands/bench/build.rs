@@ -1,3 +1,4 @@
+use super::history::{self, HistoryOptions};
 use fastnode_core::bench::{
     run_build_bench, BenchTarget, BuildBenchParams, BuildBenchReport, Severity,
 };
@@ -12,7 +13,13 @@ pub const DEFAULT_ITERS: u32 = 10;
 pub const DEFAULT_WARMUP: u32 = 2;
 
 /// Run the bench transpile command.
-pub fn run_transpile(iters: u32, warmup: u32, project: Option<PathBuf>, json: bool) -> Result<()> {
+pub fn run_transpile(
+    iters: u32,
+    warmup: u32,
+    project: Option<PathBuf>,
+    history: &HistoryOptions,
+    json: bool,
+) -> Result<()> {
     let params = BuildBenchParams {
         target: BenchTarget::Transpile,
         iters,
@@ -22,17 +29,17 @@ pub fn run_transpile(iters: u32, warmup: u32, project: Option<PathBuf>, json: bo
     let project_path = project.as_deref();
     let report = run_build_bench(params, project_path);
 
-    if json {
-        print_json(&report)?;
-    } else {
-        print_human(&report)?;
-    }
-
-    Ok(())
+    history::finish("transpile", &report, history, json, print_human)
 }
 
 /// Run the bench devloop command.
-pub fn run_devloop(iters: u32, warmup: u32, project: Option<PathBuf>, json: bool) -> Result<()> {
+pub fn run_devloop(
+    iters: u32,
+    warmup: u32,
+    project: Option<PathBuf>,
+    history: &HistoryOptions,
+    json: bool,
+) -> Result<()> {
     let params = BuildBenchParams {
         target: BenchTarget::Devloop,
         iters,
@@ -42,19 +49,7 @@ pub fn run_devloop(iters: u32, warmup: u32, project: Option<PathBuf>, json: bool
     let project_path = project.as_deref();
     let report = run_build_bench(params, project_path);
 
-    if json {
-        print_json(&report)?;
-    } else {
-        print_human(&report)?;
-    }
-
-    Ok(())
-}
-
-fn print_json(report: &BuildBenchReport) -> Result<()> {
-    let json = serde_json::to_string_pretty(report).into_diagnostic()?;
-    println!("{json}");
-    Ok(())
+    history::finish("devloop", &report, history, json, print_human)
 }
 
 /// ANSI color codes for each case in the benchmark.
@@ -1,3 +1,4 @@
+use super::history::{self, HistoryOptions};
 use fastnode_core::bench::{run_bundler_bench, BundlerBenchParams, BundlerBenchReport, Severity};
 use miette::{IntoDiagnostic, Result};
 use std::io::{self, Write};
@@ -12,7 +13,13 @@ pub const DEFAULT_ITERS: u32 = 5;
 pub const DEFAULT_WARMUP: u32 = 1;
 
 /// Run the bench bundler command.
-pub fn run(modules: u32, iters: u32, warmup: u32, json: bool) -> Result<()> {
+pub fn run(
+    modules: u32,
+    iters: u32,
+    warmup: u32,
+    history: &HistoryOptions,
+    json: bool,
+) -> Result<()> {
     let params = BundlerBenchParams {
         module_count: modules,
         iters,
@@ -26,19 +33,7 @@ pub fn run(modules: u32, iters: u32, warmup: u32, json: bool) -> Result<()> {
 
     let report = run_bundler_bench(params);
 
-    if json {
-        print_json(&report)?;
-    } else {
-        print_human(&report)?;
-    }
-
-    Ok(())
-}
-
-fn print_json(report: &BundlerBenchReport) -> Result<()> {
-    let json = serde_json::to_string_pretty(report).into_diagnostic()?;
-    println!("{json}");
-    Ok(())
+    history::finish("bundler", &report, history, json, print_human)
 }
 
 fn print_human(report: &BundlerBenchReport) -> Result<()> {
@@ -0,0 +1,212 @@
+use fastnode_core::bench::{
+    compare, find_baseline, BenchComparison, BenchMetrics, BenchRun, DeltaStatus, MetricDelta,
+};
+use miette::{miette, IntoDiagnostic, Result};
+use serde::Serialize;
+use std::io::{self, Write};
+use std::path::PathBuf;
+
+/// Saving and baseline comparison, shared by every `howth bench` subcommand.
+#[derive(Debug, Clone)]
+pub struct HistoryOptions {
+    /// Save this run under `dir`, keyed by the current git sha.
+    pub save: bool,
+    /// Baseline to compare against: a sha (prefix), `latest`, or a file path.
+    pub baseline: Option<String>,
+    /// Regression threshold in percent.
+    pub threshold_pct: f64,
+    /// Directory holding saved runs.
+    pub dir: PathBuf,
+}
+
+/// Print a bench report, then save it and compare it against a baseline as
+/// requested. Fails when any metric regressed past the threshold.
+///
+/// With `--json` and a baseline, stdout is one object with `report` and
+/// `comparison` keys; otherwise the report is printed unchanged.
+pub fn finish<R: Serialize + BenchMetrics>(
+    kind: &str,
+    report: &R,
+    options: &HistoryOptions,
+    json: bool,
+    print_human: impl FnOnce(&R) -> Result<()>,
+) -> Result<()> {
+    let metrics = report.metrics();
+    let cwd = std::env::current_dir().into_diagnostic()?;
+    let current_sha = if options.save || options.baseline.is_some() {
+        fastnode_core::git::head_sha(&cwd).ok()
+    } else {
+        None
+    };
+
+    let comparison = match &options.baseline {
+        Some(reference) => {
+            let baseline = find_baseline(&options.dir, kind, reference, current_sha.as_deref())
+                .map_err(|e| miette!("[{}] {e}", e.code()))?;
+            Some(compare(
+                &baseline,
+                current_sha.as_deref(),
+                &metrics,
+                options.threshold_pct,
+            ))
+        }
+        None => None,
+    };
+
+    if json {
+        let value = match &comparison {
+            Some(comparison) => serde_json::json!({ "report": report, "comparison": comparison }),
+            None => serde_json::to_value(report).into_diagnostic()?,
+        };
+        let json = serde_json::to_string_pretty(&value).into_diagnostic()?;
+        println!("{json}");
+    } else {
+        print_human(report)?;
+        if let Some(comparison) = &comparison {
+            print_comparison(comparison)?;
+        }
+    }
+
+    if options.save {
+        let Some(sha) = current_sha else {
+            return Err(miette!(
+                "--save needs a git commit to key the run by (no HEAD in {})",
+                cwd.display()
+            ));
+        };
+        let run = BenchRun::new(kind, sha, metrics);
+        let path = run
+            .save(&options.dir)
+            .map_err(|e| miette!("[{}] {e}", e.code()))?;
+        if !json {
+            eprintln!(
+                "\x1b[90mSaved {} results to {}\x1b[0m",
+                run.short_sha(),
+                path.display()
+            );
+        }
+    }
+
+    if let Some(comparison) = &comparison {
+        let regressed = comparison.regressions().count();
+        if regressed > 0 {
+            return Err(miette!(
+                "{regressed} metric{} regressed more than {}% against {}",
+                if regressed == 1 { "" } else { "s" },
+                comparison.threshold_pct,
+                short(&comparison.baseline_sha)
+            ));
+        }
+    }
+    Ok(())
+}
+
+fn short(sha: &str) -> &str {
+    &sha[..sha.len().min(7)]
+}
+
+fn print_comparison(comparison: &BenchComparison) -> Result<()> {
+    let mut out = io::stdout().lock();
+
+    writeln!(
+        out,
+        "\x1b[1mCompared with {}\x1b[0m \x1b[90m(threshold {}%)\x1b[0m",
+        short(&comparison.baseline_sha),
+        comparison.threshold_pct
+    )
+    .into_diagnostic()?;
+
+    let width = comparison
+        .deltas
+        .iter()
+        .map(|d| d.name.len())
+        .max()
+        .unwrap_or(0)
+        .max("Metric".len());
+    writeln!(
+        out,
+        "  \x1b[90m{:<width$}  {:>12}  {:>12}  {:>8}\x1b[0m",
+        "Metric", "Baseline", "Current", "Delta"
+    )
+    .into_diagnostic()?;
+
+    for delta in &comparison.deltas {
+        let (color, label) = match delta.status {
+            DeltaStatus::Regressed => ("\x1b[31m", "regressed"),
+            DeltaStatus::Improved => ("\x1b[32m", "improved"),
+            DeltaStatus::Unchanged => ("\x1b[90m", ""),
+            DeltaStatus::Added => ("\x1b[90m", "new"),
+            DeltaStatus::Removed => ("\x1b[90m", "removed"),
+        };
+        writeln!(
+            out,
+            "  {:<width$}  {:>12}  {:>12}  {color}{:>8}  {label}\x1b[0m",
+            delta.name,
+            format_value(delta, delta.baseline),
+            format_value(delta, delta.current),
+            delta
+                .change_pct
+                .map_or_else(|| "-".to_string(), |pct| format!("{pct:+.1}%")),
+        )
+        .into_diagnostic()?;
+    }
+    writeln!(out).into_diagnostic()?;
+
+    out.flush().into_diagnostic()?;
+    Ok(())
+}
+
+/// Format a metric value for the delta table.
+fn format_value(delta: &MetricDelta, value: Option<f64>) -> String {
+    let Some(value) = value else {
+        return "-".to_string();
+    };
+    match delta.unit.as_str() {
+        "ns" => {
+            if value >= 1_000_000_000.0 {
+                format!("{:.2}s", value / 1_000_000_000.0)
+            } else if value >= 1_000_000.0 {
+                format!("{:.2}ms", value / 1_000_000.0)
+            } else if value >= 1_000.0 {
+                format!("{:.2}us", value / 1_000.0)
+            } else {
+                format!("{value:.0}ns")
+            }
+        }
+        "bytes" => {
+            if value >= 1024.0 * 1024.0 {
+                format!("{:.1} MiB", value / (1024.0 * 1024.0))
+            } else if value >= 1024.0 {
+                format!("{:.1} KiB", value / 1024.0)
+            } else {
+                format!("{value:.0} B")
+            }
+        }
+        unit => format!("{value:.0} {unit}"),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn delta(unit: &str) -> MetricDelta {
+        MetricDelta {
+            name: "x".to_string(),
+            unit: unit.to_string(),
+            baseline: None,
+            current: None,
+            change_pct: None,
+            status: DeltaStatus::Added,
+        }
+    }
+
+    #[test]
+    fn test_format_value() {
+        assert_eq!(format_value(&delta("ns"), Some(1_500_000.0)), "1.50ms");
+        assert_eq!(format_value(&delta("ns"), Some(750.0)), "750ns");
+        assert_eq!(format_value(&delta("bytes"), Some(2048.0)), "2.0 KiB");
+        assert_eq!(format_value(&delta("req/s"), Some(51234.4)), "51234 req/s");
+        assert_eq!(format_value(&delta("ns"), None), "-");
+    }
+}
@@ -1,3 +1,4 @@
+use super::history::{self, HistoryOptions};
 use fastnode_core::bench::http::{run_http_bench, HttpBenchParams, HttpBenchReport};
 use fastnode_core::bench::Severity;
 use miette::{IntoDiagnostic, Result};
@@ -12,36 +13,35 @@ pub const DEFAULT_CONNECTIONS: u32 = fastnode_core::bench::DEFAULT_CONNECTIONS;
 /// Default warmup duration in seconds.
 pub const DEFAULT_WARMUP_SECS: u32 = fastnode_core::bench::DEFAULT_WARMUP_SECS;
 
+/// Default requests per round trip on each connection.
+pub const DEFAULT_PIPELINING: u32 = fastnode_core::bench::DEFAULT_PIPELINING;
+
 /// Run the bench http command.
-pub fn run(duration_secs: u32, connections: u32, warmup_secs: u32, json: bool) -> Result<()> {
+pub fn run(
+    duration_secs: u32,
+    connections: u32,
+    warmup_secs: u32,
+    pipelining: u32,
+    history: &HistoryOptions,
+    json: bool,
+) -> Result<()> {
     let params = HttpBenchParams {
         duration_secs,
         connections,
         warmup_secs,
+        pipelining,
     };
 
     eprintln!("Running HTTP benchmark...");
     eprintln!(
-        "  Duration: {}s, Connections: {}, Warmup: {}s",
-        duration_secs, connections, warmup_secs
+        "  Duration: {}s, Connections: {}, Pipelining: {}, Warmup: {}s",
+        duration_secs, connections, pipelining, warmup_secs
     );
     eprintln!();
 
     let report = run_http_bench(params);
 
-    if json {
-        print_json(&report)?;
-    } else {
-        print_human(&report)?;
-    }
-
-    Ok(())
-}
-
-fn print_json(report: &HttpBenchReport) -> Result<()> {
-    let json = serde_json::to_string_pretty(report).into_diagnostic()?;
-    println!("{json}");
-    Ok(())
+    history::finish("http", &report, history, json, print_human)
 }
 
 /// ANSI color codes for each tool in the benchmark.
@@ -73,8 +73,11 @@ fn print_human(report: &HttpBenchReport) -> Result<()> {
     .into_diagnostic()?;
     writeln!(
         out,
-        "\x1b[90mParams: {}s duration, {} connections, {}s warmup\x1b[0m",
-        report.params.duration_secs, report.params.connections, report.params.warmup_secs
+        "\x1b[90mParams: {}s duration, {} connections, pipelining {}, {}s warmup\x1b[0m",
+        report.params.duration_secs,
+        report.params.connections,
+        report.params.pipelining,
+        report.params.warmup_secs
     )
     .into_diagnostic()?;
     writeln!(out).into_diagnostic()?;
@@ -85,14 +88,14 @@ fn print_human(report: &HttpBenchReport) -> Result<()> {
     // Results table header
     writeln!(
         out,
-        "{:<10} {:>12} {:>14} {:>12} {:>12} {:>8}",
-        "Tool", "RPS", "Total Reqs", "Avg Lat", "p99 Lat", "Errors"
+        "{:<12} {:>10} {:>12} {:>10} {:>10} {:>10} {:>10} {:>8}",
+        "Tool", "RPS", "Total Reqs", "Avg Lat", "p50 Lat", "p99 Lat", "p99.9 Lat", "Errors"
     )
     .into_diagnostic()?;
     writeln!(
         out,
-        "{:-<10} {:-<12} {:-<14} {:-<12} {:-<12} {:-<8}",
-        "", "", "", "", "", ""
+        "{:-<12} {:-<10} {:-<12} {:-<10} {:-<10} {:-<10} {:-<10} {:-<8}",
+        "", "", "", "", "", "", "", ""
     )
     .into_diagnostic()?;
 
@@ -102,21 +105,23 @@ fn print_human(report: &HttpBenchReport) -> Result<()> {
         let rps = format_rps(result.rps);
         let total = format_number(result.total_requests);
         let avg_lat = format_latency(result.avg_latency_us);
+        let p50_lat = format_latency(result.p50_latency_us);
         let p99_lat = format_latency(result.p99_latency_us);
+        let tail_lat = format_latency(result.p999_latency_us);
 
         // Highlight the fastest
         if (result.rps - max_rps).abs() < 0.01 {
             writeln!(
                 out,
-                "{color}{:<10}\x1b[0m \x1b[1;32m{:>12}\x1b[0m {:>14} {:>12} {:>12} {:>8}",
-                result.tool, rps, total, avg_lat, p99_lat, result.errors
+                "{color}{:<12}\x1b[0m \x1b[1;32m{:>10}\x1b[0m {:>12} {:>10} {:>10} {:>10} {:>10} {:>8}",
+                result.tool, rps, total, avg_lat, p50_lat, p99_lat, tail_lat, result.errors
             )
             .into_diagnostic()?;
         } else {
             writeln!(
                 out,
-                "{color}{:<10}\x1b[0m {:>12} {:>14} {:>12} {:>12} {:>8}",
-                result.tool, rps, total, avg_lat, p99_lat, result.errors
+                "{color}{:<12}\x1b[0m {:>10} {:>12} {:>10} {:>10} {:>10} {:>10} {:>8}",
+                result.tool, rps, total, avg_lat, p50_lat, p99_lat, tail_lat, result.errors
             )
             .into_diagnostic()?;
         }
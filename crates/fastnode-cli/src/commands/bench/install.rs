@@ -1,3 +1,4 @@
+use super::history::{self, HistoryOptions};
 use fastnode_core::bench::install::{run_install_bench, InstallBenchParams, InstallBenchReport};
 use fastnode_core::bench::Severity;
 use miette::{IntoDiagnostic, Result};
@@ -11,24 +12,18 @@ pub const DEFAULT_ITERS: u32 = fastnode_core::bench::install::DEFAULT_ITERS;
 pub const DEFAULT_WARMUP: u32 = fastnode_core::bench::install::DEFAULT_WARMUP;
 
 /// Run the bench install command.
-pub fn run(iters: u32, warmup: u32, project: Option<PathBuf>, json: bool) -> Result<()> {
+pub fn run(
+    iters: u32,
+    warmup: u32,
+    project: Option<PathBuf>,
+    history: &HistoryOptions,
+    json: bool,
+) -> Result<()> {
     let params = InstallBenchParams { iters, warmup };
     let project_path = project.as_deref();
     let report = run_install_bench(params, project_path);
 
-    if json {
-        print_json(&report)?;
-    } else {
-        print_human(&report)?;
-    }
-
-    Ok(())
-}
-
-fn print_json(report: &InstallBenchReport) -> Result<()> {
-    let json = serde_json::to_string_pretty(report).into_diagnostic()?;
-    println!("{json}");
-    Ok(())
+    history::finish("install", &report, history, json, print_human)
 }
 
 /// ANSI color codes for each tool in the benchmark.
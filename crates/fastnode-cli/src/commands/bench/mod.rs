@@ -1,5 +1,6 @@
 pub mod build;
 pub mod bundler;
+pub mod history;
 pub mod http;
 pub mod install;
 pub mod smoke;
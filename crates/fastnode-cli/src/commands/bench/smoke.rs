@@ -1,3 +1,4 @@
+use super::history::{self, HistoryOptions};
 use fastnode_core::bench::{run_smoke_benchmarks, BenchReport, Severity};
 use miette::{IntoDiagnostic, Result};
 use std::io::{self, Write};
@@ -15,25 +16,19 @@ pub const DEFAULT_SIZE_MIB: u32 = 8;
 ///
 /// When `json` is true, outputs a single JSON object to stdout.
 /// Otherwise, outputs human-readable formatted text to stdout.
-pub fn run(iters: u32, warmup: u32, size_mib: u32, json: bool) -> Result<()> {
+pub fn run(
+    iters: u32,
+    warmup: u32,
+    size_mib: u32,
+    history: &HistoryOptions,
+    json: bool,
+) -> Result<()> {
     // Convert MiB to bytes
     let size_bytes = u64::from(size_mib) * 1024 * 1024;
 
     let report = run_smoke_benchmarks(iters, warmup, size_bytes);
 
-    if json {
-        print_json(&report)?;
-    } else {
-        print_human(&report)?;
-    }
-
-    Ok(())
-}
-
-fn print_json(report: &BenchReport) -> Result<()> {
-    let json = serde_json::to_string_pretty(report).into_diagnostic()?;
-    println!("{json}");
-    Ok(())
+    history::finish("smoke", &report, history, json, print_human)
 }
 
 /// ANSI color codes for each smoke benchmark.
@@ -1,3 +1,4 @@
+use super::history::{self, HistoryOptions};
 use fastnode_core::bench::test::{run_test_bench, TestBenchParams, TestBenchReport};
 use fastnode_core::bench::Severity;
 use miette::{IntoDiagnostic, Result};
@@ -10,23 +11,11 @@ pub const DEFAULT_ITERS: u32 = fastnode_core::bench::test::DEFAULT_ITERS;
 pub const DEFAULT_WARMUP: u32 = fastnode_core::bench::test::DEFAULT_WARMUP;
 
 /// Run the bench test command.
-pub fn run(iters: u32, warmup: u32, json: bool) -> Result<()> {
+pub fn run(iters: u32, warmup: u32, history: &HistoryOptions, json: bool) -> Result<()> {
     let params = TestBenchParams { iters, warmup };
     let report = run_test_bench(params);
 
-    if json {
-        print_json(&report)?;
-    } else {
-        print_human(&report)?;
-    }
-
-    Ok(())
-}
-
-fn print_json(report: &TestBenchReport) -> Result<()> {
-    let json = serde_json::to_string_pretty(report).into_diagnostic()?;
-    println!("{json}");
-    Ok(())
+    history::finish("test", &report, history, json, print_human)
 }
 
 /// ANSI color codes for each tool in the benchmark.
@@ -1,23 +1,28 @@
 //! `howth build` command implementation.
 
+use crate::progress::Progress;
+use fastnode_core::build::{self, build_graph_from_project, BUILD_CLEAN_FAILED};
 use fastnode_core::config::Channel;
 use fastnode_core::paths;
+use fastnode_core::pkg::WorkspacePackage;
 use fastnode_core::VERSION;
 use fastnode_daemon::ipc::{IpcStream, MAX_FRAME_SIZE};
 use fastnode_proto::{
-    encode_frame, BuildCacheStatus, BuildRunResult, Frame, FrameResponse, Request, Response,
-    BUILD_RUN_SCHEMA_VERSION,
+    encode_frame, BuildCacheStatus, BuildDiagnostic, BuildDiagnosticSeverity, BuildRunResult,
+    Frame, FrameResponse, Request, Response, BUILD_RUN_SCHEMA_VERSION,
 };
 use miette::{IntoDiagnostic, Result};
 use serde::Serialize;
-use std::io;
-use std::path::PathBuf;
+use std::io::{self, IsTerminal};
+use std::path::{Path, PathBuf};
 
 /// Build command action.
 #[derive(Debug, Clone)]
 pub struct BuildAction {
     pub cwd: PathBuf,
     pub force: bool,
+    /// Remove node outputs before building.
+    pub clean: bool,
     pub dry_run: bool,
     pub max_parallel: Option<u32>,
     pub profile: bool,
@@ -27,8 +32,12 @@ pub struct BuildAction {
     pub watch: bool,
     /// Debounce delay in milliseconds for watch mode.
     pub debounce_ms: u32,
+    /// Show the interactive dashboard in watch mode.
+    pub ui: bool,
     /// Targets to build (v2.1). Empty = use defaults.
     pub targets: Vec<String>,
+    /// Executor command for running script nodes remotely.
+    pub remote_exec: Option<String>,
 }
 
 /// Build result for JSON output (matches protocol's `BuildRunResult`).
@@ -79,9 +88,15 @@ struct BuildNodeResultJson {
     /// Number of files processed (for batch transpile nodes, v3.1.2).
     #[serde(skip_serializing_if = "Option::is_none")]
     files_count: Option<u32>,
+    /// Number of files actually re-emitted (batch transpile nodes).
+    #[serde(skip_serializing_if = "Option::is_none")]
+    changed_count: Option<u32>,
     /// Whether this node was auto-discovered (v3.1.2).
     #[serde(skip_serializing_if = "std::ops::Not::not", default)]
     auto_discovered: bool,
+    /// Structured diagnostics (e.g. type errors).
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    diagnostics: Vec<BuildDiagnostic>,
 }
 
 #[derive(Serialize)]
@@ -106,6 +121,10 @@ pub fn run(action: BuildAction, channel: Channel, json: bool) -> Result<()> {
     let endpoint = paths::ipc_endpoint(channel);
     let show_why = action.why;
 
+    if action.clean && !action.dry_run {
+        clean_outputs(&action.cwd, json);
+    }
+
     // Run the async client
     let runtime = tokio::runtime::Runtime::new().into_diagnostic()?;
 
@@ -121,7 +140,7 @@ pub fn run(action: BuildAction, channel: Channel, json: bool) -> Result<()> {
         }
     } else {
         // Single build
-        let result = runtime.block_on(async { send_build_request(&endpoint, &action).await });
+        let result = runtime.block_on(async { send_build_request(&endpoint, &action, json).await });
 
         match result {
             Ok((response, _server_version)) => handle_response(response, json, show_why),
@@ -148,6 +167,75 @@ pub fn run(action: BuildAction, channel: Channel, json: bool) -> Result<()> {
     }
 }
 
+/// Build each selected workspace package in turn (human output only).
+///
+/// Packages are built in the given (dependency) order; the first failing
+/// build exits the process.
+pub fn run_workspaces(
+    action: BuildAction,
+    packages: &[WorkspacePackage],
+    channel: Channel,
+) -> Result<()> {
+    if packages.is_empty() {
+        println!("No workspace packages selected.");
+        return Ok(());
+    }
+    for pkg in packages {
+        println!("{}:", pkg.name);
+        run(
+            BuildAction {
+                cwd: pkg.path.clone(),
+                ..action.clone()
+            },
+            channel,
+            false,
+        )?;
+    }
+    Ok(())
+}
+
+/// Remove the outputs of every node in the project's build graph.
+///
+/// Exits on failure so a clean build never runs on top of stale outputs.
+fn clean_outputs(cwd: &Path, json: bool) {
+    let result = build_graph_from_project(cwd)
+        .map_err(|e| (e.code.to_string(), e.message))
+        .and_then(|graph| {
+            build::clean_outputs(&graph).map_err(|e| {
+                (
+                    BUILD_CLEAN_FAILED.to_string(),
+                    format!("Failed to clean: {e}"),
+                )
+            })
+        });
+
+    match result {
+        Ok(removed) => {
+            if !json && !removed.is_empty() {
+                println!("Cleaned {}", removed.join(", "));
+            }
+        }
+        Err((code, message)) => {
+            if json {
+                let result = BuildErrorResult {
+                    schema_version: BUILD_RUN_SCHEMA_VERSION,
+                    ok: false,
+                    error: BuildErrorJson {
+                        code,
+                        message,
+                        detail: None,
+                    },
+                    notes: Vec::new(),
+                };
+                println!("{}", serde_json::to_string(&result).unwrap());
+            } else {
+                eprintln!("error: {message}");
+            }
+            std::process::exit(1);
+        }
+    }
+}
+
 fn handle_response(response: Response, json: bool, show_why: bool) -> Result<()> {
     match response {
         Response::BuildResult { result } => {
@@ -210,7 +298,7 @@ fn print_human_output(result: &BuildRunResult, show_why: bool) {
     // v3.1.2: Include file count for batch transpile nodes
 
     // Collect nodes that need --why explanation (rebuilt or failed)
-    let mut why_nodes: Vec<(&str, &str, bool)> = Vec::new(); // (id, reason, auto_discovered)
+    let mut why_nodes: Vec<(&str, &str, bool, &[String])> = Vec::new(); // (id, reason, auto_discovered, notes)
 
     for node_result in &result.results {
         let (symbol, base_status) = if node_result.ok {
@@ -226,7 +314,12 @@ fn print_human_output(result: &BuildRunResult, show_why: bool) {
 
         // v3.1.2: Include file count for transpile nodes
         let status_text = if let Some(count) = node_result.files_count {
-            format!("({}, {} files)", base_status, count)
+            match node_result.changed_count {
+                Some(changed) if changed != count => {
+                    format!("({}, {} files, {} changed)", base_status, count, changed)
+                }
+                _ => format!("({}, {} files)", base_status, count),
+            }
         } else {
             format!("({})", base_status)
         };
@@ -237,12 +330,18 @@ fn print_human_output(result: &BuildRunResult, show_why: bool) {
         if !node_result.ok {
             if let Some(error) = &node_result.error {
                 eprintln!("  error: {}: {}", error.code, error.message);
-                if let Some(detail) = &error.detail {
-                    for line in detail.lines().take(10) {
-                        eprintln!("  | {line}");
+                if node_result.diagnostics.is_empty() {
+                    if let Some(detail) = &error.detail {
+                        for line in detail.lines().take(10) {
+                            eprintln!("  | {line}");
+                        }
                     }
                 }
             }
+            print_diagnostics(Path::new(&result.cwd), &node_result.diagnostics);
+            if node_result.log_path.is_some() {
+                eprintln!("  full output: howth build logs {}", node_result.id);
+            }
         }
 
         // Collect --why info for non-cached nodes
@@ -252,6 +351,7 @@ fn print_human_output(result: &BuildRunResult, show_why: bool) {
                     &node_result.id,
                     reason.to_human_string(),
                     node_result.auto_discovered,
+                    &node_result.notes,
                 ));
             }
         }
@@ -280,9 +380,13 @@ fn print_human_output(result: &BuildRunResult, show_why: bool) {
     // v3.1.2: Include auto-discovered note
     if show_why && !why_nodes.is_empty() {
         println!();
-        for (node_id, reason, auto_discovered) in &why_nodes {
+        for (node_id, reason, auto_discovered, notes) in &why_nodes {
             println!("{node_id} rebuilt because:");
             println!("  - {reason}");
+            // Notes such as "tsc version changed: ..." name the changed input
+            for note in *notes {
+                println!("  - note: {note}");
+            }
             if *auto_discovered {
                 println!("  - note: auto-discovered from src/");
             }
@@ -290,6 +394,78 @@ fn print_human_output(result: &BuildRunResult, show_why: bool) {
     }
 }
 
+/// Maximum number of diagnostics rendered per node in human output.
+const MAX_DIAGNOSTICS_SHOWN: usize = 20;
+
+/// Print diagnostics with a code frame for each located one.
+fn print_diagnostics(cwd: &Path, diagnostics: &[BuildDiagnostic]) {
+    for diagnostic in diagnostics.iter().take(MAX_DIAGNOSTICS_SHOWN) {
+        eprintln!();
+        let severity = match diagnostic.severity {
+            BuildDiagnosticSeverity::Error => "error",
+            BuildDiagnosticSeverity::Warning => "warning",
+            BuildDiagnosticSeverity::Info => "info",
+        };
+        match &diagnostic.file {
+            Some(file) => eprintln!(
+                "  {file}:{}:{} - {severity} {}: {}",
+                diagnostic.line,
+                diagnostic.column,
+                diagnostic.code,
+                first_line(&diagnostic.message)
+            ),
+            None => eprintln!(
+                "  {severity} {}: {}",
+                diagnostic.code,
+                first_line(&diagnostic.message)
+            ),
+        }
+        for line in diagnostic.message.lines().skip(1) {
+            eprintln!("  {line}");
+        }
+
+        if let Some(file) = &diagnostic.file {
+            if let Ok(source) = std::fs::read_to_string(cwd.join(file)) {
+                if let Some(frame) = code_frame(&source, diagnostic.line, diagnostic.column) {
+                    eprintln!();
+                    eprint!("{frame}");
+                }
+            }
+        }
+    }
+
+    if diagnostics.len() > MAX_DIAGNOSTICS_SHOWN {
+        eprintln!();
+        eprintln!(
+            "  ... and {} more",
+            diagnostics.len() - MAX_DIAGNOSTICS_SHOWN
+        );
+    }
+}
+
+fn first_line(s: &str) -> &str {
+    s.lines().next().unwrap_or("")
+}
+
+/// Render the source line at `line` (1-based) with a caret under `column`.
+fn code_frame(source: &str, line: u32, column: u32) -> Option<String> {
+    if line == 0 {
+        return None;
+    }
+    let text = source.lines().nth(line as usize - 1)?;
+    let gutter = line.to_string();
+    // Keep tabs so the caret lines up with the source line.
+    let pad: String = text
+        .chars()
+        .take(column.saturating_sub(1) as usize)
+        .map(|c| if c == '\t' { '\t' } else { ' ' })
+        .collect();
+    Some(format!(
+        "  {gutter} | {text}\n  {} | {pad}^\n",
+        " ".repeat(gutter.len())
+    ))
+}
+
 fn convert_to_json(result: BuildRunResult) -> BuildResultJson {
     BuildResultJson {
         schema_version: result.schema_version,
@@ -331,7 +507,9 @@ fn convert_to_json(result: BuildRunResult) -> BuildResultJson {
                 stderr_truncated: r.stderr_truncated,
                 notes: r.notes,
                 files_count: r.files_count,
+                changed_count: r.changed_count,
                 auto_discovered: r.auto_discovered,
+                diagnostics: r.diagnostics,
             })
             .collect(),
         notes: result.notes,
@@ -341,13 +519,14 @@ fn convert_to_json(result: BuildRunResult) -> BuildResultJson {
 async fn send_build_request(
     endpoint: &str,
     action: &BuildAction,
+    json: bool,
 ) -> io::Result<(Response, String)> {
-    use tokio::io::{AsyncReadExt, AsyncWriteExt};
+    use tokio::io::AsyncWriteExt;
 
     // Connect using cross-platform IpcStream
     let mut stream = IpcStream::connect(endpoint).await?;
 
-    // Create build request
+    // Create build request; human output renders streamed progress
     let request = Request::Build {
         cwd: action.cwd.to_string_lossy().into_owned(),
         force: action.force,
@@ -355,6 +534,9 @@ async fn send_build_request(
         max_parallel: action.max_parallel.unwrap_or_else(default_max_parallel),
         profile: action.profile,
         targets: action.targets.clone(),
+        progress: !json,
+        remote_exec: action.remote_exec.clone(),
+        source_date_epoch: fastnode_core::deterministic::source_date_epoch(),
     };
 
     // Create and send request frame
@@ -364,7 +546,35 @@ async fn send_build_request(
     stream.write_all(&encoded).await?;
     stream.flush().await?;
 
-    // Read response
+    let mut progress = Progress::new("building", json);
+    loop {
+        let response = progress.run(read_response_frame(&mut stream)).await?;
+        match response.response {
+            Response::BuildProgress {
+                node_id,
+                status,
+                completed,
+                total,
+            } => {
+                if status == "started" {
+                    progress.start(&node_id);
+                } else {
+                    progress.finish_item(&node_id, completed, total);
+                }
+            }
+            other => {
+                progress.finish();
+                return Ok((other, response.hello.server_version));
+            }
+        }
+    }
+}
+
+/// Read one length-prefixed response frame.
+async fn read_response_frame(stream: &mut IpcStream) -> io::Result<FrameResponse> {
+    use tokio::io::AsyncReadExt;
+
+    // Read length prefix
     let mut len_buf = [0u8; 4];
     stream.read_exact(&mut len_buf).await?;
     let len = u32::from_le_bytes(len_buf) as usize;
@@ -379,10 +589,7 @@ async fn send_build_request(
     let mut buf = vec![0u8; len];
     stream.read_exact(&mut buf).await?;
 
-    let response: FrameResponse =
-        serde_json::from_slice(&buf).map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e))?;
-
-    Ok((response.response, response.hello.server_version))
+    serde_json::from_slice(&buf).map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e))
 }
 
 fn default_max_parallel() -> u32 {
@@ -416,6 +623,13 @@ async fn run_watch_build(endpoint: &str, action: &BuildAction) -> io::Result<()>
     stream.write_all(&encoded).await?;
     stream.flush().await?;
 
+    if action.ui {
+        if std::io::stdout().is_terminal() {
+            return super::build_ui::run(&mut stream, &action.targets).await;
+        }
+        eprintln!("note: --ui needs a terminal; using plain output");
+    }
+
     // Show which targets are active (v3.4: watch mode defaults to transpile-only)
     let targets_display = if action.targets.is_empty() {
         "all".to_string()
@@ -451,7 +665,10 @@ async fn run_watch_build(endpoint: &str, action: &BuildAction) -> io::Result<()>
                                 eprintln!("watching: {cwd} (targets: {targets_str}, debounce: {debounce_ms}ms)");
                             }
                             Response::BuildResult { result } => {
-                                // Print build result
+                                // Show what triggered the rebuild, then the result
+                                for note in &result.notes {
+                                    println!("{note}");
+                                }
                                 print_human_output(&result, action.why);
                                 println!();
                             }
@@ -482,27 +699,142 @@ async fn run_watch_build(endpoint: &str, action: &BuildAction) -> io::Result<()>
 }
 
 /// Read a single response frame from the stream.
-async fn read_watch_response(stream: &mut IpcStream) -> io::Result<Response> {
-    use tokio::io::AsyncReadExt;
+pub(super) async fn read_watch_response(stream: &mut IpcStream) -> io::Result<Response> {
+    Ok(read_response_frame(stream).await?.response)
+}
 
-    // Read length prefix
-    let mut len_buf = [0u8; 4];
-    stream.read_exact(&mut len_buf).await?;
-    let len = u32::from_le_bytes(len_buf) as usize;
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_code_frame() {
+        let source = "const a = 1;\n\tlet b: number = 'x';\n";
+        let frame = code_frame(source, 2, 6).unwrap();
+        assert_eq!(frame, "  2 | \tlet b: number = 'x';\n    | \t    ^\n");
+        assert!(code_frame(source, 0, 0).is_none());
+        assert!(code_frame(source, 9, 1).is_none());
+    }
+}
 
-    if len > MAX_FRAME_SIZE {
-        return Err(io::Error::new(
-            io::ErrorKind::InvalidData,
-            format!("response frame too large: {len} bytes"),
-        ));
+/// Result of `howth build logs --json`.
+#[derive(Serialize)]
+struct BuildLogsJson {
+    ok: bool,
+    node: String,
+    /// Logs of the node, oldest first.
+    logs: Vec<String>,
+    /// Contents of the latest log (omitted with `--list`).
+    #[serde(skip_serializing_if = "Option::is_none")]
+    content: Option<String>,
+}
+
+/// Show the logs of the last runs of a build node (`howth build logs`).
+///
+/// `node` is a node ID or a script name (`build` for `script:build`).
+/// Prints the latest log, its last `tail` lines, or with `list` the paths of
+/// all kept logs. `follow` keeps printing lines as they are written,
+/// switching to the next run's log when one starts.
+pub fn logs(
+    cwd: &Path,
+    node: &str,
+    tail: Option<usize>,
+    follow: bool,
+    list: bool,
+    json: bool,
+) -> Result<()> {
+    let node_id = if build::list_logs(cwd, node).is_empty() && !node.contains(':') {
+        format!("script:{node}")
+    } else {
+        node.to_string()
+    };
+    let logs = build::list_logs(cwd, &node_id);
+
+    if json {
+        let content = match logs.last() {
+            Some(path) if !list => Some(read_tail(path, tail)?),
+            _ => None,
+        };
+        let result = BuildLogsJson {
+            ok: !logs.is_empty(),
+            node: node_id,
+            logs: logs.iter().map(|p| p.display().to_string()).collect(),
+            content,
+        };
+        println!("{}", serde_json::to_string_pretty(&result).unwrap());
+        return Ok(());
     }
 
-    // Read JSON payload
-    let mut buf = vec![0u8; len];
-    stream.read_exact(&mut buf).await?;
+    if list {
+        for path in &logs {
+            println!("{}", path.display());
+        }
+        return Ok(());
+    }
 
-    let response: FrameResponse =
-        serde_json::from_slice(&buf).map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e))?;
+    let Some(latest) = logs.last() else {
+        if !follow {
+            eprintln!("error: no logs for {node_id}");
+            eprintln!("hint: logs are written when a node runs; try `howth build --force`");
+            std::process::exit(1);
+        }
+        return follow_logs(cwd, &node_id, None);
+    };
+
+    print!("{}", read_tail(latest, tail)?);
+    if follow {
+        return follow_logs(cwd, &node_id, Some(latest.clone()));
+    }
+    Ok(())
+}
 
-    Ok(response.response)
+/// Contents of a log, or its last `tail` lines.
+fn read_tail(path: &Path, tail: Option<usize>) -> Result<String> {
+    let content = String::from_utf8_lossy(&std::fs::read(path).into_diagnostic()?).into_owned();
+    let Some(n) = tail else {
+        return Ok(content);
+    };
+    let lines: Vec<&str> = content.lines().collect();
+    let mut out = lines[lines.len().saturating_sub(n)..].join("\n");
+    if !out.is_empty() {
+        out.push('\n');
+    }
+    Ok(out)
+}
+
+/// Print lines appended to the node's latest log until interrupted.
+fn follow_logs(cwd: &Path, node_id: &str, mut current: Option<PathBuf>) -> Result<()> {
+    use std::io::{Read, Seek, SeekFrom, Write};
+
+    let mut offset = current
+        .as_ref()
+        .and_then(|path| std::fs::metadata(path).ok())
+        .map_or(0, |meta| meta.len());
+    loop {
+        // A new run writes a new log
+        if let Some(latest) = build::latest_log(cwd, node_id) {
+            if current.as_ref() != Some(&latest) {
+                if current.is_some() {
+                    println!("==> new run: {}", latest.display());
+                }
+                current = Some(latest);
+                offset = 0;
+            }
+        }
+
+        if let Some(path) = &current {
+            if let Ok(mut file) = std::fs::File::open(path) {
+                let len = file.metadata().map_or(0, |meta| meta.len());
+                if len > offset && file.seek(SeekFrom::Start(offset)).is_ok() {
+                    let mut appended = Vec::new();
+                    file.read_to_end(&mut appended).into_diagnostic()?;
+                    offset += appended.len() as u64;
+                    let mut stdout = io::stdout();
+                    stdout.write_all(&appended).into_diagnostic()?;
+                    stdout.flush().into_diagnostic()?;
+                }
+            }
+        }
+        std::thread::sleep(std::time::Duration::from_millis(200));
+    }
 }
@@ -0,0 +1,488 @@
+//! Interactive dashboard for `howth build --watch --ui`.
+//!
+//! Shows the build nodes with their cache status and last duration, the type
+//! errors from the latest results and a scrolling log, updated after every
+//! rebuild wave. Keys: `q`/`Esc`/`Ctrl+C` quit, `↑`/`↓`/`PgUp`/`PgDn` scroll
+//! the log, `End` follows it again.
+
+use super::build::read_watch_response;
+use fastnode_daemon::ipc::IpcStream;
+use fastnode_proto::{
+    BuildCacheStatus, BuildDiagnostic, BuildDiagnosticSeverity, BuildRunResult, Response,
+};
+use ratatui::crossterm::event::{self, Event, KeyCode, KeyEventKind, KeyModifiers};
+use ratatui::layout::{Constraint, Layout, Rect};
+use ratatui::style::{Color, Modifier, Style};
+use ratatui::text::{Line, Span};
+use ratatui::widgets::{Block, Borders, Cell, List, ListItem, Paragraph, Row, Table};
+use ratatui::Frame;
+use std::collections::{BTreeMap, VecDeque};
+use std::io;
+use std::time::Duration;
+
+/// Log lines kept for scrolling.
+const MAX_LOG_LINES: usize = 1000;
+
+/// How often keyboard input is polled.
+const INPUT_POLL: Duration = Duration::from_millis(50);
+
+/// Latest state of one build node.
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct NodeRow {
+    ok: bool,
+    cache: BuildCacheStatus,
+    duration_ms: u64,
+    diagnostics: Vec<BuildDiagnostic>,
+}
+
+/// Dashboard state, updated from daemon responses.
+#[derive(Debug, Default)]
+struct Dashboard {
+    status: String,
+    builds: u32,
+    last_ok: Option<bool>,
+    last_duration_ms: u64,
+    nodes: BTreeMap<String, NodeRow>,
+    log: VecDeque<String>,
+    /// Lines scrolled up from the end of the log (0 follows new output).
+    scroll: usize,
+}
+
+impl Dashboard {
+    fn new(targets: &[String]) -> Self {
+        let targets = if targets.is_empty() {
+            "all".to_string()
+        } else {
+            targets.join(", ")
+        };
+        Self {
+            status: format!("watching [{targets}]"),
+            ..Self::default()
+        }
+    }
+
+    /// Apply a daemon response. Returns `false` once watching has stopped.
+    fn apply(&mut self, response: Response) -> bool {
+        match response {
+            Response::WatchBuildStarted {
+                cwd, debounce_ms, ..
+            } => {
+                self.push_log(format!("watching {cwd} (debounce {debounce_ms}ms)"));
+            }
+            Response::BuildResult { result } => self.apply_result(&result),
+            Response::WatchBuildStopped { reason } => {
+                self.push_log(format!("watch stopped: {reason}"));
+                self.status = "stopped".to_string();
+                return false;
+            }
+            Response::Error { code, message } => {
+                self.push_log(format!("error: {code}: {message}"));
+                self.status = "error".to_string();
+                return false;
+            }
+            _ => {}
+        }
+        true
+    }
+
+    fn apply_result(&mut self, result: &BuildRunResult) {
+        self.builds += 1;
+        self.last_ok = Some(result.ok);
+        self.last_duration_ms = result.summary.total_duration_ms;
+
+        let build = self.builds;
+        for note in &result.notes {
+            self.push_log(format!("#{build} {note}"));
+        }
+        for node in &result.results {
+            let symbol = if node.ok { "\u{2713}" } else { "\u{2717}" };
+            self.push_log(format!(
+                "#{build} {symbol} {} ({}) {}ms",
+                node.id,
+                cache_label(node.ok, &node.cache),
+                node.duration_ms
+            ));
+            if let Some(error) = &node.error {
+                self.push_log(format!("  error: {}: {}", error.code, error.message));
+                if node.diagnostics.is_empty() {
+                    for line in error.detail.iter().flat_map(|detail| detail.lines()) {
+                        self.push_log(format!("    {line}"));
+                    }
+                }
+            }
+            self.nodes.insert(
+                node.id.clone(),
+                NodeRow {
+                    ok: node.ok,
+                    cache: node.cache.clone(),
+                    duration_ms: node.duration_ms,
+                    diagnostics: node.diagnostics.clone(),
+                },
+            );
+        }
+    }
+
+    fn push_log(&mut self, line: String) {
+        if self.log.len() == MAX_LOG_LINES {
+            self.log.pop_front();
+        }
+        self.log.push_back(line);
+        if self.scroll > 0 {
+            // Keep the view on the same lines while scrolled up
+            self.scroll = (self.scroll + 1).min(self.log.len());
+        }
+    }
+
+    fn scroll_up(&mut self, lines: usize) {
+        self.scroll = (self.scroll + lines).min(self.log.len().saturating_sub(1));
+    }
+
+    fn scroll_down(&mut self, lines: usize) {
+        self.scroll = self.scroll.saturating_sub(lines);
+    }
+
+    fn draw(&self, frame: &mut Frame) {
+        let [header, middle, log] = Layout::vertical([
+            Constraint::Length(1),
+            Constraint::Percentage(50),
+            Constraint::Fill(1),
+        ])
+        .areas(frame.area());
+        let [nodes, errors] =
+            Layout::horizontal([Constraint::Percentage(50), Constraint::Percentage(50)])
+                .areas(middle);
+
+        frame.render_widget(Paragraph::new(self.header_line()), header);
+        frame.render_widget(self.nodes_table(), nodes);
+        frame.render_widget(self.errors_list(), errors);
+        self.draw_log(frame, log);
+    }
+
+    fn header_line(&self) -> Line<'_> {
+        let last = match self.last_ok {
+            None => Span::raw("waiting for first build"),
+            Some(true) => Span::styled(
+                format!("build #{} ok in {}ms", self.builds, self.last_duration_ms),
+                Style::new().fg(Color::Green),
+            ),
+            Some(false) => Span::styled(
+                format!(
+                    "build #{} failed in {}ms",
+                    self.builds, self.last_duration_ms
+                ),
+                Style::new().fg(Color::Red),
+            ),
+        };
+        Line::from(vec![
+            Span::styled("howth build ", Style::new().add_modifier(Modifier::BOLD)),
+            Span::raw(format!("{} \u{2502} ", self.status)),
+            last,
+            Span::styled(
+                " \u{2502} q quit, \u{2191}\u{2193} scroll",
+                Style::new().fg(Color::DarkGray),
+            ),
+        ])
+    }
+
+    fn nodes_table(&self) -> Table<'_> {
+        let rows = self.nodes.iter().map(|(id, node)| {
+            let color = if !node.ok {
+                Color::Red
+            } else if node.cache == BuildCacheStatus::Hit {
+                Color::DarkGray
+            } else {
+                Color::Green
+            };
+            Row::new(vec![
+                Cell::from(id.as_str()),
+                Cell::from(cache_label(node.ok, &node.cache)).style(Style::new().fg(color)),
+                Cell::from(format!("{}ms", node.duration_ms)),
+            ])
+        });
+        Table::new(
+            rows,
+            [
+                Constraint::Fill(1),
+                Constraint::Length(8),
+                Constraint::Length(9),
+            ],
+        )
+        .header(
+            Row::new(vec!["Node", "Status", "Duration"])
+                .style(Style::new().add_modifier(Modifier::BOLD)),
+        )
+        .block(Block::new().borders(Borders::ALL).title(" Nodes "))
+    }
+
+    fn errors_list(&self) -> List<'_> {
+        let items: Vec<ListItem> = self
+            .nodes
+            .values()
+            .flat_map(|node| &node.diagnostics)
+            .map(|diagnostic| {
+                let color = match diagnostic.severity {
+                    BuildDiagnosticSeverity::Error => Color::Red,
+                    BuildDiagnosticSeverity::Warning => Color::Yellow,
+                    BuildDiagnosticSeverity::Info => Color::Blue,
+                };
+                let location = match &diagnostic.file {
+                    Some(file) => format!("{file}:{}:{} ", diagnostic.line, diagnostic.column),
+                    None => String::new(),
+                };
+                ListItem::new(Line::from(vec![
+                    Span::raw(location),
+                    Span::styled(format!("{} ", diagnostic.code), Style::new().fg(color)),
+                    Span::raw(diagnostic.message.as_str()),
+                ]))
+            })
+            .collect();
+        let title = format!(" Type errors ({}) ", items.len());
+        List::new(items).block(Block::new().borders(Borders::ALL).title(title))
+    }
+
+    fn draw_log(&self, frame: &mut Frame, area: Rect) {
+        let height = usize::from(area.height.saturating_sub(2));
+        let end = self.log.len() - self.scroll.min(self.log.len());
+        let start = end.saturating_sub(height);
+        let lines: Vec<Line> = self
+            .log
+            .range(start..end)
+            .map(|line| Line::raw(line.as_str()))
+            .collect();
+        let title = if self.scroll > 0 {
+            format!(" Log (\u{2191}{}, End to follow) ", self.scroll)
+        } else {
+            " Log ".to_string()
+        };
+        frame.render_widget(
+            Paragraph::new(lines).block(Block::new().borders(Borders::ALL).title(title)),
+            area,
+        );
+    }
+}
+
+fn cache_label(ok: bool, cache: &BuildCacheStatus) -> &'static str {
+    if !ok {
+        return "failed";
+    }
+    match cache {
+        BuildCacheStatus::Hit => "cached",
+        BuildCacheStatus::Miss | BuildCacheStatus::Bypass => "rebuilt",
+        BuildCacheStatus::Skipped => "skipped",
+    }
+}
+
+/// What a key press asks for.
+enum KeyAction {
+    Quit,
+    Redraw,
+    Ignore,
+}
+
+fn handle_key(dashboard: &mut Dashboard, code: KeyCode, modifiers: KeyModifiers) -> KeyAction {
+    match code {
+        KeyCode::Char('q') | KeyCode::Esc => return KeyAction::Quit,
+        KeyCode::Char('c') if modifiers.contains(KeyModifiers::CONTROL) => return KeyAction::Quit,
+        KeyCode::Up | KeyCode::Char('k') => dashboard.scroll_up(1),
+        KeyCode::Down | KeyCode::Char('j') => dashboard.scroll_down(1),
+        KeyCode::PageUp => dashboard.scroll_up(10),
+        KeyCode::PageDown => dashboard.scroll_down(10),
+        KeyCode::End => dashboard.scroll = 0,
+        _ => return KeyAction::Ignore,
+    }
+    KeyAction::Redraw
+}
+
+/// Run the dashboard on an established watch-build stream until the user
+/// quits or the daemon stops watching.
+pub async fn run(stream: &mut IpcStream, targets: &[String]) -> io::Result<()> {
+    let mut terminal = ratatui::try_init()?;
+    let result = event_loop(&mut terminal, stream, targets).await;
+    ratatui::try_restore()?;
+    result
+}
+
+async fn event_loop(
+    terminal: &mut ratatui::DefaultTerminal,
+    stream: &mut IpcStream,
+    targets: &[String],
+) -> io::Result<()> {
+    let mut dashboard = Dashboard::new(targets);
+    let mut input = tokio::time::interval(INPUT_POLL);
+    terminal.draw(|frame| dashboard.draw(frame))?;
+
+    loop {
+        tokio::select! {
+            response = read_watch_response(stream) => {
+                let response = match response {
+                    Ok(response) => response,
+                    Err(e) if e.kind() == io::ErrorKind::UnexpectedEof => {
+                        Response::WatchBuildStopped { reason: "connection closed".to_string() }
+                    }
+                    Err(e) => return Err(e),
+                };
+                let watching = dashboard.apply(response);
+                terminal.draw(|frame| dashboard.draw(frame))?;
+                if !watching {
+                    // Leave the final state up until the user quits
+                    return wait_for_quit(terminal, &mut dashboard);
+                }
+            }
+            _ = input.tick() => {
+                while event::poll(Duration::ZERO)? {
+                    match event::read()? {
+                        Event::Key(key) if key.kind == KeyEventKind::Press => {
+                            match handle_key(&mut dashboard, key.code, key.modifiers) {
+                                KeyAction::Quit => return Ok(()),
+                                KeyAction::Redraw => {
+                                    terminal.draw(|frame| dashboard.draw(frame))?;
+                                }
+                                KeyAction::Ignore => {}
+                            }
+                        }
+                        Event::Resize(..) => {
+                            terminal.draw(|frame| dashboard.draw(frame))?;
+                        }
+                        _ => {}
+                    }
+                }
+            }
+        }
+    }
+}
+
+fn wait_for_quit(
+    terminal: &mut ratatui::DefaultTerminal,
+    dashboard: &mut Dashboard,
+) -> io::Result<()> {
+    loop {
+        if let Event::Key(key) = event::read()? {
+            if key.kind == KeyEventKind::Press {
+                match handle_key(dashboard, key.code, key.modifiers) {
+                    KeyAction::Quit => return Ok(()),
+                    KeyAction::Redraw => {
+                        terminal.draw(|frame| dashboard.draw(frame))?;
+                    }
+                    KeyAction::Ignore => {}
+                }
+            }
+        } else {
+            terminal.draw(|frame| dashboard.draw(frame))?;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use fastnode_proto::{BuildErrorInfo, BuildNodeResult, BuildRunCounts, BuildRunSummary};
+    use ratatui::backend::TestBackend;
+    use ratatui::Terminal;
+
+    fn node(id: &str, ok: bool, cache: BuildCacheStatus) -> BuildNodeResult {
+        BuildNodeResult {
+            id: id.to_string(),
+            ok,
+            cache,
+            hash: String::new(),
+            duration_ms: 42,
+            reason: None,
+            error: None,
+            stdout_truncated: false,
+            stderr_truncated: false,
+            notes: Vec::new(),
+            files_count: None,
+            changed_count: None,
+            auto_discovered: false,
+            diagnostics: Vec::new(),
+            log_path: None,
+        }
+    }
+
+    fn wave(results: Vec<BuildNodeResult>) -> Response {
+        Response::BuildResult {
+            result: BuildRunResult {
+                schema_version: 1,
+                cwd: "/p".to_string(),
+                ok: results.iter().all(|r| r.ok),
+                counts: BuildRunCounts::default(),
+                summary: BuildRunSummary::default(),
+                results,
+                notes: vec!["triggered by: src/a.ts".to_string()],
+            },
+        }
+    }
+
+    fn render(dashboard: &Dashboard) -> String {
+        let mut terminal = Terminal::new(TestBackend::new(100, 24)).unwrap();
+        terminal.draw(|frame| dashboard.draw(frame)).unwrap();
+        let buffer = terminal.backend().buffer();
+        buffer
+            .content()
+            .chunks(usize::from(buffer.area.width))
+            .map(|row| {
+                row.iter()
+                    .map(ratatui::buffer::Cell::symbol)
+                    .collect::<String>()
+            })
+            .collect::<Vec<_>>()
+            .join("\n")
+    }
+
+    #[test]
+    fn test_waves_merge_nodes_and_collect_type_errors() {
+        let mut dashboard = Dashboard::new(&["transpile".to_string()]);
+        assert!(dashboard.apply(wave(vec![
+            node("transpile", true, BuildCacheStatus::Miss),
+            node("typecheck", true, BuildCacheStatus::Hit),
+        ])));
+
+        let mut failed = node("typecheck", false, BuildCacheStatus::Miss);
+        failed.error = Some(BuildErrorInfo {
+            code: "BUILD_TYPECHECK_FAILED".to_string(),
+            message: "1 type error".to_string(),
+            detail: None,
+        });
+        failed.diagnostics.push(BuildDiagnostic {
+            file: Some("src/a.ts".to_string()),
+            line: 3,
+            column: 7,
+            code: "TS2322".to_string(),
+            severity: BuildDiagnosticSeverity::Error,
+            message: "Type 'string' is not assignable to type 'number'.".to_string(),
+        });
+        // Watch rebuilds only report affected nodes
+        assert!(dashboard.apply(wave(vec![failed])));
+
+        assert_eq!(dashboard.nodes.len(), 2);
+        assert!(dashboard.nodes["transpile"].ok);
+        assert!(!dashboard.nodes["typecheck"].ok);
+
+        let screen = render(&dashboard);
+        assert!(screen.contains("build #2 failed"), "{screen}");
+        assert!(screen.contains("Type errors (1)"), "{screen}");
+        assert!(screen.contains("src/a.ts:3:7 TS2322"), "{screen}");
+        assert!(
+            screen.contains("#2 \u{2717} typecheck (failed) 42ms"),
+            "{screen}"
+        );
+    }
+
+    #[test]
+    fn test_stop_and_scroll() {
+        let mut dashboard = Dashboard::new(&[]);
+        for i in 0..30 {
+            dashboard.push_log(format!("line {i}"));
+        }
+        dashboard.scroll_up(5);
+        dashboard.push_log("line 30".to_string());
+        assert_eq!(dashboard.scroll, 6, "view stays put while scrolled up");
+        dashboard.scroll_down(100);
+        assert_eq!(dashboard.scroll, 0);
+
+        assert!(!dashboard.apply(Response::WatchBuildStopped {
+            reason: "cwd removed".to_string()
+        }));
+        assert_eq!(dashboard.status, "stopped");
+    }
+}
@@ -1,25 +1,42 @@
 //! `howth bundle` command implementation.
 //!
-//! Bundles JavaScript/TypeScript modules into a single output file.
+//! Bundles JavaScript/TypeScript modules into a single output file, or, with
+//! `--outdir`, one or more entry points into per-entry chunks sharing common
+//! chunks and a runtime.
 
+use fastnode_core::build::{
+    execute_dts, hash_node, BuildErrorInfo, BuildInput, BuildNode, CacheSigning, CacheStatus,
+    DtsSpec, ExecOptions, PersistentBuildCache,
+};
 use fastnode_core::bundler::{
-    AliasPlugin, BannerPlugin, BundleFormat, BundleOptions, Bundler, JsonPlugin, Plugin,
-    ReplacePlugin,
+    AliasPlugin, BannerPlugin, BuiltinStrategy, BundleAnalysis, BundleError, BundleFormat,
+    BundleOptions, Bundler, JsonPlugin, PackagesMode, Platform, Plugin, ReplacePlugin,
 };
+use fastnode_core::dev::{client_env_replacements, load_client_env};
+use fastnode_util::fs::atomic_write;
 use miette::{IntoDiagnostic, Result};
 use serde::Serialize;
-use std::path::PathBuf;
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
 use std::time::Instant;
 
+/// File name of the `--analyze` treemap, written next to the output.
+const ANALYSIS_REPORT: &str = "bundle-analysis.html";
+
+/// Modules listed in the `--analyze` console table.
+const ANALYSIS_TOP_MODULES: usize = 10;
+
 /// Bundle command action.
 #[derive(Debug, Clone)]
 pub struct BundleAction {
-    /// Entry point file.
-    pub entry: PathBuf,
+    /// Entry point files (more than one requires `outdir`).
+    pub entries: Vec<PathBuf>,
     /// Working directory.
     pub cwd: PathBuf,
     /// Output file (if None, prints to stdout).
     pub outfile: Option<PathBuf>,
+    /// Output directory for multi-entry builds.
+    pub outdir: Option<PathBuf>,
     /// Output format.
     pub format: BundleFormat,
     /// Minify output.
@@ -36,10 +53,28 @@ pub struct BundleAction {
     pub splitting: bool,
     /// Define replacements (e.g., __DEV__=false).
     pub define: Vec<String>,
+    /// Mode for `import.meta.env` and `.env` file loading.
+    pub mode: String,
     /// Import aliases (e.g., @=./src).
     pub alias: Vec<String>,
     /// Banner text to prepend.
     pub banner: Option<String>,
+    /// Platform the bundle runs on.
+    pub platform: Platform,
+    /// Per-module handling of Node.js built-ins in browser builds.
+    pub builtins: HashMap<String, BuiltinStrategy>,
+    /// Download and bundle URL imports.
+    pub vendor_urls: bool,
+    /// How bare package imports are handled.
+    pub packages: PackagesMode,
+    /// Write both ESM (`.mjs`) and CJS (`.cjs`) outputs next to `outfile`.
+    pub dual: bool,
+    /// Generate TypeScript declarations next to the output.
+    pub dts: bool,
+    /// Report per-module output size and write an HTML treemap.
+    pub analyze: bool,
+    /// Reproducible output (`--deterministic` or `SOURCE_DATE_EPOCH`).
+    pub deterministic: bool,
 }
 
 /// JSON output for bundle command.
@@ -53,10 +88,85 @@ struct BundleResultJson {
     modules: Vec<String>,
     size_bytes: usize,
     duration_ms: u64,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    warnings: Vec<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    types: Option<BundleTypesJson>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    analysis: Option<BundleAnalysisJson>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    error: Option<BundleErrorJson>,
+}
+
+/// A written chunk in multi-entry JSON output.
+#[derive(Serialize)]
+struct BundleChunkJson {
+    name: String,
+    file: String,
+    size_bytes: usize,
+}
+
+/// JSON output for multi-entry bundles (`--outdir`).
+#[derive(Serialize)]
+struct BundleEntriesResultJson {
+    ok: bool,
+    entries: Vec<String>,
+    outdir: String,
+    format: String,
+    chunks: Vec<BundleChunkJson>,
+    modules: Vec<String>,
+    duration_ms: u64,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    warnings: Vec<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    types: Option<BundleTypesJson>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    analysis: Option<BundleAnalysisJson>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    error: Option<BundleErrorJson>,
+}
+
+/// One output of a dual ESM + CJS build.
+#[derive(Serialize)]
+struct BundleDualOutputJson {
+    format: String,
+    file: String,
+    size_bytes: usize,
+}
+
+/// JSON output for dual ESM + CJS builds (`--dual`).
+#[derive(Serialize)]
+struct BundleDualResultJson {
+    ok: bool,
+    entry: String,
+    outputs: Vec<BundleDualOutputJson>,
+    modules: Vec<String>,
+    duration_ms: u64,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    warnings: Vec<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    package_json: Option<serde_json::Value>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    types: Option<BundleTypesJson>,
     #[serde(skip_serializing_if = "Option::is_none")]
     error: Option<BundleErrorJson>,
 }
 
+/// Size breakdown written by `--analyze`.
+#[derive(Serialize)]
+struct BundleAnalysisJson {
+    report: String,
+    #[serde(flatten)]
+    analysis: BundleAnalysis,
+}
+
+/// Declarations written by `--dts`.
+#[derive(Serialize)]
+struct BundleTypesJson {
+    files: Vec<String>,
+    cached: bool,
+}
+
 #[derive(Serialize)]
 struct BundleErrorJson {
     code: String,
@@ -67,58 +177,22 @@ struct BundleErrorJson {
 
 /// Run the bundle command.
 pub fn run(action: BundleAction, json: bool) -> Result<()> {
-    let start = Instant::now();
-
-    // Build plugins from CLI options
-    let mut plugins: Vec<Box<dyn Plugin>> = Vec::new();
-
-    // Add JSON plugin by default
-    plugins.push(Box::new(JsonPlugin));
-
-    // Add define/replace plugin
-    if !action.define.is_empty() {
-        let mut replace = ReplacePlugin::new();
-        for def in &action.define {
-            if let Some((key, value)) = def.split_once('=') {
-                replace = replace.replace(key.trim(), value.trim());
-            }
-        }
-        plugins.push(Box::new(replace));
+    if let Some(outdir) = &action.outdir {
+        return run_entries(&action, outdir, json);
     }
-
-    // Add alias plugin
-    if !action.alias.is_empty() {
-        let mut alias_plugin = AliasPlugin::new();
-        for a in &action.alias {
-            if let Some((from, to)) = a.split_once('=') {
-                alias_plugin = alias_plugin.alias(from.trim(), to.trim());
-            }
+    if action.dual {
+        if let Some(outfile) = &action.outfile {
+            return run_dual(&action, outfile, json);
         }
-        plugins.push(Box::new(alias_plugin));
-    }
-
-    // Add banner plugin
-    if let Some(ref banner) = action.banner {
-        plugins.push(Box::new(BannerPlugin::new().banner(banner)));
     }
 
-    // Create bundler with plugins
-    let bundler = Bundler::with_cwd(&action.cwd).plugins(plugins);
-
-    // Create options
-    let options = BundleOptions {
-        format: action.format,
-        minify: action.minify,
-        mangle: action.mangle,
-        sourcemap: action.sourcemap,
-        external: action.external.clone(),
-        treeshake: action.treeshake,
-        splitting: action.splitting,
-        ..Default::default()
-    };
+    let start = Instant::now();
+    let entry = &action.entries[0];
+    let bundler = create_bundler(&action);
+    let options = bundle_options(&action);
 
     // Run bundler
-    let result = bundler.bundle(&action.entry, &action.cwd, &options);
+    let result = bundler.bundle(entry, &action.cwd, &options);
 
     let duration_ms = start.elapsed().as_millis() as u64;
 
@@ -136,26 +210,21 @@ pub fn run(action: BundleAction, json: bool) -> Result<()> {
                         std::fs::create_dir_all(parent).into_diagnostic()?;
                     }
                 }
-                std::fs::write(outfile, code).into_diagnostic()?;
-
-                // Write sourcemap if generated
-                if let Some(ref map) = bundle_result.map {
-                    let map_path = outfile.with_extension("js.map");
-                    std::fs::write(&map_path, map).into_diagnostic()?;
-                }
+                write_with_map(outfile, code, bundle_result.map.as_deref())?;
 
                 // Write additional chunks if code splitting is enabled
                 if has_chunks {
                     let parent = outfile.parent().unwrap_or(std::path::Path::new("."));
                     for chunk in &bundle_result.chunks {
                         let chunk_path = parent.join(format!("{}.js", chunk.name));
-                        std::fs::write(&chunk_path, &chunk.code).into_diagnostic()?;
+                        atomic_write(&chunk_path, chunk.code.as_bytes()).into_diagnostic()?;
                     }
 
                     // Write manifest
                     if let Some(ref manifest) = bundle_result.manifest {
                         let manifest_path = parent.join("manifest.json");
-                        std::fs::write(&manifest_path, manifest.to_json()).into_diagnostic()?;
+                        atomic_write(&manifest_path, manifest.to_json().as_bytes())
+                            .into_diagnostic()?;
                     }
                 }
 
@@ -163,7 +232,7 @@ pub fn run(action: BundleAction, json: bool) -> Result<()> {
                 let parent = outfile.parent().unwrap_or(std::path::Path::new("."));
                 if let Some(ref css) = bundle_result.css {
                     let css_path = parent.join(&css.name);
-                    std::fs::write(&css_path, &css.code).into_diagnostic()?;
+                    atomic_write(&css_path, css.code.as_bytes()).into_diagnostic()?;
                 }
 
                 // Copy assets
@@ -173,15 +242,50 @@ pub fn run(action: BundleAction, json: bool) -> Result<()> {
                 }
             }
 
+            let types = match (&action.outfile, action.dts) {
+                (Some(outfile), true) => {
+                    let stubs = [(entry.as_path(), vec![outfile.with_extension("d.ts")])];
+                    let parent = outfile.parent().unwrap_or(Path::new(""));
+                    Some(
+                        generate_declarations(&action, parent, &stubs).unwrap_or_else(|e| {
+                            exit_with_dts_error(&e, json, |error| BundleResultJson {
+                                ok: false,
+                                entry: entry.display().to_string(),
+                                outfile: Some(outfile.display().to_string()),
+                                format: format_to_string(action.format),
+                                modules: Vec::new(),
+                                size_bytes: 0,
+                                duration_ms,
+                                warnings: Vec::new(),
+                                types: None,
+                                analysis: None,
+                                error: Some(error),
+                            })
+                        }),
+                    )
+                }
+                _ => None,
+            };
+            let analysis = match (bundle_result.analysis, &action.outfile) {
+                (Some(analysis), Some(outfile)) => Some(write_analysis(
+                    analysis,
+                    outfile.parent().unwrap_or(Path::new("")),
+                )?),
+                _ => None,
+            };
+
             if json {
                 let json_result = BundleResultJson {
                     ok: true,
-                    entry: action.entry.display().to_string(),
+                    entry: entry.display().to_string(),
                     outfile: action.outfile.as_ref().map(|p| p.display().to_string()),
                     format: format_to_string(action.format),
                     modules: bundle_result.modules,
                     size_bytes,
                     duration_ms,
+                    warnings: bundle_result.warnings,
+                    types,
+                    analysis,
                     error: None,
                 };
                 println!("{}", serde_json::to_string(&json_result).unwrap());
@@ -194,7 +298,7 @@ pub fn run(action: BundleAction, json: bool) -> Result<()> {
                     let chunk_count = bundle_result.chunks.len();
                     println!(
                         "  {} -> {} ({} modules, {} chunks, {:.1}KB, {}ms)",
-                        action.entry.display(),
+                        entry.display(),
                         outfile.display(),
                         modules_count,
                         chunk_count + 1, // +1 for main chunk
@@ -208,7 +312,7 @@ pub fn run(action: BundleAction, json: bool) -> Result<()> {
                 } else {
                     println!(
                         "  {} -> {} ({} modules, {:.1}KB, {}ms)",
-                        action.entry.display(),
+                        entry.display(),
                         outfile.display(),
                         modules_count,
                         size_kb,
@@ -227,6 +331,16 @@ pub fn run(action: BundleAction, json: bool) -> Result<()> {
                     println!("    + {}", asset.name);
                 }
 
+                // Show declarations
+                if let Some(ref types) = types {
+                    print_types(types);
+                }
+
+                // Show size breakdown
+                if let Some(ref analysis) = analysis {
+                    print_analysis(analysis);
+                }
+
                 // Show warnings
                 for warning in &bundle_result.warnings {
                     eprintln!("  warning: {warning}");
@@ -234,6 +348,9 @@ pub fn run(action: BundleAction, json: bool) -> Result<()> {
             } else {
                 // No outfile, print code to stdout
                 print!("{code}");
+                for warning in &bundle_result.warnings {
+                    eprintln!("warning: {warning}");
+                }
             }
 
             Ok(())
@@ -242,12 +359,15 @@ pub fn run(action: BundleAction, json: bool) -> Result<()> {
             if json {
                 let json_result = BundleResultJson {
                     ok: false,
-                    entry: action.entry.display().to_string(),
+                    entry: entry.display().to_string(),
                     outfile: action.outfile.as_ref().map(|p| p.display().to_string()),
                     format: format_to_string(action.format),
                     modules: Vec::new(),
                     size_bytes: 0,
                     duration_ms,
+                    warnings: Vec::new(),
+                    types: None,
+                    analysis: None,
                     error: Some(BundleErrorJson {
                         code: e.code.to_string(),
                         message: e.message.clone(),
@@ -256,16 +376,686 @@ pub fn run(action: BundleAction, json: bool) -> Result<()> {
                 };
                 println!("{}", serde_json::to_string(&json_result).unwrap());
             } else {
-                eprintln!("error: {}", e);
-                if let Some(path) = &e.path {
-                    eprintln!("  at {path}");
-                }
+                print_bundle_error(&e);
             }
             std::process::exit(1);
         }
     }
 }
 
+/// Bundle one or more entries into `outdir`.
+///
+/// Writes a chunk per entry, common chunks for shared modules, the shared
+/// `runtime.js` and a combined `manifest.json`.
+fn run_entries(action: &BundleAction, outdir: &Path, json: bool) -> Result<()> {
+    let start = Instant::now();
+    let bundler = create_bundler(action);
+    let options = bundle_options(action);
+    let entries: Vec<&Path> = action.entries.iter().map(PathBuf::as_path).collect();
+
+    let result = bundler.bundle_entries(&entries, &action.cwd, &options);
+    let duration_ms = start.elapsed().as_millis() as u64;
+    let entry_names: Vec<String> = entries.iter().map(|e| e.display().to_string()).collect();
+
+    match result {
+        Ok(bundle_result) => {
+            std::fs::create_dir_all(outdir).into_diagnostic()?;
+
+            let mut chunks = Vec::new();
+            for chunk in &bundle_result.chunks {
+                let file = format!("{}.js", chunk.name);
+                write_with_map(&outdir.join(&file), &chunk.code, chunk.map.as_deref())?;
+                chunks.push(BundleChunkJson {
+                    name: chunk.name.clone(),
+                    file,
+                    size_bytes: chunk.code.len(),
+                });
+            }
+
+            if let Some(ref manifest) = bundle_result.manifest {
+                atomic_write(&outdir.join("manifest.json"), manifest.to_json().as_bytes())
+                    .into_diagnostic()?;
+            }
+            if let Some(ref css) = bundle_result.css {
+                atomic_write(&outdir.join(&css.name), css.code.as_bytes()).into_diagnostic()?;
+            }
+            for asset in &bundle_result.assets {
+                std::fs::copy(&asset.source, outdir.join(&asset.name)).into_diagnostic()?;
+            }
+
+            let types = if action.dts {
+                // Entry chunks follow the runtime chunk, in entry order
+                let stubs: Vec<(&Path, Vec<PathBuf>)> = entries
+                    .iter()
+                    .zip(bundle_result.chunks.iter().skip(1))
+                    .map(|(entry, chunk)| {
+                        (*entry, vec![outdir.join(format!("{}.d.ts", chunk.name))])
+                    })
+                    .collect();
+                Some(
+                    generate_declarations(action, outdir, &stubs).unwrap_or_else(|e| {
+                        exit_with_dts_error(&e, json, |error| BundleEntriesResultJson {
+                            ok: false,
+                            entries: entry_names.clone(),
+                            outdir: outdir.display().to_string(),
+                            format: format_to_string(action.format),
+                            chunks: Vec::new(),
+                            modules: Vec::new(),
+                            duration_ms,
+                            warnings: Vec::new(),
+                            types: None,
+                            analysis: None,
+                            error: Some(error),
+                        })
+                    }),
+                )
+            } else {
+                None
+            };
+            let analysis = match bundle_result.analysis {
+                Some(analysis) => Some(write_analysis(analysis, outdir)?),
+                None => None,
+            };
+
+            if json {
+                let json_result = BundleEntriesResultJson {
+                    ok: true,
+                    entries: entry_names,
+                    outdir: outdir.display().to_string(),
+                    format: format_to_string(action.format),
+                    chunks,
+                    modules: bundle_result.modules,
+                    duration_ms,
+                    warnings: bundle_result.warnings,
+                    types,
+                    analysis,
+                    error: None,
+                };
+                println!("{}", serde_json::to_string(&json_result).unwrap());
+                return Ok(());
+            }
+
+            let total_kb = chunks.iter().map(|c| c.size_bytes).sum::<usize>() as f64 / 1024.0;
+            println!(
+                "  {} -> {} ({} modules, {} chunks, {:.1}KB, {}ms)",
+                entry_names.join(", "),
+                outdir.display(),
+                bundle_result.modules.len(),
+                chunks.len(),
+                total_kb,
+                duration_ms
+            );
+            for chunk in &chunks {
+                println!(
+                    "    + {} ({:.1}KB)",
+                    chunk.file,
+                    chunk.size_bytes as f64 / 1024.0
+                );
+            }
+            println!("    + manifest.json");
+            if let Some(ref css) = bundle_result.css {
+                println!(
+                    "    + {} ({:.1}KB)",
+                    css.name,
+                    css.code.len() as f64 / 1024.0
+                );
+            }
+            for asset in &bundle_result.assets {
+                println!("    + {}", asset.name);
+            }
+            if let Some(ref types) = types {
+                print_types(types);
+            }
+            if let Some(ref analysis) = analysis {
+                print_analysis(analysis);
+            }
+            for warning in &bundle_result.warnings {
+                eprintln!("  warning: {warning}");
+            }
+            Ok(())
+        }
+        Err(e) => {
+            if json {
+                let json_result = BundleEntriesResultJson {
+                    ok: false,
+                    entries: entry_names,
+                    outdir: outdir.display().to_string(),
+                    format: format_to_string(action.format),
+                    chunks: Vec::new(),
+                    modules: Vec::new(),
+                    duration_ms,
+                    warnings: Vec::new(),
+                    types: None,
+                    analysis: None,
+                    error: Some(BundleErrorJson {
+                        code: e.code.to_string(),
+                        message: e.message.clone(),
+                        path: e.path.clone(),
+                    }),
+                };
+                println!("{}", serde_json::to_string(&json_result).unwrap());
+            } else {
+                print_bundle_error(&e);
+            }
+            std::process::exit(1);
+        }
+    }
+}
+
+/// Bundle an entry twice, as ESM (`<outfile>.mjs`) and CJS (`<outfile>.cjs`),
+/// and suggest `package.json` fields pointing at both.
+fn run_dual(action: &BundleAction, outfile: &Path, json: bool) -> Result<()> {
+    let start = Instant::now();
+    let entry = &action.entries[0];
+    let bundler = create_bundler(action);
+
+    let mut outputs = Vec::new();
+    let mut modules = Vec::new();
+    let mut warnings: Vec<String> = Vec::new();
+    for format in [BundleFormat::Esm, BundleFormat::Cjs] {
+        let options = BundleOptions {
+            format,
+            ..bundle_options(action)
+        };
+        let bundle_result = match bundler.bundle(entry, &action.cwd, &options) {
+            Ok(result) => result,
+            Err(e) => {
+                if json {
+                    let json_result = BundleDualResultJson {
+                        ok: false,
+                        entry: entry.display().to_string(),
+                        outputs: Vec::new(),
+                        modules: Vec::new(),
+                        duration_ms: start.elapsed().as_millis() as u64,
+                        warnings: Vec::new(),
+                        package_json: None,
+                        types: None,
+                        error: Some(BundleErrorJson {
+                            code: e.code.to_string(),
+                            message: e.message.clone(),
+                            path: e.path.clone(),
+                        }),
+                    };
+                    println!("{}", serde_json::to_string(&json_result).unwrap());
+                } else {
+                    print_bundle_error(&e);
+                }
+                std::process::exit(1);
+            }
+        };
+
+        let file = outfile.with_extension(match format {
+            BundleFormat::Cjs => "cjs",
+            _ => "mjs",
+        });
+        if let Some(parent) = file.parent() {
+            if !parent.as_os_str().is_empty() {
+                std::fs::create_dir_all(parent).into_diagnostic()?;
+            }
+        }
+        write_with_map(&file, &bundle_result.code, bundle_result.map.as_deref())?;
+
+        for warning in bundle_result.warnings {
+            if !warnings.contains(&warning) {
+                warnings.push(warning);
+            }
+        }
+        modules = bundle_result.modules;
+        outputs.push(BundleDualOutputJson {
+            format: format_to_string(format),
+            file: file.display().to_string(),
+            size_bytes: bundle_result.code.len(),
+        });
+    }
+    let duration_ms = start.elapsed().as_millis() as u64;
+
+    let types = if action.dts {
+        let stubs = [(
+            entry.as_path(),
+            vec![
+                outfile.with_extension("d.mts"),
+                outfile.with_extension("d.cts"),
+            ],
+        )];
+        let parent = outfile.parent().unwrap_or(Path::new(""));
+        Some(
+            generate_declarations(action, parent, &stubs).unwrap_or_else(|e| {
+                exit_with_dts_error(&e, json, |error| BundleDualResultJson {
+                    ok: false,
+                    entry: entry.display().to_string(),
+                    outputs: Vec::new(),
+                    modules: Vec::new(),
+                    duration_ms,
+                    warnings: Vec::new(),
+                    package_json: None,
+                    types: None,
+                    error: Some(error),
+                })
+            }),
+        )
+    } else {
+        None
+    };
+
+    let esm = package_relative(&action.cwd, &outfile.with_extension("mjs"));
+    let cjs = package_relative(&action.cwd, &outfile.with_extension("cjs"));
+    let package_json = if types.is_some() {
+        let esm_types = package_relative(&action.cwd, &outfile.with_extension("d.mts"));
+        let cjs_types = package_relative(&action.cwd, &outfile.with_extension("d.cts"));
+        serde_json::json!({
+            "main": cjs,
+            "module": esm,
+            "types": cjs_types,
+            "exports": {
+                ".": {
+                    "import": { "types": esm_types, "default": esm },
+                    "require": { "types": cjs_types, "default": cjs },
+                }
+            }
+        })
+    } else {
+        serde_json::json!({
+            "main": cjs,
+            "module": esm,
+            "exports": {
+                ".": {
+                    "import": esm,
+                    "require": cjs,
+                }
+            }
+        })
+    };
+
+    if json {
+        let json_result = BundleDualResultJson {
+            ok: true,
+            entry: entry.display().to_string(),
+            outputs,
+            modules,
+            duration_ms,
+            warnings,
+            package_json: Some(package_json),
+            types,
+            error: None,
+        };
+        println!("{}", serde_json::to_string(&json_result).unwrap());
+        return Ok(());
+    }
+
+    println!(
+        "  {} -> {} ({} modules, {}ms)",
+        entry.display(),
+        outputs
+            .iter()
+            .map(|o| o.file.as_str())
+            .collect::<Vec<_>>()
+            .join(", "),
+        modules.len(),
+        duration_ms
+    );
+    if let Some(ref types) = types {
+        print_types(types);
+    }
+    for warning in &warnings {
+        eprintln!("  warning: {warning}");
+    }
+    println!();
+    println!("Suggested package.json fields:");
+    println!("{}", serde_json::to_string_pretty(&package_json).unwrap());
+    Ok(())
+}
+
+/// Source extensions whose changes invalidate generated declarations.
+const DTS_SOURCE_GLOBS: &[&str] = &["**/*.ts", "**/*.tsx", "**/*.mts", "**/*.cts"];
+
+/// Generate TypeScript declarations for `entries`.
+///
+/// Runs a cached `dts` build node that emits declarations into
+/// `<out_dir>/types/`, then writes each entry's declaration files (e.g.
+/// `index.d.ts` next to `index.js`) re-exporting its emitted declarations.
+/// The node is cached under `.howth/cache/`, so unchanged sources skip tsc.
+fn generate_declarations(
+    action: &BundleAction,
+    out_dir: &Path,
+    entries: &[(&Path, Vec<PathBuf>)],
+) -> std::result::Result<BundleTypesJson, BuildErrorInfo> {
+    let cwd = &action.cwd;
+    let outside_project = |path: &Path| {
+        BuildErrorInfo::new(
+            fastnode_core::build::BUILD_DTS_FAILED,
+            format!("{} is outside the project directory", path.display()),
+        )
+    };
+    std::fs::create_dir_all(out_dir.join("types")).map_err(|e| {
+        BuildErrorInfo::new(
+            fastnode_core::build::BUILD_DTS_FAILED,
+            format!("Failed to create {}: {e}", out_dir.join("types").display()),
+        )
+    })?;
+    let types_dir = out_dir.join("types");
+    let types_rel = project_relative(cwd, &types_dir).ok_or_else(|| outside_project(&types_dir))?;
+
+    let mut entry_rels = Vec::new();
+    for (entry, _) in entries {
+        let absolute = cwd.join(entry);
+        let rel = project_relative(cwd, &absolute).ok_or_else(|| outside_project(entry))?;
+        entry_rels.push(rel);
+    }
+
+    let tsconfig = cwd
+        .join("tsconfig.json")
+        .exists()
+        .then(|| "tsconfig.json".to_string());
+    let mut node = BuildNode::dts(DtsSpec {
+        entries: entry_rels.clone(),
+        out_dir: types_rel.clone(),
+        tsconfig: tsconfig.clone(),
+    });
+    node.add_input(BuildInput::file_optional("package.json"));
+    if let Some(tsconfig) = tsconfig {
+        node.add_input(BuildInput::file(tsconfig));
+    }
+    let mut roots: Vec<String> = entry_rels
+        .iter()
+        .map(|rel| rel.rsplit_once('/').map_or(".", |(dir, _)| dir).to_string())
+        .collect();
+    roots.sort();
+    roots.dedup();
+    for root in &roots {
+        for pattern in DTS_SOURCE_GLOBS {
+            node.add_input(BuildInput::glob_optional(*pattern, root.clone()));
+        }
+    }
+    node.normalize();
+
+    let hash = hash_node(&node, cwd)
+        .map_err(|e| BuildErrorInfo::new(e.code.to_string(), e.message.clone()))?;
+    let signing = CacheSigning::load(cwd)?;
+    let mut cache = PersistentBuildCache::open_with_signing(cwd, signing);
+    for rejected in cache.rejected() {
+        eprintln!("warning: {}: {}", rejected.code, rejected.message);
+    }
+    let result = execute_dts(&node, cwd, &hash, Some(&mut cache), &ExecOptions::new());
+    // The cache is an optimization; failing to persist it isn't an error
+    let _ = cache.save();
+    if let Some(error) = result.error {
+        return Err(error);
+    }
+
+    let mut files = Vec::new();
+    for ((_, stubs), rel) in entries.iter().zip(&entry_rels) {
+        let (stem, extension) = declaration_path(rel);
+        let declaration = types_dir.join(format!("{stem}{extension}"));
+        let source = std::fs::read_to_string(&declaration).map_err(|_| {
+            BuildErrorInfo::new(
+                fastnode_core::build::BUILD_DTS_FAILED,
+                format!("tsc emitted no declarations for {rel}"),
+            )
+        })?;
+        let has_default = source.contains("export default") || source.contains(" as default");
+
+        // Import the declarations by their runtime (.js) path, which every
+        // TypeScript module resolution mode maps back to the .d.ts file
+        let runtime_ext = if extension == ".d.mts" { ".mjs" } else { ".js" };
+        let specifier = format!("./types/{stem}{runtime_ext}");
+        let mut stub = format!("export * from \"{specifier}\";\n");
+        if has_default {
+            stub.push_str(&format!("export {{ default }} from \"{specifier}\";\n"));
+        }
+        for file in stubs {
+            std::fs::write(file, &stub).map_err(|e| {
+                BuildErrorInfo::new(
+                    fastnode_core::build::BUILD_DTS_FAILED,
+                    format!("Failed to write {}: {e}", file.display()),
+                )
+            })?;
+            files.push(file.display().to_string());
+        }
+    }
+
+    Ok(BundleTypesJson {
+        files,
+        cached: result.cache == CacheStatus::Hit,
+    })
+}
+
+/// Split a source path into the stem and extension of its declaration file
+/// (`src/index.ts` -> `("src/index", ".d.ts")`).
+fn declaration_path(source: &str) -> (&str, &'static str) {
+    let (stem, ext) = source.rsplit_once('.').unwrap_or((source, ""));
+    match ext {
+        "mts" | "mjs" => (stem, ".d.mts"),
+        "cts" | "cjs" => (stem, ".d.cts"),
+        _ => (stem, ".d.ts"),
+    }
+}
+
+/// Print the declaration files written by `--dts`.
+fn print_types(types: &BundleTypesJson) {
+    let status = if types.cached { "cached" } else { "generated" };
+    for file in &types.files {
+        println!("    + {file} (types, {status})");
+    }
+}
+
+/// Write the `--analyze` HTML treemap into `dir`.
+fn write_analysis(analysis: BundleAnalysis, dir: &Path) -> Result<BundleAnalysisJson> {
+    let report = dir.join(ANALYSIS_REPORT);
+    std::fs::write(&report, analysis.to_html()).into_diagnostic()?;
+    Ok(BundleAnalysisJson {
+        report: report.display().to_string(),
+        analysis,
+    })
+}
+
+/// Print the `--analyze` size breakdown: packages, then the largest modules.
+fn print_analysis(json: &BundleAnalysisJson) {
+    let kb = |bytes: usize| format!("{:.1}KB", bytes as f64 / 1024.0);
+    let analysis = &json.analysis;
+
+    println!();
+    println!(
+        "  Size by package ({} output, ~{} minified)",
+        kb(analysis.output_size),
+        kb(analysis.minified_size)
+    );
+    println!(
+        "    {:<40} {:>7} {:>10} {:>10}",
+        "package", "modules", "output", "minified"
+    );
+    for package in &analysis.packages {
+        println!(
+            "    {:<40} {:>7} {:>10} {:>10}",
+            package.name,
+            package.modules,
+            kb(package.output_size),
+            kb(package.minified_size)
+        );
+    }
+
+    println!();
+    println!("  Largest modules");
+    println!("    {:<48} {:>10} {:>10}", "module", "output", "minified");
+    for module in analysis.modules.iter().take(ANALYSIS_TOP_MODULES) {
+        println!(
+            "    {:<48} {:>10} {:>10}",
+            module.path,
+            kb(module.output_size),
+            kb(module.minified_size)
+        );
+    }
+    if analysis.modules.len() > ANALYSIS_TOP_MODULES {
+        println!(
+            "    ... {} more",
+            analysis.modules.len() - ANALYSIS_TOP_MODULES
+        );
+    }
+    println!("    + {} (treemap)", json.report);
+}
+
+/// Report a declaration generation failure and exit.
+fn exit_with_dts_error<T: Serialize>(
+    error: &BuildErrorInfo,
+    json: bool,
+    json_failure: impl FnOnce(BundleErrorJson) -> T,
+) -> ! {
+    if json {
+        let json_result = json_failure(BundleErrorJson {
+            code: error.code.clone(),
+            message: error.message.clone(),
+            path: None,
+        });
+        println!("{}", serde_json::to_string(&json_result).unwrap());
+    } else {
+        eprintln!("error: {}: {}", error.code, error.message);
+        if let Some(detail) = error.detail.as_deref().filter(|d| !d.is_empty()) {
+            for line in detail.lines() {
+                eprintln!("  {line}");
+            }
+        }
+    }
+    std::process::exit(1);
+}
+
+/// Path of `file` relative to the package root, as used in `package.json`
+/// (`./dist/index.mjs`). Falls back to the path as given.
+fn package_relative(root: &Path, file: &Path) -> String {
+    project_relative(root, file).map_or_else(|| file.display().to_string(), |p| format!("./{p}"))
+}
+
+/// `/`-separated path of `file` (relative to the current directory) within
+/// `root`, or `None` if it lies outside.
+fn project_relative(root: &Path, file: &Path) -> Option<String> {
+    let absolute = if file.is_absolute() {
+        file.to_path_buf()
+    } else {
+        std::env::current_dir()
+            .map(|dir| dir.join(file))
+            .unwrap_or_else(|_| file.to_path_buf())
+    };
+    let root = dunce::canonicalize(root).unwrap_or_else(|_| root.to_path_buf());
+    let absolute = absolute
+        .parent()
+        .and_then(|parent| dunce::canonicalize(parent).ok())
+        .and_then(|parent| absolute.file_name().map(|name| parent.join(name)))
+        .unwrap_or(absolute);
+
+    let relative = absolute.strip_prefix(&root).ok()?;
+    let parts: Vec<String> = relative
+        .components()
+        .map(|c| c.as_os_str().to_string_lossy().into_owned())
+        .collect();
+    Some(parts.join("/"))
+}
+
+/// Print a bundle error for humans.
+/// Write `code` to `file`, and its source map (if any) to `<file>.map`,
+/// linked from the code with a `//# sourceMappingURL=` comment.
+fn write_with_map(file: &Path, code: &str, map: Option<&str>) -> Result<()> {
+    let Some(map) = map else {
+        atomic_write(file, code.as_bytes()).into_diagnostic()?;
+        return mark_executable_if_script(file, code);
+    };
+    let mut map_path = file.as_os_str().to_os_string();
+    map_path.push(".map");
+    let map_path = PathBuf::from(map_path);
+    let map_name = map_path
+        .file_name()
+        .map(|name| name.to_string_lossy().into_owned())
+        .unwrap_or_default();
+    let linked = format!(
+        "{}\n//# sourceMappingURL={map_name}\n",
+        code.trim_end_matches('\n')
+    );
+    atomic_write(file, linked.as_bytes()).into_diagnostic()?;
+    atomic_write(&map_path, map.as_bytes()).into_diagnostic()?;
+    mark_executable_if_script(file, code)
+}
+
+/// Make output starting with a shebang (a CLI entry) executable, so it can
+/// be used as a package `bin` directly.
+fn mark_executable_if_script(file: &Path, code: &str) -> Result<()> {
+    #[cfg(unix)]
+    if code.starts_with("#!") {
+        use std::os::unix::fs::PermissionsExt;
+        let mut perms = std::fs::metadata(file).into_diagnostic()?.permissions();
+        perms.set_mode(perms.mode() | 0o111);
+        std::fs::set_permissions(file, perms).into_diagnostic()?;
+    }
+    #[cfg(not(unix))]
+    let _ = (file, code);
+    Ok(())
+}
+
+fn print_bundle_error(e: &BundleError) {
+    eprintln!("error: {}", e);
+    if let Some(path) = &e.path {
+        eprintln!("  at {path}");
+    }
+}
+
+/// Create the bundler with plugins from CLI options.
+fn create_bundler(action: &BundleAction) -> Bundler {
+    // Build plugins from CLI options
+    let mut plugins: Vec<Box<dyn Plugin>> = Vec::new();
+
+    // Add JSON plugin by default
+    plugins.push(Box::new(JsonPlugin));
+
+    // Add define/replace plugin: import.meta.env from .env files, then --define
+    let client_env = load_client_env(&action.cwd, &action.mode);
+    let mut replace = ReplacePlugin::new();
+    for (from, to) in client_env_replacements(&client_env, &action.mode) {
+        replace = replace.replace(from, to);
+    }
+    for def in &action.define {
+        if let Some((key, value)) = def.split_once('=') {
+            replace = replace.replace(key.trim(), value.trim());
+        }
+    }
+    plugins.push(Box::new(replace));
+
+    // Add alias plugin
+    if !action.alias.is_empty() {
+        let mut alias_plugin = AliasPlugin::new();
+        for a in &action.alias {
+            if let Some((from, to)) = a.split_once('=') {
+                alias_plugin = alias_plugin.alias(from.trim(), to.trim());
+            }
+        }
+        plugins.push(Box::new(alias_plugin));
+    }
+
+    // Add banner plugin
+    if let Some(ref banner) = action.banner {
+        plugins.push(Box::new(BannerPlugin::new().banner(banner)));
+    }
+
+    // Create bundler with plugins
+    Bundler::with_cwd(&action.cwd).plugins(plugins)
+}
+
+/// Bundle options from CLI options.
+fn bundle_options(action: &BundleAction) -> BundleOptions {
+    BundleOptions {
+        format: action.format,
+        minify: action.minify,
+        mangle: action.mangle,
+        sourcemap: action.sourcemap,
+        external: action.external.clone(),
+        treeshake: action.treeshake,
+        splitting: action.splitting,
+        platform: action.platform,
+        builtins: action.builtins.clone(),
+        vendor_urls: action.vendor_urls,
+        packages: action.packages,
+        analyze: action.analyze,
+        production: action.mode == "production",
+        deterministic: action.deterministic,
+        ..Default::default()
+    }
+}
+
 fn format_to_string(format: BundleFormat) -> String {
     match format {
         BundleFormat::Esm => "esm".to_string(),
@@ -274,6 +1064,24 @@ fn format_to_string(format: BundleFormat) -> String {
     }
 }
 
+/// Parse `--builtin <module>=<strategy>` entries.
+///
+/// Returns the first malformed entry on error.
+pub fn parse_builtins(entries: &[String]) -> Result<HashMap<String, BuiltinStrategy>, String> {
+    entries
+        .iter()
+        .map(|entry| {
+            let (name, strategy) = entry.split_once('=').ok_or_else(|| entry.clone())?;
+            let name = name.trim().trim_start_matches("node:");
+            let strategy = BuiltinStrategy::parse(strategy).ok_or_else(|| entry.clone())?;
+            if name.is_empty() {
+                return Err(entry.clone());
+            }
+            Ok((name.to_string(), strategy))
+        })
+        .collect()
+}
+
 /// Parse format string to BundleFormat.
 pub fn parse_format(s: &str) -> Option<BundleFormat> {
     match s.to_lowercase().as_str() {
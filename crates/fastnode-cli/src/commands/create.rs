@@ -1,11 +1,20 @@
 //! `howth create` command implementation.
 //!
-//! Scaffolds a new project from a template.
+//! Scaffolds a new project from a template. GitHub templates are fetched as a
+//! tarball (no git required), cached for offline reuse, and `{{name}}`
+//! placeholders are replaced with the project name.
 
+use fastnode_core::config::Channel;
+use fastnode_core::paths;
+use fastnode_core::pkg::{download_tarball, MAX_TARBALL_SIZE};
+use flate2::read::GzDecoder;
 use miette::{IntoDiagnostic, Result};
 use serde::Serialize;
-use std::path::Path;
+use std::fs;
+use std::path::{Component, Path, PathBuf};
 use std::process::Command;
+use std::time::Duration;
+use tar::Archive;
 
 /// Known templates with their GitHub repositories.
 const KNOWN_TEMPLATES: &[(&str, &str)] = &[
@@ -18,41 +27,139 @@ const KNOWN_TEMPLATES: &[(&str, &str)] = &[
     ("solid", "solidjs/templates/tree/main/ts"),
 ];
 
+/// How long (in seconds) a cached template tarball is used without re-downloading.
+const TEMPLATE_CACHE_TTL_SECS: u64 = 24 * 60 * 60;
+
+/// Placeholder replaced with the project name in template files.
+const NAME_PLACEHOLDER: &str = "{{name}}";
+
+/// Version control metadata that is never copied into the new project.
+const VCS_DIRS: &[&str] = &[".git", ".hg", ".svn"];
+
 #[derive(Serialize)]
 struct CreateResult {
     ok: bool,
     template: String,
     project_name: String,
     path: String,
+    #[serde(skip_serializing_if = "std::ops::Not::not")]
+    from_cache: bool,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    installed: Option<bool>,
     #[serde(skip_serializing_if = "Option::is_none")]
     error: Option<String>,
 }
 
+/// A template hosted in a GitHub repository.
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct GithubTemplate {
+    owner: String,
+    repo: String,
+    /// Branch, tag or commit. `None` uses the default branch.
+    git_ref: Option<String>,
+    /// Subdirectory of the repository to use as the template root.
+    subdir: Option<String>,
+}
+
+impl GithubTemplate {
+    /// Parse `github:owner/repo[/tree/<ref>[/<path>]][#<ref>]` or the
+    /// equivalent `https://github.com/...` URL.
+    fn parse(source: &str) -> Option<Self> {
+        let rest = source
+            .strip_prefix("github:")
+            .or_else(|| source.strip_prefix("https://github.com/"))
+            .or_else(|| source.strip_prefix("http://github.com/"))?;
+
+        let (rest, hash_ref) = match rest.split_once('#') {
+            Some((rest, r)) if !r.is_empty() => (rest, Some(r.to_string())),
+            Some((rest, _)) => (rest, None),
+            None => (rest, None),
+        };
+
+        let mut segments = rest.trim_end_matches('/').split('/');
+        let owner = segments.next().filter(|s| !s.is_empty())?.to_string();
+        let repo = segments
+            .next()
+            .filter(|s| !s.is_empty())?
+            .trim_end_matches(".git")
+            .to_string();
+
+        let mut git_ref = hash_ref;
+        let mut subdir = None;
+        if segments.next() == Some("tree") {
+            if let Some(r) = segments.next() {
+                git_ref.get_or_insert_with(|| r.to_string());
+            }
+            let path: Vec<&str> = segments.filter(|s| !s.is_empty()).collect();
+            if !path.is_empty() {
+                subdir = Some(path.join("/"));
+            }
+        }
+
+        Some(Self {
+            owner,
+            repo,
+            git_ref,
+            subdir,
+        })
+    }
+
+    /// URL of the repository tarball for the selected ref.
+    fn tarball_url(&self) -> String {
+        format!(
+            "https://codeload.github.com/{}/{}/tar.gz/{}",
+            self.owner,
+            self.repo,
+            self.git_ref.as_deref().unwrap_or("HEAD")
+        )
+    }
+
+    /// Path of the cached tarball under `cache_root`.
+    fn cache_path(&self, cache_root: &Path) -> PathBuf {
+        let git_ref = self.git_ref.as_deref().unwrap_or("HEAD").replace('/', "+");
+        cache_root
+            .join(&self.owner)
+            .join(&self.repo)
+            .join(format!("{git_ref}.tar.gz"))
+    }
+}
+
 /// Run the create command.
-pub fn run(cwd: &Path, template: &str, name: Option<&str>, json: bool) -> Result<()> {
+pub fn run(
+    cwd: &Path,
+    template: &str,
+    name: Option<&str>,
+    install: bool,
+    channel: Channel,
+    json: bool,
+) -> Result<()> {
     // Determine project name
-    let project_name = name.unwrap_or(template.split('/').next_back().unwrap_or("my-app"));
+    let default_name = template
+        .split('#')
+        .next()
+        .unwrap_or(template)
+        .trim_end_matches('/')
+        .split('/')
+        .next_back()
+        .filter(|s| !s.is_empty())
+        .unwrap_or("my-app");
+    let project_name = name.unwrap_or(default_name);
     let project_path = cwd.join(project_name);
 
+    let mut result = CreateResult {
+        ok: false,
+        template: template.to_string(),
+        project_name: project_name.to_string(),
+        path: project_path.to_string_lossy().to_string(),
+        from_cache: false,
+        installed: None,
+        error: None,
+    };
+
     // Check if directory already exists
     if project_path.exists() {
-        let error = format!("Directory '{}' already exists", project_name);
-        if json {
-            let result = CreateResult {
-                ok: false,
-                template: template.to_string(),
-                project_name: project_name.to_string(),
-                path: project_path.to_string_lossy().to_string(),
-                error: Some(error.clone()),
-            };
-            println!(
-                "{}",
-                serde_json::to_string_pretty(&result).into_diagnostic()?
-            );
-        } else {
-            eprintln!("error: {}", error);
-        }
-        std::process::exit(1);
+        result.error = Some(format!("Directory '{}' already exists", project_name));
+        return fail(&result, json);
     }
 
     // Resolve template to a GitHub URL or degit-compatible path
@@ -63,54 +170,67 @@ pub fn run(cwd: &Path, template: &str, name: Option<&str>, json: bool) -> Result
         println!("Project name: {}", project_name);
     }
 
-    // Try degit first (preferred for templates), fall back to git clone
-    let result = try_degit(&template_source, &project_path)
-        .or_else(|_| try_git_clone(&template_source, &project_path));
-
-    match result {
-        Ok(()) => {
-            if json {
-                let result = CreateResult {
-                    ok: true,
-                    template: template.to_string(),
-                    project_name: project_name.to_string(),
-                    path: project_path.to_string_lossy().to_string(),
-                    error: None,
-                };
-                println!(
-                    "{}",
-                    serde_json::to_string_pretty(&result).into_diagnostic()?
-                );
-            } else {
-                println!();
-                println!("Created project at: {}", project_path.display());
-                println!();
-                println!("Next steps:");
-                println!("  cd {}", project_name);
-                println!("  howth install");
-                println!("  howth run dev");
-            }
-            Ok(())
+    let created = match GithubTemplate::parse(&template_source) {
+        Some(github) => {
+            let cache_root = paths::cache_dir(channel).join("templates");
+            create_from_github(&github, &cache_root, &project_path, json)
         }
+        None => try_git_clone(&template_source, &project_path).map(|()| false),
+    };
+
+    match created {
+        Ok(from_cache) => result.from_cache = from_cache,
         Err(e) => {
-            if json {
-                let result = CreateResult {
-                    ok: false,
-                    template: template.to_string(),
-                    project_name: project_name.to_string(),
-                    path: project_path.to_string_lossy().to_string(),
-                    error: Some(e.clone()),
-                };
-                println!(
-                    "{}",
-                    serde_json::to_string_pretty(&result).into_diagnostic()?
-                );
-            } else {
-                eprintln!("error: {}", e);
-            }
-            std::process::exit(1);
+            let _ = fs::remove_dir_all(&project_path);
+            result.error = Some(e);
+            return fail(&result, json);
+        }
+    }
+
+    if let Err(e) = substitute_placeholders(&project_path, project_name) {
+        result.error = Some(e);
+        return fail(&result, json);
+    }
+
+    if install {
+        let installed = run_install(&project_path, json);
+        result.installed = Some(installed);
+        if !installed && !json {
+            eprintln!("warning: dependency install failed; run `howth install` in the project");
+        }
+    }
+
+    result.ok = true;
+    if json {
+        println!(
+            "{}",
+            serde_json::to_string_pretty(&result).into_diagnostic()?
+        );
+    } else {
+        println!();
+        println!("Created project at: {}", project_path.display());
+        println!();
+        println!("Next steps:");
+        println!("  cd {}", project_name);
+        if result.installed != Some(true) {
+            println!("  howth install");
         }
+        println!("  howth run dev");
     }
+    Ok(())
+}
+
+/// Print a failed result and exit.
+fn fail(result: &CreateResult, json: bool) -> Result<()> {
+    if json {
+        println!(
+            "{}",
+            serde_json::to_string_pretty(result).into_diagnostic()?
+        );
+    } else if let Some(error) = &result.error {
+        eprintln!("error: {}", error);
+    }
+    std::process::exit(1);
 }
 
 /// Resolve a template name to a source path.
@@ -141,47 +261,209 @@ fn resolve_template(template: &str) -> String {
     template.to_string()
 }
 
-/// Try to use degit for scaffolding (faster, no .git directory).
-fn try_degit(source: &str, dest: &Path) -> Result<(), String> {
-    // Strip the "github:" prefix for degit
-    let degit_source = source.strip_prefix("github:").unwrap_or(source);
+/// Download (or reuse) a GitHub template tarball and extract it to `dest`.
+///
+/// Returns whether the cached tarball was used.
+fn create_from_github(
+    template: &GithubTemplate,
+    cache_root: &Path,
+    dest: &Path,
+    json: bool,
+) -> Result<bool, String> {
+    let (bytes, from_cache) = fetch_template(template, cache_root, json)?;
+    let extracted = extract_template(&bytes, template.subdir.as_deref(), dest)?;
+    if extracted == 0 {
+        return Err(match &template.subdir {
+            Some(subdir) => format!("Path '{}' not found in template", subdir),
+            None => "Template archive is empty".to_string(),
+        });
+    }
+    Ok(from_cache)
+}
 
-    let output = Command::new("npx")
-        .arg("degit")
-        .arg(degit_source)
-        .arg(dest)
-        .output()
-        .map_err(|e| format!("Failed to run degit: {}", e))?;
+/// Get template tarball bytes, preferring a fresh cache entry.
+///
+/// Falls back to a stale cache entry when the download fails, so recently
+/// used templates work offline.
+fn fetch_template(
+    template: &GithubTemplate,
+    cache_root: &Path,
+    json: bool,
+) -> Result<(Vec<u8>, bool), String> {
+    let cache_path = template.cache_path(cache_root);
 
-    if output.status.success() {
-        Ok(())
+    let cache_age = fs::metadata(&cache_path)
+        .and_then(|m| m.modified())
+        .ok()
+        .and_then(|t| t.elapsed().ok());
+    if cache_age.is_some_and(|age| age < Duration::from_secs(TEMPLATE_CACHE_TTL_SECS)) {
+        if let Ok(bytes) = fs::read(&cache_path) {
+            return Ok((bytes, true));
+        }
+    }
+
+    match download_template(template) {
+        Ok(bytes) => {
+            // Caching is best-effort; a failure here shouldn't fail the create.
+            if let Some(parent) = cache_path.parent() {
+                let tmp = cache_path.with_extension(format!("tmp.{}", std::process::id()));
+                if fs::create_dir_all(parent).is_ok() && fs::write(&tmp, &bytes).is_ok() {
+                    let _ = fs::rename(&tmp, &cache_path);
+                }
+            }
+            Ok((bytes, false))
+        }
+        Err(e) => match fs::read(&cache_path) {
+            Ok(bytes) => {
+                if !json {
+                    eprintln!("warning: {}; using cached template", e);
+                }
+                Ok((bytes, true))
+            }
+            Err(_) => Err(e),
+        },
+    }
+}
+
+/// Download the repository tarball from GitHub.
+fn download_template(template: &GithubTemplate) -> Result<Vec<u8>, String> {
+    let runtime =
+        tokio::runtime::Runtime::new().map_err(|e| format!("Failed to start runtime: {}", e))?;
+    runtime.block_on(async {
+        let client = reqwest::Client::builder()
+            .user_agent(concat!("howth/", env!("CARGO_PKG_VERSION")))
+            .build()
+            .map_err(|e| format!("Failed to create HTTP client: {}", e))?;
+        let token = std::env::var("GITHUB_TOKEN").ok();
+        download_tarball(
+            &client,
+            &template.tarball_url(),
+            MAX_TARBALL_SIZE,
+            token.as_deref(),
+        )
+        .await
+        .map(|bytes| bytes.to_vec())
+        .map_err(|e| e.to_string())
+    })
+}
+
+/// Extract a GitHub tarball into `dest`.
+///
+/// Strips the top-level `<repo>-<sha>/` directory, keeps only `subdir` if
+/// given, and skips VCS metadata. Only regular files and directories are
+/// extracted; a link entry could point later entries outside `dest`.
+/// Returns the number of entries written.
+fn extract_template(bytes: &[u8], subdir: Option<&str>, dest: &Path) -> Result<usize, String> {
+    let subdir = subdir.map(Path::new);
+    let mut archive = Archive::new(GzDecoder::new(bytes));
+    let entries = archive
+        .entries()
+        .map_err(|e| format!("Invalid template archive: {}", e))?;
+
+    fs::create_dir_all(dest).map_err(|e| format!("Failed to create directory: {}", e))?;
+
+    let mut count = 0;
+    for entry in entries {
+        let mut entry = entry.map_err(|e| format!("Invalid template archive: {}", e))?;
+        let path = entry
+            .path()
+            .map_err(|e| format!("Invalid template archive: {}", e))?
+            .into_owned();
+
+        // Only plain relative components; drop the archive root directory.
+        if !path.components().all(|c| matches!(c, Component::Normal(_))) {
+            continue;
+        }
+        let rel: PathBuf = path.components().skip(1).collect();
+        let rel = match subdir {
+            Some(subdir) => match rel.strip_prefix(subdir) {
+                Ok(stripped) => stripped.to_path_buf(),
+                Err(_) => continue,
+            },
+            None => rel,
+        };
+        if rel.as_os_str().is_empty()
+            || rel
+                .components()
+                .any(|c| VCS_DIRS.iter().any(|vcs| c.as_os_str() == *vcs))
+        {
+            continue;
+        }
+
+        let target = dest.join(&rel);
+        let entry_type = entry.header().entry_type();
+        if entry_type.is_dir() {
+            fs::create_dir_all(&target)
+                .map_err(|e| format!("Failed to create directory: {}", e))?;
+        } else if entry_type.is_file() {
+            if let Some(parent) = target.parent() {
+                fs::create_dir_all(parent)
+                    .map_err(|e| format!("Failed to create directory: {}", e))?;
+            }
+            entry
+                .unpack(&target)
+                .map_err(|e| format!("Failed to extract '{}': {}", rel.display(), e))?;
+        } else {
+            // Symlinks, hard links and special files
+            continue;
+        }
+        count += 1;
+    }
+
+    Ok(count)
+}
+
+/// Replace `{{name}}` with the project name in every text file under `dir`.
+fn substitute_placeholders(dir: &Path, project_name: &str) -> Result<(), String> {
+    for entry in walkdir::WalkDir::new(dir) {
+        let entry = entry.map_err(|e| format!("Failed to read template: {}", e))?;
+        if !entry.file_type().is_file() {
+            continue;
+        }
+        // Binary files are left untouched.
+        let Ok(content) = fs::read_to_string(entry.path()) else {
+            continue;
+        };
+        if content.contains(NAME_PLACEHOLDER) {
+            fs::write(
+                entry.path(),
+                content.replace(NAME_PLACEHOLDER, project_name),
+            )
+            .map_err(|e| format!("Failed to write '{}': {}", entry.path().display(), e))?;
+        }
+    }
+    Ok(())
+}
+
+/// Install dependencies in the new project with `howth install`.
+///
+/// Output is shown in human mode and suppressed in JSON mode.
+fn run_install(project_path: &Path, json: bool) -> bool {
+    let Ok(exe) = std::env::current_exe() else {
+        return false;
+    };
+    if !json {
+        println!();
+        println!("Installing dependencies...");
+    }
+    let mut cmd = Command::new(exe);
+    cmd.arg("install").arg("--cwd").arg(project_path);
+    if json {
+        cmd.output().is_ok_and(|o| o.status.success())
     } else {
-        let stderr = String::from_utf8_lossy(&output.stderr);
-        Err(format!("degit failed: {}", stderr))
+        cmd.status().is_ok_and(|s| s.success())
     }
 }
 
 /// Fall back to git clone.
 fn try_git_clone(source: &str, dest: &Path) -> Result<(), String> {
     // Convert source to a git URL
-    let git_url = if source.starts_with("github:") {
-        let repo = source.strip_prefix("github:").unwrap();
-        // Handle tree/branch paths
-        if repo.contains("/tree/") {
-            // Extract repo and path
-            let parts: Vec<&str> = repo.splitn(2, "/tree/").collect();
-            if parts.len() == 2 {
-                // Clone the whole repo, we'll handle subdirs later
-                format!("https://github.com/{}.git", parts[0])
-            } else {
-                format!("https://github.com/{}.git", repo)
-            }
-        } else {
-            format!("https://github.com/{}.git", repo)
-        }
-    } else if source.starts_with("gitlab:") {
+    let git_url = if source.starts_with("gitlab:") {
         let repo = source.strip_prefix("gitlab:").unwrap();
         format!("https://gitlab.com/{}.git", repo)
+    } else if source.starts_with("bitbucket:") {
+        let repo = source.strip_prefix("bitbucket:").unwrap();
+        format!("https://bitbucket.org/{}.git", repo)
     } else if source.starts_with("http://") || source.starts_with("https://") {
         source.to_string()
     } else {
@@ -209,3 +491,143 @@ fn try_git_clone(source: &str, dest: &Path) -> Result<(), String> {
         Err(format!("git clone failed: {}", stderr))
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use flate2::write::GzEncoder;
+    use flate2::Compression;
+
+    fn make_tarball(files: &[(&str, &str)]) -> Vec<u8> {
+        let encoder = GzEncoder::new(Vec::new(), Compression::default());
+        let mut builder = tar::Builder::new(encoder);
+        for (path, content) in files {
+            let mut header = tar::Header::new_gnu();
+            header.set_size(content.len() as u64);
+            header.set_mode(0o644);
+            header.set_cksum();
+            builder
+                .append_data(&mut header, path, content.as_bytes())
+                .unwrap();
+        }
+        builder.into_inner().unwrap().finish().unwrap()
+    }
+
+    #[test]
+    fn test_parse_github_template() {
+        let t = GithubTemplate::parse("github:user/repo").unwrap();
+        assert_eq!(t.owner, "user");
+        assert_eq!(t.repo, "repo");
+        assert_eq!(t.git_ref, None);
+        assert_eq!(t.subdir, None);
+        assert_eq!(
+            t.tarball_url(),
+            "https://codeload.github.com/user/repo/tar.gz/HEAD"
+        );
+
+        let t = GithubTemplate::parse("github:vercel/next.js/tree/canary/examples/basic").unwrap();
+        assert_eq!(t.repo, "next.js");
+        assert_eq!(t.git_ref.as_deref(), Some("canary"));
+        assert_eq!(t.subdir.as_deref(), Some("examples/basic"));
+
+        let t = GithubTemplate::parse("https://github.com/user/repo.git#v2").unwrap();
+        assert_eq!(t.repo, "repo");
+        assert_eq!(t.git_ref.as_deref(), Some("v2"));
+
+        assert!(GithubTemplate::parse("gitlab:user/repo").is_none());
+        assert!(GithubTemplate::parse("github:user").is_none());
+    }
+
+    #[test]
+    fn test_extract_template_strips_root_and_vcs() {
+        let bytes = make_tarball(&[
+            ("repo-abc123/package.json", r#"{"name": "{{name}}"}"#),
+            ("repo-abc123/src/index.js", "console.log('{{name}}');"),
+            ("repo-abc123/.git/HEAD", "ref: refs/heads/main"),
+            ("repo-abc123/.gitignore", "node_modules"),
+        ]);
+        let dir = tempfile::tempdir().unwrap();
+        let dest = dir.path().join("app");
+
+        let count = extract_template(&bytes, None, &dest).unwrap();
+        assert_eq!(count, 3);
+        assert!(dest.join("src/index.js").is_file());
+        assert!(dest.join(".gitignore").is_file());
+        assert!(!dest.join(".git").exists());
+
+        substitute_placeholders(&dest, "my-app").unwrap();
+        assert_eq!(
+            fs::read_to_string(dest.join("package.json")).unwrap(),
+            r#"{"name": "my-app"}"#
+        );
+        assert_eq!(
+            fs::read_to_string(dest.join("src/index.js")).unwrap(),
+            "console.log('my-app');"
+        );
+    }
+
+    #[test]
+    fn test_extract_template_subdir() {
+        let bytes = make_tarball(&[
+            ("repo-abc/README.md", "root"),
+            ("repo-abc/examples/basic/index.js", "basic"),
+            ("repo-abc/examples/other/index.js", "other"),
+        ]);
+        let dir = tempfile::tempdir().unwrap();
+        let dest = dir.path().join("app");
+
+        let count = extract_template(&bytes, Some("examples/basic"), &dest).unwrap();
+        assert_eq!(count, 1);
+        assert_eq!(fs::read_to_string(dest.join("index.js")).unwrap(), "basic");
+        assert!(!dest.join("README.md").exists());
+    }
+
+    #[test]
+    fn test_extract_template_skips_links() {
+        let dir = tempfile::tempdir().unwrap();
+        let outside = dir.path().join("outside");
+        fs::create_dir(&outside).unwrap();
+
+        let encoder = GzEncoder::new(Vec::new(), Compression::default());
+        let mut builder = tar::Builder::new(encoder);
+        for (kind, path) in [
+            (tar::EntryType::Symlink, "repo-abc/link"),
+            (tar::EntryType::Link, "repo-abc/hard"),
+        ] {
+            let mut header = tar::Header::new_gnu();
+            header.set_entry_type(kind);
+            header.set_size(0);
+            builder.append_link(&mut header, path, &outside).unwrap();
+        }
+        let mut header = tar::Header::new_gnu();
+        header.set_size(5);
+        header.set_mode(0o644);
+        header.set_cksum();
+        builder
+            .append_data(&mut header, "repo-abc/link/x", "owned".as_bytes())
+            .unwrap();
+        let bytes = builder.into_inner().unwrap().finish().unwrap();
+
+        let dest = dir.path().join("app");
+        let count = extract_template(&bytes, None, &dest).unwrap();
+        assert_eq!(count, 1);
+        // The write through `link` stayed inside the project
+        assert!(!outside.join("x").exists());
+        assert!(!dest.join("link").is_symlink());
+        assert_eq!(fs::read_to_string(dest.join("link/x")).unwrap(), "owned");
+        assert!(!dest.join("hard").exists());
+    }
+
+    #[test]
+    fn test_fetch_template_uses_fresh_cache() {
+        let dir = tempfile::tempdir().unwrap();
+        let template = GithubTemplate::parse("github:user/repo#main").unwrap();
+        let cache_path = template.cache_path(dir.path());
+        fs::create_dir_all(cache_path.parent().unwrap()).unwrap();
+        fs::write(&cache_path, b"cached").unwrap();
+
+        let (bytes, from_cache) = fetch_template(&template, dir.path(), true).unwrap();
+        assert!(from_cache);
+        assert_eq!(bytes, b"cached");
+    }
+}
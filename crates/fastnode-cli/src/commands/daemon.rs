@@ -1,8 +1,27 @@
+use super::{ping, stop};
 use fastnode_core::config::Channel;
 use fastnode_core::paths;
+use fastnode_core::VERSION;
 use fastnode_daemon::{run_server, DaemonConfig};
 use miette::{IntoDiagnostic, Result};
-use std::io::Write;
+use std::fs;
+use std::io::{self, Write};
+use std::path::PathBuf;
+use std::process::{Command, Stdio};
+use std::time::{Duration, Instant};
+use tracing::debug;
+
+/// Environment variable disabling daemon auto-start (same as `--no-autostart`).
+pub const NO_AUTOSTART_ENV: &str = "HOWTH_NO_AUTOSTART";
+
+/// How long to wait for a daemon to come up or shut down.
+const DAEMON_WAIT: Duration = Duration::from_secs(10);
+
+/// Interval between connection attempts while waiting.
+const POLL_INTERVAL: Duration = Duration::from_millis(50);
+
+/// A start lock older than this was left behind by a CLI that died.
+const STALE_LOCK_AGE: Duration = Duration::from_secs(30);
 
 /// Run the daemon command.
 ///
@@ -12,13 +31,247 @@ pub fn run(channel: Channel, _json: bool) -> Result<()> {
     paths::ensure_ipc_dir(channel).into_diagnostic()?;
 
     let endpoint = paths::ipc_endpoint(channel);
-    let config = DaemonConfig { endpoint };
+    let config = DaemonConfig { endpoint, channel };
 
     // Print startup message to stderr
-    eprintln!("daemon listening at {}", config.endpoint);
+    eprintln!(
+        "daemon ({}) listening at {}",
+        config.channel, config.endpoint
+    );
     std::io::stderr().flush().into_diagnostic()?;
 
     // Run the async server
     let runtime = tokio::runtime::Runtime::new().into_diagnostic()?;
     runtime.block_on(async { run_server(config).await.into_diagnostic() })
 }
+
+/// Make sure a daemon at least as new as this CLI is running.
+///
+/// Starts one in the background when none answers, and restarts one that
+/// reports an older version. Failures are only warned about; the command's
+/// own connection error explains what went wrong.
+pub fn ensure_running(channel: Channel, json: bool) {
+    let runtime = match tokio::runtime::Builder::new_current_thread()
+        .enable_all()
+        .build()
+    {
+        Ok(runtime) => runtime,
+        Err(e) => {
+            debug!(error = %e, "cannot create runtime for daemon auto-start");
+            return;
+        }
+    };
+
+    if let Err(e) = runtime.block_on(ensure_running_async(channel, json)) {
+        debug!(error = %e, "daemon auto-start failed");
+        if !json {
+            eprintln!("warning: could not start the daemon: {e}");
+        }
+    }
+}
+
+async fn ensure_running_async(channel: Channel, json: bool) -> io::Result<()> {
+    let endpoint = paths::ipc_endpoint(channel);
+    if server_version(&endpoint)
+        .await
+        .is_some_and(|v| !is_older(&v))
+    {
+        return Ok(());
+    }
+
+    // Only one CLI starts (or restarts) the daemon; the others wait for it
+    let Some(_lock) = StartLock::acquire(&endpoint, channel)? else {
+        return wait_until_current(&endpoint).await;
+    };
+
+    match server_version(&endpoint).await {
+        Some(version) if !is_older(&version) => return Ok(()),
+        Some(version) => {
+            if !json {
+                eprintln!("note: restarting daemon {version} to match CLI {VERSION}");
+            }
+            stop::stop_daemon(&endpoint).await?;
+            wait_until_stopped(&endpoint).await?;
+        }
+        None => {}
+    }
+
+    debug!(%endpoint, %channel, "starting daemon");
+    paths::ensure_ipc_dir(channel)?;
+    spawn_daemon(channel)?;
+    wait_until_current(&endpoint).await
+}
+
+/// Version reported by the daemon's handshake, `None` if none answers.
+async fn server_version(endpoint: &str) -> Option<String> {
+    ping::ping_daemon(endpoint, 0)
+        .await
+        .ok()
+        .map(|(_, version)| version)
+}
+
+/// Check if a daemon `version` predates this CLI.
+///
+/// Versions that don't parse are never treated as older.
+fn is_older(version: &str) -> bool {
+    match (
+        semver::Version::parse(version),
+        semver::Version::parse(VERSION),
+    ) {
+        (Ok(server), Ok(client)) => server < client,
+        _ => false,
+    }
+}
+
+async fn wait_until_current(endpoint: &str) -> io::Result<()> {
+    let deadline = Instant::now() + DAEMON_WAIT;
+    while Instant::now() < deadline {
+        if server_version(endpoint)
+            .await
+            .is_some_and(|v| !is_older(&v))
+        {
+            return Ok(());
+        }
+        tokio::time::sleep(POLL_INTERVAL).await;
+    }
+    Err(io::Error::new(
+        io::ErrorKind::TimedOut,
+        format!("daemon did not come up at {endpoint}"),
+    ))
+}
+
+async fn wait_until_stopped(endpoint: &str) -> io::Result<()> {
+    let deadline = Instant::now() + DAEMON_WAIT;
+    while Instant::now() < deadline {
+        if server_version(endpoint).await.is_none() {
+            return Ok(());
+        }
+        tokio::time::sleep(POLL_INTERVAL).await;
+    }
+    Err(io::Error::new(
+        io::ErrorKind::TimedOut,
+        format!("outdated daemon at {endpoint} did not shut down"),
+    ))
+}
+
+/// Start `howth daemon` for `channel`, detached from this process.
+///
+/// The daemon inherits the environment, including `HOWTH_IPC_ENDPOINT`.
+fn spawn_daemon(channel: Channel) -> io::Result<()> {
+    let mut command = Command::new(std::env::current_exe()?);
+    command
+        .args(["--channel", channel.as_str(), "daemon"])
+        .stdin(Stdio::null())
+        .stdout(Stdio::null())
+        .stderr(Stdio::null());
+
+    // Keep the daemon alive when the terminal's process group gets Ctrl-C
+    #[cfg(unix)]
+    {
+        use std::os::unix::process::CommandExt;
+        command.process_group(0);
+    }
+    #[cfg(windows)]
+    {
+        use std::os::windows::process::CommandExt;
+        const DETACHED_PROCESS: u32 = 0x0000_0008;
+        const CREATE_NEW_PROCESS_GROUP: u32 = 0x0000_0200;
+        command.creation_flags(DETACHED_PROCESS | CREATE_NEW_PROCESS_GROUP);
+    }
+
+    command.spawn().map(drop)
+}
+
+/// Lock file held while a CLI starts the daemon, so concurrent commands
+/// don't race to start several.
+struct StartLock {
+    path: PathBuf,
+}
+
+impl StartLock {
+    /// Take the lock, or return `None` if another CLI holds it.
+    fn acquire(endpoint: &str, channel: Channel) -> io::Result<Option<Self>> {
+        let path = start_lock_path(endpoint, channel);
+        if let Some(parent) = path.parent() {
+            fs::create_dir_all(parent)?;
+        }
+
+        // A second attempt follows removing a stale lock
+        for _ in 0..2 {
+            match fs::OpenOptions::new()
+                .write(true)
+                .create_new(true)
+                .open(&path)
+            {
+                Ok(mut file) => {
+                    let _ = write!(file, "{}", std::process::id());
+                    return Ok(Some(Self { path }));
+                }
+                Err(e) if e.kind() == io::ErrorKind::AlreadyExists => {
+                    let stale = fs::metadata(&path)
+                        .and_then(|m| m.modified())
+                        .ok()
+                        .and_then(|modified| modified.elapsed().ok())
+                        .is_some_and(|age| age > STALE_LOCK_AGE);
+                    if !stale {
+                        return Ok(None);
+                    }
+                    let _ = fs::remove_file(&path);
+                }
+                Err(e) => return Err(e),
+            }
+        }
+        Ok(None)
+    }
+}
+
+impl Drop for StartLock {
+    fn drop(&mut self) {
+        let _ = fs::remove_file(&self.path);
+    }
+}
+
+/// Lock file next to the socket, so every endpoint has its own.
+#[cfg(unix)]
+fn start_lock_path(endpoint: &str, _channel: Channel) -> PathBuf {
+    PathBuf::from(format!("{endpoint}.start.lock"))
+}
+
+/// Named pipes have no directory, so the lock lives in the data dir.
+#[cfg(windows)]
+fn start_lock_path(_endpoint: &str, channel: Channel) -> PathBuf {
+    paths::data_dir(channel).join("daemon.start.lock")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::tempdir;
+
+    #[test]
+    fn test_is_older() {
+        assert!(is_older("0.0.1"));
+        assert!(!is_older(VERSION));
+        assert!(!is_older("999.0.0"));
+        assert!(!is_older("not-a-version"));
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_start_lock_is_exclusive() {
+        let dir = tempdir().unwrap();
+        let endpoint = dir.path().join("howth.sock").to_string_lossy().into_owned();
+
+        let lock = StartLock::acquire(&endpoint, Channel::Stable)
+            .unwrap()
+            .expect("first acquire takes the lock");
+        assert!(StartLock::acquire(&endpoint, Channel::Stable)
+            .unwrap()
+            .is_none());
+
+        drop(lock);
+        assert!(StartLock::acquire(&endpoint, Channel::Stable)
+            .unwrap()
+            .is_some());
+    }
+}
@@ -20,26 +20,34 @@
 //! Dependencies from `node_modules` are pre-bundled on startup into `.howth/deps/`
 //! and served at `/@modules/{pkg}` URLs.
 
+use super::dev_ssr::SsrRenderer;
 use axum::{
     body::Body,
     extract::{
         ws::{Message, WebSocket, WebSocketUpgrade},
         Path as AxumPath, RawQuery, Request, State,
     },
+    http::Uri,
     http::{header, Method, StatusCode},
-    response::{Html, IntoResponse, Response},
+    response::{IntoResponse, Response},
     routing::get,
     Router,
 };
+use fastnode_core::build::BuildDiagnostic;
 use fastnode_core::bundler::{
     plugins::ReactRefreshPlugin, AliasPlugin, BundleFormat, BundleOptions, Bundler, DevConfig,
-    PluginContainer, ReplacePlugin,
+    Platform, PluginContainer, ReplacePlugin,
 };
+use fastnode_core::compiler::backend_from_env;
+use fastnode_core::config::Channel;
 use fastnode_core::dev::config::ProxyConfig;
 use fastnode_core::dev::{
-    client_env_replacements, extract_import_urls, is_self_accepting_module, load_config,
-    load_env_files, HmrEngine, ModuleTransformer, PreBundler,
+    client_env_replacements, extract_import_urls, is_page_request, is_self_accepting_module,
+    load_client_env, load_config, load_env_files, stitch_html, HmrEngine, ModuleTransformer,
+    PreBundler, CLASSIC_WORKER_QUERY,
 };
+use fastnode_core::paths;
+use fastnode_daemon::DaemonCompiler;
 use miette::{IntoDiagnostic, Result};
 use notify::{Config, RecommendedWatcher, RecursiveMode, Watcher};
 use std::collections::HashSet;
@@ -66,6 +74,10 @@ pub struct DevAction {
     pub config: Option<PathBuf>,
     /// Mode (e.g. "development", "production").
     pub mode: String,
+    /// SSR entry (overrides `ssr.entry` from the config file).
+    pub ssr: Option<PathBuf>,
+    /// Channel whose daemon caches module transforms.
+    pub channel: Channel,
 }
 
 /// Shared server state for Vite-compatible unbundled serving.
@@ -94,6 +106,8 @@ struct DevState {
     proxy: std::collections::HashMap<String, ProxyConfig>,
     /// HTTP client for proxying requests.
     http_client: reqwest::Client,
+    /// Server-side renderer for page requests (`--ssr`).
+    ssr: Option<Arc<SsrRenderer>>,
 }
 
 /// HMR message types.
@@ -103,8 +117,11 @@ enum HmrMessage {
     Reload,
     /// Partial module update (Vite-compatible).
     Update { updates: Vec<HmrModuleUpdate> },
-    /// Build error.
-    Error { message: String },
+    /// Build error, with structured diagnostics (e.g. type errors) if available.
+    Error {
+        message: String,
+        diagnostics: Vec<BuildDiagnostic>,
+    },
     /// Connected confirmation.
     Connected,
 }
@@ -139,12 +156,15 @@ impl HmrMessage {
                     update_json.join(",")
                 )
             }
-            HmrMessage::Error { message } => {
-                format!(
-                    r#"{{"type":"error","message":"{}"}}"#,
-                    message.replace('"', "\\\"")
-                )
-            }
+            HmrMessage::Error {
+                message,
+                diagnostics,
+            } => serde_json::json!({
+                "type": "error",
+                "message": message,
+                "diagnostics": diagnostics,
+            })
+            .to_string(),
         }
     }
 }
@@ -217,17 +237,14 @@ pub async fn run(action: DevAction) -> Result<()> {
     // Load .env files
     let mode = &action.mode;
     let dot_env = load_env_files(&cwd, mode);
-    let env_replacements = client_env_replacements(&dot_env, mode);
-    let env_var_count = dot_env
-        .iter()
-        .filter(|(k, _)| k.starts_with("VITE_") || k.starts_with("HOWTH_"))
-        .count();
+    let client_env = load_client_env(&cwd, mode);
+    let env_replacements = client_env_replacements(&client_env, mode);
     if !dot_env.is_empty() {
         println!(
             "  Loaded {} env var{} ({} exposed to client)",
             dot_env.len(),
             if dot_env.len() == 1 { "" } else { "s" },
-            env_var_count,
+            client_env.len(),
         );
     }
 
@@ -342,8 +359,11 @@ pub async fn run(action: DevAction) -> Result<()> {
     let _ = plugins.call_config(&mut dev_config);
     let _ = plugins.call_config_resolved(&dev_config);
 
-    // Initialize module transformer
-    let transformer = ModuleTransformer::new(cwd.clone());
+    // Initialize module transformer; transforms go through the daemon's
+    // shared cache when it is running
+    let transformer = ModuleTransformer::new(cwd.clone()).with_compiler(Arc::new(
+        DaemonCompiler::with_fallback(paths::ipc_endpoint(action.channel), backend_from_env()),
+    ));
 
     // Pre-bundle dependencies
     println!("  Scanning dependencies...");
@@ -371,6 +391,25 @@ pub async fn run(action: DevAction) -> Result<()> {
         }
     }
 
+    // Load the SSR entry (CLI flag overrides config)
+    let ssr_entry = action.ssr.clone().or_else(|| {
+        howth_config
+            .as_ref()
+            .and_then(|cfg| cfg.ssr.entry.as_ref().map(PathBuf::from))
+    });
+    let ssr = match ssr_entry {
+        Some(entry) => {
+            let entry = if entry.is_absolute() {
+                entry
+            } else {
+                cwd.join(entry)
+            };
+            let renderer = SsrRenderer::start(&entry, &cwd).map_err(|e| miette::miette!("{e}"))?;
+            Some(Arc::new(renderer))
+        }
+        None => None,
+    };
+
     // Initialize HMR engine
     let hmr_engine = HmrEngine::new();
 
@@ -379,6 +418,7 @@ pub async fn run(action: DevAction) -> Result<()> {
     let bundle_options = BundleOptions {
         format: BundleFormat::Esm,
         treeshake: false,
+        platform: Platform::Browser,
         ..Default::default()
     };
 
@@ -417,6 +457,7 @@ pub async fn run(action: DevAction) -> Result<()> {
         bundle_options,
         proxy: proxy_config,
         http_client,
+        ssr,
     });
 
     // Set up file watcher
@@ -475,7 +516,7 @@ pub async fn run(action: DevAction) -> Result<()> {
         .route("/@modules/*pkg", get(serve_prebundled_dep))
         .route("/@style/*path", get(serve_css_module))
         .route("/*path", get(serve_module))
-        .with_state((state, index_html))
+        .with_state((state.clone(), index_html))
         .layer(
             CorsLayer::new()
                 .allow_origin(Any)
@@ -501,6 +542,10 @@ pub async fn run(action: DevAction) -> Result<()> {
     );
     println!("  Vite-compatible unbundled serving enabled");
     println!("  Hot Module Replacement enabled");
+    if let Some(ssr) = &state.ssr {
+        let entry = ssr.entry().strip_prefix(&cwd).unwrap_or(ssr.entry());
+        println!("  SSR enabled (entry: {})", entry.display());
+    }
     println!();
     println!("  Press Ctrl+C to stop");
     println!();
@@ -525,9 +570,58 @@ pub async fn run(action: DevAction) -> Result<()> {
 
 type AppState = (Arc<DevState>, &'static str);
 
-/// Serve the index HTML page.
-async fn serve_index(State((_state, index_html)): State<AppState>) -> Html<&'static str> {
-    Html(index_html)
+/// Serve the index HTML page (server-rendered when SSR is enabled).
+async fn serve_index(State((state, index_html)): State<AppState>, uri: Uri) -> Response<String> {
+    let url = uri.path_and_query().map_or("/", |pq| pq.as_str());
+    serve_page(&state, url, index_html).await
+}
+
+/// Serve `index.html` for a page route, rendering it with the SSR entry if
+/// one is configured.
+async fn serve_page(state: &DevState, url: &str, index_html: &'static str) -> Response<String> {
+    let Some(ssr) = state.ssr.clone() else {
+        return html_response(StatusCode::OK, index_html.to_string());
+    };
+
+    let url = url.to_string();
+    let rendered = tokio::task::spawn_blocking(move || ssr.render(&url))
+        .await
+        .unwrap_or_else(|e| Err(format!("SSR render task failed: {e}")));
+
+    match rendered {
+        Ok(render) => {
+            let status = render
+                .status
+                .and_then(|status| StatusCode::from_u16(status).ok())
+                .unwrap_or(StatusCode::OK);
+            html_response(status, stitch_html(index_html, &render))
+        }
+        Err(error) => {
+            eprintln!("  SSR error: {error}");
+            // Keep the HMR client on the page so fixing the error reloads it
+            let body = format!(
+                "<!DOCTYPE html>\n<html>\n<head>\n  <title>SSR error</title>\n  \
+                 <script type=\"module\" src=\"/@hmr-client\"></script>\n</head>\n\
+                 <body>\n  <h1>SSR error</h1>\n  <pre>{}</pre>\n</body>\n</html>\n",
+                escape_html(&error)
+            );
+            html_response(StatusCode::INTERNAL_SERVER_ERROR, body)
+        }
+    }
+}
+
+fn html_response(status: StatusCode, body: String) -> Response<String> {
+    Response::builder()
+        .status(status)
+        .header("Content-Type", "text/html")
+        .body(body)
+        .unwrap()
+}
+
+fn escape_html(text: &str) -> String {
+    text.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
 }
 
 /// Serve the HMR client runtime at `/@hmr-client`.
@@ -739,6 +833,34 @@ async fn handle_proxy(
 ///
 /// Also handles SPA fallback: non-file routes (no extension) return index.html
 /// so client-side routing (React Router, Vue Router, etc.) works on refresh.
+/// Serve a classic worker script (`new Worker(new URL(...))` without
+/// `{ type: 'module' }`) bundled into a single IIFE.
+fn serve_classic_worker(state: &DevState, url_path: &str) -> Response<String> {
+    let file = state.cwd.join(url_path.trim_start_matches('/'));
+    let options = BundleOptions {
+        format: BundleFormat::Iife,
+        splitting: false,
+        ..state.bundle_options.clone()
+    };
+    match state.bundler.bundle(&file, &state.cwd, &options) {
+        Ok(result) => Response::builder()
+            .status(StatusCode::OK)
+            .header("Content-Type", "application/javascript")
+            .header("Cache-Control", "no-cache")
+            .body(result.code)
+            .unwrap(),
+        Err(e) => Response::builder()
+            .status(StatusCode::INTERNAL_SERVER_ERROR)
+            .header("Content-Type", "application/javascript")
+            .body(format!(
+                "console.error({});",
+                serde_json::to_string(&format!("Worker bundle error: {}", e.message))
+                    .unwrap_or_default()
+            ))
+            .unwrap(),
+    }
+}
+
 async fn serve_module(
     State((state, index_html)): State<AppState>,
     AxumPath(path): AxumPath<String>,
@@ -764,8 +886,20 @@ async fn serve_module(
     // Check if this is a JS/TS module request
     let ext = url_path.rsplit('.').next().unwrap_or("");
 
+    let is_module = matches!(ext, "ts" | "tsx" | "js" | "jsx" | "mjs" | "cjs" | "json")
+        || state.plugins.handles_extension(ext);
+
+    // Classic workers can't `import`, so they're served bundled
+    if is_module
+        && query
+            .as_deref()
+            .is_some_and(|q| q.contains(CLASSIC_WORKER_QUERY))
+    {
+        return serve_classic_worker(&state, url_path);
+    }
+
     match ext {
-        "ts" | "tsx" | "js" | "jsx" | "mjs" | "cjs" | "json" => {
+        _ if is_module => {
             match state.transformer.transform_module(url_path, &state.plugins) {
                 Ok(module) => {
                     // Register in HMR module graph
@@ -898,15 +1032,15 @@ async fn serve_module(
                         .body(format!("Not found: {}", path))
                         .unwrap(),
                 }
-            } else if ext.is_empty() {
+            } else if ext.is_empty() || is_page_request(url_path) {
                 // SPA fallback: no file extension means this is likely a client-side
                 // route (e.g., /about, /users/123). Return index.html so the app's
-                // router can handle it.
-                Response::builder()
-                    .status(StatusCode::OK)
-                    .header("Content-Type", "text/html")
-                    .body(index_html.to_string())
-                    .unwrap()
+                // router can handle it, or the server-rendered page with SSR.
+                let url = match query.as_deref() {
+                    Some(query) => format!("{url_path}?{query}"),
+                    None => url_path.to_string(),
+                };
+                serve_page(&state, &url, index_html).await
             } else {
                 Response::builder()
                     .status(StatusCode::NOT_FOUND)
@@ -1015,7 +1149,7 @@ fn should_ignore(path: &std::path::Path) -> bool {
         }
     }
 
-    false
+    fastnode_daemon::watch::is_editor_temp(path)
 }
 
 /// Watch files for changes.
@@ -1103,6 +1237,14 @@ async fn handle_file_change(state: &DevState, changed: Vec<String>) {
         state.transformer.invalidate(file_path);
     }
 
+    // Reload server modules before the next SSR request if any of them changed
+    if let Some(ssr) = &state.ssr {
+        let changed_paths: Vec<PathBuf> = changed.iter().map(PathBuf::from).collect();
+        if ssr.invalidate(&changed_paths) {
+            println!("  SSR modules invalidated");
+        }
+    }
+
     // Determine HMR updates
     let mut updates = Vec::new();
     let mut needs_full_reload = false;
@@ -0,0 +1,289 @@
+//! SSR renderer for `howth dev --ssr <entry>`.
+//!
+//! The SSR entry runs in the native runtime on a dedicated thread (the
+//! runtime is `!Send`, see `js_plugin`). Each page request calls the entry's
+//! `render(url, { ssrLoadModule })`; `globalThis.ssrLoadModule(path)` is also
+//! available to server code for loading root-relative modules on demand.
+//!
+//! Server modules stay cached in the runtime between requests. When a file in
+//! the entry's import graph changes, the next request reloads the entry in a
+//! fresh runtime so it sees the new code.
+
+use fastnode_core::dev::{SsrModuleGraph, SsrRender};
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+
+/// Renders pages with the project's SSR entry.
+pub struct SsrRenderer {
+    entry: PathBuf,
+    graph: Mutex<SsrModuleGraph>,
+    #[cfg(feature = "native-runtime")]
+    host: host::SsrHost,
+}
+
+impl SsrRenderer {
+    /// Load the SSR entry.
+    ///
+    /// Errors from evaluating the entry are reported by [`Self::render`], so
+    /// the server keeps running until the entry is fixed.
+    pub fn start(entry: &Path, cwd: &Path) -> Result<Self, String> {
+        if !entry.is_file() {
+            return Err(format!("SSR entry not found: {}", entry.display()));
+        }
+        #[cfg(feature = "native-runtime")]
+        {
+            Ok(Self {
+                entry: entry.to_path_buf(),
+                graph: Mutex::new(SsrModuleGraph::new(entry)),
+                host: host::SsrHost::start(entry, cwd)?,
+            })
+        }
+        #[cfg(not(feature = "native-runtime"))]
+        {
+            let _ = cwd;
+            Err(
+                "SSR needs the native runtime; rebuild howth with `--features native-runtime`"
+                    .to_string(),
+            )
+        }
+    }
+
+    /// The SSR entry path.
+    pub fn entry(&self) -> &Path {
+        &self.entry
+    }
+
+    /// Record changed files. Returns `true` if the server modules will be
+    /// reloaded before the next render.
+    pub fn invalidate(&self, changed: &[PathBuf]) -> bool {
+        self.graph
+            .lock()
+            .map(|mut graph| graph.invalidate(changed))
+            .unwrap_or(false)
+    }
+
+    /// Render `url` (path and query). Blocks until the entry's `render` settles.
+    pub fn render(&self, url: &str) -> Result<SsrRender, String> {
+        let mut graph = self
+            .graph
+            .lock()
+            .map_err(|_| "SSR module graph lock poisoned".to_string())?;
+        #[cfg(feature = "native-runtime")]
+        {
+            if graph.is_stale() {
+                self.host.reload()?;
+                graph.mark_loaded();
+            }
+            SsrRender::from_json(&self.host.render(url)?)
+        }
+        #[cfg(not(feature = "native-runtime"))]
+        {
+            let _ = (&mut graph, url);
+            Err("SSR needs the native runtime".to_string())
+        }
+    }
+}
+
+#[cfg(feature = "native-runtime")]
+mod host {
+    use std::path::{Path, PathBuf};
+    use std::sync::{mpsc, Mutex};
+
+    enum SsrRequest {
+        Render { url: String },
+        Reload,
+        Shutdown,
+    }
+
+    /// Owns the runtime thread.
+    pub struct SsrHost {
+        request_tx: mpsc::Sender<SsrRequest>,
+        response_rx: Mutex<mpsc::Receiver<Result<String, String>>>,
+        thread: Option<std::thread::JoinHandle<()>>,
+    }
+
+    impl SsrHost {
+        pub fn start(entry: &Path, cwd: &Path) -> Result<Self, String> {
+            let entry = entry.to_path_buf();
+            let cwd = cwd.to_path_buf();
+            let (request_tx, request_rx) = mpsc::channel::<SsrRequest>();
+            let (response_tx, response_rx) = mpsc::channel::<Result<String, String>>();
+
+            let thread = std::thread::Builder::new()
+                .name("howth-ssr".to_string())
+                .spawn(move || {
+                    let rt = tokio::runtime::Builder::new_current_thread()
+                        .enable_all()
+                        .build()
+                        .expect("Failed to create tokio runtime for SSR host");
+                    rt.block_on(run_ssr_thread(&entry, &cwd, request_rx, response_tx));
+                })
+                .map_err(|e| format!("Failed to start SSR thread: {e}"))?;
+
+            Ok(Self {
+                request_tx,
+                response_rx: Mutex::new(response_rx),
+                thread: Some(thread),
+            })
+        }
+
+        pub fn render(&self, url: &str) -> Result<String, String> {
+            self.call(SsrRequest::Render {
+                url: url.to_string(),
+            })
+        }
+
+        pub fn reload(&self) -> Result<(), String> {
+            self.call(SsrRequest::Reload).map(|_| ())
+        }
+
+        fn call(&self, request: SsrRequest) -> Result<String, String> {
+            let rx = self
+                .response_rx
+                .lock()
+                .map_err(|_| "SSR response channel lock poisoned".to_string())?;
+            self.request_tx
+                .send(request)
+                .map_err(|_| "SSR thread disconnected".to_string())?;
+            rx.recv()
+                .map_err(|_| "SSR thread disconnected".to_string())?
+        }
+    }
+
+    impl Drop for SsrHost {
+        fn drop(&mut self) {
+            let _ = self.request_tx.send(SsrRequest::Shutdown);
+            if let Some(thread) = self.thread.take() {
+                let _ = thread.join();
+            }
+        }
+    }
+
+    /// Bootstrap JS: `ssrLoadModule` and the render trampoline.
+    const BOOTSTRAP_JS: &str = r#"
+globalThis.__howthSsrResult = 'null';
+
+globalThis.ssrLoadModule = (path) =>
+  import(new URL(String(path).replace(/^\//, ''), globalThis.__howthSsrRoot).href);
+
+globalThis.__howthSsrRender = async (url) => {
+  try {
+    const entry = globalThis.__howthSsrEntry;
+    if (!entry || typeof entry.render !== 'function') {
+      throw new Error('SSR entry must export a render(url) function');
+    }
+    const result = await entry.render(url, { ssrLoadModule: globalThis.ssrLoadModule });
+    globalThis.__howthSsrResult = JSON.stringify(result ?? '');
+  } catch (err) {
+    globalThis.__howthSsrResult = JSON.stringify({ __error: (err && err.stack) || String(err) });
+  }
+};
+"#;
+
+    async fn run_ssr_thread(
+        entry: &Path,
+        cwd: &Path,
+        request_rx: mpsc::Receiver<SsrRequest>,
+        response_tx: mpsc::Sender<Result<String, String>>,
+    ) {
+        let mut runtime = load_entry(entry, cwd).await;
+
+        while let Ok(request) = request_rx.recv() {
+            let response = match request {
+                SsrRequest::Shutdown => break,
+                SsrRequest::Reload => {
+                    // Drop the old isolate before creating the next one
+                    runtime = Err("reloading".to_string());
+                    runtime = load_entry(entry, cwd).await;
+                    runtime
+                        .as_ref()
+                        .map(|_| String::new())
+                        .map_err(Clone::clone)
+                }
+                SsrRequest::Render { url } => match runtime.as_mut() {
+                    Ok(runtime) => render(runtime, &url).await,
+                    Err(e) => Err(e.clone()),
+                },
+            };
+            if response_tx.send(response).is_err() {
+                break;
+            }
+        }
+    }
+
+    /// Create a runtime and evaluate the SSR entry in it.
+    async fn load_entry(entry: &Path, cwd: &Path) -> Result<fastnode_runtime::Runtime, String> {
+        use fastnode_runtime::{Runtime, RuntimeOptions, VirtualModuleMap};
+        use std::cell::RefCell;
+        use std::collections::HashMap;
+        use std::rc::Rc;
+
+        let entry_url = url::Url::from_file_path(entry)
+            .map_err(|_| format!("Invalid SSR entry path: {}", entry.display()))?;
+        let root_url = url::Url::from_directory_path(cwd)
+            .map_err(|_| format!("Invalid project root: {}", cwd.display()))?;
+
+        let loader_code = format!(
+            "import * as entry from {};\nglobalThis.__howthSsrEntry = entry;\n",
+            serde_json::to_string(entry_url.as_str()).unwrap_or_default()
+        );
+        let virtual_modules: VirtualModuleMap = Rc::new(RefCell::new(HashMap::new()));
+        let loader_path: PathBuf = cwd.join("__howth_ssr_loader__.mjs");
+        virtual_modules
+            .borrow_mut()
+            .insert(loader_path.to_string_lossy().to_string(), loader_code);
+
+        let mut runtime = Runtime::new(RuntimeOptions {
+            main_module: Some(loader_path.clone()),
+            cwd: Some(cwd.to_path_buf()),
+            virtual_modules: Some(virtual_modules),
+            ..Default::default()
+        })
+        .map_err(|e| format!("Failed to create runtime: {e}"))?;
+
+        runtime
+            .execute_script(BOOTSTRAP_JS)
+            .await
+            .map_err(|e| format!("Failed to inject SSR bootstrap: {e}"))?;
+        runtime
+            .execute_script(&format!(
+                "globalThis.__howthSsrRoot = {};",
+                serde_json::to_string(root_url.as_str()).unwrap_or_default()
+            ))
+            .await
+            .map_err(|e| format!("Failed to inject SSR bootstrap: {e}"))?;
+        runtime
+            .execute_module(&loader_path)
+            .await
+            .map_err(|e| format!("Failed to load SSR entry {}: {e}", entry.display()))?;
+        runtime
+            .run_event_loop()
+            .await
+            .map_err(|e| format!("Failed to load SSR entry {}: {e}", entry.display()))?;
+
+        Ok(runtime)
+    }
+
+    /// Call the entry's `render(url)` and return its JSON-encoded result.
+    async fn render(runtime: &mut fastnode_runtime::Runtime, url: &str) -> Result<String, String> {
+        let url_json = serde_json::to_string(url).unwrap_or_default();
+        runtime
+            .execute_script(&format!("globalThis.__howthSsrRender({url_json});"))
+            .await
+            .map_err(|e| format!("SSR render failed: {e}"))?;
+        runtime
+            .run_event_loop()
+            .await
+            .map_err(|e| format!("SSR render failed: {e}"))?;
+        let result = runtime
+            .eval_to_string("globalThis.__howthSsrResult")
+            .map_err(|e| format!("SSR render failed: {e}"))?;
+
+        if let Ok(serde_json::Value::Object(obj)) = serde_json::from_str(&result) {
+            if let Some(error) = obj.get("__error").and_then(|v| v.as_str()) {
+                return Err(error.to_string());
+            }
+        }
+        Ok(result)
+    }
+}
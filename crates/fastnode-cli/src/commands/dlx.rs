@@ -0,0 +1,379 @@
+//! `howth x` / `howth dlx` command implementation.
+//!
+//! Run a package's binary without adding it to the project, like `npx`.
+//! Packages are installed into a per-version prefix under the cache directory
+//! (`<cache>/dlx/<name>@<version>`) and reused on subsequent runs.
+
+use fastnode_core::config::Channel;
+use fastnode_core::paths;
+use fastnode_core::pkg::{
+    format_pnpm_key, resolve_version, PackageCache, PackageSpec, PkgError, RegistryClient,
+};
+use fastnode_proto::Response;
+use miette::{IntoDiagnostic, Result};
+use serde::Serialize;
+use serde_json::Value;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::process::{Command, Stdio};
+
+/// Exit code for binary not found errors.
+const EXIT_NOT_FOUND: i32 = 127;
+
+/// Exit code for execution errors.
+const EXIT_ERROR: i32 = 1;
+
+/// Marker written once a prefix has been fully installed.
+const PREFIX_MARKER: &str = ".howth-dlx";
+
+/// Error codes for `howth x`.
+mod codes {
+    pub const DLX_INSTALL_FAILED: &str = "DLX_INSTALL_FAILED";
+    pub const DLX_BIN_NOT_FOUND: &str = "DLX_BIN_NOT_FOUND";
+    pub const DLX_EXEC_FAILED: &str = "DLX_EXEC_FAILED";
+}
+
+/// Result for JSON output (only emitted on error; success hands over stdio).
+#[derive(Serialize)]
+struct DlxResult {
+    ok: bool,
+    package: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    version: Option<String>,
+    error: DlxErrorInfo,
+}
+
+/// Error info for JSON output.
+#[derive(Serialize)]
+struct DlxErrorInfo {
+    code: String,
+    message: String,
+}
+
+/// A failure while preparing or running the package.
+struct DlxError {
+    code: String,
+    message: String,
+    exit_code: i32,
+    version: Option<String>,
+}
+
+impl DlxError {
+    fn new(code: &str, message: impl Into<String>) -> Self {
+        Self {
+            code: code.to_string(),
+            message: message.into(),
+            exit_code: EXIT_ERROR,
+            version: None,
+        }
+    }
+}
+
+impl From<PkgError> for DlxError {
+    fn from(e: PkgError) -> Self {
+        Self::new(e.code(), e.to_string())
+    }
+}
+
+/// An installed package prefix ready to run.
+#[derive(Debug)]
+struct DlxPrefix {
+    name: String,
+    version: String,
+    dir: PathBuf,
+}
+
+/// Run a binary from a (possibly not yet installed) registry package.
+///
+/// `command` is both the package spec and the binary name unless `package`
+/// overrides the spec, in which case `command` names the binary to run.
+pub fn run(
+    cwd: &Path,
+    command: &str,
+    args: &[String],
+    package: Option<&str>,
+    channel: Channel,
+    json: bool,
+) -> Result<()> {
+    let spec_str = package.unwrap_or(command);
+
+    let runtime = tokio::runtime::Runtime::new().into_diagnostic()?;
+    let prepared = runtime.block_on(prepare_prefix(cwd, spec_str, channel));
+
+    let result = prepared.and_then(|prefix| {
+        let bin_hint = package.map(|_| command);
+        let bin = resolve_bin(&prefix, bin_hint).map_err(|mut e| {
+            e.version = Some(prefix.version.clone());
+            e
+        })?;
+        execute(&prefix, &bin, args, cwd).map_err(|mut e| {
+            e.version = Some(prefix.version.clone());
+            e
+        })
+    });
+
+    match result {
+        Ok(code) => std::process::exit(code),
+        Err(e) => {
+            if json {
+                let result = DlxResult {
+                    ok: false,
+                    package: spec_str.to_string(),
+                    version: e.version,
+                    error: DlxErrorInfo {
+                        code: e.code,
+                        message: e.message,
+                    },
+                };
+                println!("{}", serde_json::to_string_pretty(&result).unwrap());
+            } else {
+                eprintln!("error: {}", e.message);
+            }
+            std::process::exit(e.exit_code);
+        }
+    }
+}
+
+/// Resolve the spec and make sure its prefix is installed.
+async fn prepare_prefix(
+    cwd: &Path,
+    spec_str: &str,
+    channel: Channel,
+) -> std::result::Result<DlxPrefix, DlxError> {
+    let spec = PackageSpec::parse(spec_str)?;
+    let dlx_root = paths::cache_dir(channel).join("dlx");
+
+    // An exact version that is already installed needs no registry round-trip.
+    if let Some(range) = spec.range.as_deref() {
+        let dir = dlx_root.join(format_pnpm_key(&spec.name, range));
+        if dir.join(PREFIX_MARKER).is_file() {
+            return Ok(DlxPrefix {
+                name: spec.name,
+                version: range.to_string(),
+                dir,
+            });
+        }
+    }
+
+    let cache = PackageCache::new(channel);
+    let registry = RegistryClient::from_env_with_cache(cache)?.with_npmrc(cwd);
+    let packument = registry.fetch_packument(&spec.name).await?;
+    let version = resolve_version(&packument, spec.range.as_deref())?;
+
+    let dir = dlx_root.join(format_pnpm_key(&spec.name, &version));
+    let prefix = DlxPrefix {
+        name: spec.name,
+        version,
+        dir,
+    };
+    if !prefix.dir.join(PREFIX_MARKER).is_file() {
+        install_prefix(&prefix, channel).await?;
+    }
+
+    Ok(prefix)
+}
+
+/// Install a package into a fresh prefix directory.
+///
+/// The marker is written last, so an interrupted install is redone from
+/// scratch on the next run.
+async fn install_prefix(prefix: &DlxPrefix, channel: Channel) -> std::result::Result<(), DlxError> {
+    let io_err = |e: std::io::Error| {
+        DlxError::new(
+            codes::DLX_INSTALL_FAILED,
+            format!("Failed to prepare '{}': {e}", prefix.dir.display()),
+        )
+    };
+
+    if prefix.dir.exists() {
+        fs::remove_dir_all(&prefix.dir).map_err(io_err)?;
+    }
+    fs::create_dir_all(&prefix.dir).map_err(io_err)?;
+
+    let manifest = serde_json::json!({
+        "name": "howth-dlx",
+        "private": true,
+        "dependencies": { &prefix.name: &prefix.version },
+    });
+    fs::write(
+        prefix.dir.join("package.json"),
+        serde_json::to_string_pretty(&manifest).unwrap(),
+    )
+    .map_err(io_err)?;
+
+    let response = fastnode_daemon::pkg::handle_pkg_install(
+        &prefix.dir.to_string_lossy(),
+        channel.as_str(),
+        false, // frozen
+        false, // include_dev
+        false, // include_optional
+        None,  // auto_install_peers
+    )
+    .await;
+
+    match response {
+        Response::PkgInstallResult { result } if result.ok => {
+            fs::write(prefix.dir.join(PREFIX_MARKER), &prefix.version).map_err(io_err)?;
+            Ok(())
+        }
+        Response::PkgInstallResult { result } => {
+            let detail = result
+                .errors
+                .first()
+                .map_or_else(|| "install failed".to_string(), |e| e.message.clone());
+            Err(DlxError::new(
+                codes::DLX_INSTALL_FAILED,
+                format!(
+                    "Failed to install {}@{}: {detail}",
+                    prefix.name, prefix.version
+                ),
+            ))
+        }
+        Response::Error { code, message } => Err(DlxError::new(&code, message)),
+        _ => Err(DlxError::new(
+            codes::DLX_INSTALL_FAILED,
+            "Unexpected install response",
+        )),
+    }
+}
+
+/// Find the binary to run inside an installed prefix.
+fn resolve_bin(
+    prefix: &DlxPrefix,
+    bin_hint: Option<&str>,
+) -> std::result::Result<PathBuf, DlxError> {
+    let pkg_json_path = prefix
+        .dir
+        .join("node_modules")
+        .join(&prefix.name)
+        .join("package.json");
+    let pkg_json: Value = fs::read_to_string(&pkg_json_path)
+        .ok()
+        .and_then(|s| serde_json::from_str(&s).ok())
+        .unwrap_or(Value::Null);
+
+    let bin = select_bin(&prefix.name, pkg_json.get("bin"), bin_hint).map_err(|message| {
+        let mut e = DlxError::new(codes::DLX_BIN_NOT_FOUND, message);
+        e.exit_code = EXIT_NOT_FOUND;
+        e
+    })?;
+
+    let path = prefix.dir.join("node_modules").join(".bin").join(&bin);
+    if path.exists() {
+        Ok(path)
+    } else {
+        let mut e = DlxError::new(
+            codes::DLX_BIN_NOT_FOUND,
+            format!("Binary '{bin}' was not linked for {}", prefix.name),
+        );
+        e.exit_code = EXIT_NOT_FOUND;
+        Err(e)
+    }
+}
+
+/// Pick a binary name from a package's `bin` field.
+///
+/// With an explicit hint the hint must be declared. Otherwise a string `bin`
+/// maps to the unscoped package name, and an object `bin` must either contain
+/// the unscoped package name or declare exactly one entry.
+fn select_bin(
+    name: &str,
+    bin: Option<&Value>,
+    hint: Option<&str>,
+) -> std::result::Result<String, String> {
+    let unscoped = name.rsplit('/').next().unwrap_or(name);
+
+    let declared: Vec<&str> = match bin {
+        Some(Value::String(_)) => vec![unscoped],
+        Some(Value::Object(map)) => map.keys().map(String::as_str).collect(),
+        _ => Vec::new(),
+    };
+
+    if declared.is_empty() {
+        return Err(format!("Package '{name}' does not declare any binaries"));
+    }
+
+    let wanted = hint.unwrap_or(unscoped);
+    if declared.contains(&wanted) {
+        return Ok(wanted.to_string());
+    }
+    if hint.is_none() && declared.len() == 1 {
+        return Ok(declared[0].to_string());
+    }
+
+    Err(format!(
+        "Package '{name}' has no binary '{wanted}' (available: {})",
+        declared.join(", ")
+    ))
+}
+
+/// Run the binary with the prefix's `.bin` first on PATH.
+fn execute(
+    prefix: &DlxPrefix,
+    bin: &Path,
+    args: &[String],
+    cwd: &Path,
+) -> std::result::Result<i32, DlxError> {
+    let bin_dir = prefix.dir.join("node_modules").join(".bin");
+    let mut dirs = vec![bin_dir];
+    if let Some(path) = std::env::var_os("PATH") {
+        dirs.extend(std::env::split_paths(&path));
+    }
+    let search_path = std::env::join_paths(dirs).unwrap_or_default();
+
+    let status = Command::new(bin)
+        .args(args)
+        .current_dir(cwd)
+        .stdin(Stdio::inherit())
+        .stdout(Stdio::inherit())
+        .stderr(Stdio::inherit())
+        .env("PATH", search_path)
+        .status()
+        .map_err(|e| {
+            DlxError::new(
+                codes::DLX_EXEC_FAILED,
+                format!("Failed to execute '{}': {e}", bin.display()),
+            )
+        })?;
+
+    Ok(status.code().unwrap_or(EXIT_ERROR))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn test_select_bin_string_form() {
+        let bin = json!("./cli.js");
+        assert_eq!(select_bin("cowsay", Some(&bin), None).unwrap(), "cowsay");
+        assert_eq!(select_bin("@scope/tool", Some(&bin), None).unwrap(), "tool");
+    }
+
+    #[test]
+    fn test_select_bin_object_form() {
+        let bin = json!({ "tsc": "./bin/tsc", "tsserver": "./bin/tsserver" });
+        assert!(select_bin("typescript", Some(&bin), None).is_err());
+        assert_eq!(
+            select_bin("typescript", Some(&bin), Some("tsc")).unwrap(),
+            "tsc"
+        );
+
+        let single = json!({ "create-vite": "index.js" });
+        assert_eq!(
+            select_bin("create-vite", Some(&single), None).unwrap(),
+            "create-vite"
+        );
+        let renamed = json!({ "vc": "index.js" });
+        assert_eq!(select_bin("vercel", Some(&renamed), None).unwrap(), "vc");
+    }
+
+    #[test]
+    fn test_select_bin_missing() {
+        assert!(select_bin("left-pad", None, None).is_err());
+        let bin = json!({ "a": "a.js" });
+        let err = select_bin("pkg", Some(&bin), Some("b")).unwrap_err();
+        assert!(err.contains("available: a"));
+    }
+}
@@ -1,15 +1,24 @@
+use super::ping::ping_daemon;
 use fastnode_core::config::Channel;
-use fastnode_core::doctor::{DoctorReport, Severity};
-use miette::{IntoDiagnostic, Result};
-use std::io::{self, Write};
-use std::path::Path;
+use fastnode_core::doctor::{
+    collect_bundle, format_bytes, write_bundle, DaemonInfo, DoctorReport, Severity,
+};
+use fastnode_core::paths;
+use fastnode_proto::Response;
+use miette::{miette, IntoDiagnostic, Result};
+use std::io::{self, BufRead, IsTerminal, Write};
+use std::path::{Path, PathBuf};
+use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
+
+/// How long to wait for the daemon to answer a ping.
+const DAEMON_TIMEOUT: Duration = Duration::from_secs(2);
 
 /// Run the doctor command.
 ///
 /// When `json` is true, outputs a single JSON object to stdout.
 /// Otherwise, outputs human-readable formatted text to stdout.
 pub fn run(cwd: &Path, channel: Channel, json: bool) -> Result<()> {
-    let report = DoctorReport::collect(cwd, channel);
+    let report = DoctorReport::collect(cwd, channel).with_daemon(probe_daemon(channel));
 
     if json {
         print_json(&report)?;
@@ -20,6 +29,94 @@ pub fn run(cwd: &Path, channel: Channel, json: bool) -> Result<()> {
     Ok(())
 }
 
+/// Write a redacted diagnostics bundle (`howth doctor --bundle`).
+///
+/// Lists what will be included and asks for consent on the terminal unless
+/// `yes` is set. Non-interactive sessions must pass `yes`.
+pub fn run_bundle(cwd: &Path, channel: Channel, output: Option<&Path>, yes: bool) -> Result<()> {
+    let report = DoctorReport::collect(cwd, channel).with_daemon(probe_daemon(channel));
+    let files = collect_bundle(&report, cwd, channel);
+    let output = output.map_or_else(|| cwd.join(default_bundle_name()), Path::to_path_buf);
+
+    eprintln!("The diagnostics bundle will contain:");
+    for file in &files {
+        eprintln!(
+            "  {:<26} {:>9}  {}",
+            file.name,
+            format_bytes(file.contents.len() as u64),
+            file.description
+        );
+    }
+    eprintln!("Secrets, credentials and your home directory are redacted.");
+
+    if !yes && !confirm(&output)? {
+        eprintln!("Aborted; no bundle written.");
+        return Ok(());
+    }
+
+    write_bundle(&files, &output).into_diagnostic()?;
+    eprintln!("Wrote {}", output.display());
+    eprintln!("Review it before attaching it to a bug report.");
+    Ok(())
+}
+
+fn default_bundle_name() -> PathBuf {
+    let secs = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map_or(0, |d| d.as_secs());
+    PathBuf::from(format!("howth-diagnostics-{secs}.tar.gz"))
+}
+
+/// Ask before writing the bundle.
+fn confirm(output: &Path) -> Result<bool> {
+    if !io::stdin().is_terminal() || !io::stderr().is_terminal() {
+        return Err(miette!(
+            "Not writing a diagnostics bundle without confirmation; pass --yes to skip the prompt"
+        ));
+    }
+    eprint!("Write {}? [y/N] ", output.display());
+    let _ = io::stderr().flush();
+
+    let mut input = String::new();
+    io::stdin().lock().read_line(&mut input).into_diagnostic()?;
+    Ok(matches!(input.trim().to_lowercase().as_str(), "y" | "yes"))
+}
+
+/// Ping the daemon and record whether it answered, and with which version.
+#[allow(clippy::cast_possible_truncation)]
+fn probe_daemon(channel: Channel) -> DaemonInfo {
+    let endpoint = paths::ipc_endpoint(channel);
+    let start = Instant::now();
+    let result = tokio::runtime::Builder::new_current_thread()
+        .enable_all()
+        .build()
+        .map_err(|e| e.to_string())
+        .and_then(|runtime| {
+            runtime.block_on(async {
+                tokio::time::timeout(DAEMON_TIMEOUT, ping_daemon(&endpoint, 0))
+                    .await
+                    .map_err(|_| format!("no response within {}s", DAEMON_TIMEOUT.as_secs()))?
+                    .map_err(|e| e.to_string())
+            })
+        });
+
+    let (running, version, error) = match result {
+        Ok((Response::Pong { .. }, version)) => (true, Some(version), None),
+        Ok((Response::Error { code, message }, version)) => {
+            (true, Some(version), Some(format!("{code}: {message}")))
+        }
+        Ok((_, version)) => (true, Some(version), Some("unexpected response".to_string())),
+        Err(e) => (false, None, Some(e)),
+    };
+    DaemonInfo {
+        endpoint,
+        running,
+        version,
+        latency_ms: running.then(|| start.elapsed().as_millis() as u64),
+        error,
+    }
+}
+
 fn print_json(report: &DoctorReport) -> Result<()> {
     let json = serde_json::to_string_pretty(report).into_diagnostic()?;
     println!("{json}");
@@ -177,6 +274,71 @@ fn print_human(report: &DoctorReport) -> Result<()> {
 
     w(&mut out, "\n")?;
 
+    // Daemon
+    if let Some(daemon) = &report.daemon {
+        w(&mut out, "\x1b[1m## Daemon\x1b[0m\n")?;
+        w(
+            &mut out,
+            &format!("  Endpoint:       {}\n", daemon.endpoint),
+        )?;
+        let status = if daemon.running {
+            format!(
+                "\x1b[32mrunning\x1b[0m ({}ms)",
+                daemon.latency_ms.unwrap_or_default()
+            )
+        } else {
+            "\x1b[33mnot running\x1b[0m".to_string()
+        };
+        w(&mut out, &format!("  Status:         {status}\n"))?;
+        if let Some(version) = &daemon.version {
+            w(&mut out, &format!("  Version:        {version}\n"))?;
+        }
+        w(&mut out, "\n")?;
+    }
+
+    // Cache usage
+    w(&mut out, "\x1b[1m## Cache Usage\x1b[0m\n")?;
+    for usage in &report.cache_usage {
+        let size = if usage.exists {
+            format!("{} in {} files", format_bytes(usage.bytes), usage.files)
+        } else {
+            "(none)".to_string()
+        };
+        w(
+            &mut out,
+            &format!("  {:<16}{size}\n", format!("{}:", usage.channel)),
+        )?;
+    }
+    w(&mut out, "\n")?;
+
+    // Watcher
+    w(&mut out, "\x1b[1m## Watcher\x1b[0m\n")?;
+    w(
+        &mut out,
+        &format!("  Backend:        {}\n", report.watcher.backend),
+    )?;
+    if let Some(watches) = report.watcher.inotify_max_user_watches {
+        w(&mut out, &format!("  Max watches:    {watches}\n"))?;
+    }
+    if let Some(instances) = report.watcher.inotify_max_user_instances {
+        w(&mut out, &format!("  Max instances:  {instances}\n"))?;
+    }
+    w(&mut out, "\n")?;
+
+    // Tools
+    w(&mut out, "\x1b[1m## Tools\x1b[0m\n")?;
+    for tool in &report.tools {
+        let found = match (&tool.version, &tool.path) {
+            (Some(version), Some(path)) => format!("{version} ({})", path.display()),
+            _ => "\x1b[33mnot found\x1b[0m".to_string(),
+        };
+        w(
+            &mut out,
+            &format!("  {:<16}{found}\n", format!("{}:", tool.name)),
+        )?;
+    }
+    w(&mut out, "\n")?;
+
     // Warnings
     if report.warnings.is_empty() {
         w(&mut out, "\x1b[1m## Warnings\x1b[0m\n")?;
@@ -198,6 +360,9 @@ fn print_human(report: &DoctorReport) -> Result<()> {
                 &mut out,
                 &format!("  [{prefix}] {}: {}\n", warning.code, warning.message),
             )?;
+            if let Some(fix) = &warning.fix {
+                w(&mut out, &format!("         fix: {fix}\n"))?;
+            }
         }
     }
 
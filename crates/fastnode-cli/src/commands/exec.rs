@@ -1,11 +1,17 @@
 //! `howth exec` command implementation.
 //!
-//! Execute binaries from node_modules/.bin or PATH.
+//! Execute binaries from node_modules/.bin or PATH, optionally installing the
+//! providing package as a devDependency when the binary is missing.
 
+use super::pkg::{self, PkgAction};
+use fastnode_core::config::Channel;
+use fastnode_proto::{ExecResult, RunErrorInfo, EXEC_RESULT_SCHEMA_VERSION};
+use fastnode_util::process;
 use miette::Result;
-use serde::Serialize;
-use std::path::Path;
+use std::io::{IsTerminal, Write};
+use std::path::{Path, PathBuf};
 use std::process::{Command, Stdio};
+use std::time::Instant;
 
 /// Exit code for binary not found errors.
 const EXIT_NOT_FOUND: i32 = 127;
@@ -13,22 +19,15 @@ const EXIT_NOT_FOUND: i32 = 127;
 /// Exit code for execution errors.
 const EXIT_ERROR: i32 = 1;
 
-/// Result for JSON output.
-#[derive(Serialize)]
-struct ExecResult {
-    ok: bool,
-    binary: String,
-    resolved_path: Option<String>,
-    #[serde(skip_serializing_if = "Option::is_none")]
-    error: Option<ExecError>,
-}
-
-/// Error info for JSON output.
-#[derive(Serialize)]
-struct ExecError {
-    code: String,
-    message: String,
-}
+/// Binaries whose providing package has a different name.
+const BINARY_PACKAGES: &[(&str, &str)] = &[
+    ("tsc", "typescript"),
+    ("tsserver", "typescript"),
+    ("ng", "@angular/cli"),
+    ("nest", "@nestjs/cli"),
+    ("vue-cli-service", "@vue/cli-service"),
+    ("biome", "@biomejs/biome"),
+];
 
 /// Run a binary from node_modules/.bin or PATH.
 ///
@@ -36,37 +35,117 @@ struct ExecError {
 /// 1. `./node_modules/.bin/<binary>` (local project)
 /// 2. Walk up directories looking for `node_modules/.bin/<binary>`
 /// 3. System PATH
-pub fn run(cwd: &Path, binary: &str, args: &[String], json: bool) -> Result<()> {
+///
+/// If the binary is not found inside a project, offers to install the
+/// providing package into devDependencies (or does so directly with `yes`)
+/// and retries.
+///
+/// With `json`, an [`ExecResult`] is printed after the binary exits. `capture`
+/// collects the binary's stdout/stderr into the result instead of streaming it.
+pub fn run(
+    cwd: &Path,
+    binary: &str,
+    args: &[String],
+    yes: bool,
+    capture: bool,
+    channel: Channel,
+    json: bool,
+) -> Result<()> {
     // Try to find the binary
-    let (resolved_path, search_path) = resolve_binary(cwd, binary);
+    let (mut resolved_path, mut search_path) = resolve_binary(cwd, binary);
 
-    if let Some(path) = &resolved_path {
-        if !json {
-            // Don't print anything in non-JSON mode, just execute
+    if resolved_path.is_none() {
+        if let Some(project_dir) = find_package_dir(cwd) {
+            let package = providing_package(binary);
+            if confirm_install(binary, package, yes, json) {
+                pkg::run(
+                    PkgAction::Add {
+                        specs: vec![package.to_string()],
+                        cwd: project_dir,
+                        save_dev: true,
+                        global: false,
+                        auto_install_peers: None,
+                    },
+                    channel,
+                    json,
+                )?;
+                (resolved_path, search_path) = resolve_binary(cwd, binary);
+            }
         }
+    }
+
+    let mut result = ExecResult {
+        schema_version: EXEC_RESULT_SCHEMA_VERSION,
+        ok: false,
+        binary: binary.to_string(),
+        resolved_path: resolved_path.clone(),
+        args: args.to_vec(),
+        cwd: cwd.to_string_lossy().into_owned(),
+        exit_code: None,
+        duration_ms: 0.0,
+        stdout: None,
+        stderr: None,
+        error: None,
+    };
 
+    if let Some(path) = &resolved_path {
         // Execute the binary
-        execute_binary(path, args, cwd, &search_path, json)
+        execute_binary(path, &search_path, &mut result, capture && json, json)
     } else {
         if json {
-            let result = ExecResult {
-                ok: false,
-                binary: binary.to_string(),
-                resolved_path: None,
-                error: Some(ExecError {
-                    code: "BINARY_NOT_FOUND".to_string(),
-                    message: format!("Binary '{}' not found in node_modules/.bin or PATH", binary),
-                }),
-            };
+            result.error = Some(RunErrorInfo {
+                code: "BINARY_NOT_FOUND".to_string(),
+                message: format!("Binary '{}' not found in node_modules/.bin or PATH", binary),
+            });
             println!("{}", serde_json::to_string_pretty(&result).unwrap());
         } else {
             eprintln!("error: binary '{}' not found", binary);
-            eprintln!("hint: install with `howth pkg add {}`", binary);
+            eprintln!(
+                "hint: install with `howth pkg add -D {}` or rerun with --yes",
+                providing_package(binary)
+            );
         }
         std::process::exit(EXIT_NOT_FOUND);
     }
 }
 
+/// Name of the npm package that provides `binary`.
+fn providing_package(binary: &str) -> &str {
+    BINARY_PACKAGES
+        .iter()
+        .find(|(bin, _)| *bin == binary)
+        .map_or(binary, |(_, package)| package)
+}
+
+/// Find the nearest directory (from `cwd` up) containing a package.json.
+fn find_package_dir(cwd: &Path) -> Option<PathBuf> {
+    cwd.ancestors()
+        .find(|dir| dir.join("package.json").is_file())
+        .map(Path::to_path_buf)
+}
+
+/// Decide whether to install the package providing a missing binary.
+///
+/// With `yes` this always installs. Otherwise the user is asked on stderr,
+/// which only happens for interactive, non-JSON sessions.
+fn confirm_install(binary: &str, package: &str, yes: bool, json: bool) -> bool {
+    if yes {
+        return true;
+    }
+    if json || !std::io::stdin().is_terminal() || !std::io::stderr().is_terminal() {
+        return false;
+    }
+
+    eprint!("'{binary}' is not installed. Add {package} to devDependencies? [y/N] ");
+    let _ = std::io::stderr().flush();
+
+    let mut input = String::new();
+    if std::io::stdin().read_line(&mut input).is_err() {
+        return false;
+    }
+    matches!(input.trim().to_lowercase().as_str(), "y" | "yes")
+}
+
 /// Resolve a binary by searching node_modules/.bin directories and PATH.
 /// Returns (resolved_path, search_path_with_bins).
 fn resolve_binary(cwd: &Path, binary: &str) -> (Option<String>, String) {
@@ -123,39 +202,50 @@ fn resolve_binary(cwd: &Path, binary: &str) -> (Option<String>, String) {
     (None, search_path)
 }
 
-/// Execute a binary with the given arguments.
+/// Execute a binary with the arguments recorded in `result`, then exit with its code.
 fn execute_binary(
     binary_path: &str,
-    args: &[String],
-    cwd: &Path,
     search_path: &str,
+    result: &mut ExecResult,
+    capture: bool,
     json: bool,
 ) -> Result<()> {
     let mut cmd = Command::new(binary_path);
 
-    cmd.args(args)
-        .current_dir(cwd)
+    cmd.args(&result.args)
+        .current_dir(&result.cwd)
         .stdin(Stdio::inherit())
-        .stdout(Stdio::inherit())
-        .stderr(Stdio::inherit())
         .env("PATH", search_path);
 
-    match cmd.status() {
+    let start = Instant::now();
+    let status = if capture {
+        process::output(&mut cmd).map(|output| {
+            result.stdout = Some(String::from_utf8_lossy(&output.stdout).into_owned());
+            result.stderr = Some(String::from_utf8_lossy(&output.stderr).into_owned());
+            output.status
+        })
+    } else {
+        cmd.stdout(Stdio::inherit()).stderr(Stdio::inherit());
+        process::status(&mut cmd)
+    };
+    result.duration_ms = start.elapsed().as_secs_f64() * 1000.0;
+
+    match status {
         Ok(status) => {
-            // Exit with the same code as the child process
-            std::process::exit(status.code().unwrap_or(EXIT_ERROR));
+            result.exit_code = Some(process::exit_code(status));
+            result.ok = status.success();
+            if json {
+                println!("{}", serde_json::to_string_pretty(&result).unwrap());
+            }
+            // Exit with the same code (or signal) as the child process
+            process::exit_with(status);
         }
         Err(e) => {
             if json {
-                let result = ExecResult {
-                    ok: false,
-                    binary: binary_path.to_string(),
-                    resolved_path: Some(binary_path.to_string()),
-                    error: Some(ExecError {
-                        code: "EXEC_FAILED".to_string(),
-                        message: format!("Failed to execute '{}': {}", binary_path, e),
-                    }),
-                };
+                result.error = Some(RunErrorInfo {
+                    code: "EXEC_FAILED".to_string(),
+                    message: format!("Failed to execute '{}': {}", binary_path, e),
+                });
                 println!("{}", serde_json::to_string_pretty(&result).unwrap());
             } else {
                 eprintln!("error: failed to execute '{}': {}", binary_path, e);
@@ -164,3 +254,25 @@ fn execute_binary(
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_providing_package() {
+        assert_eq!(providing_package("jest"), "jest");
+        assert_eq!(providing_package("tsc"), "typescript");
+        assert_eq!(providing_package("ng"), "@angular/cli");
+    }
+
+    #[test]
+    fn test_find_package_dir() {
+        let dir = tempfile::tempdir().unwrap();
+        let nested = dir.path().join("src/lib");
+        std::fs::create_dir_all(&nested).unwrap();
+        std::fs::write(dir.path().join("package.json"), "{}").unwrap();
+
+        assert_eq!(find_package_dir(&nested).unwrap(), dir.path());
+    }
+}
@@ -0,0 +1,102 @@
+//! `howth fmt` command implementation.
+//!
+//! Formats JS/TS sources in place. Directories are expanded to the files git
+//! would track, so `.gitignore`d output isn't touched. `--check` writes
+//! nothing and exits 1 when a file isn't formatted, for CI.
+
+use fastnode_core::fmt::{collect_files, format_file, FmtError};
+use serde::Serialize;
+use std::path::{Path, PathBuf};
+
+/// Fmt result for JSON output.
+#[derive(Serialize)]
+struct FmtJsonResult {
+    ok: bool,
+    check: bool,
+    /// Number of files looked at.
+    files: usize,
+    /// Files that were reformatted, or would be with `--check`.
+    changed: Vec<String>,
+    errors: Vec<FmtErrorJson>,
+}
+
+#[derive(Serialize)]
+struct FmtErrorJson {
+    code: String,
+    message: String,
+}
+
+/// Run `howth fmt`.
+///
+/// Exits 1 if a file can't be formatted, or with `check` if a file isn't
+/// formatted; exits 2 if a path doesn't exist.
+pub fn run(cwd: &Path, paths: &[PathBuf], check: bool, json: bool) {
+    let files = match collect_files(cwd, paths) {
+        Ok(files) => files,
+        Err(e) => {
+            if json {
+                let result = FmtJsonResult {
+                    ok: false,
+                    check,
+                    files: 0,
+                    changed: Vec::new(),
+                    errors: vec![error_json(&e)],
+                };
+                println!("{}", serde_json::to_string_pretty(&result).unwrap());
+            } else {
+                eprintln!("error: [{}] {e}", e.code());
+            }
+            std::process::exit(2);
+        }
+    };
+
+    let mut changed = Vec::new();
+    let mut errors = Vec::new();
+    for file in &files {
+        match format_file(file, !check) {
+            Ok(true) => changed.push(display_path(cwd, file)),
+            Ok(false) => {}
+            Err(e) => errors.push(e),
+        }
+    }
+    let ok = errors.is_empty() && (!check || changed.is_empty());
+
+    if json {
+        let result = FmtJsonResult {
+            ok,
+            check,
+            files: files.len(),
+            changed,
+            errors: errors.iter().map(error_json).collect(),
+        };
+        println!("{}", serde_json::to_string_pretty(&result).unwrap());
+    } else {
+        for path in &changed {
+            println!("{path}");
+        }
+        for e in &errors {
+            eprintln!("error: [{}] {e}", e.code());
+        }
+        let summary = match (check, changed.len()) {
+            (true, 0) => format!("All {} files are formatted", files.len()),
+            (true, n) => format!("{n} of {} files need formatting", files.len()),
+            (false, n) => format!("Formatted {n} of {} files", files.len()),
+        };
+        eprintln!("{summary}");
+    }
+    if !ok {
+        std::process::exit(1);
+    }
+}
+
+fn error_json(e: &FmtError) -> FmtErrorJson {
+    FmtErrorJson {
+        code: e.code().to_string(),
+        message: e.to_string(),
+    }
+}
+
+/// `path` relative to `cwd` when it's inside it.
+fn display_path(cwd: &Path, path: &Path) -> String {
+    path.strip_prefix(cwd).unwrap_or(path).display().to_string()
+}
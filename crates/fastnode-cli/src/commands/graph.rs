@@ -0,0 +1,157 @@
+//! `howth graph` command implementation.
+//!
+//! Prints the bundler's module graph for one or more entries without
+//! emitting a bundle. `--json` output is a stable, versioned
+//! [`GraphReport`] for editor extensions and custom tooling.
+
+use fastnode_core::bundler::{
+    AliasPlugin, BundleError, BundleOptions, Bundler, GraphReport, ImportKind, JsonPlugin,
+    Platform, Plugin,
+};
+use miette::Result;
+use serde::Serialize;
+use std::path::{Path, PathBuf};
+
+/// Graph command action.
+#[derive(Debug, Clone)]
+pub struct GraphAction {
+    /// Entry point files.
+    pub entries: Vec<PathBuf>,
+    /// Working directory.
+    pub cwd: PathBuf,
+    /// External packages (reported as externals, not followed).
+    pub external: Vec<String>,
+    /// Import aliases (e.g., @=./src).
+    pub alias: Vec<String>,
+    /// Platform used for resolution.
+    pub platform: Platform,
+}
+
+/// JSON output for the graph command.
+#[derive(Serialize)]
+struct GraphResultJson {
+    ok: bool,
+    #[serde(flatten)]
+    report: Option<GraphReport>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    error: Option<GraphErrorJson>,
+}
+
+#[derive(Serialize)]
+struct GraphErrorJson {
+    code: String,
+    message: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    path: Option<String>,
+}
+
+/// Run the graph command.
+pub fn run(action: GraphAction, json: bool) -> Result<()> {
+    match build_report(&action) {
+        Ok(report) => {
+            if json {
+                let result = GraphResultJson {
+                    ok: true,
+                    report: Some(report),
+                    error: None,
+                };
+                println!("{}", serde_json::to_string_pretty(&result).unwrap());
+            } else {
+                print!("{}", render_tree(&report));
+            }
+            Ok(())
+        }
+        Err(e) => {
+            if json {
+                let result = GraphResultJson {
+                    ok: false,
+                    report: None,
+                    error: Some(GraphErrorJson {
+                        code: e.code.to_string(),
+                        message: e.message.clone(),
+                        path: e.path.clone(),
+                    }),
+                };
+                println!("{}", serde_json::to_string_pretty(&result).unwrap());
+            } else {
+                eprintln!("error: {e}");
+                if let Some(path) = &e.path {
+                    eprintln!("  at {path}");
+                }
+            }
+            std::process::exit(1);
+        }
+    }
+}
+
+/// Build the module graph and its report.
+fn build_report(action: &GraphAction) -> Result<GraphReport, BundleError> {
+    let mut plugins: Vec<Box<dyn Plugin>> = vec![Box::new(JsonPlugin)];
+    if !action.alias.is_empty() {
+        let mut alias_plugin = AliasPlugin::new();
+        for a in &action.alias {
+            if let Some((from, to)) = a.split_once('=') {
+                alias_plugin = alias_plugin.alias(from.trim(), to.trim());
+            }
+        }
+        plugins.push(Box::new(alias_plugin));
+    }
+    let bundler = Bundler::with_cwd(&action.cwd).plugins(plugins);
+
+    let options = BundleOptions {
+        external: action.external.clone(),
+        platform: action.platform,
+        ..Default::default()
+    };
+    let entries: Vec<&Path> = action.entries.iter().map(PathBuf::as_path).collect();
+    let (graph, entry_ids) = bundler.module_graph(&entries, &action.cwd, &options)?;
+    Ok(GraphReport::new(&graph, &entry_ids, &action.cwd))
+}
+
+/// Render the report as one block per module listing its imports.
+fn render_tree(report: &GraphReport) -> String {
+    use std::fmt::Write as _;
+
+    let mut out = String::new();
+    for node in &report.nodes {
+        let entry = if node.entry { " [entry]" } else { "" };
+        let _ = writeln!(out, "{} ({}){entry}", node.path, format_size(node.size));
+        for edge in report.edges.iter().filter(|e| e.from == node.id) {
+            let target = &report.nodes[edge.to].path;
+            let _ = writeln!(out, "  {} {target}", arrow(edge.kind));
+        }
+        for external in report.externals.iter().filter(|e| e.from == node.id) {
+            let _ = writeln!(
+                out,
+                "  {} {} (external)",
+                arrow(external.kind),
+                external.specifier
+            );
+        }
+    }
+    let _ = writeln!(
+        out,
+        "\n{} modules, {} imports, {} external",
+        report.nodes.len(),
+        report.edges.len(),
+        report.externals.len()
+    );
+    out
+}
+
+fn arrow(kind: ImportKind) -> &'static str {
+    match kind {
+        ImportKind::Static => "->",
+        ImportKind::Dynamic => "~>",
+        ImportKind::SideEffect => "=>",
+    }
+}
+
+#[allow(clippy::cast_precision_loss)]
+fn format_size(bytes: u64) -> String {
+    if bytes < 1024 {
+        format!("{bytes} B")
+    } else {
+        format!("{:.1} KB", bytes as f64 / 1024.0)
+    }
+}
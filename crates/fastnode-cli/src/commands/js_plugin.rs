@@ -97,6 +97,8 @@ pub struct JsPluginDef {
     pub has_build_start: bool,
     pub has_build_end: bool,
     pub has_handle_hot_update: bool,
+    /// File extensions the plugin compiles to JavaScript (`extensions: ['vue']`).
+    pub extensions: Vec<String>,
 }
 
 // ============================================================================
@@ -224,6 +226,7 @@ globalThis.__howthExtractPlugins = () => {
     has_buildStart: typeof p.buildStart === 'function',
     has_buildEnd: typeof p.buildEnd === 'function',
     has_handleHotUpdate: typeof p.handleHotUpdate === 'function',
+    extensions: Array.isArray(p.extensions) ? p.extensions.filter((e) => typeof e === 'string') : [],
   })));
 };
 
@@ -386,6 +389,14 @@ fn parse_plugin_metadata(json: &str) -> Result<Vec<JsPluginDef>, String> {
             has_build_start: v["has_buildStart"].as_bool().unwrap_or(false),
             has_build_end: v["has_buildEnd"].as_bool().unwrap_or(false),
             has_handle_hot_update: v["has_handleHotUpdate"].as_bool().unwrap_or(false),
+            extensions: v["extensions"]
+                .as_array()
+                .map(|exts| {
+                    exts.iter()
+                        .filter_map(|e| e.as_str().map(|s| s.to_string()))
+                        .collect()
+                })
+                .unwrap_or_default(),
         })
         .collect())
 }
@@ -562,6 +573,10 @@ fn parse_hook_response(json_str: &str, hook: &HookCall) -> PluginResponse {
                         .get("map")
                         .and_then(|v| v.as_str())
                         .map(|s| s.to_string()),
+                    css: value
+                        .get("css")
+                        .and_then(|v| v.as_str())
+                        .map(|s| s.to_string()),
                 }))
             } else {
                 PluginResponse::Transform(None)
@@ -608,6 +623,7 @@ pub struct JsPlugin {
     has_build_start: bool,
     has_build_end: bool,
     has_handle_hot_update: bool,
+    extensions: Vec<String>,
 }
 
 impl JsPlugin {
@@ -630,6 +646,7 @@ impl JsPlugin {
             has_build_start: def.has_build_start,
             has_build_end: def.has_build_end,
             has_handle_hot_update: def.has_handle_hot_update,
+            extensions: def.extensions.clone(),
         }
     }
 }
@@ -653,6 +670,10 @@ impl Plugin for JsPlugin {
         self.enforce
     }
 
+    fn extensions(&self) -> Vec<String> {
+        self.extensions.clone()
+    }
+
     fn build_start(&self, _ctx: &PluginContext) -> HookResult<()> {
         if !self.has_build_start {
             return Ok(());
@@ -852,6 +873,14 @@ mod tests {
         assert!(!defs[0].has_build_start);
         assert!(!defs[0].has_build_end);
         assert!(!defs[0].has_handle_hot_update);
+        assert!(defs[0].extensions.is_empty());
+    }
+
+    #[test]
+    fn test_parse_plugin_metadata_extensions() {
+        let json = r#"[{"name": "vue", "has_transform": true, "extensions": ["vue", 1]}]"#;
+        let defs = parse_plugin_metadata(json).unwrap();
+        assert_eq!(defs[0].extensions, ["vue"]);
     }
 
     #[test]
@@ -939,6 +968,22 @@ mod tests {
         }
     }
 
+    #[test]
+    fn test_parse_hook_response_transform_with_css() {
+        let hook = HookCall::Transform {
+            code: "<template></template>".to_string(),
+            id: "App.vue".to_string(),
+        };
+        let json = r#"{"code": "export default {};", "css": ".a { color: red; }"}"#;
+        match parse_hook_response(json, &hook) {
+            PluginResponse::Transform(Some(result)) => {
+                assert_eq!(result.code, "export default {};");
+                assert_eq!(result.css.as_deref(), Some(".a { color: red; }"));
+            }
+            other => panic!("Expected Transform(Some), got {:?}", other),
+        }
+    }
+
     #[test]
     fn test_parse_hook_response_resolve_id_with_id() {
         let hook = HookCall::ResolveId {
@@ -1082,6 +1127,7 @@ mod tests {
             has_build_start: false,
             has_build_end: false,
             has_handle_hot_update: false,
+            extensions: Vec::new(),
         };
 
         // We can't construct a real host without V8, but we can test enforce parsing
@@ -1106,6 +1152,7 @@ mod tests {
             has_build_start: false,
             has_build_end: false,
             has_handle_hot_update: false,
+            extensions: Vec::new(),
         };
         let enforce = match def.enforce.as_deref() {
             Some("pre") => PluginEnforce::Pre,
@@ -1127,6 +1174,7 @@ mod tests {
             has_build_start: false,
             has_build_end: false,
             has_handle_hot_update: false,
+            extensions: Vec::new(),
         };
         let enforce = match def.enforce.as_deref() {
             Some("pre") => PluginEnforce::Pre,
@@ -1148,6 +1196,7 @@ mod tests {
             has_build_start: false,
             has_build_end: false,
             has_handle_hot_update: false,
+            extensions: Vec::new(),
         };
         let enforce = match def.enforce.as_deref() {
             Some("pre") => PluginEnforce::Pre,
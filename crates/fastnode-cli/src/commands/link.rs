@@ -10,6 +10,7 @@
 
 use fastnode_core::config::Channel;
 use fastnode_core::paths::data_dir;
+use fastnode_util::fs as fs_util;
 use miette::Result;
 use serde_json::Value;
 use std::path::Path;
@@ -89,22 +90,12 @@ fn register_package(cwd: &Path, channel: Channel, json: bool) -> Result<()> {
 
     // Remove existing link if present
     if link_path.exists() || link_path.symlink_metadata().is_ok() {
-        std::fs::remove_file(&link_path)
-            .or_else(|_| std::fs::remove_dir(&link_path))
+        fs_util::remove_within(&links, &link_path)
             .map_err(|e| miette::miette!("Failed to remove existing link: {}", e))?;
     }
 
-    #[cfg(unix)]
-    {
-        std::os::unix::fs::symlink(cwd, &link_path)
-            .map_err(|e| miette::miette!("Failed to create symlink: {}", e))?;
-    }
-
-    #[cfg(windows)]
-    {
-        std::os::windows::fs::symlink_dir(cwd, &link_path)
-            .map_err(|e| miette::miette!("Failed to create symlink: {}", e))?;
-    }
+    fs_util::symlink_dir(cwd, &link_path)
+        .map_err(|e| miette::miette!("Failed to create symlink: {}", e))?;
 
     if json {
         println!(
@@ -247,23 +238,13 @@ fn link_package(cwd: &Path, pkg: &str, save: bool, channel: Channel, json: bool)
 
     // Remove existing if present
     if link_dest.exists() || link_dest.symlink_metadata().is_ok() {
-        std::fs::remove_file(&link_dest)
-            .or_else(|_| std::fs::remove_dir_all(&link_dest))
+        fs_util::remove_within(&node_modules, &link_dest)
             .map_err(|e| miette::miette!("Failed to remove existing: {}", e))?;
     }
 
     // Create symlink
-    #[cfg(unix)]
-    {
-        std::os::unix::fs::symlink(&actual_path, &link_dest)
-            .map_err(|e| miette::miette!("Failed to create symlink: {}", e))?;
-    }
-
-    #[cfg(windows)]
-    {
-        std::os::windows::fs::symlink_dir(&actual_path, &link_dest)
-            .map_err(|e| miette::miette!("Failed to create symlink: {}", e))?;
-    }
+    fs_util::symlink_dir(&actual_path, &link_dest)
+        .map_err(|e| miette::miette!("Failed to create symlink: {}", e))?;
 
     // Optionally add to package.json
     if save {
@@ -321,8 +302,7 @@ fn unlink_package(cwd: &Path, pkg: &str, json: bool) -> Result<()> {
         std::process::exit(1);
     }
 
-    std::fs::remove_file(&link_dest)
-        .or_else(|_| std::fs::remove_dir_all(&link_dest))
+    fs_util::remove_within(&node_modules, &link_dest)
         .map_err(|e| miette::miette!("Failed to remove link: {}", e))?;
 
     if json {
@@ -0,0 +1,243 @@
+//! `howth lint` command implementation.
+//!
+//! Lints JS/TS sources with the rules in `fastnode_core::lint`. Output is
+//! a stylish per-file listing, JSON, or GitHub Actions annotations
+//! (`--format github`) so problems show inline on pull requests.
+
+use fastnode_core::lint::{collect_files, lint_file, FileReport, LintConfig, LintError, Severity};
+use serde::Serialize;
+use std::path::{Path, PathBuf};
+
+/// Lint result for JSON output.
+#[derive(Serialize)]
+struct LintJsonResult {
+    ok: bool,
+    /// Number of files looked at.
+    files: usize,
+    error_count: usize,
+    warning_count: usize,
+    /// Fixes applied with `--fix`.
+    fixed: usize,
+    results: Vec<FileJson>,
+    errors: Vec<LintErrorJson>,
+}
+
+#[derive(Serialize)]
+struct FileJson {
+    path: String,
+    diagnostics: Vec<fastnode_core::lint::Diagnostic>,
+}
+
+#[derive(Serialize)]
+struct LintErrorJson {
+    code: String,
+    message: String,
+}
+
+/// Run `howth lint`.
+///
+/// Exits 1 if any problem has error severity or a file can't be parsed;
+/// exits 2 if the config is invalid or a path doesn't exist.
+pub fn run(cwd: &Path, paths: &[PathBuf], fix: bool, format: &str) {
+    let loaded = LintConfig::load(cwd).and_then(|config| Ok((config, collect_files(cwd, paths)?)));
+    let (config, files) = match loaded {
+        Ok(loaded) => loaded,
+        Err(e) => {
+            report_error(cwd, &e, format);
+            std::process::exit(2);
+        }
+    };
+
+    let mut reports = Vec::new();
+    let mut errors = Vec::new();
+    for file in &files {
+        match lint_file(file, &config, fix) {
+            Ok(report) => reports.push(report),
+            Err(e) => errors.push(e),
+        }
+    }
+
+    let count = |severity| {
+        reports
+            .iter()
+            .flat_map(|r| &r.diagnostics)
+            .filter(|d| d.severity == severity)
+            .count()
+    };
+    let error_count = count(Severity::Error);
+    let warning_count = count(Severity::Warn);
+    let fixed = reports.iter().map(|r| r.fixed).sum();
+    let ok = error_count == 0 && errors.is_empty();
+
+    match format {
+        "json" => {
+            let result = LintJsonResult {
+                ok,
+                files: files.len(),
+                error_count,
+                warning_count,
+                fixed,
+                results: reports
+                    .iter()
+                    .filter(|r| !r.diagnostics.is_empty())
+                    .map(|r| FileJson {
+                        path: display_path(cwd, &r.path),
+                        diagnostics: r.diagnostics.clone(),
+                    })
+                    .collect(),
+                errors: errors.iter().map(error_json).collect(),
+            };
+            println!("{}", serde_json::to_string_pretty(&result).unwrap());
+        }
+        "github" => {
+            for report in &reports {
+                let path = display_path(cwd, &report.path);
+                for d in &report.diagnostics {
+                    println!(
+                        "::{} file={path},line={},col={},title={}::{}",
+                        annotation_level(d.severity),
+                        d.line,
+                        d.column,
+                        d.rule.name(),
+                        escape_annotation(&d.message)
+                    );
+                }
+            }
+            for e in &errors {
+                report_error(cwd, e, format);
+            }
+        }
+        _ => print_stylish(cwd, &reports, &errors, fixed),
+    }
+    if !ok {
+        std::process::exit(1);
+    }
+}
+
+fn print_stylish(cwd: &Path, reports: &[FileReport], errors: &[LintError], fixed: usize) {
+    let mut problems = (0, 0, 0);
+    for report in reports.iter().filter(|r| !r.diagnostics.is_empty()) {
+        println!("{}", display_path(cwd, &report.path));
+        for d in &report.diagnostics {
+            if d.severity == Severity::Error {
+                problems.0 += 1;
+            } else {
+                problems.1 += 1;
+            }
+            let severity = annotation_level(d.severity);
+            if d.fixable {
+                problems.2 += 1;
+            }
+            let position = format!("{}:{}", d.line, d.column);
+            println!(
+                "  {position:<8} {severity:<8} {}  {}",
+                d.message,
+                d.rule.name()
+            );
+        }
+        println!();
+    }
+    for e in errors {
+        eprintln!("error: [{}] {e}", e.code());
+    }
+
+    let (error_count, warning_count, fixable) = problems;
+    if fixed > 0 {
+        eprintln!("Fixed {fixed} {}", plural(fixed, "problem"));
+    }
+    let total = error_count + warning_count;
+    if total == 0 {
+        if errors.is_empty() {
+            eprintln!("No problems found");
+        }
+        return;
+    }
+    eprintln!(
+        "{total} {} ({error_count} {}, {warning_count} {})",
+        plural(total, "problem"),
+        plural(error_count, "error"),
+        plural(warning_count, "warning")
+    );
+    if fixable > 0 {
+        eprintln!(
+            "{fixable} {} fixable with --fix",
+            if fixable == 1 { "is" } else { "are" }
+        );
+    }
+}
+
+/// Report an error that isn't tied to a diagnostic.
+fn report_error(cwd: &Path, e: &LintError, format: &str) {
+    match format {
+        "json" => {
+            let result = LintJsonResult {
+                ok: false,
+                files: 0,
+                error_count: 0,
+                warning_count: 0,
+                fixed: 0,
+                results: Vec::new(),
+                errors: vec![error_json(e)],
+            };
+            println!("{}", serde_json::to_string_pretty(&result).unwrap());
+        }
+        "github" => {
+            if let LintError::Parse {
+                path,
+                line,
+                column,
+                message,
+            } = e
+            {
+                println!(
+                    "::error file={},line={line},col={column},title={}::{}",
+                    display_path(cwd, path),
+                    e.code(),
+                    escape_annotation(message)
+                );
+            } else {
+                println!(
+                    "::error title={}::{}",
+                    e.code(),
+                    escape_annotation(&e.to_string())
+                );
+            }
+        }
+        _ => eprintln!("error: [{}] {e}", e.code()),
+    }
+}
+
+fn error_json(e: &LintError) -> LintErrorJson {
+    LintErrorJson {
+        code: e.code().to_string(),
+        message: e.to_string(),
+    }
+}
+
+fn annotation_level(severity: Severity) -> &'static str {
+    match severity {
+        Severity::Error => "error",
+        _ => "warning",
+    }
+}
+
+/// Escape a workflow command message (`%`, CR and LF are significant).
+fn escape_annotation(message: &str) -> String {
+    message
+        .replace('%', "%25")
+        .replace('\r', "%0D")
+        .replace('\n', "%0A")
+}
+
+fn plural(n: usize, word: &str) -> String {
+    if n == 1 {
+        word.to_string()
+    } else {
+        format!("{word}s")
+    }
+}
+
+/// `path` relative to `cwd` when it's inside it.
+fn display_path(cwd: &Path, path: &Path) -> String {
+    path.strip_prefix(cwd).unwrap_or(path).display().to_string()
+}
@@ -1,17 +1,28 @@
 pub mod bench;
 pub mod build;
+pub mod build_ui;
 pub mod bundle;
 pub mod create;
 pub mod daemon;
 pub mod dev;
+pub mod dev_ssr;
+pub mod dlx;
 pub mod doctor;
 pub mod exec;
+pub mod fmt;
+pub mod graph;
 pub mod init;
 #[cfg(feature = "native-runtime")]
 pub mod js_plugin;
 pub mod link;
+pub mod lint;
 pub mod ping;
 pub mod pkg;
+pub mod pkg_check;
+pub mod pkg_global;
+pub mod pkg_graph;
+pub mod pkg_publish;
+pub mod pkg_update;
 pub mod run;
 pub mod stop;
 pub mod test;
@@ -133,7 +133,7 @@ fn handle_response(
     }
 }
 
-async fn ping_daemon(endpoint: &str, nonce: u64) -> io::Result<(Response, String)> {
+pub(crate) async fn ping_daemon(endpoint: &str, nonce: u64) -> io::Result<(Response, String)> {
     use tokio::io::{AsyncReadExt, AsyncWriteExt};
 
     // Connect using cross-platform IpcStream
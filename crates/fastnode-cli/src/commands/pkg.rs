@@ -6,16 +6,25 @@ use fastnode_core::pkg::{read_package_deps, PkgDepError};
 use fastnode_core::VERSION;
 use fastnode_daemon::ipc::{IpcStream, MAX_FRAME_SIZE};
 use fastnode_proto::{
-    encode_frame, CachedPackage, DoctorFinding, Frame, FrameResponse, GraphDepEdge,
-    GraphPackageNode, InstalledPackage, OutdatedPackage, PackageGraph, PkgDoctorReport,
-    PkgErrorInfo, PkgExplainResult, PkgInstallResult, PkgWhyChain, PkgWhyResult, Request, Response,
-    UpdatedPackage,
+    encode_frame, CachedPackage, DoctorFinding, FileStoreInfo, Frame, FrameResponse, GraphDepEdge,
+    GraphPackageNode, InstalledPackage, OutdatedPackage, PackFileInfo, PackageGraph,
+    PkgDoctorReport, PkgErrorInfo, PkgExplainResult, PkgInstallResult, PkgWhyChain,
+    PkgWhyDependents, PkgWhyResult, Request, Response, UpdatedPackage,
 };
 use miette::{IntoDiagnostic, Result};
 use serde::Serialize;
 use std::io;
 use std::path::PathBuf;
 
+use crate::progress::Progress;
+
+use super::pkg_global::{
+    link_installed_bins, prepare_global_prefix, print_path_advice, run_list, unlink_removed_bins,
+};
+use super::pkg_graph::{render_dot, render_mermaid, render_npm_ls_json, GraphFormat};
+use super::pkg_publish::{print_tarball_report, prompt_otp, run_prepublish_only, TarballDetails};
+use super::pkg_update::{prompt_selection, update_candidates};
+
 /// Pkg command action.
 #[derive(Debug, Clone)]
 pub enum PkgAction {
@@ -23,33 +32,40 @@ pub enum PkgAction {
         specs: Vec<String>,
         cwd: PathBuf,
         save_dev: bool,
+        global: bool,
+        auto_install_peers: Option<bool>,
     },
     AddDeps {
         cwd: PathBuf,
         include_dev: bool,
         include_optional: bool,
+        auto_install_peers: Option<bool>,
     },
     Remove {
         packages: Vec<String>,
         cwd: PathBuf,
+        global: bool,
     },
     Update {
         packages: Vec<String>,
         cwd: PathBuf,
         latest: bool,
+        interactive: bool,
     },
     Graph {
         cwd: PathBuf,
         include_dev: bool,
         include_optional: bool,
         max_depth: u32,
-        format: String,
+        format: GraphFormat,
     },
     Explain {
         specifier: String,
         cwd: PathBuf,
         parent: PathBuf,
         kind: String,
+        conditions: Vec<String>,
+        platform: Option<String>,
     },
     Why {
         arg: String,
@@ -62,6 +78,7 @@ pub enum PkgAction {
         include_trace: bool,
         trace_kind: Option<String>,
         trace_parent: Option<PathBuf>,
+        dependents: bool,
     },
     Doctor {
         cwd: PathBuf,
@@ -77,6 +94,7 @@ pub enum PkgAction {
         frozen: bool,
         include_dev: bool,
         include_optional: bool,
+        auto_install_peers: Option<bool>,
     },
     Outdated {
         cwd: PathBuf,
@@ -87,9 +105,23 @@ pub enum PkgAction {
         tag: String,
         access: Option<String>,
         registry: Option<String>,
+        otp: Option<String>,
+        provenance: bool,
+        ignore_scripts: bool,
+    },
+    Pack {
+        cwd: PathBuf,
+        destination: Option<PathBuf>,
+        dry_run: bool,
+        source_date_epoch: Option<u64>,
+    },
+    List {
+        cwd: PathBuf,
+        global: bool,
     },
     CacheList,
     CachePrune,
+    CacheDedupe,
 }
 
 /// Add result for JSON output.
@@ -99,6 +131,8 @@ struct PkgAddResult {
     installed: Vec<InstalledPackage>,
     errors: Vec<PkgErrorInfo>,
     reused_cache: u32,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    bins: Vec<String>,
     #[serde(skip_serializing_if = "Option::is_none")]
     error: Option<String>,
 }
@@ -130,6 +164,9 @@ struct PkgCacheListResult {
     ok: bool,
     packages: Vec<CachedPackage>,
     total_size_bytes: u64,
+    disk_size_bytes: u64,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    file_store: Option<FileStoreInfo>,
     #[serde(skip_serializing_if = "Option::is_none")]
     error: Option<String>,
 }
@@ -144,6 +181,18 @@ struct PkgCachePruneResult {
     error: Option<String>,
 }
 
+/// Cache dedupe result for JSON output.
+#[derive(Serialize)]
+struct PkgCacheDedupeResult {
+    ok: bool,
+    packages: u32,
+    files: u64,
+    linked_files: u64,
+    saved_bytes: u64,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    error: Option<String>,
+}
+
 /// Graph result for JSON output.
 #[derive(Serialize)]
 struct PkgGraphResult {
@@ -209,24 +258,192 @@ struct PkgPublishJsonResult {
     tag: Option<String>,
     tarball_size: Option<u64>,
     files_count: Option<u32>,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    files: Vec<PackFileInfo>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    unpacked_size: Option<u64>,
     #[serde(skip_serializing_if = "Option::is_none")]
     error: Option<String>,
 }
 
-/// Check if stdout is a TTY (for interactive progress display).
-fn is_tty() -> bool {
-    use std::io::IsTerminal;
-    std::io::stdout().is_terminal()
+/// Pack result for JSON output.
+#[derive(Serialize)]
+struct PkgPackJsonResult {
+    ok: bool,
+    name: Option<String>,
+    version: Option<String>,
+    filename: Option<String>,
+    path: Option<String>,
+    files: Vec<PackFileInfo>,
+    unpacked_size: Option<u64>,
+    tarball_size: Option<u64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    error: Option<String>,
 }
 
 /// Run the pkg command.
+/// Print the `pkg outdated` table, with a Workspace column in monorepos.
+fn print_outdated_table(outdated: &[OutdatedPackage]) {
+    let show_workspace = outdated.iter().any(|p| p.workspace.is_some());
+    if show_workspace {
+        println!(
+            "{:<30} {:>12} {:>12} {:>12} {:>8}  Workspace",
+            "Package", "Current", "Wanted", "Latest", "Type"
+        );
+        println!("{}", "-".repeat(96));
+    } else {
+        println!(
+            "{:<30} {:>12} {:>12} {:>12} {:>8}",
+            "Package", "Current", "Wanted", "Latest", "Type"
+        );
+        println!("{}", "-".repeat(78));
+    }
+    for pkg in outdated {
+        if show_workspace {
+            println!(
+                "{:<30} {:>12} {:>12} {:>12} {:>8}  {}",
+                pkg.name,
+                pkg.current,
+                pkg.wanted,
+                pkg.latest,
+                pkg.dep_type,
+                pkg.workspace.as_deref().unwrap_or("(root)")
+            );
+        } else {
+            println!(
+                "{:<30} {:>12} {:>12} {:>12} {:>8}",
+                pkg.name, pkg.current, pkg.wanted, pkg.latest, pkg.dep_type
+            );
+        }
+    }
+}
+
+/// Query outdated packages and ask which ones to update.
+///
+/// `filter` restricts the choices to the named packages. Returns `None` when
+/// there is nothing to do (all up to date, or nothing selected).
+fn select_interactive_updates(
+    endpoint: &str,
+    cwd: &std::path::Path,
+    filter: &[String],
+    latest: bool,
+    channel: Channel,
+    json: bool,
+) -> Result<Option<Vec<String>>> {
+    use std::io::IsTerminal;
+
+    if json || !std::io::stdin().is_terminal() {
+        let message = "--interactive requires a terminal and cannot be combined with --json";
+        if json {
+            let result = PkgUpdateResult {
+                ok: false,
+                updated: Vec::new(),
+                up_to_date: Vec::new(),
+                errors: Vec::new(),
+                error: Some(message.to_string()),
+            };
+            println!("{}", serde_json::to_string_pretty(&result).unwrap());
+        } else {
+            eprintln!("error: {message}");
+        }
+        std::process::exit(2);
+    }
+
+    let outdated_action = PkgAction::Outdated {
+        cwd: cwd.to_path_buf(),
+    };
+    let runtime = tokio::runtime::Runtime::new().into_diagnostic()?;
+    let result =
+        runtime.block_on(async { send_pkg_request(endpoint, &outdated_action, channel).await });
+
+    let outdated = match result {
+        Ok((Response::PkgOutdatedResult { outdated, .. }, _)) => outdated,
+        Ok((response, _)) => {
+            handle_response(response, &outdated_action, json, Vec::new())?;
+            return Ok(None);
+        }
+        Err(_) => {
+            eprintln!("error: daemon not running");
+            eprintln!("hint: start with `howth daemon`");
+            std::process::exit(1);
+        }
+    };
+
+    let mut candidates = update_candidates(&outdated, latest);
+    if !filter.is_empty() {
+        candidates.retain(|c| filter.contains(&c.name));
+    }
+    if candidates.is_empty() {
+        println!("All packages are up to date.");
+        return Ok(None);
+    }
+
+    let selected = prompt_selection(&candidates);
+    if selected.is_empty() {
+        println!("No updates selected.");
+        return Ok(None);
+    }
+    Ok(Some(selected))
+}
+
 pub fn run(action: PkgAction, channel: Channel, json: bool) -> Result<()> {
+    // Listing reads package.json and node_modules directly
+    if let PkgAction::List { cwd, global } = &action {
+        run_list(cwd, *global, channel, json);
+        return Ok(());
+    }
+
+    // Global add/remove operate on the global prefix as their project
+    let action = match action {
+        PkgAction::Add {
+            specs,
+            save_dev,
+            global: true,
+            auto_install_peers,
+            ..
+        } => PkgAction::Add {
+            specs,
+            cwd: global_prefix_or_exit(channel, json),
+            save_dev,
+            global: true,
+            auto_install_peers,
+        },
+        PkgAction::Remove {
+            packages,
+            global: true,
+            ..
+        } => {
+            let prefix = paths::global_dir(channel);
+            if let Err(e) = unlink_removed_bins(&prefix, &packages) {
+                if json {
+                    let result = PkgRemoveResult {
+                        ok: false,
+                        removed: Vec::new(),
+                        errors: Vec::new(),
+                        error: Some(format!("{}: {}", e.code(), e.message())),
+                    };
+                    println!("{}", serde_json::to_string_pretty(&result).unwrap());
+                } else {
+                    eprintln!("error: {e}");
+                }
+                std::process::exit(2);
+            }
+            PkgAction::Remove {
+                packages,
+                cwd: prefix,
+                global: true,
+            }
+        }
+        other => other,
+    };
+
     // Handle AddDeps by converting to Add with specs from package.json
     let (effective_action, dep_errors) = match &action {
         PkgAction::AddDeps {
             cwd,
             include_dev,
             include_optional,
+            auto_install_peers,
         } => {
             let pkg_json_path = cwd.join("package.json");
             match read_package_deps(&pkg_json_path, *include_dev, *include_optional) {
@@ -253,6 +470,7 @@ pub fn run(action: PkgAction, channel: Channel, json: bool) -> Result<()> {
                                 installed: Vec::new(),
                                 errors: Vec::new(),
                                 reused_cache: 0,
+                                bins: Vec::new(),
                                 error: None,
                             };
                             println!("{}", serde_json::to_string_pretty(&result).unwrap());
@@ -270,6 +488,7 @@ pub fn run(action: PkgAction, channel: Channel, json: bool) -> Result<()> {
                                 installed: Vec::new(),
                                 errors: dep_errors,
                                 reused_cache: 0,
+                                bins: Vec::new(),
                                 error: None,
                             };
                             println!("{}", serde_json::to_string_pretty(&result).unwrap());
@@ -286,6 +505,8 @@ pub fn run(action: PkgAction, channel: Channel, json: bool) -> Result<()> {
                             specs,
                             cwd: cwd.clone(),
                             save_dev: false, // --deps mode reads from existing package.json
+                            global: false,
+                            auto_install_peers: *auto_install_peers,
                         },
                         dep_errors,
                     )
@@ -300,6 +521,7 @@ pub fn run(action: PkgAction, channel: Channel, json: bool) -> Result<()> {
                             installed: Vec::new(),
                             errors: Vec::new(),
                             reused_cache: 0,
+                            bins: Vec::new(),
                             error: Some(format!("{}: {}", e.code(), e.message())),
                         };
                         println!("{}", serde_json::to_string_pretty(&result).unwrap());
@@ -321,6 +543,25 @@ pub fn run(action: PkgAction, channel: Channel, json: bool) -> Result<()> {
 
     let endpoint = paths::ipc_endpoint(channel);
 
+    // For interactive updates, let the user pick from the outdated report first
+    let effective_action = match effective_action {
+        PkgAction::Update {
+            packages,
+            cwd,
+            latest,
+            interactive: true,
+        } => match select_interactive_updates(&endpoint, &cwd, &packages, latest, channel, json)? {
+            Some(packages) => PkgAction::Update {
+                packages,
+                cwd,
+                latest,
+                interactive: false,
+            },
+            None => return Ok(()),
+        },
+        other => other,
+    };
+
     // For PkgInstall, use streaming path to show per-package progress
     if matches!(effective_action, PkgAction::Install { .. }) {
         let runtime = tokio::runtime::Runtime::new().into_diagnostic()?;
@@ -347,10 +588,69 @@ pub fn run(action: PkgAction, channel: Channel, json: bool) -> Result<()> {
         };
     }
 
+    // Run prepublishOnly before handing the package to the daemon
+    if let PkgAction::Publish {
+        cwd,
+        ignore_scripts: false,
+        ..
+    } = &effective_action
+    {
+        if let Err(message) = run_prepublish_only(cwd, json) {
+            if json {
+                let result = PkgPublishJsonResult {
+                    ok: false,
+                    name: None,
+                    version: None,
+                    registry: None,
+                    tag: None,
+                    tarball_size: None,
+                    files_count: None,
+                    files: Vec::new(),
+                    unpacked_size: None,
+                    error: Some(message),
+                };
+                println!("{}", serde_json::to_string_pretty(&result).unwrap());
+            } else {
+                eprintln!("error: {message}");
+            }
+            std::process::exit(2);
+        }
+    }
+
     // Run the async client
     let runtime = tokio::runtime::Runtime::new().into_diagnostic()?;
-    let result =
-        runtime.block_on(async { send_pkg_request(&endpoint, &effective_action, channel).await });
+    let mut effective_action = effective_action;
+    let mut result = if let PkgAction::Add { specs, .. } = &effective_action {
+        // Resolving and installing can take a while; keep a spinner going
+        let mut progress = Progress::new(format!("adding {}", specs.join(" ")), json);
+        let result =
+            runtime.block_on(progress.run(send_pkg_request(&endpoint, &effective_action, channel)));
+        progress.finish();
+        result
+    } else {
+        runtime.block_on(async { send_pkg_request(&endpoint, &effective_action, channel).await })
+    };
+
+    // Retry publish with a one-time password if the registry asks for one
+    if let (
+        Ok((
+            Response::PkgPublishResult {
+                otp_required: true, ..
+            },
+            _,
+        )),
+        PkgAction::Publish { otp, .. },
+    ) = (&result, &mut effective_action)
+    {
+        if otp.is_none() && !json {
+            if let Some(code) = prompt_otp() {
+                *otp = Some(code);
+                result = runtime.block_on(async {
+                    send_pkg_request(&endpoint, &effective_action, channel).await
+                });
+            }
+        }
+    }
 
     match result {
         Ok((response, _server_version)) => {
@@ -365,6 +665,7 @@ pub fn run(action: PkgAction, channel: Channel, json: bool) -> Result<()> {
                             installed: Vec::new(),
                             errors: dep_errors,
                             reused_cache: 0,
+                            bins: Vec::new(),
                             error: Some(format!("Failed to connect: {e}")),
                         };
                         println!("{}", serde_json::to_string_pretty(&result).unwrap());
@@ -393,6 +694,8 @@ pub fn run(action: PkgAction, channel: Channel, json: bool) -> Result<()> {
                             ok: false,
                             packages: Vec::new(),
                             total_size_bytes: 0,
+                            disk_size_bytes: 0,
+                            file_store: None,
                             error: Some(format!("Failed to connect: {e}")),
                         };
                         println!("{}", serde_json::to_string_pretty(&result).unwrap());
@@ -406,6 +709,17 @@ pub fn run(action: PkgAction, channel: Channel, json: bool) -> Result<()> {
                         };
                         println!("{}", serde_json::to_string_pretty(&result).unwrap());
                     }
+                    PkgAction::CacheDedupe => {
+                        let result = PkgCacheDedupeResult {
+                            ok: false,
+                            packages: 0,
+                            files: 0,
+                            linked_files: 0,
+                            saved_bytes: 0,
+                            error: Some(format!("Failed to connect: {e}")),
+                        };
+                        println!("{}", serde_json::to_string_pretty(&result).unwrap());
+                    }
                     PkgAction::Graph { .. } => {
                         let result = PkgGraphResult {
                             ok: false,
@@ -464,6 +778,23 @@ pub fn run(action: PkgAction, channel: Channel, json: bool) -> Result<()> {
                             tag: None,
                             tarball_size: None,
                             files_count: None,
+                            files: Vec::new(),
+                            unpacked_size: None,
+                            error: Some(format!("Failed to connect: {e}")),
+                        };
+                        println!("{}", serde_json::to_string_pretty(&result).unwrap());
+                    }
+                    PkgAction::List { .. } => unreachable!("List is handled locally"),
+                    PkgAction::Pack { .. } => {
+                        let result = PkgPackJsonResult {
+                            ok: false,
+                            name: None,
+                            version: None,
+                            filename: None,
+                            path: None,
+                            files: Vec::new(),
+                            unpacked_size: None,
+                            tarball_size: None,
                             error: Some(format!("Failed to connect: {e}")),
                         };
                         println!("{}", serde_json::to_string_pretty(&result).unwrap());
@@ -478,6 +809,26 @@ pub fn run(action: PkgAction, channel: Channel, json: bool) -> Result<()> {
     }
 }
 
+/// Create the global prefix for `pkg add --global`, exiting with code 2 on failure.
+fn global_prefix_or_exit(channel: Channel, json: bool) -> PathBuf {
+    prepare_global_prefix(channel).unwrap_or_else(|e| {
+        if json {
+            let result = PkgAddResult {
+                ok: false,
+                installed: Vec::new(),
+                errors: Vec::new(),
+                reused_cache: 0,
+                bins: Vec::new(),
+                error: Some(format!("{}: {}", e.code(), e.message())),
+            };
+            println!("{}", serde_json::to_string_pretty(&result).unwrap());
+        } else {
+            eprintln!("error: {e}");
+        }
+        std::process::exit(2);
+    })
+}
+
 /// Convert a `PkgDepError` to `PkgErrorInfo` for protocol/output.
 fn dep_error_to_pkg_error_info(err: &PkgDepError) -> PkgErrorInfo {
     PkgErrorInfo {
@@ -503,6 +854,22 @@ fn handle_response(
             let mut all_errors = dep_errors;
             all_errors.extend(errors);
 
+            // Global installs get executable shims in the global bin dir
+            let global_prefix = match action {
+                PkgAction::Add {
+                    cwd, global: true, ..
+                } => Some(cwd),
+                _ => None,
+            };
+            let bins = match global_prefix {
+                Some(prefix) => {
+                    let (bins, link_errors) = link_installed_bins(prefix, &installed);
+                    all_errors.extend(link_errors);
+                    bins
+                }
+                None => Vec::new(),
+            };
+
             let has_errors = !all_errors.is_empty();
 
             if json {
@@ -511,6 +878,7 @@ fn handle_response(
                     installed,
                     errors: all_errors,
                     reused_cache,
+                    bins,
                     error: None,
                 };
                 println!("{}", serde_json::to_string_pretty(&result).unwrap());
@@ -521,9 +889,15 @@ fn handle_response(
                 if reused_cache > 0 {
                     println!("({reused_cache} from cache)");
                 }
+                for bin in &bins {
+                    println!("  bin: {bin}");
+                }
                 for err in &all_errors {
                     eprintln!("! {}: {} {}", err.spec, err.code, err.message);
                 }
+                if let (Some(prefix), false) = (global_prefix, bins.is_empty()) {
+                    print_path_advice(prefix);
+                }
             }
 
             // Exit with code 2 if any errors (both JSON and human mode)
@@ -597,12 +971,16 @@ fn handle_response(
         Response::PkgCacheListResult {
             packages,
             total_size_bytes,
+            disk_size_bytes,
+            file_store,
         } => {
             if json {
                 let result = PkgCacheListResult {
                     ok: true,
                     packages,
                     total_size_bytes,
+                    disk_size_bytes,
+                    file_store,
                     error: None,
                 };
                 println!("{}", serde_json::to_string_pretty(&result).unwrap());
@@ -613,6 +991,15 @@ fn handle_response(
                 }
                 let total_mb = total_size_bytes as f64 / (1024.0 * 1024.0);
                 println!("\nTotal: {total_mb:.2} MB");
+                if let Some(store) = file_store {
+                    let disk_mb = disk_size_bytes as f64 / (1024.0 * 1024.0);
+                    let saved_mb =
+                        total_size_bytes.saturating_sub(disk_size_bytes) as f64 / (1024.0 * 1024.0);
+                    println!(
+                        "On disk: {disk_mb:.2} MB ({} files in the file store, {saved_mb:.2} MB saved)",
+                        store.files
+                    );
+                }
             }
             Ok(())
         }
@@ -634,11 +1021,42 @@ fn handle_response(
             }
             Ok(())
         }
+        Response::PkgCacheDedupeResult {
+            packages,
+            files,
+            linked_files,
+            saved_bytes,
+            error,
+        } => {
+            let ok = error.is_none();
+            if json {
+                let result = PkgCacheDedupeResult {
+                    ok,
+                    packages,
+                    files,
+                    linked_files,
+                    saved_bytes,
+                    error,
+                };
+                println!("{}", serde_json::to_string_pretty(&result).unwrap());
+            } else if let Some(error) = &error {
+                eprintln!("error: {error}");
+            } else {
+                let saved_mb = saved_bytes as f64 / (1024.0 * 1024.0);
+                println!(
+                    "Deduplicated {packages} packages: {linked_files} of {files} files linked, saved {saved_mb:.2} MB"
+                );
+            }
+            if !ok {
+                std::process::exit(2);
+            }
+            Ok(())
+        }
         Response::PkgGraphResult { graph } => {
             // Get format from action
             let format = match action {
-                PkgAction::Graph { format, .. } => format.as_str(),
-                _ => "tree",
+                PkgAction::Graph { format, .. } => *format,
+                _ => GraphFormat::Tree,
             };
 
             let has_errors = !graph.errors.is_empty();
@@ -651,7 +1069,12 @@ fn handle_response(
                 };
                 println!("{}", serde_json::to_string_pretty(&result).unwrap());
             } else {
-                print_graph_human(&graph, format);
+                match format {
+                    GraphFormat::NpmLsJson => println!("{}", render_npm_ls_json(&graph)),
+                    GraphFormat::Dot => print!("{}", render_dot(&graph)),
+                    GraphFormat::Mermaid => print!("{}", render_mermaid(&graph)),
+                    GraphFormat::Tree | GraphFormat::List => print_graph_human(&graph, format),
+                }
             }
 
             // Exit with code 2 if any errors
@@ -695,7 +1118,7 @@ fn handle_response(
             if json {
                 let result = PkgWhyJsonResult {
                     ok: found && !has_errors,
-                    why: Some(why_result),
+                    why: Some(*why_result),
                     error: None,
                 };
                 println!("{}", serde_json::to_string_pretty(&result).unwrap());
@@ -801,6 +1224,7 @@ fn handle_response(
             outdated,
             up_to_date_count,
         } => {
+            let has_outdated = !outdated.is_empty();
             if json {
                 let result = PkgOutdatedJsonResult {
                     ok: true,
@@ -812,18 +1236,7 @@ fn handle_response(
             } else if outdated.is_empty() {
                 println!("All packages are up to date.");
             } else {
-                // Print table header
-                println!(
-                    "{:<30} {:>12} {:>12} {:>12} {:>8}",
-                    "Package", "Current", "Wanted", "Latest", "Type"
-                );
-                println!("{}", "-".repeat(78));
-                for pkg in &outdated {
-                    println!(
-                        "{:<30} {:>12} {:>12} {:>12} {:>8}",
-                        pkg.name, pkg.current, pkg.wanted, pkg.latest, pkg.dep_type
-                    );
-                }
+                print_outdated_table(&outdated);
                 println!();
                 println!(
                     "{} outdated, {} up to date",
@@ -831,6 +1244,10 @@ fn handle_response(
                     up_to_date_count
                 );
             }
+            // Exit with code 1 if anything is outdated so CI can gate on it
+            if has_outdated {
+                std::process::exit(1);
+            }
             Ok(())
         }
         Response::PkgPublishResult {
@@ -842,7 +1259,11 @@ fn handle_response(
             tarball_size,
             files_count,
             error,
+            files,
+            unpacked_size,
+            ..
         } => {
+            let dry_run = matches!(action, PkgAction::Publish { dry_run: true, .. });
             if json {
                 let result = PkgPublishJsonResult {
                     ok,
@@ -852,15 +1273,75 @@ fn handle_response(
                     tag: Some(tag.clone()),
                     tarball_size: Some(tarball_size),
                     files_count: Some(files_count),
+                    files,
+                    unpacked_size: Some(unpacked_size),
                     error,
                 };
                 println!("{}", serde_json::to_string_pretty(&result).unwrap());
+                if !ok {
+                    std::process::exit(2);
+                }
             } else if ok {
-                let size_kb = tarball_size / 1024;
-                println!("+ {name}@{version}");
+                print_tarball_report(&TarballDetails {
+                    name: &name,
+                    version: &version,
+                    filename: None,
+                    tarball_size,
+                    unpacked_size,
+                    files: &files,
+                });
+                if dry_run {
+                    println!("+ {name}@{version} (dry run, not published)");
+                } else {
+                    println!("+ {name}@{version}");
+                }
                 println!("  registry: {registry}");
                 println!("  tag: {tag}");
-                println!("  size: {size_kb} KB ({files_count} files)");
+            } else if let Some(err) = error {
+                eprintln!("error: {err}");
+                std::process::exit(2);
+            }
+            Ok(())
+        }
+        Response::PkgPackResult {
+            ok,
+            name,
+            version,
+            filename,
+            path,
+            files,
+            unpacked_size,
+            tarball_size,
+            error,
+        } => {
+            if json {
+                let result = PkgPackJsonResult {
+                    ok,
+                    name: Some(name),
+                    version: Some(version),
+                    filename: Some(filename),
+                    path,
+                    files,
+                    unpacked_size: Some(unpacked_size),
+                    tarball_size: Some(tarball_size),
+                    error,
+                };
+                println!("{}", serde_json::to_string_pretty(&result).unwrap());
+                if !ok {
+                    std::process::exit(2);
+                }
+            } else if ok {
+                print_tarball_report(&TarballDetails {
+                    name: &name,
+                    version: &version,
+                    filename: Some(&filename),
+                    tarball_size,
+                    unpacked_size,
+                    files: &files,
+                });
+                if let Some(path) = path {
+                    println!("wrote {path}");
+                }
             } else if let Some(err) = error {
                 eprintln!("error: {err}");
                 std::process::exit(2);
@@ -876,6 +1357,7 @@ fn handle_response(
                             installed: Vec::new(),
                             errors: dep_errors,
                             reused_cache: 0,
+                            bins: Vec::new(),
                             error: Some(format!("{code}: {message}")),
                         };
                         println!("{}", serde_json::to_string_pretty(&result).unwrap());
@@ -904,6 +1386,8 @@ fn handle_response(
                             ok: false,
                             packages: Vec::new(),
                             total_size_bytes: 0,
+                            disk_size_bytes: 0,
+                            file_store: None,
                             error: Some(format!("{code}: {message}")),
                         };
                         println!("{}", serde_json::to_string_pretty(&result).unwrap());
@@ -917,6 +1401,17 @@ fn handle_response(
                         };
                         println!("{}", serde_json::to_string_pretty(&result).unwrap());
                     }
+                    PkgAction::CacheDedupe => {
+                        let result = PkgCacheDedupeResult {
+                            ok: false,
+                            packages: 0,
+                            files: 0,
+                            linked_files: 0,
+                            saved_bytes: 0,
+                            error: Some(format!("{code}: {message}")),
+                        };
+                        println!("{}", serde_json::to_string_pretty(&result).unwrap());
+                    }
                     PkgAction::Graph { .. } => {
                         let result = PkgGraphResult {
                             ok: false,
@@ -975,6 +1470,23 @@ fn handle_response(
                             tag: None,
                             tarball_size: None,
                             files_count: None,
+                            files: Vec::new(),
+                            unpacked_size: None,
+                            error: Some(format!("{code}: {message}")),
+                        };
+                        println!("{}", serde_json::to_string_pretty(&result).unwrap());
+                    }
+                    PkgAction::List { .. } => unreachable!("List is handled locally"),
+                    PkgAction::Pack { .. } => {
+                        let result = PkgPackJsonResult {
+                            ok: false,
+                            name: None,
+                            version: None,
+                            filename: None,
+                            path: None,
+                            files: Vec::new(),
+                            unpacked_size: None,
+                            tarball_size: None,
                             error: Some(format!("{code}: {message}")),
                         };
                         println!("{}", serde_json::to_string_pretty(&result).unwrap());
@@ -994,6 +1506,7 @@ fn handle_response(
                             installed: Vec::new(),
                             errors: dep_errors,
                             reused_cache: 0,
+                            bins: Vec::new(),
                             error: Some("Unexpected response type".to_string()),
                         };
                         println!("{}", serde_json::to_string_pretty(&result).unwrap());
@@ -1022,6 +1535,8 @@ fn handle_response(
                             ok: false,
                             packages: Vec::new(),
                             total_size_bytes: 0,
+                            disk_size_bytes: 0,
+                            file_store: None,
                             error: Some("Unexpected response type".to_string()),
                         };
                         println!("{}", serde_json::to_string_pretty(&result).unwrap());
@@ -1035,6 +1550,17 @@ fn handle_response(
                         };
                         println!("{}", serde_json::to_string_pretty(&result).unwrap());
                     }
+                    PkgAction::CacheDedupe => {
+                        let result = PkgCacheDedupeResult {
+                            ok: false,
+                            packages: 0,
+                            files: 0,
+                            linked_files: 0,
+                            saved_bytes: 0,
+                            error: Some("Unexpected response type".to_string()),
+                        };
+                        println!("{}", serde_json::to_string_pretty(&result).unwrap());
+                    }
                     PkgAction::Graph { .. } => {
                         let result = PkgGraphResult {
                             ok: false,
@@ -1093,6 +1619,23 @@ fn handle_response(
                             tag: None,
                             tarball_size: None,
                             files_count: None,
+                            files: Vec::new(),
+                            unpacked_size: None,
+                            error: Some("Unexpected response type".to_string()),
+                        };
+                        println!("{}", serde_json::to_string_pretty(&result).unwrap());
+                    }
+                    PkgAction::List { .. } => unreachable!("List is handled locally"),
+                    PkgAction::Pack { .. } => {
+                        let result = PkgPackJsonResult {
+                            ok: false,
+                            name: None,
+                            version: None,
+                            filename: None,
+                            path: None,
+                            files: Vec::new(),
+                            unpacked_size: None,
+                            tarball_size: None,
                             error: Some("Unexpected response type".to_string()),
                         };
                         println!("{}", serde_json::to_string_pretty(&result).unwrap());
@@ -1107,7 +1650,7 @@ fn handle_response(
 }
 
 /// Print the dependency graph in human-readable format.
-fn print_graph_human(graph: &PackageGraph, format: &str) {
+fn print_graph_human(graph: &PackageGraph, format: GraphFormat) {
     // Print errors first
     for err in &graph.errors {
         eprintln!("! [{}] {}: {}", err.code, err.path, err.message);
@@ -1126,7 +1669,7 @@ fn print_graph_human(graph: &PackageGraph, format: &str) {
         .collect();
 
     match format {
-        "list" => print_graph_list(graph),
+        GraphFormat::List => print_graph_list(graph),
         _ => print_graph_tree(graph, &node_map),
     }
 }
@@ -1223,6 +1766,9 @@ fn print_explain_human(result: &PkgExplainResult) {
     println!("Specifier: {}", result.specifier);
     println!("Kind: {}", result.kind);
     println!("Parent: {}", result.parent);
+    if !result.conditions.is_empty() {
+        println!("Conditions: {}", result.conditions.join(", "));
+    }
     println!();
 
     // Print resolution status
@@ -1255,7 +1801,9 @@ fn print_explain_human(result: &PkgExplainResult) {
         if let Some(ref target) = step.target {
             println!("      target: {target}");
         }
-        if let Some(ref condition) = step.condition {
+        if step.conditions.len() > 1 {
+            println!("      conditions: {}", step.conditions.join(" > "));
+        } else if let Some(ref condition) = step.condition {
             println!("      condition: {condition}");
         }
         for note in &step.notes {
@@ -1303,8 +1851,10 @@ fn print_why_human(result: &PkgWhyResult, format: &str) {
         println!();
     }
 
-    // 3) Print chains based on format
-    if format == "list" {
+    // 3) Print dependents or chains based on format
+    if let Some(ref dependents) = result.dependents {
+        print_why_dependents(&target.name, dependents);
+    } else if format == "list" {
         print_why_chains_list(result);
     } else {
         print_why_chains_tree(result);
@@ -1375,6 +1925,35 @@ fn print_why_human(result: &PkgWhyResult, format: &str) {
     }
 }
 
+/// Print the packages that depend on the target.
+fn print_why_dependents(name: &str, dependents: &PkgWhyDependents) {
+    println!(
+        "Dependents of {name} ({} direct, {} at any depth):",
+        dependents.direct.len(),
+        dependents.transitive_count
+    );
+    if dependents.root_dependency {
+        println!("  <root> (package.json)");
+    }
+    for dep in &dependents.direct {
+        let kind = if dep.kind == "dep" {
+            String::new()
+        } else {
+            format!(" [{}]", dep.kind)
+        };
+        println!(
+            "  {}@{} requires {} -> {}{kind}",
+            dep.name,
+            dep.version,
+            dep.req.as_deref().unwrap_or("*"),
+            dep.resolved_version
+        );
+    }
+    if dependents.direct.is_empty() && !dependents.root_dependency {
+        println!("  (nothing depends on {name})");
+    }
+}
+
 /// Print chains in list format (one line per chain).
 fn print_why_chains_list(result: &PkgWhyResult) {
     if result.chains.is_empty() {
@@ -1616,7 +2195,7 @@ async fn send_pkg_install_streaming(
     channel: Channel,
     json: bool,
 ) -> io::Result<Response> {
-    use tokio::io::{AsyncReadExt, AsyncWriteExt};
+    use tokio::io::AsyncWriteExt;
 
     let mut stream = IpcStream::connect(endpoint).await?;
 
@@ -1627,12 +2206,14 @@ async fn send_pkg_install_streaming(
             frozen,
             include_dev,
             include_optional,
+            auto_install_peers,
         } => Request::PkgInstall {
             cwd: cwd.to_string_lossy().into_owned(),
             channel: channel.as_str().to_string(),
             frozen: *frozen,
             include_dev: *include_dev,
             include_optional: *include_optional,
+            auto_install_peers: *auto_install_peers,
         },
         _ => unreachable!("send_pkg_install_streaming called with non-Install action"),
     };
@@ -1648,27 +2229,11 @@ async fn send_pkg_install_streaming(
         println!("howth install");
     }
 
-    let tty = is_tty();
+    let mut progress = Progress::new("installing", json);
 
-    // Read streaming responses
+    // Read streaming responses, ticking the spinner while waiting
     loop {
-        // Read length prefix
-        let mut len_buf = [0u8; 4];
-        stream.read_exact(&mut len_buf).await?;
-        let len = u32::from_le_bytes(len_buf) as usize;
-
-        if len > MAX_FRAME_SIZE {
-            return Err(io::Error::new(
-                io::ErrorKind::InvalidData,
-                format!("response frame too large: {len} bytes"),
-            ));
-        }
-
-        let mut buf = vec![0u8; len];
-        stream.read_exact(&mut buf).await?;
-
-        let response_frame: FrameResponse = serde_json::from_slice(&buf)
-            .map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e))?;
+        let response_frame = progress.run(read_response_frame(&mut stream)).await?;
 
         match response_frame.response {
             Response::PkgInstallProgress {
@@ -1681,28 +2246,12 @@ async fn send_pkg_install_streaming(
                 // In JSON mode, skip progress display
                 if !json {
                     // Print each package on its own line (permanent)
-                    if tty {
-                        // Clear the progress counter line, print package, then reprint counter
-                        eprint!("\r\x1b[2K");
-                    }
-                    println!("  + {}@{} ({})", name, version, status,);
-                    if tty {
-                        // Show running progress counter on a transient line
-                        eprint!("  Progress: {completed}/{total}");
-                    }
-                }
-            }
-            Response::PkgInstallResult { .. } => {
-                // Clear the progress counter line on TTY
-                if !json && tty {
-                    eprint!("\r\x1b[2K");
+                    progress.println(&format!("  + {name}@{version} ({status})"));
+                    progress.finish_item(name, completed, total);
                 }
-                return Ok(response_frame.response);
             }
-            Response::Error { .. } => {
-                if !json && tty {
-                    eprint!("\r\x1b[2K");
-                }
+            Response::PkgInstallResult { .. } | Response::Error { .. } => {
+                progress.finish();
                 return Ok(response_frame.response);
             }
             _ => {
@@ -1712,6 +2261,28 @@ async fn send_pkg_install_streaming(
     }
 }
 
+/// Read one length-prefixed response frame.
+async fn read_response_frame(stream: &mut IpcStream) -> io::Result<FrameResponse> {
+    use tokio::io::AsyncReadExt;
+
+    // Read length prefix
+    let mut len_buf = [0u8; 4];
+    stream.read_exact(&mut len_buf).await?;
+    let len = u32::from_le_bytes(len_buf) as usize;
+
+    if len > MAX_FRAME_SIZE {
+        return Err(io::Error::new(
+            io::ErrorKind::InvalidData,
+            format!("response frame too large: {len} bytes"),
+        ));
+    }
+
+    let mut buf = vec![0u8; len];
+    stream.read_exact(&mut buf).await?;
+
+    serde_json::from_slice(&buf).map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e))
+}
+
 async fn send_pkg_request(
     endpoint: &str,
     action: &PkgAction,
@@ -1728,17 +2299,21 @@ async fn send_pkg_request(
             specs,
             cwd,
             save_dev,
+            auto_install_peers,
+            ..
         } => Request::PkgAdd {
             specs: specs.clone(),
             cwd: cwd.to_string_lossy().into_owned(),
             channel: channel.as_str().to_string(),
             save_dev: *save_dev,
+            auto_install_peers: *auto_install_peers,
         },
         PkgAction::AddDeps { .. } => {
             // AddDeps is converted to Add before reaching this function
             unreachable!("AddDeps should be converted to Add before sending request")
         }
-        PkgAction::Remove { packages, cwd } => Request::PkgRemove {
+        PkgAction::List { .. } => unreachable!("List is handled locally"),
+        PkgAction::Remove { packages, cwd, .. } => Request::PkgRemove {
             packages: packages.clone(),
             cwd: cwd.to_string_lossy().into_owned(),
             channel: channel.as_str().to_string(),
@@ -1747,6 +2322,7 @@ async fn send_pkg_request(
             packages,
             cwd,
             latest,
+            ..
         } => Request::PkgUpdate {
             packages: packages.clone(),
             cwd: cwd.to_string_lossy().into_owned(),
@@ -1759,6 +2335,9 @@ async fn send_pkg_request(
         PkgAction::CachePrune => Request::PkgCachePrune {
             channel: channel.as_str().to_string(),
         },
+        PkgAction::CacheDedupe => Request::PkgCacheDedupe {
+            channel: channel.as_str().to_string(),
+        },
         PkgAction::Graph {
             cwd,
             include_dev,
@@ -1771,19 +2350,23 @@ async fn send_pkg_request(
             include_dev_root: *include_dev,
             include_optional: *include_optional,
             max_depth: *max_depth,
-            format: format.clone(),
+            format: format.wire_format().to_string(),
         },
         PkgAction::Explain {
             specifier,
             cwd,
             parent,
             kind,
+            conditions,
+            platform,
         } => Request::PkgExplain {
             specifier: specifier.clone(),
             cwd: cwd.to_string_lossy().into_owned(),
             parent: parent.to_string_lossy().into_owned(),
             channel: channel.as_str().to_string(),
             kind: kind.clone(),
+            conditions: conditions.clone(),
+            platform: platform.clone(),
         },
         PkgAction::Why {
             arg,
@@ -1796,6 +2379,7 @@ async fn send_pkg_request(
             include_trace,
             trace_kind,
             trace_parent,
+            dependents,
         } => Request::PkgWhy {
             arg: arg.clone(),
             cwd: cwd.to_string_lossy().into_owned(),
@@ -1810,6 +2394,7 @@ async fn send_pkg_request(
             trace_parent: trace_parent
                 .as_ref()
                 .map(|p| p.to_string_lossy().into_owned()),
+            dependents: *dependents,
         },
         PkgAction::Doctor {
             cwd,
@@ -1834,12 +2419,14 @@ async fn send_pkg_request(
             frozen,
             include_dev,
             include_optional,
+            auto_install_peers,
         } => Request::PkgInstall {
             cwd: cwd.to_string_lossy().into_owned(),
             channel: channel.as_str().to_string(),
             frozen: *frozen,
             include_dev: *include_dev,
             include_optional: *include_optional,
+            auto_install_peers: *auto_install_peers,
         },
         PkgAction::Outdated { cwd } => Request::PkgOutdated {
             cwd: cwd.to_string_lossy().into_owned(),
@@ -1851,6 +2438,9 @@ async fn send_pkg_request(
             tag,
             access,
             registry,
+            otp,
+            provenance,
+            ..
         } => Request::PkgPublish {
             cwd: cwd.to_string_lossy().into_owned(),
             registry: registry.clone(),
@@ -1858,6 +2448,21 @@ async fn send_pkg_request(
             dry_run: *dry_run,
             tag: Some(tag.clone()),
             access: access.clone(),
+            otp: otp.clone(),
+            provenance: *provenance,
+        },
+        PkgAction::Pack {
+            cwd,
+            destination,
+            dry_run,
+            source_date_epoch,
+        } => Request::PkgPack {
+            cwd: cwd.to_string_lossy().into_owned(),
+            destination: destination
+                .as_ref()
+                .map(|d| d.to_string_lossy().into_owned()),
+            dry_run: *dry_run,
+            source_date_epoch: *source_date_epoch,
         },
     };
 
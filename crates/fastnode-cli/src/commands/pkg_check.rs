@@ -0,0 +1,120 @@
+//! `howth install --check`: verify `node_modules` against the lockfile.
+//!
+//! Reads the lockfile, `node_modules` and the package cache directly (no
+//! daemon needed) and never writes. Exits 1 when the tree differs from the
+//! lockfile, so CI can run it as a pre-flight check.
+
+use fastnode_core::config::Channel;
+use fastnode_core::pkg::{
+    check_install, detect_workspaces, find_workspace_root, load_npmrc_files, CheckDiffKind,
+    InstallCheckOptions, InstallCheckReport, Lockfile, PackageCache, LOCKFILE_NAME,
+};
+use serde::Serialize;
+use std::path::Path;
+
+/// Check result for JSON output (`{ ok, check }`).
+#[derive(Serialize)]
+struct InstallCheckJsonResult {
+    ok: bool,
+    check: Option<InstallCheckReport>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    error: Option<String>,
+}
+
+/// Run `howth install --check`.
+///
+/// Exits 1 if `node_modules` differs from the lockfile, 2 if the lockfile
+/// can't be read.
+pub fn run_install_check(
+    cwd: &Path,
+    include_dev: bool,
+    include_optional: bool,
+    sample: usize,
+    channel: Channel,
+    json: bool,
+) {
+    let lockfile = match Lockfile::read_from(&cwd.join(LOCKFILE_NAME)) {
+        Ok(lockfile) => lockfile,
+        Err(e) => {
+            if json {
+                let result = InstallCheckJsonResult {
+                    ok: false,
+                    check: None,
+                    error: Some(format!("{}: {}", e.code(), e.message())),
+                };
+                println!("{}", serde_json::to_string_pretty(&result).unwrap());
+            } else {
+                eprintln!("error: {e}");
+            }
+            std::process::exit(2);
+        }
+    };
+
+    let workspaces = find_workspace_root(cwd).and_then(|root| detect_workspaces(&root));
+    let opts = InstallCheckOptions {
+        include_dev,
+        include_optional,
+        node_linker: load_npmrc_files(cwd).node_linker.unwrap_or_default(),
+        sample,
+    };
+    let report = check_install(
+        cwd,
+        &lockfile,
+        workspaces.as_ref(),
+        &PackageCache::new(channel),
+        &opts,
+    );
+    let ok = report.ok;
+
+    if json {
+        let result = InstallCheckJsonResult {
+            ok,
+            check: Some(report),
+            error: None,
+        };
+        println!("{}", serde_json::to_string_pretty(&result).unwrap());
+    } else {
+        print_report(&report);
+    }
+    if !ok {
+        std::process::exit(1);
+    }
+}
+
+/// Print a human-readable check report.
+fn print_report(report: &InstallCheckReport) {
+    for diff in &report.diffs {
+        let expected = diff.expected.as_deref().unwrap_or("-");
+        let actual = diff.actual.as_deref().unwrap_or("-");
+        let line = match diff.kind {
+            CheckDiffKind::Missing => format!("- {}@{expected}", diff.name),
+            CheckDiffKind::WrongVersion => {
+                format!("~ {}@{expected} (installed {actual})", diff.name)
+            }
+            CheckDiffKind::Modified => format!("! {}@{expected} ({actual} modified)", diff.name),
+            CheckDiffKind::Extraneous => format!("+ {}@{actual}", diff.name),
+        };
+        println!("{line}  {}", diff.path);
+    }
+
+    let verified = if report.unverified > 0 {
+        format!(
+            "{} verified against the cache, {} not cached",
+            report.verified, report.unverified
+        )
+    } else {
+        format!("{} verified against the cache", report.verified)
+    };
+    if report.ok {
+        println!(
+            "node_modules matches the lockfile ({} packages, {verified})",
+            report.checked
+        );
+    } else {
+        eprintln!(
+            "error: node_modules differs from the lockfile ({} difference(s))",
+            report.diffs.len()
+        );
+        eprintln!("hint: run howth install");
+    }
+}
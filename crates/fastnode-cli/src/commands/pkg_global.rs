@@ -0,0 +1,185 @@
+//! Helpers for global installs (`howth pkg add --global`) and `howth pkg ls`.
+//!
+//! Global packages live in a prefix under the channel cache and are installed
+//! by the daemon like any other project. This module prepares the prefix,
+//! manages the executable shims in `<prefix>/bin` and prints PATH advice.
+
+use fastnode_core::config::Channel;
+use fastnode_core::paths;
+use fastnode_core::pkg::{
+    ensure_global_prefix, global_bin_dir, is_on_path, link_global_bins, list_packages,
+    unlink_global_bins, PkgError,
+};
+use fastnode_proto::{InstalledPackage, PkgErrorInfo};
+use serde::Serialize;
+use std::path::{Path, PathBuf};
+
+/// Create the global prefix for `channel` and return its path.
+///
+/// # Errors
+/// Returns an error if the prefix cannot be created.
+pub fn prepare_global_prefix(channel: Channel) -> Result<PathBuf, PkgError> {
+    let prefix = paths::global_dir(channel);
+    ensure_global_prefix(&prefix)?;
+    Ok(prefix)
+}
+
+/// Link the executables of newly installed global packages.
+///
+/// Returns the shim names and any linking errors.
+pub fn link_installed_bins(
+    prefix: &Path,
+    installed: &[InstalledPackage],
+) -> (Vec<String>, Vec<PkgErrorInfo>) {
+    let mut bins = Vec::new();
+    let mut errors = Vec::new();
+    for pkg in installed {
+        match link_global_bins(prefix, &pkg.name) {
+            Ok(links) => bins.extend(
+                links
+                    .iter()
+                    .filter_map(|link| link.file_name())
+                    .map(|name| name.to_string_lossy().into_owned()),
+            ),
+            Err(e) => errors.push(PkgErrorInfo {
+                spec: pkg.name.clone(),
+                code: e.code().to_string(),
+                message: format!("Installed but failed to link executables: {e}"),
+            }),
+        }
+    }
+    bins.sort();
+    (bins, errors)
+}
+
+/// Remove the executable shims of global packages before they are removed.
+///
+/// # Errors
+/// Returns the first error encountered.
+pub fn unlink_removed_bins(prefix: &Path, packages: &[String]) -> Result<(), PkgError> {
+    for name in packages {
+        unlink_global_bins(prefix, name)?;
+    }
+    Ok(())
+}
+
+/// Tell the user how to put the global bin dir on their PATH, if needed.
+pub fn print_path_advice(prefix: &Path) {
+    let bin_dir = global_bin_dir(prefix);
+    if is_on_path(&bin_dir) {
+        return;
+    }
+    eprintln!();
+    eprintln!("note: {} is not on your PATH", bin_dir.display());
+    if cfg!(windows) {
+        eprintln!("hint: add it to the Path environment variable to run global executables");
+    } else {
+        eprintln!(
+            "hint: add `export PATH=\"{}:$PATH\"` to your shell profile",
+            bin_dir.display()
+        );
+    }
+}
+
+/// A listed package for JSON output.
+#[derive(Serialize)]
+struct PkgListEntry {
+    name: String,
+    range: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    version: Option<String>,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    bins: Vec<String>,
+}
+
+/// List result for JSON output.
+#[derive(Serialize)]
+struct PkgListJsonResult {
+    ok: bool,
+    root: String,
+    global: bool,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    bin_dir: Option<String>,
+    packages: Vec<PkgListEntry>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    error: Option<String>,
+}
+
+/// Run `howth pkg ls [--global]`.
+///
+/// Lists the top-level packages of the project, or of the global prefix.
+/// Reads `package.json` and `node_modules` directly (no daemon needed).
+pub fn run_list(cwd: &Path, global: bool, channel: Channel, json: bool) {
+    let root = if global {
+        paths::global_dir(channel)
+    } else {
+        cwd.to_path_buf()
+    };
+    let bin_dir = global.then(|| global_bin_dir(&root));
+
+    // A missing global prefix just means nothing was installed yet
+    let listed = if global && !root.join("package.json").exists() {
+        Ok(Vec::new())
+    } else {
+        list_packages(&root)
+    };
+
+    match listed {
+        Ok(packages) => {
+            if json {
+                let result = PkgListJsonResult {
+                    ok: true,
+                    root: root.to_string_lossy().into_owned(),
+                    global,
+                    bin_dir: bin_dir.map(|d| d.to_string_lossy().into_owned()),
+                    packages: packages
+                        .into_iter()
+                        .map(|pkg| PkgListEntry {
+                            name: pkg.name,
+                            range: pkg.range,
+                            version: pkg.version,
+                            bins: pkg.bins,
+                        })
+                        .collect(),
+                    error: None,
+                };
+                println!("{}", serde_json::to_string_pretty(&result).unwrap());
+                return;
+            }
+
+            println!("{}", root.display());
+            if packages.is_empty() {
+                println!("(no packages)");
+            }
+            for pkg in &packages {
+                match &pkg.version {
+                    Some(version) => print!("{}@{}", pkg.name, version),
+                    None => print!("{}@{} (not installed)", pkg.name, pkg.range),
+                }
+                if global && !pkg.bins.is_empty() {
+                    print!("  [{}]", pkg.bins.join(", "));
+                }
+                println!();
+            }
+            if global && !packages.is_empty() {
+                print_path_advice(&root);
+            }
+        }
+        Err(e) => {
+            if json {
+                let result = PkgListJsonResult {
+                    ok: false,
+                    root: root.to_string_lossy().into_owned(),
+                    global,
+                    bin_dir: bin_dir.map(|d| d.to_string_lossy().into_owned()),
+                    packages: Vec::new(),
+                    error: Some(format!("{}: {}", e.code(), e.message())),
+                };
+                println!("{}", serde_json::to_string_pretty(&result).unwrap());
+            } else {
+                eprintln!("error: {e}");
+            }
+            std::process::exit(2);
+        }
+    }
+}
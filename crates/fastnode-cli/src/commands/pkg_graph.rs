@@ -0,0 +1,427 @@
+//! Client-side renderers for `howth pkg graph --format`.
+//!
+//! All formats are rendered from the `PackageGraph` payload returned by the
+//! daemon, so the protocol schema stays unchanged.
+
+use fastnode_proto::{GraphDepEdge, GraphPackageNode, PackageGraph};
+use serde_json::{json, Map, Value};
+use std::collections::{BTreeSet, HashMap, HashSet};
+use std::fmt::Write as _;
+use std::path::Path;
+
+/// Output format for `pkg graph`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum GraphFormat {
+    /// Indented tree (default).
+    Tree,
+    /// Flat `name@version` list.
+    List,
+    /// JSON compatible with `npm ls --all --json`.
+    NpmLsJson,
+    /// Graphviz DOT.
+    Dot,
+    /// Mermaid flowchart.
+    Mermaid,
+}
+
+/// Parse a `--format` value.
+#[must_use]
+pub fn parse_format(s: &str) -> Option<GraphFormat> {
+    match s {
+        "tree" => Some(GraphFormat::Tree),
+        "list" => Some(GraphFormat::List),
+        "npm-ls-json" => Some(GraphFormat::NpmLsJson),
+        "dot" => Some(GraphFormat::Dot),
+        "mermaid" => Some(GraphFormat::Mermaid),
+        _ => None,
+    }
+}
+
+impl GraphFormat {
+    /// Format sent in the `PkgGraph` request. The daemon only knows the
+    /// human formats; the others are rendered client-side from the tree
+    /// payload.
+    #[must_use]
+    pub fn wire_format(self) -> &'static str {
+        match self {
+            Self::List => "list",
+            Self::Tree | Self::NpmLsJson | Self::Dot | Self::Mermaid => "tree",
+        }
+    }
+}
+
+/// Lookup of graph nodes by package path.
+type NodeIndex<'a> = HashMap<&'a str, &'a GraphPackageNode>;
+
+fn index_nodes(graph: &PackageGraph) -> NodeIndex<'_> {
+    graph
+        .nodes
+        .iter()
+        .map(|n| (n.id.path.as_str(), n))
+        .collect()
+}
+
+/// Label used for a package in DOT and Mermaid output.
+fn node_label(node: &GraphPackageNode) -> String {
+    format!("{}@{}", node.id.name, node.id.version)
+}
+
+/// Label used for a dependency that is not installed.
+fn missing_label(dep: &GraphDepEdge) -> String {
+    format!(
+        "{}@{} (missing)",
+        dep.name,
+        dep.req.as_deref().unwrap_or("*")
+    )
+}
+
+/// Top-level packages: the root's dependencies installed directly in
+/// `<root>/node_modules`.
+///
+/// The payload has no root node, so the root's dependency names are read
+/// from its package.json. When that is unavailable, packages no other
+/// package depends on are used instead.
+fn top_level<'a>(
+    graph: &'a PackageGraph,
+    root_deps: Option<&BTreeSet<String>>,
+) -> Vec<&'a GraphPackageNode> {
+    let node_modules = Path::new(&graph.root).join("node_modules");
+    if let Some(root_deps) = root_deps {
+        return graph
+            .nodes
+            .iter()
+            .filter(|n| {
+                root_deps.contains(&n.id.name)
+                    && Path::new(&n.id.path) == node_modules.join(&n.id.name)
+            })
+            .collect();
+    }
+
+    let depended_on: HashSet<&str> = graph
+        .nodes
+        .iter()
+        .flat_map(|n| &n.dependencies)
+        .filter_map(|d| d.to.as_ref().map(|to| to.path.as_str()))
+        .collect();
+    graph
+        .nodes
+        .iter()
+        .filter(|n| !depended_on.contains(n.id.path.as_str()))
+        .collect()
+}
+
+/// Read the root package.json: `(name, version, dependency names)`.
+fn read_root_manifest(root: &str) -> Option<(Option<String>, Option<String>, BTreeSet<String>)> {
+    let content = std::fs::read_to_string(Path::new(root).join("package.json")).ok()?;
+    let pkg: Value = serde_json::from_str(&content).ok()?;
+    let deps = [
+        "dependencies",
+        "devDependencies",
+        "optionalDependencies",
+        "peerDependencies",
+    ]
+    .iter()
+    .filter_map(|field| pkg.get(field).and_then(Value::as_object))
+    .flat_map(|deps| deps.keys().cloned())
+    .collect();
+    let field = |name: &str| pkg.get(name).and_then(Value::as_str).map(str::to_string);
+    Some((field("name"), field("version"), deps))
+}
+
+/// Render the graph as `npm ls --all --json` output.
+///
+/// Each package's dependencies are expanded on first occurrence only; later
+/// occurrences list just the version, like npm's "deduped" entries. Missing
+/// dependencies are reported with `"missing": true` and graph errors as
+/// `problems`.
+#[must_use]
+pub fn render_npm_ls_json(graph: &PackageGraph) -> String {
+    let manifest = read_root_manifest(&graph.root);
+    let (name, version, root_deps) = match manifest {
+        Some((name, version, deps)) => (name, version, Some(deps)),
+        None => (None, None, None),
+    };
+    let index = index_nodes(graph);
+    let mut expanded: HashSet<&str> = HashSet::new();
+
+    let mut dependencies = Map::new();
+    for node in top_level(graph, root_deps.as_ref()) {
+        dependencies.insert(
+            node.id.name.clone(),
+            npm_ls_package(node, &index, &mut expanded),
+        );
+    }
+
+    let mut out = Map::new();
+    out.insert(
+        "name".to_string(),
+        json!(name.unwrap_or_else(|| {
+            Path::new(&graph.root)
+                .file_name()
+                .map(|n| n.to_string_lossy().into_owned())
+                .unwrap_or_default()
+        })),
+    );
+    if let Some(version) = version {
+        out.insert("version".to_string(), json!(version));
+    }
+    if !graph.errors.is_empty() {
+        let problems: Vec<String> = graph
+            .errors
+            .iter()
+            .map(|e| format!("{}: {}: {}", e.code, e.message, e.path))
+            .collect();
+        out.insert("problems".to_string(), json!(problems));
+    }
+    if !dependencies.is_empty() {
+        out.insert("dependencies".to_string(), Value::Object(dependencies));
+    }
+
+    serde_json::to_string_pretty(&Value::Object(out)).unwrap_or_default()
+}
+
+fn npm_ls_package<'a>(
+    node: &'a GraphPackageNode,
+    index: &NodeIndex<'a>,
+    expanded: &mut HashSet<&'a str>,
+) -> Value {
+    let mut entry = Map::new();
+    entry.insert("version".to_string(), json!(node.id.version));
+    if !expanded.insert(node.id.path.as_str()) {
+        return Value::Object(entry);
+    }
+    entry.insert("path".to_string(), json!(node.id.path));
+
+    let mut dependencies = Map::new();
+    for dep in &node.dependencies {
+        let value = match dep.to.as_ref().and_then(|to| index.get(to.path.as_str())) {
+            Some(child) => npm_ls_package(child, index, expanded),
+            None => match &dep.to {
+                // Installed but not traversed (e.g. depth limit)
+                Some(to) => json!({ "version": to.version }),
+                None if dep.kind == "optional" || dep.kind == "peer" => continue,
+                None => json!({
+                    "required": dep.req.as_deref().unwrap_or("*"),
+                    "missing": true,
+                }),
+            },
+        };
+        dependencies.insert(dep.name.clone(), value);
+    }
+    if !dependencies.is_empty() {
+        entry.insert("dependencies".to_string(), Value::Object(dependencies));
+    }
+    Value::Object(entry)
+}
+
+/// Quote a string for DOT.
+fn dot_quote(s: &str) -> String {
+    format!("\"{}\"", s.replace('\\', "\\\\").replace('"', "\\\""))
+}
+
+/// Render the graph as Graphviz DOT.
+///
+/// Non-`dep` edges are labelled with their kind; missing dependencies are
+/// drawn as dashed red nodes.
+#[must_use]
+pub fn render_dot(graph: &PackageGraph) -> String {
+    let index = index_nodes(graph);
+    let mut out = String::from("digraph packages {\n    rankdir=LR;\n    node [shape=box];\n");
+
+    for node in &graph.nodes {
+        let _ = writeln!(out, "    {};", dot_quote(&node_label(node)));
+    }
+    for orphan in &graph.orphans {
+        let label = format!("{}@{}", orphan.name, orphan.version);
+        let _ = writeln!(out, "    {} [style=dotted];", dot_quote(&label));
+    }
+
+    for node in &graph.nodes {
+        let from = dot_quote(&node_label(node));
+        for dep in &node.dependencies {
+            let mut attrs = Vec::new();
+            if dep.kind != "dep" {
+                attrs.push(format!("label={}", dot_quote(&dep.kind)));
+            }
+            let to = if let Some(to) = dep.to.as_ref() {
+                index.get(to.path.as_str()).map_or_else(
+                    || format!("{}@{}", to.name, to.version),
+                    |child| node_label(child),
+                )
+            } else {
+                let label = missing_label(dep);
+                let _ = writeln!(out, "    {} [style=dashed, color=red];", dot_quote(&label));
+                attrs.push("style=dashed".to_string());
+                label
+            };
+            if attrs.is_empty() {
+                let _ = writeln!(out, "    {from} -> {};", dot_quote(&to));
+            } else {
+                let _ = writeln!(
+                    out,
+                    "    {from} -> {} [{}];",
+                    dot_quote(&to),
+                    attrs.join(", ")
+                );
+            }
+        }
+    }
+
+    out.push_str("}\n");
+    out
+}
+
+/// Escape a label for Mermaid (double quotes are not allowed inside labels).
+fn mermaid_label(s: &str) -> String {
+    s.replace('"', "#quot;")
+}
+
+/// Render the graph as a Mermaid flowchart.
+///
+/// Nodes get stable `n<index>` IDs in graph order; non-`dep` edges are dotted
+/// and labelled with their kind.
+#[must_use]
+pub fn render_mermaid(graph: &PackageGraph) -> String {
+    let mut out = String::from("graph LR\n");
+    let mut ids: HashMap<String, String> = HashMap::new();
+
+    let mut declare = |out: &mut String, key: String, label: &str| -> String {
+        let next = ids.len();
+        ids.entry(key)
+            .or_insert_with(|| {
+                let id = format!("n{next}");
+                let _ = writeln!(out, "    {id}[\"{}\"]", mermaid_label(label));
+                id
+            })
+            .clone()
+    };
+
+    for node in &graph.nodes {
+        declare(&mut out, node.id.path.clone(), &node_label(node));
+    }
+    for orphan in &graph.orphans {
+        let label = format!("{}@{}", orphan.name, orphan.version);
+        declare(&mut out, orphan.path.clone(), &label);
+    }
+
+    for node in &graph.nodes {
+        let from = declare(&mut out, node.id.path.clone(), &node_label(node));
+        for dep in &node.dependencies {
+            let to = if let Some(to) = &dep.to {
+                let label = format!("{}@{}", to.name, to.version);
+                declare(&mut out, to.path.clone(), &label)
+            } else {
+                let label = missing_label(dep);
+                declare(&mut out, format!("missing:{label}"), &label)
+            };
+            if dep.kind == "dep" {
+                let _ = writeln!(out, "    {from} --> {to}");
+            } else {
+                let _ = writeln!(out, "    {from} -.->|{}| {to}", dep.kind);
+            }
+        }
+    }
+
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use fastnode_proto::GraphPackageId;
+
+    fn id(name: &str, version: &str) -> GraphPackageId {
+        GraphPackageId {
+            name: name.to_string(),
+            version: version.to_string(),
+            path: format!("/nonexistent/project/node_modules/{name}"),
+            integrity: None,
+        }
+    }
+
+    fn edge(to: &GraphPackageId, kind: &str) -> GraphDepEdge {
+        GraphDepEdge {
+            name: to.name.clone(),
+            req: Some(format!("^{}", to.version)),
+            to: Some(to.clone()),
+            kind: kind.to_string(),
+        }
+    }
+
+    fn sample_graph() -> PackageGraph {
+        let react = id("react", "18.2.0");
+        let envify = id("loose-envify", "1.4.0");
+        let tokens = id("js-tokens", "4.0.0");
+        PackageGraph {
+            schema_version: 1,
+            root: "/nonexistent/project".to_string(),
+            nodes: vec![
+                GraphPackageNode {
+                    id: envify.clone(),
+                    dependencies: vec![edge(&tokens, "dep")],
+                },
+                GraphPackageNode {
+                    id: tokens.clone(),
+                    dependencies: Vec::new(),
+                },
+                GraphPackageNode {
+                    id: react,
+                    dependencies: vec![
+                        edge(&envify, "dep"),
+                        GraphDepEdge {
+                            name: "scheduler".to_string(),
+                            req: Some("^0.23.0".to_string()),
+                            to: None,
+                            kind: "dep".to_string(),
+                        },
+                    ],
+                },
+            ],
+            orphans: Vec::new(),
+            errors: Vec::new(),
+        }
+    }
+
+    #[test]
+    fn test_parse_format() {
+        assert_eq!(parse_format("npm-ls-json"), Some(GraphFormat::NpmLsJson));
+        assert_eq!(parse_format("dot"), Some(GraphFormat::Dot));
+        assert_eq!(parse_format("svg"), None);
+    }
+
+    #[test]
+    fn test_render_npm_ls_json() {
+        let value: Value = serde_json::from_str(&render_npm_ls_json(&sample_graph())).unwrap();
+        assert_eq!(value["name"], "project");
+
+        // Without a root package.json, only react has no dependents
+        let deps = value["dependencies"].as_object().unwrap();
+        assert_eq!(deps.keys().collect::<Vec<_>>(), vec!["react"]);
+
+        let react = &deps["react"];
+        assert_eq!(react["version"], "18.2.0");
+        let envify = &react["dependencies"]["loose-envify"];
+        assert_eq!(envify["dependencies"]["js-tokens"]["version"], "4.0.0");
+        assert_eq!(react["dependencies"]["scheduler"]["missing"], true);
+        assert_eq!(react["dependencies"]["scheduler"]["required"], "^0.23.0");
+    }
+
+    #[test]
+    fn test_render_dot() {
+        let dot = render_dot(&sample_graph());
+        assert!(dot.starts_with("digraph packages {"));
+        assert!(dot.contains(r#""react@18.2.0" -> "loose-envify@1.4.0";"#));
+        assert!(dot.contains(r#""scheduler@^0.23.0 (missing)" [style=dashed, color=red];"#));
+        assert!(dot.trim_end().ends_with('}'));
+    }
+
+    #[test]
+    fn test_render_mermaid() {
+        let mermaid = render_mermaid(&sample_graph());
+        assert!(mermaid.starts_with("graph LR\n"));
+        assert!(mermaid.contains(r#"n0["loose-envify@1.4.0"]"#));
+        assert!(mermaid.contains("n0 --> n1"));
+        assert!(mermaid.contains("n2 --> n0"));
+        assert!(mermaid.contains(r#"n3["scheduler@^0.23.0 (missing)"]"#));
+    }
+}
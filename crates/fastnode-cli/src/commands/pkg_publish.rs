@@ -0,0 +1,132 @@
+//! Helpers for `howth pkg pack` and `howth pkg publish`.
+//!
+//! - npm-style tarball reports (contents and details)
+//! - the `prepublishOnly` lifecycle script, run before publishing
+//! - the one-time password prompt for registries that require 2FA
+
+use super::run::execute_script;
+use fastnode_core::scripts::PackageScripts;
+use fastnode_proto::PackFileInfo;
+use std::io::{BufRead, IsTerminal, Write};
+use std::path::Path;
+
+/// Format bytes like npm's pack report (B/kB/MB, decimal units).
+#[allow(clippy::cast_precision_loss)]
+pub fn format_size(bytes: u64) -> String {
+    if bytes >= 1_000_000 {
+        format!("{:.1}MB", bytes as f64 / 1_000_000.0)
+    } else if bytes >= 1_000 {
+        format!("{:.1}kB", bytes as f64 / 1_000.0)
+    } else {
+        format!("{bytes}B")
+    }
+}
+
+/// Tarball details printed after the contents list.
+pub struct TarballDetails<'a> {
+    pub name: &'a str,
+    pub version: &'a str,
+    pub filename: Option<&'a str>,
+    pub tarball_size: u64,
+    pub unpacked_size: u64,
+    pub files: &'a [PackFileInfo],
+}
+
+/// Print the tarball contents and details, as `npm pack --dry-run` does.
+pub fn print_tarball_report(details: &TarballDetails<'_>) {
+    println!("package: {}@{}", details.name, details.version);
+    println!("Tarball Contents");
+    for file in details.files {
+        println!("{:>8} {}", format_size(file.size), file.path);
+    }
+    println!("Tarball Details");
+    println!("name:          {}", details.name);
+    println!("version:       {}", details.version);
+    if let Some(filename) = details.filename {
+        println!("filename:      {filename}");
+    }
+    println!("package size:  {}", format_size(details.tarball_size));
+    println!("unpacked size: {}", format_size(details.unpacked_size));
+    println!("total files:   {}", details.files.len());
+}
+
+/// Run the package's `prepublishOnly` script, if it has one.
+///
+/// Output is captured in JSON mode so it doesn't corrupt the result.
+///
+/// # Errors
+/// Returns a message if the script fails.
+pub fn run_prepublish_only(cwd: &Path, json: bool) -> Result<(), String> {
+    let Ok(pkg) = PackageScripts::load(cwd) else {
+        // Missing/invalid package.json is reported by the publish itself
+        return Ok(());
+    };
+    if pkg.get("prepublishOnly").is_none() {
+        return Ok(());
+    }
+
+    let result = execute_script(cwd, &pkg, "prepublishOnly", &[], json, !json);
+    if result.ok {
+        return Ok(());
+    }
+    Err(match result.error {
+        Some(error) => error.message,
+        None => format!(
+            "prepublishOnly script failed with exit code {}",
+            result.exit_code.unwrap_or(-1)
+        ),
+    })
+}
+
+/// Ask for a one-time password on the terminal.
+///
+/// Returns `None` when not interactive or nothing was entered.
+pub fn prompt_otp() -> Option<String> {
+    if !std::io::stdin().is_terminal() || !std::io::stderr().is_terminal() {
+        return None;
+    }
+    eprintln!("This operation requires a one-time password from your authenticator.");
+    eprint!("Enter OTP: ");
+    let _ = std::io::stderr().flush();
+
+    let mut input = String::new();
+    std::io::stdin().lock().read_line(&mut input).ok()?;
+    let code = input.trim();
+    (!code.is_empty()).then(|| code.to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_format_size() {
+        assert_eq!(format_size(512), "512B");
+        assert_eq!(format_size(1_536), "1.5kB");
+        assert_eq!(format_size(2_500_000), "2.5MB");
+    }
+
+    #[test]
+    fn test_run_prepublish_only_missing_script_is_noop() {
+        let dir = tempfile::tempdir().unwrap();
+        std::fs::write(
+            dir.path().join("package.json"),
+            r#"{"name":"a","version":"1.0.0"}"#,
+        )
+        .unwrap();
+        assert_eq!(run_prepublish_only(dir.path(), true), Ok(()));
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_run_prepublish_only_failure() {
+        let dir = tempfile::tempdir().unwrap();
+        std::fs::write(
+            dir.path().join("package.json"),
+            r#"{"name":"a","version":"1.0.0","scripts":{"prepublishOnly":"exit 3"}}"#,
+        )
+        .unwrap();
+        let err = run_prepublish_only(dir.path(), true).unwrap_err();
+        assert!(err.contains('3'), "{err}");
+    }
+}
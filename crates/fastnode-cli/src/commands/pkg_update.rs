@@ -0,0 +1,252 @@
+//! Interactive selection for `howth pkg update --interactive`.
+//!
+//! Builds a list of update candidates from the `pkg outdated` report, groups
+//! them by semver bump severity and lets the user pick which ones to apply.
+//! The chosen names are then sent through the regular `PkgUpdate` request.
+
+use fastnode_proto::OutdatedPackage;
+use std::io::{BufRead, Write};
+
+/// Severity of a version bump, ordered from most to least disruptive.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum BumpKind {
+    Major,
+    Minor,
+    Patch,
+    /// Prerelease or non-numeric version change.
+    Other,
+}
+
+impl BumpKind {
+    /// Heading shown above the group in the selection list.
+    fn heading(self) -> &'static str {
+        match self {
+            Self::Major => "Major (breaking)",
+            Self::Minor => "Minor (features)",
+            Self::Patch => "Patch (fixes)",
+            Self::Other => "Other",
+        }
+    }
+}
+
+/// A package that can be updated from `current` to `target`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct UpdateCandidate {
+    pub name: String,
+    pub current: String,
+    pub target: String,
+    pub dep_type: String,
+    pub bump: BumpKind,
+}
+
+/// Parse the `major.minor.patch` core of a version, ignoring prerelease/build.
+fn version_core(version: &str) -> Option<(u64, u64, u64)> {
+    let core = version.trim_start_matches('v').split(['-', '+']).next()?;
+    let mut parts = core.split('.').map(str::parse::<u64>);
+    let major = parts.next()?.ok()?;
+    let minor = parts.next()?.ok()?;
+    let patch = parts.next()?.ok()?;
+    Some((major, minor, patch))
+}
+
+/// Classify the bump from `from` to `to`.
+///
+/// Follows npm's 0.x convention: a minor bump below 1.0.0 is breaking.
+pub fn classify_bump(from: &str, to: &str) -> BumpKind {
+    let (Some(a), Some(b)) = (version_core(from), version_core(to)) else {
+        return BumpKind::Other;
+    };
+    if a.0 != b.0 || (a.0 == 0 && a.1 != b.1) {
+        BumpKind::Major
+    } else if a.1 != b.1 {
+        BumpKind::Minor
+    } else if a.2 != b.2 {
+        BumpKind::Patch
+    } else {
+        BumpKind::Other
+    }
+}
+
+/// Build update candidates from an outdated report.
+///
+/// Only root dependencies are offered since `pkg update` edits the root
+/// package.json. The target is `latest` with `--latest`, otherwise `wanted`.
+/// Candidates are grouped by bump severity, then sorted by name.
+pub fn update_candidates(outdated: &[OutdatedPackage], latest: bool) -> Vec<UpdateCandidate> {
+    let mut candidates: Vec<UpdateCandidate> = outdated
+        .iter()
+        .filter(|pkg| pkg.workspace.is_none())
+        .filter_map(|pkg| {
+            let target = if latest { &pkg.latest } else { &pkg.wanted };
+            if *target == pkg.current {
+                return None;
+            }
+            Some(UpdateCandidate {
+                name: pkg.name.clone(),
+                current: pkg.current.clone(),
+                target: target.clone(),
+                dep_type: pkg.dep_type.clone(),
+                bump: classify_bump(&pkg.current, target),
+            })
+        })
+        .collect();
+    candidates.sort_by(|a, b| (a.bump, &a.name).cmp(&(b.bump, &b.name)));
+    candidates
+}
+
+/// Parse a selection like `1,3-5`, `all` or an empty line (nothing).
+///
+/// Returns zero-based indices in ascending order without duplicates.
+pub fn parse_selection(input: &str, count: usize) -> Result<Vec<usize>, String> {
+    let input = input.trim();
+    if input.is_empty() || input.eq_ignore_ascii_case("none") {
+        return Ok(Vec::new());
+    }
+    if input.eq_ignore_ascii_case("a") || input.eq_ignore_ascii_case("all") {
+        return Ok((0..count).collect());
+    }
+
+    let parse_index = |s: &str| -> Result<usize, String> {
+        let n: usize = s
+            .trim()
+            .parse()
+            .map_err(|_| format!("invalid selection '{}'", s.trim()))?;
+        if n == 0 || n > count {
+            return Err(format!("selection {n} is out of range (1-{count})"));
+        }
+        Ok(n - 1)
+    };
+
+    let mut selected = Vec::new();
+    for part in input.split([',', ' ']).filter(|p| !p.trim().is_empty()) {
+        if let Some((start, end)) = part.split_once('-') {
+            let (start, end) = (parse_index(start)?, parse_index(end)?);
+            if start > end {
+                return Err(format!("invalid range '{}'", part.trim()));
+            }
+            selected.extend(start..=end);
+        } else {
+            selected.push(parse_index(part)?);
+        }
+    }
+    selected.sort_unstable();
+    selected.dedup();
+    Ok(selected)
+}
+
+/// Print the grouped, numbered candidate list to stderr.
+fn print_candidates(candidates: &[UpdateCandidate]) {
+    let mut current_group = None;
+    for (i, c) in candidates.iter().enumerate() {
+        if current_group != Some(c.bump) {
+            eprintln!();
+            eprintln!("{}", c.bump.heading());
+            current_group = Some(c.bump);
+        }
+        eprintln!(
+            "  {:>3}) {:<30} {:>12} -> {:<12} {}",
+            i + 1,
+            c.name,
+            c.current,
+            c.target,
+            c.dep_type
+        );
+    }
+    eprintln!();
+}
+
+/// Ask the user which candidates to update.
+///
+/// Re-prompts on invalid input and returns the selected package names
+/// (empty if nothing was chosen or stdin closed).
+pub fn prompt_selection(candidates: &[UpdateCandidate]) -> Vec<String> {
+    print_candidates(candidates);
+
+    let stdin = std::io::stdin();
+    loop {
+        eprint!("Select packages to update (e.g. 1,3-5, 'all', or empty for none): ");
+        let _ = std::io::stderr().flush();
+
+        let mut input = String::new();
+        match stdin.lock().read_line(&mut input) {
+            Ok(0) | Err(_) => return Vec::new(),
+            Ok(_) => {}
+        }
+        match parse_selection(&input, candidates.len()) {
+            Ok(indices) => {
+                return indices
+                    .into_iter()
+                    .map(|i| candidates[i].name.clone())
+                    .collect()
+            }
+            Err(e) => eprintln!("error: {e}"),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn outdated(name: &str, current: &str, wanted: &str, latest: &str) -> OutdatedPackage {
+        OutdatedPackage {
+            name: name.to_string(),
+            current: current.to_string(),
+            wanted: wanted.to_string(),
+            latest: latest.to_string(),
+            dep_type: "dep".to_string(),
+            workspace: None,
+        }
+    }
+
+    #[test]
+    fn test_classify_bump() {
+        assert_eq!(classify_bump("1.2.3", "2.0.0"), BumpKind::Major);
+        assert_eq!(classify_bump("0.2.3", "0.3.0"), BumpKind::Major);
+        assert_eq!(classify_bump("1.2.3", "1.3.0"), BumpKind::Minor);
+        assert_eq!(classify_bump("1.2.3", "1.2.4"), BumpKind::Patch);
+        assert_eq!(classify_bump("1.2.3-beta.1", "1.2.3"), BumpKind::Other);
+        assert_eq!(classify_bump("none", "1.0.0"), BumpKind::Other);
+    }
+
+    #[test]
+    fn test_update_candidates_groups_and_targets() {
+        let mut ws_pkg = outdated("ws-only", "1.0.0", "1.0.1", "1.0.1");
+        ws_pkg.workspace = Some("app".to_string());
+        let report = vec![
+            outdated("zod", "3.0.0", "3.0.1", "4.0.0"),
+            outdated("react", "18.2.0", "18.3.1", "19.0.0"),
+            outdated("pinned", "1.0.0", "1.0.0", "1.1.0"),
+            ws_pkg,
+        ];
+
+        let wanted = update_candidates(&report, false);
+        let names: Vec<_> = wanted.iter().map(|c| (c.name.as_str(), c.bump)).collect();
+        assert_eq!(
+            names,
+            vec![("react", BumpKind::Minor), ("zod", BumpKind::Patch)]
+        );
+
+        let latest = update_candidates(&report, true);
+        let names: Vec<_> = latest.iter().map(|c| (c.name.as_str(), c.bump)).collect();
+        assert_eq!(
+            names,
+            vec![
+                ("react", BumpKind::Major),
+                ("zod", BumpKind::Major),
+                ("pinned", BumpKind::Minor),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_parse_selection() {
+        assert_eq!(parse_selection("", 3), Ok(vec![]));
+        assert_eq!(parse_selection("all", 3), Ok(vec![0, 1, 2]));
+        assert_eq!(parse_selection("3, 1-2,2", 5), Ok(vec![0, 1, 2]));
+        assert!(parse_selection("0", 3).is_err());
+        assert!(parse_selection("4", 3).is_err());
+        assert!(parse_selection("3-1", 3).is_err());
+        assert!(parse_selection("x", 3).is_err());
+    }
+}
@@ -4,15 +4,24 @@
 use fastnode_core::compiler;
 use fastnode_core::config::Channel;
 use fastnode_core::paths;
-use fastnode_core::{build_run_plan, runplan_codes, RunPlanInput, RunPlanOutput, VERSION};
+use fastnode_core::scripts::{self, PackageScripts, ScriptRunOptions};
+use fastnode_core::{
+    build_run_plan, runplan_codes, ImportGraphSummary, RunPlanInput, RunPlanOutput,
+    ScriptPlanOutput, FULL_GRAPH_DEPTH, VERSION,
+};
 use fastnode_daemon::ipc::{IpcStream, MAX_FRAME_SIZE};
-use fastnode_proto::{encode_frame, Frame, FrameResponse, Request, Response, RunPlan};
+use fastnode_daemon::DaemonCompiler;
+use fastnode_proto::{
+    encode_frame, Frame, FrameResponse, Request, Response, RunErrorInfo, RunPlan, ScriptRunResult,
+    ScriptStepResult, SCRIPT_RUN_SCHEMA_VERSION,
+};
+use fastnode_util::process;
 use miette::{IntoDiagnostic, Result};
 use serde::Serialize;
-use serde_json::Value;
 use std::io;
 use std::path::Path;
 use std::process::{Command, Stdio};
+use std::time::Instant;
 
 /// Exit code for validation errors.
 const EXIT_VALIDATION_ERROR: i32 = 2;
@@ -27,10 +36,23 @@ const EXIT_INTERNAL_ERROR: i32 = 1;
 /// - A package.json script name (e.g., "test", "build", "dev")
 ///
 /// Like bun, we first check if entry matches a script in package.json.
-/// If it does, we run that script. Otherwise, we treat it as a file path.
+/// If it does, we run that script (with npm `pre`/`post` hooks, `node_modules/.bin`
+/// on PATH and `npm_*` env vars). Otherwise, we treat it as a file path.
+/// With `dry_run`, the plan describes the script's steps instead of running them.
+///
+/// With `if_present`, a script-like entry that is not defined is a silent no-op
+/// instead of falling back to a file path.
+///
+/// Script runs print a [`ScriptRunResult`] with `json`; `capture` collects each
+/// step's stdout/stderr into it instead of streaming them.
 ///
 /// If dry_run is true, just outputs the execution plan.
+/// With `graph_depth`, the plan includes a summary of the entry's transitive
+/// import graph (totals, unresolved imports by reason, cycles); when running,
+/// the summary is printed to stderr first.
 /// Otherwise, transpiles (if needed) and executes the file via Node (or native V8 if enabled).
+/// Node runs of a TS entry use the daemon's warm compiler and cache the
+/// output under `node_modules/.cache/howth/run`.
 ///
 /// When compiled with native-runtime feature:
 /// - Native V8 runtime is used by default
@@ -46,12 +68,27 @@ pub fn run(
     native: bool,
     node: bool,
     local: bool,
+    if_present: bool,
+    capture: bool,
+    graph_depth: Option<u32>,
     channel: Channel,
     json: bool,
 ) -> Result<()> {
     // First, check if entry is a package.json script
-    if let Some(script_cmd) = get_package_script(cwd, entry) {
-        return run_script(cwd, entry, &script_cmd, args, json);
+    if let Some(pkg) = get_package_script(cwd, entry) {
+        if dry_run {
+            // The plan resolves the script the same way
+            let entry_path = Path::new(entry);
+            return if daemon {
+                run_via_daemon(cwd, entry_path, args, true, None, channel, json)
+            } else {
+                run_local(cwd, entry_path, args, true, None, channel, json)
+            };
+        }
+        return run_script(cwd, &pkg, entry, args, capture, json);
+    }
+    if if_present && scripts::is_script_name(entry) {
+        return Ok(());
     }
 
     // Not a script, treat as file path
@@ -63,7 +100,10 @@ pub fn run(
         // --node forces Node.js subprocess
         // Otherwise use native (either explicitly via --native or by default)
         if !node {
-            return run_native(cwd, entry_path, args, local, json);
+            if let Some(depth) = graph_depth {
+                report_graph_before_native(cwd, entry_path, depth, channel, json);
+            }
+            return run_native(cwd, entry_path, args, local, channel, json);
         }
         // Fall through to Node.js execution
     }
@@ -79,95 +119,186 @@ pub fn run(
     let _ = (native, node, local);
 
     if daemon {
-        run_via_daemon(cwd, entry_path, args, dry_run, channel, json)
+        run_via_daemon(cwd, entry_path, args, dry_run, graph_depth, channel, json)
     } else {
-        run_local(cwd, entry_path, args, dry_run, channel, json)
+        run_local(cwd, entry_path, args, dry_run, graph_depth, channel, json)
     }
 }
 
-/// Check if entry matches a script in package.json and return the script command.
-fn get_package_script(cwd: &Path, entry: &str) -> Option<String> {
+/// Print the import graph summary of an entry run by the native runtime,
+/// which doesn't go through a run plan.
+#[cfg(feature = "native-runtime")]
+fn report_graph_before_native(cwd: &Path, entry: &Path, depth: u32, channel: Channel, json: bool) {
+    let input = RunPlanInput {
+        cwd: cwd.to_path_buf(),
+        entry: entry.to_path_buf(),
+        args: Vec::new(),
+        channel,
+        graph_depth: Some(depth),
+    };
+    if let Ok(RunPlanOutput {
+        graph: Some(graph), ..
+    }) = build_run_plan(input)
+    {
+        if !json {
+            print_graph_summary(&graph, true);
+        }
+    }
+}
+
+/// Load package.json scripts if entry names a script defined there.
+fn get_package_script(cwd: &Path, entry: &str) -> Option<PackageScripts> {
     // Don't treat paths as script names
-    if entry.contains('/') || entry.contains('\\') || entry.contains('.') {
+    if !scripts::is_script_name(entry) {
         return None;
     }
 
-    let package_json_path = cwd.join("package.json");
-    let content = std::fs::read_to_string(&package_json_path).ok()?;
-    let package: Value = serde_json::from_str(&content).ok()?;
-
-    package
-        .get("scripts")?
-        .get(entry)?
-        .as_str()
-        .map(std::string::ToString::to_string)
+    let pkg = PackageScripts::load(cwd).ok()?;
+    pkg.get(entry).is_some().then_some(pkg)
 }
 
-/// Run a package.json script.
+/// Run a package.json script with its `pre`/`post` lifecycle hooks.
+///
+/// Stops at the first failing step and exits with its code.
 fn run_script(
     cwd: &Path,
+    pkg: &PackageScripts,
     script_name: &str,
-    script_cmd: &str,
     args: &[String],
+    capture: bool,
     json: bool,
 ) -> Result<()> {
+    let result = execute_script(cwd, pkg, script_name, args, capture && json, !json);
+
+    if json {
+        println!(
+            "{}",
+            serde_json::to_string_pretty(&result).into_diagnostic()?
+        );
+    } else if let Some(error) = &result.error {
+        eprintln!("error: {}", error.message);
+    }
+
+    if !result.ok {
+        std::process::exit(result.exit_code.unwrap_or(EXIT_INTERNAL_ERROR));
+    }
+    Ok(())
+}
+
+/// Run a package.json script's steps and record the outcome.
+///
+/// Steps inherit stdio unless `capture` is set. With `echo`, each command is
+/// printed before it runs.
+pub(crate) fn execute_script(
+    cwd: &Path,
+    pkg: &PackageScripts,
+    script_name: &str,
+    args: &[String],
+    capture: bool,
+    echo: bool,
+) -> ScriptRunResult {
     use std::io::Write;
-    if !json {
-        println!("$ {}", script_cmd);
-        let _ = std::io::stdout().flush();
-    }
-
-    // Use sh on Unix, cmd on Windows
-    #[cfg(unix)]
-    let mut cmd = {
-        let mut c = Command::new("sh");
-        c.arg("-c");
-        // Append any extra args to the script command
-        if args.is_empty() {
-            c.arg(script_cmd);
-        } else {
-            c.arg(format!("{} {}", script_cmd, args.join(" ")));
-        }
-        c
+
+    let start = Instant::now();
+    let mut result = ScriptRunResult {
+        schema_version: SCRIPT_RUN_SCHEMA_VERSION,
+        ok: true,
+        script: script_name.to_string(),
+        cwd: cwd.to_string_lossy().into_owned(),
+        exit_code: None,
+        duration_ms: 0.0,
+        steps: Vec::new(),
+        error: None,
     };
 
-    #[cfg(windows)]
-    let mut cmd = {
-        let mut c = Command::new("cmd");
-        c.arg("/C");
-        if args.is_empty() {
-            c.arg(script_cmd);
-        } else {
-            c.arg(format!("{} {}", script_cmd, args.join(" ")));
+    let opts = ScriptRunOptions {
+        args: args.to_vec(),
+        ..Default::default()
+    };
+    let steps = match scripts::plan_script(pkg, script_name, &opts) {
+        Ok(steps) => steps,
+        Err(e) => {
+            result.ok = false;
+            result.error = Some(RunErrorInfo {
+                code: e.code().to_string(),
+                message: e.to_string(),
+            });
+            return result;
         }
-        c
     };
 
-    cmd.current_dir(cwd)
-        .stdin(Stdio::inherit())
-        .stdout(Stdio::inherit())
-        .stderr(Stdio::inherit());
+    for step in &steps {
+        if echo {
+            println!("$ {}", step.command);
+            let _ = std::io::stdout().flush();
+        }
 
-    // Add node_modules/.bin to PATH so scripts can find local binaries
-    let node_modules_bin = cwd.join("node_modules").join(".bin");
-    if node_modules_bin.exists() {
-        let path = std::env::var("PATH").unwrap_or_default();
-        let new_path = format!("{}:{}", node_modules_bin.display(), path);
-        cmd.env("PATH", new_path);
-    }
+        let step_start = Instant::now();
+        let mut cmd = scripts::step_command(pkg, step, cwd);
+        cmd.stdin(Stdio::inherit());
+        let output = if capture {
+            process::output(&mut cmd).map(|o| {
+                (
+                    o.status,
+                    Some(String::from_utf8_lossy(&o.stdout).into_owned()),
+                    Some(String::from_utf8_lossy(&o.stderr).into_owned()),
+                )
+            })
+        } else {
+            cmd.stdout(Stdio::inherit()).stderr(Stdio::inherit());
+            process::status(&mut cmd).map(|status| (status, None, None))
+        };
 
-    let status = cmd
-        .status()
-        .map_err(|e| miette::miette!("Failed to execute script '{}': {}", script_name, e))?;
+        let (status, stdout, stderr) = match output {
+            Ok(output) => output,
+            Err(e) => {
+                result.ok = false;
+                result.error = Some(RunErrorInfo {
+                    code: "SCRIPT_EXEC_FAILED".to_string(),
+                    message: format!("Failed to execute script '{}': {}", step.event, e),
+                });
+                break;
+            }
+        };
+
+        // Signals map to 128 + n, as a shell would report them
+        let exit_code = process::exit_code(status);
+        result.exit_code = Some(exit_code);
+        result.steps.push(ScriptStepResult {
+            event: step.event.clone(),
+            command: step.command.clone(),
+            exit_code: Some(exit_code),
+            duration_ms: step_start.elapsed().as_secs_f64() * 1000.0,
+            stdout,
+            stderr,
+        });
+
+        if !status.success() {
+            result.ok = false;
+            break;
+        }
+    }
 
-    std::process::exit(status.code().unwrap_or(1));
+    result.duration_ms = start.elapsed().as_secs_f64() * 1000.0;
+    result
 }
 
 /// Run using native V8 runtime (no Node.js subprocess).
 /// When `local` is true, runs within a LocalSet for same-thread HTTP handling.
+///
+/// TS/TSX/JSX modules are transpiled as they are imported, through the
+/// daemon's warm compiler when it is running and in-process otherwise.
 #[cfg(feature = "native-runtime")]
-fn run_native(cwd: &Path, entry: &Path, args: &[String], local: bool, json: bool) -> Result<()> {
-    use fastnode_runtime::{create_local_server_future, Runtime, RuntimeOptions};
+fn run_native(
+    cwd: &Path,
+    entry: &Path,
+    args: &[String],
+    local: bool,
+    channel: Channel,
+    json: bool,
+) -> Result<()> {
+    use fastnode_runtime::{create_local_server_future, ModuleTranspiler, Runtime, RuntimeOptions};
+    use std::sync::Arc;
 
     // Resolve entry path
     let entry_path = if entry.is_absolute() {
@@ -186,6 +317,9 @@ fn run_native(cwd: &Path, entry: &Path, args: &[String], local: bool, json: bool
     ];
     script_args.extend(args.iter().cloned());
 
+    let transpiler =
+        ModuleTranspiler::new(Arc::new(DaemonCompiler::new(paths::ipc_endpoint(channel))));
+
     // Create runtime and execute as module (supports imports)
     // deno_core requires a current_thread runtime for async ops
     let rt = tokio::runtime::Builder::new_current_thread()
@@ -201,6 +335,7 @@ fn run_native(cwd: &Path, entry: &Path, args: &[String], local: bool, json: bool
                 cwd: Some(cwd.to_path_buf()),
                 main_module: Some(entry_path.clone()),
                 args: Some(script_args),
+                transpiler: Some(transpiler),
                 ..Default::default()
             })
             .map_err(|e| miette::miette!("Failed to create runtime: {}", e))?;
@@ -238,6 +373,7 @@ fn run_native(cwd: &Path, entry: &Path, args: &[String], local: bool, json: bool
                 cwd: Some(cwd.to_path_buf()),
                 main_module: Some(entry_path.clone()),
                 args: Some(script_args),
+                transpiler: Some(transpiler),
                 ..Default::default()
             })
             .map_err(|e| miette::miette!("Failed to create runtime: {}", e))?;
@@ -256,6 +392,9 @@ fn run_native(cwd: &Path, entry: &Path, args: &[String], local: bool, json: bool
         })
     };
 
+    // Don't leave the shell in raw mode if the script forgot setRawMode(false)
+    fastnode_runtime::restore_terminal();
+
     match result {
         Ok(()) => Ok(()),
         Err(e) => {
@@ -282,6 +421,7 @@ fn run_local(
     entry: &Path,
     args: &[String],
     dry_run: bool,
+    graph_depth: Option<u32>,
     channel: Channel,
     json: bool,
 ) -> Result<()> {
@@ -290,6 +430,7 @@ fn run_local(
         entry: entry.to_path_buf(),
         args: args.to_vec(),
         channel,
+        graph_depth,
     };
 
     match build_run_plan(input) {
@@ -298,7 +439,7 @@ fn run_local(
                 output_plan_local(&plan, json);
                 Ok(())
             } else {
-                execute_plan(&plan, cwd, json)
+                execute_plan(&plan, cwd, channel, json)
             }
         }
         Err(e) => {
@@ -320,21 +461,16 @@ fn run_local(
     }
 }
 
-/// Check if a file needs transpilation (TypeScript/TSX/JSX).
-fn needs_transpilation(path: &Path) -> bool {
-    path.extension()
-        .and_then(|ext| ext.to_str())
-        .map(|ext| {
-            matches!(
-                ext.to_lowercase().as_str(),
-                "ts" | "tsx" | "jsx" | "mts" | "cts"
-            )
-        })
-        .unwrap_or(false)
-}
-
 /// Execute the run plan by running the file with Node.
-fn execute_plan(plan: &RunPlanOutput, cwd: &Path, json: bool) -> Result<()> {
+///
+/// A TS/TSX/JSX entry and the local modules it imports are transpiled into
+/// the package's run cache first (see [`compiler::transpile_tree`]), through
+/// the daemon's warm compiler when it is running and in-process otherwise.
+fn execute_plan(plan: &RunPlanOutput, cwd: &Path, channel: Channel, json: bool) -> Result<()> {
+    if let (Some(graph), false) = (&plan.graph, json) {
+        print_graph_summary(graph, true);
+    }
+
     let resolved_entry = if let Some(entry) = &plan.resolved_entry {
         entry
     } else {
@@ -356,16 +492,11 @@ fn execute_plan(plan: &RunPlanOutput, cwd: &Path, json: bool) -> Result<()> {
     let entry_path = Path::new(resolved_entry);
 
     // Determine what file to actually run
-    let (file_to_run, temp_file) = if needs_transpilation(entry_path) {
-        // Transpile TypeScript/JSX to JavaScript
-        match transpile_file(entry_path) {
-            Ok((code, temp_path)) => {
-                // Write transpiled code to temp file
-                let temp_file = temp_path;
-                std::fs::write(&temp_file, &code)
-                    .map_err(|e| miette::miette!("Failed to write transpiled file: {}", e))?;
-                (temp_file.clone(), Some(temp_file))
-            }
+    let file_to_run = if compiler::needs_transpile(entry_path) {
+        let daemon = DaemonCompiler::new(paths::ipc_endpoint(channel));
+        let root = compiler::package_root(entry_path);
+        match compiler::transpile_tree(&daemon, &root, entry_path) {
+            Ok(tree) => tree.entry,
             Err(e) => {
                 if json {
                     let error_json = serde_json::json!({
@@ -384,7 +515,7 @@ fn execute_plan(plan: &RunPlanOutput, cwd: &Path, json: bool) -> Result<()> {
         }
     } else {
         // Run JavaScript directly
-        (entry_path.to_path_buf(), None)
+        entry_path.to_path_buf()
     };
 
     // Execute with Node
@@ -396,42 +527,11 @@ fn execute_plan(plan: &RunPlanOutput, cwd: &Path, json: bool) -> Result<()> {
         .stdout(Stdio::inherit())
         .stderr(Stdio::inherit());
 
-    let status = cmd
-        .status()
+    let status = process::status(&mut cmd)
         .map_err(|e| miette::miette!("Failed to execute node: {}. Is Node.js installed?", e))?;
 
-    // Clean up temp file if created
-    if let Some(temp) = temp_file {
-        let _ = std::fs::remove_file(temp);
-    }
-
-    // Exit with the same code as the child process
-    std::process::exit(status.code().unwrap_or(1));
-}
-
-/// Transpile a TypeScript/JSX file to JavaScript using howth-parser.
-fn transpile_file(path: &Path) -> Result<(String, std::path::PathBuf)> {
-    let source =
-        std::fs::read_to_string(path).map_err(|e| miette::miette!("Failed to read file: {}", e))?;
-
-    let ext = path.extension().and_then(|e| e.to_str()).unwrap_or("");
-
-    let (code, _imports) = match ext.to_lowercase().as_str() {
-        "tsx" => compiler::transform_tsx(&source),
-        "jsx" => compiler::transform_jsx(&source),
-        _ => compiler::transform_ts(&source),
-    }
-    .map_err(|e| miette::miette!("Transpilation failed: {}", e.message))?;
-
-    // Create output path in temp directory
-    let file_name = path
-        .file_stem()
-        .and_then(|s| s.to_str())
-        .unwrap_or("output");
-    let temp_dir = std::env::temp_dir();
-    let output_path = temp_dir.join(format!("howth-{}-{}.mjs", file_name, std::process::id()));
-
-    Ok((code, output_path))
+    // Exit with the same code (or signal) as the child process
+    process::exit_with(status);
 }
 
 /// Generate execution plan via daemon, and optionally execute.
@@ -440,6 +540,7 @@ fn run_via_daemon(
     entry: &Path,
     args: &[String],
     dry_run: bool,
+    graph_depth: Option<u32>,
     channel: Channel,
     json: bool,
 ) -> Result<()> {
@@ -451,11 +552,14 @@ fn run_via_daemon(
 
     // Run the async client
     let runtime = tokio::runtime::Runtime::new().into_diagnostic()?;
-    let result =
-        runtime.block_on(async { send_run_request(&endpoint, &entry_str, args, &cwd_str).await });
+    let result = runtime.block_on(async {
+        send_run_request(&endpoint, &entry_str, args, &cwd_str, graph_depth).await
+    });
 
     match result {
-        Ok((response, _server_version)) => handle_daemon_response(response, cwd, dry_run, json),
+        Ok((response, _server_version)) => {
+            handle_daemon_response(response, cwd, dry_run, channel, json)
+        }
         Err(e) => {
             let exit_code = EXIT_INTERNAL_ERROR;
             if json {
@@ -477,7 +581,13 @@ fn run_via_daemon(
 }
 
 /// Handle daemon response.
-fn handle_daemon_response(response: Response, cwd: &Path, dry_run: bool, json: bool) -> Result<()> {
+fn handle_daemon_response(
+    response: Response,
+    cwd: &Path,
+    dry_run: bool,
+    channel: Channel,
+    json: bool,
+) -> Result<()> {
     match response {
         Response::RunPlan { plan } => {
             if dry_run {
@@ -497,8 +607,10 @@ fn handle_daemon_response(response: Response, cwd: &Path, dry_run: bool, json: b
                     imports: vec![],
                     resolved_imports: vec![],
                     resolver: Default::default(),
+                    graph: plan.graph(),
+                    script: plan.script(),
                 };
-                execute_plan(&local_plan, cwd, json)
+                execute_plan(&local_plan, cwd, channel, json)
             }
         }
         Response::Error { code, message } => {
@@ -544,6 +656,8 @@ trait PlanOutput: Serialize {
     fn args(&self) -> &[String];
     fn channel(&self) -> &str;
     fn notes(&self) -> &[String];
+    fn graph(&self) -> Option<ImportGraphSummary>;
+    fn script(&self) -> Option<ScriptPlanOutput>;
 }
 
 impl PlanOutput for RunPlanOutput {
@@ -568,6 +682,12 @@ impl PlanOutput for RunPlanOutput {
     fn notes(&self) -> &[String] {
         &self.notes
     }
+    fn graph(&self) -> Option<ImportGraphSummary> {
+        self.graph.clone()
+    }
+    fn script(&self) -> Option<ScriptPlanOutput> {
+        self.script.clone()
+    }
 }
 
 impl PlanOutput for RunPlan {
@@ -592,6 +712,15 @@ impl PlanOutput for RunPlan {
     fn notes(&self) -> &[String] {
         &self.notes
     }
+    fn graph(&self) -> Option<ImportGraphSummary> {
+        // The proto summary has the same shape as core's
+        let value = serde_json::to_value(self.graph.as_ref()?).ok()?;
+        serde_json::from_value(value).ok()
+    }
+    fn script(&self) -> Option<ScriptPlanOutput> {
+        let value = serde_json::to_value(self.script.as_ref()?).ok()?;
+        serde_json::from_value(value).ok()
+    }
 }
 
 /// Output the run plan from local execution in human or JSON format.
@@ -611,11 +740,16 @@ fn output_plan<T: PlanOutput>(plan: &T, json: bool) {
         println!("{}", serde_json::to_string_pretty(plan).unwrap());
     } else {
         // Human format
+        let script = plan.script();
         println!("CWD: {}", plan.resolved_cwd());
         println!(
             "Entry: {} -> {}",
             plan.requested_entry(),
-            plan.resolved_entry().unwrap_or("(not resolved)")
+            match (&script, plan.resolved_entry()) {
+                (Some(script), _) => script.package_json.as_str(),
+                (None, Some(entry)) => entry,
+                (None, None) => "(not resolved)",
+            }
         );
         println!("Kind: {}", plan.entry_kind());
         if !plan.args().is_empty() {
@@ -628,6 +762,87 @@ fn output_plan<T: PlanOutput>(plan: &T, json: bool) {
                 println!("  - {note}");
             }
         }
+        if let Some(script) = script {
+            print_script_plan(&script);
+        }
+        if let Some(graph) = plan.graph() {
+            print_graph_summary(&graph, false);
+        }
+    }
+}
+
+/// Print the steps and environment of a planned script.
+fn print_script_plan(script: &ScriptPlanOutput) {
+    println!("Shell: {}", script.shell);
+    println!("Steps:");
+    for (i, step) in script.steps.iter().enumerate() {
+        println!("  {}. [{}] {}", i + 1, step.event, step.command);
+    }
+    if !script.bin_paths.is_empty() {
+        println!("PATH prepends:");
+        for path in &script.bin_paths {
+            println!("  - {path}");
+        }
+    }
+}
+
+/// Print an import graph summary to stdout, or stderr with `to_stderr`.
+fn print_graph_summary(graph: &ImportGraphSummary, to_stderr: bool) {
+    let mut lines = Vec::new();
+    let depth = graph
+        .depth_limit
+        .map_or_else(|| "full".to_string(), |limit| format!("limit {limit}"));
+    lines.push(format!(
+        "Graph: {} files, {} imports ({} local, {} external, {} unresolved), depth {} ({depth})",
+        graph.files,
+        graph.imports,
+        graph.local,
+        graph.external,
+        graph.unresolved_count(),
+        graph.max_depth,
+    ));
+    if graph.truncated {
+        lines.push("  (stopped early: too many modules)".to_string());
+    }
+    if !graph.unresolved.is_empty() {
+        lines.push("Unresolved:".to_string());
+        for group in &graph.unresolved {
+            lines.push(format!("  {} ({})", group.reason, group.count));
+            for site in &group.sites {
+                let location = site
+                    .line
+                    .map_or_else(|| site.from.clone(), |line| format!("{}:{line}", site.from));
+                lines.push(format!("    {location}  {}", site.raw));
+            }
+            let hidden = group.count as usize - group.sites.len();
+            if hidden > 0 {
+                lines.push(format!("    ... and {hidden} more"));
+            }
+        }
+    }
+    if !graph.cycles.is_empty() {
+        lines.push(format!("Cycles ({}):", graph.cycles.len()));
+        for cycle in &graph.cycles {
+            lines.push(format!("  {} -> {}", cycle.join(" -> "), cycle[0]));
+        }
+    }
+
+    for line in lines {
+        if to_stderr {
+            eprintln!("{line}");
+        } else {
+            println!("{line}");
+        }
+    }
+}
+
+/// Depth of the import graph walk for `--depth N` / `--full-graph`.
+#[must_use]
+pub fn graph_depth(depth: Option<u32>, full_graph: bool) -> Option<u32> {
+    if full_graph {
+        Some(FULL_GRAPH_DEPTH)
+    } else {
+        depth
     }
 }
 
@@ -649,6 +864,7 @@ async fn send_run_request(
     entry: &str,
     args: &[String],
     cwd: &str,
+    graph_depth: Option<u32>,
 ) -> io::Result<(Response, String)> {
     use tokio::io::{AsyncReadExt, AsyncWriteExt};
 
@@ -662,6 +878,7 @@ async fn send_run_request(
             entry: entry.to_string(),
             args: args.to_vec(),
             cwd: Some(cwd.to_string()),
+            graph_depth,
         },
     );
     let encoded = encode_frame(&frame)?;
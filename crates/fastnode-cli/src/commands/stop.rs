@@ -29,7 +29,7 @@ pub fn run(channel: Channel, _json: bool) -> Result<()> {
     }
 }
 
-async fn stop_daemon(endpoint: &str) -> io::Result<Response> {
+pub(crate) async fn stop_daemon(endpoint: &str) -> io::Result<Response> {
     use tokio::io::{AsyncReadExt, AsyncWriteExt};
 
     let mut stream = IpcStream::connect(endpoint).await?;
@@ -3,22 +3,28 @@
 //! If package.json has a "test" script, runs that.
 //! Otherwise, discovers test files and runs via daemon's warm Node worker pool
 //! (falling back to direct `node --test` if the daemon is not running).
+//!
+//! With `--json`, a [`TestRunResult`] is printed (or a [`ScriptRunResult`]
+//! when the package.json script was run).
+#![allow(clippy::too_many_arguments)]
 
-use fastnode_core::compiler::{CompilerBackend, SwcBackend, TranspileSpec};
+use fastnode_core::compiler::{backend_from_env, TranspileSpec};
 use fastnode_core::config::Channel;
 use fastnode_core::paths;
+use fastnode_core::scripts::PackageScripts;
 use fastnode_core::Config;
 #[cfg(unix)]
 use fastnode_core::VERSION;
 #[cfg(unix)]
 use fastnode_daemon::ipc::MAX_FRAME_SIZE;
-use fastnode_proto::Response;
 #[cfg(unix)]
 use fastnode_proto::{encode_frame, Frame, FrameResponse, Request};
-use miette::Result;
+use fastnode_proto::{Response, TestDiff, TestEnvironment, TestRunResult, TEST_RUN_SCHEMA_VERSION};
+use miette::{IntoDiagnostic, Result};
 use serde_json::Value;
 use std::path::{Path, PathBuf};
 use std::process::{Command, Stdio};
+use std::time::Instant;
 use walkdir::WalkDir;
 
 /// Exit code for validation errors.
@@ -49,16 +55,21 @@ pub fn run(
     setup: Option<&str>,
     timeout: Option<u64>,
     force_exit: bool,
+    keep_tmp: bool,
+    environment: TestEnvironment,
     paths: &[String],
+    json: bool,
 ) -> Result<()> {
     let cwd = &config.cwd;
 
     // Check for package.json test script first (only if no howth-specific flags given)
-    let has_howth_flags = setup.is_some() || timeout.is_some() || force_exit;
-    if paths.is_empty() && !has_howth_flags {
-        if let Some(script) = get_test_script(cwd) {
-            return run_test_script(cwd, &script);
-        }
+    let has_howth_flags = setup.is_some()
+        || timeout.is_some()
+        || force_exit
+        || keep_tmp
+        || environment != TestEnvironment::Node;
+    if paths.is_empty() && !has_howth_flags && get_test_script(cwd).is_some() {
+        return run_test_script(cwd, json);
     }
 
     // Discover test files from explicit paths or cwd
@@ -87,14 +98,20 @@ pub fn run(
     };
 
     if test_files.is_empty() {
-        println!("No test files found.");
-        println!("hint: create files matching *.test.ts, *.spec.ts, etc.");
+        if json {
+            print_json(&empty_result(cwd, 0.0, String::new()));
+        } else {
+            println!("No test files found.");
+            println!("hint: create files matching *.test.ts, *.spec.ts, etc.");
+        }
         return Ok(());
     }
 
-    println!("Found {} test file(s)", test_files.len());
-    for f in &test_files {
-        println!("  {}", f.display());
+    if !json {
+        println!("Found {} test file(s)", test_files.len());
+        for f in &test_files {
+            println!("  {}", f.display());
+        }
     }
 
     // Resolve setup file path
@@ -108,14 +125,52 @@ pub fn run(
     });
 
     // Try running via daemon first
-    if let Some(exit_code) =
-        try_run_via_daemon(cwd, &test_files, setup_path.as_deref(), timeout, force_exit)
-    {
+    if let Some(exit_code) = try_run_via_daemon(
+        cwd,
+        config.channel,
+        &test_files,
+        setup_path.as_deref(),
+        timeout,
+        force_exit,
+        keep_tmp,
+        environment,
+        json,
+    ) {
+        if keep_tmp && !json {
+            println!(
+                "temporary directories kept in {}",
+                paths::test_tmp_dir().display()
+            );
+        }
         std::process::exit(exit_code);
     }
 
     // Fallback: run directly via node --test
-    run_direct(cwd, test_files, setup_path.as_deref(), force_exit)
+    if environment == TestEnvironment::Dom && !json {
+        eprintln!("warning: the daemon is not running; tests run without a DOM under node --test");
+    }
+    run_direct(cwd, test_files, setup_path.as_deref(), force_exit, json)
+}
+
+/// Print a JSON test result to stdout.
+fn print_json(result: &TestRunResult) {
+    println!("{}", serde_json::to_string_pretty(result).unwrap());
+}
+
+/// A test result without per-test details.
+fn empty_result(cwd: &Path, duration_ms: f64, diagnostics: String) -> TestRunResult {
+    TestRunResult {
+        schema_version: TEST_RUN_SCHEMA_VERSION,
+        cwd: cwd.to_string_lossy().into_owned(),
+        ok: true,
+        total: 0,
+        passed: 0,
+        failed: 0,
+        skipped: 0,
+        duration_ms,
+        tests: Vec::new(),
+        diagnostics,
+    }
 }
 
 /// Try to run tests via the daemon's warm Node worker pool.
@@ -124,12 +179,16 @@ pub fn run(
 /// Uses a blocking Unix socket to avoid tokio runtime startup overhead.
 fn try_run_via_daemon(
     cwd: &Path,
+    channel: Channel,
     test_files: &[PathBuf],
     setup: Option<&Path>,
     timeout: Option<u64>,
     force_exit: bool,
+    keep_tmp: bool,
+    environment: TestEnvironment,
+    json: bool,
 ) -> Option<i32> {
-    let endpoint = paths::ipc_endpoint(Channel::Stable);
+    let endpoint = paths::ipc_endpoint(channel);
 
     let file_paths: Vec<String> = test_files
         .iter()
@@ -145,10 +204,12 @@ fn try_run_via_daemon(
         setup_str.as_deref(),
         timeout,
         force_exit,
+        keep_tmp,
+        environment,
     );
 
     match result {
-        Ok(response) => Some(handle_test_response(response)),
+        Ok(response) => Some(handle_test_response(response, json)),
         Err(_) => {
             // Daemon not running — fall back to direct execution
             None
@@ -166,9 +227,20 @@ fn send_run_tests_blocking(
     setup: Option<&str>,
     timeout: Option<u64>,
     force_exit: bool,
+    keep_tmp: bool,
+    environment: TestEnvironment,
 ) -> std::io::Result<Response> {
     let mut stream = std::os::unix::net::UnixStream::connect(endpoint)?;
-    send_run_tests_blocking_impl(&mut stream, cwd, files, setup, timeout, force_exit)
+    send_run_tests_blocking_impl(
+        &mut stream,
+        cwd,
+        files,
+        setup,
+        timeout,
+        force_exit,
+        keep_tmp,
+        environment,
+    )
 }
 
 /// Send RunTests request to daemon using named pipes on Windows.
@@ -180,6 +252,8 @@ fn send_run_tests_blocking(
     _setup: Option<&str>,
     _timeout: Option<u64>,
     _force_exit: bool,
+    _keep_tmp: bool,
+    _environment: TestEnvironment,
 ) -> std::io::Result<Response> {
     // On Windows, we can't use blocking named pipes easily without tokio.
     // Return an error indicating daemon mode isn't supported for blocking tests on Windows.
@@ -198,6 +272,8 @@ fn send_run_tests_blocking_impl(
     setup: Option<&str>,
     timeout: Option<u64>,
     force_exit: bool,
+    keep_tmp: bool,
+    environment: TestEnvironment,
 ) -> std::io::Result<Response> {
     let frame = Frame::new(
         VERSION,
@@ -207,6 +283,8 @@ fn send_run_tests_blocking_impl(
             setup: setup.map(String::from),
             timeout_ms: timeout,
             force_exit,
+            keep_tmp,
+            environment,
         },
     );
     let encoded = encode_frame(&frame)?;
@@ -235,10 +313,88 @@ fn send_run_tests_blocking_impl(
     Ok(response.response)
 }
 
+/// Lines printed under a failed test: the error message, with the
+/// assertion's diff inserted after its first line (before the stack).
+fn error_lines(error: &str, diff: Option<&TestDiff>) -> Vec<String> {
+    let mut lines = error.lines().map(String::from);
+    let Some(diff) = diff else {
+        return lines.collect();
+    };
+
+    let mut out: Vec<String> = lines.next().into_iter().collect();
+    out.push(String::new());
+    if !diff.expected.contains('\n') && !diff.actual.contains('\n') {
+        out.push(format!("Expected: \x1b[32m{}\x1b[0m", diff.expected));
+        out.push(format!("Received: \x1b[31m{}\x1b[0m", diff.actual));
+    } else {
+        out.push("\x1b[32m- Expected\x1b[0m".to_string());
+        out.push("\x1b[31m+ Received\x1b[0m".to_string());
+        out.push(String::new());
+        for line in diff_lines(&diff.expected, &diff.actual) {
+            out.push(match line {
+                DiffLine::Same(l) => format!("  {l}"),
+                DiffLine::Expected(l) => format!("\x1b[32m- {l}\x1b[0m"),
+                DiffLine::Received(l) => format!("\x1b[31m+ {l}\x1b[0m"),
+            });
+        }
+    }
+    out.extend(lines);
+    out
+}
+
+/// One line of an expected/received diff.
+#[derive(Debug, PartialEq, Eq)]
+enum DiffLine<'a> {
+    Same(&'a str),
+    Expected(&'a str),
+    Received(&'a str),
+}
+
+/// Line diff of two pretty-printed values (longest common subsequence).
+fn diff_lines<'a>(expected: &'a str, actual: &'a str) -> Vec<DiffLine<'a>> {
+    let old: Vec<&str> = expected.lines().collect();
+    let new: Vec<&str> = actual.lines().collect();
+
+    // lcs[i][j] = length of the LCS of old[i..] and new[j..]
+    let mut lcs = vec![vec![0usize; new.len() + 1]; old.len() + 1];
+    for i in (0..old.len()).rev() {
+        for j in (0..new.len()).rev() {
+            lcs[i][j] = if old[i] == new[j] {
+                lcs[i + 1][j + 1] + 1
+            } else {
+                lcs[i + 1][j].max(lcs[i][j + 1])
+            };
+        }
+    }
+
+    let (mut i, mut j) = (0, 0);
+    let mut out = Vec::with_capacity(old.len().max(new.len()));
+    while i < old.len() && j < new.len() {
+        if old[i] == new[j] {
+            out.push(DiffLine::Same(old[i]));
+            i += 1;
+            j += 1;
+        } else if lcs[i + 1][j] >= lcs[i][j + 1] {
+            out.push(DiffLine::Expected(old[i]));
+            i += 1;
+        } else {
+            out.push(DiffLine::Received(new[j]));
+            j += 1;
+        }
+    }
+    out.extend(old[i..].iter().map(|l| DiffLine::Expected(l)));
+    out.extend(new[j..].iter().map(|l| DiffLine::Received(l)));
+    out
+}
+
 /// Handle test response from daemon and print results.
 /// Returns the exit code.
-fn handle_test_response(response: Response) -> i32 {
+fn handle_test_response(response: Response, json: bool) -> i32 {
     match response {
+        Response::TestRunResult { result } if json => {
+            print_json(&result);
+            i32::from(!result.ok)
+        }
         Response::TestRunResult { result } => {
             // Print results
             for test in &result.tests {
@@ -251,9 +407,19 @@ fn handle_test_response(response: Response) -> i32 {
                 if test.duration_ms > 0.0 {
                     print!(" ({:.0}ms)", test.duration_ms);
                 }
+                if test.flaky {
+                    print!(" \x1b[33mflaky, passed on retry {}\x1b[0m", test.retries);
+                } else if test.retries > 0 {
+                    let noun = if test.retries == 1 {
+                        "retry"
+                    } else {
+                        "retries"
+                    };
+                    print!(" ({} {noun})", test.retries);
+                }
                 println!();
                 if let Some(ref err) = test.error {
-                    for line in err.lines() {
+                    for line in error_lines(err, test.diff.as_ref()) {
                         eprintln!("    {line}");
                     }
                 }
@@ -283,6 +449,11 @@ fn handle_test_response(response: Response) -> i32 {
                 println!("{} skipped", result.skipped);
             }
 
+            let flaky = result.tests.iter().filter(|t| t.flaky).count();
+            if flaky > 0 {
+                println!("\x1b[33m{flaky} flaky\x1b[0m");
+            }
+
             if !result.diagnostics.is_empty() {
                 eprintln!("{}", result.diagnostics.trim_end());
             }
@@ -290,7 +461,14 @@ fn handle_test_response(response: Response) -> i32 {
             i32::from(!result.ok)
         }
         Response::Error { code, message } => {
-            eprintln!("error: {code}: {message}");
+            if json {
+                println!(
+                    "{}",
+                    serde_json::json!({ "ok": false, "error": { "code": code, "message": message } })
+                );
+            } else {
+                eprintln!("error: {code}: {message}");
+            }
             EXIT_INTERNAL_ERROR
         }
         _ => {
@@ -306,7 +484,10 @@ fn run_direct(
     test_files: Vec<PathBuf>,
     setup: Option<&Path>,
     force_exit: bool,
+    json: bool,
 ) -> Result<()> {
+    let start = Instant::now();
+
     // Separate files by type
     let (ts_files, js_files): (Vec<_>, Vec<_>) =
         test_files.into_iter().partition(|f| needs_transpilation(f));
@@ -435,6 +616,18 @@ export default describe;
     // Clean up temp files
     cleanup_temp_files(&temp_files);
 
+    if json {
+        // node --test output is not parsed, so only the overall outcome is known.
+        let mut result = empty_result(
+            cwd,
+            start.elapsed().as_secs_f64() * 1000.0,
+            "Ran via `node --test` (daemon not running); per-test results are unavailable"
+                .to_string(),
+        );
+        result.ok = exit_code == 0;
+        print_json(&result);
+    }
+
     std::process::exit(exit_code);
 }
 
@@ -508,7 +701,7 @@ fn transpile_test_file(path: &Path, mocha_shim: Option<&str>) -> Result<PathBuf>
     let source =
         std::fs::read_to_string(path).map_err(|e| miette::miette!("Failed to read file: {}", e))?;
 
-    let backend = SwcBackend::new();
+    let backend = backend_from_env();
 
     // Write next to the original so Node's module resolution finds node_modules.
     // Strip .test/.spec from the name to avoid node:test discovery.
@@ -713,45 +906,25 @@ fn get_test_script(cwd: &Path) -> Option<String> {
     Some(script.to_string())
 }
 
-/// Run the test script from package.json.
-fn run_test_script(cwd: &Path, script: &str) -> Result<()> {
-    use std::io::Write;
-
-    println!("$ {}", script);
-    let _ = std::io::stdout().flush();
-
-    #[cfg(unix)]
-    let mut cmd = {
-        let mut c = Command::new("sh");
-        c.arg("-c").arg(script);
-        c
-    };
-
-    #[cfg(windows)]
-    let mut cmd = {
-        let mut c = Command::new("cmd");
-        c.arg("/C").arg(script);
-        c
-    };
-
-    cmd.current_dir(cwd)
-        .stdin(Stdio::inherit())
-        .stdout(Stdio::inherit())
-        .stderr(Stdio::inherit());
-
-    // Add node_modules/.bin to PATH
-    let node_modules_bin = cwd.join("node_modules").join(".bin");
-    if node_modules_bin.exists() {
-        let path = std::env::var("PATH").unwrap_or_default();
-        let new_path = format!("{}:{}", node_modules_bin.display(), path);
-        cmd.env("PATH", new_path);
+/// Run the test script from package.json (with `pretest`/`posttest` hooks).
+fn run_test_script(cwd: &Path, json: bool) -> Result<()> {
+    let pkg = PackageScripts::load(cwd).into_diagnostic()?;
+    let result = super::run::execute_script(cwd, &pkg, "test", &[], false, !json);
+
+    if json {
+        println!(
+            "{}",
+            serde_json::to_string_pretty(&result).into_diagnostic()?
+        );
+    } else if let Some(error) = &result.error {
+        eprintln!("error: {}", error.message);
     }
 
-    let status = cmd
-        .status()
-        .map_err(|e| miette::miette!("Failed to execute test script: {}", e))?;
-
-    std::process::exit(status.code().unwrap_or(EXIT_INTERNAL_ERROR));
+    std::process::exit(if result.ok {
+        0
+    } else {
+        result.exit_code.unwrap_or(EXIT_INTERNAL_ERROR)
+    });
 }
 
 #[cfg(test)]
@@ -791,4 +964,40 @@ mod tests {
         assert!(!is_supported_extension(Path::new("foo.py")));
         assert!(!is_supported_extension(Path::new("foo.rs")));
     }
+
+    #[test]
+    fn test_diff_lines() {
+        let expected = "{\n  \"a\": 1,\n  \"b\": 3,\n}";
+        let actual = "{\n  \"a\": 1,\n  \"b\": 2,\n  \"c\": 4,\n}";
+        assert_eq!(
+            diff_lines(expected, actual),
+            vec![
+                DiffLine::Same("{"),
+                DiffLine::Same("  \"a\": 1,"),
+                DiffLine::Expected("  \"b\": 3,"),
+                DiffLine::Received("  \"b\": 2,"),
+                DiffLine::Received("  \"c\": 4,"),
+                DiffLine::Same("}"),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_error_lines_inserts_diff_before_stack() {
+        let diff = TestDiff {
+            matcher: "toBe".to_string(),
+            expected: "3".to_string(),
+            actual: "4".to_string(),
+        };
+        let lines = error_lines(
+            "AssertionError: expect(received).toBe(expected)\n    at math.test.js:3:5",
+            Some(&diff),
+        );
+        assert_eq!(lines[0], "AssertionError: expect(received).toBe(expected)");
+        assert!(lines[2].starts_with("Expected: ") && lines[2].contains('3'));
+        assert!(lines[3].starts_with("Received: ") && lines[3].contains('4'));
+        assert_eq!(lines[4], "    at math.test.js:3:5");
+
+        assert_eq!(error_lines("boom\nstack", None), vec!["boom", "stack"]);
+    }
 }
@@ -4,7 +4,10 @@ use fastnode_core::config::Channel;
 use fastnode_core::paths;
 use fastnode_core::VERSION;
 use fastnode_daemon::ipc::{IpcStream, MAX_FRAME_SIZE};
-use fastnode_proto::{encode_frame, Frame, FrameResponse, Request, Response};
+use fastnode_proto::{
+    encode_frame, Frame, FrameResponse, Request, Response, WatchBackend, WatchBackendStatus,
+    WatchCacheStats, WatchFileEvent, WatchFileEventKind,
+};
 use miette::{IntoDiagnostic, Result};
 use serde::Serialize;
 use std::io;
@@ -13,7 +16,12 @@ use std::path::PathBuf;
 /// Watch command action.
 #[derive(Debug, Clone)]
 pub enum WatchAction {
-    Start { roots: Vec<PathBuf> },
+    Start {
+        roots: Vec<PathBuf>,
+        ignore: Vec<String>,
+        backend: WatchBackend,
+        poll_interval_ms: Option<u64>,
+    },
     Stop,
     Status,
 }
@@ -24,8 +32,12 @@ struct WatchStatusResult {
     ok: bool,
     running: bool,
     roots: Vec<String>,
+    ignore: Vec<String>,
     #[serde(skip_serializing_if = "Option::is_none")]
     last_event_unix_ms: Option<u64>,
+    caches: WatchCacheStats,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    backend: Option<WatchBackendStatus>,
     #[serde(skip_serializing_if = "Option::is_none")]
     error: Option<String>,
 }
@@ -58,7 +70,10 @@ pub fn run(action: WatchAction, channel: Channel, json: bool) -> Result<()> {
                             ok: false,
                             running: false,
                             roots: Vec::new(),
+                            ignore: Vec::new(),
                             last_event_unix_ms: None,
+                            caches: WatchCacheStats::default(),
+                            backend: None,
                             error: Some(format!("Failed to connect: {e}")),
                         };
                         println!("{}", serde_json::to_string_pretty(&result).unwrap());
@@ -82,6 +97,114 @@ pub fn run(action: WatchAction, channel: Channel, json: bool) -> Result<()> {
     }
 }
 
+/// Stream file events from the daemon's watcher until Ctrl+C or disconnect.
+///
+/// With `--json`, each event is printed as one JSON object per line.
+pub fn run_events(paths: &[PathBuf], channel: Channel, json: bool) -> Result<()> {
+    let endpoint = paths::ipc_endpoint(channel);
+
+    let runtime = tokio::runtime::Runtime::new().into_diagnostic()?;
+    let result = runtime.block_on(async { stream_watch_events(&endpoint, paths, json).await });
+
+    if let Err(e) = result {
+        if e.kind() == io::ErrorKind::NotFound || e.kind() == io::ErrorKind::ConnectionRefused {
+            eprintln!("error: daemon not running");
+            eprintln!("hint: start with `howth daemon`");
+        } else {
+            eprintln!("error: {e}");
+        }
+        std::process::exit(1);
+    }
+    Ok(())
+}
+
+async fn stream_watch_events(endpoint: &str, paths: &[PathBuf], json: bool) -> io::Result<()> {
+    use tokio::io::AsyncWriteExt;
+
+    let mut stream = IpcStream::connect(endpoint).await?;
+
+    let request = Request::WatchSubscribe {
+        paths: paths
+            .iter()
+            .map(|p| p.to_string_lossy().into_owned())
+            .collect(),
+    };
+    let frame = Frame::new(VERSION, request);
+    let encoded = encode_frame(&frame)?;
+    stream.write_all(&encoded).await?;
+    stream.flush().await?;
+
+    let ctrl_c = tokio::signal::ctrl_c();
+    tokio::pin!(ctrl_c);
+
+    loop {
+        tokio::select! {
+            _ = &mut ctrl_c => return Ok(()),
+            result = read_response(&mut stream) => {
+                match result?.0 {
+                    Response::WatchSubscribed { roots } => {
+                        if !json {
+                            eprintln!("Streaming events for {} (ctrl+c to exit)", roots.join(", "));
+                        }
+                    }
+                    Response::WatchEvents { events } => {
+                        for event in &events {
+                            print_event(event, json);
+                        }
+                    }
+                    Response::WatchUnsubscribed { reason } => {
+                        if !json {
+                            eprintln!("Subscription ended: {reason}");
+                        }
+                        return Ok(());
+                    }
+                    Response::Error { code, message } => {
+                        eprintln!("error: {code}: {message}");
+                        std::process::exit(1);
+                    }
+                    _ => eprintln!("warning: unexpected response type"),
+                }
+            }
+        }
+    }
+}
+
+fn print_event(event: &WatchFileEvent, json: bool) {
+    if json {
+        println!("{}", serde_json::to_string(event).unwrap());
+        return;
+    }
+    let kind = match event.kind {
+        WatchFileEventKind::Create => "create",
+        WatchFileEventKind::Modify => "modify",
+        WatchFileEventKind::Remove => "remove",
+        WatchFileEventKind::Rename => "rename",
+        WatchFileEventKind::Other => "other",
+    };
+    println!("{kind:<7} {}", event.path);
+}
+
+/// One-line description of the watcher backend, e.g. `poll (every 1000ms, content hashing)`.
+fn describe_backend(status: &WatchBackendStatus) -> String {
+    match status.backend {
+        WatchBackend::Poll => {
+            let mut details = Vec::new();
+            if let Some(ms) = status.poll_interval_ms {
+                details.push(format!("every {ms}ms"));
+            }
+            if status.content_hashing {
+                details.push("content hashing".to_string());
+            }
+            if details.is_empty() {
+                "poll".to_string()
+            } else {
+                format!("poll ({})", details.join(", "))
+            }
+        }
+        WatchBackend::Native | WatchBackend::Auto => "native".to_string(),
+    }
+}
+
 fn action_name(action: &WatchAction) -> &'static str {
     match action {
         WatchAction::Start { .. } => "start",
@@ -127,27 +250,60 @@ fn handle_response(response: Response, action: &WatchAction, json: bool) -> Resu
             roots,
             running,
             last_event_unix_ms,
+            ignore,
+            caches,
+            backend,
         } => {
             if json {
                 let result = WatchStatusResult {
                     ok: true,
                     running,
                     roots,
+                    ignore,
                     last_event_unix_ms,
+                    caches,
+                    backend,
                     error: None,
                 };
                 println!("{}", serde_json::to_string_pretty(&result).unwrap());
             } else {
                 println!("Status: {}", if running { "running" } else { "stopped" });
+                if let Some(backend) = &backend {
+                    println!("Backend: {}", describe_backend(backend));
+                    if let Some(reason) = &backend.fallback_reason {
+                        println!("  Native watcher failed: {reason}");
+                    }
+                }
                 if !roots.is_empty() {
                     println!("Roots:");
                     for root in &roots {
                         println!("  {root}");
                     }
                 }
+                if !ignore.is_empty() {
+                    println!("Ignored:");
+                    for pattern in &ignore {
+                        println!("  {pattern}");
+                    }
+                }
                 if let Some(ts) = last_event_unix_ms {
                     println!("Last event: {ts} ms since epoch");
                 }
+                println!(
+                    "Resolver cache: {} entries, {} invalidated",
+                    caches.resolver_entries, caches.resolver_invalidations
+                );
+                println!(
+                    "package.json cache: {} entries, {} invalidated",
+                    caches.pkg_json_entries, caches.pkg_json_invalidations
+                );
+                let hit_rate = caches
+                    .transpile_hit_rate()
+                    .map_or_else(|| "n/a".to_string(), |rate| format!("{:.1}%", rate * 100.0));
+                println!(
+                    "Transform cache: {} entries, {} hits, {} misses ({hit_rate} hit rate)",
+                    caches.transpile_entries, caches.transpile_hits, caches.transpile_misses
+                );
             }
             Ok(())
         }
@@ -159,7 +315,10 @@ fn handle_response(response: Response, action: &WatchAction, json: bool) -> Resu
                             ok: false,
                             running: false,
                             roots: Vec::new(),
+                            ignore: Vec::new(),
                             last_event_unix_ms: None,
+                            caches: WatchCacheStats::default(),
+                            backend: None,
                             error: Some(format!("{code}: {message}")),
                         };
                         println!("{}", serde_json::to_string_pretty(&result).unwrap());
@@ -187,7 +346,10 @@ fn handle_response(response: Response, action: &WatchAction, json: bool) -> Resu
                             ok: false,
                             running: false,
                             roots: Vec::new(),
+                            ignore: Vec::new(),
                             last_event_unix_ms: None,
+                            caches: WatchCacheStats::default(),
+                            backend: None,
                             error: Some("Unexpected response type".to_string()),
                         };
                         println!("{}", serde_json::to_string_pretty(&result).unwrap());
@@ -214,18 +376,26 @@ async fn send_watch_request(
     endpoint: &str,
     action: &WatchAction,
 ) -> io::Result<(Response, String)> {
-    use tokio::io::{AsyncReadExt, AsyncWriteExt};
+    use tokio::io::AsyncWriteExt;
 
     // Connect using cross-platform IpcStream
     let mut stream = IpcStream::connect(endpoint).await?;
 
     // Create request based on action
     let request = match action {
-        WatchAction::Start { roots } => Request::WatchStart {
+        WatchAction::Start {
+            roots,
+            ignore,
+            backend,
+            poll_interval_ms,
+        } => Request::WatchStart {
             roots: roots
                 .iter()
                 .map(|p| p.to_string_lossy().into_owned())
                 .collect(),
+            ignore: ignore.clone(),
+            backend: *backend,
+            poll_interval_ms: *poll_interval_ms,
         },
         WatchAction::Stop => Request::WatchStop,
         WatchAction::Status => Request::WatchStatus,
@@ -238,7 +408,13 @@ async fn send_watch_request(
     stream.write_all(&encoded).await?;
     stream.flush().await?;
 
-    // Read response
+    read_response(&mut stream).await
+}
+
+/// Read a single response frame from the stream.
+async fn read_response(stream: &mut IpcStream) -> io::Result<(Response, String)> {
+    use tokio::io::AsyncReadExt;
+
     let mut len_buf = [0u8; 4];
     stream.read_exact(&mut len_buf).await?;
     let len = u32::from_le_bytes(len_buf) as usize;
@@ -2,7 +2,14 @@
 //!
 //! List and manage workspace packages in a monorepo.
 
-use fastnode_core::pkg::{detect_workspaces, find_workspace_root, link_workspace_packages};
+use super::run::execute_script;
+use fastnode_core::git;
+use fastnode_core::pkg::{
+    detect_workspaces, find_workspace_root, link_workspace_packages, select_workspace_packages,
+    WorkspaceGraph, WorkspacePackage,
+};
+use fastnode_core::scripts::PackageScripts;
+use fastnode_proto::{RunErrorInfo, ScriptRunResult, SCRIPT_RUN_SCHEMA_VERSION};
 use miette::Result;
 use std::path::Path;
 
@@ -132,3 +139,153 @@ pub fn link(cwd: &Path, json: bool) -> Result<()> {
         }
     }
 }
+
+/// Workspace package selection for `--filter` and `--since`.
+#[derive(Debug, Clone, Default)]
+pub struct WorkspaceFilter {
+    /// Package names or globs; `pkg...` adds dependencies, `...pkg` dependents.
+    pub filters: Vec<String>,
+    /// Only packages changed since this git ref, plus their dependents.
+    pub since: Option<String>,
+}
+
+impl WorkspaceFilter {
+    /// Whether any selection flag was given.
+    #[must_use]
+    pub fn is_active(&self) -> bool {
+        !self.filters.is_empty() || self.since.is_some()
+    }
+}
+
+/// Print a workspace error in the command's output format and exit 1.
+fn exit_with_error(code: &str, message: &str, json: bool) -> ! {
+    if json {
+        println!(
+            "{}",
+            serde_json::json!({
+                "ok": false,
+                "error": {
+                    "code": code,
+                    "message": message
+                }
+            })
+        );
+    } else {
+        eprintln!("error: {message}");
+    }
+    std::process::exit(1);
+}
+
+/// Select workspace packages, in dependency order (dependencies first).
+///
+/// Exits if no workspaces are configured or change detection fails.
+pub fn select_packages(cwd: &Path, filter: &WorkspaceFilter, json: bool) -> Vec<WorkspacePackage> {
+    let root = find_workspace_root(cwd).unwrap_or_else(|| cwd.to_path_buf());
+    // Canonical paths so they line up with the paths reported by git
+    let root = dunce::canonicalize(&root).unwrap_or(root);
+    let Some(config) = detect_workspaces(&root) else {
+        exit_with_error("NO_WORKSPACES", "No workspaces configured", json);
+    };
+    let graph = WorkspaceGraph::from_config(&config);
+
+    let changed = match &filter.since {
+        Some(reference) => match git::changed_files(&root, reference) {
+            Ok(files) => Some(files),
+            Err(e) => exit_with_error(e.code(), &e.to_string(), json),
+        },
+        None => None,
+    };
+
+    select_workspace_packages(&graph, &filter.filters, changed.as_deref())
+        .into_iter()
+        .cloned()
+        .collect()
+}
+
+/// Run a package.json script in the selected workspace packages.
+///
+/// Packages run in dependency order, then by name. Each package runs with
+/// full script runner semantics (`pre`/`post` hooks, `node_modules/.bin` on
+/// PATH, `npm_*` env). Stops at the first failure.
+pub fn run_script(
+    cwd: &Path,
+    script: &str,
+    args: &[String],
+    if_present: bool,
+    filter: &WorkspaceFilter,
+    json: bool,
+) -> Result<()> {
+    let packages = select_packages(cwd, filter, json);
+    if packages.is_empty() && !json {
+        println!("No workspace packages selected.");
+    }
+
+    let mut results = Vec::new();
+    let mut failed = None;
+    for pkg in packages {
+        let defined = PackageScripts::load(&pkg.path)
+            .map(|scripts| scripts.get(script).is_some())
+            .unwrap_or(false);
+        if !defined && if_present {
+            results.push(serde_json::json!({
+                "name": pkg.name,
+                "path": pkg.path.to_string_lossy(),
+                "skipped": true
+            }));
+            continue;
+        }
+
+        if !json {
+            println!("{}:", pkg.name);
+        }
+        let result = match PackageScripts::load(&pkg.path) {
+            Ok(pkg_scripts) => execute_script(&pkg.path, &pkg_scripts, script, args, json, !json),
+            Err(e) => ScriptRunResult {
+                schema_version: SCRIPT_RUN_SCHEMA_VERSION,
+                ok: false,
+                script: script.to_string(),
+                cwd: pkg.path.to_string_lossy().into_owned(),
+                exit_code: None,
+                duration_ms: 0.0,
+                steps: Vec::new(),
+                error: Some(RunErrorInfo {
+                    code: e.code().to_string(),
+                    message: e.to_string(),
+                }),
+            },
+        };
+
+        let ok = result.ok;
+        if !json {
+            if let Some(error) = &result.error {
+                eprintln!("error: {}: {}", pkg.name, error.message);
+            }
+        }
+        results.push(serde_json::json!({
+            "name": pkg.name,
+            "path": pkg.path.to_string_lossy(),
+            "skipped": false,
+            "result": result
+        }));
+        if !ok {
+            failed = Some((pkg.name.clone(), result.exit_code.unwrap_or(1)));
+            break;
+        }
+    }
+
+    if json {
+        println!(
+            "{}",
+            serde_json::json!({ "ok": failed.is_none(), "script": script, "results": results })
+        );
+    }
+
+    if let Some((name, exit_code)) = failed {
+        if !json {
+            eprintln!("error: {name}: script \"{script}\" exited with code {exit_code}");
+        }
+        std::process::exit(exit_code);
+    }
+
+    Ok(())
+}
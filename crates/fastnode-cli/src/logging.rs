@@ -1,8 +1,14 @@
 //! Logging initialization for the CLI.
 //!
 //! Logging is owned by the CLI crate to keep library crates lightweight.
-//! Uses tracing with structured JSON output for machine-readable logs.
+//! Uses tracing with structured JSON output for machine-readable logs, and
+//! optionally exports spans over OTLP (see [`crate::telemetry`]).
 
+use opentelemetry_sdk::trace::Tracer;
+use std::fs::{self, File};
+use std::io;
+use std::path::Path;
+use std::sync::Mutex;
 use tracing::Level;
 use tracing_subscriber::{fmt, prelude::*, EnvFilter};
 
@@ -11,6 +17,9 @@ use tracing_subscriber::{fmt, prelude::*, EnvFilter};
 /// # Arguments
 /// * `verbosity` - 0 = INFO, 1 = DEBUG, 2+ = TRACE
 /// * `json` - If true, output stable JSON lines to stderr
+/// * `stderr` - If false, nothing is logged to stderr (spans still reach `tracer`)
+/// * `file` - Log file that also receives JSON lines (see [`open_log_file`])
+/// * `tracer` - OpenTelemetry tracer to export spans to, if enabled
 ///
 /// JSON output format (stable contract):
 /// ```json
@@ -19,7 +28,7 @@ use tracing_subscriber::{fmt, prelude::*, EnvFilter};
 ///
 /// # Panics
 /// Panics if the subscriber cannot be initialized (e.g., called twice).
-pub fn init(verbosity: u8, json: bool) {
+pub fn init(verbosity: u8, json: bool, stderr: bool, file: Option<File>, tracer: Option<Tracer>) {
     let level = match verbosity {
         0 => Level::INFO,
         1 => Level::DEBUG,
@@ -32,9 +41,22 @@ pub fn init(verbosity: u8, json: bool) {
         .add_directive(format!("fastnode={level}").parse().unwrap())
         .add_directive(level.into());
 
-    let subscriber = tracing_subscriber::registry().with(filter);
+    let otel = tracer.map(|tracer| tracing_opentelemetry::layer().with_tracer(tracer));
+    let file = file.map(|file| {
+        fmt::layer()
+            .json()
+            .with_current_span(true)
+            .with_span_list(false)
+            .with_writer(Mutex::new(file))
+    });
+    let subscriber = tracing_subscriber::registry()
+        .with(filter)
+        .with(otel)
+        .with(file);
 
-    if json {
+    if !stderr {
+        subscriber.init();
+    } else if json {
         // Stable JSON format for machine parsing
         subscriber
             .with(
@@ -51,3 +73,23 @@ pub fn init(verbosity: u8, json: bool) {
             .init();
     }
 }
+
+/// Log files larger than this are rotated when opened (5 MiB).
+const MAX_LOG_FILE_SIZE: u64 = 5 * 1024 * 1024;
+
+/// Open `path` for appending, first moving a file over the size limit to
+/// `<path>.1` (replacing the previous one).
+///
+/// # Errors
+/// Returns an error if the directory or file can't be created.
+pub fn open_log_file(path: &Path) -> io::Result<File> {
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent)?;
+    }
+    if fs::metadata(path).is_ok_and(|meta| meta.len() > MAX_LOG_FILE_SIZE) {
+        let mut rotated = path.as_os_str().to_os_string();
+        rotated.push(".1");
+        fs::rename(path, rotated)?;
+    }
+    File::options().create(true).append(true).open(path)
+}
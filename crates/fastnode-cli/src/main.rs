@@ -25,9 +25,11 @@
 
 mod commands;
 mod logging;
+mod progress;
+mod telemetry;
 
-use clap::Parser;
-use fastnode_core::config::Channel;
+use clap::{CommandFactory, FromArgMatches, Parser};
+use fastnode_core::config::{Channel, CHANNEL_ENV};
 use fastnode_core::Config;
 use miette::Result;
 use std::path::PathBuf;
@@ -48,6 +50,25 @@ struct Cli {
     #[arg(long, global = true, value_name = "PATH")]
     cwd: Option<PathBuf>,
 
+    /// Release channel; each channel has its own cache, socket and daemon
+    #[arg(
+        long,
+        global = true,
+        env = CHANNEL_ENV,
+        default_value_t = Channel::Stable,
+        value_name = "CHANNEL"
+    )]
+    channel: Channel,
+
+    /// Don't start (or restart an outdated) daemon for commands that need one
+    #[arg(
+        long,
+        global = true,
+        env = commands::daemon::NO_AUTOSTART_ENV,
+        value_parser = clap::builder::FalseyValueParser::new()
+    )]
+    no_autostart: bool,
+
     #[command(subcommand)]
     command: Option<Commands>,
 }
@@ -58,7 +79,24 @@ enum Commands {
     Version,
 
     /// Check system health and capabilities
-    Doctor,
+    Doctor {
+        /// Write a redacted diagnostics tarball to attach to bug reports
+        ///
+        /// Includes the doctor report, recent daemon logs, allowlisted
+        /// package.json fields and .npmrc settings, allowlisted environment
+        /// variables, lockfile hashes and the last failed build. Other values
+        /// are replaced with "[redacted]".
+        #[arg(long)]
+        bundle: bool,
+
+        /// Where to write the bundle (default: howth-diagnostics-<time>.tar.gz)
+        #[arg(short, long, requires = "bundle")]
+        output: Option<PathBuf>,
+
+        /// Create the bundle without asking for confirmation
+        #[arg(short, long, requires = "bundle")]
+        yes: bool,
+    },
 
     /// Initialize a new project
     Init {
@@ -74,6 +112,10 @@ enum Commands {
 
         /// Project name / directory name
         name: Option<String>,
+
+        /// Install dependencies after creating the project
+        #[arg(long)]
+        install: bool,
     },
 
     /// Register or link a local package
@@ -100,6 +142,22 @@ enum Commands {
     Bench {
         #[command(subcommand)]
         bench_cmd: BenchCommands,
+
+        /// Save results under the history directory, keyed by the current git sha
+        #[arg(long, global = true)]
+        save: bool,
+
+        /// Compare against a saved run: a git sha (or prefix), `latest`, or a file path
+        #[arg(long, global = true, value_name = "REF")]
+        baseline: Option<String>,
+
+        /// Fail when a metric regresses by more than this percentage against the baseline
+        #[arg(long, global = true, value_name = "PCT", default_value_t = fastnode_core::bench::DEFAULT_THRESHOLD_PCT)]
+        threshold: f64,
+
+        /// Directory holding saved bench runs
+        #[arg(long, global = true, value_name = "DIR", default_value = fastnode_core::bench::DEFAULT_HISTORY_DIR)]
+        history_dir: PathBuf,
     },
 
     /// Start the daemon (foreground)
@@ -136,6 +194,31 @@ enum Commands {
         #[arg(long)]
         local: bool,
 
+        /// Don't fail if the package.json script is not defined
+        #[arg(long)]
+        if_present: bool,
+
+        /// Capture script stdout/stderr into the --json result instead of streaming it
+        #[arg(long)]
+        capture: bool,
+
+        /// Summarize the entry's imports N levels deep (unresolved imports, cycles)
+        #[arg(long, value_name = "N")]
+        depth: Option<u32>,
+
+        /// Summarize the entry's whole import graph
+        #[arg(long, conflicts_with = "depth")]
+        full_graph: bool,
+
+        /// Only in workspace packages matching a name or glob (repeatable;
+        /// `pkg...` adds its dependencies, `...pkg` its dependents)
+        #[arg(long = "filter", value_name = "PKG", conflicts_with_all = ["daemon", "dry_run", "native", "node", "local", "depth", "full_graph"])]
+        filter: Vec<String>,
+
+        /// Only in workspace packages changed since a git ref, plus their dependents
+        #[arg(long, value_name = "REF", conflicts_with_all = ["daemon", "dry_run", "native", "node", "local", "depth", "full_graph"])]
+        since: Option<String>,
+
         /// Arguments to pass to the script (after --)
         #[arg(last = true)]
         args: Vec<String>,
@@ -146,11 +229,34 @@ enum Commands {
         /// Binary name to execute (e.g., "jest", "eslint", "tsc")
         binary: String,
 
+        /// Install the providing package into devDependencies without asking if missing
+        #[arg(long, short = 'y')]
+        yes: bool,
+
+        /// Capture stdout/stderr into the --json result instead of streaming it
+        #[arg(long)]
+        capture: bool,
+
         /// Arguments to pass to the binary (after --)
         #[arg(last = true)]
         args: Vec<String>,
     },
 
+    /// Run a binary from a registry package without installing it (like npx)
+    #[command(name = "x", visible_alias = "dlx")]
+    Dlx {
+        /// Package to install (e.g., "cowsay@1.5.0"); the command then names its binary
+        #[arg(long, short = 'p')]
+        package: Option<String>,
+
+        /// Package spec or binary name to run (e.g., "cowsay", "prettier@3")
+        command: String,
+
+        /// Arguments to pass to the binary
+        #[arg(trailing_var_arg = true, allow_hyphen_values = true)]
+        args: Vec<String>,
+    },
+
     /// Install dependencies from lockfile
     Install {
         /// Fail if lockfile is missing or out of date
@@ -172,17 +278,83 @@ enum Commands {
         /// Skip optionalDependencies
         #[arg(long, conflicts_with = "optional")]
         no_optional: bool,
+
+        /// Install missing peer dependencies (default: on, or `.npmrc`'s
+        /// `auto-install-peers`); `--auto-install-peers=false` only records them
+        #[arg(long, num_args = 0..=1, default_missing_value = "true")]
+        auto_install_peers: Option<bool>,
+
+        /// Only verify that node_modules matches the lockfile, without
+        /// writing anything (exits 1 on differences)
+        #[arg(long, conflicts_with_all = ["frozen_lockfile", "auto_install_peers"])]
+        check: bool,
+
+        /// Number of packages whose files --check compares with the cache
+        /// (default: 16, 0 to skip)
+        #[arg(long, requires = "check")]
+        check_sample: Option<usize>,
+    },
+
+    /// Format JavaScript/TypeScript files in place
+    Fmt {
+        /// Files or directories to format (default: cwd); directories skip
+        /// files ignored by git
+        paths: Vec<PathBuf>,
+
+        /// Don't write; list unformatted files and exit 1 if there are any
+        #[arg(long)]
+        check: bool,
+    },
+
+    /// Lint JavaScript/TypeScript files (rules configured in package.json "howth.lint")
+    Lint {
+        /// Files or directories to lint (default: cwd); directories skip
+        /// files ignored by git
+        paths: Vec<PathBuf>,
+
+        /// Fix mechanical problems in place
+        #[arg(long)]
+        fix: bool,
+
+        /// Output format (--json implies json)
+        #[arg(long, default_value = "stylish", value_parser = ["stylish", "json", "github"])]
+        format: String,
+    },
+
+    /// Print the module graph for entries without bundling
+    Graph {
+        /// Entry point file(s)
+        #[arg(required = true)]
+        entries: Vec<PathBuf>,
+
+        /// External packages (listed as externals, not followed)
+        #[arg(long, value_delimiter = ',')]
+        external: Vec<String>,
+
+        /// Import path aliases (e.g., --alias @=./src)
+        #[arg(long = "alias", value_delimiter = ',')]
+        aliases: Vec<String>,
+
+        /// Platform used for resolution: node or browser
+        #[arg(long, default_value = "node")]
+        platform: String,
     },
 
     /// Bundle JavaScript/TypeScript modules
     Bundle {
-        /// Entry point file
-        entry: PathBuf,
+        /// Entry point file(s); several entries require --outdir
+        #[arg(required = true)]
+        entries: Vec<PathBuf>,
 
         /// Output file (if not specified, prints to stdout)
         #[arg(long, short = 'o')]
         outfile: Option<PathBuf>,
 
+        /// Output directory: writes a chunk per entry, common chunks for
+        /// shared modules, a shared runtime.js and manifest.json
+        #[arg(long, conflicts_with_all = ["outfile", "splitting"])]
+        outdir: Option<PathBuf>,
+
         /// Output format: esm, cjs, or iife
         #[arg(long, default_value = "esm")]
         format: String,
@@ -219,6 +391,10 @@ enum Commands {
         #[arg(long, value_delimiter = ',')]
         define: Vec<String>,
 
+        /// Mode (e.g. "development", "production") — controls `import.meta.env` and which .env files are loaded
+        #[arg(long, short = 'm', default_value = "production")]
+        mode: String,
+
         /// Import path aliases (e.g., --alias @=./src)
         #[arg(long = "alias", value_delimiter = ',')]
         aliases: Vec<String>,
@@ -226,6 +402,45 @@ enum Commands {
         /// Banner text to prepend to output
         #[arg(long)]
         banner: Option<String>,
+
+        /// Platform the bundle runs on: node or browser
+        #[arg(long, default_value = "node")]
+        platform: String,
+
+        /// Handling of a Node.js built-in in browser builds: empty, error, or
+        /// a polyfill package (e.g., --builtin fs=empty,path=path-browserify)
+        #[arg(long = "builtin", value_delimiter = ',')]
+        builtins: Vec<String>,
+
+        /// Download http(s) and data: URL imports and bundle them
+        /// (dynamic import() of URLs is always left as-is)
+        #[arg(long)]
+        vendor_urls: bool,
+
+        /// Bare package imports: bundle, or external to keep every
+        /// node_modules import as an import statement (library builds)
+        #[arg(long, default_value = "bundle")]
+        packages: String,
+
+        /// Write both <outfile>.mjs and <outfile>.cjs and suggest
+        /// package.json exports for them
+        #[arg(long, requires = "outfile", conflicts_with = "splitting")]
+        dual: bool,
+
+        /// Generate TypeScript declarations (.d.ts) next to the output using
+        /// tsc; cached so unchanged sources skip regeneration
+        #[arg(long)]
+        dts: bool,
+
+        /// Report each module's and package's share of the output size and
+        /// write an HTML treemap (bundle-analysis.html) next to the output
+        #[arg(long, conflicts_with = "dual")]
+        analyze: bool,
+
+        /// Reproducible output: no absolute paths or discovery-order
+        /// dependent ordering (implied by SOURCE_DATE_EPOCH)
+        #[arg(long)]
+        deterministic: bool,
     },
 
     /// Start development server with HMR, or run the "dev" script from package.json
@@ -252,6 +467,10 @@ enum Commands {
         /// Mode (e.g. "development", "production") — controls which .env files are loaded
         #[arg(long, short = 'm', default_value = "development")]
         mode: String,
+
+        /// Render pages on the server with this entry (exports `render(url)`)
+        #[arg(long, value_name = "ENTRY")]
+        ssr: Option<PathBuf>,
     },
 
     /// Build the project
@@ -260,6 +479,10 @@ enum Commands {
         #[arg(long)]
         force: bool,
 
+        /// Remove node output directories before building (implies --force)
+        #[arg(long)]
+        clean: bool,
+
         /// Dry run (plan only, don't execute)
         #[arg(long)]
         dry_run: bool,
@@ -287,6 +510,34 @@ enum Commands {
         #[arg(long, default_value = "100")]
         debounce_ms: u32,
 
+        /// Show an interactive dashboard in watch mode (needs a terminal)
+        ///
+        /// Lists nodes with cache status and durations, type errors and a
+        /// scrolling log. Falls back to plain output when stdout isn't a tty.
+        #[arg(long, requires = "watch")]
+        ui: bool,
+
+        /// Only in workspace packages matching a name or glob (repeatable;
+        /// `pkg...` adds its dependencies, `...pkg` its dependents)
+        #[arg(long = "filter", value_name = "PKG", conflicts_with = "watch")]
+        filter: Vec<String>,
+
+        /// Only in workspace packages changed since a git ref, plus their dependents
+        #[arg(long, value_name = "REF", conflicts_with = "watch")]
+        since: Option<String>,
+
+        /// Run script nodes through an external executor command
+        ///
+        /// The command runs once per script node with HOWTH_REMOTE_JOB (job
+        /// manifest), HOWTH_REMOTE_UPLOAD (changed inputs), HOWTH_REMOTE_OUTPUT
+        /// (where to put outputs) and HOWTH_REMOTE_COMMAND set, e.g. an SSH,
+        /// container or build farm wrapper.
+        #[arg(long, value_name = "CMD", conflicts_with = "watch")]
+        remote_exec: Option<String>,
+
+        #[command(subcommand)]
+        build_cmd: Option<BuildCommands>,
+
         /// Targets to build (e.g., "typecheck" or "transpile,typecheck")
         ///
         /// Without --watch: empty means all targets.
@@ -306,6 +557,13 @@ enum Commands {
         /// Force exit after tests complete (useful when tests leave open handles)
         #[arg(long)]
         exit: bool,
+        /// Keep temporary directories made by test.tmpdir() for debugging
+        #[arg(long)]
+        keep_tmp: bool,
+        /// Globals for test files: `node`, or `dom` for a DOM-lite `window`
+        /// and `document` (a file's `@jest-environment` docblock overrides it)
+        #[arg(long, value_name = "ENV", default_value = "node", value_parser = ["node", "dom"])]
+        environment: String,
         /// Paths to test files or directories (default: discover in cwd)
         paths: Vec<String>,
     },
@@ -327,6 +585,9 @@ enum Commands {
         /// Link all workspace packages into node_modules
         #[arg(long)]
         link: bool,
+
+        #[command(subcommand)]
+        workspaces_cmd: Option<WorkspacesCommands>,
     },
 
     /// Run a package.json script directly (e.g., `howth test` instead of `howth run test`)
@@ -421,6 +682,10 @@ enum BenchCommands {
         /// Warmup duration in seconds
         #[arg(long, default_value_t = commands::bench::http::DEFAULT_WARMUP_SECS)]
         warmup: u32,
+
+        /// Requests sent per round trip on each connection (1 disables pipelining)
+        #[arg(long, default_value_t = commands::bench::http::DEFAULT_PIPELINING, value_parser = clap::value_parser!(u32).range(1..))]
+        pipelining: u32,
     },
 
     /// Benchmark bundler performance (howth vs bun vs esbuild)
@@ -439,6 +704,53 @@ enum BenchCommands {
     },
 }
 
+#[derive(clap::Subcommand, Debug)]
+enum BuildCommands {
+    /// Show the full output of a node's last runs
+    Logs {
+        /// Node ID or script name (e.g., "script:build" or "build")
+        node: String,
+
+        /// Only show the last N lines
+        #[arg(long, short = 'n', value_name = "N")]
+        tail: Option<usize>,
+
+        /// Keep printing new output, following into the next run
+        #[arg(long, short = 'f')]
+        follow: bool,
+
+        /// List the kept log files instead of showing one
+        #[arg(long, conflicts_with_all = ["tail", "follow"])]
+        list: bool,
+    },
+}
+
+#[derive(clap::Subcommand, Debug)]
+enum WorkspacesCommands {
+    /// Run a package.json script in every workspace package
+    Run {
+        /// Script name (e.g., "build", "test")
+        script: String,
+
+        /// Skip packages that don't define the script instead of failing
+        #[arg(long)]
+        if_present: bool,
+
+        /// Only in workspace packages matching a name or glob (repeatable;
+        /// `pkg...` adds its dependencies, `...pkg` its dependents)
+        #[arg(long = "filter", value_name = "PKG")]
+        filter: Vec<String>,
+
+        /// Only in workspace packages changed since a git ref, plus their dependents
+        #[arg(long, value_name = "REF")]
+        since: Option<String>,
+
+        /// Arguments to pass to the script (after --)
+        #[arg(last = true)]
+        args: Vec<String>,
+    },
+}
+
 #[derive(clap::Subcommand, Debug)]
 enum WatchCommands {
     /// Start watching directories
@@ -446,6 +758,21 @@ enum WatchCommands {
         /// Directories to watch (defaults to current directory)
         #[arg(default_value = ".")]
         roots: Vec<PathBuf>,
+
+        /// Extra glob to ignore (gitignore syntax, repeatable).
+        /// node_modules, .git, dist and .gitignore entries are always ignored.
+        #[arg(long = "ignore", value_name = "GLOB")]
+        ignore: Vec<String>,
+
+        /// File event source. `auto` uses native notifications and falls
+        /// back to polling if they fail; use `poll` on network filesystems
+        /// and Docker volumes.
+        #[arg(long, default_value = "auto", value_parser = ["auto", "native", "poll"])]
+        backend: String,
+
+        /// Rescan interval for the polling backend, in milliseconds
+        #[arg(long, value_name = "MS")]
+        poll_interval: Option<u64>,
     },
 
     /// Stop the file watcher
@@ -453,6 +780,12 @@ enum WatchCommands {
 
     /// Show watcher status
     Status,
+
+    /// Stream file events from the running watcher until interrupted
+    Events {
+        /// Only show events under these paths (defaults to all watched roots)
+        paths: Vec<PathBuf>,
+    },
 }
 
 #[derive(clap::Subcommand, Debug)]
@@ -477,12 +810,32 @@ enum PkgCommands {
         /// Save as devDependency (-D is shorthand for --save-dev)
         #[arg(short = 'D', long = "save-dev", conflicts_with = "deps")]
         save_dev: bool,
+
+        /// Install globally and link executables into the global bin dir
+        #[arg(short = 'g', long, conflicts_with_all = ["deps", "save_dev"])]
+        global: bool,
+
+        /// Install missing peer dependencies (default: on, or `.npmrc`'s
+        /// `auto-install-peers`); `--auto-install-peers=false` only records them
+        #[arg(long, num_args = 0..=1, default_missing_value = "true")]
+        auto_install_peers: Option<bool>,
     },
 
     /// Remove packages from the project
     Remove {
         /// Package names to remove (e.g., "react", "lodash")
         packages: Vec<String>,
+
+        /// Remove globally installed packages and their executables
+        #[arg(short = 'g', long)]
+        global: bool,
+    },
+
+    /// List the project's top-level packages
+    Ls {
+        /// List globally installed packages instead
+        #[arg(short = 'g', long)]
+        global: bool,
     },
 
     /// Update packages to latest versions
@@ -493,9 +846,13 @@ enum PkgCommands {
         /// Update to latest version, ignoring semver ranges
         #[arg(long)]
         latest: bool,
+
+        /// Choose which outdated packages to update from a list
+        #[arg(short, long)]
+        interactive: bool,
     },
 
-    /// Show outdated packages
+    /// Show outdated packages (exits 1 if any are outdated)
     Outdated,
 
     /// Publish package to npm registry
@@ -515,6 +872,29 @@ enum PkgCommands {
         /// Custom registry URL
         #[arg(long)]
         registry: Option<String>,
+
+        /// One-time password for accounts with 2FA (prompted if required)
+        #[arg(long)]
+        otp: Option<String>,
+
+        /// Publish with a signed provenance statement (from supported CI)
+        #[arg(long)]
+        provenance: bool,
+
+        /// Don't run the prepublishOnly script
+        #[arg(long)]
+        ignore_scripts: bool,
+    },
+
+    /// Create a tarball of the package, as it would be published
+    Pack {
+        /// Show the tarball contents without writing it
+        #[arg(long)]
+        dry_run: bool,
+
+        /// Directory to write the tarball to (defaults to the package root)
+        #[arg(long, value_name = "DIR")]
+        pack_destination: Option<PathBuf>,
     },
 
     /// Show the dependency graph
@@ -531,7 +911,7 @@ enum PkgCommands {
         #[arg(long, default_value = "25")]
         max_depth: u32,
 
-        /// Output format: "tree" or "list"
+        /// Output format: "tree", "list", "npm-ls-json", "dot" or "mermaid"
         #[arg(long, default_value = "tree")]
         format: String,
     },
@@ -555,6 +935,14 @@ enum PkgCommands {
         #[arg(long)]
         parent: Option<PathBuf>,
 
+        /// Extra export conditions to match, highest priority first (e.g., development,worker)
+        #[arg(long, value_delimiter = ',', conflicts_with = "why")]
+        conditions: Vec<String>,
+
+        /// Platform condition to simulate: "node" or "browser"
+        #[arg(long, value_parser = ["node", "browser"], conflicts_with = "why")]
+        platform: Option<String>,
+
         /// Show dependency chain instead of resolution path
         #[arg(long)]
         why: bool,
@@ -582,6 +970,10 @@ enum PkgCommands {
         /// Include resolver trace for subpath resolution (--why only)
         #[arg(long, requires = "why")]
         trace: bool,
+
+        /// List every package that depends on the target instead of chains (--why only)
+        #[arg(long, requires = "why")]
+        dependents: bool,
     },
 
     /// Run package health diagnostics
@@ -619,10 +1011,50 @@ enum PkgCacheCommands {
 
     /// Remove unused cached packages
     Prune,
+
+    /// Deduplicate cached files into a content-addressed store (hard links)
+    Dedupe,
+}
+
+/// Check if a command talks to the daemon, so it is started first.
+fn requires_daemon(command: Option<&Commands>) -> bool {
+    matches!(
+        command,
+        Some(
+            Commands::Run { daemon: true, .. }
+                | Commands::Install { check: false, .. }
+                | Commands::Pkg {
+                    pkg_cmd: PkgCommands::Add { .. }
+                        | PkgCommands::Remove { .. }
+                        | PkgCommands::Update { .. }
+                        | PkgCommands::Outdated
+                        | PkgCommands::Publish { .. }
+                        | PkgCommands::Pack { .. }
+                        | PkgCommands::Graph { .. }
+                        | PkgCommands::Cache { .. }
+                        | PkgCommands::Explain { .. }
+                        | PkgCommands::Doctor { .. }
+                }
+                | Commands::Build { .. }
+                | Commands::Watch { .. }
+        )
+    )
+}
+
+/// Full subcommand path, e.g. `pkg install`.
+fn command_name(matches: &clap::ArgMatches) -> String {
+    let mut names = Vec::new();
+    let mut current = matches;
+    while let Some((name, sub)) = current.subcommand() {
+        names.push(name);
+        current = sub;
+    }
+    names.join(" ")
 }
 
 fn main() -> Result<()> {
-    let cli = Cli::parse();
+    let matches = Cli::command().get_matches();
+    let cli = Cli::from_arg_matches(&matches).unwrap_or_else(|e| e.exit());
 
     // Determine working directory
     let cwd = cli
@@ -632,20 +1064,74 @@ fn main() -> Result<()> {
 
     // Build config
     let config = Config::new(cwd.clone())
+        .with_channel(cli.channel)
         .with_verbosity(cli.verbose)
         .with_json_logs(cli.json);
 
+    // Only commands that reach the dispatch below log to stderr; the rest
+    // handle their own output. Trace export (opt-in) covers every command.
+    let log_to_stderr = matches!(
+        cli.command,
+        None | Some(Commands::Version | Commands::Test { .. })
+    );
+    let telemetry = telemetry::Telemetry::init(&cwd);
+    // The daemon runs unattended, so it keeps a log for `doctor --bundle`
+    let log_file = matches!(cli.command, Some(Commands::Daemon))
+        .then(|| {
+            let path = fastnode_core::paths::daemon_log_path(cli.channel);
+            logging::open_log_file(&path)
+                .map_err(|e| eprintln!("warning: cannot open {}: {e}", path.display()))
+                .ok()
+        })
+        .flatten();
+    if log_to_stderr || telemetry.is_some() || log_file.is_some() {
+        logging::init(
+            config.verbosity,
+            config.json_logs,
+            log_to_stderr,
+            log_file,
+            telemetry.as_ref().map(telemetry::Telemetry::tracer),
+        );
+    }
+    let _command_span =
+        tracing::info_span!("command", cmd = %command_name(&matches), cwd = %cwd.display())
+            .entered();
+
+    if !cli.no_autostart && requires_daemon(cli.command.as_ref()) {
+        commands::daemon::ensure_running(cli.channel, cli.json);
+    }
+
     // Commands that handle their own output (JSON to stdout, no logging)
-    if matches!(cli.command, Some(Commands::Doctor)) {
-        return commands::doctor::run(&cwd, Channel::Stable, cli.json);
+    if let Some(Commands::Doctor {
+        bundle,
+        output,
+        yes,
+    }) = &cli.command
+    {
+        if *bundle {
+            return commands::doctor::run_bundle(&cwd, cli.channel, output.as_deref(), *yes);
+        }
+        return commands::doctor::run(&cwd, cli.channel, cli.json);
     }
 
     if let Some(Commands::Init { yes }) = &cli.command {
         return commands::init::run(&cwd, *yes, cli.json);
     }
 
-    if let Some(Commands::Create { template, name }) = &cli.command {
-        return commands::create::run(&cwd, template, name.as_deref(), cli.json);
+    if let Some(Commands::Create {
+        template,
+        name,
+        install,
+    }) = &cli.command
+    {
+        return commands::create::run(
+            &cwd,
+            template,
+            name.as_deref(),
+            *install,
+            cli.channel,
+            cli.json,
+        );
     }
 
     if let Some(Commands::Link {
@@ -655,16 +1141,41 @@ fn main() -> Result<()> {
     }) = &cli.command
     {
         if *list {
-            return commands::link::list(Channel::Stable, cli.json);
+            return commands::link::list(cli.channel, cli.json);
         }
-        return commands::link::link(&cwd, package.as_deref(), *save, Channel::Stable, cli.json);
+        return commands::link::link(&cwd, package.as_deref(), *save, cli.channel, cli.json);
     }
 
     if let Some(Commands::Unlink { package }) = &cli.command {
-        return commands::link::unlink(&cwd, package.as_deref(), Channel::Stable, cli.json);
+        return commands::link::unlink(&cwd, package.as_deref(), cli.channel, cli.json);
     }
 
-    if let Some(Commands::Workspaces { link }) = &cli.command {
+    if let Some(Commands::Workspaces {
+        link,
+        workspaces_cmd,
+    }) = &cli.command
+    {
+        if let Some(WorkspacesCommands::Run {
+            script,
+            if_present,
+            filter,
+            since,
+            args,
+        }) = workspaces_cmd
+        {
+            let filter = commands::workspaces::WorkspaceFilter {
+                filters: filter.clone(),
+                since: since.clone(),
+            };
+            return commands::workspaces::run_script(
+                &cwd,
+                script,
+                args,
+                *if_present,
+                &filter,
+                cli.json,
+            );
+        }
         if *link {
             return commands::workspaces::link(&cwd, cli.json);
         }
@@ -685,60 +1196,98 @@ fn main() -> Result<()> {
                 false, // native
                 false, // node
                 false, // local
-                Channel::Stable,
+                false, // if_present
+                false, // capture
+                None,  // graph_depth
+                cli.channel,
                 cli.json,
             );
         }
     }
 
-    if let Some(Commands::Bench { bench_cmd }) = &cli.command {
+    if let Some(Commands::Bench {
+        bench_cmd,
+        save,
+        baseline,
+        threshold,
+        history_dir,
+    }) = &cli.command
+    {
+        let history = commands::bench::history::HistoryOptions {
+            save: *save,
+            baseline: baseline.clone(),
+            threshold_pct: *threshold,
+            dir: history_dir.clone(),
+        };
         return match bench_cmd {
             BenchCommands::Smoke {
                 iters,
                 warmup,
                 size,
-            } => commands::bench::smoke::run(*iters, *warmup, *size, cli.json),
+            } => commands::bench::smoke::run(*iters, *warmup, *size, &history, cli.json),
             BenchCommands::Transpile {
                 iters,
                 warmup,
                 project,
-            } => commands::bench::build::run_transpile(*iters, *warmup, project.clone(), cli.json),
+            } => commands::bench::build::run_transpile(
+                *iters,
+                *warmup,
+                project.clone(),
+                &history,
+                cli.json,
+            ),
             BenchCommands::Devloop {
                 iters,
                 warmup,
                 project,
-            } => commands::bench::build::run_devloop(*iters, *warmup, project.clone(), cli.json),
+            } => commands::bench::build::run_devloop(
+                *iters,
+                *warmup,
+                project.clone(),
+                &history,
+                cli.json,
+            ),
             BenchCommands::Install {
                 iters,
                 warmup,
                 project,
-            } => commands::bench::install::run(*iters, *warmup, project.clone(), cli.json),
+            } => {
+                commands::bench::install::run(*iters, *warmup, project.clone(), &history, cli.json)
+            }
             BenchCommands::TestRun { iters, warmup } => {
-                commands::bench::test::run(*iters, *warmup, cli.json)
+                commands::bench::test::run(*iters, *warmup, &history, cli.json)
             }
             BenchCommands::Http {
                 duration,
                 connections,
                 warmup,
-            } => commands::bench::http::run(*duration, *connections, *warmup, cli.json),
+                pipelining,
+            } => commands::bench::http::run(
+                *duration,
+                *connections,
+                *warmup,
+                *pipelining,
+                &history,
+                cli.json,
+            ),
             BenchCommands::Bundler {
                 modules,
                 iters,
                 warmup,
-            } => commands::bench::bundler::run(*modules, *iters, *warmup, cli.json),
+            } => commands::bench::bundler::run(*modules, *iters, *warmup, &history, cli.json),
         };
     }
 
     if matches!(cli.command, Some(Commands::Daemon)) {
-        return commands::daemon::run(Channel::Stable, cli.json);
+        return commands::daemon::run(cli.channel, cli.json);
     }
 
     if matches!(cli.command, Some(Commands::Stop)) {
-        return commands::stop::run(Channel::Stable, cli.json);
+        return commands::stop::run(cli.channel, cli.json);
     }
 
     if matches!(cli.command, Some(Commands::Ping)) {
-        return commands::ping::run(Channel::Stable, cli.json);
+        return commands::ping::run(cli.channel, cli.json);
     }
 
     if let Some(Commands::Run {
@@ -748,9 +1297,29 @@ fn main() -> Result<()> {
         native,
         node,
         local,
+        if_present,
+        capture,
+        depth,
+        full_graph,
+        filter,
+        since,
         args,
     }) = &cli.command
     {
+        let filter = commands::workspaces::WorkspaceFilter {
+            filters: filter.clone(),
+            since: since.clone(),
+        };
+        if filter.is_active() {
+            return commands::workspaces::run_script(
+                &cwd,
+                entry,
+                args,
+                *if_present,
+                &filter,
+                cli.json,
+            );
+        }
         return commands::run::run(
             &cwd,
             entry,
@@ -760,18 +1329,65 @@ fn main() -> Result<()> {
             *native,
             *node,
             *local,
-            Channel::Stable,
+            *if_present,
+            *capture,
+            commands::run::graph_depth(*depth, *full_graph),
+            cli.channel,
             cli.json,
         );
     }
 
-    if let Some(Commands::Exec { binary, args }) = &cli.command {
-        return commands::exec::run(&cwd, binary, args, cli.json);
+    if let Some(Commands::Exec {
+        binary,
+        yes,
+        capture,
+        args,
+    }) = &cli.command
+    {
+        return commands::exec::run(&cwd, binary, args, *yes, *capture, cli.channel, cli.json);
+    }
+
+    if let Some(Commands::Dlx {
+        package,
+        command,
+        args,
+    }) = &cli.command
+    {
+        return commands::dlx::run(
+            &cwd,
+            command,
+            args,
+            package.as_deref(),
+            cli.channel,
+            cli.json,
+        );
+    }
+
+    if let Some(Commands::Watch {
+        watch_cmd: WatchCommands::Events { paths },
+    }) = &cli.command
+    {
+        let absolute_paths: Vec<PathBuf> = paths
+            .iter()
+            .map(|p| {
+                if p.is_absolute() {
+                    p.clone()
+                } else {
+                    cwd.join(p)
+                }
+            })
+            .collect();
+        return commands::watch::run_events(&absolute_paths, cli.channel, cli.json);
     }
 
     if let Some(Commands::Watch { watch_cmd }) = &cli.command {
         let action = match watch_cmd {
-            WatchCommands::Start { roots } => {
+            WatchCommands::Start {
+                roots,
+                ignore,
+                backend,
+                poll_interval,
+            } => {
                 // Convert relative paths to absolute
                 let absolute_roots: Vec<PathBuf> = roots
                     .iter()
@@ -785,12 +1401,20 @@ fn main() -> Result<()> {
                     .collect();
                 commands::watch::WatchAction::Start {
                     roots: absolute_roots,
+                    ignore: ignore.clone(),
+                    backend: match backend.as_str() {
+                        "native" => fastnode_proto::WatchBackend::Native,
+                        "poll" => fastnode_proto::WatchBackend::Poll,
+                        _ => fastnode_proto::WatchBackend::Auto,
+                    },
+                    poll_interval_ms: *poll_interval,
                 }
             }
             WatchCommands::Stop => commands::watch::WatchAction::Stop,
             WatchCommands::Status => commands::watch::WatchAction::Status,
+            WatchCommands::Events { .. } => unreachable!("handled above"),
         };
-        return commands::watch::run(action, Channel::Stable, cli.json);
+        return commands::watch::run(action, cli.channel, cli.json);
     }
 
     if let Some(Commands::Pkg { pkg_cmd }) = &cli.command {
@@ -801,12 +1425,15 @@ fn main() -> Result<()> {
                 dev,
                 optional,
                 save_dev,
+                global,
+                auto_install_peers,
             } => {
                 if *deps {
                     commands::pkg::PkgAction::AddDeps {
                         cwd: cwd.clone(),
                         include_dev: *dev,
                         include_optional: *optional,
+                        auto_install_peers: *auto_install_peers,
                     }
                 } else if specs.is_empty() {
                     // No specs and no --deps: error
@@ -817,10 +1444,12 @@ fn main() -> Result<()> {
                         specs: specs.clone(),
                         cwd: cwd.clone(),
                         save_dev: *save_dev,
+                        global: *global,
+                        auto_install_peers: *auto_install_peers,
                     }
                 }
             }
-            PkgCommands::Remove { packages } => {
+            PkgCommands::Remove { packages, global } => {
                 if packages.is_empty() {
                     eprintln!("error: specify at least one package to remove");
                     std::process::exit(2);
@@ -828,12 +1457,22 @@ fn main() -> Result<()> {
                 commands::pkg::PkgAction::Remove {
                     packages: packages.clone(),
                     cwd: cwd.clone(),
+                    global: *global,
                 }
             }
-            PkgCommands::Update { packages, latest } => commands::pkg::PkgAction::Update {
+            PkgCommands::Ls { global } => commands::pkg::PkgAction::List {
+                cwd: cwd.clone(),
+                global: *global,
+            },
+            PkgCommands::Update {
+                packages,
+                latest,
+                interactive,
+            } => commands::pkg::PkgAction::Update {
                 packages: packages.clone(),
                 cwd: cwd.clone(),
                 latest: *latest,
+                interactive: *interactive,
             },
             PkgCommands::Outdated => commands::pkg::PkgAction::Outdated { cwd: cwd.clone() },
             PkgCommands::Publish {
@@ -841,12 +1480,27 @@ fn main() -> Result<()> {
                 tag,
                 access,
                 registry,
+                otp,
+                provenance,
+                ignore_scripts,
             } => commands::pkg::PkgAction::Publish {
                 cwd: cwd.clone(),
                 dry_run: *dry_run,
                 tag: tag.clone(),
                 access: access.clone(),
                 registry: registry.clone(),
+                otp: otp.clone(),
+                provenance: *provenance,
+                ignore_scripts: *ignore_scripts,
+            },
+            PkgCommands::Pack {
+                dry_run,
+                pack_destination,
+            } => commands::pkg::PkgAction::Pack {
+                cwd: cwd.clone(),
+                destination: pack_destination.as_ref().map(|d| cwd.join(d)),
+                dry_run: *dry_run,
+                source_date_epoch: fastnode_core::deterministic::source_date_epoch(),
             },
             PkgCommands::Graph {
                 dev,
@@ -858,16 +1512,25 @@ fn main() -> Result<()> {
                 include_dev: *dev,
                 include_optional: !*no_optional,
                 max_depth: *max_depth,
-                format: format.clone(),
+                format: commands::pkg_graph::parse_format(format).unwrap_or_else(|| {
+                    eprintln!(
+                        "error: invalid format '{}'. Use: tree, list, npm-ls-json, dot, or mermaid",
+                        format
+                    );
+                    std::process::exit(2);
+                }),
             },
             PkgCommands::Cache { cache_cmd } => match cache_cmd {
                 PkgCacheCommands::Ls => commands::pkg::PkgAction::CacheList,
                 PkgCacheCommands::Prune => commands::pkg::PkgAction::CachePrune,
+                PkgCacheCommands::Dedupe => commands::pkg::PkgAction::CacheDedupe,
             },
             PkgCommands::Explain {
                 specifier,
                 kind,
                 parent,
+                conditions,
+                platform,
                 why,
                 dev,
                 no_optional,
@@ -875,6 +1538,7 @@ fn main() -> Result<()> {
                 max_chains,
                 format,
                 trace,
+                dependents,
             } => {
                 if *why {
                     commands::pkg::PkgAction::Why {
@@ -888,6 +1552,7 @@ fn main() -> Result<()> {
                         include_trace: *trace,
                         trace_kind: Some(kind.clone()),
                         trace_parent: parent.clone(),
+                        dependents: *dependents,
                     }
                 } else {
                     commands::pkg::PkgAction::Explain {
@@ -895,6 +1560,8 @@ fn main() -> Result<()> {
                         cwd: cwd.clone(),
                         parent: parent.clone().unwrap_or_else(|| cwd.clone()),
                         kind: kind.clone(),
+                        conditions: conditions.clone(),
+                        platform: platform.clone(),
                     }
                 }
             }
@@ -915,7 +1582,7 @@ fn main() -> Result<()> {
                 format: format.clone(),
             },
         };
-        return commands::pkg::run(action, Channel::Stable, cli.json);
+        return commands::pkg::run(action, cli.channel, cli.json);
     }
 
     if let Some(Commands::Install {
@@ -924,21 +1591,37 @@ fn main() -> Result<()> {
         no_dev,
         optional,
         no_optional,
+        auto_install_peers,
+        check,
+        check_sample,
     }) = &cli.command
     {
+        if *check {
+            commands::pkg_check::run_install_check(
+                &cwd,
+                *dev && !*no_dev,
+                *optional && !*no_optional,
+                check_sample.unwrap_or(fastnode_core::pkg::DEFAULT_CHECK_SAMPLE),
+                cli.channel,
+                cli.json,
+            );
+            return Ok(());
+        }
         let action = commands::pkg::PkgAction::Install {
             cwd: cwd.clone(),
             frozen: *frozen_lockfile,
             include_dev: *dev && !*no_dev,
             include_optional: *optional && !*no_optional,
+            auto_install_peers: *auto_install_peers,
         };
-        return commands::pkg::run(action, Channel::Stable, cli.json);
+        return commands::pkg::run(action, cli.channel, cli.json);
     }
 
     // Handle bundle command
     if let Some(Commands::Bundle {
-        entry,
+        entries,
         outfile,
+        outdir,
         format,
         minify,
         no_mangle,
@@ -948,19 +1631,65 @@ fn main() -> Result<()> {
         no_treeshake,
         splitting,
         define,
+        mode,
         aliases,
         banner,
+        platform,
+        builtins,
+        vendor_urls,
+        packages,
+        dual,
+        dts,
+        analyze,
+        deterministic,
     }) = &cli.command
     {
         let bundle_format = commands::bundle::parse_format(format).unwrap_or_else(|| {
             eprintln!("error: invalid format '{}'. Use: esm, cjs, or iife", format);
             std::process::exit(2);
         });
+        let bundle_platform =
+            fastnode_core::bundler::Platform::parse(platform).unwrap_or_else(|| {
+                eprintln!(
+                    "error: invalid platform '{}'. Use: node or browser",
+                    platform
+                );
+                std::process::exit(2);
+            });
+        let packages_mode =
+            fastnode_core::bundler::PackagesMode::parse(packages).unwrap_or_else(|| {
+                eprintln!(
+                    "error: invalid packages mode '{}'. Use: bundle or external",
+                    packages
+                );
+                std::process::exit(2);
+            });
+        let builtins = commands::bundle::parse_builtins(builtins).unwrap_or_else(|entry| {
+            eprintln!(
+                "error: invalid --builtin '{}'. Use: <module>=empty|error|<polyfill>",
+                entry
+            );
+            std::process::exit(2);
+        });
+
+        if entries.len() > 1 && outdir.is_none() {
+            eprintln!("error: multiple entry points require --outdir");
+            std::process::exit(2);
+        }
+        if *dts && outfile.is_none() && outdir.is_none() {
+            eprintln!("error: --dts requires --outfile or --outdir");
+            std::process::exit(2);
+        }
+        if *analyze && outfile.is_none() && outdir.is_none() {
+            eprintln!("error: --analyze requires --outfile or --outdir");
+            std::process::exit(2);
+        }
 
         let action = commands::bundle::BundleAction {
-            entry: entry.clone(),
+            entries: entries.clone(),
             cwd: cwd.clone(),
             outfile: outfile.clone(),
+            outdir: outdir.clone(),
             format: bundle_format,
             minify: *minify,
             mangle: *minify && !*no_mangle,
@@ -969,12 +1698,58 @@ fn main() -> Result<()> {
             treeshake: *treeshake && !*no_treeshake,
             splitting: *splitting,
             define: define.clone(),
+            mode: mode.clone(),
             alias: aliases.clone(),
             banner: banner.clone(),
+            platform: bundle_platform,
+            builtins,
+            vendor_urls: *vendor_urls,
+            packages: packages_mode,
+            dual: *dual,
+            dts: *dts,
+            analyze: *analyze,
+            deterministic: fastnode_core::deterministic::is_deterministic(*deterministic),
         };
         return commands::bundle::run(action, cli.json);
     }
 
+    // Handle graph command
+    if let Some(Commands::Fmt { paths, check }) = &cli.command {
+        commands::fmt::run(&cwd, paths, *check, cli.json);
+        return Ok(());
+    }
+
+    if let Some(Commands::Lint { paths, fix, format }) = &cli.command {
+        let format = if cli.json { "json" } else { format.as_str() };
+        commands::lint::run(&cwd, paths, *fix, format);
+        return Ok(());
+    }
+
+    if let Some(Commands::Graph {
+        entries,
+        external,
+        aliases,
+        platform,
+    }) = &cli.command
+    {
+        let graph_platform =
+            fastnode_core::bundler::Platform::parse(platform).unwrap_or_else(|| {
+                eprintln!(
+                    "error: invalid platform '{}'. Use: node or browser",
+                    platform
+                );
+                std::process::exit(2);
+            });
+        let action = commands::graph::GraphAction {
+            entries: entries.clone(),
+            cwd: cwd.clone(),
+            external: external.clone(),
+            alias: aliases.clone(),
+            platform: graph_platform,
+        };
+        return commands::graph::run(action, cli.json);
+    }
+
     // Handle dev command
     if let Some(Commands::Dev {
         entry,
@@ -983,6 +1758,7 @@ fn main() -> Result<()> {
         open,
         config,
         mode,
+        ssr,
     }) = &cli.command
     {
         match entry {
@@ -998,6 +1774,8 @@ fn main() -> Result<()> {
                     open: *open,
                     config: config.clone(),
                     mode: mode.clone(),
+                    ssr: ssr.clone(),
+                    channel: cli.channel,
                 };
 
                 let rt = tokio::runtime::Runtime::new().unwrap();
@@ -1014,7 +1792,10 @@ fn main() -> Result<()> {
                     false, // native
                     false, // node
                     false, // local
-                    Channel::Stable,
+                    false, // if_present
+                    false, // capture
+                    None,  // graph_depth
+                    cli.channel,
                     cli.json,
                 );
             }
@@ -1024,15 +1805,41 @@ fn main() -> Result<()> {
     // Handle build command early (like other daemon commands)
     if let Some(Commands::Build {
         force,
+        clean,
         dry_run,
         max_parallel,
         profile,
         why,
         watch,
         debounce_ms,
+        ui,
+        filter,
+        since,
+        remote_exec,
+        build_cmd,
         targets,
     }) = &cli.command
     {
+        if let Some(BuildCommands::Logs {
+            node,
+            tail,
+            follow,
+            list,
+        }) = build_cmd
+        {
+            return commands::build::logs(&cwd, node, *tail, *follow, *list, cli.json);
+        }
+
+        let filter = commands::workspaces::WorkspaceFilter {
+            filters: filter.clone(),
+            since: since.clone(),
+        };
+        if filter.is_active() && cli.json {
+            eprintln!("error: --filter/--since and --json cannot be combined");
+            eprintln!("hint: --json requires exactly one output object; each selected package is built separately");
+            std::process::exit(2);
+        }
+
         // v3.0: --watch --json is disallowed (violates "one JSON object" contract)
         if *watch && cli.json {
             eprintln!("error: --watch and --json cannot be combined");
@@ -1064,28 +1871,35 @@ fn main() -> Result<()> {
 
         let action = commands::build::BuildAction {
             cwd: cwd.clone(),
-            force: *force,
+            force: *force || *clean,
+            clean: *clean,
             dry_run: *dry_run,
             max_parallel: *max_parallel,
             profile: *profile,
             why: *why,
             watch: *watch,
             debounce_ms: *debounce_ms,
+            ui: *ui,
             targets: effective_targets,
+            remote_exec: remote_exec.clone(),
         };
-        return commands::build::run(action, Channel::Stable, cli.json);
+        if filter.is_active() {
+            let packages = commands::workspaces::select_packages(&cwd, &filter, cli.json);
+            return commands::build::run_workspaces(action, &packages, cli.channel);
+        }
+        return commands::build::run(action, cli.channel, cli.json);
     }
 
-    // Initialize logging for other commands
-    logging::init(config.verbosity, config.json_logs);
-
     // Dispatch to command
     match cli.command {
         Some(Commands::Version) | None => commands::version::run(),
         Some(
-            Commands::Doctor
+            Commands::Doctor { .. }
             | Commands::Bench { .. }
             | Commands::Bundle { .. }
+            | Commands::Graph { .. }
+            | Commands::Fmt { .. }
+            | Commands::Lint { .. }
             | Commands::Create { .. }
             | Commands::Daemon
             | Commands::Stop
@@ -1096,6 +1910,7 @@ fn main() -> Result<()> {
             | Commands::Ping
             | Commands::Run { .. }
             | Commands::Exec { .. }
+            | Commands::Dlx { .. }
             | Commands::Script(_)
             | Commands::Watch { .. }
             | Commands::Workspaces { .. }
@@ -1109,11 +1924,22 @@ fn main() -> Result<()> {
             setup,
             timeout,
             exit,
+            keep_tmp,
+            environment,
             paths,
         }) => {
             let span = tracing::info_span!("test", cmd = "test", cwd = %cwd.display());
             let _guard = span.enter();
-            commands::test::run(&config, setup.as_deref(), timeout, exit, &paths)
+            commands::test::run(
+                &config,
+                setup.as_deref(),
+                timeout,
+                exit,
+                keep_tmp,
+                fastnode_proto::TestEnvironment::from_name(&environment).unwrap_or_default(),
+                &paths,
+                cli.json,
+            )
         }
     }
 }
@@ -0,0 +1,252 @@
+//! Progress rendering for long-running commands (install, add, build).
+//!
+//! On a terminal, a status line with a spinner, a progress bar, live counts
+//! and the items in flight is redrawn in place on stderr. Otherwise a plain
+//! status line is printed at most every [`PLAIN_INTERVAL`], so CI logs show
+//! that the command is alive without filling up with redraws. JSON output
+//! disables rendering entirely.
+
+use std::future::Future;
+use std::io::{IsTerminal, Write};
+use std::time::{Duration, Instant};
+
+/// How often the spinner advances while waiting.
+const TICK: Duration = Duration::from_millis(80);
+
+/// Minimum time between plain-text status lines.
+const PLAIN_INTERVAL: Duration = Duration::from_secs(2);
+
+const SPINNER: &[char] = &['⠋', '⠙', '⠹', '⠸', '⠼', '⠴', '⠦', '⠧', '⠇', '⠏'];
+const BAR_WIDTH: usize = 24;
+
+/// How progress is shown.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Mode {
+    /// Redraw a status line in place.
+    Tty,
+    /// Print periodic plain-text lines.
+    Plain,
+    /// Show nothing.
+    Hidden,
+}
+
+impl Mode {
+    /// Pick the mode for the current stderr.
+    #[must_use]
+    pub fn detect(json: bool) -> Self {
+        if json {
+            Self::Hidden
+        } else if std::io::stderr().is_terminal()
+            && std::env::var("TERM").map_or(true, |term| term != "dumb")
+        {
+            Self::Tty
+        } else {
+            Self::Plain
+        }
+    }
+}
+
+/// Progress of one operation, rendered to stderr.
+#[derive(Debug)]
+pub struct Progress {
+    mode: Mode,
+    label: String,
+    completed: u32,
+    total: u32,
+    /// Items in flight, in start order.
+    active: Vec<String>,
+    frame: usize,
+    drawn: bool,
+    last_plain: Option<Instant>,
+}
+
+impl Progress {
+    /// Progress for `label`, rendered for the current terminal.
+    #[must_use]
+    pub fn new(label: impl Into<String>, json: bool) -> Self {
+        Self::with_mode(label, Mode::detect(json))
+    }
+
+    /// Progress for `label`, rendered in `mode`.
+    #[must_use]
+    pub fn with_mode(label: impl Into<String>, mode: Mode) -> Self {
+        Self {
+            mode,
+            label: label.into(),
+            completed: 0,
+            total: 0,
+            active: Vec::new(),
+            frame: 0,
+            drawn: false,
+            last_plain: None,
+        }
+    }
+
+    /// An item started.
+    pub fn start(&mut self, item: &str) {
+        self.active.push(item.to_string());
+        self.render(false);
+    }
+
+    /// An item finished; `completed` and `total` are the running counts.
+    pub fn finish_item(&mut self, item: &str, completed: u32, total: u32) {
+        self.active.retain(|active| active != item);
+        self.completed = completed;
+        self.total = total;
+        self.render(completed == total);
+    }
+
+    /// Print a permanent line to stdout without garbling the status line.
+    pub fn println(&mut self, line: &str) {
+        self.clear();
+        println!("{line}");
+        self.render(false);
+    }
+
+    /// Advance the spinner (terminal) or print a status line if one is due.
+    pub fn tick(&mut self) {
+        self.frame = self.frame.wrapping_add(1);
+        self.render(false);
+    }
+
+    /// Remove the status line.
+    pub fn finish(&mut self) {
+        self.clear();
+    }
+
+    /// Run `future`, ticking the spinner until it completes.
+    pub async fn run<F: Future>(&mut self, future: F) -> F::Output {
+        tokio::pin!(future);
+        let mut ticker = tokio::time::interval(TICK);
+        loop {
+            tokio::select! {
+                output = &mut future => return output,
+                _ = ticker.tick() => self.tick(),
+            }
+        }
+    }
+
+    fn render(&mut self, force_plain: bool) {
+        match self.mode {
+            Mode::Hidden => {}
+            Mode::Tty => {
+                let line = self.status_line(terminal_width());
+                let mut err = std::io::stderr().lock();
+                let _ = write!(err, "\r\x1b[2K{line}");
+                let _ = err.flush();
+                self.drawn = true;
+            }
+            Mode::Plain => {
+                let due = self
+                    .last_plain
+                    .is_none_or(|last| last.elapsed() >= PLAIN_INTERVAL);
+                if due || force_plain {
+                    eprintln!("{}", self.plain_line());
+                    self.last_plain = Some(Instant::now());
+                }
+            }
+        }
+    }
+
+    fn clear(&mut self) {
+        if self.drawn {
+            let mut err = std::io::stderr().lock();
+            let _ = write!(err, "\r\x1b[2K");
+            let _ = err.flush();
+            self.drawn = false;
+        }
+    }
+
+    /// Terminal status line, at most `width` characters.
+    fn status_line(&self, width: usize) -> String {
+        let spinner = SPINNER[self.frame % SPINNER.len()];
+        let mut line = if self.total == 0 {
+            format!("{spinner} {}", self.label)
+        } else {
+            let filled = (BAR_WIDTH * self.completed as usize / self.total as usize).min(BAR_WIDTH);
+            format!(
+                "{spinner} {} [{}{}] {}/{}",
+                self.label,
+                "=".repeat(filled),
+                " ".repeat(BAR_WIDTH - filled),
+                self.completed,
+                self.total
+            )
+        };
+        if !self.active.is_empty() {
+            line.push(' ');
+            line.push_str(&self.active.join(", "));
+        }
+        truncate(&line, width)
+    }
+
+    /// Plain-text status line.
+    fn plain_line(&self) -> String {
+        let mut line = if self.total == 0 {
+            format!("{}: working", self.label)
+        } else {
+            format!("{}: {}/{}", self.label, self.completed, self.total)
+        };
+        if !self.active.is_empty() {
+            line.push_str(&format!(" ({})", self.active.join(", ")));
+        }
+        line
+    }
+}
+
+impl Drop for Progress {
+    fn drop(&mut self) {
+        self.clear();
+    }
+}
+
+/// Terminal width from `COLUMNS`, defaulting to 80.
+fn terminal_width() -> usize {
+    std::env::var("COLUMNS")
+        .ok()
+        .and_then(|columns| columns.parse().ok())
+        .filter(|&columns: &usize| columns > 0)
+        .unwrap_or(80)
+}
+
+/// Truncate to `width` characters, ending with an ellipsis when cut.
+fn truncate(line: &str, width: usize) -> String {
+    if line.chars().count() <= width {
+        return line.to_string();
+    }
+    let mut truncated: String = line.chars().take(width.saturating_sub(1)).collect();
+    truncated.push('…');
+    truncated
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_status_line() {
+        let mut progress = Progress::with_mode("build", Mode::Hidden);
+        assert_eq!(progress.status_line(80), "⠋ build");
+
+        progress.start("script:lint");
+        progress.finish_item("script:lint", 1, 4);
+        progress.start("typecheck");
+        assert_eq!(
+            progress.status_line(80),
+            "⠋ build [======                  ] 1/4 typecheck"
+        );
+        assert_eq!(progress.plain_line(), "build: 1/4 (typecheck)");
+
+        assert_eq!(progress.status_line(12), "⠋ build [==…");
+    }
+
+    #[test]
+    fn test_finish_item_removes_active() {
+        let mut progress = Progress::with_mode("install", Mode::Hidden);
+        progress.start("a");
+        progress.start("b");
+        progress.finish_item("a", 1, 2);
+        assert_eq!(progress.active, ["b"]);
+        assert_eq!(progress.plain_line(), "install: 1/2 (b)");
+    }
+}
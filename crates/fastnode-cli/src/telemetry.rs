@@ -0,0 +1,73 @@
+//! Opt-in OpenTelemetry trace export.
+//!
+//! When an OTLP endpoint is configured (see [`fastnode_core::telemetry`]),
+//! tracing spans are exported over OTLP/HTTP in addition to the regular log
+//! output. That covers the command itself (`command` span), daemon request
+//! handling (`daemon.request`), build nodes (`build.node`) and package
+//! installs (`pkg.install`).
+//!
+//! Spans are batched in the background and flushed when [`Telemetry`] is
+//! dropped. Commands that end with `std::process::exit` skip that flush, so
+//! their last batch may be lost.
+
+use fastnode_core::telemetry::TelemetryConfig;
+use opentelemetry::trace::TracerProvider as _;
+use opentelemetry_otlp::{SpanExporter, WithExportConfig, WithHttpConfig};
+use opentelemetry_sdk::trace::{SdkTracerProvider, Tracer};
+use opentelemetry_sdk::Resource;
+use std::path::Path;
+
+/// Instrumentation scope name for exported spans.
+const TRACER_NAME: &str = "howth";
+
+/// An active trace exporter. Flushes pending spans on drop.
+pub struct Telemetry {
+    provider: SdkTracerProvider,
+}
+
+impl Telemetry {
+    /// Start exporting if an endpoint is configured for `cwd`.
+    ///
+    /// Export problems never fail the command: a bad configuration is
+    /// reported on stderr and export stays off.
+    pub fn init(cwd: &Path) -> Option<Self> {
+        let config = TelemetryConfig::load(cwd)?;
+        let exporter = SpanExporter::builder()
+            .with_http()
+            .with_endpoint(&config.endpoint)
+            .with_headers(config.headers.into_iter().collect())
+            .build();
+        let exporter = match exporter {
+            Ok(exporter) => exporter,
+            Err(e) => {
+                eprintln!("warning: trace export disabled: {e}");
+                return None;
+            }
+        };
+
+        let resource = Resource::builder()
+            .with_service_name(config.service_name)
+            .with_attribute(opentelemetry::KeyValue::new(
+                "service.version",
+                fastnode_core::VERSION,
+            ))
+            .build();
+        let provider = SdkTracerProvider::builder()
+            .with_batch_exporter(exporter)
+            .with_resource(resource)
+            .build();
+        Some(Self { provider })
+    }
+
+    /// Tracer for the `tracing-opentelemetry` layer.
+    pub fn tracer(&self) -> Tracer {
+        self.provider.tracer(TRACER_NAME)
+    }
+}
+
+impl Drop for Telemetry {
+    fn drop(&mut self) {
+        // Flushes the batch; errors mean the collector was unreachable
+        let _ = self.provider.shutdown();
+    }
+}
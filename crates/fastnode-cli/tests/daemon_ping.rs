@@ -224,3 +224,43 @@ fn test_daemon_and_ping_json() {
         "server_version should match"
     );
 }
+
+#[test]
+#[serial]
+fn test_pkg_command_autostarts_daemon() {
+    let endpoint = test_endpoint();
+    cleanup_endpoint(&endpoint);
+
+    // Opting out leaves the daemon down
+    let output = cargo_bin()
+        .args(["--json", "--no-autostart", "pkg", "cache", "ls"])
+        .env("HOWTH_IPC_ENDPOINT", &endpoint)
+        .output()
+        .expect("Failed to run pkg cache ls");
+    let json: serde_json::Value = serde_json::from_slice(&output.stdout).unwrap();
+    assert_eq!(json["ok"].as_bool(), Some(false));
+
+    let output = cargo_bin()
+        .args(["--json", "pkg", "cache", "ls"])
+        .env("HOWTH_IPC_ENDPOINT", &endpoint)
+        .env_remove("HOWTH_NO_AUTOSTART")
+        .output()
+        .expect("Failed to run pkg cache ls");
+
+    // The started daemon keeps running for later commands
+    let ping = cargo_bin()
+        .arg("ping")
+        .env("HOWTH_IPC_ENDPOINT", &endpoint)
+        .output()
+        .expect("Failed to run ping command");
+    let _ = cargo_bin()
+        .arg("stop")
+        .env("HOWTH_IPC_ENDPOINT", &endpoint)
+        .output();
+    cleanup_endpoint(&endpoint);
+
+    let json: serde_json::Value =
+        serde_json::from_slice(&output.stdout).expect("stdout should be valid JSON");
+    assert_eq!(json["ok"].as_bool(), Some(true), "{json}");
+    assert!(ping.status.success(), "daemon should still be running");
+}
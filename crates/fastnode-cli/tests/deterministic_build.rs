@@ -0,0 +1,98 @@
+//! Integration tests for `howth bundle --deterministic`.
+//!
+//! The same project bundled from two different directories must produce
+//! byte-identical output and source maps.
+
+use std::path::Path;
+use std::process::Command;
+
+fn cargo_bin() -> Command {
+    let mut cmd = Command::new(env!("CARGO"));
+    cmd.args(["run", "-p", "fastnode-cli", "--bin", "howth", "--"]);
+    cmd.env("HOWTH_NO_AUTOSTART", "1");
+    cmd.env_remove("SOURCE_DATE_EPOCH");
+    cmd
+}
+
+fn write_fixture(root: &Path) {
+    let write = |rel: &str, content: &str| {
+        let path = root.join(rel);
+        std::fs::create_dir_all(path.parent().unwrap()).unwrap();
+        std::fs::write(path, content).unwrap();
+    };
+    write(
+        "package.json",
+        r#"{"name":"fixture","version":"1.0.0","type":"module"}"#,
+    );
+    write(
+        "src/index.ts",
+        "import { greet } from './greet';\nimport { add } from './math';\nconsole.log(greet('world'), add(1, 2));\n",
+    );
+    write(
+        "src/greet.ts",
+        "export const greet = (name: string): string => `hello ${name}`;\n",
+    );
+    write(
+        "src/math.ts",
+        "export function add(a: number, b: number): number {\n  return a + b;\n}\n",
+    );
+}
+
+/// Bundle the fixture in a fresh directory and return `(code, map, root)`.
+fn bundle(extra_args: &[&str]) -> (Vec<u8>, Vec<u8>, String) {
+    let dir = tempfile::tempdir().unwrap();
+    write_fixture(dir.path());
+
+    let output = cargo_bin()
+        .args(["bundle", "src/index.ts", "--sourcemap", "--outfile"])
+        .arg(dir.path().join("dist/out.js"))
+        .args(extra_args)
+        .arg("--cwd")
+        .arg(dir.path())
+        .output()
+        .expect("Failed to run command");
+    assert!(
+        output.status.success(),
+        "bundle failed. stderr: {}",
+        String::from_utf8_lossy(&output.stderr)
+    );
+
+    let code = std::fs::read(dir.path().join("dist/out.js")).unwrap();
+    let map = std::fs::read(dir.path().join("dist/out.js.map")).unwrap();
+    let root = dunce::canonicalize(dir.path()).unwrap();
+    (code, map, root.to_string_lossy().into_owned())
+}
+
+#[test]
+fn test_deterministic_bundle_is_byte_identical() {
+    let (code_a, map_a, root_a) = bundle(&["--deterministic"]);
+    let (code_b, map_b, _) = bundle(&["--deterministic"]);
+
+    assert_eq!(code_a, code_b, "bundles differ between checkouts");
+    assert_eq!(map_a, map_b, "source maps differ between checkouts");
+    assert!(!String::from_utf8_lossy(&code_a).contains(&root_a));
+    assert!(!String::from_utf8_lossy(&map_a).contains(&root_a));
+}
+
+#[test]
+fn test_source_date_epoch_implies_deterministic() {
+    let dir = tempfile::tempdir().unwrap();
+    write_fixture(dir.path());
+
+    let output = cargo_bin()
+        .env("SOURCE_DATE_EPOCH", "1700000000")
+        .args(["bundle", "src/index.ts", "--cwd"])
+        .arg(dir.path())
+        .output()
+        .expect("Failed to run command");
+    assert!(
+        output.status.success(),
+        "bundle failed. stderr: {}",
+        String::from_utf8_lossy(&output.stderr)
+    );
+
+    let root = dunce::canonicalize(dir.path()).unwrap();
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    assert!(stdout.contains("src/math.ts"), "{stdout}");
+    assert!(!stdout.contains(&*root.to_string_lossy()), "{stdout}");
+}
@@ -33,13 +33,17 @@ fn test_doctor_json_is_valid_json() {
     assert!(json.get("paths").is_some(), "Missing paths");
     assert!(json.get("project").is_some(), "Missing project");
     assert!(json.get("capabilities").is_some(), "Missing capabilities");
+    assert!(json.get("daemon").is_some(), "Missing daemon");
+    assert!(json.get("cache_usage").is_some(), "Missing cache_usage");
+    assert!(json.get("watcher").is_some(), "Missing watcher");
+    assert!(json.get("tools").is_some(), "Missing tools");
     assert!(json.get("warnings").is_some(), "Missing warnings");
 
     // Verify report_schema_version is correct
     assert_eq!(
         json["report_schema_version"].as_u64(),
-        Some(1),
-        "report_schema_version should be 1"
+        Some(2),
+        "report_schema_version should be 2"
     );
 
     // Verify runtime has required fields
@@ -148,5 +152,54 @@ fn test_doctor_human_output_not_json() {
         stdout.contains("Capabilities"),
         "Missing Capabilities section"
     );
+    assert!(stdout.contains("Daemon"), "Missing Daemon section");
+    assert!(
+        stdout.contains("Cache Usage"),
+        "Missing Cache Usage section"
+    );
+    assert!(stdout.contains("Watcher"), "Missing Watcher section");
+    assert!(stdout.contains("Tools"), "Missing Tools section");
     assert!(stdout.contains("Warnings"), "Missing Warnings section");
 }
+
+#[test]
+fn test_doctor_bundle_requires_consent_when_not_interactive() {
+    let dir = tempfile::tempdir().unwrap();
+    let bundle = dir.path().join("diag.tar.gz");
+    let output = cargo_bin()
+        .args(["doctor", "--bundle", "-o"])
+        .arg(&bundle)
+        .stdin(std::process::Stdio::null())
+        .output()
+        .expect("Failed to run doctor command");
+
+    assert!(!output.status.success());
+    assert!(!bundle.exists(), "bundle written without consent");
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    assert!(
+        stderr.contains("--yes"),
+        "stderr should mention --yes: {stderr}"
+    );
+}
+
+#[test]
+fn test_doctor_bundle_writes_tarball() {
+    let dir = tempfile::tempdir().unwrap();
+    let bundle = dir.path().join("diag.tar.gz");
+    let output = cargo_bin()
+        .args(["doctor", "--bundle", "--yes", "-o"])
+        .arg(&bundle)
+        .output()
+        .expect("Failed to run doctor command");
+
+    assert!(
+        output.status.success(),
+        "stderr: {}",
+        String::from_utf8_lossy(&output.stderr)
+    );
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    assert!(stderr.contains("manifest.json"), "stderr: {stderr}");
+    assert!(stderr.contains("doctor.json"), "stderr: {stderr}");
+    let bytes = std::fs::read(&bundle).expect("bundle should exist");
+    assert_eq!(&bytes[..2], &[0x1f, 0x8b], "bundle should be gzipped");
+}
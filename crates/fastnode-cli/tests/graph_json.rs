@@ -0,0 +1,86 @@
+//! Integration tests for `howth graph --json` output.
+//!
+//! These tests verify:
+//! - The module graph is printed as valid JSON with a schema version
+//! - Nodes, edges and externals use paths relative to `--cwd`
+//! - A missing entry reports `ok: false` with a SCREAMING_SNAKE_CASE code
+
+use std::process::Command;
+use tempfile::tempdir;
+
+fn cargo_bin() -> Command {
+    let mut cmd = Command::new(env!("CARGO"));
+    cmd.args(["run", "-p", "fastnode-cli", "--bin", "howth", "--"]);
+    cmd
+}
+
+#[test]
+fn test_graph_json_nodes_and_edges() {
+    let dir = tempdir().unwrap();
+    std::fs::create_dir_all(dir.path().join("src")).unwrap();
+    std::fs::write(
+        dir.path().join("src/index.ts"),
+        "import { add } from \"./math\";\nimport React from \"react\";\nconsole.log(add(1, 2), React);\n",
+    )
+    .unwrap();
+    std::fs::write(
+        dir.path().join("src/math.ts"),
+        "export const add = (a: number, b: number) => a + b;\n",
+    )
+    .unwrap();
+
+    let output = cargo_bin()
+        .args(["--json", "--cwd"])
+        .arg(dir.path())
+        .args(["graph", "src/index.ts", "--external", "react"])
+        .output()
+        .expect("Failed to run graph command");
+
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    let json: serde_json::Value =
+        serde_json::from_str(&stdout).expect("Output should be valid JSON");
+
+    assert_eq!(json["ok"], true);
+    assert_eq!(json["schema_version"], 1);
+    assert_eq!(json["entries"], serde_json::json!([0]));
+
+    let paths: Vec<&str> = json["nodes"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .map(|n| n["path"].as_str().unwrap())
+        .collect();
+    assert_eq!(paths, ["src/index.ts", "src/math.ts"]);
+
+    let edges = json["edges"].as_array().unwrap();
+    assert_eq!(edges.len(), 1);
+    assert_eq!(edges[0]["from"], 0);
+    assert_eq!(edges[0]["to"], 1);
+    assert_eq!(edges[0]["kind"], "static");
+
+    assert_eq!(json["externals"][0]["specifier"], "react");
+}
+
+#[test]
+fn test_graph_json_missing_entry() {
+    let dir = tempdir().unwrap();
+
+    let output = cargo_bin()
+        .args(["--json", "--cwd"])
+        .arg(dir.path())
+        .args(["graph", "missing.ts"])
+        .output()
+        .expect("Failed to run graph command");
+
+    assert!(!output.status.success());
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    let json: serde_json::Value =
+        serde_json::from_str(&stdout).expect("Output should be valid JSON");
+
+    assert_eq!(json["ok"], false);
+    let code = json["error"]["code"].as_str().unwrap();
+    assert!(
+        code.chars().all(|c| c.is_ascii_uppercase() || c == '_'),
+        "error code should be SCREAMING_SNAKE_CASE: {code}"
+    );
+}
@@ -32,6 +32,8 @@ static PORT_COUNTER: AtomicU16 = AtomicU16::new(19800);
 fn cargo_bin() -> Command {
     let mut cmd = Command::new(env!("CARGO"));
     cmd.args(["run", "-p", "fastnode-cli", "--bin", "howth", "--"]);
+    // Tests start their own daemon where they need one
+    cmd.env("HOWTH_NO_AUTOSTART", "1");
     cmd
 }
 
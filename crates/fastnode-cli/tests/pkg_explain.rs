@@ -15,6 +15,8 @@ use tempfile::TempDir;
 fn cargo_bin() -> Command {
     let mut cmd = Command::new(env!("CARGO"));
     cmd.args(["run", "-p", "fastnode-cli", "--bin", "howth", "--"]);
+    // Tests start their own daemon where they need one
+    cmd.env("HOWTH_NO_AUTOSTART", "1");
     cmd
 }
 
@@ -115,6 +117,10 @@ fn create_project_with_exports_package() -> TempDir {
         "version": "1.0.0",
         "exports": {
             ".": {
+                "browser": {
+                    "development": "./browser.dev.js",
+                    "default": "./browser.js"
+                },
                 "import": "./esm/index.js",
                 "require": "./cjs/index.js",
                 "default": "./index.js"
@@ -136,6 +142,8 @@ fn create_project_with_exports_package() -> TempDir {
     std::fs::write(pkg_dir.join("esm/index.js"), "// esm").unwrap();
     std::fs::write(pkg_dir.join("cjs/index.js"), "// cjs").unwrap();
     std::fs::write(pkg_dir.join("feature.js"), "// feature").unwrap();
+    std::fs::write(pkg_dir.join("browser.js"), "// browser").unwrap();
+    std::fs::write(pkg_dir.join("browser.dev.js"), "// browser dev").unwrap();
 
     dir
 }
@@ -262,6 +270,73 @@ fn test_explain_require_kind() {
     );
 }
 
+#[test]
+#[serial]
+fn test_explain_platform_and_conditions() {
+    let endpoint = test_endpoint();
+    cleanup_endpoint(&endpoint);
+
+    let project = create_project_with_exports_package();
+
+    // Start daemon
+    let mut daemon = start_daemon(&endpoint);
+    assert!(wait_for_daemon(&endpoint), "Daemon should start");
+
+    let explain = |extra: &[&str]| {
+        cargo_bin()
+            .args(["--json", "pkg", "explain", "test-pkg", "--kind", "import"])
+            .args(extra)
+            .args(["--cwd", project.path().to_str().unwrap()])
+            .env("HOWTH_IPC_ENDPOINT", &endpoint)
+            .output()
+            .expect("Failed to run pkg explain")
+    };
+    let browser = explain(&["--platform", "browser"]);
+    let development = explain(&["--platform", "browser", "--conditions", "development"]);
+
+    // Cleanup
+    let _ = daemon.kill();
+    let _ = daemon.wait();
+    cleanup_endpoint(&endpoint);
+
+    let parse = |output: &std::process::Output| {
+        assert!(output.status.success(), "Should succeed");
+        let json: serde_json::Value =
+            serde_json::from_slice(&output.stdout).expect("Should be valid JSON");
+        json["result"].clone()
+    };
+    let matched_conditions = |result: &serde_json::Value| {
+        result["trace"]
+            .as_array()
+            .unwrap()
+            .iter()
+            .find(|step| step["step"] == "match_exports_key")
+            .map(|step| step["conditions"].clone())
+            .expect("Should match an exports key")
+    };
+
+    let result = parse(&browser);
+    assert!(result["resolved"].as_str().unwrap().ends_with("browser.js"));
+    assert_eq!(
+        result["conditions"],
+        serde_json::json!(["browser", "import", "default"])
+    );
+    assert_eq!(
+        matched_conditions(&result),
+        serde_json::json!(["browser", "default"])
+    );
+
+    let result = parse(&development);
+    assert!(result["resolved"]
+        .as_str()
+        .unwrap()
+        .ends_with("browser.dev.js"));
+    assert_eq!(
+        matched_conditions(&result),
+        serde_json::json!(["browser", "development"])
+    );
+}
+
 #[test]
 #[serial]
 fn test_explain_unresolved_package() {
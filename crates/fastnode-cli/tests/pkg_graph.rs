@@ -16,6 +16,8 @@ use tempfile::TempDir;
 fn cargo_bin() -> Command {
     let mut cmd = Command::new(env!("CARGO"));
     cmd.args(["run", "-p", "fastnode-cli", "--bin", "howth", "--"]);
+    // Tests start their own daemon where they need one
+    cmd.env("HOWTH_NO_AUTOSTART", "1");
     cmd
 }
 
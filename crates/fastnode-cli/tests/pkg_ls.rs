@@ -0,0 +1,123 @@
+//! Integration tests for `howth pkg ls [--global]`.
+//!
+//! Listing reads package.json and node_modules directly, so no daemon is needed.
+
+use std::path::Path;
+use std::process::Command;
+
+fn cargo_bin() -> Command {
+    let mut cmd = Command::new(env!("CARGO"));
+    cmd.args(["run", "-p", "fastnode-cli", "--bin", "howth", "--"]);
+    // Tests start their own daemon where they need one
+    cmd.env("HOWTH_NO_AUTOSTART", "1");
+    cmd
+}
+
+fn write_package(dir: &Path, json: &serde_json::Value) {
+    std::fs::create_dir_all(dir).unwrap();
+    std::fs::write(
+        dir.join("package.json"),
+        serde_json::to_string_pretty(json).unwrap(),
+    )
+    .unwrap();
+}
+
+#[test]
+fn test_pkg_ls_project_json() {
+    let project = tempfile::tempdir().unwrap();
+    write_package(
+        project.path(),
+        &serde_json::json!({
+            "name": "app",
+            "dependencies": { "pkg-a": "^1.0.0" },
+            "devDependencies": { "pkg-b": "^2.0.0" }
+        }),
+    );
+    write_package(
+        &project.path().join("node_modules/pkg-a"),
+        &serde_json::json!({ "name": "pkg-a", "version": "1.4.0" }),
+    );
+
+    let output = cargo_bin()
+        .args([
+            "--json",
+            "pkg",
+            "ls",
+            "--cwd",
+            project.path().to_str().unwrap(),
+        ])
+        .output()
+        .expect("Failed to run pkg ls");
+
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    assert!(output.status.success(), "Should succeed: {stdout}");
+
+    let json: serde_json::Value = serde_json::from_str(&stdout).expect("Should be valid JSON");
+    assert_eq!(json["ok"].as_bool(), Some(true));
+    assert_eq!(json["global"].as_bool(), Some(false));
+
+    let packages = json["packages"].as_array().unwrap();
+    assert_eq!(packages.len(), 2);
+    assert_eq!(packages[0]["name"].as_str(), Some("pkg-a"));
+    assert_eq!(packages[0]["version"].as_str(), Some("1.4.0"));
+    assert_eq!(packages[1]["name"].as_str(), Some("pkg-b"));
+    assert!(packages[1].get("version").is_none());
+}
+
+#[cfg(unix)]
+#[test]
+fn test_pkg_ls_does_not_autostart_daemon() {
+    let project = tempfile::tempdir().unwrap();
+    write_package(project.path(), &serde_json::json!({ "name": "app" }));
+    let endpoint = project.path().join("howth.sock");
+
+    let output = cargo_bin()
+        .args(["--json", "pkg", "ls", "--cwd"])
+        .arg(project.path())
+        .env("HOWTH_IPC_ENDPOINT", &endpoint)
+        .env_remove("HOWTH_NO_AUTOSTART")
+        .output()
+        .expect("Failed to run pkg ls");
+
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    assert!(output.status.success(), "Should succeed: {stdout}");
+    assert!(!endpoint.exists(), "pkg ls should not start the daemon");
+}
+
+// The global prefix lives under the cache dir, which follows XDG_CACHE_HOME on Linux
+#[cfg(target_os = "linux")]
+#[test]
+fn test_pkg_ls_global_lists_prefix_with_bins() {
+    let cache = tempfile::tempdir().unwrap();
+
+    // Empty (never created) prefix lists nothing
+    let output = cargo_bin()
+        .args(["--json", "pkg", "ls", "--global"])
+        .env("XDG_CACHE_HOME", cache.path())
+        .output()
+        .expect("Failed to run pkg ls --global");
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    assert!(output.status.success(), "Should succeed: {stdout}");
+    let json: serde_json::Value = serde_json::from_str(&stdout).expect("Should be valid JSON");
+    assert_eq!(json["packages"].as_array().map(Vec::len), Some(0));
+
+    let prefix = Path::new(json["root"].as_str().unwrap()).to_path_buf();
+    assert!(prefix.starts_with(cache.path()));
+    write_package(
+        &prefix,
+        &serde_json::json!({ "dependencies": { "tool": "^3.0.0" } }),
+    );
+    write_package(
+        &prefix.join("node_modules/tool"),
+        &serde_json::json!({ "name": "tool", "version": "3.1.0", "bin": { "tool": "cli.js" } }),
+    );
+
+    let output = cargo_bin()
+        .args(["pkg", "ls", "--global"])
+        .env("XDG_CACHE_HOME", cache.path())
+        .output()
+        .expect("Failed to run pkg ls --global");
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    assert!(output.status.success(), "Should succeed: {stdout}");
+    assert!(stdout.contains("tool@3.1.0  [tool]"), "{stdout}");
+}
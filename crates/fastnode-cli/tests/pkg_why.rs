@@ -15,6 +15,8 @@ use tempfile::TempDir;
 fn cargo_bin() -> Command {
     let mut cmd = Command::new(env!("CARGO"));
     cmd.args(["run", "-p", "fastnode-cli", "--bin", "howth", "--"]);
+    // Tests start their own daemon where they need one
+    cmd.env("HOWTH_NO_AUTOSTART", "1");
     cmd
 }
 
@@ -483,3 +485,49 @@ fn test_why_list_format() {
         stdout
     );
 }
+
+#[test]
+#[serial]
+fn test_why_dependents() {
+    let endpoint = test_endpoint();
+    cleanup_endpoint(&endpoint);
+
+    let project = create_project_with_dep_chain();
+
+    // Start daemon
+    let mut daemon = start_daemon(&endpoint);
+    assert!(wait_for_daemon(&endpoint), "Daemon should start");
+
+    let output = cargo_bin()
+        .args([
+            "pkg",
+            "explain",
+            "pkg-c",
+            "--why",
+            "--dependents",
+            "--cwd",
+            project.path().to_str().unwrap(),
+        ])
+        .env("HOWTH_IPC_ENDPOINT", &endpoint)
+        .output()
+        .expect("Failed to run pkg explain --why --dependents");
+
+    // Cleanup
+    let _ = daemon.kill();
+    let _ = daemon.wait();
+    cleanup_endpoint(&endpoint);
+
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    assert!(output.status.success(), "Should succeed: {stdout}");
+
+    // pkg-b depends on pkg-c directly, pkg-a through pkg-b
+    assert!(
+        stdout.contains("Dependents of pkg-c (1 direct, 2 at any depth):"),
+        "{stdout}"
+    );
+    assert!(
+        stdout.contains("pkg-b@2.0.0 requires ^3.0.0 -> 3.0.0"),
+        "{stdout}"
+    );
+    assert!(!stdout.contains("<root>"), "{stdout}");
+}
@@ -0,0 +1,109 @@
+//! Integration tests for `howth run --filter` / `--since` across workspaces.
+//!
+//! Scripts run locally, so no daemon is needed.
+
+#![cfg(unix)]
+
+use std::path::Path;
+use std::process::Command;
+
+fn cargo_bin() -> Command {
+    let mut cmd = Command::new(env!("CARGO"));
+    cmd.args(["run", "-p", "fastnode-cli", "--bin", "howth", "--"]);
+    // Tests start their own daemon where they need one
+    cmd.env("HOWTH_NO_AUTOSTART", "1");
+    cmd
+}
+
+fn write_json(path: &Path, json: &serde_json::Value) {
+    std::fs::create_dir_all(path.parent().unwrap()).unwrap();
+    std::fs::write(path, serde_json::to_string_pretty(json).unwrap()).unwrap();
+}
+
+/// Workspace where `app` depends on `lib`, plus an unrelated `docs`.
+fn create_workspace() -> tempfile::TempDir {
+    let dir = tempfile::tempdir().unwrap();
+    let root = dir.path();
+    write_json(
+        &root.join("package.json"),
+        &serde_json::json!({ "name": "root", "private": true, "workspaces": ["packages/*"] }),
+    );
+    for (name, deps) in [
+        ("app", serde_json::json!({ "lib": "*" })),
+        ("lib", serde_json::json!({})),
+        ("docs", serde_json::json!({})),
+    ] {
+        write_json(
+            &root.join("packages").join(name).join("package.json"),
+            &serde_json::json!({
+                "name": name,
+                "version": "1.0.0",
+                "dependencies": deps,
+                "scripts": { "build": format!("echo built-{name}") }
+            }),
+        );
+    }
+    dir
+}
+
+fn run_build(root: &Path, extra: &[&str]) -> Vec<String> {
+    let output = cargo_bin()
+        .args(["--json", "run", "build", "--cwd", root.to_str().unwrap()])
+        .args(extra)
+        .output()
+        .expect("Failed to run howth run");
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    assert!(output.status.success(), "Should succeed: {stdout}");
+
+    let json: serde_json::Value = serde_json::from_str(&stdout).expect("Should be valid JSON");
+    assert_eq!(json["ok"].as_bool(), Some(true));
+    json["results"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .map(|r| r["name"].as_str().unwrap().to_string())
+        .collect()
+}
+
+fn git(root: &Path, args: &[&str]) {
+    let status = Command::new("git")
+        .args(args)
+        .current_dir(root)
+        .status()
+        .expect("Failed to run git");
+    assert!(status.success(), "git {args:?} failed");
+}
+
+#[test]
+fn test_run_filter_with_dependencies_runs_in_dependency_order() {
+    let workspace = create_workspace();
+
+    assert_eq!(
+        run_build(workspace.path(), &["--filter", "app..."]),
+        vec!["lib", "app"]
+    );
+    assert_eq!(
+        run_build(workspace.path(), &["--filter", "d*"]),
+        vec!["docs"]
+    );
+}
+
+#[test]
+fn test_run_since_selects_changed_packages_and_dependents() {
+    if Command::new("git").arg("--version").output().is_err() {
+        return;
+    }
+    let workspace = create_workspace();
+    let root = workspace.path();
+    git(root, &["init", "-q"]);
+    git(root, &["config", "user.email", "test@example.com"]);
+    git(root, &["config", "user.name", "test"]);
+    git(root, &["add", "."]);
+    git(root, &["commit", "-q", "--no-gpg-sign", "-m", "initial"]);
+
+    // Nothing changed yet
+    assert_eq!(run_build(root, &["--since", "HEAD"]), Vec::<String>::new());
+
+    std::fs::write(root.join("packages/lib/index.js"), "export {}\n").unwrap();
+    assert_eq!(run_build(root, &["--since", "HEAD"]), vec!["lib", "app"]);
+}
@@ -12,6 +12,8 @@ use std::time::Duration;
 fn cargo_bin() -> Command {
     let mut cmd = Command::new(env!("CARGO"));
     cmd.args(["run", "-p", "fastnode-cli", "--bin", "howth", "--"]);
+    // Tests start their own daemon where they need one
+    cmd.env("HOWTH_NO_AUTOSTART", "1");
     cmd
 }
 
@@ -179,6 +181,52 @@ fn test_run_local_with_args() {
     assert_eq!(args[2].as_str(), Some("--debug"));
 }
 
+#[test]
+#[serial]
+fn test_run_local_script_dry_run() {
+    let dir = tempfile::tempdir().unwrap();
+    std::fs::write(
+        dir.path().join("package.json"),
+        r#"{"scripts":{"prebuild":"touch pre-ran","build":"touch ran"}}"#,
+    )
+    .unwrap();
+    // A file with the script's name loses to the script
+    std::fs::write(dir.path().join("build"), "// file").unwrap();
+
+    let output = cargo_bin()
+        .args(["--json", "run", "build", "--dry-run", "--cwd"])
+        .arg(dir.path())
+        .args(["--", "--verbose"])
+        .output()
+        .expect("Failed to run command");
+
+    assert!(
+        output.status.success(),
+        "Command should succeed. stderr: {}",
+        String::from_utf8_lossy(&output.stderr)
+    );
+    // Nothing ran
+    assert!(!dir.path().join("pre-ran").exists());
+    assert!(!dir.path().join("ran").exists());
+
+    let json: serde_json::Value = serde_json::from_slice(&output.stdout).unwrap();
+    assert_eq!(json["entry_kind"].as_str(), Some("script"));
+    let script = &json["script"];
+    assert_eq!(script["command"].as_str(), Some("touch ran"));
+    assert_eq!(
+        script["steps"],
+        serde_json::json!([
+            {"event": "prebuild", "command": "touch pre-ran"},
+            {"event": "build", "command": "touch ran --verbose"}
+        ])
+    );
+    assert!(script["shadowed_file"].as_str().unwrap().ends_with("build"));
+    let notes = json["notes"].as_array().unwrap();
+    assert!(notes
+        .iter()
+        .any(|n| n.as_str().unwrap().contains("scripts take precedence")));
+}
+
 #[test]
 #[serial]
 fn test_run_local_missing_entry_exit_2() {
@@ -6,7 +6,10 @@
 //! This crate will provide compatibility with Node.js APIs, allowing fastnode
 //! to run Node.js programs and npm packages.
 //!
-//! Currently a placeholder - implementation coming in future milestones.
+//! The [`os`] module is engine-independent host introspection used by the
+//! runtime's `node:os`; the engine-specific modules are still placeholders.
+
+pub mod os;
 
 /// Placeholder module for future Node.js fs API compatibility.
 #[cfg(any(feature = "engine-v8", feature = "engine-sm", feature = "engine-jsc"))]
@@ -45,7 +48,7 @@ mod tests {
         #[cfg(not(any(feature = "engine-v8", feature = "engine-sm", feature = "engine-jsc")))]
         {
             let info = super::stub::info();
-            assert!(!info.is_empty());
+            assert_ne!(info, "");
         }
     }
 }
@@ -0,0 +1,488 @@
+//! Host information backing the `node:os` module.
+//!
+//! Everything here is engine-independent: the runtime calls [`info`] once per
+//! `os.*` lookup and shapes the result into Node's return values. Fields that
+//! cannot be determined on the current platform are `None` (or empty) so the
+//! JS side can fall back to Node's documented defaults.
+
+use serde::Serialize;
+
+/// Snapshot of the host system, serialized with Node's field names.
+#[derive(Debug, Clone, Default, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct OsInfo {
+    /// Host name (`os.hostname()`).
+    pub hostname: Option<String>,
+    /// Kernel name as reported by `uname -s` (`os.type()`).
+    pub sysname: Option<String>,
+    /// Kernel release (`os.release()`).
+    pub release: Option<String>,
+    /// Kernel version string (`os.version()`).
+    pub version: Option<String>,
+    /// Hardware name as reported by `uname -m` (`os.machine()`).
+    pub machine: Option<String>,
+    /// Seconds since boot (`os.uptime()`).
+    pub uptime: Option<f64>,
+    /// Total physical memory in bytes (`os.totalmem()`).
+    pub totalmem: Option<u64>,
+    /// Available memory in bytes (`os.freemem()`).
+    pub freemem: Option<u64>,
+    /// 1, 5 and 15 minute load averages (`os.loadavg()`).
+    pub loadavg: Option<[f64; 3]>,
+    /// One entry per logical CPU (`os.cpus()`).
+    pub cpus: Vec<CpuInfo>,
+    /// Number of CPUs the process may use (`os.availableParallelism()`).
+    pub available_parallelism: usize,
+    /// Current user (`os.userInfo()`).
+    pub user: Option<UserInfo>,
+}
+
+/// A single logical CPU.
+#[derive(Debug, Clone, Default, PartialEq, Serialize)]
+pub struct CpuInfo {
+    /// Model name, e.g. `Intel(R) Core(TM) i7-9750H CPU @ 2.60GHz`.
+    pub model: String,
+    /// Clock speed in MHz.
+    pub speed: u64,
+    /// Time spent in each mode, in milliseconds.
+    pub times: CpuTimes,
+}
+
+/// Per-CPU time counters in milliseconds, as in `os.cpus()[n].times`.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize)]
+pub struct CpuTimes {
+    pub user: u64,
+    pub nice: u64,
+    pub sys: u64,
+    pub idle: u64,
+    pub irq: u64,
+}
+
+/// The effective user, as in `os.userInfo()`.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize)]
+pub struct UserInfo {
+    pub uid: i64,
+    pub gid: i64,
+    pub username: String,
+    pub homedir: String,
+    pub shell: Option<String>,
+}
+
+/// Collect a fresh snapshot of the host system.
+#[must_use]
+pub fn info() -> OsInfo {
+    let available_parallelism = std::thread::available_parallelism().map_or(1, usize::from);
+    let uname = uname();
+
+    let mut cpus = cpus();
+    if cpus.is_empty() {
+        cpus = vec![
+            CpuInfo {
+                model: cpu_model().unwrap_or_else(|| "Unknown CPU".to_string()),
+                ..CpuInfo::default()
+            };
+            available_parallelism
+        ];
+    }
+
+    OsInfo {
+        hostname: hostname(),
+        sysname: uname.as_ref().map(|u| u.sysname.clone()),
+        release: uname.as_ref().map(|u| u.release.clone()),
+        version: uname.as_ref().map(|u| u.version.clone()),
+        machine: uname.map(|u| u.machine),
+        uptime: uptime(),
+        totalmem: totalmem(),
+        freemem: freemem(),
+        loadavg: loadavg(),
+        cpus,
+        available_parallelism,
+        user: user(),
+    }
+}
+
+struct Uname {
+    sysname: String,
+    release: String,
+    version: String,
+    machine: String,
+}
+
+#[cfg(unix)]
+fn uname() -> Option<Uname> {
+    use std::ffi::CStr;
+    use std::mem::MaybeUninit;
+
+    let mut buf = MaybeUninit::<libc::utsname>::uninit();
+    // SAFETY: buf is a valid pointer to a utsname struct
+    if unsafe { libc::uname(buf.as_mut_ptr()) } != 0 {
+        return None;
+    }
+    // SAFETY: uname succeeded, buf is initialized with NUL-terminated fields
+    let buf = unsafe { buf.assume_init() };
+    let field = |chars: &[libc::c_char]| {
+        // SAFETY: uname guarantees each field is NUL-terminated
+        unsafe { CStr::from_ptr(chars.as_ptr()) }
+            .to_string_lossy()
+            .into_owned()
+    };
+    Some(Uname {
+        sysname: field(&buf.sysname),
+        release: field(&buf.release),
+        version: field(&buf.version),
+        machine: field(&buf.machine),
+    })
+}
+
+#[cfg(not(unix))]
+fn uname() -> Option<Uname> {
+    None
+}
+
+#[cfg(unix)]
+fn hostname() -> Option<String> {
+    let mut buf = [0u8; 256];
+    // SAFETY: buf is valid for buf.len() bytes
+    if unsafe { libc::gethostname(buf.as_mut_ptr().cast(), buf.len()) } != 0 {
+        return None;
+    }
+    let len = buf.iter().position(|&b| b == 0).unwrap_or(buf.len());
+    Some(String::from_utf8_lossy(&buf[..len]).into_owned())
+}
+
+#[cfg(not(unix))]
+fn hostname() -> Option<String> {
+    std::env::var("COMPUTERNAME").ok()
+}
+
+#[cfg(target_os = "linux")]
+fn uptime() -> Option<f64> {
+    let contents = std::fs::read_to_string("/proc/uptime").ok()?;
+    contents.split_whitespace().next()?.parse().ok()
+}
+
+#[cfg(target_os = "macos")]
+fn uptime() -> Option<f64> {
+    let boot: libc::timeval = sysctl_value("kern.boottime")?;
+    let now = std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .ok()?;
+    #[allow(clippy::cast_precision_loss)]
+    Some(now.as_secs_f64() - boot.tv_sec as f64)
+}
+
+#[cfg(not(any(target_os = "linux", target_os = "macos")))]
+fn uptime() -> Option<f64> {
+    None
+}
+
+#[cfg(unix)]
+fn totalmem() -> Option<u64> {
+    // SAFETY: sysconf has no memory-safety preconditions
+    let (pages, page_size) = unsafe {
+        (
+            libc::sysconf(libc::_SC_PHYS_PAGES),
+            libc::sysconf(libc::_SC_PAGESIZE),
+        )
+    };
+    let pages = u64::try_from(pages).ok()?;
+    let page_size = u64::try_from(page_size).ok()?;
+    Some(pages * page_size)
+}
+
+#[cfg(not(unix))]
+fn totalmem() -> Option<u64> {
+    None
+}
+
+#[cfg(target_os = "linux")]
+fn freemem() -> Option<u64> {
+    parse_meminfo_available(&std::fs::read_to_string("/proc/meminfo").ok()?)
+}
+
+#[cfg(target_os = "macos")]
+fn freemem() -> Option<u64> {
+    let free: u32 = sysctl_value("vm.page_free_count")?;
+    // SAFETY: sysconf has no memory-safety preconditions
+    let page_size = u64::try_from(unsafe { libc::sysconf(libc::_SC_PAGESIZE) }).ok()?;
+    Some(u64::from(free) * page_size)
+}
+
+#[cfg(not(any(target_os = "linux", target_os = "macos")))]
+fn freemem() -> Option<u64> {
+    None
+}
+
+#[cfg(unix)]
+fn loadavg() -> Option<[f64; 3]> {
+    let mut avg = [0f64; 3];
+    // SAFETY: avg is valid for 3 doubles
+    (unsafe { libc::getloadavg(avg.as_mut_ptr(), 3) } == 3).then_some(avg)
+}
+
+#[cfg(not(unix))]
+fn loadavg() -> Option<[f64; 3]> {
+    None
+}
+
+#[cfg(target_os = "linux")]
+fn cpus() -> Vec<CpuInfo> {
+    let Ok(cpuinfo) = std::fs::read_to_string("/proc/cpuinfo") else {
+        return Vec::new();
+    };
+    let stat = std::fs::read_to_string("/proc/stat").unwrap_or_default();
+    // SAFETY: sysconf has no memory-safety preconditions
+    let ticks = u64::try_from(unsafe { libc::sysconf(libc::_SC_CLK_TCK) }).unwrap_or(100);
+
+    let mut cpus = parse_cpuinfo(&cpuinfo);
+    for (cpu, times) in cpus.iter_mut().zip(parse_proc_stat(&stat, ticks)) {
+        cpu.times = times;
+    }
+    cpus
+}
+
+#[cfg(not(target_os = "linux"))]
+fn cpus() -> Vec<CpuInfo> {
+    Vec::new()
+}
+
+#[cfg(target_os = "macos")]
+fn cpu_model() -> Option<String> {
+    let mut buf = [0u8; 256];
+    let mut len = buf.len();
+    let name = std::ffi::CString::new("machdep.cpu.brand_string").ok()?;
+    // SAFETY: buf is valid for len bytes and len is updated with the written size
+    let rc = unsafe {
+        libc::sysctlbyname(
+            name.as_ptr(),
+            buf.as_mut_ptr().cast(),
+            &mut len,
+            std::ptr::null_mut(),
+            0,
+        )
+    };
+    if rc != 0 {
+        return None;
+    }
+    let end = buf[..len].iter().position(|&b| b == 0).unwrap_or(len);
+    Some(String::from_utf8_lossy(&buf[..end]).into_owned())
+}
+
+#[cfg(not(target_os = "macos"))]
+fn cpu_model() -> Option<String> {
+    None
+}
+
+/// Read a fixed-size sysctl value by name.
+#[cfg(target_os = "macos")]
+fn sysctl_value<T: Copy>(name: &str) -> Option<T> {
+    use std::mem::MaybeUninit;
+
+    let name = std::ffi::CString::new(name).ok()?;
+    let mut value = MaybeUninit::<T>::uninit();
+    let mut len = std::mem::size_of::<T>();
+    // SAFETY: value is valid for size_of::<T>() bytes
+    let rc = unsafe {
+        libc::sysctlbyname(
+            name.as_ptr(),
+            value.as_mut_ptr().cast(),
+            &mut len,
+            std::ptr::null_mut(),
+            0,
+        )
+    };
+    // SAFETY: sysctlbyname succeeded and wrote exactly size_of::<T>() bytes
+    (rc == 0 && len == std::mem::size_of::<T>()).then(|| unsafe { value.assume_init() })
+}
+
+// Always `Some` on unix; the signature matches the non-unix fallback.
+#[cfg(unix)]
+#[allow(clippy::unnecessary_wraps)]
+fn user() -> Option<UserInfo> {
+    use std::ffi::CStr;
+    use std::mem::MaybeUninit;
+
+    // SAFETY: getuid/getgid cannot fail
+    let (uid, gid) = unsafe { (libc::getuid(), libc::getgid()) };
+
+    let mut pwd = MaybeUninit::<libc::passwd>::uninit();
+    let mut buf = vec![0 as libc::c_char; 4096];
+    let mut result: *mut libc::passwd = std::ptr::null_mut();
+    // SAFETY: all pointers are valid for the duration of the call
+    let rc = unsafe {
+        libc::getpwuid_r(
+            uid,
+            pwd.as_mut_ptr(),
+            buf.as_mut_ptr(),
+            buf.len(),
+            std::ptr::addr_of_mut!(result),
+        )
+    };
+
+    let (username, homedir, shell) = if rc == 0 && !result.is_null() {
+        // SAFETY: getpwuid_r succeeded, pwd's string fields point into buf
+        let pwd = unsafe { pwd.assume_init() };
+        let field = |ptr: *const libc::c_char| {
+            // SAFETY: non-null passwd fields are NUL-terminated strings in buf
+            (!ptr.is_null()).then(|| {
+                unsafe { CStr::from_ptr(ptr) }
+                    .to_string_lossy()
+                    .into_owned()
+            })
+        };
+        (field(pwd.pw_name), field(pwd.pw_dir), field(pwd.pw_shell))
+    } else {
+        (None, None, None)
+    };
+
+    Some(UserInfo {
+        uid: i64::from(uid),
+        gid: i64::from(gid),
+        username: username
+            .or_else(|| std::env::var("USER").ok())
+            .unwrap_or_default(),
+        homedir: homedir
+            .or_else(|| std::env::var("HOME").ok())
+            .unwrap_or_default(),
+        shell: shell.or_else(|| std::env::var("SHELL").ok()),
+    })
+}
+
+#[cfg(not(unix))]
+fn user() -> Option<UserInfo> {
+    Some(UserInfo {
+        uid: -1,
+        gid: -1,
+        username: std::env::var("USERNAME").ok()?,
+        homedir: std::env::var("USERPROFILE").unwrap_or_default(),
+        shell: None,
+    })
+}
+
+/// Parse `MemAvailable` (falling back to `MemFree`) from `/proc/meminfo`.
+#[cfg_attr(not(target_os = "linux"), allow(dead_code))]
+fn parse_meminfo_available(meminfo: &str) -> Option<u64> {
+    let field = |name: &str| {
+        meminfo.lines().find_map(|line| {
+            let rest = line.strip_prefix(name)?.strip_prefix(':')?;
+            let kb: u64 = rest.trim().trim_end_matches("kB").trim().parse().ok()?;
+            Some(kb * 1024)
+        })
+    };
+    field("MemAvailable").or_else(|| field("MemFree"))
+}
+
+/// Parse one [`CpuInfo`] per `processor` block of `/proc/cpuinfo`.
+#[cfg_attr(not(target_os = "linux"), allow(dead_code))]
+fn parse_cpuinfo(cpuinfo: &str) -> Vec<CpuInfo> {
+    let mut cpus = Vec::new();
+    for block in cpuinfo.split("\n\n") {
+        let mut is_processor = false;
+        let mut cpu = CpuInfo::default();
+        for line in block.lines() {
+            let Some((key, value)) = line.split_once(':') else {
+                continue;
+            };
+            let value = value.trim();
+            match key.trim() {
+                "processor" => is_processor = true,
+                // x86 and most arm64 kernels respectively
+                "model name" | "Model" if cpu.model.is_empty() => cpu.model = value.to_string(),
+                "cpu MHz" =>
+                {
+                    #[allow(clippy::cast_possible_truncation, clippy::cast_sign_loss)]
+                    if let Ok(mhz) = value.parse::<f64>() {
+                        cpu.speed = mhz as u64;
+                    }
+                }
+                _ => {}
+            }
+        }
+        if is_processor {
+            cpus.push(cpu);
+        }
+    }
+    cpus
+}
+
+/// Parse per-CPU `cpuN` lines of `/proc/stat`, converting ticks to milliseconds.
+#[cfg_attr(not(target_os = "linux"), allow(dead_code))]
+fn parse_proc_stat(stat: &str, ticks_per_sec: u64) -> Vec<CpuTimes> {
+    let to_ms = |ticks: u64| ticks * 1000 / ticks_per_sec.max(1);
+    stat.lines()
+        .filter(|line| {
+            line.strip_prefix("cpu")
+                .is_some_and(|rest| rest.starts_with(|c: char| c.is_ascii_digit()))
+        })
+        .map(|line| {
+            let fields: Vec<u64> = line
+                .split_whitespace()
+                .skip(1)
+                .map(|f| f.parse().unwrap_or(0))
+                .collect();
+            let get = |i: usize| fields.get(i).copied().unwrap_or(0);
+            CpuTimes {
+                user: to_ms(get(0)),
+                nice: to_ms(get(1)),
+                sys: to_ms(get(2)),
+                idle: to_ms(get(3)),
+                irq: to_ms(get(5)),
+            }
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_cpuinfo() {
+        let cpuinfo = "processor\t: 0\nmodel name\t: Test CPU @ 2.60GHz\ncpu MHz\t\t: 2600.123\n\n\
+                       processor\t: 1\nmodel name\t: Test CPU @ 2.60GHz\ncpu MHz\t\t: 800.000\n";
+        let cpus = parse_cpuinfo(cpuinfo);
+        assert_eq!(cpus.len(), 2);
+        assert_eq!(cpus[0].model, "Test CPU @ 2.60GHz");
+        assert_eq!(cpus[0].speed, 2600);
+        assert_eq!(cpus[1].speed, 800);
+    }
+
+    #[test]
+    fn test_parse_proc_stat() {
+        let stat =
+            "cpu  10 20 30 40 50 60 70\ncpu0 100 0 50 200 0 10 0\ncpu1 1 2 3 4 5 6 7\nintr 1 2\n";
+        let times = parse_proc_stat(stat, 100);
+        assert_eq!(times.len(), 2);
+        assert_eq!(
+            times[0],
+            CpuTimes {
+                user: 1000,
+                nice: 0,
+                sys: 500,
+                idle: 2000,
+                irq: 100
+            }
+        );
+    }
+
+    #[test]
+    fn test_parse_meminfo_available() {
+        let meminfo =
+            "MemTotal:       16000000 kB\nMemFree:         1000 kB\nMemAvailable:    2000 kB\n";
+        assert_eq!(parse_meminfo_available(meminfo), Some(2000 * 1024));
+        assert_eq!(parse_meminfo_available("MemFree: 3 kB\n"), Some(3 * 1024));
+    }
+
+    #[test]
+    fn test_info_reports_host() {
+        let info = info();
+        assert_ne!(info.cpus, []);
+        assert!(info.available_parallelism >= 1);
+        #[cfg(unix)]
+        {
+            assert!(info.totalmem.unwrap_or(0) > 0);
+            assert!(info.release.is_some());
+            assert!(info.user.is_some());
+        }
+    }
+}
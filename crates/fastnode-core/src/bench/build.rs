@@ -1127,9 +1127,9 @@ mod tests {
     #[test]
     fn test_machine_info_detect() {
         let info = MachineInfo::detect();
-        assert!(!info.cpu.is_empty());
-        assert!(!info.os.is_empty());
-        assert!(!info.arch.is_empty());
+        assert_ne!(info.cpu, "");
+        assert_ne!(info.os, "");
+        assert_ne!(info.arch, "");
         assert!(info.cores > 0);
     }
 
@@ -226,8 +226,7 @@ fn tool_available(tool: &str) -> bool {
     Command::new("which")
         .arg(binary)
         .output()
-        .map(|o| o.status.success())
-        .unwrap_or(false)
+        .is_ok_and(|o| o.status.success())
 }
 
 /// Run a single bundler and measure performance.
@@ -0,0 +1,574 @@
+//! Bench history: persisted runs and baseline comparison.
+//!
+//! Each report is flattened into named [`Metric`]s and saved as one JSON
+//! file per commit (`<dir>/<kind>/<git sha>.json`). A later run compares its
+//! metrics against a saved baseline and flags any that moved past a threshold
+//! in the wrong direction, so `howth bench` can act as a CI perf gate.
+//!
+//! Comparative benches (install, test, http, bundler) only record howth's own
+//! numbers; other tools' timings are context, not something to gate on.
+
+use super::{
+    BenchReport, BuildBenchReport, BundlerBenchReport, HttpBenchReport, InstallBenchReport,
+    TestBenchReport,
+};
+use serde::{Deserialize, Serialize};
+use std::path::{Path, PathBuf};
+use thiserror::Error;
+
+/// History file schema version. Bump when changing JSON structure.
+pub const HISTORY_SCHEMA_VERSION: u32 = 1;
+
+/// Default directory for saved runs, relative to the working directory.
+pub const DEFAULT_HISTORY_DIR: &str = ".howth/bench";
+
+/// Default regression threshold, in percent.
+pub const DEFAULT_THRESHOLD_PCT: f64 = 5.0;
+
+/// Stable error codes for bench history.
+pub mod codes {
+    pub const BENCH_HISTORY_IO: &str = "BENCH_HISTORY_IO";
+    pub const BENCH_HISTORY_INVALID: &str = "BENCH_HISTORY_INVALID";
+    pub const BENCH_BASELINE_NOT_FOUND: &str = "BENCH_BASELINE_NOT_FOUND";
+    pub const BENCH_BASELINE_AMBIGUOUS: &str = "BENCH_BASELINE_AMBIGUOUS";
+    pub const BENCH_BASELINE_KIND_MISMATCH: &str = "BENCH_BASELINE_KIND_MISMATCH";
+}
+
+/// Error type for bench history.
+#[derive(Error, Debug)]
+pub enum HistoryError {
+    #[error("failed to access {path}: {source}")]
+    Io {
+        path: PathBuf,
+        source: std::io::Error,
+    },
+
+    #[error("invalid bench history file {path}: {reason}")]
+    Invalid { path: PathBuf, reason: String },
+
+    #[error("no saved `{kind}` bench run matches \"{reference}\"")]
+    BaselineNotFound { kind: String, reference: String },
+
+    #[error("\"{reference}\" matches {matches} saved runs; use a longer sha")]
+    BaselineAmbiguous { reference: String, matches: usize },
+
+    #[error("baseline is a `{found}` run, expected `{expected}`")]
+    BaselineKindMismatch { expected: String, found: String },
+}
+
+impl HistoryError {
+    /// Get the stable error code.
+    #[must_use]
+    pub fn code(&self) -> &'static str {
+        match self {
+            Self::Io { .. } => codes::BENCH_HISTORY_IO,
+            Self::Invalid { .. } => codes::BENCH_HISTORY_INVALID,
+            Self::BaselineNotFound { .. } => codes::BENCH_BASELINE_NOT_FOUND,
+            Self::BaselineAmbiguous { .. } => codes::BENCH_BASELINE_AMBIGUOUS,
+            Self::BaselineKindMismatch { .. } => codes::BENCH_BASELINE_KIND_MISMATCH,
+        }
+    }
+}
+
+/// One comparable number from a bench report.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Metric {
+    /// Stable name within the bench kind (e.g., `cold`, `howth rps`).
+    pub name: String,
+    /// Measured value.
+    pub value: f64,
+    /// Unit: `ns`, `bytes` or `req/s`.
+    pub unit: String,
+    /// Whether a larger value is an improvement (throughput) rather than a
+    /// regression (time, size).
+    #[serde(default)]
+    pub higher_is_better: bool,
+}
+
+impl Metric {
+    fn time_ns(name: impl Into<String>, ns: f64) -> Self {
+        Self {
+            name: name.into(),
+            value: ns,
+            unit: "ns".to_string(),
+            higher_is_better: false,
+        }
+    }
+
+    #[allow(clippy::cast_precision_loss)]
+    fn bytes(name: impl Into<String>, bytes: u64) -> Self {
+        Self {
+            name: name.into(),
+            value: bytes as f64,
+            unit: "bytes".to_string(),
+            higher_is_better: false,
+        }
+    }
+}
+
+/// Reports that can be saved to and compared against bench history.
+pub trait BenchMetrics {
+    /// Flatten the report into named metrics.
+    fn metrics(&self) -> Vec<Metric>;
+}
+
+#[allow(clippy::cast_precision_loss)]
+impl BenchMetrics for BenchReport {
+    fn metrics(&self) -> Vec<Metric> {
+        self.results
+            .iter()
+            .map(|r| Metric::time_ns(&r.name, r.median_ns as f64))
+            .collect()
+    }
+}
+
+#[allow(clippy::cast_precision_loss)]
+impl BenchMetrics for BuildBenchReport {
+    fn metrics(&self) -> Vec<Metric> {
+        self.results
+            .iter()
+            .map(|r| Metric::time_ns(&r.case, r.median_ns as f64))
+            .collect()
+    }
+}
+
+#[allow(clippy::cast_precision_loss)]
+fn tool_metrics(tool: &str, median_ns: u64, peak_rss_bytes: Option<u64>) -> Vec<Metric> {
+    let mut metrics = vec![Metric::time_ns(tool, median_ns as f64)];
+    if let Some(rss) = peak_rss_bytes {
+        metrics.push(Metric::bytes(format!("{tool} peak rss"), rss));
+    }
+    metrics
+}
+
+impl BenchMetrics for InstallBenchReport {
+    fn metrics(&self) -> Vec<Metric> {
+        self.results
+            .iter()
+            .filter(|r| r.tool == "howth")
+            .flat_map(|r| tool_metrics(&r.tool, r.median_ns, r.peak_rss_bytes))
+            .collect()
+    }
+}
+
+impl BenchMetrics for TestBenchReport {
+    fn metrics(&self) -> Vec<Metric> {
+        self.results
+            .iter()
+            .filter(|r| r.tool == "howth")
+            .flat_map(|r| tool_metrics(&r.tool, r.median_ns, r.peak_rss_bytes))
+            .collect()
+    }
+}
+
+#[allow(clippy::cast_precision_loss)]
+impl BenchMetrics for HttpBenchReport {
+    fn metrics(&self) -> Vec<Metric> {
+        let mut metrics = Vec::new();
+        for r in self.results.iter().filter(|r| r.tool.starts_with("howth")) {
+            metrics.push(Metric {
+                name: format!("{} rps", r.tool),
+                value: r.rps,
+                unit: "req/s".to_string(),
+                higher_is_better: true,
+            });
+            metrics.push(Metric::time_ns(
+                format!("{} avg latency", r.tool),
+                r.avg_latency_us as f64 * 1_000.0,
+            ));
+        }
+        metrics
+    }
+}
+
+impl BenchMetrics for BundlerBenchReport {
+    fn metrics(&self) -> Vec<Metric> {
+        let mut metrics = Vec::new();
+        for r in self
+            .results
+            .iter()
+            .filter(|r| r.name == "howth" && r.available)
+        {
+            if let Some(ms) = r.median_ms {
+                metrics.push(Metric::time_ns(&r.name, ms * 1_000_000.0));
+            }
+            if let Some(size) = r.bundle_size_bytes {
+                metrics.push(Metric::bytes(format!("{} bundle size", r.name), size));
+            }
+        }
+        metrics
+    }
+}
+
+/// A saved bench run.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BenchRun {
+    /// Schema version for this file format.
+    pub schema_version: u32,
+    /// Bench kind (e.g., `smoke`, `transpile`, `test`).
+    pub kind: String,
+    /// Commit the run was measured at.
+    pub git_sha: String,
+    /// Unix timestamp (seconds) of the run.
+    pub recorded_at: u64,
+    /// howth version that produced the run.
+    pub fastnode_version: String,
+    /// Measured metrics.
+    pub metrics: Vec<Metric>,
+}
+
+impl BenchRun {
+    /// Create a run recorded now.
+    #[must_use]
+    pub fn new(kind: impl Into<String>, git_sha: impl Into<String>, metrics: Vec<Metric>) -> Self {
+        let recorded_at = std::time::SystemTime::now()
+            .duration_since(std::time::UNIX_EPOCH)
+            .map_or(0, |d| d.as_secs());
+        Self {
+            schema_version: HISTORY_SCHEMA_VERSION,
+            kind: kind.into(),
+            git_sha: git_sha.into(),
+            recorded_at,
+            fastnode_version: crate::version::VERSION.to_string(),
+            metrics,
+        }
+    }
+
+    /// Short form of the sha for display.
+    #[must_use]
+    pub fn short_sha(&self) -> &str {
+        &self.git_sha[..self.git_sha.len().min(7)]
+    }
+
+    /// Write the run to `<dir>/<kind>/<sha>.json`, replacing any earlier run
+    /// at the same commit. Returns the file path.
+    pub fn save(&self, dir: &Path) -> Result<PathBuf, HistoryError> {
+        let kind_dir = dir.join(&self.kind);
+        std::fs::create_dir_all(&kind_dir).map_err(|source| HistoryError::Io {
+            path: kind_dir.clone(),
+            source,
+        })?;
+        let path = kind_dir.join(format!("{}.json", self.git_sha));
+        let json = serde_json::to_string_pretty(self).map_err(|e| HistoryError::Invalid {
+            path: path.clone(),
+            reason: e.to_string(),
+        })?;
+        std::fs::write(&path, json + "\n").map_err(|source| HistoryError::Io {
+            path: path.clone(),
+            source,
+        })?;
+        Ok(path)
+    }
+
+    /// Read a run from a history file.
+    pub fn load(path: &Path) -> Result<Self, HistoryError> {
+        let text = std::fs::read_to_string(path).map_err(|source| HistoryError::Io {
+            path: path.to_path_buf(),
+            source,
+        })?;
+        let run: Self = serde_json::from_str(&text).map_err(|e| HistoryError::Invalid {
+            path: path.to_path_buf(),
+            reason: e.to_string(),
+        })?;
+        if run.schema_version != HISTORY_SCHEMA_VERSION {
+            return Err(HistoryError::Invalid {
+                path: path.to_path_buf(),
+                reason: format!(
+                    "schema version {} (expected {HISTORY_SCHEMA_VERSION})",
+                    run.schema_version
+                ),
+            });
+        }
+        Ok(run)
+    }
+}
+
+/// Find the baseline run for `kind`.
+///
+/// `reference` is a path to a history file, `latest` for the most recent run
+/// at a commit other than `current_sha`, or a (prefix of a) git sha.
+pub fn find_baseline(
+    dir: &Path,
+    kind: &str,
+    reference: &str,
+    current_sha: Option<&str>,
+) -> Result<BenchRun, HistoryError> {
+    let not_found = || HistoryError::BaselineNotFound {
+        kind: kind.to_string(),
+        reference: reference.to_string(),
+    };
+
+    let as_path = Path::new(reference);
+    let run = if as_path.is_file() {
+        BenchRun::load(as_path)?
+    } else {
+        let runs = saved_runs(&dir.join(kind))?;
+        if reference == "latest" {
+            runs.into_iter()
+                .filter(|run| Some(run.git_sha.as_str()) != current_sha)
+                .max_by_key(|run| run.recorded_at)
+                .ok_or_else(not_found)?
+        } else {
+            let prefix = reference.to_ascii_lowercase();
+            let mut matches: Vec<BenchRun> = runs
+                .into_iter()
+                .filter(|run| run.git_sha.starts_with(&prefix))
+                .collect();
+            match matches.len() {
+                0 => return Err(not_found()),
+                1 => matches.remove(0),
+                n => {
+                    return Err(HistoryError::BaselineAmbiguous {
+                        reference: reference.to_string(),
+                        matches: n,
+                    })
+                }
+            }
+        }
+    };
+
+    if run.kind != kind {
+        return Err(HistoryError::BaselineKindMismatch {
+            expected: kind.to_string(),
+            found: run.kind,
+        });
+    }
+    Ok(run)
+}
+
+/// All runs saved in one kind's directory. A missing directory has none.
+fn saved_runs(kind_dir: &Path) -> Result<Vec<BenchRun>, HistoryError> {
+    let entries = match std::fs::read_dir(kind_dir) {
+        Ok(entries) => entries,
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(Vec::new()),
+        Err(source) => {
+            return Err(HistoryError::Io {
+                path: kind_dir.to_path_buf(),
+                source,
+            })
+        }
+    };
+    let mut runs = Vec::new();
+    for entry in entries.flatten() {
+        let path = entry.path();
+        if path.extension().is_some_and(|ext| ext == "json") {
+            runs.push(BenchRun::load(&path)?);
+        }
+    }
+    Ok(runs)
+}
+
+/// How a metric moved relative to the baseline.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum DeltaStatus {
+    /// Better by more than the threshold.
+    Improved,
+    /// Within the threshold.
+    Unchanged,
+    /// Worse by more than the threshold.
+    Regressed,
+    /// Only in the current run.
+    Added,
+    /// Only in the baseline.
+    Removed,
+}
+
+/// One metric's change between the baseline and the current run.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct MetricDelta {
+    pub name: String,
+    pub unit: String,
+    pub baseline: Option<f64>,
+    pub current: Option<f64>,
+    /// Change relative to the baseline, in percent (positive = larger).
+    pub change_pct: Option<f64>,
+    pub status: DeltaStatus,
+}
+
+/// Comparison of a run against its baseline.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BenchComparison {
+    pub kind: String,
+    pub baseline_sha: String,
+    pub current_sha: Option<String>,
+    pub threshold_pct: f64,
+    pub deltas: Vec<MetricDelta>,
+}
+
+impl BenchComparison {
+    /// Metrics that regressed past the threshold.
+    pub fn regressions(&self) -> impl Iterator<Item = &MetricDelta> {
+        self.deltas
+            .iter()
+            .filter(|d| d.status == DeltaStatus::Regressed)
+    }
+
+    /// Whether any metric regressed past the threshold.
+    #[must_use]
+    pub fn has_regressions(&self) -> bool {
+        self.regressions().next().is_some()
+    }
+}
+
+/// Compare `current` metrics against `baseline`, in the current run's order
+/// followed by any metrics the current run no longer has.
+#[must_use]
+pub fn compare(
+    baseline: &BenchRun,
+    current_sha: Option<&str>,
+    current: &[Metric],
+    threshold_pct: f64,
+) -> BenchComparison {
+    let mut deltas: Vec<MetricDelta> = current
+        .iter()
+        .map(|metric| {
+            let Some(base) = baseline.metrics.iter().find(|m| m.name == metric.name) else {
+                return MetricDelta {
+                    name: metric.name.clone(),
+                    unit: metric.unit.clone(),
+                    baseline: None,
+                    current: Some(metric.value),
+                    change_pct: None,
+                    status: DeltaStatus::Added,
+                };
+            };
+            let change_pct =
+                (base.value != 0.0).then(|| (metric.value - base.value) / base.value * 100.0);
+            let worse_pct =
+                change_pct.map_or(0.0, |pct| if metric.higher_is_better { -pct } else { pct });
+            let status = if worse_pct > threshold_pct {
+                DeltaStatus::Regressed
+            } else if worse_pct < -threshold_pct {
+                DeltaStatus::Improved
+            } else {
+                DeltaStatus::Unchanged
+            };
+            MetricDelta {
+                name: metric.name.clone(),
+                unit: metric.unit.clone(),
+                baseline: Some(base.value),
+                current: Some(metric.value),
+                change_pct,
+                status,
+            }
+        })
+        .collect();
+
+    for base in &baseline.metrics {
+        if !current.iter().any(|m| m.name == base.name) {
+            deltas.push(MetricDelta {
+                name: base.name.clone(),
+                unit: base.unit.clone(),
+                baseline: Some(base.value),
+                current: None,
+                change_pct: None,
+                status: DeltaStatus::Removed,
+            });
+        }
+    }
+
+    BenchComparison {
+        kind: baseline.kind.clone(),
+        baseline_sha: baseline.git_sha.clone(),
+        current_sha: current_sha.map(str::to_string),
+        threshold_pct,
+        deltas,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn run(sha: &str, recorded_at: u64, metrics: Vec<Metric>) -> BenchRun {
+        BenchRun {
+            recorded_at,
+            ..BenchRun::new("smoke", sha, metrics)
+        }
+    }
+
+    #[test]
+    fn test_compare_flags_regressions_past_threshold() {
+        let baseline = run(
+            "aaaa",
+            1,
+            vec![
+                Metric::time_ns("hash", 100.0),
+                Metric::time_ns("walk", 100.0),
+                Metric::time_ns("gone", 100.0),
+                Metric {
+                    name: "rps".to_string(),
+                    value: 1000.0,
+                    unit: "req/s".to_string(),
+                    higher_is_better: true,
+                },
+            ],
+        );
+        let current = vec![
+            Metric::time_ns("hash", 110.0),
+            Metric::time_ns("walk", 103.0),
+            Metric::time_ns("new", 1.0),
+            Metric {
+                name: "rps".to_string(),
+                value: 1200.0,
+                unit: "req/s".to_string(),
+                higher_is_better: true,
+            },
+        ];
+
+        let comparison = compare(&baseline, Some("bbbb"), &current, 5.0);
+        let statuses: Vec<(&str, DeltaStatus)> = comparison
+            .deltas
+            .iter()
+            .map(|d| (d.name.as_str(), d.status))
+            .collect();
+        assert_eq!(
+            statuses,
+            vec![
+                ("hash", DeltaStatus::Regressed),
+                ("walk", DeltaStatus::Unchanged),
+                ("new", DeltaStatus::Added),
+                ("rps", DeltaStatus::Improved),
+                ("gone", DeltaStatus::Removed),
+            ]
+        );
+        assert!(comparison.has_regressions());
+        assert!((comparison.deltas[0].change_pct.unwrap() - 10.0).abs() < 1e-9);
+
+        let lenient = compare(&baseline, Some("bbbb"), &current, 15.0);
+        assert!(!lenient.has_regressions());
+    }
+
+    #[test]
+    fn test_save_and_find_baseline() {
+        let dir = tempfile::tempdir().unwrap();
+        run("abc123", 10, vec![Metric::time_ns("a", 1.0)])
+            .save(dir.path())
+            .unwrap();
+        run("abd456", 20, vec![Metric::time_ns("a", 2.0)])
+            .save(dir.path())
+            .unwrap();
+
+        let found = find_baseline(dir.path(), "smoke", "abc", None).unwrap();
+        assert_eq!(found.git_sha, "abc123");
+
+        // `latest` skips the commit being measured
+        let latest = find_baseline(dir.path(), "smoke", "latest", None).unwrap();
+        assert_eq!(latest.git_sha, "abd456");
+        let previous = find_baseline(dir.path(), "smoke", "latest", Some("abd456")).unwrap();
+        assert_eq!(previous.git_sha, "abc123");
+
+        let err = find_baseline(dir.path(), "smoke", "ab", None).unwrap_err();
+        assert_eq!(err.code(), codes::BENCH_BASELINE_AMBIGUOUS);
+        let err = find_baseline(dir.path(), "smoke", "fff", None).unwrap_err();
+        assert_eq!(err.code(), codes::BENCH_BASELINE_NOT_FOUND);
+        let err = find_baseline(dir.path(), "test", "latest", None).unwrap_err();
+        assert_eq!(err.code(), codes::BENCH_BASELINE_NOT_FOUND);
+
+        // A file path works for any location, but must be the same kind
+        let file = dir.path().join("smoke").join("abc123.json");
+        let err = find_baseline(dir.path(), "test", file.to_str().unwrap(), None).unwrap_err();
+        assert_eq!(err.code(), codes::BENCH_BASELINE_KIND_MISMATCH);
+    }
+}
@@ -1,21 +1,21 @@
 //! HTTP server benchmark harness for fastnode.
 //!
 //! Compares HTTP server throughput (requests per second) across howth, node, bun, and deno
-//! by starting a "Hello World" HTTP server for each runtime and load testing it.
+//! by starting a "Hello World" HTTP server for each runtime and load testing it with the
+//! built-in generator in [`super::loadgen`].
 
 #![allow(clippy::cast_possible_truncation)]
 #![allow(clippy::cast_precision_loss)]
 
 use crate::bench::build::MachineInfo;
+use crate::bench::loadgen::{run_load, LoadGenParams, LoadGenResult};
 use crate::bench::BenchWarning;
 use serde::{Deserialize, Serialize};
 use std::fs;
 use std::io::{BufRead, BufReader};
-use std::net::TcpStream;
+use std::net::{SocketAddr, TcpStream};
 use std::path::Path;
 use std::process::{Child, Command, Stdio};
-use std::sync::atomic::{AtomicU64, Ordering};
-use std::sync::Arc;
 use std::thread;
 use std::time::{Duration, Instant};
 
@@ -31,6 +31,9 @@ pub const DEFAULT_CONNECTIONS: u32 = 50;
 /// Default number of warmup seconds.
 pub const DEFAULT_WARMUP_SECS: u32 = 2;
 
+/// Default requests in flight per connection (no pipelining).
+pub const DEFAULT_PIPELINING: u32 = 1;
+
 /// Port to use for benchmarks (cycles through to avoid conflicts).
 const BASE_PORT: u16 = 9100;
 
@@ -43,6 +46,13 @@ pub struct HttpBenchParams {
     pub connections: u32,
     /// Warmup duration in seconds.
     pub warmup_secs: u32,
+    /// Requests sent per round trip on each connection.
+    #[serde(default = "default_pipelining")]
+    pub pipelining: u32,
+}
+
+fn default_pipelining() -> u32 {
+    DEFAULT_PIPELINING
 }
 
 /// Result for a single tool's HTTP benchmark.
@@ -56,8 +66,20 @@ pub struct HttpToolResult {
     pub total_requests: u64,
     /// Average latency in microseconds.
     pub avg_latency_us: u64,
+    /// Median latency in microseconds.
+    #[serde(default)]
+    pub p50_latency_us: u64,
+    /// p90 latency in microseconds.
+    #[serde(default)]
+    pub p90_latency_us: u64,
     /// p99 latency in microseconds.
     pub p99_latency_us: u64,
+    /// p99.9 latency in microseconds.
+    #[serde(default)]
+    pub p999_latency_us: u64,
+    /// Maximum latency in microseconds.
+    #[serde(default)]
+    pub max_latency_us: u64,
     /// Number of errors.
     pub errors: u64,
 }
@@ -263,11 +285,11 @@ fn bench_tool(
 
     // Warmup
     eprintln!("    Warming up for {}s...", params.warmup_secs);
-    run_load_test(port, params.connections, params.warmup_secs);
+    run_load_test(port, params, params.warmup_secs);
 
     // Actual benchmark
     eprintln!("    Running benchmark for {}s...", params.duration_secs);
-    let result = run_load_test(port, params.connections, params.duration_secs);
+    let result = run_load_test(port, params, params.duration_secs);
 
     // Stop server
     let _ = server.kill();
@@ -276,12 +298,17 @@ fn bench_tool(
     // Small delay to let port be released
     thread::sleep(Duration::from_millis(100));
 
+    let latency = &result.latency_us;
     Some(HttpToolResult {
         tool: tool.to_string(),
-        rps: result.rps,
-        total_requests: result.total_requests,
-        avg_latency_us: result.avg_latency_us,
-        p99_latency_us: result.p99_latency_us,
+        rps: result.rps(),
+        total_requests: result.requests,
+        avg_latency_us: latency.mean(),
+        p50_latency_us: latency.percentile(50.0),
+        p90_latency_us: latency.percentile(90.0),
+        p99_latency_us: latency.percentile(99.0),
+        p999_latency_us: latency.percentile(99.9),
+        max_latency_us: latency.max(),
         errors: result.errors,
     })
 }
@@ -383,188 +410,15 @@ fn wait_for_ready(server: &mut Child, port: u16, timeout: Duration) -> bool {
     false
 }
 
-/// Result of a load test run.
-struct LoadTestResult {
-    rps: f64,
-    total_requests: u64,
-    avg_latency_us: u64,
-    p99_latency_us: u64,
-    errors: u64,
-}
-
-/// Run a load test against the server.
-fn run_load_test(port: u16, connections: u32, duration_secs: u32) -> LoadTestResult {
-    let total_requests = Arc::new(AtomicU64::new(0));
-    let total_errors = Arc::new(AtomicU64::new(0));
-    let latencies = Arc::new(std::sync::Mutex::new(Vec::new()));
-
-    let duration = Duration::from_secs(u64::from(duration_secs));
-    let start = Instant::now();
-
-    // Spawn worker threads - each maintains a persistent connection
-    let mut handles = Vec::new();
-    for _ in 0..connections {
-        let requests = Arc::clone(&total_requests);
-        let errors = Arc::clone(&total_errors);
-        let lats = Arc::clone(&latencies);
-
-        let handle = thread::spawn(move || {
-            let addr = format!("127.0.0.1:{port}");
-            let mut local_latencies = Vec::new();
-            let mut conn: Option<TcpStream> = None;
-
-            while start.elapsed() < duration {
-                let req_start = Instant::now();
-
-                // Get or create connection
-                let stream = match conn.take() {
-                    Some(s) => s,
-                    None => {
-                        if let Ok(s) = TcpStream::connect(&addr) {
-                            let _ = s.set_read_timeout(Some(Duration::from_secs(5)));
-                            let _ = s.set_write_timeout(Some(Duration::from_secs(5)));
-                            let _ = s.set_nodelay(true);
-                            s
-                        } else {
-                            errors.fetch_add(1, Ordering::Relaxed);
-                            continue;
-                        }
-                    }
-                };
-
-                match make_request_keepalive(stream) {
-                    Ok(s) => {
-                        requests.fetch_add(1, Ordering::Relaxed);
-                        local_latencies.push(req_start.elapsed().as_micros() as u64);
-                        conn = Some(s); // Reuse connection
-                    }
-                    Err(_) => {
-                        errors.fetch_add(1, Ordering::Relaxed);
-                        // Connection failed, will reconnect on next iteration
-                    }
-                }
-            }
-
-            // Merge local latencies
-            if let Ok(mut guard) = lats.lock() {
-                guard.extend(local_latencies);
-            }
-        });
-        handles.push(handle);
-    }
-
-    // Wait for all threads
-    for handle in handles {
-        let _ = handle.join();
-    }
-
-    let elapsed = start.elapsed();
-    let total = total_requests.load(Ordering::Relaxed);
-    let errs = total_errors.load(Ordering::Relaxed);
-
-    // Compute latency stats
-    let (avg_latency_us, p99_latency_us) = if let Ok(mut lats) = latencies.lock() {
-        if lats.is_empty() {
-            (0, 0)
-        } else {
-            lats.sort_unstable();
-            let avg = lats.iter().sum::<u64>() / lats.len() as u64;
-            let p99_idx = (lats.len() * 99) / 100;
-            let p99 = lats.get(p99_idx.min(lats.len() - 1)).copied().unwrap_or(0);
-            (avg, p99)
-        }
-    } else {
-        (0, 0)
-    };
-
-    let rps = total as f64 / elapsed.as_secs_f64();
-
-    LoadTestResult {
-        rps,
-        total_requests: total,
-        avg_latency_us,
-        p99_latency_us,
-        errors: errs,
-    }
-}
-
-/// Make a single HTTP request over an existing connection (keep-alive).
-/// Returns the connection back if successful for reuse.
-fn make_request_keepalive(mut stream: TcpStream) -> Result<TcpStream, std::io::Error> {
-    use std::io::{Read, Write};
-
-    // Send HTTP/1.1 request with keep-alive
-    stream.write_all(b"GET / HTTP/1.1\r\nHost: localhost\r\n\r\n")?;
-
-    // Read response headers to find Content-Length
-    let mut buf = [0u8; 4096];
-    let mut total_read = 0;
-    #[allow(unused_assignments)]
-    let mut headers_end = None;
-    #[allow(unused_assignments)]
-    let mut content_length: Option<usize> = None;
-
-    // Read until we have complete headers
-    loop {
-        let n = stream.read(&mut buf[total_read..])?;
-        if n == 0 {
-            return Err(std::io::Error::new(
-                std::io::ErrorKind::UnexpectedEof,
-                "connection closed",
-            ));
-        }
-        total_read += n;
-
-        // Look for end of headers
-        if let Some(pos) = find_header_end(&buf[..total_read]) {
-            headers_end = Some(pos);
-            // Parse Content-Length from headers
-            content_length = parse_content_length(&buf[..pos]);
-            break;
-        }
-
-        if total_read >= buf.len() {
-            // Headers too large, bail
-            return Err(std::io::Error::new(
-                std::io::ErrorKind::InvalidData,
-                "headers too large",
-            ));
-        }
-    }
-
-    let headers_end = headers_end.unwrap();
-    let body_start = headers_end + 4; // Skip \r\n\r\n
-
-    // Read remaining body if needed
-    if let Some(content_len) = content_length {
-        let body_read = total_read - body_start;
-        let remaining = content_len.saturating_sub(body_read);
-        if remaining > 0 {
-            // Read remaining body bytes
-            let mut discard = vec![0u8; remaining];
-            stream.read_exact(&mut discard)?;
-        }
-    }
-
-    Ok(stream)
-}
-
-/// Find the position of \r\n\r\n in the buffer (end of HTTP headers).
-fn find_header_end(buf: &[u8]) -> Option<usize> {
-    buf.windows(4).position(|w| w == b"\r\n\r\n")
-}
-
-/// Parse Content-Length header from HTTP headers.
-fn parse_content_length(headers: &[u8]) -> Option<usize> {
-    let headers_str = std::str::from_utf8(headers).ok()?;
-    for line in headers_str.lines() {
-        let lower = line.to_ascii_lowercase();
-        if lower.starts_with("content-length:") {
-            let value = line[15..].trim();
-            return value.parse().ok();
-        }
-    }
-    None
+/// Run the built-in load generator against the server.
+fn run_load_test(port: u16, params: &HttpBenchParams, duration_secs: u32) -> LoadGenResult {
+    run_load(&LoadGenParams {
+        addr: SocketAddr::from(([127, 0, 0, 1], port)),
+        connections: params.connections,
+        duration: Duration::from_secs(u64::from(duration_secs)),
+        pipelining: params.pipelining,
+        path: "/".to_string(),
+    })
 }
 
 /// Compute comparisons between howth and other tools.
@@ -599,8 +453,7 @@ fn tool_available(tool: &str) -> bool {
     Command::new("which")
         .arg(binary)
         .output()
-        .map(|o| o.status.success())
-        .unwrap_or(false)
+        .is_ok_and(|o| o.status.success())
 }
 
 #[cfg(test)]
@@ -620,7 +473,11 @@ mod tests {
                 rps: 50000.0,
                 total_requests: 500_000,
                 avg_latency_us: 100,
+                p50_latency_us: 90,
+                p90_latency_us: 200,
                 p99_latency_us: 500,
+                p999_latency_us: 800,
+                max_latency_us: 1200,
                 errors: 0,
             },
             HttpToolResult {
@@ -628,7 +485,11 @@ mod tests {
                 rps: 25000.0,
                 total_requests: 250_000,
                 avg_latency_us: 200,
+                p50_latency_us: 180,
+                p90_latency_us: 400,
                 p99_latency_us: 1000,
+                p999_latency_us: 1600,
+                max_latency_us: 2400,
                 errors: 0,
             },
         ];
@@ -458,8 +458,7 @@ fn tool_available(tool: &str) -> bool {
     Command::new("which")
         .arg(tool)
         .output()
-        .map(|o| o.status.success())
-        .unwrap_or(false)
+        .is_ok_and(|o| o.status.success())
 }
 
 /// Parse a command string into (program, args).
@@ -0,0 +1,545 @@
+//! Built-in HTTP/1.1 load generator for `howth bench http`.
+//!
+//! Drives keep-alive connections from a tokio runtime, optionally pipelining
+//! several requests per round trip, and records per-request latency in a
+//! [`LatencyHistogram`]. No external tool (wrk, bombardier) is needed.
+
+#![allow(clippy::cast_possible_truncation)]
+#![allow(clippy::cast_precision_loss)]
+#![allow(clippy::cast_sign_loss)]
+
+use std::net::SocketAddr;
+use std::time::{Duration, Instant};
+use tokio::io::{AsyncReadExt, AsyncWriteExt};
+use tokio::net::TcpStream;
+
+/// Per-request I/O timeout; a stalled connection counts as an error.
+const IO_TIMEOUT: Duration = Duration::from_secs(5);
+
+/// Pause before reconnecting after a failed connect, so a dead server
+/// doesn't turn into a busy loop of errors.
+const RECONNECT_BACKOFF: Duration = Duration::from_millis(10);
+
+/// Largest response head accepted.
+const MAX_HEAD_BYTES: usize = 64 * 1024;
+
+/// Sub-buckets per power of two. 64 keeps every recorded value within ~1.6%
+/// of its true value, like an HDR histogram with two significant digits.
+const SUB_BUCKETS: u64 = 64;
+const SUB_BUCKET_BITS: u32 = SUB_BUCKETS.trailing_zeros();
+
+/// Log-linear latency histogram with bounded relative error.
+///
+/// Values below [`SUB_BUCKETS`] are exact; above that each power of two is
+/// split into `SUB_BUCKETS` equal buckets. Memory is fixed (about 30 KiB) no
+/// matter how many values are recorded, and histograms merge by addition.
+#[derive(Debug, Clone)]
+pub struct LatencyHistogram {
+    counts: Vec<u64>,
+    total: u64,
+    sum: u128,
+    min: u64,
+    max: u64,
+}
+
+impl Default for LatencyHistogram {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl LatencyHistogram {
+    /// Create an empty histogram.
+    #[must_use]
+    pub fn new() -> Self {
+        Self {
+            counts: vec![0; Self::bucket_index(u64::MAX) + 1],
+            total: 0,
+            sum: 0,
+            min: u64::MAX,
+            max: 0,
+        }
+    }
+
+    fn bucket_index(value: u64) -> usize {
+        if value < SUB_BUCKETS {
+            return value as usize;
+        }
+        let shift = value.ilog2() - SUB_BUCKET_BITS;
+        let sub = (value >> shift) - SUB_BUCKETS;
+        ((u64::from(shift) + 1) * SUB_BUCKETS + sub) as usize
+    }
+
+    /// Highest value that falls in bucket `index`.
+    fn bucket_value(index: usize) -> u64 {
+        let index = index as u64;
+        if index < SUB_BUCKETS {
+            return index;
+        }
+        let shift = index / SUB_BUCKETS - 1;
+        let sub = index % SUB_BUCKETS + SUB_BUCKETS;
+        // The top bucket's bound shifts out of range and wraps to u64::MAX
+        ((sub + 1) << shift).wrapping_sub(1)
+    }
+
+    /// Record one value.
+    pub fn record(&mut self, value: u64) {
+        self.counts[Self::bucket_index(value)] += 1;
+        self.total += 1;
+        self.sum += u128::from(value);
+        self.min = self.min.min(value);
+        self.max = self.max.max(value);
+    }
+
+    /// Add another histogram's values to this one.
+    pub fn merge(&mut self, other: &Self) {
+        for (count, other) in self.counts.iter_mut().zip(&other.counts) {
+            *count += other;
+        }
+        self.total += other.total;
+        self.sum += other.sum;
+        self.min = self.min.min(other.min);
+        self.max = self.max.max(other.max);
+    }
+
+    /// Number of recorded values.
+    #[must_use]
+    pub fn len(&self) -> u64 {
+        self.total
+    }
+
+    /// Whether nothing has been recorded.
+    #[must_use]
+    pub fn is_empty(&self) -> bool {
+        self.total == 0
+    }
+
+    /// Exact mean of the recorded values (0 when empty).
+    #[must_use]
+    pub fn mean(&self) -> u64 {
+        if self.total == 0 {
+            0
+        } else {
+            (self.sum / u128::from(self.total)) as u64
+        }
+    }
+
+    /// Largest recorded value (0 when empty).
+    #[must_use]
+    pub fn max(&self) -> u64 {
+        self.max
+    }
+
+    /// Value at percentile `p` (0-100), by nearest rank. Reported as the top
+    /// of its bucket, clamped to the recorded range; 0 when empty.
+    #[must_use]
+    pub fn percentile(&self, p: f64) -> u64 {
+        if self.total == 0 {
+            return 0;
+        }
+        let rank = ((p.clamp(0.0, 100.0) / 100.0) * self.total as f64).ceil() as u64;
+        let rank = rank.max(1);
+        let mut seen = 0;
+        for (index, &count) in self.counts.iter().enumerate() {
+            seen += count;
+            if seen >= rank {
+                return Self::bucket_value(index).clamp(self.min, self.max);
+            }
+        }
+        self.max
+    }
+}
+
+/// Load generator settings.
+#[derive(Debug, Clone)]
+pub struct LoadGenParams {
+    /// Server address.
+    pub addr: SocketAddr,
+    /// Concurrent keep-alive connections.
+    pub connections: u32,
+    /// How long to generate load.
+    pub duration: Duration,
+    /// Requests written per round trip on each connection (1 = no pipelining).
+    pub pipelining: u32,
+    /// Request path.
+    pub path: String,
+}
+
+/// Outcome of a load generator run.
+#[derive(Debug, Clone)]
+pub struct LoadGenResult {
+    /// Completed requests.
+    pub requests: u64,
+    /// Failed connects, writes, reads and malformed responses.
+    pub errors: u64,
+    /// Wall time from start until every connection stopped.
+    pub elapsed: Duration,
+    /// Per-request latency in microseconds.
+    pub latency_us: LatencyHistogram,
+}
+
+impl LoadGenResult {
+    /// Completed requests per second.
+    #[must_use]
+    pub fn rps(&self) -> f64 {
+        let secs = self.elapsed.as_secs_f64();
+        if secs > 0.0 {
+            self.requests as f64 / secs
+        } else {
+            0.0
+        }
+    }
+}
+
+/// Generate load against `params.addr` and collect the results.
+///
+/// # Panics
+///
+/// Panics if the tokio runtime cannot be created.
+#[must_use]
+pub fn run_load(params: &LoadGenParams) -> LoadGenResult {
+    let runtime = tokio::runtime::Builder::new_multi_thread()
+        .enable_all()
+        .build()
+        .expect("Failed to create load generator runtime");
+    runtime.block_on(generate(params))
+}
+
+async fn generate(params: &LoadGenParams) -> LoadGenResult {
+    let request = format!(
+        "GET {} HTTP/1.1\r\nHost: {}\r\nConnection: keep-alive\r\n\r\n",
+        params.path, params.addr
+    );
+    let batch = request
+        .repeat(params.pipelining.max(1) as usize)
+        .into_bytes();
+    let start = Instant::now();
+    let deadline = tokio::time::Instant::from_std(start + params.duration);
+
+    let tasks: Vec<_> = (0..params.connections.max(1))
+        .map(|_| {
+            let batch = batch.clone();
+            let addr = params.addr;
+            let pipelining = params.pipelining.max(1);
+            tokio::spawn(drive_connection(addr, batch, pipelining, deadline))
+        })
+        .collect();
+
+    let mut result = LoadGenResult {
+        requests: 0,
+        errors: 0,
+        elapsed: Duration::ZERO,
+        latency_us: LatencyHistogram::new(),
+    };
+    for task in tasks {
+        if let Ok(stats) = task.await {
+            result.requests += stats.requests;
+            result.errors += stats.errors;
+            result.latency_us.merge(&stats.latency_us);
+        }
+    }
+    result.elapsed = start.elapsed();
+    result
+}
+
+struct ConnectionStats {
+    requests: u64,
+    errors: u64,
+    latency_us: LatencyHistogram,
+}
+
+/// Keep one connection busy until the deadline, reconnecting after errors.
+async fn drive_connection(
+    addr: SocketAddr,
+    batch: Vec<u8>,
+    pipelining: u32,
+    deadline: tokio::time::Instant,
+) -> ConnectionStats {
+    let mut stats = ConnectionStats {
+        requests: 0,
+        errors: 0,
+        latency_us: LatencyHistogram::new(),
+    };
+    let mut reader = ResponseReader::default();
+    let mut conn: Option<TcpStream> = None;
+
+    while tokio::time::Instant::now() < deadline {
+        let mut stream = if let Some(stream) = conn.take() {
+            stream
+        } else if let Ok(Ok(stream)) =
+            tokio::time::timeout(IO_TIMEOUT, TcpStream::connect(addr)).await
+        {
+            let _ = stream.set_nodelay(true);
+            reader.clear();
+            stream
+        } else {
+            stats.errors += 1;
+            tokio::time::sleep(RECONNECT_BACKOFF).await;
+            continue;
+        };
+
+        let sent = Instant::now();
+        let round_trip = async {
+            stream.write_all(&batch).await?;
+            for _ in 0..pipelining {
+                reader.read_response(&mut stream).await?;
+                stats.requests += 1;
+                stats.latency_us.record(sent.elapsed().as_micros() as u64);
+            }
+            Ok::<_, std::io::Error>(())
+        };
+        let outcome = tokio::time::timeout(IO_TIMEOUT, round_trip).await;
+        match outcome {
+            Ok(Ok(())) => conn = Some(stream),
+            // Drop the connection; the next iteration reconnects
+            _ => stats.errors += 1,
+        }
+    }
+    stats
+}
+
+/// Buffered HTTP/1.1 response reader that keeps bytes belonging to the next
+/// pipelined response.
+#[derive(Default)]
+struct ResponseReader {
+    buf: Vec<u8>,
+}
+
+impl ResponseReader {
+    fn clear(&mut self) {
+        self.buf.clear();
+    }
+
+    async fn fill(&mut self, stream: &mut TcpStream) -> std::io::Result<()> {
+        self.buf.reserve(16 * 1024);
+        if stream.read_buf(&mut self.buf).await? == 0 {
+            return Err(std::io::Error::new(
+                std::io::ErrorKind::UnexpectedEof,
+                "connection closed",
+            ));
+        }
+        Ok(())
+    }
+
+    /// Read one complete response and drop it from the buffer.
+    async fn read_response(&mut self, stream: &mut TcpStream) -> std::io::Result<()> {
+        let head_end = loop {
+            if let Some(pos) = find_header_end(&self.buf) {
+                break pos + 4;
+            }
+            if self.buf.len() > MAX_HEAD_BYTES {
+                return Err(invalid("response head too large"));
+            }
+            self.fill(stream).await?;
+        };
+
+        let body = parse_body_framing(&self.buf[..head_end])?;
+        let total = loop {
+            match body {
+                BodyFraming::Length(len) => {
+                    if self.buf.len() >= head_end + len {
+                        break head_end + len;
+                    }
+                }
+                BodyFraming::Chunked => {
+                    if let Some(end) = chunked_body_end(&self.buf[head_end..])? {
+                        break head_end + end;
+                    }
+                }
+            }
+            self.fill(stream).await?;
+        };
+        self.buf.drain(..total);
+        Ok(())
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum BodyFraming {
+    Length(usize),
+    Chunked,
+}
+
+fn invalid(message: &str) -> std::io::Error {
+    std::io::Error::new(std::io::ErrorKind::InvalidData, message.to_string())
+}
+
+/// Find the position of \r\n\r\n in the buffer (end of HTTP headers).
+fn find_header_end(buf: &[u8]) -> Option<usize> {
+    buf.windows(4).position(|w| w == b"\r\n\r\n")
+}
+
+/// How the body after `head` is delimited. Bodies without a length (read to
+/// close) can't be pipelined, so they are rejected.
+fn parse_body_framing(head: &[u8]) -> std::io::Result<BodyFraming> {
+    let head = std::str::from_utf8(head).map_err(|_| invalid("response head is not UTF-8"))?;
+    let mut lines = head.split("\r\n");
+    let status = lines.next().unwrap_or_default();
+    if !status.starts_with("HTTP/1.") {
+        return Err(invalid("not an HTTP/1.x response"));
+    }
+    // 1xx, 204 and 304 responses never have a body
+    let code = status.split(' ').nth(1).unwrap_or_default();
+    if code.starts_with('1') || code == "204" || code == "304" {
+        return Ok(BodyFraming::Length(0));
+    }
+    for line in lines {
+        let Some((name, value)) = line.split_once(':') else {
+            continue;
+        };
+        let value = value.trim();
+        if name.eq_ignore_ascii_case("content-length") {
+            return value
+                .parse()
+                .map(BodyFraming::Length)
+                .map_err(|_| invalid("invalid Content-Length"));
+        }
+        if name.eq_ignore_ascii_case("transfer-encoding")
+            && value.to_ascii_lowercase().contains("chunked")
+        {
+            return Ok(BodyFraming::Chunked);
+        }
+    }
+    Err(invalid(
+        "response has neither Content-Length nor chunked encoding",
+    ))
+}
+
+/// Length of a complete chunked body at the start of `buf`, or `None` if more
+/// bytes are needed. Trailers are not supported.
+fn chunked_body_end(buf: &[u8]) -> std::io::Result<Option<usize>> {
+    let mut pos = 0;
+    loop {
+        let Some(line_len) = buf[pos..].windows(2).position(|w| w == b"\r\n") else {
+            return Ok(None);
+        };
+        let line = std::str::from_utf8(&buf[pos..pos + line_len])
+            .map_err(|_| invalid("invalid chunk size"))?;
+        let size_text = line.split(';').next().unwrap_or_default().trim();
+        let size =
+            usize::from_str_radix(size_text, 16).map_err(|_| invalid("invalid chunk size"))?;
+        pos += line_len + 2;
+        if size == 0 {
+            return Ok((buf.len() >= pos + 2).then_some(pos + 2));
+        }
+        if buf.len() < pos + size + 2 {
+            return Ok(None);
+        }
+        pos += size + 2;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_histogram_percentiles_within_error_bound() {
+        let mut histogram = LatencyHistogram::new();
+        for value in 1..=10_000 {
+            histogram.record(value);
+        }
+        assert_eq!(histogram.len(), 10_000);
+        assert_eq!(histogram.mean(), 5_000);
+        assert_eq!(histogram.max(), 10_000);
+        for (p, exact) in [
+            (50.0, 5_000.0),
+            (90.0, 9_000.0),
+            (99.0, 9_900.0),
+            (99.9, 9_990.0),
+        ] {
+            let value = histogram.percentile(p) as f64;
+            assert!(
+                (value - exact).abs() / exact < 0.02,
+                "p{p} = {value}, expected about {exact}"
+            );
+        }
+        assert_eq!(histogram.percentile(100.0), 10_000);
+        assert_eq!(histogram.percentile(0.0), 1);
+    }
+
+    #[test]
+    fn test_histogram_small_values_are_exact_and_merge() {
+        let mut a = LatencyHistogram::new();
+        let mut b = LatencyHistogram::new();
+        a.record(3);
+        b.record(7);
+        b.record(u64::MAX);
+        a.merge(&b);
+        assert_eq!(a.len(), 3);
+        assert_eq!(a.percentile(34.0), 7);
+        assert_eq!(a.percentile(100.0), u64::MAX);
+        assert!(LatencyHistogram::new().is_empty());
+        assert_eq!(LatencyHistogram::new().percentile(50.0), 0);
+    }
+
+    #[test]
+    fn test_parse_body_framing() {
+        let framing = |head: &str| parse_body_framing(head.as_bytes());
+        assert_eq!(
+            framing("HTTP/1.1 200 OK\r\ncontent-length: 12\r\n\r\n").unwrap(),
+            BodyFraming::Length(12)
+        );
+        assert_eq!(
+            framing("HTTP/1.1 200 OK\r\nTransfer-Encoding: chunked\r\n\r\n").unwrap(),
+            BodyFraming::Chunked
+        );
+        assert_eq!(
+            framing("HTTP/1.1 204 No Content\r\n\r\n").unwrap(),
+            BodyFraming::Length(0)
+        );
+        assert!(framing("HTTP/1.1 200 OK\r\n\r\n").is_err());
+        assert!(framing("SSH-2.0\r\n\r\n").is_err());
+    }
+
+    #[test]
+    fn test_chunked_body_end() {
+        let body = b"5\r\nhello\r\n0\r\n\r\nHTTP/1.1";
+        assert_eq!(chunked_body_end(body).unwrap(), Some(15));
+        assert_eq!(chunked_body_end(b"5\r\nhel").unwrap(), None);
+        assert_eq!(chunked_body_end(b"5\r\nhello\r\n0\r\n").unwrap(), None);
+        assert!(chunked_body_end(b"zz\r\n").is_err());
+    }
+
+    #[test]
+    fn test_run_load_against_pipelining_server() {
+        use std::io::{Read, Write};
+
+        let listener = std::net::TcpListener::bind("127.0.0.1:0").unwrap();
+        let addr = listener.local_addr().unwrap();
+        std::thread::spawn(move || {
+            for stream in listener.incoming() {
+                let Ok(mut stream) = stream else { break };
+                std::thread::spawn(move || {
+                    let response = b"HTTP/1.1 200 OK\r\nContent-Length: 12\r\n\r\nHello World\n";
+                    let mut pending = Vec::new();
+                    let mut buf = [0u8; 4096];
+                    while let Ok(n) = stream.read(&mut buf) {
+                        if n == 0 {
+                            break;
+                        }
+                        pending.extend_from_slice(&buf[..n]);
+                        while let Some(end) = find_header_end(&pending) {
+                            pending.drain(..end + 4);
+                            if stream.write_all(response).is_err() {
+                                return;
+                            }
+                        }
+                    }
+                });
+            }
+        });
+
+        let result = run_load(&LoadGenParams {
+            addr,
+            connections: 4,
+            duration: Duration::from_millis(300),
+            pipelining: 8,
+            path: "/".to_string(),
+        });
+        assert!(result.requests > 0);
+        assert_eq!(result.errors, 0);
+        assert_eq!(result.latency_us.len(), result.requests);
+        assert!(result.rps() > 0.0);
+    }
+}
@@ -13,8 +13,10 @@ use serde::{Deserialize, Serialize};
 
 pub mod build;
 pub mod bundler;
+pub mod history;
 pub mod http;
 pub mod install;
+pub mod loadgen;
 pub mod rusage;
 pub mod smoke;
 pub mod stats;
@@ -28,14 +30,20 @@ pub use bundler::{
     run_bundler_bench, BundlerBenchParams, BundlerBenchReport, BundlerComparison,
     BundlerToolResult, BUNDLER_BENCH_SCHEMA_VERSION,
 };
+pub use history::{
+    compare, find_baseline, BenchComparison, BenchMetrics, BenchRun, DeltaStatus, HistoryError,
+    Metric, MetricDelta, DEFAULT_HISTORY_DIR, DEFAULT_THRESHOLD_PCT,
+};
 pub use http::{
     run_http_bench, HttpBenchParams, HttpBenchReport, HttpComparison, HttpToolResult,
-    DEFAULT_CONNECTIONS, DEFAULT_DURATION_SECS, DEFAULT_WARMUP_SECS, HTTP_BENCH_SCHEMA_VERSION,
+    DEFAULT_CONNECTIONS, DEFAULT_DURATION_SECS, DEFAULT_PIPELINING, DEFAULT_WARMUP_SECS,
+    HTTP_BENCH_SCHEMA_VERSION,
 };
 pub use install::{
     run_install_bench, InstallBenchParams, InstallBenchReport, InstallComparison,
     InstallProjectInfo, InstallToolResult, INSTALL_BENCH_SCHEMA_VERSION,
 };
+pub use loadgen::{run_load, LatencyHistogram, LoadGenParams, LoadGenResult};
 pub use smoke::run_smoke_benchmarks;
 pub use stats::compute_stats;
 pub use test::{
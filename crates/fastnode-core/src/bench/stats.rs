@@ -43,7 +43,7 @@ pub fn compute_stats(samples: &[u64]) -> BenchStats {
     clippy::cast_lossless
 )]
 fn percentile(sorted: &[u64], p: u32) -> u64 {
-    assert!(!sorted.is_empty());
+    assert_ne!(sorted.len(), 0);
     assert!(p <= 100);
 
     if p == 0 {
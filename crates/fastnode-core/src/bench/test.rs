@@ -813,8 +813,7 @@ fn tool_available(tool: &str) -> bool {
     Command::new("which")
         .arg(tool)
         .output()
-        .map(|o| o.status.success())
-        .unwrap_or(false)
+        .is_ok_and(|o| o.status.success())
 }
 
 /// Recursively copy directory contents (skips `node_modules`).
@@ -874,8 +873,8 @@ mod tests {
         let mut bodies = Vec::new();
         for i in 0..6 {
             let (name, body) = generate_test_case(0, i);
-            assert!(!name.is_empty());
-            assert!(!body.is_empty());
+            assert_ne!(name, "");
+            assert_ne!(body, "");
             bodies.push(body);
         }
         // Not all bodies should be identical
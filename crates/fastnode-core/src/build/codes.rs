@@ -11,6 +11,9 @@ pub const BUILD_SCRIPT_NOT_FOUND: &str = "BUILD_SCRIPT_NOT_FOUND";
 /// Build script exited with non-zero status.
 pub const BUILD_SCRIPT_FAILED: &str = "BUILD_SCRIPT_FAILED";
 
+/// Build script exceeded its timeout and was killed.
+pub const BUILD_SCRIPT_TIMEOUT: &str = "BUILD_SCRIPT_TIMEOUT";
+
 /// I/O error during hash computation.
 pub const BUILD_HASH_IO_ERROR: &str = "BUILD_HASH_IO_ERROR";
 
@@ -47,6 +50,18 @@ pub const BUILD_NO_COMPILER_BACKEND: &str = "BUILD_NO_COMPILER_BACKEND";
 /// TypeScript type checking failed (v3.2).
 pub const BUILD_TYPECHECK_FAILED: &str = "BUILD_TYPECHECK_FAILED";
 
+/// TypeScript declaration generation failed.
+pub const BUILD_DTS_FAILED: &str = "BUILD_DTS_FAILED";
+
+/// Failed to remove node outputs for a clean build.
+pub const BUILD_CLEAN_FAILED: &str = "BUILD_CLEAN_FAILED";
+
+/// A configured build cache signing or trusted key cannot be read.
+pub const BUILD_CACHE_KEY_INVALID: &str = "BUILD_CACHE_KEY_INVALID";
+
+/// A shared build cache entry is unsigned or its signature doesn't verify.
+pub const BUILD_CACHE_SIGNATURE_INVALID: &str = "BUILD_CACHE_SIGNATURE_INVALID";
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -57,6 +72,7 @@ mod tests {
             BUILD_CWD_INVALID,
             BUILD_SCRIPT_NOT_FOUND,
             BUILD_SCRIPT_FAILED,
+            BUILD_SCRIPT_TIMEOUT,
             BUILD_HASH_IO_ERROR,
             BUILD_WATCH_ERROR,
             BUILD_GRAPH_INTERNAL_ERROR,
@@ -69,6 +85,10 @@ mod tests {
             BUILD_TRANSPILE_WRITE_ERROR,
             BUILD_NO_COMPILER_BACKEND,
             BUILD_TYPECHECK_FAILED,
+            BUILD_DTS_FAILED,
+            BUILD_CLEAN_FAILED,
+            BUILD_CACHE_KEY_INVALID,
+            BUILD_CACHE_SIGNATURE_INVALID,
         ];
 
         for code in codes {
@@ -0,0 +1,162 @@
+//! Structured diagnostics parsed from tool output.
+//!
+//! Currently understands TypeScript's `--pretty false` format:
+//!
+//! ```text
+//! src/a.ts(3,7): error TS2322: Type 'string' is not assignable to type 'number'.
+//!   Additional message chain lines are indented.
+//! error TS5058: The specified path does not exist: 'tsconfig.json'.
+//! ```
+
+use serde::{Deserialize, Serialize};
+
+/// Severity of a diagnostic.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum DiagnosticSeverity {
+    /// An error (fails the node).
+    Error,
+    /// A warning.
+    Warning,
+    /// An informational message or suggestion.
+    Info,
+}
+
+/// A single diagnostic (e.g. a type error) with its source location.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct BuildDiagnostic {
+    /// File path as reported by the tool (usually relative to the project root).
+    /// `None` for global diagnostics such as config errors.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub file: Option<String>,
+    /// 1-based line number (0 when there is no location).
+    pub line: u32,
+    /// 1-based column number (0 when there is no location).
+    pub column: u32,
+    /// Tool-specific code (e.g. "TS2322").
+    pub code: String,
+    /// Severity.
+    pub severity: DiagnosticSeverity,
+    /// Message text. Message chains are joined with newlines.
+    pub message: String,
+}
+
+/// Parse `tsc --pretty false` output into diagnostics.
+///
+/// Lines that don't start a diagnostic and aren't indented continuation lines
+/// (e.g. the "Found N errors" summary) are ignored.
+#[must_use]
+pub fn parse_tsc_output(output: &str) -> Vec<BuildDiagnostic> {
+    let mut diagnostics: Vec<BuildDiagnostic> = Vec::new();
+
+    for line in output.lines() {
+        if let Some(diagnostic) = parse_tsc_line(line) {
+            diagnostics.push(diagnostic);
+        } else if line.starts_with(' ') && !line.trim().is_empty() {
+            if let Some(last) = diagnostics.last_mut() {
+                last.message.push('\n');
+                last.message.push_str(line.trim_end());
+            }
+        }
+    }
+
+    diagnostics
+}
+
+/// Parse a single diagnostic header line.
+fn parse_tsc_line(line: &str) -> Option<BuildDiagnostic> {
+    // Split off the optional "file(line,col): " location prefix.
+    let (location, rest) = match line.find("): ") {
+        Some(idx) if !line.starts_with(' ') => {
+            let (loc, rest) = line.split_at(idx + 1);
+            (Some(loc), &rest[2..])
+        }
+        _ => (None, line),
+    };
+
+    let (severity, rest) = [
+        ("error ", DiagnosticSeverity::Error),
+        ("warning ", DiagnosticSeverity::Warning),
+        ("message ", DiagnosticSeverity::Info),
+        ("suggestion ", DiagnosticSeverity::Info),
+    ]
+    .into_iter()
+    .find_map(|(prefix, severity)| rest.strip_prefix(prefix).map(|rest| (severity, rest)))?;
+
+    let (code, message) = rest.split_once(": ")?;
+    if !code.starts_with("TS") || !code[2..].chars().all(|c| c.is_ascii_digit()) {
+        return None;
+    }
+
+    let (file, line_no, column) = match location {
+        Some(loc) => {
+            let open = loc.rfind('(')?;
+            let (line_no, column) = loc[open + 1..loc.len() - 1].split_once(',')?;
+            (
+                Some(loc[..open].to_string()),
+                line_no.parse().ok()?,
+                column.parse().ok()?,
+            )
+        }
+        None => (None, 0, 0),
+    };
+
+    Some(BuildDiagnostic {
+        file,
+        line: line_no,
+        column,
+        code: code.to_string(),
+        severity,
+        message: message.trim_end().to_string(),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_tsc_located_error() {
+        let output =
+            "src/a.ts(3,7): error TS2322: Type 'string' is not assignable to type 'number'.\n";
+        let diags = parse_tsc_output(output);
+        assert_eq!(diags.len(), 1);
+        assert_eq!(diags[0].file.as_deref(), Some("src/a.ts"));
+        assert_eq!(diags[0].line, 3);
+        assert_eq!(diags[0].column, 7);
+        assert_eq!(diags[0].code, "TS2322");
+        assert_eq!(diags[0].severity, DiagnosticSeverity::Error);
+        assert_eq!(
+            diags[0].message,
+            "Type 'string' is not assignable to type 'number'."
+        );
+    }
+
+    #[test]
+    fn test_parse_tsc_message_chain_and_global() {
+        let output = "\
+src/b (1).ts(10,1): error TS2345: Argument of type '{}' is not assignable.
+  Property 'x' is missing in type '{}'.
+error TS5058: The specified path does not exist: 'tsconfig.json'.
+
+Found 2 errors in 1 file.
+";
+        let diags = parse_tsc_output(output);
+        assert_eq!(diags.len(), 2);
+        assert_eq!(diags[0].file.as_deref(), Some("src/b (1).ts"));
+        assert_eq!(diags[0].line, 10);
+        assert_eq!(
+            diags[0].message,
+            "Argument of type '{}' is not assignable.\n  Property 'x' is missing in type '{}'."
+        );
+        assert_eq!(diags[1].file, None);
+        assert_eq!(diags[1].code, "TS5058");
+        assert_eq!(diags[1].line, 0);
+    }
+
+    #[test]
+    fn test_parse_tsc_ignores_noise() {
+        let output = "npm ERR! could not determine executable to run\nFound 0 errors.\n";
+        assert_eq!(parse_tsc_output(output), []);
+    }
+}
@@ -23,18 +23,30 @@
 #![allow(clippy::manual_div_ceil)]
 
 use super::codes;
-use super::fingerprint::{compute_fingerprint, OutputFingerprint};
+use super::diagnostics::{parse_tsc_output, DiagnosticSeverity};
+use super::fingerprint::{compute_fingerprint, normalize_output_path, OutputFingerprint};
 use super::graph::{
     BuildErrorInfo, BuildGraph, BuildNode, BuildNodeKind, BuildNodeReason, BuildNodeResult,
-    BuildRunResult, CacheStatus, MAX_OUTPUT_SIZE,
+    BuildRunResult, BuildSandbox, CacheStatus, DtsSpec, MAX_OUTPUT_SIZE,
 };
+use super::hash::{hash_bytes, hash_string, toolchain_fingerprint, HASH_ALGORITHM};
+use super::logs;
+use super::outputs::{prune_stale_outputs, EmittedOutputs};
+use super::remote::{ExecutionBackend, LocalExecution, ScriptJob};
+use super::signing::{CacheSigning, EntrySignature};
+use super::tools::version_changes;
 use crate::compiler::{CompilerBackend, TranspileSpec};
-use std::collections::HashMap;
+use fastnode_util::fs::{atomic_write, remove_within};
+use rayon::prelude::*;
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, HashMap};
+use std::ffi::{OsStr, OsString};
 use std::fs;
-use std::io::{self, BufRead, BufReader};
-use std::path::Path;
-use std::process::{Command, Stdio};
-use std::time::Instant;
+use std::io::{self, BufRead, BufReader, Write};
+use std::path::{Path, PathBuf};
+use std::process::Stdio;
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
 
 /// Options for build execution.
 #[derive(Debug, Clone, Default)]
@@ -50,6 +62,11 @@ pub struct ExecOptions {
     /// Target nodes to execute (empty = all nodes).
     /// Only nodes in this set (and their dependencies) will be executed.
     pub targets: Vec<String>,
+    /// Where script nodes run (`None` = [`LocalExecution`]).
+    pub executor: Option<Arc<dyn ExecutionBackend>>,
+    /// `SOURCE_DATE_EPOCH` exported to script nodes, so tools that honor it
+    /// produce reproducible outputs. Part of their cache key.
+    pub source_date_epoch: Option<u64>,
 }
 
 impl ExecOptions {
@@ -62,6 +79,8 @@ impl ExecOptions {
             max_parallel: num_cpus(),
             profile: false,
             targets: Vec::new(),
+            executor: None,
+            source_date_epoch: None,
         }
     }
 
@@ -85,6 +104,20 @@ impl ExecOptions {
         self.targets = targets;
         self
     }
+
+    /// Run script nodes with `executor` instead of locally.
+    #[must_use]
+    pub fn with_executor(mut self, executor: Arc<dyn ExecutionBackend>) -> Self {
+        self.executor = Some(executor);
+        self
+    }
+
+    /// Export `SOURCE_DATE_EPOCH` to script nodes.
+    #[must_use]
+    pub fn with_source_date_epoch(mut self, epoch: Option<u64>) -> Self {
+        self.source_date_epoch = epoch;
+        self
+    }
 }
 
 /// Get number of CPUs (clamped to 1..=64).
@@ -96,7 +129,7 @@ fn num_cpus() -> usize {
 }
 
 /// Cache entry with fingerprint support (v2.2).
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct CacheEntry {
     /// Input hash.
     pub hash: String,
@@ -104,6 +137,9 @@ pub struct CacheEntry {
     pub ok: bool,
     /// Output fingerprint (None if no outputs declared).
     pub fingerprint: Option<OutputFingerprint>,
+    /// Versions of the external tools the node ran, by tool name.
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub tools: BTreeMap<String, String>,
 }
 
 impl CacheEntry {
@@ -114,6 +150,7 @@ impl CacheEntry {
             hash: hash.to_string(),
             ok,
             fingerprint: None,
+            tools: BTreeMap::new(),
         }
     }
 
@@ -124,6 +161,7 @@ impl CacheEntry {
             hash: hash.to_string(),
             ok,
             fingerprint,
+            tools: BTreeMap::new(),
         }
     }
 }
@@ -153,6 +191,14 @@ pub trait BuildCache {
         fingerprint: Option<OutputFingerprint>,
     );
 
+    /// Tool versions recorded with the node's entry, whatever its hash.
+    fn tool_versions(&self, _node_id: &str) -> Option<BTreeMap<String, String>> {
+        None
+    }
+
+    /// Record the tool versions the node's current entry was built with.
+    fn set_tool_versions(&mut self, _node_id: &str, _tools: BTreeMap<String, String>) {}
+
     /// Invalidate cache for a node.
     fn invalidate(&mut self, node_id: &str);
 
@@ -215,6 +261,16 @@ impl BuildCache for MemoryCache {
         );
     }
 
+    fn tool_versions(&self, node_id: &str) -> Option<BTreeMap<String, String>> {
+        self.entries.get(node_id).map(|entry| entry.tools.clone())
+    }
+
+    fn set_tool_versions(&mut self, node_id: &str, tools: BTreeMap<String, String>) {
+        if let Some(entry) = self.entries.get_mut(node_id) {
+            entry.tools = tools;
+        }
+    }
+
     fn invalidate(&mut self, node_id: &str) {
         self.entries.remove(node_id);
     }
@@ -224,10 +280,190 @@ impl BuildCache for MemoryCache {
     }
 }
 
+/// Schema version for the persistent build cache.
+pub const BUILD_CACHE_SCHEMA_VERSION: u32 = 1;
+
+/// Location of the persistent build cache, relative to the project root.
+const BUILD_CACHE_PATH: &str = ".howth/cache/build-nodes.json";
+
+/// On-disk format of the persistent build cache.
+#[derive(Debug, Default, Serialize, Deserialize)]
+struct BuildCacheFile {
+    schema_version: u32,
+    /// Hash algorithm behind the node hashes; empty for caches written before it was recorded.
+    #[serde(default)]
+    algorithm: String,
+    entries: HashMap<String, CacheEntry>,
+    /// Entry signatures by node ID (see [`CacheSigning`]).
+    #[serde(default, skip_serializing_if = "HashMap::is_empty")]
+    signatures: HashMap<String, EntrySignature>,
+}
+
+/// Build cache persisted under `.howth/cache/`.
+///
+/// Used by commands that run build nodes outside the daemon (such as
+/// `bundle --dts`), so results survive across invocations. Call
+/// [`save`](Self::save) after executing nodes to write changes back.
+///
+/// Opened with [`CacheSigning`] keys, entries are signed when saved and
+/// only entries with a trusted signature are loaded; the others are
+/// listed by [`rejected`](Self::rejected).
+#[derive(Debug)]
+pub struct PersistentBuildCache {
+    path: PathBuf,
+    inner: MemoryCache,
+    dirty: bool,
+    signing: CacheSigning,
+    signatures: HashMap<String, EntrySignature>,
+    rejected: Vec<BuildErrorInfo>,
+}
+
+impl PersistentBuildCache {
+    /// Open the cache for a project, starting empty if it is missing or invalid.
+    #[must_use]
+    pub fn open(project_root: &Path) -> Self {
+        Self::open_with_signing(project_root, CacheSigning::default())
+    }
+
+    /// Open the cache for a project, signing and verifying entries with
+    /// `signing`.
+    #[must_use]
+    pub fn open_with_signing(project_root: &Path, signing: CacheSigning) -> Self {
+        let path = project_root.join(BUILD_CACHE_PATH);
+        let file = fs::read_to_string(&path)
+            .ok()
+            .and_then(|content| serde_json::from_str::<BuildCacheFile>(&content).ok())
+            .filter(|file| {
+                file.schema_version == BUILD_CACHE_SCHEMA_VERSION
+                    && file.algorithm == HASH_ALGORITHM
+            })
+            .unwrap_or_default();
+        let (mut entries, mut signatures) = (file.entries, file.signatures);
+
+        let mut rejected = Vec::new();
+        if signing.verifies() {
+            let mut node_ids: Vec<String> = entries.keys().cloned().collect();
+            node_ids.sort();
+            for node_id in node_ids {
+                if let Err(error) =
+                    signing.verify(&node_id, &entries[&node_id], signatures.get(&node_id))
+                {
+                    entries.remove(&node_id);
+                    signatures.remove(&node_id);
+                    rejected.push(error);
+                }
+            }
+        }
+
+        Self {
+            path,
+            inner: MemoryCache { entries },
+            // Rewrite the file without the rejected entries
+            dirty: !rejected.is_empty(),
+            signing,
+            signatures,
+            rejected,
+        }
+    }
+
+    /// Entries dropped on open because their signature was missing or
+    /// invalid, as [`BUILD_CACHE_SIGNATURE_INVALID`](super::codes::BUILD_CACHE_SIGNATURE_INVALID) errors.
+    #[must_use]
+    pub fn rejected(&self) -> &[BuildErrorInfo] {
+        &self.rejected
+    }
+
+    /// Forget the signature of a changed entry, re-signing it if possible.
+    fn resign(&mut self, node_id: &str) {
+        self.signatures.remove(node_id);
+        if let Some(entry) = self.inner.entries.get(node_id) {
+            if let Some(signature) = self.signing.sign(node_id, entry) {
+                self.signatures.insert(node_id.to_string(), signature);
+            }
+        }
+    }
+
+    /// Write the cache back to disk if it changed.
+    ///
+    /// # Errors
+    /// Returns an error if the cache file cannot be written.
+    pub fn save(&mut self) -> io::Result<()> {
+        if !self.dirty {
+            return Ok(());
+        }
+        let file = BuildCacheFile {
+            schema_version: BUILD_CACHE_SCHEMA_VERSION,
+            algorithm: HASH_ALGORITHM.to_string(),
+            entries: self.inner.entries.clone(),
+            signatures: self.signatures.clone(),
+        };
+        let json = serde_json::to_string(&file).map_err(io::Error::other)?;
+
+        if let Some(parent) = self.path.parent() {
+            fs::create_dir_all(parent)?;
+        }
+        // Readers never see a partial file
+        atomic_write(&self.path, json.as_bytes())?;
+        self.dirty = false;
+        Ok(())
+    }
+}
+
+impl BuildCache for PersistentBuildCache {
+    fn get(&self, node_id: &str, hash: &str) -> Option<bool> {
+        self.inner.get(node_id, hash)
+    }
+
+    fn get_entry(&self, node_id: &str, hash: &str) -> Option<CacheEntry> {
+        self.inner.get_entry(node_id, hash)
+    }
+
+    fn set(&mut self, node_id: &str, hash: &str, ok: bool) {
+        self.dirty = true;
+        self.inner.set(node_id, hash, ok);
+        self.resign(node_id);
+    }
+
+    fn set_with_fingerprint(
+        &mut self,
+        node_id: &str,
+        hash: &str,
+        ok: bool,
+        fingerprint: Option<OutputFingerprint>,
+    ) {
+        self.dirty = true;
+        self.inner
+            .set_with_fingerprint(node_id, hash, ok, fingerprint);
+        self.resign(node_id);
+    }
+
+    fn tool_versions(&self, node_id: &str) -> Option<BTreeMap<String, String>> {
+        self.inner.tool_versions(node_id)
+    }
+
+    fn set_tool_versions(&mut self, node_id: &str, tools: BTreeMap<String, String>) {
+        self.dirty = true;
+        self.inner.set_tool_versions(node_id, tools);
+        self.resign(node_id);
+    }
+
+    fn invalidate(&mut self, node_id: &str) {
+        self.dirty = true;
+        self.inner.invalidate(node_id);
+        self.signatures.remove(node_id);
+    }
+
+    fn clear(&mut self) {
+        self.dirty = true;
+        self.inner.clear();
+        self.signatures.clear();
+    }
+}
+
 /// Output from running a script.
 #[derive(Debug, Default)]
 pub struct ScriptOutput {
-    /// Exit code.
+    /// Exit code (-1 if the script was killed by a signal).
     pub exit_code: i32,
     /// Captured stdout (may be truncated).
     pub stdout: String,
@@ -237,63 +473,276 @@ pub struct ScriptOutput {
     pub stdout_truncated: bool,
     /// Whether stderr was truncated.
     pub stderr_truncated: bool,
+    /// Whether the script was killed for exceeding its timeout.
+    pub timed_out: bool,
+    /// Signal that terminated the script (Unix only).
+    pub signal: Option<i32>,
+    /// Notes from the execution backend, added to the node result.
+    pub notes: Vec<String>,
 }
 
+/// Environment variables passed through even when the environment is restricted.
+const SANDBOX_BASE_ENV: &[&str] = &[
+    "HOME",
+    "USER",
+    "LOGNAME",
+    "SHELL",
+    "LANG",
+    "TERM",
+    "TMPDIR",
+    "TMP",
+    "TEMP",
+    "SYSTEMROOT",
+    "COMSPEC",
+    "PATHEXT",
+    "WINDIR",
+];
+
 /// Run a script command.
 ///
+/// Uses the shared script runner shell so `node_modules/.bin` is on `PATH`.
+///
 /// # Errors
 /// Returns an error if the shell command fails to spawn or wait.
 pub fn run_script(command: &str, cwd: &Path) -> io::Result<ScriptOutput> {
-    let (shell, shell_arg) = if cfg!(windows) {
-        ("cmd.exe", "/C")
-    } else {
-        ("sh", "-c")
+    run_script_sandboxed(command, cwd, None, &BuildSandbox::default(), &[])
+}
+
+/// Run a script command under a sandbox policy.
+///
+/// - `timeout` kills the script along with the processes it started
+/// - `sandbox.restrict_env` passes only `env_allowlist` plus [`SANDBOX_BASE_ENV`]
+/// - `sandbox.max_output_bytes` caps captured output; the rest is drained and dropped
+/// - `sandbox.cpu_seconds` / `sandbox.memory_mb` set rlimits on Unix
+///
+/// # Errors
+/// Returns an error if the shell command fails to spawn or wait.
+pub fn run_script_sandboxed(
+    command: &str,
+    cwd: &Path,
+    timeout: Option<Duration>,
+    sandbox: &BuildSandbox,
+    env_allowlist: &[String],
+) -> io::Result<ScriptOutput> {
+    run_script_with_env(command, cwd, timeout, sandbox, env_allowlist, &[], None)
+}
+
+/// Run a script command under a sandbox policy with extra environment
+/// variables, which are set even when the environment is restricted.
+///
+/// When `log` is set, the full output (stdout and stderr interleaved, never
+/// truncated) is appended to that file.
+pub(crate) fn run_script_with_env(
+    command: &str,
+    cwd: &Path,
+    timeout: Option<Duration>,
+    sandbox: &BuildSandbox,
+    env_allowlist: &[String],
+    extra_env: &[(&str, &OsStr)],
+    log: Option<&Path>,
+) -> io::Result<ScriptOutput> {
+    let mut cmd = crate::scripts::shell_command(command, cwd);
+    if sandbox.restrict_env {
+        cmd.env_clear()
+            .envs(restricted_env(cwd, env_allowlist, |key| {
+                std::env::var_os(key)
+            }));
+    }
+    cmd.envs(extra_env.iter().copied());
+    #[cfg(unix)]
+    apply_unix_limits(&mut cmd, sandbox, timeout.is_some());
+
+    let log = match log {
+        Some(path) => Some(Arc::new(Mutex::new(
+            fs::OpenOptions::new()
+                .create(true)
+                .append(true)
+                .open(path)?,
+        ))),
+        None => None,
+    };
+    let mut child = cmd.stdout(Stdio::piped()).stderr(Stdio::piped()).spawn()?;
+
+    // Read both streams concurrently so neither pipe can fill up and block the child
+    let limit = sandbox.max_output_bytes.unwrap_or(MAX_OUTPUT_SIZE);
+    let stdout_reader = child.stdout.take().map(|stream| {
+        let log = log.clone();
+        std::thread::spawn(move || capture_stream(stream, limit, log.as_deref()))
+    });
+    let stderr_reader = child.stderr.take().map(|stream| {
+        let log = log.clone();
+        std::thread::spawn(move || capture_stream(stream, limit, log.as_deref()))
+    });
+
+    let (status, timed_out) = wait_with_timeout(&mut child, timeout)?;
+
+    let mut output = ScriptOutput {
+        exit_code: status.code().unwrap_or(-1),
+        timed_out,
+        ..ScriptOutput::default()
     };
+    #[cfg(unix)]
+    {
+        use std::os::unix::process::ExitStatusExt;
+        output.signal = status.signal();
+    }
+    if let Some(reader) = stdout_reader {
+        (output.stdout, output.stdout_truncated) = reader.join().unwrap_or_default();
+    }
+    if let Some(reader) = stderr_reader {
+        (output.stderr, output.stderr_truncated) = reader.join().unwrap_or_default();
+    }
 
-    let mut child = Command::new(shell)
-        .arg(shell_arg)
-        .arg(command)
-        .current_dir(cwd)
-        .stdout(Stdio::piped())
-        .stderr(Stdio::piped())
-        .spawn()?;
-
-    let mut output = ScriptOutput::default();
-
-    // Read stdout
-    if let Some(stdout) = child.stdout.take() {
-        let reader = BufReader::new(stdout);
-        for line in reader.lines().map_while(Result::ok) {
-            if output.stdout.len() + line.len() + 1 > MAX_OUTPUT_SIZE {
-                output.stdout_truncated = true;
-                break;
-            }
-            if !output.stdout.is_empty() {
-                output.stdout.push('\n');
+    Ok(output)
+}
+
+/// Environment of a script under `restrict_env`: the project's `PATH` plus
+/// [`SANDBOX_BASE_ENV`] and `env_allowlist`, looked up with `parent`.
+fn restricted_env(
+    cwd: &Path,
+    env_allowlist: &[String],
+    parent: impl Fn(&str) -> Option<OsString>,
+) -> Vec<(String, OsString)> {
+    let keys = SANDBOX_BASE_ENV
+        .iter()
+        .copied()
+        .chain(env_allowlist.iter().map(String::as_str));
+    std::iter::once(("PATH".to_string(), crate::scripts::bin_path(cwd)))
+        .chain(keys.filter_map(|key| Some((key.to_string(), parent(key)?))))
+        .collect()
+}
+
+/// Start the run log of a node, returning its path.
+///
+/// Logging is best effort: a log that can't be created leaves the node
+/// without one rather than failing it.
+fn start_log(cwd: &Path, node_id: &str) -> Option<PathBuf> {
+    logs::create_log(cwd, node_id).ok().map(|(path, _)| path)
+}
+
+/// Run a command for a node with its output logged.
+fn run_logged(command: &str, cwd: &Path, log: Option<&Path>) -> io::Result<ScriptOutput> {
+    run_script_with_env(command, cwd, None, &BuildSandbox::default(), &[], &[], log)
+}
+
+/// Read a stream line by line, keeping at most `limit` bytes and writing
+/// every line to `log`.
+///
+/// Returns the captured text and whether it was truncated.
+fn capture_stream(
+    stream: impl io::Read,
+    limit: usize,
+    log: Option<&Mutex<fs::File>>,
+) -> (String, bool) {
+    let mut captured = String::new();
+    let mut truncated = false;
+    for line in BufReader::new(stream).lines().map_while(Result::ok) {
+        if let Some(log) = log {
+            if let Ok(mut file) = log.lock() {
+                // A full disk shouldn't fail the script
+                let _ = writeln!(file, "{line}");
             }
-            output.stdout.push_str(&line);
         }
+        if truncated || captured.len() + line.len() + 1 > limit {
+            // Keep draining so the child never blocks on a full pipe
+            truncated = true;
+            continue;
+        }
+        if !captured.is_empty() {
+            captured.push('\n');
+        }
+        captured.push_str(&line);
     }
+    (captured, truncated)
+}
 
-    // Read stderr
-    if let Some(stderr) = child.stderr.take() {
-        let reader = BufReader::new(stderr);
-        for line in reader.lines().map_while(Result::ok) {
-            if output.stderr.len() + line.len() + 1 > MAX_OUTPUT_SIZE {
-                output.stderr_truncated = true;
-                break;
-            }
-            if !output.stderr.is_empty() {
-                output.stderr.push('\n');
+/// Wait for a child, killing it once `timeout` elapses.
+///
+/// Returns the exit status and whether the timeout was hit.
+fn wait_with_timeout(
+    child: &mut std::process::Child,
+    timeout: Option<Duration>,
+) -> io::Result<(std::process::ExitStatus, bool)> {
+    let Some(timeout) = timeout else {
+        return child.wait().map(|status| (status, false));
+    };
+
+    let deadline = Instant::now() + timeout;
+    loop {
+        if let Some(status) = child.try_wait()? {
+            return Ok((status, false));
+        }
+        if Instant::now() >= deadline {
+            kill_process_tree(child);
+            return child.wait().map(|status| (status, true));
+        }
+        std::thread::sleep(Duration::from_millis(10));
+    }
+}
+
+/// Kill a timed-out script along with anything it spawned.
+fn kill_process_tree(child: &mut std::process::Child) {
+    #[cfg(unix)]
+    {
+        // The child leads its own process group (see `apply_unix_limits`).
+        if let Ok(pid) = i32::try_from(child.id()) {
+            // SAFETY: kill has no memory safety requirements
+            unsafe {
+                libc::kill(-pid, libc::SIGKILL);
             }
-            output.stderr.push_str(&line);
         }
     }
+    #[cfg(windows)]
+    {
+        // Killing the child only ends the shell; take its descendants too.
+        let _ = std::process::Command::new("taskkill")
+            .args(["/T", "/F", "/PID"])
+            .arg(child.id().to_string())
+            .stdout(Stdio::null())
+            .stderr(Stdio::null())
+            .status();
+    }
+    let _ = child.kill();
+}
 
-    let status = child.wait()?;
-    output.exit_code = status.code().unwrap_or(-1);
+/// Apply rlimits and process-group isolation before the shell execs.
+#[cfg(unix)]
+fn apply_unix_limits(cmd: &mut std::process::Command, sandbox: &BuildSandbox, new_group: bool) {
+    use std::os::unix::process::CommandExt;
 
-    Ok(output)
+    let cpu = sandbox.cpu_seconds;
+    let memory = sandbox.memory_mb.map(|mb| mb.saturating_mul(1024 * 1024));
+    if cpu.is_none() && memory.is_none() && !new_group {
+        return;
+    }
+
+    let rlimit = |value: u64| libc::rlimit {
+        rlim_cur: value,
+        rlim_max: value,
+    };
+
+    // SAFETY: the closure runs between fork and exec and only calls the
+    // async-signal-safe setpgid and setrlimit.
+    unsafe {
+        cmd.pre_exec(move || {
+            if new_group && libc::setpgid(0, 0) != 0 {
+                return Err(io::Error::last_os_error());
+            }
+            if let Some(secs) = cpu {
+                if libc::setrlimit(libc::RLIMIT_CPU, &rlimit(secs)) != 0 {
+                    return Err(io::Error::last_os_error());
+                }
+            }
+            // RLIMIT_AS would also count address space that is only
+            // reserved, which V8 reserves gigabytes of at startup
+            if let Some(bytes) = memory {
+                if libc::setrlimit(libc::RLIMIT_DATA, &rlimit(bytes)) != 0 {
+                    return Err(io::Error::last_os_error());
+                }
+            }
+            Ok(())
+        });
+    }
 }
 
 /// Execute a single build node.
@@ -390,26 +839,67 @@ pub fn execute_node(
         );
     };
 
+    let timeout = node
+        .command
+        .as_ref()
+        .and_then(|command| command.timeout_ms)
+        .map(Duration::from_millis);
+    let sandbox = node.sandbox.clone().unwrap_or_default();
+
+    let executor = options
+        .executor
+        .as_deref()
+        .unwrap_or(&LocalExecution as &dyn ExecutionBackend);
+    let log = start_log(cwd, &node.id);
+    let log_path = log.as_deref().map(|path| normalize_output_path(path, cwd));
+    let job = ScriptJob {
+        node,
+        command: &script.command,
+        cwd,
+        hash,
+        timeout,
+        sandbox: &sandbox,
+        log: log.as_deref(),
+        source_date_epoch: options.source_date_epoch,
+    };
+
     let start = Instant::now();
-    let output = match run_script(&script.command, cwd) {
+    let output = match executor.execute(&job) {
         Ok(out) => out,
         Err(e) => {
             let duration_ms = start.elapsed().as_millis() as u64;
+            let message = if executor.name() == "local" {
+                format!("Failed to spawn: {e}")
+            } else {
+                format!("{} execution failed: {e}", executor.name())
+            };
             return BuildNodeResult::failed(
                 &node.id,
                 hash,
                 duration_ms,
-                BuildErrorInfo::new(codes::BUILD_SCRIPT_FAILED, format!("Failed to spawn: {e}")),
+                BuildErrorInfo::new(codes::BUILD_SCRIPT_FAILED, message),
             );
         }
     };
     let duration_ms = start.elapsed().as_millis() as u64;
 
     if output.exit_code != 0 {
-        let error = BuildErrorInfo::new(
-            codes::BUILD_SCRIPT_FAILED,
-            format!("Exit code {}", output.exit_code),
-        )
+        let error = if output.timed_out {
+            BuildErrorInfo::new(
+                codes::BUILD_SCRIPT_TIMEOUT,
+                format!("Timed out after {}ms", timeout.map_or(0, |t| t.as_millis())),
+            )
+        } else if let Some(signal) = output.signal {
+            BuildErrorInfo::new(
+                codes::BUILD_SCRIPT_FAILED,
+                format!("Terminated by signal {signal}"),
+            )
+        } else {
+            BuildErrorInfo::new(
+                codes::BUILD_SCRIPT_FAILED,
+                format!("Exit code {}", output.exit_code),
+            )
+        }
         .with_detail(if output.stderr.is_empty() {
             String::new()
         } else {
@@ -427,6 +917,8 @@ pub fn execute_node(
         });
 
         let mut result = BuildNodeResult::failed(&node.id, hash, duration_ms, error);
+        result.notes.extend(output.notes);
+        result.log_path = log_path;
         result.stdout_truncated = output.stdout_truncated;
         result.stderr_truncated = output.stderr_truncated;
         result.cache = if options.force {
@@ -452,6 +944,8 @@ pub fn execute_node(
 
     let mut result =
         BuildNodeResult::cache_miss_with_reason(&node.id, hash, duration_ms, rebuild_reason);
+    result.notes.extend(output.notes);
+    result.log_path = log_path;
     result.stdout_truncated = output.stdout_truncated;
     result.stderr_truncated = output.stderr_truncated;
     result.cache = if options.force {
@@ -596,7 +1090,7 @@ pub fn execute_transpile(
         match spec.sourcemaps {
             crate::compiler::SourceMapKind::Inline => {
                 // Append inline source map as data URL
-                let encoded = base64_encode(map.as_bytes());
+                let encoded = crate::sourcemap::base64_encode(map.as_bytes());
                 format!(
                     "{}\n//# sourceMappingURL=data:application/json;base64,{}",
                     output.code, encoded
@@ -605,7 +1099,7 @@ pub fn execute_transpile(
             crate::compiler::SourceMapKind::External => {
                 // Write external source map file
                 let map_path = output_path.with_extension("js.map");
-                if let Err(e) = fs::write(&map_path, map) {
+                if let Err(e) = atomic_write(&map_path, map.as_bytes()) {
                     let duration_ms = start.elapsed().as_millis() as u64;
                     return BuildNodeResult::failed(
                         &node.id,
@@ -630,7 +1124,7 @@ pub fn execute_transpile(
         output.code.clone()
     };
 
-    if let Err(e) = fs::write(&output_path, &code_with_sourcemap) {
+    if let Err(e) = atomic_write(&output_path, code_with_sourcemap.as_bytes()) {
         let duration_ms = start.elapsed().as_millis() as u64;
         return BuildNodeResult::failed(
             &node.id,
@@ -673,16 +1167,69 @@ pub fn execute_transpile(
 ///
 /// Prefers local `node_modules/.bin/tsc` if present, otherwise uses `npx --no-install tsc`.
 /// This reduces variance from npx resolution and avoids surprise network calls.
+/// `--pretty false` keeps the output in the one-line format parsed into diagnostics.
 fn resolve_tsc_command(cwd: &Path) -> String {
+    format!("{} --noEmit --pretty false", resolve_tsc(cwd))
+}
+
+/// Resolve the tsc executable: local `node_modules/.bin/tsc`, else `npx --no-install tsc`.
+fn resolve_tsc(cwd: &Path) -> String {
     let local_tsc = cwd.join("node_modules/.bin/tsc");
     if local_tsc.exists() {
-        format!("{} --noEmit", local_tsc.to_string_lossy())
+        crate::scripts::shell_quote(&local_tsc.to_string_lossy())
     } else {
         // Use npx --no-install to fail fast if tsc not installed
-        "npx --no-install tsc --noEmit".to_string()
+        "npx --no-install tsc".to_string()
     }
 }
 
+/// Build the tsc command line for a dts node.
+///
+/// Declarations are emitted relative to the project root (`--rootDir .`), so
+/// `src/index.ts` becomes `<out_dir>/src/index.d.ts`. Without a tsconfig the
+/// entries are compiled directly with bundler-style module resolution.
+fn resolve_dts_command(cwd: &Path, spec: &DtsSpec) -> String {
+    let mut args: Vec<&str> = Vec::new();
+    if let Some(tsconfig) = &spec.tsconfig {
+        args.extend(["-p", tsconfig.as_str()]);
+    } else {
+        args.extend(spec.entries.iter().map(String::as_str));
+        args.extend([
+            "--allowJs",
+            "--skipLibCheck",
+            "--module",
+            "esnext",
+            "--moduleResolution",
+            "bundler",
+            "--target",
+            "es2022",
+            "--jsx",
+            "react-jsx",
+        ]);
+    }
+    args.extend([
+        "--declaration",
+        "--emitDeclarationOnly",
+        "--noEmit",
+        "false",
+        "--declarationMap",
+        "false",
+        "--rootDir",
+        ".",
+        "--outDir",
+        spec.out_dir.as_str(),
+        "--pretty",
+        "false",
+    ]);
+
+    let mut command = resolve_tsc(cwd);
+    for arg in args {
+        command.push(' ');
+        command.push_str(&crate::scripts::shell_quote(arg));
+    }
+    command
+}
+
 /// Execute a typecheck node using `tsc --noEmit`.
 ///
 /// Typecheck nodes are validation-only (no outputs). They run TypeScript's
@@ -732,8 +1279,10 @@ pub fn execute_typecheck(
     // Resolve the tsc command (prefer local, fallback to npx --no-install)
     let command_str = resolve_tsc_command(cwd);
 
+    let log = start_log(cwd, &node.id);
+    let log_path = log.as_deref().map(|path| normalize_output_path(path, cwd));
     let start = Instant::now();
-    let output = match run_script(&command_str, cwd) {
+    let output = match run_logged(&command_str, cwd, log.as_deref()) {
         Ok(out) => out,
         Err(e) => {
             let duration_ms = start.elapsed().as_millis() as u64;
@@ -751,41 +1300,217 @@ pub fn execute_typecheck(
     let duration_ms = start.elapsed().as_millis() as u64;
 
     if output.exit_code != 0 {
-        let error = BuildErrorInfo::new(
-            codes::BUILD_TYPECHECK_FAILED,
-            format!("Type errors found (exit code {})", output.exit_code),
-        )
-        .with_detail(if output.stdout.is_empty() && output.stderr.is_empty() {
-            String::new()
+        // tsc outputs type errors to stdout, not stderr
+        let diagnostics = parse_tsc_output(&output.stdout);
+        let error_count = diagnostics
+            .iter()
+            .filter(|d| d.severity == DiagnosticSeverity::Error)
+            .count();
+        let message = if error_count > 0 {
+            format!(
+                "{error_count} type error{} found",
+                if error_count == 1 { "" } else { "s" }
+            )
         } else {
-            // tsc outputs type errors to stdout, not stderr
-            let combined = if !output.stdout.is_empty() {
-                output.stdout.clone()
+            format!("Type errors found (exit code {})", output.exit_code)
+        };
+        let error = BuildErrorInfo::new(codes::BUILD_TYPECHECK_FAILED, message).with_detail(
+            if output.stdout.is_empty() && output.stderr.is_empty() {
+                String::new()
             } else {
-                output.stderr.clone()
-            };
-            // Get last 30 lines
-            combined
-                .lines()
-                .rev()
-                .take(30)
-                .collect::<Vec<_>>()
-                .into_iter()
-                .rev()
-                .collect::<Vec<_>>()
-                .join("\n")
-        });
+                // tsc outputs type errors to stdout, not stderr
+                let combined = if !output.stdout.is_empty() {
+                    output.stdout.clone()
+                } else {
+                    output.stderr.clone()
+                };
+                // Get last 30 lines
+                combined
+                    .lines()
+                    .rev()
+                    .take(30)
+                    .collect::<Vec<_>>()
+                    .into_iter()
+                    .rev()
+                    .collect::<Vec<_>>()
+                    .join("\n")
+            },
+        );
+
+        let mut result = BuildNodeResult::failed(&node.id, hash, duration_ms, error);
+        result.log_path = log_path;
+        result.stdout_truncated = output.stdout_truncated;
+        result.stderr_truncated = output.stderr_truncated;
+        result.diagnostics = diagnostics;
+        result.cache = if options.force {
+            CacheStatus::Bypass
+        } else {
+            CacheStatus::Miss
+        };
+
+        // Update cache with failure
+        if let Some(cache) = cache {
+            cache.set(&node.id, hash, false);
+        }
+
+        return result;
+    }
+
+    // Success - no fingerprint needed since typecheck has no outputs
+    let mut result =
+        BuildNodeResult::cache_miss_with_reason(&node.id, hash, duration_ms, rebuild_reason);
+    result.log_path = log_path;
+    result.stdout_truncated = output.stdout_truncated;
+    result.stderr_truncated = output.stderr_truncated;
+    result.cache = if options.force {
+        CacheStatus::Bypass
+    } else {
+        CacheStatus::Miss
+    };
+
+    // Update cache (no fingerprint)
+    if let Some(cache) = cache {
+        cache.set(&node.id, hash, true);
+    }
+
+    result
+}
+
+/// Execute a dts node using `tsc --emitDeclarationOnly`.
+///
+/// The declaration directory is the node's output: a cache hit requires both
+/// the input hash and the output fingerprint to match, so unchanged sources
+/// skip regeneration. The directory is cleared before each run so
+/// declarations of deleted sources don't linger.
+///
+/// Returns a `BuildNodeResult` with success/failure status.
+#[must_use]
+#[allow(clippy::cast_possible_truncation)]
+pub fn execute_dts(
+    node: &BuildNode,
+    cwd: &Path,
+    hash: &str,
+    cache: Option<&mut dyn BuildCache>,
+    options: &ExecOptions,
+) -> BuildNodeResult {
+    let Some(spec) = &node.dts else {
+        return BuildNodeResult::failed(
+            &node.id,
+            hash,
+            0,
+            BuildErrorInfo::new(
+                codes::BUILD_DTS_FAILED,
+                "Dts node missing dts specification",
+            ),
+        );
+    };
+
+    // Track reason for rebuild
+    let mut rebuild_reason = BuildNodeReason::FirstBuild;
+
+    // Check cache unless force
+    if options.force {
+        rebuild_reason = BuildNodeReason::Forced;
+    } else if let Some(cache) = cache.as_ref() {
+        if let Some(entry) = cache.get_entry(&node.id, hash) {
+            if entry.ok {
+                let current_fingerprint = compute_fingerprint(&node.outputs, cwd).ok();
+                let fingerprint_matches = match (&current_fingerprint, &entry.fingerprint) {
+                    (Some(Some(current)), Some(cached)) => current.hash == cached.hash,
+                    _ => false,
+                };
+                if fingerprint_matches {
+                    return BuildNodeResult::cache_hit(&node.id, hash);
+                }
+                rebuild_reason = BuildNodeReason::OutputsChanged;
+            }
+        }
+    }
+
+    // Dry run - don't execute
+    if options.dry_run {
+        let mut result = BuildNodeResult::cache_miss_with_reason(&node.id, hash, 0, rebuild_reason);
+        result.cache = if options.force {
+            CacheStatus::Bypass
+        } else {
+            CacheStatus::Miss
+        };
+        result.notes.push("dry run - not executed".to_string());
+        return result;
+    }
+
+    let start = Instant::now();
+    let out_dir = cwd.join(&spec.out_dir);
+    if out_dir.exists() {
+        if let Err(e) = remove_within(cwd, &out_dir) {
+            return BuildNodeResult::failed(
+                &node.id,
+                hash,
+                start.elapsed().as_millis() as u64,
+                BuildErrorInfo::new(
+                    codes::BUILD_CLEAN_FAILED,
+                    format!("Failed to clear {}: {e}", out_dir.display()),
+                ),
+            );
+        }
+    }
+
+    let command_str = resolve_dts_command(cwd, spec);
+    let log = start_log(cwd, &node.id);
+    let log_path = log.as_deref().map(|path| normalize_output_path(path, cwd));
+    let output = match run_logged(&command_str, cwd, log.as_deref()) {
+        Ok(out) => out,
+        Err(e) => {
+            let duration_ms = start.elapsed().as_millis() as u64;
+            return BuildNodeResult::failed(
+                &node.id,
+                hash,
+                duration_ms,
+                BuildErrorInfo::new(codes::BUILD_DTS_FAILED, format!("Failed to spawn: {e}")),
+            );
+        }
+    };
+    let duration_ms = start.elapsed().as_millis() as u64;
+
+    let result_cache = if options.force {
+        CacheStatus::Bypass
+    } else {
+        CacheStatus::Miss
+    };
+
+    if output.exit_code != 0 {
+        // tsc outputs errors to stdout, not stderr
+        let diagnostics = parse_tsc_output(&output.stdout);
+        let combined = if !output.stdout.is_empty() {
+            &output.stdout
+        } else {
+            &output.stderr
+        };
+        let detail = combined
+            .lines()
+            .rev()
+            .take(30)
+            .collect::<Vec<_>>()
+            .into_iter()
+            .rev()
+            .collect::<Vec<_>>()
+            .join("\n");
+        let error = BuildErrorInfo::new(
+            codes::BUILD_DTS_FAILED,
+            format!(
+                "Declaration generation failed (exit code {})",
+                output.exit_code
+            ),
+        )
+        .with_detail(detail);
 
         let mut result = BuildNodeResult::failed(&node.id, hash, duration_ms, error);
+        result.log_path = log_path;
         result.stdout_truncated = output.stdout_truncated;
         result.stderr_truncated = output.stderr_truncated;
-        result.cache = if options.force {
-            CacheStatus::Bypass
-        } else {
-            CacheStatus::Miss
-        };
+        result.diagnostics = diagnostics;
+        result.cache = result_cache;
 
-        // Update cache with failure
         if let Some(cache) = cache {
             cache.set(&node.id, hash, false);
         }
@@ -793,20 +1518,16 @@ pub fn execute_typecheck(
         return result;
     }
 
-    // Success - no fingerprint needed since typecheck has no outputs
+    let fingerprint = compute_fingerprint(&node.outputs, cwd).ok().flatten();
     let mut result =
         BuildNodeResult::cache_miss_with_reason(&node.id, hash, duration_ms, rebuild_reason);
+    result.log_path = log_path;
     result.stdout_truncated = output.stdout_truncated;
     result.stderr_truncated = output.stderr_truncated;
-    result.cache = if options.force {
-        CacheStatus::Bypass
-    } else {
-        CacheStatus::Miss
-    };
+    result.cache = result_cache;
 
-    // Update cache (no fingerprint)
     if let Some(cache) = cache {
-        cache.set(&node.id, hash, true);
+        cache.set_with_fingerprint(&node.id, hash, true, fingerprint);
     }
 
     result
@@ -819,9 +1540,13 @@ const TRANSPILABLE_EXTENSIONS: &[&str] = &["ts", "tsx", "js", "jsx", "mts", "cts
 ///
 /// This function:
 /// 1. Scans the input directory for matching files (*.ts, *.tsx, *.js, *.jsx)
-/// 2. Transpiles each file using the provided compiler backend
+/// 2. Transpiles each file in parallel using the provided compiler backend,
+///    skipping files whose content hash matches the previous run
 /// 3. Writes output files to the output directory, preserving structure
 ///
+/// `files_count` reports every file in the batch and `changed_count` the
+/// files that were re-emitted.
+///
 /// v3.5: Uses lazy fingerprinting - skips fingerprint on first build.
 ///
 /// Returns a `BuildNodeResult` with aggregate success/failure status.
@@ -940,106 +1665,61 @@ pub fn execute_transpile_batch(
         let mut result =
             BuildNodeResult::cache_miss_with_reason(&node.id, hash, duration_ms, rebuild_reason);
         result.files_count = Some(0);
+        result.changed_count = Some(0);
         result.notes.push("no files to transpile".to_string());
+        let pruned = record_emitted_outputs(node, cwd, &output_dir, &EmittedOutputs::new(&[], cwd));
+        if pruned > 0 {
+            result
+                .notes
+                .push(format!("pruned {pruned} stale output(s)"));
+        }
         if let Some(cache) = cache {
             cache.set_with_fingerprint(&node.id, hash, true, None);
         }
         return result;
     }
 
-    let mut transpiled_count = 0;
-    let mut errors = Vec::new();
-
-    for file_path in &files {
-        // Compute relative path from input_dir
-        let rel_path = file_path.strip_prefix(&input_dir).unwrap_or(file_path);
-
-        // Compute output path: change extension to .js
-        let output_rel = rel_path.with_extension("js");
-        let output_path = output_dir.join(&output_rel);
-
-        // Create parent directories for output
-        if let Some(parent) = output_path.parent() {
-            if !parent.exists() {
-                if let Err(e) = fs::create_dir_all(parent) {
-                    errors.push(format!(
-                        "{}: failed to create directory: {e}",
-                        rel_path.display()
-                    ));
-                    continue;
-                }
-            }
-        }
-
-        // Read input file
-        let source = match fs::read_to_string(file_path) {
-            Ok(s) => s,
-            Err(e) => {
-                errors.push(format!("{}: failed to read: {e}", rel_path.display()));
-                continue;
-            }
-        };
+    // Per-file hashes from the previous run let unchanged files be skipped.
+    // They are ignored when forced or when options/toolchain changed.
+    let options_key = transpile_options_key(spec);
+    let previous = if options.force {
+        None
+    } else {
+        EmittedOutputs::load(cwd, &node.id)
+    };
 
-        // Create a file-specific spec for transpilation
-        let file_spec = TranspileSpec::new(
-            file_path.to_string_lossy().to_string(),
-            output_path.to_string_lossy().to_string(),
-        )
-        .with_jsx_runtime(spec.jsx_runtime)
-        .with_module(spec.module)
-        .with_sourcemaps(spec.sourcemaps)
-        .with_target(spec.target)
-        .with_minify(spec.minify);
-
-        // Transpile
-        let output = match backend.transpile(&file_spec, &source) {
-            Ok(out) => out,
-            Err(e) => {
-                errors.push(format!("{}: {e}", rel_path.display()));
-                continue;
-            }
-        };
+    let outcomes: Vec<Result<BatchFileOutcome, String>> = files
+        .par_iter()
+        .map(|file_path| {
+            transpile_batch_file(
+                file_path,
+                &input_dir,
+                &output_dir,
+                cwd,
+                spec,
+                backend,
+                previous.as_ref(),
+                &options_key,
+            )
+        })
+        .collect();
 
-        // Write output with source map handling
-        let code_with_sourcemap = if let Some(ref map) = output.source_map {
-            match spec.sourcemaps {
-                crate::compiler::SourceMapKind::Inline => {
-                    let encoded = base64_encode(map.as_bytes());
-                    format!(
-                        "{}\n//# sourceMappingURL=data:application/json;base64,{}",
-                        output.code, encoded
-                    )
-                }
-                crate::compiler::SourceMapKind::External => {
-                    let map_path = output_path.with_extension("js.map");
-                    if let Err(e) = fs::write(&map_path, map) {
-                        errors.push(format!(
-                            "{}: failed to write source map: {e}",
-                            rel_path.display()
-                        ));
-                        continue;
-                    }
-                    let map_filename = map_path
-                        .file_name()
-                        .and_then(|n| n.to_str())
-                        .unwrap_or("output.js.map");
-                    format!("{}\n//# sourceMappingURL={}", output.code, map_filename)
+    let mut changed_count = 0;
+    let mut errors = Vec::new();
+    let mut emitted: Vec<PathBuf> = Vec::with_capacity(files.len());
+    let mut sources = BTreeMap::new();
+
+    for outcome in outcomes {
+        match outcome {
+            Ok(outcome) => {
+                if outcome.changed {
+                    changed_count += 1;
                 }
-                crate::compiler::SourceMapKind::None => output.code.clone(),
+                emitted.extend(outcome.outputs);
+                sources.insert(outcome.source, outcome.source_hash);
             }
-        } else {
-            output.code.clone()
-        };
-
-        if let Err(e) = fs::write(&output_path, &code_with_sourcemap) {
-            errors.push(format!(
-                "{}: failed to write output: {e}",
-                rel_path.display()
-            ));
-            continue;
+            Err(e) => errors.push(e),
         }
-
-        transpiled_count += 1;
     }
 
     let duration_ms = start.elapsed().as_millis() as u64;
@@ -1064,6 +1744,10 @@ pub fn execute_transpile_batch(
         );
     }
 
+    // Remove outputs emitted by the previous run whose sources are gone
+    let current = EmittedOutputs::new(&emitted, cwd).with_sources(sources, options_key);
+    let pruned = record_emitted_outputs(node, cwd, &output_dir, &current);
+
     // Compute output fingerprint
     // v3.5: Skip fingerprint on first build (lazy fingerprinting)
     let fingerprint = if has_outputs && rebuild_reason != BuildNodeReason::FirstBuild {
@@ -1080,7 +1764,13 @@ pub fn execute_transpile_batch(
         CacheStatus::Miss
     };
     // Set structured file count (v3.1.2)
-    result.files_count = Some(transpiled_count as u32);
+    result.files_count = Some(files.len() as u32);
+    result.changed_count = Some(changed_count);
+    if pruned > 0 {
+        result
+            .notes
+            .push(format!("pruned {pruned} stale output(s)"));
+    }
 
     // Update cache with fingerprint
     if let Some(cache) = cache {
@@ -1090,34 +1780,154 @@ pub fn execute_transpile_batch(
     result
 }
 
-/// Simple base64 encoding for source maps.
-fn base64_encode(data: &[u8]) -> String {
-    const ALPHABET: &[u8] = b"ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789+/";
-    let mut result = String::with_capacity((data.len() + 2) / 3 * 4);
+/// Result of handling a single file inside a batch transpile.
+struct BatchFileOutcome {
+    /// Source path relative to the project root.
+    source: String,
+    /// Content hash of the source.
+    source_hash: String,
+    /// Output files owned by this source.
+    outputs: Vec<PathBuf>,
+    /// Whether the outputs were (re-)emitted in this run.
+    changed: bool,
+}
 
-    for chunk in data.chunks(3) {
-        let mut buffer = [0u8; 3];
-        buffer[..chunk.len()].copy_from_slice(chunk);
+/// Hash of the options that affect batch transpile output, including the
+/// toolchain, so per-file hashes are invalidated when either changes.
+fn transpile_options_key(spec: &TranspileSpec) -> String {
+    hash_string(&format!(
+        "{}|{:?}|{:?}|{:?}|{:?}|{}|{:?}|{}",
+        toolchain_fingerprint(),
+        spec.jsx_runtime,
+        spec.module,
+        spec.sourcemaps,
+        spec.target,
+        spec.minify,
+        spec.decorators,
+        spec.react_refresh
+    ))
+}
 
-        let n = u32::from(buffer[0]) << 16 | u32::from(buffer[1]) << 8 | u32::from(buffer[2]);
+/// Transpile one file of a batch, skipping it when its content hash matches
+/// the previous run and its outputs are still on disk.
+///
+/// Errors are returned as `"<relative path>: <message>"`.
+#[allow(clippy::too_many_arguments)]
+fn transpile_batch_file(
+    file_path: &Path,
+    input_dir: &Path,
+    output_dir: &Path,
+    cwd: &Path,
+    spec: &TranspileSpec,
+    backend: &dyn CompilerBackend,
+    previous: Option<&EmittedOutputs>,
+    options_key: &str,
+) -> Result<BatchFileOutcome, String> {
+    // Compute relative path from input_dir
+    let rel_path = file_path.strip_prefix(input_dir).unwrap_or(file_path);
 
-        result.push(ALPHABET[(n >> 18 & 0x3F) as usize] as char);
-        result.push(ALPHABET[(n >> 12 & 0x3F) as usize] as char);
+    // Compute output path: change extension to .js
+    let output_path = output_dir.join(rel_path.with_extension("js"));
+    let map_path = output_path.with_extension("js.map");
+    let writes_map = spec.sourcemaps == crate::compiler::SourceMapKind::External;
 
-        if chunk.len() > 1 {
-            result.push(ALPHABET[(n >> 6 & 0x3F) as usize] as char);
-        } else {
-            result.push('=');
-        }
+    // Read input file
+    let source = fs::read_to_string(file_path)
+        .map_err(|e| format!("{}: failed to read: {e}", rel_path.display()))?;
+    let source_key = normalize_output_path(file_path, cwd);
+    let source_hash = hash_bytes(source.as_bytes());
+
+    let mut outputs = vec![output_path.clone()];
+    if writes_map {
+        outputs.push(map_path.clone());
+    }
+
+    let unchanged = previous
+        .and_then(|previous| previous.source_hash(&source_key, options_key))
+        .is_some_and(|stored| stored == source_hash)
+        && outputs.iter().all(|path| path.is_file());
+    if unchanged {
+        return Ok(BatchFileOutcome {
+            source: source_key,
+            source_hash,
+            outputs,
+            changed: false,
+        });
+    }
 
-        if chunk.len() > 2 {
-            result.push(ALPHABET[(n & 0x3F) as usize] as char);
-        } else {
-            result.push('=');
+    // Create parent directories for output
+    if let Some(parent) = output_path.parent() {
+        fs::create_dir_all(parent)
+            .map_err(|e| format!("{}: failed to create directory: {e}", rel_path.display()))?;
+    }
+
+    // Create a file-specific spec for transpilation
+    let file_spec = TranspileSpec::new(
+        file_path.to_string_lossy().to_string(),
+        output_path.to_string_lossy().to_string(),
+    )
+    .with_jsx_runtime(spec.jsx_runtime)
+    .with_module(spec.module)
+    .with_sourcemaps(spec.sourcemaps)
+    .with_target(spec.target)
+    .with_minify(spec.minify)
+    .with_decorators(spec.decorators)
+    .with_react_refresh(spec.react_refresh);
+
+    let output = backend
+        .transpile(&file_spec, &source)
+        .map_err(|e| format!("{}: {e}", rel_path.display()))?;
+
+    // Write output with source map handling
+    let code_with_sourcemap = match (&output.source_map, spec.sourcemaps) {
+        (Some(map), crate::compiler::SourceMapKind::Inline) => {
+            let encoded = crate::sourcemap::base64_encode(map.as_bytes());
+            format!(
+                "{}\n//# sourceMappingURL=data:application/json;base64,{}",
+                output.code, encoded
+            )
         }
-    }
+        (Some(map), crate::compiler::SourceMapKind::External) => {
+            atomic_write(&map_path, map.as_bytes())
+                .map_err(|e| format!("{}: failed to write source map: {e}", rel_path.display()))?;
+            let map_filename = map_path
+                .file_name()
+                .and_then(|n| n.to_str())
+                .unwrap_or("output.js.map");
+            format!("{}\n//# sourceMappingURL={}", output.code, map_filename)
+        }
+        _ => output.code,
+    };
 
-    result
+    atomic_write(&output_path, code_with_sourcemap.as_bytes())
+        .map_err(|e| format!("{}: failed to write output: {e}", rel_path.display()))?;
+
+    // The backend may omit the map; only claim the files actually written.
+    outputs.retain(|path| path.is_file());
+    Ok(BatchFileOutcome {
+        source: source_key,
+        source_hash,
+        outputs,
+        changed: true,
+    })
+}
+
+/// Record the files emitted by a batch transpile run and prune the ones
+/// emitted by the previous run that were not emitted this time.
+///
+/// Returns the number of pruned files. Manifest write failures are ignored;
+/// they only mean the next run can't prune.
+fn record_emitted_outputs(
+    node: &BuildNode,
+    cwd: &Path,
+    output_dir: &Path,
+    current: &EmittedOutputs,
+) -> usize {
+    let pruned = EmittedOutputs::load(cwd, &node.id)
+        .map(|previous| prune_stale_outputs(cwd, output_dir, &previous, current).len())
+        .unwrap_or(0);
+    let _ = current.save(cwd, &node.id);
+    pruned
 }
 
 /// Execute a build graph.
@@ -1167,13 +1977,43 @@ pub fn execute_graph_with_backend(
 ///
 /// # Errors
 /// Returns an error if hash computation fails.
-#[allow(clippy::cast_possible_truncation)]
 pub fn execute_graph_with_file_cache(
+    graph: &BuildGraph,
+    cache: Option<&mut dyn BuildCache>,
+    options: &ExecOptions,
+    backend: Option<&dyn CompilerBackend>,
+    file_cache: Option<&dyn super::hash::FileHashCache>,
+) -> super::hash::HashResult<BuildRunResult> {
+    execute_graph_with_progress(graph, cache, options, backend, file_cache, &mut |_| {})
+}
+
+/// Progress of a running build, reported as nodes start and finish.
+#[derive(Debug, Clone, Copy)]
+pub struct BuildProgress<'a> {
+    /// The node.
+    pub node_id: &'a str,
+    /// `None` when the node starts, its result once it finished or was skipped.
+    pub result: Option<&'a BuildNodeResult>,
+    /// Nodes finished so far.
+    pub completed: u32,
+    /// Nodes this run will process.
+    pub total: u32,
+}
+
+/// Execute a build graph, calling `on_progress` as each node starts and finishes.
+///
+/// See [`execute_graph_with_file_cache`] for target filtering.
+///
+/// # Errors
+/// Returns an error if hash computation fails.
+#[allow(clippy::cast_possible_truncation)]
+pub fn execute_graph_with_progress(
     graph: &BuildGraph,
     mut cache: Option<&mut dyn BuildCache>,
     options: &ExecOptions,
     backend: Option<&dyn CompilerBackend>,
     file_cache: Option<&dyn super::hash::FileHashCache>,
+    on_progress: &mut dyn FnMut(BuildProgress<'_>),
 ) -> super::hash::HashResult<BuildRunResult> {
     let cwd = Path::new(&graph.cwd);
     let mut result = BuildRunResult::new(&graph.cwd);
@@ -1182,7 +2022,8 @@ pub fn execute_graph_with_file_cache(
     let hash_ctx = match file_cache {
         Some(fc) => super::hash::HashContext::with_cache(fc),
         None => super::hash::HashContext::empty(),
-    };
+    }
+    .with_source_date_epoch(options.source_date_epoch);
     let hashes = super::hash::hash_graph_with_ctx(graph, &hash_ctx)?;
 
     // Get execution order
@@ -1196,6 +2037,13 @@ pub fn execute_graph_with_file_cache(
     // Track which nodes succeeded
     let mut succeeded: HashMap<&str, bool> = HashMap::new();
 
+    let total = if filter_by_targets {
+        order.iter().filter(|id| target_set.contains(*id)).count()
+    } else {
+        order.len()
+    } as u32;
+    let mut completed = 0;
+
     // Execute nodes in order
     // Note: For v2.0, we execute sequentially. Parallel execution can be added later.
     for node_id in order {
@@ -1221,12 +2069,43 @@ pub fn execute_graph_with_file_cache(
             // Skip this node - dependency failed
             let skipped = BuildNodeResult::skipped(node_id);
             succeeded.insert(node_id, false);
+            completed += 1;
+            on_progress(BuildProgress {
+                node_id,
+                result: Some(&skipped),
+                completed,
+                total,
+            });
             result.add_result(skipped);
             continue;
         }
 
+        on_progress(BuildProgress {
+            node_id,
+            result: None,
+            completed,
+            total,
+        });
+
+        let span = tracing::info_span!(
+            "build.node",
+            node = node_id,
+            kind = node.kind.as_str(),
+            cache = tracing::field::Empty,
+            ok = tracing::field::Empty,
+        );
+        let _span = span.enter();
+
+        // Tool versions the cached entry was built with (before it is replaced)
+        let tools = hash_ctx.tools.node_versions(node, cwd);
+        let previous_tools = if tools.is_empty() {
+            None
+        } else {
+            cache.as_ref().and_then(|c| c.tool_versions(node_id))
+        };
+
         // Execute the node based on its kind
-        let node_result = match node.kind {
+        let mut node_result = match node.kind {
             BuildNodeKind::Transpile => {
                 // Transpile nodes require a backend and spec
                 if let (Some(backend), Some(spec)) = (backend, &node.transpile) {
@@ -1280,6 +2159,13 @@ pub fn execute_graph_with_file_cache(
                     execute_typecheck(node, cwd, hash, None, options)
                 }
             }
+            BuildNodeKind::Dts => {
+                if let Some(ref mut c) = cache {
+                    execute_dts(node, cwd, hash, Some(*c), options)
+                } else {
+                    execute_dts(node, cwd, hash, None, options)
+                }
+            }
             // Script and other node types use the regular execute_node
             _ => {
                 if let Some(ref mut c) = cache {
@@ -1290,7 +2176,37 @@ pub fn execute_graph_with_file_cache(
             }
         };
 
+        if !tools.is_empty() {
+            // Explain rebuilds caused by a tool upgrade (shown by --why)
+            if node_result.cache != CacheStatus::Hit {
+                let changes = previous_tools
+                    .as_ref()
+                    .map(|previous| version_changes(previous, &tools))
+                    .unwrap_or_default();
+                if !changes.is_empty() {
+                    if node_result.reason == Some(BuildNodeReason::FirstBuild) {
+                        node_result.reason = Some(BuildNodeReason::InputChanged);
+                    }
+                    node_result.notes.extend(changes);
+                }
+            }
+            if node_result.ok && !options.dry_run && previous_tools.as_ref() != Some(&tools) {
+                if let Some(ref mut c) = cache {
+                    c.set_tool_versions(node_id, tools);
+                }
+            }
+        }
+
+        span.record("cache", node_result.cache.as_str());
+        span.record("ok", node_result.ok);
         succeeded.insert(node_id, node_result.ok);
+        completed += 1;
+        on_progress(BuildProgress {
+            node_id,
+            result: Some(&node_result),
+            completed,
+            total,
+        });
         result.add_result(node_result);
     }
 
@@ -1322,6 +2238,133 @@ mod tests {
         assert!(cache.get("node1", "hash1").is_none());
     }
 
+    #[test]
+    fn test_persistent_build_cache_round_trip() {
+        let dir = tempdir().unwrap();
+
+        let mut cache = PersistentBuildCache::open(dir.path());
+        cache.set("dts:dist/types", "hash1", true);
+        cache.save().unwrap();
+
+        let cache = PersistentBuildCache::open(dir.path());
+        assert_eq!(cache.get("dts:dist/types", "hash1"), Some(true));
+        assert!(cache.get("dts:dist/types", "hash2").is_none());
+    }
+
+    #[test]
+    fn test_persistent_build_cache_rejects_tampered_entries() {
+        use fastnode_util::sign::SigningKey;
+
+        let dir = tempdir().unwrap();
+        let key = SigningKey::generate().unwrap();
+        let mut cache = PersistentBuildCache::open_with_signing(
+            dir.path(),
+            CacheSigning::new(Some(key.clone()), Vec::new()),
+        );
+        cache.set("script:build", "hash1", true);
+        cache.set("script:test", "hash2", true);
+        cache.save().unwrap();
+
+        // A consumer trusting the key sees both entries
+        let trusting = || CacheSigning::new(None, vec![key.public_key()]);
+        let cache = PersistentBuildCache::open_with_signing(dir.path(), trusting());
+        assert_eq!(cache.rejected(), []);
+        assert_eq!(cache.get("script:build", "hash1"), Some(true));
+
+        // Tamper with one entry in the shared file
+        let path = dir.path().join(BUILD_CACHE_PATH);
+        let content = fs::read_to_string(&path).unwrap();
+        fs::write(&path, content.replace("hash2", "forged")).unwrap();
+
+        let mut cache = PersistentBuildCache::open_with_signing(dir.path(), trusting());
+        assert_eq!(cache.rejected().len(), 1);
+        assert_eq!(
+            cache.rejected()[0].code,
+            codes::BUILD_CACHE_SIGNATURE_INVALID
+        );
+        assert!(cache.rejected()[0].message.contains("script:test"));
+        assert!(cache.get("script:test", "forged").is_none());
+        assert_eq!(cache.get("script:build", "hash1"), Some(true));
+        cache.save().unwrap();
+
+        // Unsigned caches are rejected when verifying, but usable otherwise
+        let unsigned = tempdir().unwrap();
+        let mut cache = PersistentBuildCache::open(unsigned.path());
+        cache.set("script:build", "hash1", true);
+        cache.save().unwrap();
+        let cache = PersistentBuildCache::open_with_signing(unsigned.path(), trusting());
+        assert_eq!(cache.rejected().len(), 1);
+        assert!(cache.get("script:build", "hash1").is_none());
+        let cache = PersistentBuildCache::open(unsigned.path());
+        assert_eq!(cache.get("script:build", "hash1"), Some(true));
+    }
+
+    #[test]
+    fn test_resolve_dts_command() {
+        let dir = tempdir().unwrap();
+        let mut spec = DtsSpec {
+            entries: vec!["src/index.ts".to_string()],
+            out_dir: "dist/types".to_string(),
+            tsconfig: None,
+        };
+
+        let command = resolve_dts_command(dir.path(), &spec);
+        assert!(command.starts_with("npx --no-install tsc src/index.ts --allowJs"));
+        assert!(command.contains("--emitDeclarationOnly"));
+        assert!(command.contains("--outDir dist/types"));
+
+        spec.tsconfig = Some("tsconfig.json".to_string());
+        let command = resolve_dts_command(dir.path(), &spec);
+        assert!(command.contains("tsc -p tsconfig.json --declaration"));
+        assert!(!command.contains("src/index.ts"));
+    }
+
+    #[test]
+    #[cfg(unix)]
+    fn test_execute_dts_caches_declarations() {
+        use std::os::unix::fs::PermissionsExt;
+
+        let dir = tempdir().unwrap();
+        fs::create_dir_all(dir.path().join("src")).unwrap();
+        fs::write(dir.path().join("src/index.ts"), "export const a = 1;\n").unwrap();
+
+        // Fake tsc that writes one declaration into --outDir and counts runs
+        let bin = dir.path().join("node_modules/.bin");
+        fs::create_dir_all(&bin).unwrap();
+        let tsc = bin.join("tsc");
+        fs::write(
+            &tsc,
+            "#!/bin/sh\nwhile [ \"$1\" != \"--outDir\" ]; do shift; done\n\
+             mkdir -p \"$2/src\"\necho 'export declare const a = 1;' > \"$2/src/index.d.ts\"\n\
+             echo run >> runs.log\n",
+        )
+        .unwrap();
+        fs::set_permissions(&tsc, fs::Permissions::from_mode(0o755)).unwrap();
+
+        let node = BuildNode::dts(DtsSpec {
+            entries: vec!["src/index.ts".to_string()],
+            out_dir: "dist/types".to_string(),
+            tsconfig: None,
+        });
+        let mut cache = MemoryCache::new();
+        let options = ExecOptions::new();
+
+        let result = execute_dts(&node, dir.path(), "h1", Some(&mut cache), &options);
+        assert!(result.ok, "{:?}", result.error);
+        assert_eq!(result.cache, CacheStatus::Miss);
+        assert!(dir.path().join("dist/types/src/index.d.ts").exists());
+
+        let result = execute_dts(&node, dir.path(), "h1", Some(&mut cache), &options);
+        assert_eq!(result.cache, CacheStatus::Hit);
+
+        // Deleted outputs are regenerated even though the inputs are unchanged
+        fs::remove_dir_all(dir.path().join("dist/types")).unwrap();
+        let result = execute_dts(&node, dir.path(), "h1", Some(&mut cache), &options);
+        assert_eq!(result.reason, Some(BuildNodeReason::OutputsChanged));
+        let runs = fs::read_to_string(dir.path().join("runs.log")).unwrap();
+        assert_eq!(runs.lines().count(), 2);
+    }
+
     #[test]
     fn test_run_script_success() {
         let dir = tempdir().unwrap();
@@ -1341,6 +2384,83 @@ mod tests {
         assert_ne!(output.exit_code, 0);
     }
 
+    #[test]
+    #[cfg(unix)]
+    fn test_execute_node_timeout() {
+        let dir = tempdir().unwrap();
+        let mut node = BuildNode::script("slow", "sleep 5");
+        node.command.as_mut().unwrap().timeout_ms = Some(200);
+
+        let start = Instant::now();
+        let result = execute_node(&node, dir.path(), "abc", None, &ExecOptions::new());
+
+        assert!(start.elapsed() < Duration::from_secs(4));
+        assert!(!result.ok);
+        assert_eq!(
+            result.error.as_ref().unwrap().code,
+            codes::BUILD_SCRIPT_TIMEOUT
+        );
+    }
+
+    #[test]
+    #[cfg(unix)]
+    fn test_execute_node_writes_full_log() {
+        let dir = tempdir().unwrap();
+        let mut node = BuildNode::script("build", "seq 1 5000; echo boom >&2; exit 1");
+        node.sandbox = Some(BuildSandbox {
+            max_output_bytes: Some(64),
+            ..BuildSandbox::default()
+        });
+
+        let result = execute_node(&node, dir.path(), "abc", None, &ExecOptions::new());
+
+        assert!(!result.ok);
+        let log_path = result.log_path.as_deref().unwrap();
+        assert!(log_path.starts_with(".howth/logs/script_build/"));
+        let log = fs::read_to_string(dir.path().join(log_path)).unwrap();
+        assert!(log.contains("\n5000\n"));
+        assert!(log.contains("boom"));
+        assert_eq!(
+            super::logs::latest_log(dir.path(), &node.id),
+            Some(dir.path().join(log_path))
+        );
+    }
+
+    #[test]
+    #[cfg(unix)]
+    fn test_run_script_sandboxed_restricts_env_and_output() {
+        let dir = tempdir().unwrap();
+        let sandbox = BuildSandbox {
+            restrict_env: true,
+            max_output_bytes: Some(16),
+            ..BuildSandbox::default()
+        };
+
+        let output = run_script_sandboxed(
+            "echo ready; seq 1 10000",
+            dir.path(),
+            None,
+            &sandbox,
+            &["CI".to_string()],
+        )
+        .unwrap();
+
+        assert_eq!(output.exit_code, 0);
+        assert!(output.stdout.starts_with("ready\n1\n"));
+        assert!(output.stdout.len() <= 16);
+        assert!(output.stdout_truncated);
+
+        let parent = |key: &str| match key {
+            "HOME" => Some(OsString::from("/home/ci")),
+            "CI" => Some(OsString::from("true")),
+            "HOWTH_SANDBOX_TEST_SECRET" => Some(OsString::from("leak")),
+            _ => None,
+        };
+        let env = restricted_env(dir.path(), &["CI".to_string()], parent);
+        let keys: Vec<&str> = env.iter().map(|(key, _)| key.as_str()).collect();
+        assert_eq!(keys, ["PATH", "HOME", "CI"]);
+    }
+
     #[test]
     fn test_execute_node_cache_hit() {
         let dir = tempdir().unwrap();
@@ -1413,6 +2533,88 @@ mod tests {
         assert!(result.results[0].ok);
     }
 
+    #[test]
+    fn test_execute_graph_reports_progress() {
+        let dir = tempdir().unwrap();
+        std::fs::write(dir.path().join("package.json"), "{}").unwrap();
+
+        let mut graph = BuildGraph::new(dir.path().to_string_lossy().to_string());
+        graph.add_node(BuildNode::script("lint", "exit 1"));
+        let mut build = BuildNode::script("build", "echo built");
+        build.deps.push("script:lint".to_string());
+        graph.add_node(build);
+        graph.add_default("script:build");
+        graph.normalize();
+
+        let mut events = Vec::new();
+        execute_graph_with_progress(
+            &graph,
+            None,
+            &ExecOptions::new(),
+            None,
+            None,
+            &mut |progress| {
+                events.push((
+                    progress.node_id.to_string(),
+                    progress.result.map(|r| r.cache),
+                    progress.completed,
+                    progress.total,
+                ));
+            },
+        )
+        .unwrap();
+
+        assert_eq!(
+            events,
+            [
+                ("script:lint".to_string(), None, 0, 2),
+                ("script:lint".to_string(), Some(CacheStatus::Miss), 1, 2),
+                ("script:build".to_string(), Some(CacheStatus::Skipped), 2, 2),
+            ]
+        );
+    }
+
+    #[test]
+    #[cfg(unix)]
+    fn test_execute_graph_notes_tool_upgrade() {
+        use std::os::unix::fs::PermissionsExt;
+
+        let dir = tempdir().unwrap();
+        std::fs::write(dir.path().join("package.json"), "{}").unwrap();
+        let bin = dir.path().join("node_modules/.bin");
+        fs::create_dir_all(&bin).unwrap();
+        let tsc = bin.join("tsc");
+        let install_tsc = |version: &str| {
+            fs::write(&tsc, format!("#!/bin/sh\necho 'Version {version}'\n")).unwrap();
+            fs::set_permissions(&tsc, fs::Permissions::from_mode(0o755)).unwrap();
+        };
+        install_tsc("5.4.5");
+
+        let mut graph = BuildGraph::new(dir.path().to_string_lossy().to_string());
+        graph.add_node(BuildNode::script("build", "./node_modules/.bin/tsc"));
+        graph.add_default("script:build");
+        graph.normalize();
+
+        let mut cache = MemoryCache::new();
+        let options = ExecOptions::new();
+        let first = execute_graph(&graph, Some(&mut cache), &options).unwrap();
+        assert_eq!(first.results[0].reason, Some(BuildNodeReason::FirstBuild));
+        assert_eq!(first.results[0].notes, Vec::<String>::new());
+
+        let cached = execute_graph(&graph, Some(&mut cache), &options).unwrap();
+        assert_eq!(cached.results[0].cache, CacheStatus::Hit);
+
+        install_tsc("5.5.0-beta");
+        let upgraded = execute_graph(&graph, Some(&mut cache), &options).unwrap();
+        let result = &upgraded.results[0];
+        assert_eq!(result.cache, CacheStatus::Miss);
+        assert_eq!(result.reason, Some(BuildNodeReason::InputChanged));
+        assert_eq!(
+            result.notes,
+            ["tsc version changed: Version 5.4.5 -> Version 5.5.0-beta"]
+        );
+    }
+
     #[test]
     fn test_execute_graph_dependency_failure_skips() {
         let dir = tempdir().unwrap();
@@ -1940,6 +3142,80 @@ mod tests {
         assert_eq!(result.files_count, Some(2), "files_count should be 2");
     }
 
+    #[test]
+    fn test_execute_transpile_batch_prunes_deleted_sources() {
+        let dir = tempdir().unwrap();
+        std::fs::create_dir(dir.path().join("src")).unwrap();
+        std::fs::write(dir.path().join("src/index.ts"), "export const x = 1;").unwrap();
+        std::fs::write(dir.path().join("src/old.ts"), "export const y = 2;").unwrap();
+
+        let spec = CompilerTranspileSpec::batch("src", "dist");
+        let node = BuildNode::transpile_batch(&spec);
+        let backend = SwcBackend::new();
+        let options = ExecOptions::new();
+
+        let result =
+            execute_transpile_batch(&node, dir.path(), "h1", &spec, &backend, None, &options);
+        assert!(result.ok);
+        assert!(dir.path().join("dist/old.js").exists());
+        assert!(dir.path().join("dist/old.js.map").exists());
+
+        // A file the build never emitted must survive pruning
+        std::fs::write(dir.path().join("dist/README.md"), "keep").unwrap();
+        std::fs::remove_file(dir.path().join("src/old.ts")).unwrap();
+
+        let result =
+            execute_transpile_batch(&node, dir.path(), "h2", &spec, &backend, None, &options);
+        assert!(result.ok);
+        assert!(dir.path().join("dist/index.js").exists());
+        assert!(!dir.path().join("dist/old.js").exists());
+        assert!(!dir.path().join("dist/old.js.map").exists());
+        assert!(dir.path().join("dist/README.md").exists());
+        assert!(result.notes.iter().any(|n| n == "pruned 2 stale output(s)"));
+    }
+
+    #[test]
+    fn test_execute_transpile_batch_only_reemits_changed_files() {
+        let dir = tempdir().unwrap();
+        std::fs::create_dir(dir.path().join("src")).unwrap();
+        std::fs::write(dir.path().join("src/a.ts"), "export const a = 1;").unwrap();
+        std::fs::write(dir.path().join("src/b.ts"), "export const b = 2;").unwrap();
+        std::fs::write(dir.path().join("src/c.ts"), "export const c = 3;").unwrap();
+
+        let spec = CompilerTranspileSpec::batch("src", "dist");
+        let node = BuildNode::transpile_batch(&spec);
+        let backend = SwcBackend::new();
+        let options = ExecOptions::new();
+
+        let result =
+            execute_transpile_batch(&node, dir.path(), "h1", &spec, &backend, None, &options);
+        assert!(result.ok);
+        assert_eq!(result.files_count, Some(3));
+        assert_eq!(result.changed_count, Some(3));
+
+        std::fs::write(dir.path().join("src/b.ts"), "export const b = 20;").unwrap();
+        let result =
+            execute_transpile_batch(&node, dir.path(), "h2", &spec, &backend, None, &options);
+        assert!(result.ok);
+        assert_eq!(result.files_count, Some(3));
+        assert_eq!(result.changed_count, Some(1));
+        let b = std::fs::read_to_string(dir.path().join("dist/b.js")).unwrap();
+        assert!(b.contains("20"));
+
+        // A deleted output is re-emitted even though its source is unchanged
+        std::fs::remove_file(dir.path().join("dist/a.js")).unwrap();
+        let result =
+            execute_transpile_batch(&node, dir.path(), "h3", &spec, &backend, None, &options);
+        assert_eq!(result.changed_count, Some(1));
+        assert!(dir.path().join("dist/a.js").exists());
+
+        // Forcing re-emits everything
+        let forced = ExecOptions::new().with_force(true);
+        let result =
+            execute_transpile_batch(&node, dir.path(), "h3", &spec, &backend, None, &forced);
+        assert_eq!(result.changed_count, Some(3));
+    }
+
     #[test]
     fn test_execute_transpile_batch_nested_directories() {
         let dir = tempdir().unwrap();
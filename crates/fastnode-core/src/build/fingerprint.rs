@@ -33,7 +33,9 @@ pub const FINGERPRINT_SCHEMA_VERSION: u32 = 1;
 pub struct OutputFingerprint {
     /// Schema version for format evolution.
     pub schema_version: u32,
-    /// Blake3 hash of the canonical fingerprint encoding.
+    /// Hash of the canonical fingerprint encoding, tagged with the
+    /// algorithm (`blake3:<hex>`) so fingerprints from another algorithm
+    /// never match.
     pub hash: String,
     /// Number of outputs fingerprinted.
     pub output_count: u32,
@@ -356,7 +358,7 @@ pub fn compute_fingerprint(
         entry.encode(&mut hasher);
     }
 
-    let hash = hasher.finalize().to_hex().to_string();
+    let hash = fastnode_util::hash::tagged(&hasher.finalize().to_hex());
 
     Ok(Some(OutputFingerprint {
         schema_version: FINGERPRINT_SCHEMA_VERSION,
@@ -366,45 +368,16 @@ pub fn compute_fingerprint(
     }))
 }
 
+/// Directories never fingerprinted, at any depth.
+const FINGERPRINT_IGNORES: &[&str] = &["**/node_modules", "**/.git", "**/.howth"];
+
 /// Expand a glob pattern for fingerprinting.
 fn expand_glob_for_fingerprint(pattern: &str, root: &Path) -> Vec<std::path::PathBuf> {
-    use walkdir::WalkDir;
-
-    let mut files = Vec::new();
-
-    for entry in WalkDir::new(root)
-        .follow_links(false)
-        .into_iter()
-        .filter_entry(|e| {
-            // Skip common excluded directories
-            let name = e.file_name().to_string_lossy();
-            !matches!(name.as_ref(), "node_modules" | ".git" | ".howth")
-        })
-        .flatten()
-    {
-        if entry.file_type().is_file() {
-            let path = entry.path();
-            let rel = path.strip_prefix(root).unwrap_or(path);
-            let rel_str = rel.to_string_lossy();
-
-            // Check if matches pattern
-            let matches = if pattern == "**/*" {
-                true
-            } else if let Ok(glob_pattern) = glob::Pattern::new(pattern) {
-                glob_pattern.matches(&rel_str)
-            } else {
-                false
-            };
-
-            if matches {
-                files.push(path.to_path_buf());
-            }
-        }
-    }
-
-    // Sort for determinism
-    files.sort();
-    files
+    fastnode_util::glob::GlobSet::new([pattern])
+        .and_then(|set| set.with_ignore(FINGERPRINT_IGNORES))
+        .ok()
+        .and_then(|set| fastnode_util::glob::expand(root, &set).ok())
+        .unwrap_or_default()
 }
 
 /// Compare two fingerprints and return whether they match.
@@ -465,7 +438,7 @@ mod tests {
         assert_eq!(fp.schema_version, FINGERPRINT_SCHEMA_VERSION);
         assert_eq!(fp.output_count, 1);
         assert!(fp.total_size > 0);
-        assert!(!fp.hash.is_empty());
+        assert!(fp.hash.starts_with("blake3:"));
     }
 
     #[test]
@@ -10,10 +10,12 @@
 
 #![allow(clippy::struct_excessive_bools)]
 
+use super::diagnostics::BuildDiagnostic;
+use super::hash::{glob_matches, is_excluded};
 use crate::compiler::TranspileSpec;
 use serde::{Deserialize, Serialize};
 use std::collections::BTreeMap;
-use std::path::Path;
+use std::path::{Path, PathBuf};
 
 /// Schema version for the `BuildGraph` format.
 /// v2.1: Multi-node support with defaults.
@@ -94,6 +96,8 @@ pub enum BuildNodeKind {
     Transpile,
     /// TypeScript type checking (v3.2).
     Typecheck,
+    /// TypeScript declaration (`.d.ts`) generation.
+    Dts,
 }
 
 impl BuildNodeKind {
@@ -107,6 +111,7 @@ impl BuildNodeKind {
             Self::Test => "test",
             Self::Transpile => "transpile",
             Self::Typecheck => "typecheck",
+            Self::Dts => "dts",
         }
     }
 }
@@ -168,6 +173,27 @@ impl BuildCommand {
     }
 }
 
+/// Sandbox policy for script nodes.
+///
+/// All limits are optional. CPU and memory limits are applied with rlimits
+/// and only take effect on Unix.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct BuildSandbox {
+    /// Only pass the node's env allowlist plus essentials (`PATH`, `HOME`, ...).
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub restrict_env: bool,
+    /// Maximum captured bytes per output stream (defaults to `MAX_OUTPUT_SIZE`).
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub max_output_bytes: Option<usize>,
+    /// CPU time limit in seconds (`RLIMIT_CPU`).
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub cpu_seconds: Option<u64>,
+    /// Memory limit in megabytes (`RLIMIT_DATA`: heap and other private
+    /// writable memory, not reserved address space).
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub memory_mb: Option<u64>,
+}
+
 /// Cache policy for a build node (v2.1).
 #[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
 pub struct BuildCachePolicy {
@@ -220,6 +246,18 @@ impl BuildScriptSpec {
     }
 }
 
+/// Declaration generation specification for a `dts` build node.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct DtsSpec {
+    /// Entry files whose declarations are emitted (relative to graph cwd).
+    pub entries: Vec<String>,
+    /// Directory the declarations are written to (relative to graph cwd).
+    pub out_dir: String,
+    /// `tsconfig.json` to compile with, if the project has one.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub tsconfig: Option<String>,
+}
+
 /// A build input source.
 #[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize, PartialOrd, Ord)]
 #[serde(tag = "type", rename_all = "lowercase")]
@@ -369,6 +407,31 @@ impl BuildInput {
         }
     }
 
+    /// Check whether a change to `path` affects this input.
+    ///
+    /// Relative input paths are resolved against `cwd`. Glob and directory
+    /// inputs honor [`DEFAULT_GLOB_EXCLUSIONS`], so writes to `dist/` or
+    /// `node_modules/` never match. Package, env and node inputs never match
+    /// a path.
+    #[must_use]
+    pub fn matches_path(&self, path: &Path, cwd: &Path) -> bool {
+        match self {
+            Self::File { path: file, .. } | Self::Lockfile { path: file, .. } => {
+                path == cwd.join(file)
+            }
+            Self::Dir { path: dir, .. } => path
+                .strip_prefix(cwd.join(dir))
+                .is_ok_and(|rel| !is_excluded(&normalize_rel_path(rel), DEFAULT_GLOB_EXCLUSIONS)),
+            Self::Glob { pattern, root, .. } => {
+                path.strip_prefix(cwd.join(root)).is_ok_and(|rel| {
+                    let rel = normalize_rel_path(rel);
+                    !is_excluded(&rel, DEFAULT_GLOB_EXCLUSIONS) && glob_matches(pattern, &rel)
+                })
+            }
+            Self::Package { .. } | Self::Env { .. } | Self::Node { .. } => false,
+        }
+    }
+
     /// Get the sort key for deterministic ordering.
     fn sort_key(&self) -> (u8, String, String, bool) {
         match self {
@@ -485,12 +548,18 @@ pub struct BuildNode {
     /// Transpilation specification (v3.1).
     #[serde(skip_serializing_if = "Option::is_none")]
     pub transpile: Option<TranspileSpec>,
+    /// Declaration generation specification.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub dts: Option<DtsSpec>,
     /// Node IDs this depends on (sorted).
     #[serde(default, skip_serializing_if = "Vec::is_empty")]
     pub deps: Vec<String>,
     /// Cache policy (v2.1).
     #[serde(default)]
     pub cache: BuildCachePolicy,
+    /// Sandbox policy for script execution.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub sandbox: Option<BuildSandbox>,
 }
 
 impl BuildNode {
@@ -511,8 +580,10 @@ impl BuildNode {
             command: Some(BuildCommand::shell(cmd)),
             script: Some(BuildScriptSpec::new(name, cmd)),
             transpile: None,
+            dts: None,
             deps: Vec::new(),
             cache: BuildCachePolicy::default(),
+            sandbox: None,
         }
     }
 
@@ -538,8 +609,10 @@ impl BuildNode {
             command: None,
             script: None,
             transpile: Some(spec),
+            dts: None,
             deps: Vec::new(),
             cache: BuildCachePolicy::default(),
+            sandbox: None,
         }
     }
 
@@ -565,8 +638,10 @@ impl BuildNode {
             command: None,
             script: None,
             transpile: Some(spec.clone()),
+            dts: None,
             deps: Vec::new(),
             cache: BuildCachePolicy::default(),
+            sandbox: None,
         }
     }
 
@@ -593,8 +668,40 @@ impl BuildNode {
             command: None, // Command resolved at execution time
             script: None,
             transpile: None,
+            dts: None,
             deps: Vec::new(),
             cache: BuildCachePolicy::default(),
+            sandbox: None,
+        }
+    }
+
+    /// Create a new declaration generation node.
+    ///
+    /// Dts nodes run `tsc --emitDeclarationOnly` for the entries and write
+    /// the declarations to `spec.out_dir`, which is fingerprinted as the
+    /// node's output so unchanged sources skip regeneration.
+    ///
+    /// Like typecheck, the tsc command is resolved at execution time.
+    #[must_use]
+    pub fn dts(spec: DtsSpec) -> Self {
+        Self {
+            id: format!("dts:{}", spec.out_dir),
+            kind: BuildNodeKind::Dts,
+            label: format!("dts {}/", spec.out_dir),
+            inputs: Vec::new(), // Will be populated by caller
+            outputs: vec![BuildOutput::dir(spec.out_dir.clone())],
+            env: Vec::new(),
+            env_allowlist: DEFAULT_ENV_ALLOWLIST
+                .iter()
+                .map(|s| (*s).to_string())
+                .collect(),
+            command: None, // Command resolved at execution time
+            script: None,
+            transpile: None,
+            dts: Some(spec),
+            deps: Vec::new(),
+            cache: BuildCachePolicy::default(),
+            sandbox: None,
         }
     }
 
@@ -812,6 +919,50 @@ impl BuildGraph {
         (all_nodes, levels)
     }
 
+    /// Find the nodes affected by a set of changed paths.
+    ///
+    /// A node is affected when one of its inputs matches a changed path
+    /// (see [`BuildInput::matches_path`]) or when it depends, directly or
+    /// transitively, on an affected node. Returns node IDs in topological
+    /// order; an empty result means no node needs to run.
+    #[must_use]
+    pub fn affected_nodes(&self, changed: &[PathBuf]) -> Vec<String> {
+        use std::collections::HashSet;
+
+        let cwd = Path::new(&self.cwd);
+        let mut affected: HashSet<&str> = self
+            .nodes
+            .iter()
+            .filter(|node| {
+                node.inputs
+                    .iter()
+                    .any(|input| changed.iter().any(|path| input.matches_path(path, cwd)))
+            })
+            .map(|node| node.id.as_str())
+            .collect();
+
+        // Dependents of an affected node are affected too; toposort order
+        // guarantees dependencies are visited first.
+        let order = self.toposort();
+        for id in &order {
+            if affected.contains(id) {
+                continue;
+            }
+            let depends_on_affected = self
+                .get_node(id)
+                .is_some_and(|node| node.deps.iter().any(|dep| affected.contains(dep.as_str())));
+            if depends_on_affected {
+                affected.insert(id);
+            }
+        }
+
+        order
+            .into_iter()
+            .filter(|id| affected.contains(id))
+            .map(str::to_string)
+            .collect()
+    }
+
     /// Plan which nodes to execute for the given targets.
     ///
     /// Resolves target aliases, computes the dependency closure, and returns
@@ -1094,9 +1245,19 @@ pub struct BuildNodeResult {
     /// Number of files processed (for batch transpile nodes, v3.1.2).
     #[serde(skip_serializing_if = "Option::is_none")]
     pub files_count: Option<u32>,
+    /// Number of files actually re-emitted (for batch transpile nodes).
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub changed_count: Option<u32>,
     /// Whether this node was auto-discovered (v3.1.2).
     #[serde(default, skip_serializing_if = "std::ops::Not::not")]
     pub auto_discovered: bool,
+    /// Structured diagnostics (e.g. type errors from typecheck nodes).
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub diagnostics: Vec<BuildDiagnostic>,
+    /// Full output of this run, relative to the project root (see
+    /// [`super::logs`]).
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub log_path: Option<String>,
 }
 
 impl BuildNodeResult {
@@ -1115,7 +1276,10 @@ impl BuildNodeResult {
             error: None,
             notes: Vec::new(),
             files_count: None,
+            changed_count: None,
             auto_discovered: false,
+            diagnostics: Vec::new(),
+            log_path: None,
         }
     }
 
@@ -1134,7 +1298,10 @@ impl BuildNodeResult {
             error: None,
             notes: Vec::new(),
             files_count: None,
+            changed_count: None,
             auto_discovered: false,
+            diagnostics: Vec::new(),
+            log_path: None,
         }
     }
 
@@ -1158,7 +1325,10 @@ impl BuildNodeResult {
             error: None,
             notes: Vec::new(),
             files_count: None,
+            changed_count: None,
             auto_discovered: false,
+            diagnostics: Vec::new(),
+            log_path: None,
         }
     }
 
@@ -1182,7 +1352,10 @@ impl BuildNodeResult {
             error: Some(error),
             notes: Vec::new(),
             files_count: None,
+            changed_count: None,
             auto_discovered: false,
+            diagnostics: Vec::new(),
+            log_path: None,
         }
     }
 
@@ -1201,7 +1374,10 @@ impl BuildNodeResult {
             error: None,
             notes: vec!["skipped due to dependency failure".to_string()],
             files_count: None,
+            changed_count: None,
             auto_discovered: false,
+            diagnostics: Vec::new(),
+            log_path: None,
         }
     }
 
@@ -1683,6 +1859,53 @@ mod tests {
         assert!(build_pos < test_pos);
     }
 
+    #[test]
+    fn test_input_matches_path() {
+        let cwd = Path::new("/project");
+        let glob = BuildInput::glob("src/**/*.ts", "/project");
+        assert!(glob.matches_path(Path::new("/project/src/a/b.ts"), cwd));
+        assert!(!glob.matches_path(Path::new("/project/src/a/b.css"), cwd));
+        assert!(!glob.matches_path(Path::new("/other/src/b.ts"), cwd));
+
+        let all = BuildInput::glob("**/*", "/project");
+        assert!(all.matches_path(Path::new("/project/README.md"), cwd));
+        assert!(!all.matches_path(Path::new("/project/dist/index.js"), cwd));
+        assert!(!all.matches_path(Path::new("/project/node_modules/x/index.js"), cwd));
+
+        let file = BuildInput::file("tsconfig.json");
+        assert!(file.matches_path(Path::new("/project/tsconfig.json"), cwd));
+        assert!(!BuildInput::env("NODE_ENV").matches_path(Path::new("/project/NODE_ENV"), cwd));
+    }
+
+    #[test]
+    fn test_affected_nodes_includes_dependents() {
+        let mut graph = BuildGraph::new("/project");
+
+        let mut styles = BuildNode::script("styles", "sass");
+        styles.add_input(BuildInput::glob("styles/**/*.scss", "/project"));
+        graph.add_node(styles);
+
+        let mut build = BuildNode::script("build", "tsc");
+        build.add_input(BuildInput::glob("src/**/*.ts", "/project"));
+        graph.add_node(build);
+
+        let mut test = BuildNode::script("test", "vitest");
+        test.deps = vec!["script:build".to_string()];
+        graph.add_node(test);
+        graph.normalize();
+
+        let affected = graph.affected_nodes(&[PathBuf::from("/project/src/index.ts")]);
+        assert_eq!(affected, vec!["script:build", "script:test"]);
+
+        let affected = graph.affected_nodes(&[PathBuf::from("/project/styles/main.scss")]);
+        assert_eq!(affected, vec!["script:styles"]);
+
+        assert_eq!(
+            graph.affected_nodes(&[PathBuf::from("/project/dist/index.js")]),
+            Vec::<String>::new()
+        );
+    }
+
     #[test]
     fn test_plan_targets_parallel_levels() {
         let mut graph = BuildGraph::new("/project");
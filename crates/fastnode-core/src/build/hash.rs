@@ -1,6 +1,9 @@
 //! Deterministic hashing for the build system.
 //!
-//! Uses blake3 for fast, cryptographic hashing.
+//! Uses blake3 for fast, cryptographic hashing. Large files are hashed in
+//! parallel chunks and memoized for the life of the process. The algorithm
+//! ([`HASH_ALGORITHM`]) is part of the toolchain fingerprint and of every
+//! persisted cache, so switching it invalidates old entries.
 //! All hashing is deterministic across platforms.
 //!
 //! ## Hashing Rules (v1)
@@ -8,20 +11,29 @@
 //! - Paths are normalized to forward slashes
 //! - Files are hashed by content
 //! - Globs expand deterministically (sorted by path)
+//! - Input globs skip paths excluded by `.howthignore`
 //! - Environment variables are hashed by allowlist only
+//! - Every node hash includes the toolchain fingerprint
+//! - Nodes that run external tools (node, tsc, swc) include their versions
 
 #![allow(clippy::doc_markdown)]
 
 use super::graph::{BuildInput, BuildNode, DEFAULT_GLOB_EXCLUSIONS};
+use super::tools::ToolVersionCache;
+use crate::howthignore::HowthIgnore;
 use blake3::Hasher;
+use fastnode_util::glob::GlobSet;
+pub use fastnode_util::hash::HASH_ALGORITHM;
+use fastnode_util::hash::LARGE_FILE_THRESHOLD;
 use rayon::prelude::*;
+use serde::{Deserialize, Serialize};
 use std::collections::{BTreeMap, HashMap};
 use std::fs;
 use std::io;
 use std::path::{Path, PathBuf};
-use std::sync::RwLock;
+use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
+use std::sync::{OnceLock, RwLock};
 use std::time::SystemTime;
-use walkdir::WalkDir;
 
 /// Result type for hashing operations.
 pub type HashResult<T> = Result<T, HashError>;
@@ -191,13 +203,216 @@ impl FileHashCache for InMemoryFileHashCache {
     }
 }
 
+/// Schema version for the persistent file hash cache.
+pub const FILE_HASH_CACHE_SCHEMA_VERSION: u32 = 1;
+
+/// Location of the persistent file hash cache, relative to the project root.
+const FILE_HASH_CACHE_PATH: &str = ".howth/cache/file-hashes.json";
+
+/// Files modified this close to a save are not persisted (nanoseconds).
+///
+/// A file written within the filesystem's mtime granularity of being hashed
+/// could change again without its (mtime, size) changing.
+const RACY_MTIME_WINDOW_NS: u128 = 2_000_000_000;
+
+/// A persisted file hash record.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+struct FileHashRecord {
+    mtime_ns: u128,
+    size: u64,
+    hash: String,
+}
+
+/// On-disk format of the persistent file hash cache.
+#[derive(Debug, Default, Serialize, Deserialize)]
+struct FileHashCacheFile {
+    schema_version: u32,
+    /// Hash algorithm of the entries; empty for caches written before it was recorded.
+    #[serde(default)]
+    algorithm: String,
+    entries: HashMap<String, FileHashRecord>,
+}
+
+/// Persistent per-project file hash cache.
+///
+/// Stores path → (mtime, size, hash) under `.howth/cache/` so hashes survive
+/// daemon restarts. Lookups are a fast path on (mtime, size); any mismatch is
+/// a miss and the caller falls back to hashing the full contents.
+///
+/// Call [`save`](Self::save) after a build to write new entries back.
+#[derive(Debug)]
+pub struct PersistentFileHashCache {
+    path: PathBuf,
+    entries: RwLock<HashMap<String, FileHashRecord>>,
+    hits: AtomicU64,
+    misses: AtomicU64,
+    dirty: AtomicBool,
+}
+
+impl PersistentFileHashCache {
+    /// Open the cache for a project, starting empty if it is missing or invalid.
+    #[must_use]
+    pub fn open(project_root: &Path) -> Self {
+        let path = project_root.join(FILE_HASH_CACHE_PATH);
+        let entries = fs::read_to_string(&path)
+            .ok()
+            .and_then(|content| serde_json::from_str::<FileHashCacheFile>(&content).ok())
+            .filter(|file| {
+                file.schema_version == FILE_HASH_CACHE_SCHEMA_VERSION
+                    && file.algorithm == HASH_ALGORITHM
+            })
+            .map(|file| file.entries)
+            .unwrap_or_default();
+
+        Self {
+            path,
+            entries: RwLock::new(entries),
+            hits: AtomicU64::new(0),
+            misses: AtomicU64::new(0),
+            dirty: AtomicBool::new(false),
+        }
+    }
+
+    /// Path of the cache file.
+    #[must_use]
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    /// Write the cache back to disk if it changed.
+    ///
+    /// Entries for files that no longer exist are dropped, and entries whose
+    /// mtime is too recent to trust are kept in memory only.
+    ///
+    /// # Errors
+    /// Returns an error if the cache file cannot be written.
+    ///
+    /// # Panics
+    /// Panics if the entries lock is poisoned.
+    pub fn save(&self) -> io::Result<()> {
+        if !self.dirty.swap(false, Ordering::SeqCst) {
+            return Ok(());
+        }
+
+        let now_ns = SystemTime::now()
+            .duration_since(SystemTime::UNIX_EPOCH)
+            .map(|d| d.as_nanos())
+            .unwrap_or(0);
+        let entries: HashMap<String, FileHashRecord> = self
+            .entries
+            .read()
+            .unwrap()
+            .iter()
+            .filter(|(_, record)| record.mtime_ns + RACY_MTIME_WINDOW_NS < now_ns)
+            .filter(|(path, _)| Path::new(path).exists())
+            .map(|(path, record)| (path.clone(), record.clone()))
+            .collect();
+
+        let file = FileHashCacheFile {
+            schema_version: FILE_HASH_CACHE_SCHEMA_VERSION,
+            algorithm: HASH_ALGORITHM.to_string(),
+            entries,
+        };
+        let json = serde_json::to_string(&file).map_err(io::Error::other)?;
+
+        if let Some(parent) = self.path.parent() {
+            fs::create_dir_all(parent)?;
+        }
+        fastnode_util::fs::atomic_write(&self.path, json.as_bytes())
+    }
+}
+
+impl FileHashCache for PersistentFileHashCache {
+    fn get(&self, key: &FileHashKey) -> Option<String> {
+        let result = self
+            .entries
+            .read()
+            .unwrap()
+            .get(&key.path)
+            .filter(|record| record.mtime_ns == key.mtime_ns && record.size == key.size)
+            .map(|record| record.hash.clone());
+        if result.is_some() {
+            self.hits.fetch_add(1, Ordering::Relaxed);
+        } else {
+            self.misses.fetch_add(1, Ordering::Relaxed);
+        }
+        result
+    }
+
+    fn put(&self, key: FileHashKey, hash: String) {
+        let record = FileHashRecord {
+            mtime_ns: key.mtime_ns,
+            size: key.size,
+            hash,
+        };
+        self.entries.write().unwrap().insert(key.path, record);
+        self.dirty.store(true, Ordering::SeqCst);
+    }
+
+    fn stats(&self) -> FileHashCacheStats {
+        FileHashCacheStats {
+            hits: self.hits.load(Ordering::Relaxed),
+            misses: self.misses.load(Ordering::Relaxed),
+            entries: self.entries.read().unwrap().len(),
+        }
+    }
+}
+
+/// Fingerprint of the toolchain that produces build outputs.
+///
+/// Covers the howth version, the parser/codegen version and its enabled
+/// features, the compiler backend selected by `HOWTH_COMPILER`, and the
+/// content hash algorithm, so upgrading howth, switching backends or
+/// changing how files are hashed invalidates every cached node.
+#[must_use]
+pub fn toolchain_fingerprint() -> &'static str {
+    static FINGERPRINT: OnceLock<String> = OnceLock::new();
+    FINGERPRINT.get_or_init(|| {
+        let mut hasher = Hasher::new();
+        hasher.update(b"howth:");
+        hasher.update(crate::version::VERSION.as_bytes());
+        hasher.update(b"\0parser:");
+        hasher.update(howth_parser::VERSION.as_bytes());
+        hasher.update(b"\0features:");
+        hasher.update(howth_parser::FEATURES.join(",").as_bytes());
+        hasher.update(b"\0compiler:");
+        hasher.update(
+            crate::compiler::CompilerKind::from_env()
+                .as_str()
+                .as_bytes(),
+        );
+        hasher.update(b"\0hash:");
+        hasher.update(HASH_ALGORITHM.as_bytes());
+        hasher.update(b"\0");
+        hasher.finalize().to_hex()[..16].to_string()
+    })
+}
+
 /// Context for hash operations.
 ///
-/// Provides shared state like file hash cache to avoid re-reading unchanged files.
-#[derive(Default)]
+/// Provides shared state like file hash cache to avoid re-reading unchanged files,
+/// the toolchain fingerprint mixed into every node hash, and the probed
+/// versions of external tools that nodes run.
 pub struct HashContext<'a> {
     /// Optional file hash cache for avoiding redundant file reads.
     pub file_cache: Option<&'a dyn FileHashCache>,
+    /// Toolchain fingerprint (see [`toolchain_fingerprint`]).
+    pub toolchain: &'a str,
+    /// External tool versions (process-wide cache by default).
+    pub tools: &'a ToolVersionCache,
+    /// `SOURCE_DATE_EPOCH` given to scripts, which their outputs may embed.
+    pub source_date_epoch: Option<u64>,
+}
+
+impl Default for HashContext<'_> {
+    fn default() -> Self {
+        Self {
+            file_cache: None,
+            toolchain: toolchain_fingerprint(),
+            tools: ToolVersionCache::global(),
+            source_date_epoch: None,
+        }
+    }
 }
 
 impl<'a> HashContext<'a> {
@@ -206,6 +421,7 @@ impl<'a> HashContext<'a> {
     pub fn with_cache(cache: &'a dyn FileHashCache) -> Self {
         Self {
             file_cache: Some(cache),
+            ..Self::default()
         }
     }
 
@@ -214,6 +430,27 @@ impl<'a> HashContext<'a> {
     pub fn empty() -> Self {
         Self::default()
     }
+
+    /// Override the toolchain fingerprint.
+    #[must_use]
+    pub fn with_toolchain(mut self, toolchain: &'a str) -> Self {
+        self.toolchain = toolchain;
+        self
+    }
+
+    /// Override the tool version cache.
+    #[must_use]
+    pub fn with_tools(mut self, tools: &'a ToolVersionCache) -> Self {
+        self.tools = tools;
+        self
+    }
+
+    /// Set the `SOURCE_DATE_EPOCH` scripts run with.
+    #[must_use]
+    pub fn with_source_date_epoch(mut self, epoch: Option<u64>) -> Self {
+        self.source_date_epoch = epoch;
+        self
+    }
 }
 
 /// Normalize a path for hashing.
@@ -250,19 +487,25 @@ pub fn hash_file(path: &Path) -> HashResult<String> {
 /// If a file hash cache is provided in the context, this will:
 /// 1. Check if (path, mtime, size) is in cache
 /// 2. If hit, return cached hash without reading file
-/// 3. If miss, read file, compute hash, store in cache
+/// 3. If miss, stream the file, compute hash, store in cache
+///
+/// Without a cache, files of at least [`LARGE_FILE_THRESHOLD`] bytes are
+/// still memoized process-wide on (path, mtime, size), since re-hashing
+/// them dominates repeated graph hashes.
 pub fn hash_file_with_ctx(path: &Path, ctx: &HashContext<'_>) -> HashResult<String> {
     // Try to get cache key (requires file to exist and be readable)
-    if let Some(cache) = ctx.file_cache {
-        if let Some(key) = FileHashKey::from_path(path) {
+    if let Some(key) = FileHashKey::from_path(path) {
+        let cache = ctx
+            .file_cache
+            .or_else(|| (key.size >= LARGE_FILE_THRESHOLD).then(large_file_memo));
+        if let Some(cache) = cache {
             // Check cache first
             if let Some(cached_hash) = cache.get(&key) {
                 return Ok(cached_hash);
             }
 
-            // Cache miss - read and hash file
-            let contents = fs::read(path).map_err(|e| HashError::io(path, e))?;
-            let hash = hash_bytes(&contents);
+            // Cache miss - hash file
+            let hash = read_and_hash(path)?;
 
             // Store in cache
             cache.put(key, hash.clone());
@@ -272,8 +515,18 @@ pub fn hash_file_with_ctx(path: &Path, ctx: &HashContext<'_>) -> HashResult<Stri
     }
 
     // No cache or couldn't get metadata - fall back to direct read
-    let contents = fs::read(path).map_err(|e| HashError::io(path, e))?;
-    Ok(hash_bytes(&contents))
+    read_and_hash(path)
+}
+
+/// Stream a file through the hasher (chunked and parallel for large files).
+fn read_and_hash(path: &Path) -> HashResult<String> {
+    fastnode_util::hash::blake3_file(path).map_err(|e| HashError::io(path, e))
+}
+
+/// Process-wide memo for large files hashed without a context cache.
+fn large_file_memo() -> &'static dyn FileHashCache {
+    static MEMO: OnceLock<InMemoryFileHashCache> = OnceLock::new();
+    MEMO.get_or_init(InMemoryFileHashCache::new)
 }
 
 /// Hash raw bytes.
@@ -289,73 +542,57 @@ pub fn hash_string(s: &str) -> String {
     hash_bytes(s.as_bytes())
 }
 
+/// Check whether a root-relative path is covered by an exclusion pattern.
+pub(crate) fn is_excluded(rel: &str, exclusions: &[&str]) -> bool {
+    GlobSet::default()
+        .with_ignore(exclusions)
+        .is_ok_and(|set| set.is_excluded(rel))
+}
+
+/// Check whether a root-relative path matches a glob pattern.
+pub(crate) fn glob_matches(pattern: &str, rel: &str) -> bool {
+    GlobSet::new([pattern]).is_ok_and(|set| set.is_match(rel))
+}
+
 /// Expand a glob pattern and return sorted file paths.
 ///
-/// Files are sorted by normalized path for determinism.
+/// Supports braces, extglobs and `!`-negated alternatives (see
+/// [`fastnode_util::glob`]). Excluded directories are not descended into,
+/// symlinks are followed with cycle protection, and files are sorted by
+/// path for determinism.
 pub fn expand_glob(pattern: &str, root: &Path, exclusions: &[&str]) -> HashResult<Vec<PathBuf>> {
-    let mut files = Vec::new();
-
-    // Walk the directory
-    for entry in WalkDir::new(root)
-        .follow_links(false)
-        .into_iter()
-        .filter_entry(|e| {
-            // Check exclusions
-            let path = e.path();
-            let rel = path.strip_prefix(root).unwrap_or(path);
-            let rel_str = rel.to_string_lossy();
-
-            // Skip excluded patterns
-            for exclusion in exclusions {
-                if let Some(prefix) = exclusion.strip_suffix("/**") {
-                    if rel_str.starts_with(prefix) || rel_str == prefix.trim_end_matches('/') {
-                        return false;
-                    }
-                } else if glob::Pattern::new(exclusion)
-                    .map(|p| p.matches(&rel_str))
-                    .unwrap_or(false)
-                {
-                    return false;
-                }
-            }
-            true
-        })
-    {
-        let entry = entry.map_err(|e| {
-            HashError::new(
-                super::codes::BUILD_HASH_IO_ERROR,
-                format!("Failed to walk directory: {e}"),
-            )
-        })?;
-
-        if entry.file_type().is_file() {
-            let path = entry.path();
-            let rel = path.strip_prefix(root).unwrap_or(path);
-            let rel_str = rel.to_string_lossy();
-
-            // Check if matches pattern
-            let matches = if pattern == "**/*" {
-                true
-            } else {
-                glob::Pattern::new(pattern)
-                    .map(|p| p.matches(&rel_str))
-                    .unwrap_or(false)
-            };
-
-            if matches {
-                files.push(path.to_path_buf());
-            }
-        }
-    }
+    expand_glob_filtered(pattern, root, exclusions, |_, _| false)
+}
 
-    // Sort by normalized path for determinism
-    files.sort_by(|a, b| {
-        let a_norm = normalize_path(a);
-        let b_norm = normalize_path(b);
-        a_norm.cmp(&b_norm)
-    });
+/// Expand a build input glob: like [`expand_glob`], also skipping paths
+/// excluded by the project's `.howthignore` (see [`crate::howthignore`]).
+pub fn expand_input_glob(
+    pattern: &str,
+    root: &Path,
+    exclusions: &[&str],
+) -> HashResult<Vec<PathBuf>> {
+    let ignore = HowthIgnore::load(root);
+    expand_glob_filtered(pattern, root, exclusions, |path, is_dir| {
+        ignore.is_ignored(path, is_dir)
+    })
+}
 
-    Ok(files)
+fn expand_glob_filtered(
+    pattern: &str,
+    root: &Path,
+    exclusions: &[&str],
+    skip: impl Fn(&Path, bool) -> bool,
+) -> HashResult<Vec<PathBuf>> {
+    let set = GlobSet::new([pattern])
+        .and_then(|set| set.with_ignore(exclusions))
+        .map_err(|e| HashError::new(super::codes::BUILD_HASH_IO_ERROR, e.to_string()))?;
+
+    fastnode_util::glob::expand_filtered(root, &set, skip).map_err(|e| {
+        HashError::new(
+            super::codes::BUILD_HASH_IO_ERROR,
+            format!("Failed to walk directory: {e}"),
+        )
+    })
 }
 
 /// Hash all files matched by a glob pattern.
@@ -365,6 +602,7 @@ pub fn hash_glob(pattern: &str, root: &Path, exclusions: &[&str]) -> HashResult<
 
 /// Hash all files matched by a glob pattern, with optional caching.
 ///
+/// Paths excluded by `.howthignore` are not part of the hash.
 /// Uses file hash cache to avoid re-reading unchanged files.
 /// v3.5: Parallelizes file hashing using rayon for cold builds.
 pub fn hash_glob_with_ctx(
@@ -373,7 +611,7 @@ pub fn hash_glob_with_ctx(
     exclusions: &[&str],
     ctx: &HashContext<'_>,
 ) -> HashResult<String> {
-    let files = expand_glob(pattern, root, exclusions)?;
+    let files = expand_input_glob(pattern, root, exclusions)?;
 
     // Parallel hash computation for all files
     // Each file is hashed independently, then results are combined deterministically
@@ -677,6 +915,11 @@ pub fn hash_node_with_deps_ctx(
     );
     hasher.update(b"\0");
 
+    // Toolchain (howth + parser versions)
+    hasher.update(b"toolchain:");
+    hasher.update(ctx.toolchain.as_bytes());
+    hasher.update(b"\0");
+
     // Kind
     hasher.update(b"kind:");
     hasher.update(node.kind.as_str().as_bytes());
@@ -709,6 +952,11 @@ pub fn hash_node_with_deps_ctx(
     hasher.update(b"env:");
     hasher.update(hash_env(&node.env_allowlist).as_bytes());
     hasher.update(b"\0");
+    if let (Some(epoch), Some(_)) = (ctx.source_date_epoch, &node.script) {
+        hasher.update(b"source_date_epoch:");
+        hasher.update(epoch.to_string().as_bytes());
+        hasher.update(b"\0");
+    }
 
     // Script
     if let Some(script) = &node.script {
@@ -728,6 +976,31 @@ pub fn hash_node_with_deps_ctx(
         hasher.update(b"\0");
     }
 
+    // Dts spec
+    if let Some(dts) = &node.dts {
+        hasher.update(b"dts:");
+        for entry in &dts.entries {
+            hasher.update(entry.as_bytes());
+            hasher.update(b"\0");
+        }
+        hasher.update(dts.out_dir.as_bytes());
+        hasher.update(b"\0");
+        hasher.update(dts.tsconfig.as_deref().unwrap_or("").as_bytes());
+        hasher.update(b"\0");
+    }
+
+    // External tool versions (only for nodes that run tools)
+    let tools = ctx.tools.node_versions(node, cwd);
+    if !tools.is_empty() {
+        hasher.update(b"tools:");
+        for (tool, version) in &tools {
+            hasher.update(tool.as_bytes());
+            hasher.update(b"=");
+            hasher.update(version.as_bytes());
+            hasher.update(b"\0");
+        }
+    }
+
     // Dependencies (sorted) - include dep hashes for additional invalidation
     let mut deps = node.deps.clone();
     deps.sort();
@@ -894,6 +1167,131 @@ mod tests {
         assert!(files[0].to_string_lossy().contains("a.ts"));
     }
 
+    #[test]
+    fn test_input_glob_respects_howthignore() {
+        let dir = tempdir().unwrap();
+        std::fs::create_dir_all(dir.path().join("src/vendor")).unwrap();
+        std::fs::write(dir.path().join("src/a.ts"), "a").unwrap();
+        std::fs::write(dir.path().join("src/vendor/b.ts"), "b").unwrap();
+        std::fs::write(dir.path().join("src/c.gen.ts"), "c").unwrap();
+        let before = hash_glob("**/*.ts", dir.path(), &[]).unwrap();
+
+        std::fs::write(dir.path().join(".howthignore"), "vendor/\n*.gen.ts\n").unwrap();
+        let files = expand_input_glob("**/*.ts", dir.path(), &[]).unwrap();
+        assert_eq!(files, [dir.path().join("src/a.ts")]);
+        // Plain expansion (e.g. of outputs) is unaffected
+        assert_eq!(expand_glob("**/*.ts", dir.path(), &[]).unwrap().len(), 3);
+
+        // Ignored files no longer affect the input hash
+        let ignored = hash_glob("**/*.ts", dir.path(), &[]).unwrap();
+        assert_ne!(before, ignored);
+        std::fs::write(dir.path().join("src/vendor/b.ts"), "changed").unwrap();
+        assert_eq!(hash_glob("**/*.ts", dir.path(), &[]).unwrap(), ignored);
+    }
+
+    #[test]
+    fn test_expand_glob_braces_and_negation() {
+        let dir = tempdir().unwrap();
+        std::fs::create_dir_all(dir.path().join("src/ui")).unwrap();
+        for file in ["src/a.ts", "src/ui/App.tsx", "src/a.test.ts", "src/b.js"] {
+            std::fs::write(dir.path().join(file), "").unwrap();
+        }
+
+        let files = expand_glob("src/**/*.{ts,tsx}", dir.path(), &["**/*.test.ts"]).unwrap();
+        assert_eq!(
+            files,
+            [
+                dir.path().join("src/a.ts"),
+                dir.path().join("src/ui/App.tsx")
+            ]
+        );
+        assert!(glob_matches("src/**/!(*.test).ts", "src/x/a.ts"));
+        assert!(!glob_matches("src/**/!(*.test).ts", "src/x/a.test.ts"));
+    }
+
+    /// Write a file whose mtime is safely outside the racy window.
+    fn write_old_file(path: &Path, contents: &str) {
+        std::fs::write(path, contents).unwrap();
+        let old = SystemTime::now() - std::time::Duration::from_secs(60);
+        std::fs::File::options()
+            .write(true)
+            .open(path)
+            .unwrap()
+            .set_modified(old)
+            .unwrap();
+    }
+
+    #[test]
+    fn test_persistent_file_hash_cache_survives_reopen() {
+        let dir = tempdir().unwrap();
+        let file = dir.path().join("index.ts");
+        write_old_file(&file, "export const x = 1;");
+
+        let cache = PersistentFileHashCache::open(dir.path());
+        let ctx = HashContext::with_cache(&cache);
+        let hash = hash_file_with_ctx(&file, &ctx).unwrap();
+        assert_eq!(cache.stats().misses, 1);
+        cache.save().unwrap();
+        assert!(cache.path().exists());
+
+        let reopened = PersistentFileHashCache::open(dir.path());
+        let ctx = HashContext::with_cache(&reopened);
+        assert_eq!(hash_file_with_ctx(&file, &ctx).unwrap(), hash);
+        assert_eq!(reopened.stats().hits, 1);
+
+        // A size change falls back to a full hash
+        write_old_file(&file, "export const x = 12;");
+        assert_ne!(hash_file_with_ctx(&file, &ctx).unwrap(), hash);
+        assert_eq!(reopened.stats().misses, 1);
+    }
+
+    #[test]
+    fn test_persistent_file_hash_cache_ignores_other_algorithm() {
+        let dir = tempdir().unwrap();
+        let file = dir.path().join("index.ts");
+        write_old_file(&file, "export const x = 1;");
+
+        let cache = PersistentFileHashCache::open(dir.path());
+        hash_file_with_ctx(&file, &HashContext::with_cache(&cache)).unwrap();
+        cache.save().unwrap();
+
+        let content = std::fs::read_to_string(cache.path()).unwrap();
+        let legacy = content.replace(r#""algorithm":"blake3","#, "");
+        assert_ne!(legacy, content);
+        std::fs::write(cache.path(), legacy).unwrap();
+        assert_eq!(PersistentFileHashCache::open(dir.path()).stats().entries, 0);
+    }
+
+    #[test]
+    fn test_large_file_hash_is_memoized() {
+        let dir = tempdir().unwrap();
+        let file = dir.path().join("bundle.wasm");
+        #[allow(clippy::cast_possible_truncation)]
+        let data: Vec<u8> = (0..=LARGE_FILE_THRESHOLD)
+            .map(|i| (i % 251) as u8)
+            .collect();
+        std::fs::write(&file, &data).unwrap();
+
+        let hash = hash_file(&file).unwrap();
+        assert_eq!(hash, hash_bytes(&data));
+        let key = FileHashKey::from_path(&file).unwrap();
+        assert_eq!(large_file_memo().get(&key), Some(hash));
+    }
+
+    #[test]
+    fn test_persistent_file_hash_cache_skips_racy_entries() {
+        let dir = tempdir().unwrap();
+        let file = dir.path().join("fresh.ts");
+        std::fs::write(&file, "export {};").unwrap();
+
+        let cache = PersistentFileHashCache::open(dir.path());
+        hash_file_with_ctx(&file, &HashContext::with_cache(&cache)).unwrap();
+        cache.save().unwrap();
+
+        let reopened = PersistentFileHashCache::open(dir.path());
+        assert_eq!(reopened.stats().entries, 0);
+    }
+
     #[test]
     fn test_hash_node_deterministic() {
         let dir = tempdir().unwrap();
@@ -913,6 +1311,25 @@ mod tests {
         assert_eq!(hash1, hash2);
     }
 
+    #[test]
+    fn test_hash_node_includes_source_date_epoch() {
+        let dir = tempdir().unwrap();
+        let node = BuildNode::script("build", "echo hello");
+        let hash = |epoch| {
+            hash_node_with_deps_ctx(
+                &node,
+                dir.path(),
+                &BTreeMap::new(),
+                &HashContext::empty().with_source_date_epoch(epoch),
+            )
+            .unwrap()
+        };
+
+        assert_eq!(hash(None), hash_node(&node, dir.path()).unwrap());
+        assert_ne!(hash(Some(1)), hash(None));
+        assert_ne!(hash(Some(1)), hash(Some(2)));
+    }
+
     #[test]
     fn test_hash_node_changes_on_input_change() {
         let dir = tempdir().unwrap();
@@ -932,6 +1349,43 @@ mod tests {
         assert_ne!(hash1, hash2);
     }
 
+    #[test]
+    fn test_hash_node_changes_on_toolchain_change() {
+        let dir = tempdir().unwrap();
+        let node = BuildNode::script("build", "echo hello");
+        let deps = BTreeMap::new();
+
+        let current = hash_node_with_deps_ctx(&node, dir.path(), &deps, &HashContext::empty());
+        let upgraded = hash_node_with_deps_ctx(
+            &node,
+            dir.path(),
+            &deps,
+            &HashContext::empty().with_toolchain("other-toolchain"),
+        );
+
+        assert_eq!(current.unwrap(), hash_node(&node, dir.path()).unwrap());
+        assert_ne!(hash_node(&node, dir.path()).unwrap(), upgraded.unwrap());
+        assert_eq!(toolchain_fingerprint().len(), 16);
+    }
+
+    #[test]
+    fn test_hash_node_changes_on_tool_version_change() {
+        let dir = tempdir().unwrap();
+        let deps = BTreeMap::new();
+        let node20 = ToolVersionCache::with_versions([("node", Some("v20.0.0"))]);
+        let node22 = ToolVersionCache::with_versions([("node", Some("v22.0.0"))]);
+        let hash = |node: &BuildNode, tools| {
+            let ctx = HashContext::empty().with_tools(tools);
+            hash_node_with_deps_ctx(node, dir.path(), &deps, &ctx).unwrap()
+        };
+
+        let uses_node = BuildNode::script("build", "node build.js");
+        assert_ne!(hash(&uses_node, &node20), hash(&uses_node, &node22));
+
+        let no_tools = BuildNode::script("build", "echo hello");
+        assert_eq!(hash(&no_tools, &node20), hash(&no_tools, &node22));
+    }
+
     #[test]
     fn test_hash_input_missing_file() {
         let dir = tempdir().unwrap();
@@ -939,7 +1393,7 @@ mod tests {
         let hash = hash_input(&input, dir.path()).unwrap();
 
         // Should not error, but include "missing" marker
-        assert!(!hash.is_empty());
+        assert_ne!(hash, "");
     }
 
     #[test]
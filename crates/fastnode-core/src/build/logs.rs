@@ -0,0 +1,134 @@
+//! Per-node run logs.
+//!
+//! Node results only keep a truncated tail of a script's output. The full
+//! output of every run is also written to `.howth/logs/<node>/<unix_ms>.log`,
+//! stdout and stderr interleaved in the order the lines arrived. The newest
+//! [`MAX_LOGS_PER_NODE`] runs of each node are kept; older logs are removed
+//! when a new run starts. `howth build logs <node>` displays them.
+
+use super::outputs::node_file_stem;
+use std::fs::{self, File, OpenOptions};
+use std::io;
+use std::path::{Path, PathBuf};
+use std::time::{SystemTime, UNIX_EPOCH};
+
+/// Directory (relative to the project root) holding run logs.
+pub const LOG_DIR: &str = ".howth/logs";
+
+/// Runs kept per node.
+pub const MAX_LOGS_PER_NODE: usize = 5;
+
+/// Directory holding the logs of a node.
+#[must_use]
+pub fn node_log_dir(root: &Path, node_id: &str) -> PathBuf {
+    root.join(LOG_DIR).join(node_file_stem(node_id))
+}
+
+/// Logs of a node, oldest first.
+#[must_use]
+pub fn list_logs(root: &Path, node_id: &str) -> Vec<PathBuf> {
+    let Ok(entries) = fs::read_dir(node_log_dir(root, node_id)) else {
+        return Vec::new();
+    };
+    let mut logs: Vec<(u128, u32, PathBuf)> = entries
+        .filter_map(Result::ok)
+        .filter_map(|entry| {
+            let path = entry.path();
+            let (ms, seq) = parse_log_name(&path)?;
+            Some((ms, seq, path))
+        })
+        .collect();
+    logs.sort();
+    logs.into_iter().map(|(_, _, path)| path).collect()
+}
+
+/// Log of the most recent run of a node.
+#[must_use]
+pub fn latest_log(root: &Path, node_id: &str) -> Option<PathBuf> {
+    list_logs(root, node_id).pop()
+}
+
+/// Create the log file for a new run of a node, removing the oldest logs so
+/// at most [`MAX_LOGS_PER_NODE`] remain.
+///
+/// # Errors
+/// Returns an error if the log directory or file cannot be created.
+pub fn create_log(root: &Path, node_id: &str) -> io::Result<(PathBuf, File)> {
+    let dir = node_log_dir(root, node_id);
+    fs::create_dir_all(&dir)?;
+
+    let existing = list_logs(root, node_id);
+    let ms = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map_or(0, |d| d.as_millis());
+    // Runs started within the same millisecond as the newest log (or while
+    // the clock is behind it) get a sequence suffix so they sort after it
+    let (ms, mut seq) = match existing.last().and_then(|path| parse_log_name(path)) {
+        Some((last_ms, last_seq)) if last_ms >= ms => (last_ms, last_seq + 1),
+        _ => (ms, 0),
+    };
+
+    let excess = (existing.len() + 1).saturating_sub(MAX_LOGS_PER_NODE);
+    for old in existing.iter().take(excess) {
+        let _ = fs::remove_file(old);
+    }
+
+    loop {
+        let name = if seq == 0 {
+            format!("{ms}.log")
+        } else {
+            format!("{ms}-{seq}.log")
+        };
+        let path = dir.join(name);
+        match OpenOptions::new().write(true).create_new(true).open(&path) {
+            Ok(file) => return Ok((path, file)),
+            // Another run created it concurrently
+            Err(e) if e.kind() == io::ErrorKind::AlreadyExists => seq += 1,
+            Err(e) => return Err(e),
+        }
+    }
+}
+
+/// Parse `<unix_ms>.log` or `<unix_ms>-<seq>.log`.
+fn parse_log_name(path: &Path) -> Option<(u128, u32)> {
+    if path.extension()? != "log" {
+        return None;
+    }
+    let stem = path.file_stem()?.to_str()?;
+    match stem.split_once('-') {
+        Some((ms, seq)) => Some((ms.parse().ok()?, seq.parse().ok()?)),
+        None => Some((stem.parse().ok()?, 0)),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_logs_rotate_and_sort() {
+        let dir = tempfile::tempdir().unwrap();
+        let mut created = Vec::new();
+        for _ in 0..MAX_LOGS_PER_NODE + 2 {
+            let (path, _) = create_log(dir.path(), "script:build").unwrap();
+            created.push(path);
+        }
+
+        let logs = list_logs(dir.path(), "script:build");
+        assert_eq!(logs, created[2..]);
+        assert_eq!(
+            latest_log(dir.path(), "script:build"),
+            created.last().cloned()
+        );
+        assert!(logs[0].starts_with(dir.path().join(".howth/logs/script_build")));
+        assert!(latest_log(dir.path(), "script:test").is_none());
+    }
+
+    #[test]
+    fn test_parse_log_name() {
+        assert_eq!(parse_log_name(Path::new("12.log")), Some((12, 0)));
+        assert_eq!(parse_log_name(Path::new("12-3.log")), Some((12, 3)));
+        assert_eq!(parse_log_name(Path::new("notes.txt")), None);
+        assert_eq!(parse_log_name(Path::new("latest.log")), None);
+    }
+}
@@ -19,21 +19,31 @@
 //! - Graph schema v1 (v2.0): Initial graph format (single node)
 //! - Graph schema v2 (v2.1): Multi-node graph with defaults + targets
 //! - Fingerprint schema v1 (v2.2): Output fingerprinting for cache correctness
+//! - Emitted outputs schema v1: Per-node manifests used to prune stale outputs
+//! - Remote job schema v1: `job.json` handed to `--remote-exec` executors
 
 #![allow(clippy::redundant_closure_for_method_calls)]
 #![allow(clippy::map_unwrap_or)]
 
 pub mod codes;
+pub mod diagnostics;
 pub mod exec;
 pub mod fingerprint;
 pub mod graph;
 pub mod hash;
+pub mod logs;
+pub mod outputs;
+pub mod remote;
+pub mod signing;
+pub mod tools;
 
 pub use codes::*;
+pub use diagnostics::{parse_tsc_output, BuildDiagnostic, DiagnosticSeverity};
 pub use exec::{
-    execute_graph, execute_graph_with_backend, execute_graph_with_file_cache, execute_node,
-    execute_transpile, execute_transpile_batch, execute_typecheck, run_script, BuildCache,
-    CacheEntry, ExecOptions, MemoryCache,
+    execute_dts, execute_graph, execute_graph_with_backend, execute_graph_with_file_cache,
+    execute_graph_with_progress, execute_node, execute_transpile, execute_transpile_batch,
+    execute_typecheck, run_script, run_script_sandboxed, BuildCache, BuildProgress, CacheEntry,
+    ExecOptions, MemoryCache, PersistentBuildCache, ScriptOutput, BUILD_CACHE_SCHEMA_VERSION,
 };
 pub use fingerprint::{
     compute_fingerprint, fingerprints_match, normalize_output_path, FingerprintError,
@@ -42,23 +52,41 @@ pub use fingerprint::{
 pub use graph::{
     resolve_target_alias, BuildErrorInfo, BuildGraph, BuildInput, BuildNode, BuildNodeKind,
     BuildNodeReason, BuildNodeResult, BuildOutput, BuildPlan, BuildRunCounts, BuildRunResult,
-    BuildRunSummary, BuildScriptSpec, CacheStatus, BUILD_GRAPH_SCHEMA_VERSION,
-    BUILD_RUN_SCHEMA_VERSION, DEFAULT_ENV_ALLOWLIST, DEFAULT_GLOB_EXCLUSIONS, MAX_OUTPUT_SIZE,
-    TARGET_ALIASES,
+    BuildRunSummary, BuildSandbox, BuildScriptSpec, CacheStatus, DtsSpec,
+    BUILD_GRAPH_SCHEMA_VERSION, BUILD_RUN_SCHEMA_VERSION, DEFAULT_ENV_ALLOWLIST,
+    DEFAULT_GLOB_EXCLUSIONS, MAX_OUTPUT_SIZE, TARGET_ALIASES,
 };
 pub use hash::{
-    expand_glob, hash_bytes, hash_env, hash_file, hash_file_with_ctx, hash_glob,
+    expand_glob, expand_input_glob, hash_bytes, hash_env, hash_file, hash_file_with_ctx, hash_glob,
     hash_glob_with_ctx, hash_graph, hash_graph_with_ctx, hash_input, hash_input_with_ctx,
     hash_input_with_deps, hash_input_with_deps_ctx, hash_node, hash_node_with_deps,
-    hash_node_with_deps_ctx, hash_string, normalize_path, FileHashCache, FileHashCacheStats,
-    FileHashKey, HashContext, HashError, HashResult, InMemoryFileHashCache,
+    hash_node_with_deps_ctx, hash_string, normalize_path, toolchain_fingerprint, FileHashCache,
+    FileHashCacheStats, FileHashKey, HashContext, HashError, HashResult, InMemoryFileHashCache,
+    PersistentFileHashCache, FILE_HASH_CACHE_SCHEMA_VERSION,
+};
+pub use logs::{create_log, latest_log, list_logs, node_log_dir, LOG_DIR, MAX_LOGS_PER_NODE};
+pub use outputs::{
+    clean_outputs, prune_stale_outputs, EmittedOutputs, EMITTED_OUTPUTS_SCHEMA_VERSION,
+};
+pub use remote::{
+    input_files, CommandExecution, ExecutionBackend, InputFile, LocalExecution, RemoteJob,
+    ScriptJob, REMOTE_JOB_SCHEMA_VERSION,
+};
+pub use signing::{CacheSigning, EntrySignature, SIGNING_KEY_ENV};
+pub use tools::{
+    node_tools, tool_path, version_changes, ToolVersionCache, MISSING_TOOL_VERSION, PROBED_TOOLS,
 };
 
 use crate::compiler::TranspileSpec;
 use crate::pkg::LOCKFILE_NAME;
+use serde::Deserialize;
 use std::collections::BTreeMap;
 use std::path::Path;
 
+/// Where the daemon keeps the most recent failed build result, relative to the
+/// project root (collected by `howth doctor --bundle`).
+pub const LAST_FAILED_BUILD_PATH: &str = ".howth/last-failed-build.json";
+
 /// Scripts that signal this is a typical JS project (for transpile auto-discovery).
 const JS_PROJECT_SIGNAL_SCRIPTS: &[&str] = &["build", "dev", "test", "lint", "typecheck"];
 
@@ -84,6 +112,12 @@ const TRANSPILABLE_EXTENSIONS: &[&str] = &["ts", "tsx", "js", "jsx"];
 /// - Is NOT added to defaults (scripts take precedence)
 ///
 /// To disable auto-discovery, set `HOWTH_NO_TRANSPILE=1` environment variable.
+///
+/// ## Script Sandboxing
+///
+/// Script nodes can be given a timeout and resource limits in the
+/// `howth.pipeline` section of package.json, keyed by script name
+/// (see [`BuildSandbox`]).
 pub fn build_graph_from_project(cwd: &Path) -> Result<BuildGraph, BuildGraphError> {
     let cwd_str = cwd.to_string_lossy().to_string();
     let mut graph = BuildGraph::new(&cwd_str);
@@ -132,6 +166,9 @@ pub fn build_graph_from_project(cwd: &Path) -> Result<BuildGraph, BuildGraphErro
         })
         .unwrap_or_default();
 
+    // Per-script sandbox settings ("howth": { "pipeline": { ... } })
+    let pipeline = read_pipeline_config(&pkg_json)?;
+
     // Common inputs for all nodes
     let pkg_json_input = BuildInput::file(pkg_json_path.to_string_lossy().to_string());
     let lockfile_input = {
@@ -186,6 +223,10 @@ pub fn build_graph_from_project(cwd: &Path) -> Result<BuildGraph, BuildGraphErro
             node.add_input(BuildInput::env((*env_key).to_string()));
         }
 
+        if let Some(config) = pipeline.get(name) {
+            config.apply(&mut node);
+        }
+
         graph.add_node(node);
     }
 
@@ -258,6 +299,56 @@ pub fn build_graph_from_project(cwd: &Path) -> Result<BuildGraph, BuildGraphErro
     Ok(graph)
 }
 
+/// Sandbox settings for one script node, read from the `howth.pipeline`
+/// section of package.json:
+///
+/// ```json
+/// { "howth": { "pipeline": { "build": { "timeoutMs": 60000, "restrictEnv": true } } } }
+/// ```
+#[derive(Debug, Default, Deserialize)]
+#[serde(rename_all = "camelCase", deny_unknown_fields)]
+struct PipelineNodeConfig {
+    timeout_ms: Option<u64>,
+    #[serde(default)]
+    restrict_env: bool,
+    max_output_bytes: Option<usize>,
+    cpu_seconds: Option<u64>,
+    memory_mb: Option<u64>,
+}
+
+impl PipelineNodeConfig {
+    /// Apply the settings to a script node.
+    fn apply(&self, node: &mut BuildNode) {
+        if let Some(command) = node.command.as_mut() {
+            command.timeout_ms = self.timeout_ms;
+        }
+        let sandbox = BuildSandbox {
+            restrict_env: self.restrict_env,
+            max_output_bytes: self.max_output_bytes,
+            cpu_seconds: self.cpu_seconds,
+            memory_mb: self.memory_mb,
+        };
+        if sandbox != BuildSandbox::default() {
+            node.sandbox = Some(sandbox);
+        }
+    }
+}
+
+/// Read the `howth.pipeline` section of package.json.
+fn read_pipeline_config(
+    pkg_json: &serde_json::Value,
+) -> Result<BTreeMap<String, PipelineNodeConfig>, BuildGraphError> {
+    let Some(pipeline) = pkg_json.get("howth").and_then(|h| h.get("pipeline")) else {
+        return Ok(BTreeMap::new());
+    };
+    serde_json::from_value(pipeline.clone()).map_err(|e| {
+        BuildGraphError::new(
+            codes::BUILD_PACKAGE_JSON_INVALID,
+            format!("Invalid howth.pipeline in package.json: {e}"),
+        )
+    })
+}
+
 /// Check if automatic typecheck discovery should be enabled (v3.2).
 ///
 /// Returns true if all conditions are met:
@@ -475,7 +566,7 @@ mod tests {
         let graph = build_graph_from_project(dir.path()).unwrap();
         assert_eq!(graph.nodes.len(), 2);
         // No "build" script, so no defaults
-        assert!(graph.defaults.is_empty());
+        assert_eq!(graph.defaults, Vec::<String>::new());
     }
 
     #[test]
@@ -537,6 +628,42 @@ mod tests {
         assert_eq!(meta.version, Some("1.2.3".to_string()));
     }
 
+    #[test]
+    fn test_build_graph_pipeline_config() {
+        let dir = tempdir().unwrap();
+        std::fs::write(
+            dir.path().join("package.json"),
+            r#"{
+                "scripts": {"build": "echo", "lint": "echo"},
+                "howth": {"pipeline": {"build": {"timeoutMs": 1000, "restrictEnv": true, "memoryMb": 512}}}
+            }"#,
+        )
+        .unwrap();
+
+        let graph = build_graph_from_project(dir.path()).unwrap();
+        let build = graph.get_node("script:build").unwrap();
+        assert_eq!(build.command.as_ref().unwrap().timeout_ms, Some(1000));
+        let sandbox = build.sandbox.as_ref().unwrap();
+        assert!(sandbox.restrict_env);
+        assert_eq!(sandbox.memory_mb, Some(512));
+
+        let lint = graph.get_node("script:lint").unwrap();
+        assert!(lint.sandbox.is_none());
+    }
+
+    #[test]
+    fn test_build_graph_pipeline_config_invalid() {
+        let dir = tempdir().unwrap();
+        std::fs::write(
+            dir.path().join("package.json"),
+            r#"{"scripts": {"build": "echo"}, "howth": {"pipeline": {"build": {"timeout": 5}}}}"#,
+        )
+        .unwrap();
+
+        let err = build_graph_from_project(dir.path()).unwrap_err();
+        assert_eq!(err.code, codes::BUILD_PACKAGE_JSON_INVALID);
+    }
+
     #[test]
     fn test_build_graph_deterministic_ordering() {
         let dir = tempdir().unwrap();
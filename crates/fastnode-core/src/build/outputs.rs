@@ -0,0 +1,352 @@
+//! Emitted output tracking, stale output pruning and output cleaning.
+//!
+//! Transpile nodes record the files they emit in a per-node manifest under
+//! `.howth/build/`. On the next successful run, files listed in the previous
+//! manifest but no longer emitted (because their source was deleted or
+//! renamed) are removed from the output directory. Files that were never
+//! emitted by howth are left alone.
+//!
+//! Batch transpile nodes also record a content hash per source file, so a
+//! rebuild only re-emits the files that changed since the last run.
+//!
+//! `.howth/` is excluded from build inputs and output fingerprints, so the
+//! manifest itself never affects cache keys.
+
+use super::fingerprint::normalize_output_path;
+use super::graph::BuildGraph;
+use fastnode_util::fs::{atomic_write, remove_within};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::fs;
+use std::io;
+use std::path::{Component, Path, PathBuf};
+
+/// Schema version for emitted output manifests.
+pub const EMITTED_OUTPUTS_SCHEMA_VERSION: u32 = 1;
+
+/// Directory (relative to the project root) holding emitted output manifests.
+const MANIFEST_DIR: &str = ".howth/build";
+
+/// Files emitted by a node during its last successful run.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct EmittedOutputs {
+    /// Schema version for format evolution.
+    pub schema_version: u32,
+    /// Emitted files, normalized and relative to the project root, sorted.
+    pub files: Vec<String>,
+    /// Content hash of each source file that was emitted, keyed by path
+    /// relative to the project root (batch transpile nodes only).
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub sources: BTreeMap<String, String>,
+    /// Hash of the transpile options and toolchain the sources were emitted
+    /// with. Source hashes are only trusted when this still matches.
+    #[serde(default, skip_serializing_if = "String::is_empty")]
+    pub options_key: String,
+}
+
+impl EmittedOutputs {
+    /// Create a manifest from absolute or root-relative paths.
+    #[must_use]
+    pub fn new(files: &[PathBuf], root: &Path) -> Self {
+        let mut files: Vec<String> = files
+            .iter()
+            .map(|path| normalize_output_path(path, root))
+            .collect();
+        files.sort();
+        files.dedup();
+        Self {
+            schema_version: EMITTED_OUTPUTS_SCHEMA_VERSION,
+            files,
+            sources: BTreeMap::new(),
+            options_key: String::new(),
+        }
+    }
+
+    /// Attach per-source content hashes and the options key they were
+    /// emitted with.
+    #[must_use]
+    pub fn with_sources(
+        mut self,
+        sources: BTreeMap<String, String>,
+        options_key: impl Into<String>,
+    ) -> Self {
+        self.sources = sources;
+        self.options_key = options_key.into();
+        self
+    }
+
+    /// Stored content hash for a source, if it was emitted with `options_key`.
+    #[must_use]
+    pub fn source_hash(&self, source: &str, options_key: &str) -> Option<&str> {
+        if self.options_key != options_key {
+            return None;
+        }
+        self.sources.get(source).map(String::as_str)
+    }
+
+    /// Load the manifest for a node, if one exists and is readable.
+    #[must_use]
+    pub fn load(root: &Path, node_id: &str) -> Option<Self> {
+        let content = fs::read_to_string(manifest_path(root, node_id)).ok()?;
+        let manifest: Self = serde_json::from_str(&content).ok()?;
+        (manifest.schema_version == EMITTED_OUTPUTS_SCHEMA_VERSION).then_some(manifest)
+    }
+
+    /// Write the manifest for a node.
+    ///
+    /// # Errors
+    /// Returns an error if the manifest cannot be written.
+    pub fn save(&self, root: &Path, node_id: &str) -> io::Result<()> {
+        let path = manifest_path(root, node_id);
+        if let Some(parent) = path.parent() {
+            fs::create_dir_all(parent)?;
+        }
+        let json = serde_json::to_string_pretty(self).map_err(io::Error::other)?;
+        atomic_write(&path, json.as_bytes())
+    }
+}
+
+/// Path of the emitted output manifest for a node.
+#[must_use]
+pub fn manifest_path(root: &Path, node_id: &str) -> PathBuf {
+    root.join(MANIFEST_DIR)
+        .join(format!("{}.json", node_file_stem(node_id)))
+}
+
+/// File name for per-node state: the node ID with everything but ASCII
+/// alphanumerics and `-` replaced by `_`.
+pub(crate) fn node_file_stem(node_id: &str) -> String {
+    node_id
+        .chars()
+        .map(|c| {
+            if c.is_ascii_alphanumeric() || c == '-' {
+                c
+            } else {
+                '_'
+            }
+        })
+        .collect()
+}
+
+/// Delete files from `previous` that are not in `current`.
+///
+/// Directories left empty by the deletion are removed too, up to (but not
+/// including) `output_dir`. Paths outside `output_dir` are never touched.
+/// Returns the pruned paths.
+#[must_use]
+pub fn prune_stale_outputs(
+    root: &Path,
+    output_dir: &Path,
+    previous: &EmittedOutputs,
+    current: &EmittedOutputs,
+) -> Vec<String> {
+    let mut pruned = Vec::new();
+
+    for file in &previous.files {
+        if current.files.binary_search(file).is_ok() || !is_safe_relative(file) {
+            continue;
+        }
+        let path = root.join(file);
+        if !path.starts_with(output_dir) || !path.is_file() {
+            continue;
+        }
+        if fs::remove_file(&path).is_ok() {
+            remove_empty_parents(&path, output_dir);
+            pruned.push(file.clone());
+        }
+    }
+
+    pruned
+}
+
+/// Remove empty directories above `path`, stopping at `stop`.
+fn remove_empty_parents(path: &Path, stop: &Path) {
+    let mut dir = path.parent();
+    while let Some(current) = dir {
+        if current == stop || !current.starts_with(stop) {
+            break;
+        }
+        // Fails (and stops) as soon as a directory is not empty.
+        if fs::remove_dir(current).is_err() {
+            break;
+        }
+        dir = current.parent();
+    }
+}
+
+/// Check that a relative path stays inside the project root.
+fn is_safe_relative(path: &str) -> bool {
+    let path = Path::new(path);
+    let mut depth = 0usize;
+    for component in path.components() {
+        match component {
+            Component::Normal(_) => depth += 1,
+            Component::CurDir => {}
+            _ => return false,
+        }
+    }
+    depth > 0
+}
+
+/// Check that an output path can be wiped: inside the root, not the root
+/// itself, and not a directory howth or git relies on.
+fn is_cleanable(path: &str) -> bool {
+    if !is_safe_relative(path) {
+        return false;
+    }
+    let first = Path::new(path)
+        .components()
+        .find(|c| matches!(c, Component::Normal(_)))
+        .map(|c| c.as_os_str().to_string_lossy().into_owned())
+        .unwrap_or_default();
+    !matches!(
+        first.as_str(),
+        "node_modules" | ".git" | "src" | "package.json"
+    )
+}
+
+/// Remove the declared `file` and `dir` outputs of every node in the graph,
+/// the output directories of batch transpile nodes, and the emitted output
+/// manifests.
+///
+/// Glob outputs and outputs that would escape the project root or point at
+/// sources, `node_modules` or `.git` are skipped. Returns the removed paths.
+///
+/// # Errors
+/// Returns an error if an existing output cannot be removed.
+pub fn clean_outputs(graph: &BuildGraph) -> io::Result<Vec<String>> {
+    let root = Path::new(&graph.cwd);
+    let mut targets: Vec<String> = Vec::new();
+    for node in &graph.nodes {
+        targets.extend(
+            node.outputs
+                .iter()
+                .filter(|output| output.kind == "file" || output.kind == "dir")
+                .map(|output| output.path.clone()),
+        );
+        // Batch transpile nodes declare a glob; their output directory is theirs.
+        if let Some(spec) = node.transpile.as_ref().filter(|spec| spec.is_batch()) {
+            targets.push(normalize_output_path(&spec.output_path, root));
+        }
+    }
+    targets.retain(|path| is_cleanable(path));
+    targets.sort_unstable();
+    targets.dedup();
+
+    let mut removed = Vec::new();
+    for target in targets {
+        let path = root.join(&target);
+        match fs::symlink_metadata(&path) {
+            Ok(_) => {}
+            Err(e) if e.kind() == io::ErrorKind::NotFound => continue,
+            Err(e) => return Err(e),
+        }
+        remove_within(root, &path)?;
+        removed.push(target);
+    }
+
+    let manifest_dir = root.join(MANIFEST_DIR);
+    if manifest_dir.is_dir() {
+        remove_within(root, &manifest_dir)?;
+    }
+
+    Ok(removed)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::build::graph::{BuildNode, BuildOutput};
+    use crate::compiler::TranspileSpec;
+    use tempfile::tempdir;
+
+    #[test]
+    fn test_manifest_roundtrip() {
+        let dir = tempdir().unwrap();
+        let root = dir.path();
+        let manifest = EmittedOutputs::new(&[root.join("dist/b.js"), root.join("dist/a.js")], root);
+        assert_eq!(manifest.files, vec!["dist/a.js", "dist/b.js"]);
+
+        manifest.save(root, "transpile").unwrap();
+        assert_eq!(EmittedOutputs::load(root, "transpile"), Some(manifest));
+        assert!(EmittedOutputs::load(root, "script:build").is_none());
+    }
+
+    #[test]
+    fn test_source_hash_requires_matching_options() {
+        let sources = BTreeMap::from([("src/a.ts".to_string(), "abc".to_string())]);
+        let manifest = EmittedOutputs::default().with_sources(sources, "opts");
+        assert_eq!(manifest.source_hash("src/a.ts", "opts"), Some("abc"));
+        assert_eq!(manifest.source_hash("src/a.ts", "other"), None);
+        assert_eq!(manifest.source_hash("src/b.ts", "opts"), None);
+    }
+
+    #[test]
+    fn test_prune_stale_outputs() {
+        let dir = tempdir().unwrap();
+        let root = dir.path();
+        let out = root.join("dist");
+        fs::create_dir_all(out.join("old")).unwrap();
+        fs::write(out.join("a.js"), "").unwrap();
+        fs::write(out.join("old/b.js"), "").unwrap();
+        fs::write(out.join("manual.txt"), "").unwrap();
+
+        let previous = EmittedOutputs::new(&[out.join("a.js"), out.join("old/b.js")], root);
+        let current = EmittedOutputs::new(&[out.join("a.js")], root);
+        let pruned = prune_stale_outputs(root, &out, &previous, &current);
+
+        assert_eq!(pruned, vec!["dist/old/b.js"]);
+        assert!(out.join("a.js").exists());
+        assert!(out.join("manual.txt").exists());
+        assert!(!out.join("old").exists());
+    }
+
+    #[test]
+    fn test_prune_ignores_paths_outside_output_dir() {
+        let dir = tempdir().unwrap();
+        let root = dir.path();
+        fs::write(root.join("keep.js"), "").unwrap();
+        let previous = EmittedOutputs {
+            schema_version: EMITTED_OUTPUTS_SCHEMA_VERSION,
+            files: vec!["keep.js".to_string(), "../escape.js".to_string()],
+            ..EmittedOutputs::default()
+        };
+        let pruned = prune_stale_outputs(
+            root,
+            &root.join("dist"),
+            &previous,
+            &EmittedOutputs::default(),
+        );
+        assert_eq!(pruned, Vec::<String>::new());
+        assert!(root.join("keep.js").exists());
+    }
+
+    #[test]
+    fn test_clean_outputs_skips_unsafe_paths() {
+        let dir = tempdir().unwrap();
+        let root = dir.path();
+        fs::create_dir_all(root.join("dist")).unwrap();
+        fs::write(root.join("dist/index.js"), "").unwrap();
+        fs::create_dir_all(root.join("src")).unwrap();
+
+        let mut graph = BuildGraph::new(root.to_string_lossy());
+        let mut node = BuildNode::script("build", "tsc");
+        node.outputs = vec![
+            BuildOutput::dir("dist"),
+            BuildOutput::dir("src"),
+            BuildOutput::dir("."),
+            BuildOutput::dir("../outside"),
+        ];
+        graph.add_node(node);
+
+        let spec = TranspileSpec::batch("src", "lib");
+        graph.add_node(BuildNode::transpile_batch(&spec));
+        fs::create_dir_all(root.join("lib")).unwrap();
+
+        let removed = clean_outputs(&graph).unwrap();
+        assert_eq!(removed, vec!["dist", "lib"]);
+        assert!(!root.join("dist").exists());
+        assert!(!root.join("lib").exists());
+        assert!(root.join("src").exists());
+    }
+}
@@ -0,0 +1,573 @@
+//! Execution backends for script nodes.
+//!
+//! Script nodes run through an [`ExecutionBackend`]. [`LocalExecution`] runs
+//! them on this machine under the node's sandbox and is the default.
+//! [`CommandExecution`] (`howth build --remote-exec <cmd>`) hands each node
+//! to an external executor instead: an SSH wrapper, a container runner or a
+//! build farm client.
+//!
+//! ## Executor protocol
+//!
+//! For every script node howth stages a job directory and runs the executor
+//! command from the project root with:
+//! - `HOWTH_REMOTE_JOB`: `job.json` describing the node (command, input
+//!   files with content hashes, declared outputs, allowlisted environment)
+//! - `HOWTH_REMOTE_UPLOAD`: the input files to send, laid out relative to
+//!   the project root
+//! - `HOWTH_REMOTE_OUTPUT`: an empty directory for the executor to fill with
+//!   the node's outputs, laid out the same way
+//! - `HOWTH_REMOTE_COMMAND`: the script command to run remotely
+//!
+//! Inputs are only staged when their content hash differs from the one this
+//! executor last received (recorded under `.howth/remote/`), so the remote
+//! side keeps its copy of unchanged files. Outputs are only copied back when
+//! a declared output covers them and they differ from the file on disk. The
+//! executor's exit code and output become the node's, and the usual output
+//! fingerprint is taken after the download.
+
+use super::exec::{run_script_with_env, ScriptOutput};
+use super::fingerprint::normalize_output_path;
+use super::graph::{BuildInput, BuildNode, BuildOutput, BuildSandbox, DEFAULT_GLOB_EXCLUSIONS};
+use super::hash::{
+    expand_glob, expand_input_glob, glob_matches, hash_file, hash_string, HashResult,
+    HASH_ALGORITHM,
+};
+use crate::deterministic::SOURCE_DATE_EPOCH_ENV;
+use fastnode_util::fs::atomic_write;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::ffi::OsStr;
+use std::fmt;
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+
+/// Schema version of `job.json`.
+pub const REMOTE_JOB_SCHEMA_VERSION: u32 = 1;
+
+/// Directory holding the per-executor upload records.
+const UPLOAD_RECORD_DIR: &str = ".howth/remote";
+
+/// A script node ready to run.
+#[derive(Debug, Clone, Copy)]
+pub struct ScriptJob<'a> {
+    /// The node.
+    pub node: &'a BuildNode,
+    /// Shell command to run.
+    pub command: &'a str,
+    /// Project root.
+    pub cwd: &'a Path,
+    /// Input hash of the node.
+    pub hash: &'a str,
+    /// Kill the script after this long.
+    pub timeout: Option<Duration>,
+    /// Sandbox policy.
+    pub sandbox: &'a BuildSandbox,
+    /// File receiving the full output of the run.
+    pub log: Option<&'a Path>,
+    /// `SOURCE_DATE_EPOCH` to export to the script.
+    pub source_date_epoch: Option<u64>,
+}
+
+impl ScriptJob<'_> {
+    /// `SOURCE_DATE_EPOCH` as an environment value, if set.
+    fn source_date_epoch_env(&self) -> Option<String> {
+        self.source_date_epoch.map(|epoch| epoch.to_string())
+    }
+}
+
+/// Runs script nodes.
+pub trait ExecutionBackend: Send + Sync + fmt::Debug {
+    /// Backend name, shown in node notes (e.g., "local", "remote").
+    fn name(&self) -> &'static str;
+
+    /// Run a script node to completion.
+    ///
+    /// # Errors
+    /// Returns an error if the script (or executor) cannot be started or
+    /// its inputs and outputs cannot be transferred.
+    fn execute(&self, job: &ScriptJob<'_>) -> io::Result<ScriptOutput>;
+}
+
+/// Runs scripts on this machine.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct LocalExecution;
+
+impl ExecutionBackend for LocalExecution {
+    fn name(&self) -> &'static str {
+        "local"
+    }
+
+    fn execute(&self, job: &ScriptJob<'_>) -> io::Result<ScriptOutput> {
+        let epoch = job.source_date_epoch_env();
+        let env: Vec<(&str, &OsStr)> = epoch
+            .iter()
+            .map(|epoch| (SOURCE_DATE_EPOCH_ENV, OsStr::new(epoch)))
+            .collect();
+        run_script_with_env(
+            job.command,
+            job.cwd,
+            job.timeout,
+            job.sandbox,
+            &job.node.env_allowlist,
+            &env,
+            job.log,
+        )
+    }
+}
+
+/// An input file of a node, relative to the project root.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct InputFile {
+    /// Path relative to the project root, with forward slashes.
+    pub path: String,
+    /// Content hash.
+    pub hash: String,
+    /// Whether the file is staged in this job's upload directory.
+    #[serde(default)]
+    pub upload: bool,
+}
+
+/// Contents of `job.json`.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RemoteJob {
+    /// Schema version ([`REMOTE_JOB_SCHEMA_VERSION`]).
+    pub schema_version: u32,
+    /// Node ID.
+    pub node: String,
+    /// Script command.
+    pub command: String,
+    /// Input hash of the node.
+    pub hash: String,
+    /// Hash algorithm of `hash` and the input hashes.
+    pub algorithm: String,
+    /// Input files under the project root.
+    pub inputs: Vec<InputFile>,
+    /// Declared outputs.
+    pub outputs: Vec<BuildOutput>,
+    /// Allowlisted environment variables that are set.
+    pub env: BTreeMap<String, String>,
+    /// Timeout in milliseconds.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub timeout_ms: Option<u64>,
+}
+
+/// List a node's input files under `cwd` with their content hashes.
+///
+/// Files, globs, directories and the lockfile are expanded the same way as
+/// for the input hash. Inputs outside the project root are skipped: the
+/// remote side is expected to provide them.
+///
+/// # Errors
+/// Returns an error if a glob cannot be expanded or a file cannot be read.
+pub fn input_files(node: &BuildNode, cwd: &Path) -> HashResult<Vec<InputFile>> {
+    let resolve = |path: &str| {
+        if Path::new(path).is_absolute() {
+            PathBuf::from(path)
+        } else {
+            cwd.join(path)
+        }
+    };
+
+    let mut paths = Vec::new();
+    for input in &node.inputs {
+        match input {
+            BuildInput::File { path, .. } | BuildInput::Lockfile { path, .. } => {
+                paths.push(resolve(path));
+            }
+            BuildInput::Glob { pattern, root, .. } => {
+                let root = resolve(root);
+                if root.is_dir() {
+                    paths.extend(expand_input_glob(pattern, &root, DEFAULT_GLOB_EXCLUSIONS)?);
+                }
+            }
+            BuildInput::Dir { path, .. } => {
+                let dir = resolve(path);
+                if dir.is_dir() {
+                    paths.extend(expand_input_glob("**/*", &dir, DEFAULT_GLOB_EXCLUSIONS)?);
+                }
+            }
+            BuildInput::Package { .. } | BuildInput::Env { .. } | BuildInput::Node { .. } => {}
+        }
+    }
+
+    let mut files = BTreeMap::new();
+    for path in paths {
+        if !path.is_file() || !path.starts_with(cwd) {
+            continue;
+        }
+        let rel = normalize_output_path(&path, cwd);
+        if let std::collections::btree_map::Entry::Vacant(entry) = files.entry(rel) {
+            entry.insert(hash_file(&path)?);
+        }
+    }
+
+    Ok(files
+        .into_iter()
+        .map(|(path, hash)| InputFile {
+            path,
+            hash,
+            upload: false,
+        })
+        .collect())
+}
+
+/// Runs scripts through an external executor command.
+#[derive(Debug, Clone)]
+pub struct CommandExecution {
+    command: String,
+}
+
+impl CommandExecution {
+    /// Create a backend that runs each script node with `command`.
+    #[must_use]
+    pub fn new(command: impl Into<String>) -> Self {
+        Self {
+            command: command.into(),
+        }
+    }
+
+    /// The executor command.
+    #[must_use]
+    pub fn command(&self) -> &str {
+        &self.command
+    }
+
+    /// File recording the input hashes this executor last received.
+    fn record_path(&self, cwd: &Path) -> PathBuf {
+        let key = &hash_string(&self.command)[..16];
+        cwd.join(UPLOAD_RECORD_DIR).join(format!("{key}.json"))
+    }
+
+    /// Stage the job directory, returning the job and the number of staged inputs.
+    fn stage(&self, job: &ScriptJob<'_>, dir: &Path) -> io::Result<(RemoteJob, usize)> {
+        let mut inputs = input_files(job.node, job.cwd).map_err(io::Error::other)?;
+        let record = load_record(&self.record_path(job.cwd));
+
+        let upload_dir = dir.join("upload");
+        fs::create_dir_all(&upload_dir)?;
+        fs::create_dir_all(dir.join("output"))?;
+
+        let mut staged = 0;
+        for input in &mut inputs {
+            if record.get(&input.path) == Some(&input.hash) {
+                continue;
+            }
+            let dest = upload_dir.join(&input.path);
+            if let Some(parent) = dest.parent() {
+                fs::create_dir_all(parent)?;
+            }
+            fs::copy(job.cwd.join(&input.path), dest)?;
+            input.upload = true;
+            staged += 1;
+        }
+
+        let env = job
+            .node
+            .env_allowlist
+            .iter()
+            .filter_map(|key| std::env::var(key).ok().map(|value| (key.clone(), value)))
+            .collect();
+
+        let remote = RemoteJob {
+            schema_version: REMOTE_JOB_SCHEMA_VERSION,
+            node: job.node.id.clone(),
+            command: job.command.to_string(),
+            hash: job.hash.to_string(),
+            algorithm: HASH_ALGORITHM.to_string(),
+            inputs,
+            outputs: job.node.outputs.clone(),
+            env,
+            timeout_ms: job
+                .timeout
+                .map(|t| u64::try_from(t.as_millis()).unwrap_or(u64::MAX)),
+        };
+        let json = serde_json::to_string_pretty(&remote).map_err(io::Error::other)?;
+        fs::write(dir.join("job.json"), json)?;
+        Ok((remote, staged))
+    }
+
+    /// Remember the input hashes the executor now holds.
+    fn save_record(&self, cwd: &Path, inputs: &[InputFile]) -> io::Result<()> {
+        let path = self.record_path(cwd);
+        let mut record = load_record(&path);
+        for input in inputs {
+            record.insert(input.path.clone(), input.hash.clone());
+        }
+        if let Some(parent) = path.parent() {
+            fs::create_dir_all(parent)?;
+        }
+        let json = serde_json::to_string(&record).map_err(io::Error::other)?;
+        atomic_write(&path, json.as_bytes())
+    }
+}
+
+impl ExecutionBackend for CommandExecution {
+    fn name(&self) -> &'static str {
+        "remote"
+    }
+
+    fn execute(&self, job: &ScriptJob<'_>) -> io::Result<ScriptOutput> {
+        let dir = tempfile::Builder::new().prefix("howth-remote-").tempdir()?;
+        let (remote, staged) = self.stage(job, dir.path())?;
+
+        let job_file = dir.path().join("job.json");
+        let upload_dir = dir.path().join("upload");
+        let output_dir = dir.path().join("output");
+        let epoch = job.source_date_epoch_env();
+        let mut env: Vec<(&str, &OsStr)> = vec![
+            ("HOWTH_REMOTE_JOB", job_file.as_os_str()),
+            ("HOWTH_REMOTE_UPLOAD", upload_dir.as_os_str()),
+            ("HOWTH_REMOTE_OUTPUT", output_dir.as_os_str()),
+            ("HOWTH_REMOTE_COMMAND", OsStr::new(job.command)),
+        ];
+        if let Some(epoch) = &epoch {
+            env.push((SOURCE_DATE_EPOCH_ENV, OsStr::new(epoch)));
+        }
+        let mut output = run_script_with_env(
+            &self.command,
+            job.cwd,
+            job.timeout,
+            job.sandbox,
+            &job.node.env_allowlist,
+            &env,
+            job.log,
+        )?;
+        if output.exit_code != 0 {
+            return Ok(output);
+        }
+
+        self.save_record(job.cwd, &remote.inputs)?;
+        let (downloaded, ignored) = download_outputs(&output_dir, job.cwd, &job.node.outputs)?;
+
+        output.notes.push(format!(
+            "remote: uploaded {staged} of {} inputs, downloaded {downloaded} outputs",
+            remote.inputs.len()
+        ));
+        if ignored > 0 {
+            output.notes.push(format!(
+                "remote: ignored {ignored} files outside the declared outputs"
+            ));
+        }
+        Ok(output)
+    }
+}
+
+/// Load an upload record, starting empty if it is missing or invalid.
+fn load_record(path: &Path) -> BTreeMap<String, String> {
+    fs::read_to_string(path)
+        .ok()
+        .and_then(|content| serde_json::from_str(&content).ok())
+        .unwrap_or_default()
+}
+
+/// Copy declared outputs that changed from `output_dir` into `cwd`.
+///
+/// Returns how many files were copied and how many were ignored for not
+/// being covered by a declared output.
+fn download_outputs(
+    output_dir: &Path,
+    cwd: &Path,
+    outputs: &[BuildOutput],
+) -> io::Result<(usize, usize)> {
+    let files = expand_glob("**/*", output_dir, &[]).map_err(io::Error::other)?;
+
+    let mut downloaded = 0;
+    let mut ignored = 0;
+    for file in files {
+        let rel = normalize_output_path(&file, output_dir);
+        if !outputs.iter().any(|output| is_declared(output, &rel)) {
+            ignored += 1;
+            continue;
+        }
+
+        let dest = cwd.join(&rel);
+        if dest.is_file() && hash_file(&dest).ok() == hash_file(&file).ok() {
+            continue;
+        }
+        if let Some(parent) = dest.parent() {
+            fs::create_dir_all(parent)?;
+        }
+        fs::copy(&file, &dest)?;
+        downloaded += 1;
+    }
+    Ok((downloaded, ignored))
+}
+
+/// Whether a declared output covers the root-relative path `rel`.
+fn is_declared(output: &BuildOutput, rel: &str) -> bool {
+    let path = output.path.trim_start_matches("./").trim_end_matches('/');
+    match output.kind.as_str() {
+        "file" => rel == path,
+        "dir" => rel
+            .strip_prefix(path)
+            .is_some_and(|rest| rest.starts_with('/')),
+        "glob" => glob_matches(path, rel),
+        _ => false,
+    }
+}
+
+#[cfg(all(test, unix))]
+mod tests {
+    use super::*;
+
+    fn node(dir: &Path) -> BuildNode {
+        let mut node = BuildNode::script("build", "cp src/a.txt dist/a.txt");
+        node.inputs = vec![BuildInput::Glob {
+            pattern: "src/**/*".to_string(),
+            root: dir.to_string_lossy().into_owned(),
+            optional: false,
+        }];
+        node.outputs = vec![BuildOutput::dir("dist")];
+        node
+    }
+
+    /// Executor that runs the job in a "remote" directory beside the project.
+    fn executor(remote: &Path) -> CommandExecution {
+        CommandExecution::new(format!(
+            "mkdir -p {r} && cp -R \"$HOWTH_REMOTE_UPLOAD\"/. {r}/ && \
+             ls \"$HOWTH_REMOTE_UPLOAD\" > {r}/../uploaded.txt && \
+             (cd {r} && mkdir -p dist && sh -c \"$HOWTH_REMOTE_COMMAND\") && \
+             cp -R {r}/dist \"$HOWTH_REMOTE_OUTPUT\"/ && echo stray > \"$HOWTH_REMOTE_OUTPUT\"/stray.txt",
+            r = remote.display()
+        ))
+    }
+
+    fn run(backend: &dyn ExecutionBackend, node: &BuildNode, cwd: &Path) -> ScriptOutput {
+        let job = ScriptJob {
+            node,
+            command: &node.script.as_ref().unwrap().command,
+            cwd,
+            hash: "hash",
+            timeout: None,
+            sandbox: &BuildSandbox::default(),
+            log: None,
+            source_date_epoch: None,
+        };
+        backend.execute(&job).unwrap()
+    }
+
+    #[test]
+    fn test_input_files_are_relative_and_hashed() {
+        let dir = tempfile::tempdir().unwrap();
+        fs::create_dir_all(dir.path().join("src/lib")).unwrap();
+        fs::write(dir.path().join("src/a.txt"), "a").unwrap();
+        fs::write(dir.path().join("src/lib/b.txt"), "b").unwrap();
+
+        let mut node = node(dir.path());
+        node.inputs.push(BuildInput::File {
+            path: "src/a.txt".to_string(),
+            optional: false,
+        });
+        let files = input_files(&node, dir.path()).unwrap();
+
+        let paths: Vec<_> = files.iter().map(|f| f.path.as_str()).collect();
+        assert_eq!(paths, ["src/a.txt", "src/lib/b.txt"]);
+        assert_eq!(
+            files[0].hash,
+            hash_file(&dir.path().join("src/a.txt")).unwrap()
+        );
+    }
+
+    #[test]
+    fn test_local_execution_runs_in_cwd() {
+        let dir = tempfile::tempdir().unwrap();
+        fs::create_dir_all(dir.path().join("src")).unwrap();
+        fs::create_dir_all(dir.path().join("dist")).unwrap();
+        fs::write(dir.path().join("src/a.txt"), "a").unwrap();
+
+        let node = node(dir.path());
+        let output = run(&LocalExecution, &node, dir.path());
+        assert_eq!(output.exit_code, 0);
+        assert_eq!(
+            fs::read_to_string(dir.path().join("dist/a.txt")).unwrap(),
+            "a"
+        );
+    }
+
+    #[test]
+    fn test_source_date_epoch_is_exported() {
+        let dir = tempfile::tempdir().unwrap();
+        let node = BuildNode::script("build", "printf %s \"$SOURCE_DATE_EPOCH\"");
+        let job = ScriptJob {
+            node: &node,
+            command: &node.script.as_ref().unwrap().command,
+            cwd: dir.path(),
+            hash: "hash",
+            timeout: None,
+            sandbox: &BuildSandbox::default(),
+            log: None,
+            source_date_epoch: Some(1_700_000_000),
+        };
+        let output = LocalExecution.execute(&job).unwrap();
+        assert_eq!(output.stdout, "1700000000");
+    }
+
+    #[test]
+    fn test_command_execution_uploads_changed_inputs_and_downloads_outputs() {
+        let root = tempfile::tempdir().unwrap();
+        let project = root.path().join("project");
+        let remote = root.path().join("remote");
+        fs::create_dir_all(project.join("src")).unwrap();
+        fs::write(project.join("src/a.txt"), "one").unwrap();
+
+        let node = node(&project);
+        let backend = executor(&remote);
+
+        let output = run(&backend, &node, &project);
+        assert_eq!(output.exit_code, 0, "{}", output.stderr);
+        assert_eq!(
+            fs::read_to_string(project.join("dist/a.txt")).unwrap(),
+            "one"
+        );
+        assert!(!project.join("stray.txt").exists());
+        assert_eq!(
+            output.notes,
+            [
+                "remote: uploaded 1 of 1 inputs, downloaded 1 outputs",
+                "remote: ignored 1 files outside the declared outputs"
+            ]
+        );
+
+        // Nothing changed: nothing is uploaded or downloaded
+        let output = run(&backend, &node, &project);
+        assert!(output.notes[0].contains("uploaded 0 of 1 inputs, downloaded 0 outputs"));
+        let uploaded = fs::read_to_string(root.path().join("uploaded.txt")).unwrap();
+        assert_eq!(uploaded, "");
+
+        fs::write(project.join("src/a.txt"), "two").unwrap();
+        let output = run(&backend, &node, &project);
+        assert!(output.notes[0].contains("uploaded 1 of 1 inputs, downloaded 1 outputs"));
+        assert_eq!(
+            fs::read_to_string(project.join("dist/a.txt")).unwrap(),
+            "two"
+        );
+    }
+
+    #[test]
+    fn test_command_execution_failure_records_no_uploads() {
+        let dir = tempfile::tempdir().unwrap();
+        fs::create_dir_all(dir.path().join("src")).unwrap();
+        fs::write(dir.path().join("src/a.txt"), "a").unwrap();
+
+        let node = node(dir.path());
+        let backend = CommandExecution::new("echo unreachable >&2; exit 255");
+        let output = run(&backend, &node, dir.path());
+        assert_eq!(output.exit_code, 255);
+        assert_eq!(output.stderr, "unreachable");
+        assert!(!backend.record_path(dir.path()).exists());
+    }
+
+    #[test]
+    fn test_is_declared() {
+        assert!(is_declared(&BuildOutput::file("./dist/a.js"), "dist/a.js"));
+        assert!(is_declared(&BuildOutput::dir("dist/"), "dist/sub/a.js"));
+        assert!(!is_declared(&BuildOutput::dir("dist"), "distant/a.js"));
+        assert!(is_declared(
+            &BuildOutput::glob("dist/**/*.js"),
+            "dist/x/a.js"
+        ));
+        assert!(!is_declared(&BuildOutput::glob("dist/**/*.js"), "src/a.js"));
+    }
+}
@@ -0,0 +1,302 @@
+//! Signed entries for build caches shared between machines.
+//!
+//! The persistent build cache (`.howth/cache/build-nodes.json`) is often
+//! restored from a shared location, such as a CI cache, together with the
+//! outputs it describes. Signing its entries lets a consumer trust only
+//! entries written by the holder of a known key: an entry whose signature
+//! is missing or doesn't verify is dropped and reported with
+//! [`BUILD_CACHE_SIGNATURE_INVALID`], so the node is rebuilt instead of
+//! trusting artifacts that may have been tampered with. The signature
+//! covers the input hash, status and output fingerprint, so replacing the
+//! outputs and fixing up the fingerprint invalidates it too.
+//!
+//! Configured under `howth.buildCache` in the nearest `package.json` that
+//! has it, with paths relative to that `package.json`:
+//!
+//! ```json
+//! {
+//!   "howth": {
+//!     "buildCache": {
+//!       "signingKey": ".keys/build-cache.key",
+//!       "trustedKeys": ["keys/ci.pub", "<64 hex digits>"]
+//!     }
+//!   }
+//! }
+//! ```
+//!
+//! `HOWTH_BUILD_CACHE_SIGNING_KEY` (a path) overrides `signingKey`, so CI
+//! can sign with a key kept out of the repository. The signing key's own
+//! public key is always trusted. Keys are created and read with
+//! [`fastnode_util::sign`].
+
+use super::codes::{BUILD_CACHE_KEY_INVALID, BUILD_CACHE_SIGNATURE_INVALID};
+use super::exec::CacheEntry;
+use super::graph::BuildErrorInfo;
+use fastnode_util::sign::{PublicKey, SigningKey};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::fmt::Write;
+use std::path::Path;
+
+/// Environment variable holding the path of the signing key.
+pub const SIGNING_KEY_ENV: &str = "HOWTH_BUILD_CACHE_SIGNING_KEY";
+
+/// Domain separator for signed cache entries.
+const PAYLOAD_PREFIX: &str = "howth-build-cache-entry-v1";
+
+/// Signature of one cache entry.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct EntrySignature {
+    /// [`PublicKey::key_id`] of the signing key.
+    pub key_id: String,
+    /// Hex Ed25519 signature.
+    pub signature: String,
+}
+
+/// Keys used to sign cache entries and to verify them.
+#[derive(Debug, Clone, Default)]
+pub struct CacheSigning {
+    signer: Option<SigningKey>,
+    trusted: Vec<PublicKey>,
+}
+
+impl CacheSigning {
+    /// Sign with `signer` (if any) and trust `trusted` plus the signer's
+    /// own public key.
+    #[must_use]
+    pub fn new(signer: Option<SigningKey>, mut trusted: Vec<PublicKey>) -> Self {
+        if let Some(public) = signer.as_ref().map(SigningKey::public_key) {
+            if !trusted.contains(&public) {
+                trusted.push(public);
+            }
+        }
+        Self { signer, trusted }
+    }
+
+    /// Load the keys configured for the project at `cwd`.
+    ///
+    /// # Errors
+    /// Returns [`BUILD_CACHE_KEY_INVALID`] if a configured key cannot be read.
+    pub fn load(cwd: &Path) -> Result<Self, BuildErrorInfo> {
+        let config = cwd.ancestors().find_map(|dir| {
+            let content = std::fs::read_to_string(dir.join("package.json")).ok()?;
+            let pkg: Value = serde_json::from_str(&content).ok()?;
+            Some((
+                dir.to_path_buf(),
+                pkg.get("howth")?.get("buildCache")?.clone(),
+            ))
+        });
+        let env_key = std::env::var_os(SIGNING_KEY_ENV).filter(|value| !value.is_empty());
+        if config.is_none() && env_key.is_none() {
+            return Ok(Self::default());
+        }
+        let (root, config) = config.unwrap_or_else(|| (cwd.to_path_buf(), Value::Null));
+
+        let key_error = |what: &str, e: std::io::Error| {
+            BuildErrorInfo::new(
+                BUILD_CACHE_KEY_INVALID,
+                format!("Failed to read build cache {what}: {e}"),
+            )
+        };
+        let signing_path = env_key.map(Into::into).or_else(|| {
+            config
+                .get("signingKey")
+                .and_then(Value::as_str)
+                .map(|path| root.join(path))
+        });
+        let signer = signing_path
+            .map(|path| {
+                SigningKey::read(&path)
+                    .map_err(|e| key_error(&format!("signing key {}", path.display()), e))
+            })
+            .transpose()?;
+
+        let mut trusted = Vec::new();
+        for key in config
+            .get("trustedKeys")
+            .and_then(Value::as_array)
+            .into_iter()
+            .flatten()
+            .filter_map(Value::as_str)
+        {
+            let public = PublicKey::from_hex(key)
+                .or_else(|_| PublicKey::read(&root.join(key)))
+                .map_err(|e| key_error(&format!("trusted key {key}"), e))?;
+            trusted.push(public);
+        }
+
+        Ok(Self::new(signer, trusted))
+    }
+
+    /// Whether entries are signed when written.
+    #[must_use]
+    pub fn signs(&self) -> bool {
+        self.signer.is_some()
+    }
+
+    /// Whether entries must carry a trusted signature to be used.
+    #[must_use]
+    pub fn verifies(&self) -> bool {
+        !self.trusted.is_empty()
+    }
+
+    /// Sign the entry for `node_id`, if a signing key is configured.
+    #[must_use]
+    pub fn sign(&self, node_id: &str, entry: &CacheEntry) -> Option<EntrySignature> {
+        let signer = self.signer.as_ref()?;
+        Some(EntrySignature {
+            key_id: signer.public_key().key_id(),
+            signature: signer.sign(&payload(node_id, entry)),
+        })
+    }
+
+    /// Check the entry for `node_id` against the trusted keys.
+    ///
+    /// # Errors
+    /// Returns [`BUILD_CACHE_SIGNATURE_INVALID`] if the signature is
+    /// missing, made with an untrusted key, or doesn't match the entry.
+    pub fn verify(
+        &self,
+        node_id: &str,
+        entry: &CacheEntry,
+        signature: Option<&EntrySignature>,
+    ) -> Result<(), BuildErrorInfo> {
+        let reject = |reason: String| {
+            Err(BuildErrorInfo::new(
+                BUILD_CACHE_SIGNATURE_INVALID,
+                format!("Rejected cache entry for {node_id}: {reason}"),
+            ))
+        };
+        let Some(signature) = signature else {
+            return reject("entry is not signed".to_string());
+        };
+        let Some(key) = self
+            .trusted
+            .iter()
+            .find(|key| key.key_id() == signature.key_id)
+        else {
+            return reject(format!("signed by untrusted key {}", signature.key_id));
+        };
+        if key.verify(&payload(node_id, entry), &signature.signature) {
+            Ok(())
+        } else {
+            reject("signature does not match (the entry or its outputs were modified)".to_string())
+        }
+    }
+}
+
+/// Canonical bytes signed for an entry.
+fn payload(node_id: &str, entry: &CacheEntry) -> Vec<u8> {
+    let mut payload = format!(
+        "{PAYLOAD_PREFIX}\0{node_id}\0{}\0{}\0",
+        entry.hash, entry.ok
+    );
+    if let Some(fingerprint) = &entry.fingerprint {
+        let _ = write!(
+            payload,
+            "{}\0{}\0{}\0",
+            fingerprint.hash, fingerprint.output_count, fingerprint.total_size
+        );
+    }
+    payload.push('\0');
+    for (tool, version) in &entry.tools {
+        let _ = write!(payload, "{tool}={version}\0");
+    }
+    payload.into_bytes()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::build::OutputFingerprint;
+
+    fn entry() -> CacheEntry {
+        CacheEntry::with_fingerprint(
+            "abc",
+            true,
+            Some(OutputFingerprint {
+                schema_version: 1,
+                hash: "blake3:1234".to_string(),
+                output_count: 2,
+                total_size: 10,
+            }),
+        )
+    }
+
+    #[test]
+    fn test_sign_and_verify_entries() {
+        let key = SigningKey::generate().unwrap();
+        let signing = CacheSigning::new(Some(key.clone()), Vec::new());
+        assert!(signing.signs() && signing.verifies());
+
+        let entry = entry();
+        let signature = signing.sign("script:build", &entry).unwrap();
+        assert!(signing
+            .verify("script:build", &entry, Some(&signature))
+            .is_ok());
+
+        // A consumer that only trusts the public key
+        let consumer = CacheSigning::new(None, vec![key.public_key()]);
+        assert!(!consumer.signs());
+        assert_eq!(consumer.sign("script:build", &entry), None);
+        assert!(consumer
+            .verify("script:build", &entry, Some(&signature))
+            .is_ok());
+
+        let mut tampered = entry.clone();
+        tampered.fingerprint.as_mut().unwrap().hash = "blake3:5678".to_string();
+        let err = consumer
+            .verify("script:build", &tampered, Some(&signature))
+            .unwrap_err();
+        assert_eq!(err.code, BUILD_CACHE_SIGNATURE_INVALID);
+        assert!(consumer
+            .verify("script:other", &entry, Some(&signature))
+            .is_err());
+        assert!(consumer.verify("script:build", &entry, None).is_err());
+    }
+
+    #[test]
+    fn test_untrusted_key_is_rejected() {
+        let signing = CacheSigning::new(Some(SigningKey::generate().unwrap()), Vec::new());
+        let other = CacheSigning::new(None, vec![SigningKey::generate().unwrap().public_key()]);
+        let entry = entry();
+        let signature = signing.sign("n", &entry).unwrap();
+        let err = other.verify("n", &entry, Some(&signature)).unwrap_err();
+        assert!(err.message.contains("untrusted key"), "{}", err.message);
+    }
+
+    #[test]
+    fn test_load_from_package_json() {
+        let dir = tempfile::tempdir().unwrap();
+        let key = SigningKey::generate().unwrap();
+        std::fs::create_dir_all(dir.path().join("keys")).unwrap();
+        key.write(&dir.path().join("keys/cache.key")).unwrap();
+        let trusted = SigningKey::generate().unwrap().public_key();
+        std::fs::write(
+            dir.path().join("package.json"),
+            format!(
+                r#"{{"howth": {{"buildCache": {{"signingKey": "keys/cache.key", "trustedKeys": ["{}"]}}}}}}"#,
+                trusted.to_hex()
+            ),
+        )
+        .unwrap();
+        std::fs::create_dir_all(dir.path().join("packages/app")).unwrap();
+
+        let signing = CacheSigning::load(&dir.path().join("packages/app")).unwrap();
+        assert!(signing.signs());
+        assert_eq!(signing.trusted, vec![trusted, key.public_key()]);
+
+        std::fs::write(
+            dir.path().join("package.json"),
+            r#"{"howth": {"buildCache": {"signingKey": "missing.key"}}}"#,
+        )
+        .unwrap();
+        let err = CacheSigning::load(dir.path()).unwrap_err();
+        assert_eq!(err.code, BUILD_CACHE_KEY_INVALID);
+
+        // Nothing configured
+        let empty = tempfile::tempdir().unwrap();
+        let none = CacheSigning::load(empty.path()).unwrap();
+        assert!(!none.signs() && !none.verifies());
+    }
+}
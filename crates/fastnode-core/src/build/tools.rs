@@ -0,0 +1,323 @@
+//! Versions of external tools that build nodes run.
+//!
+//! A script that calls `tsc` or `node` produces different outputs when the
+//! tool is upgraded even though no input file changed, so the version of
+//! every tool a node uses is part of its hash. Versions are probed with
+//! `<tool> --version` and cached per binary path, size and modification
+//! time, so a long-running daemon notices an upgrade without re-running
+//! every tool on every build.
+//!
+//! ## Detection
+//!
+//! - Script nodes: tools run as a command in the script (`tsc -p .`,
+//!   `npx tsc`, `node_modules/.bin/tsc`, `NODE_ENV=production node build.js`)
+//! - Typecheck and dts nodes: `tsc`
+//!
+//! A project-local binary (`node_modules/.bin/<tool>`) is preferred over
+//! `PATH`, matching how package scripts resolve commands.
+
+use super::graph::{BuildNode, BuildNodeKind};
+use std::collections::{BTreeMap, HashMap};
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::process::{Command, Stdio};
+use std::sync::{OnceLock, RwLock};
+use std::time::UNIX_EPOCH;
+
+/// Tools whose version is mixed into the hash of nodes that use them.
+pub const PROBED_TOOLS: &[&str] = &["node", "tsc", "swc"];
+
+/// Version recorded for a tool that could not be run.
+pub const MISSING_TOOL_VERSION: &str = "missing";
+
+/// Cache of probed tool versions, keyed by the binary that was run.
+#[derive(Debug, Default)]
+pub struct ToolVersionCache {
+    versions: RwLock<HashMap<String, Option<String>>>,
+}
+
+impl ToolVersionCache {
+    /// Create an empty cache.
+    #[must_use]
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Create a cache with known versions for tools that are not installed
+    /// in the project, so they are never probed. `None` marks a tool as
+    /// missing.
+    #[must_use]
+    pub fn with_versions<'a>(
+        versions: impl IntoIterator<Item = (&'a str, Option<&'a str>)>,
+    ) -> Self {
+        let versions = versions
+            .into_iter()
+            .map(|(tool, version)| (tool.to_string(), version.map(str::to_string)))
+            .collect();
+        Self {
+            versions: RwLock::new(versions),
+        }
+    }
+
+    /// Process-wide cache used by hash contexts that don't provide one.
+    #[must_use]
+    pub fn global() -> &'static Self {
+        static CACHE: OnceLock<ToolVersionCache> = OnceLock::new();
+        CACHE.get_or_init(Self::new)
+    }
+
+    /// Version of `tool` as seen from `cwd`, probing it on first use.
+    ///
+    /// # Panics
+    /// Panics if the versions lock is poisoned.
+    #[must_use]
+    pub fn version(&self, tool: &str, cwd: &Path) -> Option<String> {
+        // Presets cover every binary of a tool except a project-local one
+        let local = local_binary(tool, cwd);
+        if local.is_none() {
+            if let Some(version) = self.versions.read().unwrap().get(tool) {
+                return version.clone();
+            }
+        }
+
+        let program = local.or_else(|| find_on_path(tool));
+        let key = program
+            .as_deref()
+            .map_or_else(|| tool.to_string(), binary_key);
+        if let Some(version) = self.versions.read().unwrap().get(&key) {
+            return version.clone();
+        }
+        let version = program.as_deref().and_then(probe_version);
+        self.versions.write().unwrap().insert(key, version.clone());
+        version
+    }
+
+    /// Versions of the tools `node` uses, keyed by tool name.
+    #[must_use]
+    pub fn node_versions(&self, node: &BuildNode, cwd: &Path) -> BTreeMap<String, String> {
+        node_tools(node)
+            .into_iter()
+            .map(|tool| {
+                let version = self
+                    .version(tool, cwd)
+                    .unwrap_or_else(|| MISSING_TOOL_VERSION.to_string());
+                (tool.to_string(), version)
+            })
+            .collect()
+    }
+}
+
+/// Describe tools whose version differs from the previous build, e.g.
+/// `node version changed: v20.11.0 -> v22.2.0`.
+///
+/// Tools that were not recorded before are not reported.
+#[must_use]
+pub fn version_changes(
+    previous: &BTreeMap<String, String>,
+    current: &BTreeMap<String, String>,
+) -> Vec<String> {
+    current
+        .iter()
+        .filter_map(|(tool, version)| {
+            let old = previous.get(tool).filter(|old| *old != version)?;
+            Some(format!("{tool} version changed: {old} -> {version}"))
+        })
+        .collect()
+}
+
+/// Tools a node runs (see the module docs), sorted and deduplicated.
+#[must_use]
+pub fn node_tools(node: &BuildNode) -> Vec<&'static str> {
+    match node.kind {
+        BuildNodeKind::Typecheck | BuildNodeKind::Dts => vec!["tsc"],
+        _ => node
+            .script
+            .as_ref()
+            .map(|script| command_tools(&script.command))
+            .unwrap_or_default(),
+    }
+}
+
+/// Words that run the command after them (`npx tsc`, `cross-env X=1 node`).
+const COMMAND_WRAPPERS: &[&str] = &[
+    "npx",
+    "pnpx",
+    "bunx",
+    "pnpm",
+    "yarn",
+    "exec",
+    "env",
+    "cross-env",
+    "time",
+];
+
+/// Tools run as the command of some segment of a shell command.
+fn command_tools(command: &str) -> Vec<&'static str> {
+    let mut tools: Vec<&'static str> = command
+        .split([';', '&', '|', '(', ')', '`', '\n'])
+        .filter_map(|segment| {
+            let program = segment.split_whitespace().find(|word| {
+                !(word.contains('=') || word.starts_with('-') || COMMAND_WRAPPERS.contains(word))
+            })?;
+            let name = program.rsplit(['/', '\\']).next().unwrap_or(program);
+            let name = name
+                .strip_suffix(".cmd")
+                .or_else(|| name.strip_suffix(".exe"))
+                .unwrap_or(name);
+            PROBED_TOOLS.iter().copied().find(|tool| *tool == name)
+        })
+        .collect();
+    tools.sort_unstable();
+    tools.dedup();
+    tools
+}
+
+/// The binary `tool` resolves to from `cwd`: the project-local one if
+/// installed, otherwise the one on `PATH`.
+#[must_use]
+pub fn tool_path(tool: &str, cwd: &Path) -> Option<PathBuf> {
+    local_binary(tool, cwd).or_else(|| find_on_path(tool))
+}
+
+/// The project-local binary for `tool`, if installed.
+fn local_binary(tool: &str, cwd: &Path) -> Option<PathBuf> {
+    let bin = cwd.join("node_modules").join(".bin");
+    let local = if cfg!(windows) {
+        bin.join(format!("{tool}.cmd"))
+    } else {
+        bin.join(tool)
+    };
+    local.is_file().then_some(local)
+}
+
+/// The binary `PATH` resolves `tool` to.
+fn find_on_path(tool: &str) -> Option<PathBuf> {
+    let names: Vec<String> = if cfg!(windows) {
+        vec![format!("{tool}.exe"), format!("{tool}.cmd")]
+    } else {
+        vec![tool.to_string()]
+    };
+    std::env::split_paths(&std::env::var_os("PATH")?)
+        .flat_map(|dir| names.iter().map(move |name| dir.join(name)))
+        .find(|candidate| candidate.is_file())
+}
+
+/// Cache key for a binary: its path plus the size and modification time of
+/// the file it points to, so reinstalling it in place changes the key.
+fn binary_key(program: &Path) -> String {
+    let (len, modified) = fs::metadata(program).map_or((0, 0), |meta| {
+        let modified = meta
+            .modified()
+            .ok()
+            .and_then(|time| time.duration_since(UNIX_EPOCH).ok())
+            .map_or(0, |duration| duration.as_nanos());
+        (meta.len(), modified)
+    });
+    format!("{}\0{len}\0{modified}", program.display())
+}
+
+/// Run `<program> --version` and return its trimmed output.
+fn probe_version(program: &Path) -> Option<String> {
+    let output = Command::new(program)
+        .arg("--version")
+        .stdin(Stdio::null())
+        .stderr(Stdio::null())
+        .output()
+        .ok()?;
+    if !output.status.success() {
+        return None;
+    }
+    let version = String::from_utf8_lossy(&output.stdout).trim().to_string();
+    (!version.is_empty()).then_some(version)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::build::DtsSpec;
+
+    #[test]
+    fn test_command_tools() {
+        assert_eq!(
+            command_tools("tsc -p . && node dist/cli.js"),
+            ["node", "tsc"]
+        );
+        assert_eq!(command_tools("npx tsc --noEmit"), ["tsc"]);
+        assert_eq!(command_tools("./node_modules/.bin/swc src -d lib"), ["swc"]);
+        assert_eq!(
+            command_tools("vite build --outDir node"),
+            Vec::<&str>::new()
+        );
+        assert_eq!(command_tools("echo tscx"), Vec::<&str>::new());
+        assert_eq!(command_tools("NODE_ENV=production node build.js"), ["node"]);
+        assert_eq!(command_tools("pnpm exec tsc; echo done"), ["tsc"]);
+    }
+
+    #[test]
+    fn test_node_tools() {
+        let script = BuildNode::script("build", "tsc && node post.js");
+        assert_eq!(node_tools(&script), ["node", "tsc"]);
+
+        let dts = BuildNode::dts(DtsSpec {
+            entries: vec!["src/index.ts".to_string()],
+            out_dir: "dist/types".to_string(),
+            tsconfig: None,
+        });
+        assert_eq!(node_tools(&dts), ["tsc"]);
+    }
+
+    #[test]
+    fn test_preset_and_missing_versions() {
+        let dir = tempfile::tempdir().unwrap();
+        let cache = ToolVersionCache::with_versions([("node", Some("v20.0.0")), ("tsc", None)]);
+        let node = BuildNode::script("build", "tsc && node post.js");
+
+        let versions = cache.node_versions(&node, dir.path());
+        assert_eq!(versions["node"], "v20.0.0");
+        assert_eq!(versions["tsc"], MISSING_TOOL_VERSION);
+    }
+
+    #[test]
+    fn test_version_changes() {
+        let versions = |node: &str| BTreeMap::from([("node".to_string(), node.to_string())]);
+
+        assert_eq!(
+            version_changes(&versions("v20.0.0"), &versions("v22.0.0")),
+            ["node version changed: v20.0.0 -> v22.0.0"]
+        );
+        assert_eq!(
+            version_changes(&versions("v20.0.0"), &versions("v20.0.0")),
+            Vec::<String>::new()
+        );
+        assert_eq!(
+            version_changes(&BTreeMap::new(), &versions("v22.0.0")),
+            Vec::<String>::new()
+        );
+    }
+
+    #[test]
+    #[cfg(unix)]
+    fn test_local_binary_is_probed() {
+        use std::os::unix::fs::PermissionsExt;
+
+        let dir = tempfile::tempdir().unwrap();
+        let bin = dir.path().join("node_modules/.bin");
+        std::fs::create_dir_all(&bin).unwrap();
+        let tsc = bin.join("tsc");
+        std::fs::write(&tsc, "#!/bin/sh\necho 'Version 5.4.5'\n").unwrap();
+        std::fs::set_permissions(&tsc, std::fs::Permissions::from_mode(0o755)).unwrap();
+
+        let cache = ToolVersionCache::with_versions([("tsc", Some("global"))]);
+        assert_eq!(
+            cache.version("tsc", dir.path()).as_deref(),
+            Some("Version 5.4.5")
+        );
+
+        // Reinstalling the binary in place is noticed
+        std::fs::write(&tsc, "#!/bin/sh\necho 'Version 5.5.0-beta'\n").unwrap();
+        assert_eq!(
+            cache.version("tsc", dir.path()).as_deref(),
+            Some("Version 5.5.0-beta")
+        );
+    }
+}
@@ -0,0 +1,249 @@
+//! Bundle size analysis.
+//!
+//! `howth bundle --analyze` reports how much each module contributes to the
+//! output, grouped by package, as a console table and an HTML treemap.
+//! Sizes are per-module estimates from the wrapped (non-hoisted) emitter:
+//! `output_size` is the module as emitted without minification and
+//! `minified_size` the same module minified and mangled, so both are known
+//! whether or not the bundle itself was minified.
+
+use super::emit::emit_module_to_string;
+use super::graph::{ModuleGraph, ModuleId};
+use super::report::display_path;
+use super::treeshake::UsedExports;
+use super::{BundleError, BundleOptions};
+use rayon::prelude::*;
+use serde::Serialize;
+use std::collections::BTreeMap;
+use std::path::Path;
+
+/// Package name used for modules outside `node_modules`.
+pub const APP_PACKAGE: &str = "(app)";
+
+const HTML_TEMPLATE: &str = include_str!("analyze.html");
+
+/// Size contribution of one module.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct ModuleSize {
+    /// Path relative to the working directory with `/` separators.
+    pub path: String,
+    /// Package the module belongs to (`None` for application code).
+    pub package: Option<String>,
+    /// Chunk the module was emitted into.
+    pub chunk: String,
+    /// Size of the source file in bytes.
+    pub source_size: usize,
+    /// Emitted size without minification.
+    pub output_size: usize,
+    /// Emitted size after minification and mangling.
+    pub minified_size: usize,
+}
+
+/// Sizes summed over the modules of one package.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct PackageSize {
+    /// Package name, or [`APP_PACKAGE`].
+    pub name: String,
+    /// Number of modules.
+    pub modules: usize,
+    /// Emitted size without minification.
+    pub output_size: usize,
+    /// Emitted size after minification and mangling.
+    pub minified_size: usize,
+}
+
+/// Per-module and per-package size breakdown of a bundle.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct BundleAnalysis {
+    /// Modules, largest output first.
+    pub modules: Vec<ModuleSize>,
+    /// Packages, largest output first.
+    pub packages: Vec<PackageSize>,
+    /// Total emitted size without minification.
+    pub output_size: usize,
+    /// Total emitted size after minification and mangling.
+    pub minified_size: usize,
+}
+
+impl BundleAnalysis {
+    /// Measure every module assigned to a chunk.
+    ///
+    /// `chunks` pairs each chunk name with its modules; `entries` drive tree
+    /// shaking exactly as in the bundle.
+    pub(super) fn new(
+        graph: &ModuleGraph,
+        chunks: &[(&str, &[ModuleId])],
+        entries: &[ModuleId],
+        cwd: &Path,
+        options: &BundleOptions,
+    ) -> Result<Self, BundleError> {
+        let root = dunce::canonicalize(cwd).unwrap_or_else(|_| cwd.to_path_buf());
+        let used_exports = options
+            .treeshake
+            .then(|| UsedExports::analyze_entries(graph, entries));
+        let plain = BundleOptions {
+            minify: false,
+            ..options.clone()
+        };
+        let minified = BundleOptions {
+            minify: true,
+            mangle: options.mangle || !options.minify,
+            ..options.clone()
+        };
+
+        let assigned: Vec<(&str, ModuleId)> = chunks
+            .iter()
+            .flat_map(|(name, ids)| ids.iter().map(move |&id| (*name, id)))
+            .collect();
+        let mut modules = assigned
+            .par_iter()
+            .filter_map(|&(chunk, id)| graph.get(id).map(|module| (chunk, id, module)))
+            .map(|(chunk, id, module)| {
+                let output =
+                    emit_module_to_string(id, module, graph, &plain, used_exports.as_ref())?;
+                // A module the minifier cannot parse would fail a minified
+                // build anyway; report its plain size rather than erroring.
+                let minified_size =
+                    emit_module_to_string(id, module, graph, &minified, used_exports.as_ref())
+                        .map_or(output.len(), |code| code.len());
+                let source_size = std::fs::metadata(&module.path)
+                    .map(|m| m.len() as usize)
+                    .unwrap_or(module.source.len());
+                Ok(ModuleSize {
+                    path: display_path(&module.path, &root),
+                    package: package_name(&module.path),
+                    chunk: chunk.to_string(),
+                    source_size,
+                    output_size: output.len(),
+                    minified_size,
+                })
+            })
+            .collect::<Result<Vec<_>, BundleError>>()?;
+        modules.sort_by(|a, b| b.output_size.cmp(&a.output_size).then(a.path.cmp(&b.path)));
+
+        let mut by_package: BTreeMap<&str, PackageSize> = BTreeMap::new();
+        for module in &modules {
+            let name = module.package.as_deref().unwrap_or(APP_PACKAGE);
+            let entry = by_package.entry(name).or_insert_with(|| PackageSize {
+                name: name.to_string(),
+                modules: 0,
+                output_size: 0,
+                minified_size: 0,
+            });
+            entry.modules += 1;
+            entry.output_size += module.output_size;
+            entry.minified_size += module.minified_size;
+        }
+        let mut packages: Vec<PackageSize> = by_package.into_values().collect();
+        packages.sort_by(|a, b| b.output_size.cmp(&a.output_size).then(a.name.cmp(&b.name)));
+
+        Ok(Self {
+            output_size: modules.iter().map(|m| m.output_size).sum(),
+            minified_size: modules.iter().map(|m| m.minified_size).sum(),
+            modules,
+            packages,
+        })
+    }
+
+    /// Render a self-contained HTML treemap of the analysis.
+    pub fn to_html(&self) -> String {
+        // Keep `</script>` inside strings from closing the script element
+        let data = serde_json::to_string(self)
+            .unwrap_or_else(|_| "null".to_string())
+            .replace("</", "<\\/");
+        HTML_TEMPLATE
+            .replace("__APP__", APP_PACKAGE)
+            .replace("__DATA__", &data)
+    }
+}
+
+/// Name of the package a module path is in, from its last `node_modules`.
+fn package_name(path: &str) -> Option<String> {
+    let normalized = path.replace('\\', "/");
+    let (_, rest) = normalized.rsplit_once("node_modules/")?;
+    let mut parts = rest.split('/');
+    let first = parts.next()?;
+    if first.starts_with('@') {
+        let second = parts.next()?;
+        Some(format!("{first}/{second}"))
+    } else {
+        Some(first.to_string())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::bundler::Bundler;
+    use std::fs;
+
+    #[test]
+    fn test_package_name() {
+        assert_eq!(package_name("/app/src/index.ts"), None);
+        assert_eq!(
+            package_name("/app/node_modules/lodash/get.js").as_deref(),
+            Some("lodash")
+        );
+        assert_eq!(
+            package_name("/app/node_modules/a/node_modules/@scope/b/index.js").as_deref(),
+            Some("@scope/b")
+        );
+    }
+
+    #[test]
+    fn test_analysis_groups_by_package() {
+        let dir = tempfile::tempdir().unwrap();
+        let root = dunce::canonicalize(dir.path()).unwrap();
+        fs::create_dir_all(root.join("src")).unwrap();
+        fs::create_dir_all(root.join("node_modules/big")).unwrap();
+        fs::write(
+            root.join("src/index.js"),
+            "import { big } from \"big\";\nconsole.log(big());\n",
+        )
+        .unwrap();
+        fs::write(
+            root.join("node_modules/big/package.json"),
+            r#"{"name":"big","main":"index.js"}"#,
+        )
+        .unwrap();
+        fs::write(
+            root.join("node_modules/big/index.js"),
+            "export function big() {\n  const someLongVariableName = [1, 2, 3];\n  return someLongVariableName.length;\n}\n",
+        )
+        .unwrap();
+
+        let options = BundleOptions {
+            analyze: true,
+            ..Default::default()
+        };
+        let result = Bundler::new()
+            .bundle(Path::new("src/index.js"), &root, &options)
+            .unwrap();
+        let analysis = result.analysis.unwrap();
+
+        assert_eq!(analysis.modules.len(), 2);
+        let big = analysis
+            .modules
+            .iter()
+            .find(|m| m.package.as_deref() == Some("big"))
+            .unwrap();
+        assert_eq!(big.path, "node_modules/big/index.js");
+        assert_eq!(big.chunk, "main");
+        assert!(big.minified_size < big.output_size);
+
+        let names: Vec<&str> = analysis.packages.iter().map(|p| p.name.as_str()).collect();
+        assert_eq!(names, ["big", APP_PACKAGE]);
+        assert_eq!(
+            analysis.output_size,
+            analysis
+                .packages
+                .iter()
+                .map(|p| p.output_size)
+                .sum::<usize>()
+        );
+
+        let html = analysis.to_html();
+        assert!(html.contains("node_modules/big/index.js"));
+        assert!(!html.contains("__DATA__"));
+    }
+}
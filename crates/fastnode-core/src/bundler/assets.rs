@@ -4,7 +4,8 @@
 
 #![allow(dead_code)]
 
-use rustc_hash::FxHashMap as HashMap;
+use super::{BundleFormat, Platform};
+use std::collections::BTreeMap;
 use std::path::{Path, PathBuf};
 
 /// Asset types that can be imported.
@@ -68,8 +69,9 @@ pub struct Asset {
 /// Collected assets from bundling.
 #[derive(Debug, Default)]
 pub struct AssetCollection {
-    /// All collected assets, keyed by source path.
-    assets: HashMap<String, Asset>,
+    /// All collected assets, keyed by source path (so they are listed in a
+    /// stable order).
+    assets: BTreeMap<String, Asset>,
     /// CSS content in import order.
     css_chunks: Vec<String>,
 }
@@ -107,9 +109,8 @@ impl AssetCollection {
     /// Add a static asset (image, font, etc.).
     pub fn add_asset(&mut self, path: &Path, content: &[u8]) -> String {
         let hash = hash_bytes(content);
-        let stem = path.file_stem().and_then(|s| s.to_str()).unwrap_or("asset");
+        let output_name = hashed_name(path, &hash);
         let ext = path.extension().and_then(|s| s.to_str()).unwrap_or("bin");
-        let output_name = format!("{}.{}.{}", stem, &hash[..8], ext);
 
         let path_str = path.display().to_string();
         let asset_type = AssetType::from_extension(ext).unwrap_or(AssetType::Other);
@@ -164,6 +165,49 @@ impl AssetCollection {
     }
 }
 
+/// Output filename of a static asset: `stem.<hash>.ext`.
+pub fn asset_output_name(path: &Path, content: &[u8]) -> String {
+    hashed_name(path, &hash_bytes(content))
+}
+
+fn hashed_name(path: &Path, hash: &str) -> String {
+    let stem = path.file_stem().and_then(|s| s.to_str()).unwrap_or("asset");
+    let ext = path.extension().and_then(|s| s.to_str()).unwrap_or("bin");
+    format!("{}.{}.{}", stem, &hash[..8], ext)
+}
+
+/// Base URL expression for rewritten `new URL('./file', import.meta.url)`
+/// references. Assets are written next to the bundle, so the base is the
+/// bundle's own URL; `import.meta.url` only exists in ES modules. Classic
+/// worker scripts have no `document`, and their `location` is the script.
+pub fn asset_url_base(format: BundleFormat, platform: Platform) -> &'static str {
+    match (format, platform) {
+        (BundleFormat::Esm, _) => "import.meta.url",
+        (_, Platform::Node) => "\"file:\" + __filename",
+        (_, Platform::Browser) => {
+            "typeof document !== \"undefined\" && document.currentScript && document.currentScript.src || location.href"
+        }
+    }
+}
+
+/// Chunk name (without `.js`) of a bundled worker: `stem.<hash>`.
+pub fn worker_chunk_name(path: &Path, code: &str) -> String {
+    let stem = path
+        .file_stem()
+        .and_then(|s| s.to_str())
+        .unwrap_or("worker");
+    format!("{}.{}", stem, &hash_content(code)[..8])
+}
+
+/// Placeholder URL written for a worker entry until its chunk is bundled
+/// and named.
+pub fn worker_placeholder(path: &Path) -> String {
+    format!(
+        "__HOWTH_WORKER_{}__",
+        &hash_content(&path.display().to_string())[..16]
+    )
+}
+
 /// Hash string content using blake3.
 fn hash_content(content: &str) -> String {
     hash_bytes(content.as_bytes())
@@ -337,6 +381,18 @@ mod tests {
         assert!(collection.has_css());
     }
 
+    #[test]
+    fn test_asset_output_name() {
+        let name = asset_output_name(Path::new("/src/logo.png"), b"png");
+        let mut collection = AssetCollection::new();
+        assert_eq!(
+            collection.add_asset(Path::new("/src/logo.png"), b"png"),
+            name
+        );
+        assert!(name.starts_with("logo.") && name.ends_with(".png"));
+        assert_eq!(name.len(), "logo.12345678.png".len());
+    }
+
     #[test]
     fn test_content_hashing() {
         let hash1 = hash_content("hello");
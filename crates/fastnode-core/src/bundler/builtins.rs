@@ -0,0 +1,195 @@
+//! Node.js built-in module handling for browser builds.
+//!
+//! Browsers have no `fs`, `path` or `crypto`. When bundling for the browser,
+//! each built-in import is either aliased to a lightweight polyfill package,
+//! replaced with an empty module, or rejected with a clear error.
+//!
+//! Without explicit configuration, a built-in is aliased to its well-known
+//! polyfill when that package is installed, and stubbed (with a warning)
+//! otherwise.
+
+/// Platform the bundle will run on.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Platform {
+    /// Node.js: built-ins are left as runtime imports.
+    #[default]
+    Node,
+    /// Browser: built-ins are polyfilled, stubbed or rejected.
+    Browser,
+}
+
+impl Platform {
+    /// Parse a platform name (`node` or `browser`).
+    pub fn parse(s: &str) -> Option<Self> {
+        match s.to_lowercase().as_str() {
+            "node" => Some(Self::Node),
+            "browser" => Some(Self::Browser),
+            _ => None,
+        }
+    }
+}
+
+/// How to handle a Node.js built-in import in a browser build.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum BuiltinStrategy {
+    /// Alias to a polyfill package (e.g. `path` -> `path-browserify`).
+    Polyfill(String),
+    /// Replace with an empty module.
+    Empty,
+    /// Fail the build.
+    Error,
+}
+
+impl BuiltinStrategy {
+    /// Parse a strategy: `empty`, `error`, or a polyfill package name.
+    pub fn parse(s: &str) -> Option<Self> {
+        match s.trim() {
+            "" => None,
+            "empty" => Some(Self::Empty),
+            "error" => Some(Self::Error),
+            pkg => Some(Self::Polyfill(pkg.to_string())),
+        }
+    }
+}
+
+/// Node.js built-in module names (without the `node:` prefix).
+pub const NODE_BUILTINS: &[&str] = &[
+    "assert",
+    "assert/strict",
+    "async_hooks",
+    "buffer",
+    "child_process",
+    "cluster",
+    "console",
+    "constants",
+    "crypto",
+    "dgram",
+    "diagnostics_channel",
+    "dns",
+    "dns/promises",
+    "domain",
+    "events",
+    "fs",
+    "fs/promises",
+    "http",
+    "http2",
+    "https",
+    "inspector",
+    "module",
+    "net",
+    "os",
+    "path",
+    "path/posix",
+    "path/win32",
+    "perf_hooks",
+    "process",
+    "punycode",
+    "querystring",
+    "readline",
+    "readline/promises",
+    "repl",
+    "stream",
+    "stream/promises",
+    "stream/web",
+    "string_decoder",
+    "sys",
+    "timers",
+    "timers/promises",
+    "tls",
+    "trace_events",
+    "tty",
+    "url",
+    "util",
+    "util/types",
+    "v8",
+    "vm",
+    "wasi",
+    "worker_threads",
+    "zlib",
+];
+
+/// Get the built-in module name for a specifier, if it names one.
+///
+/// Accepts both `fs` and `node:fs`. Modules that only exist with the
+/// `node:` prefix (such as `node:test`) are recognized too.
+pub fn builtin_name(specifier: &str) -> Option<&str> {
+    if let Some(name) = specifier.strip_prefix("node:") {
+        return Some(name);
+    }
+    NODE_BUILTINS.contains(&specifier).then_some(specifier)
+}
+
+/// Well-known browser polyfill package for a built-in, if there is one.
+pub fn default_polyfill(name: &str) -> Option<&'static str> {
+    let polyfill = match name {
+        "assert" => "assert",
+        "buffer" => "buffer",
+        "console" => "console-browserify",
+        "constants" => "constants-browserify",
+        "crypto" => "crypto-browserify",
+        "domain" => "domain-browser",
+        "events" => "events",
+        "http" => "stream-http",
+        "https" => "https-browserify",
+        "os" => "os-browserify/browser",
+        "path" | "path/posix" => "path-browserify",
+        "process" => "process/browser",
+        "punycode" => "punycode",
+        "querystring" => "querystring-es3",
+        "stream" => "stream-browserify",
+        "string_decoder" => "string_decoder",
+        "sys" | "util" => "util",
+        "timers" => "timers-browserify",
+        "tty" => "tty-browserify",
+        "url" => "url",
+        "vm" => "vm-browserify",
+        "zlib" => "browserify-zlib",
+        _ => return None,
+    };
+    Some(polyfill)
+}
+
+/// Prefix of the module path used for empty built-in stubs.
+///
+/// Not a real file; the bundler adds stub modules to the graph directly.
+pub const EMPTY_STUB_PREFIX: &str = "(empty):";
+
+/// Module path of the empty stub for a built-in.
+pub fn empty_stub_path(name: &str) -> String {
+    format!("{EMPTY_STUB_PREFIX}{name}")
+}
+
+/// Source of an empty built-in stub.
+pub const EMPTY_STUB_SOURCE: &str = "module.exports = {};\n";
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_builtin_name() {
+        assert_eq!(builtin_name("fs"), Some("fs"));
+        assert_eq!(builtin_name("node:fs"), Some("fs"));
+        assert_eq!(builtin_name("fs/promises"), Some("fs/promises"));
+        assert_eq!(builtin_name("node:test"), Some("test"));
+        assert_eq!(builtin_name("lodash"), None);
+        assert_eq!(builtin_name("./path"), None);
+    }
+
+    #[test]
+    fn test_parse_strategy() {
+        assert_eq!(
+            BuiltinStrategy::parse("empty"),
+            Some(BuiltinStrategy::Empty)
+        );
+        assert_eq!(
+            BuiltinStrategy::parse("error"),
+            Some(BuiltinStrategy::Error)
+        );
+        assert_eq!(
+            BuiltinStrategy::parse("path-browserify"),
+            Some(BuiltinStrategy::Polyfill("path-browserify".to_string()))
+        );
+        assert_eq!(BuiltinStrategy::parse(""), None);
+    }
+}
@@ -1,9 +1,12 @@
 //! Code splitting and chunk generation.
 //!
-//! Splits the module graph into chunks based on dynamic import boundaries.
+//! Splits the module graph into chunks based on dynamic import boundaries,
+//! or, for multi-entry builds, into per-entry chunks plus common chunks for
+//! modules shared between entries.
 
 use super::graph::{ModuleGraph, ModuleId};
 use rustc_hash::{FxHashMap as HashMap, FxHashSet as HashSet};
+use std::collections::BTreeMap;
 
 /// A chunk is a group of modules that are loaded together.
 #[derive(Debug, Clone)]
@@ -57,6 +60,72 @@ impl ChunkGraph {
         chunk_graph
     }
 
+    /// Split a module graph with several entry points into chunks.
+    ///
+    /// Each entry gets a chunk holding the modules only it reaches. Modules
+    /// reached from several entries go into a common chunk shared by exactly
+    /// that set of entries, named after them (`a~b`). Entry chunks depend on
+    /// the common chunks they use.
+    pub fn from_entries(graph: &ModuleGraph, entries: &[(String, ModuleId)]) -> Self {
+        let mut chunk_graph = ChunkGraph {
+            chunks: Vec::new(),
+            module_to_chunk: HashMap::default(),
+            shared_modules: HashSet::default(),
+        };
+
+        // Which entries reach each module
+        let mut reached_by: HashMap<ModuleId, Vec<usize>> = HashMap::default();
+        for (index, (name, entry_id)) in entries.iter().enumerate() {
+            chunk_graph.create_chunk(name.clone(), *entry_id, true);
+            for module_id in get_all_reachable(graph, *entry_id) {
+                let entry_set = reached_by.entry(module_id).or_default();
+                if !entry_set.contains(&index) {
+                    entry_set.push(index);
+                }
+            }
+        }
+
+        // Group modules by the set of entries that reach them, in a stable order
+        let order: HashMap<ModuleId, usize> = graph
+            .toposort()
+            .into_iter()
+            .enumerate()
+            .map(|(position, id)| (id, position))
+            .collect();
+        let mut groups: BTreeMap<Vec<usize>, Vec<ModuleId>> = BTreeMap::new();
+        for (module_id, mut entry_set) in reached_by {
+            entry_set.sort_unstable();
+            groups.entry(entry_set).or_default().push(module_id);
+        }
+
+        for (entry_set, mut modules) in groups {
+            modules.sort_by_key(|id| order.get(id).copied().unwrap_or(usize::MAX));
+
+            let chunk_id = if let [index] = entry_set[..] {
+                index
+            } else {
+                let name = entry_set
+                    .iter()
+                    .map(|&i| entries[i].0.as_str())
+                    .collect::<Vec<_>>()
+                    .join("~");
+                let chunk_id = chunk_graph.create_chunk(name, modules[0], false);
+                for &index in &entry_set {
+                    chunk_graph.chunks[index].dependencies.push(chunk_id);
+                }
+                chunk_graph.shared_modules.extend(modules.iter().copied());
+                chunk_id
+            };
+
+            for &module_id in &modules {
+                chunk_graph.module_to_chunk.insert(module_id, chunk_id);
+            }
+            chunk_graph.chunks[chunk_id].modules.extend(modules);
+        }
+
+        chunk_graph
+    }
+
     /// Create chunks by traversing the graph.
     fn create_chunks(
         &mut self,
@@ -68,16 +137,20 @@ impl ChunkGraph {
         let main_chunk_id = self.create_chunk("main".to_string(), entry_id, true);
         self.assign_modules_to_chunk(graph, entry_id, main_chunk_id, split_points);
 
-        // Create chunks for each split point
-        for &split_id in split_points {
-            if let Some(module) = graph.get(split_id) {
-                let chunk_name = generate_chunk_name(&module.path);
-                let chunk_id = self.create_chunk(chunk_name, split_id, false);
-                self.assign_modules_to_chunk(graph, split_id, chunk_id, split_points);
-
-                // Add dependency from main chunk to this chunk
-                self.chunks[main_chunk_id].dependencies.push(chunk_id);
-            }
+        // Create chunks for each split point, in path order so chunk IDs
+        // don't depend on discovery order
+        let mut split_modules: Vec<_> = split_points
+            .iter()
+            .filter_map(|&id| graph.get(id).map(|module| (id, module)))
+            .collect();
+        split_modules.sort_by(|a, b| a.1.path.cmp(&b.1.path));
+        for (split_id, module) in split_modules {
+            let chunk_name = generate_chunk_name(&module.path);
+            let chunk_id = self.create_chunk(chunk_name, split_id, false);
+            self.assign_modules_to_chunk(graph, split_id, chunk_id, split_points);
+
+            // Add dependency from main chunk to this chunk
+            self.chunks[main_chunk_id].dependencies.push(chunk_id);
         }
     }
 
@@ -191,6 +264,7 @@ impl ChunkGraph {
     /// Generate a manifest for the chunk graph.
     pub fn generate_manifest(&self, graph: &ModuleGraph) -> ChunkManifest {
         ChunkManifest {
+            runtime: None,
             chunks: self
                 .chunks
                 .iter()
@@ -224,6 +298,29 @@ fn find_split_points(graph: &ModuleGraph) -> HashSet<ModuleId> {
     split_points
 }
 
+/// Get all modules reachable from a module through static and dynamic imports.
+fn get_all_reachable(graph: &ModuleGraph, start: ModuleId) -> HashSet<ModuleId> {
+    let mut reachable = HashSet::default();
+    let mut stack = vec![start];
+
+    while let Some(module_id) = stack.pop() {
+        if !reachable.insert(module_id) {
+            continue;
+        }
+        if let Some(module) = graph.get(module_id) {
+            stack.extend(
+                module
+                    .dependencies
+                    .iter()
+                    .chain(&module.dynamic_dependencies)
+                    .filter(|id| !reachable.contains(id)),
+            );
+        }
+    }
+
+    reachable
+}
+
 /// Get all static dependencies of a module recursively.
 fn get_all_dependencies(graph: &ModuleGraph, start: ModuleId) -> HashSet<ModuleId> {
     let mut deps = HashSet::default();
@@ -259,6 +356,8 @@ fn generate_chunk_name(path: &str) -> String {
 /// Chunk manifest for runtime loading.
 #[derive(Debug, Clone)]
 pub struct ChunkManifest {
+    /// Shared runtime file loaded by every chunk (multi-entry builds).
+    pub runtime: Option<String>,
     /// Information about each chunk.
     pub chunks: Vec<ChunkInfo>,
 }
@@ -283,7 +382,11 @@ pub struct ChunkInfo {
 impl ChunkManifest {
     /// Serialize manifest to JSON.
     pub fn to_json(&self) -> String {
-        let mut json = String::from("{\n  \"chunks\": [\n");
+        let mut json = String::from("{\n");
+        if let Some(runtime) = &self.runtime {
+            json.push_str(&format!("  \"runtime\": \"{}\",\n", runtime));
+        }
+        json.push_str("  \"chunks\": [\n");
 
         for (i, chunk) in self.chunks.iter().enumerate() {
             json.push_str("    {\n");
@@ -369,4 +472,47 @@ mod tests {
         assert_eq!(async_chunks.len(), 1);
         assert!(async_chunks[0].modules.contains(&1));
     }
+
+    #[test]
+    fn test_multi_entry_extracts_common_chunks() {
+        let mut graph = ModuleGraph::new();
+        let module = |path: &str, dependencies: Vec<ModuleId>| Module {
+            path: path.to_string(),
+            source: "".to_string(),
+            imports: Vec::new(),
+            dependencies,
+            dynamic_dependencies: Vec::new(),
+        };
+
+        // a -> shared, only-a; b -> shared; c -> (nothing)
+        graph.add(module("/a.ts", vec![2, 3])); // 0
+        graph.add(module("/b.ts", vec![2])); // 1
+        graph.add(module("/shared.ts", vec![])); // 2
+        graph.add(module("/only-a.ts", vec![])); // 3
+        graph.add(module("/c.ts", vec![])); // 4
+
+        let entries = vec![
+            ("a".to_string(), 0),
+            ("b".to_string(), 1),
+            ("c".to_string(), 4),
+        ];
+        let chunks = ChunkGraph::from_entries(&graph, &entries);
+
+        assert_eq!(chunks.chunks().len(), 4);
+        let names: Vec<&str> = chunks.chunks().iter().map(|c| c.name.as_str()).collect();
+        assert_eq!(names, vec!["a", "b", "c", "a~b"]);
+
+        let a = &chunks.chunks()[0];
+        assert!(a.is_entry);
+        assert_eq!(a.modules, vec![3, 0]);
+        assert_eq!(a.dependencies, vec![3]);
+        assert_eq!(chunks.chunks()[2].dependencies, Vec::<ChunkId>::new());
+
+        let common = &chunks.chunks()[3];
+        assert!(!common.is_entry);
+        assert_eq!(common.modules, vec![2]);
+        assert!(chunks.is_shared(2));
+        assert!(!chunks.is_shared(3));
+        assert_eq!(chunks.chunk_for_module(2), Some(3));
+    }
 }
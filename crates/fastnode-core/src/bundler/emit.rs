@@ -8,13 +8,17 @@
 #![allow(clippy::unnecessary_wraps)]
 #![allow(clippy::manual_pattern_char_comparison)]
 
-use super::graph::{ModuleGraph, ModuleId};
+use super::graph::{Module, ModuleGraph, ModuleId};
 use super::scope::ScopeHoistContext;
-use super::treeshake::UsedExports;
-use super::{BundleError, BundleOptions};
-use howth_parser::{Codegen, CodegenOptions, Parser, ParserOptions};
+use super::treeshake::{extract_exports, extract_re_exports, UsedExports};
+use super::{BundleError, BundleOptions, PackagesMode};
+use crate::sourcemap::{Original, SourceMap};
+use howth_parser::{
+    Codegen, CodegenOptions, ImportSpecifier, Parser, ParserOptions, SourceMapping,
+};
 use rayon::prelude::*;
 use rustc_hash::{FxHashMap as HashMap, FxHashSet as HashSet};
+use std::borrow::Cow;
 
 // =============================================================================
 // Minification
@@ -25,7 +29,13 @@ use rustc_hash::{FxHashMap as HashMap, FxHashSet as HashSet};
 /// Parses the concatenated bundle and re-emits with minified codegen
 /// (no whitespace, no newlines, deferred semicolons).
 /// When `mangle` is true, also shortens local variable names.
-fn minify_bundle(code: &str, mangle: bool) -> Result<String, BundleError> {
+/// With `with_map`, also returns the mappings from the minified code back
+/// to `code`.
+fn minify_bundle(
+    code: &str,
+    mangle: bool,
+    with_map: bool,
+) -> Result<(String, Vec<SourceMapping>), BundleError> {
     let opts = ParserOptions {
         module: false,
         ..Default::default()
@@ -36,6 +46,7 @@ fn minify_bundle(code: &str, mangle: bool) -> Result<String, BundleError> {
         path: None,
     })?;
 
+    howth_parser::fold::fold(&mut ast);
     if mangle {
         howth_parser::mangle::mangle(&mut ast, &howth_parser::mangle::MangleOptions::default());
     }
@@ -44,138 +55,70 @@ fn minify_bundle(code: &str, mangle: bool) -> Result<String, BundleError> {
         minify: true,
         ..Default::default()
     };
-    Ok(Codegen::new(&ast, codegen_opts).generate())
+    Ok(generate(&ast, codegen_opts, with_map))
 }
 
-// =============================================================================
-// Source Map Support
-// =============================================================================
-
-/// VLQ-encode a signed integer and append to output string.
-fn vlq_encode(value: i64, out: &mut String) {
-    const B64: &[u8] = b"ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789+/";
-    #[allow(clippy::cast_sign_loss)]
-    let mut v = (if value < 0 {
-        ((-value) << 1) | 1
-    } else {
-        value << 1
-    }) as u64;
-    loop {
-        let mut digit = (v & 0x1f) as u8;
-        v >>= 5;
-        if v > 0 {
-            digit |= 0x20; // continuation bit
-        }
-        out.push(B64[digit as usize] as char);
-        if v == 0 {
-            break;
-        }
+/// Fold constants and remove dead code for a production build that isn't
+/// minified.
+///
+/// Returns the re-printed code (and with `with_map`, its mappings back to
+/// `code`), or `None` when nothing changed or the code doesn't parse on its
+/// own (a CommonJS body with a top-level `return`).
+fn fold_code(code: &str, module: bool, with_map: bool) -> Option<(String, Vec<SourceMapping>)> {
+    let opts = ParserOptions {
+        module,
+        ..Default::default()
+    };
+    let mut ast = Parser::new(code, opts).parse().ok()?;
+    if !howth_parser::fold::fold(&mut ast) {
+        return None;
     }
+    Some(generate(&ast, CodegenOptions::default(), with_map))
 }
 
-/// Tracks source positions during bundle emission and generates a V3 sourcemap.
-struct SourceMapBuilder {
-    /// Module paths (source file names).
-    sources: Vec<String>,
-    /// Original source content for each module.
-    sources_content: Vec<String>,
-    /// Mapping segments: (output_line, output_col, source_idx, source_line, source_col).
-    mappings: Vec<(u32, u32, u32, u32, u32)>,
-}
-
-impl SourceMapBuilder {
-    fn new() -> Self {
-        Self {
-            sources: Vec::new(),
-            sources_content: Vec::new(),
-            mappings: Vec::new(),
-        }
-    }
-
-    /// Register a source file and return its index.
-    fn add_source(&mut self, path: &str, content: &str) -> u32 {
-        let idx = self.sources.len() as u32;
-        self.sources.push(path.to_string());
-        self.sources_content.push(content.to_string());
-        idx
-    }
-
-    /// Add a line-level mapping: output_line maps to source_line in source_idx.
-    fn add_line_mapping(&mut self, output_line: u32, source_idx: u32, source_line: u32) {
-        self.mappings
-            .push((output_line, 0, source_idx, source_line, 0));
-    }
-
-    /// Generate a V3 sourcemap JSON string.
-    fn generate(&self, file: &str) -> String {
-        let mut mappings_str = String::new();
-        let mut prev_output_line: u32 = 0;
-        let mut prev_source: i64 = 0;
-        let mut prev_source_line: i64 = 0;
-        let mut prev_source_col: i64 = 0;
-
-        // Sort mappings by output line
-        let mut sorted = self.mappings.clone();
-        sorted.sort_by_key(|m| (m.0, m.1));
-
-        for &(output_line, output_col, source_idx, source_line, source_col) in &sorted {
-            // Add semicolons for skipped lines
-            while prev_output_line < output_line {
-                mappings_str.push(';');
-                prev_output_line += 1;
-            }
-
-            // If not the first segment on this line, add comma
-            // (We only emit one segment per line, so this rarely triggers)
-
-            // Encode: output_col (relative), source_idx (relative), source_line (relative), source_col (relative)
-            vlq_encode(i64::from(output_col), &mut mappings_str);
-            vlq_encode(i64::from(source_idx) - prev_source, &mut mappings_str);
-            vlq_encode(i64::from(source_line) - prev_source_line, &mut mappings_str);
-            vlq_encode(i64::from(source_col) - prev_source_col, &mut mappings_str);
-
-            prev_source = i64::from(source_idx);
-            prev_source_line = i64::from(source_line);
-            prev_source_col = i64::from(source_col);
-        }
-
-        // Build JSON manually (avoid serde dependency for this small structure)
-        let sources_json: Vec<String> = self.sources.iter().map(|s| json_string(s)).collect();
-        let contents_json: Vec<String> = self
-            .sources_content
-            .iter()
-            .map(|s| json_string(s))
-            .collect();
-
-        format!(
-            r#"{{"version":3,"file":{},"sources":[{}],"sourcesContent":[{}],"mappings":{}}}"#,
-            json_string(file),
-            sources_json.join(","),
-            contents_json.join(","),
-            json_string(&mappings_str),
-        )
+/// Print `ast`, with source mappings only when asked for.
+fn generate(
+    ast: &howth_parser::Ast,
+    options: CodegenOptions,
+    with_map: bool,
+) -> (String, Vec<SourceMapping>) {
+    let options = CodegenOptions {
+        source_map: with_map,
+        ..options
+    };
+    let codegen = Codegen::new(ast, options);
+    if with_map {
+        codegen.generate_with_source_map()
+    } else {
+        (codegen.generate(), Vec::new())
     }
 }
 
-/// JSON-encode a string value (with escaping).
-fn json_string(s: &str) -> String {
-    let mut out = String::with_capacity(s.len() + 2);
-    out.push('"');
-    for c in s.chars() {
-        match c {
-            '"' => out.push_str("\\\""),
-            '\\' => out.push_str("\\\\"),
-            '\n' => out.push_str("\\n"),
-            '\r' => out.push_str("\\r"),
-            '\t' => out.push_str("\\t"),
-            c if (c as u32) < 0x20 => {
-                out.push_str(&format!("\\u{:04x}", c as u32));
-            }
-            _ => out.push(c),
-        }
-    }
-    out.push('"');
-    out
+// =============================================================================
+// Source Map Support
+// =============================================================================
+
+/// Trace `map`, a map of `before`, through a reprint of `before[start..]`
+/// (the part after an unchanged header) whose `mappings` point back into
+/// it.
+fn remap_reprint(
+    map: &SourceMap,
+    before: &str,
+    start: usize,
+    reprinted: &str,
+    mappings: &[SourceMapping],
+) -> SourceMap {
+    let mappings: Vec<SourceMapping> = mappings
+        .iter()
+        .map(|m| SourceMapping {
+            orig_offset: m.orig_offset + start as u32,
+            ..m.clone()
+        })
+        .collect();
+    let mut reprint_map = SourceMap::from_codegen("bundle.js", before, reprinted, &mappings);
+    reprint_map.prepend_lines(before[..start].matches('\n').count());
+    reprint_map.file.clone_from(&map.file);
+    reprint_map.remap(|_| Some(map))
 }
 
 /// Output format for the bundle.
@@ -233,7 +176,7 @@ pub fn emit_bundle_with_entry(
         output.push_str("// Generated by howth v0.1.0\n\n");
     }
 
-    match options.format {
+    let module_maps = match options.format {
         BundleFormat::Esm => emit_esm(
             graph,
             order,
@@ -258,47 +201,85 @@ pub fn emit_bundle_with_entry(
             entry_id,
             &mut output,
         )?,
-    }
+    };
 
     // Minification is handled per-module in emit_module_to_string (parallel).
     // Scope-hoisted bundles still use minify_bundle since they share a single scope.
 
     // Generate sourcemap if requested
-    let map = if options.sourcemap {
-        Some(build_sourcemap_from_output(&output, graph, order))
-    } else {
-        None
-    };
+    let mut map = options
+        .sourcemap
+        .then(|| build_sourcemap_from_output(&output, graph, order, None, &module_maps));
+
+    if options.packages == PackagesMode::External && options.format != BundleFormat::Iife {
+        output = hoist_prepending(graph, order, output, options.format, map.as_mut());
+    }
 
-    Ok(BundleOutput { code: output, map })
+    Ok(BundleOutput {
+        code: output,
+        map: map.map(|map| map.to_json()),
+    })
+}
+
+/// [`hoist_external_imports`], moving `map` down past the prepended lines.
+fn hoist_prepending(
+    graph: &ModuleGraph,
+    modules: &[ModuleId],
+    output: String,
+    format: BundleFormat,
+    map: Option<&mut SourceMap>,
+) -> String {
+    let hoisted = hoist_external_imports(graph, modules, &output, format);
+    if let Some(map) = map {
+        let prepended = &hoisted[..hoisted.len() - output.len()];
+        map.prepend_lines(prepended.matches('\n').count());
+    }
+    hoisted
 }
 
 /// Build a line-level sourcemap by scanning the output for module path comments.
 /// This works for both wrapped and scope-hoisted output since both emit `// /path` comments.
-fn build_sourcemap_from_output(output: &str, graph: &ModuleGraph, order: &[ModuleId]) -> String {
-    let mut builder = SourceMapBuilder::new();
-
-    // Register all sources
-    let mut source_indices: HashMap<ModuleId, u32> = HashMap::default();
+///
+/// Modules scope hoisted by `hoisted` are re-printed rather than emitted
+/// line by line, so their code is re-printed again with mappings to map it;
+/// `module_maps` has the maps of modules re-printed while emitting.
+/// Lines map into each module's transformed `source`, then through the
+/// module's input map (plugins and transpilation) back to the file on disk.
+fn build_sourcemap_from_output(
+    output: &str,
+    graph: &ModuleGraph,
+    order: &[ModuleId],
+    hoisted: Option<&ScopeHoistContext>,
+    module_maps: &[ModuleMap],
+) -> SourceMap {
+    let mut map = SourceMap::new();
+    map.file = Some("bundle.js".to_string());
+
+    // Build a map from module ID (as written in the output) to (module_id, source_idx)
+    let mut path_to_source: HashMap<Cow<'_, str>, (ModuleId, u32)> = HashMap::default();
     for &id in order {
         if let Some(module) = graph.get(id) {
-            let idx = builder.add_source(&module.path, &module.source);
-            source_indices.insert(id, idx);
+            let output_id = graph.output_id(&module.path);
+            let src_idx = map.add_source(&output_id, Some(&module.source));
+            path_to_source.insert(output_id, (id, src_idx));
         }
     }
 
-    // Build a map from module path to (module_id, source_idx)
-    let mut path_to_source: HashMap<&str, (ModuleId, u32)> = HashMap::default();
-    for &id in order {
-        if let Some(module) = graph.get(id) {
-            if let Some(&src_idx) = source_indices.get(&id) {
-                path_to_source.insert(&module.path, (id, src_idx));
-            }
-        }
-    }
+    // The map of a hoisted module's re-printed code, whose lines follow its
+    // path comment
+    let hoisted_map = |id: ModuleId| {
+        let ctx = hoisted.filter(|ctx| !ctx.is_wrapped(id))?;
+        let module = graph.get(id)?;
+        let renames = ctx.build_module_renames(id);
+        let (code, mappings) = emit_hoisted_module_ast(&module.source, &renames, true).ok()?;
+        let output_id = graph.output_id(&module.path);
+        let map = SourceMap::from_codegen(&output_id, &module.source, &code, &mappings);
+        Some((code, map))
+    };
 
     // Scan output lines for module path comments and track which module each line belongs to
     let mut current_source: Option<(u32, u32)> = None; // (source_idx, source_line_offset)
+    let mut current_hoisted: Option<(String, SourceMap)> = None;
     for (output_line, line) in output.lines().enumerate() {
         let trimmed = line.trim();
 
@@ -310,16 +291,19 @@ fn build_sourcemap_from_output(output: &str, graph: &ModuleGraph, order: &[Modul
             && !trimmed.starts_with("// Entry")
         {
             let path = &trimmed[3..];
-            if let Some(&(_, src_idx)) = path_to_source.get(path) {
+            if let Some(&(id, src_idx)) = path_to_source.get(path) {
                 current_source = Some((src_idx, 0));
+                current_hoisted = hoisted_map(id);
                 continue;
             }
             // Also match "Module N: /path" pattern
             if trimmed.starts_with("// Module ") {
                 if let Some(colon_idx) = trimmed.find(": ") {
                     let path = &trimmed[colon_idx + 2..];
-                    if let Some(&(_, src_idx)) = path_to_source.get(path) {
-                        current_source = Some((src_idx, 0));
+                    if let Some(&(id, src_idx)) = path_to_source.get(path) {
+                        let mapped = module_maps.iter().any(|m| m.id == id);
+                        current_source = (!mapped).then_some((src_idx, 0));
+                        current_hoisted = None;
                         continue;
                     }
                 }
@@ -328,17 +312,51 @@ fn build_sourcemap_from_output(output: &str, graph: &ModuleGraph, order: &[Modul
 
         // Map this output line to the current source
         if let Some((src_idx, ref mut src_line)) = current_source {
-            if !trimmed.is_empty()
-                && !trimmed.starts_with("__modules[")
+            if let Some((code, hoisted_map)) = &current_hoisted {
+                // Line `src_line` of the re-printed code, maybe indented
+                let printed = code.lines().nth(*src_line as usize).unwrap_or("");
+                let indent = (line.len() - line.trim_start().len())
+                    .saturating_sub(printed.len() - printed.trim_start().len())
+                    as u32;
+                let segments = hoisted_map.lines.get(*src_line as usize);
+                for segment in segments.into_iter().flatten() {
+                    let original = segment.original.map(|original| Original {
+                        source: src_idx,
+                        ..original
+                    });
+                    map.push(output_line as u32, segment.column + indent, original);
+                }
+                *src_line += 1;
+            } else if trimmed.is_empty() {
+                // Blank source lines still count
+                *src_line += 1;
+            } else if !trimmed.starts_with("__modules[")
+                && !is_export_setup(trimmed)
                 && !trimmed.starts_with("};")
             {
-                builder.add_line_mapping(output_line as u32, src_idx, *src_line);
+                map.push(
+                    output_line as u32,
+                    0,
+                    Some(Original {
+                        source: src_idx,
+                        line: *src_line,
+                        column: 0,
+                        name: None,
+                    }),
+                );
                 *src_line += 1;
             }
         }
     }
 
-    builder.generate("bundle.js")
+    for module_map in module_maps {
+        map.insert(&module_map.map, module_map.line, module_map.column);
+    }
+
+    map.remap(|path| {
+        let &(id, _) = path_to_source.get(path)?;
+        graph.input_map(id)
+    })
 }
 
 /// Emit ESM bundle.
@@ -349,26 +367,396 @@ fn emit_esm(
     used_exports: Option<&UsedExports>,
     entry_id: Option<ModuleId>,
     output: &mut String,
-) -> Result<(), BundleError> {
+) -> Result<Vec<ModuleMap>, BundleError> {
+    // Parallel emit: process each module in parallel, then concatenate in order
+    let module_outputs: Vec<(String, Option<SourceMap>)> = order
+        .par_iter()
+        .map(|&id| {
+            let module = graph.get(id).ok_or_else(|| BundleError {
+                code: "BUNDLE_INTERNAL_ERROR",
+                message: format!("Module {} not found in graph", id),
+                path: None,
+            })?;
+            emit_module_mapped(id, module, graph, options, used_exports)
+        })
+        .collect::<Result<_, BundleError>>()?;
+
     // For ESM, we use a module registry pattern
+    push_module_registry(options, output);
+    let codes: Vec<&str> = module_outputs
+        .iter()
+        .map(|(code, _)| code.as_str())
+        .collect();
+    output.push_str(&interop_helpers(&codes));
+
+    // Pre-allocate capacity for concatenation
+    output.reserve(codes.iter().map(|code| code.len()).sum());
+
+    // Concatenate results in order
+    let module_maps = push_modules(order, module_outputs, output);
+
+    // Entry point execution
+    let library_entry = entry_id
+        .filter(|_| {
+            options.format == BundleFormat::Esm && options.packages == PackagesMode::External
+        })
+        .and_then(|entry| graph.get(entry).map(|module| (entry, module)));
+    if let Some((entry, module)) = library_entry {
+        push_entry_exports(module, entry, options.minify, output);
+    } else if let Some(entry) = entry_id {
+        if options.minify {
+            output.push_str(&format!("__require({});", entry));
+        } else {
+            output.push_str(&format!("\n// Entry point\n__require({});\n", entry));
+        }
+    }
+
+    Ok(module_maps)
+}
+
+/// The map of an emitted module whose code was re-printed, placed at
+/// `line` and `column` of the output.
+struct ModuleMap {
+    id: ModuleId,
+    line: u32,
+    column: u32,
+    map: SourceMap,
+}
+
+/// Append the emitted `modules`, returning where their maps go.
+fn push_modules(
+    ids: &[ModuleId],
+    modules: Vec<(String, Option<SourceMap>)>,
+    output: &mut String,
+) -> Vec<ModuleMap> {
+    let mut module_maps = Vec::new();
+    let mut line = output.matches('\n').count() as u32;
+    let mut column = utf16_len(&output[output.rfind('\n').map_or(0, |i| i + 1)..]);
+    for (&id, (code, map)) in ids.iter().zip(modules) {
+        if let Some(map) = map {
+            module_maps.push(ModuleMap {
+                id,
+                line,
+                column,
+                map,
+            });
+        }
+        output.push_str(&code);
+        match code.rfind('\n') {
+            Some(last) => {
+                line += code.matches('\n').count() as u32;
+                column = utf16_len(&code[last + 1..]);
+            }
+            None => column += utf16_len(&code),
+        }
+    }
+    module_maps
+}
+
+fn utf16_len(text: &str) -> u32 {
+    text.encode_utf16().count() as u32
+}
+
+/// Helper that gives an imported ESM namespace the shape of a CommonJS
+/// `module.exports`: for a CommonJS package, Node only exposes statically
+/// detectable names on the namespace, so the `default` export is used instead.
+/// The result is marked `__esModule`, so `__toESM` leaves it as is.
+const EXTERNAL_INTEROP: &str = "function __extInterop(ns){const d=ns.default;let m=Object.assign({},ns);if(d!=null&&(typeof d===\"object\"||typeof d===\"function\")&&Object.keys(ns).every(k=>k===\"default\"||k===\"module.exports\"||ns[k]===d[k]))m=Object.assign({},d,{default:d});return __markAsModule(m);}\n";
+
+/// Helpers for mixing CommonJS and ES modules (`esModuleInterop`
+/// semantics), as `(name, definition)`. Bundles define the ones their
+/// modules use once, ahead of the module registry entries.
+///
+/// - `__markAsModule(exports)` flags the exports of an ES module with
+///   `__esModule`, run first thing by every ES module
+/// - `__toESM(exports)` is the namespace an `import` sees: ES module exports
+///   as they are, CommonJS `module.exports` with its properties as named
+///   exports and itself as `default`
+/// - `__export(exports, getters)` defines an ES module's exports as getters
+///   of its bindings, so importers see reassignments and, in an import
+///   cycle, hoisted functions before the module has run
+/// - `__reExport(exports, from)` forwards the named exports for `export *`,
+///   skipping `default` and names the module exports itself
+const INTEROP_HELPERS: &[(&str, &str)] = &[
+    (
+        "__markAsModule",
+        "function __markAsModule(e){return Object.defineProperty(e,\"__esModule\",{value:true});}\n",
+    ),
+    (
+        "__toESM",
+        "function __toESM(m){if(m&&m.__esModule)return m;const ns={};if(m!=null&&(typeof m===\"object\"||typeof m===\"function\"))for(const k of Object.keys(m))if(k!==\"default\")Object.defineProperty(ns,k,{enumerable:true,get:()=>m[k]});ns.default=m;return __markAsModule(ns);}\n",
+    ),
+    (
+        "__export",
+        "function __export(t,a){for(const k in a)Object.defineProperty(t,k,{enumerable:true,get:a[k]});}\n",
+    ),
+    (
+        "__reExport",
+        "function __reExport(t,m){for(const k of Object.keys(m))if(k!==\"default\"&&!(k in t))Object.defineProperty(t,k,{enumerable:true,get:()=>m[k]});}\n",
+    ),
+];
+
+/// Definitions of the interop helpers called by `modules`.
+///
+/// `__toESM` and `__extInterop` call `__markAsModule`, so it's included
+/// with them.
+fn interop_helpers<S: AsRef<str>>(modules: &[S]) -> String {
+    let uses = |name: &str| {
+        let call = format!("{}(", name);
+        modules.iter().any(|code| code.as_ref().contains(&call))
+    };
+    let mut helpers = String::new();
+    for (name, definition) in INTEROP_HELPERS {
+        let needed =
+            uses(name) || (*name == "__markAsModule" && (uses("__toESM") || uses("__extInterop")));
+        if needed {
+            helpers.push_str(definition);
+        }
+    }
+    helpers
+}
+
+/// Package specifiers imported by `modules` that stay external.
+fn external_specifiers<'a>(graph: &'a ModuleGraph, modules: &[ModuleId]) -> Vec<&'a str> {
+    let mut specifiers: Vec<&str> = Vec::new();
+    for &id in modules {
+        let Some(module) = graph.get(id) else {
+            continue;
+        };
+        for import in &module.imports {
+            let spec = import.specifier.as_str();
+            if import.dynamic
+                || spec.starts_with('.')
+                || is_css_import(spec)
+                || is_asset_import(spec)
+                || graph.resolve_specifier(&module.path, spec).is_some()
+                || specifiers.contains(&spec)
+            {
+                continue;
+            }
+            specifiers.push(spec);
+        }
+    }
+    specifiers.sort_unstable();
+    specifiers
+}
+
+/// Top-level binding an external package is hoisted into.
+///
+/// Characters that can't appear in an identifier are hex-escaped so distinct
+/// specifiers never share a binding (`@scope/pkg` -> `__ext__40scope_2fpkg`).
+fn external_binding(specifier: &str) -> String {
+    let mut name = String::from("__ext_");
+    for c in specifier.chars() {
+        if c.is_ascii_alphanumeric() {
+            name.push(c);
+        } else {
+            name.push_str(&format!("_{:x}", u32::from(c)));
+        }
+    }
+    name
+}
+
+/// Point a module's `require()`s of external packages at their hoisted
+/// bindings (see [`hoist_external_imports`]).
+///
+/// Runs before minification so mangling the wrapper's `require` parameter
+/// can't hide the calls.
+fn rewrite_external_requires(module_id: ModuleId, graph: &ModuleGraph, code: String) -> String {
+    let mut code = code;
+    for spec in external_specifiers(graph, &[module_id]) {
+        let binding = external_binding(spec);
+        code = code
+            .replace(&format!("require('{}')", spec), &binding)
+            .replace(&format!("require(\"{}\")", spec), &binding);
+    }
+    code
+}
+
+/// Hoist the imports of external packages to the top of the bundle.
+///
+/// Used for library builds ([`PackagesMode::External`]) so the output keeps
+/// real imports that Node and downstream bundlers can resolve. ESM output
+/// imports each package once as a namespace; CommonJS output requires it
+/// once at the top level, outside the module registry's `require`.
+fn hoist_external_imports(
+    graph: &ModuleGraph,
+    modules: &[ModuleId],
+    code: &str,
+    format: BundleFormat,
+) -> String {
+    let specifiers = external_specifiers(graph, modules);
+    if specifiers.is_empty() {
+        return code.to_string();
+    }
+
+    let mut imports = String::new();
+    let mut bindings = String::new();
+    for spec in specifiers {
+        let name = external_binding(spec);
+        let quoted = spec.replace('"', "\\\"");
+        if format == BundleFormat::Cjs {
+            imports.push_str(&format!("const {} = require(\"{}\");\n", name, quoted));
+        } else {
+            imports.push_str(&format!("import * as {}_ns from \"{}\";\n", name, quoted));
+            bindings.push_str(&format!("const {0} = __extInterop({0}_ns);\n", name));
+        }
+    }
+    if !bindings.is_empty() {
+        imports.push_str(EXTERNAL_INTEROP);
+        if !code.contains("function __markAsModule(") {
+            imports.push_str(INTEROP_HELPERS[0].1);
+        }
+        imports.push_str(&bindings);
+    }
+    imports.push_str(code);
+    imports
+}
+
+/// Names exported by a library entry, re-exported from ESM output.
+///
+/// `export *` re-exports can't be listed without analyzing the target module
+/// and are skipped.
+fn entry_export_names(module: &Module) -> Vec<String> {
+    let mut names: Vec<String> = extract_exports(&module.source)
+        .into_iter()
+        .map(|(name, _)| name)
+        .chain(
+            extract_re_exports(&module.source)
+                .into_iter()
+                .filter(|(_, _, source)| source != "*")
+                .map(|(name, _, _)| name),
+        )
+        .collect();
+    names.dedup();
+    names
+}
+
+/// Run the entry of an ESM library build and re-export its exports.
+fn push_entry_exports(module: &Module, entry: ModuleId, minify: bool, output: &mut String) {
+    if !minify {
+        output.push_str("\n// Entry point\n");
+    }
+    output.push_str(&format!("const __entry = __require({});\n", entry));
+    let mut names = Vec::new();
+    for name in entry_export_names(module) {
+        if name == "default" {
+            output.push_str("export default __entry.default;\n");
+        } else if !names.contains(&name) {
+            names.push(name);
+        }
+    }
+    if !names.is_empty() {
+        output.push_str(&format!(
+            "export const {{ {} }} = __entry;\n",
+            names.join(", ")
+        ));
+    }
+}
+
+/// Write the module registry (`__modules` and `__require`).
+///
+/// IDs not in the registry (externals and `require()` of a computed path)
+/// are passed on to the host `require` when there is one.
+fn push_module_registry(options: &BundleOptions, output: &mut String) {
     if options.minify {
         output.push_str("const __modules={};const __exports={};");
-        output.push_str("function __require(id){if(__exports[id])return __exports[id];const module={exports:{}};__modules[id](module,module.exports,__require);__exports[id]=module.exports;return module.exports;}");
+        output.push_str("function __require(id){if(__exports[id])return __exports[id];if(!(id in __modules)){if(typeof require===\"function\")return require(id);throw new Error(\"Cannot find module '\"+id+\"'\");}const module={exports:{}};__exports[id]=module.exports;__modules[id](module,module.exports,__require);__exports[id]=module.exports;return module.exports;}");
     } else {
         output.push_str("const __modules = {};\n");
         output.push_str("const __exports = {};\n\n");
 
         output.push_str("function __require(id) {\n");
         output.push_str("  if (__exports[id]) return __exports[id];\n");
+        output.push_str("  if (!(id in __modules)) {\n");
+        output.push_str("    if (typeof require === \"function\") return require(id);\n");
+        output.push_str("    throw new Error(\"Cannot find module '\" + id + \"'\");\n");
+        output.push_str("  }\n");
         output.push_str("  const module = { exports: {} };\n");
+        // Cached before it runs, so an import cycle sees the partial exports
+        output.push_str("  __exports[id] = module.exports;\n");
         output.push_str("  __modules[id](module, module.exports, __require);\n");
         output.push_str("  __exports[id] = module.exports;\n");
         output.push_str("  return module.exports;\n");
         output.push_str("}\n\n");
     }
+}
 
-    // Parallel emit: process each module in parallel, then concatenate in order
-    let module_outputs: Vec<Result<String, BundleError>> = order
+/// Emit the runtime shared by all chunks of a multi-entry build.
+///
+/// Holds the single module registry so a module shared between entries is
+/// defined and evaluated once. Only ESM and CJS are supported.
+pub fn emit_shared_runtime(options: &BundleOptions) -> String {
+    let mut output = String::new();
+    if !options.minify {
+        output.push_str("// howth runtime\n");
+        output.push_str("// Generated by howth v0.1.0\n\n");
+    }
+    push_module_registry(options, &mut output);
+    for (_, definition) in INTEROP_HELPERS {
+        output.push_str(definition);
+    }
+    let names = runtime_bindings();
+    match options.format {
+        BundleFormat::Cjs => output.push_str(&format!("module.exports = {{ {} }};\n", names)),
+        _ => output.push_str(&format!("export {{ {} }};\n", names)),
+    }
+    output
+}
+
+/// Bindings the shared runtime exports to chunks: the module registry and
+/// the interop helpers.
+fn runtime_bindings() -> String {
+    ["__modules", "__require"]
+        .into_iter()
+        .chain(INTEROP_HELPERS.iter().map(|(name, _)| *name))
+        .collect::<Vec<_>>()
+        .join(", ")
+}
+
+/// Emit one chunk of a multi-entry build.
+///
+/// The chunk loads the shared runtime (`runtime_file`) and the chunk files it
+/// depends on, registers its modules, and, for entry chunks, runs the entry.
+pub fn emit_chunk(
+    graph: &ModuleGraph,
+    modules: &[ModuleId],
+    entry: Option<ModuleId>,
+    runtime_file: &str,
+    dependencies: &[String],
+    options: &BundleOptions,
+    used_exports: Option<&UsedExports>,
+) -> Result<BundleOutput, BundleError> {
+    let mut output = String::new();
+    if !options.minify {
+        output.push_str("// howth bundle\n");
+        output.push_str("// Generated by howth v0.1.0\n\n");
+    }
+
+    let cjs = options.format == BundleFormat::Cjs;
+    let bindings = runtime_bindings();
+    if cjs {
+        output.push_str(&format!(
+            "const {{ {} }} = require(\"./{}\");\n",
+            bindings, runtime_file
+        ));
+        for dep in dependencies {
+            output.push_str(&format!("require(\"./{}\");\n", dep));
+        }
+    } else {
+        output.push_str(&format!(
+            "import {{ {} }} from \"./{}\";\n",
+            bindings, runtime_file
+        ));
+        for dep in dependencies {
+            output.push_str(&format!("import \"./{}\";\n", dep));
+        }
+    }
+    if !options.minify {
+        output.push('\n');
+    }
+
+    let module_outputs: Vec<(String, Option<SourceMap>)> = modules
         .par_iter()
         .map(|&id| {
             let module = graph.get(id).ok_or_else(|| BundleError {
@@ -376,33 +764,34 @@ fn emit_esm(
                 message: format!("Module {} not found in graph", id),
                 path: None,
             })?;
-            emit_module_to_string(id, module, graph, options, used_exports)
+            emit_module_mapped(id, module, graph, options, used_exports)
         })
-        .collect();
-
-    // Pre-allocate capacity for concatenation (estimate ~1KB per module)
-    let total_len: usize = module_outputs
-        .iter()
-        .filter_map(|r| r.as_ref().ok())
-        .map(|s| s.len())
-        .sum();
-    output.reserve(total_len);
+        .collect::<Result<_, BundleError>>()?;
+    let module_maps = push_modules(modules, module_outputs, &mut output);
 
-    // Concatenate results in order
-    for result in module_outputs {
-        output.push_str(&result?);
-    }
-
-    // Entry point execution
-    if let Some(entry) = entry_id {
-        if options.minify {
-            output.push_str(&format!("__require({});", entry));
+    if let Some(entry) = entry {
+        if !options.minify {
+            output.push_str("\n// Entry point\n");
+        }
+        if cjs {
+            output.push_str(&format!("module.exports = __require({});\n", entry));
         } else {
-            output.push_str(&format!("\n// Entry point\n__require({});\n", entry));
+            output.push_str(&format!("__require({});\n", entry));
         }
     }
 
-    Ok(())
+    let mut map = options
+        .sourcemap
+        .then(|| build_sourcemap_from_output(&output, graph, modules, None, &module_maps));
+
+    if options.packages == PackagesMode::External {
+        output = hoist_prepending(graph, modules, output, options.format, map.as_mut());
+    }
+
+    Ok(BundleOutput {
+        code: output,
+        map: map.map(|map| map.to_json()),
+    })
 }
 
 /// Emit CJS bundle.
@@ -413,9 +802,9 @@ fn emit_cjs(
     used_exports: Option<&UsedExports>,
     entry_id: Option<ModuleId>,
     output: &mut String,
-) -> Result<(), BundleError> {
+) -> Result<Vec<ModuleMap>, BundleError> {
     // Similar to ESM but with CommonJS wrapper
-    emit_esm(graph, order, options, used_exports, entry_id, output)?;
+    let module_maps = emit_esm(graph, order, options, used_exports, entry_id, output)?;
 
     // Add module.exports for the entry
     if let Some(entry) = entry_id {
@@ -426,7 +815,7 @@ fn emit_cjs(
         }
     }
 
-    Ok(())
+    Ok(module_maps)
 }
 
 /// Emit IIFE bundle.
@@ -437,7 +826,7 @@ fn emit_iife(
     used_exports: Option<&UsedExports>,
     entry_id: Option<ModuleId>,
     output: &mut String,
-) -> Result<(), BundleError> {
+) -> Result<Vec<ModuleMap>, BundleError> {
     if options.minify {
         output.push_str("(function(){'use strict';");
     } else {
@@ -447,7 +836,22 @@ fn emit_iife(
 
     // Emit the ESM content inside IIFE
     let mut inner = String::new();
-    emit_esm(graph, order, options, used_exports, entry_id, &mut inner)?;
+    let mut module_maps = emit_esm(graph, order, options, used_exports, entry_id, &mut inner)?;
+
+    // Place the module maps within the wrapper
+    let line = output.matches('\n').count() as u32;
+    let column = utf16_len(&output[output.rfind('\n').map_or(0, |i| i + 1)..]);
+    for module_map in &mut module_maps {
+        if module_map.line == 0 {
+            module_map.column += column;
+        }
+        module_map.line += line;
+        if !options.minify {
+            for segment in module_map.map.lines.iter_mut().flatten() {
+                segment.column += 2;
+            }
+        }
+    }
 
     if options.minify {
         output.push_str(&inner);
@@ -465,22 +869,39 @@ fn emit_iife(
         output.push('\n');
     }
 
-    Ok(())
+    Ok(module_maps)
 }
 
 /// Emit a single module to a string (for parallel processing).
-fn emit_module_to_string(
+pub(super) fn emit_module_to_string(
     id: ModuleId,
     module: &super::graph::Module,
     graph: &ModuleGraph,
     options: &BundleOptions,
     used_exports: Option<&UsedExports>,
 ) -> Result<String, BundleError> {
+    emit_module_mapped(id, module, graph, options, used_exports).map(|(code, _)| code)
+}
+
+/// Like [`emit_module_to_string`], also returning the map of the code when
+/// a source map is requested and the module was re-printed (minified or
+/// folded), so its lines no longer follow `module.source`.
+fn emit_module_mapped(
+    id: ModuleId,
+    module: &super::graph::Module,
+    graph: &ModuleGraph,
+    options: &BundleOptions,
+    used_exports: Option<&UsedExports>,
+) -> Result<(String, Option<SourceMap>), BundleError> {
     // Get the set of used exports for tree shaking
     let used_set: Option<HashSet<String>> = used_exports.and_then(|u| u.get_used(id).cloned());
 
     // Transform the source code with tree shaking info
-    let transformed = transform_module(&module.source, &module.path, graph, used_set.as_ref())?;
+    let mut transformed = transform_module(&module.source, &module.path, graph, used_set.as_ref())?;
+    if options.packages == PackagesMode::External && options.format != BundleFormat::Iife {
+        transformed = rewrite_external_requires(id, graph, transformed);
+    }
+    let origins = source_lines(&transformed);
 
     if options.minify {
         // Build the wrapped module string, then parse+minify+mangle in one shot
@@ -489,11 +910,13 @@ fn emit_module_to_string(
             "__modules[{}]=function(module,exports,require){{",
             id
         ));
-        for line in transformed.lines() {
+        let mut wrapped_origins = Vec::new();
+        for (line, origin) in transformed.lines().zip(&origins) {
             let trimmed = line.trim();
             if !trimmed.is_empty() {
                 wrapped.push_str(trimmed);
                 wrapped.push('\n');
+                wrapped_origins.push(*origin);
             }
         }
         wrapped.push_str("};");
@@ -514,6 +937,7 @@ fn emit_module_to_string(
                 path: Some(module.path.clone()),
             })?;
 
+        howth_parser::fold::fold(&mut ast);
         if options.mangle {
             howth_parser::mangle::mangle(&mut ast, &howth_parser::mangle::MangleOptions::default());
         }
@@ -522,11 +946,48 @@ fn emit_module_to_string(
             minify: true,
             ..Default::default()
         };
-        Ok(Codegen::new(&ast, codegen_opts).generate())
+        // Minified output drops the final `;`, which the next module needs
+        let (mut code, mappings) = generate(&ast, codegen_opts, options.sourcemap);
+        if !code.ends_with(';') {
+            code.push(';');
+        }
+        let map = options.sourcemap.then(|| {
+            let wrapped_map = line_map(module, graph, &wrapped_origins);
+            SourceMap::from_codegen(&graph.output_id(&module.path), &wrapped, &code, &mappings)
+                .remap(|_| Some(&wrapped_map))
+        });
+        Ok((code, map))
     } else {
+        let mut map = None;
+        if options.production {
+            if let Some((folded, mappings)) = fold_code(&transformed, false, options.sourcemap) {
+                if options.sourcemap {
+                    let transformed_map = line_map(module, graph, &origins);
+                    let mut folded_map = SourceMap::from_codegen(
+                        &graph.output_id(&module.path),
+                        &transformed,
+                        &folded,
+                        &mappings,
+                    )
+                    .remap(|_| Some(&transformed_map));
+                    // Below the module comment and wrapper, indented
+                    folded_map.prepend_lines(2);
+                    for segment in folded_map.lines.iter_mut().flatten() {
+                        segment.column += 2;
+                    }
+                    map = Some(folded_map);
+                }
+                transformed = folded;
+            }
+        }
+
         // Pretty-print with indentation
         let mut output = String::with_capacity(module.source.len() + 200);
-        output.push_str(&format!("// Module {}: {}\n", id, module.path));
+        output.push_str(&format!(
+            "// Module {}: {}\n",
+            id,
+            graph.output_id(&module.path)
+        ));
         output.push_str(&format!(
             "__modules[{}]=function(module,exports,require){{",
             id
@@ -540,10 +1001,57 @@ fn emit_module_to_string(
         }
 
         output.push_str("};\n\n");
-        Ok(output)
+        Ok((output, map))
     }
 }
 
+/// The line of `module.source` each line of its transformed code comes
+/// from; `None` for lines the transform added.
+fn source_lines(transformed: &str) -> Vec<Option<u32>> {
+    let mut source_line = 0;
+    transformed
+        .lines()
+        .map(|line| {
+            if is_export_setup(line.trim()) {
+                return None;
+            }
+            source_line += 1;
+            Some(source_line - 1)
+        })
+        .collect()
+}
+
+/// Whether a line of transformed module code is export setup the transform
+/// adds, rather than a line of the module's source.
+fn is_export_setup(trimmed: &str) -> bool {
+    trimmed.starts_with("__markAsModule(") || trimmed.starts_with("__export(exports,")
+}
+
+/// Line-level map of code whose lines come from lines of `module.source`.
+fn line_map(
+    module: &super::graph::Module,
+    graph: &ModuleGraph,
+    origins: &[Option<u32>],
+) -> SourceMap {
+    let mut map = SourceMap::new();
+    let source = map.add_source(&graph.output_id(&module.path), Some(&module.source));
+    for (line, origin) in origins.iter().enumerate() {
+        if let Some(origin) = *origin {
+            map.push(
+                line as u32,
+                0,
+                Some(Original {
+                    source,
+                    line: origin,
+                    column: 0,
+                    name: None,
+                }),
+            );
+        }
+    }
+    map
+}
+
 /// Emit a single module (legacy function, kept for compatibility).
 #[allow(dead_code)]
 fn emit_module(
@@ -567,13 +1075,69 @@ fn transform_module(
     graph: &ModuleGraph,
     used_exports: Option<&HashSet<String>>,
 ) -> Result<String, BundleError> {
+    let mut live = live_imports(source, module_path, graph);
+    let (mut result, mut pending_exports, is_esm) =
+        transform_lines(source, module_path, graph, used_exports, &live)?;
+    if !live.renames.is_empty() {
+        if let Some(renamed) = rename_live_imports(&result, &live.renames) {
+            result = renamed;
+        } else {
+            // The uses can't be renamed, so fall back to copies of the imports
+            live = LiveImports::default();
+            (result, pending_exports, _) =
+                transform_lines(source, module_path, graph, used_exports, &live)?;
+        }
+    }
+
+    // Define the exports as getters before the body runs: a module in an
+    // import cycle can read them early, and `export let` stays live
+    if !pending_exports.is_empty() {
+        let getters: Vec<String> = pending_exports
+            .iter()
+            .map(|(exported, local)| {
+                let local = live.renames.get(local).unwrap_or(local);
+                format!("{}: () => {}", exported, local)
+            })
+            .collect();
+        result.insert_str(
+            0,
+            &format!("__export(exports, {{ {} }});\n", getters.join(", ")),
+        );
+    }
+
+    // Flag ES module exports before anything can import them, so `__toESM`
+    // and CommonJS consumers with `esModuleInterop` handle `default` right
+    if is_esm {
+        result.insert_str(0, "__markAsModule(exports);\n");
+    }
+
+    Ok(result)
+}
+
+/// Rewrite the imports and exports of `source` line by line. Returns the
+/// code, the exports to define as (exported, local), and whether the
+/// module is an ES module.
+fn transform_lines(
+    source: &str,
+    module_path: &str,
+    graph: &ModuleGraph,
+    used_exports: Option<&HashSet<String>>,
+    live: &LiveImports,
+) -> Result<(String, Vec<(String, String)>, bool), BundleError> {
     // Source is already transpiled - just rewrite imports/exports
-    // Collect exports to emit at the end
-    let mut pending_exports: Vec<String> = Vec::new();
+    let mut pending_exports: Vec<(String, String)> = Vec::new();
     // Pre-allocate: source size + some extra for export statements
     let mut result = String::with_capacity(source.len() + 100);
+    let mut is_esm = false;
 
-    for line in source.lines() {
+    for (index, line) in source.lines().enumerate() {
+        let trimmed = line.trim_start();
+        is_esm |= trimmed.starts_with("import ") || trimmed.starts_with("export ");
+        if let Some(replacement) = live.lines.get(&index) {
+            result.push_str(replacement);
+            result.push('\n');
+            continue;
+        }
         let (transformed, export_stmts) =
             transform_line_with_exports(line, module_path, graph, used_exports)?;
 
@@ -587,13 +1151,139 @@ fn transform_module(
         pending_exports.extend(export_stmts);
     }
 
-    // Emit all pending exports at the end (only used ones)
-    for export_stmt in pending_exports {
-        result.push_str(&export_stmt);
-        result.push('\n');
+    let result = rewrite_requires(result, module_path, graph);
+    Ok((result, pending_exports, is_esm))
+}
+
+/// Imports that have to be read from the exporting module on each use
+/// rather than copied once.
+#[derive(Default)]
+struct LiveImports {
+    /// Replacement for each import statement, by line index
+    lines: HashMap<usize, String>,
+    /// Local name -> `__import_N.name` member expression
+    renames: HashMap<String, String>,
+}
+
+/// Find the imports of `source` that would go stale as a destructured copy:
+/// everything imported from a module in the same import cycle (it may not
+/// have run yet), and `let`/`var` exports the exporting module reassigns.
+///
+/// Those imports are bound to the module's exports object instead, e.g.
+/// `import { count } from './counter'` becomes
+/// `const __import_1 = __toESM(require(1));`, with `count` read as
+/// `__import_1.count`. Within a cycle the binding is a hoisted function,
+/// `__import_1().count`, since the other module can call into this one
+/// before the `require` returns.
+fn live_imports(source: &str, module_path: &str, graph: &ModuleGraph) -> LiveImports {
+    let mut live = LiveImports::default();
+    let Some(importer) = graph.id_by_path(module_path) else {
+        return live;
+    };
+    let Some(module) = graph.get(importer) else {
+        return live;
+    };
+    let needs_live =
+        |&dep: &ModuleId| graph.in_cycle(importer, dep) || graph.reassigned_exports(dep).is_some();
+    if !module.dependencies.iter().any(needs_live) {
+        return live;
     }
+    let options = ParserOptions {
+        module: true,
+        ..Default::default()
+    };
+    let Ok(ast) = Parser::new(source, options).parse() else {
+        return live;
+    };
 
-    Ok(result)
+    let mut declared = HashSet::default();
+    for stmt in &ast.stmts {
+        let howth_parser::StmtKind::Import(import) = &stmt.kind else {
+            continue;
+        };
+        let Some(dep) = graph.resolve_specifier(module_path, &import.source) else {
+            continue;
+        };
+        let in_cycle = graph.in_cycle(importer, dep);
+        let reassigned = graph.reassigned_exports(dep);
+        let is_live = |imported: &str| in_cycle || reassigned.is_some_and(|r| r.contains(imported));
+        let any_live = import.specifiers.iter().any(|spec| match spec {
+            ImportSpecifier::Default { .. } => is_live("default"),
+            ImportSpecifier::Named { imported, .. } => is_live(imported),
+            // A namespace is the exports object itself, so it's live already
+            ImportSpecifier::Namespace { .. } => false,
+        });
+        if !any_live {
+            continue;
+        }
+
+        let binding = if in_cycle {
+            format!("__import_{}()", dep)
+        } else {
+            format!("__import_{}", dep)
+        };
+        for spec in &import.specifiers {
+            let (local, member) = match spec {
+                ImportSpecifier::Default { local, .. } => (local, format!("{}.default", binding)),
+                ImportSpecifier::Named {
+                    imported, local, ..
+                } => (local, format!("{}.{}", binding, imported)),
+                ImportSpecifier::Namespace { local, .. } => (local, binding.clone()),
+            };
+            live.renames.insert(local.clone(), member);
+        }
+        let line = source[..stmt.span.start as usize].matches('\n').count();
+        let require = require_expr(&import.source, module_path, graph);
+        let replacement = match (declared.insert(dep), in_cycle) {
+            (true, true) => format!(
+                "function __import_{}() {{ return __toESM({}); }} {};",
+                dep, require, require
+            ),
+            (true, false) => format!("const {} = __toESM({});", binding, require),
+            (false, _) => String::new(),
+        };
+        live.lines.insert(line, replacement);
+    }
+    live
+}
+
+/// Replace the free uses of live imports with reads of the exporting
+/// module's exports. Returns `None` if `code` doesn't parse.
+fn rename_live_imports(code: &str, renames: &HashMap<String, String>) -> Option<String> {
+    let options = ParserOptions {
+        module: false,
+        ..Default::default()
+    };
+    let mut ast = Parser::new(code, options).parse().ok()?;
+    let renames = renames
+        .iter()
+        .map(|(local, member)| (local.clone(), member.clone()))
+        .collect();
+    howth_parser::mangle::rename_free(&mut ast, &renames);
+    Some(Codegen::new(&ast, CodegenOptions::default()).generate())
+}
+
+/// Point a CommonJS module's `require('./dep')` calls at the bundled module
+/// IDs. Requires of modules outside the bundle keep their specifier and are
+/// passed on to the host `require` at runtime (see [`push_module_registry`]).
+fn rewrite_requires(code: String, module_path: &str, graph: &ModuleGraph) -> String {
+    let Some((_, module)) = graph.get_by_path(std::path::Path::new(module_path)) else {
+        return code;
+    };
+    let mut code = code;
+    for import in module.imports.iter().filter(|import| !import.dynamic) {
+        let Some(id) = graph.resolve_specifier(module_path, &import.specifier) else {
+            continue;
+        };
+        let to = format!("require({})", id);
+        for quote in ['\'', '"', '`'] {
+            let from = format!("require({quote}{}{quote})", import.specifier);
+            if code.contains(&from) {
+                code = code.replace(&from, &to);
+            }
+        }
+    }
+    code
 }
 
 /// Filter SWC-generated `exports.xxx = xxx;` statements based on tree shaking.
@@ -625,13 +1315,14 @@ fn filter_swc_export(line: &str, used_exports: Option<&HashSet<String>>) -> Opti
 }
 
 /// Transform a single line (basic import/export rewriting).
-/// Returns (transformed_line, pending_exports_to_emit_later).
+/// Returns (transformed_line, pending_exports) with the exports as
+/// (exported, local) pairs.
 fn transform_line_with_exports(
     line: &str,
     module_path: &str,
     graph: &ModuleGraph,
     used_exports: Option<&HashSet<String>>,
-) -> Result<(String, Vec<String>), BundleError> {
+) -> Result<(String, Vec<(String, String)>), BundleError> {
     let trimmed = line.trim();
 
     // Rewrite imports
@@ -641,6 +1332,9 @@ fn transform_line_with_exports(
 
     // Rewrite exports
     if trimmed.starts_with("export ") {
+        if let Some(re_export) = rewrite_re_export(trimmed, module_path, graph, used_exports) {
+            return Ok((re_export, Vec::new()));
+        }
         let (transformed, exports) = rewrite_export_with_pending(line, used_exports);
         return Ok((transformed, exports));
     }
@@ -667,25 +1361,58 @@ fn is_asset_import(spec: &str) -> bool {
         .is_some_and(|ext| asset_exts.iter().any(|e| ext.eq_ignore_ascii_case(e)))
 }
 
+/// `require()` call for an import specifier: the module ID when it's in the
+/// graph, the specifier itself for external or unresolved imports.
+fn require_expr(spec: &str, module_path: &str, graph: &ModuleGraph) -> String {
+    if let Some(id) = graph.resolve_specifier(module_path, spec) {
+        format!("require({})", id)
+    } else {
+        format!("require('{}')", spec)
+    }
+}
+
+/// Drop the import attributes clause (`with { type: 'json' }`, or the older
+/// `assert { ... }`) from an import or re-export statement. Bundled modules
+/// are loaded by the module registry, so there's nothing to apply them to.
+fn strip_import_attributes(statement: &str) -> String {
+    // The specifier is the statement's first string literal
+    let Some(open) = statement.find(['\'', '"']) else {
+        return statement.to_string();
+    };
+    let quote = &statement[open..=open];
+    let Some(close) = statement[open + 1..].find(quote).map(|i| open + 1 + i) else {
+        return statement.to_string();
+    };
+    let rest = statement[close + 1..].trim_start();
+    let is_clause = ["with", "assert"].iter().any(|keyword| {
+        rest.strip_prefix(keyword)
+            .is_some_and(|clause| clause.trim_start().starts_with('{'))
+    });
+    if !is_clause {
+        return statement.to_string();
+    }
+    let semicolon = if rest.trim_end().ends_with(';') {
+        ";"
+    } else {
+        ""
+    };
+    format!("{}{}", &statement[..=close], semicolon)
+}
+
 /// Rewrite an import statement.
 fn rewrite_import(line: &str, module_path: &str, graph: &ModuleGraph) -> String {
-    // import { foo } from './bar' -> const { foo } = require(1)
-    // import foo from './bar' -> const foo = require(1).default
+    // import { foo } from './bar' -> const { foo } = require(1);
+    // import foo from './bar' -> const foo = __toESM(require(1)).default;
+    // import * as ns from './bar' -> const ns = __toESM(require(1));
+    // import foo, { bar } from './baz' -> const { default: foo, bar } = __toESM(require(1));
     // import './bar' -> require(1)
     // import './style.css' -> (CSS injection, handled at bundle level)
     // import logo from './logo.png' -> const logo = './logo.abc123.png'
 
-    let trimmed = line.trim();
+    let statement = strip_import_attributes(line.trim());
+    let trimmed = statement.as_str();
 
-    // Helper to resolve specifier to module ID or keep as string
-    let resolve_require = |spec: &str| -> String {
-        if let Some(id) = graph.resolve_specifier(module_path, spec) {
-            format!("require({})", id)
-        } else {
-            // External or unresolved - keep as string
-            format!("require('{}')", spec)
-        }
-    };
+    let resolve_require = |spec: &str| require_expr(spec, module_path, graph);
 
     // Side-effect import: import './foo'
     if let Some(rest) = trimmed.strip_prefix("import '") {
@@ -711,13 +1438,28 @@ fn rewrite_import(line: &str, module_path: &str, graph: &ModuleGraph) -> String
     if trimmed.contains('{') && trimmed.contains('}') && trimmed.contains(" from ") {
         // Extract the names and specifier
         if let Some(from_idx) = trimmed.find(" from ") {
-            let imports_part = &trimmed[7..from_idx]; // after "import "
+            let imports_part = trimmed[7..from_idx].trim(); // after "import "
             let spec_part = &trimmed[from_idx + 6..].trim();
-            let spec = spec_part.trim_matches(|c| c == '\'' || c == '"' || c == ';');
+            let spec = spec_part
+                .trim_end_matches(';')
+                .trim_matches(|c| c == '\'' || c == '"');
 
             // Convert import-style `as` to destructuring-style `:`
             // e.g. `{ jsx as _jsx }` → `{ jsx: _jsx }`
             let destructure_part = imports_part.replace(" as ", ": ");
+
+            // Default and named: import foo, { bar } from './baz'
+            if let Some((default, named)) = destructure_part.split_once(',') {
+                if !default.contains('{') {
+                    let named = named.trim().trim_start_matches('{').trim_end_matches('}');
+                    return format!(
+                        "const {{ default: {}, {} }} = __toESM({});",
+                        default.trim(),
+                        named.trim(),
+                        resolve_require(spec)
+                    );
+                }
+            }
             return format!("const {} = {};", destructure_part, resolve_require(spec));
         }
     }
@@ -727,12 +1469,25 @@ fn rewrite_import(line: &str, module_path: &str, graph: &ModuleGraph) -> String
         if let Some(from_idx) = trimmed.find(" from ") {
             let name = trimmed[7..from_idx].trim(); // after "import "
             let spec_part = &trimmed[from_idx + 6..].trim();
-            let spec = spec_part.trim_matches(|c| c == '\'' || c == '"' || c == ';');
+            let spec = spec_part
+                .trim_end_matches(';')
+                .trim_matches(|c| c == '\'' || c == '"');
 
             // Check for * as namespace import
             if name.starts_with("* as ") {
                 let ns_name = name.strip_prefix("* as ").unwrap().trim();
-                return format!("const {} = {};", ns_name, resolve_require(spec));
+                return format!("const {} = __toESM({});", ns_name, resolve_require(spec));
+            }
+
+            // Default and namespace: import foo, * as ns from './bar'
+            if let Some((default, ns_name)) = name.split_once(", * as ") {
+                return format!(
+                    "const {} = __toESM({}); const {} = {}.default;",
+                    ns_name.trim(),
+                    resolve_require(spec),
+                    default.trim(),
+                    ns_name.trim()
+                );
             }
 
             // Asset import: import logo from './logo.png'
@@ -741,8 +1496,11 @@ fn rewrite_import(line: &str, module_path: &str, graph: &ModuleGraph) -> String
                 return format!("const {} = '{}';", name, spec);
             }
 
-            let req = resolve_require(spec);
-            return format!("const {} = {}.default || {};", name, req, req);
+            return format!(
+                "const {} = __toESM({}).default;",
+                name,
+                resolve_require(spec)
+            );
         }
     }
 
@@ -750,13 +1508,72 @@ fn rewrite_import(line: &str, module_path: &str, graph: &ModuleGraph) -> String
     format!("/* TODO: transform */ {}", line)
 }
 
+/// Rewrite a re-export (`export * from`, `export * as ns from`,
+/// `export { a, b as c } from`). Returns `None` for other exports.
+///
+/// Names are read through `__toESM`, so a CommonJS module's `module.exports`
+/// re-exports as its `default` and its properties as named exports.
+fn rewrite_re_export(
+    trimmed: &str,
+    module_path: &str,
+    graph: &ModuleGraph,
+    used_exports: Option<&HashSet<String>>,
+) -> Option<String> {
+    let statement = strip_import_attributes(trimmed);
+    let (clause, spec_part) = statement.strip_prefix("export ")?.split_once(" from ")?;
+    let clause = clause.trim();
+    let spec = spec_part
+        .trim()
+        .trim_end_matches(';')
+        .trim_matches(|c| c == '\'' || c == '"');
+    let require = require_expr(spec, module_path, graph);
+    let is_used =
+        |name: &str| used_exports.is_none_or(|set| set.contains(name) || name == "default");
+
+    // export * from './mod'
+    if clause == "*" {
+        return Some(format!("__reExport(exports, {});", require));
+    }
+
+    // export * as ns from './mod'
+    if let Some(name) = clause.strip_prefix("* as ") {
+        let name = name.trim();
+        return Some(if is_used(name) {
+            format!("exports.{} = __toESM({});", name, require)
+        } else {
+            format!("{};", require)
+        });
+    }
+
+    // export { a, b as c } from './mod'
+    let names = clause.strip_prefix('{')?.strip_suffix('}')?;
+    let mut getters = Vec::new();
+    for name in names.split(',').map(str::trim).filter(|n| !n.is_empty()) {
+        let (imported, exported) = name
+            .split_once(" as ")
+            .map_or((name, name), |(i, e)| (i.trim(), e.trim()));
+        if is_used(exported) {
+            getters.push(format!("{}: () => __m.{}", exported, imported));
+        }
+    }
+    Some(if getters.is_empty() {
+        format!("{};", require)
+    } else {
+        format!(
+            "{{ const __m = __toESM({}); __export(exports, {{ {} }}); }}",
+            require,
+            getters.join(", ")
+        )
+    })
+}
+
 /// Rewrite an export statement, returning the transformed line and pending exports.
-/// Returns (transformed_line, vec_of_exports_to_emit_at_end).
+/// Returns (transformed_line, vec_of_(exported, local)_to_define_up_front).
 /// If used_exports is Some, only exports in that set will be emitted.
 fn rewrite_export_with_pending(
     line: &str,
     used_exports: Option<&HashSet<String>>,
-) -> (String, Vec<String>) {
+) -> (String, Vec<(String, String)>) {
     let trimmed = line.trim();
 
     // Helper to check if an export is used
@@ -770,6 +1587,20 @@ fn rewrite_export_with_pending(
     // export default - always include (entry point API)
     if trimmed.starts_with("export default ") {
         let value = trimmed.strip_prefix("export default ").unwrap();
+        // A named function stays a hoisted declaration, callable from an
+        // import cycle before this line runs
+        let name = value
+            .strip_prefix("function ")
+            .or_else(|| value.strip_prefix("async function "))
+            .and_then(|rest| rest.split_once('('))
+            .map(|(name, _)| name.trim())
+            .filter(|name| !name.is_empty() && name.chars().all(is_ident_char));
+        if let Some(name) = name {
+            return (
+                value.to_string(),
+                vec![("default".to_string(), name.to_string())],
+            );
+        }
         let value = value.trim_end_matches(';');
         return (format!("exports.default = {};", value), Vec::new());
     }
@@ -784,10 +1615,7 @@ fn rewrite_export_with_pending(
         if parts.len() >= 2 {
             let name = parts[1].trim_end_matches(['=', ':', ' ']);
             if is_used(name) {
-                return (
-                    decl.to_string(),
-                    vec![format!("exports.{} = {};", name, name)],
-                );
+                return (decl.to_string(), vec![(name.to_string(), name.to_string())]);
             } else {
                 // Tree shake: include declaration but don't export
                 return (decl.to_string(), Vec::new());
@@ -801,10 +1629,7 @@ fn rewrite_export_with_pending(
         if let Some(paren_idx) = decl.find('(') {
             let name = decl[9..paren_idx].trim(); // after "function "
             if is_used(name) {
-                return (
-                    decl.to_string(),
-                    vec![format!("exports.{} = {};", name, name)],
-                );
+                return (decl.to_string(), vec![(name.to_string(), name.to_string())]);
             } else {
                 // Tree shake: include function but don't export
                 return (decl.to_string(), Vec::new());
@@ -819,10 +1644,7 @@ fn rewrite_export_with_pending(
         if parts.len() >= 2 {
             let name = parts[1].trim_end_matches(|c: char| matches!(c, '{' | ' '));
             if is_used(name) {
-                return (
-                    decl.to_string(),
-                    vec![format!("exports.{} = {};", name, name)],
-                );
+                return (decl.to_string(), vec![(name.to_string(), name.to_string())]);
             } else {
                 // Tree shake: include class but don't export
                 return (decl.to_string(), Vec::new());
@@ -841,14 +1663,11 @@ fn rewrite_export_with_pending(
                 if name.contains(" as ") {
                     let parts: Vec<&str> = name.split(" as ").collect();
                     if parts.len() == 2 {
-                        export_stmts.push(format!(
-                            "exports.{} = {};",
-                            parts[1].trim(),
-                            parts[0].trim()
-                        ));
+                        export_stmts
+                            .push((parts[1].trim().to_string(), parts[0].trim().to_string()));
                     }
                 } else if !name.is_empty() {
-                    export_stmts.push(format!("exports.{} = {};", name, name));
+                    export_stmts.push((name.to_string(), name.to_string()));
                 }
             }
             return (String::new(), export_stmts); // Remove the export line, emit exports at end
@@ -885,6 +1704,7 @@ pub fn emit_scope_hoisted(
         output.push_str("// howth bundle (scope hoisted)\n");
         output.push_str("// Generated by howth v0.1.0\n\n");
     }
+    let body_start = output.len();
 
     match options.format {
         BundleFormat::Esm => emit_scope_hoisted_esm(graph, order, options, &ctx, &mut output)?,
@@ -892,19 +1712,36 @@ pub fn emit_scope_hoisted(
         BundleFormat::Iife => emit_scope_hoisted_iife(graph, order, options, &ctx, &mut output)?,
     }
 
+    // Map the bundle as emitted, while it still has its module comments;
+    // minifying or folding re-prints it, so that map is traced through the
+    // re-print's own mappings.
+    let mut map = options
+        .sourcemap
+        .then(|| build_sourcemap_from_output(&output, graph, order, Some(&ctx), &[]));
+
     // Run minifier when minify is enabled (whitespace removal)
     if options.minify {
-        output = minify_bundle(&output, options.mangle).unwrap_or(output);
+        if let Ok((minified, mappings)) = minify_bundle(&output, options.mangle, map.is_some()) {
+            if let Some(map) = &mut map {
+                *map = remap_reprint(map, &output, 0, &minified, &mappings);
+            }
+            output = minified;
+        }
+    } else if options.production {
+        let module = options.format == BundleFormat::Esm;
+        if let Some((folded, mappings)) = fold_code(&output[body_start..], module, map.is_some()) {
+            if let Some(map) = &mut map {
+                *map = remap_reprint(map, &output, body_start, &folded, &mappings);
+            }
+            output.truncate(body_start);
+            output.push_str(&folded);
+        }
     }
 
-    // Generate sourcemap if requested (must be after minification since line numbers change)
-    let map = if options.sourcemap {
-        Some(build_sourcemap_from_output(&output, graph, order))
-    } else {
-        None
-    };
-
-    Ok(BundleOutput { code: output, map })
+    Ok(BundleOutput {
+        code: output,
+        map: map.map(|map| map.to_json()),
+    })
 }
 
 /// Emit scope-hoisted ESM bundle.
@@ -920,22 +1757,13 @@ fn emit_scope_hoisted_esm(
     // For modules that need wrapping, emit the module registry
     let has_wrapped = order.iter().any(|&id| ctx.is_wrapped(id));
     if has_wrapped {
-        if minify {
-            output.push_str("const __modules={};const __exports={};");
-            output.push_str("function __require(id){if(__exports[id])return __exports[id];const module={exports:{}};__modules[id](module,module.exports,__require);__exports[id]=module.exports;return module.exports;}");
-        } else {
+        if !minify {
             output.push_str("// Module registry for wrapped modules\n");
-            output.push_str("const __modules = {};\n");
-            output.push_str("const __exports = {};\n\n");
-            output.push_str("function __require(id) {\n");
-            output.push_str("  if (__exports[id]) return __exports[id];\n");
-            output.push_str("  const module = { exports: {} };\n");
-            output.push_str("  __modules[id](module, module.exports, __require);\n");
-            output.push_str("  __exports[id] = module.exports;\n");
-            output.push_str("  return module.exports;\n");
-            output.push_str("}\n\n");
         }
+        push_module_registry(options, output);
     }
+    // Interop helpers the wrapped modules use go here, once they're emitted
+    let helpers_at = output.len();
 
     // Emit each module in topological order
     for &module_id in order {
@@ -946,7 +1774,13 @@ fn emit_scope_hoisted_esm(
         })?;
 
         if !minify {
-            output.push_str(&format!("// {}\n", module.path));
+            output.push_str(&format!("// {}\n", graph.output_id(&module.path)));
+        } else if options.sourcemap {
+            // Where the source map finds the module; minifying drops it
+            if !output.is_empty() && !output.ends_with('\n') {
+                output.push('\n');
+            }
+            output.push_str(&format!("// {}\n", graph.output_id(&module.path)));
         }
 
         if ctx.is_wrapped(module_id) {
@@ -963,6 +1797,11 @@ fn emit_scope_hoisted_esm(
         }
     }
 
+    if has_wrapped {
+        let helpers = interop_helpers(&[&output[helpers_at..]]);
+        output.insert_str(helpers_at, &helpers);
+    }
+
     Ok(())
 }
 
@@ -1089,7 +1928,7 @@ fn emit_hoisted_module(
     output: &mut String,
 ) -> Result<(), BundleError> {
     // Try AST-based renaming first
-    if let Ok(renamed_code) = emit_hoisted_module_ast(source, renames) {
+    if let Ok((renamed_code, _)) = emit_hoisted_module_ast(source, renames, false) {
         output.push_str(&renamed_code);
         return Ok(());
     }
@@ -1099,10 +1938,13 @@ fn emit_hoisted_module(
 }
 
 /// AST-based module emission with proper identifier renaming.
+///
+/// With `with_map`, also returns the mappings from the code back to `source`.
 fn emit_hoisted_module_ast(
     source: &str,
     renames: &HashMap<String, String>,
-) -> Result<String, BundleError> {
+    with_map: bool,
+) -> Result<(String, Vec<SourceMapping>), BundleError> {
     // Parse the source
     let parser_opts = ParserOptions {
         module: true,
@@ -1150,14 +1992,20 @@ fn emit_hoisted_module_ast(
     let filtered_ast = howth_parser::Ast::new(filtered_stmts, ast.source.clone());
 
     // Generate code with renames applied
-    let codegen_opts = CodegenOptions::default();
+    let codegen_opts = CodegenOptions {
+        source_map: with_map,
+        ..Default::default()
+    };
     let std_renames: std::collections::HashMap<String, String> = renames
         .iter()
         .map(|(k, v)| (k.clone(), v.clone()))
         .collect();
-    let code = Codegen::with_renames(&filtered_ast, codegen_opts, std_renames).generate();
-
-    Ok(code)
+    let codegen = Codegen::with_renames(&filtered_ast, codegen_opts, std_renames);
+    if with_map {
+        Ok(codegen.generate_with_source_map())
+    } else {
+        Ok((codegen.generate(), Vec::new()))
+    }
 }
 
 /// Fallback line-based module emission.
@@ -1260,7 +2108,7 @@ fn apply_renames(line: &str, renames: &HashMap<String, String>) -> String {
 
     // Sort by length (longest first) to avoid partial replacements
     let mut sorted_renames: Vec<_> = renames.iter().collect();
-    sorted_renames.sort_by(|a, b| b.0.len().cmp(&a.0.len()));
+    sorted_renames.sort_by_key(|r| std::cmp::Reverse(r.0.len()));
 
     for (old_name, new_name) in sorted_renames {
         if old_name == new_name {
@@ -1352,22 +2200,99 @@ mod tests {
         let graph = empty_graph();
         assert_eq!(
             rewrite_import("import React from 'react';", "/test/file.ts", &graph),
-            "const React = require('react').default || require('react');"
+            "const React = __toESM(require('react')).default;"
+        );
+    }
+
+    #[test]
+    fn test_rewrite_import_interop() {
+        let graph = empty_graph();
+        let rewrite = |line| rewrite_import(line, "/test/file.ts", &graph);
+        assert_eq!(
+            rewrite("import * as ns from './cjs';"),
+            "const ns = __toESM(require('./cjs'));"
+        );
+        assert_eq!(
+            rewrite("import def, { a, b as c } from './cjs';"),
+            "const { default: def, a, b: c } = __toESM(require('./cjs'));"
+        );
+        assert_eq!(
+            rewrite("import def, * as ns from './cjs';"),
+            "const ns = __toESM(require('./cjs')); const def = ns.default;"
+        );
+    }
+
+    #[test]
+    fn test_strip_import_attributes() {
+        let graph = empty_graph();
+        assert_eq!(
+            rewrite_import(
+                "import config from './config' with { type: 'json' };",
+                "/test/file.ts",
+                &graph
+            ),
+            "const config = __toESM(require('./config')).default;"
+        );
+        assert_eq!(
+            strip_import_attributes("import './a' assert { type: \"css\" }"),
+            "import './a'"
+        );
+        assert_eq!(
+            strip_import_attributes("export * from \"./with\";"),
+            "export * from \"./with\";"
         );
     }
 
+    #[test]
+    fn test_rewrite_re_export() {
+        let graph = empty_graph();
+        let rewrite = |line, used| rewrite_re_export(line, "/test/file.ts", &graph, used);
+        assert_eq!(
+            rewrite("export * from './a';", None).unwrap(),
+            "__reExport(exports, require('./a'));"
+        );
+        assert_eq!(
+            rewrite("export * as ns from './a' with { type: 'json' };", None).unwrap(),
+            "exports.ns = __toESM(require('./a'));"
+        );
+        assert_eq!(
+            rewrite("export { a, default as b } from './a';", None).unwrap(),
+            "{ const __m = __toESM(require('./a')); __export(exports, { a: () => __m.a, b: () => __m.default }); }"
+        );
+
+        let used: HashSet<String> = ["b".to_string()].into_iter().collect();
+        assert_eq!(
+            rewrite("export { a, c as b } from './a';", Some(&used)).unwrap(),
+            "{ const __m = __toESM(require('./a')); __export(exports, { b: () => __m.c }); }"
+        );
+        assert_eq!(
+            rewrite("export { a } from './a';", Some(&used)).unwrap(),
+            "require('./a');"
+        );
+        assert!(rewrite("export { a };", None).is_none());
+    }
+
+    #[test]
+    fn test_interop_helpers_only_when_used() {
+        assert_eq!(interop_helpers(&["const a = require(1);"]), "");
+        let helpers = interop_helpers(&["const a = __toESM(require(1)).default;"]);
+        assert!(helpers.contains("function __toESM("));
+        assert!(helpers.contains("function __markAsModule("));
+        assert!(!helpers.contains("function __reExport("));
+    }
+
     #[test]
     fn test_rewrite_export_const() {
         let (decl, exports) = rewrite_export_with_pending("export const foo = 1;", None);
         assert_eq!(decl, "const foo = 1;");
-        assert_eq!(exports, vec!["exports.foo = foo;"]);
+        assert_eq!(exports, vec![("foo".to_string(), "foo".to_string())]);
     }
 
     #[test]
     fn test_rewrite_export_default() {
         let (result, exports) = rewrite_export_with_pending("export default App;", None);
         assert_eq!(result, "exports.default = App;");
-        assert!(exports.is_empty());
+        assert_eq!(exports, Vec::<(String, String)>::new());
     }
 
     #[test]
@@ -1377,12 +2302,12 @@ mod tests {
 
         // Used export should be included
         let (_, exports) = rewrite_export_with_pending("export function usedFn() {}", Some(&used));
-        assert_eq!(exports, vec!["exports.usedFn = usedFn;"]);
+        assert_eq!(exports, vec![("usedFn".to_string(), "usedFn".to_string())]);
 
         // Unused export should be filtered
         let (_, exports) =
             rewrite_export_with_pending("export function unusedFn() {}", Some(&used));
-        assert!(exports.is_empty());
+        assert_eq!(exports, Vec::<(String, String)>::new());
     }
 
     #[test]
@@ -1712,6 +2637,64 @@ console.log(a + b);
         assert!(!output.code.contains("export async"));
     }
 
+    #[test]
+    fn test_production_removes_dead_branches() {
+        use crate::bundler::graph::Module;
+        use crate::bundler::BundleOptions;
+
+        let mut graph = ModuleGraph::new();
+        let id = graph.add(Module {
+            path: "/app.js".to_string(),
+            source: "if (\"production\" !== \"production\") { console.log(\"dev\"); }\nconst timeout = 60 * 1000;\nconsole.log(timeout);".to_string(),
+            imports: vec![],
+            dependencies: vec![],
+            dynamic_dependencies: vec![],
+        });
+
+        let order = vec![id];
+        let options = BundleOptions {
+            scope_hoist: true,
+            production: true,
+            ..Default::default()
+        };
+        let output = emit_scope_hoisted(&graph, &order, &options).unwrap();
+        assert!(!output.code.contains("\"dev\""));
+        assert!(output.code.contains("60000"));
+        assert!(output.code.starts_with("// howth bundle"));
+
+        // Development builds keep the code as written
+        let options = BundleOptions {
+            scope_hoist: true,
+            ..Default::default()
+        };
+        let output = emit_scope_hoisted(&graph, &order, &options).unwrap();
+        assert!(output.code.contains("\"dev\""));
+    }
+
+    #[test]
+    fn test_minify_folds_module() {
+        use crate::bundler::graph::Module;
+        use crate::bundler::BundleOptions;
+
+        let mut graph = ModuleGraph::new();
+        let id = graph.add(Module {
+            path: "/app.js".to_string(),
+            source: "if (typeof window === \"undefined\" && false) { server(); }\nmodule.exports = \"v\" + 2;".to_string(),
+            imports: vec![],
+            dependencies: vec![],
+            dynamic_dependencies: vec![],
+        });
+
+        let options = BundleOptions {
+            minify: true,
+            ..Default::default()
+        };
+        let module = graph.get(id).unwrap();
+        let code = emit_module_to_string(id, module, &graph, &options, None).unwrap();
+        assert!(!code.contains("server"), "{code}");
+        assert!(code.contains("\"v2\""));
+    }
+
     #[test]
     fn test_scope_hoisted_class() {
         use crate::bundler::graph::Module;
@@ -2049,7 +3032,7 @@ const internal = 42;
 
         let result = emit_hoisted_module("", &renames, &mut output);
         assert!(result.is_ok());
-        assert!(output.is_empty());
+        assert_eq!(output, "");
     }
 
     #[test]
@@ -2081,7 +3064,7 @@ const obj = { foo: foo, bar: foo };
         let mut renames = HashMap::default();
         renames.insert("foo".to_string(), "foo$1".to_string());
 
-        let result = emit_hoisted_module_ast(source, &renames);
+        let result = emit_hoisted_module_ast(source, &renames, false).map(|(code, _)| code);
         assert!(result.is_ok());
 
         let code = result.unwrap();
@@ -2104,7 +3087,7 @@ const y = new MyClass();
         renames.insert("myFunc".to_string(), "myFunc$1".to_string());
         renames.insert("MyClass".to_string(), "MyClass$1".to_string());
 
-        let result = emit_hoisted_module_ast(source, &renames);
+        let result = emit_hoisted_module_ast(source, &renames, false).map(|(code, _)| code);
         assert!(result.is_ok());
 
         let code = result.unwrap();
@@ -2125,7 +3108,7 @@ const x = foo + bar;
 ";
         let renames = HashMap::default();
 
-        let result = emit_hoisted_module_ast(source, &renames);
+        let result = emit_hoisted_module_ast(source, &renames, false).map(|(code, _)| code);
         assert!(result.is_ok());
 
         let code = result.unwrap();
@@ -2144,7 +3127,7 @@ export class Baz {}
 ";
         let renames = HashMap::default();
 
-        let result = emit_hoisted_module_ast(source, &renames);
+        let result = emit_hoisted_module_ast(source, &renames, false).map(|(code, _)| code);
         assert!(result.is_ok());
 
         let code = result.unwrap();
@@ -2164,6 +3147,7 @@ export class Baz {}
 
     #[test]
     fn test_vlq_encode_zero() {
+        use crate::sourcemap::vlq_encode;
         let mut out = String::new();
         vlq_encode(0, &mut out);
         assert_eq!(out, "A");
@@ -2171,6 +3155,7 @@ export class Baz {}
 
     #[test]
     fn test_vlq_encode_positive() {
+        use crate::sourcemap::vlq_encode;
         let mut out = String::new();
         vlq_encode(1, &mut out);
         assert_eq!(out, "C");
@@ -2178,6 +3163,7 @@ export class Baz {}
 
     #[test]
     fn test_vlq_encode_negative() {
+        use crate::sourcemap::vlq_encode;
         let mut out = String::new();
         vlq_encode(-1, &mut out);
         assert_eq!(out, "D");
@@ -2185,6 +3171,7 @@ export class Baz {}
 
     #[test]
     fn test_vlq_encode_large() {
+        use crate::sourcemap::vlq_encode;
         // 16 → encoded as 32 (shifted) → first 5 bits = 0, continuation, next = 1
         let mut out = String::new();
         vlq_encode(16, &mut out);
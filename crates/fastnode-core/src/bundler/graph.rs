@@ -3,8 +3,14 @@
 //! Tracks modules and their dependencies for bundling.
 
 use super::Import;
-use rustc_hash::FxHashMap as HashMap;
-use std::path::Path;
+use crate::sourcemap::SourceMap;
+use howth_parser::{
+    BindingKind, ExportDecl, ExprKind, ImportSpecifier, Parser, ParserOptions, StmtKind, VarKind,
+};
+use rustc_hash::{FxHashMap as HashMap, FxHashSet as HashSet};
+use std::borrow::Cow;
+use std::path::{Path, PathBuf};
+use std::sync::OnceLock;
 
 /// Unique identifier for a module in the graph.
 pub type ModuleId = usize;
@@ -33,6 +39,31 @@ pub struct ModuleGraph {
     path_to_id: HashMap<String, ModuleId>,
     /// Specifier resolution: (from_path, specifier) -> target_module_id.
     specifier_map: HashMap<(String, String), ModuleId>,
+    /// Files referenced with `new URL('./file', import.meta.url)`.
+    url_assets: Vec<PathBuf>,
+    /// Worker entries referenced with `new Worker(new URL(...))`.
+    workers: Vec<WorkerEntry>,
+    /// Strongly connected component of each module; modules in an import
+    /// cycle share one. Computed on first use.
+    components: OnceLock<Vec<usize>>,
+    /// Exports each module reassigns after declaring them. Computed on
+    /// first use.
+    reassigned_exports: OnceLock<Vec<HashSet<String>>>,
+    /// Source maps from each module's original source to its transformed
+    /// `source`, for modules plugins or the transpiler changed.
+    input_maps: HashMap<ModuleId, SourceMap>,
+    /// Directory module IDs in the output are written relative to, for
+    /// deterministic builds. `None` writes absolute paths.
+    id_root: Option<PathBuf>,
+}
+
+/// A worker script referenced with `new Worker(new URL('./w.js', import.meta.url))`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct WorkerEntry {
+    /// Resolved path of the worker entry.
+    pub path: PathBuf,
+    /// Whether it's a module worker (`{ type: 'module' }`).
+    pub module: bool,
 }
 
 impl ModuleGraph {
@@ -47,9 +78,16 @@ impl ModuleGraph {
         let id = self.modules.len();
         self.path_to_id.insert(module.path.clone(), id);
         self.modules.push(module);
+        self.invalidate();
         id
     }
 
+    /// Drop analyses of the previous shape of the graph.
+    fn invalidate(&mut self) {
+        self.components = OnceLock::new();
+        self.reassigned_exports = OnceLock::new();
+    }
+
     /// Get a module by ID.
     #[must_use]
     pub fn get(&self, id: ModuleId) -> Option<&Module> {
@@ -71,6 +109,71 @@ impl ModuleGraph {
         self.path_to_id.get(path).copied()
     }
 
+    /// Record a file referenced with `new URL('./file', import.meta.url)`.
+    pub fn add_url_asset(&mut self, path: PathBuf) {
+        if !self.url_assets.contains(&path) {
+            self.url_assets.push(path);
+        }
+    }
+
+    /// Files referenced with `new URL('./file', import.meta.url)`, emitted
+    /// as assets.
+    #[must_use]
+    pub fn url_assets(&self) -> &[PathBuf] {
+        &self.url_assets
+    }
+
+    /// Record a worker entry, bundled as its own chunk.
+    pub fn add_worker(&mut self, worker: WorkerEntry) {
+        if !self.workers.contains(&worker) {
+            self.workers.push(worker);
+        }
+    }
+
+    /// Worker entries referenced by modules in the graph.
+    #[must_use]
+    pub fn workers(&self) -> &[WorkerEntry] {
+        &self.workers
+    }
+
+    /// Record the source map from a module's original source to its
+    /// `source`. Its sources are rewritten as [`Self::output_id`]s.
+    pub fn set_input_map(&mut self, id: ModuleId, mut map: SourceMap) {
+        if self.id_root.is_some() {
+            for source in &mut map.sources {
+                *source = self.output_id(source).into_owned();
+            }
+        }
+        self.input_maps.insert(id, map);
+    }
+
+    /// Write module IDs in the output relative to `root` (see
+    /// [`Self::output_id`]).
+    pub fn set_id_root(&mut self, root: &Path) {
+        self.id_root = Some(dunce::canonicalize(root).unwrap_or_else(|_| root.to_path_buf()));
+    }
+
+    /// How a module path appears in the output: in module comments and as a
+    /// source map source. Absolute paths are relative to the
+    /// [ID root](Self::set_id_root) when there is one; other paths (virtual
+    /// modules) are kept.
+    #[must_use]
+    pub fn output_id<'a>(&self, path: &'a str) -> Cow<'a, str> {
+        match &self.id_root {
+            Some(root) if Path::new(path).is_absolute() => {
+                Cow::Owned(crate::deterministic::relative_path(root, Path::new(path)))
+            }
+            _ => Cow::Borrowed(path),
+        }
+    }
+
+    /// Source map from a module's original source to its `source`, if it
+    /// was transformed.
+    #[must_use]
+    pub fn input_map(&self, id: ModuleId) -> Option<&SourceMap> {
+        self.input_maps.get(&id)
+    }
+
     /// Number of modules in the graph.
     #[must_use]
     pub fn len(&self) -> usize {
@@ -85,6 +188,7 @@ impl ModuleGraph {
 
     /// Set dependencies from a map of module path -> (specifier, resolved_path, is_dynamic) tuples.
     pub fn set_dependencies(&mut self, dep_info: &HashMap<String, Vec<(String, String, bool)>>) {
+        self.invalidate();
         for module in &mut self.modules {
             if let Some(deps) = dep_info.get(&module.path) {
                 // Static dependencies
@@ -158,12 +262,30 @@ impl ModuleGraph {
             }
         }
 
-        // If we didn't get all modules, there's a cycle
-        // For now, include remaining modules anyway (circular deps are allowed in JS)
+        // If we didn't get all modules, there's a cycle (circular deps are
+        // allowed in JS). Order dependencies first as evaluation does, from
+        // the entry, so it stays last
         if order.len() < n {
-            for id in 0..n {
-                if !order.contains(&id) {
-                    order.push(id);
+            order.clear();
+            let mut visited = vec![false; n];
+            for root in 0..n {
+                if visited[root] {
+                    continue;
+                }
+                visited[root] = true;
+                // (module, next dependency to look at)
+                let mut stack = vec![(root, 0)];
+                while let Some((id, edge)) = stack.last_mut() {
+                    if let Some(&dep) = self.modules[*id].dependencies.get(*edge) {
+                        *edge += 1;
+                        if !visited[dep] {
+                            visited[dep] = true;
+                            stack.push((dep, 0));
+                        }
+                    } else {
+                        order.push(*id);
+                        stack.pop();
+                    }
                 }
             }
         }
@@ -175,6 +297,277 @@ impl ModuleGraph {
     pub fn iter(&self) -> impl Iterator<Item = (ModuleId, &Module)> {
         self.modules.iter().enumerate()
     }
+
+    /// Whether `from` and `to` import each other, directly or through other
+    /// modules. `from` can then run while `to` is still evaluating.
+    #[must_use]
+    pub fn in_cycle(&self, from: ModuleId, to: ModuleId) -> bool {
+        let components = self.components.get_or_init(|| self.strongly_connected());
+        if from == to {
+            return self.modules[from].dependencies.contains(&from);
+        }
+        components.get(from).is_some() && components.get(from) == components.get(to)
+    }
+
+    /// Names `id` exports from a `let` or `var` it later reassigns, so
+    /// importers have to read them live.
+    ///
+    /// Includes the names a module re-exports from such a module, with
+    /// `export * from`, `export { x } from` or by exporting an import.
+    #[must_use]
+    pub fn reassigned_exports(&self, id: ModuleId) -> Option<&HashSet<String>> {
+        let exports = self.reassigned_exports.get_or_init(|| {
+            let mut exports: Vec<HashSet<String>> = self
+                .modules
+                .iter()
+                .map(|module| reassigned_exports(&module.source))
+                .collect();
+            if exports.iter().all(HashSet::is_empty) {
+                return exports;
+            }
+
+            // (module, exported, imported, from); `*` forwards every name
+            let forwards: Vec<(ModuleId, String, String, ModuleId)> = self
+                .modules
+                .iter()
+                .enumerate()
+                .flat_map(|(id, module)| {
+                    forwarded_exports(&module.source).into_iter().filter_map(
+                        move |(exported, imported, specifier)| {
+                            let from = self.resolve_specifier(&module.path, &specifier)?;
+                            Some((id, exported, imported, from))
+                        },
+                    )
+                })
+                .collect();
+            let mut changed = true;
+            while changed {
+                changed = false;
+                for (id, exported, imported, from) in &forwards {
+                    let names: Vec<String> = match imported.as_str() {
+                        "*" => exports[*from]
+                            .iter()
+                            .filter(|name| *name != "default")
+                            .cloned()
+                            .collect(),
+                        _ if exports[*from].contains(imported) => vec![exported.clone()],
+                        _ => Vec::new(),
+                    };
+                    for name in names {
+                        changed |= exports[*id].insert(name);
+                    }
+                }
+            }
+            exports
+        });
+        exports.get(id).filter(|names| !names.is_empty())
+    }
+
+    /// Tarjan's algorithm, iteratively: the component of each module.
+    fn strongly_connected(&self) -> Vec<usize> {
+        const UNVISITED: usize = usize::MAX;
+        let n = self.modules.len();
+        let mut index = vec![UNVISITED; n];
+        let mut low = vec![0; n];
+        let mut on_stack = vec![false; n];
+        let mut component = vec![UNVISITED; n];
+        let mut stack = Vec::new();
+        let mut next_index = 0;
+        let mut next_component = 0;
+
+        for root in 0..n {
+            if index[root] != UNVISITED {
+                continue;
+            }
+            // (module, next dependency to look at)
+            let mut work = vec![(root, 0)];
+            while let Some(&mut (id, ref mut edge)) = work.last_mut() {
+                if *edge == 0 && index[id] == UNVISITED {
+                    index[id] = next_index;
+                    low[id] = next_index;
+                    next_index += 1;
+                    stack.push(id);
+                    on_stack[id] = true;
+                }
+                let dependencies = &self.modules[id].dependencies;
+                if let Some(&dep) = dependencies.get(*edge) {
+                    *edge += 1;
+                    if index[dep] == UNVISITED {
+                        work.push((dep, 0));
+                    } else if on_stack[dep] {
+                        low[id] = low[id].min(index[dep]);
+                    }
+                    continue;
+                }
+
+                work.pop();
+                if let Some(&(parent, _)) = work.last() {
+                    low[parent] = low[parent].min(low[id]);
+                }
+                if low[id] == index[id] {
+                    while let Some(member) = stack.pop() {
+                        on_stack[member] = false;
+                        component[member] = next_component;
+                        if member == id {
+                            break;
+                        }
+                    }
+                    next_component += 1;
+                }
+            }
+        }
+        component
+    }
+}
+
+/// Exports a module takes from other modules, as (exported, imported,
+/// specifier): `export { a as b } from './x'`, `export * from './x'` (both
+/// names `*`) and imports exported again with `export { a }`.
+fn forwarded_exports(source: &str) -> Vec<(String, String, String)> {
+    let mut forwards = Vec::new();
+    if !source.contains("export *") && !source.contains("export {") {
+        return forwards;
+    }
+    let options = ParserOptions {
+        module: true,
+        ..Default::default()
+    };
+    let Ok(ast) = Parser::new(source, options).parse() else {
+        return forwards;
+    };
+
+    // Local name -> (imported name, specifier)
+    let mut imports = HashMap::default();
+    for stmt in &ast.stmts {
+        if let StmtKind::Import(import) = &stmt.kind {
+            for spec in &import.specifiers {
+                match spec {
+                    ImportSpecifier::Default { local, .. } => {
+                        imports.insert(local.clone(), ("default".to_string(), &import.source));
+                    }
+                    ImportSpecifier::Named {
+                        imported, local, ..
+                    } => {
+                        imports.insert(local.clone(), (imported.clone(), &import.source));
+                    }
+                    ImportSpecifier::Namespace { .. } => {}
+                }
+            }
+        }
+    }
+    for stmt in &ast.stmts {
+        let StmtKind::Export(export) = &stmt.kind else {
+            continue;
+        };
+        match export.as_ref() {
+            ExportDecl::All {
+                exported: None,
+                source,
+                ..
+            } => forwards.push(("*".to_string(), "*".to_string(), source.clone())),
+            ExportDecl::Named {
+                specifiers,
+                source: Some(source),
+                ..
+            } => {
+                for spec in specifiers {
+                    forwards.push((spec.exported.clone(), spec.local.clone(), source.clone()));
+                }
+            }
+            ExportDecl::Named {
+                specifiers,
+                source: None,
+                ..
+            } => {
+                for spec in specifiers {
+                    if let Some((imported, source)) = imports.get(&spec.local) {
+                        forwards.push((spec.exported.clone(), imported.clone(), (*source).clone()));
+                    }
+                }
+            }
+            _ => {}
+        }
+    }
+    forwards
+}
+
+/// Exported names whose `let` or `var` binding is assigned again somewhere
+/// in the module.
+fn reassigned_exports(source: &str) -> HashSet<String> {
+    let mut exports = HashSet::default();
+    // Only modules exporting a `let` or `var` can have any
+    let may_export_mutable = source.contains("export let ")
+        || source.contains("export var ")
+        || (source.contains("export {") && (source.contains("let ") || source.contains("var ")));
+    if !may_export_mutable {
+        return exports;
+    }
+    let options = ParserOptions {
+        module: true,
+        ..Default::default()
+    };
+    let Ok(ast) = Parser::new(source, options).parse() else {
+        return exports;
+    };
+
+    // Top-level `let`/`var` names, and exported name -> local name
+    let mut mutable = HashSet::default();
+    let mut exported = Vec::new();
+    for stmt in &ast.stmts {
+        let (decl, is_export) = match &stmt.kind {
+            StmtKind::Export(export) => match export.as_ref() {
+                ExportDecl::Decl { decl, .. } => (decl, true),
+                ExportDecl::Named {
+                    specifiers,
+                    source: None,
+                    ..
+                } => {
+                    for spec in specifiers {
+                        exported.push((spec.exported.clone(), spec.local.clone()));
+                    }
+                    continue;
+                }
+                _ => continue,
+            },
+            _ => (stmt, false),
+        };
+        let StmtKind::Var { kind, decls } = &decl.kind else {
+            continue;
+        };
+        if matches!(kind, VarKind::Const) {
+            continue;
+        }
+        for decl in decls {
+            if let BindingKind::Ident { name, .. } = &decl.binding.kind {
+                mutable.insert(name.clone());
+                if is_export {
+                    exported.push((name.clone(), name.clone()));
+                }
+            }
+        }
+    }
+    if exported.is_empty() {
+        return exports;
+    }
+
+    // Any assignment to the name counts, even to a shadowing local
+    let mut assigned = HashSet::default();
+    howth_parser::visit::walk_exprs(&ast.stmts, &mut |expr| {
+        let target = match &expr.kind {
+            ExprKind::Assign { left, .. } => left,
+            ExprKind::Update { arg, .. } => arg,
+            _ => return,
+        };
+        if let ExprKind::Ident(name) = &target.kind {
+            assigned.insert(name.clone());
+        }
+    });
+    for (name, local) in exported {
+        if mutable.contains(&local) && assigned.contains(&local) {
+            exports.insert(name);
+        }
+    }
+    exports
 }
 
 #[cfg(test)]
@@ -234,4 +627,76 @@ mod tests {
         // C should come before B, B before A
         assert_eq!(order, vec![0, 1, 2]);
     }
+
+    fn module(path: &str, source: &str, dependencies: Vec<ModuleId>) -> Module {
+        Module {
+            path: path.to_string(),
+            source: source.to_string(),
+            imports: Vec::new(),
+            dependencies,
+            dynamic_dependencies: Vec::new(),
+        }
+    }
+
+    #[test]
+    fn test_in_cycle() {
+        let mut graph = ModuleGraph::new();
+        // a -> b -> c -> a, and a -> d
+        graph.add(module("/a.js", "", vec![1, 3]));
+        graph.add(module("/b.js", "", vec![2]));
+        graph.add(module("/c.js", "", vec![0]));
+        graph.add(module("/d.js", "", Vec::new()));
+
+        assert!(graph.in_cycle(0, 1));
+        assert!(graph.in_cycle(2, 0));
+        assert!(!graph.in_cycle(0, 3));
+        assert!(!graph.in_cycle(3, 3));
+
+        // Adding a module drops the cached components
+        graph.add(module("/e.js", "", vec![4]));
+        assert!(graph.in_cycle(4, 4));
+    }
+
+    #[test]
+    fn test_toposort_cycle_keeps_entry_last() {
+        let mut graph = ModuleGraph::new();
+        // entry -> a <-> b, entry -> c
+        graph.add(module("/entry.js", "", vec![1, 3]));
+        graph.add(module("/a.js", "", vec![2]));
+        graph.add(module("/b.js", "", vec![1]));
+        graph.add(module("/c.js", "", Vec::new()));
+
+        assert_eq!(graph.toposort(), vec![2, 1, 3, 0]);
+    }
+
+    #[test]
+    fn test_reassigned_exports() {
+        let mut graph = ModuleGraph::new();
+        graph.add(module(
+            "/counter.js",
+            "export let count = 0;\nexport let fixed = 1;\nexport function inc() { count++; }",
+            Vec::new(),
+        ));
+        graph.add(module(
+            "/named.js",
+            "var state = 'a';\nlet other = 1;\nexport { state as current, other };\nexport function set(s) { state = s; }",
+            Vec::new(),
+        ));
+        graph.add(module(
+            "/const.js",
+            "export const x = 1;\nexport function f() { let x = 2; x = 3; }",
+            Vec::new(),
+        ));
+
+        let counter = graph.reassigned_exports(0).unwrap();
+        assert!(counter.contains("count"));
+        assert!(!counter.contains("fixed"));
+
+        let named = graph.reassigned_exports(1).unwrap();
+        assert!(named.contains("current"));
+        assert!(!named.contains("state"));
+        assert!(!named.contains("other"));
+
+        assert!(graph.reassigned_exports(2).is_none());
+    }
 }
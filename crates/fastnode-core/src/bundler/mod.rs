@@ -14,7 +14,8 @@
 //!
 //! ## Architecture
 //!
-//! 1. **Resolution** - Resolve import specifiers to file paths
+//! 1. **Resolution** - Resolve import specifiers to file paths (Node.js
+//!    built-ins are polyfilled or stubbed for browser builds)
 //! 2. **Graph** - Build dependency graph from entry point
 //! 3. **Transform** - Transpile each module (TS → JS, JSX → JS)
 //! 4. **Emit** - Concatenate modules into single output
@@ -38,23 +39,31 @@
 #![allow(clippy::unnecessary_wraps)]
 #![allow(dead_code)]
 
+mod analyze;
 mod assets;
+mod builtins;
 mod chunks;
 mod emit;
 mod graph;
 mod plugin;
 pub mod plugins;
+mod remote;
+mod report;
 mod resolve;
 mod scope;
 mod treeshake;
 
+pub use analyze::{BundleAnalysis, ModuleSize, PackageSize, APP_PACKAGE};
 pub use assets::{Asset, AssetCollection, AssetType};
+pub use builtins::{builtin_name, default_polyfill, BuiltinStrategy, Platform, NODE_BUILTINS};
 pub use chunks::{Chunk, ChunkGraph, ChunkId, ChunkManifest};
 pub use emit::{
-    emit_bundle, emit_bundle_with_entry, emit_scope_hoisted, BundleFormat, BundleOutput,
+    emit_bundle, emit_bundle_with_entry, emit_chunk, emit_scope_hoisted, emit_shared_runtime,
+    BundleFormat, BundleOutput,
 };
-pub use graph::{Module, ModuleGraph, ModuleId};
+pub use graph::{Module, ModuleGraph, ModuleId, WorkerEntry};
 pub use plugin::{
+    virtual_css_id,
     AliasPlugin,
     BannerPlugin,
     ChunkInfo,
@@ -77,12 +86,20 @@ pub use plugin::{
     TransformResult,
     VirtualPlugin,
 };
+pub use report::{
+    GraphEdge, GraphExternal, GraphNode, GraphReport, ImportKind, NodeKind,
+    GRAPH_REPORT_SCHEMA_VERSION,
+};
 pub use resolve::{ResolveError, ResolveResult, Resolver};
 pub use scope::{ScopeHoistContext, Symbol, SymbolId, SymbolKind};
 pub use treeshake::UsedExports;
 
+use crate::compiler::asset_url::AssetUrlKind;
+use crate::howthignore::HowthIgnore;
+use crate::sourcemap::{compose, SourceMap};
 use rayon::prelude::*;
-use std::path::Path;
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
 
 /// Bundle options.
 #[derive(Debug, Clone)]
@@ -108,6 +125,78 @@ pub struct BundleOptions {
     /// Enable variable name mangling (shortens local variable names).
     /// Only effective when minify is also enabled.
     pub mangle: bool,
+    /// Platform the bundle runs on. Controls how Node.js built-ins are handled.
+    pub platform: Platform,
+    /// Per-module handling of Node.js built-ins in browser builds, keyed by
+    /// name without the `node:` prefix. Unlisted built-ins use their default
+    /// polyfill when installed and an empty stub otherwise.
+    pub builtins: HashMap<String, BuiltinStrategy>,
+    /// Download `http(s):` and decode `data:` modules imported statically and
+    /// bundle them. When disabled they are left as external imports.
+    /// Dynamic `import()` of a URL is always left as-is.
+    pub vendor_urls: bool,
+    /// How to treat bare package imports (`react`, `@scope/pkg/sub`).
+    pub packages: PackagesMode,
+    /// Measure each module's contribution to the output
+    /// ([`BundleResult::analysis`]).
+    pub analyze: bool,
+    /// Production build: fold constants and remove dead code
+    /// ([`howth_parser::fold`]) even when not minifying. Minified output is
+    /// always folded.
+    pub production: bool,
+    /// Reproducible output ([`crate::deterministic`]): module path comments
+    /// and source map sources are written relative to the working
+    /// directory instead of as absolute paths.
+    pub deterministic: bool,
+}
+
+/// How bare package imports are handled.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum PackagesMode {
+    /// Resolve from node_modules and bundle them.
+    #[default]
+    Bundle,
+    /// Leave every package import external (library builds). ESM output
+    /// keeps them as `import` statements.
+    External,
+}
+
+impl PackagesMode {
+    /// Parse a mode name (`bundle` or `external`).
+    pub fn parse(s: &str) -> Option<Self> {
+        match s.to_lowercase().as_str() {
+            "bundle" => Some(Self::Bundle),
+            "external" => Some(Self::External),
+            _ => None,
+        }
+    }
+}
+
+impl BundleOptions {
+    /// Check if an import is left external rather than bundled.
+    ///
+    /// True for specifiers matching [`BundleOptions::external`] and, with
+    /// [`PackagesMode::External`], for every bare package specifier.
+    /// Node.js built-ins are governed by [`BundleOptions::platform`] instead.
+    pub fn is_external(&self, specifier: &str) -> bool {
+        if self.external.iter().any(|e| specifier.starts_with(e)) {
+            return true;
+        }
+        self.packages == PackagesMode::External
+            && is_package_specifier(specifier)
+            && builtin_name(specifier).is_none()
+    }
+}
+
+/// Check if a specifier names a package (not a path, URL or `#` import).
+pub fn is_package_specifier(specifier: &str) -> bool {
+    !(specifier.is_empty()
+        || specifier.starts_with('.')
+        || specifier.starts_with('/')
+        || specifier.starts_with('#')
+        || specifier.starts_with('\\')
+        || remote::url_scheme(specifier).is_some()
+        || Path::new(specifier).is_absolute())
 }
 
 impl Default for BundleOptions {
@@ -122,6 +211,13 @@ impl Default for BundleOptions {
             splitting: false,   // Disabled by default
             scope_hoist: false, // Disabled by default for backwards compatibility
             mangle: false,      // Disabled by default
+            platform: Platform::Node,
+            builtins: HashMap::new(),
+            vendor_urls: false,
+            packages: PackagesMode::Bundle,
+            analyze: false,
+            production: false,
+            deterministic: false,
         }
     }
 }
@@ -145,6 +241,8 @@ pub struct BundleResult {
     pub css: Option<CssOutput>,
     /// Static assets to copy.
     pub assets: Vec<AssetOutput>,
+    /// Per-module size breakdown (with [`BundleOptions::analyze`]).
+    pub analysis: Option<BundleAnalysis>,
 }
 
 /// CSS output.
@@ -208,6 +306,18 @@ impl From<ResolveError> for BundleError {
 
 pub type BundleResult2 = Result<BundleResult, BundleError>;
 
+/// What an import specifier resolves to while building the graph.
+enum ImportTarget {
+    /// A file to bundle.
+    File(PathBuf),
+    /// An empty stub module for a Node.js built-in (virtual module path).
+    Stub(String),
+    /// A vendored URL or data: URI module, loaded at build time.
+    Remote(String),
+    /// Left as a runtime import (externals, built-ins on Node, unresolved).
+    Skip,
+}
+
 /// The main bundler.
 pub struct Bundler {
     resolver: Resolver,
@@ -251,6 +361,23 @@ impl Bundler {
         &mut self.plugins
     }
 
+    /// Build the module graph for `entries` without emitting a bundle.
+    ///
+    /// Modules are resolved and transpiled exactly as `bundle` would; the
+    /// returned ids are the entries' modules, in order.
+    pub fn module_graph(
+        &self,
+        entries: &[&Path],
+        cwd: &Path,
+        options: &BundleOptions,
+    ) -> Result<(ModuleGraph, Vec<ModuleId>), BundleError> {
+        let mut graph = ModuleGraph::new();
+        let mut warnings = Vec::new();
+        let entry_ids =
+            self.build_graph_parallel(entries, cwd, &mut graph, options, &mut warnings)?;
+        Ok((graph, entry_ids))
+    }
+
     /// Bundle from an entry point.
     pub fn bundle(&self, entry: &Path, cwd: &Path, options: &BundleOptions) -> BundleResult2 {
         // 0. Call build_start hook
@@ -262,20 +389,40 @@ impl Bundler {
 
         // 1. Build module graph starting from entry (using parallel processing)
         let mut graph = ModuleGraph::new();
-        let entry_id = self.build_graph_parallel(entry, cwd, &mut graph, options)?;
+        let mut warnings = Vec::new();
+        let entry_id =
+            self.build_graph_parallel(&[entry], cwd, &mut graph, options, &mut warnings)?[0];
 
         // 2. Check if code splitting is enabled and there are dynamic imports
         if options.splitting {
             let chunk_graph = ChunkGraph::from_module_graph(&graph, entry_id);
 
             if chunk_graph.has_splits() {
-                let result = self.bundle_with_splitting(&graph, &chunk_graph, options)?;
+                let mut result = self.bundle_with_splitting(&graph, &chunk_graph, options)?;
+                result.warnings = warnings;
+                result.assets = self.collect_assets(&graph, cwd)?.1;
+                if options.analyze {
+                    let chunks: Vec<(&str, &[ModuleId])> = chunk_graph
+                        .chunks()
+                        .iter()
+                        .map(|c| (c.name.as_str(), c.modules.as_slice()))
+                        .collect();
+                    result.analysis = Some(BundleAnalysis::new(
+                        &graph,
+                        &chunks,
+                        &[entry_id],
+                        cwd,
+                        options,
+                    )?);
+                }
                 // Call build_end hook
                 self.plugins.build_end().map_err(|e| BundleError {
                     code: "PLUGIN_ERROR",
                     message: e.to_string(),
                     path: None,
                 })?;
+                keep_shebang(&graph, entry_id, &mut result);
+                self.emit_workers(&graph, cwd, options, &mut result)?;
                 return Ok(result);
             }
         }
@@ -313,6 +460,17 @@ impl Bundler {
 
         // 6. Collect CSS and assets
         let (css, asset_outputs) = self.collect_assets(&graph, cwd)?;
+        let analysis = if options.analyze {
+            Some(BundleAnalysis::new(
+                &graph,
+                &[("main", order.as_slice())],
+                &[entry_id],
+                cwd,
+                options,
+            )?)
+        } else {
+            None
+        };
 
         // 7. Call build_end hook
         self.plugins.build_end().map_err(|e| BundleError {
@@ -321,19 +479,222 @@ impl Bundler {
             path: None,
         })?;
 
-        Ok(BundleResult {
+        let mut result = BundleResult {
             code: final_code,
             map: output.map,
             modules: order
                 .iter()
                 .map(|id| graph.get(*id).unwrap().path.clone())
                 .collect(),
-            warnings: Vec::new(),
+            warnings,
             chunks: Vec::new(),
             manifest: None,
             css,
             assets: asset_outputs,
-        })
+            analysis,
+        };
+        keep_shebang(&graph, entry_id, &mut result);
+        self.emit_workers(&graph, cwd, options, &mut result)?;
+        Ok(result)
+    }
+
+    /// Bundle several entry points at once.
+    ///
+    /// Produces one chunk per entry (named after the entry file), common
+    /// chunks for modules shared between entries, and a `runtime` chunk
+    /// holding the module registry they all share. Every output file is in
+    /// [`BundleResult::chunks`] (runtime first) and `code` is empty; the
+    /// manifest lists the entry and common chunks.
+    ///
+    /// Only the ESM and CJS formats are supported.
+    pub fn bundle_entries(
+        &self,
+        entries: &[&Path],
+        cwd: &Path,
+        options: &BundleOptions,
+    ) -> BundleResult2 {
+        if options.format == BundleFormat::Iife {
+            return Err(BundleError {
+                code: "BUNDLE_MULTI_ENTRY_FORMAT",
+                message: "Multiple entry points require the esm or cjs format".to_string(),
+                path: None,
+            });
+        }
+
+        self.plugins.build_start().map_err(|e| BundleError {
+            code: "PLUGIN_ERROR",
+            message: e.to_string(),
+            path: None,
+        })?;
+
+        let mut graph = ModuleGraph::new();
+        let mut warnings = Vec::new();
+        let entry_ids =
+            self.build_graph_parallel(entries, cwd, &mut graph, options, &mut warnings)?;
+
+        let names = entry_chunk_names(entries);
+        let named: Vec<(String, ModuleId)> =
+            names.into_iter().zip(entry_ids.iter().copied()).collect();
+        let chunk_graph = ChunkGraph::from_entries(&graph, &named);
+
+        let used_exports = options
+            .treeshake
+            .then(|| UsedExports::analyze_entries(&graph, &entry_ids));
+
+        let runtime_file = format!("{}.js", RUNTIME_CHUNK);
+        let mut chunk_outputs = vec![ChunkOutput {
+            name: RUNTIME_CHUNK.to_string(),
+            code: emit_shared_runtime(options),
+            map: None,
+        }];
+
+        for chunk in chunk_graph.chunks() {
+            let dependencies: Vec<String> = chunk
+                .dependencies
+                .iter()
+                .map(|&id| format!("{}.js", chunk_graph.chunks()[id].name))
+                .collect();
+            let output = emit_chunk(
+                &graph,
+                &chunk.modules,
+                chunk.is_entry.then_some(chunk.entry),
+                &runtime_file,
+                &dependencies,
+                options,
+                used_exports.as_ref(),
+            )?;
+
+            let code = if self.plugins.has_plugins() {
+                let chunk_info = ChunkInfo {
+                    name: chunk.name.clone(),
+                    is_entry: chunk.is_entry,
+                    modules: chunk
+                        .modules
+                        .iter()
+                        .filter_map(|id| graph.get(*id).map(|m| m.path.clone()))
+                        .collect(),
+                };
+                self.plugins
+                    .render_chunk(&output.code, &chunk_info)
+                    .map_err(|e| BundleError {
+                        code: "PLUGIN_ERROR",
+                        message: e.to_string(),
+                        path: None,
+                    })?
+            } else {
+                output.code
+            };
+
+            chunk_outputs.push(ChunkOutput {
+                name: chunk.name.clone(),
+                code,
+                map: output.map,
+            });
+        }
+
+        let mut manifest = chunk_graph.generate_manifest(&graph);
+        manifest.runtime = Some(runtime_file);
+
+        let analysis = if options.analyze {
+            let chunks: Vec<(&str, &[ModuleId])> = chunk_graph
+                .chunks()
+                .iter()
+                .map(|c| (c.name.as_str(), c.modules.as_slice()))
+                .collect();
+            Some(BundleAnalysis::new(
+                &graph, &chunks, &entry_ids, cwd, options,
+            )?)
+        } else {
+            None
+        };
+
+        let (css, asset_outputs) = self.collect_assets(&graph, cwd)?;
+
+        self.plugins.build_end().map_err(|e| BundleError {
+            code: "PLUGIN_ERROR",
+            message: e.to_string(),
+            path: None,
+        })?;
+
+        let mut result = BundleResult {
+            code: String::new(),
+            map: None,
+            modules: graph
+                .toposort()
+                .iter()
+                .filter_map(|id| graph.get(*id).map(|m| m.path.clone()))
+                .collect(),
+            warnings,
+            chunks: chunk_outputs,
+            manifest: Some(manifest),
+            css,
+            assets: asset_outputs,
+            analysis,
+        };
+        self.emit_workers(&graph, cwd, options, &mut result)?;
+        Ok(result)
+    }
+
+    /// Bundle each worker referenced with `new Worker(new URL(...))` as its
+    /// own chunk and point the placeholder URLs at it.
+    ///
+    /// Module workers (`{ type: 'module' }`) are bundled as ESM, classic
+    /// workers as an IIFE. Chunks are named after the worker file and a hash
+    /// of their code.
+    fn emit_workers(
+        &self,
+        graph: &ModuleGraph,
+        cwd: &Path,
+        options: &BundleOptions,
+        result: &mut BundleResult,
+    ) -> Result<(), BundleError> {
+        for worker in graph.workers() {
+            let worker_options = BundleOptions {
+                format: if worker.module {
+                    BundleFormat::Esm
+                } else {
+                    BundleFormat::Iife
+                },
+                // Workers load standalone: no chunks, no externals
+                splitting: false,
+                packages: PackagesMode::Bundle,
+                analyze: false,
+                ..options.clone()
+            };
+            let bundled = self.bundle(&worker.path, cwd, &worker_options)?;
+
+            let name = assets::worker_chunk_name(&worker.path, &bundled.code);
+            let placeholder = assets::worker_placeholder(&worker.path);
+            let url = format!("./{name}.js");
+            result.code = result.code.replace(&placeholder, &url);
+            for chunk in &mut result.chunks {
+                chunk.code = chunk.code.replace(&placeholder, &url);
+            }
+
+            result.chunks.push(ChunkOutput {
+                name,
+                code: bundled.code,
+                map: bundled.map,
+            });
+            // Workers of the worker
+            result.chunks.extend(bundled.chunks);
+            for asset in bundled.assets {
+                if !result.assets.iter().any(|a| a.name == asset.name) {
+                    result.assets.push(asset);
+                }
+            }
+            for module in bundled.modules {
+                if !result.modules.contains(&module) {
+                    result.modules.push(module);
+                }
+            }
+            for warning in bundled.warnings {
+                if !result.warnings.contains(&warning) {
+                    result.warnings.push(warning);
+                }
+            }
+        }
+        Ok(())
     }
 
     /// Bundle with code splitting enabled.
@@ -395,18 +756,31 @@ impl Bundler {
             manifest: Some(manifest),
             css: None, // TODO: collect CSS in splitting mode
             assets: Vec::new(),
+            analysis: None,
         })
     }
 
     /// Collect CSS and assets from the module graph.
+    ///
+    /// Static assets excluded by `.howthignore` are not emitted; CSS is
+    /// still bundled.
     fn collect_assets(
         &self,
         graph: &ModuleGraph,
         cwd: &Path,
     ) -> Result<(Option<CssOutput>, Vec<AssetOutput>), BundleError> {
         let mut collection = AssetCollection::new();
+        let ignore = HowthIgnore::load(&dunce::canonicalize(cwd).unwrap_or_else(|_| cwd.into()));
 
         for (_, module) in graph.iter() {
+            // CSS extracted by plugins (e.g. single-file component styles)
+            if let Some(css) = self.plugins.emitted_css(&module.path) {
+                collection.add_css(
+                    Path::new(&virtual_css_id(&module.path)),
+                    assets::process_css(&css),
+                );
+            }
+
             for import in &module.imports {
                 // Check if this is a CSS or asset import
                 if let Some(resolved) = self.try_resolve_asset(&import.specifier, &module.path, cwd)
@@ -419,7 +793,7 @@ impl Bundler {
                             let processed = assets::process_css(&content);
                             collection.add_css(&resolved, processed);
                         }
-                    } else if AssetType::is_asset(ext) {
+                    } else if AssetType::is_asset(ext) && !ignore.is_ignored(&resolved, false) {
                         // Read asset for hashing
                         if let Ok(content) = std::fs::read(&resolved) {
                             collection.add_asset(&resolved, &content);
@@ -429,6 +803,15 @@ impl Bundler {
             }
         }
 
+        for path in graph.url_assets() {
+            if ignore.is_ignored(path, false) {
+                continue;
+            }
+            if let Ok(content) = std::fs::read(path) {
+                collection.add_asset(path, &content);
+            }
+        }
+
         // Build outputs
         let css = if collection.has_css() {
             Some(CssOutput {
@@ -561,11 +944,7 @@ impl Bundler {
             let mut module_deps: Vec<(String, String, bool)> = Vec::new();
             for import in &imports {
                 // Check if external
-                if options
-                    .external
-                    .iter()
-                    .any(|e| import.specifier.starts_with(e))
-                {
+                if options.is_external(&import.specifier) {
                     continue;
                 }
 
@@ -659,7 +1038,7 @@ impl Bundler {
             // Add module to graph (dependencies will be set in second pass)
             let module = Module {
                 path: path_str,
-                source,
+                source: blank_shebang(source),
                 imports,
                 dependencies: Vec::new(),
                 dynamic_dependencies: Vec::new(),
@@ -681,6 +1060,133 @@ impl Bundler {
             })
     }
 
+    /// Resolve an import for the module graph.
+    ///
+    /// Returns the target and an optional warning. Node.js built-ins are
+    /// left alone on [`Platform::Node`] and handled per
+    /// [`BundleOptions::builtins`] on [`Platform::Browser`].
+    fn resolve_import(
+        &self,
+        import: &Import,
+        from: &Path,
+        cwd: &Path,
+        options: &BundleOptions,
+    ) -> Result<(ImportTarget, Option<String>), BundleError> {
+        let specifier = import.specifier.as_str();
+
+        // Imports inside a vendored URL module resolve against its URL;
+        // bare imports from it are left external
+        let from_str = from.to_str().unwrap_or("");
+        if remote::is_vendorable(from_str) && remote::url_scheme(specifier).is_none() {
+            let relative = specifier.starts_with("./")
+                || specifier.starts_with("../")
+                || specifier.starts_with('/');
+            return match remote::join_url(from_str, specifier) {
+                Some(url) if relative => Ok(self.resolve_url(&url, import.dynamic, options)),
+                _ => Ok((ImportTarget::Skip, None)),
+            };
+        }
+
+        match self.resolver.resolve(specifier, from, cwd) {
+            Ok(ResolveResult::Found(path)) => Ok((ImportTarget::File(path), None)),
+            Ok(ResolveResult::Builtin(_)) if options.platform == Platform::Browser => {
+                self.resolve_builtin(specifier, from, cwd, options)
+            }
+            Ok(ResolveResult::External(url)) if remote::url_scheme(&url).is_some() => {
+                Ok(self.resolve_url(&url, import.dynamic, options))
+            }
+            _ => Ok((ImportTarget::Skip, None)),
+        }
+    }
+
+    /// Decide what to do with a URL import.
+    ///
+    /// Unsupported schemes are passed through untouched with an
+    /// `UNSUPPORTED_SCHEME` warning.
+    fn resolve_url(
+        &self,
+        url: &str,
+        dynamic: bool,
+        options: &BundleOptions,
+    ) -> (ImportTarget, Option<String>) {
+        if !remote::is_vendorable(url) {
+            let warning = format!(
+                "{}: import '{}' uses an unsupported URL scheme and was left as-is",
+                remote::UNSUPPORTED_SCHEME,
+                url
+            );
+            return (ImportTarget::Skip, Some(warning));
+        }
+        if options.vendor_urls && !dynamic {
+            (ImportTarget::Remote(url.to_string()), None)
+        } else {
+            (ImportTarget::Skip, None)
+        }
+    }
+
+    /// Resolve a Node.js built-in import for a browser build.
+    fn resolve_builtin(
+        &self,
+        specifier: &str,
+        from: &Path,
+        cwd: &Path,
+        options: &BundleOptions,
+    ) -> Result<(ImportTarget, Option<String>), BundleError> {
+        let name = builtin_name(specifier).unwrap_or(specifier);
+        let find_polyfill = |pkg: &str| match self.resolver.resolve_package(pkg, from, cwd) {
+            Ok(ResolveResult::Found(path)) => Some(path),
+            _ => None,
+        };
+
+        match options.builtins.get(name) {
+            Some(BuiltinStrategy::Polyfill(pkg)) => match find_polyfill(pkg) {
+                Some(path) => Ok((ImportTarget::File(path), None)),
+                None => Err(BundleError {
+                    code: "BUNDLE_POLYFILL_NOT_FOUND",
+                    message: format!(
+                        "Cannot find polyfill '{}' for Node.js built-in '{}'; is it installed?",
+                        pkg, name
+                    ),
+                    path: Some(from.display().to_string()),
+                }),
+            },
+            Some(BuiltinStrategy::Empty) => {
+                Ok((ImportTarget::Stub(builtins::empty_stub_path(name)), None))
+            }
+            Some(BuiltinStrategy::Error) => Err(BundleError {
+                code: "BUNDLE_NODE_BUILTIN",
+                message: format!(
+                    "Node.js built-in '{}' is not available in the browser; remove the import, \
+                     or configure a polyfill or empty stub for it",
+                    specifier
+                ),
+                path: Some(from.display().to_string()),
+            }),
+            None => {
+                let polyfill = default_polyfill(name);
+                if let Some(path) = polyfill.and_then(find_polyfill) {
+                    return Ok((ImportTarget::File(path), None));
+                }
+                let warning = match polyfill {
+                    Some(pkg) => format!(
+                        "Node.js built-in '{}' was replaced with an empty module for the browser; \
+                         install '{}' to polyfill it",
+                        name,
+                        pkg.split('/').next().unwrap_or(pkg)
+                    ),
+                    None => format!(
+                        "Node.js built-in '{}' has no browser polyfill and was replaced with an empty module",
+                        name
+                    ),
+                };
+                Ok((
+                    ImportTarget::Stub(builtins::empty_stub_path(name)),
+                    Some(warning),
+                ))
+            }
+        }
+    }
+
     /// Extract import statements from source.
     fn extract_imports(&self, source: &str, path: &Path) -> Result<Vec<Import>, BundleError> {
         use crate::compiler::parse_imports;
@@ -694,26 +1200,36 @@ impl Bundler {
 
     /// Build the module graph with parallel file reading and transformation.
     /// This is significantly faster for large codebases.
+    ///
+    /// Returns the module ID of each entry, in order.
     fn build_graph_parallel(
         &self,
-        entry: &Path,
+        entries: &[&Path],
         cwd: &Path,
         graph: &mut ModuleGraph,
         options: &BundleOptions,
-    ) -> Result<ModuleId, BundleError> {
+        warnings: &mut Vec<String>,
+    ) -> Result<Vec<ModuleId>, BundleError> {
         use rustc_hash::{FxHashMap as HashMap, FxHashSet as HashSet};
 
-        let entry_path = if entry.is_absolute() {
-            entry.to_path_buf()
-        } else {
-            cwd.join(entry)
-        };
-
-        let entry_path = dunce::canonicalize(entry_path).map_err(|e| BundleError {
-            code: "BUNDLE_ENTRY_NOT_FOUND",
-            message: format!("Cannot find entry point: {}", e),
-            path: Some(entry.display().to_string()),
-        })?;
+        if options.deterministic {
+            graph.set_id_root(cwd);
+        }
+        let entry_paths = entries
+            .iter()
+            .map(|entry| {
+                let entry_path = if entry.is_absolute() {
+                    entry.to_path_buf()
+                } else {
+                    cwd.join(entry)
+                };
+                dunce::canonicalize(entry_path).map_err(|e| BundleError {
+                    code: "BUNDLE_ENTRY_NOT_FOUND",
+                    message: format!("Cannot find entry point: {}", e),
+                    path: Some(entry.display().to_string()),
+                })
+            })
+            .collect::<Result<Vec<_>, _>>()?;
 
         // Phase 1: Parallel discovery - process files level by level
         // Each level is processed in parallel for both reading and import extraction
@@ -721,32 +1237,63 @@ impl Bundler {
         let mut path_set: HashSet<String> = HashSet::default();
         let mut ordered_paths: Vec<String> = Vec::new();
 
-        // Start with entry file
-        let mut current_level: Vec<std::path::PathBuf> = vec![entry_path.clone()];
-        path_set.insert(entry_path.display().to_string());
+        // Start with the entry files
+        let mut current_level: Vec<std::path::PathBuf> = Vec::new();
+        for entry_path in &entry_paths {
+            if path_set.insert(entry_path.display().to_string()) {
+                current_level.push(entry_path.clone());
+            }
+        }
 
         // Process level by level
-        let externals = &options.external;
-
         while !current_level.is_empty() {
             // Read all files in current level in parallel, resolve imports in parallel too
-            let level_results: Vec<(String, String, Vec<Import>, Vec<std::path::PathBuf>)> =
-                current_level
-                    .par_iter()
-                    .filter_map(|path| {
-                        let path_str = path.display().to_string();
-                        let source = std::fs::read_to_string(path).ok()?;
-                        let imports = self.extract_imports(&source, path).unwrap_or_default();
-
-                        // Resolve imports in parallel (resolver uses RwLock cache)
-                        let mut resolved_deps = Vec::new();
-                        for import in &imports {
-                            if externals.iter().any(|e| import.specifier.starts_with(e)) {
-                                continue;
+            #[allow(clippy::type_complexity)]
+            let level_results: Vec<
+                Option<(String, String, Vec<Import>, Vec<PathBuf>)>,
+            > = current_level
+                .par_iter()
+                .map(|path| {
+                    let path_str = path.display().to_string();
+                    let source = if remote::is_vendorable(&path_str) {
+                        remote::load(&path_str).map_err(|message| BundleError {
+                            code: "BUNDLE_VENDOR_ERROR",
+                            message,
+                            path: Some(path_str.clone()),
+                        })?
+                    } else {
+                        match std::fs::read_to_string(path) {
+                            Ok(source) => source,
+                            Err(_) => return Ok(None),
+                        }
+                    };
+                    // Plugin-compiled files (e.g. `.vue`) only have imports once transformed
+                    let ext = path.extension().and_then(|e| e.to_str()).unwrap_or("");
+                    let imports = if self.plugins.handles_extension(ext) {
+                        let compiled = self.plugins.transform(&source, &path_str).map_err(|e| {
+                            BundleError {
+                                code: "PLUGIN_ERROR",
+                                message: e.to_string(),
+                                path: Some(path_str.clone()),
                             }
-                            if let Ok(ResolveResult::Found(dep_path)) =
-                                self.resolver.resolve(&import.specifier, path, cwd)
-                            {
+                        })?;
+                        let mut imports = self.extract_imports(&compiled, path).unwrap_or_default();
+                        add_require_imports(&compiled, &mut imports);
+                        imports
+                    } else {
+                        let mut imports = self.extract_imports(&source, path).unwrap_or_default();
+                        add_require_imports(&source, &mut imports);
+                        imports
+                    };
+
+                    // Resolve imports in parallel (resolver uses RwLock cache)
+                    let mut resolved_deps = Vec::new();
+                    for import in &imports {
+                        if options.is_external(&import.specifier) {
+                            continue;
+                        }
+                        match self.resolve_import(import, path, cwd, options) {
+                            Ok((ImportTarget::File(dep_path), _)) => {
                                 let ext =
                                     dep_path.extension().and_then(|e| e.to_str()).unwrap_or("");
                                 if AssetType::is_css(ext) || AssetType::is_asset(ext) {
@@ -754,16 +1301,21 @@ impl Bundler {
                                 }
                                 resolved_deps.push(dep_path);
                             }
+                            Ok((ImportTarget::Remote(url), _)) => {
+                                resolved_deps.push(PathBuf::from(url));
+                            }
+                            _ => {}
                         }
+                    }
 
-                        Some((path_str, source, imports, resolved_deps))
-                    })
-                    .collect();
+                    Ok(Some((path_str, source, imports, resolved_deps)))
+                })
+                .collect::<Result<_, BundleError>>()?;
 
             // Collect next level of files to process (sequential dedup only)
             let mut next_level: Vec<std::path::PathBuf> = Vec::new();
 
-            for (path_str, source, _imports, resolved_deps) in level_results {
+            for (path_str, source, _imports, resolved_deps) in level_results.into_iter().flatten() {
                 ordered_paths.push(path_str.clone());
 
                 for dep_path in resolved_deps {
@@ -790,24 +1342,45 @@ impl Bundler {
 
         // Phase 2: Transform all files AND resolve imports in parallel
         // Each worker: plugin transform → transpile → extract imports → resolve deps
-        let externals = &options.external;
 
+        #[allow(clippy::type_complexity)]
         let processed: Vec<
-            Result<(String, String, Vec<Import>, Vec<(String, String, bool)>), BundleError>,
+            Result<
+                (
+                    String,
+                    String,
+                    Vec<Import>,
+                    Vec<(String, String, bool)>,
+                    Vec<String>,
+                    Vec<PathBuf>,
+                    Vec<WorkerEntry>,
+                    Option<SourceMap>,
+                ),
+                BundleError,
+            >,
         > = paths_and_sources
             .par_iter()
             .map(|(path_str, source)| {
                 // First apply plugin transforms if any
-                let plugin_transformed = if has_plugins {
-                    self.plugins
-                        .transform(source, path_str)
-                        .map_err(|e| BundleError {
-                            code: "PLUGIN_ERROR",
-                            message: e.to_string(),
-                            path: Some(path_str.clone()),
-                        })?
+                let (plugin_transformed, plugin_map) = if has_plugins {
+                    let plugin_error = |e: PluginError| BundleError {
+                        code: "PLUGIN_ERROR",
+                        message: e.to_string(),
+                        path: Some(path_str.clone()),
+                    };
+                    if options.sourcemap {
+                        self.plugins
+                            .transform_with_map(source, path_str)
+                            .map_err(plugin_error)?
+                    } else {
+                        let code = self
+                            .plugins
+                            .transform(source, path_str)
+                            .map_err(plugin_error)?;
+                        (code, None)
+                    }
                 } else {
-                    source.clone()
+                    (source.clone(), None)
                 };
 
                 let ext = Path::new(path_str)
@@ -815,7 +1388,7 @@ impl Bundler {
                     .and_then(|e| e.to_str())
                     .unwrap_or("");
 
-                let (transpiled_code, imports) = match ext {
+                let (transpiled_code, mut imports) = match ext {
                     // Fast path: JSX files use howth-parser (no SWC)
                     "jsx" => crate::compiler::transform_jsx(&plugin_transformed).map_err(|e| {
                         BundleError {
@@ -847,59 +1420,188 @@ impl Bundler {
                     }
                 };
 
-                // Resolve imports to dependencies (in parallel!)
+                // Map from the file on disk to the transpiled code, chaining
+                // the plugin and transpile maps. A plugin that changed the
+                // code without a map leaves the module unmapped.
+                let input_map = if options.sourcemap {
+                    let transpile_map =
+                        crate::compiler::transpile_source_map(&plugin_transformed, path_str);
+                    match (plugin_map, transpile_map) {
+                        (Some(plugin_map), Some(transpile_map)) => {
+                            compose(&[&plugin_map, &transpile_map])
+                        }
+                        (None, transpile_map) if plugin_transformed == *source => transpile_map,
+                        (plugin_map, None) => plugin_map,
+                        (None, Some(_)) => None,
+                    }
+                } else {
+                    None
+                };
+
+                // CommonJS dependencies. A `require()` of a computed path is
+                // left to the host `require` at runtime.
+                let mut module_warnings = Vec::new();
+                if add_require_imports(&transpiled_code, &mut imports) > 0 {
+                    module_warnings.push(format!(
+                        "{}: {} calls require() with a computed path, which can't be bundled; \
+                         it is left to the runtime's require",
+                        DYNAMIC_REQUIRE, path_str
+                    ));
+                }
+
+                // `new URL('./file', import.meta.url)`: emit the file as a
+                // hashed asset next to the bundle. Worker entries get a
+                // placeholder until they're bundled as their own chunk.
                 let path = std::path::PathBuf::from(path_str);
+                let mut url_assets = Vec::new();
+                let mut workers = Vec::new();
+                let asset_urls = crate::compiler::asset_url::find_asset_urls(&transpiled_code);
+                let transpiled_code = if asset_urls.is_empty() {
+                    transpiled_code
+                } else {
+                    let dir = path.parent().unwrap_or(Path::new(""));
+                    let base = assets::asset_url_base(options.format, options.platform);
+                    crate::compiler::asset_url::rewrite_asset_urls(
+                        &transpiled_code,
+                        &asset_urls,
+                        base,
+                        |url| match url.kind {
+                            AssetUrlKind::Asset => {
+                                let file = dunce::canonicalize(dir.join(&url.specifier)).ok()?;
+                                let content = std::fs::read(&file).ok()?;
+                                let name = assets::asset_output_name(&file, &content);
+                                url_assets.push(file);
+                                Some(format!("./{name}"))
+                            }
+                            AssetUrlKind::Worker { module } => {
+                                let Ok(ResolveResult::Found(file)) =
+                                    self.resolver.resolve(&url.specifier, &path, cwd)
+                                else {
+                                    return None;
+                                };
+                                let placeholder = assets::worker_placeholder(&file);
+                                workers.push(WorkerEntry { path: file, module });
+                                Some(placeholder)
+                            }
+                        },
+                    )
+                };
+
+                // Resolve imports to dependencies (in parallel!)
                 let mut module_deps: Vec<(String, String, bool)> = Vec::new();
                 for import in &imports {
-                    if externals.iter().any(|e| import.specifier.starts_with(e)) {
+                    if options.is_external(&import.specifier) {
                         continue;
                     }
 
-                    if let Ok(ResolveResult::Found(dep_path)) =
-                        self.resolver.resolve(&import.specifier, &path, cwd)
-                    {
-                        let ext = dep_path.extension().and_then(|e| e.to_str()).unwrap_or("");
-                        if AssetType::is_css(ext) || AssetType::is_asset(ext) {
-                            continue;
-                        }
+                    let (target, warning) = self.resolve_import(import, &path, cwd, options)?;
+                    module_warnings.extend(warning);
+                    match target {
+                        ImportTarget::File(dep_path) => {
+                            let ext = dep_path.extension().and_then(|e| e.to_str()).unwrap_or("");
+                            if AssetType::is_css(ext) || AssetType::is_asset(ext) {
+                                continue;
+                            }
 
-                        let dep_str = dep_path.display().to_string();
-                        module_deps.push((import.specifier.clone(), dep_str, import.dynamic));
+                            let dep_str = dep_path.display().to_string();
+                            module_deps.push((import.specifier.clone(), dep_str, import.dynamic));
+                        }
+                        ImportTarget::Stub(dep_str) | ImportTarget::Remote(dep_str) => {
+                            module_deps.push((import.specifier.clone(), dep_str, import.dynamic));
+                        }
+                        ImportTarget::Skip => {}
                     }
                 }
 
-                Ok((path_str.clone(), transpiled_code, imports, module_deps))
+                Ok((
+                    path_str.clone(),
+                    transpiled_code,
+                    imports,
+                    module_deps,
+                    module_warnings,
+                    url_assets,
+                    workers,
+                    input_map,
+                ))
             })
             .collect();
 
         // Phase 3: Build the graph from processed results (just assembly, no I/O)
         let mut dep_info: HashMap<String, Vec<(String, String, bool)>> = HashMap::default();
 
+        let mut stubs: Vec<String> = Vec::new();
+
         for result in processed {
-            let (path_str, source, imports, module_deps) = result?;
+            let (
+                path_str,
+                source,
+                imports,
+                module_deps,
+                module_warnings,
+                url_assets,
+                workers,
+                input_map,
+            ) = result?;
+
+            for (_, dep_path, _) in &module_deps {
+                if dep_path.starts_with(builtins::EMPTY_STUB_PREFIX) && !stubs.contains(dep_path) {
+                    stubs.push(dep_path.clone());
+                }
+            }
+            for warning in module_warnings {
+                if !warnings.contains(&warning) {
+                    warnings.push(warning);
+                }
+            }
+
+            for asset in url_assets {
+                graph.add_url_asset(asset);
+            }
+            for worker in workers {
+                graph.add_worker(worker);
+            }
 
             dep_info.insert(path_str.clone(), module_deps);
 
             let module = Module {
                 path: path_str,
-                source,
+                source: blank_shebang(source),
                 imports,
                 dependencies: Vec::new(),
                 dynamic_dependencies: Vec::new(),
             };
-            graph.add(module);
+            let id = graph.add(module);
+            if let Some(map) = input_map {
+                graph.set_input_map(id, map);
+            }
+        }
+
+        // Empty stubs for Node.js built-ins are virtual modules
+        for stub in stubs {
+            graph.add(Module {
+                path: stub,
+                source: builtins::EMPTY_STUB_SOURCE.to_string(),
+                imports: Vec::new(),
+                dependencies: Vec::new(),
+                dynamic_dependencies: Vec::new(),
+            });
         }
 
         graph.set_dependencies(&dep_info);
 
-        graph
-            .get_by_path(&entry_path)
-            .map(|m| m.0)
-            .ok_or_else(|| BundleError {
-                code: "BUNDLE_INTERNAL_ERROR",
-                message: "Entry module not found after graph build".to_string(),
-                path: None,
+        entry_paths
+            .iter()
+            .map(|entry_path| {
+                graph
+                    .get_by_path(entry_path)
+                    .map(|m| m.0)
+                    .ok_or_else(|| BundleError {
+                        code: "BUNDLE_INTERNAL_ERROR",
+                        message: "Entry module not found after graph build".to_string(),
+                        path: None,
+                    })
             })
+            .collect()
     }
 }
 
@@ -909,6 +1611,77 @@ impl Default for Bundler {
     }
 }
 
+/// Warning code for a `require()` with a computed path.
+pub const DYNAMIC_REQUIRE: &str = "DYNAMIC_REQUIRE";
+
+/// Add the modules `code` loads with `require('x')` to its imports.
+///
+/// A required module can use any of its exports, so the import is a
+/// namespace import. Returns the number of `require()` calls with a
+/// computed path, which can't be bundled.
+/// Shebang line (`#!/usr/bin/env node`) at the start of a file, if any.
+fn read_shebang(path: &Path) -> Option<String> {
+    let source = std::fs::read_to_string(path).ok()?;
+    let line = source.lines().next()?;
+    line.starts_with("#!").then(|| line.trim_end().to_string())
+}
+
+/// Blank out a module's shebang line, which is only valid at the very start
+/// of a file. The line itself is kept so line numbers don't shift.
+fn blank_shebang(source: String) -> String {
+    if !source.starts_with("#!") {
+        return source;
+    }
+    let end = source.find('\n').unwrap_or(source.len());
+    source[end..].to_string()
+}
+
+/// Start the bundle with the entry's shebang so CLI entries stay runnable
+/// as-is. Parsing drops it, so it's read from the entry file.
+fn keep_shebang(graph: &ModuleGraph, entry_id: ModuleId, result: &mut BundleResult) {
+    let Some(entry) = graph.get(entry_id) else {
+        return;
+    };
+    let Some(shebang) = read_shebang(Path::new(&entry.path)) else {
+        return;
+    };
+    if result.code.starts_with("#!") {
+        // A plugin already emitted one
+        return;
+    }
+    result.code = format!("{shebang}\n{}", result.code);
+    if let Some(mut map) = result.map.as_deref().and_then(|m| SourceMap::parse(m).ok()) {
+        map.prepend_lines(1);
+        result.map = Some(map.to_json());
+    }
+}
+
+fn add_require_imports(code: &str, imports: &mut Vec<Import>) -> usize {
+    let calls = crate::compiler::require::find_require_calls(code);
+    let namespace = || ImportedName {
+        imported: "*".to_string(),
+        local: "*".to_string(),
+    };
+    for specifier in calls.specifiers {
+        match imports
+            .iter_mut()
+            .find(|import| !import.dynamic && import.specifier == specifier)
+        {
+            Some(import) => {
+                if !import.names.iter().any(|name| name.imported == "*") {
+                    import.names.push(namespace());
+                }
+            }
+            None => imports.push(Import {
+                specifier,
+                dynamic: false,
+                names: vec![namespace()],
+            }),
+        }
+    }
+    calls.dynamic
+}
+
 /// Generate the runtime code for loading chunks dynamically.
 fn generate_chunk_loader_runtime(chunk_graph: &ChunkGraph) -> String {
     let mut runtime = String::new();
@@ -948,6 +1721,33 @@ fn generate_chunk_loader_runtime(chunk_graph: &ChunkGraph) -> String {
     runtime
 }
 
+/// Name of the shared runtime chunk in multi-entry builds.
+pub const RUNTIME_CHUNK: &str = "runtime";
+
+/// Chunk names for multi-entry builds: the entry file stems, with a numeric
+/// suffix when two entries share a stem (or an entry is named `runtime`).
+fn entry_chunk_names(entries: &[&Path]) -> Vec<String> {
+    let mut used: Vec<String> = vec![RUNTIME_CHUNK.to_string()];
+    entries
+        .iter()
+        .map(|entry| {
+            let stem = entry
+                .file_stem()
+                .and_then(|s| s.to_str())
+                .unwrap_or("entry")
+                .to_string();
+            let mut name = stem.clone();
+            let mut n = 2;
+            while used.contains(&name) {
+                name = format!("{}-{}", stem, n);
+                n += 1;
+            }
+            used.push(name.clone());
+            name
+        })
+        .collect()
+}
+
 /// An import statement.
 #[derive(Debug, Clone)]
 pub struct Import {
@@ -967,3 +1767,907 @@ pub struct ImportedName {
     /// The local binding name.
     pub local: String,
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::fs;
+
+    fn write(dir: &Path, path: &str, content: &str) {
+        let path = dir.join(path);
+        fs::create_dir_all(path.parent().unwrap()).unwrap();
+        fs::write(path, content).unwrap();
+    }
+
+    fn browser_options(builtins: &[(&str, BuiltinStrategy)]) -> BundleOptions {
+        BundleOptions {
+            platform: Platform::Browser,
+            builtins: builtins
+                .iter()
+                .map(|(name, strategy)| ((*name).to_string(), strategy.clone()))
+                .collect(),
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn test_plugin_extension_modules_and_css() {
+        let dir = tempfile::tempdir().unwrap();
+        write(
+            dir.path(),
+            "index.js",
+            "import Hello from './Hello.sfc';\nconsole.log(Hello);\n",
+        );
+        write(
+            dir.path(),
+            "Hello.sfc",
+            "<script>\nimport { greet } from './greet.js';\nexport default { msg: greet() };\n</script>\n\
+             <template><p class=\"hello\">{{ msg }}</p></template>\n\
+             <style>.hello { color: red; }</style>\n",
+        );
+        write(dir.path(), "greet.js", "export const greet = () => 'hi';\n");
+
+        let result = Bundler::new()
+            .plugin(Box::new(plugins::SfcPlugin::new("sfc")))
+            .bundle(Path::new("index.js"), dir.path(), &BundleOptions::default())
+            .unwrap();
+
+        // The component's imports are followed once it is compiled
+        assert!(result.modules.iter().any(|m| m.ends_with("greet.js")));
+        assert!(result.code.contains("__sfc__.template"));
+        let css = result.css.unwrap();
+        assert!(css.code.contains(".hello"));
+    }
+
+    #[test]
+    fn test_howthignored_assets_are_not_emitted() {
+        let dir = tempfile::tempdir().unwrap();
+        write(
+            dir.path(),
+            "index.ts",
+            "const logo = new URL('./assets/logo.png', import.meta.url);\n\
+             const big = new URL('./vendor/big.png', import.meta.url);\n\
+             console.log(logo, big);\n",
+        );
+        write(dir.path(), "assets/logo.png", "png");
+        write(dir.path(), "vendor/big.png", "big");
+        write(dir.path(), ".howthignore", "vendor/\n");
+
+        let result = Bundler::new()
+            .bundle(
+                Path::new("index.ts"),
+                dir.path(),
+                &BundleOptions {
+                    format: BundleFormat::Esm,
+                    ..BundleOptions::default()
+                },
+            )
+            .unwrap();
+
+        assert_eq!(result.assets.len(), 1);
+        assert!(result.assets[0].source.ends_with("assets/logo.png"));
+    }
+
+    #[test]
+    fn test_new_url_assets_are_hashed_and_emitted() {
+        let dir = tempfile::tempdir().unwrap();
+        write(
+            dir.path(),
+            "index.ts",
+            "const logo: URL = new URL('./assets/logo.png', import.meta.url);\n\
+             const missing = new URL('./missing.png', import.meta.url);\n\
+             console.log(logo, missing);\n",
+        );
+        write(dir.path(), "assets/logo.png", "png");
+
+        let result = Bundler::new()
+            .bundle(
+                Path::new("index.ts"),
+                dir.path(),
+                &BundleOptions {
+                    format: BundleFormat::Esm,
+                    ..BundleOptions::default()
+                },
+            )
+            .unwrap();
+
+        assert_eq!(result.assets.len(), 1);
+        let name = &result.assets[0].name;
+        assert!(name.starts_with("logo.") && name.ends_with(".png"));
+        assert!(result.assets[0].source.ends_with("assets/logo.png"));
+        assert!(result
+            .code
+            .contains(&format!("new URL(\"./{name}\", import.meta.url)")));
+        assert!(result.code.contains("./missing.png"));
+
+        // `import.meta.url` doesn't exist outside ES modules
+        let cjs = Bundler::new()
+            .bundle(
+                Path::new("index.ts"),
+                dir.path(),
+                &BundleOptions {
+                    format: BundleFormat::Cjs,
+                    ..BundleOptions::default()
+                },
+            )
+            .unwrap();
+        assert!(cjs
+            .code
+            .contains(&format!("new URL(\"./{name}\", \"file:\" + __filename)")));
+    }
+
+    #[test]
+    fn test_cjs_esm_interop_helpers() {
+        let dir = tempfile::tempdir().unwrap();
+        write(
+            dir.path(),
+            "index.js",
+            "import greet, { name } from './cjs.js';\n\
+             import * as esm from './esm.js';\n\
+             export * from './cjs.js';\n\
+             console.log(greet(name), esm.default);\n",
+        );
+        write(
+            dir.path(),
+            "cjs.js",
+            "module.exports = function greet(n) { return n; };\nmodule.exports.name = 'x';\n",
+        );
+        write(dir.path(), "esm.js", "export default 1;\n");
+
+        let result = Bundler::new()
+            .bundle(Path::new("index.js"), dir.path(), &BundleOptions::default())
+            .unwrap();
+
+        // Defined once, ahead of the modules
+        assert_eq!(result.code.matches("function __toESM(").count(), 1);
+        assert_eq!(result.code.matches("function __reExport(").count(), 1);
+        assert!(result.code.find("function __toESM(") < result.code.find("__modules[0]"));
+        assert!(result
+            .code
+            .contains("const { default: greet, name } = __toESM(require("));
+        assert!(result.code.contains("const esm = __toESM(require("));
+        assert!(result.code.contains("__reExport(exports, require("));
+        // Only the ES modules are flagged
+        assert_eq!(result.code.matches("__markAsModule(exports);").count(), 2);
+
+        // No interop needed, no helpers
+        write(dir.path(), "plain.js", "console.log(1);\n");
+        let plain = Bundler::new()
+            .bundle(Path::new("plain.js"), dir.path(), &BundleOptions::default())
+            .unwrap();
+        assert!(!plain.code.contains("__markAsModule"));
+        assert!(!plain.code.contains("__toESM"));
+    }
+
+    #[test]
+    fn test_workers_are_bundled_as_chunks() {
+        let dir = tempfile::tempdir().unwrap();
+        write(
+            dir.path(),
+            "index.ts",
+            "const a = new Worker(new URL('./worker', import.meta.url), { type: 'module' });\n\
+             const b = new Worker(new URL('./classic.js', import.meta.url));\n\
+             console.log(a, b);\n",
+        );
+        write(
+            dir.path(),
+            "worker.ts",
+            "import { square } from './math';\nself.onmessage = (e: MessageEvent) => self.postMessage(square(e.data));\n",
+        );
+        write(
+            dir.path(),
+            "math.ts",
+            "export const square = (n: number) => n * n;\n",
+        );
+        write(dir.path(), "classic.js", "self.postMessage('ready');\n");
+
+        let result = Bundler::new()
+            .bundle(
+                Path::new("index.ts"),
+                dir.path(),
+                &BundleOptions {
+                    format: BundleFormat::Esm,
+                    ..BundleOptions::default()
+                },
+            )
+            .unwrap();
+
+        assert_eq!(result.chunks.len(), 2);
+        let module = &result.chunks[0];
+        let classic = &result.chunks[1];
+        assert!(module.name.starts_with("worker."));
+        assert!(module.code.contains("n * n"));
+        assert!(classic.name.starts_with("classic."));
+        assert!(classic.code.contains("postMessage('ready')"));
+        for chunk in &result.chunks {
+            assert!(result.code.contains(&format!(
+                "new URL(\"./{}.js\", import.meta.url)",
+                chunk.name
+            )));
+        }
+        assert!(!result.code.contains("__HOWTH_WORKER_"));
+        // The worker's modules stay out of the main bundle
+        assert!(!result.code.contains("n * n"));
+        assert!(result.modules.iter().any(|m| m.ends_with("math.ts")));
+    }
+
+    #[test]
+    fn test_node_platform_keeps_builtin_imports() {
+        let dir = tempfile::tempdir().unwrap();
+        write(dir.path(), "index.js", "const fs = require('fs');\n");
+
+        let result = Bundler::new()
+            .bundle(Path::new("index.js"), dir.path(), &BundleOptions::default())
+            .unwrap();
+        assert!(result.code.contains("require('fs')"));
+        assert_eq!(result.modules.len(), 1);
+        assert_eq!(result.warnings, Vec::<String>::new());
+    }
+
+    #[test]
+    fn test_browser_builtins_polyfill_or_stub_with_warning() {
+        let dir = tempfile::tempdir().unwrap();
+        write(
+            dir.path(),
+            "index.js",
+            "import path from 'path';\nimport fs from 'node:fs';\nconsole.log(path, fs);\n",
+        );
+        write(
+            dir.path(),
+            "node_modules/path-browserify/package.json",
+            r#"{"name":"path-browserify","main":"index.js"}"#,
+        );
+        write(
+            dir.path(),
+            "node_modules/path-browserify/index.js",
+            "module.exports = { sep: '/' };\n",
+        );
+
+        let result = Bundler::new()
+            .bundle(Path::new("index.js"), dir.path(), &browser_options(&[]))
+            .unwrap();
+
+        assert!(result
+            .modules
+            .iter()
+            .any(|m| m.ends_with("path-browserify/index.js")));
+        assert!(result.modules.contains(&"(empty):fs".to_string()));
+        assert!(!result.code.contains("require('path')"));
+        assert!(!result.code.contains("require('node:fs')"));
+        assert_eq!(result.warnings.len(), 1);
+        assert!(result.warnings[0].contains("'fs'"));
+    }
+
+    #[test]
+    fn test_browser_builtins_configured_per_module() {
+        let dir = tempfile::tempdir().unwrap();
+        write(dir.path(), "index.js", "import crypto from 'crypto';\n");
+
+        // Explicit empty stub: no warning
+        let result = Bundler::new()
+            .bundle(
+                Path::new("index.js"),
+                dir.path(),
+                &browser_options(&[("crypto", BuiltinStrategy::Empty)]),
+            )
+            .unwrap();
+        assert_eq!(result.warnings, Vec::<String>::new());
+        assert!(result.modules.contains(&"(empty):crypto".to_string()));
+
+        let err = Bundler::new()
+            .bundle(
+                Path::new("index.js"),
+                dir.path(),
+                &browser_options(&[("crypto", BuiltinStrategy::Error)]),
+            )
+            .unwrap_err();
+        assert_eq!(err.code, "BUNDLE_NODE_BUILTIN");
+
+        let err = Bundler::new()
+            .bundle(
+                Path::new("index.js"),
+                dir.path(),
+                &browser_options(&[(
+                    "crypto",
+                    BuiltinStrategy::Polyfill("crypto-browserify".to_string()),
+                )]),
+            )
+            .unwrap_err();
+        assert_eq!(err.code, "BUNDLE_POLYFILL_NOT_FOUND");
+    }
+
+    #[test]
+    fn test_url_imports_left_external_by_default() {
+        let dir = tempfile::tempdir().unwrap();
+        write(
+            dir.path(),
+            "index.js",
+            "import confetti from 'https://esm.sh/canvas-confetti@1';\n\
+             import x from 'ftp://example.com/x.js';\n\
+             const lazy = () => import('https://esm.sh/lodash-es@4');\n\
+             console.log(confetti, x, lazy);\n",
+        );
+
+        let result = Bundler::new()
+            .bundle(Path::new("index.js"), dir.path(), &BundleOptions::default())
+            .unwrap();
+        assert_eq!(result.modules.len(), 1);
+        assert!(result
+            .code
+            .contains("require('https://esm.sh/canvas-confetti@1')"));
+        assert!(result.code.contains("import('https://esm.sh/lodash-es@4')"));
+        assert_eq!(result.warnings.len(), 1);
+        assert!(result.warnings[0].starts_with("UNSUPPORTED_SCHEME"));
+    }
+
+    #[test]
+    fn test_vendor_urls_bundles_data_and_http_modules() {
+        use std::io::{Read, Write};
+
+        // Serve /lib/main.js, which imports ./util.js relative to its URL
+        let listener = std::net::TcpListener::bind("127.0.0.1:0").unwrap();
+        let port = listener.local_addr().unwrap().port();
+        std::thread::spawn(move || {
+            for stream in listener.incoming().take(2) {
+                let mut stream = stream.unwrap();
+                let mut request = [0u8; 1024];
+                let n = stream.read(&mut request).unwrap();
+                let request = String::from_utf8_lossy(&request[..n]);
+                let body = if request.starts_with("GET /lib/main.js") {
+                    "import { util } from './util.js';\nexport const main = util;\n"
+                } else {
+                    "export const util = 'from-util';\n"
+                };
+                let response = format!(
+                    "HTTP/1.1 200 OK\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{}",
+                    body.len(),
+                    body
+                );
+                stream.write_all(response.as_bytes()).unwrap();
+            }
+        });
+
+        let dir = tempfile::tempdir().unwrap();
+        write(
+            dir.path(),
+            "index.js",
+            &format!(
+                "import {{ main }} from 'http://127.0.0.1:{port}/lib/main.js';\n\
+                 import {{ x }} from 'data:text/javascript,export const x = 1;';\n\
+                 const lazy = () => import('http://127.0.0.1:{port}/lazy.js');\n\
+                 console.log(main, x, lazy);\n"
+            ),
+        );
+
+        let options = BundleOptions {
+            vendor_urls: true,
+            ..Default::default()
+        };
+        let result = Bundler::new()
+            .bundle(Path::new("index.js"), dir.path(), &options)
+            .unwrap();
+
+        assert!(result
+            .modules
+            .contains(&format!("http://127.0.0.1:{port}/lib/util.js")));
+        assert!(result
+            .modules
+            .contains(&"data:text/javascript,export const x = 1;".to_string()));
+        assert!(result.code.contains("from-util"));
+        assert!(!result.code.contains("require('http://"));
+        assert!(!result.code.contains("require('data:"));
+        // Dynamic imports of URLs are preserved, not downloaded
+        assert!(result
+            .code
+            .contains(&format!("import('http://127.0.0.1:{port}/lazy.js')")));
+        assert_eq!(result.modules.len(), 4);
+    }
+
+    #[test]
+    fn test_bundle_entries_shares_common_modules() {
+        let dir = tempfile::tempdir().unwrap();
+        write(
+            dir.path(),
+            "src/shared.js",
+            "export const shared = 'shared';\n",
+        );
+        write(
+            dir.path(),
+            "src/a.js",
+            "import { shared } from './shared.js';\nconsole.log('a', shared);\n",
+        );
+        write(
+            dir.path(),
+            "src/b.js",
+            "import { shared } from './shared.js';\nconsole.log('b', shared);\n",
+        );
+        write(dir.path(), "other/a.js", "console.log('other');\n");
+
+        let entries = [
+            Path::new("src/a.js"),
+            Path::new("src/b.js"),
+            Path::new("other/a.js"),
+        ];
+        let result = Bundler::new()
+            .bundle_entries(&entries, dir.path(), &BundleOptions::default())
+            .unwrap();
+
+        let names: Vec<&str> = result.chunks.iter().map(|c| c.name.as_str()).collect();
+        assert_eq!(names, vec!["runtime", "a", "b", "a-2", "a~b"]);
+        assert!(result.chunks[0].code.contains(
+            "export { __modules, __require, __markAsModule, __toESM, __export, __reExport }"
+        ));
+
+        let chunk = |name: &str| &result.chunks.iter().find(|c| c.name == name).unwrap().code;
+        assert!(chunk("a").contains("import \"./a~b.js\";"));
+        assert!(chunk("a").contains("from \"./runtime.js\""));
+        assert!(!chunk("a-2").contains("a~b.js"));
+        assert!(chunk("a~b").contains("'shared'"));
+        assert!(!chunk("a").contains("'shared'"));
+        assert!(!chunk("a~b").contains("// Entry point"));
+
+        let manifest = result.manifest.unwrap();
+        assert_eq!(manifest.runtime.as_deref(), Some("runtime.js"));
+        assert_eq!(manifest.chunks.len(), 4);
+        assert_eq!(result.modules.len(), 4);
+
+        let err = Bundler::new()
+            .bundle_entries(
+                &entries,
+                dir.path(),
+                &BundleOptions {
+                    format: BundleFormat::Iife,
+                    ..Default::default()
+                },
+            )
+            .unwrap_err();
+        assert_eq!(err.code, "BUNDLE_MULTI_ENTRY_FORMAT");
+    }
+
+    #[test]
+    fn test_packages_external_keeps_imports() {
+        let dir = tempfile::tempdir().unwrap();
+        write(
+            dir.path(),
+            "src/index.js",
+            "import React from 'react';\nimport { get } from 'lodash/get';\n\
+             import { helper } from './helper.js';\nconsole.log(React, get, helper);\n\
+             export const version = 1;\n",
+        );
+        write(dir.path(), "src/helper.js", "export const helper = 1;\n");
+        write(
+            dir.path(),
+            "node_modules/react/package.json",
+            r#"{"name":"react","main":"index.js"}"#,
+        );
+        write(
+            dir.path(),
+            "node_modules/react/index.js",
+            "module.exports = {};\n",
+        );
+
+        let options = BundleOptions {
+            packages: PackagesMode::External,
+            ..Default::default()
+        };
+        assert!(options.is_external("react"));
+        assert!(options.is_external("@scope/pkg/sub"));
+        assert!(!options.is_external("./helper.js"));
+        assert!(!options.is_external("fs"));
+        assert!(!options.is_external("https://esm.sh/react"));
+
+        let result = Bundler::new()
+            .bundle(Path::new("src/index.js"), dir.path(), &options)
+            .unwrap();
+        assert_eq!(result.modules.len(), 2);
+        assert!(result
+            .code
+            .contains("import * as __ext_lodash_2fget_ns from \"lodash/get\";"));
+        assert!(result
+            .code
+            .contains("import * as __ext_react_ns from \"react\";"));
+        assert!(!result.code.contains("require('react')"));
+        assert!(result.code.contains("export const { version } = __entry;"));
+
+        // CJS output requires packages at the top level
+        let options = BundleOptions {
+            format: BundleFormat::Cjs,
+            ..options
+        };
+        let result = Bundler::new()
+            .bundle(Path::new("src/index.js"), dir.path(), &options)
+            .unwrap();
+        assert!(result
+            .code
+            .contains("const __ext_react = require(\"react\");"));
+        assert!(!result.code.contains("import * as"));
+        assert!(!result.code.contains("export const"));
+    }
+
+    #[test]
+    fn test_cjs_requires_are_bundled() {
+        let dir = tempfile::tempdir().unwrap();
+        write(
+            dir.path(),
+            "index.js",
+            "const lib = require('lib');\nconst locale = require('./locale/' + lang);\n\
+             console.log(lib.version, locale);\n",
+        );
+        write(
+            dir.path(),
+            "node_modules/lib/package.json",
+            r#"{"name":"lib","main":"index.js"}"#,
+        );
+        write(
+            dir.path(),
+            "node_modules/lib/index.js",
+            "module.exports = require(\"./impl.js\");\n",
+        );
+        write(
+            dir.path(),
+            "node_modules/lib/impl.js",
+            "exports.version = '1.0.0';\n",
+        );
+
+        let result = Bundler::new()
+            .bundle(Path::new("index.js"), dir.path(), &BundleOptions::default())
+            .unwrap();
+        assert_eq!(result.modules.len(), 3);
+        assert!(!result.code.contains("require('lib')"));
+        assert!(!result.code.contains("require(\"./impl.js\")"));
+        assert!(result.code.contains("exports.version = '1.0.0'"));
+        // The computed require is left to the host `require`
+        assert!(result.code.contains("require('./locale/' + lang)"));
+        assert_eq!(result.warnings.len(), 1);
+        assert!(result.warnings[0].starts_with(DYNAMIC_REQUIRE));
+    }
+
+    /// Bundle `files` as CommonJS, the first being the entry, and run the
+    /// bundle with node. Returns the code, and what it logged if node is
+    /// installed.
+    fn bundle_and_run(files: &[(&str, &str)], scope_hoist: bool) -> (String, Option<String>) {
+        let dir = tempfile::tempdir().unwrap();
+        for (path, content) in files {
+            write(dir.path(), path, content);
+        }
+        let options = BundleOptions {
+            format: BundleFormat::Cjs,
+            scope_hoist,
+            ..BundleOptions::default()
+        };
+        let result = Bundler::new()
+            .bundle(Path::new(files[0].0), dir.path(), &options)
+            .unwrap();
+        let out = dir.path().join("out.cjs");
+        fs::write(&out, &result.code).unwrap();
+        let output = std::process::Command::new("node")
+            .arg(&out)
+            .output()
+            .ok()
+            .map(|output| {
+                assert!(
+                    output.status.success(),
+                    "{}\n{}",
+                    String::from_utf8_lossy(&output.stderr),
+                    result.code
+                );
+                String::from_utf8(output.stdout).unwrap()
+            });
+        (result.code, output)
+    }
+
+    const COUNTER: (&str, &str) = (
+        "counter.js",
+        "export let count = 0;\nexport function inc() { count++; }\n",
+    );
+
+    #[test]
+    fn test_live_binding_export_let() {
+        let (code, output) = bundle_and_run(
+            &[
+                (
+                    "index.js",
+                    "import { count, inc } from './counter.js';\ninc();\ninc();\nconst read = () => { const count = 'shadow'; return count; };\nconsole.log(count, read(), JSON.stringify({ count }));\n",
+                ),
+                COUNTER,
+            ],
+            false,
+        );
+        assert!(code.contains("__export(exports, { count: () => count, inc: () => inc });"));
+        assert!(code.contains("const __import_1 = __toESM(require(1));"));
+        if let Some(output) = output {
+            assert_eq!(output, "2 shadow {\"count\":2}\n");
+        }
+    }
+
+    #[test]
+    fn test_live_binding_renamed_export() {
+        let (_, output) = bundle_and_run(
+            &[
+                (
+                    "index.js",
+                    "import { current, set } from './state.js';\nimport { fixed } from './state.js';\nset('b');\nconsole.log(current, fixed);\n",
+                ),
+                (
+                    "state.js",
+                    "var state = 'a';\nlet fixed = 1;\nexport { state as current, fixed };\nexport function set(s) { state = s; }\n",
+                ),
+            ],
+            false,
+        );
+        if let Some(output) = output {
+            assert_eq!(output, "b 1\n");
+        }
+    }
+
+    #[test]
+    fn test_live_binding_through_re_exports() {
+        let (_, output) = bundle_and_run(
+            &[
+                (
+                    "index.js",
+                    "import { count, inc, total, again } from './all.js';\ninc();\nconsole.log(count, total, again);\n",
+                ),
+                (
+                    "all.js",
+                    "import { count } from './counter.js';\nexport * from './counter.js';\nexport { count as total } from './counter.js';\nexport { count as again };\n",
+                ),
+                COUNTER,
+            ],
+            false,
+        );
+        if let Some(output) = output {
+            assert_eq!(output, "1 1 1\n");
+        }
+    }
+
+    #[test]
+    fn test_constant_imports_are_copied() {
+        let (code, _) = bundle_and_run(
+            &[
+                ("index.js", "import { x } from './x.js';\nconsole.log(x);\n"),
+                ("x.js", "export const x = 1;\nexport let y = 2;\n"),
+            ],
+            false,
+        );
+        assert!(code.contains("const { x } = require(1);"));
+        assert!(!code.contains("__import_"));
+    }
+
+    #[test]
+    fn test_cycle_function_hoisting() {
+        let (code, output) = bundle_and_run(
+            &[
+                ("index.js", "import { a } from './a.js';\nconsole.log(a());\n"),
+                (
+                    "a.js",
+                    "import { b } from './b.js';\nexport function a() { return 'a'; }\nexport const fromB = b();\n",
+                ),
+                (
+                    "b.js",
+                    "import { a } from './a.js';\nimport def from './a.js';\nexport function b() { return 'b' + a(); }\n",
+                ),
+            ],
+            false,
+        );
+        assert!(code.contains("function __import_1() {"));
+        assert!(code.contains("return \"b\" + __import_1().a();"));
+        if let Some(output) = output {
+            assert_eq!(output, "a\n");
+        }
+    }
+
+    #[test]
+    fn test_cycle_default_function_and_tdz() {
+        let (_, output) = bundle_and_run(
+            &[
+                (
+                    "a.js",
+                    "import { early, late } from './b.js';\nexport default function greet() { return 'hi'; }\nexport let value = 'ready';\nconsole.log(early, late());\n",
+                ),
+                (
+                    "b.js",
+                    "import greet, { value } from './a.js';\nexport const early = greet();\nlet seen;\ntry { value; seen = 'no'; } catch (e) { seen = e instanceof ReferenceError ? 'tdz' : 'other'; }\nexport function late() { return seen + ' ' + value; }\n",
+                ),
+            ],
+            false,
+        );
+        if let Some(output) = output {
+            assert_eq!(output, "hi tdz ready\n");
+        }
+    }
+
+    #[test]
+    fn test_live_binding_scope_hoisted() {
+        let (_, output) = bundle_and_run(
+            &[
+                (
+                    "index.js",
+                    "import { count, inc } from './counter.js';\ninc();\nconsole.log(count);\n",
+                ),
+                COUNTER,
+            ],
+            true,
+        );
+        if let Some(output) = output {
+            assert_eq!(output, "1\n");
+        }
+    }
+
+    #[test]
+    fn test_sourcemap_chains_plugin_and_transpile_maps() {
+        let dir = tempfile::tempdir().unwrap();
+        write(
+            dir.path(),
+            "index.ts",
+            "// header\ninterface Point { x: number }\n\nconst marker: number = __VALUE__;\nif (1 > 2) {\n  console.error(marker);\n}\nconsole.log(marker);\n",
+        );
+
+        for (format, scope_hoist, minify) in [
+            (BundleFormat::Cjs, false, false),
+            (BundleFormat::Cjs, false, true),
+            (BundleFormat::Iife, false, false),
+            (BundleFormat::Iife, false, true),
+            (BundleFormat::Cjs, true, false),
+            (BundleFormat::Cjs, true, true),
+        ] {
+            let options = BundleOptions {
+                format,
+                scope_hoist,
+                minify,
+                production: true,
+                sourcemap: true,
+                ..BundleOptions::default()
+            };
+            let result = Bundler::new()
+                .plugin(Box::new(ReplacePlugin::new().replace("__VALUE__", "42")))
+                .bundle(Path::new("index.ts"), dir.path(), &options)
+                .unwrap();
+            let map = SourceMap::parse(&result.map.unwrap()).unwrap();
+            // The dead branch is folded away, moving the lines after it
+            assert!(!result.code.contains("console.error"));
+
+            let (line, text) = result
+                .code
+                .lines()
+                .enumerate()
+                .find(|(_, line)| line.contains("console.log("))
+                .unwrap();
+            let column = text.find("console.log(").unwrap() as u32;
+            let original = map.lookup(line as u32, column).unwrap();
+            assert!(map.sources[original.source as usize].ends_with("index.ts"));
+            assert_eq!(
+                original.line, 7,
+                "{format:?} scope_hoist={scope_hoist} minify={minify}"
+            );
+            let content = map.sources_content[original.source as usize].as_deref();
+            assert!(content.unwrap().contains("__VALUE__"));
+        }
+    }
+
+    #[test]
+    fn test_entry_shebang_is_kept() {
+        let dir = tempfile::tempdir().unwrap();
+        write(
+            dir.path(),
+            "cli.js",
+            "#!/usr/bin/env node\nimport { greet } from './greet.js';\nconsole.log(greet());\n",
+        );
+        write(
+            dir.path(),
+            "greet.js",
+            "#!/usr/bin/env node\nexport const greet = () => 'hi';\n",
+        );
+
+        for scope_hoist in [false, true] {
+            let options = BundleOptions {
+                scope_hoist,
+                sourcemap: true,
+                ..BundleOptions::default()
+            };
+            let result = Bundler::new()
+                .bundle(Path::new("cli.js"), dir.path(), &options)
+                .unwrap();
+
+            assert!(result.code.starts_with("#!/usr/bin/env node\n"));
+            assert_eq!(result.code.matches("#!").count(), 1, "{}", result.code);
+
+            let map = SourceMap::parse(&result.map.unwrap()).unwrap();
+            let (line, text) = result
+                .code
+                .lines()
+                .enumerate()
+                .find(|(_, line)| line.contains("console.log("))
+                .unwrap();
+            let column = text.find("console.log(").unwrap() as u32;
+            let original = map.lookup(line as u32, column).unwrap();
+            assert_eq!(original.line, 2, "scope_hoist={scope_hoist}");
+        }
+    }
+
+    #[test]
+    fn test_deterministic_output_is_location_independent() {
+        let build = |deterministic: bool| {
+            let dir = tempfile::tempdir().unwrap();
+            write(
+                dir.path(),
+                "src/index.ts",
+                "import { a } from './a';\nconst lazy = import('./lazy');\nconsole.log(a, lazy);\n",
+            );
+            write(dir.path(), "src/a.ts", "export const a: number = 1;\n");
+            write(dir.path(), "src/lazy.ts", "export default 'lazy';\n");
+            let options = BundleOptions {
+                format: BundleFormat::Esm,
+                sourcemap: true,
+                splitting: true,
+                deterministic,
+                ..BundleOptions::default()
+            };
+            let result = Bundler::new()
+                .bundle(Path::new("src/index.ts"), dir.path(), &options)
+                .unwrap();
+            let root = dunce::canonicalize(dir.path()).unwrap();
+            (result, root.to_string_lossy().into_owned())
+        };
+
+        let (first, first_root) = build(true);
+        let (second, _) = build(true);
+        assert_eq!(first.code, second.code);
+        assert_eq!(first.chunks.len(), second.chunks.len());
+        for (a, b) in first.chunks.iter().zip(&second.chunks) {
+            assert_eq!(a.name, b.name);
+            assert_eq!(a.code, b.code);
+            assert_eq!(a.map, b.map);
+        }
+        assert!(!first.code.contains(&first_root));
+        // Split builds only map their async chunks
+        let map = SourceMap::parse(first.chunks[0].map.as_deref().unwrap()).unwrap();
+        assert!(
+            map.sources.iter().any(|s| s == "src/lazy.ts"),
+            "{:?}",
+            map.sources
+        );
+
+        // Without the flag, paths are absolute
+        let (plain, plain_root) = build(false);
+        assert!(plain.code.contains(&plain_root));
+    }
+
+    #[test]
+    fn test_deterministic_keeps_path_comments_in_user_code() {
+        let dir = tempfile::tempdir().unwrap();
+        let root = dunce::canonicalize(dir.path()).unwrap();
+        let index = root.join("src/index.js");
+        let comment = format!("// {}", index.display());
+        write(
+            &root,
+            "src/index.js",
+            &format!("{comment}\nconst answer = 42;\nconsole.log(answer);\n"),
+        );
+
+        for scope_hoist in [false, true] {
+            let options = BundleOptions {
+                scope_hoist,
+                deterministic: true,
+                ..BundleOptions::default()
+            };
+            let result = Bundler::new()
+                .bundle(Path::new("src/index.js"), &root, &options)
+                .unwrap();
+            let headers = result
+                .code
+                .lines()
+                .filter(|line| *line == "// src/index.js" || line.ends_with(": src/index.js"))
+                .count();
+            assert_eq!(headers, 1, "scope_hoist={scope_hoist}\n{}", result.code);
+            if !scope_hoist {
+                // Hoisting drops comments; the wrapped module keeps them as written
+                assert!(
+                    result.code.contains(&comment),
+                    "scope_hoist={scope_hoist}\n{}",
+                    result.code
+                );
+            }
+        }
+    }
+}
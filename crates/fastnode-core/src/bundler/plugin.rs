@@ -28,9 +28,10 @@
 #![allow(clippy::needless_lifetimes)]
 #![allow(clippy::unnecessary_literal_bound)]
 
+use crate::sourcemap::{compose, SourceMap};
 use rustc_hash::FxHashMap as HashMap;
 use std::path::PathBuf;
-use std::sync::Arc;
+use std::sync::{Arc, RwLock};
 
 /// Result type for plugin hooks.
 pub type HookResult<T> = Result<T, PluginError>;
@@ -142,6 +143,10 @@ pub struct TransformResult {
     pub code: String,
     /// Optional source map.
     pub map: Option<String>,
+    /// CSS extracted from the module (e.g. the `<style>` blocks of a
+    /// single-file component), served as the virtual module
+    /// [`virtual_css_id`]`(id)`.
+    pub css: Option<String>,
 }
 
 impl TransformResult {
@@ -150,8 +155,29 @@ impl TransformResult {
         Self {
             code: code.into(),
             map: None,
+            css: None,
         }
     }
+
+    /// Attach a source map (JSON) from the input code to `code`.
+    #[must_use]
+    pub fn with_map(mut self, map: impl Into<String>) -> Self {
+        self.map = Some(map.into());
+        self
+    }
+
+    /// Attach extracted CSS.
+    #[must_use]
+    pub fn with_css(mut self, css: impl Into<String>) -> Self {
+        self.css = Some(css.into());
+        self
+    }
+}
+
+/// Id of the virtual stylesheet holding the CSS a transform extracted from
+/// module `id`.
+pub fn virtual_css_id(id: &str) -> String {
+    format!("{id}.css")
 }
 
 /// Plugin enforcement ordering.
@@ -294,6 +320,17 @@ pub trait Plugin: Send + Sync {
         PluginEnforce::Normal
     }
 
+    /// File extensions (without the dot) this plugin compiles to JavaScript,
+    /// e.g. `["vue"]` or `["svelte"]`.
+    ///
+    /// The dev server and bundler treat such files as modules: they are
+    /// loaded, run through `transform` (which must return JavaScript), and
+    /// their imports are followed. CSS returned in [`TransformResult::css`]
+    /// becomes a virtual stylesheet for the module.
+    fn extensions(&self) -> Vec<String> {
+        Vec::new()
+    }
+
     /// Called at the start of the build.
     fn build_start(&self, _ctx: &PluginContext) -> HookResult<()> {
         Ok(())
@@ -407,6 +444,10 @@ pub struct PluginContainer {
     ctx: PluginContext,
     /// Whether plugins need re-sorting after insertion.
     needs_sort: bool,
+    /// Module extensions registered by plugins.
+    extensions: Vec<String>,
+    /// CSS extracted by the latest transform of each module.
+    emitted_css: RwLock<HashMap<String, String>>,
 }
 
 impl PluginContainer {
@@ -416,6 +457,8 @@ impl PluginContainer {
             plugins: Vec::new(),
             ctx: PluginContext::new(cwd),
             needs_sort: false,
+            extensions: Vec::new(),
+            emitted_css: RwLock::new(HashMap::default()),
         }
     }
 
@@ -425,9 +468,30 @@ impl PluginContainer {
         if enforce != PluginEnforce::Normal {
             self.needs_sort = true;
         }
+        for ext in plugin.extensions() {
+            let ext = ext.trim_start_matches('.').to_string();
+            if !self.extensions.contains(&ext) {
+                self.extensions.push(ext);
+            }
+        }
         self.plugins.push(plugin);
     }
 
+    /// Module extensions registered by plugins (without the dot).
+    pub fn extensions(&self) -> &[String] {
+        &self.extensions
+    }
+
+    /// Whether a plugin compiles files with this extension to JavaScript.
+    pub fn handles_extension(&self, ext: &str) -> bool {
+        self.extensions.iter().any(|e| e == ext)
+    }
+
+    /// CSS extracted from module `id` by its latest transform.
+    pub fn emitted_css(&self, id: &str) -> Option<String> {
+        self.emitted_css.read().ok()?.get(id).cloned()
+    }
+
     /// Sort plugins by enforce order (Pre → Normal → Post).
     /// Uses a stable sort to preserve insertion order within each level.
     fn ensure_sorted(&mut self) {
@@ -477,7 +541,13 @@ impl PluginContainer {
 
     /// Try to load a module through plugins.
     /// Returns None if no plugin handled the load.
+    ///
+    /// Virtual stylesheets ([`virtual_css_id`]) load the CSS extracted by the
+    /// latest transform of their module.
     pub fn load(&self, id: &str) -> HookResult<Option<LoadResult>> {
+        if let Some(css) = id.strip_suffix(".css").and_then(|m| self.emitted_css(m)) {
+            return Ok(Some(LoadResult::code(css)));
+        }
         for plugin in &self.plugins {
             if let Some(result) = plugin.load(id, &self.ctx)? {
                 return Ok(Some(result));
@@ -488,14 +558,60 @@ impl PluginContainer {
 
     /// Transform code through all plugins.
     /// Each plugin's output is passed to the next plugin.
+    ///
+    /// CSS returned alongside the code is recorded for [`Self::emitted_css`].
     pub fn transform(&self, code: &str, id: &str) -> HookResult<String> {
+        self.run_transforms(code, id, false).map(|(code, _)| code)
+    }
+
+    /// Like [`Self::transform`], also composing the source maps the plugins
+    /// return into one from `code` to the result.
+    ///
+    /// The map is `None` when no plugin changed the code, or when one
+    /// changed it without a (valid) map, so positions can't be traced.
+    pub fn transform_with_map(
+        &self,
+        code: &str,
+        id: &str,
+    ) -> HookResult<(String, Option<SourceMap>)> {
+        self.run_transforms(code, id, true)
+    }
+
+    fn run_transforms(
+        &self,
+        code: &str,
+        id: &str,
+        with_maps: bool,
+    ) -> HookResult<(String, Option<SourceMap>)> {
         let mut current = code.to_string();
+        let mut css = String::new();
+        let mut maps = Vec::new();
+        let mut traceable = true;
         for plugin in &self.plugins {
             if let Some(result) = plugin.transform(&current, id, &self.ctx)? {
+                if with_maps && traceable && result.code != current {
+                    match result.map.as_deref().map(SourceMap::parse) {
+                        Some(Ok(map)) => maps.push(map),
+                        _ => traceable = false,
+                    }
+                }
                 current = result.code;
+                if let Some(extracted) = result.css {
+                    css.push_str(&extracted);
+                }
             }
         }
-        Ok(current)
+
+        if let Ok(mut emitted) = self.emitted_css.write() {
+            if css.is_empty() {
+                emitted.remove(id);
+            } else {
+                emitted.insert(id.to_string(), css);
+            }
+        }
+        let maps: Vec<&SourceMap> = maps.iter().collect();
+        let map = if traceable { compose(&maps) } else { None };
+        Ok((current, map))
     }
 
     /// Transform a chunk through all plugins.
@@ -618,6 +734,32 @@ impl ReplacePlugin {
     }
 }
 
+/// Replace occurrences of `from` that aren't part of a longer identifier or
+/// member expression (`VITE_A` doesn't match in `VITE_AB` or `x.VITE_A`).
+fn replace_identifier(code: &str, from: &str, to: &str) -> Option<String> {
+    let is_ident = |c: char| c.is_alphanumeric() || c == '_' || c == '$';
+    let mut result = String::with_capacity(code.len());
+    let mut last = 0;
+
+    for (pos, _) in code.match_indices(from) {
+        let end = pos + from.len();
+        let before = code[..pos].chars().next_back();
+        let after = code[end..].chars().next();
+        if before.is_some_and(|c| is_ident(c) || c == '.') || after.is_some_and(is_ident) {
+            continue;
+        }
+        result.push_str(&code[last..pos]);
+        result.push_str(to);
+        last = end;
+    }
+
+    if last == 0 {
+        return None;
+    }
+    result.push_str(&code[last..]);
+    Some(result)
+}
+
 impl Default for ReplacePlugin {
     fn default() -> Self {
         Self::new()
@@ -632,28 +774,39 @@ impl Plugin for ReplacePlugin {
     fn transform(
         &self,
         code: &str,
-        _id: &str,
+        id: &str,
         _ctx: &PluginContext,
     ) -> HookResult<Option<TransformResult>> {
         if self.replacements.is_empty() {
             return Ok(None);
         }
 
+        // Longest keys first, so `import.meta.env.MODE` wins over `import.meta.env`
+        let mut keys: Vec<&String> = self.replacements.keys().collect();
+        keys.sort_by(|a, b| b.len().cmp(&a.len()).then_with(|| a.cmp(b)));
+
         let mut result = code.to_string();
         let mut changed = false;
 
-        for (from, to) in &self.replacements {
-            if result.contains(from) {
-                result = result.replace(from, to);
+        for from in keys {
+            if let Some(replaced) = replace_identifier(&result, from, &self.replacements[from]) {
+                result = replaced;
                 changed = true;
             }
         }
 
-        if changed {
-            Ok(Some(TransformResult::code(result)))
-        } else {
-            Ok(None)
+        if !changed {
+            return Ok(None);
         }
+        // Replacements stay on their line, so unless one spans lines the
+        // code keeps its line structure and a line-level map traces it.
+        let map = (result.lines().count() == code.lines().count())
+            .then(|| SourceMap::identity_lines(id, code).to_json());
+        let result = TransformResult::code(result);
+        Ok(Some(match map {
+            Some(map) => result.with_map(map),
+            None => result,
+        }))
     }
 }
 
@@ -892,6 +1045,26 @@ mod tests {
         assert!(transformed.contains(r#"const env = "production""#));
     }
 
+    #[test]
+    fn test_replace_plugin_identifier_boundaries() {
+        let plugin = ReplacePlugin::new()
+            .replace("import.meta.env", r#"{"MODE":"production"}"#)
+            .replace("import.meta.env.MODE", r#""production""#)
+            .replace("import.meta.env.VITE_A", r#""a""#);
+
+        let code = "f(import.meta.env.VITE_A, import.meta.env.VITE_AB, import.meta.env.MODE, import.meta.env, x.import.meta.env.MODE);";
+        let transformed = plugin
+            .transform(code, "test.js", &PluginContext::default())
+            .unwrap()
+            .unwrap()
+            .code;
+
+        assert_eq!(
+            transformed,
+            r#"f("a", {"MODE":"production"}.VITE_AB, "production", {"MODE":"production"}, x.import.meta.env.MODE);"#
+        );
+    }
+
     #[test]
     fn test_virtual_plugin() {
         let plugin = VirtualPlugin::new().module("my-module", "export const x = 1;");
@@ -978,4 +1151,81 @@ mod tests {
         let result = container.transform("const x = FOO;", "test.js").unwrap();
         assert_eq!(result, "const x = BAZ;");
     }
+
+    #[test]
+    fn test_plugin_container_transform_with_map() {
+        let mut container = PluginContainer::default();
+        container.add(Box::new(ReplacePlugin::new().replace("FOO", "BAR")));
+        container.add(Box::new(ReplacePlugin::new().replace("BAR", "BAZ")));
+
+        let (code, map) = container
+            .transform_with_map("let a;\nconst x = FOO;", "/src/a.js")
+            .unwrap();
+        assert_eq!(code, "let a;\nconst x = BAZ;");
+        let map = map.unwrap();
+        assert_eq!(map.sources, vec!["/src/a.js"]);
+        assert_eq!(map.lookup(1, 10).unwrap().line, 1);
+
+        // Unchanged code has no map
+        let (_, map) = container.transform_with_map("let a;", "/src/a.js").unwrap();
+        assert!(map.is_none());
+
+        // A plugin changing the code without a map makes it untraceable
+        container.add(Box::new(StylePlugin));
+        let (_, map) = container
+            .transform_with_map("const x = FOO;", "/src/a.js")
+            .unwrap();
+        assert!(map.is_none());
+    }
+
+    struct StylePlugin;
+
+    impl Plugin for StylePlugin {
+        fn name(&self) -> &str {
+            "style"
+        }
+
+        fn extensions(&self) -> Vec<String> {
+            vec![".styled".to_string()]
+        }
+
+        fn transform(
+            &self,
+            code: &str,
+            _id: &str,
+            _ctx: &PluginContext,
+        ) -> HookResult<Option<TransformResult>> {
+            let result = TransformResult::code("export default 1;");
+            Ok(Some(match code.strip_prefix("css:") {
+                Some(css) => result.with_css(css),
+                None => result,
+            }))
+        }
+    }
+
+    #[test]
+    fn test_plugin_container_extensions_and_emitted_css() {
+        let mut container = PluginContainer::default();
+        container.add(Box::new(StylePlugin));
+
+        assert_eq!(container.extensions(), ["styled"]);
+        assert!(container.handles_extension("styled"));
+        assert!(!container.handles_extension("vue"));
+
+        let code = container.transform("css:.a{}", "/src/A.styled").unwrap();
+        assert_eq!(code, "export default 1;");
+        assert_eq!(
+            container.emitted_css("/src/A.styled").as_deref(),
+            Some(".a{}")
+        );
+
+        // The virtual stylesheet loads the extracted CSS
+        let loaded = container.load(&virtual_css_id("/src/A.styled")).unwrap();
+        assert_eq!(loaded.unwrap().code, ".a{}");
+
+        // Re-transforming without styles drops the stylesheet
+        container.transform("", "/src/A.styled").unwrap();
+        assert!(container.emitted_css("/src/A.styled").is_none());
+        assert!(container.load("/src/A.styled.css").unwrap().is_none());
+    }
 }
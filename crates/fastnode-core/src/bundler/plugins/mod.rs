@@ -3,5 +3,7 @@
 //! These plugins are compiled into the binary and provide common functionality.
 
 pub mod react_refresh;
+pub mod sfc;
 
 pub use react_refresh::ReactRefreshPlugin;
+pub use sfc::SfcPlugin;
@@ -7,9 +7,14 @@
 //! ## How It Works
 //!
 //! 1. Serves `/@react-refresh` virtual module with the refresh runtime
-//! 2. For each `.tsx`/`.jsx` file, injects a preamble that registers
-//!    components with the refresh runtime
-//! 3. Appends a footer that performs the actual refresh call
+//! 2. The compiler appends `$RefreshReg$` calls for each component (see
+//!    [`crate::compiler::refresh`]); for each `.tsx`/`.jsx` file that declares
+//!    components, a preamble points `$RefreshReg$` at the runtime with the
+//!    module id as prefix
+//! 3. Modules that export only components (refresh boundaries) accept their
+//!    own updates and schedule a refresh, keeping component state. Other
+//!    modules don't accept, so the update propagates to their importers and
+//!    falls back to a full reload
 //!
 //! ## Usage
 //!
@@ -23,6 +28,8 @@
 use crate::bundler::{
     HookResult, LoadResult, Plugin, PluginContext, PluginEnforce, ResolveIdResult, TransformResult,
 };
+use crate::compiler::refresh;
+use howth_parser::{Ast, Parser, ParserOptions};
 
 /// React Fast Refresh plugin.
 ///
@@ -49,18 +56,15 @@ impl ReactRefreshPlugin {
         id.ends_with(".tsx") || id.ends_with(".jsx")
     }
 
-    /// Check if the transformed code likely contains React components.
-    ///
-    /// A function is considered a component if it starts with an uppercase letter
-    /// and returns JSX (which after transpilation becomes createElement/jsx calls).
-    fn has_react_components(code: &str) -> bool {
-        // Look for function components: function App() or const App = () =>
-        // After SWC transpilation, JSX becomes React.createElement or _jsx calls
-        code.contains("_jsx(")
-            || code.contains("_jsxs(")
-            || code.contains("jsx(")
-            || code.contains("jsxs(")
-            || code.contains("createElement(")
+    /// Parse transpiled module code, or `None` if it doesn't parse (the
+    /// module is then left alone and edits fall back to a full reload).
+    fn parse(code: &str) -> Option<Ast> {
+        let opts = ParserOptions {
+            module: true,
+            jsx: true,
+            typescript: true,
+        };
+        Parser::new(code, opts).parse().ok()
     }
 }
 
@@ -117,14 +121,16 @@ impl Plugin for ReactRefreshPlugin {
             return Ok(None);
         }
 
-        // Only transform files that contain React components
-        if !Self::has_react_components(code) {
+        // Only transform modules that declare React components
+        let Some(ast) = Self::parse(code) else {
+            return Ok(None);
+        };
+        if refresh::component_names(&ast).is_empty() {
             return Ok(None);
         }
 
-        // Inject refresh preamble and footer
-        let preamble = REACT_REFRESH_PREAMBLE;
-        let footer = generate_refresh_footer(id);
+        let preamble = generate_refresh_preamble(id);
+        let footer = generate_refresh_footer(refresh::is_refresh_boundary(&ast));
 
         let transformed = format!("{}\n{}\n{}", preamble, code, footer);
 
@@ -149,158 +155,225 @@ window.__vite_plugin_react_preamble_installed__ = true;
     }
 }
 
-/// Generate the refresh footer for a specific module.
+/// Generate the refresh preamble for a specific module.
 ///
-/// This footer registers all exported components with the refresh runtime
-/// and performs the actual hot update.
-fn generate_refresh_footer(module_id: &str) -> String {
-    let escaped_id = module_id.replace('\\', "\\\\").replace('"', "\\\"");
+/// Points `$RefreshReg$` at the runtime while the module body runs, so the
+/// registrations appended by the compiler are keyed by module id.
+fn generate_refresh_preamble(module_id: &str) -> String {
+    let module_id = serde_json::to_string(module_id).unwrap_or_default();
 
     format!(
-        r#"
+        r"import RefreshRuntime from '/@react-refresh';
+
+const __howthPrevRefreshReg = window.$RefreshReg$;
+const __howthPrevRefreshSig = window.$RefreshSig$;
+window.$RefreshReg$ = (type, id) => {{
+  RefreshRuntime.register(type, {module_id} + ' ' + id);
+}};
+window.$RefreshSig$ = RefreshRuntime.createSignatureFunctionForTransform;"
+    )
+}
+
+/// Generate the refresh footer.
+///
+/// Restores the previous registration functions. Refresh boundaries also
+/// accept their own updates and schedule a refresh of the re-registered
+/// components.
+fn generate_refresh_footer(is_boundary: bool) -> String {
+    let mut footer = String::from(
+        r"
 // React Refresh Footer
-if (import.meta.hot) {{
-  import.meta.hot.accept();
-  if (!window.__vite_plugin_react_preamble_installed__) {{
+window.$RefreshReg$ = __howthPrevRefreshReg;
+window.$RefreshSig$ = __howthPrevRefreshSig;
+",
+    );
+    if is_boundary {
+        footer.push_str(
+            r#"if (import.meta.hot) {
+  if (!window.__vite_plugin_react_preamble_installed__) {
     throw new Error(
       "React refresh preamble was not loaded. " +
       "Make sure the index.html includes the refresh runtime script."
     );
-  }}
-  RefreshRuntime.performReactRefresh();
-}}
-
-window.$RefreshReg$ && window.$RefreshReg$(function() {{}}, "{module_id}");
+  }
+  import.meta.hot.accept();
+  RefreshRuntime.enqueueUpdate();
+}
 "#,
-        module_id = escaped_id
-    )
+        );
+    }
+    footer
 }
 
-/// React Refresh preamble injected at the top of each component file.
-///
-/// Sets up the registration functions that React Refresh uses to track components.
-const REACT_REFRESH_PREAMBLE: &str = r"import RefreshRuntime from '/@react-refresh';
-
-const prevRefreshReg = window.$RefreshReg$;
-const prevRefreshSig = window.$RefreshSig$;
-
-window.$RefreshReg$ = (type, id) => {
-  RefreshRuntime.register(type, __MODULE_ID__ + ' ' + id);
-};
-window.$RefreshSig$ = RefreshRuntime.createSignatureFunctionForTransform;";
-
 /// The React Refresh runtime module served at `/@react-refresh`.
 ///
-/// This is a minimal implementation of the React Refresh runtime that
-/// provides the core APIs:
-/// - `register(type, id)` — Register a component with its unique ID
+/// A compact implementation of `react-refresh/runtime`:
+/// - `register(type, id)` — Record a component under its family id
 /// - `createSignatureFunctionForTransform()` — Track hook signatures
-/// - `performReactRefresh()` — Trigger refresh for registered components
-/// - `injectIntoGlobalHook(window)` — Set up global hooks
+/// - `performReactRefresh()` — Re-render updated families in mounted roots
+/// - `enqueueUpdate()` — Debounced `performReactRefresh()`
+/// - `injectIntoGlobalHook(window)` — Collect renderers and mounted roots
 const REACT_REFRESH_RUNTIME: &str = r"
-// React Refresh Runtime (minimal implementation for howth dev server)
+// React Refresh Runtime for the howth dev server.
 //
-// This provides the core APIs that the React Refresh Babel/SWC transform expects.
-// In production, this would use react-refresh/runtime, but for the dev server
-// we provide a lightweight shim that delegates to React's internal refresh mechanism.
-
-const registeredComponents = new Map();
-const pendingUpdates = new Set();
+// Components are grouped into families by registration id (module + name).
+// When a module re-runs, its components register again under the same ids;
+// performReactRefresh then points each family at the new implementation and
+// asks React to re-render the mounted roots. Families whose hook signature
+// changed are remounted (state reset), the rest keep their state.
+
+const REACT_FORWARD_REF = Symbol.for('react.forward_ref');
+const REACT_MEMO = Symbol.for('react.memo');
+
+const allFamiliesById = new Map();
+const familiesByType = new WeakMap();
+const signaturesByType = new WeakMap();
+const helpersByRendererId = new Map();
+const helpersByRoot = new Map();
+const mountedRoots = new Set();
+let pendingUpdates = [];
 let isPerformingRefresh = false;
 
-function debounce(fn, delay) {
-  let timer;
-  return (...args) => {
-    clearTimeout(timer);
-    timer = setTimeout(() => fn(...args), delay);
-  };
-}
+function register(type, id) {
+  if (type == null || (typeof type !== 'function' && typeof type !== 'object')) return;
+  if (familiesByType.has(type)) return;
 
-function register(type, fullID) {
-  if (type == null || typeof type !== 'function') return;
+  let family = allFamiliesById.get(id);
+  if (family === undefined) {
+    family = { current: type };
+    allFamiliesById.set(id, family);
+  } else {
+    pendingUpdates.push([family, type]);
+  }
+  familiesByType.set(type, family);
 
-  registeredComponents.set(fullID, type);
-  pendingUpdates.add(fullID);
+  // memo() and forwardRef() wrap the component that actually renders
+  if (typeof type === 'object') {
+    if (type.$$typeof === REACT_FORWARD_REF) register(type.render, id + '$render');
+    else if (type.$$typeof === REACT_MEMO) register(type.type, id + '$type');
+  }
 }
 
-function createSignatureFunctionForTransform() {
-  let savedType;
-  let hasCustomHooks = false;
-  let didCollectHooks = false;
+function setSignature(type, key, forceReset = false, getCustomHooks) {
+  if (!signaturesByType.has(type)) {
+    signaturesByType.set(type, { key, forceReset, getCustomHooks });
+  }
+}
 
+function createSignatureFunctionForTransform() {
   return function(type, key, forceReset, getCustomHooks) {
-    if (typeof key === 'string') {
-      if (!savedType) {
-        savedType = type;
-        hasCustomHooks = typeof getCustomHooks === 'function';
-      }
-
-      if (type != null && (typeof type === 'function' || typeof type === 'object')) {
-        // Store the signature for comparison on update
-        if (!type.__signature) {
-          type.__signature = { key, forceReset, getCustomHooks };
-        }
-      }
-    } else {
-      // No key means we're just wrapping
+    if (typeof key === 'string' && type != null) {
+      setSignature(type, key, forceReset, getCustomHooks);
     }
     return type;
   };
 }
 
-const scheduleRefresh = debounce(() => {
-  performReactRefresh();
-}, 30);
+function canPreserveState(prevType, nextType) {
+  const prev = signaturesByType.get(prevType);
+  const next = signaturesByType.get(nextType);
+  if (prev === undefined && next === undefined) return true;
+  if (prev === undefined || next === undefined) return false;
+  return prev.key === next.key && !next.forceReset;
+}
+
+function resolveFamily(type) {
+  return familiesByType.get(type);
+}
 
 function performReactRefresh() {
-  if (isPerformingRefresh) return;
+  if (pendingUpdates.length === 0 || isPerformingRefresh) return null;
   isPerformingRefresh = true;
 
   try {
-    // Attempt to use React's built-in refresh mechanism if available
-    if (window.__REACT_DEVTOOLS_GLOBAL_HOOK__?.renderers?.size > 0) {
-      for (const [, renderer] of window.__REACT_DEVTOOLS_GLOBAL_HOOK__.renderers) {
-        if (renderer.scheduleRefresh) {
-          renderer.scheduleRefresh(
-            new Set(Array.from(pendingUpdates).map(id => registeredComponents.get(id)).filter(Boolean)),
-            new Map()
-          );
-        }
+    const updatedFamilies = new Set();
+    const staleFamilies = new Set();
+    const updates = pendingUpdates;
+    pendingUpdates = [];
+    for (const [family, nextType] of updates) {
+      const prevType = family.current;
+      family.current = nextType;
+      if (canPreserveState(prevType, nextType)) {
+        updatedFamilies.add(family);
+      } else {
+        staleFamilies.add(family);
       }
     }
-    pendingUpdates.clear();
+
+    const update = { updatedFamilies, staleFamilies };
+    for (const helpers of helpersByRendererId.values()) {
+      helpers.setRefreshHandler(resolveFamily);
+    }
+    for (const root of mountedRoots) {
+      const helpers = helpersByRoot.get(root);
+      if (helpers) helpers.scheduleRefresh(root, update);
+    }
+    return update;
   } catch (e) {
     console.error('[react-refresh] Failed to perform refresh:', e);
-    // Fallback: full page reload
     window.location.reload();
+    return null;
   } finally {
     isPerformingRefresh = false;
   }
 }
 
+let refreshTimer;
+function enqueueUpdate() {
+  clearTimeout(refreshTimer);
+  refreshTimer = setTimeout(performReactRefresh, 30);
+}
+
 function injectIntoGlobalHook(globalObject) {
-  if (!globalObject.__REACT_DEVTOOLS_GLOBAL_HOOK__) {
-    // Create a minimal DevTools hook for refresh to work
-    let nextID = 0;
-    globalObject.__REACT_DEVTOOLS_GLOBAL_HOOK__ = {
+  let hook = globalObject.__REACT_DEVTOOLS_GLOBAL_HOOK__;
+  if (hook === undefined) {
+    let nextId = 0;
+    hook = globalObject.__REACT_DEVTOOLS_GLOBAL_HOOK__ = {
       renderers: new Map(),
       supportsFiber: true,
       inject(renderer) {
-        const id = nextID++;
-        this.renderers.set(id, renderer);
-        return id;
+        return nextId++;
       },
       onScheduleFiberRoot() {},
       onCommitFiberRoot() {},
       onCommitFiberUnmount() {},
     };
   }
+
+  // Remember renderers that support refresh
+  const oldInject = hook.inject;
+  hook.inject = function(injected) {
+    const id = oldInject.apply(this, arguments);
+    if (typeof injected.scheduleRefresh === 'function' &&
+        typeof injected.setRefreshHandler === 'function') {
+      helpersByRendererId.set(id, injected);
+    }
+    return id;
+  };
+
+  // Track mounted roots
+  const oldOnCommitFiberRoot = hook.onCommitFiberRoot;
+  hook.onCommitFiberRoot = function(id, root, ...rest) {
+    const helpers = helpersByRendererId.get(id);
+    if (helpers !== undefined) {
+      helpersByRoot.set(root, helpers);
+      const element = root.current && root.current.memoizedState && root.current.memoizedState.element;
+      if (element == null) {
+        mountedRoots.delete(root);
+        helpersByRoot.delete(root);
+      } else {
+        mountedRoots.add(root);
+      }
+    }
+    return oldOnCommitFiberRoot.call(this, id, root, ...rest);
+  };
 }
 
 export default {
   register,
   createSignatureFunctionForTransform,
   performReactRefresh,
+  enqueueUpdate,
   injectIntoGlobalHook,
 };
 
@@ -308,6 +381,7 @@ export {
   register,
   createSignatureFunctionForTransform,
   performReactRefresh,
+  enqueueUpdate,
   injectIntoGlobalHook,
 };
 ";
@@ -329,19 +403,15 @@ mod tests {
     }
 
     #[test]
-    fn test_has_react_components() {
-        assert!(ReactRefreshPlugin::has_react_components(
-            "return _jsx(\"div\", { children: \"Hello\" });"
-        ));
-        assert!(ReactRefreshPlugin::has_react_components(
-            "return _jsxs(\"div\", { children: [\"Hello\"] });"
-        ));
-        assert!(ReactRefreshPlugin::has_react_components(
-            "React.createElement('div', null, 'Hello')"
-        ));
-        assert!(!ReactRefreshPlugin::has_react_components(
-            "export const x = 42;"
-        ));
+    fn test_parse_detects_components() {
+        let ast = ReactRefreshPlugin::parse(
+            "function App() { return _jsx(\"div\", { children: \"Hello\" }); }",
+        )
+        .unwrap();
+        assert_eq!(refresh::component_names(&ast), ["App"]);
+
+        let ast = ReactRefreshPlugin::parse("export const x = 42;").unwrap();
+        assert_eq!(refresh::component_names(&ast), Vec::<String>::new());
     }
 
     #[test]
@@ -363,9 +433,42 @@ export default App;
         assert!(result.is_some());
 
         let transformed = result.unwrap().code;
-        assert!(transformed.contains("RefreshRuntime"));
-        assert!(transformed.contains("performReactRefresh"));
-        assert!(transformed.contains("import.meta.hot"));
+        assert!(transformed.contains("RefreshRuntime.register(type, \"App.tsx\" + ' ' + id)"));
+        assert!(transformed.contains("RefreshRuntime.enqueueUpdate()"));
+        assert!(transformed.contains("import.meta.hot.accept()"));
+    }
+
+    #[test]
+    fn test_transform_non_boundary_does_not_accept() {
+        let plugin = ReactRefreshPlugin::new().dev_only(false);
+        let ctx = PluginContext::default();
+
+        // Exports a non-component next to the component: importers may hold
+        // on to `theme`, so the update must propagate instead
+        let code = r#"
+export const theme = "dark";
+export function App() {
+  return _jsx("div", { children: theme });
+}
+"#;
+
+        let transformed = plugin
+            .transform(code, "App.tsx", &ctx)
+            .unwrap()
+            .unwrap()
+            .code;
+        assert!(transformed.contains("RefreshRuntime.register"));
+        assert!(!transformed.contains("import.meta.hot.accept"));
+        assert!(transformed.contains("window.$RefreshReg$ = __howthPrevRefreshReg;"));
+    }
+
+    #[test]
+    fn test_no_transform_without_components() {
+        let plugin = ReactRefreshPlugin::new().dev_only(false);
+        let ctx = PluginContext::default();
+
+        let code = "export function useCounter() { return 0; }";
+        assert!(plugin.transform(code, "hooks.tsx", &ctx).unwrap().is_none());
     }
 
     #[test]
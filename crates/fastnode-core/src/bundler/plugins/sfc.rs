@@ -0,0 +1,194 @@
+//! Example single-file component plugin.
+//!
+//! A minimal compiler for Vue-style single-file components, kept as the
+//! reference for community `.vue` / `.svelte` plugins. It uses only the
+//! public plugin extension points:
+//!
+//! - [`Plugin::extensions`] registers the file extension, so the dev server
+//!   and bundler treat the files as modules
+//! - `transform` compiles the component to JavaScript
+//! - [`TransformResult::css`] returns the `<style>` blocks, which are served
+//!   as the component's virtual stylesheet (`App.sfc.css`) in dev and bundled
+//!   into the CSS output in builds
+//!
+//! ```html
+//! <script>
+//! import { greet } from './greet';
+//! export default { name: 'Hello', data: () => ({ msg: greet() }) };
+//! </script>
+//! <template><p class="hello">{{ msg }}</p></template>
+//! <style>.hello { color: red; }</style>
+//! ```
+//!
+//! compiles to the script with the template attached:
+//!
+//! ```js
+//! import { greet } from './greet';
+//! const __sfc__ = { name: 'Hello', data: () => ({ msg: greet() }) };
+//! __sfc__.template = "<p class=\"hello\">{{ msg }}</p>";
+//! export default __sfc__;
+//! ```
+
+use crate::bundler::{HookResult, Plugin, PluginContext, TransformResult};
+
+/// Example single-file component plugin.
+pub struct SfcPlugin {
+    /// Component file extension (without the dot).
+    extension: String,
+}
+
+impl SfcPlugin {
+    /// Compile files with `extension` (e.g. `"sfc"`).
+    pub fn new(extension: impl Into<String>) -> Self {
+        Self {
+            extension: extension.into(),
+        }
+    }
+
+    fn is_component(&self, id: &str) -> bool {
+        id.rsplit_once('.')
+            .is_some_and(|(_, ext)| ext == self.extension)
+    }
+}
+
+impl Plugin for SfcPlugin {
+    fn name(&self) -> &'static str {
+        "sfc"
+    }
+
+    fn extensions(&self) -> Vec<String> {
+        vec![self.extension.clone()]
+    }
+
+    fn transform(
+        &self,
+        code: &str,
+        id: &str,
+        _ctx: &PluginContext,
+    ) -> HookResult<Option<TransformResult>> {
+        if !self.is_component(id) {
+            return Ok(None);
+        }
+
+        let script = blocks(code, "script").next().unwrap_or("");
+        let script = if script.contains("export default") {
+            script.replacen("export default", "const __sfc__ =", 1)
+        } else {
+            format!("{script}\nconst __sfc__ = {{}};")
+        };
+        let template = blocks(code, "template").next().unwrap_or("").trim();
+
+        let js = format!(
+            "{}\n__sfc__.template = {};\nexport default __sfc__;\n",
+            script.trim(),
+            serde_json::to_string(template).unwrap_or_default()
+        );
+
+        let css: String = blocks(code, "style")
+            .map(str::trim)
+            .collect::<Vec<_>>()
+            .join("\n");
+        let result = TransformResult::code(js);
+        Ok(Some(if css.is_empty() {
+            result
+        } else {
+            result.with_css(css)
+        }))
+    }
+}
+
+/// Contents of the top-level `<tag ...>...</tag>` blocks of a component.
+fn blocks<'a>(source: &'a str, tag: &'a str) -> impl Iterator<Item = &'a str> + 'a {
+    let open = format!("<{tag}");
+    let close = format!("</{tag}>");
+    let mut rest = source;
+    std::iter::from_fn(move || loop {
+        let start = rest.find(&open)?;
+        let after_name = &rest[start + open.len()..];
+        // `<script>` or `<script lang="ts">`, not `<scripts>`
+        if !after_name.starts_with(['>', ' ', '\t', '\n', '\r']) {
+            rest = after_name;
+            continue;
+        }
+        let body_start = after_name.find('>')? + 1;
+        let body = &after_name[body_start..];
+        // Templates nest, so their block ends at the last closing tag
+        let end = if tag == "template" {
+            body.rfind(&close)?
+        } else {
+            body.find(&close)?
+        };
+        rest = &body[end + close.len()..];
+        return Some(&body[..end]);
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const COMPONENT: &str = r#"<script>
+import { greet } from './greet';
+export default { name: 'Hello' };
+</script>
+
+<template>
+  <div><template v-if="ok"><p class="hello">{{ msg }}</p></template></div>
+</template>
+
+<style>
+.hello { color: red; }
+</style>
+<style scoped>
+.other { color: blue; }
+</style>
+"#;
+
+    #[test]
+    fn test_blocks() {
+        assert_eq!(
+            blocks(COMPONENT, "template").next().unwrap().trim(),
+            r#"<div><template v-if="ok"><p class="hello">{{ msg }}</p></template></div>"#
+        );
+        assert_eq!(blocks(COMPONENT, "style").count(), 2);
+        assert_eq!(blocks("<scripts></scripts>", "script").count(), 0);
+    }
+
+    #[test]
+    fn test_transform_component() {
+        let plugin = SfcPlugin::new("sfc");
+        let ctx = PluginContext::default();
+
+        let result = plugin
+            .transform(COMPONENT, "/src/Hello.sfc", &ctx)
+            .unwrap()
+            .unwrap();
+        assert!(result.code.starts_with("import { greet } from './greet';"));
+        assert!(result.code.contains("const __sfc__ = { name: 'Hello' };"));
+        assert!(result
+            .code
+            .contains(r#"__sfc__.template = "<div><template v-if=\"ok\">"#));
+        assert!(result.code.ends_with("export default __sfc__;\n"));
+        assert_eq!(
+            result.css.as_deref(),
+            Some(".hello { color: red; }\n.other { color: blue; }")
+        );
+
+        assert!(plugin.transform("x", "/src/a.js", &ctx).unwrap().is_none());
+    }
+
+    #[test]
+    fn test_transform_without_script_or_style() {
+        let plugin = SfcPlugin::new("sfc");
+        let result = plugin
+            .transform(
+                "<template><p/></template>",
+                "A.sfc",
+                &PluginContext::default(),
+            )
+            .unwrap()
+            .unwrap();
+        assert!(result.code.contains("const __sfc__ = {};"));
+        assert!(result.css.is_none());
+    }
+}
@@ -0,0 +1,195 @@
+//! URL and data: URI imports.
+//!
+//! Imports such as `https://esm.sh/react` or `data:text/javascript,...` never
+//! resolve on disk. By default they are left as external imports; with
+//! [`super::BundleOptions::vendor_urls`] static imports of `http:`, `https:`
+//! and `data:` modules are downloaded (or decoded) at build time and bundled.
+//! Dynamic `import()` of a URL is always preserved as-is.
+
+use std::time::Duration;
+
+/// Warning code for imports with a URL scheme that can't be vendored.
+pub const UNSUPPORTED_SCHEME: &str = "UNSUPPORTED_SCHEME";
+
+/// Schemes that can be vendored into the bundle.
+const VENDORABLE_SCHEMES: [&str; 3] = ["http", "https", "data"];
+
+/// Get the URL scheme of a specifier (`https` for `https://...`), if any.
+///
+/// Single-letter schemes are rejected so Windows drive paths (`C:\...`) are
+/// not mistaken for URLs. `node:` is handled as a built-in, not a URL.
+pub fn url_scheme(specifier: &str) -> Option<&str> {
+    let (scheme, _) = specifier.split_once(':')?;
+    let mut chars = scheme.chars();
+    let valid = scheme.len() > 1
+        && chars.next().is_some_and(|c| c.is_ascii_alphabetic())
+        && chars.all(|c| c.is_ascii_alphanumeric() || matches!(c, '+' | '-' | '.'));
+    (valid && scheme != "node").then_some(scheme)
+}
+
+/// Check if a URL uses a scheme that can be vendored.
+pub fn is_vendorable(url: &str) -> bool {
+    url_scheme(url).is_some_and(|scheme| VENDORABLE_SCHEMES.contains(&scheme))
+}
+
+/// Resolve a relative (`./x`, `../x`) or root-relative (`/x`) specifier
+/// against the URL of the importing module.
+pub fn join_url(base: &str, specifier: &str) -> Option<String> {
+    let base = url::Url::parse(base).ok()?;
+    if base.cannot_be_a_base() {
+        return None;
+    }
+    base.join(specifier).ok().map(String::from)
+}
+
+/// Load the source of a vendorable URL.
+///
+/// # Errors
+/// Returns a message if the URL can't be decoded or downloaded.
+pub fn load(url: &str) -> Result<String, String> {
+    match url_scheme(url) {
+        Some("data") => decode_data_url(url),
+        Some("http" | "https") => fetch(url),
+        _ => Err(format!("cannot vendor '{}': unsupported URL scheme", url)),
+    }
+}
+
+/// Decode the payload of a `data:` URL (`data:[<media type>][;base64],<data>`).
+pub fn decode_data_url(url: &str) -> Result<String, String> {
+    let rest = url
+        .strip_prefix("data:")
+        .ok_or_else(|| format!("not a data: URL: '{}'", url))?;
+    let (meta, data) = rest
+        .split_once(',')
+        .ok_or_else(|| format!("malformed data: URL (missing ','): '{}'", url))?;
+
+    let bytes = if meta.ends_with(";base64") {
+        decode_base64(data).ok_or_else(|| "malformed base64 in data: URL".to_string())?
+    } else {
+        percent_decode(data)
+    };
+    String::from_utf8(bytes).map_err(|_| "data: URL is not valid UTF-8".to_string())
+}
+
+/// Download a module over HTTP(S).
+///
+/// Runs on its own thread with a private runtime so it works whether or not
+/// the caller is already inside a tokio runtime.
+fn fetch(url: &str) -> Result<String, String> {
+    let owned = url.to_string();
+    let handle = std::thread::spawn(move || {
+        let rt = tokio::runtime::Builder::new_current_thread()
+            .enable_all()
+            .build()
+            .map_err(|e| e.to_string())?;
+        rt.block_on(async {
+            let client = reqwest::Client::builder()
+                .connect_timeout(Duration::from_secs(5))
+                .timeout(Duration::from_secs(30))
+                .user_agent(concat!("howth/", env!("CARGO_PKG_VERSION")))
+                .build()
+                .map_err(|e| e.to_string())?;
+            let response = client.get(&owned).send().await.map_err(|e| e.to_string())?;
+            let status = response.status();
+            if !status.is_success() {
+                return Err(format!("HTTP {}", status));
+            }
+            response.text().await.map_err(|e| e.to_string())
+        })
+    });
+
+    handle
+        .join()
+        .map_err(|_| "download thread panicked".to_string())?
+        .map_err(|e| format!("failed to download '{}': {}", url, e))
+}
+
+/// Decode `%XX` escapes, leaving malformed escapes as-is.
+fn percent_decode(input: &str) -> Vec<u8> {
+    let bytes = input.as_bytes();
+    let mut out = Vec::with_capacity(bytes.len());
+    let mut i = 0;
+    while i < bytes.len() {
+        if bytes[i] == b'%' && i + 2 < bytes.len() {
+            let hex = std::str::from_utf8(&bytes[i + 1..i + 3]).ok();
+            if let Some(byte) = hex.and_then(|hex| u8::from_str_radix(hex, 16).ok()) {
+                out.push(byte);
+                i += 3;
+                continue;
+            }
+        }
+        out.push(bytes[i]);
+        i += 1;
+    }
+    out
+}
+
+/// Decode standard base64 (padding optional, whitespace ignored).
+fn decode_base64(input: &str) -> Option<Vec<u8>> {
+    let mut out = Vec::with_capacity(input.len() * 3 / 4);
+    let mut buffer = 0u32;
+    let mut bits = 0;
+    for c in input.bytes() {
+        let value = match c {
+            b'A'..=b'Z' => c - b'A',
+            b'a'..=b'z' => c - b'a' + 26,
+            b'0'..=b'9' => c - b'0' + 52,
+            b'+' | b'-' => 62,
+            b'/' | b'_' => 63,
+            b'=' | b' ' | b'\n' | b'\r' | b'\t' => continue,
+            _ => return None,
+        };
+        buffer = (buffer << 6) | u32::from(value);
+        bits += 6;
+        if bits >= 8 {
+            bits -= 8;
+            out.push((buffer >> bits) as u8);
+            buffer &= (1 << bits) - 1;
+        }
+    }
+    Some(out)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_url_scheme() {
+        assert_eq!(url_scheme("https://esm.sh/react"), Some("https"));
+        assert_eq!(url_scheme("data:text/javascript,1"), Some("data"));
+        assert_eq!(url_scheme("ftp://example.com/a.js"), Some("ftp"));
+        assert_eq!(url_scheme("node:fs"), None);
+        assert_eq!(url_scheme("C:\\src\\a.js"), None);
+        assert_eq!(url_scheme("./a:b.js"), None);
+        assert_eq!(url_scheme("lodash"), None);
+        assert!(is_vendorable("http://localhost/a.js"));
+        assert!(!is_vendorable("ftp://example.com/a.js"));
+    }
+
+    #[test]
+    fn test_decode_data_url() {
+        assert_eq!(
+            decode_data_url("data:text/javascript,export%20const%20x%20=%201;").unwrap(),
+            "export const x = 1;"
+        );
+        assert_eq!(
+            decode_data_url("data:text/javascript;base64,ZXhwb3J0IGRlZmF1bHQgNDI7").unwrap(),
+            "export default 42;"
+        );
+        assert!(decode_data_url("data:text/javascript").is_err());
+    }
+
+    #[test]
+    fn test_join_url() {
+        assert_eq!(
+            join_url("https://esm.sh/v1/react.mjs", "./dom.mjs").as_deref(),
+            Some("https://esm.sh/v1/dom.mjs")
+        );
+        assert_eq!(
+            join_url("https://esm.sh/v1/react.mjs", "/v2/x.mjs").as_deref(),
+            Some("https://esm.sh/v2/x.mjs")
+        );
+        assert_eq!(join_url("data:text/javascript,1", "./x.js"), None);
+    }
+}
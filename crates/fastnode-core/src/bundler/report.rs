@@ -0,0 +1,285 @@
+//! Stable JSON view of a module graph.
+//!
+//! `howth graph <entry> --json` prints a [`GraphReport`] so editor extensions
+//! and other tooling can show project structure without bundling. The shape
+//! is versioned by [`GRAPH_REPORT_SCHEMA_VERSION`] and deterministic: nodes
+//! are sorted by path and numbered in that order, edges by source, target
+//! and specifier.
+
+use super::builtins::EMPTY_STUB_PREFIX;
+use super::graph::{ModuleGraph, ModuleId};
+use super::remote;
+use super::Import;
+use serde::Serialize;
+use std::path::Path;
+
+/// Schema version of [`GraphReport`].
+pub const GRAPH_REPORT_SCHEMA_VERSION: u32 = 1;
+
+/// How a module refers to another.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ImportKind {
+    /// `import { x } from`, `export * from`, ...
+    Static,
+    /// `import()`, a code split point.
+    Dynamic,
+    /// `import "./polyfill"`, for side effects only.
+    SideEffect,
+}
+
+impl ImportKind {
+    fn of(import: &Import) -> Self {
+        if import.dynamic {
+            Self::Dynamic
+        } else if import.names.is_empty() {
+            Self::SideEffect
+        } else {
+            Self::Static
+        }
+    }
+}
+
+/// What a node stands for.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum NodeKind {
+    /// A file on disk.
+    File,
+    /// A vendored `http(s):`/`data:` URL.
+    Remote,
+    /// An empty stub standing in for a Node.js built-in.
+    Stub,
+}
+
+/// A module in the report.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct GraphNode {
+    /// Index into [`GraphReport::nodes`].
+    pub id: usize,
+    /// Path relative to the working directory with `/` separators, or the
+    /// URL/stub name for non-file modules.
+    pub path: String,
+    /// What the node stands for.
+    pub kind: NodeKind,
+    /// Source size in bytes.
+    pub size: u64,
+    /// Size after transpilation in bytes.
+    pub output_size: u64,
+    /// Whether this is one of the requested entries.
+    pub entry: bool,
+}
+
+/// An import between two modules in the graph.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct GraphEdge {
+    /// Importing node.
+    pub from: usize,
+    /// Imported node.
+    pub to: usize,
+    /// Specifier as written in the source.
+    pub specifier: String,
+    /// Import kind.
+    pub kind: ImportKind,
+    /// Imported names (`default`, `*`, or export names).
+    pub names: Vec<String>,
+}
+
+/// An import that is not a module in the graph: external packages,
+/// CSS/assets and skipped built-ins.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct GraphExternal {
+    /// Importing node.
+    pub from: usize,
+    /// Specifier as written in the source.
+    pub specifier: String,
+    /// Import kind.
+    pub kind: ImportKind,
+}
+
+/// Serializable module graph.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct GraphReport {
+    /// Always [`GRAPH_REPORT_SCHEMA_VERSION`].
+    pub schema_version: u32,
+    /// Working directory paths are relative to.
+    pub cwd: String,
+    /// Entry node ids, in the order requested.
+    pub entries: Vec<usize>,
+    /// Modules sorted by path.
+    pub nodes: Vec<GraphNode>,
+    /// Imports resolved to modules in the graph.
+    pub edges: Vec<GraphEdge>,
+    /// Imports left outside the graph.
+    pub externals: Vec<GraphExternal>,
+}
+
+impl GraphReport {
+    /// Build the report for `graph` rooted at `entries`.
+    #[must_use]
+    pub fn new(graph: &ModuleGraph, entries: &[ModuleId], cwd: &Path) -> Self {
+        let root = dunce::canonicalize(cwd).unwrap_or_else(|_| cwd.to_path_buf());
+        let mut order: Vec<(String, ModuleId)> = graph
+            .iter()
+            .map(|(id, module)| (display_path(&module.path, &root), id))
+            .collect();
+        order.sort();
+
+        let mut report_id = vec![0; graph.len()];
+        for (index, (_, id)) in order.iter().enumerate() {
+            report_id[*id] = index;
+        }
+
+        let mut nodes = Vec::with_capacity(order.len());
+        let mut edges = Vec::new();
+        let mut externals = Vec::new();
+        for (index, (path, id)) in order.into_iter().enumerate() {
+            let module = graph.get(id).expect("module id from graph");
+            let kind = node_kind(&module.path);
+            let output_size = module.source.len() as u64;
+            let size = match kind {
+                NodeKind::File => std::fs::metadata(&module.path)
+                    .map(|m| m.len())
+                    .unwrap_or(output_size),
+                NodeKind::Remote | NodeKind::Stub => output_size,
+            };
+            nodes.push(GraphNode {
+                id: index,
+                path,
+                kind,
+                size,
+                output_size,
+                entry: entries.contains(&id),
+            });
+
+            for import in &module.imports {
+                let kind = ImportKind::of(import);
+                match graph.resolve_specifier(&module.path, &import.specifier) {
+                    Some(target) => edges.push(GraphEdge {
+                        from: index,
+                        to: report_id[target],
+                        specifier: import.specifier.clone(),
+                        kind,
+                        names: import.names.iter().map(|n| n.imported.clone()).collect(),
+                    }),
+                    None => externals.push(GraphExternal {
+                        from: index,
+                        specifier: import.specifier.clone(),
+                        kind,
+                    }),
+                }
+            }
+        }
+
+        edges.sort_by(|a, b| {
+            (a.from, a.to, &a.specifier, a.kind).cmp(&(b.from, b.to, &b.specifier, b.kind))
+        });
+        edges.dedup();
+        externals
+            .sort_by(|a, b| (a.from, &a.specifier, a.kind).cmp(&(b.from, &b.specifier, b.kind)));
+        externals.dedup();
+
+        Self {
+            schema_version: GRAPH_REPORT_SCHEMA_VERSION,
+            cwd: cwd.display().to_string(),
+            entries: entries.iter().map(|&id| report_id[id]).collect(),
+            nodes,
+            edges,
+            externals,
+        }
+    }
+}
+
+fn node_kind(path: &str) -> NodeKind {
+    if path.starts_with(EMPTY_STUB_PREFIX) {
+        NodeKind::Stub
+    } else if remote::is_vendorable(path) {
+        NodeKind::Remote
+    } else {
+        NodeKind::File
+    }
+}
+
+/// Path relative to `root` with `/` separators; other modules are unchanged.
+pub(super) fn display_path(path: &str, root: &Path) -> String {
+    if node_kind(path) != NodeKind::File {
+        return path.to_string();
+    }
+    Path::new(path)
+        .strip_prefix(root)
+        .unwrap_or(Path::new(path))
+        .to_string_lossy()
+        .replace('\\', "/")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::bundler::{BundleOptions, Bundler};
+    use std::fs;
+
+    #[test]
+    fn test_report_nodes_edges_and_externals() {
+        let dir = tempfile::tempdir().unwrap();
+        let root = dunce::canonicalize(dir.path()).unwrap();
+        fs::create_dir_all(root.join("src")).unwrap();
+        fs::write(
+            root.join("src/index.ts"),
+            "import { add } from \"./math\";\nimport \"./setup.js\";\nimport React from \"react\";\nconst lazy = () => import(\"./lazy.js\");\nconsole.log(add(1, 2), React, lazy);\n",
+        )
+        .unwrap();
+        fs::write(
+            root.join("src/math.ts"),
+            "export const add = (a: number, b: number) => a + b;\n",
+        )
+        .unwrap();
+        fs::write(root.join("src/setup.js"), "globalThis.ready = true;\n").unwrap();
+        fs::write(root.join("src/lazy.js"), "export default 1;\n").unwrap();
+
+        let options = BundleOptions {
+            external: vec!["react".to_string()],
+            ..Default::default()
+        };
+        let (graph, entries) = Bundler::new()
+            .module_graph(&[Path::new("src/index.ts")], &root, &options)
+            .unwrap();
+        let report = GraphReport::new(&graph, &entries, &root);
+
+        let paths: Vec<&str> = report.nodes.iter().map(|n| n.path.as_str()).collect();
+        assert_eq!(
+            paths,
+            ["src/index.ts", "src/lazy.js", "src/math.ts", "src/setup.js"]
+        );
+        assert_eq!(report.entries, [0]);
+        assert!(report.nodes[0].entry);
+        assert_eq!(report.nodes[2].size, 52);
+        assert!(report.nodes[2].output_size < report.nodes[2].size);
+
+        let edges: Vec<(usize, usize, &str, ImportKind)> = report
+            .edges
+            .iter()
+            .map(|e| (e.from, e.to, e.specifier.as_str(), e.kind))
+            .collect();
+        assert_eq!(
+            edges,
+            [
+                (0, 1, "./lazy.js", ImportKind::Dynamic),
+                (0, 2, "./math", ImportKind::Static),
+                (0, 3, "./setup.js", ImportKind::SideEffect),
+            ]
+        );
+        assert_eq!(report.edges[1].names, ["add"]);
+        assert_eq!(
+            report.externals,
+            [GraphExternal {
+                from: 0,
+                specifier: "react".to_string(),
+                kind: ImportKind::Static,
+            }]
+        );
+
+        let json = serde_json::to_string(&report).unwrap();
+        assert!(json.contains("\"schema_version\":1"));
+        assert!(json.contains("\"kind\":\"side_effect\""));
+    }
+}
@@ -7,6 +7,9 @@
 //! - Relative: `./utils`, `../lib/foo`
 //! - Absolute: `/abs/path/to/module`
 //! - Bare: `lodash`, `@scope/pkg`, `react/jsx-runtime`
+//! - Built-in: `fs`, `node:path` (see [`super::builtins`])
+//! - URL: `https://esm.sh/react`, `data:text/javascript,...` (external, see
+//!   [`super::remote`])
 
 #![allow(clippy::manual_strip)]
 #![allow(clippy::needless_lifetimes)]
@@ -114,6 +117,19 @@ impl Resolver {
         Ok(result)
     }
 
+    /// Resolve a bare specifier from node_modules only.
+    ///
+    /// Unlike [`Resolver::resolve`], names of Node.js built-ins are looked up
+    /// as packages, so `buffer` finds the `buffer` polyfill package.
+    pub fn resolve_package(
+        &self,
+        specifier: &str,
+        from: &Path,
+        cwd: &Path,
+    ) -> Result<ResolveResult, ResolveError> {
+        self.resolve_bare(specifier, from, cwd)
+    }
+
     /// Get or populate the directory listing cache for the given directory.
     fn get_dir_listing(&self, dir: &Path) -> Option<DirListing> {
         // Fast path: check cache with read lock
@@ -180,11 +196,16 @@ impl Resolver {
         from: &Path,
         cwd: &Path,
     ) -> Result<ResolveResult, ResolveError> {
-        // Handle built-in modules
-        if specifier.starts_with("node:") {
+        // Handle built-in modules (core modules take precedence over node_modules)
+        if super::builtins::builtin_name(specifier).is_some() {
             return Ok(ResolveResult::Builtin(specifier.to_string()));
         }
 
+        // URLs and data: URIs never resolve on disk
+        if super::remote::url_scheme(specifier).is_some() {
+            return Ok(ResolveResult::External(specifier.to_string()));
+        }
+
         // Handle relative imports
         if specifier.starts_with("./") || specifier.starts_with("../") {
             return self.resolve_relative(specifier, from);
@@ -547,4 +568,27 @@ mod tests {
             panic!("Expected Found result");
         }
     }
+
+    #[test]
+    fn test_resolve_builtins() {
+        let dir = tempdir().unwrap();
+        let pkg = dir.path().join("node_modules/buffer");
+        std::fs::create_dir_all(&pkg).unwrap();
+        std::fs::write(pkg.join("index.js"), "module.exports = {};").unwrap();
+        let from = dir.path().join("index.js");
+
+        let resolver = Resolver::new();
+        for specifier in ["fs", "node:fs", "buffer"] {
+            assert!(matches!(
+                resolver.resolve(specifier, &from, dir.path()),
+                Ok(ResolveResult::Builtin(s)) if s == specifier
+            ));
+        }
+
+        // Polyfill lookup bypasses the built-in check
+        match resolver.resolve_package("buffer", &from, dir.path()) {
+            Ok(ResolveResult::Found(path)) => assert_eq!(path, pkg.join("index.js")),
+            other => panic!("Expected Found result, got {other:?}"),
+        }
+    }
 }
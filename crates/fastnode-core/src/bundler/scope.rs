@@ -997,7 +997,7 @@ const internal = 42;
         let ctx = ScopeHoistContext::analyze(&graph, &order);
 
         // Empty module should work without error
-        assert!(ctx.get_module_symbols(id).is_empty());
+        assert_eq!(ctx.get_module_symbols(id), &[] as &[SymbolId]);
     }
 
     #[test]
@@ -1018,7 +1018,7 @@ const internal = 42;
         let ctx = ScopeHoistContext::analyze(&graph, &order);
 
         // Whitespace-only module should work
-        assert!(ctx.get_module_symbols(id).is_empty());
+        assert_eq!(ctx.get_module_symbols(id), &[] as &[SymbolId]);
     }
 
     #[test]
@@ -1114,7 +1114,7 @@ const internal = 42;
         let ctx = ScopeHoistContext::analyze(&graph, &order);
 
         // Comment-only module should have no symbols
-        assert!(ctx.get_module_symbols(id).is_empty());
+        assert_eq!(ctx.get_module_symbols(id), &[] as &[SymbolId]);
     }
 
     #[test]
@@ -1122,7 +1122,7 @@ const internal = 42;
         let ctx = ScopeHoistContext::new();
 
         // Getting symbols for non-existent module should return empty
-        assert!(ctx.get_module_symbols(999).is_empty());
+        assert_eq!(ctx.get_module_symbols(999), &[] as &[SymbolId]);
         assert!(ctx.get_exports(999).is_none());
         assert!(!ctx.is_wrapped(999));
     }
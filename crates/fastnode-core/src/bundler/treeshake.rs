@@ -44,20 +44,30 @@ impl UsedExports {
 
     /// Analyze the module graph and determine which exports are used.
     pub fn analyze(graph: &ModuleGraph, entry_id: ModuleId) -> Self {
+        Self::analyze_entries(graph, &[entry_id])
+    }
+
+    /// Analyze used exports for several entry points at once.
+    ///
+    /// An export is kept if any entry uses it, so modules shared between
+    /// entries are tree-shaken consistently.
+    pub fn analyze_entries(graph: &ModuleGraph, entry_ids: &[ModuleId]) -> Self {
         let mut used = Self::new();
 
         // First pass: extract re-exports from all modules
         used.extract_re_exports(graph);
 
-        // Entry module: all exports are used (it's the public API)
-        used.mark_all_used(entry_id);
-
         // BFS traversal to mark used exports
         let mut queue: VecDeque<ModuleId> = VecDeque::new();
         let mut visited: HashSet<ModuleId> = HashSet::default();
 
-        queue.push_back(entry_id);
-        visited.insert(entry_id);
+        for &entry_id in entry_ids {
+            // Entry modules: all exports are used (they're the public API)
+            used.mark_all_used(entry_id);
+            if visited.insert(entry_id) {
+                queue.push_back(entry_id);
+            }
+        }
 
         while let Some(module_id) = queue.pop_front() {
             if let Some(module) = graph.get(module_id) {
@@ -133,6 +143,9 @@ impl UsedExports {
             // Side-effect import: import './module'
             // Don't mark all exports - just mark the module as having side effects
             self.side_effect_modules.insert(target_id);
+        } else if import.names.iter().any(|name| name.imported == "*") {
+            // Namespace import or require(): any export can be read
+            self.mark_all_used(target_id);
         } else {
             // Named imports - mark specific exports as used
             for name in &import.names {
@@ -0,0 +1,240 @@
+//! `new URL('./file', import.meta.url)` asset references.
+//!
+//! The standard way for module code to reference a file next to it:
+//!
+//! ```js
+//! const logo = new URL('./logo.png', import.meta.url);
+//! ```
+//!
+//! [`find_asset_urls`] locates these expressions so the bundler can emit the
+//! file as a hashed asset and the dev server can point the URL at the file's
+//! root-relative path (see [`rewrite_asset_urls`]). Only relative string
+//! specifiers are matched; any other first argument is a runtime URL and is
+//! left alone.
+//!
+//! A URL passed straight to `new Worker(...)` or `new SharedWorker(...)`
+//! references a worker script rather than a static file, and is reported as
+//! [`AssetUrlKind::Worker`] so it can be bundled as its own entry.
+
+use howth_parser::visit::walk_exprs;
+use howth_parser::{Ast, Expr, ExprKind, Parser, ParserOptions, PropertyKey};
+use std::collections::HashMap;
+use std::fmt::Write;
+
+/// What an asset URL references.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum AssetUrlKind {
+    /// A static file.
+    Asset,
+    /// A worker script, i.e. the URL is the first argument of `new Worker`
+    /// or `new SharedWorker`. `module` is set by `{ type: 'module' }`.
+    Worker {
+        /// Whether the worker is a module worker.
+        module: bool,
+    },
+}
+
+/// A `new URL(specifier, import.meta.url)` expression.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct AssetUrl {
+    /// Relative specifier, e.g. `./logo.png`.
+    pub specifier: String,
+    /// What the URL references.
+    pub kind: AssetUrlKind,
+    /// Byte offset where the expression starts.
+    pub start: usize,
+    /// Byte offset where the expression ends (exclusive).
+    pub end: usize,
+}
+
+/// Asset URL expressions in JavaScript `code`, in source order.
+///
+/// Returns nothing if the code doesn't parse.
+#[must_use]
+pub fn find_asset_urls(code: &str) -> Vec<AssetUrl> {
+    if !code.contains("import.meta.url") {
+        return Vec::new();
+    }
+    let options = ParserOptions {
+        module: true,
+        ..Default::default()
+    };
+    Parser::new(code, options)
+        .parse()
+        .map(|ast| asset_urls(&ast))
+        .unwrap_or_default()
+}
+
+/// Asset URL expressions in a parsed module, in source order.
+#[must_use]
+pub fn asset_urls(ast: &Ast) -> Vec<AssetUrl> {
+    let mut urls = Vec::new();
+    // Worker constructors are visited before their arguments
+    let mut worker_args = HashMap::new();
+    walk_exprs(&ast.stmts, &mut |expr: &Expr| {
+        if let Some((arg_start, module)) = worker_url_arg(expr) {
+            worker_args.insert(arg_start, module);
+        }
+        if let Some(specifier) = asset_url_specifier(expr) {
+            let start = expr.span.start as usize;
+            // Spans run to the next token; drop the whitespace in between
+            let text = ast.source.get(start..expr.span.end as usize).unwrap_or("");
+            let kind = worker_args
+                .get(&expr.span.start)
+                .map_or(AssetUrlKind::Asset, |&module| AssetUrlKind::Worker {
+                    module,
+                });
+            urls.push(AssetUrl {
+                specifier,
+                kind,
+                start,
+                end: start + text.trim_end().len(),
+            });
+        }
+    });
+    urls.sort_by_key(|url| url.start);
+    urls
+}
+
+/// Replace each asset URL with `new URL("<url>", <base>)`, where `url` is
+/// what `resolve` returns for it. URLs `resolve` returns `None` for are left
+/// unchanged.
+pub fn rewrite_asset_urls(
+    code: &str,
+    urls: &[AssetUrl],
+    base: &str,
+    mut resolve: impl FnMut(&AssetUrl) -> Option<String>,
+) -> String {
+    let mut out = String::with_capacity(code.len());
+    let mut last = 0;
+    for url in urls {
+        let Some(resolved) = resolve(url) else {
+            continue;
+        };
+        out.push_str(&code[last..url.start]);
+        let _ = write!(
+            out,
+            "new URL({}, {base})",
+            serde_json::to_string(&resolved).unwrap_or_default()
+        );
+        last = url.end;
+    }
+    out.push_str(&code[last..]);
+    out
+}
+
+fn asset_url_specifier(expr: &Expr) -> Option<String> {
+    let ExprKind::New { callee, args } = &expr.kind else {
+        return None;
+    };
+    if !matches!(&callee.kind, ExprKind::Ident(name) if name == "URL") || args.len() != 2 {
+        return None;
+    }
+    let (ExprKind::String(specifier) | ExprKind::TemplateNoSub(specifier)) = &args[0].kind else {
+        return None;
+    };
+    let relative = specifier.starts_with("./") || specifier.starts_with("../");
+    (relative && is_import_meta_url(&args[1])).then(|| specifier.clone())
+}
+
+/// Start of the first argument of `new Worker(...)` / `new SharedWorker(...)`
+/// and whether the worker is a module worker.
+fn worker_url_arg(expr: &Expr) -> Option<(u32, bool)> {
+    let ExprKind::New { callee, args } = &expr.kind else {
+        return None;
+    };
+    if !matches!(&callee.kind, ExprKind::Ident(name) if name == "Worker" || name == "SharedWorker")
+    {
+        return None;
+    }
+    let url = args.first()?;
+    let module = args.get(1).is_some_and(|options| {
+        let ExprKind::Object(props) = &options.kind else {
+            return false;
+        };
+        props.iter().any(|prop| {
+            matches!(&prop.key, PropertyKey::Ident(key) | PropertyKey::String(key) if key == "type")
+                && matches!(&prop.value.kind, ExprKind::String(value) if value == "module")
+        })
+    });
+    Some((url.span.start, module))
+}
+
+fn is_import_meta_url(expr: &Expr) -> bool {
+    let ExprKind::Member {
+        object,
+        property,
+        computed: false,
+    } = &expr.kind
+    else {
+        return false;
+    };
+    matches!(&object.kind, ExprKind::MetaProperty { meta, property } if meta == "import" && property == "meta")
+        && matches!(&property.kind, ExprKind::Ident(name) if name == "url")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_find_asset_urls() {
+        let code = "const logo = new URL('./logo.png', import.meta.url).href;\n\
+                    function load() { return fetch(new URL(`../data.json` , import.meta.url)); }\n\
+                    new URL('https://example.com', import.meta.url);\n\
+                    new URL(name, import.meta.url);\n\
+                    new URL('./other.png', location.href);\n";
+        let urls = find_asset_urls(code);
+        let specifiers: Vec<&str> = urls.iter().map(|u| u.specifier.as_str()).collect();
+        assert_eq!(specifiers, ["./logo.png", "../data.json"]);
+        assert_eq!(
+            &code[urls[0].start..urls[0].end],
+            "new URL('./logo.png', import.meta.url)"
+        );
+        assert_eq!(
+            &code[urls[1].start..urls[1].end],
+            "new URL(`../data.json` , import.meta.url)"
+        );
+
+        assert_eq!(find_asset_urls("new URL('./a.png', base);"), []);
+        assert!(urls.iter().all(|u| u.kind == AssetUrlKind::Asset));
+    }
+
+    #[test]
+    fn test_find_worker_urls() {
+        let code = "new Worker(new URL('./a.js', import.meta.url));\n\
+                    new Worker(new URL('./b.ts', import.meta.url), { type: 'module' });\n\
+                    new SharedWorker(new URL('./c.js', import.meta.url), { \"type\": \"module\", name: 'c' });\n\
+                    new Worker(new URL('./d.js', import.meta.url), { type: 'classic' });\n\
+                    fetch(new URL('./e.json', import.meta.url), new Worker(x));\n";
+        let kinds: Vec<(&str, AssetUrlKind)> = find_asset_urls(code)
+            .iter()
+            .map(|u| (&code[u.start..u.end], u.kind))
+            .map(|(text, kind)| (text.split('\'').nth(1).unwrap(), kind))
+            .collect();
+        assert_eq!(
+            kinds,
+            [
+                ("./a.js", AssetUrlKind::Worker { module: false }),
+                ("./b.ts", AssetUrlKind::Worker { module: true }),
+                ("./c.js", AssetUrlKind::Worker { module: true }),
+                ("./d.js", AssetUrlKind::Worker { module: false }),
+                ("./e.json", AssetUrlKind::Asset),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_rewrite_asset_urls() {
+        let code =
+            "a(new URL('./a.png', import.meta.url), new URL('./missing.png', import.meta.url));";
+        let urls = find_asset_urls(code);
+        let rewritten = rewrite_asset_urls(code, &urls, "import.meta.url", |url| {
+            (url.specifier == "./a.png").then(|| "./a.1234abcd.png".to_string())
+        });
+        assert_eq!(
+            rewritten,
+            "a(new URL(\"./a.1234abcd.png\", import.meta.url), new URL('./missing.png', import.meta.url));"
+        );
+    }
+}
@@ -2,9 +2,15 @@
 //!
 //! This module provides the howth-parser-based implementation of the `CompilerBackend` trait.
 //! It handles JS/TS/JSX/TSX transpilation without any SWC dependency.
+//!
+//! Syntax newer than `TranspileSpec::target` is lowered, decorators follow
+//! `TranspileSpec::decorators`, and `TranspileSpec::react_refresh` appends
+//! React Refresh registrations for top-level components.
 
-use super::spec::{JsxRuntime, SourceMapKind};
+use super::refresh;
+use super::spec::{DecoratorMode, EsTarget, JsxRuntime, SourceMapKind};
 use super::{CompilerBackend, CompilerError, TranspileOutput, TranspileSpec};
+use crate::sourcemap::SourceMap;
 
 /// howth-parser-based compiler backend.
 ///
@@ -37,6 +43,29 @@ impl HowthBackend {
             .and_then(|ext| ext.to_str())
             .is_some_and(|ext| matches!(ext.to_lowercase().as_str(), "jsx" | "tsx"))
     }
+
+    /// Map the spec target onto the codegen target.
+    fn codegen_target(target: EsTarget) -> howth_parser::Target {
+        use howth_parser::Target;
+        match target {
+            EsTarget::ES2015 => Target::ES2015,
+            EsTarget::ES2016 => Target::ES2016,
+            EsTarget::ES2017 => Target::ES2017,
+            EsTarget::ES2018 => Target::ES2018,
+            EsTarget::ES2019 => Target::ES2019,
+            EsTarget::ES2020 => Target::ES2020,
+            EsTarget::ES2021 => Target::ES2021,
+            EsTarget::ES2022 => Target::ES2022,
+            EsTarget::ES2023 | EsTarget::ES2024 | EsTarget::ESNext => Target::ESNext,
+        }
+    }
+
+    fn codegen_decorators(mode: DecoratorMode) -> howth_parser::DecoratorMode {
+        match mode {
+            DecoratorMode::Tc39 => howth_parser::DecoratorMode::Tc39,
+            DecoratorMode::Legacy => howth_parser::DecoratorMode::Legacy,
+        }
+    }
 }
 
 impl CompilerBackend for HowthBackend {
@@ -64,47 +93,84 @@ impl CompilerBackend for HowthBackend {
             typescript: is_ts,
         };
 
-        let ast = Parser::new(source, parser_opts)
+        let mut ast = Parser::new(source, parser_opts)
             .parse()
             .map_err(|e| CompilerError::parse_error(e.to_string()))?;
+        if spec.minify {
+            howth_parser::fold::fold(&mut ast);
+        }
 
+        let wants_map = matches!(
+            spec.sourcemaps,
+            SourceMapKind::Inline | SourceMapKind::External
+        );
         let codegen_opts = CodegenOptions {
             minify: spec.minify,
+            source_map: wants_map,
+            target: Self::codegen_target(spec.target),
+            decorators: Self::codegen_decorators(spec.decorators),
             ..Default::default()
         };
-        let mut code = Codegen::new(&ast, codegen_opts).generate();
+        let (mut code, mappings) = Codegen::new(&ast, codegen_opts).generate_with_source_map();
+        let mut map = wants_map
+            .then(|| SourceMap::from_codegen(&source_map_name(spec), source, &code, &mappings));
+
+        if spec.react_refresh {
+            let registrations = refresh::registrations(&ast);
+            if !registrations.is_empty() {
+                if !code.ends_with('\n') {
+                    code.push('\n');
+                }
+                code.push_str(&registrations);
+            }
+        }
 
         // Prepend JSX runtime import for JSX/TSX files (automatic mode only)
         if is_jsx && spec.jsx_runtime == JsxRuntime::Automatic {
             code = format!(
                 "import {{ jsx as _jsx, jsxs as _jsxs, Fragment as _Fragment }} from \"react/jsx-runtime\";\n{code}"
             );
+            if let Some(map) = &mut map {
+                map.prepend_lines(1);
+            }
         }
 
-        let mut output = TranspileOutput::new(code);
+        let output = TranspileOutput::new(code);
+        Ok(match map {
+            Some(map) => output.with_source_map(map.to_json()),
+            None => output,
+        })
+    }
+}
 
-        // Generate placeholder source map if requested
-        if matches!(
-            spec.sourcemaps,
-            SourceMapKind::Inline | SourceMapKind::External
-        ) {
-            let filename = spec
-                .input_path
-                .file_name()
-                .and_then(|n| n.to_str())
-                .unwrap_or("unknown");
-            let map =
-                format!(r#"{{"version":3,"sources":["{filename}"],"names":[],"mappings":"AAAA"}}"#);
-            output = output.with_source_map(map);
-        }
+/// Name of the input file in its source map.
+fn source_map_name(spec: &TranspileSpec) -> String {
+    spec.input_path
+        .file_name()
+        .and_then(|n| n.to_str())
+        .unwrap_or("unknown")
+        .to_string()
+}
 
-        Ok(output)
+/// Attach a placeholder source map if the spec asks for one.
+pub(super) fn with_placeholder_source_map(
+    spec: &TranspileSpec,
+    output: TranspileOutput,
+) -> TranspileOutput {
+    if !matches!(
+        spec.sourcemaps,
+        SourceMapKind::Inline | SourceMapKind::External
+    ) {
+        return output;
     }
+    let filename = source_map_name(spec);
+    let map = format!(r#"{{"version":3,"sources":["{filename}"],"names":[],"mappings":"AAAA"}}"#);
+    output.with_source_map(map)
 }
 
 #[cfg(test)]
 mod tests {
-    use super::super::spec::{JsxRuntime, SourceMapKind};
+    use super::super::spec::{DecoratorMode, EsTarget, JsxRuntime, SourceMapKind};
     use super::*;
     use std::path::PathBuf;
 
@@ -223,6 +289,76 @@ mod tests {
         assert!(output.source_map.is_some());
         let map = output.source_map.unwrap();
         assert!(map.contains("\"version\":3"));
+
+        // Statements map back through type stripping and the JSX import
+        let spec = TranspileSpec::new("src/app.tsx", "dist/app.js")
+            .with_sourcemaps(SourceMapKind::External);
+        let source = "type A = string;\n\nconst a: A = 'a';\nconst el = <div />;\n";
+        let output = backend.transpile(&spec, source).unwrap();
+        let map = SourceMap::parse(&output.source_map.unwrap()).unwrap();
+        assert_eq!(map.sources, vec!["app.tsx"]);
+        let line = |text: &str| {
+            output
+                .code
+                .lines()
+                .position(|l| l.starts_with(text))
+                .unwrap()
+        };
+        let original = map.lookup(line("const a") as u32, 0).unwrap();
+        assert_eq!(original.line, 2);
+        let original = map.lookup(line("const el") as u32, 0).unwrap();
+        assert_eq!(original.line, 3);
+    }
+
+    #[test]
+    fn test_transpile_lowers_to_target() {
+        let backend = HowthBackend::new();
+        let source = "const v = a?.b ?? c;";
+
+        let spec = TranspileSpec::new("src/app.js", "dist/app.js");
+        let output = backend.transpile(&spec, source).unwrap();
+        assert!(output.code.contains("a?.b ?? c"));
+
+        let spec = spec.with_target(EsTarget::ES2019);
+        let output = backend.transpile(&spec, source).unwrap();
+        assert!(!output.code.contains("?."));
+        assert!(!output.code.contains("??"));
+        assert!(output.code.contains("var __tmp, __ctx;"));
+    }
+
+    #[test]
+    fn test_transpile_decorators() {
+        let backend = HowthBackend::new();
+        let source = "@sealed class Service { @log run() {} }";
+
+        let spec = TranspileSpec::new("src/service.ts", "dist/service.js");
+        let output = backend.transpile(&spec, source).unwrap();
+        assert!(output
+            .code
+            .contains("__decorateElement(Service, \"method\", \"run\""));
+
+        let spec = spec.with_decorators(DecoratorMode::Legacy);
+        let output = backend.transpile(&spec, source).unwrap();
+        assert!(output
+            .code
+            .contains("__decorate([log], Service.prototype, \"run\", null)"));
+        assert!(output
+            .code
+            .contains("Service = __decorate([sealed], Service)"));
+    }
+
+    #[test]
+    fn test_transpile_react_refresh() {
+        let backend = HowthBackend::new();
+        let source = "export function App() { return <div />; }";
+
+        let spec = TranspileSpec::new("src/App.jsx", "dist/App.js");
+        let output = backend.transpile(&spec, source).unwrap();
+        assert!(!output.code.contains("$RefreshReg$"));
+
+        let spec = spec.with_react_refresh(true);
+        let output = backend.transpile(&spec, source).unwrap();
+        assert!(output.code.ends_with("$RefreshReg$(App, \"App\");\n"));
     }
 
     #[test]
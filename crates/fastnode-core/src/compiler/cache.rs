@@ -0,0 +1,275 @@
+//! In-memory transpile cache keyed by content hash.
+//!
+//! Used by the native runtime's module loader and by the daemon, where one
+//! cache serves `Transpile` requests from runtimes and dev servers as well
+//! as the transpile nodes of builds (through [`CachedBackend`]). The key
+//! covers the backend, the full `TranspileSpec` and the source text, so an
+//! edited file or a changed option is always a miss and never stale.
+
+use super::{CompilerBackend, CompilerError, EsTarget, TranspileOutput, TranspileSpec};
+use blake3::Hasher;
+use std::collections::HashMap;
+use std::path::Path;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, RwLock};
+
+/// Entries kept before the cache is cleared.
+pub const DEFAULT_MAX_ENTRIES: usize = 4096;
+
+/// Spec for transpiling a module on demand for direct execution.
+///
+/// ESM output for the latest target with no source map, matching what the
+/// module loader has always produced.
+#[must_use]
+pub fn on_demand_spec(path: &Path) -> TranspileSpec {
+    TranspileSpec::new(path, path).with_target(EsTarget::ESNext)
+}
+
+/// Hit/miss counters for a [`TranspileCache`].
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct TranspileCacheStats {
+    /// Lookups served from the cache.
+    pub hits: u64,
+    /// Lookups that ran the compiler.
+    pub misses: u64,
+    /// Entries currently cached.
+    pub entries: usize,
+}
+
+/// Thread-safe cache of transpile outputs.
+#[derive(Debug)]
+pub struct TranspileCache {
+    entries: RwLock<HashMap<String, Arc<TranspileOutput>>>,
+    max_entries: usize,
+    hits: AtomicU64,
+    misses: AtomicU64,
+}
+
+impl Default for TranspileCache {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl TranspileCache {
+    /// Create an empty cache holding up to [`DEFAULT_MAX_ENTRIES`].
+    #[must_use]
+    pub fn new() -> Self {
+        Self::with_max_entries(DEFAULT_MAX_ENTRIES)
+    }
+
+    /// Create an empty cache that is cleared once it exceeds `max_entries`.
+    #[must_use]
+    pub fn with_max_entries(max_entries: usize) -> Self {
+        Self {
+            entries: RwLock::new(HashMap::new()),
+            max_entries: max_entries.max(1),
+            hits: AtomicU64::new(0),
+            misses: AtomicU64::new(0),
+        }
+    }
+
+    /// Cache key for transpiling `source` with `backend` and `spec`.
+    #[must_use]
+    pub fn key(backend: &dyn CompilerBackend, spec: &TranspileSpec, source: &str) -> String {
+        let mut hasher = Hasher::new();
+        hasher.update(backend.name().as_bytes());
+        hasher.update(b"\0");
+        hasher.update(&spec.canonical_encoding());
+        hasher.update(b"\0");
+        hasher.update(source.as_bytes());
+        hasher.finalize().to_hex().to_string()
+    }
+
+    /// Return the cached code for `source`, transpiling it on a miss.
+    ///
+    /// The flag is `true` when the code came from the cache.
+    ///
+    /// # Errors
+    /// Returns the backend's error on a miss that fails to transpile.
+    /// Failures are not cached.
+    ///
+    /// # Panics
+    /// Panics if the entries lock is poisoned.
+    pub fn get_or_transpile(
+        &self,
+        backend: &dyn CompilerBackend,
+        spec: &TranspileSpec,
+        source: &str,
+    ) -> Result<(Arc<str>, bool), CompilerError> {
+        let (output, cached) = self.get_or_transpile_output(backend, spec, source)?;
+        Ok((output.code.as_str().into(), cached))
+    }
+
+    /// Like [`get_or_transpile`](Self::get_or_transpile), returning the
+    /// whole output including its source map.
+    ///
+    /// # Errors
+    /// Returns the backend's error on a miss that fails to transpile.
+    /// Failures are not cached.
+    ///
+    /// # Panics
+    /// Panics if the entries lock is poisoned.
+    pub fn get_or_transpile_output(
+        &self,
+        backend: &dyn CompilerBackend,
+        spec: &TranspileSpec,
+        source: &str,
+    ) -> Result<(Arc<TranspileOutput>, bool), CompilerError> {
+        let key = Self::key(backend, spec, source);
+        if let Some(output) = self.entries.read().unwrap().get(&key) {
+            self.hits.fetch_add(1, Ordering::Relaxed);
+            return Ok((Arc::clone(output), true));
+        }
+
+        self.misses.fetch_add(1, Ordering::Relaxed);
+        let output = Arc::new(backend.transpile(spec, source)?);
+
+        let mut entries = self.entries.write().unwrap();
+        if entries.len() >= self.max_entries {
+            entries.clear();
+        }
+        entries.insert(key, Arc::clone(&output));
+        Ok((output, false))
+    }
+
+    /// Current hit/miss counters.
+    ///
+    /// # Panics
+    /// Panics if the entries lock is poisoned.
+    #[must_use]
+    pub fn stats(&self) -> TranspileCacheStats {
+        TranspileCacheStats {
+            hits: self.hits.load(Ordering::Relaxed),
+            misses: self.misses.load(Ordering::Relaxed),
+            entries: self.entries.read().unwrap().len(),
+        }
+    }
+}
+
+/// Compiler backend that transpiles through a shared [`TranspileCache`].
+///
+/// Reports the wrapped backend's name, so its entries are the ones a direct
+/// lookup with that backend would hit.
+pub struct CachedBackend {
+    inner: Arc<dyn CompilerBackend>,
+    cache: Arc<TranspileCache>,
+}
+
+impl std::fmt::Debug for CachedBackend {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("CachedBackend")
+            .field("inner", &self.inner.name())
+            .field("cache", &self.cache.stats())
+            .finish()
+    }
+}
+
+impl CachedBackend {
+    /// Wrap `inner` so its outputs are stored in `cache`.
+    #[must_use]
+    pub fn new(inner: Arc<dyn CompilerBackend>, cache: Arc<TranspileCache>) -> Self {
+        Self { inner, cache }
+    }
+}
+
+impl CompilerBackend for CachedBackend {
+    fn name(&self) -> &'static str {
+        self.inner.name()
+    }
+
+    fn transpile(
+        &self,
+        spec: &TranspileSpec,
+        source: &str,
+    ) -> Result<TranspileOutput, CompilerError> {
+        let (output, _) = self
+            .cache
+            .get_or_transpile_output(self.inner.as_ref(), spec, source)?;
+        Ok(TranspileOutput::clone(&output))
+    }
+
+    fn supports_extension(&self, ext: &str) -> bool {
+        self.inner.supports_extension(ext)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::compiler::{HowthBackend, HowthParserBackend, SourceMapKind};
+
+    #[test]
+    fn test_hit_after_miss() {
+        let cache = TranspileCache::new();
+        let backend = HowthBackend::new();
+        let spec = TranspileSpec::new("src/app.ts", "src/app.ts");
+
+        let (code, cached) = cache
+            .get_or_transpile(&backend, &spec, "const a: number = 1;")
+            .unwrap();
+        assert!(!cached);
+        assert!(code.contains("const a = 1"));
+
+        let (again, cached) = cache
+            .get_or_transpile(&backend, &spec, "const a: number = 1;")
+            .unwrap();
+        assert!(cached);
+        assert_eq!(code, again);
+        assert_eq!(
+            cache.stats(),
+            TranspileCacheStats {
+                hits: 1,
+                misses: 1,
+                entries: 1
+            }
+        );
+    }
+
+    #[test]
+    fn test_key_covers_source_spec_and_backend() {
+        let swc = HowthBackend::new();
+        let native = HowthParserBackend::new();
+        let spec = TranspileSpec::new("src/app.ts", "src/app.ts");
+        let key = TranspileCache::key(&swc, &spec, "let a = 1;");
+
+        assert_ne!(key, TranspileCache::key(&swc, &spec, "let a = 2;"));
+        assert_ne!(key, TranspileCache::key(&native, &spec, "let a = 1;"));
+        let tsx = TranspileSpec::new("src/app.tsx", "src/app.tsx");
+        assert_ne!(key, TranspileCache::key(&swc, &tsx, "let a = 1;"));
+    }
+
+    #[test]
+    fn test_cached_backend_shares_entries_and_source_maps() {
+        let cache = Arc::new(TranspileCache::new());
+        let inner: Arc<dyn CompilerBackend> = Arc::new(HowthParserBackend::new());
+        let backend = CachedBackend::new(Arc::clone(&inner), Arc::clone(&cache));
+        let mut spec = TranspileSpec::new("src/app.ts", "src/app.ts");
+        spec.sourcemaps = SourceMapKind::External;
+
+        let built = backend.transpile(&spec, "const a: number = 1;").unwrap();
+        assert_eq!(backend.name(), inner.name());
+
+        let (output, cached) = cache
+            .get_or_transpile_output(inner.as_ref(), &spec, "const a: number = 1;")
+            .unwrap();
+        assert!(cached);
+        assert_eq!(*output, built);
+        assert!(output.source_map.is_some());
+    }
+
+    #[test]
+    fn test_errors_are_not_cached_and_size_is_bounded() {
+        let cache = TranspileCache::with_max_entries(2);
+        let backend = HowthBackend::new();
+        let spec = TranspileSpec::new("src/app.js", "src/app.js");
+
+        assert!(cache.get_or_transpile(&backend, &spec, "let = ;").is_err());
+        assert_eq!(cache.stats().entries, 0);
+
+        for source in ["let a;", "let b;", "let c;"] {
+            cache.get_or_transpile(&backend, &spec, source).unwrap();
+        }
+        assert!(cache.stats().entries <= 2);
+    }
+}
@@ -0,0 +1,85 @@
+//! Conformance between the `swc` and `native` compiler backends.
+//!
+//! Every file in `tests/fixtures/compiler` is transpiled by both backends
+//! with the default spec and the outputs must match. Options the native
+//! backend ignores are listed in `DIVERGENCES`; those cases must still
+//! differ, so a fixed divergence fails here until its entry is removed.
+
+use super::{
+    CompilerBackend, DecoratorMode, EsTarget, HowthBackend, HowthParserBackend, TranspileSpec,
+};
+use std::path::{Path, PathBuf};
+
+/// Known parity gaps: fixture, description and the spec that exposes it.
+const DIVERGENCES: &[(&str, &str, fn(TranspileSpec) -> TranspileSpec)] = &[
+    ("modern.js", "target lowering", |spec| {
+        spec.with_target(EsTarget::ES2019)
+    }),
+    ("decorators.ts", "legacy decorators", |spec| {
+        spec.with_decorators(DecoratorMode::Legacy)
+    }),
+    ("component.tsx", "react refresh", |spec| {
+        spec.with_react_refresh(true)
+    }),
+    ("basic.js", "minify", |spec| spec.with_minify(true)),
+];
+
+fn fixture_dir() -> PathBuf {
+    Path::new(env!("CARGO_MANIFEST_DIR")).join("tests/fixtures/compiler")
+}
+
+fn fixtures() -> Vec<PathBuf> {
+    let mut files: Vec<PathBuf> = std::fs::read_dir(fixture_dir())
+        .unwrap()
+        .map(|entry| entry.unwrap().path())
+        .collect();
+    files.sort();
+    files
+}
+
+fn spec_for(path: &Path) -> TranspileSpec {
+    let name = path.file_name().unwrap().to_string_lossy();
+    TranspileSpec::new(format!("src/{name}"), "dist/out.js")
+}
+
+/// Transpile with both backends, returning `(swc, native)` output.
+fn both(spec: &TranspileSpec, source: &str) -> (String, String) {
+    let swc = HowthBackend::new().transpile(spec, source);
+    let native = HowthParserBackend::new().transpile(spec, source);
+    let path = spec.input_path.display();
+    let swc = swc.unwrap_or_else(|e| panic!("swc failed on {path}: {e}"));
+    let native = native.unwrap_or_else(|e| panic!("native failed on {path}: {e}"));
+    (swc.code, native.code)
+}
+
+#[test]
+fn test_backends_agree_on_fixtures() {
+    let files = fixtures();
+    assert!(files.len() >= 5, "fixture corpus is missing");
+
+    let mut mismatches = Vec::new();
+    for path in &files {
+        let source = std::fs::read_to_string(path).unwrap();
+        let (swc, native) = both(&spec_for(path), &source);
+        if swc != native {
+            mismatches.push(format!(
+                "{}\n--- swc\n{swc}\n--- native\n{native}",
+                path.display()
+            ));
+        }
+    }
+    assert!(mismatches.is_empty(), "{}", mismatches.join("\n\n"));
+}
+
+#[test]
+fn test_known_divergences() {
+    for (fixture, what, configure) in DIVERGENCES {
+        let path = fixture_dir().join(fixture);
+        let source = std::fs::read_to_string(&path).unwrap();
+        let (swc, native) = both(&configure(spec_for(&path)), &source);
+        assert_ne!(
+            swc, native,
+            "{fixture}: native now matches swc for {what}; remove it from DIVERGENCES"
+        );
+    }
+}
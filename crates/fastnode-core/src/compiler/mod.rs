@@ -28,21 +28,100 @@
 #![allow(clippy::redundant_closure_for_method_calls)]
 #![allow(clippy::manual_strip)]
 
+pub mod asset_url;
 pub mod ast_parser;
 pub mod backend;
+pub mod cache;
+#[cfg(test)]
+mod conformance;
+pub mod native;
+pub mod refresh;
+pub mod require;
 pub mod spec;
+pub mod tree;
 
 pub use backend::HowthBackend;
+pub use cache::{on_demand_spec, CachedBackend, TranspileCache, TranspileCacheStats};
+pub use native::HowthParserBackend;
 pub use spec::{
-    Diagnostic, DiagnosticSeverity, EsTarget, JsxRuntime, ModuleKind, SourceMapKind,
+    DecoratorMode, Diagnostic, DiagnosticSeverity, EsTarget, JsxRuntime, ModuleKind, SourceMapKind,
     TranspileOutput, TranspileSpec,
 };
+pub use tree::{needs_transpile, package_root, transpile_tree, TranspiledTree, RUN_CACHE_DIR};
 
 /// Backward-compatible alias for `HowthBackend`.
 pub type SwcBackend = HowthBackend;
 
 use std::fmt;
 use std::path::Path;
+use std::sync::Arc;
+
+/// Which compiler backend to transpile with.
+///
+/// Selected with the `HOWTH_COMPILER` environment variable (`swc` or
+/// `native`); unset or unknown values use the default.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum CompilerKind {
+    /// The configurable `HowthBackend` (historically SWC).
+    #[default]
+    Swc,
+    /// The single-pass `HowthParserBackend`.
+    Native,
+}
+
+impl CompilerKind {
+    /// Environment variable that selects the backend.
+    pub const ENV_VAR: &'static str = "HOWTH_COMPILER";
+
+    /// Parse a backend name.
+    #[must_use]
+    pub fn parse(name: &str) -> Option<Self> {
+        match name.trim().to_lowercase().as_str() {
+            "swc" | "howth" => Some(Self::Swc),
+            "native" => Some(Self::Native),
+            _ => None,
+        }
+    }
+
+    /// Read the backend from `HOWTH_COMPILER`.
+    #[must_use]
+    pub fn from_env() -> Self {
+        std::env::var(Self::ENV_VAR)
+            .ok()
+            .and_then(|v| Self::parse(&v))
+            .unwrap_or_default()
+    }
+
+    /// Get the string representation.
+    #[must_use]
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::Swc => "swc",
+            Self::Native => "native",
+        }
+    }
+
+    /// Construct the backend.
+    #[must_use]
+    pub fn backend(self) -> Arc<dyn CompilerBackend> {
+        match self {
+            Self::Swc => Arc::new(HowthBackend::new()),
+            Self::Native => Arc::new(HowthParserBackend::new()),
+        }
+    }
+}
+
+impl fmt::Display for CompilerKind {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+/// Construct the backend selected by `HOWTH_COMPILER`.
+#[must_use]
+pub fn backend_from_env() -> Arc<dyn CompilerBackend> {
+    CompilerKind::from_env().backend()
+}
 
 /// Target ES version (alias for compatibility).
 pub type Target = EsTarget;
@@ -611,6 +690,45 @@ pub fn transform_tsx(source: &str) -> Result<(String, Vec<crate::bundler::Import
     Ok((code, imports))
 }
 
+/// Source map of the code [`transform_jsx`], [`transform_ts`] or
+/// [`transform_tsx`] (chosen by the extension of `path`) produce from
+/// `source`, mapping it back to `source` under the name `path`.
+///
+/// Returns `None` for other extensions (their code isn't transformed) or
+/// when `source` doesn't parse.
+#[must_use]
+pub fn transpile_source_map(source: &str, path: &str) -> Option<crate::sourcemap::SourceMap> {
+    use howth_parser::{Codegen, CodegenOptions, Parser, ParserOptions};
+
+    let ext = Path::new(path)
+        .extension()
+        .and_then(|e| e.to_str())
+        .unwrap_or("");
+    let (jsx, typescript) = match ext {
+        "jsx" => (true, false),
+        "ts" | "mts" | "cts" => (false, true),
+        "tsx" => (true, true),
+        _ => return None,
+    };
+    let parser_opts = ParserOptions {
+        module: true,
+        jsx,
+        typescript,
+    };
+    let ast = Parser::new(source, parser_opts).parse().ok()?;
+    let codegen_opts = CodegenOptions {
+        source_map: true,
+        ..Default::default()
+    };
+    let (code, mappings) = Codegen::new(&ast, codegen_opts).generate_with_source_map();
+    let mut map = crate::sourcemap::SourceMap::from_codegen(path, source, &code, &mappings);
+    if jsx {
+        // The jsx runtime import line
+        map.prepend_lines(1);
+    }
+    Some(map)
+}
+
 /// Error during compilation.
 #[derive(Debug)]
 pub struct CompilerError {
@@ -748,6 +866,17 @@ mod tests {
         assert!(display.contains("Missing semicolon"));
     }
 
+    #[test]
+    fn test_compiler_kind() {
+        assert_eq!(CompilerKind::parse("swc"), Some(CompilerKind::Swc));
+        assert_eq!(CompilerKind::parse(" Native "), Some(CompilerKind::Native));
+        assert_eq!(CompilerKind::parse("oxc"), None);
+        assert_eq!(CompilerKind::default(), CompilerKind::Swc);
+        assert_eq!(CompilerKind::Swc.backend().name(), "howth");
+        assert_eq!(CompilerKind::Native.backend().name(), "native");
+        assert_eq!(CompilerKind::Native.to_string(), "native");
+    }
+
     #[test]
     fn test_default_extension_support() {
         // Test trait default implementation via SwcBackend
@@ -0,0 +1,145 @@
+//! Native compiler backend built on the single-pass transforms.
+//!
+//! `HowthParserBackend` routes each file through `transform_ts`,
+//! `transform_tsx` or `transform_jsx` — the same parse+codegen pass that
+//! `howth run` uses — instead of the configurable pipeline in
+//! [`HowthBackend`](super::HowthBackend). It always emits ESNext with TC39
+//! decorators and ignores `target`, `decorators`, `minify` and
+//! `react_refresh`; the conformance suite records where that diverges.
+//!
+//! Select it with `HOWTH_COMPILER=native` (see [`CompilerKind`](super::CompilerKind)).
+
+use super::backend::with_placeholder_source_map;
+use super::spec::JsxRuntime;
+use super::{
+    transform_jsx, transform_ts, transform_tsx, CompilerBackend, CompilerError, TranspileOutput,
+    TranspileSpec,
+};
+
+/// Import line `transform_jsx`/`transform_tsx` prepend for the automatic runtime.
+const JSX_RUNTIME_IMPORT: &str =
+    "import { jsx as _jsx, jsxs as _jsxs, Fragment as _Fragment } from \"react/jsx-runtime\";\n";
+
+/// Compiler backend using the howth-parser single-pass transforms.
+#[derive(Debug, Clone, Default)]
+pub struct HowthParserBackend {
+    _private: (),
+}
+
+impl HowthParserBackend {
+    /// Create a new native backend.
+    #[must_use]
+    pub fn new() -> Self {
+        Self { _private: () }
+    }
+
+    /// Parse and regenerate plain JavaScript.
+    fn transform_js(source: &str) -> Result<String, CompilerError> {
+        use howth_parser::{Codegen, CodegenOptions, Parser, ParserOptions};
+
+        let parser_opts = ParserOptions {
+            module: true,
+            jsx: false,
+            typescript: false,
+        };
+        let ast = Parser::new(source, parser_opts)
+            .parse()
+            .map_err(|e| CompilerError::parse_error(e.to_string()))?;
+        Ok(Codegen::new(&ast, CodegenOptions::default()).generate())
+    }
+}
+
+impl CompilerBackend for HowthParserBackend {
+    fn name(&self) -> &'static str {
+        "native"
+    }
+
+    fn transpile(
+        &self,
+        spec: &TranspileSpec,
+        source: &str,
+    ) -> Result<TranspileOutput, CompilerError> {
+        if source.is_empty() {
+            return Ok(TranspileOutput::new(""));
+        }
+
+        let ext = spec
+            .input_path
+            .extension()
+            .and_then(|e| e.to_str())
+            .unwrap_or("js")
+            .to_lowercase();
+
+        let mut code = match ext.as_str() {
+            "ts" | "mts" | "cts" => transform_ts(source)?.0,
+            "tsx" => transform_tsx(source)?.0,
+            "jsx" => transform_jsx(source)?.0,
+            _ => Self::transform_js(source)?,
+        };
+
+        // The transforms always target the automatic runtime; classic mode
+        // keeps the calls but leaves the import to the caller.
+        if spec.jsx_runtime != JsxRuntime::Automatic {
+            if let Some(rest) = code.strip_prefix(JSX_RUNTIME_IMPORT) {
+                code = rest.to_string();
+            }
+        }
+
+        Ok(with_placeholder_source_map(
+            spec,
+            TranspileOutput::new(code),
+        ))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::compiler::SourceMapKind;
+
+    #[test]
+    fn test_backend_name() {
+        assert_eq!(HowthParserBackend::new().name(), "native");
+    }
+
+    #[test]
+    fn test_transpile_by_extension() {
+        let backend = HowthParserBackend::new();
+
+        let spec = TranspileSpec::new("src/app.ts", "dist/app.js");
+        let output = backend.transpile(&spec, "const x: number = 1;").unwrap();
+        assert!(output.code.contains("const x = 1"));
+
+        let spec = TranspileSpec::new("src/App.tsx", "dist/App.js");
+        let output = backend
+            .transpile(&spec, "const a = <div id={x as string} />;")
+            .unwrap();
+        assert!(output.code.starts_with(JSX_RUNTIME_IMPORT));
+        assert!(!output.code.contains(" as string"));
+
+        let spec = TranspileSpec::new("src/util.js", "dist/util.js");
+        let output = backend.transpile(&spec, "export const y = a < b;").unwrap();
+        assert!(output.code.contains("a < b"));
+    }
+
+    #[test]
+    fn test_transpile_classic_runtime_omits_import() {
+        let backend = HowthParserBackend::new();
+        let spec =
+            TranspileSpec::new("src/App.jsx", "dist/App.js").with_jsx_runtime(JsxRuntime::Classic);
+        let output = backend.transpile(&spec, "const a = <div />;").unwrap();
+        assert!(!output.code.contains("react/jsx-runtime"));
+    }
+
+    #[test]
+    fn test_transpile_source_map_and_errors() {
+        let backend = HowthParserBackend::new();
+        let spec =
+            TranspileSpec::new("src/app.ts", "dist/app.js").with_sourcemaps(SourceMapKind::Inline);
+        let output = backend.transpile(&spec, "let a = 1;").unwrap();
+        assert!(output.source_map.is_some());
+
+        let err = backend.transpile(&spec, "let = ;").unwrap_err();
+        assert_eq!(err.code, "COMPILER_PARSE_ERROR");
+    }
+}
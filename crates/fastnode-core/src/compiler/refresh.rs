@@ -0,0 +1,266 @@
+//! React Refresh component registration.
+//!
+//! With `TranspileSpec::react_refresh`, every top-level binding that looks
+//! like a React component is registered with the refresh runtime after the
+//! module body runs, the same way `react-refresh/babel` does:
+//!
+//! ```text
+//! function App() { ... }
+//! export const Button = memo(() => ...);
+//! // appended:
+//! typeof $RefreshReg$ === "function" && $RefreshReg$(App, "App");
+//! typeof $RefreshReg$ === "function" && $RefreshReg$(Button, "Button");
+//! ```
+//!
+//! The dev server's React Refresh plugin defines `$RefreshReg$` per module
+//! and prefixes the id with the module path. The check keeps the output
+//! runnable where no refresh runtime is installed.
+//!
+//! A module is a refresh boundary ([`is_refresh_boundary`]) when it exports
+//! only components: re-running it can update those components in place.
+//! Any other export could be captured by importers, so edits to such modules
+//! propagate to their importers instead (and end in a full reload).
+
+use howth_parser::{Ast, BindingKind, ExportDecl, Expr, ExprKind, Stmt, StmtKind};
+use std::collections::HashSet;
+
+/// Wrappers whose result is still a component.
+const COMPONENT_WRAPPERS: &[&str] = &["memo", "forwardRef", "React.memo", "React.forwardRef"];
+
+/// Names of the top-level components declared in a module, in source order.
+#[must_use]
+pub fn component_names(ast: &Ast) -> Vec<String> {
+    let mut names = Vec::new();
+    for stmt in &ast.stmts {
+        collect_stmt(stmt, &mut names);
+    }
+    names
+}
+
+/// Registration statements to append to a module, or an empty string.
+#[must_use]
+pub fn registrations(ast: &Ast) -> String {
+    use std::fmt::Write;
+
+    let mut out = String::new();
+    for name in component_names(ast) {
+        let _ = writeln!(
+            out,
+            "typeof $RefreshReg$ === \"function\" && $RefreshReg$({name}, \"{name}\");"
+        );
+    }
+    out
+}
+
+/// Whether edits to the module can be applied by React Refresh alone: it
+/// declares components and every export is one of them.
+#[must_use]
+pub fn is_refresh_boundary(ast: &Ast) -> bool {
+    let components: HashSet<String> = component_names(ast).into_iter().collect();
+    let mut has_exports = false;
+    for stmt in &ast.stmts {
+        let StmtKind::Export(export) = &stmt.kind else {
+            continue;
+        };
+        has_exports = true;
+        let only_components = match export.as_ref() {
+            ExportDecl::Decl { decl, .. } => declared_names(decl)
+                .is_some_and(|names| names.iter().all(|name| components.contains(name.as_str()))),
+            ExportDecl::Default { expr, .. } => match &expr.kind {
+                ExprKind::Ident(name) => components.contains(name.as_str()),
+                ExprKind::Function(func) => func
+                    .name
+                    .as_deref()
+                    .is_some_and(|name| components.contains(name)),
+                _ => false,
+            },
+            ExportDecl::Named {
+                specifiers, source, ..
+            } => {
+                source.is_none()
+                    && specifiers
+                        .iter()
+                        .all(|spec| components.contains(spec.local.as_str()))
+            }
+            ExportDecl::All { .. } => false,
+        };
+        if !only_components {
+            return false;
+        }
+    }
+    has_exports && !components.is_empty()
+}
+
+/// Names bound by a declaration, or `None` for declarations (classes,
+/// destructuring) that are never treated as components.
+fn declared_names(stmt: &Stmt) -> Option<Vec<String>> {
+    match &stmt.kind {
+        StmtKind::Function(func) => func.name.clone().map(|name| vec![name]),
+        StmtKind::Var { decls, .. } => decls
+            .iter()
+            .map(|decl| match &decl.binding.kind {
+                BindingKind::Ident { name, .. } => Some(name.clone()),
+                _ => None,
+            })
+            .collect(),
+        _ => None,
+    }
+}
+
+fn collect_stmt(stmt: &Stmt, names: &mut Vec<String>) {
+    match &stmt.kind {
+        StmtKind::Function(func) => {
+            if let Some(name) = func.name.as_deref().filter(|n| is_component_name(n)) {
+                names.push(name.to_string());
+            }
+        }
+        StmtKind::Var { decls, .. } => {
+            for decl in decls {
+                let BindingKind::Ident { name, .. } = &decl.binding.kind else {
+                    continue;
+                };
+                if is_component_name(name) && decl.init.as_ref().is_some_and(is_component_init) {
+                    names.push(name.clone());
+                }
+            }
+        }
+        StmtKind::Export(export) => match export.as_ref() {
+            ExportDecl::Decl { decl, .. } => collect_stmt(decl, names),
+            ExportDecl::Default { expr, .. } => {
+                if let ExprKind::Function(func) = &expr.kind {
+                    if let Some(name) = func.name.as_deref().filter(|n| is_component_name(n)) {
+                        names.push(name.to_string());
+                    }
+                }
+            }
+            _ => {}
+        },
+        _ => {}
+    }
+}
+
+/// Components are named in PascalCase.
+fn is_component_name(name: &str) -> bool {
+    name.chars().next().is_some_and(|c| c.is_ascii_uppercase())
+}
+
+/// Whether a variable initializer produces a component.
+fn is_component_init(expr: &Expr) -> bool {
+    match &expr.kind {
+        ExprKind::Arrow(_) | ExprKind::Function(_) => true,
+        ExprKind::Call { callee, args } => {
+            callee_name(callee).is_some_and(|name| COMPONENT_WRAPPERS.contains(&name.as_str()))
+                && args.first().is_some_and(is_component_init_or_ref)
+        }
+        _ => false,
+    }
+}
+
+/// Argument of `memo(...)`/`forwardRef(...)`: a function or a component name.
+fn is_component_init_or_ref(expr: &Expr) -> bool {
+    match &expr.kind {
+        ExprKind::Ident(name) => is_component_name(name),
+        _ => is_component_init(expr),
+    }
+}
+
+/// Dotted name of a callee (`memo`, `React.memo`).
+fn callee_name(expr: &Expr) -> Option<String> {
+    match &expr.kind {
+        ExprKind::Ident(name) => Some(name.clone()),
+        ExprKind::Member {
+            object,
+            property,
+            computed: false,
+        } => {
+            let ExprKind::Ident(property) = &property.kind else {
+                return None;
+            };
+            Some(format!("{}.{property}", callee_name(object)?))
+        }
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use howth_parser::{Parser, ParserOptions};
+
+    fn names(source: &str) -> Vec<String> {
+        let opts = ParserOptions {
+            module: true,
+            jsx: true,
+            typescript: true,
+        };
+        component_names(&Parser::new(source, opts).parse().unwrap())
+    }
+
+    #[test]
+    fn test_component_names() {
+        let source = r"
+            function App() { return <div />; }
+            export function Header() { return null; }
+            export default function Page() { return null; }
+            const Button = () => <button />;
+            export const Card = memo(function Card() { return null; });
+            const Input = React.forwardRef((props, ref) => null);
+            const Wrapped = memo(App);
+            function helper() {}
+            const CONFIG = { a: 1 };
+            const lower = () => null;
+        ";
+        assert_eq!(
+            names(source),
+            ["App", "Header", "Page", "Button", "Card", "Input", "Wrapped"]
+        );
+    }
+
+    fn boundary(source: &str) -> bool {
+        let opts = ParserOptions {
+            module: true,
+            jsx: true,
+            typescript: true,
+        };
+        is_refresh_boundary(&Parser::new(source, opts).parse().unwrap())
+    }
+
+    #[test]
+    fn test_is_refresh_boundary() {
+        assert!(boundary(
+            "export default function App() { return <div />; }"
+        ));
+        assert!(boundary(
+            "const Button = () => <button />;\nfunction Card() {}\nexport { Button, Card };"
+        ));
+        assert!(boundary("function App() {}\nexport default App;"));
+        assert!(boundary(
+            "import { x } from './x';\nconst helper = () => x;\nexport const App = () => helper();"
+        ));
+
+        // Non-component exports can be captured by importers
+        assert!(!boundary(
+            "export const App = () => null;\nexport const THEME = 'dark';"
+        ));
+        assert!(!boundary("export function useCounter() {}"));
+        assert!(!boundary("export * from './components';"));
+        assert!(!boundary("export default () => <div />;"));
+        // Components without exports (e.g. the app entry) are not boundaries
+        assert!(!boundary("function App() {}\nrender(<App />);"));
+        assert!(!boundary("export const x = 1;"));
+    }
+
+    #[test]
+    fn test_registrations() {
+        let opts = ParserOptions {
+            module: true,
+            jsx: true,
+            typescript: false,
+        };
+        let ast = Parser::new("function App() {}", opts).parse().unwrap();
+        assert_eq!(
+            registrations(&ast),
+            "typeof $RefreshReg$ === \"function\" && $RefreshReg$(App, \"App\");\n"
+        );
+    }
+}
@@ -0,0 +1,97 @@
+//! CommonJS `require()` calls.
+//!
+//! CommonJS modules load their dependencies with `require('./dep')` instead
+//! of `import` statements. [`find_require_calls`] finds these so the bundler
+//! can add the required modules to the graph and point the calls at the
+//! module registry. A `require()` whose argument isn't a string literal
+//! (`require('./locale/' + lang)`) can't be resolved at build time and is
+//! only counted.
+
+use howth_parser::visit::walk_exprs;
+use howth_parser::{Ast, Expr, ExprKind, Parser, ParserOptions};
+
+/// The `require()` calls of a module.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct RequireCalls {
+    /// Specifiers of `require('x')` calls, deduplicated, in source order.
+    pub specifiers: Vec<String>,
+    /// Number of `require(expr)` calls with a non-literal argument.
+    pub dynamic: usize,
+}
+
+/// The `require()` calls in JavaScript `code`.
+///
+/// Returns nothing if the code doesn't parse.
+#[must_use]
+pub fn find_require_calls(code: &str) -> RequireCalls {
+    if !code.contains("require") {
+        return RequireCalls::default();
+    }
+    // CommonJS is script code, but a module may mix in `import`s
+    [true, false]
+        .into_iter()
+        .find_map(|module| {
+            let options = ParserOptions {
+                module,
+                ..Default::default()
+            };
+            Parser::new(code, options).parse().ok()
+        })
+        .map(|ast| require_calls(&ast))
+        .unwrap_or_default()
+}
+
+/// The `require()` calls in a parsed module.
+#[must_use]
+pub fn require_calls(ast: &Ast) -> RequireCalls {
+    let mut calls = RequireCalls::default();
+    walk_exprs(&ast.stmts, &mut |expr: &Expr| {
+        let ExprKind::Call { callee, args } = &expr.kind else {
+            return;
+        };
+        if !matches!(&callee.kind, ExprKind::Ident(name) if name == "require") || args.len() != 1 {
+            return;
+        }
+        match &args[0].kind {
+            ExprKind::String(specifier) | ExprKind::TemplateNoSub(specifier) => {
+                if !calls.specifiers.contains(specifier) {
+                    calls.specifiers.push(specifier.clone());
+                }
+            }
+            _ => calls.dynamic += 1,
+        }
+    });
+    calls
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_find_require_calls() {
+        let code = "const a = require('./a');\n\
+                    if (process.env.NODE_ENV === 'production') {\n\
+                      module.exports = require(\"./prod.js\");\n\
+                    } else {\n\
+                      module.exports = { dev: require(`./dev.js`).dev, a: require('./a') };\n\
+                    }\n\
+                    try { require('optional'); } catch (e) {}\n\
+                    const locale = require('./locale/' + lang);\n\
+                    require.resolve('./not-a-call');\n\
+                    obj.require('./method');\n";
+        let calls = find_require_calls(code);
+        assert_eq!(
+            calls.specifiers,
+            ["./a", "./prod.js", "./dev.js", "optional"]
+        );
+        assert_eq!(calls.dynamic, 1);
+    }
+
+    #[test]
+    fn test_find_require_calls_in_esm() {
+        let code = "import x from './x';\nconst y = require('./y');\nexport default x + y;\n";
+        assert_eq!(find_require_calls(code).specifiers, ["./y"]);
+        assert_eq!(find_require_calls("const a = 1;"), RequireCalls::default());
+    }
+}
@@ -163,6 +163,34 @@ impl std::fmt::Display for EsTarget {
     }
 }
 
+/// Decorator semantics.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize, Default)]
+#[serde(rename_all = "lowercase")]
+pub enum DecoratorMode {
+    /// Standard (TC39) decorators.
+    #[default]
+    Tc39,
+    /// TypeScript `experimentalDecorators`.
+    Legacy,
+}
+
+impl DecoratorMode {
+    /// Get the string representation.
+    #[must_use]
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::Tc39 => "tc39",
+            Self::Legacy => "legacy",
+        }
+    }
+}
+
+impl std::fmt::Display for DecoratorMode {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.as_str())
+    }
+}
+
 /// Diagnostic severity level.
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
 #[serde(rename_all = "lowercase")]
@@ -284,6 +312,12 @@ pub struct TranspileSpec {
     /// Whether to minify the output.
     #[serde(default)]
     pub minify: bool,
+    /// Decorator semantics.
+    #[serde(default)]
+    pub decorators: DecoratorMode,
+    /// Register React components with React Refresh (`$RefreshReg$`) for HMR.
+    #[serde(default)]
+    pub react_refresh: bool,
     /// Batch mode: transpile all files in input directory to output directory.
     #[serde(default)]
     pub batch: bool,
@@ -301,6 +335,8 @@ impl TranspileSpec {
             sourcemaps: SourceMapKind::default(),
             target: EsTarget::default(),
             minify: false,
+            decorators: DecoratorMode::default(),
+            react_refresh: false,
             batch: false,
         }
     }
@@ -319,6 +355,8 @@ impl TranspileSpec {
             sourcemaps: SourceMapKind::External,
             target: EsTarget::ES2020,
             minify: false,
+            decorators: DecoratorMode::default(),
+            react_refresh: false,
             batch: true,
         }
     }
@@ -364,6 +402,20 @@ impl TranspileSpec {
         self
     }
 
+    /// Set the decorator semantics.
+    #[must_use]
+    pub fn with_decorators(mut self, decorators: DecoratorMode) -> Self {
+        self.decorators = decorators;
+        self
+    }
+
+    /// Enable or disable React Refresh registration.
+    #[must_use]
+    pub fn with_react_refresh(mut self, react_refresh: bool) -> Self {
+        self.react_refresh = react_refresh;
+        self
+    }
+
     /// Get a deterministic canonical encoding for hashing.
     ///
     /// The encoding is stable and platform-independent.
@@ -416,6 +468,20 @@ impl TranspileSpec {
         buf.extend_from_slice(if self.minify { b"true" } else { b"false" });
         buf.push(0);
 
+        // Decorators
+        buf.extend_from_slice(b"decorators:");
+        buf.extend_from_slice(self.decorators.as_str().as_bytes());
+        buf.push(0);
+
+        // React Refresh
+        buf.extend_from_slice(b"react_refresh:");
+        buf.extend_from_slice(if self.react_refresh {
+            b"true"
+        } else {
+            b"false"
+        });
+        buf.push(0);
+
         // Batch mode
         buf.extend_from_slice(b"batch:");
         buf.extend_from_slice(if self.batch { b"true" } else { b"false" });
@@ -435,6 +501,8 @@ impl Default for TranspileSpec {
             sourcemaps: SourceMapKind::default(),
             target: EsTarget::default(),
             minify: false,
+            decorators: DecoratorMode::default(),
+            react_refresh: false,
             batch: false,
         }
     }
@@ -589,6 +657,23 @@ mod tests {
             TranspileSpec::new("src/App.tsx", "dist/App.js").with_jsx_runtime(JsxRuntime::Classic);
 
         assert_ne!(spec1.canonical_encoding(), spec3.canonical_encoding());
+
+        let legacy = spec1.clone().with_decorators(DecoratorMode::Legacy);
+        assert_ne!(spec1.canonical_encoding(), legacy.canonical_encoding());
+        let refresh = spec1.clone().with_react_refresh(true);
+        assert_ne!(spec1.canonical_encoding(), refresh.canonical_encoding());
+    }
+
+    #[test]
+    fn test_transpile_spec_new_fields_default_when_missing() {
+        let json = r#"{"input_path":"src/a.ts","output_path":"dist/a.js"}"#;
+        let spec: TranspileSpec = serde_json::from_str(json).unwrap();
+        assert_eq!(spec.decorators, DecoratorMode::Tc39);
+        assert!(!spec.react_refresh);
+        assert_eq!(
+            serde_json::to_string(&DecoratorMode::Legacy).unwrap(),
+            "\"legacy\""
+        );
     }
 
     #[test]
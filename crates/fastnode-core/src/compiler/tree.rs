@@ -0,0 +1,522 @@
+//! Transpile an entry file and the local modules it imports so plain `node`
+//! can run a TypeScript entry without a build step.
+//!
+//! Starting from the entry, every relative import that resolves to a
+//! TypeScript/JSX file is transpiled into [`RUN_CACHE_DIR`] under the
+//! package root, mirroring the source layout. Relative specifiers in the
+//! output are rewritten to point at the transpiled copies, or back at the
+//! original file for anything that isn't transpiled (`.json`, assets, and
+//! `.js` that doesn't lead to TypeScript). Bare specifiers are left alone:
+//! the cache lives inside the package's `node_modules`, so Node resolves
+//! packages exactly as it would from the sources.
+//!
+//! Each output is `.mjs` or `.cjs` to match the module syntax it uses, so
+//! CommonJS-style TypeScript keeps working. `import.meta.url` and `__dirname`
+//! refer to the cache copy, not the source file.
+
+use super::{CompilerBackend, CompilerError, TranspileSpec};
+use crate::imports::scan_imports;
+use std::collections::{HashMap, HashSet, VecDeque};
+use std::path::{Component, Path, PathBuf};
+
+/// Cache directory for transpiled run entries, relative to the package root.
+pub const RUN_CACHE_DIR: &str = "node_modules/.cache/howth/run";
+
+/// Directory under the cache for modules outside the package root.
+const EXTERNAL_DIR: &str = "_external";
+
+/// Extensions tried, in order, for a relative specifier without one.
+const RESOLVE_EXTENSIONS: &[&str] = &["ts", "tsx", "mts", "cts", "js", "jsx", "mjs", "cjs", "json"];
+
+/// Result of transpiling an entry's local module tree.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TranspiledTree {
+    /// Transpiled entry to hand to `node`.
+    pub entry: PathBuf,
+    /// Source files written to the cache, entry first.
+    pub sources: Vec<PathBuf>,
+}
+
+/// Whether `path` must be transpiled before Node can run it.
+#[must_use]
+pub fn needs_transpile(path: &Path) -> bool {
+    path.extension()
+        .and_then(|ext| ext.to_str())
+        .is_some_and(|ext| {
+            matches!(
+                ext.to_ascii_lowercase().as_str(),
+                "ts" | "tsx" | "mts" | "cts" | "jsx"
+            )
+        })
+}
+
+/// Nearest ancestor of `entry` containing a `package.json`, or its directory.
+#[must_use]
+pub fn package_root(entry: &Path) -> PathBuf {
+    let dir = entry.parent().unwrap_or(entry);
+    dir.ancestors()
+        .find(|d| d.join("package.json").is_file())
+        .unwrap_or(dir)
+        .to_path_buf()
+}
+
+/// A local module reached from the entry.
+struct Module {
+    /// Transpiled code, or the source itself for plain JavaScript.
+    code: String,
+    /// Where the module is written if it ends up in the cache.
+    out_path: PathBuf,
+    /// Relative specifiers and the files they resolve to, if any.
+    imports: Vec<(String, Option<PathBuf>)>,
+}
+
+/// Transpile `entry` and the TypeScript files it reaches through relative
+/// imports into `root/`[`RUN_CACHE_DIR`].
+///
+/// Plain JavaScript modules are copied into the cache too when they import
+/// something that is, so a `.js` file in the middle of a chain still loads
+/// the transpiled TypeScript rather than the source.
+///
+/// `entry` and `root` should be absolute.
+///
+/// # Errors
+/// Returns an error if a reachable module can't be read, transpiled or written.
+pub fn transpile_tree(
+    compiler: &dyn CompilerBackend,
+    root: &Path,
+    entry: &Path,
+) -> Result<TranspiledTree, CompilerError> {
+    let out_dir = root.join(RUN_CACHE_DIR);
+    let mut queue = VecDeque::from([entry.to_path_buf()]);
+    let mut seen: HashSet<PathBuf> = HashSet::from([entry.to_path_buf()]);
+    let mut order = Vec::new();
+    let mut modules: HashMap<PathBuf, Module> = HashMap::new();
+
+    while let Some(source_path) = queue.pop_front() {
+        let source = std::fs::read_to_string(&source_path)
+            .map_err(|e| CompilerError::io_error(format!("{}: {e}", source_path.display())))?;
+        let cache_path = cache_path(&out_dir, root, &source_path);
+        let code = if needs_transpile(&source_path) {
+            let spec = TranspileSpec::new(&source_path, &cache_path);
+            compiler.transpile(&spec, &source)?.code
+        } else {
+            source
+        };
+
+        let source_dir = source_path.parent().unwrap_or(root);
+        let mut imports = Vec::new();
+        for import in scan_imports(&code) {
+            if !(import.raw.starts_with("./") || import.raw.starts_with("../")) {
+                continue;
+            }
+            let target = resolve_relative(source_dir, &import.raw);
+            if let Some(target) = &target {
+                if (needs_transpile(target) || is_javascript(target)) && seen.insert(target.clone())
+                {
+                    queue.push_back(target.clone());
+                }
+            }
+            imports.push((import.raw, target));
+        }
+
+        let out_path = cache_path.with_extension(output_extension(&source_path, &code));
+        order.push(source_path.clone());
+        modules.insert(
+            source_path,
+            Module {
+                code,
+                out_path,
+                imports,
+            },
+        );
+    }
+
+    // Plain JavaScript only moves into the cache when it (transitively)
+    // imports a module that does
+    let mut cached: HashSet<&Path> = order
+        .iter()
+        .filter(|path| needs_transpile(path))
+        .map(PathBuf::as_path)
+        .collect();
+    loop {
+        let before = cached.len();
+        for path in &order {
+            let reaches_cache = modules[path]
+                .imports
+                .iter()
+                .any(|(_, target)| target.as_deref().is_some_and(|t| cached.contains(t)));
+            if reaches_cache {
+                cached.insert(path);
+            }
+        }
+        if cached.len() == before {
+            break;
+        }
+    }
+
+    let mut sources = Vec::new();
+    for path in &order {
+        if !cached.contains(path.as_path()) {
+            continue;
+        }
+        let module = &modules[path];
+        let source_dir = path.parent().unwrap_or(root);
+        let out_parent = module.out_path.parent().unwrap_or(&out_dir);
+        let rewrites: HashMap<String, String> = module
+            .imports
+            .iter()
+            .map(|(raw, target)| {
+                let new_target = match target {
+                    Some(target) if cached.contains(target.as_path()) => {
+                        modules[target].out_path.clone()
+                    }
+                    Some(target) => target.clone(),
+                    // Keep pointing at the source tree so Node reports the right path
+                    None => source_dir.join(raw),
+                };
+                (raw.clone(), relative_specifier(out_parent, &new_target))
+            })
+            .collect();
+
+        std::fs::create_dir_all(out_parent)
+            .and_then(|()| {
+                std::fs::write(
+                    &module.out_path,
+                    rewrite_specifiers(&module.code, &rewrites),
+                )
+            })
+            .map_err(|e| CompilerError::io_error(format!("{}: {e}", module.out_path.display())))?;
+        sources.push(path.clone());
+    }
+
+    Ok(TranspiledTree {
+        entry: modules[entry].out_path.clone(),
+        sources,
+    })
+}
+
+/// Cache path for `source`, mirroring its place under `root`. The extension
+/// is replaced by [`output_extension`] once the module's code is known.
+fn cache_path(out_dir: &Path, root: &Path, source: &Path) -> PathBuf {
+    let relative = source.strip_prefix(root).map_or_else(
+        |_| {
+            let mut external = PathBuf::from(EXTERNAL_DIR);
+            external.extend(source.components().filter_map(|c| match c {
+                Component::Normal(part) => Some(part),
+                _ => None,
+            }));
+            external
+        },
+        Path::to_path_buf,
+    );
+    out_dir.join(relative)
+}
+
+/// Whether `path` is JavaScript Node can load as is.
+fn is_javascript(path: &Path) -> bool {
+    path.extension()
+        .and_then(|ext| ext.to_str())
+        .is_some_and(|ext| matches!(ext.to_ascii_lowercase().as_str(), "js" | "mjs" | "cjs"))
+}
+
+/// Extension for the cached copy of `source`, so Node loads `code` in the
+/// module format it is written in.
+///
+/// `.mts`/`.mjs` and `.cts`/`.cjs` keep their format. Otherwise `import` or
+/// `export` statements make an ES module and `require`/`module.exports` a
+/// CommonJS one; code with neither follows the nearest `package.json`
+/// `"type"`, like Node.
+fn output_extension(source: &Path, code: &str) -> &'static str {
+    let ext = source.extension().and_then(|ext| ext.to_str());
+    match ext.map(str::to_ascii_lowercase).as_deref() {
+        Some("mts" | "mjs") => "mjs",
+        Some("cts" | "cjs") => "cjs",
+        _ if has_module_syntax(code) => "mjs",
+        _ if has_commonjs_syntax(code) => "cjs",
+        _ if is_module_package(source) => "mjs",
+        _ => "cjs",
+    }
+}
+
+/// Whether `code` has statements only an ES module may contain.
+fn has_module_syntax(code: &str) -> bool {
+    code.contains("import.meta")
+        || code.lines().map(str::trim_start).any(|line| {
+            ["import", "export"].iter().any(|keyword| {
+                line.strip_prefix(keyword)
+                    .is_some_and(|rest| rest.starts_with([' ', '{', '*', '"', '\'']))
+            })
+        })
+}
+
+/// Whether `code` uses CommonJS `require` or exports.
+fn has_commonjs_syntax(code: &str) -> bool {
+    code.contains("module.exports")
+        || code.contains("exports.")
+        || scan_imports(code)
+            .iter()
+            .any(|import| import.kind == "cjs_require")
+}
+
+/// Whether the nearest `package.json` above `source` has `"type": "module"`.
+fn is_module_package(source: &Path) -> bool {
+    source
+        .ancestors()
+        .skip(1)
+        .find_map(|dir| std::fs::read_to_string(dir.join("package.json")).ok())
+        .and_then(|json| serde_json::from_str::<serde_json::Value>(&json).ok())
+        .is_some_and(|pkg| pkg["type"] == "module")
+}
+
+/// Resolve a relative specifier the way TypeScript does: exact file, then
+/// `.js` written for a `.ts` source, then added extensions, then `index`.
+fn resolve_relative(dir: &Path, specifier: &str) -> Option<PathBuf> {
+    let base = normalize(&dir.join(specifier));
+    if base.is_file() {
+        return Some(base);
+    }
+
+    let swapped = match base.extension().and_then(|ext| ext.to_str()) {
+        Some("js") => &["ts", "tsx"][..],
+        Some("jsx") => &["tsx"][..],
+        Some("mjs") => &["mts"][..],
+        Some("cjs") => &["cts"][..],
+        _ => &[][..],
+    };
+    let with_ext = |path: &Path, ext: &str| {
+        let mut name = path.as_os_str().to_os_string();
+        name.push(".");
+        name.push(ext);
+        PathBuf::from(name)
+    };
+
+    swapped
+        .iter()
+        .map(|ext| base.with_extension(ext))
+        .chain(RESOLVE_EXTENSIONS.iter().map(|ext| with_ext(&base, ext)))
+        .chain(
+            RESOLVE_EXTENSIONS
+                .iter()
+                .map(|ext| base.join(format!("index.{ext}"))),
+        )
+        .find(|candidate| candidate.is_file())
+}
+
+/// Resolve `.` and `..` components without touching the filesystem.
+fn normalize(path: &Path) -> PathBuf {
+    let mut out = PathBuf::new();
+    for component in path.components() {
+        match component {
+            Component::CurDir => {}
+            Component::ParentDir => {
+                out.pop();
+            }
+            other => out.push(other),
+        }
+    }
+    out
+}
+
+/// Relative import specifier from `from_dir` to `to`, always starting with
+/// `./` or `../` and using `/` separators.
+fn relative_specifier(from_dir: &Path, to: &Path) -> String {
+    let from_dir = normalize(from_dir);
+    let from: Vec<Component> = from_dir.components().collect();
+    let to = normalize(to);
+    let to_components: Vec<Component> = to.components().collect();
+    let common = from
+        .iter()
+        .zip(&to_components)
+        .take_while(|(a, b)| a == b)
+        .count();
+    if common == 0 {
+        // Different roots (e.g. another drive): use the absolute path
+        return to.to_string_lossy().replace('\\', "/");
+    }
+
+    let mut parts: Vec<String> = vec!["..".to_string(); from.len() - common];
+    parts.extend(
+        to_components[common..]
+            .iter()
+            .map(|c| c.as_os_str().to_string_lossy().into_owned()),
+    );
+    let joined = parts.join("/");
+    if joined.starts_with("..") {
+        joined
+    } else {
+        format!("./{joined}")
+    }
+}
+
+/// Replace quoted occurrences of each specifier in `code`.
+fn rewrite_specifiers(code: &str, rewrites: &HashMap<String, String>) -> String {
+    let mut code = code.to_string();
+    for (from, to) in rewrites {
+        for quote in ['"', '\'', '`'] {
+            code = code.replace(
+                &format!("{quote}{from}{quote}"),
+                &format!("{quote}{to}{quote}"),
+            );
+        }
+    }
+    code
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::compiler::HowthParserBackend;
+    use std::fs;
+
+    #[test]
+    fn test_relative_specifier() {
+        assert_eq!(
+            relative_specifier(Path::new("/p/cache/src"), Path::new("/p/cache/src/a.mjs")),
+            "./a.mjs"
+        );
+        assert_eq!(
+            relative_specifier(Path::new("/p/cache/src"), Path::new("/p/src/data.json")),
+            "../../src/data.json"
+        );
+    }
+
+    #[test]
+    fn test_transpile_tree_follows_relative_imports() {
+        let dir = tempfile::tempdir().unwrap();
+        let root = dir.path();
+        fs::create_dir_all(root.join("src/lib")).unwrap();
+        fs::write(root.join("package.json"), "{}").unwrap();
+        fs::write(
+            root.join("src/main.ts"),
+            r#"import { add } from "./lib/math.js";
+import type { Options } from "./types";
+import data from "./data.json";
+import { helper } from "./helper.js";
+import express from "express";
+const opts: Options = { n: add(1, 2) };
+console.log(opts, data, helper, express);
+"#,
+        )
+        .unwrap();
+        fs::write(
+            root.join("src/lib/math.ts"),
+            "export const add = (a: number, b: number): number => a + b;\n",
+        )
+        .unwrap();
+        fs::write(
+            root.join("src/types.ts"),
+            "export interface Options { n: number }\n",
+        )
+        .unwrap();
+        fs::write(root.join("src/data.json"), "{}").unwrap();
+        fs::write(root.join("src/helper.js"), "export const helper = 1;\n").unwrap();
+
+        let tree =
+            transpile_tree(&HowthParserBackend::new(), root, &root.join("src/main.ts")).unwrap();
+
+        let out_dir = root.join(RUN_CACHE_DIR);
+        assert_eq!(tree.entry, out_dir.join("src/main.mjs"));
+        // Type-only imports are erased, so types.ts is never reached
+        assert_eq!(
+            tree.sources,
+            [root.join("src/main.ts"), root.join("src/lib/math.ts")]
+        );
+
+        let main = fs::read_to_string(&tree.entry).unwrap();
+        assert!(main.contains(r#""./lib/math.mjs""#), "{main}");
+        assert!(main.contains(r#""../../../../../src/data.json""#), "{main}");
+        assert!(main.contains(r#""../../../../../src/helper.js""#), "{main}");
+        assert!(main.contains(r#""express""#), "{main}");
+        let math = fs::read_to_string(out_dir.join("src/lib/math.mjs")).unwrap();
+        assert!(math.contains("a + b") && !math.contains(": number"));
+    }
+
+    #[test]
+    fn test_transpile_tree_commonjs_output() {
+        let dir = tempfile::tempdir().unwrap();
+        let root = dir.path();
+        fs::write(root.join("package.json"), r#"{"type": "module"}"#).unwrap();
+        fs::write(
+            root.join("main.ts"),
+            "const { greet } = require(\"./greet\");\nconsole.log(greet(\"x\"));\n",
+        )
+        .unwrap();
+        fs::write(
+            root.join("greet.ts"),
+            "module.exports = { greet: (name: string) => `hi ${name}` };\n",
+        )
+        .unwrap();
+        fs::write(root.join("esm.ts"), "export const n: number = 1;\n").unwrap();
+
+        let tree = transpile_tree(&HowthParserBackend::new(), root, &root.join("main.ts")).unwrap();
+
+        let out_dir = root.join(RUN_CACHE_DIR);
+        assert_eq!(tree.entry, out_dir.join("main.cjs"));
+        let main = fs::read_to_string(&tree.entry).unwrap();
+        assert!(main.contains(r#"require("./greet.cjs")"#), "{main}");
+        assert!(out_dir.join("greet.cjs").is_file());
+
+        assert_eq!(
+            output_extension(&root.join("esm.ts"), "export const n = 1;"),
+            "mjs"
+        );
+        assert_eq!(
+            output_extension(&root.join("script.ts"), "console.log(1);"),
+            "mjs"
+        );
+        fs::write(root.join("package.json"), "{}").unwrap();
+        assert_eq!(
+            output_extension(&root.join("script.ts"), "console.log(1);"),
+            "cjs"
+        );
+        assert_eq!(output_extension(&root.join("a.cts"), "export {};"), "cjs");
+    }
+
+    #[test]
+    fn test_transpile_tree_copies_javascript_leading_to_typescript() {
+        let dir = tempfile::tempdir().unwrap();
+        let root = dir.path();
+        fs::write(root.join("package.json"), "{}").unwrap();
+        fs::write(
+            root.join("main.ts"),
+            "import { value } from \"./bridge.js\";\nimport { plain } from \"./plain.js\";\nconsole.log(value, plain);\n",
+        )
+        .unwrap();
+        fs::write(
+            root.join("bridge.js"),
+            "export { value } from \"./value\";\n",
+        )
+        .unwrap();
+        fs::write(root.join("value.ts"), "export const value: number = 1;\n").unwrap();
+        fs::write(root.join("plain.js"), "export const plain = 2;\n").unwrap();
+
+        let tree = transpile_tree(&HowthParserBackend::new(), root, &root.join("main.ts")).unwrap();
+
+        let out_dir = root.join(RUN_CACHE_DIR);
+        assert_eq!(
+            tree.sources,
+            [
+                root.join("main.ts"),
+                root.join("bridge.js"),
+                root.join("value.ts")
+            ]
+        );
+        let main = fs::read_to_string(&tree.entry).unwrap();
+        assert!(main.contains(r#""./bridge.mjs""#), "{main}");
+        assert!(main.contains(r#""../../../../plain.js""#), "{main}");
+        let bridge = fs::read_to_string(out_dir.join("bridge.mjs")).unwrap();
+        assert!(bridge.contains(r#""./value.mjs""#), "{bridge}");
+        assert!(out_dir.join("value.mjs").is_file());
+    }
+
+    #[test]
+    fn test_package_root() {
+        let dir = tempfile::tempdir().unwrap();
+        fs::create_dir_all(dir.path().join("app/src")).unwrap();
+        fs::write(dir.path().join("app/package.json"), "{}").unwrap();
+        assert_eq!(
+            package_root(&dir.path().join("app/src/main.ts")),
+            dir.path().join("app")
+        );
+    }
+}
@@ -1,5 +1,10 @@
 use serde::{Deserialize, Serialize};
+use std::fmt;
 use std::path::PathBuf;
+use std::str::FromStr;
+
+/// Environment variable selecting the channel (`stable`, `nightly`, `dev`).
+pub const CHANNEL_ENV: &str = "HOWTH_CHANNEL";
 
 /// Runtime configuration for fastnode CLI.
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -18,6 +23,10 @@ pub struct Config {
 }
 
 /// Release channel for cache/data directory namespacing.
+///
+/// Each channel has its own cache, data directory, IPC endpoint and daemon,
+/// so channels can run side by side. Project state derived from the lockfile
+/// (`node_modules`) is shared and records the channel that installed it.
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, Default)]
 #[serde(rename_all = "lowercase")]
 pub enum Channel {
@@ -38,6 +47,27 @@ impl Channel {
     }
 }
 
+impl fmt::Display for Channel {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+impl FromStr for Channel {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_ascii_lowercase().as_str() {
+            "stable" => Ok(Self::Stable),
+            "nightly" => Ok(Self::Nightly),
+            "dev" => Ok(Self::Dev),
+            _ => Err(format!(
+                "unknown channel '{s}' (expected stable, nightly or dev)"
+            )),
+        }
+    }
+}
+
 impl Default for Config {
     fn default() -> Self {
         Self {
@@ -0,0 +1,86 @@
+//! Deterministic (reproducible) output.
+//!
+//! With `--deterministic`, or whenever `SOURCE_DATE_EPOCH` is set (see
+//! <https://reproducible-builds.org/specs/source-date-epoch/>), bundles,
+//! transpiled files and packed tarballs are byte-identical across machines
+//! and checkout locations:
+//! - timestamps written into outputs come from `SOURCE_DATE_EPOCH`
+//! - paths embedded in outputs (module comments, source map `sources`) are
+//!   relative instead of absolute
+//! - output order never depends on file discovery order
+
+use std::path::{Component, Path};
+
+/// Environment variable holding the timestamp for reproducible outputs, in
+/// seconds since the Unix epoch.
+pub const SOURCE_DATE_EPOCH_ENV: &str = "SOURCE_DATE_EPOCH";
+
+/// `SOURCE_DATE_EPOCH` in seconds, if set to a valid value.
+#[must_use]
+pub fn source_date_epoch() -> Option<u64> {
+    parse_epoch(&std::env::var(SOURCE_DATE_EPOCH_ENV).ok()?)
+}
+
+/// Whether deterministic mode is on: requested with `--deterministic`
+/// (`flag`) or implied by `SOURCE_DATE_EPOCH`.
+#[must_use]
+pub fn is_deterministic(flag: bool) -> bool {
+    flag || source_date_epoch().is_some()
+}
+
+fn parse_epoch(value: &str) -> Option<u64> {
+    value.trim().parse().ok()
+}
+
+/// `path` relative to `base`, `/`-separated, going through `..` when it is
+/// outside `base`. Both should be absolute (or both relative to the same
+/// directory).
+#[must_use]
+pub fn relative_path(base: &Path, path: &Path) -> String {
+    let base: Vec<Component> = base.components().collect();
+    let path: Vec<Component> = path.components().collect();
+    let common = base.iter().zip(&path).take_while(|(a, b)| a == b).count();
+
+    let mut parts: Vec<String> = vec!["..".to_string(); base.len() - common];
+    parts.extend(
+        path[common..]
+            .iter()
+            .map(|c| c.as_os_str().to_string_lossy().into_owned()),
+    );
+    if parts.is_empty() {
+        ".".to_string()
+    } else {
+        parts.join("/")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_epoch() {
+        assert_eq!(parse_epoch("1700000000"), Some(1_700_000_000));
+        assert_eq!(parse_epoch(" 0\n"), Some(0));
+        assert_eq!(parse_epoch("yesterday"), None);
+        assert_eq!(parse_epoch("-5"), None);
+    }
+
+    #[test]
+    fn test_relative_path() {
+        let base = Path::new("/work/app");
+        assert_eq!(
+            relative_path(base, Path::new("/work/app/src/index.ts")),
+            "src/index.ts"
+        );
+        assert_eq!(
+            relative_path(base, Path::new("/work/node_modules/lodash/index.js")),
+            "../node_modules/lodash/index.js"
+        );
+        assert_eq!(relative_path(base, Path::new("/work/app")), ".");
+        assert_eq!(
+            relative_path(Path::new("/work/app/dist"), Path::new("/work/app/src/a.ts")),
+            "../src/a.ts"
+        );
+    }
+}
@@ -11,6 +11,7 @@
 //!   resolve: { alias: { '@': './src' } },
 //!   define: { 'process.env.NODE_ENV': '"development"' },
 //!   base: '/',
+//!   ssr: { entry: './src/entry-server.tsx' },
 //! };
 //! ```
 
@@ -31,6 +32,8 @@ pub struct HowthConfig {
     pub define: HashMap<String, String>,
     /// Base public path.
     pub base: Option<String>,
+    /// Server-side rendering options.
+    pub ssr: SsrConfig,
     /// Whether the config file contains a `plugins` array (requires V8 runtime to evaluate).
     pub has_js_plugins: bool,
 }
@@ -75,6 +78,13 @@ pub struct ProxyConfig {
     pub secure: bool,
 }
 
+/// Server-side rendering configuration from config file.
+#[derive(Debug, Clone, Default)]
+pub struct SsrConfig {
+    /// SSR entry module exporting `render(url)`, relative to the root.
+    pub entry: Option<String>,
+}
+
 /// Resolve configuration from config file.
 #[derive(Debug, Clone, Default)]
 pub struct ResolveConfig {
@@ -143,9 +153,9 @@ pub fn load_config(
 
 /// Transpile a TypeScript config file to JavaScript using SWC.
 fn transpile_ts_config(source: &str, path: &Path) -> Result<String, String> {
-    use crate::compiler::{CompilerBackend, ModuleKind, SourceMapKind, SwcBackend, TranspileSpec};
+    use crate::compiler::{backend_from_env, ModuleKind, SourceMapKind, TranspileSpec};
 
-    let backend = SwcBackend::new();
+    let backend = backend_from_env();
     let input_name = path.display().to_string();
     let mut spec = TranspileSpec::new(&input_name, &input_name);
     spec.module = ModuleKind::ESM;
@@ -242,6 +252,15 @@ fn parse_config_object(source: &str) -> Result<HowthConfig, String> {
         if let Some(base) = obj.get("base").and_then(|v| v.as_str()) {
             config.base = Some(base.to_string());
         }
+
+        // ssr
+        if let Some(entry) = obj
+            .get("ssr")
+            .and_then(|ssr| ssr.get("entry"))
+            .and_then(|v| v.as_str())
+        {
+            config.ssr.entry = Some(entry.to_string());
+        }
     }
 
     Ok(config)
@@ -878,6 +897,17 @@ mod tests {
         assert_eq!(config.base.as_deref(), Some("/app/"));
     }
 
+    #[test]
+    fn test_parse_ssr_entry() {
+        let source = r"
+            export default {
+                ssr: { entry: './src/entry-server.tsx' },
+            };
+        ";
+        let config = parse_config_object(source).unwrap();
+        assert_eq!(config.ssr.entry.as_deref(), Some("./src/entry-server.tsx"));
+    }
+
     #[test]
     fn test_parse_config_with_comments() {
         let source = r"
@@ -1,14 +1,27 @@
-//! `.env` file loading for the dev server.
+//! `.env` file loading for the dev server and bundler.
 //!
 //! Vite-compatible: loads `.env`, `.env.local`, `.env.[mode]`, `.env.[mode].local`
 //! in order, with later files overriding earlier ones. System environment variables
 //! already set take precedence (are not overwritten).
+//!
+//! Variables with a [`CLIENT_ENV_PREFIXES`] prefix are exposed to client code
+//! as `import.meta.env.*`, next to the built-in `MODE`, `DEV`, `PROD`,
+//! `BASE_URL` and `SSR`.
 
 #![allow(clippy::implicit_hasher)]
 
 use std::collections::HashMap;
 use std::path::Path;
 
+/// Prefixes of variables exposed to client code.
+pub const CLIENT_ENV_PREFIXES: &[&str] = &["VITE_", "HOWTH_"];
+
+fn is_client_env_key(key: &str) -> bool {
+    CLIENT_ENV_PREFIXES
+        .iter()
+        .any(|prefix| key.starts_with(prefix))
+}
+
 /// Parse a `.env` file's contents into key-value pairs.
 ///
 /// Supports:
@@ -148,39 +161,59 @@ pub fn load_env_files(root: &Path, mode: &str) -> HashMap<String, String> {
     env
 }
 
+/// Load the variables exposed to client code for `mode`: prefixed variables
+/// from the `.env` files and the process environment (which wins).
+#[must_use]
+pub fn load_client_env(root: &Path, mode: &str) -> HashMap<String, String> {
+    let mut env = load_env_files(root, mode);
+    env.extend(std::env::vars().filter(|(key, _)| is_client_env_key(key)));
+    env.retain(|key, _| is_client_env_key(key));
+    env
+}
+
 /// Filter environment variables to those exposed to client code and return
 /// `import.meta.env.*` replacement mappings.
 ///
-/// Only variables prefixed with `VITE_` or `HOWTH_` are exposed.
+/// Only variables with a [`CLIENT_ENV_PREFIXES`] prefix are exposed.
 /// Also includes built-in replacements:
 /// - `import.meta.env.MODE` → `"development"` (or current mode)
 /// - `import.meta.env.DEV` → `true` / `false`
 /// - `import.meta.env.PROD` → `true` / `false`
 /// - `import.meta.env.BASE_URL` → `"/"`
+/// - `import.meta.env.SSR` → `false`
+/// - `import.meta.env` → an object literal with all of the above, for code
+///   that reads the object as a whole
+///
+/// Apply the mappings longest key first (as `ReplacePlugin` does), so the
+/// object literal only replaces references no specific key matched.
 #[must_use]
 pub fn client_env_replacements(
     env: &HashMap<String, String>,
     mode: &str,
 ) -> HashMap<String, String> {
-    let mut replacements = HashMap::new();
-
-    // Built-in replacements
     let is_dev = mode == "development";
-    replacements.insert("import.meta.env.MODE".to_string(), format!("\"{mode}\""));
-    replacements.insert("import.meta.env.DEV".to_string(), is_dev.to_string());
-    replacements.insert("import.meta.env.PROD".to_string(), (!is_dev).to_string());
-    replacements.insert("import.meta.env.BASE_URL".to_string(), "\"/\"".to_string());
+    let mut object = serde_json::Map::new();
+    object.insert("MODE".to_string(), mode.into());
+    object.insert("DEV".to_string(), is_dev.into());
+    object.insert("PROD".to_string(), (!is_dev).into());
+    object.insert("BASE_URL".to_string(), "/".into());
+    object.insert("SSR".to_string(), false.into());
 
     // User-defined env vars with allowed prefixes
     for (key, value) in env {
-        if key.starts_with("VITE_") || key.starts_with("HOWTH_") {
-            replacements.insert(
-                format!("import.meta.env.{key}"),
-                format!("\"{}\"", value.replace('\\', "\\\\").replace('"', "\\\"")),
-            );
+        if is_client_env_key(key) {
+            object.insert(key.clone(), value.as_str().into());
         }
     }
 
+    let mut replacements: HashMap<String, String> = object
+        .iter()
+        .map(|(key, value)| (format!("import.meta.env.{key}"), value.to_string()))
+        .collect();
+    replacements.insert(
+        "import.meta.env".to_string(),
+        serde_json::Value::Object(object).to_string(),
+    );
     replacements
 }
 
@@ -327,6 +360,39 @@ mod tests {
         );
     }
 
+    #[test]
+    fn test_client_env_replacements_object() {
+        let mut env = HashMap::new();
+        env.insert("VITE_A".to_string(), "1".to_string());
+        env.insert("SECRET".to_string(), "x".to_string());
+
+        let replacements = client_env_replacements(&env, "staging");
+        assert_eq!(
+            replacements.get("import.meta.env").unwrap(),
+            r#"{"BASE_URL":"/","DEV":false,"MODE":"staging","PROD":true,"SSR":false,"VITE_A":"1"}"#
+        );
+        assert_eq!(replacements.get("import.meta.env.SSR").unwrap(), "false");
+    }
+
+    #[test]
+    fn test_load_client_env_filters_and_prefers_process_env() {
+        let dir = tempfile::tempdir().unwrap();
+        let root = dir.path();
+        std::fs::write(
+            root.join(".env"),
+            "VITE_FROM_FILE=file\nDB_PASSWORD=secret\nHOWTH_TEST_CLIENT_ENV_PROCESS=file\n",
+        )
+        .unwrap();
+        std::env::set_var("HOWTH_TEST_CLIENT_ENV_PROCESS", "process");
+
+        let env = load_client_env(root, "production");
+        std::env::remove_var("HOWTH_TEST_CLIENT_ENV_PROCESS");
+
+        assert_eq!(env.get("VITE_FROM_FILE").unwrap(), "file");
+        assert_eq!(env.get("HOWTH_TEST_CLIENT_ENV_PROCESS").unwrap(), "process");
+        assert!(!env.contains_key("DB_PASSWORD"));
+    }
+
     #[test]
     fn test_load_env_files_merges_in_order() {
         let dir = tempfile::tempdir().unwrap();
@@ -338,7 +338,7 @@ function handleMessage(msg) {
 
     case 'error':
       console.error('[howth] build error:', msg.message);
-      showErrorOverlay(msg.message);
+      showErrorOverlay(msg.message, msg.diagnostics);
       break;
 
     case 'custom':
@@ -393,7 +393,12 @@ async function handleUpdate(update) {
   }
 }
 
-function showErrorOverlay(message) {
+function formatDiagnostic(d) {
+  const loc = d.file ? `${d.file}:${d.line}:${d.column} - ` : '';
+  return `${loc}${d.severity} ${d.code}: ${d.message}`;
+}
+
+function showErrorOverlay(message, diagnostics) {
   let overlay = document.getElementById('__howth_error_overlay');
   if (!overlay) {
     overlay = document.createElement('div');
@@ -406,7 +411,11 @@ function showErrorOverlay(message) {
     `;
     document.body.appendChild(overlay);
   }
-  overlay.textContent = 'Build Error:\n\n' + message;
+  let text = 'Build Error:\n\n' + message;
+  if (diagnostics && diagnostics.length) {
+    text += '\n\n' + diagnostics.map(formatDiagnostic).join('\n\n');
+  }
+  overlay.textContent = text;
   overlay.style.display = 'block';
 }
 
@@ -464,6 +473,7 @@ export function createHotContext(ownerPath) {
 
     invalidate() {
       // Tell the server this module can't self-update
+      if (!ws) return;
       ws.send(JSON.stringify({ type: 'invalidate', path: ownerPath }));
       location.reload();
     },
@@ -476,15 +486,16 @@ export function createHotContext(ownerPath) {
     },
 
     send(event, data) {
-      ws.send(JSON.stringify({ type: 'custom', event, data }));
+      if (ws) ws.send(JSON.stringify({ type: 'custom', event, data }));
     },
   };
 
   return hot;
 }
 
-// Initialize
-setupWebSocket();
+// Initialize. Module workers load this too, but have no page to update:
+// edits to worker code reload the page that started them.
+if (typeof document !== 'undefined') setupWebSocket();
 ";
 
 #[cfg(test)]
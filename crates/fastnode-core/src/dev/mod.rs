@@ -9,11 +9,15 @@ pub mod env;
 pub mod hmr;
 pub mod prebundle;
 pub mod rewrite;
+pub mod ssr;
 pub mod transform;
 
 pub use config::{find_config_file, load_config, load_tsconfig_paths, HowthConfig};
-pub use env::{client_env_replacements, load_env_files};
+pub use env::{client_env_replacements, load_client_env, load_env_files, CLIENT_ENV_PREFIXES};
 pub use hmr::{HmrEngine, HmrModuleGraph, HmrModuleNode};
 pub use prebundle::PreBundler;
-pub use rewrite::{extract_import_urls, is_self_accepting_module, ImportRewriter};
+pub use rewrite::{
+    extract_import_urls, is_self_accepting_module, ImportRewriter, CLASSIC_WORKER_QUERY,
+};
+pub use ssr::{is_page_request, stitch_html, SsrModuleGraph, SsrRender};
 pub use transform::ModuleTransformer;
@@ -6,10 +6,14 @@
 //!
 //! Pre-bundled deps are served at `/@modules/{pkg}` URLs.
 
-use crate::bundler::{BundleFormat, BundleOptions, Bundler};
+use crate::build::toolchain_fingerprint;
+use crate::bundler::{BundleFormat, BundleOptions, Bundler, Platform};
 use std::collections::{HashMap, HashSet};
 use std::path::{Path, PathBuf};
 
+/// File in the deps dir recording the toolchain that produced it.
+const METADATA_FILE: &str = "_toolchain";
+
 /// Pre-bundled dependency.
 #[derive(Debug, Clone)]
 pub struct PreBundledDep {
@@ -139,17 +143,26 @@ impl PreBundler {
             return Ok(());
         }
 
+        // Deps bundled by a different toolchain may be stale
+        let metadata_path = self.deps_dir.join(METADATA_FILE);
+        let toolchain = toolchain_fingerprint();
+        if std::fs::read_to_string(&metadata_path).is_ok_and(|cached| cached.trim() != toolchain) {
+            let _ = std::fs::remove_dir_all(&self.deps_dir);
+        }
+
         // Create output directory
         std::fs::create_dir_all(&self.deps_dir).map_err(|e| PreBundleError {
             message: format!("Failed to create deps dir: {e}"),
             package: None,
         })?;
+        let _ = std::fs::write(&metadata_path, toolchain);
 
         let bundler = Bundler::with_cwd(&self.root);
         let options = BundleOptions {
             format: BundleFormat::Esm,
             treeshake: false, // Don't treeshake deps (we need all exports)
             minify: false,    // No minification in dev
+            platform: Platform::Browser,
             ..Default::default()
         };
 
@@ -348,6 +361,25 @@ const lazy = import('lazy-module');
         assert!(!imports.contains("node:path"));
     }
 
+    #[test]
+    fn test_bundle_deps_discards_other_toolchain_output() {
+        let dir = tempfile::tempdir().unwrap();
+        let deps_dir = dir.path().join(".howth/deps");
+        std::fs::create_dir_all(&deps_dir).unwrap();
+        std::fs::write(deps_dir.join(METADATA_FILE), "older-toolchain").unwrap();
+        std::fs::write(deps_dir.join("react.js"), "stale").unwrap();
+
+        let mut prebundler = PreBundler::new(dir.path().to_path_buf());
+        let packages = HashSet::from(["missing-pkg".to_string()]);
+        prebundler.bundle_deps(&packages).unwrap();
+
+        assert!(!deps_dir.join("react.js").exists());
+        assert_eq!(
+            std::fs::read_to_string(deps_dir.join(METADATA_FILE)).unwrap(),
+            toolchain_fingerprint()
+        );
+    }
+
     #[test]
     fn test_package_name_from_specifier() {
         assert_eq!(package_name_from_specifier("react"), "react");
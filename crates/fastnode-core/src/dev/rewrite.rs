@@ -4,13 +4,20 @@
 //! - Bare specifiers (`react`) → `/@modules/react`
 //! - Relative imports (`./App`) → `/src/App.tsx` (resolved absolute from project root)
 //! - CSS imports (`./style.css`) → `/@style/src/style.css` (CSS injection module)
+//! - Asset URLs (`new URL('./logo.png', import.meta.url)`) → `new URL("/src/logo.png", import.meta.url)`
+//! - Classic worker URLs (`new Worker(new URL('./w.ts', import.meta.url))`) →
+//!   `new URL("/src/w.ts?worker_file&type=classic", import.meta.url)`, served as a bundled script
 
 #![allow(clippy::case_sensitive_file_extension_comparisons)]
 #![allow(clippy::if_same_then_else)]
 
 use crate::bundler::PluginContainer;
+use crate::compiler::asset_url::{find_asset_urls, rewrite_asset_urls, AssetUrlKind};
 use std::path::{Path, PathBuf};
 
+/// Query marking a classic worker script, served bundled as an IIFE.
+pub const CLASSIC_WORKER_QUERY: &str = "worker_file&type=classic";
+
 /// Import rewriter for dev server module serving.
 pub struct ImportRewriter {
     /// Project root directory.
@@ -31,8 +38,9 @@ impl ImportRewriter {
     /// through to bare specifier handling.
     #[must_use]
     pub fn rewrite(&self, code: &str, module_path: &Path, plugins: &PluginContainer) -> String {
-        let mut result = String::with_capacity(code.len());
         let module_dir = module_path.parent().unwrap_or(Path::new("/"));
+        let code = &self.rewrite_asset_urls(code, module_dir);
+        let mut result = String::with_capacity(code.len());
 
         for line in code.lines() {
             let trimmed = line.trim();
@@ -55,6 +63,30 @@ impl ImportRewriter {
         result
     }
 
+    /// Point `new URL('./file', import.meta.url)` at the file's root-relative
+    /// URL, so it doesn't depend on the URL the module itself is served from.
+    ///
+    /// Module workers are served like any other module. Classic workers
+    /// can't `import`, so their URL asks for the bundled script instead.
+    fn rewrite_asset_urls(&self, code: &str, module_dir: &Path) -> String {
+        let urls = find_asset_urls(code);
+        if urls.is_empty() {
+            return code.to_string();
+        }
+        rewrite_asset_urls(code, &urls, "import.meta.url", |url| {
+            let resolved = self.resolve_to_root_path(&url.specifier, module_dir);
+            if !resolved.starts_with('/') {
+                return None;
+            }
+            Some(match url.kind {
+                AssetUrlKind::Worker { module: false } => {
+                    format!("{resolved}?{CLASSIC_WORKER_QUERY}")
+                }
+                _ => resolved,
+            })
+        })
+    }
+
     /// Rewrite a single static import/export line.
     fn rewrite_import_line(
         &self,
@@ -196,8 +228,9 @@ impl ImportRewriter {
                 // Try common extensions
                 for ext in &[".ts", ".tsx", ".js", ".jsx", ".mjs"] {
                     let with_ext = format!("{}{}", absolute.display(), ext);
-                    if Path::new(&with_ext).exists() {
-                        if let Ok(rel) = Path::new(&with_ext).strip_prefix(&self.root) {
+                    // Canonicalize to drop the `./` segments the join kept
+                    if let Ok(with_ext) = dunce::canonicalize(&with_ext) {
+                        if let Ok(rel) = with_ext.strip_prefix(&self.root) {
                             return format!("/{}", rel.display());
                         }
                     }
@@ -530,6 +563,44 @@ const lazy = import('/src/Lazy.tsx');";
     // Asset import tests
     // ========================================================================
 
+    #[test]
+    fn test_rewrite_asset_url() {
+        let dir = tempfile::tempdir().unwrap();
+        let root = dunce::canonicalize(dir.path()).unwrap();
+        std::fs::create_dir_all(root.join("src/assets")).unwrap();
+        std::fs::write(root.join("src/assets/logo.png"), b"png").unwrap();
+
+        let rewriter = ImportRewriter::new(root.clone());
+        let code = "const logo = new URL('./assets/logo.png', import.meta.url).href;";
+        let result = rewriter.rewrite(code, &root.join("src/main.js"), &empty_plugins());
+        assert_eq!(
+            result,
+            "const logo = new URL(\"/src/assets/logo.png\", import.meta.url).href;"
+        );
+    }
+
+    #[test]
+    fn test_rewrite_worker_url() {
+        let dir = tempfile::tempdir().unwrap();
+        let root = dunce::canonicalize(dir.path()).unwrap();
+        std::fs::create_dir_all(root.join("src")).unwrap();
+        std::fs::write(root.join("src/worker.ts"), "self.onmessage = () => {};").unwrap();
+
+        let rewriter = ImportRewriter::new(root.clone());
+        let main = root.join("src/main.js");
+        let code = "new Worker(new URL('./worker', import.meta.url), { type: 'module' });";
+        assert_eq!(
+            rewriter.rewrite(code, &main, &empty_plugins()),
+            "new Worker(new URL(\"/src/worker.ts\", import.meta.url), { type: 'module' });"
+        );
+
+        let code = "new Worker(new URL('./worker.ts', import.meta.url));";
+        assert_eq!(
+            rewriter.rewrite(code, &main, &empty_plugins()),
+            "new Worker(new URL(\"/src/worker.ts?worker_file&type=classic\", import.meta.url));"
+        );
+    }
+
     /// 1: Asset imports get ?import appended.
     #[test]
     fn test_rewrite_asset_import_png() {
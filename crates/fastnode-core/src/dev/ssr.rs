@@ -0,0 +1,288 @@
+//! Server-side rendering support for `howth dev`.
+//!
+//! The dev server loads an SSR entry (e.g. `src/entry-server.tsx`) in the
+//! native runtime and calls its exported `render(url)` for page requests.
+//! `render` may return an HTML string or `{ html, head, status }`, sync or
+//! async. The result is stitched into `index.html`:
+//!
+//! ```html
+//! <head><!--ssr-head--></head>
+//! <body><div id="root"><!--ssr-outlet--></div></body>
+//! ```
+//!
+//! Vite's `<!--app-head-->` / `<!--app-html-->` markers work too. Without a
+//! marker, the HTML goes inside `<div id="root">` (or `id="app"`).
+//!
+//! Server modules stay cached in the runtime between requests.
+//! [`SsrModuleGraph`] records which files the entry imports, so a file change
+//! only invalidates the cache (before the next request) when it can affect
+//! rendering.
+
+use super::rewrite::extract_import_urls;
+use serde::Deserialize;
+use std::collections::HashSet;
+use std::path::{Path, PathBuf};
+
+/// Markers replaced with the rendered app HTML.
+pub const SSR_OUTLET_MARKERS: &[&str] = &["<!--ssr-outlet-->", "<!--app-html-->"];
+
+/// Markers replaced with the rendered head tags.
+pub const SSR_HEAD_MARKERS: &[&str] = &["<!--ssr-head-->", "<!--app-head-->"];
+
+/// Mount points used when the template has no outlet marker.
+const MOUNT_POINTS: &[&str] = &[r#"<div id="root">"#, r#"<div id="app">"#];
+
+/// Extensions tried when resolving extensionless relative imports.
+const MODULE_EXTENSIONS: &[&str] = &[".ts", ".tsx", ".js", ".jsx", ".mjs", ".cjs"];
+
+/// Output of the SSR entry's `render(url)`.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Deserialize)]
+pub struct SsrRender {
+    /// Rendered app HTML.
+    pub html: String,
+    /// Extra tags for `<head>`.
+    #[serde(default)]
+    pub head: String,
+    /// HTTP status (defaults to 200).
+    #[serde(default)]
+    pub status: Option<u16>,
+}
+
+impl SsrRender {
+    /// Parse the JSON-encoded return value of `render(url)`.
+    ///
+    /// # Errors
+    /// Returns an error if the value is neither a string nor an object with
+    /// an `html` string.
+    pub fn from_json(json: &str) -> Result<Self, String> {
+        let value: serde_json::Value =
+            serde_json::from_str(json).map_err(|e| format!("invalid render result: {e}"))?;
+        match value {
+            serde_json::Value::String(html) => Ok(Self {
+                html,
+                ..Self::default()
+            }),
+            value @ serde_json::Value::Object(_) => serde_json::from_value(value)
+                .map_err(|e| format!("render() must return a string or {{ html, head }}: {e}")),
+            _ => Err("render() must return a string or { html, head }".to_string()),
+        }
+    }
+}
+
+/// Insert rendered output into an HTML template.
+#[must_use]
+pub fn stitch_html(template: &str, render: &SsrRender) -> String {
+    let mut html = template.to_string();
+
+    match SSR_HEAD_MARKERS.iter().find(|m| html.contains(*m)) {
+        Some(marker) => html = html.replacen(marker, &render.head, 1),
+        None if !render.head.is_empty() => {
+            if let Some(pos) = html.find("</head>") {
+                html.insert_str(pos, &render.head);
+            }
+        }
+        None => {}
+    }
+
+    if let Some(marker) = SSR_OUTLET_MARKERS.iter().find(|m| html.contains(*m)) {
+        return html.replacen(marker, &render.html, 1);
+    }
+    for mount in MOUNT_POINTS {
+        if let Some(pos) = html.find(mount) {
+            html.insert_str(pos + mount.len(), &render.html);
+            return html;
+        }
+    }
+    match html.find("<body>") {
+        Some(pos) => html.insert_str(pos + "<body>".len(), &render.html),
+        None => html.push_str(&render.html),
+    }
+    html
+}
+
+/// Whether a request path is a page navigation (rendered by SSR) rather than
+/// a module or asset request.
+#[must_use]
+pub fn is_page_request(url_path: &str) -> bool {
+    let last = url_path.rsplit('/').next().unwrap_or("");
+    !url_path.starts_with("/@") && !last.contains('.')
+}
+
+/// Local modules imported (transitively) by the SSR entry.
+#[derive(Debug, Clone, Default)]
+pub struct SsrModuleGraph {
+    entry: PathBuf,
+    modules: HashSet<PathBuf>,
+    stale: bool,
+}
+
+impl SsrModuleGraph {
+    /// Scan the import graph of `entry`.
+    #[must_use]
+    pub fn new(entry: &Path) -> Self {
+        let mut graph = Self {
+            entry: entry.to_path_buf(),
+            ..Self::default()
+        };
+        graph.rescan();
+        graph
+    }
+
+    /// Files the entry depends on, including itself.
+    #[must_use]
+    pub fn modules(&self) -> &HashSet<PathBuf> {
+        &self.modules
+    }
+
+    /// Record changed files. Returns `true` if any of them is part of the
+    /// graph, in which case the server module cache must be reloaded.
+    pub fn invalidate(&mut self, changed: &[PathBuf]) -> bool {
+        let affected = changed.iter().any(|path| self.modules.contains(path));
+        self.stale |= affected;
+        affected
+    }
+
+    /// Whether the server modules must be reloaded before the next render.
+    #[must_use]
+    pub fn is_stale(&self) -> bool {
+        self.stale
+    }
+
+    /// Mark the server modules as freshly loaded, picking up new imports.
+    pub fn mark_loaded(&mut self) {
+        self.rescan();
+        self.stale = false;
+    }
+
+    fn rescan(&mut self) {
+        self.modules.clear();
+        let mut queue = vec![self.entry.clone()];
+        while let Some(path) = queue.pop() {
+            if !self.modules.insert(path.clone()) {
+                continue;
+            }
+            let Ok(source) = std::fs::read_to_string(&path) else {
+                continue;
+            };
+            let dir = path.parent().unwrap_or(Path::new(""));
+            for specifier in extract_import_urls(&source) {
+                if specifier.starts_with("./") || specifier.starts_with("../") {
+                    if let Some(resolved) = resolve_relative(dir, &specifier) {
+                        queue.push(resolved);
+                    }
+                }
+            }
+        }
+    }
+}
+
+/// Resolve a relative import to an existing file.
+fn resolve_relative(dir: &Path, specifier: &str) -> Option<PathBuf> {
+    let base = dir.join(specifier);
+    if base.is_file() {
+        return Some(base);
+    }
+    let with_ext = |path: &Path, ext: &str| PathBuf::from(format!("{}{ext}", path.display()));
+    MODULE_EXTENSIONS
+        .iter()
+        .map(|ext| with_ext(&base, ext))
+        .chain(
+            MODULE_EXTENSIONS
+                .iter()
+                .map(|ext| with_ext(&base.join("index"), ext)),
+        )
+        .find(|path| path.is_file())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_from_json() {
+        assert_eq!(
+            SsrRender::from_json(r#""<p>hi</p>""#).unwrap().html,
+            "<p>hi</p>"
+        );
+        let render =
+            SsrRender::from_json(r#"{"html":"<p/>","head":"<title>x</title>","status":404}"#)
+                .unwrap();
+        assert_eq!(render.head, "<title>x</title>");
+        assert_eq!(render.status, Some(404));
+        assert!(SsrRender::from_json("42").is_err());
+        assert!(SsrRender::from_json(r#"{"head":""}"#).is_err());
+    }
+
+    #[test]
+    fn test_stitch_html() {
+        let render = SsrRender {
+            html: "<h1>Hi</h1>".to_string(),
+            head: "<title>Hi</title>".to_string(),
+            status: None,
+        };
+        assert_eq!(
+            stitch_html(
+                "<head><!--ssr-head--></head><body><div id=\"root\"><!--ssr-outlet--></div></body>",
+                &render
+            ),
+            "<head><title>Hi</title></head><body><div id=\"root\"><h1>Hi</h1></div></body>"
+        );
+        assert_eq!(
+            stitch_html("<head></head><body><div id=\"app\"></div></body>", &render),
+            "<head><title>Hi</title></head><body><div id=\"app\"><h1>Hi</h1></div></body>"
+        );
+        assert_eq!(
+            stitch_html("<body></body>", &render),
+            "<body><h1>Hi</h1></body>"
+        );
+    }
+
+    #[test]
+    fn test_is_page_request() {
+        assert!(is_page_request("/"));
+        assert!(is_page_request("/users/123"));
+        assert!(!is_page_request("/src/App.tsx"));
+        assert!(!is_page_request("/@modules/react"));
+        assert!(!is_page_request("/favicon.ico"));
+    }
+
+    #[test]
+    fn test_module_graph_invalidation() {
+        let dir = tempfile::tempdir().unwrap();
+        let root = dir.path();
+        std::fs::create_dir_all(root.join("src/components")).unwrap();
+        std::fs::write(
+            root.join("src/entry-server.tsx"),
+            "import { App } from './App';\nimport React from 'react';\nexport function render() {}\n",
+        )
+        .unwrap();
+        std::fs::write(
+            root.join("src/App.tsx"),
+            "import { Button } from './components';\nexport const App = () => null;\n",
+        )
+        .unwrap();
+        std::fs::write(root.join("src/components/index.ts"), "export {};\n").unwrap();
+        std::fs::write(root.join("src/client.tsx"), "export {};\n").unwrap();
+
+        let mut graph = SsrModuleGraph::new(&root.join("src/entry-server.tsx"));
+        assert_eq!(graph.modules().len(), 3);
+        assert!(graph
+            .modules()
+            .contains(&root.join("src/components/index.ts")));
+
+        assert!(!graph.invalidate(&[root.join("src/client.tsx")]));
+        assert!(!graph.is_stale());
+        assert!(graph.invalidate(&[root.join("src/App.tsx")]));
+        assert!(graph.is_stale());
+
+        // New imports are picked up on reload
+        std::fs::write(
+            root.join("src/App.tsx"),
+            "import './client';\nexport const App = () => null;\n",
+        )
+        .unwrap();
+        graph.mark_loaded();
+        assert!(!graph.is_stale());
+        assert!(graph.modules().contains(&root.join("src/client.tsx")));
+    }
+}
@@ -6,11 +6,13 @@
 #![allow(clippy::unused_self)]
 #![allow(clippy::format_push_string)]
 
-use crate::bundler::{LoadResult, PluginContainer, ResolveIdResult};
+use crate::bundler::{virtual_css_id, LoadResult, PluginContainer, ResolveIdResult};
+use crate::compiler::{backend_from_env, CompilerBackend};
 use crate::dev::rewrite::ImportRewriter;
+use crate::sourcemap::{compose, SourceMap};
 use std::collections::HashMap;
 use std::path::{Path, PathBuf};
-use std::sync::RwLock;
+use std::sync::{Arc, RwLock};
 
 /// Cached transform result for a module.
 #[derive(Debug, Clone)]
@@ -35,6 +37,8 @@ pub struct ModuleTransformer {
     rewriter: ImportRewriter,
     /// Module cache: `file_path` → `TransformedModule`.
     cache: RwLock<HashMap<String, TransformedModule>>,
+    /// Compiler for TypeScript/JSX modules.
+    compiler: Arc<dyn CompilerBackend>,
 }
 
 impl ModuleTransformer {
@@ -46,9 +50,18 @@ impl ModuleTransformer {
             root,
             rewriter,
             cache: RwLock::new(HashMap::new()),
+            compiler: backend_from_env(),
         }
     }
 
+    /// Transpile with `compiler` instead of the default backend, e.g. one
+    /// that goes through the daemon's shared transform cache.
+    #[must_use]
+    pub fn with_compiler(mut self, compiler: Arc<dyn CompilerBackend>) -> Self {
+        self.compiler = compiler;
+        self
+    }
+
     /// Transform a module for serving.
     ///
     /// This runs the full pipeline: resolve → load → transpile → transform → rewrite.
@@ -73,16 +86,22 @@ impl ModuleTransformer {
         // Determine content type and whether to transpile
         let ext = file_path.extension().and_then(|e| e.to_str()).unwrap_or("");
 
+        // Source map from the file to the served code, for modules whose
+        // code was changed. Import rewriting keeps lines where they are.
+        let mut map = None;
         let (code, content_type) = match ext {
             "ts" | "tsx" | "jsx" | "mts" | "cts" => {
-                let transpiled = self.transpile(&source, &file_path)?;
-                let transformed =
+                let (transpiled, transpile_map) = self.transpile(&source, &file_path)?;
+                let (transformed, plugin_map) =
                     self.apply_plugin_transforms(&transpiled, &file_path_str, plugins)?;
+                map = chain_map(transpile_map, &transpiled, &transformed, plugin_map);
                 let rewritten = self.rewriter.rewrite(&transformed, &file_path, plugins);
                 (rewritten, "application/javascript")
             }
             "js" | "mjs" | "cjs" => {
-                let transformed = self.apply_plugin_transforms(&source, &file_path_str, plugins)?;
+                let (transformed, plugin_map) =
+                    self.apply_plugin_transforms(&source, &file_path_str, plugins)?;
+                map = plugin_map;
                 let rewritten = self.rewriter.rewrite(&transformed, &file_path, plugins);
                 (rewritten, "application/javascript")
             }
@@ -154,9 +173,23 @@ impl ModuleTransformer {
 
                 (js_module, "application/javascript")
             }
+            ext if plugins.handles_extension(ext) => {
+                // Plugin-compiled module (e.g. `.vue`, `.svelte`): the plugin
+                // transform produces JavaScript, extracted CSS is imported
+                // from its virtual stylesheet
+                let (mut compiled, plugin_map) =
+                    self.apply_plugin_transforms(&source, &file_path_str, plugins)?;
+                map = plugin_map;
+                if plugins.emitted_css(&file_path_str).is_some() {
+                    let css_url = virtual_css_id(url_path.split('?').next().unwrap_or(url_path));
+                    compiled.push_str(&format!("\nimport \"/@style{css_url}\";\n"));
+                }
+                let rewritten = self.rewriter.rewrite(&compiled, &file_path, plugins);
+                (rewritten, "application/javascript")
+            }
             "json" => {
                 let json_module = json_to_esm(&source);
-                let transformed =
+                let (transformed, _) =
                     self.apply_plugin_transforms(&json_module, &file_path_str, plugins)?;
                 (transformed, "application/javascript")
             }
@@ -173,6 +206,15 @@ impl ModuleTransformer {
             .unwrap_or_default()
             .as_millis() as u64;
 
+        let code = match map {
+            Some(map) => format!(
+                "{}\n//# sourceMappingURL={}\n",
+                code.trim_end_matches('\n'),
+                map.to_data_url()
+            ),
+            None => code,
+        };
+
         let module = TransformedModule {
             code,
             content_type,
@@ -196,9 +238,11 @@ impl ModuleTransformer {
         let mut cache = self.cache.write().unwrap();
         let mut invalidated = Vec::new();
 
-        // Remove all cache entries that came from this file
+        // Remove all cache entries that came from this file, including the
+        // virtual stylesheet of a plugin-compiled module
+        let css_path = virtual_css_id(file_path);
         cache.retain(|url_path, module| {
-            if module.file_path == file_path {
+            if module.file_path == file_path || module.file_path == css_path {
                 invalidated.push(url_path.clone());
                 false
             } else {
@@ -250,6 +294,13 @@ impl ModuleTransformer {
             return Ok(file_path);
         }
 
+        // Virtual stylesheet extracted from a plugin-compiled module
+        if let Some(module_path) = file_path.to_str().and_then(|p| p.strip_suffix(".css")) {
+            if plugins.emitted_css(module_path).is_some() {
+                return Ok(file_path);
+            }
+        }
+
         // Try with extensions
         for ext in &[".ts", ".tsx", ".js", ".jsx", ".mjs", ".cjs"] {
             let with_ext = PathBuf::from(format!("{}{}", file_path.display(), ext));
@@ -290,13 +341,15 @@ impl ModuleTransformer {
         })
     }
 
-    /// Transpile TypeScript/JSX to JavaScript using SWC.
-    fn transpile(&self, source: &str, file_path: &Path) -> Result<String, ModuleTransformError> {
-        use crate::compiler::{
-            CompilerBackend, JsxRuntime, ModuleKind, SourceMapKind, SwcBackend, TranspileSpec,
-        };
+    /// Transpile TypeScript/JSX to JavaScript, with its source map when the
+    /// backend produces one.
+    fn transpile(
+        &self,
+        source: &str,
+        file_path: &Path,
+    ) -> Result<(String, Option<SourceMap>), ModuleTransformError> {
+        use crate::compiler::{JsxRuntime, ModuleKind, SourceMapKind, TranspileSpec};
 
-        let backend = SwcBackend::new();
         let ext = file_path
             .extension()
             .and_then(|e| e.to_str())
@@ -305,32 +358,39 @@ impl ModuleTransformer {
         let input_name = file_path.display().to_string();
         let mut spec = TranspileSpec::new(&input_name, &input_name);
         spec.module = ModuleKind::ESM;
-        spec.sourcemaps = SourceMapKind::None;
+        spec.sourcemaps = SourceMapKind::External;
 
-        // Enable JSX for .tsx and .jsx files
+        // Enable JSX for .tsx and .jsx files, registering components for HMR
         if ext == "tsx" || ext == "jsx" {
             spec.jsx_runtime = JsxRuntime::Automatic;
+            spec.react_refresh = true;
         }
 
-        let output = backend
+        let output = self
+            .compiler
             .transpile(&spec, source)
             .map_err(|e| ModuleTransformError {
                 message: format!("Transpile error: {e}"),
                 file: Some(input_name),
             })?;
 
-        Ok(output.code)
+        let map = output
+            .source_map
+            .as_deref()
+            .and_then(|map| SourceMap::parse(map).ok());
+        Ok((output.code, map))
     }
 
-    /// Apply plugin transform hooks.
+    /// Apply plugin transform hooks, returning the composed map of the
+    /// plugins that changed the code.
     fn apply_plugin_transforms(
         &self,
         code: &str,
         id: &str,
         plugins: &PluginContainer,
-    ) -> Result<String, ModuleTransformError> {
+    ) -> Result<(String, Option<SourceMap>), ModuleTransformError> {
         plugins
-            .transform(code, id)
+            .transform_with_map(code, id)
             .map_err(|e| ModuleTransformError {
                 message: format!("Plugin transform error: {e}"),
                 file: Some(id.to_string()),
@@ -338,6 +398,21 @@ impl ModuleTransformer {
     }
 }
 
+/// Chain the map of a transform from `input` to `output` onto `input_map`.
+/// Unchanged code keeps `input_map`; changed code without a map of its own
+/// can't be traced.
+fn chain_map(
+    input_map: Option<SourceMap>,
+    input: &str,
+    output: &str,
+    map: Option<SourceMap>,
+) -> Option<SourceMap> {
+    if input == output {
+        return input_map;
+    }
+    compose(&[&input_map?, &map?])
+}
+
 /// Create a CSS-as-JS module that injects a <style> tag.
 fn create_css_module(css: &str) -> String {
     let escaped = css
@@ -434,6 +509,99 @@ impl std::error::Error for ModuleTransformError {}
 mod tests {
     use super::*;
 
+    #[test]
+    fn test_plugin_extension_module_with_virtual_css() {
+        use crate::bundler::plugins::SfcPlugin;
+
+        let dir = tempfile::tempdir().unwrap();
+        let root = dir.path().to_path_buf();
+        std::fs::create_dir_all(root.join("src")).unwrap();
+        std::fs::write(
+            root.join("src/Hello.sfc"),
+            "<script>export default {};</script>\n<template><p class=\"hello\"></p></template>\n\
+             <style>.hello { color: red; }</style>\n",
+        )
+        .unwrap();
+
+        let mut plugins = PluginContainer::new(root.clone());
+        plugins.add(Box::new(SfcPlugin::new("sfc")));
+        let transformer = ModuleTransformer::new(root.clone());
+
+        let module = transformer
+            .transform_module("/src/Hello.sfc", &plugins)
+            .unwrap();
+        assert_eq!(module.content_type, "application/javascript");
+        assert!(module.code.contains("__sfc__.template"));
+        assert!(module
+            .code
+            .contains("import \"/@style/src/Hello.sfc.css\";"));
+
+        let style = transformer
+            .transform_module("/@style/src/Hello.sfc.css", &plugins)
+            .unwrap();
+        assert!(style.code.contains(".hello"));
+
+        // Editing the component drops its stylesheet from the cache too
+        let invalidated = transformer.invalidate(&root.join("src/Hello.sfc").display().to_string());
+        assert_eq!(invalidated.len(), 2);
+    }
+
+    #[test]
+    fn test_transpiled_module_has_source_map() {
+        use crate::bundler::ReplacePlugin;
+
+        let dir = tempfile::tempdir().unwrap();
+        let root = dir.path().to_path_buf();
+        std::fs::write(
+            root.join("app.ts"),
+            "type Id = number;\n\nconst id: Id = __ID__;\nconsole.log(id);\n",
+        )
+        .unwrap();
+
+        let mut plugins = PluginContainer::new(root.clone());
+        plugins.add(Box::new(ReplacePlugin::new().replace("__ID__", "7")));
+        let transformer = ModuleTransformer::new(root);
+
+        let module = transformer.transform_module("/app.ts", &plugins).unwrap();
+        let (code, url) = module.code.split_once("//# sourceMappingURL=").unwrap();
+        let map = SourceMap::from_data_url(url.trim_end()).unwrap();
+
+        let line = code
+            .lines()
+            .position(|line| line.contains("console.log(id)"))
+            .unwrap();
+        let original = map.lookup(line as u32, 0).unwrap();
+        assert!(map.sources[original.source as usize].ends_with("app.ts"));
+        assert_eq!(original.line, 3);
+    }
+
+    #[test]
+    fn test_sessions_share_transform_cache() {
+        use crate::compiler::{CachedBackend, HowthParserBackend, TranspileCache};
+
+        let dir = tempfile::tempdir().unwrap();
+        let root = dir.path().to_path_buf();
+        std::fs::write(root.join("app.ts"), "const id: number = 7;\n").unwrap();
+
+        let cache = Arc::new(TranspileCache::new());
+        let compiler: Arc<dyn CompilerBackend> = Arc::new(CachedBackend::new(
+            Arc::new(HowthParserBackend::new()),
+            Arc::clone(&cache),
+        ));
+        let plugins = PluginContainer::new(root.clone());
+
+        // Two dev sessions over the same project
+        let first = ModuleTransformer::new(root.clone()).with_compiler(Arc::clone(&compiler));
+        let second = ModuleTransformer::new(root).with_compiler(compiler);
+        let a = first.transform_module("/app.ts", &plugins).unwrap();
+        let b = second.transform_module("/app.ts", &plugins).unwrap();
+
+        assert_eq!(a.code, b.code);
+        assert!(a.code.contains("//# sourceMappingURL=data:"));
+        let stats = cache.stats();
+        assert_eq!((stats.hits, stats.misses), (1, 1));
+    }
+
     #[test]
     fn test_create_css_module() {
         let css = "body { color: red; }";
@@ -1,7 +1,7 @@
 //! Data collectors for doctor report.
 //!
 //! All collectors use only file reads and syscalls.
-//! No subprocesses are spawned.
+//! No subprocesses are spawned (see the `system` module for checks that do).
 
 use super::capabilities::{
     detect_case_sensitivity, detect_hardlink_support, detect_symlink_support,
@@ -11,6 +11,7 @@ use super::{
 };
 use crate::config::Channel;
 use crate::paths;
+use crate::pkg::NodeModulesLayout;
 use crate::version::{SCHEMA_VERSION, VERSION};
 use std::fs;
 use std::path::Path;
@@ -110,9 +111,7 @@ fn get_os_version() -> Option<String> {
 #[must_use]
 pub fn collect_hardware() -> HardwareInfo {
     HardwareInfo {
-        cpu_cores: std::thread::available_parallelism()
-            .map(std::num::NonZero::get)
-            .unwrap_or(1),
+        cpu_cores: std::thread::available_parallelism().map_or(1, std::num::NonZero::get),
         cpu_cores_physical: None, // Would need sys-info crate for this
     }
 }
@@ -127,17 +126,23 @@ pub fn collect_paths(cwd: &Path, channel: Channel, warnings: &mut Vec<Warning>)
     let data_writable = is_dir_writable(&data_dir);
 
     if !cache_writable {
-        warnings.push(Warning::warn(
-            codes::CACHE_NOT_WRITABLE,
-            format!("Cache directory is not writable: {}", cache_dir.display()),
-        ));
+        warnings.push(
+            Warning::warn(
+                codes::CACHE_NOT_WRITABLE,
+                format!("Cache directory is not writable: {}", cache_dir.display()),
+            )
+            .with_fix("Fix the directory's ownership or permissions, or set XDG_CACHE_HOME to a writable location"),
+        );
     }
 
     if !data_writable {
-        warnings.push(Warning::warn(
-            codes::DATA_NOT_WRITABLE,
-            format!("Data directory is not writable: {}", data_dir.display()),
-        ));
+        warnings.push(
+            Warning::warn(
+                codes::DATA_NOT_WRITABLE,
+                format!("Data directory is not writable: {}", data_dir.display()),
+            )
+            .with_fix("Fix the directory's ownership or permissions"),
+        );
     }
 
     PathsInfo {
@@ -186,6 +191,29 @@ pub fn collect_project(cwd: &Path, warnings: &mut Vec<Warning>) -> ProjectInfo {
     }
 }
 
+/// Warn when `node_modules` was installed by another channel.
+///
+/// Channels keep separate package caches, so a tree installed by one channel
+/// links into a cache the current channel never updates or prunes.
+pub fn channel_warnings(project_root: &Path, channel: Channel, warnings: &mut Vec<Warning>) {
+    let Some(installed) = NodeModulesLayout::read_from(project_root).and_then(|l| l.channel) else {
+        return;
+    };
+    if installed != channel {
+        warnings.push(
+            Warning::warn(
+                codes::CHANNEL_MISMATCH,
+                format!(
+                    "node_modules was installed by the {installed} channel, but this is the {channel} channel"
+                ),
+            )
+            .with_fix(format!(
+                "Use `--channel {installed}` for this project, or reinstall with `howth --channel {channel} install`"
+            )),
+        );
+    }
+}
+
 /// Collect filesystem capabilities.
 #[must_use]
 pub fn collect_capabilities(warnings: &mut Vec<Warning>) -> Capabilities {
@@ -251,10 +279,18 @@ fn get_rlimit_nofile(warnings: &mut Vec<Warning>) -> Option<super::RlimitInfo> {
 
         // Warn if soft limit is low (< 1024)
         if soft < 1024 {
-            warnings.push(Warning::warn(
-                codes::LOW_NOFILE_LIMIT,
-                format!("Low file descriptor limit ({soft}). Consider increasing with 'ulimit -n'"),
-            ));
+            warnings.push(
+                Warning::warn(
+                    codes::LOW_NOFILE_LIMIT,
+                    format!(
+                        "Low file descriptor limit ({soft}). Consider increasing with 'ulimit -n'"
+                    ),
+                )
+                .with_fix(format!(
+                    "Run `ulimit -n {}` in your shell profile",
+                    hard.min(65_536)
+                )),
+            );
         }
 
         Some(super::RlimitInfo { soft, hard })
@@ -271,7 +307,7 @@ mod tests {
     #[test]
     fn test_collect_runtime() {
         let info = collect_runtime(Channel::Stable);
-        assert!(!info.version.is_empty());
+        assert_ne!(info.version, "");
         assert_eq!(info.channel, "stable");
     }
 
@@ -279,8 +315,8 @@ mod tests {
     fn test_collect_os() {
         let mut warnings = Vec::new();
         let info = collect_os(&mut warnings);
-        assert!(!info.name.is_empty());
-        assert!(!info.arch.is_empty());
+        assert_ne!(info.name, "");
+        assert_ne!(info.arch, "");
     }
 
     #[test]
@@ -313,4 +349,31 @@ mod tests {
         assert!(info.has_package_json);
         assert!(!info.has_git);
     }
+
+    #[test]
+    fn test_channel_warnings() {
+        let dir = tempdir().unwrap();
+        let mut warnings = Vec::new();
+        channel_warnings(dir.path(), Channel::Dev, &mut warnings);
+        assert!(warnings.is_empty());
+
+        fs::create_dir_all(dir.path().join("node_modules")).unwrap();
+        fs::write(
+            dir.path().join("node_modules/.howth-layout.json"),
+            r#"{"schema_version":1,"node_linker":"isolated","lockfile_hash":"x","packages":{},"channel":"stable"}"#,
+        )
+        .unwrap();
+
+        channel_warnings(dir.path(), Channel::Stable, &mut warnings);
+        assert!(warnings.is_empty());
+
+        channel_warnings(dir.path(), Channel::Dev, &mut warnings);
+        assert_eq!(warnings.len(), 1);
+        assert_eq!(warnings[0].code, codes::CHANNEL_MISMATCH);
+        assert!(warnings[0]
+            .fix
+            .as_deref()
+            .unwrap()
+            .contains("--channel stable"));
+    }
 }
@@ -4,24 +4,30 @@
 //! Used by `fastnode doctor` to report system capabilities and warnings.
 //!
 //! ## Design Principles
-//! - No subprocess calls (fast, sandbox-friendly)
 //! - No network calls
-//! - No directory walks beyond project root detection
-//! - All detection done via file reads or syscalls
+//! - Environment detection uses only file reads and syscalls
+//! - The system checks in [`system`] are the exception: they probe
+//!   `node`, `npm` and `tsc` with `--version` and walk the cache
+//!   directories to measure them
+//! - Every warning that has a remedy carries it as `fix`
 
 #![allow(clippy::doc_markdown)]
 
 use serde::{Deserialize, Serialize};
 use std::path::PathBuf;
 
+mod bundle;
 mod capabilities;
 mod collectors;
+mod system;
 
+pub use bundle::*;
 pub use capabilities::*;
 pub use collectors::*;
+pub use system::*;
 
 /// Report schema version. Bump when changing JSON structure.
-pub const REPORT_SCHEMA_VERSION: u32 = 1;
+pub const REPORT_SCHEMA_VERSION: u32 = 2;
 
 /// Warning severity levels.
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
@@ -40,6 +46,9 @@ pub struct Warning {
     pub severity: Severity,
     /// Human-readable message.
     pub message: String,
+    /// Suggested remedy, if there is one.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub fix: Option<String>,
 }
 
 impl Warning {
@@ -49,6 +58,7 @@ impl Warning {
             code: code.to_string(),
             severity: Severity::Info,
             message: message.into(),
+            fix: None,
         }
     }
 
@@ -58,8 +68,16 @@ impl Warning {
             code: code.to_string(),
             severity: Severity::Warn,
             message: message.into(),
+            fix: None,
         }
     }
+
+    /// Attach a suggested remedy.
+    #[must_use]
+    pub fn with_fix(mut self, fix: impl Into<String>) -> Self {
+        self.fix = Some(fix.into());
+        self
+    }
 }
 
 /// Stable warning codes. These are part of the public API and must not change.
@@ -73,6 +91,16 @@ pub mod codes {
     pub const SYMLINK_UNAVAILABLE: &str = "SYMLINK_UNAVAILABLE";
     pub const HARDLINK_UNAVAILABLE: &str = "HARDLINK_UNAVAILABLE";
     pub const UNKNOWN_OS_VERSION: &str = "UNKNOWN_OS_VERSION";
+    pub const DAEMON_NOT_RUNNING: &str = "DAEMON_NOT_RUNNING";
+    pub const DAEMON_VERSION_MISMATCH: &str = "DAEMON_VERSION_MISMATCH";
+    pub const CACHE_LARGE: &str = "CACHE_LARGE";
+    pub const LOW_INOTIFY_WATCHES: &str = "LOW_INOTIFY_WATCHES";
+    pub const LOW_INOTIFY_INSTANCES: &str = "LOW_INOTIFY_INSTANCES";
+    pub const NODE_NOT_FOUND: &str = "NODE_NOT_FOUND";
+    pub const NODE_VERSION_UNSUPPORTED: &str = "NODE_VERSION_UNSUPPORTED";
+    pub const NPM_NOT_FOUND: &str = "NPM_NOT_FOUND";
+    pub const TSC_NOT_FOUND: &str = "TSC_NOT_FOUND";
+    pub const CHANNEL_MISMATCH: &str = "CHANNEL_MISMATCH";
 }
 
 /// Runtime information.
@@ -149,6 +177,12 @@ pub struct DoctorReport {
     pub paths: PathsInfo,
     pub project: ProjectInfo,
     pub capabilities: Capabilities,
+    /// Daemon connectivity, when probed (see [`DoctorReport::with_daemon`]).
+    pub daemon: Option<DaemonInfo>,
+    /// Cache disk usage per channel.
+    pub cache_usage: Vec<CacheUsage>,
+    pub watcher: WatcherInfo,
+    pub tools: Vec<ToolInfo>,
     pub warnings: Vec<Warning>,
 }
 
@@ -156,9 +190,9 @@ impl DoctorReport {
     /// Collect all diagnostic information.
     ///
     /// This function:
-    /// - Does NOT spawn subprocesses
     /// - Does NOT make network calls
-    /// - Only reads files and makes syscalls
+    /// - Only spawns `<tool> --version` for the tool checks
+    /// - Does not contact the daemon; add that with [`Self::with_daemon`]
     #[must_use]
     pub fn collect(cwd: &std::path::Path, channel: crate::config::Channel) -> Self {
         let mut warnings = Vec::new();
@@ -168,7 +202,17 @@ impl DoctorReport {
         let hardware = collectors::collect_hardware();
         let paths = collectors::collect_paths(cwd, channel, &mut warnings);
         let project = collectors::collect_project(cwd, &mut warnings);
+        if let Some(root) = &project.root {
+            collectors::channel_warnings(root, channel, &mut warnings);
+        }
         let capabilities = collectors::collect_capabilities(&mut warnings);
+        let cache_usage = system::collect_cache_usage(&mut warnings);
+        let watcher = system::collect_watcher(&mut warnings);
+        let tools = system::collect_tools(
+            project.root.as_deref().unwrap_or(cwd),
+            crate::build::ToolVersionCache::global(),
+            &mut warnings,
+        );
 
         Self {
             report_schema_version: REPORT_SCHEMA_VERSION,
@@ -178,9 +222,21 @@ impl DoctorReport {
             paths,
             project,
             capabilities,
+            daemon: None,
+            cache_usage,
+            watcher,
+            tools,
             warnings,
         }
     }
+
+    /// Add the result of probing the daemon, with its warnings.
+    #[must_use]
+    pub fn with_daemon(mut self, daemon: DaemonInfo) -> Self {
+        system::daemon_warnings(&daemon, &mut self.warnings);
+        self.daemon = Some(daemon);
+        self
+    }
 }
 
 #[cfg(test)]
@@ -191,7 +247,7 @@ mod tests {
     fn test_report_schema_version_is_stable() {
         // This test documents the current schema version
         // Update this when you intentionally bump the schema
-        assert_eq!(REPORT_SCHEMA_VERSION, 1);
+        assert_eq!(REPORT_SCHEMA_VERSION, 2);
     }
 
     #[test]
@@ -206,6 +262,16 @@ mod tests {
             codes::SYMLINK_UNAVAILABLE,
             codes::HARDLINK_UNAVAILABLE,
             codes::UNKNOWN_OS_VERSION,
+            codes::DAEMON_NOT_RUNNING,
+            codes::DAEMON_VERSION_MISMATCH,
+            codes::CACHE_LARGE,
+            codes::LOW_INOTIFY_WATCHES,
+            codes::LOW_INOTIFY_INSTANCES,
+            codes::NODE_NOT_FOUND,
+            codes::NODE_VERSION_UNSUPPORTED,
+            codes::NPM_NOT_FOUND,
+            codes::TSC_NOT_FOUND,
+            codes::CHANNEL_MISMATCH,
         ];
 
         for code in codes {
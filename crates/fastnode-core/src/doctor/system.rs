@@ -0,0 +1,368 @@
+//! System checks for doctor report.
+//!
+//! Unlike the other collectors these may be slow: tool versions are probed
+//! with `<tool> --version` and cache sizes are measured by walking the cache
+//! directories. Daemon connectivity is probed by the caller and passed in
+//! through [`DoctorReport::with_daemon`](super::DoctorReport::with_daemon).
+
+use super::{codes, Warning};
+use crate::build::{tool_path, ToolVersionCache};
+use crate::config::Channel;
+use crate::paths;
+use crate::version::VERSION;
+use serde::{Deserialize, Serialize};
+use std::path::{Path, PathBuf};
+use walkdir::WalkDir;
+
+/// Channels whose caches are measured.
+const CHANNELS: [Channel; 3] = [Channel::Stable, Channel::Nightly, Channel::Dev];
+
+/// Cache size above which pruning is suggested (10 GiB).
+const LARGE_CACHE_BYTES: u64 = 10 * 1024 * 1024 * 1024;
+
+/// Recommended minimum for `fs.inotify.max_user_watches`.
+const MIN_INOTIFY_WATCHES: u64 = 65_536;
+
+/// Recommended minimum for `fs.inotify.max_user_instances`.
+const MIN_INOTIFY_INSTANCES: u64 = 128;
+
+/// Oldest supported Node.js major version.
+const MIN_NODE_MAJOR: u32 = 18;
+
+/// Daemon connectivity.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DaemonInfo {
+    pub endpoint: String,
+    pub running: bool,
+    /// Version reported by the daemon.
+    pub version: Option<String>,
+    /// Round-trip time of a ping.
+    pub latency_ms: Option<u64>,
+    /// Why the daemon could not be reached.
+    pub error: Option<String>,
+}
+
+/// Disk usage of one channel's cache directory.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CacheUsage {
+    pub channel: String,
+    pub dir: PathBuf,
+    pub exists: bool,
+    pub bytes: u64,
+    pub files: u64,
+}
+
+/// File watcher backend and its limits.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct WatcherInfo {
+    /// Native backend used by the watcher (`inotify`, `fsevents`, ...).
+    pub backend: String,
+    /// `fs.inotify.max_user_watches` (Linux only).
+    pub inotify_max_user_watches: Option<u64>,
+    /// `fs.inotify.max_user_instances` (Linux only).
+    pub inotify_max_user_instances: Option<u64>,
+}
+
+/// An external tool and its version.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ToolInfo {
+    pub name: String,
+    pub path: Option<PathBuf>,
+    pub version: Option<String>,
+}
+
+/// Measure the cache directory of every channel.
+#[must_use]
+pub fn collect_cache_usage(warnings: &mut Vec<Warning>) -> Vec<CacheUsage> {
+    CHANNELS
+        .iter()
+        .map(|&channel| {
+            let dir = paths::cache_dir(channel);
+            let (bytes, files) = dir_size(&dir);
+            if bytes > LARGE_CACHE_BYTES {
+                warnings.push(
+                    Warning::info(
+                        codes::CACHE_LARGE,
+                        format!(
+                            "The {} cache uses {} ({})",
+                            channel.as_str(),
+                            format_bytes(bytes),
+                            dir.display()
+                        ),
+                    )
+                    .with_fix("Run `howth pkg cache prune` to remove unused packages"),
+                );
+            }
+            CacheUsage {
+                channel: channel.as_str().to_string(),
+                exists: dir.is_dir(),
+                dir,
+                bytes,
+                files,
+            }
+        })
+        .collect()
+}
+
+/// Total size and number of files under `dir`, without following links.
+fn dir_size(dir: &Path) -> (u64, u64) {
+    WalkDir::new(dir)
+        .into_iter()
+        .filter_map(Result::ok)
+        .filter(|entry| entry.file_type().is_file())
+        .filter_map(|entry| entry.metadata().ok())
+        .fold((0, 0), |(bytes, files), meta| {
+            (bytes + meta.len(), files + 1)
+        })
+}
+
+/// Human-readable byte count, e.g. `1.5 GiB`.
+#[must_use]
+#[allow(clippy::cast_precision_loss)]
+pub fn format_bytes(bytes: u64) -> String {
+    const UNITS: [&str; 5] = ["B", "KiB", "MiB", "GiB", "TiB"];
+    let mut value = bytes as f64;
+    let mut unit = 0;
+    while value >= 1024.0 && unit + 1 < UNITS.len() {
+        value /= 1024.0;
+        unit += 1;
+    }
+    if unit == 0 {
+        format!("{bytes} B")
+    } else {
+        format!("{value:.1} {}", UNITS[unit])
+    }
+}
+
+/// Detect the watcher backend and, on Linux, the inotify limits.
+#[must_use]
+pub fn collect_watcher(warnings: &mut Vec<Warning>) -> WatcherInfo {
+    let backend = if cfg!(any(target_os = "linux", target_os = "android")) {
+        "inotify"
+    } else if cfg!(target_os = "macos") {
+        "fsevents"
+    } else if cfg!(target_os = "windows") {
+        "ReadDirectoryChangesW"
+    } else if cfg!(any(
+        target_os = "freebsd",
+        target_os = "openbsd",
+        target_os = "netbsd",
+        target_os = "dragonfly",
+        target_os = "ios"
+    )) {
+        "kqueue"
+    } else {
+        "poll"
+    };
+
+    let read_limit = |name: &str| {
+        std::fs::read_to_string(Path::new("/proc/sys/fs/inotify").join(name))
+            .ok()
+            .and_then(|value| value.trim().parse::<u64>().ok())
+    };
+    let (watches, instances) = if backend == "inotify" {
+        (
+            read_limit("max_user_watches"),
+            read_limit("max_user_instances"),
+        )
+    } else {
+        (None, None)
+    };
+
+    if let Some(watches) = watches.filter(|&w| w < MIN_INOTIFY_WATCHES) {
+        warnings.push(
+            Warning::warn(
+                codes::LOW_INOTIFY_WATCHES,
+                format!("inotify watch limit is low ({watches}); large projects may miss file changes"),
+            )
+            .with_fix(
+                "Run `sudo sysctl fs.inotify.max_user_watches=524288` and add it to /etc/sysctl.conf to persist",
+            ),
+        );
+    }
+    if let Some(instances) = instances.filter(|&i| i < MIN_INOTIFY_INSTANCES) {
+        warnings.push(
+            Warning::warn(
+                codes::LOW_INOTIFY_INSTANCES,
+                format!("inotify instance limit is low ({instances}); watchers may fail to start"),
+            )
+            .with_fix(
+                "Run `sudo sysctl fs.inotify.max_user_instances=512` and add it to /etc/sysctl.conf to persist",
+            ),
+        );
+    }
+
+    WatcherInfo {
+        backend: backend.to_string(),
+        inotify_max_user_watches: watches,
+        inotify_max_user_instances: instances,
+    }
+}
+
+/// Locate `node`, `npm` and `tsc` as seen from `cwd` and probe their versions.
+#[must_use]
+pub fn collect_tools(
+    cwd: &Path,
+    tools: &ToolVersionCache,
+    warnings: &mut Vec<Warning>,
+) -> Vec<ToolInfo> {
+    let infos: Vec<ToolInfo> = ["node", "npm", "tsc"]
+        .into_iter()
+        .map(|name| {
+            let path = tool_path(name, cwd);
+            let version = path.as_ref().and_then(|_| tools.version(name, cwd));
+            ToolInfo {
+                name: name.to_string(),
+                path,
+                version,
+            }
+        })
+        .collect();
+
+    for tool in &infos {
+        match (tool.name.as_str(), &tool.version) {
+            ("node", None) => warnings.push(
+                Warning::warn(codes::NODE_NOT_FOUND, "node was not found on PATH or could not be run")
+                    .with_fix("Install Node.js from https://nodejs.org or with a version manager such as fnm or nvm"),
+            ),
+            ("node", Some(version)) => {
+                if let Some(major) = node_major(version).filter(|&m| m < MIN_NODE_MAJOR) {
+                    warnings.push(
+                        Warning::warn(
+                            codes::NODE_VERSION_UNSUPPORTED,
+                            format!("node {version} is older than the oldest supported release (v{MIN_NODE_MAJOR})"),
+                        )
+                        .with_fix(format!("Upgrade to Node.js {MIN_NODE_MAJOR} or later (found major {major})")),
+                    );
+                }
+            }
+            ("npm", None) => warnings.push(
+                Warning::info(codes::NPM_NOT_FOUND, "npm was not found on PATH or could not be run")
+                    .with_fix("npm ships with Node.js; reinstall Node.js to restore it"),
+            ),
+            ("tsc", None) if cwd.join("tsconfig.json").is_file() => warnings.push(
+                Warning::info(
+                    codes::TSC_NOT_FOUND,
+                    "tsconfig.json found but tsc is not installed (type checking and declarations are unavailable)",
+                )
+                .with_fix("Run `howth pkg add -D typescript`"),
+            ),
+            _ => {}
+        }
+    }
+
+    infos
+}
+
+/// Major version from `node --version` output like `v20.11.0`.
+fn node_major(version: &str) -> Option<u32> {
+    version
+        .trim()
+        .trim_start_matches('v')
+        .split('.')
+        .next()?
+        .parse()
+        .ok()
+}
+
+/// Warnings for an unreachable daemon or one running another version.
+pub fn daemon_warnings(daemon: &DaemonInfo, warnings: &mut Vec<Warning>) {
+    if !daemon.running {
+        warnings.push(
+            Warning::info(
+                codes::DAEMON_NOT_RUNNING,
+                format!("Daemon is not running at {}", daemon.endpoint),
+            )
+            .with_fix("Start it with `howth daemon` for faster builds and installs"),
+        );
+        return;
+    }
+    if let Some(version) = daemon.version.as_deref().filter(|v| *v != VERSION) {
+        warnings.push(
+            Warning::warn(
+                codes::DAEMON_VERSION_MISMATCH,
+                format!("Daemon is running version {version} but the CLI is {VERSION}"),
+            )
+            .with_fix("Restart it with `howth stop` followed by `howth daemon`"),
+        );
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::tempdir;
+
+    #[test]
+    fn test_dir_size_and_format() {
+        let dir = tempdir().unwrap();
+        std::fs::create_dir_all(dir.path().join("a")).unwrap();
+        std::fs::write(dir.path().join("a/x"), [0u8; 1000]).unwrap();
+        std::fs::write(dir.path().join("y"), [0u8; 24]).unwrap();
+        assert_eq!(dir_size(dir.path()), (1024, 2));
+        assert_eq!(dir_size(&dir.path().join("missing")), (0, 0));
+
+        assert_eq!(format_bytes(512), "512 B");
+        assert_eq!(format_bytes(1536), "1.5 KiB");
+        assert_eq!(format_bytes(3 * 1024 * 1024 * 1024), "3.0 GiB");
+    }
+
+    #[test]
+    fn test_node_major() {
+        assert_eq!(node_major("v20.11.0\n"), Some(20));
+        assert_eq!(node_major("16.0.0"), Some(16));
+        assert_eq!(node_major("nightly"), None);
+    }
+
+    #[test]
+    fn test_tool_warnings() {
+        let dir = tempdir().unwrap();
+        std::fs::write(dir.path().join("tsconfig.json"), "{}").unwrap();
+        let tools = ToolVersionCache::with_versions([
+            ("node", Some("v16.20.0")),
+            ("npm", None),
+            ("tsc", None),
+        ]);
+        let mut warnings = Vec::new();
+        let infos = collect_tools(dir.path(), &tools, &mut warnings);
+        assert_eq!(infos.len(), 3);
+
+        // Only tools that resolve to a binary are versioned, so the result
+        // depends on the machine; the warnings follow from the versions.
+        let codes: Vec<&str> = warnings.iter().map(|w| w.code.as_str()).collect();
+        for info in &infos {
+            match (info.name.as_str(), info.version.as_deref()) {
+                ("node", None) => assert!(codes.contains(&codes::NODE_NOT_FOUND)),
+                ("node", Some("v16.20.0")) => {
+                    assert!(codes.contains(&codes::NODE_VERSION_UNSUPPORTED));
+                }
+                ("tsc", None) => assert!(codes.contains(&codes::TSC_NOT_FOUND)),
+                _ => {}
+            }
+        }
+        assert!(warnings.iter().all(|w| w.fix.is_some()));
+    }
+
+    #[test]
+    fn test_daemon_warnings() {
+        let daemon = |running: bool, version: Option<&str>| DaemonInfo {
+            endpoint: "/tmp/howth.sock".to_string(),
+            running,
+            version: version.map(str::to_string),
+            latency_ms: None,
+            error: None,
+        };
+
+        let mut warnings = Vec::new();
+        daemon_warnings(&daemon(false, None), &mut warnings);
+        assert_eq!(warnings[0].code, codes::DAEMON_NOT_RUNNING);
+
+        let mut warnings = Vec::new();
+        daemon_warnings(&daemon(true, Some(VERSION)), &mut warnings);
+        assert!(warnings.is_empty());
+
+        daemon_warnings(&daemon(true, Some("0.0.0-old")), &mut warnings);
+        assert_eq!(warnings[0].code, codes::DAEMON_VERSION_MISMATCH);
+        assert!(warnings[0].fix.as_deref().unwrap().contains("howth stop"));
+    }
+}
@@ -0,0 +1,231 @@
+//! Source formatting for `howth fmt`.
+//!
+//! Wraps [`howth_parser::format`] with file discovery: explicit files are
+//! formatted as given, directories are expanded to their JS/TS sources.
+//! Inside a git repository the listing comes from git, so `.gitignore` is
+//! respected; elsewhere dependency and output directories are skipped.
+
+use crate::git;
+use howth_parser::ParserOptions;
+use std::path::{Path, PathBuf};
+use thiserror::Error;
+
+/// Stable error codes for formatting.
+pub mod codes {
+    pub const FMT_IO: &str = "FMT_IO";
+    pub const FMT_PATH_NOT_FOUND: &str = "FMT_PATH_NOT_FOUND";
+    pub const FMT_UNFORMATTABLE: &str = "FMT_UNFORMATTABLE";
+}
+
+/// Error type for formatting.
+#[derive(Error, Debug)]
+pub enum FmtError {
+    #[error("failed to access {path}: {source}")]
+    Io {
+        path: PathBuf,
+        source: std::io::Error,
+    },
+
+    #[error("no such file or directory: {path}")]
+    PathNotFound { path: PathBuf },
+
+    #[error("{path}{location}: {message}")]
+    Unformattable {
+        path: PathBuf,
+        /// `:line:column`, or empty when the whole file is affected.
+        location: String,
+        message: String,
+    },
+}
+
+impl FmtError {
+    /// Get the stable error code.
+    #[must_use]
+    pub fn code(&self) -> &'static str {
+        match self {
+            Self::Io { .. } => codes::FMT_IO,
+            Self::PathNotFound { .. } => codes::FMT_PATH_NOT_FOUND,
+            Self::Unformattable { .. } => codes::FMT_UNFORMATTABLE,
+        }
+    }
+}
+
+/// Extensions `howth fmt` formats.
+pub const EXTENSIONS: &[&str] = &["js", "jsx", "mjs", "cjs", "ts", "tsx", "mts", "cts"];
+
+/// Directories skipped when walking outside a git repository.
+const SKIP_DIRS: &[&str] = &["node_modules", ".git", "dist", "build", "coverage"];
+
+/// Whether `path` has an extension `howth fmt` formats.
+#[must_use]
+pub fn is_formattable(path: &Path) -> bool {
+    path.extension()
+        .and_then(|ext| ext.to_str())
+        .is_some_and(|ext| EXTENSIONS.contains(&ext))
+}
+
+/// Parser options for a file, chosen by extension.
+#[must_use]
+pub fn parser_options(path: &Path) -> ParserOptions {
+    let ext = path.extension().and_then(|ext| ext.to_str()).unwrap_or("");
+    ParserOptions {
+        module: ext != "cjs" && ext != "cts",
+        jsx: matches!(ext, "js" | "jsx" | "tsx"),
+        typescript: matches!(ext, "ts" | "tsx" | "mts" | "cts"),
+    }
+}
+
+/// Expand `paths` (relative to `cwd`) into the files to format.
+///
+/// Files are kept as given, even when ignored. An empty `paths` means
+/// `cwd`. The result is sorted and deduplicated.
+pub fn collect_files(cwd: &Path, paths: &[PathBuf]) -> Result<Vec<PathBuf>, FmtError> {
+    if paths.is_empty() {
+        return list_dir(cwd);
+    }
+
+    let mut files = Vec::new();
+    for path in paths {
+        let path = if path == Path::new(".") {
+            cwd.to_path_buf()
+        } else {
+            cwd.join(path)
+        };
+        if path.is_file() {
+            files.push(path);
+        } else if path.is_dir() {
+            files.extend(list_dir(&path)?);
+        } else {
+            return Err(FmtError::PathNotFound { path });
+        }
+    }
+    files.sort();
+    files.dedup();
+    Ok(files)
+}
+
+/// Formattable files under `dir`, sorted: tracked and untracked files that
+/// aren't ignored when `dir` is in a git repository, otherwise a walk.
+fn list_dir(dir: &Path) -> Result<Vec<PathBuf>, FmtError> {
+    if let Ok(files) = git::list_files(dir) {
+        return Ok(files
+            .into_iter()
+            .filter(|path| is_formattable(path) && path.is_file())
+            .collect());
+    }
+
+    let mut files = Vec::new();
+    let walker = walkdir::WalkDir::new(dir)
+        .into_iter()
+        .filter_entry(|entry| {
+            entry.depth() == 0
+                || !entry.file_type().is_dir()
+                || !entry
+                    .file_name()
+                    .to_str()
+                    .is_some_and(|name| SKIP_DIRS.contains(&name))
+        });
+    for entry in walker {
+        let entry = entry.map_err(|e| FmtError::Io {
+            path: e.path().unwrap_or(dir).to_path_buf(),
+            source: e.into(),
+        })?;
+        if entry.file_type().is_file() && is_formattable(entry.path()) {
+            files.push(entry.into_path());
+        }
+    }
+    files.sort();
+    Ok(files)
+}
+
+/// Format one file's source. `path` picks the syntax and labels errors.
+pub fn format_source(path: &Path, source: &str) -> Result<String, FmtError> {
+    howth_parser::format(source, parser_options(path)).map_err(|e| {
+        let location = if e.span.start == 0 && e.span.end == 0 {
+            String::new()
+        } else {
+            let (line, column) = line_column(source, e.span.start as usize);
+            format!(":{line}:{column}")
+        };
+        FmtError::Unformattable {
+            path: path.to_path_buf(),
+            location,
+            message: e.message,
+        }
+    })
+}
+
+/// Format `path`. Returns whether the file's contents changed; with
+/// `write`, the formatted text is written back.
+pub fn format_file(path: &Path, write: bool) -> Result<bool, FmtError> {
+    let io = |source| FmtError::Io {
+        path: path.to_path_buf(),
+        source,
+    };
+    let source = std::fs::read_to_string(path).map_err(io)?;
+    let formatted = format_source(path, &source)?;
+    if formatted == source {
+        return Ok(false);
+    }
+    if write {
+        std::fs::write(path, formatted).map_err(io)?;
+    }
+    Ok(true)
+}
+
+/// 1-based line and column of byte `offset`.
+pub(crate) fn line_column(source: &str, offset: usize) -> (usize, usize) {
+    let before = &source[..offset.min(source.len())];
+    let line = before.matches('\n').count() + 1;
+    let column = before.len() - before.rfind('\n').map_or(0, |i| i + 1) + 1;
+    (line, column)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::fs;
+
+    #[test]
+    fn test_format_source() {
+        let out = format_source(Path::new("a.ts"), "let x:number=1").unwrap();
+        assert_eq!(out, "let x: number = 1;\n");
+    }
+
+    #[test]
+    fn test_format_source_reports_location() {
+        let err = format_source(Path::new("a.js"), "let a = 1;\nlet = ;").unwrap_err();
+        assert_eq!(err.code(), codes::FMT_UNFORMATTABLE);
+        assert!(err.to_string().starts_with("a.js:2:"), "{err}");
+    }
+
+    #[test]
+    fn test_format_file_check_and_write() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("a.js");
+        fs::write(&path, "f( 1 )").unwrap();
+
+        assert!(format_file(&path, false).unwrap());
+        assert_eq!(fs::read_to_string(&path).unwrap(), "f( 1 )");
+
+        assert!(format_file(&path, true).unwrap());
+        assert_eq!(fs::read_to_string(&path).unwrap(), "f(1);\n");
+        assert!(!format_file(&path, false).unwrap());
+    }
+
+    #[test]
+    fn test_collect_files_skips_dependencies() {
+        let dir = tempfile::tempdir().unwrap();
+        fs::create_dir_all(dir.path().join("src")).unwrap();
+        fs::create_dir_all(dir.path().join("node_modules/x")).unwrap();
+        fs::write(dir.path().join("src/a.ts"), "").unwrap();
+        fs::write(dir.path().join("src/b.css"), "").unwrap();
+        fs::write(dir.path().join("node_modules/x/index.js"), "").unwrap();
+
+        let files = collect_files(dir.path(), &[]).unwrap();
+        assert_eq!(files, vec![dir.path().join("src/a.ts")]);
+
+        let missing = collect_files(dir.path(), &[PathBuf::from("nope")]).unwrap_err();
+        assert_eq!(missing.code(), codes::FMT_PATH_NOT_FOUND);
+    }
+}
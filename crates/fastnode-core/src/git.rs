@@ -0,0 +1,271 @@
+//! Minimal git integration for change detection.
+//!
+//! Shells out to the `git` binary; used by `--since <ref>` to find the files
+//! changed since a commit, branch or tag.
+
+use std::path::{Path, PathBuf};
+use std::process::Command;
+use thiserror::Error;
+
+/// Stable error codes for git operations.
+pub mod codes {
+    pub const GIT_NOT_FOUND: &str = "GIT_NOT_FOUND";
+    pub const GIT_NOT_A_REPO: &str = "GIT_NOT_A_REPO";
+    pub const GIT_REF_INVALID: &str = "GIT_REF_INVALID";
+    pub const GIT_COMMAND_FAILED: &str = "GIT_COMMAND_FAILED";
+}
+
+/// Error type for git operations.
+#[derive(Error, Debug)]
+pub enum GitError {
+    #[error("git is not installed or not on PATH")]
+    NotFound,
+
+    #[error("{dir} is not inside a git repository")]
+    NotARepo { dir: PathBuf },
+
+    #[error("unknown git revision: \"{reference}\"")]
+    RefInvalid { reference: String },
+
+    #[error("git {args} failed: {reason}")]
+    CommandFailed { args: String, reason: String },
+}
+
+impl GitError {
+    /// Get the stable error code.
+    #[must_use]
+    pub fn code(&self) -> &'static str {
+        match self {
+            Self::NotFound => codes::GIT_NOT_FOUND,
+            Self::NotARepo { .. } => codes::GIT_NOT_A_REPO,
+            Self::RefInvalid { .. } => codes::GIT_REF_INVALID,
+            Self::CommandFailed { .. } => codes::GIT_COMMAND_FAILED,
+        }
+    }
+}
+
+/// Run git in `dir` and return stdout, or the trimmed stderr on failure.
+fn git(dir: &Path, args: &[&str]) -> Result<Result<String, String>, GitError> {
+    let output = Command::new("git")
+        .args(args)
+        .current_dir(dir)
+        .output()
+        .map_err(|e| match e.kind() {
+            std::io::ErrorKind::NotFound => GitError::NotFound,
+            _ => GitError::CommandFailed {
+                args: args.join(" "),
+                reason: e.to_string(),
+            },
+        })?;
+
+    if output.status.success() {
+        Ok(Ok(String::from_utf8_lossy(&output.stdout).into_owned()))
+    } else {
+        Ok(Err(String::from_utf8_lossy(&output.stderr)
+            .trim()
+            .to_string()))
+    }
+}
+
+/// Run git and treat a non-zero exit as [`GitError::CommandFailed`].
+fn git_ok(dir: &Path, args: &[&str]) -> Result<String, GitError> {
+    git(dir, args)?.map_err(|reason| GitError::CommandFailed {
+        args: args.join(" "),
+        reason,
+    })
+}
+
+/// Find the top-level directory of the repository containing `dir`.
+pub fn repo_root(dir: &Path) -> Result<PathBuf, GitError> {
+    match git(dir, &["rev-parse", "--show-toplevel"])? {
+        Ok(stdout) => Ok(PathBuf::from(stdout.trim())),
+        Err(_) => Err(GitError::NotARepo {
+            dir: dir.to_path_buf(),
+        }),
+    }
+}
+
+/// Full SHA of the commit checked out in the repository containing `dir`.
+pub fn head_sha(dir: &Path) -> Result<String, GitError> {
+    match git(dir, &["rev-parse", "--verify", "--quiet", "HEAD"])? {
+        Ok(stdout) => Ok(stdout.trim().to_string()),
+        Err(_) => Err(GitError::NotARepo {
+            dir: dir.to_path_buf(),
+        }),
+    }
+}
+
+/// List files changed since `reference`, as absolute paths.
+///
+/// Covers commits after `reference`, uncommitted changes to tracked files
+/// and untracked files that aren't ignored. Sorted and deduplicated.
+pub fn changed_files(dir: &Path, reference: &str) -> Result<Vec<PathBuf>, GitError> {
+    let root = repo_root(dir)?;
+
+    let commit = format!("{reference}^{{commit}}");
+    if git(&root, &["rev-parse", "--verify", "--quiet", &commit])?.is_err() {
+        return Err(GitError::RefInvalid {
+            reference: reference.to_string(),
+        });
+    }
+
+    let diff = git_ok(&root, &["diff", "--name-only", "--no-renames", reference])?;
+    let untracked = git_ok(&root, &["ls-files", "--others", "--exclude-standard"])?;
+
+    let mut files: Vec<PathBuf> = diff
+        .lines()
+        .chain(untracked.lines())
+        .filter(|line| !line.is_empty())
+        .map(|line| root.join(line))
+        .collect();
+    files.sort();
+    files.dedup();
+    Ok(files)
+}
+
+/// List the files under `dir` that git tracks or would track, as absolute
+/// paths: committed and untracked files, minus ignored ones. Sorted.
+pub fn list_files(dir: &Path) -> Result<Vec<PathBuf>, GitError> {
+    repo_root(dir)?;
+    let stdout = git_ok(
+        dir,
+        &[
+            "ls-files",
+            "--cached",
+            "--others",
+            "--exclude-standard",
+            "-z",
+        ],
+    )?;
+    let mut files: Vec<PathBuf> = stdout
+        .split('\0')
+        .filter(|line| !line.is_empty())
+        .map(|line| dir.join(line))
+        .collect();
+    files.sort();
+    files.dedup();
+    Ok(files)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::fs;
+
+    fn run_git(dir: &Path, args: &[&str]) {
+        let status = Command::new("git")
+            .args(args)
+            .current_dir(dir)
+            .status()
+            .unwrap();
+        assert!(status.success(), "git {args:?} failed");
+    }
+
+    fn init_repo(dir: &Path) -> bool {
+        if Command::new("git").arg("--version").output().is_err() {
+            return false;
+        }
+        run_git(dir, &["init", "-q"]);
+        run_git(dir, &["config", "user.email", "test@example.com"]);
+        run_git(dir, &["config", "user.name", "test"]);
+        run_git(dir, &["config", "commit.gpgsign", "false"]);
+        true
+    }
+
+    #[test]
+    fn test_changed_files_since_ref() {
+        let dir = tempfile::tempdir().unwrap();
+        let root = dunce::canonicalize(dir.path()).unwrap();
+        if !init_repo(&root) {
+            return;
+        }
+        fs::write(root.join("a.txt"), "a").unwrap();
+        fs::write(root.join("b.txt"), "b").unwrap();
+        run_git(&root, &["add", "."]);
+        run_git(&root, &["commit", "-q", "-m", "initial"]);
+
+        fs::write(root.join("a.txt"), "changed").unwrap();
+        fs::write(root.join("c.txt"), "new").unwrap();
+        fs::write(root.join(".gitignore"), "ignored.txt\n").unwrap();
+        fs::write(root.join("ignored.txt"), "x").unwrap();
+
+        let changed = changed_files(&root, "HEAD").unwrap();
+        let root_repo = repo_root(&root).unwrap();
+        assert_eq!(
+            changed,
+            vec![
+                root_repo.join(".gitignore"),
+                root_repo.join("a.txt"),
+                root_repo.join("c.txt"),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_changed_files_invalid_ref() {
+        let dir = tempfile::tempdir().unwrap();
+        if !init_repo(dir.path()) {
+            return;
+        }
+        let err = changed_files(dir.path(), "no-such-branch").unwrap_err();
+        assert_eq!(err.code(), codes::GIT_REF_INVALID);
+    }
+
+    #[test]
+    fn test_head_sha() {
+        let dir = tempfile::tempdir().unwrap();
+        if !init_repo(dir.path()) {
+            return;
+        }
+        // No commits yet
+        assert!(head_sha(dir.path()).is_err());
+
+        fs::write(dir.path().join("a.txt"), "a").unwrap();
+        run_git(dir.path(), &["add", "."]);
+        run_git(dir.path(), &["commit", "-q", "-m", "initial"]);
+        let sha = head_sha(dir.path()).unwrap();
+        assert_eq!(sha.len(), 40);
+        assert!(sha.chars().all(|c| c.is_ascii_hexdigit()));
+    }
+
+    #[test]
+    fn test_repo_root_outside_repo() {
+        let dir = tempfile::tempdir().unwrap();
+        // The temp dir may itself be inside a repository on some machines
+        if let Err(e) = repo_root(dir.path()) {
+            assert!(matches!(
+                e.code(),
+                codes::GIT_NOT_A_REPO | codes::GIT_NOT_FOUND
+            ));
+        }
+    }
+
+    #[test]
+    fn test_list_files_respects_gitignore() {
+        let dir = tempfile::tempdir().unwrap();
+        let root = dunce::canonicalize(dir.path()).unwrap();
+        if !init_repo(&root) {
+            return;
+        }
+        fs::create_dir_all(root.join("src")).unwrap();
+        fs::write(root.join("src/a.js"), "a").unwrap();
+        fs::write(root.join(".gitignore"), "ignored.js\n").unwrap();
+        fs::write(root.join("ignored.js"), "x").unwrap();
+        run_git(&root, &["add", "src"]);
+        fs::write(root.join("b.js"), "b").unwrap();
+
+        let files = list_files(&root).unwrap();
+        assert_eq!(
+            files,
+            vec![
+                root.join(".gitignore"),
+                root.join("b.js"),
+                root.join("src/a.js")
+            ]
+        );
+        assert_eq!(
+            list_files(&root.join("src")).unwrap(),
+            vec![root.join("src/a.js")]
+        );
+    }
+}
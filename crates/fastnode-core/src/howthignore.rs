@@ -0,0 +1,268 @@
+//! Per-project `.howthignore` file.
+//!
+//! One place to exclude generated or vendored directories from howth: the
+//! file watcher, build input globs, bundler asset emission and `pkg pack`
+//! all skip matching paths. The syntax is gitignore's:
+//! - blank lines and `#` comments are skipped
+//! - later rules win, and `!pattern` re-includes a path
+//! - a trailing `/` only matches directories
+//! - a pattern containing `/` is anchored to the directory holding the file;
+//!   otherwise it matches any path component
+//! - a path is ignored if any of its ancestor directories is ignored
+//!
+//! The nearest `.howthignore` in a directory or its ancestors applies.
+//! Patterns use [`fastnode_util::glob`] syntax. Each file is parsed once per
+//! process and again only when it changes.
+//!
+//! [`IgnoreRule`] and [`is_ignored_by`] are the gitignore matcher itself, for
+//! callers with other rule sources (the watcher's `.gitignore`, `pkg pack`'s
+//! `.npmignore`).
+
+use fastnode_util::glob::Glob;
+use std::collections::HashMap;
+use std::path::{Component, Path, PathBuf};
+use std::sync::{Arc, Mutex, OnceLock};
+use std::time::SystemTime;
+
+/// File name of the ignore file.
+pub const HOWTHIGNORE_FILE: &str = ".howthignore";
+
+/// One parsed gitignore-style rule.
+#[derive(Debug, Clone)]
+pub struct IgnoreRule {
+    /// Compiled glob (without `!`, leading `/` or trailing `/`).
+    pattern: Glob,
+    negated: bool,
+    /// Matched against the whole root-relative path.
+    anchored: bool,
+    dir_only: bool,
+}
+
+impl IgnoreRule {
+    /// Parse a rule line. Returns `None` for blanks, comments and invalid
+    /// globs.
+    #[must_use]
+    pub fn parse(line: &str) -> Option<Self> {
+        let line = line.trim_end();
+        if line.is_empty() || line.starts_with('#') {
+            return None;
+        }
+        let (negated, rest) = match line.strip_prefix('!') {
+            Some(rest) => (true, rest),
+            None => (false, line.strip_prefix('\\').unwrap_or(line)),
+        };
+        let (dir_only, rest) = match rest.strip_suffix('/') {
+            Some(rest) => (true, rest),
+            None => (false, rest),
+        };
+        let anchored = rest.contains('/');
+        let rest = rest.strip_prefix('/').unwrap_or(rest);
+        if rest.is_empty() {
+            return None;
+        }
+        Some(Self {
+            pattern: Glob::new(rest).ok()?,
+            negated,
+            anchored,
+            dir_only,
+        })
+    }
+
+    /// Whether this is a `!pattern` rule re-including matching paths.
+    #[must_use]
+    pub fn is_negated(&self) -> bool {
+        self.negated
+    }
+
+    /// Check the rule against a relative path, given as its segments. Only
+    /// the path itself is tested, not its ancestors.
+    #[must_use]
+    pub fn matches(&self, segments: &[String], is_dir: bool) -> bool {
+        if self.dir_only && !is_dir {
+            return false;
+        }
+        if self.anchored {
+            self.pattern.is_match(&segments.join("/"))
+        } else {
+            segments
+                .last()
+                .is_some_and(|name| self.pattern.is_match(name))
+        }
+    }
+}
+
+/// Parsed `.howthignore` rules.
+#[derive(Debug, Clone, Default)]
+pub struct HowthIgnore {
+    /// Directory holding the file; rules are relative to it.
+    root: PathBuf,
+    rules: Vec<IgnoreRule>,
+    /// Rule lines, for reporting.
+    patterns: Vec<String>,
+}
+
+impl HowthIgnore {
+    /// Parse the contents of a `.howthignore` in `root`.
+    #[must_use]
+    pub fn parse(root: &Path, content: &str) -> Self {
+        let mut ignore = Self {
+            root: root.to_path_buf(),
+            ..Self::default()
+        };
+        for line in content.lines() {
+            if let Some(rule) = IgnoreRule::parse(line) {
+                ignore.rules.push(rule);
+                ignore.patterns.push(line.trim_end().to_string());
+            }
+        }
+        ignore
+    }
+
+    /// Rules from the nearest `.howthignore` in `dir` or its ancestors
+    /// (empty if there is none).
+    #[must_use]
+    pub fn load(dir: &Path) -> Arc<Self> {
+        static CACHE: OnceLock<Mutex<HashMap<PathBuf, (FileStamp, Arc<HowthIgnore>)>>> =
+            OnceLock::new();
+
+        let Some((path, metadata)) = dir.ancestors().find_map(|d| {
+            let path = d.join(HOWTHIGNORE_FILE);
+            let metadata = std::fs::metadata(&path)
+                .ok()
+                .filter(std::fs::Metadata::is_file)?;
+            Some((path, metadata))
+        }) else {
+            return Arc::new(Self::default());
+        };
+        let stamp = (metadata.modified().ok(), metadata.len());
+
+        let mut cache = CACHE
+            .get_or_init(Mutex::default)
+            .lock()
+            .unwrap_or_else(std::sync::PoisonError::into_inner);
+        if let Some((cached, ignore)) = cache.get(&path) {
+            if *cached == stamp {
+                return ignore.clone();
+            }
+        }
+        let content = std::fs::read_to_string(&path).unwrap_or_default();
+        let root = path.parent().unwrap_or(Path::new(""));
+        let ignore = Arc::new(Self::parse(root, &content));
+        cache.insert(path, (stamp, ignore.clone()));
+        ignore
+    }
+
+    /// Directory the rules are relative to.
+    #[must_use]
+    pub fn root(&self) -> &Path {
+        &self.root
+    }
+
+    /// The rule lines, in order.
+    #[must_use]
+    pub fn patterns(&self) -> &[String] {
+        &self.patterns
+    }
+
+    /// Whether there are no rules.
+    #[must_use]
+    pub fn is_empty(&self) -> bool {
+        self.rules.is_empty()
+    }
+
+    /// Check whether `path` is ignored. Relative paths are taken relative to
+    /// [`root`](Self::root); absolute paths outside it are never ignored.
+    #[must_use]
+    pub fn is_ignored(&self, path: &Path, is_dir: bool) -> bool {
+        if self.rules.is_empty() {
+            return false;
+        }
+        let rel = if path.is_absolute() {
+            match path.strip_prefix(&self.root) {
+                Ok(rel) => rel,
+                Err(_) => return false,
+            }
+        } else {
+            path
+        };
+
+        is_ignored_by(&self.rules, rel, is_dir)
+    }
+}
+
+/// Check a relative path against `rules` in order; the last matching rule
+/// wins. A path is ignored if it or any of its ancestor directories is.
+#[must_use]
+pub fn is_ignored_by<'a, I>(rules: I, rel: &Path, is_dir: bool) -> bool
+where
+    I: IntoIterator<Item = &'a IgnoreRule>,
+    I::IntoIter: Clone + DoubleEndedIterator,
+{
+    let rules = rules.into_iter();
+    let components: Vec<String> = rel
+        .components()
+        .filter_map(|c| match c {
+            Component::Normal(part) => Some(part.to_string_lossy().into_owned()),
+            _ => None,
+        })
+        .collect();
+    // An ignored directory hides everything below it
+    (1..=components.len()).any(|len| {
+        let prefix_is_dir = is_dir || len < components.len();
+        rules
+            .clone()
+            .rev()
+            .find(|rule| rule.matches(&components[..len], prefix_is_dir))
+            .is_some_and(|rule| !rule.negated)
+    })
+}
+
+/// Modification time and size, to notice edited files.
+type FileStamp = (Option<SystemTime>, u64);
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_gitignore_semantics() {
+        let root = Path::new("/proj");
+        let ignore = HowthIgnore::parse(
+            root,
+            "# generated\n\nvendor/\n/generated\n*.gen.ts\n!keep.gen.ts\nsrc/**/fixtures\n",
+        );
+        assert_eq!(ignore.patterns().len(), 5);
+
+        assert!(ignore.is_ignored(&root.join("vendor/lib/a.js"), false));
+        assert!(ignore.is_ignored(&root.join("packages/a/vendor"), true));
+        // `vendor/` only matches directories
+        assert!(!ignore.is_ignored(&root.join("vendor"), false));
+        assert!(ignore.is_ignored(&root.join("generated/types.d.ts"), false));
+        assert!(!ignore.is_ignored(&root.join("src/generated/types.d.ts"), false));
+        assert!(ignore.is_ignored(&root.join("src/api.gen.ts"), false));
+        assert!(!ignore.is_ignored(&root.join("src/keep.gen.ts"), false));
+        assert!(ignore.is_ignored(&root.join("src/a/b/fixtures/x.json"), false));
+        assert!(!ignore.is_ignored(&root.join("src/index.ts"), false));
+        // Relative paths are relative to the root; other trees are untouched
+        assert!(ignore.is_ignored(Path::new("vendor/a.js"), false));
+        assert!(!ignore.is_ignored(Path::new("/elsewhere/vendor/a.js"), false));
+    }
+
+    #[test]
+    fn test_load_nearest_and_reparse_on_change() {
+        let dir = tempfile::tempdir().unwrap();
+        let nested = dir.path().join("packages/app/src");
+        std::fs::create_dir_all(&nested).unwrap();
+        assert!(HowthIgnore::load(&nested).is_empty());
+
+        std::fs::write(dir.path().join(HOWTHIGNORE_FILE), "vendor/\n").unwrap();
+        let ignore = HowthIgnore::load(&nested);
+        assert_eq!(ignore.root(), dir.path());
+        assert!(ignore.is_ignored(&dir.path().join("packages/app/vendor/x.js"), false));
+        // Parsed once
+        assert!(Arc::ptr_eq(&ignore, &HowthIgnore::load(dir.path())));
+
+        std::fs::write(dir.path().join(HOWTHIGNORE_FILE), "vendor/\n*.log\n").unwrap();
+        assert_eq!(HowthIgnore::load(&nested).patterns(), ["vendor/", "*.log"]);
+    }
+}
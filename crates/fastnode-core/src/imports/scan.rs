@@ -554,14 +554,14 @@ import b from "./b";
     fn test_empty_source() {
         let source = "";
         let imports = scan_imports(source);
-        assert!(imports.is_empty());
+        assert_eq!(imports, []);
     }
 
     #[test]
     fn test_no_imports() {
         let source = "console.log('hello');";
         let imports = scan_imports(source);
-        assert!(imports.is_empty());
+        assert_eq!(imports, []);
     }
 
     #[test]
@@ -14,14 +14,22 @@ pub mod bundler;
 pub mod compiler;
 pub mod config;
 pub mod css;
+pub mod deterministic;
 pub mod dev;
 pub mod doctor;
 pub mod error;
+pub mod fmt;
+pub mod git;
+pub mod howthignore;
 pub mod imports;
+pub mod lint;
 pub mod paths;
 pub mod pkg;
 pub mod resolver;
 pub mod runplan;
+pub mod scripts;
+pub mod sourcemap;
+pub mod telemetry;
 pub mod version;
 
 pub use config::Config;
@@ -32,8 +40,9 @@ pub use resolver::{
     ResolverCache, ResolverConfig,
 };
 pub use runplan::{
-    build_run_plan, build_run_plan_with_cache, codes as runplan_codes, ImportSpecOutput,
-    ResolvedImportOutput, ResolverInfoOutput, RunPlanError, RunPlanInput, RunPlanOutput,
-    RESOLVER_SCHEMA_VERSION, RUNPLAN_SCHEMA_VERSION,
+    analyze_import_graph, build_run_plan, build_run_plan_with_cache, codes as runplan_codes,
+    ImportGraphSummary, ImportSpecOutput, ResolvedImportOutput, ResolverInfoOutput, RunPlanError,
+    RunPlanInput, RunPlanOutput, ScriptPlanOutput, ScriptStepOutput, UnresolvedImportGroup,
+    UnresolvedImportSite, FULL_GRAPH_DEPTH, RESOLVER_SCHEMA_VERSION, RUNPLAN_SCHEMA_VERSION,
 };
 pub use version::VERSION;
@@ -0,0 +1,497 @@
+//! Linting for `howth lint`.
+//!
+//! Parses each file with howth-parser, runs one scope-aware walk over the
+//! AST and reports a small core rule set:
+//!
+//! | Rule             | Default | Fixable |
+//! |------------------|---------|---------|
+//! | `no-unused-vars` | warn    | no      |
+//! | `no-undef`       | error   | no      |
+//! | `no-debugger`    | error   | yes     |
+//! | `eqeqeq`         | warn    | when safe (`typeof` or two literals) |
+//! | `no-dupe-keys`   | error   | no      |
+//!
+//! Severities and extra globals come from the `howth.lint` section of
+//! package.json:
+//!
+//! ```json
+//! { "howth": { "lint": { "rules": { "eqeqeq": "off" }, "globals": ["__DEV__"] } } }
+//! ```
+//!
+//! `no-undef` is skipped for TypeScript files, where the compiler already
+//! checks names and ambient declarations live in other files.
+
+mod rules;
+mod scope;
+
+use crate::fmt::{self, FmtError};
+use howth_parser::Parser;
+use serde::{Deserialize, Serialize, Serializer};
+use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
+use thiserror::Error;
+
+/// Stable error codes for linting.
+pub mod codes {
+    pub const LINT_IO: &str = "LINT_IO";
+    pub const LINT_PATH_NOT_FOUND: &str = "LINT_PATH_NOT_FOUND";
+    pub const LINT_PARSE_ERROR: &str = "LINT_PARSE_ERROR";
+    pub const LINT_CONFIG_INVALID: &str = "LINT_CONFIG_INVALID";
+}
+
+/// Error type for linting.
+#[derive(Error, Debug)]
+pub enum LintError {
+    #[error("failed to access {path}: {source}")]
+    Io {
+        path: PathBuf,
+        source: std::io::Error,
+    },
+
+    #[error("no such file or directory: {path}")]
+    PathNotFound { path: PathBuf },
+
+    #[error("{path}:{line}:{column}: {message}")]
+    Parse {
+        path: PathBuf,
+        line: usize,
+        column: usize,
+        message: String,
+    },
+
+    #[error("invalid howth.lint in package.json: {message}")]
+    ConfigInvalid { message: String },
+}
+
+impl LintError {
+    /// Get the stable error code.
+    #[must_use]
+    pub fn code(&self) -> &'static str {
+        match self {
+            Self::Io { .. } => codes::LINT_IO,
+            Self::PathNotFound { .. } => codes::LINT_PATH_NOT_FOUND,
+            Self::Parse { .. } => codes::LINT_PARSE_ERROR,
+            Self::ConfigInvalid { .. } => codes::LINT_CONFIG_INVALID,
+        }
+    }
+}
+
+/// How a rule's findings are reported.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Severity {
+    Off,
+    Warn,
+    Error,
+}
+
+/// A lint rule.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum Rule {
+    NoUnusedVars,
+    NoUndef,
+    NoDebugger,
+    Eqeqeq,
+    NoDupeKeys,
+}
+
+impl Rule {
+    /// Every rule, in documentation order.
+    pub const ALL: &'static [Rule] = &[
+        Self::NoUnusedVars,
+        Self::NoUndef,
+        Self::NoDebugger,
+        Self::Eqeqeq,
+        Self::NoDupeKeys,
+    ];
+
+    /// The rule's name, as used in config and output.
+    #[must_use]
+    pub fn name(self) -> &'static str {
+        match self {
+            Self::NoUnusedVars => "no-unused-vars",
+            Self::NoUndef => "no-undef",
+            Self::NoDebugger => "no-debugger",
+            Self::Eqeqeq => "eqeqeq",
+            Self::NoDupeKeys => "no-dupe-keys",
+        }
+    }
+
+    /// Look up a rule by name.
+    #[must_use]
+    pub fn from_name(name: &str) -> Option<Self> {
+        Self::ALL.iter().copied().find(|rule| rule.name() == name)
+    }
+
+    /// Severity when the config doesn't set one.
+    #[must_use]
+    pub fn default_severity(self) -> Severity {
+        match self {
+            Self::NoUnusedVars | Self::Eqeqeq => Severity::Warn,
+            Self::NoUndef | Self::NoDebugger | Self::NoDupeKeys => Severity::Error,
+        }
+    }
+}
+
+impl Serialize for Rule {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_str(self.name())
+    }
+}
+
+/// Lint settings.
+#[derive(Debug, Clone, Default)]
+pub struct LintConfig {
+    /// Severity overrides by rule.
+    pub rules: BTreeMap<Rule, Severity>,
+    /// Extra globals for `no-undef`.
+    pub globals: Vec<String>,
+}
+
+/// The `howth.lint` section of package.json.
+#[derive(Debug, Default, Deserialize)]
+#[serde(deny_unknown_fields)]
+struct LintConfigJson {
+    #[serde(default)]
+    rules: BTreeMap<String, Severity>,
+    #[serde(default)]
+    globals: Vec<String>,
+}
+
+impl LintConfig {
+    /// Read the `howth.lint` section of `cwd/package.json`. A missing file
+    /// or section gives the defaults.
+    pub fn load(cwd: &Path) -> Result<Self, LintError> {
+        let path = cwd.join("package.json");
+        let Ok(text) = std::fs::read_to_string(&path) else {
+            return Ok(Self::default());
+        };
+        let invalid = |message: String| LintError::ConfigInvalid { message };
+        let pkg_json: serde_json::Value =
+            serde_json::from_str(&text).map_err(|e| invalid(e.to_string()))?;
+        let Some(lint) = pkg_json.get("howth").and_then(|h| h.get("lint")) else {
+            return Ok(Self::default());
+        };
+        let json: LintConfigJson =
+            serde_json::from_value(lint.clone()).map_err(|e| invalid(e.to_string()))?;
+
+        let mut rules = BTreeMap::new();
+        for (name, severity) in json.rules {
+            let rule = Rule::from_name(&name).ok_or_else(|| {
+                let known: Vec<_> = Rule::ALL.iter().map(|rule| rule.name()).collect();
+                invalid(format!(
+                    "unknown rule \"{name}\" (known rules: {})",
+                    known.join(", ")
+                ))
+            })?;
+            rules.insert(rule, severity);
+        }
+        Ok(Self {
+            rules,
+            globals: json.globals,
+        })
+    }
+
+    /// The severity `rule` runs at.
+    #[must_use]
+    pub fn severity(&self, rule: Rule) -> Severity {
+        self.rules
+            .get(&rule)
+            .copied()
+            .unwrap_or_else(|| rule.default_severity())
+    }
+}
+
+/// A text edit that fixes a diagnostic.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Fix {
+    pub start: u32,
+    pub end: u32,
+    pub replacement: String,
+}
+
+/// One reported problem.
+#[derive(Debug, Clone, Serialize)]
+pub struct Diagnostic {
+    pub rule: Rule,
+    pub severity: Severity,
+    pub message: String,
+    /// 1-based.
+    pub line: usize,
+    /// 1-based, in bytes.
+    pub column: usize,
+    pub fixable: bool,
+    #[serde(skip)]
+    pub fix: Option<Fix>,
+}
+
+/// Diagnostics for one file.
+#[derive(Debug, Clone)]
+pub struct FileReport {
+    pub path: PathBuf,
+    pub diagnostics: Vec<Diagnostic>,
+    /// Fixes applied with `fix`.
+    pub fixed: usize,
+}
+
+/// Expand `paths` (relative to `cwd`) into the files to lint; see
+/// [`fmt::collect_files`].
+pub fn collect_files(cwd: &Path, paths: &[PathBuf]) -> Result<Vec<PathBuf>, LintError> {
+    fmt::collect_files(cwd, paths).map_err(|e| match e {
+        FmtError::Io { path, source } => LintError::Io { path, source },
+        FmtError::PathNotFound { path } => LintError::PathNotFound { path },
+        FmtError::Unformattable { .. } => unreachable!("collecting files doesn't format"),
+    })
+}
+
+/// Lint one file's source. `path` picks the syntax and labels errors.
+pub fn lint_source(
+    path: &Path,
+    source: &str,
+    config: &LintConfig,
+) -> Result<Vec<Diagnostic>, LintError> {
+    let options = fmt::parser_options(path);
+    let typescript = options.typescript;
+    let ast = Parser::new(source, options).parse().map_err(|e| {
+        let (line, column) = fmt::line_column(source, e.span.start as usize);
+        LintError::Parse {
+            path: path.to_path_buf(),
+            line,
+            column,
+            message: e.message,
+        }
+    })?;
+
+    let mut findings = Vec::new();
+    let scopes = scope::analyze(&ast.stmts, source, &mut |node| {
+        rules::check_node(&node, source, &mut findings);
+    });
+    rules::unused_vars(&scopes, &mut findings);
+    if !typescript {
+        rules::undefined(&scopes, &config.globals, &mut findings);
+    }
+
+    let mut diagnostics: Vec<(u32, Diagnostic)> = findings
+        .into_iter()
+        .filter_map(|finding| {
+            let severity = config.severity(finding.rule);
+            if severity == Severity::Off {
+                return None;
+            }
+            let (line, column) = fmt::line_column(source, finding.start as usize);
+            let diagnostic = Diagnostic {
+                rule: finding.rule,
+                severity,
+                message: finding.message,
+                line,
+                column,
+                fixable: finding.fix.is_some(),
+                fix: finding.fix,
+            };
+            Some((finding.start, diagnostic))
+        })
+        .collect();
+    diagnostics.sort_by_key(|(start, diagnostic)| (*start, diagnostic.rule));
+    Ok(diagnostics.into_iter().map(|(_, d)| d).collect())
+}
+
+/// Apply the fixes of `diagnostics` to `source`, skipping fixes that
+/// overlap an earlier one. Returns the new source and the fixes applied.
+#[must_use]
+pub fn apply_fixes(source: &str, diagnostics: &[Diagnostic]) -> (String, usize) {
+    let mut fixes: Vec<&Fix> = diagnostics.iter().filter_map(|d| d.fix.as_ref()).collect();
+    fixes.sort_by_key(|fix| (fix.start, fix.end));
+
+    let mut out = String::with_capacity(source.len());
+    let mut pos = 0;
+    let mut applied = 0;
+    for fix in fixes {
+        let (start, end) = (fix.start as usize, fix.end as usize);
+        if start < pos {
+            continue;
+        }
+        out.push_str(&source[pos..start]);
+        out.push_str(&fix.replacement);
+        pos = end;
+        applied += 1;
+    }
+    out.push_str(&source[pos..]);
+    (out, applied)
+}
+
+/// Lint `path`. With `fix`, fixable problems are fixed in place and the
+/// remaining ones reported.
+pub fn lint_file(path: &Path, config: &LintConfig, fix: bool) -> Result<FileReport, LintError> {
+    let io = |source| LintError::Io {
+        path: path.to_path_buf(),
+        source,
+    };
+    let source = std::fs::read_to_string(path).map_err(io)?;
+    let mut diagnostics = lint_source(path, &source, config)?;
+    let mut fixed = 0;
+    if fix && diagnostics.iter().any(|d| d.fix.is_some()) {
+        let (fixed_source, applied) = apply_fixes(&source, &diagnostics);
+        std::fs::write(path, &fixed_source).map_err(io)?;
+        diagnostics = lint_source(path, &fixed_source, config)?;
+        fixed = applied;
+    }
+    Ok(FileReport {
+        path: path.to_path_buf(),
+        diagnostics,
+        fixed,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::fs;
+
+    fn lint(source: &str) -> Vec<String> {
+        lint_as("a.js", source)
+    }
+
+    fn lint_as(path: &str, source: &str) -> Vec<String> {
+        lint_source(Path::new(path), source, &LintConfig::default())
+            .unwrap()
+            .iter()
+            .map(|d| format!("{}:{} {} {}", d.line, d.column, d.rule.name(), d.message))
+            .collect()
+    }
+
+    #[test]
+    fn test_no_unused_vars() {
+        let out = lint(
+            "import { a, b } from 'm';\n\
+             const c = 1, _d = 2;\n\
+             function f(x, y, z) { return y; }\n\
+             export function g() { f(a); }\n\
+             for (const k in {}) {}\n",
+        );
+        assert_eq!(
+            out,
+            vec![
+                "1:13 no-unused-vars 'b' is defined but never used.",
+                "2:7 no-unused-vars 'c' is assigned a value but never used.",
+                "3:18 no-unused-vars 'z' is defined but never used.",
+                "5:12 no-unused-vars 'k' is assigned a value but never used.",
+            ]
+        );
+    }
+
+    #[test]
+    fn test_no_undef() {
+        let out = lint(
+            "let x = y + z;\n\
+             console.log(x, typeof missing, process.env);\n\
+             function f() { return hoisted; var hoisted = 1; }\n\
+             f();\n\
+             {\n  let inner = 1;\n}\n\
+             inner;\n",
+        );
+        assert_eq!(
+            out,
+            vec![
+                "1:9 no-undef 'y' is not defined.",
+                "1:13 no-undef 'z' is not defined.",
+                "6:7 no-unused-vars 'inner' is assigned a value but never used.",
+                "8:1 no-undef 'inner' is not defined.",
+            ]
+        );
+    }
+
+    #[test]
+    fn test_single_node_rules() {
+        let out = lint(
+            "const o = { a: 1, 'a': 2, get b() { return 1; }, set b(v) {}, b: 3 };\n\
+             if (o == null) debugger;\n\
+             export { o };\n",
+        );
+        assert_eq!(
+            out,
+            vec![
+                "1:19 no-dupe-keys Duplicate key 'a'.",
+                "1:63 no-dupe-keys Duplicate key 'b'.",
+                "2:7 eqeqeq Expected '===' and instead saw '=='.",
+                "2:16 no-debugger Unexpected 'debugger' statement.",
+            ]
+        );
+    }
+
+    #[test]
+    fn test_typescript_and_jsx() {
+        let out = lint_as(
+            "a.tsx",
+            "import React from 'react';\n\
+             import type { Props, Unused } from './types';\n\
+             enum Color { Red }\n\
+             export class C {\n  constructor(private readonly p: Props, q: number) { this.p; }\n}\n\
+             export const App = ({ name }: Props) => <Box title={name} />;\n",
+        );
+        assert_eq!(
+            out,
+            vec![
+                "2:22 no-unused-vars 'Unused' is defined but never used.",
+                "3:6 no-unused-vars 'Color' is defined but never used.",
+                "5:42 no-unused-vars 'q' is defined but never used.",
+            ]
+        );
+    }
+
+    #[test]
+    fn test_config_severity_and_globals() {
+        let dir = tempfile::tempdir().unwrap();
+        fs::write(
+            dir.path().join("package.json"),
+            r#"{"howth": {"lint": {"rules": {"eqeqeq": "off", "no-debugger": "warn"}, "globals": ["__DEV__"]}}}"#,
+        )
+        .unwrap();
+        let config = LintConfig::load(dir.path()).unwrap();
+        let diagnostics = lint_source(
+            Path::new("a.js"),
+            "if (__DEV__ == 1) { debugger; }",
+            &config,
+        )
+        .unwrap();
+        assert_eq!(diagnostics.len(), 1);
+        assert_eq!(diagnostics[0].rule, Rule::NoDebugger);
+        assert_eq!(diagnostics[0].severity, Severity::Warn);
+
+        fs::write(
+            dir.path().join("package.json"),
+            r#"{"howth": {"lint": {"rules": {"no-console": "error"}}}}"#,
+        )
+        .unwrap();
+        let err = LintConfig::load(dir.path()).unwrap_err();
+        assert_eq!(err.code(), codes::LINT_CONFIG_INVALID);
+        assert!(err.to_string().contains("unknown rule \"no-console\""));
+    }
+
+    #[test]
+    fn test_fix() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("a.js");
+        fs::write(
+            &path,
+            "export function f(x) {\n  debugger;\n  if (typeof x == 'string' && x != 1) return 1;\n}\n",
+        )
+        .unwrap();
+
+        let report = lint_file(&path, &LintConfig::default(), true).unwrap();
+        assert_eq!(report.fixed, 2);
+        assert_eq!(
+            fs::read_to_string(&path).unwrap(),
+            "export function f(x) {\n  if (typeof x === 'string' && x != 1) return 1;\n}\n"
+        );
+        assert_eq!(report.diagnostics.len(), 1);
+        assert_eq!(report.diagnostics[0].rule, Rule::Eqeqeq);
+        assert!(!report.diagnostics[0].fixable);
+    }
+
+    #[test]
+    fn test_parse_error() {
+        let err = lint_source(Path::new("a.js"), "let = 1;", &LintConfig::default()).unwrap_err();
+        assert_eq!(err.code(), codes::LINT_PARSE_ERROR);
+        assert!(err.to_string().starts_with("a.js:1:5: "), "{err}");
+    }
+}
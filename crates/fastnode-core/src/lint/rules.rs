@@ -0,0 +1,408 @@
+//! The lint rules.
+//!
+//! `no-debugger`, `eqeqeq` and `no-dupe-keys` look at single nodes as the
+//! scope walk reaches them; `no-unused-vars` and `no-undef` read the
+//! resolved scopes afterwards.
+
+use super::scope::{DeclKind, Node, Scopes};
+use super::{Fix, Rule};
+use howth_parser::{
+    BinaryOp, ExprKind, Lexer, PropertyKey, PropertyKind, StmtKind, TokenKind, UnaryOp,
+};
+use std::collections::{HashMap, HashSet};
+
+/// A rule violation before severities and positions are applied.
+pub(super) struct Finding {
+    pub rule: Rule,
+    pub start: u32,
+    pub message: String,
+    pub fix: Option<Fix>,
+}
+
+/// Check the node-local rules against `node`.
+pub(super) fn check_node(node: &Node<'_>, source: &str, out: &mut Vec<Finding>) {
+    match node {
+        Node::Stmt { stmt, in_list } => {
+            if matches!(stmt.kind, StmtKind::Debugger) {
+                out.push(Finding {
+                    rule: Rule::NoDebugger,
+                    start: stmt.span.start,
+                    message: "Unexpected 'debugger' statement.".to_string(),
+                    fix: in_list.then(|| remove_debugger(source, stmt.span.start)),
+                });
+            }
+        }
+        Node::Expr(expr) => match &expr.kind {
+            ExprKind::Binary { op, left, right }
+                if matches!(op, BinaryOp::Eq | BinaryOp::NotEq) =>
+            {
+                let (loose, strict) = if *op == BinaryOp::Eq {
+                    ("==", "===")
+                } else {
+                    ("!=", "!==")
+                };
+                let start = operator_start(source, left.span.start, right.span.start)
+                    .unwrap_or(expr.span.start);
+                // Only fix comparisons whose meaning can't change
+                let safe = is_typeof(&left.kind)
+                    || is_typeof(&right.kind)
+                    || same_literal_type(&left.kind, &right.kind);
+                out.push(Finding {
+                    rule: Rule::Eqeqeq,
+                    start,
+                    message: format!("Expected '{strict}' and instead saw '{loose}'."),
+                    fix: safe.then(|| Fix {
+                        start,
+                        end: start + 2,
+                        replacement: strict.to_string(),
+                    }),
+                });
+            }
+            ExprKind::Object(properties) => {
+                // (plain value, getter, setter) seen per key
+                let mut seen: HashMap<String, (bool, bool, bool)> = HashMap::new();
+                for property in properties {
+                    if property.computed || matches!(property.value.kind, ExprKind::Spread(_)) {
+                        continue;
+                    }
+                    let Some(key) = key_name(&property.key) else {
+                        continue;
+                    };
+                    let entry = seen.entry(key.clone()).or_default();
+                    let duplicate = match property.kind {
+                        PropertyKind::Init | PropertyKind::Method => {
+                            let duplicate = entry.0 || entry.1 || entry.2;
+                            entry.0 = true;
+                            duplicate
+                        }
+                        PropertyKind::Get => {
+                            let duplicate = entry.0 || entry.1;
+                            entry.1 = true;
+                            duplicate
+                        }
+                        PropertyKind::Set => {
+                            let duplicate = entry.0 || entry.2;
+                            entry.2 = true;
+                            duplicate
+                        }
+                    };
+                    if duplicate {
+                        out.push(Finding {
+                            rule: Rule::NoDupeKeys,
+                            start: property.span.start,
+                            message: format!("Duplicate key '{key}'."),
+                            fix: None,
+                        });
+                    }
+                }
+            }
+            _ => {}
+        },
+    }
+}
+
+/// Report variables that are declared but never read.
+///
+/// Parameters are only reported after the last used one, since earlier
+/// ones hold their position. Names starting with `_` are ignored.
+pub(super) fn unused_vars(scopes: &Scopes, out: &mut Vec<Finding>) {
+    let mut trailing_params: HashSet<usize> = HashSet::new();
+    for params in &scopes.param_lists {
+        let last_used = params
+            .iter()
+            .rposition(|&id| scopes.variables[id].reads > 0)
+            .map_or(0, |i| i + 1);
+        trailing_params.extend(&params[last_used..]);
+    }
+
+    for (id, var) in scopes.variables.iter().enumerate() {
+        if var.reads > 0 || var.exported || var.name.starts_with('_') {
+            continue;
+        }
+        let message = match var.kind {
+            DeclKind::Implicit => continue,
+            DeclKind::Param if !trailing_params.contains(&id) => continue,
+            DeclKind::Var if var.writes > 0 => {
+                format!("'{}' is assigned a value but never used.", var.name)
+            }
+            _ => format!("'{}' is defined but never used.", var.name),
+        };
+        out.push(Finding {
+            rule: Rule::NoUnusedVars,
+            start: var.start,
+            message,
+            fix: None,
+        });
+    }
+}
+
+/// Report references to names that aren't declared or known globals.
+pub(super) fn undefined(scopes: &Scopes, globals: &[String], out: &mut Vec<Finding>) {
+    for reference in &scopes.unresolved {
+        let name = reference.name.as_str();
+        if GLOBALS.binary_search(&name).is_ok() || globals.iter().any(|g| g == name) {
+            continue;
+        }
+        out.push(Finding {
+            rule: Rule::NoUndef,
+            start: reference.start,
+            message: format!("'{name}' is not defined."),
+            fix: None,
+        });
+    }
+}
+
+/// Remove a `debugger;` statement, and its line when nothing else is on it.
+fn remove_debugger(source: &str, start: u32) -> Fix {
+    let start = start as usize;
+    let mut end = start + "debugger".len();
+    let rest = &source[end..];
+    let after_spaces = rest.len() - rest.trim_start_matches([' ', '\t']).len();
+    if rest[after_spaces..].starts_with(';') {
+        end += after_spaces + 1;
+    }
+
+    let line_start = source[..start].rfind('\n').map_or(0, |i| i + 1);
+    let line_end = source[end..].find('\n').map_or(source.len(), |i| end + i);
+    let (start, end) =
+        if source[line_start..start].trim().is_empty() && source[end..line_end].trim().is_empty() {
+            (line_start, (line_end + 1).min(source.len()))
+        } else {
+            (start, end)
+        };
+    Fix {
+        start: start as u32,
+        end: end as u32,
+        replacement: String::new(),
+    }
+}
+
+/// Offset of a binary expression's `==`/`!=`: the last one before the
+/// right operand.
+fn operator_start(source: &str, left: u32, right: u32) -> Option<u32> {
+    let text = source.get(left as usize..right as usize)?;
+    let mut lexer = Lexer::new(text);
+    let mut found = None;
+    loop {
+        let token = lexer.next_token();
+        match token.kind {
+            TokenKind::Eof => break,
+            TokenKind::EqEq | TokenKind::BangEq => found = Some(left + token.span.start),
+            _ => {}
+        }
+    }
+    found
+}
+
+fn is_typeof(kind: &ExprKind) -> bool {
+    matches!(
+        kind,
+        ExprKind::Unary {
+            op: UnaryOp::Typeof,
+            ..
+        }
+    )
+}
+
+fn same_literal_type(left: &ExprKind, right: &ExprKind) -> bool {
+    matches!(
+        (left, right),
+        (ExprKind::String(_), ExprKind::String(_))
+            | (ExprKind::Number(_), ExprKind::Number(_))
+            | (ExprKind::Bool(_), ExprKind::Bool(_))
+    )
+}
+
+/// A property key as the string it names (`1`, `"1"` and `1.0` are one key).
+fn key_name(key: &PropertyKey) -> Option<String> {
+    match key {
+        PropertyKey::Ident(name) if name.is_empty() => None,
+        PropertyKey::Ident(name) | PropertyKey::String(name) => Some(name.clone()),
+        PropertyKey::Number(n) => Some(n.to_string()),
+        PropertyKey::Computed(_) => None,
+    }
+}
+
+/// Globals of ES2024, Node.js and browsers, sorted.
+const GLOBALS: &[&str] = &[
+    "AbortController",
+    "AbortSignal",
+    "AggregateError",
+    "Array",
+    "ArrayBuffer",
+    "Atomics",
+    "BigInt",
+    "BigInt64Array",
+    "BigUint64Array",
+    "Blob",
+    "Boolean",
+    "BroadcastChannel",
+    "Buffer",
+    "ByteLengthQueuingStrategy",
+    "CompressionStream",
+    "CountQueuingStrategy",
+    "Crypto",
+    "CryptoKey",
+    "CustomEvent",
+    "DOMException",
+    "DataView",
+    "Date",
+    "DecompressionStream",
+    "Document",
+    "Element",
+    "Error",
+    "EvalError",
+    "Event",
+    "EventSource",
+    "EventTarget",
+    "File",
+    "FileReader",
+    "FinalizationRegistry",
+    "Float32Array",
+    "Float64Array",
+    "FormData",
+    "Function",
+    "HTMLElement",
+    "Headers",
+    "Image",
+    "Infinity",
+    "Int16Array",
+    "Int32Array",
+    "Int8Array",
+    "IntersectionObserver",
+    "Intl",
+    "JSON",
+    "KeyboardEvent",
+    "Map",
+    "Math",
+    "MessageChannel",
+    "MessageEvent",
+    "MessagePort",
+    "MouseEvent",
+    "MutationObserver",
+    "NaN",
+    "Node",
+    "Number",
+    "Object",
+    "Performance",
+    "Promise",
+    "Proxy",
+    "RangeError",
+    "ReadableStream",
+    "ReferenceError",
+    "Reflect",
+    "RegExp",
+    "Request",
+    "ResizeObserver",
+    "Response",
+    "Set",
+    "SharedArrayBuffer",
+    "String",
+    "Symbol",
+    "SyntaxError",
+    "TextDecoder",
+    "TextDecoderStream",
+    "TextEncoder",
+    "TextEncoderStream",
+    "TransformStream",
+    "TypeError",
+    "URIError",
+    "URL",
+    "URLSearchParams",
+    "Uint16Array",
+    "Uint32Array",
+    "Uint8Array",
+    "Uint8ClampedArray",
+    "WeakMap",
+    "WeakRef",
+    "WeakSet",
+    "WebAssembly",
+    "WebSocket",
+    "Worker",
+    "WritableStream",
+    "XMLHttpRequest",
+    "__dirname",
+    "__filename",
+    "addEventListener",
+    "alert",
+    "arguments",
+    "atob",
+    "btoa",
+    "cancelAnimationFrame",
+    "clearImmediate",
+    "clearInterval",
+    "clearTimeout",
+    "confirm",
+    "console",
+    "crypto",
+    "customElements",
+    "decodeURI",
+    "decodeURIComponent",
+    "document",
+    "encodeURI",
+    "encodeURIComponent",
+    "escape",
+    "eval",
+    "exports",
+    "fetch",
+    "getComputedStyle",
+    "global",
+    "globalThis",
+    "history",
+    "isFinite",
+    "isNaN",
+    "localStorage",
+    "location",
+    "matchMedia",
+    "module",
+    "navigator",
+    "parseFloat",
+    "parseInt",
+    "performance",
+    "process",
+    "queueMicrotask",
+    "removeEventListener",
+    "reportError",
+    "requestAnimationFrame",
+    "requestIdleCallback",
+    "require",
+    "self",
+    "sessionStorage",
+    "setImmediate",
+    "setInterval",
+    "setTimeout",
+    "structuredClone",
+    "undefined",
+    "unescape",
+    "window",
+];
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_globals_sorted() {
+        assert!(
+            GLOBALS.windows(2).all(|w| w[0] < w[1]),
+            "GLOBALS must be sorted"
+        );
+    }
+
+    #[test]
+    fn test_remove_debugger_line() {
+        let source = "a();\n  debugger;\nb();\n";
+        let fix = remove_debugger(source, 7);
+        assert_eq!((fix.start, fix.end), (5, 17));
+
+        let source = "a(); debugger; // why\n";
+        let fix = remove_debugger(source, 5);
+        assert_eq!(&source[fix.start as usize..fix.end as usize], "debugger;");
+    }
+
+    #[test]
+    fn test_operator_start() {
+        let source = "(a == b) != /* == */ c";
+        assert_eq!(operator_start(source, 0, 21), Some(9));
+    }
+}
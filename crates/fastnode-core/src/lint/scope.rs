@@ -0,0 +1,1056 @@
+//! Scope analysis for the linter.
+//!
+//! Walks a module once, declaring every binding in the scope it belongs to
+//! (`var` hoists to the enclosing function) and recording every identifier
+//! reference. References are resolved after the walk, so hoisted and
+//! later-declared names resolve the way the engine would. TypeScript type
+//! positions count as reads of the names they mention but are never
+//! reported as undefined, since ambient and lib types aren't declared here.
+
+use howth_parser::{
+    ArrowBody, Binding, BindingKind, Class, ClassMemberKind, ExportDecl, Expr, ExprKind, ForInit,
+    Function, ImportSpecifier, MethodKind, Param, PropertyKey, Stmt, StmtKind, UnaryOp, VarKind,
+};
+use std::collections::HashMap;
+
+type ScopeId = usize;
+
+/// A node passed to rule callbacks during the walk.
+pub(super) enum Node<'a> {
+    /// A statement; `in_list` is false when it's the sole body of an `if`,
+    /// loop or label, where removing it would change the program.
+    Stmt {
+        stmt: &'a Stmt,
+        in_list: bool,
+    },
+    Expr(&'a Expr),
+}
+
+/// What declared a variable.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(super) enum DeclKind {
+    Var,
+    Function,
+    Class,
+    Param,
+    Catch,
+    Import,
+    /// Type alias or interface.
+    Type,
+    /// Enum or namespace.
+    Enum,
+    /// Never reported unused: `declare`d names, function and class
+    /// expression names, type parameters and parameter properties.
+    Implicit,
+}
+
+/// A declared variable.
+#[derive(Debug)]
+pub(super) struct Variable {
+    pub name: String,
+    pub kind: DeclKind,
+    /// Offset of the declared name.
+    pub start: u32,
+    pub reads: usize,
+    /// Initializers and assignments.
+    pub writes: usize,
+    pub exported: bool,
+}
+
+/// A reference that didn't resolve to a declaration.
+#[derive(Debug)]
+pub(super) struct Unresolved {
+    pub name: String,
+    pub start: u32,
+}
+
+/// Result of analyzing a module.
+#[derive(Debug, Default)]
+pub(super) struct Scopes {
+    pub variables: Vec<Variable>,
+    /// Parameters of each function with a body, in order.
+    pub param_lists: Vec<Vec<usize>>,
+    /// Value references that resolve to nothing, minus `typeof x` checks.
+    pub unresolved: Vec<Unresolved>,
+}
+
+struct Reference {
+    name: String,
+    start: u32,
+    scope: ScopeId,
+    read: bool,
+    write: bool,
+    /// Reported when unresolved (false for `typeof x` and type positions).
+    reportable: bool,
+}
+
+struct Analyzer<'a, 'f> {
+    source: &'a str,
+    parents: Vec<Option<ScopeId>>,
+    /// Function (or module) scopes, where `var` hoists to.
+    hoist_targets: Vec<bool>,
+    declared: HashMap<(ScopeId, String), usize>,
+    references: Vec<Reference>,
+    scopes: Scopes,
+    on_node: &'f mut dyn FnMut(Node<'a>),
+}
+
+/// Analyze `stmts`, calling `on_node` for every statement and expression.
+pub(super) fn analyze<'a>(
+    stmts: &'a [Stmt],
+    source: &'a str,
+    on_node: &mut dyn FnMut(Node<'a>),
+) -> Scopes {
+    let mut analyzer = Analyzer {
+        source,
+        parents: vec![None],
+        hoist_targets: vec![true],
+        declared: HashMap::new(),
+        references: Vec::new(),
+        scopes: Scopes::default(),
+        on_node,
+    };
+    analyzer.stmts(stmts, 0);
+    analyzer.resolve()
+}
+
+impl<'a> Analyzer<'a, '_> {
+    fn add_scope(&mut self, parent: ScopeId, function: bool) -> ScopeId {
+        self.parents.push(Some(parent));
+        self.hoist_targets.push(function);
+        self.parents.len() - 1
+    }
+
+    fn hoist_target(&self, mut scope: ScopeId) -> ScopeId {
+        while !self.hoist_targets[scope] {
+            scope = self.parents[scope].unwrap_or(0);
+        }
+        scope
+    }
+
+    /// Declare `name` in `scope`; redeclarations share one variable.
+    fn declare(&mut self, name: &str, kind: DeclKind, start: u32, scope: ScopeId) -> usize {
+        if let Some(&id) = self.declared.get(&(scope, name.to_string())) {
+            return id;
+        }
+        let id = self.scopes.variables.len();
+        self.scopes.variables.push(Variable {
+            name: name.to_string(),
+            kind,
+            start,
+            reads: 0,
+            writes: 0,
+            exported: false,
+        });
+        self.declared.insert((scope, name.to_string()), id);
+        id
+    }
+
+    /// Declare `name`, locating it in the source after `from` (declaration
+    /// spans start at the keyword, not the name).
+    fn declare_named(&mut self, name: &str, kind: DeclKind, from: u32, scope: ScopeId) -> usize {
+        let start = find_word(self.source, from, name).unwrap_or(from);
+        self.declare(name, kind, start, scope)
+    }
+
+    fn reference(&mut self, name: &str, start: u32, scope: ScopeId, read: bool, write: bool) {
+        if name.starts_with('#') {
+            return;
+        }
+        self.references.push(Reference {
+            name: name.to_string(),
+            start,
+            scope,
+            read,
+            write,
+            reportable: true,
+        });
+    }
+
+    /// A read that's never reported as undefined.
+    fn soft_reference(&mut self, name: &str, start: u32, scope: ScopeId) {
+        self.references.push(Reference {
+            name: name.to_string(),
+            start,
+            scope,
+            read: true,
+            write: false,
+            reportable: false,
+        });
+    }
+
+    fn resolve(mut self) -> Scopes {
+        for reference in std::mem::take(&mut self.references) {
+            let mut scope = Some(reference.scope);
+            let mut found = None;
+            while let Some(id) = scope {
+                if let Some(&var) = self.declared.get(&(id, reference.name.clone())) {
+                    found = Some(var);
+                    break;
+                }
+                scope = self.parents[id];
+            }
+            match found {
+                Some(var) => {
+                    let var = &mut self.scopes.variables[var];
+                    var.reads += usize::from(reference.read);
+                    var.writes += usize::from(reference.write);
+                }
+                None if reference.reportable => self.scopes.unresolved.push(Unresolved {
+                    name: reference.name,
+                    start: reference.start,
+                }),
+                None => {}
+            }
+        }
+        self.scopes
+    }
+
+    // =========================================================================
+    // Statements
+    // =========================================================================
+
+    fn stmts(&mut self, stmts: &'a [Stmt], scope: ScopeId) {
+        for stmt in stmts {
+            self.stmt(stmt, scope, true);
+        }
+    }
+
+    /// A statement in its own block scope when it's a sole body.
+    fn body(&mut self, stmt: &'a Stmt, scope: ScopeId) {
+        let inner = self.add_scope(scope, false);
+        self.stmt(stmt, inner, false);
+    }
+
+    fn stmt(&mut self, stmt: &'a Stmt, scope: ScopeId, in_list: bool) {
+        (self.on_node)(Node::Stmt { stmt, in_list });
+        match &stmt.kind {
+            StmtKind::Var { kind, decls } => {
+                for decl in decls {
+                    let target = self.var_scope(*kind, scope);
+                    let ids = self.binding(&decl.binding, DeclKind::Var, target, scope);
+                    if let Some(init) = &decl.init {
+                        for id in ids {
+                            self.scopes.variables[id].writes += 1;
+                        }
+                        self.expr(init, scope);
+                    }
+                }
+            }
+            StmtKind::Function(func) => {
+                if let Some(name) = &func.name {
+                    self.declare_named(name, DeclKind::Function, func.span.start, scope);
+                }
+                self.function(func, scope, None);
+            }
+            StmtKind::Class(class) => {
+                if let Some(name) = &class.name {
+                    self.declare_named(name, DeclKind::Class, stmt.span.start, scope);
+                }
+                self.class(class, scope);
+            }
+            StmtKind::Block(stmts) => {
+                let inner = self.add_scope(scope, false);
+                self.stmts(stmts, inner);
+            }
+            StmtKind::If {
+                test,
+                consequent,
+                alternate,
+            } => {
+                self.expr(test, scope);
+                self.body(consequent, scope);
+                if let Some(alternate) = alternate {
+                    self.body(alternate, scope);
+                }
+            }
+            StmtKind::Switch {
+                discriminant,
+                cases,
+            } => {
+                self.expr(discriminant, scope);
+                let inner = self.add_scope(scope, false);
+                for case in cases {
+                    if let Some(test) = &case.test {
+                        self.expr(test, inner);
+                    }
+                    self.stmts(&case.consequent, inner);
+                }
+            }
+            StmtKind::For {
+                init,
+                test,
+                update,
+                body,
+            } => {
+                let inner = self.add_scope(scope, false);
+                if let Some(init) = init {
+                    self.for_init(init, inner, false);
+                }
+                for expr in [test, update].into_iter().flatten() {
+                    self.expr(expr, inner);
+                }
+                self.body(body, inner);
+            }
+            StmtKind::ForIn { left, right, body }
+            | StmtKind::ForOf {
+                left, right, body, ..
+            } => {
+                let inner = self.add_scope(scope, false);
+                self.expr(right, scope);
+                self.for_init(left, inner, true);
+                self.body(body, inner);
+            }
+            StmtKind::While { test, body } | StmtKind::DoWhile { body, test } => {
+                self.expr(test, scope);
+                self.body(body, scope);
+            }
+            StmtKind::Return { arg } => {
+                if let Some(arg) = arg {
+                    self.expr(arg, scope);
+                }
+            }
+            StmtKind::Throw { arg } | StmtKind::Expr(arg) => self.expr(arg, scope),
+            StmtKind::Try {
+                block,
+                handler,
+                finalizer,
+            } => {
+                let inner = self.add_scope(scope, false);
+                self.stmts(block, inner);
+                if let Some(handler) = handler {
+                    let inner = self.add_scope(scope, false);
+                    if let Some(param) = &handler.param {
+                        self.binding(param, DeclKind::Catch, inner, inner);
+                    }
+                    self.stmts(&handler.body, inner);
+                }
+                if let Some(finalizer) = finalizer {
+                    let inner = self.add_scope(scope, false);
+                    self.stmts(finalizer, inner);
+                }
+            }
+            StmtKind::Labeled { body, .. } => self.body(body, scope),
+            StmtKind::With { object, body } => {
+                self.expr(object, scope);
+                self.body(body, scope);
+            }
+            StmtKind::Import(import) => {
+                for specifier in &import.specifiers {
+                    let (ImportSpecifier::Default { local, span }
+                    | ImportSpecifier::Namespace { local, span }
+                    | ImportSpecifier::Named { local, span, .. }) = specifier;
+                    self.declare_named(local, DeclKind::Import, span.start, scope);
+                }
+            }
+            StmtKind::Export(export) => match export.as_ref() {
+                ExportDecl::Named {
+                    specifiers,
+                    source: None,
+                    ..
+                } => {
+                    for specifier in specifiers {
+                        self.reference(&specifier.local, specifier.span.start, scope, true, false);
+                    }
+                }
+                ExportDecl::Named { .. } | ExportDecl::All { .. } => {}
+                ExportDecl::Default { expr, .. } => self.expr(expr, scope),
+                ExportDecl::Decl { decl, .. } => {
+                    let first = self.scopes.variables.len();
+                    self.stmt(decl, scope, true);
+                    // Everything the declaration itself declared is exported
+                    for (&(owner, _), &id) in &self.declared {
+                        if owner == scope && id >= first {
+                            self.scopes.variables[id].exported = true;
+                        }
+                    }
+                }
+            },
+            StmtKind::Break { .. }
+            | StmtKind::Continue { .. }
+            | StmtKind::Empty
+            | StmtKind::Debugger => {}
+            StmtKind::TsTypeAlias(alias) => {
+                self.declare_named(&alias.name, DeclKind::Type, alias.span.start, scope);
+                let inner = self.add_scope(scope, false);
+                self.type_params(alias.type_params.as_deref(), inner);
+                self.ts_type(&alias.ty, inner);
+            }
+            StmtKind::TsInterface(interface) => {
+                self.declare_named(&interface.name, DeclKind::Type, interface.span.start, scope);
+                let inner = self.add_scope(scope, false);
+                self.type_params(interface.type_params.as_deref(), inner);
+                for ty in &interface.extends {
+                    self.ts_type(ty, inner);
+                }
+                self.type_members(&interface.body, inner);
+            }
+            StmtKind::TsEnum(ts_enum) => {
+                self.declare_named(&ts_enum.name, DeclKind::Enum, ts_enum.span.start, scope);
+                let inner = self.add_scope(scope, false);
+                for member in &ts_enum.members {
+                    self.declare(&member.name, DeclKind::Implicit, member.span.start, inner);
+                }
+                for member in &ts_enum.members {
+                    if let Some(init) = &member.init {
+                        self.expr(init, inner);
+                    }
+                }
+            }
+            StmtKind::TsNamespace(namespace) => {
+                self.declare_named(&namespace.name, DeclKind::Enum, namespace.span.start, scope);
+                let inner = self.add_scope(scope, true);
+                self.stmts(&namespace.body, inner);
+            }
+            StmtKind::TsDeclare(inner) => self.ambient(inner, scope),
+        }
+    }
+
+    /// Scope a declaration of `kind` made in `scope` belongs to.
+    fn var_scope(&self, kind: VarKind, scope: ScopeId) -> ScopeId {
+        match kind {
+            VarKind::Var => self.hoist_target(scope),
+            _ => scope,
+        }
+    }
+
+    fn for_init(&mut self, init: &'a ForInit, scope: ScopeId, assigned: bool) {
+        match init {
+            ForInit::Var { kind, decls } => {
+                for decl in decls {
+                    let target = self.var_scope(*kind, scope);
+                    let ids = self.binding(&decl.binding, DeclKind::Var, target, scope);
+                    if assigned || decl.init.is_some() {
+                        for id in ids {
+                            self.scopes.variables[id].writes += 1;
+                        }
+                    }
+                    if let Some(init) = &decl.init {
+                        self.expr(init, scope);
+                    }
+                }
+            }
+            ForInit::Expr(expr) if assigned => self.target(expr, scope),
+            ForInit::Expr(expr) => self.expr(expr, scope),
+        }
+    }
+
+    /// Declare the names of a `declare`d statement without walking it.
+    fn ambient(&mut self, stmt: &'a Stmt, scope: ScopeId) {
+        match &stmt.kind {
+            StmtKind::Var { decls, .. } => {
+                for decl in decls {
+                    self.binding(&decl.binding, DeclKind::Implicit, scope, scope);
+                }
+            }
+            StmtKind::Function(func) => {
+                if let Some(name) = &func.name {
+                    self.declare_named(name, DeclKind::Implicit, func.span.start, scope);
+                }
+            }
+            StmtKind::Class(class) => {
+                if let Some(name) = &class.name {
+                    self.declare_named(name, DeclKind::Implicit, stmt.span.start, scope);
+                }
+            }
+            StmtKind::TsEnum(ts_enum) => {
+                self.declare_named(&ts_enum.name, DeclKind::Implicit, ts_enum.span.start, scope);
+            }
+            StmtKind::TsNamespace(namespace) => {
+                let name = &namespace.name;
+                self.declare_named(name, DeclKind::Implicit, namespace.span.start, scope);
+            }
+            StmtKind::Export(export) => {
+                if let ExportDecl::Decl { decl, .. } = export.as_ref() {
+                    self.ambient(decl, scope);
+                }
+            }
+            _ => {}
+        }
+    }
+
+    // =========================================================================
+    // Bindings
+    // =========================================================================
+
+    /// Declare the names in `binding` in `target`; defaults and computed
+    /// keys are evaluated in `scope`. Returns the declared variables.
+    fn binding(
+        &mut self,
+        binding: &'a Binding,
+        kind: DeclKind,
+        target: ScopeId,
+        scope: ScopeId,
+    ) -> Vec<usize> {
+        let mut ids = Vec::new();
+        self.binding_into(binding, kind, target, scope, &mut ids);
+        ids
+    }
+
+    fn binding_into(
+        &mut self,
+        binding: &'a Binding,
+        kind: DeclKind,
+        target: ScopeId,
+        scope: ScopeId,
+        ids: &mut Vec<usize>,
+    ) {
+        match &binding.kind {
+            BindingKind::Ident { name, .. } => {
+                if name != "this" {
+                    ids.push(self.declare(name, kind, binding.span.start, target));
+                }
+            }
+            BindingKind::Array { elements, .. } => {
+                for element in elements.iter().flatten() {
+                    self.binding_into(&element.binding, kind, target, scope, ids);
+                    if let Some(default) = &element.default {
+                        self.expr(default, scope);
+                    }
+                }
+            }
+            BindingKind::Object { properties, .. } => {
+                for property in properties {
+                    if let PropertyKey::Computed(key) = &property.key {
+                        self.expr(key, scope);
+                    }
+                    self.binding_into(&property.value, kind, target, scope, ids);
+                    if let Some(default) = &property.default {
+                        self.expr(default, scope);
+                    }
+                }
+            }
+        }
+        let (BindingKind::Ident { type_ann, .. }
+        | BindingKind::Array { type_ann, .. }
+        | BindingKind::Object { type_ann, .. }) = &binding.kind;
+        if let Some(ty) = type_ann {
+            self.ts_type(ty, scope);
+        }
+    }
+
+    /// An assignment target: names in it are written, not read.
+    fn target(&mut self, expr: &'a Expr, scope: ScopeId) {
+        match &expr.kind {
+            ExprKind::Ident(name) => self.reference(name, expr.span.start, scope, false, true),
+            ExprKind::Array(elements) => {
+                for element in elements.iter().flatten() {
+                    self.target(element, scope);
+                }
+            }
+            ExprKind::Object(properties) => {
+                for property in properties {
+                    if let PropertyKey::Computed(key) = &property.key {
+                        self.expr(key, scope);
+                    }
+                    self.target(&property.value, scope);
+                }
+            }
+            ExprKind::Spread(inner) => self.target(inner, scope),
+            ExprKind::Assign { left, right, .. } => {
+                self.target(left, scope);
+                self.expr(right, scope);
+            }
+            _ => self.expr(expr, scope),
+        }
+    }
+
+    // =========================================================================
+    // Functions and classes
+    // =========================================================================
+
+    /// Walk a function in a new scope. `method` is the method kind for class
+    /// members, whose constructor parameters may be parameter properties.
+    fn function(&mut self, func: &'a Function, scope: ScopeId, method: Option<MethodKind>) {
+        let inner = self.add_scope(scope, true);
+        self.type_params(func.type_params.as_deref(), inner);
+        if let Some(ty) = &func.return_type {
+            self.ts_type(ty, inner);
+        }
+        let params = self.params(&func.params, inner, method == Some(MethodKind::Constructor));
+        if !func.body.is_empty() {
+            self.scopes.param_lists.push(params);
+        }
+        self.stmts(&func.body, inner);
+    }
+
+    fn params(&mut self, params: &'a [Param], scope: ScopeId, constructor: bool) -> Vec<usize> {
+        let mut ids = Vec::new();
+        for param in params {
+            let kind = if constructor && self.is_parameter_property(param) {
+                DeclKind::Implicit
+            } else {
+                DeclKind::Param
+            };
+            ids.extend(self.binding(&param.binding, kind, scope, scope));
+            if let Some(default) = &param.default {
+                self.expr(default, scope);
+            }
+        }
+        ids
+    }
+
+    /// Whether a constructor parameter has an accessibility or `readonly`
+    /// modifier, which makes it a class property.
+    fn is_parameter_property(&self, param: &Param) -> bool {
+        let prefix = self
+            .source
+            .get(param.span.start as usize..param.binding.span.start as usize)
+            .unwrap_or("");
+        prefix.split_whitespace().any(|word| {
+            matches!(
+                word,
+                "public" | "private" | "protected" | "readonly" | "override"
+            )
+        })
+    }
+
+    fn class(&mut self, class: &'a Class, scope: ScopeId) {
+        for decorator in &class.decorators {
+            self.expr(decorator, scope);
+        }
+        if let Some(super_class) = &class.super_class {
+            self.expr(super_class, scope);
+        }
+        let inner = self.add_scope(scope, false);
+        self.type_params(class.type_params.as_deref(), inner);
+        for ty in &class.implements {
+            self.ts_type(ty, inner);
+        }
+        for member in &class.body {
+            for decorator in &member.decorators {
+                self.expr(decorator, inner);
+            }
+            match &member.kind {
+                ClassMemberKind::Method {
+                    key, value, kind, ..
+                } => {
+                    self.property_key(key, inner);
+                    self.function(value, inner, Some(*kind));
+                }
+                ClassMemberKind::Property {
+                    key,
+                    value,
+                    type_ann,
+                    ..
+                } => {
+                    self.property_key(key, inner);
+                    if let Some(ty) = type_ann {
+                        self.ts_type(ty, inner);
+                    }
+                    if let Some(value) = value {
+                        self.expr(value, inner);
+                    }
+                }
+                ClassMemberKind::StaticBlock(stmts) => {
+                    let block = self.add_scope(inner, true);
+                    self.stmts(stmts, block);
+                }
+                ClassMemberKind::Empty => {}
+            }
+        }
+    }
+
+    fn property_key(&mut self, key: &'a PropertyKey, scope: ScopeId) {
+        if let PropertyKey::Computed(expr) = key {
+            self.expr(expr, scope);
+        }
+    }
+
+    // =========================================================================
+    // Expressions
+    // =========================================================================
+
+    fn expr(&mut self, expr: &'a Expr, scope: ScopeId) {
+        (self.on_node)(Node::Expr(expr));
+        match &expr.kind {
+            ExprKind::Ident(name) => self.reference(name, expr.span.start, scope, true, false),
+            ExprKind::Null
+            | ExprKind::Bool(_)
+            | ExprKind::Number(_)
+            | ExprKind::BigInt(_)
+            | ExprKind::String(_)
+            | ExprKind::Regex { .. }
+            | ExprKind::TemplateNoSub(_)
+            | ExprKind::This
+            | ExprKind::Super
+            | ExprKind::MetaProperty { .. } => {}
+            ExprKind::Template { exprs, .. } => {
+                for expr in exprs {
+                    self.expr(expr, scope);
+                }
+            }
+            ExprKind::Array(elements) => {
+                for element in elements.iter().flatten() {
+                    self.expr(element, scope);
+                }
+            }
+            ExprKind::Object(properties) => {
+                for property in properties {
+                    self.property_key(&property.key, scope);
+                    self.expr(&property.value, scope);
+                }
+            }
+            ExprKind::Function(func) => {
+                let inner = self.add_scope(scope, false);
+                if let Some(name) = &func.name {
+                    self.declare(name, DeclKind::Implicit, func.span.start, inner);
+                }
+                self.function(func, inner, None);
+            }
+            ExprKind::Arrow(arrow) => {
+                let inner = self.add_scope(scope, true);
+                self.type_params(arrow.type_params.as_deref(), inner);
+                if let Some(ty) = &arrow.return_type {
+                    self.ts_type(ty, inner);
+                }
+                let params = self.params(&arrow.params, inner, false);
+                match &arrow.body {
+                    ArrowBody::Expr(body) => {
+                        self.scopes.param_lists.push(params);
+                        self.expr(body, inner);
+                    }
+                    ArrowBody::Block(stmts) => {
+                        if !stmts.is_empty() {
+                            self.scopes.param_lists.push(params);
+                        }
+                        self.stmts(stmts, inner);
+                    }
+                }
+            }
+            ExprKind::Class(class) => {
+                let inner = self.add_scope(scope, false);
+                if let Some(name) = &class.name {
+                    self.declare(name, DeclKind::Implicit, class.span.start, inner);
+                }
+                self.class(class, inner);
+            }
+            ExprKind::Unary { op, arg } => match (&arg.kind, op) {
+                // `typeof x` is how code checks for a global that may not exist
+                (ExprKind::Ident(name), UnaryOp::Typeof) => {
+                    (self.on_node)(Node::Expr(arg));
+                    self.soft_reference(name, arg.span.start, scope);
+                }
+                _ => self.expr(arg, scope),
+            },
+            ExprKind::Binary { left, right, .. } => {
+                self.expr(left, scope);
+                self.expr(right, scope);
+            }
+            ExprKind::Assign { op, left, right } => {
+                match (&left.kind, op) {
+                    (_, howth_parser::AssignOp::Assign) => self.target(left, scope),
+                    (ExprKind::Ident(name), _) => {
+                        self.reference(name, left.span.start, scope, true, true);
+                    }
+                    _ => self.expr(left, scope),
+                }
+                self.expr(right, scope);
+            }
+            ExprKind::Update { arg, .. } => match &arg.kind {
+                ExprKind::Ident(name) => self.reference(name, arg.span.start, scope, true, true),
+                _ => self.expr(arg, scope),
+            },
+            ExprKind::Conditional {
+                test,
+                consequent,
+                alternate,
+            } => {
+                self.expr(test, scope);
+                self.expr(consequent, scope);
+                self.expr(alternate, scope);
+            }
+            ExprKind::Sequence(exprs) => {
+                for expr in exprs {
+                    self.expr(expr, scope);
+                }
+            }
+            ExprKind::Member {
+                object,
+                property,
+                computed,
+            }
+            | ExprKind::OptionalMember {
+                object,
+                property,
+                computed,
+            } => {
+                self.expr(object, scope);
+                if *computed {
+                    self.expr(property, scope);
+                }
+            }
+            ExprKind::Call { callee, args }
+            | ExprKind::OptionalCall { callee, args }
+            | ExprKind::New { callee, args } => {
+                self.expr(callee, scope);
+                for arg in args {
+                    self.expr(arg, scope);
+                }
+            }
+            ExprKind::TaggedTemplate { tag, quasi } => {
+                self.expr(tag, scope);
+                self.expr(quasi, scope);
+            }
+            ExprKind::Spread(arg) | ExprKind::Await(arg) | ExprKind::Import(arg) => {
+                self.expr(arg, scope);
+            }
+            ExprKind::Yield { arg, .. } => {
+                if let Some(arg) = arg {
+                    self.expr(arg, scope);
+                }
+            }
+            ExprKind::JsxElement(element) => self.jsx_element(element, scope),
+            ExprKind::JsxFragment(fragment) => {
+                self.soft_reference("React", fragment.span.start, scope);
+                self.jsx_children(&fragment.children, scope);
+            }
+            ExprKind::TsAs { expr, ty }
+            | ExprKind::TsSatisfies { expr, ty }
+            | ExprKind::TsTypeAssertion { ty, expr } => {
+                self.expr(expr, scope);
+                self.ts_type(ty, scope);
+            }
+            ExprKind::TsNonNull(expr) => self.expr(expr, scope),
+        }
+    }
+
+    // =========================================================================
+    // JSX
+    // =========================================================================
+
+    fn jsx_element(&mut self, element: &'a howth_parser::JsxElement, scope: ScopeId) {
+        use howth_parser::{JsxAttrValue, JsxAttribute, JsxElementName};
+
+        // The classic runtime compiles JSX to `React.createElement`
+        self.soft_reference("React", element.span.start, scope);
+        let start = element.opening.span.start + 1;
+        match &element.opening.name {
+            // Lowercase names are intrinsic elements, not variables
+            JsxElementName::Ident(name) if name.starts_with(|c: char| c.is_ascii_uppercase()) => {
+                self.reference(name, start, scope, true, false);
+            }
+            JsxElementName::MemberExpr(parts) => {
+                if let Some(first) = parts.first() {
+                    self.reference(first, start, scope, true, false);
+                }
+            }
+            JsxElementName::Ident(_) | JsxElementName::NamespacedName { .. } => {}
+        }
+        for attribute in &element.opening.attributes {
+            match attribute {
+                JsxAttribute::SpreadAttribute { argument, .. } => self.expr(argument, scope),
+                JsxAttribute::Attribute { value, .. } => match value {
+                    Some(JsxAttrValue::Expr(expr)) => self.expr(expr, scope),
+                    Some(JsxAttrValue::Element(element)) => self.jsx_element(element, scope),
+                    Some(JsxAttrValue::Fragment(fragment)) => {
+                        self.jsx_children(&fragment.children, scope);
+                    }
+                    Some(JsxAttrValue::String(_)) | None => {}
+                },
+            }
+        }
+        self.jsx_children(&element.children, scope);
+    }
+
+    fn jsx_children(&mut self, children: &'a [howth_parser::JsxChild], scope: ScopeId) {
+        use howth_parser::JsxChild;
+
+        for child in children {
+            match child {
+                JsxChild::Element(element) => self.jsx_element(element, scope),
+                JsxChild::Fragment(fragment) => self.jsx_children(&fragment.children, scope),
+                JsxChild::Expr(expr) | JsxChild::Spread(expr) => self.expr(expr, scope),
+                JsxChild::Text(_) => {}
+            }
+        }
+    }
+
+    // =========================================================================
+    // TypeScript types
+    // =========================================================================
+
+    fn type_params(&mut self, params: Option<&'a [howth_parser::TsTypeParam]>, scope: ScopeId) {
+        for param in params.unwrap_or_default() {
+            self.declare(&param.name, DeclKind::Implicit, param.span.start, scope);
+        }
+        for param in params.unwrap_or_default() {
+            for ty in [&param.constraint, &param.default].into_iter().flatten() {
+                self.ts_type(ty, scope);
+            }
+        }
+    }
+
+    fn ts_type(&mut self, ty: &'a howth_parser::TsType, scope: ScopeId) {
+        use howth_parser::TsTypeKind;
+
+        match &ty.kind {
+            TsTypeKind::Reference { name, type_args } => {
+                let first = name.split('.').next().unwrap_or(name);
+                self.soft_reference(first, ty.span.start, scope);
+                for arg in type_args.iter().flatten() {
+                    self.ts_type(arg, scope);
+                }
+            }
+            TsTypeKind::Qualified { left, .. } => self.ts_type(left, scope),
+            TsTypeKind::Array(inner)
+            | TsTypeKind::Parenthesized(inner)
+            | TsTypeKind::Keyof(inner) => {
+                self.ts_type(inner, scope);
+            }
+            TsTypeKind::Tuple(types)
+            | TsTypeKind::Union(types)
+            | TsTypeKind::Intersection(types)
+            | TsTypeKind::Template { types, .. } => {
+                for ty in types {
+                    self.ts_type(ty, scope);
+                }
+            }
+            TsTypeKind::TypeLiteral(members) => self.type_members(members, scope),
+            TsTypeKind::Mapped(mapped) => {
+                let inner = self.add_scope(scope, false);
+                self.type_params(Some(std::slice::from_ref(&mapped.type_param)), inner);
+                for ty in [&mapped.name_type, &mapped.type_ann].into_iter().flatten() {
+                    self.ts_type(ty, inner);
+                }
+            }
+            TsTypeKind::Indexed { object, index } => {
+                self.ts_type(object, scope);
+                self.ts_type(index, scope);
+            }
+            TsTypeKind::Function(func) | TsTypeKind::Constructor(func) => {
+                let inner = self.add_scope(scope, false);
+                self.type_params(func.type_params.as_deref(), inner);
+                for param in &func.params {
+                    self.ts_type(&param.ty, inner);
+                }
+                self.ts_type(&func.return_type, inner);
+            }
+            TsTypeKind::Conditional {
+                check,
+                extends,
+                true_type,
+                false_type,
+            } => {
+                for ty in [check, extends, true_type, false_type] {
+                    self.ts_type(ty, scope);
+                }
+            }
+            TsTypeKind::Infer { param } => {
+                for ty in [&param.constraint, &param.default].into_iter().flatten() {
+                    self.ts_type(ty, scope);
+                }
+            }
+            TsTypeKind::Typeof(expr) => self.expr(expr, scope),
+            TsTypeKind::TypePredicate { ty, .. } => self.ts_type(ty, scope),
+            TsTypeKind::Import { type_args, .. } => {
+                for arg in type_args.iter().flatten() {
+                    self.ts_type(arg, scope);
+                }
+            }
+            TsTypeKind::Any
+            | TsTypeKind::Unknown
+            | TsTypeKind::Never
+            | TsTypeKind::Void
+            | TsTypeKind::Null
+            | TsTypeKind::Undefined
+            | TsTypeKind::Boolean
+            | TsTypeKind::Number
+            | TsTypeKind::String
+            | TsTypeKind::Symbol
+            | TsTypeKind::BigInt
+            | TsTypeKind::Object
+            | TsTypeKind::LitBoolean(_)
+            | TsTypeKind::LitNumber(_)
+            | TsTypeKind::LitString(_)
+            | TsTypeKind::This => {}
+        }
+    }
+
+    fn type_members(&mut self, members: &'a [howth_parser::TsTypeMember], scope: ScopeId) {
+        use howth_parser::TsTypeMemberKind;
+
+        for member in members {
+            match &member.kind {
+                TsTypeMemberKind::Property { key, type_ann, .. } => {
+                    self.property_key(key, scope);
+                    if let Some(ty) = type_ann {
+                        self.ts_type(ty, scope);
+                    }
+                }
+                TsTypeMemberKind::Index {
+                    param, type_ann, ..
+                } => {
+                    self.ts_type(&param.ty, scope);
+                    self.ts_type(type_ann, scope);
+                }
+                TsTypeMemberKind::Method {
+                    key,
+                    params,
+                    type_params,
+                    return_type,
+                    ..
+                } => {
+                    self.property_key(key, scope);
+                    self.signature(
+                        params,
+                        type_params.as_deref(),
+                        return_type.as_deref(),
+                        scope,
+                    );
+                }
+                TsTypeMemberKind::CallSignature {
+                    params,
+                    type_params,
+                    return_type,
+                }
+                | TsTypeMemberKind::ConstructSignature {
+                    params,
+                    type_params,
+                    return_type,
+                } => {
+                    self.signature(
+                        params,
+                        type_params.as_deref(),
+                        return_type.as_deref(),
+                        scope,
+                    );
+                }
+            }
+        }
+    }
+
+    fn signature(
+        &mut self,
+        params: &'a [howth_parser::TsFnParam],
+        type_params: Option<&'a [howth_parser::TsTypeParam]>,
+        return_type: Option<&'a howth_parser::TsType>,
+        scope: ScopeId,
+    ) {
+        let inner = self.add_scope(scope, false);
+        self.type_params(type_params, inner);
+        for param in params {
+            self.ts_type(&param.ty, inner);
+        }
+        if let Some(ty) = return_type {
+            self.ts_type(ty, inner);
+        }
+    }
+}
+
+/// Offset of the first whole-word `word` at or after `from`.
+fn find_word(source: &str, from: u32, word: &str) -> Option<u32> {
+    let is_ident = |c: char| c.is_alphanumeric() || c == '_' || c == '$';
+    let rest = source.get(from as usize..)?;
+    let mut offset = 0;
+    while let Some(i) = rest[offset..].find(word) {
+        let at = offset + i;
+        let before = rest[..at].chars().next_back();
+        let after = rest[at + word.len()..].chars().next();
+        if !before.is_some_and(is_ident) && !after.is_some_and(is_ident) {
+            return Some(from + at as u32);
+        }
+        offset = at + word.len();
+    }
+    None
+}
@@ -42,11 +42,18 @@ pub fn cache_dir(channel: Channel) -> PathBuf {
         .join(channel.as_str())
 }
 
+/// Get the global package prefix (`howth pkg add --global`).
+///
+/// Lives under the channel cache directory: `{cache_dir}/global`.
+#[must_use]
+pub fn global_dir(channel: Channel) -> PathBuf {
+    cache_dir(channel).join("global")
+}
+
 /// Environment variable to override the IPC endpoint (for testing).
 pub const IPC_ENDPOINT_ENV: &str = "HOWTH_IPC_ENDPOINT";
 
 /// Prefix for Windows named pipes.
-#[cfg(windows)]
 const PIPE_PREFIX: &str = r"\\.\pipe\";
 
 /// Normalize a Windows named pipe endpoint.
@@ -70,13 +77,18 @@ fn normalize_pipe_endpoint(endpoint: &str) -> String {
 /// if not already present. This allows setting `HOWTH_IPC_ENDPOINT=my-pipe`
 /// instead of the full `\\.\pipe\my-pipe`.
 ///
-/// Platform-specific defaults:
-/// - Unix: `{data_dir}/ipc/howth.sock`
-/// - Windows: `\\.\pipe\howth-{channel}-v{N}`
+/// Endpoints are namespaced per user and channel, so daemons of different
+/// users on one machine never share a socket:
+/// - Unix: `{data_dir}/ipc/howth-{uid}.sock`
+/// - Windows: `\\.\pipe\howth-{user}-{channel}-v{N}` (pipe names are machine-wide)
 #[must_use]
 pub fn ipc_endpoint(channel: Channel) -> String {
-    // Check env override first (for testing)
-    if let Ok(endpoint) = std::env::var(IPC_ENDPOINT_ENV) {
+    endpoint_for(channel, std::env::var(IPC_ENDPOINT_ENV).ok())
+}
+
+/// The IPC endpoint for `channel`, or `endpoint_override` when set.
+fn endpoint_for(channel: Channel, endpoint_override: Option<String>) -> String {
+    if let Some(endpoint) = endpoint_override {
         #[cfg(windows)]
         {
             return normalize_pipe_endpoint(&endpoint);
@@ -87,35 +99,87 @@ pub fn ipc_endpoint(channel: Channel) -> String {
         }
     }
 
-    #[cfg(unix)]
-    {
-        let dir = data_dir(channel).join("ipc");
-        dir.join("howth.sock").to_string_lossy().into_owned()
-    }
-
     #[cfg(windows)]
     {
-        format!(r"\\.\pipe\howth-{}-v{}", channel.as_str(), SCHEMA_VERSION)
+        pipe_name(&current_user(), channel)
     }
 
-    #[cfg(not(any(unix, windows)))]
+    #[cfg(not(windows))]
     {
-        // Fallback for other platforms
-        let dir = data_dir(channel).join("ipc");
-        dir.join("howth.sock").to_string_lossy().into_owned()
+        #[cfg(unix)]
+        let uid = Some(current_uid());
+        #[cfg(not(unix))]
+        let uid = None;
+        data_dir(channel)
+            .join("ipc")
+            .join(socket_file_name(uid))
+            .to_string_lossy()
+            .into_owned()
     }
 }
 
+/// Name of the daemon's named pipe for `user` on `channel`.
+#[cfg_attr(not(windows), allow(dead_code))]
+fn pipe_name(user: &str, channel: Channel) -> String {
+    format!(
+        "{PIPE_PREFIX}howth-{}-{}-v{SCHEMA_VERSION}",
+        sanitize_user(user),
+        channel.as_str()
+    )
+}
+
+/// File name of the daemon socket for `uid`.
+#[cfg_attr(windows, allow(dead_code))]
+fn socket_file_name(uid: Option<u32>) -> String {
+    uid.map_or_else(
+        || "howth.sock".to_string(),
+        |uid| format!("howth-{uid}.sock"),
+    )
+}
+
+/// Lowercase `user` and replace characters that aren't safe in an endpoint
+/// name with `_`.
+#[cfg_attr(not(windows), allow(dead_code))]
+fn sanitize_user(user: &str) -> String {
+    user.chars()
+        .map(|c| {
+            if c.is_ascii_alphanumeric() || matches!(c, '-' | '_' | '.') {
+                c.to_ascii_lowercase()
+            } else {
+                '_'
+            }
+        })
+        .collect()
+}
+
+/// Effective user ID of this process.
+#[cfg(unix)]
+fn current_uid() -> u32 {
+    // SAFETY: geteuid has no preconditions and cannot fail
+    unsafe { libc::geteuid() }
+}
+
+/// Name of the current user (`%USERNAME%`).
+#[cfg(windows)]
+fn current_user() -> String {
+    std::env::var("USERNAME").unwrap_or_else(|_| "default".to_string())
+}
+
 /// Ensure the IPC socket directory exists (Unix only).
 ///
+/// The directory is restricted to its owner (`0700`).
+///
 /// # Errors
 /// Returns an error if the directory cannot be created.
 #[allow(unused_variables)]
 pub fn ensure_ipc_dir(channel: Channel) -> std::io::Result<()> {
     #[cfg(unix)]
     {
+        use std::os::unix::fs::PermissionsExt;
+
         let dir = data_dir(channel).join("ipc");
-        std::fs::create_dir_all(dir)?;
+        std::fs::create_dir_all(&dir)?;
+        std::fs::set_permissions(&dir, std::fs::Permissions::from_mode(0o700))?;
     }
     Ok(())
 }
@@ -142,6 +206,24 @@ pub fn data_dir(channel: Channel) -> PathBuf {
         .join(channel.as_str())
 }
 
+/// Get the daemon log file: `{data_dir}/logs/daemon.log`.
+///
+/// The daemon rotates it to `daemon.log.1` when it grows too large.
+#[must_use]
+pub fn daemon_log_path(channel: Channel) -> PathBuf {
+    data_dir(channel).join("logs").join("daemon.log")
+}
+
+/// Get the root of the temporary directories made by `test.tmpdir()`:
+/// `{temp_dir}/howth-test-tmp`.
+///
+/// Each test's directory is removed when the test ends, unless
+/// `howth test --keep-tmp` keeps them for inspection.
+#[must_use]
+pub fn test_tmp_dir() -> PathBuf {
+    std::env::temp_dir().join("howth-test-tmp")
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -186,6 +268,12 @@ mod tests {
         assert!(dir_str.contains("stable"));
     }
 
+    #[test]
+    fn test_global_dir_is_under_cache_dir() {
+        let dir = global_dir(Channel::Stable);
+        assert!(dir.starts_with(cache_dir(Channel::Stable)));
+    }
+
     #[test]
     fn test_data_dir_contains_version() {
         let dir = data_dir(Channel::Dev);
@@ -207,10 +295,7 @@ mod tests {
 
     #[test]
     fn test_ipc_endpoint_contains_channel() {
-        // Clear env var to test default behavior
-        std::env::remove_var(IPC_ENDPOINT_ENV);
-
-        let endpoint = ipc_endpoint(Channel::Stable);
+        let endpoint = endpoint_for(Channel::Stable, None);
 
         #[cfg(unix)]
         assert!(
@@ -229,23 +314,16 @@ mod tests {
     #[cfg(unix)]
     fn test_ipc_endpoint_env_override() {
         let test_endpoint = "/tmp/test-howth.sock";
-        std::env::set_var(IPC_ENDPOINT_ENV, test_endpoint);
 
-        let endpoint = ipc_endpoint(Channel::Stable);
+        let endpoint = endpoint_for(Channel::Stable, Some(test_endpoint.to_string()));
         assert_eq!(endpoint, test_endpoint);
-
-        // Clean up
-        std::env::remove_var(IPC_ENDPOINT_ENV);
     }
 
     #[test]
     fn test_different_channels_different_ipc_endpoints() {
-        // Clear env var to test default behavior
-        std::env::remove_var(IPC_ENDPOINT_ENV);
-
-        let stable = ipc_endpoint(Channel::Stable);
-        let nightly = ipc_endpoint(Channel::Nightly);
-        let dev = ipc_endpoint(Channel::Dev);
+        let stable = endpoint_for(Channel::Stable, None);
+        let nightly = endpoint_for(Channel::Nightly, None);
+        let dev = endpoint_for(Channel::Dev, None);
 
         // On Unix, all endpoints are in different directories based on channel
         // On Windows, the pipe names contain the channel
@@ -253,4 +331,41 @@ mod tests {
         assert_ne!(stable, dev);
         assert_ne!(nightly, dev);
     }
+
+    #[test]
+    fn test_socket_file_name_is_per_user() {
+        assert_eq!(socket_file_name(Some(1000)), "howth-1000.sock");
+        assert_ne!(socket_file_name(Some(1000)), socket_file_name(Some(1001)));
+        assert_eq!(socket_file_name(None), "howth.sock");
+    }
+
+    #[test]
+    fn test_pipe_name_is_per_user_and_channel() {
+        assert_eq!(
+            pipe_name("Alice", Channel::Stable),
+            format!(r"\\.\pipe\howth-alice-stable-v{SCHEMA_VERSION}")
+        );
+        assert_ne!(
+            pipe_name("alice", Channel::Stable),
+            pipe_name("bob", Channel::Stable)
+        );
+        assert_ne!(
+            pipe_name("alice", Channel::Stable),
+            pipe_name("alice", Channel::Dev)
+        );
+    }
+
+    #[test]
+    fn test_pipe_name_sanitizes_user() {
+        assert_eq!(sanitize_user(r"CORP\J Doe"), "corp_j_doe");
+        assert_eq!(sanitize_user("build-agent_1.x"), "build-agent_1.x");
+    }
+
+    #[test]
+    #[cfg(unix)]
+    fn test_ipc_endpoint_contains_uid() {
+        let endpoint = endpoint_for(Channel::Stable, None);
+        let uid = current_uid();
+        assert!(endpoint.ends_with(&format!("howth-{uid}.sock")));
+    }
 }
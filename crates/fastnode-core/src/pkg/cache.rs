@@ -1,8 +1,11 @@
 //! Package cache management.
 //!
 //! Manages the global package cache where downloaded packages are stored.
+//! Package files can optionally be deduplicated through a content-addressed
+//! [`FileStore`].
 
 use super::error::PkgError;
+use super::store::{disk_usage, DedupeStats, FileStore, FILE_STORE_DIR, FILE_STORE_ENV};
 use crate::config::Channel;
 use crate::paths::cache_dir;
 use std::fs;
@@ -23,6 +26,12 @@ impl PackageCache {
         Self { root }
     }
 
+    /// Create a package cache rooted at `root`.
+    #[must_use]
+    pub fn with_root(root: impl Into<PathBuf>) -> Self {
+        Self { root: root.into() }
+    }
+
     /// Get the cache root directory.
     #[must_use]
     pub fn root(&self) -> &Path {
@@ -164,6 +173,51 @@ impl PackageCache {
         Ok(())
     }
 
+    /// Get the content-addressed file store, if enabled.
+    ///
+    /// The store is used once it exists (see [`dedupe`](Self::dedupe)) or
+    /// when [`FILE_STORE_ENV`] is set.
+    #[must_use]
+    pub fn file_store(&self) -> Option<FileStore> {
+        let store = FileStore::new(self.root.join(FILE_STORE_DIR));
+        let enabled =
+            store.exists() || std::env::var(FILE_STORE_ENV).is_ok_and(|v| v == "1" || v == "true");
+        enabled.then_some(store)
+    }
+
+    /// Move the files of all cached packages into the file store, enabling
+    /// it for future downloads.
+    ///
+    /// Returns the number of packages processed and the combined stats.
+    ///
+    /// # Errors
+    /// Returns an error if the cache or the store can't be read or created.
+    pub fn dedupe(&self) -> Result<(u32, DedupeStats), PkgError> {
+        let store = FileStore::new(self.root.join(FILE_STORE_DIR));
+        let mut packages = 0;
+        let mut stats = DedupeStats::default();
+        for (name, version) in self.list_cached()? {
+            stats.add(store.dedupe_dir(&self.package_dir(&name, &version))?);
+            packages += 1;
+        }
+        Ok((packages, stats))
+    }
+
+    /// Bytes used on disk by cached packages and the file store, counting
+    /// hard-linked files once.
+    ///
+    /// # Errors
+    /// Returns an error if the cache directory cannot be read.
+    pub fn disk_size(&self) -> Result<u64, PkgError> {
+        let mut dirs: Vec<PathBuf> = self
+            .list_cached()?
+            .into_iter()
+            .map(|(name, version)| self.package_dir(&name, &version))
+            .collect();
+        dirs.push(self.root.join(FILE_STORE_DIR));
+        Ok(disk_usage(dirs.iter().map(PathBuf::as_path)))
+    }
+
     /// Ensure cache directories exist.
     ///
     /// # Errors
@@ -242,7 +296,7 @@ mod tests {
             root: dir.path().to_path_buf(),
         };
         let cached = cache.list_cached().unwrap();
-        assert!(cached.is_empty());
+        assert_eq!(cached, Vec::<(String, String)>::new());
     }
 
     #[test]
@@ -262,6 +316,32 @@ mod tests {
         assert_eq!(cached[0], ("react".to_string(), "18.2.0".to_string()));
     }
 
+    #[test]
+    fn test_dedupe_migrates_cached_packages() {
+        let dir = tempdir().unwrap();
+        let cache = PackageCache::with_root(dir.path());
+        for version in ["1.0.0", "1.0.1"] {
+            let pkg = cache.package_dir("lodash", version);
+            fs::create_dir_all(&pkg).unwrap();
+            fs::write(pkg.join("index.js"), "module.exports = 1;").unwrap();
+        }
+        assert!(!dir.path().join(FILE_STORE_DIR).exists());
+
+        let (packages, stats) = cache.dedupe().unwrap();
+        assert_eq!(packages, 2);
+        assert_eq!(stats.files, 2);
+        assert_eq!(stats.linked, 1);
+        assert!(cache.file_store().is_some());
+
+        // The store isn't listed as a package
+        assert_eq!(cache.list_cached().unwrap().len(), 2);
+        #[cfg(unix)]
+        assert_eq!(
+            cache.disk_size().unwrap(),
+            "module.exports = 1;".len() as u64
+        );
+    }
+
     #[test]
     fn test_list_with_scoped_packages() {
         let dir = tempdir().unwrap();
@@ -0,0 +1,360 @@
+//! Read-only verification that `node_modules` matches the lockfile.
+//!
+//! Backs `howth install --check`. Computes the layout the installer would
+//! produce (see [`super::layout`]) and compares it with the tree on disk:
+//!
+//! - every package is installed where the layout puts it, at the locked
+//!   version;
+//! - nothing else is installed at the top of `node_modules`;
+//! - for a sampled subset of packages, the installed files are identical to
+//!   the downloaded package in the cache.
+//!
+//! The sample is chosen by hashing package keys, so repeated checks of the
+//! same lockfile verify the same packages. Nothing is written.
+
+use super::cache::PackageCache;
+use super::layout::{NodeLinker, NodeModulesLayout};
+use super::lockfile::Lockfile;
+use super::workspaces::WorkspaceConfig;
+use serde::Serialize;
+use serde_json::Value;
+use std::collections::{BTreeSet, HashSet};
+use std::fs;
+use std::path::Path;
+
+/// Schema version for the check report.
+pub const INSTALL_CHECK_SCHEMA_VERSION: u32 = 1;
+
+/// Default number of packages whose content is verified.
+pub const DEFAULT_CHECK_SAMPLE: usize = 16;
+
+/// How an installed package differs from the lockfile.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum CheckDiffKind {
+    /// Not installed where the layout puts it.
+    Missing,
+    /// Installed at a different version.
+    WrongVersion,
+    /// Files differ from the downloaded package.
+    Modified,
+    /// Installed but not in the lockfile's layout.
+    Extraneous,
+}
+
+/// One difference between `node_modules` and the lockfile.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct CheckDiff {
+    /// What differs.
+    pub kind: CheckDiffKind,
+    /// Package name.
+    pub name: String,
+    /// Install directory, relative to the project root.
+    pub path: String,
+    /// Locked version.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub expected: Option<String>,
+    /// Installed version, or the first differing file for `modified`.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub actual: Option<String>,
+}
+
+/// Result of checking `node_modules` against the lockfile.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct InstallCheckReport {
+    /// Schema version for the report.
+    pub schema_version: u32,
+    /// Whether `node_modules` matches the lockfile.
+    pub ok: bool,
+    /// Layout checked against.
+    pub node_linker: NodeLinker,
+    /// Number of install directories checked.
+    pub checked: u32,
+    /// Number of sampled packages whose files were compared with the cache.
+    pub verified: u32,
+    /// Number of sampled packages that aren't in the cache to compare with.
+    pub unverified: u32,
+    /// Differences, sorted by path then kind.
+    pub diffs: Vec<CheckDiff>,
+}
+
+/// Options for [`check_install`].
+#[derive(Debug, Clone)]
+pub struct InstallCheckOptions {
+    /// Include root devDependencies.
+    pub include_dev: bool,
+    /// Include root optionalDependencies.
+    pub include_optional: bool,
+    /// Layout to check against.
+    pub node_linker: NodeLinker,
+    /// Number of packages whose content is verified (0 to skip).
+    pub sample: usize,
+}
+
+impl Default for InstallCheckOptions {
+    fn default() -> Self {
+        Self {
+            include_dev: true,
+            include_optional: true,
+            node_linker: NodeLinker::default(),
+            sample: DEFAULT_CHECK_SAMPLE,
+        }
+    }
+}
+
+/// Check `project_root/node_modules` against `lockfile`.
+#[must_use]
+pub fn check_install(
+    project_root: &Path,
+    lockfile: &Lockfile,
+    workspaces: Option<&WorkspaceConfig>,
+    cache: &PackageCache,
+    opts: &InstallCheckOptions,
+) -> InstallCheckReport {
+    let mut layout = NodeModulesLayout::compute(
+        lockfile,
+        opts.node_linker,
+        opts.include_dev,
+        opts.include_optional,
+    );
+    if let Some(workspaces) = workspaces {
+        layout.link_workspace_packages(workspaces);
+    }
+
+    let mut diffs = Vec::new();
+    let mut installed = Vec::new();
+    for (install_dir, key) in &layout.packages {
+        let (name, version) = key.rsplit_once('@').unwrap_or((key, ""));
+        let diff = |kind, actual| CheckDiff {
+            kind,
+            name: name.to_string(),
+            path: install_dir.clone(),
+            expected: Some(version.to_string()),
+            actual,
+        };
+        match installed_version(&project_root.join(install_dir)) {
+            Some(found) if found == version => installed.push((install_dir, key)),
+            Some(found) => diffs.push(diff(CheckDiffKind::WrongVersion, Some(found))),
+            None => diffs.push(diff(CheckDiffKind::Missing, None)),
+        }
+    }
+
+    let top_level: BTreeSet<&str> = layout.top_level().map(|(name, _, _)| name).collect();
+    for name in top_level_packages(&project_root.join("node_modules")) {
+        if !top_level.contains(name.as_str()) {
+            let path = format!("node_modules/{name}");
+            diffs.push(CheckDiff {
+                kind: CheckDiffKind::Extraneous,
+                actual: installed_version(&project_root.join(&path)),
+                name,
+                path,
+                expected: None,
+            });
+        }
+    }
+
+    // Verify one install directory of each sampled package
+    let mut seen = HashSet::new();
+    let mut sampled: Vec<_> = installed
+        .into_iter()
+        .filter(|(_, key)| seen.insert(*key))
+        .collect();
+    sampled.sort_by_key(|(_, key)| blake3::hash(key.as_bytes()).to_hex().to_string());
+    sampled.truncate(opts.sample);
+
+    let mut verified = 0;
+    let mut unverified = 0;
+    for (install_dir, key) in sampled {
+        let Some(pkg) = lockfile.packages.get(key) else {
+            continue;
+        };
+        let name = &key[..key.len() - pkg.version.len() - 1];
+        let fetch_name = pkg.alias_for.as_deref().unwrap_or(name);
+        let cached = cache.package_dir(fetch_name, &pkg.version);
+        if !cached.is_dir() {
+            unverified += 1;
+            continue;
+        }
+        verified += 1;
+        if let Some(file) = first_modified_file(&cached, &project_root.join(install_dir), "") {
+            diffs.push(CheckDiff {
+                kind: CheckDiffKind::Modified,
+                name: name.to_string(),
+                path: install_dir.clone(),
+                expected: Some(pkg.version.clone()),
+                actual: Some(file),
+            });
+        }
+    }
+
+    diffs.sort_by(|a, b| (&a.path, a.kind as u8).cmp(&(&b.path, b.kind as u8)));
+    InstallCheckReport {
+        schema_version: INSTALL_CHECK_SCHEMA_VERSION,
+        ok: diffs.is_empty(),
+        node_linker: opts.node_linker,
+        checked: layout.packages.len() as u32,
+        verified,
+        unverified,
+        diffs,
+    }
+}
+
+/// Version in a package directory's `package.json`.
+fn installed_version(dir: &Path) -> Option<String> {
+    let content = fs::read_to_string(dir.join("package.json")).ok()?;
+    let manifest: Value = serde_json::from_str(&content).ok()?;
+    manifest.get("version")?.as_str().map(String::from)
+}
+
+/// Names of the packages in a `node_modules` directory, scoped packages
+/// included, skipping `.bin`, `.pnpm` and other dot entries.
+fn top_level_packages(node_modules: &Path) -> Vec<String> {
+    let mut names = Vec::new();
+    let Ok(entries) = fs::read_dir(node_modules) else {
+        return names;
+    };
+    for entry in entries.flatten() {
+        let name = entry.file_name().to_string_lossy().into_owned();
+        if name.starts_with('.') {
+            continue;
+        }
+        if name.starts_with('@') {
+            let Ok(scoped) = fs::read_dir(entry.path()) else {
+                continue;
+            };
+            names.extend(
+                scoped
+                    .flatten()
+                    .map(|e| format!("{name}/{}", e.file_name().to_string_lossy())),
+            );
+        } else {
+            names.push(name);
+        }
+    }
+    names.sort();
+    names
+}
+
+/// First file of the cached package (relative path) that is missing or
+/// different in the installed copy. Extra installed files are ignored.
+fn first_modified_file(cached: &Path, installed: &Path, prefix: &str) -> Option<String> {
+    let mut entries: Vec<_> = fs::read_dir(cached).ok()?.flatten().collect();
+    entries.sort_by_key(fs::DirEntry::file_name);
+    for entry in entries {
+        let name = entry.file_name().to_string_lossy().into_owned();
+        let relative = format!("{prefix}{name}");
+        let installed_path = installed.join(&name);
+        if entry.path().is_dir() {
+            if let Some(file) =
+                first_modified_file(&entry.path(), &installed_path, &format!("{relative}/"))
+            {
+                return Some(file);
+            }
+        } else if !same_file_content(&entry.path(), &installed_path) {
+            return Some(relative);
+        }
+    }
+    None
+}
+
+/// Whether two files have the same content. Hard links to the same file are
+/// recognised without reading them.
+fn same_file_content(a: &Path, b: &Path) -> bool {
+    let (Ok(meta_a), Ok(meta_b)) = (fs::metadata(a), fs::metadata(b)) else {
+        return false;
+    };
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::MetadataExt;
+        if meta_a.ino() == meta_b.ino() && meta_a.dev() == meta_b.dev() {
+            return true;
+        }
+    }
+    if meta_a.len() != meta_b.len() {
+        return false;
+    }
+    match (fs::read(a), fs::read(b)) {
+        (Ok(a), Ok(b)) => a == b,
+        _ => false,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::pkg::lockfile::{LockDep, LockPackage, LockRoot};
+
+    fn write_package(dir: &Path, name: &str, version: &str) {
+        fs::create_dir_all(dir).unwrap();
+        fs::write(
+            dir.join("package.json"),
+            format!(r#"{{"name":"{name}","version":"{version}"}}"#),
+        )
+        .unwrap();
+        fs::write(
+            dir.join("index.js"),
+            format!("module.exports = '{version}';\n"),
+        )
+        .unwrap();
+    }
+
+    #[test]
+    fn test_check_install_reports_diffs() {
+        let project = tempfile::tempdir().unwrap();
+        let cache = PackageCache::with_root(project.path().join("cache"));
+        let mut lockfile = Lockfile::new(LockRoot::new("app", None));
+        for name in ["a", "b", "c"] {
+            lockfile.add_dependency(name, LockDep::new("^1.0.0", "dep", "1.0.0"));
+            lockfile.add_package(name, LockPackage::new("1.0.0", ""));
+            write_package(&cache.package_dir(name, "1.0.0"), name, "1.0.0");
+        }
+
+        let root = project.path();
+        write_package(&root.join("node_modules/a"), "a", "1.0.0");
+        write_package(&root.join("node_modules/b"), "b", "2.0.0");
+        write_package(&root.join("node_modules/left-pad"), "left-pad", "1.3.0");
+        fs::create_dir_all(root.join("node_modules/.bin")).unwrap();
+
+        let opts = InstallCheckOptions {
+            node_linker: NodeLinker::Hoisted,
+            ..Default::default()
+        };
+        let report = check_install(root, &lockfile, None, &cache, &opts);
+        assert!(!report.ok);
+        assert_eq!(report.checked, 3);
+        assert_eq!(report.verified, 1);
+        let diffs: Vec<_> = report
+            .diffs
+            .iter()
+            .map(|d| (d.kind, d.path.as_str(), d.actual.as_deref()))
+            .collect();
+        assert_eq!(
+            diffs,
+            [
+                (CheckDiffKind::WrongVersion, "node_modules/b", Some("2.0.0")),
+                (CheckDiffKind::Missing, "node_modules/c", None),
+                (
+                    CheckDiffKind::Extraneous,
+                    "node_modules/left-pad",
+                    Some("1.3.0")
+                ),
+            ]
+        );
+
+        // Fix the tree, then tamper with a file
+        write_package(&root.join("node_modules/b"), "b", "1.0.0");
+        write_package(&root.join("node_modules/c"), "c", "1.0.0");
+        fs::remove_dir_all(root.join("node_modules/left-pad")).unwrap();
+        assert!(check_install(root, &lockfile, None, &cache, &opts).ok);
+
+        fs::write(root.join("node_modules/c/index.js"), "patched").unwrap();
+        let report = check_install(root, &lockfile, None, &cache, &opts);
+        assert_eq!(report.verified, 3);
+        assert_eq!(report.diffs.len(), 1);
+        assert_eq!(report.diffs[0].kind, CheckDiffKind::Modified);
+        assert_eq!(report.diffs[0].actual.as_deref(), Some("index.js"));
+
+        let opts = InstallCheckOptions { sample: 0, ..opts };
+        assert!(check_install(root, &lockfile, None, &cache, &opts).ok);
+    }
+}
@@ -3,6 +3,7 @@
 //! Provides utilities for reading and parsing dependencies from package.json files.
 
 use super::error::{codes, PkgError};
+use fastnode_util::fs::atomic_write;
 use serde_json::Value;
 use std::collections::HashMap;
 use std::fs;
@@ -286,7 +287,7 @@ pub fn add_dependency_to_package_json(
     // Add trailing newline
     let output = format!("{formatted}\n");
 
-    fs::write(package_json_path, output)
+    atomic_write(package_json_path, output.as_bytes())
         .map_err(|e| PkgError::package_json_invalid(format!("Failed to write: {e}")))?;
 
     Ok(())
@@ -374,7 +375,7 @@ pub fn remove_dependency_from_package_json(
         // Add trailing newline
         let output = format!("{formatted}\n");
 
-        fs::write(package_json_path, output)
+        atomic_write(package_json_path, output.as_bytes())
             .map_err(|e| PkgError::package_json_invalid(format!("Failed to write: {e}")))?;
     }
 
@@ -570,7 +571,7 @@ mod tests {
 
         let result = read_package_deps(&path, false, false).unwrap();
 
-        assert!(result.deps.is_empty());
+        assert_eq!(result.deps, Vec::<(String, String)>::new());
         assert_eq!(result.errors.len(), 1);
         assert_eq!(result.errors[0].code, codes::PKG_PACKAGE_JSON_INVALID);
     }
@@ -624,7 +625,7 @@ mod tests {
 
         let result = read_package_deps(&path, false, false).unwrap();
 
-        assert!(result.deps.is_empty());
+        assert_eq!(result.deps, Vec::<(String, String)>::new());
         assert!(result.errors.is_empty());
     }
 
@@ -641,7 +642,7 @@ mod tests {
 
         let result = read_package_deps(&path, false, false).unwrap();
 
-        assert!(result.deps.is_empty());
+        assert_eq!(result.deps, Vec::<(String, String)>::new());
         assert!(result.errors.is_empty());
     }
 
@@ -18,9 +18,14 @@
 //! then `code`, then `package`, then `path`. Truncation notice is always last.
 
 use serde::{Deserialize, Serialize};
-use std::collections::HashMap;
+use serde_json::Value;
+use std::collections::{BTreeMap, HashMap};
+use std::path::Path;
 
-use super::graph::{GraphErrorInfo, PackageGraph};
+use super::graph::{GraphErrorInfo, PackageGraph, PackageId};
+use super::layout::NodeModulesLayout;
+use super::lockfile::{lockfile_content_hash, Lockfile, LOCKFILE_NAME};
+use super::version::version_satisfies;
 
 /// Schema version for doctor report output.
 pub const PKG_DOCTOR_SCHEMA_VERSION: u32 = 1;
@@ -34,6 +39,14 @@ pub mod codes {
     pub const PKG_DOCTOR_INVALID_PACKAGE_JSON: &str = "PKG_DOCTOR_INVALID_PACKAGE_JSON";
     pub const PKG_DOCTOR_DUPLICATE_PACKAGE_VERSION: &str = "PKG_DOCTOR_DUPLICATE_PACKAGE_VERSION";
     pub const PKG_DOCTOR_MAX_ITEMS_REACHED: &str = "PKG_DOCTOR_MAX_ITEMS_REACHED";
+    pub const PKG_DOCTOR_ENGINES_MISMATCH: &str = "PKG_DOCTOR_ENGINES_MISMATCH";
+    pub const PKG_DOCTOR_BIN_TARGET_MISSING: &str = "PKG_DOCTOR_BIN_TARGET_MISSING";
+    pub const PKG_DOCTOR_CASE_COLLISION: &str = "PKG_DOCTOR_CASE_COLLISION";
+    pub const PKG_DOCTOR_BIN_LINK_BROKEN: &str = "PKG_DOCTOR_BIN_LINK_BROKEN";
+    pub const PKG_DOCTOR_PEER_CONFLICT: &str = "PKG_DOCTOR_PEER_CONFLICT";
+    pub const PKG_DOCTOR_PEER_MISSING: &str = "PKG_DOCTOR_PEER_MISSING";
+    pub const PKG_DOCTOR_LAYOUT_STALE: &str = "PKG_DOCTOR_LAYOUT_STALE";
+    pub const PKG_DOCTOR_LAYOUT_MISMATCH: &str = "PKG_DOCTOR_LAYOUT_MISMATCH";
 }
 
 /// Severity levels for doctor findings.
@@ -278,6 +291,8 @@ pub struct DoctorOptions {
     pub max_items: usize,
     /// Minimum severity to include in output.
     pub min_severity: DoctorSeverity,
+    /// Node version to check `engines.node` against (skipped when `None`).
+    pub node_version: Option<String>,
 }
 
 impl Default for DoctorOptions {
@@ -288,6 +303,7 @@ impl Default for DoctorOptions {
             max_depth: 25,
             max_items: 200,
             min_severity: DoctorSeverity::Info,
+            node_version: None,
         }
     }
 }
@@ -382,6 +398,26 @@ pub fn build_doctor_report(
         all_findings.push(finding);
     }
 
+    // 2.5: engines.node incompatible with the runtime
+    if let Some(node_version) = &opts.node_version {
+        all_findings.extend(check_engines(graph, cwd_abs, node_version));
+    }
+
+    // 2.6: bin entries pointing at missing files
+    all_findings.extend(check_bin_targets(graph));
+
+    // 2.7: package paths that collide on case-insensitive filesystems
+    all_findings.extend(check_case_collisions(graph));
+
+    // 2.8: node_modules/.bin links pointing nowhere
+    all_findings.extend(check_bin_links(cwd_abs));
+
+    // 2.9: peer dependencies that are missing or at an incompatible version
+    all_findings.extend(check_peers(graph));
+
+    // 2.10: tree differs from the layout recorded at install
+    all_findings.extend(check_layout(cwd_abs));
+
     // Sort all findings deterministically using LOCKED sort order (v1.7.1+)
     all_findings.sort_by(|a, b| doctor_sort_key(a).cmp(&doctor_sort_key(b)));
 
@@ -453,6 +489,309 @@ pub fn build_doctor_report(
     report
 }
 
+/// Detect the version of `node` on `PATH`, without the leading `v`.
+#[must_use]
+pub fn detect_node_version() -> Option<String> {
+    let output = std::process::Command::new("node")
+        .arg("--version")
+        .output()
+        .ok()?;
+    if !output.status.success() {
+        return None;
+    }
+    let version = String::from_utf8_lossy(&output.stdout)
+        .trim()
+        .trim_start_matches('v')
+        .to_string();
+    (!version.is_empty()).then_some(version)
+}
+
+/// All installed packages: reachable nodes and orphans.
+fn installed_packages(graph: &PackageGraph) -> impl Iterator<Item = &PackageId> {
+    graph
+        .nodes
+        .iter()
+        .map(|n| &n.id)
+        .chain(graph.orphans.iter())
+}
+
+/// Read a package.json, ignoring errors (graph errors already cover them).
+fn read_manifest(dir: &Path) -> Option<Value> {
+    let content = std::fs::read_to_string(dir.join("package.json")).ok()?;
+    serde_json::from_str(&content).ok()
+}
+
+/// Check `engines.node` of the root and installed packages.
+fn check_engines(graph: &PackageGraph, cwd_abs: &str, node_version: &str) -> Vec<DoctorFinding> {
+    let node_version = node_version.trim_start_matches('v');
+    let root = read_manifest(Path::new(cwd_abs)).map(|m| {
+        let name = m
+            .get("name")
+            .and_then(Value::as_str)
+            .unwrap_or("(root)")
+            .to_string();
+        (name, cwd_abs.to_string(), m)
+    });
+    let installed = installed_packages(graph).filter_map(|id| {
+        read_manifest(Path::new(&id.path))
+            .map(|m| (format!("{}@{}", id.name, id.version), id.path.clone(), m))
+    });
+
+    root.into_iter()
+        .chain(installed)
+        .filter_map(|(package, path, manifest)| {
+            let range = manifest.get("engines")?.get("node")?.as_str()?.trim();
+            if range.is_empty() || version_satisfies(node_version, range) {
+                return None;
+            }
+            let message = format!(
+                "engines.node does not match the node runtime\n  \
+                 hint: use node {range}, or a version of the package that supports {node_version}"
+            );
+            Some(
+                DoctorFinding::new(
+                    codes::PKG_DOCTOR_ENGINES_MISMATCH,
+                    DoctorSeverity::Warn,
+                    message,
+                )
+                .with_package(package)
+                .with_path(path)
+                .with_detail(format!("engines.node={range} node={node_version}")),
+            )
+        })
+        .collect()
+}
+
+/// Check the peer dependencies of installed packages against the version
+/// Node resolution finds from each package.
+fn check_peers(graph: &PackageGraph) -> Vec<DoctorFinding> {
+    let mut findings = Vec::new();
+    for id in installed_packages(graph) {
+        let dir = Path::new(&id.path);
+        let Some(manifest) = read_manifest(dir) else {
+            continue;
+        };
+        let Some(peers) = manifest.get("peerDependencies").and_then(Value::as_object) else {
+            continue;
+        };
+        let package = format!("{}@{}", id.name, id.version);
+
+        for (peer, range) in peers {
+            let Some(range) = range.as_str() else {
+                continue;
+            };
+            let optional = manifest
+                .get("peerDependenciesMeta")
+                .and_then(|meta| meta.get(peer))
+                .and_then(|meta| meta.get("optional"))
+                .and_then(Value::as_bool)
+                .unwrap_or(false);
+
+            let installed = find_visible_package(dir, peer)
+                .and_then(|peer_dir| read_manifest(&peer_dir))
+                .and_then(|m| m.get("version").and_then(Value::as_str).map(String::from));
+            let finding = match installed {
+                Some(version) if version_satisfies(&version, range) => continue,
+                Some(version) => DoctorFinding::new(
+                    codes::PKG_DOCTOR_PEER_CONFLICT,
+                    DoctorSeverity::Error,
+                    format!(
+                        "peer dependency {peer}@{range} is not satisfied by {peer}@{version}\n  \
+                         hint: install a version of {peer} matching {range}, or a version of {} that supports {version}",
+                        id.name
+                    ),
+                )
+                .with_detail(format!("peer={peer} req={range} installed={version}")),
+                None if optional => continue,
+                None => DoctorFinding::new(
+                    codes::PKG_DOCTOR_PEER_MISSING,
+                    DoctorSeverity::Warn,
+                    format!(
+                        "peer dependency {peer}@{range} is not installed\n  \
+                         hint: howth pkg add {peer}@\"{range}\", or enable auto-install-peers"
+                    ),
+                )
+                .with_detail(format!("peer={peer} req={range}")),
+            };
+            findings.push(
+                finding
+                    .with_package(package.clone())
+                    .with_path(&id.path)
+                    .with_related(vec![peer.clone()]),
+            );
+        }
+    }
+    findings
+}
+
+/// Directory of `name` as Node resolution sees it from a package directory.
+fn find_visible_package(from: &Path, name: &str) -> Option<std::path::PathBuf> {
+    from.ancestors()
+        .filter(|dir| dir.file_name().is_some_and(|n| n != "node_modules"))
+        .map(|dir| dir.join("node_modules").join(name))
+        .find(|candidate| candidate.join("package.json").is_file())
+}
+
+/// Bin entries of a manifest as `(bin name, relative target)`.
+fn bin_entries(manifest: &Value, package_name: &str) -> Vec<(String, String)> {
+    match manifest.get("bin") {
+        Some(Value::String(target)) => {
+            let name = package_name.rsplit('/').next().unwrap_or(package_name);
+            vec![(name.to_string(), target.clone())]
+        }
+        Some(Value::Object(bins)) => bins
+            .iter()
+            .filter_map(|(name, target)| Some((name.clone(), target.as_str()?.to_string())))
+            .collect(),
+        _ => Vec::new(),
+    }
+}
+
+/// Check that every `bin` entry of installed packages points at a file.
+fn check_bin_targets(graph: &PackageGraph) -> Vec<DoctorFinding> {
+    let mut findings = Vec::new();
+    for id in installed_packages(graph) {
+        let dir = Path::new(&id.path);
+        let Some(manifest) = read_manifest(dir) else {
+            continue;
+        };
+        for (name, target) in bin_entries(&manifest, &id.name) {
+            if dir.join(&target).is_file() {
+                continue;
+            }
+            findings.push(
+                DoctorFinding::new(
+                    codes::PKG_DOCTOR_BIN_TARGET_MISSING,
+                    DoctorSeverity::Warn,
+                    "bin entry points at a missing file\n  \
+                     hint: reinstall with howth install, or report it to the package author",
+                )
+                .with_package(format!("{}@{}", id.name, id.version))
+                .with_path(&id.path)
+                .with_detail(format!("bin: {name} -> {target}")),
+            );
+        }
+    }
+    findings
+}
+
+/// Find installed package paths that differ only by case.
+fn check_case_collisions(graph: &PackageGraph) -> Vec<DoctorFinding> {
+    let mut by_folded: BTreeMap<String, Vec<&PackageId>> = BTreeMap::new();
+    for id in installed_packages(graph) {
+        by_folded
+            .entry(id.path.to_lowercase())
+            .or_default()
+            .push(id);
+    }
+
+    by_folded
+        .into_values()
+        .filter_map(|mut ids| {
+            ids.sort_by(|a, b| a.path.cmp(&b.path));
+            ids.dedup_by(|a, b| a.path == b.path);
+            if ids.len() < 2 {
+                return None;
+            }
+            Some(
+                DoctorFinding::new(
+                    codes::PKG_DOCTOR_CASE_COLLISION,
+                    DoctorSeverity::Warn,
+                    "package paths differ only by case\n  \
+                     hint: these collide on case-insensitive filesystems (macOS, Windows); \
+                     depend on a single spelling",
+                )
+                .with_package(ids[0].name.clone())
+                .with_path(&ids[0].path)
+                .with_related(ids.iter().map(|id| id.path.clone()).collect()),
+            )
+        })
+        .collect()
+}
+
+/// Find `node_modules/.bin` symlinks whose target does not exist.
+fn check_bin_links(cwd_abs: &str) -> Vec<DoctorFinding> {
+    let bin_dir = Path::new(cwd_abs).join("node_modules").join(".bin");
+    let Ok(entries) = std::fs::read_dir(&bin_dir) else {
+        return Vec::new();
+    };
+
+    let mut findings = Vec::new();
+    for entry in entries.flatten() {
+        let path = entry.path();
+        let is_symlink = std::fs::symlink_metadata(&path).is_ok_and(|m| m.file_type().is_symlink());
+        if !is_symlink || path.exists() {
+            continue;
+        }
+        let target = std::fs::read_link(&path)
+            .map(|t| t.to_string_lossy().into_owned())
+            .unwrap_or_default();
+        findings.push(
+            DoctorFinding::new(
+                codes::PKG_DOCTOR_BIN_LINK_BROKEN,
+                DoctorSeverity::Warn,
+                "binary link points to a missing file\n  \
+                 hint: run howth install to relink binaries",
+            )
+            .with_path(path.to_string_lossy())
+            .with_detail(format!("target: {target}")),
+        );
+    }
+    findings
+}
+
+/// Check `node_modules` against the layout recorded by the last install:
+/// it must come from the current lockfile, and every package must still be
+/// installed where the layout put it.
+fn check_layout(cwd_abs: &str) -> Vec<DoctorFinding> {
+    let root = Path::new(cwd_abs);
+    let Some(layout) = NodeModulesLayout::read_from(root) else {
+        return Vec::new();
+    };
+
+    let mut findings = Vec::new();
+    if let Ok(lockfile) = Lockfile::read_from(&root.join(LOCKFILE_NAME)) {
+        if lockfile_content_hash(&lockfile) != layout.lockfile_hash {
+            findings.push(
+                DoctorFinding::new(
+                    codes::PKG_DOCTOR_LAYOUT_STALE,
+                    DoctorSeverity::Warn,
+                    "node_modules was installed from a different lockfile\n  \
+                     hint: run howth install",
+                )
+                .with_path(root.join(LOCKFILE_NAME).to_string_lossy()),
+            );
+        }
+    }
+
+    for (install_dir, key) in &layout.packages {
+        let expected = key.rsplit_once('@').map_or("", |(_, version)| version);
+        let dir = root.join(install_dir);
+        let installed = read_manifest(&dir)
+            .and_then(|m| m.get("version").and_then(Value::as_str).map(String::from));
+        let detail = match installed {
+            Some(version) if version == expected => continue,
+            Some(version) => format!("expected={expected} installed={version}"),
+            None => format!("expected={expected} installed=none"),
+        };
+        findings.push(
+            DoctorFinding::new(
+                codes::PKG_DOCTOR_LAYOUT_MISMATCH,
+                DoctorSeverity::Warn,
+                format!(
+                    "package is not installed where the {} layout puts it\n  \
+                     hint: run howth install",
+                    layout.node_linker.as_str()
+                ),
+            )
+            .with_package(key)
+            .with_path(dir.to_string_lossy())
+            .with_detail(detail),
+        );
+    }
+    findings
+}
+
 /// Map a graph error to a doctor finding.
 fn map_graph_error(error: &GraphErrorInfo) -> (DoctorFinding, bool) {
     use super::graph::codes as graph_codes;
@@ -984,6 +1323,215 @@ mod tests {
     }
 
     /// **LOCKED v1.7.1+**: notes field is always serialized (even when empty).
+    fn write_manifest(dir: &Path, json: &str) {
+        std::fs::create_dir_all(dir).unwrap();
+        std::fs::write(dir.join("package.json"), json).unwrap();
+    }
+
+    fn codes_of(report: &PkgDoctorReport) -> Vec<&str> {
+        report.findings.iter().map(|f| f.code.as_str()).collect()
+    }
+
+    #[test]
+    fn test_doctor_engines_mismatch() {
+        let dir = tempfile::tempdir().unwrap();
+        let root = dir.path();
+        write_manifest(root, r#"{"name":"app","engines":{"node":">=18"}}"#);
+        let old_pkg = root.join("node_modules/old");
+        write_manifest(
+            &old_pkg,
+            r#"{"name":"old","version":"1.0.0","engines":{"node":"^14"}}"#,
+        );
+
+        let graph = make_graph(
+            vec![PackageNode::new(
+                PackageId::new(
+                    "old".to_string(),
+                    "1.0.0".to_string(),
+                    old_pkg.to_string_lossy().into_owned(),
+                ),
+                vec![],
+            )],
+            vec![],
+            vec![],
+        );
+        let cwd = root.to_string_lossy();
+
+        let report = build_doctor_report(&graph, &cwd, &DoctorOptions::default());
+        assert!(!codes_of(&report).contains(&codes::PKG_DOCTOR_ENGINES_MISMATCH));
+
+        let opts = DoctorOptions {
+            node_version: Some("v20.11.1".to_string()),
+            ..Default::default()
+        };
+        let report = build_doctor_report(&graph, &cwd, &opts);
+        let mismatches: Vec<_> = report
+            .findings
+            .iter()
+            .filter(|f| f.code == codes::PKG_DOCTOR_ENGINES_MISMATCH)
+            .collect();
+        assert_eq!(mismatches.len(), 1);
+        assert_eq!(mismatches[0].package.as_deref(), Some("old@1.0.0"));
+        assert_eq!(
+            mismatches[0].detail.as_deref(),
+            Some("engines.node=^14 node=20.11.1")
+        );
+    }
+
+    #[test]
+    fn test_doctor_peer_conflict_and_missing() {
+        let dir = tempfile::tempdir().unwrap();
+        let root = dir.path();
+        write_manifest(root, r#"{"name":"app"}"#);
+        write_manifest(
+            &root.join("node_modules/react"),
+            r#"{"name":"react","version":"17.0.2"}"#,
+        );
+        let dom = root.join("node_modules/react-dom");
+        write_manifest(
+            &dom,
+            r#"{"name":"react-dom","version":"18.2.0",
+                "peerDependencies":{"react":"^18.2.0","scheduler":"*","csstype":"*"},
+                "peerDependenciesMeta":{"csstype":{"optional":true}}}"#,
+        );
+
+        let node = |name: &str, version: &str, path: &Path| {
+            PackageNode::new(
+                PackageId::new(
+                    name.to_string(),
+                    version.to_string(),
+                    path.to_string_lossy().into_owned(),
+                ),
+                vec![],
+            )
+        };
+        let graph = make_graph(
+            vec![
+                node("react", "17.0.2", &root.join("node_modules/react")),
+                node("react-dom", "18.2.0", &dom),
+            ],
+            vec![],
+            vec![],
+        );
+
+        let report =
+            build_doctor_report(&graph, &root.to_string_lossy(), &DoctorOptions::default());
+        let conflict = report
+            .findings
+            .iter()
+            .find(|f| f.code == codes::PKG_DOCTOR_PEER_CONFLICT)
+            .unwrap();
+        assert_eq!(conflict.severity, DoctorSeverity::Error);
+        assert_eq!(conflict.package.as_deref(), Some("react-dom@18.2.0"));
+        assert_eq!(
+            conflict.detail.as_deref(),
+            Some("peer=react req=^18.2.0 installed=17.0.2")
+        );
+
+        let missing: Vec<_> = report
+            .findings
+            .iter()
+            .filter(|f| f.code == codes::PKG_DOCTOR_PEER_MISSING)
+            .collect();
+        assert_eq!(missing.len(), 1);
+        assert_eq!(missing[0].related, vec!["scheduler".to_string()]);
+        assert_eq!(report.summary.severity, DoctorSeverity::Error);
+    }
+
+    #[test]
+    fn test_doctor_bin_target_missing() {
+        let dir = tempfile::tempdir().unwrap();
+        let pkg = dir.path().join("node_modules/tool");
+        write_manifest(
+            &pkg,
+            r#"{"name":"tool","version":"1.0.0","bin":{"tool":"bin/tool.js","ok":"ok.js"}}"#,
+        );
+        std::fs::write(pkg.join("ok.js"), "").unwrap();
+
+        let graph = make_graph(
+            vec![PackageNode::new(
+                PackageId::new(
+                    "tool".to_string(),
+                    "1.0.0".to_string(),
+                    pkg.to_string_lossy().into_owned(),
+                ),
+                vec![],
+            )],
+            vec![],
+            vec![],
+        );
+        let report = build_doctor_report(
+            &graph,
+            &dir.path().to_string_lossy(),
+            &DoctorOptions::default(),
+        );
+        let missing: Vec<_> = report
+            .findings
+            .iter()
+            .filter(|f| f.code == codes::PKG_DOCTOR_BIN_TARGET_MISSING)
+            .collect();
+        assert_eq!(missing.len(), 1);
+        assert_eq!(
+            missing[0].detail.as_deref(),
+            Some("bin: tool -> bin/tool.js")
+        );
+    }
+
+    #[test]
+    fn test_doctor_case_collision() {
+        let id = |name: &str, path: &str| {
+            PackageId::new(name.to_string(), "1.0.0".to_string(), path.to_string())
+        };
+        let graph = make_graph(
+            vec![
+                PackageNode::new(id("jsonstream", "/test/node_modules/jsonstream"), vec![]),
+                PackageNode::new(id("other", "/test/node_modules/other"), vec![]),
+            ],
+            vec![id("JSONStream", "/test/node_modules/JSONStream")],
+            vec![],
+        );
+        let report = build_doctor_report(&graph, "/test", &DoctorOptions::default());
+        let collisions: Vec<_> = report
+            .findings
+            .iter()
+            .filter(|f| f.code == codes::PKG_DOCTOR_CASE_COLLISION)
+            .collect();
+        assert_eq!(collisions.len(), 1);
+        assert_eq!(
+            collisions[0].related,
+            vec![
+                "/test/node_modules/JSONStream".to_string(),
+                "/test/node_modules/jsonstream".to_string(),
+            ]
+        );
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_doctor_bin_link_broken() {
+        let dir = tempfile::tempdir().unwrap();
+        let bin_dir = dir.path().join("node_modules/.bin");
+        std::fs::create_dir_all(&bin_dir).unwrap();
+        std::fs::write(dir.path().join("node_modules/real.js"), "").unwrap();
+        std::os::unix::fs::symlink("../real.js", bin_dir.join("good")).unwrap();
+        std::os::unix::fs::symlink("../gone/cli.js", bin_dir.join("bad")).unwrap();
+
+        let graph = make_graph(vec![], vec![], vec![]);
+        let report = build_doctor_report(
+            &graph,
+            &dir.path().to_string_lossy(),
+            &DoctorOptions::default(),
+        );
+        let broken: Vec<_> = report
+            .findings
+            .iter()
+            .filter(|f| f.code == codes::PKG_DOCTOR_BIN_LINK_BROKEN)
+            .collect();
+        assert_eq!(broken.len(), 1);
+        assert!(broken[0].path.as_deref().unwrap().ends_with("bad"));
+        assert_eq!(broken[0].detail.as_deref(), Some("target: ../gone/cli.js"));
+    }
+
     #[test]
     fn test_notes_always_serialized() {
         let report = PkgDoctorReport::new("/test");
@@ -994,6 +1542,51 @@ mod tests {
             json.get("notes").is_some(),
             "LOCKED: notes field must always be present"
         );
-        assert!(json["notes"].as_array().unwrap().is_empty());
+        assert_eq!(
+            json["notes"].as_array().unwrap().as_slice(),
+            Vec::<String>::new()
+        );
+    }
+
+    #[test]
+    fn test_doctor_checks_recorded_layout() {
+        use crate::pkg::layout::NodeLinker;
+        use crate::pkg::lockfile::{LockDep, LockPackage, LockRoot};
+
+        let dir = tempfile::tempdir().unwrap();
+        let mut lockfile = Lockfile::new(LockRoot::new("app", None));
+        lockfile.add_dependency("a", LockDep::new("^1.0.0", "dep", "1.0.0"));
+        lockfile.add_dependency("b", LockDep::new("^1.0.0", "dep", "1.0.0"));
+        lockfile.add_package("a", LockPackage::new("1.0.0", ""));
+        lockfile.add_package("b", LockPackage::new("1.0.0", ""));
+        lockfile.write_to(&dir.path().join(LOCKFILE_NAME)).unwrap();
+        for (name, version) in [("a", "1.0.0"), ("b", "2.0.0")] {
+            let pkg_dir = dir.path().join("node_modules").join(name);
+            std::fs::create_dir_all(&pkg_dir).unwrap();
+            std::fs::write(
+                pkg_dir.join("package.json"),
+                format!(r#"{{"name":"{name}","version":"{version}"}}"#),
+            )
+            .unwrap();
+        }
+        NodeModulesLayout::compute(&lockfile, NodeLinker::Hoisted, false, false)
+            .write_to(dir.path())
+            .unwrap();
+
+        let cwd = dir.path().to_string_lossy();
+        let findings = check_layout(&cwd);
+        assert_eq!(findings.len(), 1);
+        assert_eq!(findings[0].code, codes::PKG_DOCTOR_LAYOUT_MISMATCH);
+        assert_eq!(findings[0].package.as_deref(), Some("b@1.0.0"));
+        assert_eq!(
+            findings[0].detail.as_deref(),
+            Some("expected=1.0.0 installed=2.0.0")
+        );
+
+        lockfile.add_package("c", LockPackage::new("1.0.0", ""));
+        lockfile.write_to(&dir.path().join(LOCKFILE_NAME)).unwrap();
+        assert!(check_layout(&cwd)
+            .iter()
+            .any(|f| f.code == codes::PKG_DOCTOR_LAYOUT_STALE));
     }
 }
@@ -20,6 +20,9 @@ pub mod codes {
     pub const PKG_PACKAGE_JSON_NOT_FOUND: &str = "PKG_PACKAGE_JSON_NOT_FOUND";
     pub const PKG_PACKAGE_JSON_INVALID: &str = "PKG_PACKAGE_JSON_INVALID";
     pub const PKG_DEP_RANGE_INVALID: &str = "PKG_DEP_RANGE_INVALID";
+
+    // pack/publish error codes
+    pub const PKG_PACK_FAILED: &str = "PKG_PACK_FAILED";
 }
 
 /// Package manager error.
@@ -128,6 +131,11 @@ impl PkgError {
             format!("Invalid range for '{name}': expected string, got {actual_type}"),
         )
     }
+
+    /// Create a pack failed error.
+    pub fn pack_failed(msg: impl Into<String>) -> Self {
+        Self::new(codes::PKG_PACK_FAILED, msg)
+    }
 }
 
 impl fmt::Display for PkgError {
@@ -2,7 +2,8 @@
 //!
 //! Provides functionality to explain why a package is installed by
 //! analyzing the dependency graph and finding chains from root deps
-//! to the target package.
+//! to the target package. With `dependents`, answers the inverse query:
+//! every installed package that depends on the target.
 
 use super::graph::{DepEdge, PackageGraph, PackageId};
 use std::collections::{HashMap, HashSet, VecDeque};
@@ -57,6 +58,34 @@ pub struct WhyChain {
     pub links: Vec<WhyLink>,
 }
 
+/// A package that directly depends on the target.
+#[derive(Debug, Clone)]
+pub struct WhyDependent {
+    /// Dependent package name.
+    pub name: String,
+    /// Dependent package version.
+    pub version: String,
+    /// Dependent package root path.
+    pub path: String,
+    /// Version range it requires.
+    pub req: Option<String>,
+    /// Dependency kind: "dep", "dev", "optional", "peer".
+    pub kind: String,
+    /// Version of the target it resolves to.
+    pub resolved_version: String,
+}
+
+/// Inverse view of the graph: everything that depends on the target.
+#[derive(Debug, Clone, Default)]
+pub struct WhyDependents {
+    /// Direct dependents, sorted by name, version and path.
+    pub direct: Vec<WhyDependent>,
+    /// Number of packages depending on the target at any depth.
+    pub transitive_count: usize,
+    /// Whether the root package.json declares the target.
+    pub root_dependency: bool,
+}
+
 /// Error information for why operations.
 #[derive(Debug, Clone)]
 pub struct WhyErrorInfo {
@@ -102,6 +131,8 @@ pub struct PkgWhyResult {
     pub notes: Vec<String>,
     /// Errors encountered.
     pub errors: Vec<WhyErrorInfo>,
+    /// Dependents of the target (only for dependents queries).
+    pub dependents: Option<WhyDependents>,
 }
 
 impl PkgWhyResult {
@@ -121,6 +152,8 @@ pub struct WhyOptions {
     pub max_chains: usize,
     /// Prefer shortest paths.
     pub prefer_shortest: bool,
+    /// List dependents of the target instead of chains from the root.
+    pub dependents: bool,
 }
 
 impl Default for WhyOptions {
@@ -128,6 +161,7 @@ impl Default for WhyOptions {
         Self {
             max_chains: 5,
             prefer_shortest: true,
+            dependents: false,
         }
     }
 }
@@ -342,6 +376,20 @@ pub fn why_from_graph(graph: &PackageGraph, input: &str, opts: &WhyOptions) -> P
     // Build lookup indices
     let (by_name, by_path) = build_indices(graph);
 
+    // Dependents of a bare name cover every installed version
+    if opts.dependents && parsed.path.is_none() && parsed.version.is_none() {
+        if let Some(candidates) = by_name.get(&parsed.name).filter(|c| c.len() > 1) {
+            result.found_in_node_modules = true;
+            result.notes.push(format!(
+                "{} versions of {} installed; listing dependents of all of them",
+                candidates.len(),
+                parsed.name
+            ));
+            result.dependents = Some(collect_dependents(graph, &parsed.name, candidates));
+            return result;
+        }
+    }
+
     // Find the target package
     let Some(target_id) = find_target(&parsed, &by_name, &by_path, &mut result) else {
         result.found_in_node_modules = false;
@@ -356,6 +404,15 @@ pub fn why_from_graph(graph: &PackageGraph, input: &str, opts: &WhyOptions) -> P
     let orphan_paths: HashSet<_> = graph.orphans.iter().map(|o| &o.path).collect();
     result.is_orphan = orphan_paths.contains(&target_id.path);
 
+    if opts.dependents {
+        result.dependents = Some(collect_dependents(
+            graph,
+            &target_id.name,
+            std::slice::from_ref(&target_id),
+        ));
+        return result;
+    }
+
     if result.is_orphan {
         result.notes.push(format!(
             "{} is installed but not reachable from root dependencies (orphan)",
@@ -398,6 +455,57 @@ pub fn why_from_graph(graph: &PackageGraph, input: &str, opts: &WhyOptions) -> P
     result
 }
 
+/// Collect the dependents of `targets` (installed copies of `name`).
+fn collect_dependents(graph: &PackageGraph, name: &str, targets: &[PackageId]) -> WhyDependents {
+    let parents = build_parent_map(graph);
+
+    let mut direct: Vec<WhyDependent> = targets
+        .iter()
+        .flat_map(|target| {
+            parents
+                .get(&target.path)
+                .into_iter()
+                .flatten()
+                .map(|(id, edge)| WhyDependent {
+                    name: id.name.clone(),
+                    version: id.version.clone(),
+                    path: id.path.clone(),
+                    req: edge.req.clone(),
+                    kind: edge.kind.clone(),
+                    resolved_version: target.version.clone(),
+                })
+        })
+        .collect();
+    direct.sort_by(|a, b| {
+        a.name
+            .cmp(&b.name)
+            .then_with(|| a.version.cmp(&b.version))
+            .then_with(|| a.path.cmp(&b.path))
+    });
+
+    // Walk parent links upward to count dependents at any depth
+    let target_paths: HashSet<&str> = targets.iter().map(|t| t.path.as_str()).collect();
+    let mut seen: HashSet<String> = HashSet::new();
+    let mut queue: VecDeque<&str> = target_paths.iter().copied().collect();
+    while let Some(path) = queue.pop_front() {
+        for (id, _) in parents.get(path).into_iter().flatten() {
+            if !target_paths.contains(id.path.as_str()) && seen.insert(id.path.clone()) {
+                queue.push_back(&id.path);
+            }
+        }
+    }
+
+    let root_manifest = std::path::Path::new(&graph.root).join("package.json");
+    let root_dependency = super::deps::read_package_deps(&root_manifest, true, true)
+        .is_ok_and(|deps| deps.deps.iter().any(|(dep, _)| dep == name));
+
+    WhyDependents {
+        direct,
+        transitive_count: seen.len(),
+        root_dependency,
+    }
+}
+
 /// Build lookup indices from the graph.
 fn build_indices(
     graph: &PackageGraph,
@@ -1056,7 +1164,7 @@ mod tests {
             .any(|e| e.code == why_codes::PKG_WHY_TARGET_AMBIGUOUS));
 
         // Should have a note about which was chosen
-        assert!(!result.notes.is_empty());
+        assert_ne!(result.notes, Vec::<String>::new());
     }
 
     #[test]
@@ -1121,6 +1229,7 @@ mod tests {
         let opts = WhyOptions {
             max_chains: 1,
             prefer_shortest: true,
+            dependents: false,
         };
         let result = why_from_graph(&graph, "target", &opts);
 
@@ -1191,6 +1300,7 @@ mod tests {
         let opts = WhyOptions {
             max_chains: 10,
             prefer_shortest: true,
+            dependents: false,
         };
 
         let result1 = why_from_graph(&graph, "target", &opts);
@@ -1300,4 +1410,144 @@ mod tests {
         assert_eq!(result1.target.version, Some("1.0.0".to_string()));
         assert_eq!(result2.target.version, Some("1.0.0".to_string()));
     }
+
+    fn dep_node(
+        root: &str,
+        name: &str,
+        version: &str,
+        path: &str,
+        deps: &[(&str, &str)],
+    ) -> PackageNode {
+        PackageNode::new(
+            PackageId {
+                name: name.to_string(),
+                version: version.to_string(),
+                path: format!("{root}/{path}"),
+                integrity: None,
+            },
+            deps.iter()
+                .map(|(dep, to_path)| DepEdge {
+                    name: (*dep).to_string(),
+                    req: Some("^1.0.0".to_string()),
+                    to: Some(PackageId {
+                        name: (*dep).to_string(),
+                        version: if to_path.contains("nested") {
+                            "2.0.0"
+                        } else {
+                            "1.0.0"
+                        }
+                        .to_string(),
+                        path: format!("{root}/{to_path}"),
+                        integrity: None,
+                    }),
+                    kind: "dep".to_string(),
+                })
+                .collect(),
+        )
+    }
+
+    #[test]
+    fn test_dependents_lists_direct_and_counts_transitive() {
+        let dir = tempfile::tempdir().unwrap();
+        let root = dir.path().to_string_lossy().to_string();
+        std::fs::write(
+            dir.path().join("package.json"),
+            r#"{"dependencies":{"app":"^1.0.0","target":"^1.0.0"}}"#,
+        )
+        .unwrap();
+
+        // app -> lib -> target, app -> target
+        let graph = PackageGraph {
+            schema_version: 1,
+            root: root.clone(),
+            nodes: vec![
+                dep_node(
+                    &root,
+                    "app",
+                    "1.0.0",
+                    "node_modules/app",
+                    &[
+                        ("lib", "node_modules/lib"),
+                        ("target", "node_modules/target"),
+                    ],
+                ),
+                dep_node(
+                    &root,
+                    "lib",
+                    "1.0.0",
+                    "node_modules/lib",
+                    &[("target", "node_modules/target")],
+                ),
+                dep_node(&root, "target", "1.0.0", "node_modules/target", &[]),
+            ],
+            orphans: vec![],
+            errors: vec![],
+        };
+
+        let opts = WhyOptions {
+            dependents: true,
+            ..WhyOptions::default()
+        };
+        let result = why_from_graph(&graph, "target", &opts);
+
+        assert!(result.chains.is_empty());
+        let dependents = result.dependents.expect("dependents");
+        let names: Vec<_> = dependents.direct.iter().map(|d| d.name.as_str()).collect();
+        assert_eq!(names, vec!["app", "lib"]);
+        assert_eq!(dependents.transitive_count, 2);
+        assert!(dependents.root_dependency);
+    }
+
+    #[test]
+    fn test_dependents_covers_all_installed_versions() {
+        let root = "/project";
+        let graph = PackageGraph {
+            schema_version: 1,
+            root: root.to_string(),
+            nodes: vec![
+                dep_node(
+                    root,
+                    "a",
+                    "1.0.0",
+                    "node_modules/a",
+                    &[("target", "node_modules/target")],
+                ),
+                dep_node(
+                    root,
+                    "b",
+                    "1.0.0",
+                    "node_modules/b",
+                    &[("target", "node_modules/b/node_modules/target-nested")],
+                ),
+                dep_node(root, "target", "1.0.0", "node_modules/target", &[]),
+                dep_node(
+                    root,
+                    "target",
+                    "2.0.0",
+                    "node_modules/b/node_modules/target-nested",
+                    &[],
+                ),
+            ],
+            orphans: vec![],
+            errors: vec![],
+        };
+
+        let opts = WhyOptions {
+            dependents: true,
+            ..WhyOptions::default()
+        };
+        let result = why_from_graph(&graph, "target", &opts);
+
+        assert!(result.errors.is_empty());
+        assert_eq!(result.notes.len(), 1);
+        let dependents = result.dependents.expect("dependents");
+        let pairs: Vec<_> = dependents
+            .direct
+            .iter()
+            .map(|d| (d.name.as_str(), d.resolved_version.as_str()))
+            .collect();
+        assert_eq!(pairs, vec![("a", "1.0.0"), ("b", "2.0.0")]);
+        assert_eq!(dependents.transitive_count, 2);
+        assert!(!dependents.root_dependency);
+    }
 }
@@ -0,0 +1,226 @@
+//! Global packages (`howth pkg add --global`).
+//!
+//! The global prefix is an ordinary project under the channel cache
+//! (see [`crate::paths::global_dir`]), so installs and removals go through
+//! the regular installer and linker. Executables of the top-level packages
+//! are additionally linked into `<prefix>/bin`, which users put on their PATH.
+
+use super::deps::read_package_deps;
+use super::error::PkgError;
+use super::link::{link_binaries_into, read_bin_entries};
+use fastnode_util::fs::atomic_write;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+/// Manifest written when the global prefix is first created.
+const GLOBAL_PACKAGE_JSON: &str = r#"{
+  "name": "howth-global",
+  "private": true,
+  "dependencies": {}
+}
+"#;
+
+/// An installed top-level package.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ListedPackage {
+    /// Package name (alias name for `npm:` aliases).
+    pub name: String,
+    /// Declared version range.
+    pub range: String,
+    /// Installed version, if present in `node_modules`.
+    pub version: Option<String>,
+    /// Executables provided by the package.
+    pub bins: Vec<String>,
+}
+
+/// Directory holding executable shims for global packages.
+#[must_use]
+pub fn global_bin_dir(prefix: &Path) -> PathBuf {
+    prefix.join("bin")
+}
+
+/// Create the global prefix and its `package.json` if missing.
+///
+/// # Errors
+/// Returns an error if the prefix cannot be created.
+pub fn ensure_global_prefix(prefix: &Path) -> Result<(), PkgError> {
+    fs::create_dir_all(prefix).map_err(|e| {
+        PkgError::cache_error(format!(
+            "Failed to create global prefix {}: {e}",
+            prefix.display()
+        ))
+    })?;
+
+    let package_json = prefix.join("package.json");
+    if !package_json.exists() {
+        atomic_write(&package_json, GLOBAL_PACKAGE_JSON.as_bytes()).map_err(|e| {
+            PkgError::cache_error(format!("Failed to write {}: {e}", package_json.display()))
+        })?;
+    }
+    Ok(())
+}
+
+/// Link the executables of a global package into the global bin dir.
+///
+/// Shims point through `<prefix>/node_modules/<name>` so the package's
+/// dependencies resolve from the regular layout.
+///
+/// # Errors
+/// Returns an error if the shims cannot be created.
+pub fn link_global_bins(prefix: &Path, name: &str) -> Result<Vec<PathBuf>, PkgError> {
+    let pkg_dir = prefix.join("node_modules").join(name);
+    link_binaries_into(&global_bin_dir(prefix), name, &pkg_dir, &pkg_dir)
+}
+
+/// Remove the executable shims of a global package.
+///
+/// Must run before the package itself is removed, since its `bin` field is
+/// read from the installed `package.json`. Returns the removed shim names.
+///
+/// # Errors
+/// Returns an error if a shim exists but cannot be removed.
+pub fn unlink_global_bins(prefix: &Path, name: &str) -> Result<Vec<String>, PkgError> {
+    let pkg_dir = prefix.join("node_modules").join(name);
+    let Ok(bins) = read_bin_entries(name, &pkg_dir) else {
+        return Ok(Vec::new());
+    };
+
+    let bin_dir = global_bin_dir(prefix);
+    let mut removed = Vec::new();
+    for (bin_name, _) in bins {
+        let mut shims = vec![bin_dir.join(&bin_name)];
+        if cfg!(windows) {
+            shims.push(bin_dir.join(format!("{bin_name}.cmd")));
+        }
+        let mut found = false;
+        for shim in shims {
+            if shim.symlink_metadata().is_ok() {
+                fs::remove_file(&shim).map_err(|e| {
+                    PkgError::link_failed(format!("Failed to remove {}: {e}", shim.display()))
+                })?;
+                found = true;
+            }
+        }
+        if found {
+            removed.push(bin_name);
+        }
+    }
+    Ok(removed)
+}
+
+/// List the top-level packages declared by `root/package.json`.
+///
+/// Used for both the global prefix and regular projects (including
+/// devDependencies and optionalDependencies). Sorted by name.
+///
+/// # Errors
+/// Returns an error if `package.json` is missing or invalid.
+pub fn list_packages(root: &Path) -> Result<Vec<ListedPackage>, PkgError> {
+    let deps = read_package_deps(&root.join("package.json"), true, true)?;
+
+    Ok(deps
+        .deps
+        .into_iter()
+        .map(|(name, range)| {
+            let pkg_dir = root.join("node_modules").join(&name);
+            let version = fs::read_to_string(pkg_dir.join("package.json"))
+                .ok()
+                .and_then(|content| serde_json::from_str::<serde_json::Value>(&content).ok())
+                .and_then(|json| json.get("version")?.as_str().map(str::to_string));
+            let bins = if version.is_some() {
+                read_bin_entries(&name, &pkg_dir)
+                    .unwrap_or_default()
+                    .into_iter()
+                    .map(|(bin_name, _)| bin_name)
+                    .collect()
+            } else {
+                Vec::new()
+            };
+            ListedPackage {
+                name,
+                range,
+                version,
+                bins,
+            }
+        })
+        .collect())
+}
+
+/// Whether `dir` is one of the entries of the `PATH` environment variable.
+#[must_use]
+pub fn is_on_path(dir: &Path) -> bool {
+    std::env::var_os("PATH").is_some_and(|path| std::env::split_paths(&path).any(|p| p == dir))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn install_fake(prefix: &Path, name: &str, bin: &str) {
+        let pkg_dir = prefix.join("node_modules").join(name);
+        fs::create_dir_all(pkg_dir.join("bin")).unwrap();
+        fs::write(
+            pkg_dir.join("package.json"),
+            format!(r#"{{"name":"{name}","version":"1.2.3","bin":{bin}}}"#),
+        )
+        .unwrap();
+        fs::write(pkg_dir.join("bin/cli.js"), "#!/usr/bin/env node\n").unwrap();
+    }
+
+    #[test]
+    fn test_ensure_global_prefix_keeps_existing_manifest() {
+        let dir = tempfile::tempdir().unwrap();
+        let prefix = dir.path().join("global");
+
+        ensure_global_prefix(&prefix).unwrap();
+        assert_eq!(list_packages(&prefix).unwrap(), []);
+
+        fs::write(
+            prefix.join("package.json"),
+            r#"{"dependencies":{"tool":"^1.0.0"}}"#,
+        )
+        .unwrap();
+        ensure_global_prefix(&prefix).unwrap();
+        let listed = list_packages(&prefix).unwrap();
+        assert_eq!(listed.len(), 1);
+        assert_eq!(listed[0].version, None);
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_link_and_unlink_global_bins() {
+        let dir = tempfile::tempdir().unwrap();
+        let prefix = dir.path();
+        ensure_global_prefix(prefix).unwrap();
+        fs::write(
+            prefix.join("package.json"),
+            r#"{"dependencies":{"@scope/tool":"^1.0.0"}}"#,
+        )
+        .unwrap();
+        install_fake(prefix, "@scope/tool", r#""bin/cli.js""#);
+
+        let linked = link_global_bins(prefix, "@scope/tool").unwrap();
+        assert_eq!(linked, vec![global_bin_dir(prefix).join("tool")]);
+        assert!(linked[0].exists());
+
+        let listed = list_packages(prefix).unwrap();
+        assert_eq!(listed[0].version.as_deref(), Some("1.2.3"));
+        assert_eq!(listed[0].bins, vec!["tool".to_string()]);
+
+        let removed = unlink_global_bins(prefix, "@scope/tool").unwrap();
+        assert_eq!(removed, vec!["tool".to_string()]);
+        assert!(global_bin_dir(prefix)
+            .join("tool")
+            .symlink_metadata()
+            .is_err());
+    }
+
+    #[test]
+    fn test_unlink_global_bins_missing_package() {
+        let dir = tempfile::tempdir().unwrap();
+        assert_eq!(
+            unlink_global_bins(dir.path(), "nope").unwrap(),
+            Vec::<String>::new()
+        );
+    }
+}
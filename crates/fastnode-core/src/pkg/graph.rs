@@ -829,7 +829,7 @@ mod tests {
         };
         let graph_with_dev = build_pkg_graph(root, &opts_with_dev, &cache);
         assert_eq!(graph_with_dev.nodes.len(), 2);
-        assert!(graph_with_dev.orphans.is_empty());
+        assert_eq!(graph_with_dev.orphans, []);
     }
 
     #[test]
@@ -0,0 +1,367 @@
+//! HTTP client construction for registry traffic.
+//!
+//! One client serves every packument and tarball request of a
+//! [`RegistryClient`](super::registry::RegistryClient), so connections stay
+//! alive and are reused (HTTP/2 when the registry negotiates it over TLS).
+//!
+//! Features:
+//! - Proxies from `.npmrc` (`proxy`, `https-proxy`, `noproxy`) or the
+//!   `HTTP_PROXY`/`HTTPS_PROXY`/`NO_PROXY` environment variables
+//! - Extra CA certificates from `cafile`/`ca` or `NODE_EXTRA_CA_CERTS`, and
+//!   `strict-ssl=false` for self-signed corporate registries
+//! - A separate DNS timeout (`FASTNODE_DNS_TIMEOUT`, in seconds), so a broken
+//!   resolver is reported as such instead of as a connect timeout
+//! - `fetch-timeout` for the overall request timeout
+
+use super::error::PkgError;
+use super::npmrc::NpmrcConfig;
+use reqwest::dns::{Addrs, Name, Resolve, Resolving};
+use reqwest::{Certificate, Client, NoProxy, Proxy};
+use std::path::PathBuf;
+use std::sync::Arc;
+use std::time::Duration;
+
+/// Environment variable overriding the DNS lookup timeout, in seconds.
+pub const DNS_TIMEOUT_ENV: &str = "FASTNODE_DNS_TIMEOUT";
+
+/// How long a DNS lookup may take.
+pub const DEFAULT_DNS_TIMEOUT: Duration = Duration::from_secs(5);
+
+/// How long the TCP and TLS handshakes may take once the host is resolved.
+pub const DEFAULT_CONNECT_TIMEOUT: Duration = Duration::from_secs(5);
+
+/// Overall timeout for a request, unless `.npmrc` sets `fetch-timeout`.
+pub const DEFAULT_REQUEST_TIMEOUT: Duration = Duration::from_secs(30);
+
+/// Proxy URLs for registry requests.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct ProxyConfig {
+    /// Proxy for `http://` registries.
+    pub http: Option<String>,
+    /// Proxy for `https://` registries.
+    pub https: Option<String>,
+    /// Hosts that bypass the proxy (comma-separated, `NO_PROXY` syntax).
+    pub no_proxy: Option<String>,
+}
+
+impl ProxyConfig {
+    /// Resolve proxies from `.npmrc` settings, falling back to the
+    /// environment (read through `env`).
+    ///
+    /// `https://` requests use `https-proxy`, then `proxy`, then
+    /// `HTTPS_PROXY`; `http://` requests use `proxy`, then `HTTP_PROXY`.
+    /// Lowercase environment variables are accepted too.
+    #[must_use]
+    pub fn resolve(npmrc: &NpmrcConfig, env: impl Fn(&str) -> Option<String>) -> Self {
+        let env_any = |names: &[&str]| {
+            names
+                .iter()
+                .find_map(|name| env(name))
+                .filter(|value| !value.trim().is_empty())
+        };
+
+        Self {
+            http: npmrc
+                .proxy
+                .clone()
+                .or_else(|| env_any(&["HTTP_PROXY", "http_proxy"])),
+            https: npmrc
+                .https_proxy
+                .clone()
+                .or_else(|| npmrc.proxy.clone())
+                .or_else(|| env_any(&["HTTPS_PROXY", "https_proxy"])),
+            no_proxy: npmrc
+                .no_proxy
+                .clone()
+                .or_else(|| env_any(&["NO_PROXY", "no_proxy"])),
+        }
+    }
+}
+
+/// Settings for the registry HTTP client.
+#[derive(Debug, Clone)]
+pub struct HttpConfig {
+    /// Proxies to route requests through.
+    pub proxy: ProxyConfig,
+    /// PEM files with CA certificates trusted in addition to the built-in roots.
+    pub ca_files: Vec<PathBuf>,
+    /// Inline PEM CA certificates (`.npmrc` `ca`).
+    pub ca: Vec<String>,
+    /// Whether TLS certificates are verified (`strict-ssl`).
+    pub strict_ssl: bool,
+    /// Timeout for resolving a registry host.
+    pub dns_timeout: Duration,
+    /// Timeout for the TCP and TLS handshakes.
+    pub connect_timeout: Duration,
+    /// Overall timeout for a request.
+    pub request_timeout: Duration,
+}
+
+impl Default for HttpConfig {
+    fn default() -> Self {
+        Self {
+            proxy: ProxyConfig::default(),
+            ca_files: Vec::new(),
+            ca: Vec::new(),
+            strict_ssl: true,
+            dns_timeout: DEFAULT_DNS_TIMEOUT,
+            connect_timeout: DEFAULT_CONNECT_TIMEOUT,
+            request_timeout: DEFAULT_REQUEST_TIMEOUT,
+        }
+    }
+}
+
+impl HttpConfig {
+    /// Settings from the environment only (no `.npmrc`).
+    #[must_use]
+    pub fn from_env() -> Self {
+        Self::from_npmrc(&NpmrcConfig::default())
+    }
+
+    /// Settings from `.npmrc`, with the environment as fallback.
+    #[must_use]
+    pub fn from_npmrc(npmrc: &NpmrcConfig) -> Self {
+        let env = |name: &str| std::env::var(name).ok();
+
+        let mut ca_files: Vec<PathBuf> = npmrc.cafile.iter().cloned().collect();
+        if let Some(extra) = env("NODE_EXTRA_CA_CERTS").filter(|p| !p.is_empty()) {
+            ca_files.push(PathBuf::from(extra));
+        }
+
+        let dns_timeout = env(DNS_TIMEOUT_ENV)
+            .and_then(|secs| secs.trim().parse().ok())
+            .filter(|&secs| secs > 0)
+            .map_or(DEFAULT_DNS_TIMEOUT, Duration::from_secs);
+
+        Self {
+            proxy: ProxyConfig::resolve(npmrc, env),
+            ca_files,
+            ca: npmrc.ca.clone(),
+            strict_ssl: npmrc.strict_ssl.unwrap_or(true),
+            dns_timeout,
+            connect_timeout: DEFAULT_CONNECT_TIMEOUT,
+            request_timeout: npmrc
+                .fetch_timeout
+                .filter(|&ms| ms > 0)
+                .map_or(DEFAULT_REQUEST_TIMEOUT, Duration::from_millis),
+        }
+    }
+
+    /// Build the HTTP client.
+    ///
+    /// # Errors
+    /// Returns an error if a proxy URL is invalid or a CA file can't be read
+    /// or parsed.
+    pub fn build_client(&self) -> Result<Client, PkgError> {
+        let mut builder = Client::builder()
+            // reqwest's connect timeout includes the DNS lookup, which has
+            // its own budget in the resolver
+            .connect_timeout(self.dns_timeout + self.connect_timeout)
+            .timeout(self.request_timeout)
+            .dns_resolver(Arc::new(TimeoutResolver {
+                timeout: self.dns_timeout,
+            }))
+            .pool_max_idle_per_host(32) // More connections for parallel fetches
+            .pool_idle_timeout(Duration::from_secs(90))
+            .tcp_keepalive(Duration::from_secs(60))
+            .http2_adaptive_window(true)
+            .user_agent(concat!("howth/", env!("CARGO_PKG_VERSION")))
+            // ProxyConfig::resolve already read the environment, so
+            // turn off reqwest's own detection
+            .no_proxy();
+
+        let no_proxy = self
+            .proxy
+            .no_proxy
+            .as_deref()
+            .and_then(NoProxy::from_string);
+        if let Some(url) = &self.proxy.http {
+            let proxy = Proxy::http(url)
+                .map_err(|e| PkgError::registry(format!("Invalid proxy URL '{url}': {e}")))?;
+            builder = builder.proxy(proxy.no_proxy(no_proxy.clone()));
+        }
+        if let Some(url) = &self.proxy.https {
+            let proxy = Proxy::https(url)
+                .map_err(|e| PkgError::registry(format!("Invalid proxy URL '{url}': {e}")))?;
+            builder = builder.proxy(proxy.no_proxy(no_proxy));
+        }
+
+        for path in &self.ca_files {
+            let pem = std::fs::read(path).map_err(|e| {
+                PkgError::registry(format!("Failed to read CA file {}: {e}", path.display()))
+            })?;
+            for cert in parse_pem_bundle(&pem, &path.display().to_string())? {
+                builder = builder.add_root_certificate(cert);
+            }
+        }
+        for pem in &self.ca {
+            // `.npmrc` stores certificates on one line with literal "\n"
+            let pem = pem.replace("\\n", "\n");
+            for cert in parse_pem_bundle(pem.as_bytes(), "ca")? {
+                builder = builder.add_root_certificate(cert);
+            }
+        }
+
+        if !self.strict_ssl {
+            builder = builder.danger_accept_invalid_certs(true);
+        }
+
+        builder
+            .build()
+            .map_err(|e| PkgError::registry(format!("Failed to create HTTP client: {e}")))
+    }
+}
+
+/// Parse PEM certificates, failing if there are none.
+fn parse_pem_bundle(pem: &[u8], source: &str) -> Result<Vec<Certificate>, PkgError> {
+    let certs = Certificate::from_pem_bundle(pem)
+        .map_err(|e| PkgError::registry(format!("Invalid CA certificate in {source}: {e}")))?;
+    if certs.is_empty() {
+        return Err(PkgError::registry(format!(
+            "No CA certificates found in {source}"
+        )));
+    }
+    Ok(certs)
+}
+
+/// System DNS resolution with a timeout.
+#[derive(Debug)]
+struct TimeoutResolver {
+    timeout: Duration,
+}
+
+impl Resolve for TimeoutResolver {
+    fn resolve(&self, name: Name) -> Resolving {
+        let timeout = self.timeout;
+        let host = name.as_str().to_string();
+        Box::pin(async move {
+            match tokio::time::timeout(timeout, tokio::net::lookup_host((host.as_str(), 0))).await {
+                Ok(Ok(addrs)) => {
+                    let addrs: Addrs = Box::new(addrs.collect::<Vec<_>>().into_iter());
+                    Ok(addrs)
+                }
+                Ok(Err(e)) => Err(e.into()),
+                Err(_) => Err(format!(
+                    "DNS lookup for {host} timed out after {}s",
+                    timeout.as_secs_f32()
+                )
+                .into()),
+            }
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::pkg::npmrc::parse_npmrc;
+
+    fn env_from(vars: &'static [(&'static str, &'static str)]) -> impl Fn(&str) -> Option<String> {
+        move |name| {
+            vars.iter()
+                .find(|(key, _)| *key == name)
+                .map(|(_, value)| (*value).to_string())
+        }
+    }
+
+    #[test]
+    fn test_proxy_from_env() {
+        let proxy = ProxyConfig::resolve(
+            &NpmrcConfig::default(),
+            env_from(&[
+                ("https_proxy", "http://secure.proxy:3128"),
+                ("HTTP_PROXY", "http://plain.proxy:3128"),
+                ("NO_PROXY", "localhost,.internal"),
+            ]),
+        );
+        assert_eq!(proxy.http.as_deref(), Some("http://plain.proxy:3128"));
+        assert_eq!(proxy.https.as_deref(), Some("http://secure.proxy:3128"));
+        assert_eq!(proxy.no_proxy.as_deref(), Some("localhost,.internal"));
+    }
+
+    #[test]
+    fn test_npmrc_proxy_wins_over_env() {
+        let npmrc = parse_npmrc("proxy=http://npmrc.proxy:8080\nnoproxy=registry.local\n");
+        let proxy = ProxyConfig::resolve(
+            &npmrc,
+            env_from(&[("HTTPS_PROXY", "http://env.proxy:3128"), ("NO_PROXY", "*")]),
+        );
+        // `proxy` also covers https when `https-proxy` isn't set
+        assert_eq!(proxy.http.as_deref(), Some("http://npmrc.proxy:8080"));
+        assert_eq!(proxy.https.as_deref(), Some("http://npmrc.proxy:8080"));
+        assert_eq!(proxy.no_proxy.as_deref(), Some("registry.local"));
+
+        let npmrc = parse_npmrc("proxy=http://a:1\nhttps-proxy=http://b:2\n");
+        let proxy = ProxyConfig::resolve(&npmrc, env_from(&[]));
+        assert_eq!(proxy.https.as_deref(), Some("http://b:2"));
+    }
+
+    #[test]
+    fn test_build_client_rejects_bad_ca() {
+        let temp = tempfile::tempdir().unwrap();
+        let missing = HttpConfig {
+            ca_files: vec![temp.path().join("missing.pem")],
+            ..HttpConfig::default()
+        };
+        assert!(missing.build_client().is_err());
+
+        let not_pem = HttpConfig {
+            ca: vec!["not a certificate".to_string()],
+            ..HttpConfig::default()
+        };
+        assert!(not_pem.build_client().is_err());
+
+        assert!(HttpConfig::default().build_client().is_ok());
+    }
+
+    #[tokio::test]
+    async fn test_requests_go_through_proxy() {
+        use std::io::{BufRead, BufReader, Write};
+        use std::net::TcpListener;
+
+        // A proxy that answers every request itself and reports the target
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let addr = listener.local_addr().unwrap();
+        std::thread::spawn(move || {
+            for stream in listener.incoming() {
+                let mut stream = stream.unwrap();
+                let mut reader = BufReader::new(stream.try_clone().unwrap());
+                let mut request_line = String::new();
+                reader.read_line(&mut request_line).unwrap();
+                loop {
+                    let mut line = String::new();
+                    reader.read_line(&mut line).unwrap();
+                    if line.trim().is_empty() {
+                        break;
+                    }
+                }
+                let target = request_line.split_whitespace().nth(1).unwrap_or_default();
+                write!(
+                    stream,
+                    "HTTP/1.1 200 OK\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{target}",
+                    target.len()
+                )
+                .unwrap();
+            }
+        });
+
+        let config = HttpConfig {
+            proxy: ProxyConfig {
+                http: Some(format!("http://{addr}")),
+                ..ProxyConfig::default()
+            },
+            ..HttpConfig::default()
+        };
+        let client = config.build_client().unwrap();
+        // The host doesn't exist; only the proxy can answer
+        let body = client
+            .get("http://registry.invalid/left-pad")
+            .send()
+            .await
+            .unwrap()
+            .text()
+            .await
+            .unwrap();
+        assert_eq!(body, "http://registry.invalid/left-pad");
+    }
+}
@@ -0,0 +1,176 @@
+//! Integrity checks for downloaded tarballs.
+//!
+//! Lockfiles record either a Subresource Integrity string
+//! (`sha512-<base64>`, possibly several space-separated hashes) or, for
+//! old packuments, a hex `shasum` (SHA-1). Tarballs are checked against
+//! the strongest algorithm present, like npm's `ssri`.
+
+use crate::sourcemap::base64_encode;
+use sha1::Sha1;
+use sha2::{Digest, Sha256, Sha384, Sha512};
+use std::fmt::Write;
+
+/// Outcome of checking tarball bytes against an expected integrity value.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum IntegrityCheck {
+    /// The bytes match the expected hash.
+    Verified,
+    /// The bytes hash to `actual`, which is not what the lockfile expects.
+    Mismatch {
+        /// The expected value, as recorded.
+        expected: String,
+        /// The computed value, in the same notation.
+        actual: String,
+    },
+    /// The value uses no supported algorithm, so nothing was checked.
+    Unsupported,
+}
+
+/// Hash algorithms accepted in integrity strings, weakest first.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+enum Algorithm {
+    Sha1,
+    Sha256,
+    Sha384,
+    Sha512,
+}
+
+impl Algorithm {
+    fn parse(name: &str) -> Option<Self> {
+        match name {
+            "sha1" => Some(Self::Sha1),
+            "sha256" => Some(Self::Sha256),
+            "sha384" => Some(Self::Sha384),
+            "sha512" => Some(Self::Sha512),
+            _ => None,
+        }
+    }
+
+    fn name(self) -> &'static str {
+        match self {
+            Self::Sha1 => "sha1",
+            Self::Sha256 => "sha256",
+            Self::Sha384 => "sha384",
+            Self::Sha512 => "sha512",
+        }
+    }
+
+    fn digest(self, bytes: &[u8]) -> Vec<u8> {
+        match self {
+            Self::Sha1 => Sha1::digest(bytes).to_vec(),
+            Self::Sha256 => Sha256::digest(bytes).to_vec(),
+            Self::Sha384 => Sha384::digest(bytes).to_vec(),
+            Self::Sha512 => Sha512::digest(bytes).to_vec(),
+        }
+    }
+}
+
+/// Check `bytes` against `expected`, an SRI string or a hex SHA-1 shasum.
+#[must_use]
+pub fn check_integrity(bytes: &[u8], expected: &str) -> IntegrityCheck {
+    let expected = expected.trim();
+
+    if expected.len() == 40 && expected.bytes().all(|b| b.is_ascii_hexdigit()) {
+        let actual = Sha1::digest(bytes)
+            .iter()
+            .fold(String::with_capacity(40), |mut hex, b| {
+                let _ = write!(hex, "{b:02x}");
+                hex
+            });
+        return if actual.eq_ignore_ascii_case(expected) {
+            IntegrityCheck::Verified
+        } else {
+            IntegrityCheck::Mismatch {
+                expected: expected.to_string(),
+                actual,
+            }
+        };
+    }
+
+    // "<algo>-<base64>[?<options>]", space separated
+    let hashes: Vec<(Algorithm, &str)> = expected
+        .split_whitespace()
+        .filter_map(|entry| {
+            let (algorithm, hash) = entry.split_once('-')?;
+            let hash = hash.split('?').next().unwrap_or(hash);
+            Some((Algorithm::parse(algorithm)?, hash))
+        })
+        .collect();
+    let Some(strongest) = hashes.iter().map(|&(algorithm, _)| algorithm).max() else {
+        return IntegrityCheck::Unsupported;
+    };
+
+    let actual = base64_encode(&strongest.digest(bytes));
+    let matches = hashes
+        .iter()
+        .filter(|&&(algorithm, _)| algorithm == strongest)
+        .any(|&(_, hash)| hash.trim_end_matches('=') == actual.trim_end_matches('='));
+    if matches {
+        IntegrityCheck::Verified
+    } else {
+        IntegrityCheck::Mismatch {
+            expected: expected.to_string(),
+            actual: format!("{}-{actual}", strongest.name()),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // Hashes of b"hello"
+    const SHA512: &str = "sha512-m3HSJL1i83hdltRq0+o9czGb+8KJDKra4t/3JRlnPKcjI8PZm6XBHXx6zG4UuMXaDEZjR1wuXDre9G9zvN7AQw==";
+    const SHA1_HEX: &str = "aaf4c61ddcc5e8a2dabede0f3b482cd9aea9434d";
+
+    #[test]
+    fn test_sri_match_and_mismatch() {
+        assert_eq!(check_integrity(b"hello", SHA512), IntegrityCheck::Verified);
+
+        match check_integrity(b"hello!", SHA512) {
+            IntegrityCheck::Mismatch { expected, actual } => {
+                assert_eq!(expected, SHA512);
+                assert!(actual.starts_with("sha512-"));
+                assert_ne!(actual, SHA512);
+            }
+            other => panic!("expected mismatch, got {other:?}"),
+        }
+    }
+
+    #[test]
+    fn test_strongest_algorithm_wins() {
+        // A wrong sha1 is ignored when a sha512 is present
+        let multi = format!("sha1-AAAA {SHA512}?foo");
+        assert_eq!(check_integrity(b"hello", &multi), IntegrityCheck::Verified);
+        let multi = format!("{SHA512} sha1-qvTGHdzF6KLavt4PO0gs2a6pQ00=");
+        assert!(matches!(
+            check_integrity(b"other", &multi),
+            IntegrityCheck::Mismatch { .. }
+        ));
+    }
+
+    #[test]
+    fn test_hex_shasum() {
+        assert_eq!(
+            check_integrity(b"hello", SHA1_HEX),
+            IntegrityCheck::Verified
+        );
+        assert_eq!(
+            check_integrity(b"hello", &SHA1_HEX.to_uppercase()),
+            IntegrityCheck::Verified
+        );
+        assert!(matches!(
+            check_integrity(b"bye", SHA1_HEX),
+            IntegrityCheck::Mismatch { .. }
+        ));
+    }
+
+    #[test]
+    fn test_unsupported_values() {
+        assert_eq!(check_integrity(b"hello", ""), IntegrityCheck::Unsupported);
+        assert_eq!(
+            check_integrity(b"hello", "md5-XUFAKrxLKna5cZ2REBfFkg=="),
+            IntegrityCheck::Unsupported
+        );
+    }
+}
@@ -0,0 +1,503 @@
+//! Deterministic `node_modules` layout computed from the lockfile.
+//!
+//! Two layouts are supported, selected with `node-linker` in `.npmrc`:
+//!
+//! - **Isolated** (default): every package lives in
+//!   `node_modules/.pnpm/<name>@<version>/node_modules/<name>` and sees only
+//!   its own dependencies (see [`super::link`]).
+//! - **Hoisted**: npm-style flat tree. Each dependency is placed in the
+//!   highest `node_modules` directory where it doesn't conflict with another
+//!   version, and nested below the conflicting package otherwise.
+//!
+//! Both layouts choose one version per name for the top of `node_modules`
+//! with the same hoisting pass, so the choice never depends on install order.
+//! Packages are visited breadth-first from the root dependencies, in name
+//! order, so the same lockfile always produces the same tree.
+//!
+//! The computed layout is recorded in `node_modules/.howth-layout.json`
+//! after an install, so `pkg doctor` can verify the tree still matches it.
+//! The record also names the channel whose package cache the tree was
+//! installed from, so `howth doctor` can flag a project shared by channels.
+
+use super::link::format_pnpm_key;
+use super::lockfile::Lockfile;
+use super::workspaces::WorkspaceConfig;
+use crate::config::Channel;
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, HashMap, VecDeque};
+use std::io;
+use std::path::Path;
+
+/// Schema version for the layout record.
+pub const LAYOUT_SCHEMA_VERSION: u32 = 1;
+
+/// Layout record filename, inside `node_modules`.
+pub const LAYOUT_FILE_NAME: &str = ".howth-layout.json";
+
+/// How packages are laid out in `node_modules`.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum NodeLinker {
+    /// pnpm-style: packages in `.pnpm`, linked to their dependencies.
+    #[default]
+    Isolated,
+    /// npm-style: a flat tree, nesting only conflicting versions.
+    Hoisted,
+}
+
+impl NodeLinker {
+    /// Parse a `node-linker` value.
+    #[must_use]
+    pub fn parse(s: &str) -> Option<Self> {
+        match s {
+            "isolated" => Some(Self::Isolated),
+            "hoisted" => Some(Self::Hoisted),
+            _ => None,
+        }
+    }
+
+    /// The `node-linker` value.
+    #[must_use]
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::Isolated => "isolated",
+            Self::Hoisted => "hoisted",
+        }
+    }
+}
+
+/// A `node_modules` layout: where each locked package is installed.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct NodeModulesLayout {
+    /// Schema version for the layout record.
+    pub schema_version: u32,
+    /// Layout the tree uses.
+    pub node_linker: NodeLinker,
+    /// Content hash of the lockfile the layout was computed from.
+    pub lockfile_hash: String,
+    /// Install directory (relative to the project root, `/`-separated) ->
+    /// package key (`name@version`).
+    pub packages: BTreeMap<String, String>,
+    /// Channel that installed the tree, once recorded.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub channel: Option<Channel>,
+}
+
+impl NodeModulesLayout {
+    /// Compute the layout of `lockfile`.
+    ///
+    /// Root dependencies of kind `dev` and `optional` are left out unless
+    /// included, along with everything only they depend on.
+    #[must_use]
+    pub fn compute(
+        lockfile: &Lockfile,
+        node_linker: NodeLinker,
+        include_dev: bool,
+        include_optional: bool,
+    ) -> Self {
+        let hoisted = hoist(lockfile, include_dev, include_optional);
+        let packages = match node_linker {
+            NodeLinker::Hoisted => hoisted,
+            NodeLinker::Isolated => {
+                let mut packages: BTreeMap<String, String> = hoisted
+                    .iter()
+                    .filter(|(path, _)| !path["node_modules/".len()..].contains("/node_modules/"))
+                    .map(|(path, key)| (path.clone(), key.clone()))
+                    .collect();
+                for key in hoisted.values() {
+                    let name = package_name(key);
+                    let version = &key[name.len() + 1..];
+                    packages.insert(
+                        format!(
+                            "node_modules/.pnpm/{}/node_modules/{name}",
+                            format_pnpm_key(name, version)
+                        ),
+                        key.clone(),
+                    );
+                }
+                packages
+            }
+        };
+        Self {
+            schema_version: LAYOUT_SCHEMA_VERSION,
+            node_linker,
+            lockfile_hash: super::lockfile::lockfile_content_hash(lockfile),
+            packages,
+            channel: None,
+        }
+    }
+
+    /// Account for workspace packages, which the installer links straight
+    /// from the workspace into the top of `node_modules`: no other copies of
+    /// them are installed.
+    pub fn link_workspace_packages(&mut self, workspaces: &WorkspaceConfig) {
+        self.packages.retain(|install_dir, key| {
+            let name = package_name(key);
+            let is_workspace = workspaces
+                .get_package(name)
+                .is_some_and(|ws| key[name.len() + 1..] == ws.version);
+            !is_workspace || *install_dir == format!("node_modules/{name}")
+        });
+    }
+
+    /// Packages installed directly in the project's `node_modules`, as
+    /// (package name, install directory, package key).
+    pub fn top_level(&self) -> impl Iterator<Item = (&str, &str, &str)> {
+        self.packages.iter().filter_map(|(path, key)| {
+            let name = path.strip_prefix("node_modules/")?;
+            (!name.starts_with('.') && !name.contains("/node_modules/")).then_some((
+                name,
+                path.as_str(),
+                key.as_str(),
+            ))
+        })
+    }
+
+    /// Read the layout recorded in `project_root/node_modules`.
+    ///
+    /// Returns `None` if there is no record or it can't be read.
+    #[must_use]
+    pub fn read_from(project_root: &Path) -> Option<Self> {
+        let path = project_root.join("node_modules").join(LAYOUT_FILE_NAME);
+        let content = std::fs::read_to_string(path).ok()?;
+        serde_json::from_str::<Self>(&content)
+            .ok()
+            .filter(|layout| layout.schema_version == LAYOUT_SCHEMA_VERSION)
+    }
+
+    /// Record the layout in `project_root/node_modules`.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the record cannot be written.
+    pub fn write_to(&self, project_root: &Path) -> io::Result<()> {
+        let path = project_root.join("node_modules").join(LAYOUT_FILE_NAME);
+        let content = serde_json::to_string_pretty(self).map_err(io::Error::other)?;
+        fastnode_util::fs::atomic_write(&path, content.as_bytes())
+    }
+}
+
+/// Package name of a package key (`name@version`).
+fn package_name(key: &str) -> &str {
+    key.rsplit_once('@').map_or(key, |(name, _)| name)
+}
+
+/// The `node_modules` directories a package at `path` resolves from,
+/// nearest first.
+fn nested_dirs(path: &str) -> Vec<String> {
+    let mut dirs = vec![format!("{path}/node_modules")];
+    let mut rest = path;
+    while let Some(index) = rest.rfind("/node_modules/") {
+        rest = &rest[..index];
+        dirs.push(format!("{rest}/node_modules"));
+    }
+    dirs.push("node_modules".to_string());
+    dirs
+}
+
+/// Find the `node_modules` directory `name` resolves from for the package
+/// at `path`.
+fn resolve_from(placed: &BTreeMap<String, String>, path: &str, name: &str) -> Option<String> {
+    nested_dirs(path)
+        .into_iter()
+        .find(|dir| placed.contains_key(&format!("{dir}/{name}")))
+}
+
+/// Compute the npm-style hoisted tree: install directory -> package key.
+fn hoist(
+    lockfile: &Lockfile,
+    include_dev: bool,
+    include_optional: bool,
+) -> BTreeMap<String, String> {
+    let deps = resolve_edges(lockfile);
+    let mut placed = BTreeMap::new();
+    let mut queue = VecDeque::new();
+
+    for (name, dep) in &lockfile.dependencies {
+        if (dep.kind == "dev" && !include_dev) || (dep.kind == "optional" && !include_optional) {
+            continue;
+        }
+        let key = Lockfile::package_key(name, &dep.resolved);
+        if !lockfile.packages.contains_key(&key) {
+            continue;
+        }
+        let path = format!("node_modules/{name}");
+        placed.insert(path.clone(), key);
+        queue.push_back(path);
+    }
+
+    while let Some(path) = queue.pop_front() {
+        let key = placed[&path].clone();
+        let Some(edges) = deps.get(&key) else {
+            continue;
+        };
+        for (name, dep_key) in edges {
+            if let Some(slot) = place(&placed, &deps, &path, name, dep_key) {
+                placed.insert(slot.clone(), dep_key.clone());
+                queue.push_back(slot);
+            }
+        }
+    }
+
+    placed
+}
+
+/// Choose where to install `dep_key` (named `name`) for the package at
+/// `path`.
+///
+/// Returns `None` if the package already resolves to it.
+fn place(
+    placed: &BTreeMap<String, String>,
+    deps: &HashMap<String, BTreeMap<String, String>>,
+    path: &str,
+    name: &str,
+    dep_key: &str,
+) -> Option<String> {
+    let dirs = nested_dirs(path);
+    let mut candidates = Vec::new();
+    for dir in &dirs {
+        match placed.get(&format!("{dir}/{name}")) {
+            Some(key) if key == dep_key => return None,
+            Some(_) => break,
+            None => candidates.push(dir.as_str()),
+        }
+    }
+
+    // A cycle of conflicting versions would otherwise nest forever
+    let is_ancestor = dirs[1..]
+        .iter()
+        .filter_map(|dir| dir.strip_suffix("/node_modules"))
+        .chain(std::iter::once(path))
+        .any(|ancestor| placed.get(ancestor).is_some_and(|key| key == dep_key));
+    if is_ancestor {
+        return None;
+    }
+
+    // Highest directory first, unless the package would shadow another
+    // version for a package below it
+    let nearest = candidates.first().copied()?;
+    let dir = candidates
+        .iter()
+        .rev()
+        .copied()
+        .find(|dir| *dir == nearest || !shadows(placed, deps, dir, name, dep_key))
+        .unwrap_or(nearest);
+    Some(format!("{dir}/{name}"))
+}
+
+/// Whether installing `dep_key` in `dir` changes what `name` resolves to
+/// for a package that needs a different version.
+fn shadows(
+    placed: &BTreeMap<String, String>,
+    deps: &HashMap<String, BTreeMap<String, String>>,
+    dir: &str,
+    name: &str,
+    dep_key: &str,
+) -> bool {
+    let prefix = dir.strip_suffix("node_modules").unwrap_or(dir);
+    placed
+        .iter()
+        .filter(|(path, _)| path.starts_with(prefix))
+        .any(|(path, key)| {
+            let Some(wanted) = deps.get(key).and_then(|edges| edges.get(name)) else {
+                return false;
+            };
+            if wanted == dep_key {
+                return false;
+            }
+            // Unaffected when resolved from a directory below `dir`, or not
+            // placed yet
+            resolve_from(placed, path, name)
+                .is_some_and(|found| !(found.len() > dir.len() && found.starts_with(prefix)))
+        })
+}
+
+/// Resolve each locked package's dependencies to package keys.
+///
+/// Peers are installed like dependencies, as npm 7+ does.
+fn resolve_edges(lockfile: &Lockfile) -> HashMap<String, BTreeMap<String, String>> {
+    lockfile
+        .packages
+        .iter()
+        .map(|(key, pkg)| {
+            let mut edges = BTreeMap::new();
+            for (name, range) in pkg
+                .dependencies
+                .iter()
+                .chain(&pkg.optional_dependencies)
+                .chain(&pkg.peer_dependencies)
+            {
+                if edges.contains_key(name) {
+                    continue;
+                }
+                if let Some(dep) = lockfile.best_match(name, range) {
+                    edges.insert(name.clone(), Lockfile::package_key(name, &dep.version));
+                }
+            }
+            (key.clone(), edges)
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::pkg::lockfile::{LockDep, LockPackage, LockRoot};
+
+    fn lockfile(root: &[(&str, &str)], packages: &[(&str, &str, &[(&str, &str)])]) -> Lockfile {
+        let mut lockfile = Lockfile::new(LockRoot::new("app", None));
+        for (name, version) in root {
+            lockfile.add_dependency(*name, LockDep::new(*version, "dep", *version));
+        }
+        for (name, version, deps) in packages {
+            let mut pkg = LockPackage::new(*version, "");
+            for (dep, range) in *deps {
+                pkg.add_dependency(*dep, *range);
+            }
+            lockfile.add_package(name, pkg);
+        }
+        lockfile
+    }
+
+    fn paths(layout: &NodeModulesLayout) -> Vec<(&str, &str)> {
+        layout
+            .packages
+            .iter()
+            .map(|(path, key)| (path.as_str(), key.as_str()))
+            .collect()
+    }
+
+    #[test]
+    fn test_nested_dirs() {
+        assert_eq!(
+            nested_dirs("node_modules/a/node_modules/@s/b"),
+            [
+                "node_modules/a/node_modules/@s/b/node_modules",
+                "node_modules/a/node_modules",
+                "node_modules",
+            ]
+        );
+    }
+
+    #[test]
+    fn test_hoisted_nests_conflicting_versions() {
+        let lockfile = lockfile(
+            &[("a", "1.0.0"), ("c", "1.0.0")],
+            &[
+                ("a", "1.0.0", &[("b", "^1.0.0"), ("c", "^2.0.0")]),
+                ("b", "1.0.0", &[("c", "^1.0.0")]),
+                ("c", "1.0.0", &[]),
+                ("c", "2.0.0", &[]),
+            ],
+        );
+        let layout = NodeModulesLayout::compute(&lockfile, NodeLinker::Hoisted, false, false);
+        assert_eq!(
+            paths(&layout),
+            [
+                ("node_modules/a", "a@1.0.0"),
+                ("node_modules/a/node_modules/c", "c@2.0.0"),
+                ("node_modules/b", "b@1.0.0"),
+                ("node_modules/c", "c@1.0.0"),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_hoisted_does_not_shadow_resolved_dependencies() {
+        // a's b@1 resolves d@1 from the top, so a's e@1 can't bring d@2 up
+        // next to b
+        let lockfile = lockfile(
+            &[
+                ("a", "1.0.0"),
+                ("b", "2.0.0"),
+                ("d", "1.0.0"),
+                ("e", "2.0.0"),
+            ],
+            &[
+                ("a", "1.0.0", &[("b", "1.0.0"), ("e", "1.0.0")]),
+                ("b", "1.0.0", &[("d", "1.0.0")]),
+                ("b", "2.0.0", &[]),
+                ("d", "1.0.0", &[]),
+                ("d", "2.0.0", &[]),
+                ("e", "1.0.0", &[("d", "2.0.0")]),
+                ("e", "2.0.0", &[]),
+            ],
+        );
+        let layout = NodeModulesLayout::compute(&lockfile, NodeLinker::Hoisted, false, false);
+        assert_eq!(
+            paths(&layout),
+            [
+                ("node_modules/a", "a@1.0.0"),
+                ("node_modules/a/node_modules/b", "b@1.0.0"),
+                ("node_modules/a/node_modules/e", "e@1.0.0"),
+                ("node_modules/a/node_modules/e/node_modules/d", "d@2.0.0"),
+                ("node_modules/b", "b@2.0.0"),
+                ("node_modules/d", "d@1.0.0"),
+                ("node_modules/e", "e@2.0.0"),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_layout_skips_dev_dependencies_and_is_deterministic() {
+        let mut lockfile = lockfile(
+            &[("a", "1.0.0")],
+            &[("a", "1.0.0", &[]), ("jest", "29.0.0", &[])],
+        );
+        lockfile.add_dependency("jest", LockDep::new("^29.0.0", "dev", "29.0.0"));
+
+        let layout = NodeModulesLayout::compute(&lockfile, NodeLinker::Hoisted, false, false);
+        assert_eq!(paths(&layout), [("node_modules/a", "a@1.0.0")]);
+        let layout = NodeModulesLayout::compute(&lockfile, NodeLinker::Hoisted, true, false);
+        assert_eq!(layout.packages.len(), 2);
+        assert_eq!(
+            layout,
+            NodeModulesLayout::compute(&lockfile, NodeLinker::Hoisted, true, false)
+        );
+    }
+
+    #[test]
+    fn test_isolated_layout() {
+        let lockfile = lockfile(
+            &[("@s/a", "1.0.0"), ("c", "1.0.0")],
+            &[
+                ("@s/a", "1.0.0", &[("c", "^2.0.0")]),
+                ("c", "1.0.0", &[]),
+                ("c", "2.0.0", &[]),
+            ],
+        );
+        let layout = NodeModulesLayout::compute(&lockfile, NodeLinker::Isolated, false, false);
+        assert_eq!(
+            paths(&layout),
+            [
+                (
+                    "node_modules/.pnpm/@s+a@1.0.0/node_modules/@s/a",
+                    "@s/a@1.0.0"
+                ),
+                ("node_modules/.pnpm/c@1.0.0/node_modules/c", "c@1.0.0"),
+                ("node_modules/.pnpm/c@2.0.0/node_modules/c", "c@2.0.0"),
+                ("node_modules/@s/a", "@s/a@1.0.0"),
+                ("node_modules/c", "c@1.0.0"),
+            ]
+        );
+        assert_eq!(
+            layout.top_level().collect::<Vec<_>>(),
+            [
+                ("@s/a", "node_modules/@s/a", "@s/a@1.0.0"),
+                ("c", "node_modules/c", "c@1.0.0"),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_layout_record_roundtrip() {
+        let dir = tempfile::tempdir().unwrap();
+        std::fs::create_dir(dir.path().join("node_modules")).unwrap();
+        let lockfile = lockfile(&[("a", "1.0.0")], &[("a", "1.0.0", &[])]);
+        let layout = NodeModulesLayout::compute(&lockfile, NodeLinker::Hoisted, false, false);
+
+        assert!(NodeModulesLayout::read_from(dir.path()).is_none());
+        layout.write_to(dir.path()).unwrap();
+        assert_eq!(NodeModulesLayout::read_from(dir.path()), Some(layout));
+    }
+}
@@ -17,11 +17,15 @@
 //!   lodash -> .pnpm/lodash@4.17.21/node_modules/lodash
 //!   chalk -> .pnpm/chalk@4.1.2/node_modules/chalk
 //! ```
+//!
+//! The hoisted layout (see [`super::layout`]) instead hard-links packages
+//! straight into the nested `node_modules` directories the layout picks.
 
 #![allow(clippy::manual_let_else)]
 #![allow(clippy::items_after_statements)]
 
 use super::error::PkgError;
+use fastnode_util::fs as fs_util;
 use serde_json::Value;
 use std::collections::BTreeMap;
 use std::fs;
@@ -45,7 +49,51 @@ pub fn link_package_binaries(
     cached_pkg_dir: &Path,
     pnpm_pkg_dir: Option<&Path>,
 ) -> Result<Vec<PathBuf>, PkgError> {
-    let package_json_path = cached_pkg_dir.join("package.json");
+    let bin_dir = project_root.join("node_modules").join(".bin");
+
+    // Use the pnpm layout path when available so that binaries resolve
+    // transitive deps via .pnpm/<name>@<version>/node_modules/.
+    let target_base = pnpm_pkg_dir.unwrap_or(cached_pkg_dir);
+
+    link_binaries_into(&bin_dir, pkg_name, cached_pkg_dir, target_base)
+}
+
+/// Link package binaries into an arbitrary `bin_dir`.
+///
+/// Reads the `bin` field from `pkg_dir/package.json` and links each binary
+/// to its script under `target_base`.
+///
+/// # Errors
+/// Returns an error if the binaries cannot be linked.
+pub fn link_binaries_into(
+    bin_dir: &Path,
+    pkg_name: &str,
+    pkg_dir: &Path,
+    target_base: &Path,
+) -> Result<Vec<PathBuf>, PkgError> {
+    let bins = read_bin_entries(pkg_name, pkg_dir)?;
+    if bins.is_empty() {
+        return Ok(vec![]); // No binaries to link
+    }
+
+    // Ensure bin directory exists
+    fs::create_dir_all(bin_dir).map_err(|e| {
+        PkgError::node_modules_write_failed(format!("Failed to create .bin directory: {e}"))
+    })?;
+
+    bins.iter()
+        .map(|(bin_name, bin_path)| link_binary(bin_dir, bin_name, target_base, bin_path))
+        .collect()
+}
+
+/// Read the `bin` field of a package as (binary name, script path) pairs.
+///
+/// A string `bin` is named after the package (without scope).
+///
+/// # Errors
+/// Returns an error if `package.json` cannot be read or parsed.
+pub fn read_bin_entries(pkg_name: &str, pkg_dir: &Path) -> Result<Vec<(String, String)>, PkgError> {
+    let package_json_path = pkg_dir.join("package.json");
 
     // Read and parse package.json
     let package_json_content = fs::read_to_string(&package_json_path).map_err(|e| {
@@ -56,49 +104,26 @@ pub fn link_package_binaries(
         PkgError::link_failed(format!("Failed to parse package.json for {pkg_name}: {e}"))
     })?;
 
-    // Get the bin field - can be string or object
-    let bin_field = match package_json.get("bin") {
-        Some(bin) => bin,
-        None => return Ok(vec![]), // No binaries to link
-    };
-
-    let node_modules = project_root.join("node_modules");
-    let bin_dir = node_modules.join(".bin");
-
-    // Ensure .bin directory exists
-    fs::create_dir_all(&bin_dir).map_err(|e| {
-        PkgError::node_modules_write_failed(format!("Failed to create .bin directory: {e}"))
-    })?;
-
-    let mut linked_binaries = Vec::new();
-
-    // Use the pnpm layout path when available so that binaries resolve
-    // transitive deps via .pnpm/<name>@<version>/node_modules/.
-    let target_base = pnpm_pkg_dir.unwrap_or(cached_pkg_dir);
-
     // Handle both string and object forms of bin field
-    match bin_field {
-        Value::String(bin_path) => {
+    let entries = match package_json.get("bin") {
+        Some(Value::String(bin_path)) => {
             // Single binary: use package name as binary name
             let binary_name = pkg_name.split('/').next_back().unwrap_or(pkg_name);
-            let link_path = link_binary(&bin_dir, binary_name, target_base, bin_path)?;
-            linked_binaries.push(link_path);
+            vec![(binary_name.to_string(), bin_path.clone())]
         }
-        Value::Object(bins) => {
-            // Multiple binaries: each key is a binary name
-            for (bin_name, bin_path) in bins {
-                if let Value::String(path) = bin_path {
-                    let link_path = link_binary(&bin_dir, bin_name, target_base, path)?;
-                    linked_binaries.push(link_path);
-                }
-            }
-        }
-        _ => {
-            // Invalid bin field format, skip
-        }
-    }
+        Some(Value::Object(bins)) => bins
+            .iter()
+            .filter_map(|(bin_name, bin_path)| {
+                bin_path
+                    .as_str()
+                    .map(|path| (bin_name.clone(), path.to_string()))
+            })
+            .collect(),
+        // No binaries, or invalid bin field format
+        _ => vec![],
+    };
 
-    Ok(linked_binaries)
+    Ok(entries)
 }
 
 /// Create a symlink for a single binary.
@@ -265,6 +290,80 @@ pub fn link_into_node_modules_with_version(
     Ok(top_level_link)
 }
 
+/// Point `node_modules/<name>` at a package already in `.pnpm`.
+///
+/// Used to settle which version of a package is visible at the top level
+/// once all versions are in place.
+///
+/// # Errors
+/// Returns an error if the link cannot be created.
+pub fn link_pnpm_top_level(
+    project_root: &Path,
+    pkg_name: &str,
+    pkg_version: &str,
+) -> Result<PathBuf, PkgError> {
+    let node_modules = project_root.join("node_modules");
+    let pnpm_pkg_dest = node_modules
+        .join(".pnpm")
+        .join(format_pnpm_key(pkg_name, pkg_version))
+        .join("node_modules")
+        .join(pkg_name);
+
+    let top_level_link = get_package_link_path(&node_modules, pkg_name)?;
+    if fs::read_link(&top_level_link).is_ok_and(|target| target == pnpm_pkg_dest) {
+        return Ok(top_level_link);
+    }
+    if top_level_link.symlink_metadata().is_ok() {
+        remove_link_or_dir(&top_level_link)?;
+    }
+    create_dir_link(&pnpm_pkg_dest, &top_level_link)?;
+
+    Ok(top_level_link)
+}
+
+/// Install a cached package at `install_dir` (relative to the project root,
+/// e.g. `node_modules/a/node_modules/b`) for the hoisted layout.
+///
+/// The content is hard-linked (or copied) so the package's real path is
+/// inside the project and Node.js finds its dependencies in the parent
+/// `node_modules` directories.
+///
+/// # Errors
+/// Returns an error if the package cannot be linked.
+pub fn link_hoisted_package(
+    project_root: &Path,
+    install_dir: &str,
+    cached_pkg_dir: &Path,
+) -> Result<PathBuf, PkgError> {
+    let dest = project_root.join(install_dir);
+    if let Some(parent) = dest.parent() {
+        fs::create_dir_all(parent).map_err(|e| {
+            PkgError::node_modules_write_failed(format!(
+                "Failed to create directory {}: {e}",
+                parent.display()
+            ))
+        })?;
+    }
+
+    // A link left by the isolated layout would pass the inode check
+    let is_link = fs::symlink_metadata(&dest).is_ok_and(|m| fs_util::is_link(&m));
+    if is_link || needs_relink(cached_pkg_dir, &dest) {
+        remove_link_or_dir(&dest)?;
+        hard_link_or_copy_dir(cached_pkg_dir, &dest)?;
+    }
+
+    Ok(dest)
+}
+
+/// Remove an installed package directory (relative to the project root),
+/// if present.
+///
+/// # Errors
+/// Returns an error if the directory cannot be removed.
+pub fn remove_installed_package(project_root: &Path, install_dir: &str) -> Result<(), PkgError> {
+    remove_link_or_dir(&project_root.join(install_dir))
+}
+
 /// Check whether the destination needs to be re-linked from the cache.
 ///
 /// Returns `false` (no relink needed) when the destination's `package.json`
@@ -293,8 +392,16 @@ fn needs_relink(_cache_dir: &Path, dest_dir: &Path) -> bool {
 }
 
 /// Hard-link files from src to dst, falling back to copy if hard linking fails.
-/// Directories are created, files are hard-linked or copied.
+/// Directories are created, files are hard-linked or copied. Copies are
+/// copy-on-write clones where the filesystem supports them (APFS, btrfs,
+/// XFS; see [`fs_util::clone_or_copy`]), which matters when the cache is on
+/// another volume. Deep trees are walked through [`fs_util::long_path`] to
+/// get past `MAX_PATH` on Windows.
 fn hard_link_or_copy_dir(src: &Path, dst: &Path) -> Result<(), PkgError> {
+    link_or_copy_recursive(&fs_util::long_path(src), &fs_util::long_path(dst))
+}
+
+fn link_or_copy_recursive(src: &Path, dst: &Path) -> Result<(), PkgError> {
     fs::create_dir_all(dst).map_err(|e| {
         PkgError::link_failed(format!("Failed to create directory {}: {e}", dst.display()))
     })?;
@@ -309,11 +416,11 @@ fn hard_link_or_copy_dir(src: &Path, dst: &Path) -> Result<(), PkgError> {
         let dst_path = dst.join(entry.file_name());
 
         if src_path.is_dir() {
-            hard_link_or_copy_dir(&src_path, &dst_path)?;
+            link_or_copy_recursive(&src_path, &dst_path)?;
         } else {
-            // Try hard link first, fall back to copy
+            // Try hard link first, fall back to a clone or copy
             if fs::hard_link(&src_path, &dst_path).is_err() {
-                fs::copy(&src_path, &dst_path).map_err(|e| {
+                fs_util::clone_or_copy(&src_path, &dst_path).map_err(|e| {
                     PkgError::link_failed(format!(
                         "Failed to copy {} to {}: {e}",
                         src_path.display(),
@@ -454,96 +561,35 @@ pub fn link_into_node_modules_direct(
 
 /// Remove a symlink, junction, or directory.
 fn remove_link_or_dir(path: &Path) -> Result<(), PkgError> {
-    #[cfg(unix)]
-    {
-        // On Unix, remove_file handles symlinks
-        if let Ok(metadata) = fs::symlink_metadata(path) {
-            if metadata.file_type().is_symlink() {
-                fs::remove_file(path).map_err(|e| {
-                    PkgError::link_failed(format!("Failed to remove existing symlink: {e}"))
-                })?;
-                return Ok(());
-            }
-        }
-    }
-
-    #[cfg(windows)]
-    {
-        // On Windows, junctions are directories but need special handling
-        use std::os::windows::fs::MetadataExt;
-
-        if let Ok(metadata) = fs::symlink_metadata(path) {
-            let file_attributes = metadata.file_attributes();
-            // FILE_ATTRIBUTE_REPARSE_POINT = 0x400
-            if file_attributes & 0x400 != 0 {
-                // This is a junction or symlink
-                fs::remove_dir(path).map_err(|e| {
-                    PkgError::link_failed(format!("Failed to remove existing junction: {e}"))
-                })?;
-                return Ok(());
-            }
-        }
-    }
-
-    // Regular directory
-    if path.is_dir() {
-        fs::remove_dir_all(path).map_err(|e| {
-            PkgError::link_failed(format!("Failed to remove existing directory: {e}"))
-        })?;
-    } else if path.exists() {
-        fs::remove_file(path)
-            .map_err(|e| PkgError::link_failed(format!("Failed to remove existing file: {e}")))?;
-    }
-
-    Ok(())
+    let Ok(metadata) = fs::symlink_metadata(path) else {
+        return Ok(());
+    };
+    let (result, what) = if fs_util::is_link(&metadata) {
+        (
+            fs_util::retry_on_busy(|| fs_util::remove_link(path)),
+            "link",
+        )
+    } else if metadata.is_dir() {
+        (
+            fs_util::retry_on_busy(|| fs::remove_dir_all(path)),
+            "directory",
+        )
+    } else {
+        (fs_util::retry_on_busy(|| fs::remove_file(path)), "file")
+    };
+    result.map_err(|e| PkgError::link_failed(format!("Failed to remove existing {what}: {e}")))
 }
 
-/// Create a directory link (symlink on Unix, junction on Windows).
+/// Create a directory link (symlink, or a junction/copy where symlinks are
+/// unavailable on Windows).
 fn create_dir_link(src: &Path, dst: &Path) -> Result<(), PkgError> {
-    #[cfg(unix)]
-    {
-        std::os::unix::fs::symlink(src, dst).map_err(|e| {
-            PkgError::link_failed(format!(
-                "Failed to create symlink from {} to {}: {e}",
-                dst.display(),
-                src.display()
-            ))
-        })?;
-    }
-
-    #[cfg(windows)]
-    {
-        junction::create(src, dst).map_err(|e| {
-            PkgError::link_failed(format!(
-                "Failed to create junction from {} to {}: {e}",
-                dst.display(),
-                src.display()
-            ))
-        })?;
-    }
-
-    #[cfg(not(any(unix, windows)))]
-    {
-        // Fallback: copy directory
-        copy_dir_all(src, dst)
-            .map_err(|e| PkgError::link_failed(format!("Failed to copy directory: {e}")))?;
-    }
-
-    Ok(())
-}
-
-#[cfg(not(any(unix, windows)))]
-fn copy_dir_all(src: &Path, dst: &Path) -> std::io::Result<()> {
-    fs::create_dir_all(dst)?;
-    for entry in fs::read_dir(src)? {
-        let entry = entry?;
-        let ty = entry.file_type()?;
-        if ty.is_dir() {
-            copy_dir_all(&entry.path(), &dst.join(entry.file_name()))?;
-        } else {
-            fs::copy(entry.path(), dst.join(entry.file_name()))?;
-        }
-    }
+    fs_util::symlink_dir(src, dst).map_err(|e| {
+        PkgError::link_failed(format!(
+            "Failed to link {} to {}: {e}",
+            dst.display(),
+            src.display()
+        ))
+    })?;
     Ok(())
 }
 
@@ -806,7 +852,7 @@ mod tests {
         // Link binaries - should return empty vec
         let binaries = link_package_binaries(project.path(), "lodash", &cached_pkg, None).unwrap();
 
-        assert!(binaries.is_empty());
+        assert_eq!(binaries, Vec::<String>::new());
         // .bin directory might exist from pnpm setup, that's ok
     }
 
@@ -22,6 +22,7 @@
 #![allow(clippy::missing_panics_doc)]
 //! ```
 
+use super::version::version_satisfies;
 use serde::{Deserialize, Serialize};
 use std::collections::BTreeMap;
 use std::fmt;
@@ -293,6 +294,63 @@ impl LockDep {
     }
 }
 
+/// What resolution decided about a package's peer dependency.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum LockPeerOutcome {
+    /// No version in the tree satisfied the peer, so one was installed.
+    Installed,
+    /// The tree has other versions of the peer and none satisfies the range.
+    Conflict,
+    /// Peer auto-install is disabled and the peer is not in the tree.
+    Missing,
+}
+
+/// A peer dependency decision recorded during resolution.
+///
+/// Peers already satisfied by the tree are not recorded.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct LockPeer {
+    /// Package declaring the peer (matches a key in `packages`).
+    pub package: String,
+    /// Peer package name.
+    pub name: String,
+    /// Declared peer range.
+    pub range: String,
+    /// What resolution did about the peer.
+    pub outcome: LockPeerOutcome,
+    /// Versions of the peer in the tree: the one installed, or the
+    /// conflicting ones.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub versions: Vec<String>,
+}
+
+impl LockPeer {
+    /// Human-readable description of the decision.
+    #[must_use]
+    pub fn message(&self) -> String {
+        let found = self
+            .versions
+            .iter()
+            .map(|v| format!("{}@{v}", self.name))
+            .collect::<Vec<_>>()
+            .join(", ");
+        match self.outcome {
+            LockPeerOutcome::Installed => {
+                format!("installed peer {found} for {}", self.package)
+            }
+            LockPeerOutcome::Conflict => format!(
+                "{} requires peer {}@{}, but the tree has {found}",
+                self.package, self.name, self.range
+            ),
+            LockPeerOutcome::Missing => format!(
+                "{} requires peer {}@{}, which is not installed (auto-install-peers is off)",
+                self.package, self.name, self.range
+            ),
+        }
+    }
+}
+
 /// The complete lockfile.
 ///
 /// Records exact versions for deterministic installation.
@@ -312,6 +370,9 @@ pub struct Lockfile {
     /// All locked packages (key = "name@version").
     /// `BTreeMap` ensures deterministic ordering.
     pub packages: BTreeMap<String, LockPackage>,
+    /// Peer dependency decisions, sorted by package then peer name.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub peers: Vec<LockPeer>,
 }
 
 fn is_default_meta(m: &LockMeta) -> bool {
@@ -328,6 +389,7 @@ impl Lockfile {
             root,
             dependencies: BTreeMap::new(),
             packages: BTreeMap::new(),
+            peers: Vec::new(),
         }
     }
 
@@ -362,6 +424,37 @@ impl Lockfile {
         self.packages.contains_key(&key)
     }
 
+    /// Find the locked package a dependency on `name@range` resolves to.
+    ///
+    /// When several versions of the package are locked (e.g. `entities@4.5.0`
+    /// and `entities@6.0.1`), picks the first that satisfies the range,
+    /// falling back to the first one if none does.
+    #[must_use]
+    pub fn best_match(&self, name: &str, range: &str) -> Option<&LockPackage> {
+        let candidates: Vec<&LockPackage> = self
+            .packages
+            .iter()
+            .filter(|(key, _)| key.rsplit_once('@').is_some_and(|(n, _)| n == name))
+            .map(|(_, pkg)| pkg)
+            .collect();
+        if candidates.len() > 1 {
+            if let Some(pkg) = candidates
+                .iter()
+                .find(|pkg| version_satisfies(&pkg.version, range))
+            {
+                return Some(pkg);
+            }
+        }
+        candidates.first().copied()
+    }
+
+    /// Peer dependencies that conflict with the versions in the tree.
+    pub fn peer_conflicts(&self) -> impl Iterator<Item = &LockPeer> {
+        self.peers
+            .iter()
+            .filter(|peer| peer.outcome == LockPeerOutcome::Conflict)
+    }
+
     /// Read a lockfile from a path.
     ///
     /// # Errors
@@ -0,0 +1,262 @@
+//! Tarball mirrors (e.g. an internal Artifactory) configured in `.npmrc`.
+//!
+//! `mirror=URL` (or `mirror[]=URL`, repeated) lists mirrors tried in order
+//! before the registry; `@scope:mirror=URL` replaces that list for one
+//! scope. A mirror is expected to lay tarballs out like the registry it
+//! mirrors, so `<registry>/<path>` is fetched as `<mirror>/<path>`. With
+//! `mirror-fallback=false` the registry itself is never contacted.
+
+use super::npmrc::{auth_token_for_url, NpmrcConfig};
+use std::collections::HashMap;
+use url::Url;
+
+/// A configured mirror with the auth token for its host.
+#[derive(Debug, Clone)]
+pub struct Mirror {
+    /// Base URL, with a trailing slash.
+    pub url: Url,
+    /// Auth token from `//host/:_authToken`, if any.
+    pub auth_token: Option<String>,
+}
+
+/// Mirrors from `.npmrc`, global and per scope.
+#[derive(Debug, Clone)]
+pub struct MirrorConfig {
+    mirrors: Vec<Mirror>,
+    scoped: HashMap<String, Vec<Mirror>>,
+    fallback: bool,
+}
+
+impl Default for MirrorConfig {
+    fn default() -> Self {
+        Self {
+            mirrors: Vec::new(),
+            scoped: HashMap::new(),
+            fallback: true,
+        }
+    }
+}
+
+/// A place a tarball can be downloaded from.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TarballSource {
+    /// Full tarball URL.
+    pub url: String,
+    /// The mirror serving it, or `None` for the registry.
+    pub mirror: Option<Url>,
+    /// Auth token sent with the request.
+    pub auth_token: Option<String>,
+}
+
+impl TarballSource {
+    /// Short name for diagnostics: the mirror URL, or the registry origin.
+    #[must_use]
+    pub fn label(&self) -> String {
+        match &self.mirror {
+            Some(mirror) => mirror.to_string(),
+            None => Url::parse(&self.url).map_or_else(
+                |_| self.url.clone(),
+                |url| url.origin().ascii_serialization(),
+            ),
+        }
+    }
+}
+
+impl MirrorConfig {
+    /// Resolve the mirrors configured in `config`, with their auth tokens.
+    #[must_use]
+    pub fn from_npmrc(config: &NpmrcConfig) -> Self {
+        let resolve = |urls: &[Url]| -> Vec<Mirror> {
+            urls.iter()
+                .map(|url| Mirror {
+                    url: url.clone(),
+                    auth_token: auth_token_for_url(config, url),
+                })
+                .collect()
+        };
+        Self {
+            mirrors: resolve(&config.mirrors),
+            scoped: config
+                .scoped_mirrors
+                .iter()
+                .map(|(scope, urls)| (scope.clone(), resolve(urls)))
+                .collect(),
+            fallback: config.mirror_fallback.unwrap_or(true),
+        }
+    }
+
+    /// Whether no mirror is configured.
+    #[must_use]
+    pub fn is_empty(&self) -> bool {
+        self.mirrors.is_empty() && self.scoped.is_empty()
+    }
+
+    /// Mirrors for package `name`: its scope's, or the global ones.
+    #[must_use]
+    pub fn mirrors_for(&self, name: &str) -> &[Mirror] {
+        name.strip_prefix('@')
+            .and_then(|_| name.split('/').next())
+            .and_then(|scope| self.scoped.get(scope))
+            .unwrap_or(&self.mirrors)
+    }
+
+    /// Sources to try, in order, for the tarball of `name` at `url` on
+    /// `registry`: each mirror, then the registry itself (unless
+    /// `mirror-fallback=false`), which gets `registry_token`.
+    #[must_use]
+    pub fn sources(
+        &self,
+        name: &str,
+        url: &str,
+        registry: &Url,
+        registry_token: Option<&str>,
+    ) -> Vec<TarballSource> {
+        let mirrors = self.mirrors_for(name);
+        let mut sources: Vec<TarballSource> = mirrors
+            .iter()
+            .filter_map(|mirror| {
+                Some(TarballSource {
+                    url: mirror_url(url, registry, &mirror.url)?,
+                    mirror: Some(mirror.url.clone()),
+                    auth_token: mirror.auth_token.clone(),
+                })
+            })
+            .collect();
+        if sources.is_empty() || self.fallback {
+            sources.push(TarballSource {
+                url: url.to_string(),
+                mirror: None,
+                auth_token: registry_token.map(str::to_string),
+            });
+        }
+        sources
+    }
+}
+
+/// Rewrite tarball `url` from `registry` onto `mirror`.
+///
+/// URLs on another host (e.g. a lockfile written against a different
+/// registry) keep their path, which is the same on every npm-compatible
+/// registry.
+#[must_use]
+pub fn mirror_url(url: &str, registry: &Url, mirror: &Url) -> Option<String> {
+    let rest = if let Some(rest) = url.strip_prefix(registry.as_str()) {
+        rest.to_string()
+    } else {
+        let url = Url::parse(url).ok()?;
+        let path = url.path().trim_start_matches('/');
+        match url.query() {
+            Some(query) => format!("{path}?{query}"),
+            None => path.to_string(),
+        }
+    };
+    Some(format!("{mirror}{rest}"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::pkg::npmrc::parse_npmrc;
+
+    fn url(s: &str) -> Url {
+        Url::parse(s).unwrap()
+    }
+
+    #[test]
+    fn test_mirror_url() {
+        let registry = url("https://registry.npmjs.org/");
+        let mirror = url("https://artifactory.corp/api/npm/npm-remote/");
+        assert_eq!(
+            mirror_url(
+                "https://registry.npmjs.org/lodash/-/lodash-4.17.21.tgz",
+                &registry,
+                &mirror
+            )
+            .as_deref(),
+            Some("https://artifactory.corp/api/npm/npm-remote/lodash/-/lodash-4.17.21.tgz")
+        );
+        // Different registry: keep the path
+        assert_eq!(
+            mirror_url(
+                "https://registry.yarnpkg.com/@types/node/-/node-20.0.0.tgz",
+                &registry,
+                &mirror
+            )
+            .as_deref(),
+            Some("https://artifactory.corp/api/npm/npm-remote/@types/node/-/node-20.0.0.tgz")
+        );
+        assert_eq!(mirror_url("not a url", &registry, &mirror), None);
+    }
+
+    #[test]
+    fn test_sources_order_and_scopes() {
+        let config = MirrorConfig::from_npmrc(&parse_npmrc(
+            "\
+mirror[]=https://one.corp/npm
+mirror[]=https://two.corp/npm
+@corp:mirror=https://corp.mirror/npm
+//one.corp/:_authToken=one-token
+",
+        ));
+        let registry = url("https://registry.npmjs.org/");
+
+        let sources = config.sources(
+            "left-pad",
+            "https://registry.npmjs.org/left-pad/-/left-pad-1.3.0.tgz",
+            &registry,
+            None,
+        );
+        let urls: Vec<&str> = sources.iter().map(|s| s.url.as_str()).collect();
+        assert_eq!(
+            urls,
+            [
+                "https://one.corp/npm/left-pad/-/left-pad-1.3.0.tgz",
+                "https://two.corp/npm/left-pad/-/left-pad-1.3.0.tgz",
+                "https://registry.npmjs.org/left-pad/-/left-pad-1.3.0.tgz",
+            ]
+        );
+        assert_eq!(sources[0].auth_token.as_deref(), Some("one-token"));
+        assert_eq!(sources[1].auth_token, None);
+        assert_eq!(sources[0].label(), "https://one.corp/npm/");
+        assert_eq!(sources[2].label(), "https://registry.npmjs.org");
+
+        let scoped = config.sources(
+            "@corp/ui",
+            "https://registry.npmjs.org/@corp/ui/-/ui-1.0.0.tgz",
+            &registry,
+            Some("npm-token"),
+        );
+        assert_eq!(scoped.len(), 2);
+        assert_eq!(
+            scoped[0].url,
+            "https://corp.mirror/npm/@corp/ui/-/ui-1.0.0.tgz"
+        );
+        assert_eq!(scoped[1].auth_token.as_deref(), Some("npm-token"));
+    }
+
+    #[test]
+    fn test_sources_without_fallback() {
+        let config = MirrorConfig::from_npmrc(&parse_npmrc(
+            "mirror=https://one.corp/npm/\nmirror-fallback=false\n",
+        ));
+        let registry = url("https://registry.npmjs.org/");
+        let sources = config.sources(
+            "a",
+            "https://registry.npmjs.org/a/-/a-1.0.0.tgz",
+            &registry,
+            None,
+        );
+        assert_eq!(sources.len(), 1);
+        assert!(sources[0].mirror.is_some());
+
+        // Nothing configured: the registry only
+        let sources = MirrorConfig::default().sources(
+            "a",
+            "https://registry.npmjs.org/a/-/a-1.0.0.tgz",
+            &registry,
+            None,
+        );
+        assert_eq!(sources.len(), 1);
+        assert_eq!(sources[0].mirror, None);
+    }
+}
@@ -5,63 +5,105 @@
 //! - Fetching package metadata from npm registry
 //! - Resolving version ranges using semver
 //! - Downloading and extracting tarballs
-//! - Managing the global package cache
+//! - Managing the global package cache, optionally deduplicating files
+//!   through a content-addressed store
 //! - Creating symlinks/junctions in `node_modules`
 //! - Reading dependencies from package.json (v1.3)
 //! - Building dependency graphs from `node_modules` (v1.4)
 //! - Explaining why packages are installed (v1.6)
 //! - Health diagnostics for installed packages (v1.7)
 //! - Deterministic lockfile generation and installation (v1.9)
+//! - Hoisted or isolated `node_modules` layouts computed from the lockfile
+//! - Verifying `node_modules` against the lockfile (`install --check`)
 //! - Workspace support for monorepos (v2.0)
+//! - Workspace dependency graph for `--filter`/`--since`
+//! - Packing tarballs for `pack`/`publish`
+//! - Global installs with executable shims
+//! - Registry HTTP client with keep-alive, proxy and custom CA support
+//! - Tarball mirrors with fallback and lockfile integrity checks
 
 pub mod cache;
+pub mod check;
 pub mod deps;
 pub mod doctor;
 pub mod error;
 pub mod explain;
+pub mod global;
 pub mod graph;
+pub mod http;
+pub mod integrity;
+pub mod layout;
 pub mod link;
 pub mod lockfile;
+pub mod mirror;
 pub mod npmrc;
+pub mod pack;
+pub mod range;
 pub mod registry;
 pub mod resolve;
 pub mod spec;
+pub mod store;
 pub mod tarball;
 pub mod version;
+pub mod workspace_graph;
 pub mod workspaces;
 
 pub use cache::PackageCache;
+pub use check::{
+    check_install, CheckDiff, CheckDiffKind, InstallCheckOptions, InstallCheckReport,
+    DEFAULT_CHECK_SAMPLE, INSTALL_CHECK_SCHEMA_VERSION,
+};
 pub use deps::{
     add_dependency_to_package_json, read_package_deps, remove_dependency_from_package_json,
     PackageDeps, PkgDepError,
 };
 pub use doctor::{
-    build_doctor_report, codes as doctor_codes, DoctorCounts, DoctorFinding, DoctorOptions,
-    DoctorSeverity, DoctorSummary, PkgDoctorReport, PKG_DOCTOR_SCHEMA_VERSION,
+    build_doctor_report, codes as doctor_codes, detect_node_version, DoctorCounts, DoctorFinding,
+    DoctorOptions, DoctorSeverity, DoctorSummary, PkgDoctorReport, PKG_DOCTOR_SCHEMA_VERSION,
 };
 pub use error::{codes as pkg_codes, PkgError};
 pub use explain::{
     parse_why_arg, why_codes, why_from_graph, ParsedWhyArg, PkgWhyResult, WhyArgKind, WhyChain,
-    WhyErrorInfo, WhyLink, WhyOptions, WhyTarget, PKG_WHY_SCHEMA_VERSION,
+    WhyDependent, WhyDependents, WhyErrorInfo, WhyLink, WhyOptions, WhyTarget,
+    PKG_WHY_SCHEMA_VERSION,
+};
+pub use global::{
+    ensure_global_prefix, global_bin_dir, is_on_path, link_global_bins, list_packages,
+    unlink_global_bins, ListedPackage,
 };
 pub use graph::{
     build_pkg_graph, codes as graph_codes, DepEdge, GraphErrorInfo, GraphOptions, PackageGraph,
     PackageId, PackageNode, PKG_GRAPH_SCHEMA_VERSION,
 };
+pub use integrity::{check_integrity, IntegrityCheck};
+pub use layout::{NodeLinker, NodeModulesLayout, LAYOUT_FILE_NAME};
 pub use link::{
-    format_pnpm_key, link_into_node_modules, link_into_node_modules_direct,
-    link_into_node_modules_with_version, link_package_binaries, link_package_dependencies,
+    format_pnpm_key, link_binaries_into, link_hoisted_package, link_into_node_modules,
+    link_into_node_modules_direct, link_into_node_modules_with_version, link_package_binaries,
+    link_package_dependencies, link_pnpm_top_level, read_bin_entries, remove_installed_package,
 };
 pub use lockfile::{
     codes as lockfile_codes, lockfile_content_hash, LockDep, LockDepEdge, LockMeta, LockPackage,
-    LockResolution, LockRoot, Lockfile, LockfileError, LOCKFILE_NAME, PKG_LOCK_SCHEMA_VERSION,
+    LockPeer, LockPeerOutcome, LockResolution, LockRoot, Lockfile, LockfileError, LOCKFILE_NAME,
+    PKG_LOCK_SCHEMA_VERSION,
+};
+pub use mirror::{Mirror, MirrorConfig, TarballSource};
+pub use npmrc::{load_npmrc_files, NpmrcConfig, ScopedRegistry};
+pub use pack::{collect_pack_files, pack, tarball_filename, PackFile, PackResult};
+pub use range::{parse_version, VersionRange};
+pub use registry::{
+    get_tarball_url, RegistryClient, VerifiedTarball, DEFAULT_MAX_SOCKETS, DEFAULT_REGISTRY,
+    REGISTRY_ENV,
 };
-pub use npmrc::{NpmrcConfig, ScopedRegistry};
-pub use registry::{get_tarball_url, RegistryClient, DEFAULT_REGISTRY, REGISTRY_ENV};
 pub use resolve::{resolve_dependencies, write_lockfile, ResolveOptions, ResolveResult};
 pub use spec::PackageSpec;
-pub use tarball::{download_tarball, extract_tgz_atomic, MAX_TARBALL_SIZE};
-pub use version::{resolve_version, version_satisfies};
+pub use store::{DedupeStats, FileStore, FileStoreUsage, FILE_STORE_DIR, FILE_STORE_ENV};
+pub use tarball::{
+    download_tarball, download_tarball_with_retry, extract_tgz_atomic,
+    extract_tgz_atomic_with_store, DownloadStats, RetryPolicy, MAX_TARBALL_SIZE,
+};
+pub use version::{is_valid_range, resolve_version, version_satisfies};
+pub use workspace_graph::{select_workspace_packages, WorkspaceGraph};
 pub use workspaces::{
     detect_workspaces, find_workspace_root, link_workspace_packages, WorkspaceConfig,
     WorkspacePackage,
@@ -4,9 +4,17 @@
 //! - `@scope:registry=URL` directives for routing scoped packages
 //! - `//host/:_authToken=TOKEN` directives for registry authentication
 //! - `${ENV_VAR}` expansion in token values
-
+//! - `auto-install-peers` / `legacy-peer-deps` for peer dependency handling
+//! - `node-linker` for the `node_modules` layout
+//! - `fetch-retries`, `fetch-retry-mintimeout`, `fetch-retry-maxtimeout` and
+//!   `maxsockets` for tarball downloads
+//! - `proxy`, `https-proxy`, `noproxy`, `cafile`, `ca`, `strict-ssl` and
+//!   `fetch-timeout` for the registry HTTP client
+//! - `mirror`, `@scope:mirror` and `mirror-fallback` for tarball mirrors
+
+use super::layout::NodeLinker;
 use std::collections::HashMap;
-use std::path::Path;
+use std::path::{Path, PathBuf};
 use url::Url;
 
 /// Parsed `.npmrc` configuration.
@@ -16,6 +24,42 @@ pub struct NpmrcConfig {
     pub scoped_registries: HashMap<String, Url>,
     /// Host → auth token mapping (e.g., `registry.tiptap.dev` → `abc123`).
     pub auth_tokens: HashMap<String, String>,
+    /// Whether missing peer dependencies are installed automatically
+    /// (`auto-install-peers`, or the inverse of `legacy-peer-deps`).
+    pub auto_install_peers: Option<bool>,
+    /// How `node_modules` is laid out (`node-linker`).
+    pub node_linker: Option<NodeLinker>,
+    /// Retries for failed tarball downloads (`fetch-retries`).
+    pub fetch_retries: Option<u32>,
+    /// Backoff before the first retry, in milliseconds (`fetch-retry-mintimeout`).
+    pub fetch_retry_mintimeout: Option<u64>,
+    /// Upper bound for the retry backoff, in milliseconds (`fetch-retry-maxtimeout`).
+    pub fetch_retry_maxtimeout: Option<u64>,
+    /// Concurrent tarball downloads per registry host (`maxsockets`).
+    pub max_sockets: Option<usize>,
+    /// Proxy for registry requests (`proxy`).
+    pub proxy: Option<String>,
+    /// Proxy for `https://` registry requests (`https-proxy`).
+    pub https_proxy: Option<String>,
+    /// Hosts that bypass the proxy (`noproxy`).
+    pub no_proxy: Option<String>,
+    /// PEM file with extra CA certificates (`cafile`).
+    pub cafile: Option<PathBuf>,
+    /// Inline PEM CA certificates (`ca`, or `ca[]` repeated).
+    pub ca: Vec<String>,
+    /// Whether registry TLS certificates are verified (`strict-ssl`).
+    pub strict_ssl: Option<bool>,
+    /// Overall timeout for registry requests, in milliseconds (`fetch-timeout`).
+    pub fetch_timeout: Option<u64>,
+    /// Tarball mirrors tried before the registry, in order (`mirror`, or
+    /// `mirror[]` repeated).
+    pub mirrors: Vec<Url>,
+    /// Scope → tarball mirrors (`@scope:mirror=URL`), used instead of
+    /// [`mirrors`](Self::mirrors) for that scope.
+    pub scoped_mirrors: HashMap<String, Vec<Url>>,
+    /// Whether the registry is tried once every mirror failed
+    /// (`mirror-fallback`).
+    pub mirror_fallback: Option<bool>,
 }
 
 /// A resolved scoped registry with its auth token.
@@ -31,7 +75,12 @@ pub struct ScopedRegistry {
 
 /// Parse a single `.npmrc` file's content.
 ///
-/// Extracts `@scope:registry=URL` and `//host/:_authToken=TOKEN` directives.
+/// Extracts `@scope:registry=URL`, `//host/:_authToken=TOKEN`, peer
+/// dependency (`auto-install-peers`, `legacy-peer-deps`), `node-linker`,
+/// download (`fetch-retries`, `fetch-retry-*timeout`, `maxsockets`) and
+/// network (`proxy`, `https-proxy`, `noproxy`, `cafile`, `ca`,
+/// `strict-ssl`, `fetch-timeout`) and mirror (`mirror`, `@scope:mirror`,
+/// `mirror-fallback`) directives.
 /// Ignores comments (`#`, `;`) and blank lines. Supports `${ENV_VAR}` expansion
 /// in token values.
 #[must_use]
@@ -53,17 +102,22 @@ pub fn parse_npmrc(content: &str) -> NpmrcConfig {
                 let value = value.trim();
 
                 if let Some((scope, directive)) = key.split_once(':') {
-                    if directive == "registry" {
-                        // Ensure URL has trailing slash for proper joining
-                        let url_str = if value.ends_with('/') {
-                            value.to_string()
-                        } else {
-                            format!("{value}/")
-                        };
-
-                        if let Ok(url) = Url::parse(&url_str) {
-                            config.scoped_registries.insert(scope.to_string(), url);
+                    match directive {
+                        "registry" => {
+                            if let Some(url) = base_url(value) {
+                                config.scoped_registries.insert(scope.to_string(), url);
+                            }
+                        }
+                        "mirror" | "mirror[]" => {
+                            if let Some(url) = base_url(value) {
+                                config
+                                    .scoped_mirrors
+                                    .entry(scope.to_string())
+                                    .or_default()
+                                    .push(url);
+                            }
                         }
+                        _ => {}
                     }
                 }
             }
@@ -91,6 +145,79 @@ pub fn parse_npmrc(content: &str) -> NpmrcConfig {
                     }
                 }
             }
+            continue;
+        }
+
+        // Parse peer dependency and layout settings
+        if let Some((key, value)) = line.split_once('=') {
+            let value = value.trim();
+            match key.trim() {
+                "node-linker" => {
+                    config.node_linker = NodeLinker::parse(value);
+                    continue;
+                }
+                "fetch-retries" => {
+                    config.fetch_retries = value.parse().ok();
+                    continue;
+                }
+                "fetch-retry-mintimeout" => {
+                    config.fetch_retry_mintimeout = value.parse().ok();
+                    continue;
+                }
+                "fetch-retry-maxtimeout" => {
+                    config.fetch_retry_maxtimeout = value.parse().ok();
+                    continue;
+                }
+                "maxsockets" => {
+                    config.max_sockets = value.parse().ok().filter(|&n| n > 0);
+                    continue;
+                }
+                "proxy" => {
+                    config.proxy = setting_value(value);
+                    continue;
+                }
+                "https-proxy" => {
+                    config.https_proxy = setting_value(value);
+                    continue;
+                }
+                "noproxy" | "no-proxy" => {
+                    config.no_proxy = setting_value(value);
+                    continue;
+                }
+                "cafile" => {
+                    config.cafile = setting_value(value).map(PathBuf::from);
+                    continue;
+                }
+                "ca" | "ca[]" => {
+                    if let Some(ca) = setting_value(value) {
+                        config.ca.push(ca);
+                    }
+                    continue;
+                }
+                "fetch-timeout" => {
+                    config.fetch_timeout = value.parse().ok();
+                    continue;
+                }
+                "mirror" | "mirror[]" => {
+                    if let Some(url) = base_url(value) {
+                        config.mirrors.push(url);
+                    }
+                    continue;
+                }
+                _ => {}
+            }
+            let enabled = match value {
+                "true" => true,
+                "false" => false,
+                _ => continue,
+            };
+            match key.trim() {
+                "auto-install-peers" => config.auto_install_peers = Some(enabled),
+                "legacy-peer-deps" => config.auto_install_peers = Some(!enabled),
+                "strict-ssl" => config.strict_ssl = Some(enabled),
+                "mirror-fallback" => config.mirror_fallback = Some(enabled),
+                _ => {}
+            }
         }
     }
 
@@ -141,35 +268,33 @@ pub fn resolve_scoped_registries(config: &NpmrcConfig) -> Vec<ScopedRegistry> {
     config
         .scoped_registries
         .iter()
-        .map(|(scope, url)| {
-            // Extract host from the registry URL to find the matching auth token
-            let auth_token = url
-                .host_str()
-                .and_then(|host| {
-                    // Try exact host match first, then host with path
-                    let url_path = url.path().trim_end_matches('/');
-                    let host_with_path = if url_path.is_empty() || url_path == "/" {
-                        host.to_string()
-                    } else {
-                        format!("{host}{url_path}")
-                    };
-
-                    config
-                        .auth_tokens
-                        .get(&host_with_path)
-                        .or_else(|| config.auth_tokens.get(host))
-                })
-                .cloned();
-
-            ScopedRegistry {
-                scope: scope.clone(),
-                registry_url: url.clone(),
-                auth_token,
-            }
+        .map(|(scope, url)| ScopedRegistry {
+            scope: scope.clone(),
+            registry_url: url.clone(),
+            auth_token: auth_token_for_url(config, url),
         })
         .collect()
 }
 
+/// The `//host[/path]/:_authToken` configured for a registry or mirror URL.
+#[must_use]
+pub fn auth_token_for_url(config: &NpmrcConfig, url: &Url) -> Option<String> {
+    let host = url.host_str()?;
+    // Try exact host match first, then host with path
+    let url_path = url.path().trim_end_matches('/');
+    let host_with_path = if url_path.is_empty() || url_path == "/" {
+        host.to_string()
+    } else {
+        format!("{host}{url_path}")
+    };
+
+    config
+        .auth_tokens
+        .get(&host_with_path)
+        .or_else(|| config.auth_tokens.get(host))
+        .cloned()
+}
+
 /// Merge `source` into `target`, keeping existing entries (first wins).
 fn merge_config(target: &mut NpmrcConfig, source: &NpmrcConfig) {
     for (scope, url) in &source.scoped_registries {
@@ -184,6 +309,68 @@ fn merge_config(target: &mut NpmrcConfig, source: &NpmrcConfig) {
             .entry(host.clone())
             .or_insert_with(|| token.clone());
     }
+    if target.auto_install_peers.is_none() {
+        target.auto_install_peers = source.auto_install_peers;
+    }
+    if target.node_linker.is_none() {
+        target.node_linker = source.node_linker;
+    }
+    target.fetch_retries = target.fetch_retries.or(source.fetch_retries);
+    target.fetch_retry_mintimeout = target
+        .fetch_retry_mintimeout
+        .or(source.fetch_retry_mintimeout);
+    target.fetch_retry_maxtimeout = target
+        .fetch_retry_maxtimeout
+        .or(source.fetch_retry_maxtimeout);
+    target.max_sockets = target.max_sockets.or(source.max_sockets);
+    if target.proxy.is_none() {
+        target.proxy.clone_from(&source.proxy);
+    }
+    if target.https_proxy.is_none() {
+        target.https_proxy.clone_from(&source.https_proxy);
+    }
+    if target.no_proxy.is_none() {
+        target.no_proxy.clone_from(&source.no_proxy);
+    }
+    if target.cafile.is_none() {
+        target.cafile.clone_from(&source.cafile);
+    }
+    if target.ca.is_empty() {
+        target.ca.clone_from(&source.ca);
+    }
+    target.strict_ssl = target.strict_ssl.or(source.strict_ssl);
+    target.fetch_timeout = target.fetch_timeout.or(source.fetch_timeout);
+    if target.mirrors.is_empty() {
+        target.mirrors.clone_from(&source.mirrors);
+    }
+    for (scope, mirrors) in &source.scoped_mirrors {
+        target
+            .scoped_mirrors
+            .entry(scope.clone())
+            .or_insert_with(|| mirrors.clone());
+    }
+    target.mirror_fallback = target.mirror_fallback.or(source.mirror_fallback);
+}
+
+/// Parse a registry or mirror URL, with a trailing slash so paths join
+/// below it.
+fn base_url(value: &str) -> Option<Url> {
+    let value = expand_env_vars(value.trim_matches('"'));
+    if value.ends_with('/') {
+        Url::parse(&value).ok()
+    } else {
+        Url::parse(&format!("{value}/")).ok()
+    }
+}
+
+/// A string setting with `${ENV_VAR}` expanded, or `None` when it is empty
+/// or explicitly unset (`null`, `false`).
+fn setting_value(value: &str) -> Option<String> {
+    let value = expand_env_vars(value.trim_matches('"'));
+    match value.as_str() {
+        "" | "null" | "false" => None,
+        _ => Some(value),
+    }
 }
 
 /// Expand `${ENV_VAR}` patterns in a string.
@@ -245,6 +432,110 @@ mod tests {
         assert_eq!(config.auth_tokens["registry.tiptap.dev"], "secret123");
     }
 
+    #[test]
+    fn test_parse_peer_settings() {
+        assert_eq!(
+            parse_npmrc("auto-install-peers=false\n").auto_install_peers,
+            Some(false)
+        );
+        assert_eq!(
+            parse_npmrc("legacy-peer-deps = true\n").auto_install_peers,
+            Some(false)
+        );
+        assert_eq!(
+            parse_npmrc("auto-install-peers=true\n").auto_install_peers,
+            Some(true)
+        );
+        assert_eq!(parse_npmrc("save-exact=true\n").auto_install_peers, None);
+    }
+
+    #[test]
+    fn test_parse_node_linker() {
+        assert_eq!(
+            parse_npmrc("node-linker=hoisted\n").node_linker,
+            Some(NodeLinker::Hoisted)
+        );
+        assert_eq!(
+            parse_npmrc("node-linker = isolated\n").node_linker,
+            Some(NodeLinker::Isolated)
+        );
+        assert_eq!(parse_npmrc("node-linker=pnp\n").node_linker, None);
+    }
+
+    #[test]
+    fn test_parse_fetch_settings() {
+        let config = parse_npmrc(
+            "fetch-retries=5\nfetch-retry-mintimeout = 1000\nfetch-retry-maxtimeout=20000\nmaxsockets=4\n",
+        );
+        assert_eq!(config.fetch_retries, Some(5));
+        assert_eq!(config.fetch_retry_mintimeout, Some(1000));
+        assert_eq!(config.fetch_retry_maxtimeout, Some(20_000));
+        assert_eq!(config.max_sockets, Some(4));
+
+        let invalid = parse_npmrc("fetch-retries=many\nmaxsockets=0\n");
+        assert_eq!(invalid.fetch_retries, None);
+        assert_eq!(invalid.max_sockets, None);
+    }
+
+    #[test]
+    fn test_parse_network_settings() {
+        let config = parse_npmrc(
+            "\
+proxy=http://proxy.corp:8080
+https-proxy = http://secure.corp:8443
+noproxy=localhost,.corp
+cafile=/etc/ssl/corp.pem
+ca[]=\"-----BEGIN CERTIFICATE-----\\nabc\\n-----END CERTIFICATE-----\"
+strict-ssl=false
+fetch-timeout=60000
+",
+        );
+        assert_eq!(config.proxy.as_deref(), Some("http://proxy.corp:8080"));
+        assert_eq!(
+            config.https_proxy.as_deref(),
+            Some("http://secure.corp:8443")
+        );
+        assert_eq!(config.no_proxy.as_deref(), Some("localhost,.corp"));
+        assert_eq!(config.cafile, Some(PathBuf::from("/etc/ssl/corp.pem")));
+        assert_eq!(config.ca.len(), 1);
+        assert!(config.ca[0].starts_with("-----BEGIN CERTIFICATE-----\\n"));
+        assert_eq!(config.strict_ssl, Some(false));
+        assert_eq!(config.fetch_timeout, Some(60_000));
+
+        assert_eq!(parse_npmrc("proxy=null\n").proxy, None);
+    }
+
+    #[test]
+    fn test_parse_mirrors() {
+        let config = parse_npmrc(
+            "\
+mirror[]=https://artifactory.corp/api/npm/npm-remote
+mirror[]=https://backup.corp/npm/
+@corp:mirror=https://artifactory.corp/api/npm/corp
+mirror-fallback=false
+//artifactory.corp/:_authToken=art-token
+",
+        );
+        let mirrors: Vec<&str> = config.mirrors.iter().map(Url::as_str).collect();
+        assert_eq!(
+            mirrors,
+            [
+                "https://artifactory.corp/api/npm/npm-remote/",
+                "https://backup.corp/npm/"
+            ]
+        );
+        assert_eq!(
+            config.scoped_mirrors["@corp"][0].as_str(),
+            "https://artifactory.corp/api/npm/corp/"
+        );
+        assert_eq!(config.mirror_fallback, Some(false));
+        assert_eq!(
+            auth_token_for_url(&config, &config.mirrors[0]).as_deref(),
+            Some("art-token")
+        );
+        assert_eq!(auth_token_for_url(&config, &config.mirrors[1]), None);
+    }
+
     #[test]
     fn test_parse_combined() {
         let content = "\
@@ -0,0 +1,526 @@
+//! Package tarball creation (`howth pkg pack`, `howth pkg publish`).
+//!
+//! File selection follows npm:
+//! - `files` in package.json is an allowlist (a directory includes its contents)
+//! - without `files`, `.npmignore` (or `.gitignore` if absent) excludes paths,
+//!   matched by [`crate::howthignore::IgnoreRule`]
+//! - `.howthignore` (see [`crate::howthignore`]) excludes paths either way
+//! - package.json, README, LICENSE/LICENCE, `main` and `bin` are always included
+//! - VCS directories, `node_modules`, lockfiles and editor junk are always excluded
+//!
+//! Tarballs are deterministic: entries are sorted, prefixed with `package/`
+//! and stamped with npm's fixed mtime (or `SOURCE_DATE_EPOCH`, when given).
+
+use super::error::PkgError;
+use super::lockfile::LOCKFILE_NAME;
+use crate::howthignore::{is_ignored_by, HowthIgnore, IgnoreRule};
+use flate2::write::GzEncoder;
+use flate2::Compression;
+use glob::{MatchOptions, Pattern};
+use serde_json::Value;
+use std::path::{Path, PathBuf};
+use walkdir::WalkDir;
+
+/// Fixed mtime npm uses for tarball entries (1985-10-26T08:15:00Z).
+const NPM_TARBALL_MTIME: u64 = 499_162_500;
+
+/// Paths that are never packed, matched like `.npmignore` rules.
+const ALWAYS_IGNORED: &[&str] = &[
+    ".git",
+    ".svn",
+    ".hg",
+    "CVS",
+    "node_modules",
+    ".npmrc",
+    ".npmignore",
+    ".gitignore",
+    ".howthignore",
+    ".DS_Store",
+    "._*",
+    ".*.swp",
+    ".lock-wscript",
+    ".wafpickle-*",
+    "npm-debug.log",
+    "config.gypi",
+    "*.orig",
+    "/package-lock.json",
+    "/yarn.lock",
+    "/pnpm-lock.yaml",
+    "/bun.lockb",
+];
+
+/// A file included in a package tarball.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PackFile {
+    /// Path relative to the package root, `/`-separated.
+    pub path: String,
+    /// File size in bytes.
+    pub size: u64,
+}
+
+/// Result of packing a package.
+#[derive(Debug, Clone)]
+pub struct PackResult {
+    /// Package name.
+    pub name: String,
+    /// Package version.
+    pub version: String,
+    /// Tarball file name (e.g. `scope-pkg-1.0.0.tgz`).
+    pub filename: String,
+    /// Packed files, sorted by path.
+    pub files: Vec<PackFile>,
+    /// Sum of packed file sizes.
+    pub unpacked_size: u64,
+    /// Compressed tarball size.
+    pub tarball_size: u64,
+    /// Where the tarball was written (`None` for dry runs).
+    pub tarball_path: Option<PathBuf>,
+}
+
+fn parse_rules<'a>(lines: impl IntoIterator<Item = &'a str>) -> Vec<IgnoreRule> {
+    lines.into_iter().filter_map(IgnoreRule::parse).collect()
+}
+
+/// Check whether `rel` is selected by the `files` allowlist.
+///
+/// A pattern selects a path if it matches the path itself or any ancestor
+/// directory; `!` patterns exclude.
+fn allowed_by_files(files: &[String], rel: &str) -> bool {
+    let opts = MatchOptions {
+        require_literal_separator: true,
+        ..MatchOptions::default()
+    };
+    let mut ancestors = vec![rel];
+    let mut end = rel.len();
+    while let Some(pos) = rel[..end].rfind('/') {
+        ancestors.push(&rel[..pos]);
+        end = pos;
+    }
+
+    let mut allowed = false;
+    for entry in files {
+        let (negated, entry) = match entry.strip_prefix('!') {
+            Some(rest) => (true, rest),
+            None => (false, entry.as_str()),
+        };
+        let entry = entry
+            .trim_start_matches("./")
+            .trim_start_matches('/')
+            .trim_end_matches('/');
+        let Ok(pattern) = Pattern::new(entry) else {
+            continue;
+        };
+        if ancestors.iter().any(|p| pattern.matches_with(p, opts)) {
+            allowed = !negated;
+        }
+    }
+    allowed
+}
+
+/// Check for root files npm always packs (README, LICENSE, LICENCE).
+fn is_always_included(rel: &str) -> bool {
+    if rel.contains('/') {
+        return false;
+    }
+    let upper = rel.to_ascii_uppercase();
+    rel == "package.json"
+        || upper.starts_with("README")
+        || upper.starts_with("LICENSE")
+        || upper.starts_with("LICENCE")
+}
+
+/// Files referenced by `main` and `bin`, which npm always packs.
+fn manifest_entry_points(manifest: &Value) -> Vec<String> {
+    let mut entries = Vec::new();
+    if let Some(main) = manifest.get("main").and_then(Value::as_str) {
+        entries.push(main.to_string());
+    }
+    match manifest.get("bin") {
+        Some(Value::String(bin)) => entries.push(bin.clone()),
+        Some(Value::Object(bins)) => {
+            entries.extend(bins.values().filter_map(Value::as_str).map(str::to_string));
+        }
+        _ => {}
+    }
+    entries
+        .into_iter()
+        .map(|e| e.trim_start_matches("./").to_string())
+        .collect()
+}
+
+fn read_manifest(package_dir: &Path) -> Result<Value, PkgError> {
+    let path = package_dir.join("package.json");
+    let content =
+        std::fs::read_to_string(&path).map_err(|_| PkgError::package_json_not_found(&path))?;
+    serde_json::from_str(&content)
+        .map_err(|e| PkgError::package_json_invalid(format!("Invalid JSON: {e}")))
+}
+
+/// Tarball file name for a package, as produced by `npm pack`.
+///
+/// Scoped names drop the `@` and replace `/` with `-`.
+#[must_use]
+pub fn tarball_filename(name: &str, version: &str) -> String {
+    let name = name.trim_start_matches('@').replace('/', "-");
+    format!("{name}-{version}.tgz")
+}
+
+/// Collect the files that would be packed from `package_dir`.
+///
+/// # Errors
+/// Returns an error if package.json is missing or invalid, or the directory
+/// cannot be read.
+pub fn collect_pack_files(package_dir: &Path) -> Result<Vec<PackFile>, PkgError> {
+    let manifest = read_manifest(package_dir)?;
+    let files_field: Option<Vec<String>> =
+        manifest.get("files").and_then(Value::as_array).map(|a| {
+            a.iter()
+                .filter_map(Value::as_str)
+                .map(str::to_string)
+                .collect()
+        });
+
+    let lockfile_rule = format!("/{LOCKFILE_NAME}");
+    let always_ignored = parse_rules(
+        ALWAYS_IGNORED
+            .iter()
+            .copied()
+            .chain(std::iter::once(lockfile_rule.as_str())),
+    );
+    let ignore_rules = if files_field.is_some() {
+        Vec::new()
+    } else {
+        [".npmignore", ".gitignore"]
+            .iter()
+            .find_map(|f| std::fs::read_to_string(package_dir.join(f)).ok())
+            .map(|content| parse_rules(content.lines()))
+            .unwrap_or_default()
+    };
+    let howthignore = HowthIgnore::load(package_dir);
+    let entry_points = manifest_entry_points(&manifest);
+
+    let rel_path = |path: &Path| -> String {
+        path.strip_prefix(package_dir)
+            .unwrap_or(path)
+            .to_string_lossy()
+            .replace('\\', "/")
+    };
+
+    let mut files = Vec::new();
+    let walker = WalkDir::new(package_dir)
+        .follow_links(false)
+        .sort_by_file_name()
+        .into_iter()
+        .filter_entry(|entry| {
+            if entry.depth() == 0 {
+                return true;
+            }
+            let rel = rel_path(entry.path());
+            let is_dir = entry.file_type().is_dir();
+            if is_ignored_by(&always_ignored, Path::new(&rel), is_dir) {
+                return false;
+            }
+            // Ignored directories are pruned; files are decided below so that
+            // always-included files survive ignore rules.
+            !(is_dir
+                && (is_ignored_by(&ignore_rules, Path::new(&rel), true)
+                    || howthignore.is_ignored(entry.path(), true)))
+        });
+
+    for entry in walker {
+        let entry =
+            entry.map_err(|e| PkgError::pack_failed(format!("Failed to read package: {e}")))?;
+        if !entry.file_type().is_file() {
+            continue;
+        }
+        let rel = rel_path(entry.path());
+        let forced = is_always_included(&rel) || entry_points.contains(&rel);
+        let selected = match &files_field {
+            Some(allow) => allowed_by_files(allow, &rel),
+            None => !is_ignored_by(&ignore_rules, Path::new(&rel), false),
+        } && !howthignore.is_ignored(entry.path(), false);
+        if !forced && !selected {
+            continue;
+        }
+        let size = entry
+            .metadata()
+            .map_err(|e| PkgError::pack_failed(format!("Failed to stat {rel}: {e}")))?
+            .len();
+        files.push(PackFile { path: rel, size });
+    }
+
+    files.sort_by(|a, b| a.path.cmp(&b.path));
+    Ok(files)
+}
+
+/// Build a gzipped tarball containing `files` under `package/`, every entry
+/// stamped with `mtime`.
+fn build_tarball(package_dir: &Path, files: &[PackFile], mtime: u64) -> Result<Vec<u8>, PkgError> {
+    let encoder = GzEncoder::new(Vec::new(), Compression::default());
+    let mut builder = tar::Builder::new(encoder);
+
+    for file in files {
+        let path = package_dir.join(&file.path);
+        let data = std::fs::read(&path)
+            .map_err(|e| PkgError::pack_failed(format!("Failed to read {}: {e}", file.path)))?;
+
+        let mut header = tar::Header::new_gnu();
+        header.set_size(data.len() as u64);
+        header.set_mode(if is_executable(&path) { 0o755 } else { 0o644 });
+        header.set_mtime(mtime);
+        header.set_uid(0);
+        header.set_gid(0);
+        builder
+            .append_data(
+                &mut header,
+                format!("package/{}", file.path),
+                data.as_slice(),
+            )
+            .map_err(|e| PkgError::pack_failed(format!("Failed to add {}: {e}", file.path)))?;
+    }
+
+    builder
+        .into_inner()
+        .and_then(GzEncoder::finish)
+        .map_err(|e| PkgError::pack_failed(format!("Failed to finish tarball: {e}")))
+}
+
+#[cfg(unix)]
+fn is_executable(path: &Path) -> bool {
+    use std::os::unix::fs::PermissionsExt;
+    std::fs::metadata(path).is_ok_and(|m| m.permissions().mode() & 0o111 != 0)
+}
+
+#[cfg(not(unix))]
+fn is_executable(_path: &Path) -> bool {
+    false
+}
+
+/// Pack `package_dir` into a tarball.
+///
+/// The tarball is written to `destination` (default: `package_dir`) unless
+/// `dry_run` is set; sizes are reported either way. Entries are stamped
+/// with `mtime` (seconds since the epoch, e.g. `SOURCE_DATE_EPOCH`), or
+/// npm's fixed date when `None`.
+///
+/// # Errors
+/// Returns an error if package.json lacks a name or version, or any file
+/// cannot be read or written.
+pub fn pack(
+    package_dir: &Path,
+    destination: Option<&Path>,
+    dry_run: bool,
+    mtime: Option<u64>,
+) -> Result<PackResult, PkgError> {
+    let manifest = read_manifest(package_dir)?;
+    let field = |key: &str| {
+        manifest
+            .get(key)
+            .and_then(Value::as_str)
+            .filter(|s| !s.is_empty())
+            .map(str::to_string)
+    };
+    let (Some(name), Some(version)) = (field("name"), field("version")) else {
+        return Err(PkgError::package_json_invalid(
+            "package.json must have name and version fields",
+        ));
+    };
+
+    let files = collect_pack_files(package_dir)?;
+    let unpacked_size = files.iter().map(|f| f.size).sum();
+    let tarball = build_tarball(package_dir, &files, mtime.unwrap_or(NPM_TARBALL_MTIME))?;
+    let filename = tarball_filename(&name, &version);
+
+    let tarball_path = if dry_run {
+        None
+    } else {
+        let dest = destination.unwrap_or(package_dir);
+        std::fs::create_dir_all(dest).map_err(|e| {
+            PkgError::pack_failed(format!("Failed to create {}: {e}", dest.display()))
+        })?;
+        let path = dest.join(&filename);
+        fastnode_util::fs::atomic_write(&path, &tarball)
+            .map_err(|e| PkgError::pack_failed(format!("Failed to write {filename}: {e}")))?;
+        Some(path)
+    };
+
+    Ok(PackResult {
+        name,
+        version,
+        filename,
+        files,
+        unpacked_size,
+        tarball_size: tarball.len() as u64,
+        tarball_path,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use flate2::read::GzDecoder;
+    use std::fs;
+    use tempfile::tempdir;
+
+    fn write(root: &Path, rel: &str, content: &str) {
+        let path = root.join(rel);
+        fs::create_dir_all(path.parent().unwrap()).unwrap();
+        fs::write(path, content).unwrap();
+    }
+
+    fn paths(files: &[PackFile]) -> Vec<&str> {
+        files.iter().map(|f| f.path.as_str()).collect()
+    }
+
+    #[test]
+    fn test_tarball_filename() {
+        assert_eq!(tarball_filename("lodash", "4.17.21"), "lodash-4.17.21.tgz");
+        assert_eq!(
+            tarball_filename("@scope/pkg", "1.0.0"),
+            "scope-pkg-1.0.0.tgz"
+        );
+    }
+
+    #[test]
+    fn test_collect_respects_npmignore() {
+        let dir = tempdir().unwrap();
+        let root = dir.path();
+        write(root, "package.json", r#"{"name":"a","version":"1.0.0"}"#);
+        write(root, "README.md", "# a");
+        write(root, "index.js", "");
+        write(root, "src/util.js", "");
+        write(root, "test/a.test.js", "");
+        write(root, "debug.log", "");
+        write(root, "keep.log", "");
+        write(root, "src/util.js.map", "");
+        write(root, "node_modules/dep/index.js", "");
+        write(root, ".git/HEAD", "");
+        // Same glob syntax as `.howthignore`, braces included
+        write(
+            root,
+            ".npmignore",
+            "test/\n*.log\n!keep.log\n*.{map,tsbuildinfo}\n",
+        );
+
+        let files = collect_pack_files(root).unwrap();
+        assert_eq!(
+            paths(&files),
+            vec![
+                "README.md",
+                "index.js",
+                "keep.log",
+                "package.json",
+                "src/util.js"
+            ]
+        );
+    }
+
+    #[test]
+    fn test_collect_respects_howthignore() {
+        let dir = tempdir().unwrap();
+        let root = dir.path();
+        write(
+            root,
+            "package.json",
+            r#"{"name":"a","version":"1.0.0","main":"dist/main.js.map","files":["dist"]}"#,
+        );
+        write(root, "dist/index.js", "");
+        write(root, "dist/vendor/lib.js", "");
+        write(root, "dist/index.js.map", "");
+        write(root, "dist/main.js.map", "");
+        write(root, ".howthignore", "vendor/\n*.map\n");
+
+        // Applies with a `files` allowlist too; `main` is still packed
+        let files = collect_pack_files(root).unwrap();
+        assert_eq!(
+            paths(&files),
+            vec!["dist/index.js", "dist/main.js.map", "package.json"]
+        );
+    }
+
+    #[test]
+    fn test_collect_files_allowlist_with_forced_entries() {
+        let dir = tempdir().unwrap();
+        let root = dir.path();
+        write(
+            root,
+            "package.json",
+            r#"{"name":"a","version":"1.0.0","files":["dist","!dist/*.map"],"bin":{"a":"./cli.js"}}"#,
+        );
+        write(root, "LICENSE", "MIT");
+        write(root, "cli.js", "");
+        write(root, "dist/index.js", "");
+        write(root, "dist/index.js.map", "");
+        write(root, "src/index.ts", "");
+
+        let files = collect_pack_files(root).unwrap();
+        assert_eq!(
+            paths(&files),
+            vec!["LICENSE", "cli.js", "dist/index.js", "package.json"]
+        );
+    }
+
+    #[test]
+    fn test_pack_writes_deterministic_tarball() {
+        let dir = tempdir().unwrap();
+        let root = dir.path();
+        write(root, "package.json", r#"{"name":"@s/a","version":"2.0.0"}"#);
+        write(root, "index.js", "module.exports = 1;");
+
+        let dry = pack(root, None, true, None).unwrap();
+        assert!(dry.tarball_path.is_none());
+        assert!(!root.join("s-a-2.0.0.tgz").exists());
+
+        let out = tempdir().unwrap();
+        let result = pack(root, Some(out.path()), false, None).unwrap();
+        assert_eq!(result.filename, "s-a-2.0.0.tgz");
+        assert_eq!(result.tarball_size, dry.tarball_size);
+        assert_eq!(
+            result.unpacked_size,
+            result.files.iter().map(|f| f.size).sum::<u64>()
+        );
+
+        let bytes = fs::read(out.path().join("s-a-2.0.0.tgz")).unwrap();
+        let mut archive = tar::Archive::new(GzDecoder::new(bytes.as_slice()));
+        let entries: Vec<(String, u64)> = archive
+            .entries()
+            .unwrap()
+            .map(|e| {
+                let e = e.unwrap();
+                (
+                    e.path().unwrap().to_string_lossy().into_owned(),
+                    e.header().mtime().unwrap(),
+                )
+            })
+            .collect();
+        assert_eq!(
+            entries,
+            vec![
+                ("package/index.js".to_string(), NPM_TARBALL_MTIME),
+                ("package/package.json".to_string(), NPM_TARBALL_MTIME),
+            ]
+        );
+
+        // SOURCE_DATE_EPOCH overrides the mtime; output is byte-identical
+        let first = build_tarball(root, &result.files, 1_700_000_000).unwrap();
+        let second = build_tarball(root, &result.files, 1_700_000_000).unwrap();
+        assert_eq!(first, second);
+        assert_ne!(first, bytes);
+        let mut archive = tar::Archive::new(GzDecoder::new(first.as_slice()));
+        assert!(archive
+            .entries()
+            .unwrap()
+            .all(|e| e.unwrap().header().mtime().unwrap() == 1_700_000_000));
+    }
+
+    #[test]
+    fn test_pack_requires_name_and_version() {
+        let dir = tempdir().unwrap();
+        write(dir.path(), "package.json", r#"{"name":"a"}"#);
+        let err = pack(dir.path(), None, true, None).unwrap_err();
+        assert_eq!(
+            err.code(),
+            super::super::error::codes::PKG_PACKAGE_JSON_INVALID
+        );
+    }
+}
@@ -0,0 +1,536 @@
+//! npm-compatible semver ranges.
+//!
+//! Cargo's `semver::VersionReq` differs from npm in ways that matter for
+//! package.json ranges: a bare `1.2.3` means `^1.2.3` instead of an exact
+//! version, `||` and hyphen ranges are not supported, and prereleases match
+//! under different rules. This module parses ranges the way node-semver
+//! does and desugars them into sets of primitive comparators.
+//!
+//! ## Syntax
+//!
+//! - Primitives: `<1.2.3`, `<=1.2.3`, `>1.2.3`, `>=1.2.3`, `=1.2.3`, `1.2.3`
+//! - X-ranges: `*`, `x`, `1.x`, `1.2.X`, `1`, `1.2`, `""`
+//! - Tilde: `~1.2.3`, `~1.2`, `~1`, `~>1.2`
+//! - Caret: `^1.2.3`, `^0.2.3`, `^0.0.3`, `^1.x`, `^0.0`
+//! - Hyphen: `1.2.3 - 2.3.4`, `1.2 - 2`
+//! - Intersection by whitespace (`>=1.2.7 <1.3.0`, `>= 1.2.7 < 1.3.0`)
+//! - Union by `||` (`^1 || ^2`)
+//!
+//! ## Prereleases
+//!
+//! A version with a prerelease tag only satisfies a comparator set if some
+//! comparator in the set has a prerelease on the same `major.minor.patch`.
+//! `^1.2.3-beta.2` matches `1.2.3-beta.4` but not `1.2.4-beta.1`, and `*`
+//! never matches a prerelease.
+
+use super::error::PkgError;
+use semver::{BuildMetadata, Prerelease, Version};
+use std::cmp::Ordering;
+use std::fmt;
+use std::str::FromStr;
+
+/// A parsed npm version range: a union of comparator sets.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct VersionRange {
+    /// Alternatives separated by `||`; an empty set matches any release.
+    sets: Vec<Vec<Comparator>>,
+}
+
+/// A primitive comparison against a version.
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct Comparator {
+    op: Op,
+    version: Version,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Op {
+    Lt,
+    Le,
+    Gt,
+    Ge,
+    Eq,
+}
+
+/// A possibly incomplete version from a range (`1`, `1.x`, `1.2.3-beta`).
+///
+/// `None` marks a missing or wildcard component; every component after a
+/// wildcard is treated as a wildcard too.
+#[derive(Debug, Clone)]
+struct Partial {
+    major: Option<u64>,
+    minor: Option<u64>,
+    patch: Option<u64>,
+    pre: Prerelease,
+}
+
+impl VersionRange {
+    /// Parse an npm version range.
+    ///
+    /// # Errors
+    /// Returns an error if any comparator in the range is invalid.
+    pub fn parse(range: &str) -> Result<Self, PkgError> {
+        let sets = range
+            .split("||")
+            .map(parse_set)
+            .collect::<Result<Vec<_>, String>>()
+            .map_err(|e| PkgError::spec_invalid(format!("Invalid version range '{range}': {e}")))?;
+        Ok(Self { sets })
+    }
+
+    /// Check whether `version` satisfies any comparator set.
+    #[must_use]
+    pub fn satisfies(&self, version: &Version) -> bool {
+        self.sets.iter().any(|set| set_satisfies(set, version))
+    }
+
+    /// The highest of `versions` that satisfies the range.
+    pub fn max_satisfying<'a>(
+        &self,
+        versions: impl IntoIterator<Item = &'a Version>,
+    ) -> Option<&'a Version> {
+        versions
+            .into_iter()
+            .filter(|v| self.satisfies(v))
+            .max_by(|a, b| a.cmp_precedence(b))
+    }
+}
+
+impl FromStr for VersionRange {
+    type Err = PkgError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Self::parse(s)
+    }
+}
+
+impl fmt::Display for VersionRange {
+    /// Desugared form, e.g. `^1.2 || 3` is `>=1.2.0 <2.0.0-0 || >=3.0.0 <4.0.0-0`.
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for (i, set) in self.sets.iter().enumerate() {
+            if i > 0 {
+                f.write_str(" || ")?;
+            }
+            if set.is_empty() {
+                f.write_str("*")?;
+            }
+            for (j, comparator) in set.iter().enumerate() {
+                if j > 0 {
+                    f.write_str(" ")?;
+                }
+                write!(f, "{comparator}")?;
+            }
+        }
+        Ok(())
+    }
+}
+
+impl fmt::Display for Comparator {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let op = match self.op {
+            Op::Lt => "<",
+            Op::Le => "<=",
+            Op::Gt => ">",
+            Op::Ge => ">=",
+            Op::Eq => "",
+        };
+        write!(f, "{op}{}", self.version)
+    }
+}
+
+impl Comparator {
+    fn new(op: Op, major: u64, minor: u64, patch: u64, pre: Prerelease) -> Self {
+        Self {
+            op,
+            version: Version {
+                major,
+                minor,
+                patch,
+                pre,
+                build: BuildMetadata::EMPTY,
+            },
+        }
+    }
+
+    /// Exclusive upper bound that also excludes prereleases of the bound
+    /// (`<2.0.0-0`).
+    fn below(major: u64, minor: u64, patch: u64) -> Self {
+        Self::new(Op::Lt, major, minor, patch, zero_pre())
+    }
+
+    /// Matches no version at all.
+    fn none() -> Self {
+        Self::below(0, 0, 0)
+    }
+
+    fn matches(&self, version: &Version) -> bool {
+        let ord = version.cmp_precedence(&self.version);
+        match self.op {
+            Op::Lt => ord == Ordering::Less,
+            Op::Le => ord != Ordering::Greater,
+            Op::Gt => ord == Ordering::Greater,
+            Op::Ge => ord != Ordering::Less,
+            Op::Eq => ord == Ordering::Equal,
+        }
+    }
+}
+
+/// Parse a concrete version the way npm does, allowing a leading `v` or `=`.
+#[must_use]
+pub fn parse_version(version: &str) -> Option<Version> {
+    let version = version.trim();
+    let version = version
+        .strip_prefix('v')
+        .or_else(|| version.strip_prefix('='))
+        .unwrap_or(version);
+    Version::parse(version.trim()).ok()
+}
+
+fn set_satisfies(set: &[Comparator], version: &Version) -> bool {
+    if !set.iter().all(|c| c.matches(version)) {
+        return false;
+    }
+    if version.pre.is_empty() {
+        return true;
+    }
+    // Prereleases only match when opted into on the same release tuple
+    set.iter().any(|c| {
+        !c.version.pre.is_empty()
+            && c.version.major == version.major
+            && c.version.minor == version.minor
+            && c.version.patch == version.patch
+    })
+}
+
+fn zero_pre() -> Prerelease {
+    Prerelease::new("0").unwrap_or(Prerelease::EMPTY)
+}
+
+/// Parse one `||` alternative into its comparators.
+fn parse_set(set: &str) -> Result<Vec<Comparator>, String> {
+    let set = set.trim();
+    if let Some((from, to)) = set.split_once(" - ") {
+        return Ok(hyphen_range(
+            &parse_partial(from.trim())?,
+            &parse_partial(to.trim())?,
+        ));
+    }
+
+    let mut comparators = Vec::new();
+    let mut words = set.split_whitespace();
+    while let Some(word) = words.next() {
+        // Join operators separated from their version (`>= 1.2.3`)
+        let joined;
+        let token = if word
+            .trim_start_matches(['<', '>', '=', '~', '^'])
+            .is_empty()
+        {
+            let version = words
+                .next()
+                .ok_or_else(|| format!("'{word}' is missing a version"))?;
+            joined = format!("{word}{version}");
+            joined.as_str()
+        } else {
+            word
+        };
+        comparators.extend(parse_comparator(token)?);
+    }
+    Ok(comparators)
+}
+
+/// Desugar a single comparator token.
+fn parse_comparator(token: &str) -> Result<Vec<Comparator>, String> {
+    let (op, rest) = split_operator(token);
+    let partial = parse_partial(rest)?;
+    Ok(match op {
+        "^" => caret_range(&partial),
+        "~" | "~>" => tilde_range(&partial),
+        _ => x_range(op, &partial),
+    })
+}
+
+fn split_operator(token: &str) -> (&str, &str) {
+    for op in ["~>", ">=", "<=", "^", "~", ">", "<", "="] {
+        if let Some(rest) = token.strip_prefix(op) {
+            return (op, rest);
+        }
+    }
+    ("", token)
+}
+
+/// Parse `1`, `1.x`, `1.2.*`, `v1.2.3-beta.1+build` and the like.
+fn parse_partial(input: &str) -> Result<Partial, String> {
+    let input = input.trim_start_matches(['v', '=']);
+    let (core, pre) = match input.split_once(['-', '+']) {
+        // Prerelease/build only follow a full `major.minor.patch`
+        Some((core, _)) if core.split('.').count() == 3 => {
+            let version = Version::parse(input).map_err(|e| format!("'{input}': {e}"))?;
+            (core, version.pre)
+        }
+        Some(_) => return Err(format!("'{input}' is not a valid version")),
+        None => (input, Prerelease::EMPTY),
+    };
+
+    let mut parts = core.split('.');
+    let mut component = |wild: &mut bool| -> Result<Option<u64>, String> {
+        let Some(part) = parts.next() else {
+            *wild = true;
+            return Ok(None);
+        };
+        if matches!(part, "x" | "X" | "*") || *wild {
+            *wild = true;
+            return Ok(None);
+        }
+        if part.len() > 1 && part.starts_with('0') {
+            return Err(format!("'{input}' has a leading zero"));
+        }
+        part.parse()
+            .map(Some)
+            .map_err(|_| format!("'{input}' is not a valid version"))
+    };
+
+    let mut wild = false;
+    // An empty string is `*`
+    let major = if core.is_empty() {
+        wild = true;
+        None
+    } else {
+        component(&mut wild)?
+    };
+    let minor = component(&mut wild)?;
+    let patch = component(&mut wild)?;
+    if parts.next().is_some() {
+        return Err(format!("'{input}' has too many components"));
+    }
+
+    Ok(Partial {
+        major,
+        minor,
+        patch,
+        pre,
+    })
+}
+
+/// `^1.2.3` allows changes that do not modify the left-most non-zero part.
+fn caret_range(p: &Partial) -> Vec<Comparator> {
+    let Some(major) = p.major else {
+        return Vec::new();
+    };
+    let lower = |minor, patch, pre| Comparator::new(Op::Ge, major, minor, patch, pre);
+    match (p.minor, p.patch) {
+        (None, _) => vec![
+            lower(0, 0, Prerelease::EMPTY),
+            Comparator::below(major + 1, 0, 0),
+        ],
+        (Some(minor), None) => {
+            let upper = if major == 0 {
+                Comparator::below(0, minor + 1, 0)
+            } else {
+                Comparator::below(major + 1, 0, 0)
+            };
+            vec![lower(minor, 0, Prerelease::EMPTY), upper]
+        }
+        (Some(minor), Some(patch)) => {
+            let upper = match (major, minor) {
+                (0, 0) => Comparator::below(0, 0, patch + 1),
+                (0, _) => Comparator::below(0, minor + 1, 0),
+                _ => Comparator::below(major + 1, 0, 0),
+            };
+            vec![lower(minor, patch, p.pre.clone()), upper]
+        }
+    }
+}
+
+/// `~1.2.3` allows patch-level changes; `~1` allows minor-level changes.
+fn tilde_range(p: &Partial) -> Vec<Comparator> {
+    let Some(major) = p.major else {
+        return Vec::new();
+    };
+    match (p.minor, p.patch) {
+        (None, _) => vec![
+            Comparator::new(Op::Ge, major, 0, 0, Prerelease::EMPTY),
+            Comparator::below(major + 1, 0, 0),
+        ],
+        (Some(minor), patch) => vec![
+            Comparator::new(Op::Ge, major, minor, patch.unwrap_or(0), p.pre.clone()),
+            Comparator::below(major, minor + 1, 0),
+        ],
+    }
+}
+
+/// Primitive comparators and x-ranges (`1.x`, `>=1.2`, `<1`).
+fn x_range(op: &str, p: &Partial) -> Vec<Comparator> {
+    let Some(major) = p.major else {
+        return match op {
+            // Nothing is above or below every version
+            ">" | "<" => vec![Comparator::none()],
+            _ => Vec::new(),
+        };
+    };
+
+    let (Some(minor), Some(patch)) = (p.minor, p.patch) else {
+        // Next version after the wildcard part, e.g. `1.2` -> `1.3.0`
+        let (next_major, next_minor) = match p.minor {
+            None => (major + 1, 0),
+            Some(minor) => (major, minor + 1),
+        };
+        let minor = p.minor.unwrap_or(0);
+        return match op {
+            ">" => vec![Comparator::new(
+                Op::Ge,
+                next_major,
+                next_minor,
+                0,
+                Prerelease::EMPTY,
+            )],
+            ">=" => vec![Comparator::new(Op::Ge, major, minor, 0, Prerelease::EMPTY)],
+            "<" => vec![Comparator::below(major, minor, 0)],
+            "<=" => vec![Comparator::below(next_major, next_minor, 0)],
+            _ => vec![
+                Comparator::new(Op::Ge, major, minor, 0, Prerelease::EMPTY),
+                Comparator::below(next_major, next_minor, 0),
+            ],
+        };
+    };
+
+    let op = match op {
+        ">" => Op::Gt,
+        ">=" => Op::Ge,
+        "<" => Op::Lt,
+        "<=" => Op::Le,
+        _ => Op::Eq,
+    };
+    vec![Comparator::new(op, major, minor, patch, p.pre.clone())]
+}
+
+/// `1.2 - 2.3.4` is `>=1.2.0 <=2.3.4`; a partial upper bound is inclusive
+/// of every version it covers (`1.2.3 - 2` is `>=1.2.3 <3.0.0-0`).
+fn hyphen_range(from: &Partial, to: &Partial) -> Vec<Comparator> {
+    let mut comparators = Vec::new();
+    if let Some(major) = from.major {
+        comparators.push(Comparator::new(
+            Op::Ge,
+            major,
+            from.minor.unwrap_or(0),
+            from.patch.unwrap_or(0),
+            from.pre.clone(),
+        ));
+    }
+    if let Some(major) = to.major {
+        comparators.push(match (to.minor, to.patch) {
+            (None, _) => Comparator::below(major + 1, 0, 0),
+            (Some(minor), None) => Comparator::below(major, minor + 1, 0),
+            (Some(minor), Some(patch)) => {
+                Comparator::new(Op::Le, major, minor, patch, to.pre.clone())
+            }
+        });
+    }
+    comparators
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn desugar(range: &str) -> String {
+        VersionRange::parse(range).unwrap().to_string()
+    }
+
+    fn satisfies(version: &str, range: &str) -> bool {
+        VersionRange::parse(range)
+            .unwrap()
+            .satisfies(&parse_version(version).unwrap())
+    }
+
+    #[test]
+    fn test_desugar() {
+        assert_eq!(desugar("1.2.3"), "1.2.3");
+        assert_eq!(desugar("*"), "*");
+        assert_eq!(desugar(""), "*");
+        assert_eq!(desugar("1.x"), ">=1.0.0 <2.0.0-0");
+        assert_eq!(desugar("1.2.X"), ">=1.2.0 <1.3.0-0");
+        assert_eq!(desugar("1"), ">=1.0.0 <2.0.0-0");
+        assert_eq!(desugar("~1.2.3"), ">=1.2.3 <1.3.0-0");
+        assert_eq!(desugar("~>1"), ">=1.0.0 <2.0.0-0");
+        assert_eq!(desugar("^1.2.3"), ">=1.2.3 <2.0.0-0");
+        assert_eq!(desugar("^0.2.3"), ">=0.2.3 <0.3.0-0");
+        assert_eq!(desugar("^0.0.3"), ">=0.0.3 <0.0.4-0");
+        assert_eq!(desugar("^0.0.x"), ">=0.0.0 <0.1.0-0");
+        assert_eq!(desugar("^1.2.3-beta.2"), ">=1.2.3-beta.2 <2.0.0-0");
+        assert_eq!(desugar(">1.2"), ">=1.3.0");
+        assert_eq!(desugar("<=1.2"), "<1.3.0-0");
+        assert_eq!(desugar("<1"), "<1.0.0-0");
+        assert_eq!(desugar(">*"), "<0.0.0-0");
+        assert_eq!(desugar("1.2 - 2.3.4"), ">=1.2.0 <=2.3.4");
+        assert_eq!(desugar("1.2.3 - 2"), ">=1.2.3 <3.0.0-0");
+        assert_eq!(desugar(">= 2.1.2 < 3.0.0"), ">=2.1.2 <3.0.0");
+        assert_eq!(desugar("^1 || ^2"), ">=1.0.0 <2.0.0-0 || >=2.0.0 <3.0.0-0");
+        assert_eq!(desugar("v1.2.3"), "1.2.3");
+    }
+
+    #[test]
+    fn test_invalid_ranges() {
+        for range in [
+            "not-a-range!!!",
+            "1.2.3.4",
+            "01.2.3",
+            ">=",
+            "1.2-beta",
+            "^a",
+        ] {
+            assert!(
+                VersionRange::parse(range).is_err(),
+                "{range} should be invalid"
+            );
+        }
+    }
+
+    #[test]
+    fn test_satisfies() {
+        assert!(satisfies("1.2.3", "1.2.3"));
+        assert!(!satisfies("1.2.4", "1.2.3"));
+        assert!(satisfies("1.9.0", "^1.2.3"));
+        assert!(!satisfies("2.0.0", "^1.2.3"));
+        assert!(satisfies("0.2.9", "^0.2.3"));
+        assert!(!satisfies("0.3.0", "^0.2.3"));
+        assert!(satisfies("2.4.1", "^1 || ^2"));
+        assert!(!satisfies("3.0.0", "^1 || ^2"));
+        assert!(satisfies("1.2.9", "1.2.x"));
+        assert!(satisfies("3.0.0", "1.2.3 - 3"));
+        assert!(satisfies("v20.11.0", ">=18"));
+        assert!(satisfies("1.2.3+build.5", "=1.2.3"));
+    }
+
+    #[test]
+    fn test_prerelease_matching() {
+        assert!(satisfies("1.2.3-beta.4", "^1.2.3-beta.2"));
+        assert!(!satisfies("1.2.4-beta.1", "^1.2.3-beta.2"));
+        assert!(!satisfies("1.2.3-alpha.1", "^1.2.3-beta.2"));
+        assert!(!satisfies("2.0.0-rc.1", "*"));
+        assert!(!satisfies("2.0.0-rc.1", "^1.0.0"));
+        assert!(satisfies("2.0.0-rc.1", "2.0.0-rc.1"));
+        assert!(satisfies("2.0.0-rc.1", ">=2.0.0-rc.0 || ^1"));
+    }
+
+    #[test]
+    fn test_max_satisfying() {
+        let versions: Vec<Version> = ["1.0.0", "1.4.2", "2.0.0-beta.1", "2.0.0", "2.1.0"]
+            .iter()
+            .map(|v| Version::parse(v).unwrap())
+            .collect();
+        let max = |range: &str| {
+            VersionRange::parse(range)
+                .unwrap()
+                .max_satisfying(&versions)
+                .map(ToString::to_string)
+        };
+        assert_eq!(max("^1").as_deref(), Some("1.4.2"));
+        assert_eq!(max("*").as_deref(), Some("2.1.0"));
+        assert_eq!(max("<2.0.0").as_deref(), Some("1.4.2"));
+        assert_eq!(
+            max(">=2.0.0-beta.0 <2.0.0").as_deref(),
+            Some("2.0.0-beta.1")
+        );
+        assert_eq!(max("^3"), None);
+    }
+}
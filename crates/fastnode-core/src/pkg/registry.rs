@@ -1,16 +1,29 @@
 //! npm registry client with persistent packument caching.
 //!
 //! Features:
-//! - Disk-based packument cache with `ETag` validation
-//! - Skip network for recently cached packuments (< 5 min)
+//! - Disk-based packument cache with `ETag`/`Last-Modified` revalidation
+//! - Skip network for recently cached packuments (TTL, 5 min by default,
+//!   overridable with `FASTNODE_NPM_METADATA_TTL` in seconds)
+//! - Fall back to stale cached packuments when the registry is unreachable
 //! - In-memory cache shared across clones
 //! - Abbreviated packuments for smaller downloads
+//! - Tarball downloads with retries, resume and a per-registry concurrency
+//!   limit (`maxsockets`)
+//! - Keep-alive connections, proxies and custom CAs (see [`super::http`])
+//! - Tarball mirrors with fallback and integrity checks against the
+//!   lockfile (see [`super::mirror`])
 
 #![allow(clippy::manual_let_else)]
 
 use super::cache::PackageCache;
-use super::error::PkgError;
+use super::error::{codes, PkgError};
+use super::http::HttpConfig;
+use super::integrity::{check_integrity, IntegrityCheck};
+use super::lockfile::codes as lockfile_codes;
+use super::mirror::{MirrorConfig, TarballSource};
 use super::npmrc::{load_npmrc_files, resolve_scoped_registries, ScopedRegistry};
+use super::tarball::{download_tarball_with_retry, DownloadStats, RetryPolicy};
+use bytes::Bytes;
 use reqwest::Client;
 use serde::{Deserialize, Serialize};
 use serde_json::Value;
@@ -18,7 +31,7 @@ use std::collections::HashMap;
 use std::path::Path;
 use std::sync::Arc;
 use std::time::Duration;
-use tokio::sync::RwLock;
+use tokio::sync::{RwLock, Semaphore};
 use url::Url;
 
 /// Default npm registry URL.
@@ -27,34 +40,56 @@ pub const DEFAULT_REGISTRY: &str = "https://registry.npmjs.org/";
 /// Environment variable to override registry URL.
 pub const REGISTRY_ENV: &str = "FASTNODE_NPM_REGISTRY";
 
+/// Environment variable overriding how long cached packuments are trusted
+/// without revalidation, in seconds (`0` always revalidates).
+pub const METADATA_TTL_ENV: &str = "FASTNODE_NPM_METADATA_TTL";
+
 /// How long to trust cached packuments without revalidation (5 minutes).
-const CACHE_FRESH_DURATION_SECS: u64 = 300;
+pub const DEFAULT_METADATA_TTL: Duration = Duration::from_secs(300);
+
+/// Concurrent tarball downloads per registry host, unless `.npmrc` sets
+/// `maxsockets`.
+pub const DEFAULT_MAX_SOCKETS: usize = 16;
 
 /// Accept header for abbreviated packuments (smaller, faster).
 const ABBREVIATED_ACCEPT: &str = "application/vnd.npm.install-v1+json";
 
-/// Cached packument with `ETag` for conditional requests.
+/// Cached packument with validators for conditional requests.
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct CachedPackument {
     /// The packument JSON data.
     pub data: Value,
     /// `ETag` from the server for conditional requests.
     pub etag: Option<String>,
-    /// Unix timestamp when cached.
+    /// `Last-Modified` from the server, sent as `If-Modified-Since`.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub last_modified: Option<String>,
+    /// Unix timestamp when cached (or last revalidated).
     pub cached_at: u64,
 }
 
 impl CachedPackument {
-    /// Check if this cached packument is still fresh (< 5 min old).
-    fn is_fresh(&self) -> bool {
-        let now = std::time::SystemTime::now()
-            .duration_since(std::time::UNIX_EPOCH)
-            .map(|d| d.as_secs())
-            .unwrap_or(0);
-        now.saturating_sub(self.cached_at) < CACHE_FRESH_DURATION_SECS
+    /// Check if this cached packument is younger than `ttl`.
+    fn is_fresh(&self, ttl: Duration) -> bool {
+        unix_now().saturating_sub(self.cached_at) < ttl.as_secs()
     }
 }
 
+/// Current Unix timestamp in seconds.
+fn unix_now() -> u64 {
+    std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .map_or(0, |d| d.as_secs())
+}
+
+/// Metadata TTL from [`METADATA_TTL_ENV`], or the default.
+fn metadata_ttl_from_env() -> Duration {
+    std::env::var(METADATA_TTL_ENV)
+        .ok()
+        .and_then(|secs| secs.trim().parse().ok())
+        .map_or(DEFAULT_METADATA_TTL, Duration::from_secs)
+}
+
 /// Shared state for registry client (persists across clones).
 #[derive(Debug)]
 struct SharedState {
@@ -62,6 +97,32 @@ struct SharedState {
     memory_cache: RwLock<HashMap<String, CachedPackument>>,
     /// Optional disk cache.
     disk_cache: Option<PackageCache>,
+    /// Download slots per registry host.
+    download_slots: std::sync::Mutex<HashMap<String, Arc<Semaphore>>>,
+    /// Retry counters for tarball downloads.
+    download_stats: DownloadStats,
+}
+
+impl SharedState {
+    fn new(disk_cache: Option<PackageCache>) -> Self {
+        Self {
+            memory_cache: RwLock::new(HashMap::new()),
+            disk_cache,
+            download_slots: std::sync::Mutex::new(HashMap::new()),
+            download_stats: DownloadStats::default(),
+        }
+    }
+}
+
+/// A tarball fetched by [`RegistryClient::download_verified_tarball`].
+#[derive(Debug, Clone)]
+pub struct VerifiedTarball {
+    /// Tarball contents.
+    pub bytes: Bytes,
+    /// The mirror or registry that served it.
+    pub source: TarballSource,
+    /// Sources tried before it, each with why it was skipped.
+    pub failures: Vec<String>,
 }
 
 /// Registry client for fetching package metadata with caching.
@@ -75,6 +136,14 @@ pub struct RegistryClient {
     shared: Arc<SharedState>,
     /// Scoped registries loaded from `.npmrc` files.
     scoped_registries: Arc<Vec<ScopedRegistry>>,
+    /// How long cached packuments are used without revalidation.
+    metadata_ttl: Duration,
+    /// Retry policy for tarball downloads.
+    retry_policy: RetryPolicy,
+    /// Concurrent tarball downloads per registry host.
+    max_sockets: usize,
+    /// Tarball mirrors loaded from `.npmrc` files.
+    mirrors: Arc<MirrorConfig>,
 }
 
 impl RegistryClient {
@@ -91,13 +160,7 @@ impl RegistryClient {
         let base_url = Url::parse(base_url)
             .map_err(|e| PkgError::registry(format!("Invalid registry URL '{base_url}': {e}")))?;
 
-        let http = Client::builder()
-            .connect_timeout(Duration::from_secs(5))
-            .timeout(Duration::from_secs(30))
-            .pool_max_idle_per_host(32) // More connections for parallel fetches
-            .user_agent(concat!("howth/", env!("CARGO_PKG_VERSION")))
-            .build()
-            .map_err(|e| PkgError::registry(format!("Failed to create HTTP client: {e}")))?;
+        let http = HttpConfig::from_env().build_client()?;
 
         // Ensure cache directories exist
         if let Some(ref cache) = disk_cache {
@@ -107,11 +170,12 @@ impl RegistryClient {
         Ok(Self {
             base_url,
             http,
-            shared: Arc::new(SharedState {
-                memory_cache: RwLock::new(HashMap::new()),
-                disk_cache,
-            }),
+            shared: Arc::new(SharedState::new(disk_cache)),
             scoped_registries: Arc::new(Vec::new()),
+            metadata_ttl: DEFAULT_METADATA_TTL,
+            retry_policy: RetryPolicy::default(),
+            max_sockets: DEFAULT_MAX_SOCKETS,
+            mirrors: Arc::new(MirrorConfig::default()),
         })
     }
 
@@ -122,24 +186,64 @@ impl RegistryClient {
         let _ = cache.ensure_dirs();
 
         Self {
-            base_url: self.base_url,
-            http: self.http,
-            shared: Arc::new(SharedState {
-                memory_cache: RwLock::new(HashMap::new()),
-                disk_cache: Some(cache),
-            }),
-            scoped_registries: self.scoped_registries,
+            shared: Arc::new(SharedState::new(Some(cache))),
+            ..self
         }
     }
 
-    /// Load `.npmrc` files from the project directory and configure scoped registries.
+    /// Set how long cached packuments are used without revalidation.
+    #[must_use]
+    pub fn with_metadata_ttl(self, metadata_ttl: Duration) -> Self {
+        Self {
+            metadata_ttl,
+            ..self
+        }
+    }
+
+    /// Load `.npmrc` files from the project directory and configure scoped
+    /// registries, tarball download settings and mirrors, and the HTTP
+    /// client (proxies, CA certificates, timeouts).
+    ///
+    /// If the network settings are unusable (e.g. a missing `cafile`), a
+    /// warning is logged and the current HTTP client is kept.
     #[must_use]
     pub fn with_npmrc(self, project_dir: &Path) -> Self {
         let config = load_npmrc_files(project_dir);
         let registries = resolve_scoped_registries(&config);
 
+        let http = HttpConfig::from_npmrc(&config)
+            .build_client()
+            .unwrap_or_else(|e| {
+                tracing::warn!("ignoring .npmrc network settings: {e}");
+                self.http.clone()
+            });
+
+        let default = RetryPolicy::default();
+        let retry_policy = RetryPolicy {
+            retries: config.fetch_retries.unwrap_or(default.retries),
+            min_backoff: config
+                .fetch_retry_mintimeout
+                .map_or(default.min_backoff, Duration::from_millis),
+            max_backoff: config
+                .fetch_retry_maxtimeout
+                .map_or(default.max_backoff, Duration::from_millis),
+        };
+
         Self {
+            http,
             scoped_registries: Arc::new(registries),
+            retry_policy,
+            max_sockets: config.max_sockets.unwrap_or(self.max_sockets),
+            mirrors: Arc::new(MirrorConfig::from_npmrc(&config)),
+            ..self
+        }
+    }
+
+    /// Set the retry policy for tarball downloads.
+    #[must_use]
+    pub fn with_retry_policy(self, retry_policy: RetryPolicy) -> Self {
+        Self {
+            retry_policy,
             ..self
         }
     }
@@ -168,13 +272,13 @@ impl RegistryClient {
     /// Returns an error if the client cannot be created.
     pub fn from_env() -> Result<Self, PkgError> {
         let url = std::env::var(REGISTRY_ENV).unwrap_or_else(|_| DEFAULT_REGISTRY.to_string());
-        Self::new(&url)
+        Ok(Self::new(&url)?.with_metadata_ttl(metadata_ttl_from_env()))
     }
 
     /// Create a client from environment with persistent cache.
     pub fn from_env_with_cache(cache: PackageCache) -> Result<Self, PkgError> {
         let url = std::env::var(REGISTRY_ENV).unwrap_or_else(|_| DEFAULT_REGISTRY.to_string());
-        Self::new_with_cache(&url, Some(cache))
+        Ok(Self::new_with_cache(&url, Some(cache))?.with_metadata_ttl(metadata_ttl_from_env()))
     }
 
     /// Get the base URL.
@@ -183,12 +287,169 @@ impl RegistryClient {
         &self.base_url
     }
 
+    /// Tarball mirrors loaded from `.npmrc`.
+    #[must_use]
+    pub fn mirrors(&self) -> &MirrorConfig {
+        &self.mirrors
+    }
+
     /// Get the HTTP client (for reuse in tarball downloads).
     #[must_use]
     pub fn http(&self) -> &Client {
         &self.http
     }
 
+    /// Download the tarball of package `name` from `url`.
+    ///
+    /// Attaches the package's scoped registry token, waits for a free
+    /// download slot on the URL's host and retries transient failures,
+    /// counting them in [`download_stats`](Self::download_stats).
+    ///
+    /// # Errors
+    /// Returns an error if the download fails after the last retry or
+    /// exceeds `max_bytes`.
+    pub async fn download_tarball(
+        &self,
+        name: &str,
+        url: &str,
+        max_bytes: u64,
+    ) -> Result<Bytes, PkgError> {
+        self.download_from(url, self.auth_token_for(name), max_bytes)
+            .await
+    }
+
+    /// Download the tarball of package `name` from its mirrors, falling
+    /// back to `url` on the registry, and check it against `integrity`
+    /// (SRI or hex shasum) from the lockfile.
+    ///
+    /// A source that fails or serves a tarball with the wrong checksum is
+    /// recorded in [`VerifiedTarball::failures`] and the next one is tried.
+    ///
+    /// # Errors
+    /// Returns the last source's error (`PKG_LOCK_INTEGRITY_MISMATCH` for a
+    /// checksum mismatch) if no source serves a matching tarball.
+    pub async fn download_verified_tarball(
+        &self,
+        name: &str,
+        url: &str,
+        integrity: Option<&str>,
+        max_bytes: u64,
+    ) -> Result<VerifiedTarball, PkgError> {
+        let registry = self
+            .find_scoped_registry(name)
+            .map_or(&self.base_url, |r| &r.registry_url);
+        let sources = self
+            .mirrors
+            .sources(name, url, registry, self.auth_token_for(name));
+        let tried = sources.len();
+
+        let mut failures = Vec::new();
+        let mut last_error = None;
+        for source in sources {
+            let bytes = match self
+                .download_from(&source.url, source.auth_token.as_deref(), max_bytes)
+                .await
+            {
+                Ok(bytes) => bytes,
+                Err(e) => {
+                    failures.push(format!("{}: {}", source.label(), e.message()));
+                    last_error = Some(e);
+                    continue;
+                }
+            };
+            if let Some(IntegrityCheck::Mismatch { expected, actual }) =
+                integrity.map(|integrity| check_integrity(&bytes, integrity))
+            {
+                failures.push(format!(
+                    "{}: integrity mismatch (expected {expected}, got {actual})",
+                    source.label()
+                ));
+                last_error = Some(PkgError::new(
+                    lockfile_codes::PKG_LOCK_INTEGRITY_MISMATCH,
+                    format!(
+                        "Tarball for {name} from '{}' does not match the lockfile \
+                     (expected {expected}, got {actual})",
+                        source.url
+                    ),
+                ));
+                continue;
+            }
+            return Ok(VerifiedTarball {
+                bytes,
+                source,
+                failures,
+            });
+        }
+
+        let error = last_error
+            .unwrap_or_else(|| PkgError::download_failed(format!("No source for {name}")));
+        if tried > 1 {
+            return Err(PkgError::new(
+                error.code(),
+                format!(
+                    "No mirror served a valid tarball for {name}: {}",
+                    failures.join("; ")
+                ),
+            ));
+        }
+        Err(error)
+    }
+
+    /// Download `url`, waiting for a free slot on its host and retrying
+    /// transient failures.
+    async fn download_from(
+        &self,
+        url: &str,
+        auth_token: Option<&str>,
+        max_bytes: u64,
+    ) -> Result<Bytes, PkgError> {
+        let slots = self.download_slots(url);
+        let _permit = slots
+            .acquire()
+            .await
+            .map_err(|e| PkgError::download_failed(format!("Download slot closed: {e}")))?;
+        download_tarball_with_retry(
+            &self.http,
+            url,
+            max_bytes,
+            auth_token,
+            &self.retry_policy,
+            Some(&self.shared.download_stats),
+        )
+        .await
+    }
+
+    /// Retry counters for tarball downloads made through this client and
+    /// its clones.
+    #[must_use]
+    pub fn download_stats(&self) -> &DownloadStats {
+        &self.shared.download_stats
+    }
+
+    /// Download slots for the registry host serving `url`.
+    fn download_slots(&self, url: &str) -> Arc<Semaphore> {
+        let host = Url::parse(url).map_or_else(
+            |_| String::new(),
+            |url| {
+                format!(
+                    "{}:{}",
+                    url.host_str().unwrap_or(""),
+                    url.port_or_known_default().unwrap_or(0)
+                )
+            },
+        );
+        let mut slots = self
+            .shared
+            .download_slots
+            .lock()
+            .unwrap_or_else(std::sync::PoisonError::into_inner);
+        Arc::clone(
+            slots
+                .entry(host)
+                .or_insert_with(|| Arc::new(Semaphore::new(self.max_sockets))),
+        )
+    }
+
     /// Load cached packument from disk.
     fn load_cached_packument(&self, name: &str) -> Option<CachedPackument> {
         let cache = self.shared.disk_cache.as_ref()?;
@@ -218,38 +479,43 @@ impl RegistryClient {
                 Err(_) => return,
             };
 
-            let temp_path = path.with_extension("json.tmp");
-            if std::fs::write(&temp_path, &content).is_ok() {
-                let _ = std::fs::rename(&temp_path, &path);
-            }
+            let _ = fastnode_util::fs::atomic_write(&path, content.as_bytes());
         }
     }
 
     /// Fetch the packument (package metadata) for a package.
     ///
     /// Caching strategy:
-    /// 1. Check memory cache - return immediately if found
-    /// 2. Check disk cache - if fresh (< 5 min), return without network
-    /// 3. If disk cache exists but stale, send `ETag` for 304 validation
+    /// 1. Check memory cache - return immediately if fresh
+    /// 2. Check disk cache - if fresh (younger than the TTL), return without network
+    /// 3. If a cached copy exists but is stale, revalidate it with
+    ///    `If-None-Match`/`If-Modified-Since`; a 304 refreshes its timestamp
     /// 4. Otherwise fetch full packument (abbreviated format)
     ///
+    /// If the registry cannot be reached or fails with a server error, a
+    /// stale cached copy is returned instead (offline fallback).
+    ///
     /// # Errors
-    /// Returns an error if the request fails or the package is not found.
+    /// Returns an error if the request fails with nothing cached, or the
+    /// package is not found.
     pub async fn fetch_packument(&self, name: &str) -> Result<Value, PkgError> {
         // 1. Check memory cache first (fastest path)
-        {
-            let memory = self.shared.memory_cache.read().await;
-            if let Some(cached) = memory.get(name) {
+        let memory_cached = self.shared.memory_cache.read().await.get(name).cloned();
+        if let Some(ref cached) = memory_cached {
+            if cached.is_fresh(self.metadata_ttl) {
                 return Ok(cached.data.clone());
             }
         }
 
-        // 2. Check disk cache
-        let disk_cached = self.load_cached_packument(name);
+        // 2. Check disk cache (unless memory already holds the latest copy)
+        let stale = match memory_cached {
+            Some(cached) => Some(cached),
+            None => self.load_cached_packument(name),
+        };
 
         // If disk cache is fresh, use it without network request
-        if let Some(ref cached) = disk_cached {
-            if cached.is_fresh() {
+        if let Some(ref cached) = stale {
+            if cached.is_fresh(self.metadata_ttl) {
                 // Update memory cache and return
                 let mut memory = self.shared.memory_cache.write().await;
                 memory.insert(name.to_string(), cached.clone());
@@ -257,9 +523,33 @@ impl RegistryClient {
             }
         }
 
-        // 3. Need network request - prepare conditional headers if we have cached data
-        let cached_etag = disk_cached.as_ref().and_then(|c| c.etag.clone());
+        match self.request_packument(name, stale.as_ref()).await {
+            Ok(cached) => {
+                // Save to disk and memory caches
+                self.save_cached_packument(name, &cached);
+                let data = cached.data.clone();
+                let mut memory = self.shared.memory_cache.write().await;
+                memory.insert(name.to_string(), cached);
+                Ok(data)
+            }
+            // Offline fallback: serve the stale copy rather than failing
+            Err(e) if e.code() != codes::PKG_NOT_FOUND => match stale {
+                Some(cached) => Ok(cached.data),
+                None => Err(e),
+            },
+            Err(e) => Err(e),
+        }
+    }
 
+    /// Request a packument from the registry, revalidating `cached` if given.
+    ///
+    /// Returns the entry to cache: the new packument, or `cached` with a
+    /// fresh timestamp on 304 Not Modified.
+    async fn request_packument(
+        &self,
+        name: &str,
+        cached: Option<&CachedPackument>,
+    ) -> Result<CachedPackument, PkgError> {
         // URL-encode the name for scoped packages
         let encoded_name = if name.starts_with('@') {
             name.replace('/', "%2F")
@@ -275,7 +565,7 @@ impl RegistryClient {
             .join(&encoded_name)
             .map_err(|e| PkgError::registry(format!("Failed to build URL for '{name}': {e}")))?;
 
-        // Build request with abbreviated packument header and conditional ETag
+        // Build request with abbreviated packument header and conditional validators
         let mut request = self
             .http
             .get(url.as_str())
@@ -288,33 +578,27 @@ impl RegistryClient {
             }
         }
 
-        if let Some(etag) = &cached_etag {
-            request = request.header("If-None-Match", etag);
+        if let Some(cached) = cached {
+            if let Some(etag) = &cached.etag {
+                request = request.header("If-None-Match", etag);
+            }
+            if let Some(last_modified) = &cached.last_modified {
+                request = request.header("If-Modified-Since", last_modified);
+            }
         }
 
         let response = request.send().await?;
         let status = response.status();
 
-        // 4. Handle 304 Not Modified - use cached data, update timestamp
+        // Handle 304 Not Modified - use cached data, update timestamp
         if status == reqwest::StatusCode::NOT_MODIFIED {
-            if let Some(mut cached) = disk_cached {
-                // Update timestamp to mark as fresh
-                cached.cached_at = std::time::SystemTime::now()
-                    .duration_since(std::time::UNIX_EPOCH)
-                    .map(|d| d.as_secs())
-                    .unwrap_or(0);
-
-                // Save updated timestamp to disk
-                self.save_cached_packument(name, &cached);
-
-                // Update memory cache
-                {
-                    let mut memory = self.shared.memory_cache.write().await;
-                    memory.insert(name.to_string(), cached.clone());
-                }
-                return Ok(cached.data);
+            if let Some(cached) = cached {
+                return Ok(CachedPackument {
+                    cached_at: unix_now(),
+                    ..cached.clone()
+                });
             }
-            // Shouldn't happen, but fetch fresh if no cached data
+            // Shouldn't happen without validators; treat as a bad response
         }
 
         if status == reqwest::StatusCode::NOT_FOUND {
@@ -327,35 +611,24 @@ impl RegistryClient {
             )));
         }
 
-        // 5. Parse new packument
-        let etag = response
-            .headers()
-            .get("etag")
-            .and_then(|v| v.to_str().ok())
-            .map(String::from);
-
-        let json: Value = response.json().await?;
-
-        // Create cached entry
-        let cached = CachedPackument {
-            data: json.clone(),
-            etag,
-            cached_at: std::time::SystemTime::now()
-                .duration_since(std::time::UNIX_EPOCH)
-                .map(|d| d.as_secs())
-                .unwrap_or(0),
+        // Parse new packument
+        let header = |name: &str| {
+            response
+                .headers()
+                .get(name)
+                .and_then(|v| v.to_str().ok())
+                .map(String::from)
         };
+        let etag = header("etag");
+        let last_modified = header("last-modified");
 
-        // Save to disk cache
-        self.save_cached_packument(name, &cached);
-
-        // Save to memory cache
-        {
-            let mut memory = self.shared.memory_cache.write().await;
-            memory.insert(name.to_string(), cached);
-        }
-
-        Ok(json)
+        let data: Value = response.json().await?;
+        Ok(CachedPackument {
+            data,
+            etag,
+            last_modified,
+            cached_at: unix_now(),
+        })
     }
 
     /// Clear the in-memory packument cache.
@@ -488,16 +761,176 @@ mod tests {
         let fresh = CachedPackument {
             data: serde_json::json!({}),
             etag: None,
+            last_modified: None,
             cached_at: now,
         };
-        assert!(fresh.is_fresh());
+        assert!(fresh.is_fresh(DEFAULT_METADATA_TTL));
+        assert!(!fresh.is_fresh(Duration::ZERO));
 
         // Stale cache (10 minutes old)
         let stale = CachedPackument {
             data: serde_json::json!({}),
             etag: None,
+            last_modified: None,
             cached_at: now - 600,
         };
-        assert!(!stale.is_fresh());
+        assert!(!stale.is_fresh(DEFAULT_METADATA_TTL));
+        assert!(stale.is_fresh(Duration::from_secs(3600)));
+    }
+
+    #[tokio::test]
+    async fn test_packument_revalidation_and_offline_fallback() {
+        use std::io::{BufRead, BufReader, Write};
+        use std::net::TcpListener;
+
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let addr = listener.local_addr().unwrap();
+        let server = std::thread::spawn(move || {
+            let mut requests = Vec::new();
+            for (n, stream) in listener.incoming().take(2).enumerate() {
+                let mut stream = stream.unwrap();
+                let mut headers = Vec::new();
+                let mut reader = BufReader::new(stream.try_clone().unwrap());
+                loop {
+                    let mut line = String::new();
+                    reader.read_line(&mut line).unwrap();
+                    if line.trim().is_empty() {
+                        break;
+                    }
+                    headers.push(line.trim().to_ascii_lowercase());
+                }
+                requests.push(headers);
+
+                let response = if n == 0 {
+                    let body = r#"{"name":"left-pad","versions":{"1.3.0":{}}}"#;
+                    format!(
+                        "HTTP/1.1 200 OK\r\nContent-Type: application/json\r\n\
+                         ETag: \"v1\"\r\nLast-Modified: Tue, 01 Oct 2024 00:00:00 GMT\r\n\
+                         Content-Length: {}\r\nConnection: close\r\n\r\n{body}",
+                        body.len()
+                    )
+                } else {
+                    "HTTP/1.1 304 Not Modified\r\nConnection: close\r\n\r\n".to_string()
+                };
+                stream.write_all(response.as_bytes()).unwrap();
+            }
+            requests
+        });
+
+        let dir = tempfile::tempdir().unwrap();
+        let client = RegistryClient::new_with_cache(
+            &format!("http://{addr}/"),
+            Some(PackageCache::with_root(dir.path())),
+        )
+        .unwrap()
+        .with_metadata_ttl(Duration::ZERO);
+
+        // Initial fetch stores the validators
+        let first = client.fetch_packument("left-pad").await.unwrap();
+        assert_eq!(first["name"], "left-pad");
+        let cached = client.load_cached_packument("left-pad").unwrap();
+        assert_eq!(cached.etag.as_deref(), Some("\"v1\""));
+        assert!(cached.last_modified.is_some());
+
+        // Stale entry is revalidated; 304 keeps the cached data
+        let second = client.fetch_packument("left-pad").await.unwrap();
+        assert_eq!(second, first);
+
+        let requests = server.join().unwrap();
+        assert!(!requests[0].iter().any(|h| h.starts_with("if-none-match")));
+        assert!(requests[1].contains(&"if-none-match: \"v1\"".to_string()));
+        assert!(requests[1]
+            .iter()
+            .any(|h| h.starts_with("if-modified-since: tue, 01 oct 2024")));
+
+        // Registry is gone: serve the stale copy from disk
+        let offline = RegistryClient::new_with_cache(
+            &format!("http://{addr}/"),
+            Some(PackageCache::with_root(dir.path())),
+        )
+        .unwrap()
+        .with_metadata_ttl(Duration::ZERO);
+        let third = offline.fetch_packument("left-pad").await.unwrap();
+        assert_eq!(third, first);
+
+        // Nothing cached: the error surfaces
+        assert!(offline.fetch_packument("right-pad").await.is_err());
+    }
+
+    /// Serve `response` to each of `count` connections.
+    fn serve(response: Vec<u8>, count: usize) -> String {
+        use std::io::{BufRead, BufReader, Write};
+        use std::net::TcpListener;
+
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let addr = listener.local_addr().unwrap();
+        std::thread::spawn(move || {
+            for stream in listener.incoming().take(count) {
+                let mut stream = stream.unwrap();
+                let mut reader = BufReader::new(stream.try_clone().unwrap());
+                loop {
+                    let mut line = String::new();
+                    reader.read_line(&mut line).unwrap();
+                    if line.trim().is_empty() {
+                        break;
+                    }
+                }
+                stream.write_all(&response).unwrap();
+            }
+        });
+        format!("http://{addr}/")
+    }
+
+    fn ok(body: &[u8]) -> Vec<u8> {
+        let mut response = format!(
+            "HTTP/1.1 200 OK\r\nContent-Length: {}\r\nConnection: close\r\n\r\n",
+            body.len()
+        )
+        .into_bytes();
+        response.extend_from_slice(body);
+        response
+    }
+
+    #[tokio::test]
+    async fn test_mirror_fallback_and_integrity() {
+        use super::super::npmrc::parse_npmrc;
+
+        // sha512 of b"hello"
+        let integrity = "sha512-m3HSJL1i83hdltRq0+o9czGb+8KJDKra4t/3JRlnPKcjI8PZm6XBHXx6zG4UuMXaDEZjR1wuXDre9G9zvN7AQw==";
+        let corrupt = serve(ok(b"tampered"), 1);
+        let missing = serve(
+            b"HTTP/1.1 404 Not Found\r\nContent-Length: 0\r\nConnection: close\r\n\r\n".to_vec(),
+            1,
+        );
+        let registry = serve(ok(b"hello"), 2);
+
+        let client = RegistryClient::new(&registry).unwrap();
+        let client = RegistryClient {
+            mirrors: Arc::new(MirrorConfig::from_npmrc(&parse_npmrc(&format!(
+                "mirror[]={corrupt}\nmirror[]={missing}\n"
+            )))),
+            ..client
+        };
+        let url = format!("{registry}a/-/a-1.0.0.tgz");
+
+        // Both mirrors are skipped, the registry serves a matching tarball
+        let tarball = client
+            .download_verified_tarball("a", &url, Some(integrity), 1024)
+            .await
+            .unwrap();
+        assert_eq!(&tarball.bytes[..], b"hello");
+        assert_eq!(tarball.source.url, url);
+        assert_eq!(tarball.source.mirror, None);
+        assert_eq!(tarball.failures.len(), 2);
+        assert!(tarball.failures[0].contains("integrity mismatch"));
+        assert!(tarball.failures[1].contains("404"));
+
+        // The registry itself serving the wrong bytes is an error
+        let client = RegistryClient::new(&registry).unwrap();
+        let err = client
+            .download_verified_tarball("a", &url, Some("sha512-AAAA"), 1024)
+            .await
+            .unwrap_err();
+        assert_eq!(err.code(), lockfile_codes::PKG_LOCK_INTEGRITY_MISMATCH);
     }
 }
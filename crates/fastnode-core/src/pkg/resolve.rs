@@ -6,11 +6,11 @@
 use super::deps::{parse_npm_alias, read_package_deps};
 use super::error::PkgError;
 use super::lockfile::{
-    LockDep, LockMeta, LockPackage, LockResolution, LockRoot, Lockfile, LOCKFILE_NAME,
-    PKG_LOCK_SCHEMA_VERSION,
+    LockDep, LockMeta, LockPackage, LockPeer, LockPeerOutcome, LockResolution, LockRoot, Lockfile,
+    LOCKFILE_NAME, PKG_LOCK_SCHEMA_VERSION,
 };
 use super::registry::RegistryClient;
-use super::version::resolve_version;
+use super::version::{resolve_version, version_satisfies};
 use futures::stream::{self, StreamExt};
 use serde_json::Value;
 use std::collections::{BTreeMap, HashMap, HashSet, VecDeque};
@@ -19,12 +19,25 @@ use std::sync::Arc;
 use tokio::sync::RwLock;
 
 /// Options for dependency resolution.
-#[derive(Debug, Clone, Default)]
+#[derive(Debug, Clone)]
 pub struct ResolveOptions {
     /// Include devDependencies.
     pub include_dev: bool,
     /// Include optionalDependencies.
     pub include_optional: bool,
+    /// Install missing non-optional peer dependencies (npm 7+ behavior).
+    /// When off, missing peers are only recorded in the lockfile.
+    pub auto_install_peers: bool,
+}
+
+impl Default for ResolveOptions {
+    fn default() -> Self {
+        Self {
+            include_dev: false,
+            include_optional: false,
+            auto_install_peers: true,
+        }
+    }
 }
 
 /// Result of resolving dependencies.
@@ -166,11 +179,12 @@ pub async fn resolve_dependencies(
         }
     }
 
-    // Phase 2: Auto-install non-optional peer dependencies (pnpm v8+ behavior).
+    // Phase 2: Auto-install non-optional peer dependencies (npm 7+ behavior).
     // This runs AFTER all regular transitive deps are resolved so we can
-    // reliably check whether a compatible version already exists and avoid
-    // pulling in duplicate major versions (e.g. react@19 when 18 is in the tree).
-    resolve_missing_peers(&state, registry).await?;
+    // reliably check whether a compatible version already exists. A peer whose
+    // name is already in the tree at an incompatible version is a conflict
+    // rather than a second copy (e.g. react@19 when 18 is in the tree).
+    let peers = resolve_missing_peers(&state, registry, options.auto_install_peers).await?;
 
     // Build root dependencies map
     let mut dependencies: BTreeMap<String, LockDep> = BTreeMap::new();
@@ -214,6 +228,7 @@ pub async fn resolve_dependencies(
         root: LockRoot::new(root_name, root_version),
         dependencies,
         packages: packages.clone(),
+        peers,
     };
 
     Ok(ResolveResult {
@@ -429,51 +444,71 @@ async fn resolve_single_dep(
 /// Resolve peer dependencies that are not yet satisfied by any package in the
 /// lockfile.  Runs after all regular transitive resolution is complete so we
 /// can reliably detect existing versions and avoid duplicates.
+///
+/// Returns the decisions for peers that were not already satisfied, sorted by
+/// declaring package and peer name.
 async fn resolve_missing_peers(
     state: &Arc<ResolveState>,
     registry: &RegistryClient,
-) -> Result<(), PkgError> {
-    use super::version::version_satisfies;
+    auto_install: bool,
+) -> Result<Vec<LockPeer>, PkgError> {
+    let mut decisions: BTreeMap<(String, String), LockPeer> = BTreeMap::new();
 
     loop {
-        let missing: Vec<(String, String)> = {
+        // (declaring package, peer name, range) to install this round
+        let mut to_install: Vec<(String, String, String)> = Vec::new();
+        {
             let packages = state.packages.read().await;
 
-            let mut seen = HashSet::new();
-            let mut out = Vec::new();
-
-            for (_key, lock_pkg) in packages.iter() {
+            for (key, lock_pkg) in packages.iter() {
                 for (peer_name, peer_range) in &lock_pkg.peer_dependencies {
-                    // Already satisfied by an existing package?
-                    let satisfied = packages.iter().any(|(k, _pkg)| {
-                        k.rsplit_once('@').is_some_and(|(n, v)| {
-                            n == peer_name.as_str() && version_satisfies(v, peer_range)
-                        })
-                    });
-
-                    if !satisfied {
-                        let dedup_key = format!("{peer_name}@{peer_range}");
-                        if seen.insert(dedup_key) {
-                            out.push((peer_name.clone(), peer_range.clone()));
-                        }
+                    let decision_key = (key.clone(), peer_name.clone());
+                    if decisions.contains_key(&decision_key) {
+                        continue;
                     }
+
+                    let versions = versions_in_tree(&packages, peer_name);
+                    if versions.iter().any(|v| version_satisfies(v, peer_range)) {
+                        continue;
+                    }
+
+                    let outcome = if !versions.is_empty() {
+                        LockPeerOutcome::Conflict
+                    } else if !auto_install {
+                        LockPeerOutcome::Missing
+                    } else {
+                        // One range per peer name per round; later ranges are
+                        // checked against whatever gets installed.
+                        if !to_install.iter().any(|(_, n, _)| n == peer_name) {
+                            to_install.push((key.clone(), peer_name.clone(), peer_range.clone()));
+                        }
+                        continue;
+                    };
+                    decisions.insert(
+                        decision_key,
+                        LockPeer {
+                            package: key.clone(),
+                            name: peer_name.clone(),
+                            range: peer_range.clone(),
+                            outcome,
+                            versions: versions.into_iter().map(String::from).collect(),
+                        },
+                    );
                 }
             }
+        }
 
-            out
-        };
-
-        if missing.is_empty() {
+        if to_install.is_empty() {
             break;
         }
 
         // Resolve missing peers as a batch
-        let batch: Vec<PendingDep> = missing
-            .into_iter()
-            .map(|(name, range)| PendingDep {
-                name,
+        let batch: Vec<PendingDep> = to_install
+            .iter()
+            .map(|(_, name, range)| PendingDep {
+                name: name.clone(),
                 alias: None,
-                range,
+                range: range.clone(),
                 depth: 1, // peers are shallow
             })
             .collect();
@@ -492,11 +527,41 @@ async fn resolve_missing_peers(
             }
         }
 
+        let packages = state.packages.read().await;
+        for (key, name, range) in to_install {
+            let versions = versions_in_tree(&packages, &name)
+                .into_iter()
+                .filter(|v| version_satisfies(v, &range))
+                .map(String::from)
+                .collect();
+            decisions.insert(
+                (key.clone(), name.clone()),
+                LockPeer {
+                    package: key,
+                    name,
+                    range,
+                    outcome: LockPeerOutcome::Installed,
+                    versions,
+                },
+            );
+        }
+
         // Loop back to check if the newly resolved packages introduced more
         // unsatisfied peers (rare but possible).
     }
 
-    Ok(())
+    Ok(decisions.into_values().collect())
+}
+
+/// Versions of `name` among the resolved packages.
+fn versions_in_tree<'a>(packages: &'a BTreeMap<String, LockPackage>, name: &str) -> Vec<&'a str> {
+    packages
+        .iter()
+        .filter_map(|(key, pkg)| {
+            let (key_name, _) = key.rsplit_once('@')?;
+            (key_name == name).then_some(pkg.version.as_str())
+        })
+        .collect()
 }
 
 /// Check whether a peer dependency is optional according to peerDependenciesMeta
@@ -561,4 +626,114 @@ mod tests {
         assert_eq!(get_dep_kind(&pkg_json, "fsevents"), "optional");
         assert_eq!(get_dep_kind(&pkg_json, "unknown"), "dep");
     }
+
+    /// Serve packuments for `(name, version, peerDependencies)` over HTTP.
+    fn mock_registry(packages: &'static [(&str, &str, &str)]) -> String {
+        use std::io::{BufRead, BufReader, Write};
+        use std::net::TcpListener;
+
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let addr = listener.local_addr().unwrap();
+        std::thread::spawn(move || {
+            for stream in listener.incoming() {
+                let mut stream = stream.unwrap();
+                let mut reader = BufReader::new(stream.try_clone().unwrap());
+                let mut request_line = String::new();
+                reader.read_line(&mut request_line).unwrap();
+                loop {
+                    let mut line = String::new();
+                    reader.read_line(&mut line).unwrap();
+                    if line.trim().is_empty() {
+                        break;
+                    }
+                }
+                let name = request_line.split_whitespace().nth(1).unwrap_or("/")[1..].to_string();
+
+                let mut versions = serde_json::Map::new();
+                let mut latest = String::new();
+                for (pkg, version, peers) in packages.iter().filter(|p| p.0 == name) {
+                    let peers: Value = serde_json::from_str(peers).unwrap();
+                    versions.insert(
+                        (*version).to_string(),
+                        serde_json::json!({
+                            "name": pkg,
+                            "version": version,
+                            "peerDependencies": peers,
+                            "dist": { "tarball": format!("http://{addr}/{pkg}-{version}.tgz") }
+                        }),
+                    );
+                    latest = (*version).to_string();
+                }
+                let body = serde_json::json!({
+                    "name": name,
+                    "dist-tags": { "latest": latest },
+                    "versions": versions
+                })
+                .to_string();
+                let response = format!(
+                    "HTTP/1.1 200 OK\r\nContent-Type: application/json\r\n\
+                     Content-Length: {}\r\nConnection: close\r\n\r\n{body}",
+                    body.len()
+                );
+                stream.write_all(response.as_bytes()).unwrap();
+            }
+        });
+        format!("http://{addr}/")
+    }
+
+    #[tokio::test]
+    async fn test_peer_resolution_installs_and_detects_conflicts() {
+        static PACKAGES: &[(&str, &str, &str)] = &[
+            ("react", "17.0.2", "{}"),
+            ("react", "18.2.0", "{}"),
+            ("react-dom", "18.2.0", r#"{"react":"^18.2.0"}"#),
+            ("plugin", "1.0.0", r#"{"lodash":"^4.0.0"}"#),
+            ("lodash", "4.17.21", "{}"),
+        ];
+        let registry = RegistryClient::new(&mock_registry(PACKAGES)).unwrap();
+        let dir = tempfile::tempdir().unwrap();
+        std::fs::write(
+            dir.path().join("package.json"),
+            r#"{"name":"app","dependencies":{"react":"^17.0.0","react-dom":"^18.2.0","plugin":"^1.0.0"}}"#,
+        )
+        .unwrap();
+
+        let result = resolve_dependencies(dir.path(), &registry, &ResolveOptions::default())
+            .await
+            .unwrap();
+        let lockfile = result.lockfile;
+
+        // lodash is auto-installed for plugin
+        assert!(lockfile.packages.contains_key("lodash@4.17.21"));
+        let installed = &lockfile.peers[0];
+        assert_eq!(installed.package, "plugin@1.0.0");
+        assert_eq!(installed.outcome, LockPeerOutcome::Installed);
+        assert_eq!(installed.versions, ["4.17.21"]);
+
+        // react@18 is not pulled in next to the root's react@17
+        assert!(!lockfile.packages.contains_key("react@18.2.0"));
+        let conflicts: Vec<&LockPeer> = lockfile.peer_conflicts().collect();
+        assert_eq!(conflicts.len(), 1);
+        assert_eq!(conflicts[0].package, "react-dom@18.2.0");
+        assert_eq!(conflicts[0].versions, ["17.0.2"]);
+        assert_eq!(
+            conflicts[0].message(),
+            "react-dom@18.2.0 requires peer react@^18.2.0, but the tree has react@17.0.2"
+        );
+
+        // With auto-install off the peer is only recorded
+        let options = ResolveOptions {
+            auto_install_peers: false,
+            ..Default::default()
+        };
+        let result = resolve_dependencies(dir.path(), &registry, &options)
+            .await
+            .unwrap();
+        assert!(!result.lockfile.packages.contains_key("lodash@4.17.21"));
+        assert!(result
+            .lockfile
+            .peers
+            .iter()
+            .any(|p| p.name == "lodash" && p.outcome == LockPeerOutcome::Missing));
+    }
 }
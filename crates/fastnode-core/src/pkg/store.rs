@@ -0,0 +1,302 @@
+//! Content-addressed file store for the package cache.
+//!
+//! Versions of a package usually ship mostly identical files. With the store
+//! enabled, each extracted file is hashed (BLAKE3) and kept once under
+//! `<cache>/.files/<2 hex>/<rest of hex>`, and package directories hard-link
+//! to it. Executable files get separate entries (`-exec` suffix) because hard
+//! links share permissions.
+
+use super::error::PkgError;
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+use walkdir::WalkDir;
+
+/// Directory of the file store inside the cache root.
+pub const FILE_STORE_DIR: &str = ".files";
+
+/// Environment variable enabling the file store for new cache entries
+/// (`1` or `true`). A cache that was deduplicated once keeps using it.
+pub const FILE_STORE_ENV: &str = "FASTNODE_PKG_FILE_STORE";
+
+/// Counters from moving package files into the store.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct DedupeStats {
+    /// Regular files visited.
+    pub files: u64,
+    /// Files replaced by a link to an existing store entry.
+    pub linked: u64,
+    /// Bytes no longer stored twice.
+    pub saved_bytes: u64,
+}
+
+impl DedupeStats {
+    /// Add the counters of `other`.
+    pub fn add(&mut self, other: Self) {
+        self.files += other.files;
+        self.linked += other.linked;
+        self.saved_bytes += other.saved_bytes;
+    }
+}
+
+/// Size of the file store.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct FileStoreUsage {
+    /// Stored file entries.
+    pub files: u64,
+    /// Total size of the entries in bytes.
+    pub size_bytes: u64,
+}
+
+/// Content-addressed file store.
+#[derive(Debug, Clone)]
+pub struct FileStore {
+    root: PathBuf,
+}
+
+impl FileStore {
+    /// Create a store rooted at `root`.
+    #[must_use]
+    pub fn new(root: impl Into<PathBuf>) -> Self {
+        Self { root: root.into() }
+    }
+
+    /// Get the store root directory.
+    #[must_use]
+    pub fn root(&self) -> &Path {
+        &self.root
+    }
+
+    /// Check if the store has been created.
+    #[must_use]
+    pub fn exists(&self) -> bool {
+        self.root.is_dir()
+    }
+
+    /// Move the files of `dir` into the store, replacing each with a hard
+    /// link to its entry.
+    ///
+    /// Files that can't be linked (e.g. the store is on another filesystem)
+    /// are left as they are.
+    ///
+    /// # Errors
+    /// Returns an error if the store can't be created or `dir` can't be read.
+    pub fn dedupe_dir(&self, dir: &Path) -> Result<DedupeStats, PkgError> {
+        fs::create_dir_all(&self.root)?;
+
+        let mut stats = DedupeStats::default();
+        for entry in WalkDir::new(dir).follow_links(false) {
+            let entry = entry.map_err(io::Error::from)?;
+            if !entry.file_type().is_file() {
+                continue;
+            }
+            stats.files += 1;
+            if let Ok(Some(saved)) = self.link_file(entry.path()) {
+                stats.linked += 1;
+                stats.saved_bytes += saved;
+            }
+        }
+        Ok(stats)
+    }
+
+    /// Get the number and total size of stored entries.
+    #[must_use]
+    pub fn usage(&self) -> FileStoreUsage {
+        let mut usage = FileStoreUsage::default();
+        for entry in WalkDir::new(&self.root).into_iter().flatten() {
+            if let Ok(metadata) = entry.metadata() {
+                if metadata.is_file() {
+                    usage.files += 1;
+                    usage.size_bytes += metadata.len();
+                }
+            }
+        }
+        usage
+    }
+
+    /// Link `path` with its store entry.
+    ///
+    /// Returns the bytes saved when the file was replaced by an existing
+    /// entry, `None` if it became the entry or already was one.
+    fn link_file(&self, path: &Path) -> io::Result<Option<u64>> {
+        let metadata = fs::symlink_metadata(path)?;
+        let entry = self.entry_path(&blake3::hash(&fs::read(path)?), is_executable(&metadata));
+
+        match fs::symlink_metadata(&entry) {
+            Ok(existing) if is_same_file(&existing, &metadata) => Ok(None),
+            Ok(_) => {
+                // Swap in a link to the entry without leaving a gap
+                let file_name = path.file_name().unwrap_or_default().to_string_lossy();
+                let temp = path.with_file_name(format!(".{file_name}.howth-link"));
+                let _ = fs::remove_file(&temp);
+                fs::hard_link(&entry, &temp)?;
+                if let Err(e) = fs::rename(&temp, path) {
+                    let _ = fs::remove_file(&temp);
+                    return Err(e);
+                }
+                Ok(Some(metadata.len()))
+            }
+            Err(e) if e.kind() == io::ErrorKind::NotFound => {
+                if let Some(parent) = entry.parent() {
+                    fs::create_dir_all(parent)?;
+                }
+                match fs::hard_link(path, &entry) {
+                    Ok(()) => Ok(None),
+                    // A concurrent extraction stored the same content first
+                    Err(e) if e.kind() == io::ErrorKind::AlreadyExists => self.link_file(path),
+                    Err(e) => Err(e),
+                }
+            }
+            Err(e) => Err(e),
+        }
+    }
+
+    /// Path of the entry for content `hash`.
+    fn entry_path(&self, hash: &blake3::Hash, executable: bool) -> PathBuf {
+        let hex = hash.to_hex();
+        let suffix = if executable { "-exec" } else { "" };
+        self.root
+            .join(&hex[..2])
+            .join(format!("{}{suffix}", &hex[2..]))
+    }
+}
+
+#[cfg(unix)]
+fn is_executable(metadata: &fs::Metadata) -> bool {
+    use std::os::unix::fs::PermissionsExt;
+    metadata.permissions().mode() & 0o111 != 0
+}
+
+#[cfg(not(unix))]
+fn is_executable(_metadata: &fs::Metadata) -> bool {
+    false
+}
+
+#[cfg(unix)]
+fn is_same_file(a: &fs::Metadata, b: &fs::Metadata) -> bool {
+    use std::os::unix::fs::MetadataExt;
+    a.dev() == b.dev() && a.ino() == b.ino()
+}
+
+/// Without inode numbers, existing links are replaced again (harmless, but
+/// counted as saved).
+#[cfg(not(unix))]
+fn is_same_file(_a: &fs::Metadata, _b: &fs::Metadata) -> bool {
+    false
+}
+
+/// Bytes used on disk by the files under `dirs`, counting hard-linked files
+/// once. Without inode numbers (non-Unix), every link is counted.
+#[must_use]
+pub fn disk_usage<'a>(dirs: impl IntoIterator<Item = &'a Path>) -> u64 {
+    #[cfg(unix)]
+    let mut seen = std::collections::HashSet::new();
+    let mut size = 0;
+    for dir in dirs {
+        for entry in WalkDir::new(dir).into_iter().flatten() {
+            let Ok(metadata) = entry.metadata() else {
+                continue;
+            };
+            if !metadata.is_file() {
+                continue;
+            }
+            #[cfg(unix)]
+            {
+                use std::os::unix::fs::MetadataExt;
+                if !seen.insert((metadata.dev(), metadata.ino())) {
+                    continue;
+                }
+            }
+            size += metadata.len();
+        }
+    }
+    size
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::tempdir;
+
+    fn write_package(dir: &Path, files: &[(&str, &str)]) {
+        for (path, content) in files {
+            let path = dir.join(path);
+            fs::create_dir_all(path.parent().unwrap()).unwrap();
+            fs::write(path, content).unwrap();
+        }
+    }
+
+    #[test]
+    fn test_dedupe_links_identical_files() {
+        let dir = tempdir().unwrap();
+        let store = FileStore::new(dir.path().join(FILE_STORE_DIR));
+        let v1 = dir.path().join("pkg/1.0.0/package");
+        let v2 = dir.path().join("pkg/1.0.1/package");
+        write_package(&v1, &[("index.js", "shared"), ("README.md", "v1")]);
+        write_package(&v2, &[("index.js", "shared"), ("README.md", "v2")]);
+
+        let first = store.dedupe_dir(&v1).unwrap();
+        assert_eq!(first.files, 2);
+        assert_eq!(first.linked, 0);
+
+        let second = store.dedupe_dir(&v2).unwrap();
+        assert_eq!(second.files, 2);
+        assert_eq!(second.linked, 1);
+        assert_eq!(second.saved_bytes, "shared".len() as u64);
+
+        assert_eq!(fs::read_to_string(v2.join("index.js")).unwrap(), "shared");
+        assert_eq!(fs::read_to_string(v2.join("README.md")).unwrap(), "v2");
+        assert_eq!(
+            store.usage(),
+            FileStoreUsage {
+                files: 3,
+                size_bytes: 10,
+            }
+        );
+
+        // Already linked files are left alone
+        let again = store.dedupe_dir(&v2).unwrap();
+        #[cfg(unix)]
+        assert_eq!(again.linked, 0);
+        assert_eq!(again.files, 2);
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_dedupe_keeps_executables_separate() {
+        use std::os::unix::fs::PermissionsExt;
+
+        let dir = tempdir().unwrap();
+        let store = FileStore::new(dir.path().join(FILE_STORE_DIR));
+        let pkg = dir.path().join("pkg");
+        write_package(&pkg, &[("cli.js", "same"), ("lib.js", "same")]);
+        fs::set_permissions(pkg.join("cli.js"), fs::Permissions::from_mode(0o755)).unwrap();
+
+        let stats = store.dedupe_dir(&pkg).unwrap();
+        assert_eq!(stats.linked, 0);
+        assert_eq!(store.usage().files, 2);
+        let mode = fs::metadata(pkg.join("cli.js"))
+            .unwrap()
+            .permissions()
+            .mode();
+        assert_ne!(mode & 0o111, 0);
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_disk_usage_counts_links_once() {
+        let dir = tempdir().unwrap();
+        let store = FileStore::new(dir.path().join(FILE_STORE_DIR));
+        let v1 = dir.path().join("a");
+        let v2 = dir.path().join("b");
+        write_package(&v1, &[("index.js", "0123456789")]);
+        write_package(&v2, &[("index.js", "0123456789")]);
+
+        let before = disk_usage([v1.as_path(), v2.as_path()]);
+        assert_eq!(before, 20);
+
+        store.dedupe_dir(&v1).unwrap();
+        store.dedupe_dir(&v2).unwrap();
+        assert_eq!(disk_usage([v1.as_path(), v2.as_path(), store.root()]), 10);
+    }
+}
@@ -1,12 +1,16 @@
 //! Tarball download and extraction.
 
 use super::error::PkgError;
+use super::store::FileStore;
 use bytes::Bytes;
+use fastnode_util::fs as fs_util;
 use flate2::read::GzDecoder;
-use reqwest::Client;
+use reqwest::header::{CONTENT_RANGE, ETAG, LAST_MODIFIED};
+use reqwest::{Client, StatusCode};
 use std::fs::{self, File};
 use std::io;
 use std::path::Path;
+use std::sync::atomic::{AtomicU32, Ordering};
 use std::time::Duration;
 use tar::Archive;
 
@@ -16,7 +20,67 @@ pub const MAX_TARBALL_SIZE: u64 = 200 * 1024 * 1024;
 /// Download timeout in seconds.
 const DOWNLOAD_TIMEOUT_SECS: u64 = 30;
 
-/// Download a tarball from a URL.
+/// Retry policy for tarball downloads.
+///
+/// Mirrors npm's `fetch-retries`, `fetch-retry-mintimeout` and
+/// `fetch-retry-maxtimeout` settings.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct RetryPolicy {
+    /// Retries after the first attempt.
+    pub retries: u32,
+    /// Backoff before the first retry; doubles on each further retry.
+    pub min_backoff: Duration,
+    /// Upper bound for the backoff.
+    pub max_backoff: Duration,
+}
+
+impl Default for RetryPolicy {
+    fn default() -> Self {
+        Self {
+            retries: 3,
+            min_backoff: Duration::from_millis(500),
+            max_backoff: Duration::from_secs(10),
+        }
+    }
+}
+
+impl RetryPolicy {
+    /// Backoff before retry `attempt` (0-based): the exponential step,
+    /// capped at `max_backoff`, with jitter over its upper half so parallel
+    /// downloads don't retry in lockstep.
+    fn backoff(&self, attempt: u32) -> Duration {
+        let step = self
+            .min_backoff
+            .saturating_mul(1u32.checked_shl(attempt).unwrap_or(u32::MAX))
+            .min(self.max_backoff);
+        let half = step / 2;
+        half + half.mul_f64(f64::from(rand_u32()) / f64::from(u32::MAX))
+    }
+}
+
+/// Retry counters, shared by concurrent downloads.
+#[derive(Debug, Default)]
+pub struct DownloadStats {
+    retries: AtomicU32,
+    resumed: AtomicU32,
+}
+
+impl DownloadStats {
+    /// Number of retries after failed attempts.
+    #[must_use]
+    pub fn retries(&self) -> u32 {
+        self.retries.load(Ordering::Relaxed)
+    }
+
+    /// Number of retries that resumed a partial download instead of
+    /// starting over.
+    #[must_use]
+    pub fn resumed(&self) -> u32 {
+        self.resumed.load(Ordering::Relaxed)
+    }
+}
+
+/// Download a tarball from a URL, retrying with the default [`RetryPolicy`].
 ///
 /// If `auth_token` is provided, attaches a `Bearer` authorization header.
 ///
@@ -28,6 +92,83 @@ pub async fn download_tarball(
     max_bytes: u64,
     auth_token: Option<&str>,
 ) -> Result<Bytes, PkgError> {
+    download_tarball_with_retry(
+        client,
+        url,
+        max_bytes,
+        auth_token,
+        &RetryPolicy::default(),
+        None,
+    )
+    .await
+}
+
+/// Download a tarball from a URL, retrying transient failures.
+///
+/// Connection errors, timeouts, `408`, `429` and `5xx` responses are retried
+/// with exponential backoff. When a retry follows a partially received body,
+/// the download resumes with a `Range` request (guarded by `If-Range`) and
+/// only falls back to a full download if the server doesn't honor it.
+///
+/// # Errors
+/// Returns an error if the download still fails after the last retry, fails
+/// with a non-retryable status, or exceeds the size limit.
+pub async fn download_tarball_with_retry(
+    client: &Client,
+    url: &str,
+    max_bytes: u64,
+    auth_token: Option<&str>,
+    policy: &RetryPolicy,
+    stats: Option<&DownloadStats>,
+) -> Result<Bytes, PkgError> {
+    let mut partial = Partial::default();
+    let mut attempt = 0;
+    loop {
+        match download_attempt(client, url, max_bytes, auth_token, &mut partial, stats).await {
+            Ok(()) => return Ok(Bytes::from(partial.bytes)),
+            Err(Attempt::Retryable(e)) if attempt < policy.retries => {
+                let delay = policy.backoff(attempt);
+                tracing::debug!(url, error = %e, ?delay, received = partial.bytes.len(), "Retrying tarball download");
+                tokio::time::sleep(delay).await;
+                attempt += 1;
+                if let Some(stats) = stats {
+                    stats.retries.fetch_add(1, Ordering::Relaxed);
+                }
+            }
+            Err(Attempt::Retryable(e) | Attempt::Fatal(e)) => return Err(e),
+        }
+    }
+}
+
+/// Body received so far, with the validator to resume it.
+#[derive(Default)]
+struct Partial {
+    bytes: Vec<u8>,
+    /// Strong `ETag` or `Last-Modified` of the response, for `If-Range`.
+    validator: Option<String>,
+}
+
+impl Partial {
+    fn resumable(&self) -> bool {
+        !self.bytes.is_empty() && self.validator.is_some()
+    }
+}
+
+/// A failed download attempt.
+enum Attempt {
+    Retryable(PkgError),
+    Fatal(PkgError),
+}
+
+/// Make one download attempt, appending to `partial` when resuming.
+async fn download_attempt(
+    client: &Client,
+    url: &str,
+    max_bytes: u64,
+    auth_token: Option<&str>,
+    partial: &mut Partial,
+    stats: Option<&DownloadStats>,
+) -> Result<(), Attempt> {
     let mut request = client
         .get(url)
         .timeout(Duration::from_secs(DOWNLOAD_TIMEOUT_SECS));
@@ -36,40 +177,106 @@ pub async fn download_tarball(
         request = request.header("Authorization", format!("Bearer {token}"));
     }
 
-    let response = request
-        .send()
-        .await
-        .map_err(|e| PkgError::download_failed(format!("Failed to download '{url}': {e}")))?;
+    let resume_from = if partial.resumable() {
+        partial.bytes.len()
+    } else {
+        partial.bytes.clear();
+        0
+    };
+    if let (true, Some(validator)) = (resume_from > 0, &partial.validator) {
+        request = request
+            .header("Range", format!("bytes={resume_from}-"))
+            .header("If-Range", validator);
+    }
 
-    if !response.status().is_success() {
-        return Err(PkgError::download_failed(format!(
-            "Download failed with status {} for '{url}'",
-            response.status()
-        )));
+    let mut response = request.send().await.map_err(|e| {
+        Attempt::Retryable(PkgError::download_failed(format!(
+            "Failed to download '{url}': {e}"
+        )))
+    })?;
+
+    let code = response.status();
+    if code == StatusCode::PARTIAL_CONTENT && resume_from > 0 {
+        let expected = format!("bytes {resume_from}-");
+        let matches = response
+            .headers()
+            .get(CONTENT_RANGE)
+            .and_then(|v| v.to_str().ok())
+            .is_some_and(|range| range.starts_with(&expected));
+        if !matches {
+            partial.bytes.clear();
+            return Err(Attempt::Retryable(PkgError::download_failed(format!(
+                "Unexpected Content-Range resuming '{url}'"
+            ))));
+        }
+        if let Some(stats) = stats {
+            stats.resumed.fetch_add(1, Ordering::Relaxed);
+        }
+    } else if code.is_success() {
+        // Full body: start over and remember how to resume it
+        partial.bytes.clear();
+        partial.validator = resume_validator(&response);
+    } else {
+        let retryable = code == StatusCode::REQUEST_TIMEOUT
+            || code == StatusCode::TOO_MANY_REQUESTS
+            || code == StatusCode::RANGE_NOT_SATISFIABLE
+            || code.is_server_error();
+        if code == StatusCode::RANGE_NOT_SATISFIABLE {
+            partial.bytes.clear();
+        }
+        let error =
+            PkgError::download_failed(format!("Download failed with status {code} for '{url}'"));
+        return Err(if retryable {
+            Attempt::Retryable(error)
+        } else {
+            Attempt::Fatal(error)
+        });
     }
 
     // Check content length if available
     if let Some(len) = response.content_length() {
-        if len > max_bytes {
-            return Err(PkgError::download_failed(format!(
-                "Tarball too large: {len} bytes (max: {max_bytes})"
-            )));
+        let total = len + partial.bytes.len() as u64;
+        if total > max_bytes {
+            return Err(Attempt::Fatal(PkgError::download_failed(format!(
+                "Tarball too large: {total} bytes (max: {max_bytes})"
+            ))));
         }
     }
 
-    let bytes = response
-        .bytes()
-        .await
-        .map_err(|e| PkgError::download_failed(format!("Failed to read response body: {e}")))?;
-
-    if bytes.len() as u64 > max_bytes {
-        return Err(PkgError::download_failed(format!(
-            "Tarball too large: {} bytes (max: {max_bytes})",
-            bytes.len()
-        )));
+    loop {
+        match response.chunk().await {
+            Ok(Some(chunk)) => {
+                partial.bytes.extend_from_slice(&chunk);
+                if partial.bytes.len() as u64 > max_bytes {
+                    return Err(Attempt::Fatal(PkgError::download_failed(format!(
+                        "Tarball too large: {} bytes (max: {max_bytes})",
+                        partial.bytes.len()
+                    ))));
+                }
+            }
+            Ok(None) => return Ok(()),
+            Err(e) => {
+                return Err(Attempt::Retryable(PkgError::download_failed(format!(
+                    "Failed to read response body: {e}"
+                ))))
+            }
+        }
     }
+}
 
-    Ok(bytes)
+/// Validator for resuming `response` with `If-Range`: a strong `ETag`, or
+/// `Last-Modified` (weak `ETag`s can't be used for range requests).
+fn resume_validator(response: &reqwest::Response) -> Option<String> {
+    let header = |name| {
+        response
+            .headers()
+            .get(name)
+            .and_then(|v| v.to_str().ok())
+            .map(str::to_string)
+    };
+    header(ETAG)
+        .filter(|etag| !etag.starts_with("W/"))
+        .or_else(|| header(LAST_MODIFIED))
 }
 
 /// Extract a tarball to a destination directory atomically.
@@ -80,6 +287,25 @@ pub async fn download_tarball(
 /// # Errors
 /// Returns an error if extraction fails or the tarball is invalid.
 pub fn extract_tgz_atomic(bytes: &[u8], dest_package_dir: &Path) -> Result<(), PkgError> {
+    extract_tgz_atomic_with_store(bytes, dest_package_dir, None)
+}
+
+/// Extract a tarball like [`extract_tgz_atomic`], moving its files into
+/// `store` (when given) before the package directory appears.
+///
+/// Deduplication is best effort: files that can't be linked stay as they are.
+/// Files are written through [`fs_util::long_path`], so packages nested
+/// deeper than `MAX_PATH` extract on Windows.
+///
+/// # Errors
+/// Returns an error if extraction fails or the tarball is invalid.
+pub fn extract_tgz_atomic_with_store(
+    bytes: &[u8],
+    dest_package_dir: &Path,
+    store: Option<&FileStore>,
+) -> Result<(), PkgError> {
+    let dest_package_dir = &fs_util::long_path(dest_package_dir);
+
     // Get parent directory (version dir)
     let version_dir = dest_package_dir
         .parent()
@@ -115,6 +341,12 @@ pub fn extract_tgz_atomic(bytes: &[u8], dest_package_dir: &Path) -> Result<(), P
     // package name (e.g., `node/`, `estree/`). Find the actual directory.
     let extracted_package = find_extracted_root(&temp_dir)?;
 
+    if let Some(store) = store {
+        if let Err(e) = store.dedupe_dir(&extracted_package) {
+            tracing::debug!(error = %e, "Failed to deduplicate extracted files");
+        }
+    }
+
     // Atomically move package/ to final destination
     match fs::rename(&extracted_package, dest_package_dir) {
         Ok(()) => {
@@ -160,9 +392,7 @@ fn find_extracted_root(temp_dir: &Path) -> Result<std::path::PathBuf, PkgError>
         .map_err(|e| PkgError::extract_failed(format!("Failed to read extracted dir: {e}")))?
         .filter_map(std::result::Result::ok)
         .filter(|e| {
-            e.file_type()
-                .map(|ft| ft.is_dir())
-                .unwrap_or(false)
+            e.file_type().is_ok_and(|ft| ft.is_dir())
                 // Skip hidden/temp directories
                 && !e
                     .file_name()
@@ -216,8 +446,11 @@ fn extract_tgz_to(bytes: &[u8], dest: &Path) -> Result<(), PkgError> {
             }
         }
 
-        // Build destination path
-        let dest_path = dest.join(&*path);
+        // Build destination path one component at a time: a `\\?\` path
+        // takes `/` literally rather than as a separator
+        let dest_path = path
+            .components()
+            .fold(dest.to_path_buf(), |dest, component| dest.join(component));
 
         // Ensure it's under dest
         if !dest_path.starts_with(dest) {
@@ -266,7 +499,7 @@ fn copy_dir_all(src: &Path, dst: &Path) -> io::Result<()> {
         if ty.is_dir() {
             copy_dir_all(&src_path, &dst_path)?;
         } else if ty.is_file() {
-            fs::copy(&src_path, &dst_path)?;
+            fs_util::clone_or_copy(&src_path, &dst_path)?;
         }
         // Skip symlinks
     }
@@ -285,8 +518,7 @@ fn rand_u32() -> u32 {
     hasher.write_u64(
         std::time::SystemTime::now()
             .duration_since(std::time::UNIX_EPOCH)
-            .map(|d| d.as_nanos() as u64)
-            .unwrap_or(0),
+            .map_or(0, |d| d.as_nanos() as u64),
     );
     hasher.finish() as u32
 }
@@ -447,4 +679,125 @@ mod tests {
         // Verify file was extracted
         assert!(dest.join("deep").join("nested").join("file.txt").exists());
     }
+
+    /// Serve one canned response per connection, returning each request's
+    /// lowercased header lines.
+    fn serve(responses: Vec<Vec<u8>>) -> (String, std::thread::JoinHandle<Vec<Vec<String>>>) {
+        use std::io::{BufRead, BufReader};
+        use std::net::TcpListener;
+
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let url = format!("http://{}/pkg.tgz", listener.local_addr().unwrap());
+        let server = std::thread::spawn(move || {
+            let mut requests = Vec::new();
+            for (response, stream) in responses.into_iter().zip(listener.incoming()) {
+                let mut stream = stream.unwrap();
+                let mut headers = Vec::new();
+                let mut reader = BufReader::new(stream.try_clone().unwrap());
+                loop {
+                    let mut line = String::new();
+                    reader.read_line(&mut line).unwrap();
+                    if line.trim().is_empty() {
+                        break;
+                    }
+                    headers.push(line.trim().to_ascii_lowercase());
+                }
+                requests.push(headers);
+                stream.write_all(&response).unwrap();
+            }
+            requests
+        });
+        (url, server)
+    }
+
+    fn fast_retries() -> RetryPolicy {
+        RetryPolicy {
+            retries: 3,
+            min_backoff: Duration::from_millis(1),
+            max_backoff: Duration::from_millis(5),
+        }
+    }
+
+    #[tokio::test]
+    async fn test_download_retries_and_resumes() {
+        let body = create_test_tarball();
+        let (head, tail) = body.split_at(body.len() / 2);
+
+        let unavailable =
+            b"HTTP/1.1 503 Service Unavailable\r\nContent-Length: 0\r\nConnection: close\r\n\r\n"
+                .to_vec();
+        // Connection drops halfway through the body
+        let mut truncated = format!(
+            "HTTP/1.1 200 OK\r\nETag: \"t1\"\r\nContent-Length: {}\r\nConnection: close\r\n\r\n",
+            body.len()
+        )
+        .into_bytes();
+        truncated.extend_from_slice(head);
+        let mut rest = format!(
+            "HTTP/1.1 206 Partial Content\r\nContent-Range: bytes {}-{}/{}\r\n\
+             Content-Length: {}\r\nConnection: close\r\n\r\n",
+            head.len(),
+            body.len() - 1,
+            body.len(),
+            tail.len()
+        )
+        .into_bytes();
+        rest.extend_from_slice(tail);
+
+        let (url, server) = serve(vec![unavailable, truncated, rest]);
+        let stats = DownloadStats::default();
+        let bytes = download_tarball_with_retry(
+            &Client::new(),
+            &url,
+            MAX_TARBALL_SIZE,
+            None,
+            &fast_retries(),
+            Some(&stats),
+        )
+        .await
+        .unwrap();
+
+        assert_eq!(bytes.as_ref(), body.as_slice());
+        assert_eq!(stats.retries(), 2);
+        assert_eq!(stats.resumed(), 1);
+
+        let requests = server.join().unwrap();
+        assert!(!requests[1].iter().any(|h| h.starts_with("range:")));
+        assert!(requests[2].contains(&format!("range: bytes={}-", head.len())));
+        assert!(requests[2].contains(&"if-range: \"t1\"".to_string()));
+    }
+
+    #[tokio::test]
+    async fn test_download_does_not_retry_client_errors() {
+        let not_found =
+            b"HTTP/1.1 404 Not Found\r\nContent-Length: 0\r\nConnection: close\r\n\r\n".to_vec();
+        let (url, server) = serve(vec![not_found]);
+        let stats = DownloadStats::default();
+        let result = download_tarball_with_retry(
+            &Client::new(),
+            &url,
+            MAX_TARBALL_SIZE,
+            None,
+            &fast_retries(),
+            Some(&stats),
+        )
+        .await;
+
+        assert!(result.unwrap_err().message().contains("404"));
+        assert_eq!(stats.retries(), 0);
+        assert_eq!(server.join().unwrap().len(), 1);
+    }
+
+    #[test]
+    fn test_backoff_is_capped_with_jitter() {
+        let policy = RetryPolicy {
+            retries: 10,
+            min_backoff: Duration::from_millis(100),
+            max_backoff: Duration::from_secs(1),
+        };
+        let first = policy.backoff(0);
+        assert!(first >= Duration::from_millis(50) && first <= Duration::from_millis(100));
+        let late = policy.backoff(40);
+        assert!(late >= Duration::from_millis(500) && late <= Duration::from_secs(1));
+    }
 }
@@ -1,49 +1,40 @@
-//! Version resolution using semver.
-
-#![allow(clippy::manual_let_else)]
-#![allow(clippy::redundant_else)]
+//! Version resolution against packuments.
 
 use super::error::PkgError;
+use super::range::{parse_version, VersionRange};
 use super::registry::{get_latest_version, get_versions};
-use semver::{Version, VersionReq};
+use semver::Version;
 
 /// Check whether a concrete version satisfies an npm version range.
 ///
-/// Handles OR ranges (`^1.0.0 || ^2.0.0`), hyphen ranges, x-ranges, and all
-/// other npm-specific syntax supported by [`parse_range`].
+/// Handles OR ranges (`^1.0.0 || ^2.0.0`), hyphen ranges, x-ranges,
+/// prereleases, and all other npm syntax supported by [`VersionRange`].
+#[must_use]
 pub fn version_satisfies(version: &str, range: &str) -> bool {
-    let ver = match Version::parse(version) {
-        Ok(v) => v,
-        Err(_) => return false,
+    let Some(version) = parse_version(version) else {
+        return false;
     };
+    VersionRange::parse(range).is_ok_and(|range| range.satisfies(&version))
+}
 
-    if range.contains("||") {
-        range
-            .split("||")
-            .map(str::trim)
-            .filter(|s| !s.is_empty())
-            .any(|alt| {
-                parse_range(alt)
-                    .map(|req| req.matches(&ver))
-                    .unwrap_or(false)
-            })
-    } else {
-        parse_range(range)
-            .map(|req| req.matches(&ver))
-            .unwrap_or(false)
-    }
+/// Check whether `range` is a valid npm version range (as opposed to a
+/// dist-tag like `next`).
+#[must_use]
+pub fn is_valid_range(range: &str) -> bool {
+    VersionRange::parse(range).is_ok()
 }
 
 /// Resolve a version range against a packument.
 ///
 /// # Rules
 /// - If `range` is `None`, returns `dist-tags.latest`
-/// - If `range` is an exact version, returns it if present
-/// - If `range` is a semver range, returns the highest satisfying version
+/// - If `range` is a dist-tag (`next`, `beta`), returns the tagged version
+/// - If `dist-tags.latest` satisfies the range, returns it (as npm does)
+/// - Otherwise returns the highest satisfying version
 /// - Supports OR ranges like `^1.0.0 || ^2.0.0`
 ///
 /// # Errors
-/// Returns an error if no version satisfies the range.
+/// Returns an error if the range is invalid or no version satisfies it.
 pub fn resolve_version(
     packument: &serde_json::Value,
     range: Option<&str>,
@@ -53,239 +44,44 @@ pub fn resolve_version(
         .and_then(|v| v.as_str())
         .unwrap_or("unknown");
 
-    match range {
-        None => {
-            // Use dist-tags.latest
-            get_latest_version(packument)
-                .map(String::from)
-                .ok_or_else(|| {
-                    PkgError::version_not_found(name, "latest (no dist-tags.latest found)")
-                })
-        }
-        Some(range) => {
-            // Try to parse as exact version first
-            if let Ok(exact) = Version::parse(range) {
-                let versions = get_versions(packument);
-                if versions.contains(&range) {
-                    return Ok(range.to_string());
-                }
-                // If exact version not found, try as range below
-                let _ = exact; // suppress unused warning
-            }
-
-            // Collect and parse all versions (needed for matching)
-            let versions = get_versions(packument);
-            let mut parsed: Vec<Version> = versions
-                .iter()
-                .filter_map(|v| Version::parse(v).ok())
-                .collect();
-
-            // Sort descending to get highest first
-            parsed.sort_by(|a, b| b.cmp(a));
-
-            // Handle OR ranges (e.g., "^1.0.0 || ^2.0.0")
-            if range.contains("||") {
-                return resolve_or_range(name, range, &parsed);
-            }
-
-            // Parse as single semver range
-            let req = parse_range(range)?;
-
-            // Find first (highest) matching version
-            for version in &parsed {
-                if req.matches(version) {
-                    return Ok(version.to_string());
-                }
-            }
-
-            Err(PkgError::version_not_found(name, range))
-        }
-    }
-}
-
-/// Resolve an OR range like "^1.0.0 || ^2.0.0".
-///
-/// Returns the highest version matching any of the alternatives.
-fn resolve_or_range(name: &str, range: &str, versions: &[Version]) -> Result<String, PkgError> {
-    // Split by || and parse each alternative
-    let alternatives: Vec<&str> = range.split("||").map(str::trim).collect();
-
-    let mut reqs: Vec<VersionReq> = Vec::new();
-    for alt in &alternatives {
-        if alt.is_empty() {
-            continue;
-        }
-        if let Ok(req) = parse_range(alt) {
-            reqs.push(req);
-        }
-        // Skip invalid alternatives, try others
-    }
-
-    if reqs.is_empty() {
-        return Err(PkgError::spec_invalid(format!(
-            "Invalid version range '{range}': no valid alternatives"
-        )));
-    }
-
-    // Find highest version matching any alternative
-    for version in versions {
-        for req in &reqs {
-            if req.matches(version) {
-                return Ok(version.to_string());
-            }
-        }
-    }
-
-    Err(PkgError::version_not_found(name, range))
-}
-
-/// Parse a single version range, handling npm-specific syntax.
-///
-/// Handles:
-/// - Standard semver ranges: ^1.0.0, ~1.0.0, >=1.0.0, etc.
-/// - Hyphen ranges: 1.0.0 - 2.0.0
-/// - X-ranges: 1.x, 1.0.x, *
-/// - Space-separated comparators: >= 2.1.2 < 3.0.0
-fn parse_range(range: &str) -> Result<VersionReq, PkgError> {
-    let range = range.trim();
-
-    // Handle hyphen ranges: "1.0.0 - 2.0.0" -> ">=1.0.0, <=2.0.0"
-    if let Some((start, end)) = parse_hyphen_range(range) {
-        let converted = format!(">={start}, <={end}");
-        return VersionReq::parse(&converted)
-            .map_err(|e| PkgError::spec_invalid(format!("Invalid version range '{range}': {e}")));
-    }
-
-    // Handle x-ranges: "1.x" -> ">=1.0.0, <2.0.0"
-    if range.contains('x') || range.contains('X') || range == "*" {
-        let converted = convert_x_range(range);
-        return VersionReq::parse(&converted)
-            .map_err(|e| PkgError::spec_invalid(format!("Invalid version range '{range}': {e}")));
-    }
-
-    // Handle space-separated comparators: ">= 2.1.2 < 3.0.0" -> ">=2.1.2, <3.0.0"
-    // npm allows spaces between comparators to mean AND
-    let converted = convert_space_separated_comparators(range);
-
-    // Standard semver range
-    VersionReq::parse(&converted)
-        .map_err(|e| PkgError::spec_invalid(format!("Invalid version range '{range}': {e}")))
-}
-
-/// Parse a hyphen range like "1.0.0 - 2.0.0".
-fn parse_hyphen_range(range: &str) -> Option<(String, String)> {
-    // Look for " - " pattern (space-hyphen-space)
-    let parts: Vec<&str> = range.split(" - ").collect();
-    if parts.len() == 2 {
-        let start = parts[0].trim();
-        let end = parts[1].trim();
-        // Validate both look like versions
-        if !start.is_empty() && !end.is_empty() {
-            return Some((start.to_string(), end.to_string()));
-        }
-    }
-    None
-}
+    let Some(range) = range else {
+        return get_latest_version(packument)
+            .map(String::from)
+            .ok_or_else(|| {
+                PkgError::version_not_found(name, "latest (no dist-tags.latest found)")
+            });
+    };
 
-/// Convert space-separated comparators to comma-separated.
-///
-/// npm allows: ">= 2.1.2 < 3.0.0" which means ">=2.1.2 AND <3.0.0"
-/// Rust semver requires: ">=2.1.2, <3.0.0"
-fn convert_space_separated_comparators(range: &str) -> String {
-    let range = range.trim();
-
-    // Regex-like parsing: split on spaces, but keep operators attached to versions
-    // Comparator patterns: >=, <=, >, <, =, ~, ^, or bare version
-    let mut result = String::new();
-    let chars = range.chars().peekable();
-    let mut current_token = String::new();
-    let mut need_comma = false;
-
-    for c in chars {
-        match c {
-            ' ' => {
-                // End of current token
-                if !current_token.is_empty() {
-                    let trimmed = current_token.trim();
-                    if !trimmed.is_empty() {
-                        // Check if this looks like a complete comparator (has version number)
-                        if token_has_version(trimmed) {
-                            if need_comma {
-                                result.push_str(", ");
-                            }
-                            result.push_str(trimmed);
-                            need_comma = true;
-                        } else {
-                            // Operator without version, keep accumulating
-                            if need_comma {
-                                result.push_str(", ");
-                                need_comma = false;
-                            }
-                            result.push_str(trimmed);
-                        }
-                    }
-                    current_token.clear();
-                }
-            }
-            _ => {
-                current_token.push(c);
-            }
+    let req = match VersionRange::parse(range) {
+        Ok(req) => req,
+        Err(e) => {
+            return get_dist_tag(packument, range.trim())
+                .map(String::from)
+                .ok_or(e);
         }
-    }
+    };
 
-    // Handle last token
-    if !current_token.is_empty() {
-        let trimmed = current_token.trim();
-        if !trimmed.is_empty() {
-            if token_has_version(trimmed) && need_comma {
-                result.push_str(", ");
-            }
-            result.push_str(trimmed);
+    let available = get_versions(packument);
+    if let Some(latest) = get_latest_version(packument) {
+        let tagged =
+            available.contains(&latest) && parse_version(latest).is_some_and(|v| req.satisfies(&v));
+        if tagged {
+            return Ok(latest.to_string());
         }
     }
 
-    // If nothing was parsed (no spaces), return original
-    if result.is_empty() {
-        return range.to_string();
-    }
-
-    result
+    let versions: Vec<Version> = available
+        .into_iter()
+        .filter_map(|v| Version::parse(v).ok())
+        .collect();
+    req.max_satisfying(&versions)
+        .map(ToString::to_string)
+        .ok_or_else(|| PkgError::version_not_found(name, range))
 }
 
-/// Check if a token contains a version number (has digits).
-fn token_has_version(token: &str) -> bool {
-    token.chars().any(|c| c.is_ascii_digit())
-}
-
-/// Convert x-range to semver range.
-fn convert_x_range(range: &str) -> String {
-    let range = range.trim();
-
-    if range == "*" || range == "x" || range == "X" {
-        return ">=0.0.0".to_string();
-    }
-
-    // Replace x/X with 0 for parsing, then convert to appropriate range
-    let parts: Vec<&str> = range.split('.').collect();
-
-    match parts.as_slice() {
-        [major, "x" | "X" | "*"] => {
-            // "1.x" -> ">=1.0.0, <2.0.0"
-            if let Ok(m) = major.parse::<u64>() {
-                return format!(">={m}.0.0, <{}.0.0", m + 1);
-            }
-        }
-        [major, minor, "x" | "X" | "*"] => {
-            // "1.2.x" -> ">=1.2.0, <1.3.0"
-            if let (Ok(m), Ok(n)) = (major.parse::<u64>(), minor.parse::<u64>()) {
-                return format!(">={m}.{n}.0, <{m}.{}.0", n + 1);
-            }
-        }
-        _ => {}
-    }
-
-    // Fallback: just replace x with 0
-    range.replace(['x', 'X'], "0")
+/// Version a dist-tag points to.
+fn get_dist_tag<'a>(packument: &'a serde_json::Value, tag: &str) -> Option<&'a str> {
+    packument.get("dist-tags")?.get(tag)?.as_str()
 }
 
 #[cfg(test)]
@@ -470,4 +266,47 @@ mod tests {
         let version = resolve_version(&packument, Some(">= 2.1.2 < 3.0.0")).unwrap();
         assert_eq!(version, "2.1.2");
     }
+
+    #[test]
+    fn test_resolve_dist_tag() {
+        let mut packument = make_packument(&["1.0.0", "2.0.0-rc.1"], "1.0.0");
+        packument["dist-tags"]["next"] = serde_json::json!("2.0.0-rc.1");
+        assert_eq!(
+            resolve_version(&packument, Some("next")).unwrap(),
+            "2.0.0-rc.1"
+        );
+        assert!(resolve_version(&packument, Some("canary")).is_err());
+    }
+
+    #[test]
+    fn test_resolve_prefers_latest_tag() {
+        // A backport line published after 2.x stays `latest`
+        let packument = make_packument(&["1.0.0", "1.1.0", "2.0.0"], "1.1.0");
+        assert_eq!(resolve_version(&packument, Some("*")).unwrap(), "1.1.0");
+        assert_eq!(resolve_version(&packument, Some(">=1")).unwrap(), "1.1.0");
+        assert_eq!(resolve_version(&packument, Some("^2")).unwrap(), "2.0.0");
+    }
+
+    #[test]
+    fn test_resolve_exact_prerelease_and_x_range() {
+        let packument = make_packument(&["1.2.0", "1.2.5", "1.3.0-beta.1", "1.3.0"], "1.3.0");
+        assert_eq!(
+            resolve_version(&packument, Some("1.3.0-beta.1")).unwrap(),
+            "1.3.0-beta.1"
+        );
+        assert_eq!(resolve_version(&packument, Some("1.2.x")).unwrap(), "1.2.5");
+        assert_eq!(
+            resolve_version(&packument, Some("~1.2 || ^0.1")).unwrap(),
+            "1.2.5"
+        );
+    }
+
+    #[test]
+    fn test_version_satisfies() {
+        assert!(version_satisfies("v20.11.0", ">=18 <21"));
+        assert!(version_satisfies("1.2.3", "1.2.3"));
+        assert!(!version_satisfies("1.2.4", "1.2.3"));
+        assert!(!version_satisfies("2.0.0-beta.1", "^1 || ^2"));
+        assert!(!version_satisfies("1.0.0", "not-a-range!!!"));
+    }
 }
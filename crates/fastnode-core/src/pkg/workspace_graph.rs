@@ -0,0 +1,322 @@
+//! Dependency graph between workspace packages.
+//!
+//! Edges come from the `dependencies`, `devDependencies`,
+//! `optionalDependencies` and `peerDependencies` of each workspace package
+//! that name another workspace package. Used to select packages for
+//! `--filter`/`--since` and to run them in dependency order.
+
+use super::workspaces::{WorkspaceConfig, WorkspacePackage};
+use serde_json::Value;
+use std::collections::{BTreeMap, BTreeSet};
+use std::path::{Path, PathBuf};
+
+/// Dependency fields that create edges between workspace packages.
+const DEP_FIELDS: [&str; 4] = [
+    "dependencies",
+    "devDependencies",
+    "optionalDependencies",
+    "peerDependencies",
+];
+
+/// Workspace packages and the dependencies between them.
+#[derive(Debug, Clone)]
+pub struct WorkspaceGraph {
+    /// Packages by name.
+    packages: BTreeMap<String, WorkspacePackage>,
+    /// Package name -> workspace packages it depends on.
+    dependencies: BTreeMap<String, BTreeSet<String>>,
+}
+
+impl WorkspaceGraph {
+    /// Build the graph by reading each workspace package's `package.json`.
+    #[must_use]
+    pub fn from_config(config: &WorkspaceConfig) -> Self {
+        let packages: BTreeMap<String, WorkspacePackage> = config
+            .packages
+            .iter()
+            .map(|(name, pkg)| (name.clone(), pkg.clone()))
+            .collect();
+
+        let dependencies = packages
+            .iter()
+            .map(|(name, pkg)| {
+                let deps = read_dep_names(&pkg.path)
+                    .into_iter()
+                    .filter(|dep| dep != name && packages.contains_key(dep))
+                    .collect();
+                (name.clone(), deps)
+            })
+            .collect();
+
+        Self {
+            packages,
+            dependencies,
+        }
+    }
+
+    /// Get a workspace package by name.
+    #[must_use]
+    pub fn package(&self, name: &str) -> Option<&WorkspacePackage> {
+        self.packages.get(name)
+    }
+
+    /// Names of the workspace packages `name` depends on directly.
+    pub fn dependencies(&self, name: &str) -> impl Iterator<Item = &str> {
+        self.dependencies
+            .get(name)
+            .into_iter()
+            .flatten()
+            .map(String::as_str)
+    }
+
+    /// Names of the workspace packages that depend on `name` directly.
+    pub fn dependents<'a>(&'a self, name: &'a str) -> impl Iterator<Item = &'a str> {
+        self.dependencies
+            .iter()
+            .filter(move |(_, deps)| deps.contains(name))
+            .map(|(dependent, _)| dependent.as_str())
+    }
+
+    /// `names` plus everything they depend on, transitively.
+    #[must_use]
+    pub fn with_dependencies(&self, names: &BTreeSet<String>) -> BTreeSet<String> {
+        closure(names, |name| {
+            self.dependencies(name).map(str::to_string).collect()
+        })
+    }
+
+    /// `names` plus everything that depends on them, transitively.
+    #[must_use]
+    pub fn with_dependents(&self, names: &BTreeSet<String>) -> BTreeSet<String> {
+        closure(names, |name| {
+            self.dependents(name).map(str::to_string).collect()
+        })
+    }
+
+    /// Select packages matching a filter.
+    ///
+    /// A filter is a package name or glob (`@scope/*`). `name...` also selects
+    /// the package's dependencies and `...name` its dependents.
+    #[must_use]
+    pub fn filter(&self, filter: &str) -> BTreeSet<String> {
+        let (with_dependents, rest) = match filter.strip_prefix("...") {
+            Some(rest) => (true, rest),
+            None => (false, filter),
+        };
+        let (with_dependencies, pattern) = match rest.strip_suffix("...") {
+            Some(pattern) => (true, pattern),
+            None => (false, rest),
+        };
+
+        let matched: BTreeSet<String> = match glob::Pattern::new(pattern) {
+            Ok(glob) => self
+                .packages
+                .keys()
+                .filter(|name| glob.matches(name))
+                .cloned()
+                .collect(),
+            Err(_) => self
+                .packages
+                .keys()
+                .filter(|name| *name == pattern)
+                .cloned()
+                .collect(),
+        };
+
+        let mut selected = matched.clone();
+        if with_dependencies {
+            selected.extend(self.with_dependencies(&matched));
+        }
+        if with_dependents {
+            selected.extend(self.with_dependents(&matched));
+        }
+        selected
+    }
+
+    /// Packages containing any of `files` (absolute paths).
+    ///
+    /// Each file belongs to the package with the longest matching path, so
+    /// nested workspace packages are attributed correctly.
+    #[must_use]
+    pub fn packages_containing(&self, files: &[PathBuf]) -> BTreeSet<String> {
+        files
+            .iter()
+            .filter_map(|file| {
+                self.packages
+                    .values()
+                    .filter(|pkg| file.starts_with(&pkg.path))
+                    .max_by_key(|pkg| pkg.path.components().count())
+                    .map(|pkg| pkg.name.clone())
+            })
+            .collect()
+    }
+
+    /// Order `names` so that dependencies come before their dependents.
+    ///
+    /// Ties are broken by name; cycles are broken by name as well so every
+    /// package is still returned exactly once.
+    #[must_use]
+    pub fn topo_order(&self, names: &BTreeSet<String>) -> Vec<&WorkspacePackage> {
+        let mut order = Vec::with_capacity(names.len());
+        let mut done: BTreeSet<&str> = BTreeSet::new();
+        let mut remaining: Vec<&str> = names
+            .iter()
+            .map(String::as_str)
+            .filter(|name| self.packages.contains_key(*name))
+            .collect();
+
+        while !remaining.is_empty() {
+            // Packages whose selected dependencies are all done
+            let ready = remaining
+                .iter()
+                .position(|name| {
+                    self.dependencies(name)
+                        .all(|dep| done.contains(dep) || !names.contains(dep))
+                })
+                .unwrap_or(0); // Cycle: take the first remaining package
+            let name = remaining.remove(ready);
+            done.insert(name);
+            order.push(&self.packages[name]);
+        }
+        order
+    }
+}
+
+/// `names` plus everything reachable from them through `next`.
+fn closure(names: &BTreeSet<String>, next: impl Fn(&str) -> Vec<String>) -> BTreeSet<String> {
+    let mut seen = names.clone();
+    let mut stack: Vec<String> = names.iter().cloned().collect();
+    while let Some(name) = stack.pop() {
+        for other in next(&name) {
+            if seen.insert(other.clone()) {
+                stack.push(other);
+            }
+        }
+    }
+    seen
+}
+
+/// Read the names of every dependency declared by a package.
+fn read_dep_names(dir: &Path) -> BTreeSet<String> {
+    let Some(package) = std::fs::read_to_string(dir.join("package.json"))
+        .ok()
+        .and_then(|content| serde_json::from_str::<Value>(&content).ok())
+    else {
+        return BTreeSet::new();
+    };
+
+    DEP_FIELDS
+        .iter()
+        .filter_map(|field| package.get(field)?.as_object())
+        .flat_map(|deps| deps.keys().cloned())
+        .collect()
+}
+
+/// Select workspace packages for `--filter`/`--since`.
+///
+/// With no filters every package is a candidate. With `changed` files (from
+/// `--since`), only candidates that changed or depend on a changed package
+/// are kept. Returned in dependency order.
+#[must_use]
+pub fn select_workspace_packages<'a>(
+    graph: &'a WorkspaceGraph,
+    filters: &[String],
+    changed: Option<&[PathBuf]>,
+) -> Vec<&'a WorkspacePackage> {
+    let mut selected: BTreeSet<String> = if filters.is_empty() {
+        graph.packages.keys().cloned().collect()
+    } else {
+        filters.iter().flat_map(|f| graph.filter(f)).collect()
+    };
+
+    if let Some(files) = changed {
+        let affected = graph.with_dependents(&graph.packages_containing(files));
+        selected.retain(|name| affected.contains(name));
+    }
+
+    graph.topo_order(&selected)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::collections::HashMap;
+    use std::fs;
+
+    /// Workspace with `app -> ui -> utils` and an unrelated `docs`.
+    fn setup() -> (tempfile::TempDir, WorkspaceGraph) {
+        let dir = tempfile::tempdir().unwrap();
+        let mut packages = HashMap::new();
+        for (name, deps) in [
+            ("app", r#"{"dependencies":{"@acme/ui":"*","react":"^18"}}"#),
+            ("@acme/ui", r#"{"devDependencies":{"@acme/utils":"*"}}"#),
+            ("@acme/utils", "{}"),
+            ("docs", "{}"),
+        ] {
+            let path = dir.path().join(name.trim_start_matches("@acme/"));
+            fs::create_dir_all(&path).unwrap();
+            fs::write(path.join("package.json"), deps).unwrap();
+            packages.insert(
+                name.to_string(),
+                WorkspacePackage {
+                    name: name.to_string(),
+                    path,
+                    version: "1.0.0".to_string(),
+                },
+            );
+        }
+        let config = WorkspaceConfig {
+            root: dir.path().to_path_buf(),
+            packages,
+        };
+        let graph = WorkspaceGraph::from_config(&config);
+        (dir, graph)
+    }
+
+    fn names(packages: &[&WorkspacePackage]) -> Vec<String> {
+        packages.iter().map(|p| p.name.clone()).collect()
+    }
+
+    #[test]
+    fn test_topo_order_puts_dependencies_first() {
+        let (_dir, graph) = setup();
+        let selected = select_workspace_packages(&graph, &[], None);
+        assert_eq!(
+            names(&selected),
+            vec!["@acme/utils", "@acme/ui", "app", "docs"]
+        );
+    }
+
+    #[test]
+    fn test_filter_globs_and_closures() {
+        let (_dir, graph) = setup();
+        let set = |items: &[&str]| -> BTreeSet<String> {
+            items.iter().map(|s| (*s).to_string()).collect()
+        };
+
+        assert_eq!(graph.filter("@acme/*"), set(&["@acme/ui", "@acme/utils"]));
+        assert_eq!(
+            graph.filter("app..."),
+            set(&["app", "@acme/ui", "@acme/utils"])
+        );
+        assert_eq!(graph.filter("...@acme/ui"), set(&["app", "@acme/ui"]));
+        assert!(graph.filter("missing").is_empty());
+    }
+
+    #[test]
+    fn test_since_selects_changed_packages_and_dependents() {
+        let (dir, graph) = setup();
+        let changed = vec![
+            dir.path().join("utils/index.js"),
+            dir.path().join("README.md"),
+        ];
+
+        let selected = select_workspace_packages(&graph, &[], Some(&changed));
+        assert_eq!(names(&selected), vec!["@acme/utils", "@acme/ui", "app"]);
+
+        // Combined with a filter, only the intersection runs
+        let filters = vec!["@acme/*".to_string()];
+        let selected = select_workspace_packages(&graph, &filters, Some(&changed));
+        assert_eq!(names(&selected), vec!["@acme/utils", "@acme/ui"]);
+    }
+}
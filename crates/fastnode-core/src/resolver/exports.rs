@@ -4,7 +4,8 @@
 //! - Root exports (v1.1)
 //! - Subpath exports (v1.2)
 //! - Pattern exports with `*` wildcards (v1.2)
-//! - Conditional exports (import/require/default)
+//! - Conditional exports (import/require/default), plus user conditions and
+//!   a platform condition (see [`ExportConditions`])
 
 use serde_json::Value;
 
@@ -42,6 +43,90 @@ impl std::fmt::Display for ResolutionKind {
     }
 }
 
+/// Conditions matched against conditional exports and imports.
+///
+/// `package.json` objects are parsed into sorted maps, so their key order is
+/// not available. Conditions are tried in priority order instead: user
+/// conditions, the platform, the resolution kind's (`import` or `require`),
+/// then `default`.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct ExportConditions {
+    /// User conditions (e.g. `development`), highest priority first.
+    pub custom: Vec<String>,
+    /// Platform condition (`node` or `browser`).
+    pub platform: Option<String>,
+}
+
+impl ExportConditions {
+    /// Platforms accepted by [`ExportConditions::new`].
+    pub const PLATFORMS: &'static [&'static str] = &["node", "browser"];
+
+    /// Build conditions from user input, dropping blank and repeated ones.
+    ///
+    /// # Errors
+    /// Returns a message if `platform` is not one of [`Self::PLATFORMS`].
+    pub fn new(custom: &[String], platform: Option<&str>) -> Result<Self, String> {
+        let platform = match platform.map(str::to_lowercase) {
+            Some(p) if !Self::PLATFORMS.contains(&p.as_str()) => {
+                return Err(format!(
+                    "Invalid platform '{p}'. Expected one of: {}",
+                    Self::PLATFORMS.join(", ")
+                ));
+            }
+            platform => platform,
+        };
+        let mut conditions = Self {
+            custom: Vec::new(),
+            platform,
+        };
+        for condition in custom.iter().map(|c| c.trim()) {
+            if !condition.is_empty() && !conditions.custom.iter().any(|c| c == condition) {
+                conditions.custom.push(condition.to_string());
+            }
+        }
+        Ok(conditions)
+    }
+
+    /// Whether neither user conditions nor a platform were given.
+    #[must_use]
+    pub fn is_empty(&self) -> bool {
+        self.custom.is_empty() && self.platform.is_none()
+    }
+
+    /// The conditions tried for `kind`, in priority order.
+    #[must_use]
+    pub fn active(&self, kind: ResolutionKind) -> Vec<&str> {
+        let by_kind: &[&str] = match kind {
+            ResolutionKind::Import => &["import", "default"],
+            ResolutionKind::Require => &["require", "default"],
+            ResolutionKind::Unknown => &["default", "import", "require"],
+        };
+        let mut active: Vec<&str> = Vec::new();
+        for condition in self
+            .custom
+            .iter()
+            .map(String::as_str)
+            .chain(self.platform.as_deref())
+            .chain(by_kind.iter().copied())
+        {
+            if !active.contains(&condition) {
+                active.push(condition);
+            }
+        }
+        active
+    }
+}
+
+/// A target selected from an exports or imports map.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ExportsMatch {
+    /// Target path (starting with `./`).
+    pub target: String,
+    /// Conditions matched at each level of a conditional target, outermost
+    /// first. Empty for plain string targets.
+    pub conditions: Vec<String>,
+}
+
 /// Resolve exports for any subpath (unified entry point for v1.2).
 ///
 /// - If `subpath` is `None`, resolves root exports (equivalent to `resolve_exports_root`)
@@ -54,15 +139,28 @@ pub fn resolve_exports(
     subpath: Option<&str>,
     kind: ResolutionKind,
 ) -> Option<String> {
+    resolve_exports_with(pkg_json, subpath, kind, &ExportConditions::default()).map(|m| m.target)
+}
+
+/// Resolve exports like [`resolve_exports`], also matching `conditions`,
+/// and report which conditions selected the target.
+#[must_use]
+pub fn resolve_exports_with(
+    pkg_json: &Value,
+    subpath: Option<&str>,
+    kind: ResolutionKind,
+    conditions: &ExportConditions,
+) -> Option<ExportsMatch> {
+    let active = conditions.active(kind);
     match subpath {
-        None => resolve_exports_root(pkg_json, kind),
+        None => exports_root(pkg_json, &active),
         Some(sub) => {
             // First try exact subpath match
-            if let Some(target) = resolve_exports_subpath(pkg_json, sub, kind) {
+            if let Some(target) = exports_subpath(pkg_json, sub, &active) {
                 return Some(target);
             }
             // Then try pattern match
-            resolve_exports_pattern(pkg_json, sub, kind)
+            exports_pattern(pkg_json, sub, &active)
         }
     }
 }
@@ -79,11 +177,15 @@ pub fn resolve_exports(
 /// - `exports: { "import": "./esm.js", "require": "./cjs.js", "default": "./d.js" }` - root conditions
 #[must_use]
 pub fn resolve_exports_root(pkg_json: &Value, kind: ResolutionKind) -> Option<String> {
+    exports_root(pkg_json, &ExportConditions::default().active(kind)).map(|m| m.target)
+}
+
+fn exports_root(pkg_json: &Value, active: &[&str]) -> Option<ExportsMatch> {
     let exports = pkg_json.get("exports")?;
 
     // Case A: exports is a string
-    if let Some(s) = exports.as_str() {
-        return validate_export_path(s);
+    if exports.is_string() {
+        return resolve_export_target(exports, active);
     }
 
     // exports must be an object
@@ -91,13 +193,13 @@ pub fn resolve_exports_root(pkg_json: &Value, kind: ResolutionKind) -> Option<St
 
     // Case B: exports has "." key
     if let Some(dot) = obj.get(".") {
-        return resolve_export_target(dot, kind);
+        return resolve_export_target(dot, active);
     }
 
-    // Case D: exports is a conditions object at root level (import/require/default)
-    // Check if any key is a condition (not starting with ".")
-    if obj.contains_key("import") || obj.contains_key("require") || obj.contains_key("default") {
-        return resolve_conditions(exports, kind);
+    // Case D: exports is a conditions object at root level (no key starts
+    // with ".")
+    if !obj.is_empty() && !obj.keys().any(|k| k.starts_with('.')) {
+        return resolve_export_target(exports, active);
     }
 
     None
@@ -116,6 +218,10 @@ pub fn resolve_exports_subpath(
     subpath: &str,
     kind: ResolutionKind,
 ) -> Option<String> {
+    exports_subpath(pkg_json, subpath, &ExportConditions::default().active(kind)).map(|m| m.target)
+}
+
+fn exports_subpath(pkg_json: &Value, subpath: &str, active: &[&str]) -> Option<ExportsMatch> {
     // Subpath must start with "./"
     if !subpath.starts_with("./") {
         return None;
@@ -133,7 +239,7 @@ pub fn resolve_exports_subpath(
 
     // Look for exact subpath match
     let target = obj.get(subpath)?;
-    resolve_export_target(target, kind)
+    resolve_export_target(target, active)
 }
 
 /// Resolve a pattern export from package.json exports field.
@@ -151,6 +257,10 @@ pub fn resolve_exports_pattern(
     subpath: &str,
     kind: ResolutionKind,
 ) -> Option<String> {
+    exports_pattern(pkg_json, subpath, &ExportConditions::default().active(kind)).map(|m| m.target)
+}
+
+fn exports_pattern(pkg_json: &Value, subpath: &str, active: &[&str]) -> Option<ExportsMatch> {
     // Subpath must start with "./"
     if !subpath.starts_with("./") {
         return None;
@@ -198,10 +308,13 @@ pub fn resolve_exports_pattern(
     let (_, target_value, star_value) = &matches[0];
 
     // Resolve the target (may be string or conditions object)
-    let target_str = resolve_export_target(target_value, kind)?;
+    let selected = resolve_export_target(target_value, active)?;
 
     // Substitute * in target with the matched value
-    substitute_star(&target_str, star_value)
+    Some(ExportsMatch {
+        target: substitute_star(&selected.target, star_value)?,
+        conditions: selected.conditions,
+    })
 }
 
 /// Check if exports object has subpath keys (keys starting with "./").
@@ -272,58 +385,34 @@ fn substitute_star(target: &str, star_value: &str) -> Option<String> {
     Some(result)
 }
 
-/// Resolve an export target which can be a string or conditions object.
-fn resolve_export_target(target: &Value, kind: ResolutionKind) -> Option<String> {
-    // String target
-    if let Some(s) = target.as_str() {
-        return validate_export_path(s);
-    }
-
-    // Conditions object
-    resolve_conditions(target, kind)
+/// Resolve an export target which can be a string, a conditions object or
+/// an array of fallbacks.
+fn resolve_export_target(target: &Value, active: &[&str]) -> Option<ExportsMatch> {
+    let mut conditions = Vec::new();
+    let target = select_target(target, active, &mut conditions)?;
+    Some(ExportsMatch { target, conditions })
 }
 
-/// Resolve conditions object: { "import": ..., "require": ..., "default": ... }
-fn resolve_conditions(obj: &Value, kind: ResolutionKind) -> Option<String> {
-    let conditions = obj.as_object()?;
-
-    // Select condition based on resolution kind
-    let target = match kind {
-        ResolutionKind::Import => conditions
-            .get("import")
-            .or_else(|| conditions.get("default")),
-        ResolutionKind::Require => conditions
-            .get("require")
-            .or_else(|| conditions.get("default")),
-        ResolutionKind::Unknown => conditions
-            .get("default")
-            .or_else(|| conditions.get("import"))
-            .or_else(|| conditions.get("require")),
-    };
-
-    let target = target?;
-
-    // Target can be a string or nested conditions (we only support one level)
-    if let Some(s) = target.as_str() {
-        return validate_export_path(s);
-    }
-
-    // Try nested conditions (one more level)
-    if let Some(nested) = target.as_object() {
-        let nested_target = match kind {
-            ResolutionKind::Import => nested.get("import").or_else(|| nested.get("default")),
-            ResolutionKind::Require => nested.get("require").or_else(|| nested.get("default")),
-            ResolutionKind::Unknown => nested
-                .get("default")
-                .or_else(|| nested.get("import"))
-                .or_else(|| nested.get("require")),
-        };
-        if let Some(s) = nested_target.and_then(|v| v.as_str()) {
-            return validate_export_path(s);
-        }
+/// Follow `target` through nested conditions, recording the conditions
+/// taken in `matched`. A condition whose target doesn't resolve falls
+/// through to the next active one.
+fn select_target(target: &Value, active: &[&str], matched: &mut Vec<String>) -> Option<String> {
+    match target {
+        Value::String(s) => validate_export_path(s),
+        Value::Array(fallbacks) => fallbacks
+            .iter()
+            .find_map(|fallback| select_target(fallback, active, matched)),
+        Value::Object(conditions) => active.iter().find_map(|condition| {
+            let nested = conditions.get(*condition)?;
+            matched.push((*condition).to_string());
+            let found = select_target(nested, active, matched);
+            if found.is_none() {
+                matched.pop();
+            }
+            found
+        }),
+        _ => None,
     }
-
-    None
 }
 
 /// Validate that an export path starts with "./" as required by Node.
@@ -345,6 +434,18 @@ fn validate_export_path(path: &str) -> Option<String> {
 /// - `imports: { "#foo": { "import": "./esm.js", "require": "./cjs.js", "default": "./d.js" } }`
 #[must_use]
 pub fn resolve_imports_map(pkg_json: &Value, spec: &str, kind: ResolutionKind) -> Option<String> {
+    resolve_imports_map_with(pkg_json, spec, kind, &ExportConditions::default()).map(|m| m.target)
+}
+
+/// Resolve a #-prefixed import like [`resolve_imports_map`], also matching
+/// `conditions`, and report which conditions selected the target.
+#[must_use]
+pub fn resolve_imports_map_with(
+    pkg_json: &Value,
+    spec: &str,
+    kind: ResolutionKind,
+    conditions: &ExportConditions,
+) -> Option<ExportsMatch> {
     // Only handle #-prefixed specifiers
     if !spec.starts_with('#') {
         return None;
@@ -355,7 +456,7 @@ pub fn resolve_imports_map(pkg_json: &Value, spec: &str, kind: ResolutionKind) -
     // Exact match only (no pattern matching for v1.1)
     let target = imports.get(spec)?;
 
-    resolve_export_target(target, kind)
+    resolve_export_target(target, &conditions.active(kind))
 }
 
 /// Read and parse package.json, extracting relevant fields.
@@ -897,4 +998,92 @@ mod tests {
             Some("./dist/utils.js".to_string())
         );
     }
+
+    #[test]
+    fn test_export_conditions_new() {
+        let conditions = ExportConditions::new(
+            &["development".into(), " ".into(), "development".into()],
+            Some("Browser"),
+        )
+        .unwrap();
+        assert_eq!(conditions.custom, vec!["development"]);
+        assert_eq!(conditions.platform.as_deref(), Some("browser"));
+        assert_eq!(
+            conditions.active(ResolutionKind::Import),
+            ["development", "browser", "import", "default"]
+        );
+        assert!(ExportConditions::new(&[], Some("deno")).is_err());
+        assert!(ExportConditions::new(&[], None).unwrap().is_empty());
+    }
+
+    #[test]
+    fn test_resolve_exports_with_conditions() {
+        let pkg = json!({
+            "name": "test",
+            "exports": {
+                ".": {
+                    "browser": {
+                        "development": "./browser.dev.js",
+                        "import": "./browser.mjs"
+                    },
+                    "node": "./node.js",
+                    "default": "./index.js"
+                }
+            }
+        });
+        let resolve = |custom: &[String], platform| {
+            let conditions = ExportConditions::new(custom, platform).unwrap();
+            resolve_exports_with(&pkg, None, ResolutionKind::Import, &conditions).unwrap()
+        };
+
+        let matched = resolve(&[], None);
+        assert_eq!(matched.target, "./index.js");
+        assert_eq!(matched.conditions, ["default"]);
+
+        let matched = resolve(&[], Some("browser"));
+        assert_eq!(matched.target, "./browser.mjs");
+        assert_eq!(matched.conditions, ["browser", "import"]);
+
+        let matched = resolve(&["development".to_string()], Some("browser"));
+        assert_eq!(matched.target, "./browser.dev.js");
+        assert_eq!(matched.conditions, ["browser", "development"]);
+
+        assert_eq!(resolve(&[], Some("node")).target, "./node.js");
+    }
+
+    #[test]
+    fn test_unmatched_condition_falls_through() {
+        // "browser" has no target for require, so "default" is used
+        let pkg = json!({
+            "name": "test",
+            "exports": {
+                "browser": { "import": "./browser.mjs" },
+                "default": "./index.js"
+            }
+        });
+        let conditions = ExportConditions::new(&[], Some("browser")).unwrap();
+        let matched =
+            resolve_exports_with(&pkg, None, ResolutionKind::Require, &conditions).unwrap();
+        assert_eq!(matched.target, "./index.js");
+        assert_eq!(matched.conditions, ["default"]);
+    }
+
+    #[test]
+    fn test_resolve_imports_map_with_conditions() {
+        let pkg = json!({
+            "name": "test",
+            "imports": {
+                "#env": { "development": "./env.dev.js", "default": "./env.js" }
+            }
+        });
+        let conditions = ExportConditions::new(&["development".to_string()], None).unwrap();
+        assert_eq!(
+            resolve_imports_map(&pkg, "#env", ResolutionKind::Import),
+            Some("./env.js".to_string())
+        );
+        let matched =
+            resolve_imports_map_with(&pkg, "#env", ResolutionKind::Import, &conditions).unwrap();
+        assert_eq!(matched.target, "./env.dev.js");
+        assert_eq!(matched.conditions, ["development"]);
+    }
 }
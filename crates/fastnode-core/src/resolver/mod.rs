@@ -11,7 +11,8 @@ mod v0;
 
 pub use exports::{
     read_package_json, resolve_exports, resolve_exports_pattern, resolve_exports_root,
-    resolve_exports_subpath, resolve_imports_map, ResolutionKind,
+    resolve_exports_subpath, resolve_exports_with, resolve_imports_map, resolve_imports_map_with,
+    ExportConditions, ExportsMatch, ResolutionKind,
 };
 pub use pkg_json_cache::{CachedPkgJson, NoPkgJsonCache, PkgJsonCache, PkgJsonStamp};
 pub use trace::{
@@ -22,6 +22,9 @@ pub struct ResolveTraceStep {
     pub path: Option<PathBuf>,
     /// Export/import condition used (e.g., "import", "require", "default")
     pub condition: Option<String>,
+    /// Conditions matched at each level of a conditional target, outermost
+    /// first (e.g., `["browser", "import"]`)
+    pub conditions: Vec<String>,
     /// Package.json exports/imports key matched
     pub key: Option<String>,
     /// Target value from exports/imports map
@@ -39,6 +42,7 @@ impl ResolveTraceStep {
             detail: detail.into(),
             path: None,
             condition: None,
+            conditions: Vec::new(),
             key: None,
             target: None,
             notes: Vec::new(),
@@ -57,6 +61,21 @@ impl ResolveTraceStep {
         self
     }
 
+    /// Set the conditions matched by this step. The innermost one becomes
+    /// the step's condition; `fallback` is used when none matched (a plain
+    /// string target).
+    #[must_use]
+    pub fn with_matched_conditions(mut self, conditions: Vec<String>, fallback: &str) -> Self {
+        self.condition = Some(
+            conditions
+                .last()
+                .cloned()
+                .unwrap_or_else(|| fallback.to_string()),
+        );
+        self.conditions = conditions;
+        self
+    }
+
     /// Set the key for this step.
     pub fn with_key(mut self, key: impl Into<String>) -> Self {
         self.key = Some(key.into());
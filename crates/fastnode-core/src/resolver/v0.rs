@@ -11,8 +11,11 @@
 //! - v1.2: package.json exports subpath keys (`"./feature"`)
 //! - v1.2: package.json exports pattern keys (`"./*"`)
 
-use super::exports::{resolve_exports, resolve_exports_root, resolve_imports_map, ResolutionKind};
+use super::exports::{
+    resolve_exports_with, resolve_imports_map_with, ExportConditions, ResolutionKind,
+};
 use super::pkg_json_cache::PkgJsonCache;
+use fastnode_util::fs as fs_util;
 use serde_json::Value;
 use std::path::{Path, PathBuf};
 
@@ -27,12 +30,15 @@ const MAX_TRIED_PATHS: usize = 20;
 pub struct ResolverConfig {
     /// Extensions to probe (in order).
     pub extensions: &'static [&'static str],
+    /// Extra conditions for `exports`/`imports` (user conditions, platform).
+    pub conditions: ExportConditions,
 }
 
 impl Default for ResolverConfig {
     fn default() -> Self {
         Self {
             extensions: DEFAULT_EXTENSIONS,
+            conditions: ExportConditions::default(),
         }
     }
 }
@@ -352,9 +358,11 @@ fn resolve_hash_import(
             add_tried(tried, &pkg_json_path);
 
             if let Some(pkg_json) = read_pkg_json_cached(&pkg_json_path, ctx.pkg_json_cache) {
-                if let Some(target) = resolve_imports_map(&pkg_json, spec, kind) {
+                if let Some(matched) =
+                    resolve_imports_map_with(&pkg_json, spec, kind, &ctx.config.conditions)
+                {
                     // Target is relative to package root (dir)
-                    let target_path = dir.join(target.trim_start_matches("./"));
+                    let target_path = dir.join(matched.target.trim_start_matches("./"));
                     return resolve_path(ctx, &target_path, kind, tried);
                 }
             }
@@ -401,7 +409,7 @@ fn resolve_path(
 ) -> ResolveResult {
     // Try exact path first
     if base.is_file() {
-        let canonical = dunce::canonicalize(base).unwrap_or_else(|_| base.to_path_buf());
+        let canonical = fs_util::canonicalize(base).unwrap_or_else(|_| base.to_path_buf());
         return ResolveResult::resolved(canonical, tried.clone());
     }
 
@@ -416,7 +424,7 @@ fn resolve_path(
         add_tried(tried, &with_ext);
 
         if with_ext.is_file() {
-            let canonical = dunce::canonicalize(&with_ext).unwrap_or_else(|_| with_ext.clone());
+            let canonical = fs_util::canonicalize(&with_ext).unwrap_or_else(|_| with_ext.clone());
             return ResolveResult::resolved(canonical, tried.clone());
         }
     }
@@ -451,14 +459,17 @@ fn resolve_directory(
         // Read and parse package.json (with cache if available)
         if let Some(pkg_json) = read_pkg_json_cached(&pkg_json_path, ctx.pkg_json_cache) {
             // v1.1: Try exports field first (for root resolution only)
-            if let Some(exports_target) = resolve_exports_root(&pkg_json, kind) {
+            if let Some(exports_target) =
+                resolve_exports_with(&pkg_json, None, kind, &ctx.config.conditions)
+                    .map(|m| m.target)
+            {
                 let target_path = dir.join(exports_target.trim_start_matches("./"));
                 add_tried(tried, &target_path);
 
                 // Try exact path
                 if target_path.is_file() {
                     let canonical =
-                        dunce::canonicalize(&target_path).unwrap_or_else(|_| target_path.clone());
+                        fs_util::canonicalize(&target_path).unwrap_or_else(|_| target_path.clone());
                     return ResolveResult::resolved(canonical, tried.clone());
                 }
 
@@ -469,7 +480,7 @@ fn resolve_directory(
 
                     if with_ext.is_file() {
                         let canonical =
-                            dunce::canonicalize(&with_ext).unwrap_or_else(|_| with_ext.clone());
+                            fs_util::canonicalize(&with_ext).unwrap_or_else(|_| with_ext.clone());
                         return ResolveResult::resolved(canonical, tried.clone());
                     }
                 }
@@ -488,7 +499,7 @@ fn resolve_directory(
                 // Try exact main path
                 if main_path.is_file() {
                     let canonical =
-                        dunce::canonicalize(&main_path).unwrap_or_else(|_| main_path.clone());
+                        fs_util::canonicalize(&main_path).unwrap_or_else(|_| main_path.clone());
                     return ResolveResult::resolved(canonical, tried.clone());
                 }
 
@@ -499,7 +510,7 @@ fn resolve_directory(
 
                     if with_ext.is_file() {
                         let canonical =
-                            dunce::canonicalize(&with_ext).unwrap_or_else(|_| with_ext.clone());
+                            fs_util::canonicalize(&with_ext).unwrap_or_else(|_| with_ext.clone());
                         return ResolveResult::resolved(canonical, tried.clone());
                     }
                 }
@@ -512,7 +523,7 @@ fn resolve_directory(
 
                         if index.is_file() {
                             let canonical =
-                                dunce::canonicalize(&index).unwrap_or_else(|_| index.clone());
+                                fs_util::canonicalize(&index).unwrap_or_else(|_| index.clone());
                             return ResolveResult::resolved(canonical, tried.clone());
                         }
                     }
@@ -527,7 +538,7 @@ fn resolve_directory(
         add_tried(tried, &index);
 
         if index.is_file() {
-            let canonical = dunce::canonicalize(&index).unwrap_or_else(|_| index.clone());
+            let canonical = fs_util::canonicalize(&index).unwrap_or_else(|_| index.clone());
             return ResolveResult::resolved(canonical, tried.clone());
         }
     }
@@ -656,15 +667,20 @@ fn resolve_package_subpath(
                 let exports_subpath = format!("./{subpath}");
 
                 // v1.2: Try exports subpath/pattern resolution
-                if let Some(exports_target) =
-                    resolve_exports(&pkg_json, Some(&exports_subpath), kind)
+                if let Some(exports_target) = resolve_exports_with(
+                    &pkg_json,
+                    Some(&exports_subpath),
+                    kind,
+                    &ctx.config.conditions,
+                )
+                .map(|m| m.target)
                 {
                     let target_path = pkg_dir.join(exports_target.trim_start_matches("./"));
                     add_tried(tried, &target_path);
 
                     // Try exact path
                     if target_path.is_file() {
-                        let canonical = dunce::canonicalize(&target_path)
+                        let canonical = fs_util::canonicalize(&target_path)
                             .unwrap_or_else(|_| target_path.clone());
                         return ResolveResult::resolved(canonical, tried.clone());
                     }
@@ -675,8 +691,8 @@ fn resolve_package_subpath(
                         add_tried(tried, &with_ext);
 
                         if with_ext.is_file() {
-                            let canonical =
-                                dunce::canonicalize(&with_ext).unwrap_or_else(|_| with_ext.clone());
+                            let canonical = fs_util::canonicalize(&with_ext)
+                                .unwrap_or_else(|_| with_ext.clone());
                             return ResolveResult::resolved(canonical, tried.clone());
                         }
                     }
@@ -855,7 +871,9 @@ fn resolve_hash_import_traced(
                         "Package has imports field",
                     ));
 
-                    if let Some(target) = resolve_imports_map(&pkg_json, spec, kind) {
+                    if let Some(matched) =
+                        resolve_imports_map_with(&pkg_json, spec, kind, &ctx.config.conditions)
+                    {
                         trace.add_step(
                             ResolveTraceStep::new(
                                 steps::MATCH_IMPORTS_KEY,
@@ -863,12 +881,12 @@ fn resolve_hash_import_traced(
                                 format!("Matched imports key: {spec}"),
                             )
                             .with_key(spec)
-                            .with_target(&target)
-                            .with_condition(kind.to_string()),
+                            .with_target(&matched.target)
+                            .with_matched_conditions(matched.conditions, &kind.to_string()),
                         );
 
                         // Target is relative to package root
-                        let target_path = dir.join(target.trim_start_matches("./"));
+                        let target_path = dir.join(matched.target.trim_start_matches("./"));
                         return resolve_path_traced(ctx, &target_path, kind, tried, trace);
                     }
                     trace.add_step(ResolveTraceStep::new(
@@ -952,7 +970,7 @@ fn resolve_path_traced(
         trace.add_step(
             ResolveTraceStep::new(steps::FILE_EXISTS, true, "Exact file exists").with_path(base),
         );
-        let canonical = dunce::canonicalize(base).unwrap_or_else(|_| base.to_path_buf());
+        let canonical = fs_util::canonicalize(base).unwrap_or_else(|_| base.to_path_buf());
         trace.add_step(
             ResolveTraceStep::new(steps::FINAL_PATH, true, "Resolution complete")
                 .with_path(&canonical),
@@ -986,7 +1004,7 @@ fn resolve_path_traced(
                 )
                 .with_path(&with_ext),
             );
-            let canonical = dunce::canonicalize(&with_ext).unwrap_or_else(|_| with_ext.clone());
+            let canonical = fs_util::canonicalize(&with_ext).unwrap_or_else(|_| with_ext.clone());
             trace.add_step(
                 ResolveTraceStep::new(steps::FINAL_PATH, true, "Resolution complete")
                     .with_path(&canonical),
@@ -1033,7 +1051,9 @@ fn resolve_directory_traced(
 
         if let Some(pkg_json) = read_pkg_json_cached(&pkg_json_path, ctx.pkg_json_cache) {
             // Try exports field first
-            if let Some(exports_target) = resolve_exports_root(&pkg_json, kind) {
+            if let Some(matched) =
+                resolve_exports_with(&pkg_json, None, kind, &ctx.config.conditions)
+            {
                 trace.add_step(ResolveTraceStep::new(
                     steps::READ_EXPORTS_FIELD,
                     true,
@@ -1042,17 +1062,17 @@ fn resolve_directory_traced(
                 trace.add_step(
                     ResolveTraceStep::new(steps::MATCH_EXPORTS_KEY, true, "Matched root exports")
                         .with_key(".")
-                        .with_target(&exports_target)
-                        .with_condition(kind.to_string()),
+                        .with_target(&matched.target)
+                        .with_matched_conditions(matched.conditions, &kind.to_string()),
                 );
 
-                let target_path = dir.join(exports_target.trim_start_matches("./"));
+                let target_path = dir.join(matched.target.trim_start_matches("./"));
                 add_tried(tried, &target_path);
 
                 // Try exact path
                 if target_path.is_file() {
                     let canonical =
-                        dunce::canonicalize(&target_path).unwrap_or_else(|_| target_path.clone());
+                        fs_util::canonicalize(&target_path).unwrap_or_else(|_| target_path.clone());
                     trace.add_step(
                         ResolveTraceStep::new(
                             steps::FINAL_PATH,
@@ -1074,7 +1094,7 @@ fn resolve_directory_traced(
 
                     if with_ext.is_file() {
                         let canonical =
-                            dunce::canonicalize(&with_ext).unwrap_or_else(|_| with_ext.clone());
+                            fs_util::canonicalize(&with_ext).unwrap_or_else(|_| with_ext.clone());
                         trace.add_step(
                             ResolveTraceStep::new(
                                 steps::FINAL_PATH,
@@ -1131,7 +1151,7 @@ fn resolve_directory_traced(
                 // Try exact main path
                 if main_path.is_file() {
                     let canonical =
-                        dunce::canonicalize(&main_path).unwrap_or_else(|_| main_path.clone());
+                        fs_util::canonicalize(&main_path).unwrap_or_else(|_| main_path.clone());
                     trace.add_step(
                         ResolveTraceStep::new(
                             steps::FINAL_PATH,
@@ -1153,7 +1173,7 @@ fn resolve_directory_traced(
 
                     if with_ext.is_file() {
                         let canonical =
-                            dunce::canonicalize(&with_ext).unwrap_or_else(|_| with_ext.clone());
+                            fs_util::canonicalize(&with_ext).unwrap_or_else(|_| with_ext.clone());
                         trace.add_step(
                             ResolveTraceStep::new(
                                 steps::FINAL_PATH,
@@ -1177,7 +1197,7 @@ fn resolve_directory_traced(
 
                         if index.is_file() {
                             let canonical =
-                                dunce::canonicalize(&index).unwrap_or_else(|_| index.clone());
+                                fs_util::canonicalize(&index).unwrap_or_else(|_| index.clone());
                             trace.add_step(
                                 ResolveTraceStep::new(
                                     steps::FINAL_PATH,
@@ -1214,7 +1234,7 @@ fn resolve_directory_traced(
         add_tried(tried, &index);
 
         if index.is_file() {
-            let canonical = dunce::canonicalize(&index).unwrap_or_else(|_| index.clone());
+            let canonical = fs_util::canonicalize(&index).unwrap_or_else(|_| index.clone());
             trace.add_step(
                 ResolveTraceStep::new(
                     steps::FINAL_PATH,
@@ -1402,9 +1422,12 @@ fn resolve_package_subpath_traced(
                 // Convert subpath to exports format: "feature" -> "./feature"
                 let exports_subpath = format!("./{subpath}");
 
-                if let Some(exports_target) =
-                    resolve_exports(&pkg_json, Some(&exports_subpath), kind)
-                {
+                if let Some(matched) = resolve_exports_with(
+                    &pkg_json,
+                    Some(&exports_subpath),
+                    kind,
+                    &ctx.config.conditions,
+                ) {
                     trace.add_step(
                         ResolveTraceStep::new(
                             steps::MATCH_EXPORTS_KEY,
@@ -1412,16 +1435,16 @@ fn resolve_package_subpath_traced(
                             format!("Matched exports key: {exports_subpath}"),
                         )
                         .with_key(&exports_subpath)
-                        .with_target(&exports_target)
-                        .with_condition(kind.to_string()),
+                        .with_target(&matched.target)
+                        .with_matched_conditions(matched.conditions, &kind.to_string()),
                     );
 
-                    let target_path = pkg_dir.join(exports_target.trim_start_matches("./"));
+                    let target_path = pkg_dir.join(matched.target.trim_start_matches("./"));
                     add_tried(tried, &target_path);
 
                     // Try exact path
                     if target_path.is_file() {
-                        let canonical = dunce::canonicalize(&target_path)
+                        let canonical = fs_util::canonicalize(&target_path)
                             .unwrap_or_else(|_| target_path.clone());
                         trace.add_step(
                             ResolveTraceStep::new(
@@ -1443,8 +1466,8 @@ fn resolve_package_subpath_traced(
                         add_tried(tried, &with_ext);
 
                         if with_ext.is_file() {
-                            let canonical =
-                                dunce::canonicalize(&with_ext).unwrap_or_else(|_| with_ext.clone());
+                            let canonical = fs_util::canonicalize(&with_ext)
+                                .unwrap_or_else(|_| with_ext.clone());
                             trace.add_step(
                                 ResolveTraceStep::new(steps::FINAL_PATH, true, format!("Resolution complete via exports subpath with extension: {ext}"))
                                     .with_path(&canonical)
@@ -1553,6 +1576,52 @@ mod tests {
         assert!(resolved.to_string_lossy().ends_with("dep.ts"));
     }
 
+    #[test]
+    fn test_is_absolute_path() {
+        for spec in [
+            "/usr/lib/a.js",
+            r"C:\proj\a.js",
+            "C:/proj/a.js",
+            r"\\server\share\a.js",
+            r"\\?\C:\proj\a.js",
+        ] {
+            assert!(is_absolute_path(spec), "{spec}");
+        }
+        for spec in ["./a.js", "C:a.js", "lodash", "@scope/pkg"] {
+            assert!(!is_absolute_path(spec), "{spec}");
+        }
+    }
+
+    #[cfg(windows)]
+    #[test]
+    fn test_resolve_beyond_max_path() {
+        let dir = tempdir().unwrap();
+        let root = fastnode_util::fs::canonicalize(dir.path()).unwrap();
+        // A dependency chain nested well past MAX_PATH
+        let pkg_dir = (0..12).fold(root.clone(), |path, i| {
+            path.join("node_modules").join(format!("dependency-{i:02}"))
+        });
+        let long = fastnode_util::fs::long_path(&pkg_dir);
+        fs::create_dir_all(&long).unwrap();
+        fs::write(long.join("index.js"), "").unwrap();
+        assert!(pkg_dir.as_os_str().len() > 260);
+
+        let config = ResolverConfig::default();
+        let ctx = ResolveContext {
+            cwd: root.clone(),
+            parent: pkg_dir.clone(),
+            channel: "stable".to_string(),
+            config: &config,
+            pkg_json_cache: None,
+        };
+        let result = resolve_v0(&ctx, "./index.js");
+        assert_eq!(result.status, ResolveStatus::Resolved);
+        // Spelled like the project root, not as a `\\?\` path
+        let resolved = result.resolved.unwrap();
+        assert!(resolved.starts_with(&root), "{}", resolved.display());
+        assert_eq!(resolved, pkg_dir.join("index.js"));
+    }
+
     #[test]
     fn test_relative_not_found() {
         let dir = tempdir().unwrap();
@@ -2541,6 +2610,57 @@ mod tests {
         assert!(step_names.contains(&"final_path"));
     }
 
+    #[test]
+    fn test_resolve_with_trace_conditions() {
+        let dir = tempdir().unwrap();
+        let pkg = dir.path().join("node_modules/cond-pkg");
+        fs::create_dir_all(&pkg).unwrap();
+        fs::write(
+            pkg.join("package.json"),
+            r#"{"name": "cond-pkg", "exports": { ".": { "browser": { "import": "./browser.js" }, "default": "./index.js" } }}"#,
+        )
+        .unwrap();
+        fs::write(pkg.join("browser.js"), "").unwrap();
+        fs::write(pkg.join("index.js"), "").unwrap();
+
+        let config = ResolverConfig::default();
+        let browser = ResolverConfig {
+            conditions: ExportConditions::new(&[], Some("browser")).unwrap(),
+            ..ResolverConfig::default()
+        };
+        let ctx = |config| ResolveContext {
+            cwd: dir.path().to_path_buf(),
+            parent: dir.path().to_path_buf(),
+            channel: "stable".to_string(),
+            config,
+            pkg_json_cache: None,
+        };
+        let matched_step = |traced: &ResolveTrace| {
+            traced
+                .steps
+                .iter()
+                .find(|s| s.step == steps::MATCH_EXPORTS_KEY)
+                .cloned()
+                .unwrap()
+        };
+
+        let traced = resolve_with_trace(&ctx(&config), "cond-pkg", ResolutionKind::Import);
+        assert!(traced.result.resolved.unwrap().ends_with("index.js"));
+        let step = matched_step(&traced.trace);
+        assert_eq!(step.conditions, ["default"]);
+        assert_eq!(step.condition.as_deref(), Some("default"));
+
+        let traced = resolve_with_trace(&ctx(&browser), "cond-pkg", ResolutionKind::Import);
+        assert!(traced.result.resolved.unwrap().ends_with("browser.js"));
+        let step = matched_step(&traced.trace);
+        assert_eq!(step.conditions, ["browser", "import"]);
+        assert_eq!(step.condition.as_deref(), Some("import"));
+
+        // The untraced resolver honors the same conditions
+        let result = resolve_v0(&ctx(&browser), "cond-pkg");
+        assert!(result.resolved.unwrap().ends_with("browser.js"));
+    }
+
     #[test]
     fn test_resolve_with_trace_unresolved() {
         let dir = tempdir().unwrap();
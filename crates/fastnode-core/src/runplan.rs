@@ -1,11 +1,19 @@
 //! Execution plan builder for `fastnode run`.
 //!
 //! This module validates and resolves entry points without executing them.
+//! With [`RunPlanInput::graph_depth`] set it also walks the entry's local
+//! import graph and summarizes it (see [`analyze_import_graph`]).
+//!
+//! As in `howth run`, an entry naming a `package.json` script runs that
+//! script even if a file of the same name exists. The plan then describes
+//! the script (see [`ScriptPlanOutput`]) instead of a file.
 
 use crate::config::Channel;
 use crate::imports::ImportSpecCore;
 use crate::resolver::{ResolveContext, ResolveResult, ResolverConfig};
+use crate::scripts::{self, PackageScripts, ScriptRunOptions, ScriptShell};
 use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, HashMap, HashSet, VecDeque};
 use std::path::{Path, PathBuf};
 use thiserror::Error;
 
@@ -15,6 +23,15 @@ pub const RUNPLAN_SCHEMA_VERSION: u32 = 2;
 /// Resolver schema version.
 pub const RESOLVER_SCHEMA_VERSION: u32 = 1;
 
+/// `graph_depth` value that walks the whole import graph.
+pub const FULL_GRAPH_DEPTH: u32 = u32::MAX;
+
+/// Maximum number of modules visited by an import graph walk.
+pub const MAX_GRAPH_FILES: usize = 5_000;
+
+/// Unresolved import sites kept per reason in a graph summary.
+const MAX_UNRESOLVED_SITES: usize = 20;
+
 /// Stable error codes for run plan errors.
 pub mod codes {
     pub const ENTRY_NOT_FOUND: &str = "ENTRY_NOT_FOUND";
@@ -34,6 +51,9 @@ pub struct RunPlanInput {
     pub args: Vec<String>,
     /// Channel (dev, stable, nightly).
     pub channel: Channel,
+    /// Walk transitive imports up to this depth (`None` = direct imports
+    /// only, [`FULL_GRAPH_DEPTH`] = whole graph).
+    pub graph_depth: Option<u32>,
 }
 
 /// Discovered import from source.
@@ -168,7 +188,7 @@ pub struct RunPlanOutput {
     pub requested_entry: String,
     /// Canonicalized absolute path if entry exists.
     pub resolved_entry: Option<String>,
-    /// Entry kind: "file", "dir", "missing", or "unknown".
+    /// Entry kind: "file", "script", "dir", "missing", or "unknown".
     pub entry_kind: String,
     /// Arguments to pass to the script.
     pub args: Vec<String>,
@@ -182,6 +202,102 @@ pub struct RunPlanOutput {
     pub resolved_imports: Vec<ResolvedImportOutput>,
     /// Resolver configuration info.
     pub resolver: ResolverInfoOutput,
+    /// Transitive import graph summary (with `graph_depth`).
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub graph: Option<ImportGraphSummary>,
+    /// The `package.json` script run instead of a file (entry kind "script").
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub script: Option<ScriptPlanOutput>,
+}
+
+/// A `package.json` script chosen as the entry.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+pub struct ScriptPlanOutput {
+    /// Script name.
+    pub name: String,
+    /// The `package.json` defining the script.
+    pub package_json: String,
+    /// Script command as defined, without args.
+    pub command: String,
+    /// Steps in run order: `pre<name>`, `<name>` (with args appended) and
+    /// `post<name>`, hooks only when defined.
+    pub steps: Vec<ScriptStepOutput>,
+    /// `node_modules/.bin` directories prepended to `PATH`, nearest first.
+    pub bin_paths: Vec<String>,
+    /// Shell the steps run in.
+    pub shell: String,
+    /// File with the same name as the script, which is not run.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub shadowed_file: Option<String>,
+}
+
+/// One lifecycle step of a planned script.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+pub struct ScriptStepOutput {
+    /// Lifecycle event (e.g., `prebuild`, `build`).
+    pub event: String,
+    /// Shell command.
+    pub command: String,
+}
+
+/// Summary of an entry's transitive import graph.
+///
+/// Only local modules are walked; imports of packages and Node builtins are
+/// counted as external. Paths are relative to the working directory.
+#[derive(Debug, Clone, Default, Serialize, Deserialize, PartialEq, Eq)]
+pub struct ImportGraphSummary {
+    /// Depth limit of the walk (`None` = whole graph).
+    pub depth_limit: Option<u32>,
+    /// Deepest import level reached (the entry is level 0).
+    pub max_depth: u32,
+    /// Local modules visited, including the entry.
+    pub files: u32,
+    /// Import specifiers scanned across all visited modules.
+    pub imports: u32,
+    /// Imports resolved to local modules.
+    pub local: u32,
+    /// Imports of packages and Node builtins.
+    pub external: u32,
+    /// Unresolved imports grouped by reason code, sorted by reason.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub unresolved: Vec<UnresolvedImportGroup>,
+    /// Import cycles, each listed in import order starting from its
+    /// lexicographically first module.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub cycles: Vec<Vec<String>>,
+    /// Whether the walk stopped at [`MAX_GRAPH_FILES`].
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub truncated: bool,
+}
+
+impl ImportGraphSummary {
+    /// Total number of unresolved imports.
+    #[must_use]
+    pub fn unresolved_count(&self) -> u32 {
+        self.unresolved.iter().map(|g| g.count).sum()
+    }
+}
+
+/// Unresolved imports sharing a reason code.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+pub struct UnresolvedImportGroup {
+    /// Reason code (e.g., `NOT_FOUND`).
+    pub reason: String,
+    /// Number of unresolved imports with this reason.
+    pub count: u32,
+    /// First import sites with this reason.
+    pub sites: Vec<UnresolvedImportSite>,
+}
+
+/// Location of an unresolved import.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+pub struct UnresolvedImportSite {
+    /// Importing module.
+    pub from: String,
+    /// Specifier as written.
+    pub raw: String,
+    /// Line number (1-indexed, best-effort).
+    pub line: Option<u32>,
 }
 
 /// Error type for run plan building.
@@ -243,6 +359,28 @@ where
         path: input.cwd.clone(),
     })?;
 
+    // A script name takes precedence over a file of the same name
+    let entry_name = input.entry.to_string_lossy().into_owned();
+    if scripts::is_script_name(&entry_name) {
+        if let Some(script) = plan_script_entry(&resolved_cwd, &entry_name, &input.args) {
+            return Ok(RunPlanOutput {
+                schema_version: RUNPLAN_SCHEMA_VERSION,
+                resolved_cwd: resolved_cwd.to_string_lossy().into_owned(),
+                requested_entry: entry_name,
+                resolved_entry: None,
+                entry_kind: "script".to_string(),
+                args: input.args,
+                channel: input.channel.as_str().to_string(),
+                notes: script_notes(&script),
+                imports: Vec::new(),
+                resolved_imports: Vec::new(),
+                resolver: ResolverInfoOutput::default(),
+                graph: None,
+                script: Some(script),
+            });
+        }
+    }
+
     // Resolve entry path
     let entry_path = if input.entry.is_absolute() {
         input.entry.clone()
@@ -282,6 +420,11 @@ where
 
     // Generate notes based on entry extension
     let mut notes = generate_notes(&input.entry);
+    if scripts::is_script_name(&entry_name) && PackageScripts::load(&resolved_cwd).is_ok() {
+        notes.push(format!(
+            "\"{entry_name}\" is not a script in package.json; running it as a file"
+        ));
+    }
 
     // Scan imports and resolve them
     let (imports, resolved_imports) = if let Some(ref entry_canonical) = canonical_entry {
@@ -290,6 +433,16 @@ where
         (Vec::new(), Vec::new())
     };
 
+    let graph = match (&canonical_entry, input.graph_depth) {
+        (Some(entry), Some(depth)) => Some(analyze_import_graph(
+            entry,
+            &resolved_cwd,
+            input.channel,
+            depth,
+        )),
+        _ => None,
+    };
+
     Ok(RunPlanOutput {
         schema_version: RUNPLAN_SCHEMA_VERSION,
         resolved_cwd: resolved_cwd.to_string_lossy().into_owned(),
@@ -302,9 +455,63 @@ where
         imports,
         resolved_imports,
         resolver: ResolverInfoOutput::default(),
+        graph,
+        script: None,
+    })
+}
+
+/// Plan `name` as a script of the `package.json` in `cwd`, if it defines one.
+fn plan_script_entry(cwd: &Path, name: &str, args: &[String]) -> Option<ScriptPlanOutput> {
+    let pkg = PackageScripts::load(cwd).ok()?;
+    let command = pkg.get(name)?.to_string();
+    let opts = ScriptRunOptions {
+        args: args.to_vec(),
+        ..Default::default()
+    };
+    let steps = scripts::plan_script(&pkg, name, &opts).ok()?;
+    let shadowed_file = cwd.join(name);
+
+    Some(ScriptPlanOutput {
+        name: name.to_string(),
+        package_json: pkg.dir.join("package.json").to_string_lossy().into_owned(),
+        command,
+        steps: steps
+            .into_iter()
+            .map(|step| ScriptStepOutput {
+                event: step.event,
+                command: step.command,
+            })
+            .collect(),
+        bin_paths: scripts::bin_dirs(&pkg.dir)
+            .iter()
+            .map(|p| p.to_string_lossy().into_owned())
+            .collect(),
+        shell: ScriptShell::detect().program.to_string_lossy().into_owned(),
+        shadowed_file: shadowed_file
+            .is_file()
+            .then(|| shadowed_file.to_string_lossy().into_owned()),
     })
 }
 
+/// Notes explaining why a script was chosen and what it runs.
+fn script_notes(script: &ScriptPlanOutput) -> Vec<String> {
+    let mut notes = vec![format!(
+        "Running package.json script \"{}\": {}",
+        script.name, script.command
+    )];
+    if let Some(file) = &script.shadowed_file {
+        notes.push(format!(
+            "\"{}\" is both a script and a file ({file}); scripts take precedence, use ./{} to run the file",
+            script.name, script.name
+        ));
+    }
+    if script.steps.len() > 1 {
+        let events: Vec<&str> = script.steps.iter().map(|s| s.event.as_str()).collect();
+        notes.push(format!("Lifecycle: {}", events.join(" -> ")));
+    }
+    notes
+}
+
 /// Scan entry file for imports and resolve them.
 fn scan_and_resolve_imports(
     entry_path: &Path,
@@ -350,6 +557,228 @@ fn scan_and_resolve_imports(
     (imports, resolved_imports)
 }
 
+/// Walk the local import graph of `entry` breadth-first, up to `max_depth`
+/// levels of imports and at most [`MAX_GRAPH_FILES`] modules.
+#[must_use]
+pub fn analyze_import_graph(
+    entry: &Path,
+    cwd: &Path,
+    channel: Channel,
+    max_depth: u32,
+) -> ImportGraphSummary {
+    use crate::bundler::builtin_name;
+    use crate::imports::scan_imports;
+    use crate::resolver::resolve_v0;
+
+    let config = ResolverConfig::default();
+    let mut summary = ImportGraphSummary {
+        depth_limit: (max_depth != FULL_GRAPH_DEPTH).then_some(max_depth),
+        ..ImportGraphSummary::default()
+    };
+    let mut unresolved: BTreeMap<String, UnresolvedImportGroup> = BTreeMap::new();
+    // Local import edges, in scan order
+    let mut edges: HashMap<PathBuf, Vec<PathBuf>> = HashMap::new();
+    let mut seen: HashSet<PathBuf> = HashSet::from([entry.to_path_buf()]);
+    let mut queue = VecDeque::from([(entry.to_path_buf(), 0u32)]);
+
+    while let Some((file, depth)) = queue.pop_front() {
+        summary.files += 1;
+        summary.max_depth = summary.max_depth.max(depth);
+        let Ok(source) = std::fs::read_to_string(&file) else {
+            continue;
+        };
+        let ctx = ResolveContext {
+            cwd: cwd.to_path_buf(),
+            parent: file.parent().unwrap_or(cwd).to_path_buf(),
+            channel: channel.as_str().to_string(),
+            config: &config,
+            pkg_json_cache: None,
+        };
+
+        let mut targets = Vec::new();
+        for spec in scan_imports(&source) {
+            summary.imports += 1;
+            if builtin_name(&spec.raw).is_some() {
+                summary.external += 1;
+                continue;
+            }
+            let result = resolve_v0(&ctx, &spec.raw);
+            let Some(path) = result.resolved else {
+                let reason = result
+                    .reason
+                    .map_or_else(|| "UNKNOWN".to_string(), |r| r.to_string());
+                let group =
+                    unresolved
+                        .entry(reason.clone())
+                        .or_insert_with(|| UnresolvedImportGroup {
+                            reason,
+                            count: 0,
+                            sites: Vec::new(),
+                        });
+                group.count += 1;
+                if group.sites.len() < MAX_UNRESOLVED_SITES {
+                    group.sites.push(UnresolvedImportSite {
+                        from: display_path(&file, cwd),
+                        raw: spec.raw,
+                        line: spec.line,
+                    });
+                }
+                continue;
+            };
+            if !is_walkable(&path) {
+                summary.external += 1;
+                continue;
+            }
+            summary.local += 1;
+            let path = dunce::canonicalize(&path).unwrap_or(path);
+            targets.push(path.clone());
+            if depth < max_depth && !seen.contains(&path) {
+                if seen.len() >= MAX_GRAPH_FILES {
+                    summary.truncated = true;
+                    continue;
+                }
+                seen.insert(path.clone());
+                queue.push_back((path, depth + 1));
+            }
+        }
+        edges.insert(file, targets);
+    }
+
+    summary.unresolved = unresolved.into_values().collect();
+    summary.cycles = find_cycles(&edges)
+        .into_iter()
+        .map(|cycle| cycle.iter().map(|p| display_path(p, cwd)).collect())
+        .collect();
+    summary
+}
+
+/// Whether a resolved import is a local JS/TS module to walk into.
+fn is_walkable(path: &Path) -> bool {
+    let in_node_modules = path.components().any(|c| c.as_os_str() == "node_modules");
+    let is_source = matches!(
+        path.extension().and_then(|e| e.to_str()),
+        Some("js" | "jsx" | "mjs" | "cjs" | "ts" | "tsx" | "mts" | "cts")
+    );
+    is_source && !in_node_modules
+}
+
+/// Path relative to `cwd` when inside it, with forward slashes.
+fn display_path(path: &Path, cwd: &Path) -> String {
+    path.strip_prefix(cwd)
+        .unwrap_or(path)
+        .to_string_lossy()
+        .replace('\\', "/")
+}
+
+/// One cycle per strongly connected component of the walked graph
+/// (Tarjan's algorithm), sorted for stable output.
+fn find_cycles(edges: &HashMap<PathBuf, Vec<PathBuf>>) -> Vec<Vec<PathBuf>> {
+    struct Tarjan<'a> {
+        edges: &'a HashMap<PathBuf, Vec<PathBuf>>,
+        index: HashMap<&'a Path, usize>,
+        low: HashMap<&'a Path, usize>,
+        stack: Vec<&'a Path>,
+        on_stack: HashSet<&'a Path>,
+        components: Vec<Vec<&'a Path>>,
+    }
+
+    impl<'a> Tarjan<'a> {
+        fn visit(&mut self, node: &'a Path) {
+            let index = self.index.len();
+            self.index.insert(node, index);
+            self.low.insert(node, index);
+            self.stack.push(node);
+            self.on_stack.insert(node);
+
+            for next in self.edges.get(node).into_iter().flatten() {
+                let next = next.as_path();
+                if !self.edges.contains_key(next) {
+                    // Not walked (beyond the depth limit)
+                    continue;
+                }
+                if !self.index.contains_key(next) {
+                    self.visit(next);
+                    let low = self.low[node].min(self.low[next]);
+                    self.low.insert(node, low);
+                } else if self.on_stack.contains(next) {
+                    let low = self.low[node].min(self.index[next]);
+                    self.low.insert(node, low);
+                }
+            }
+
+            if self.low[node] == self.index[node] {
+                let mut component = Vec::new();
+                while let Some(member) = self.stack.pop() {
+                    self.on_stack.remove(member);
+                    component.push(member);
+                    if member == node {
+                        break;
+                    }
+                }
+                self.components.push(component);
+            }
+        }
+    }
+
+    let mut tarjan = Tarjan {
+        edges,
+        index: HashMap::new(),
+        low: HashMap::new(),
+        stack: Vec::new(),
+        on_stack: HashSet::new(),
+        components: Vec::new(),
+    };
+    let mut nodes: Vec<&Path> = edges.keys().map(PathBuf::as_path).collect();
+    nodes.sort();
+    for node in nodes {
+        if !tarjan.index.contains_key(node) {
+            tarjan.visit(node);
+        }
+    }
+
+    let mut cycles: Vec<Vec<PathBuf>> = tarjan
+        .components
+        .into_iter()
+        .filter_map(|component| {
+            let start = *component.iter().min()?;
+            let members: HashSet<&Path> = component.iter().copied().collect();
+            cycle_through(edges, start, &members)
+        })
+        .collect();
+    cycles.sort();
+    cycles
+}
+
+/// Shortest import path from `start` back to itself within `members`.
+fn cycle_through(
+    edges: &HashMap<PathBuf, Vec<PathBuf>>,
+    start: &Path,
+    members: &HashSet<&Path>,
+) -> Option<Vec<PathBuf>> {
+    let mut parent: HashMap<&Path, &Path> = HashMap::new();
+    let mut queue = VecDeque::from([start]);
+    while let Some(node) = queue.pop_front() {
+        for next in edges.get(node).into_iter().flatten() {
+            let next = next.as_path();
+            if next == start {
+                let mut cycle = vec![node.to_path_buf()];
+                let mut current = node;
+                while let Some(prev) = parent.get(current) {
+                    cycle.push(prev.to_path_buf());
+                    current = prev;
+                }
+                cycle.reverse();
+                return Some(cycle);
+            }
+            if members.contains(next) && !parent.contains_key(next) {
+                parent.insert(next, node);
+                queue.push_back(next);
+            }
+        }
+    }
+    None
+}
+
 /// Generate notes based on the entry point extension.
 fn generate_notes(entry: &Path) -> Vec<String> {
     let ext = entry
@@ -381,6 +810,7 @@ mod tests {
             entry: PathBuf::from("nonexistent.js"),
             args: vec![],
             channel: Channel::Stable,
+            graph_depth: None,
         };
 
         let result = build_run_plan(input);
@@ -400,6 +830,7 @@ mod tests {
             entry: PathBuf::from("subdir"),
             args: vec![],
             channel: Channel::Stable,
+            graph_depth: None,
         };
 
         let result = build_run_plan(input);
@@ -415,6 +846,7 @@ mod tests {
             entry: PathBuf::from("main.js"),
             args: vec![],
             channel: Channel::Stable,
+            graph_depth: None,
         };
 
         let result = build_run_plan(input);
@@ -434,6 +866,7 @@ mod tests {
             entry: PathBuf::from("main.js"),
             args: vec![],
             channel: Channel::Stable,
+            graph_depth: None,
         };
 
         let result = build_run_plan(input).unwrap();
@@ -453,14 +886,15 @@ mod tests {
             entry: PathBuf::from("index.ts"),
             args: vec![],
             channel: Channel::Dev,
+            graph_depth: None,
         };
 
         let result = build_run_plan(input).unwrap();
         assert_eq!(result.schema_version, 2);
         assert_eq!(result.schema_version, RUNPLAN_SCHEMA_VERSION);
         // New fields should be present
-        assert!(result.imports.is_empty()); // No imports in "// test"
-        assert!(result.resolved_imports.is_empty());
+        assert_eq!(result.imports, []); // No imports in "// test"
+        assert_eq!(result.resolved_imports, []);
         assert_eq!(result.resolver.schema_version, RESOLVER_SCHEMA_VERSION);
     }
 
@@ -475,10 +909,11 @@ mod tests {
             entry: PathBuf::from("app.ts"),
             args: vec![],
             channel: Channel::Stable,
+            graph_depth: None,
         };
 
         let result = build_run_plan(input).unwrap();
-        assert!(!result.notes.is_empty());
+        assert_ne!(result.notes, Vec::<String>::new());
         assert!(result.notes[0].contains("TypeScript"));
     }
 
@@ -493,13 +928,81 @@ mod tests {
             entry: PathBuf::from("app.js"),
             args: vec![],
             channel: Channel::Stable,
+            graph_depth: None,
         };
 
         let result = build_run_plan(input).unwrap();
-        assert!(!result.notes.is_empty());
+        assert_ne!(result.notes, Vec::<String>::new());
         assert!(result.notes[0].contains("JavaScript"));
     }
 
+    #[test]
+    fn test_script_takes_precedence_over_file() {
+        let dir = tempdir().unwrap();
+        fs::write(
+            dir.path().join("package.json"),
+            r#"{"scripts":{"prebuild":"rm -rf dist","build":"tsc","postbuild":"echo done"}}"#,
+        )
+        .unwrap();
+        fs::write(dir.path().join("build"), "#!/bin/sh").unwrap();
+        fs::create_dir_all(dir.path().join("node_modules/.bin")).unwrap();
+
+        let input = RunPlanInput {
+            cwd: dir.path().to_path_buf(),
+            entry: PathBuf::from("build"),
+            args: vec!["--watch".to_string()],
+            channel: Channel::Stable,
+            graph_depth: None,
+        };
+
+        let result = build_run_plan(input).unwrap();
+        assert_eq!(result.entry_kind, "script");
+        assert_eq!(result.resolved_entry, None);
+        let script = result.script.as_ref().unwrap();
+        assert_eq!(script.command, "tsc");
+        let steps: Vec<(&str, &str)> = script
+            .steps
+            .iter()
+            .map(|s| (s.event.as_str(), s.command.as_str()))
+            .collect();
+        assert_eq!(
+            steps,
+            [
+                ("prebuild", "rm -rf dist"),
+                ("build", "tsc --watch"),
+                ("postbuild", "echo done")
+            ]
+        );
+        assert_eq!(script.bin_paths.len(), 1);
+        assert!(Path::new(&script.bin_paths[0]).ends_with("node_modules/.bin"));
+        assert!(script.shadowed_file.as_ref().unwrap().ends_with("build"));
+        assert!(result
+            .notes
+            .iter()
+            .any(|n| n.contains("scripts take precedence")));
+        assert!(result
+            .notes
+            .iter()
+            .any(|n| n == "Lifecycle: prebuild -> build -> postbuild"));
+
+        // Without a matching script the file runs, and the plan says why
+        let input = RunPlanInput {
+            cwd: dir.path().to_path_buf(),
+            entry: PathBuf::from("build"),
+            args: vec![],
+            channel: Channel::Stable,
+            graph_depth: None,
+        };
+        fs::write(dir.path().join("package.json"), r#"{"scripts":{}}"#).unwrap();
+        let result = build_run_plan(input).unwrap();
+        assert_eq!(result.entry_kind, "file");
+        assert!(result.script.is_none());
+        assert!(result
+            .notes
+            .iter()
+            .any(|n| n.contains("not a script in package.json")));
+    }
+
     #[test]
     fn test_args_are_preserved() {
         let dir = tempdir().unwrap();
@@ -511,6 +1014,7 @@ mod tests {
             entry: PathBuf::from("main.js"),
             args: vec!["--port".to_string(), "3000".to_string()],
             channel: Channel::Stable,
+            graph_depth: None,
         };
 
         let result = build_run_plan(input).unwrap();
@@ -528,9 +1032,91 @@ mod tests {
             entry: PathBuf::from("main.js"),
             args: vec![],
             channel: Channel::Nightly,
+            graph_depth: None,
         };
 
         let result = build_run_plan(input).unwrap();
         assert_eq!(result.channel, "nightly");
     }
+
+    fn graph_plan(dir: &Path, depth: u32) -> RunPlanOutput {
+        build_run_plan(RunPlanInput {
+            cwd: dir.to_path_buf(),
+            entry: PathBuf::from("main.js"),
+            args: vec![],
+            channel: Channel::Stable,
+            graph_depth: Some(depth),
+        })
+        .unwrap()
+    }
+
+    #[test]
+    fn test_graph_summary_reports_unresolved_and_cycles() {
+        let dir = tempdir().unwrap();
+        fs::write(
+            dir.path().join("main.js"),
+            "import './a.js';\nimport fs from 'node:fs';\nimport './missing.js';\n",
+        )
+        .unwrap();
+        fs::write(dir.path().join("a.js"), "import './b.js';\n").unwrap();
+        fs::write(
+            dir.path().join("b.js"),
+            "import './a.js';\nimport 'left-pad';\nimport './gone.js';\n",
+        )
+        .unwrap();
+
+        let plan = graph_plan(dir.path(), FULL_GRAPH_DEPTH);
+        // Direct imports are unaffected
+        assert_eq!(plan.imports.len(), 3);
+
+        let graph = plan.graph.unwrap();
+        assert_eq!(graph.depth_limit, None);
+        assert_eq!(graph.files, 3);
+        assert_eq!(graph.max_depth, 2);
+        assert_eq!(graph.imports, 7);
+        assert_eq!(graph.local, 3);
+        assert_eq!(graph.external, 1);
+        assert_eq!(graph.unresolved_count(), 3);
+
+        let not_found = graph
+            .unresolved
+            .iter()
+            .find(|g| g.reason == "NOT_FOUND")
+            .unwrap();
+        assert_eq!(not_found.count, 2);
+        assert_eq!(not_found.sites[0].from, "main.js");
+        assert_eq!(not_found.sites[0].raw, "./missing.js");
+        assert_eq!(not_found.sites[0].line, Some(3));
+
+        assert_eq!(graph.cycles, vec![vec!["a.js", "b.js"]]);
+        assert!(!graph.truncated);
+    }
+
+    #[test]
+    fn test_graph_depth_limits_walk() {
+        let dir = tempdir().unwrap();
+        fs::write(dir.path().join("main.js"), "require('./a.js');\n").unwrap();
+        fs::write(dir.path().join("a.js"), "require('./b.js');\n").unwrap();
+        fs::write(dir.path().join("b.js"), "require('./nope.js');\n").unwrap();
+
+        let graph = graph_plan(dir.path(), 1).graph.unwrap();
+        assert_eq!(graph.depth_limit, Some(1));
+        assert_eq!(graph.files, 2);
+        assert_eq!(graph.max_depth, 1);
+        assert_eq!(graph.unresolved, []);
+
+        let graph = graph_plan(dir.path(), 2).graph.unwrap();
+        assert_eq!(graph.files, 3);
+        assert_eq!(graph.unresolved_count(), 1);
+    }
+
+    #[test]
+    fn test_graph_self_import_is_cycle() {
+        let dir = tempdir().unwrap();
+        fs::write(dir.path().join("main.js"), "import './main.js';\n").unwrap();
+
+        let graph = graph_plan(dir.path(), FULL_GRAPH_DEPTH).graph.unwrap();
+        assert_eq!(graph.files, 1);
+        assert_eq!(graph.cycles, vec![vec!["main.js"]]);
+    }
 }
@@ -0,0 +1,834 @@
+//! package.json script runner.
+//!
+//! Implements npm-compatible `run-script` semantics shared by `howth run`,
+//! `howth workspaces run` and build script nodes:
+//! - `pre<name>` / `post<name>` lifecycle hooks around the main script
+//! - `node_modules/.bin` (of the package and every ancestor) prepended to `PATH`
+//! - `npm_lifecycle_event`, `npm_lifecycle_script` and `npm_package_*` env vars
+//! - `--if-present` to silently skip missing scripts
+//!
+//! ## Shells
+//!
+//! Commands run through `sh -c` on Unix and `cmd.exe /d /s /c` on Windows,
+//! or through the shell named by `npm_config_script_shell` (npm's
+//! `script-shell`). Scripts are written for `sh`, so for `cmd` and
+//! PowerShell the command is adjusted before it runs:
+//! - `$VAR` / `${VAR}` become `%VAR%` (`cmd`) or `$env:VAR` (PowerShell)
+//! - leading `VAR=value cmd` assignments become `set "VAR=value" && cmd`
+//!
+//! A `node_modules/.bin` entry that the OS can't execute directly (no `.cmd`
+//! shim on Windows, no execute bit on Unix) runs through the interpreter
+//! named by its shebang, e.g. `#!/usr/bin/env node`.
+
+use crate::version::VERSION;
+use serde_json::Value;
+use std::collections::BTreeMap;
+use std::ffi::OsString;
+use std::fmt::Write as _;
+use std::path::{Path, PathBuf};
+use std::process::Command;
+use thiserror::Error;
+
+/// Stable error codes for script runner errors.
+pub mod codes {
+    pub const SCRIPT_NOT_FOUND: &str = "SCRIPT_NOT_FOUND";
+    pub const PACKAGE_JSON_NOT_FOUND: &str = "PACKAGE_JSON_NOT_FOUND";
+    pub const PACKAGE_JSON_INVALID: &str = "PACKAGE_JSON_INVALID";
+}
+
+/// Error type for script runner operations.
+#[derive(Error, Debug)]
+pub enum ScriptError {
+    #[error("package.json not found in {dir}")]
+    PackageJsonNotFound { dir: PathBuf },
+
+    #[error("invalid package.json at {path}: {reason}")]
+    PackageJsonInvalid { path: PathBuf, reason: String },
+
+    #[error("missing script: \"{name}\"")]
+    NotFound {
+        name: String,
+        /// Scripts that do exist, for hints.
+        available: Vec<String>,
+    },
+}
+
+impl ScriptError {
+    /// Get the stable error code.
+    #[must_use]
+    pub fn code(&self) -> &'static str {
+        match self {
+            Self::PackageJsonNotFound { .. } => codes::PACKAGE_JSON_NOT_FOUND,
+            Self::PackageJsonInvalid { .. } => codes::PACKAGE_JSON_INVALID,
+            Self::NotFound { .. } => codes::SCRIPT_NOT_FOUND,
+        }
+    }
+}
+
+/// A package.json loaded for script execution.
+#[derive(Debug, Clone)]
+pub struct PackageScripts {
+    /// Directory containing package.json.
+    pub dir: PathBuf,
+    /// Parsed package.json.
+    pub package: Value,
+    /// Scripts defined in package.json.
+    pub scripts: BTreeMap<String, String>,
+}
+
+impl PackageScripts {
+    /// Load scripts from `<dir>/package.json`.
+    ///
+    /// # Errors
+    /// Returns an error if package.json is missing or is not a JSON object.
+    pub fn load(dir: &Path) -> Result<Self, ScriptError> {
+        let path = dir.join("package.json");
+        let content =
+            std::fs::read_to_string(&path).map_err(|_| ScriptError::PackageJsonNotFound {
+                dir: dir.to_path_buf(),
+            })?;
+        let package: Value =
+            serde_json::from_str(&content).map_err(|e| ScriptError::PackageJsonInvalid {
+                path: path.clone(),
+                reason: e.to_string(),
+            })?;
+        if !package.is_object() {
+            return Err(ScriptError::PackageJsonInvalid {
+                path,
+                reason: "expected a JSON object".to_string(),
+            });
+        }
+
+        let scripts = package
+            .get("scripts")
+            .and_then(Value::as_object)
+            .map(|obj| {
+                obj.iter()
+                    .filter_map(|(k, v)| v.as_str().map(|s| (k.clone(), s.to_string())))
+                    .collect()
+            })
+            .unwrap_or_default();
+
+        Ok(Self {
+            dir: dir.to_path_buf(),
+            package,
+            scripts,
+        })
+    }
+
+    /// Get a script command by name.
+    #[must_use]
+    pub fn get(&self, name: &str) -> Option<&str> {
+        self.scripts.get(name).map(String::as_str)
+    }
+
+    /// Package name, if any.
+    #[must_use]
+    pub fn name(&self) -> Option<&str> {
+        self.package.get("name").and_then(Value::as_str)
+    }
+
+    /// Package version, if any.
+    #[must_use]
+    pub fn version(&self) -> Option<&str> {
+        self.package.get("version").and_then(Value::as_str)
+    }
+}
+
+/// One step of a script lifecycle (`pre<name>`, `<name>` or `post<name>`).
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ScriptStep {
+    /// Lifecycle event name (exported as `npm_lifecycle_event`).
+    pub event: String,
+    /// Shell command to run, with extra args appended for the main script.
+    pub command: String,
+}
+
+/// Options for planning a script run.
+#[derive(Debug, Clone, Default)]
+pub struct ScriptRunOptions {
+    /// Extra arguments appended to the main script (not to pre/post hooks).
+    pub args: Vec<String>,
+    /// Return an empty plan instead of an error when the script is missing.
+    pub if_present: bool,
+    /// Skip `pre<name>` and `post<name>` hooks.
+    pub skip_hooks: bool,
+}
+
+/// Plan the lifecycle steps for running `name`.
+///
+/// Returns `pre<name>`, `<name>` and `post<name>` (hooks only when defined).
+///
+/// # Errors
+/// Returns `ScriptError::NotFound` if the script is missing and `if_present` is false.
+pub fn plan_script(
+    pkg: &PackageScripts,
+    name: &str,
+    opts: &ScriptRunOptions,
+) -> Result<Vec<ScriptStep>, ScriptError> {
+    let Some(main) = pkg.get(name) else {
+        if opts.if_present {
+            return Ok(Vec::new());
+        }
+        return Err(ScriptError::NotFound {
+            name: name.to_string(),
+            available: pkg.scripts.keys().cloned().collect(),
+        });
+    };
+
+    let mut steps = Vec::with_capacity(3);
+    let hook = |event: String| {
+        pkg.get(&event).map(|cmd| ScriptStep {
+            command: cmd.to_string(),
+            event,
+        })
+    };
+
+    if !opts.skip_hooks {
+        steps.extend(hook(format!("pre{name}")));
+    }
+    steps.push(ScriptStep {
+        event: name.to_string(),
+        command: append_args(main, &opts.args),
+    });
+    if !opts.skip_hooks {
+        steps.extend(hook(format!("post{name}")));
+    }
+
+    Ok(steps)
+}
+
+/// Append shell-quoted arguments to a script command.
+#[must_use]
+pub fn append_args(command: &str, args: &[String]) -> String {
+    if args.is_empty() {
+        return command.to_string();
+    }
+    let quoted: Vec<String> = args.iter().map(|a| shell_quote(a)).collect();
+    format!("{command} {}", quoted.join(" "))
+}
+
+/// Quote an argument for the platform shell if needed.
+#[must_use]
+pub fn shell_quote(arg: &str) -> String {
+    let is_safe = |c: char| c.is_ascii_alphanumeric() || "-_./=:@%+,".contains(c);
+    if !arg.is_empty() && arg.chars().all(is_safe) {
+        return arg.to_string();
+    }
+    if cfg!(windows) {
+        format!("\"{}\"", arg.replace('"', "\\\""))
+    } else {
+        format!("'{}'", arg.replace('\'', "'\\''"))
+    }
+}
+
+/// Check whether a `howth run` entry looks like a script name rather than a
+/// file path (no separators and no extension).
+#[must_use]
+pub fn is_script_name(entry: &str) -> bool {
+    !(entry.is_empty() || entry.contains('/') || entry.contains('\\') || entry.contains('.'))
+}
+
+/// Existing `node_modules/.bin` directories of `dir` and its ancestors,
+/// nearest first.
+#[must_use]
+pub fn bin_dirs(dir: &Path) -> Vec<PathBuf> {
+    dir.ancestors()
+        .map(|d| d.join("node_modules").join(".bin"))
+        .filter(|p| p.is_dir())
+        .collect()
+}
+
+/// Build `PATH` with `node_modules/.bin` of `dir` and its ancestors prepended.
+///
+/// The nearest `.bin` comes first so local binaries shadow hoisted ones.
+#[must_use]
+pub fn bin_path(dir: &Path) -> OsString {
+    let mut entries = bin_dirs(dir);
+    if let Some(system) = std::env::var_os("PATH") {
+        entries.extend(std::env::split_paths(&system));
+    }
+    std::env::join_paths(entries).unwrap_or_else(|_| std::env::var_os("PATH").unwrap_or_default())
+}
+
+/// Environment variables npm exposes to lifecycle scripts.
+///
+/// Includes `npm_lifecycle_event`, `npm_lifecycle_script`, `npm_package_json`,
+/// `npm_package_name`, `npm_package_version`, flattened `npm_package_config_*`,
+/// `npm_package_engines_*` and `npm_package_bin_*`, plus `npm_config_user_agent`.
+#[must_use]
+pub fn script_env(pkg: &PackageScripts, step: &ScriptStep) -> Vec<(String, String)> {
+    let mut env = vec![
+        ("npm_lifecycle_event".to_string(), step.event.clone()),
+        ("npm_lifecycle_script".to_string(), step.command.clone()),
+        (
+            "npm_package_json".to_string(),
+            pkg.dir.join("package.json").display().to_string(),
+        ),
+        (
+            "npm_config_user_agent".to_string(),
+            format!(
+                "howth/{VERSION} {} {}",
+                std::env::consts::OS,
+                std::env::consts::ARCH
+            ),
+        ),
+    ];
+
+    if let Some(name) = pkg.name() {
+        env.push(("npm_package_name".to_string(), name.to_string()));
+    }
+    if let Some(version) = pkg.version() {
+        env.push(("npm_package_version".to_string(), version.to_string()));
+    }
+
+    for field in ["config", "engines", "bin"] {
+        match pkg.package.get(field) {
+            Some(Value::Object(obj)) => {
+                for (key, value) in obj {
+                    if let Some(value) = scalar_to_string(value) {
+                        env.push((format!("npm_package_{field}_{}", env_key(key)), value));
+                    }
+                }
+            }
+            // "bin": "./cli.js" is shorthand for { [name]: "./cli.js" }
+            Some(Value::String(bin)) if field == "bin" => {
+                if let Some(name) = pkg.name() {
+                    let name = name.rsplit('/').next().unwrap_or(name);
+                    env.push((format!("npm_package_bin_{}", env_key(name)), bin.clone()));
+                }
+            }
+            _ => {}
+        }
+    }
+
+    env
+}
+
+/// Sanitize a key for use in an environment variable name.
+fn env_key(key: &str) -> String {
+    key.chars()
+        .map(|c| if c.is_ascii_alphanumeric() { c } else { '_' })
+        .collect()
+}
+
+/// Convert a JSON scalar to its env representation.
+fn scalar_to_string(value: &Value) -> Option<String> {
+    match value {
+        Value::String(s) => Some(s.clone()),
+        Value::Number(n) => Some(n.to_string()),
+        Value::Bool(b) => Some(b.to_string()),
+        _ => None,
+    }
+}
+
+/// Environment variable naming the shell scripts run in (npm's `script-shell`).
+pub const SCRIPT_SHELL_ENV: &str = "npm_config_script_shell";
+
+/// Syntax family of a script shell.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ShellKind {
+    /// `sh`, `bash`, `zsh` and other POSIX shells.
+    Posix,
+    /// Windows `cmd.exe`.
+    Cmd,
+    /// Windows PowerShell or `pwsh`.
+    PowerShell,
+}
+
+/// The shell a script command runs in.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ScriptShell {
+    /// Shell program.
+    pub program: PathBuf,
+    /// Syntax the shell understands.
+    pub kind: ShellKind,
+}
+
+impl ScriptShell {
+    /// Shell for `program`, classified by its file name.
+    #[must_use]
+    pub fn from_program(program: impl Into<PathBuf>) -> Self {
+        let program = program.into();
+        // Split on both separators so Windows paths classify on any host
+        let name = program.to_string_lossy().to_ascii_lowercase();
+        let name = name.rsplit(['/', '\\']).next().unwrap_or_default();
+        let stem = name.strip_suffix(".exe").unwrap_or(name);
+        let kind = match stem {
+            "cmd" => ShellKind::Cmd,
+            "powershell" | "pwsh" => ShellKind::PowerShell,
+            _ => ShellKind::Posix,
+        };
+        Self { program, kind }
+    }
+
+    /// `npm_config_script_shell` if set, otherwise `%ComSpec%` (or `cmd.exe`)
+    /// on Windows and `sh` elsewhere.
+    #[must_use]
+    pub fn detect() -> Self {
+        if let Some(shell) = std::env::var_os(SCRIPT_SHELL_ENV).filter(|s| !s.is_empty()) {
+            return Self::from_program(shell);
+        }
+        if cfg!(windows) {
+            let comspec = std::env::var_os("ComSpec").unwrap_or_else(|| "cmd.exe".into());
+            Self::from_program(comspec)
+        } else {
+            Self::from_program("sh")
+        }
+    }
+
+    /// Rewrite a script written for `sh` into this shell's syntax.
+    #[must_use]
+    pub fn translate(&self, command: &str) -> String {
+        match self.kind {
+            ShellKind::Posix => command.to_string(),
+            ShellKind::Cmd | ShellKind::PowerShell => {
+                let command = hoist_env_assignments(command, self.kind);
+                replace_env_refs(&command, self.kind)
+            }
+        }
+    }
+
+    /// Build the command that runs `command` in this shell.
+    #[must_use]
+    pub fn command(&self, command: &str) -> Command {
+        let mut cmd = Command::new(&self.program);
+        match self.kind {
+            ShellKind::Posix => {
+                cmd.arg("-c").arg(command);
+            }
+            ShellKind::PowerShell => {
+                cmd.args(["-NoProfile", "-NonInteractive", "-Command"])
+                    .arg(command);
+            }
+            ShellKind::Cmd => {
+                // cmd.exe does its own parsing, so pass the command verbatim
+                // (like npm's windowsVerbatimArguments); /s strips the outer quotes
+                #[cfg(windows)]
+                {
+                    use std::os::windows::process::CommandExt;
+                    cmd.raw_arg(format!("/d /s /c \"{command}\""));
+                }
+                #[cfg(not(windows))]
+                cmd.args(["/d", "/s", "/c", command]);
+            }
+        }
+        cmd
+    }
+}
+
+/// Build a platform shell command (see the module docs) for a raw command string.
+///
+/// The command runs in `cwd` with `node_modules/.bin` prepended to `PATH`.
+/// Stdio is left at its defaults so callers can inherit or capture.
+#[must_use]
+pub fn shell_command(command: &str, cwd: &Path) -> Command {
+    shell_command_with(&ScriptShell::detect(), command, cwd)
+}
+
+/// Like [`shell_command`], with an explicit shell.
+#[must_use]
+pub fn shell_command_with(shell: &ScriptShell, command: &str, cwd: &Path) -> Command {
+    let path = bin_path(cwd);
+    let command = shebang_command(command, &path).unwrap_or_else(|| command.to_string());
+    let mut cmd = shell.command(&shell.translate(&command));
+    cmd.current_dir(cwd).env("PATH", path);
+    cmd
+}
+
+/// Whether `word` is a `NAME=value` shell assignment.
+fn is_env_assignment(word: &str) -> bool {
+    word.split_once('=')
+        .is_some_and(|(name, _)| is_env_name(name))
+}
+
+/// Whether `name` is a valid environment variable name.
+fn is_env_name(name: &str) -> bool {
+    let mut chars = name.chars();
+    chars
+        .next()
+        .is_some_and(|c| c.is_ascii_alphabetic() || c == '_')
+        && chars.all(|c| c.is_ascii_alphanumeric() || c == '_')
+}
+
+/// Turn leading `NAME=value` words of each `&&` segment into statements the
+/// shell understands: `set "NAME=value" &&` for `cmd`, `$env:NAME='value';`
+/// for PowerShell.
+fn hoist_env_assignments(command: &str, kind: ShellKind) -> String {
+    command
+        .split("&&")
+        .map(|segment| {
+            let trimmed = segment.trim_start();
+            let leading = &segment[..segment.len() - trimmed.len()];
+            let mut rest = trimmed;
+            let mut out = String::new();
+            while let Some((word, tail)) = rest.split_once(char::is_whitespace) {
+                if !is_env_assignment(word) {
+                    break;
+                }
+                let (name, value) = word.split_once('=').unwrap_or((word, ""));
+                let value = value.trim_matches(|c| c == '"' || c == '\'');
+                match kind {
+                    ShellKind::PowerShell => {
+                        let _ = write!(out, "$env:{name}='{value}'; ");
+                    }
+                    _ => {
+                        let _ = write!(out, "set \"{name}={value}\" && ");
+                    }
+                }
+                rest = tail.trim_start();
+            }
+            format!("{leading}{out}{rest}")
+        })
+        .collect::<Vec<_>>()
+        .join("&&")
+}
+
+/// Replace `$NAME` and `${NAME}` outside single quotes with the shell's
+/// variable syntax.
+fn replace_env_refs(command: &str, kind: ShellKind) -> String {
+    let mut out = String::with_capacity(command.len());
+    let mut in_single_quote = false;
+    let mut rest = command;
+    while let Some(c) = rest.chars().next() {
+        rest = &rest[c.len_utf8()..];
+        if c == '\'' {
+            in_single_quote = !in_single_quote;
+        }
+        if c != '$' || in_single_quote || kind == ShellKind::Posix {
+            out.push(c);
+            continue;
+        }
+
+        let (name, len) = if let Some(braced) = rest.strip_prefix('{') {
+            match braced.split_once('}') {
+                Some((name, _)) if is_env_name(name) => (name, name.len() + 2),
+                _ => ("", 0),
+            }
+        } else {
+            let end = rest
+                .find(|c: char| !(c.is_ascii_alphanumeric() || c == '_'))
+                .unwrap_or(rest.len());
+            (&rest[..end], end)
+        };
+        // `$env:NAME` is already PowerShell syntax; `$1`, `$?` etc. are left alone
+        if !is_env_name(name) || (kind == ShellKind::PowerShell && rest.starts_with("env:")) {
+            out.push(c);
+            continue;
+        }
+        match kind {
+            ShellKind::PowerShell => {
+                let _ = write!(out, "$env:{name}");
+            }
+            _ => {
+                let _ = write!(out, "%{name}%");
+            }
+        }
+        rest = &rest[len..];
+    }
+    out
+}
+
+/// Interpreter and arguments from a script's `#!` line.
+///
+/// `#!/usr/bin/env node --flag` yields `["node", "--flag"]`; an absolute
+/// interpreter keeps its path on Unix and is reduced to its file name on
+/// Windows, where it is looked up on `PATH` instead.
+#[must_use]
+pub fn parse_shebang(content: &str) -> Option<Vec<String>> {
+    let line = content.strip_prefix("#!")?.lines().next()?;
+    let mut words = line.split_whitespace();
+    let mut interpreter = words.next()?.to_string();
+    let mut args: Vec<String> = words.map(str::to_string).collect();
+
+    if interpreter.rsplit('/').next() == Some("env") {
+        // `env -S node --flag` splits the rest of the line itself
+        if args.first().is_some_and(|a| a == "-S") {
+            args.remove(0);
+        }
+        if args.is_empty() {
+            return None;
+        }
+        interpreter = args.remove(0);
+    } else if cfg!(windows) {
+        interpreter = interpreter
+            .rsplit('/')
+            .next()
+            .unwrap_or(&interpreter)
+            .to_string();
+    }
+
+    args.insert(0, interpreter);
+    Some(args)
+}
+
+/// Rewrite `command` to run its program through the shebang interpreter when
+/// the program is a script on `path` the OS can't execute directly.
+fn shebang_command(command: &str, path: &std::ffi::OsStr) -> Option<String> {
+    let trimmed = command.trim_start();
+    let (program, rest) = trimmed
+        .split_once(char::is_whitespace)
+        .unwrap_or((trimmed, ""));
+    if program.contains(['/', '\\', '$', '%', '"', '\'']) {
+        return None;
+    }
+
+    let script = std::env::split_paths(path)
+        .map(|dir| dir.join(program))
+        .find(|candidate| candidate.is_file())?;
+    if is_directly_executable(&script) {
+        return None;
+    }
+    let mut header = [0u8; 256];
+    let len = std::io::Read::read(&mut std::fs::File::open(&script).ok()?, &mut header).ok()?;
+    let argv = parse_shebang(&String::from_utf8_lossy(&header[..len]))?;
+
+    let mut rewritten: Vec<String> = argv.iter().map(|a| shell_quote(a)).collect();
+    rewritten.push(shell_quote(&script.to_string_lossy()));
+    if !rest.is_empty() {
+        rewritten.push(rest.to_string());
+    }
+    Some(rewritten.join(" "))
+}
+
+/// Whether the OS can run `script` itself: it has the execute bit on Unix, or
+/// a `.cmd` shim next to it on Windows.
+fn is_directly_executable(script: &Path) -> bool {
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::PermissionsExt;
+        std::fs::metadata(script).is_ok_and(|m| m.permissions().mode() & 0o111 != 0)
+    }
+    #[cfg(not(unix))]
+    {
+        script.with_extension("cmd").is_file()
+    }
+}
+
+/// Build the command for one lifecycle step, with npm env vars applied.
+///
+/// `init_cwd` is exported as `INIT_CWD` (the directory the user ran howth from).
+#[must_use]
+pub fn step_command(pkg: &PackageScripts, step: &ScriptStep, init_cwd: &Path) -> Command {
+    let mut cmd = shell_command(&step.command, &pkg.dir);
+    cmd.envs(script_env(pkg, step)).env("INIT_CWD", init_cwd);
+    cmd
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::tempdir;
+
+    fn write_pkg(dir: &Path, json: &str) -> PackageScripts {
+        std::fs::write(dir.join("package.json"), json).unwrap();
+        PackageScripts::load(dir).unwrap()
+    }
+
+    #[test]
+    fn test_plan_with_hooks() {
+        let dir = tempdir().unwrap();
+        let pkg = write_pkg(
+            dir.path(),
+            r#"{"scripts":{"prebuild":"echo pre","build":"tsc","postbuild":"echo post"}}"#,
+        );
+        let opts = ScriptRunOptions {
+            args: vec!["--watch".to_string()],
+            ..Default::default()
+        };
+        let steps = plan_script(&pkg, "build", &opts).unwrap();
+        let events: Vec<_> = steps.iter().map(|s| s.event.as_str()).collect();
+        assert_eq!(events, ["prebuild", "build", "postbuild"]);
+        // Args only go to the main script
+        assert_eq!(steps[0].command, "echo pre");
+        assert_eq!(steps[1].command, "tsc --watch");
+        assert_eq!(steps[2].command, "echo post");
+
+        let opts = ScriptRunOptions {
+            skip_hooks: true,
+            ..Default::default()
+        };
+        assert_eq!(plan_script(&pkg, "build", &opts).unwrap().len(), 1);
+    }
+
+    #[test]
+    fn test_plan_missing_script() {
+        let dir = tempdir().unwrap();
+        let pkg = write_pkg(dir.path(), r#"{"scripts":{"test":"jest"}}"#);
+
+        let err = plan_script(&pkg, "lint", &ScriptRunOptions::default()).unwrap_err();
+        assert_eq!(err.code(), codes::SCRIPT_NOT_FOUND);
+        assert!(matches!(err, ScriptError::NotFound { available, .. } if available == ["test"]));
+
+        let opts = ScriptRunOptions {
+            if_present: true,
+            ..Default::default()
+        };
+        assert_eq!(plan_script(&pkg, "lint", &opts).unwrap(), []);
+    }
+
+    #[test]
+    fn test_load_errors() {
+        let dir = tempdir().unwrap();
+        let err = PackageScripts::load(dir.path()).unwrap_err();
+        assert_eq!(err.code(), codes::PACKAGE_JSON_NOT_FOUND);
+
+        std::fs::write(dir.path().join("package.json"), "[]").unwrap();
+        let err = PackageScripts::load(dir.path()).unwrap_err();
+        assert_eq!(err.code(), codes::PACKAGE_JSON_INVALID);
+    }
+
+    #[test]
+    fn test_shell_quote() {
+        assert_eq!(shell_quote("--port=3000"), "--port=3000");
+        if cfg!(windows) {
+            assert_eq!(shell_quote("a b"), "\"a b\"");
+        } else {
+            assert_eq!(shell_quote("a b"), "'a b'");
+            assert_eq!(shell_quote("it's"), "'it'\\''s'");
+            assert_eq!(shell_quote(""), "''");
+        }
+    }
+
+    #[test]
+    fn test_script_env() {
+        let dir = tempdir().unwrap();
+        let pkg = write_pkg(
+            dir.path(),
+            r#"{"name":"@scope/app","version":"1.2.3","config":{"port":8080,"dry-run":true},
+                "engines":{"node":">=18"},"bin":"./cli.js","scripts":{"start":"node ."}}"#,
+        );
+        let step = ScriptStep {
+            event: "start".to_string(),
+            command: "node .".to_string(),
+        };
+        let env: BTreeMap<_, _> = script_env(&pkg, &step).into_iter().collect();
+        assert_eq!(env["npm_lifecycle_event"], "start");
+        assert_eq!(env["npm_lifecycle_script"], "node .");
+        assert_eq!(env["npm_package_name"], "@scope/app");
+        assert_eq!(env["npm_package_version"], "1.2.3");
+        assert_eq!(env["npm_package_config_port"], "8080");
+        assert_eq!(env["npm_package_config_dry_run"], "true");
+        assert_eq!(env["npm_package_engines_node"], ">=18");
+        assert_eq!(env["npm_package_bin_app"], "./cli.js");
+        assert!(env["npm_config_user_agent"].starts_with("howth/"));
+    }
+
+    #[test]
+    fn test_bin_path_includes_ancestors() {
+        let dir = tempdir().unwrap();
+        let root_bin = dir.path().join("node_modules/.bin");
+        let pkg_dir = dir.path().join("packages/app");
+        let pkg_bin = pkg_dir.join("node_modules/.bin");
+        std::fs::create_dir_all(&root_bin).unwrap();
+        std::fs::create_dir_all(&pkg_bin).unwrap();
+
+        assert_eq!(bin_dirs(&pkg_dir), [pkg_bin.clone(), root_bin.clone()]);
+        let path = bin_path(&pkg_dir);
+        let entries: Vec<PathBuf> = std::env::split_paths(&path).collect();
+        assert_eq!(entries[0], pkg_bin);
+        assert_eq!(entries[1], root_bin);
+    }
+
+    #[test]
+    fn test_is_script_name() {
+        for name in ["build", "test:unit", "dev-server"] {
+            assert!(is_script_name(name), "{name}");
+        }
+        for path in ["", "index.ts", "./build", "scripts/build", "bin\\build"] {
+            assert!(!is_script_name(path), "{path}");
+        }
+    }
+
+    #[test]
+    fn test_shell_detection_by_program() {
+        assert_eq!(
+            ScriptShell::from_program("/bin/bash").kind,
+            ShellKind::Posix
+        );
+        assert_eq!(
+            ScriptShell::from_program(r"C:\Windows\System32\cmd.exe").kind,
+            ShellKind::Cmd
+        );
+        assert_eq!(
+            ScriptShell::from_program("pwsh").kind,
+            ShellKind::PowerShell
+        );
+    }
+
+    #[test]
+    fn test_translate_for_cmd() {
+        let cmd = ScriptShell::from_program("cmd.exe");
+        assert_eq!(
+            cmd.translate("NODE_ENV=production PORT=3000 node server.js"),
+            r#"set "NODE_ENV=production" && set "PORT=3000" && node server.js"#
+        );
+        assert_eq!(
+            cmd.translate("echo $HOME ${USER} '$LITERAL' $1"),
+            "echo %HOME% %USER% '$LITERAL' $1"
+        );
+        assert_eq!(
+            cmd.translate("tsc && DEBUG=1 node dist/index.js"),
+            r#"tsc && set "DEBUG=1" && node dist/index.js"#
+        );
+        // POSIX shells run scripts unchanged
+        let sh = ScriptShell::from_program("sh");
+        assert_eq!(sh.translate("A=1 echo $A"), "A=1 echo $A");
+    }
+
+    #[test]
+    fn test_translate_for_powershell() {
+        let pwsh = ScriptShell::from_program("pwsh");
+        assert_eq!(
+            pwsh.translate("NODE_ENV=test jest $CI_FLAGS $env:PATH"),
+            "$env:NODE_ENV='test'; jest $env:CI_FLAGS $env:PATH"
+        );
+    }
+
+    #[test]
+    fn test_parse_shebang() {
+        assert_eq!(
+            parse_shebang("#!/usr/bin/env node\nrequire('./cli')").unwrap(),
+            ["node"]
+        );
+        assert_eq!(
+            parse_shebang("#!/usr/bin/env -S node --no-warnings\n").unwrap(),
+            ["node", "--no-warnings"]
+        );
+        let sh = parse_shebang("#!/bin/sh -e\n").unwrap();
+        assert_eq!(sh[1], "-e");
+        assert!(sh[0].ends_with("sh"));
+        assert!(parse_shebang("console.log(1)").is_none());
+        assert!(parse_shebang("#!/usr/bin/env\n").is_none());
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_bin_script_without_exec_bit_runs_via_shebang() {
+        let dir = tempdir().unwrap();
+        let bin = dir.path().join("node_modules/.bin");
+        std::fs::create_dir_all(&bin).unwrap();
+        // Extracted without mode bits, so the OS can't run it directly
+        std::fs::write(bin.join("greet"), "#!/bin/sh\necho \"hi $1\" > out.txt\n").unwrap();
+
+        let status = shell_command("greet there", dir.path()).status().unwrap();
+        assert!(status.success());
+        let out = std::fs::read_to_string(dir.path().join("out.txt")).unwrap();
+        assert_eq!(out.trim(), "hi there");
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_step_command_runs_with_env() {
+        let dir = tempdir().unwrap();
+        let pkg = write_pkg(
+            dir.path(),
+            r#"{"name":"app","scripts":{"greet":"echo $npm_lifecycle_event:$npm_package_name > out.txt"}}"#,
+        );
+        let steps = plan_script(&pkg, "greet", &ScriptRunOptions::default()).unwrap();
+        let status = step_command(&pkg, &steps[0], dir.path()).status().unwrap();
+        assert!(status.success());
+        let out = std::fs::read_to_string(dir.path().join("out.txt")).unwrap();
+        assert_eq!(out.trim(), "greet:app");
+    }
+}
@@ -0,0 +1,753 @@
+//! Source maps (V3): decoding, encoding and composition.
+//!
+//! Each stage that rewrites a module — a plugin transform, TypeScript
+//! stripping, bundling, minification — can describe its output with a map
+//! back to its input. [`SourceMap::remap`] chains them segment by segment,
+//! so the final map points at the file as written instead of at an
+//! intermediate stage.
+//!
+//! Columns are UTF-16 code units, as the format specifies.
+
+use howth_parser::SourceMapping;
+use serde::{Deserialize, Serialize};
+use thiserror::Error;
+
+/// Error type for source maps.
+#[derive(Error, Debug)]
+pub enum SourceMapError {
+    #[error("invalid source map: {0}")]
+    Json(#[from] serde_json::Error),
+
+    #[error("unsupported source map version {0}")]
+    Version(u32),
+
+    #[error("invalid mappings: {0}")]
+    Mappings(&'static str),
+
+    #[error("not a base64 source map data URL")]
+    DataUrl,
+}
+
+/// A decoded source map.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct SourceMap {
+    /// Name of the generated file.
+    pub file: Option<String>,
+    /// Original file names.
+    pub sources: Vec<String>,
+    /// Original file contents, by source.
+    pub sources_content: Vec<Option<String>>,
+    /// Identifier names referenced by segments.
+    pub names: Vec<String>,
+    /// Segments of each generated line, sorted by column.
+    pub lines: Vec<Vec<Segment>>,
+}
+
+/// A mapped position in the generated code.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Segment {
+    /// Generated column.
+    pub column: u32,
+    /// Where it came from; `None` for generated code with no source.
+    pub original: Option<Original>,
+}
+
+/// A position in an original source.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Original {
+    /// Index into [`SourceMap::sources`].
+    pub source: u32,
+    /// Zero-based line.
+    pub line: u32,
+    /// Zero-based column.
+    pub column: u32,
+    /// Index into [`SourceMap::names`].
+    pub name: Option<u32>,
+}
+
+/// The JSON shape of a source map.
+#[derive(Deserialize, Serialize)]
+#[serde(rename_all = "camelCase")]
+struct RawSourceMap {
+    version: u32,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    file: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    source_root: Option<String>,
+    #[serde(default)]
+    sources: Vec<Option<String>>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    sources_content: Option<Vec<Option<String>>>,
+    #[serde(default)]
+    names: Vec<String>,
+    mappings: String,
+}
+
+impl SourceMap {
+    /// Create an empty source map.
+    #[must_use]
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Decode a source map from JSON.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error for invalid JSON or mappings, and for versions
+    /// other than 3 (index maps with `sections` are not supported).
+    pub fn parse(json: &str) -> Result<Self, SourceMapError> {
+        let raw: RawSourceMap = serde_json::from_str(json)?;
+        if raw.version != 3 {
+            return Err(SourceMapError::Version(raw.version));
+        }
+        let root = raw.source_root.unwrap_or_default();
+        let sources: Vec<String> = raw
+            .sources
+            .into_iter()
+            .map(|source| {
+                let source = source.unwrap_or_default();
+                if root.is_empty() || source.contains("://") || source.starts_with('/') {
+                    source
+                } else {
+                    format!("{}/{}", root.trim_end_matches('/'), source)
+                }
+            })
+            .collect();
+        let mut sources_content = raw.sources_content.unwrap_or_default();
+        sources_content.resize(sources.len(), None);
+
+        let mut map = Self {
+            file: raw.file,
+            sources,
+            sources_content,
+            names: raw.names,
+            lines: Vec::new(),
+        };
+        map.lines = decode_mappings(&raw.mappings, map.sources.len(), map.names.len())?;
+        Ok(map)
+    }
+
+    /// Encode as JSON.
+    #[must_use]
+    pub fn to_json(&self) -> String {
+        let has_content = self.sources_content.iter().any(Option::is_some);
+        let raw = RawSourceMap {
+            version: 3,
+            file: self.file.clone(),
+            source_root: None,
+            sources: self.sources.iter().cloned().map(Some).collect(),
+            sources_content: has_content.then(|| self.sources_content.clone()),
+            names: self.names.clone(),
+            mappings: encode_mappings(&self.lines),
+        };
+        serde_json::to_string(&raw).unwrap_or_default()
+    }
+
+    /// Build the map of code generated by howth-parser from `source`, using
+    /// the mappings of [`Codegen::generate_with_source_map`].
+    ///
+    /// [`Codegen::generate_with_source_map`]: howth_parser::Codegen::generate_with_source_map
+    #[must_use]
+    pub fn from_codegen(
+        source_name: &str,
+        source: &str,
+        generated: &str,
+        mappings: &[SourceMapping],
+    ) -> Self {
+        let mut map = Self::new();
+        let source_idx = map.add_source(source_name, Some(source));
+        let source_lines = LineIndex::new(source);
+        let generated_lines: Vec<&str> = generated.split('\n').collect();
+        for mapping in mappings {
+            let (line, column) = source_lines.position(source, mapping.orig_offset as usize);
+            let generated_line = generated_lines
+                .get(mapping.gen_line as usize)
+                .copied()
+                .unwrap_or("");
+            map.push(
+                mapping.gen_line,
+                utf16_column(generated_line, mapping.gen_col as usize),
+                Some(Original {
+                    source: source_idx,
+                    line,
+                    column,
+                    name: None,
+                }),
+            );
+        }
+        map
+    }
+
+    /// A line-level map of code whose lines correspond one-to-one with
+    /// `source`: each generated line maps to the start of the same line.
+    #[must_use]
+    pub fn identity_lines(source_name: &str, source: &str) -> Self {
+        let mut map = Self::new();
+        let source_idx = map.add_source(source_name, Some(source));
+        for line in 0..source.split('\n').count() as u32 {
+            map.push(
+                line,
+                0,
+                Some(Original {
+                    source: source_idx,
+                    line,
+                    column: 0,
+                    name: None,
+                }),
+            );
+        }
+        map
+    }
+
+    /// Register a source, returning its index. A source already registered
+    /// under the same name is reused.
+    pub fn add_source(&mut self, name: &str, content: Option<&str>) -> u32 {
+        if let Some(idx) = self.sources.iter().position(|s| s == name) {
+            if self.sources_content[idx].is_none() {
+                self.sources_content[idx] = content.map(str::to_string);
+            }
+            return idx as u32;
+        }
+        self.sources.push(name.to_string());
+        self.sources_content.push(content.map(str::to_string));
+        (self.sources.len() - 1) as u32
+    }
+
+    /// Register a name, returning its index.
+    pub fn add_name(&mut self, name: &str) -> u32 {
+        if let Some(idx) = self.names.iter().position(|n| n == name) {
+            return idx as u32;
+        }
+        self.names.push(name.to_string());
+        (self.names.len() - 1) as u32
+    }
+
+    /// Add a segment at a generated position.
+    pub fn push(&mut self, line: u32, column: u32, original: Option<Original>) {
+        let line = line as usize;
+        if self.lines.len() <= line {
+            self.lines.resize_with(line + 1, Vec::new);
+        }
+        let segments = &mut self.lines[line];
+        let at = segments.partition_point(|s| s.column <= column);
+        segments.insert(at, Segment { column, original });
+    }
+
+    /// Shift every generated line down by `count`, for code prepended to
+    /// the output.
+    pub fn prepend_lines(&mut self, count: usize) {
+        self.lines
+            .splice(0..0, std::iter::repeat_with(Vec::new).take(count));
+    }
+
+    /// Add the segments of `other`, the map of code inserted into this
+    /// map's code at `line` and `column`: its first line is shifted by
+    /// `column`, the rest start at column 0 as in `other`.
+    pub fn insert(&mut self, other: &SourceMap, line: u32, column: u32) {
+        let sources: Vec<u32> = other
+            .sources
+            .iter()
+            .zip(&other.sources_content)
+            .map(|(name, content)| self.add_source(name, content.as_deref()))
+            .collect();
+        let names: Vec<u32> = other.names.iter().map(|name| self.add_name(name)).collect();
+        for (offset, segments) in other.lines.iter().enumerate() {
+            let shift = if offset == 0 { column } else { 0 };
+            for segment in segments {
+                let original = segment.original.map(|original| Original {
+                    source: sources[original.source as usize],
+                    name: original.name.map(|n| names[n as usize]),
+                    ..original
+                });
+                self.push(line + offset as u32, segment.column + shift, original);
+            }
+        }
+    }
+
+    /// The original position of a generated position: the closest segment
+    /// at or before `column` on `line`. A position before the first segment
+    /// of the line takes that segment, so line-level maps (one segment per
+    /// line, at column 0) still find indented code.
+    #[must_use]
+    pub fn lookup(&self, line: u32, column: u32) -> Option<Original> {
+        let segments = self.lines.get(line as usize)?;
+        let at = segments.partition_point(|s| s.column <= column);
+        let segment = if at == 0 {
+            segments.first()?
+        } else {
+            &segments[at - 1]
+        };
+        segment.original
+    }
+
+    /// Compose with the maps of this map's sources: each segment pointing
+    /// into a source `inner` has a map for is traced through that map to
+    /// its original position. Sources without a map are kept as they are.
+    #[must_use]
+    pub fn remap<'a>(&self, inner: impl Fn(&str) -> Option<&'a SourceMap>) -> SourceMap {
+        let inner_maps: Vec<Option<&SourceMap>> =
+            self.sources.iter().map(|source| inner(source)).collect();
+        let mut result = SourceMap {
+            file: self.file.clone(),
+            ..SourceMap::default()
+        };
+
+        for (line, segments) in self.lines.iter().enumerate() {
+            for segment in segments {
+                let original = segment.original.and_then(|original| {
+                    let source = original.source as usize;
+                    let name = original.name.map(|n| self.names[n as usize].as_str());
+                    match inner_maps[source] {
+                        Some(map) => {
+                            let traced = map.lookup(original.line, original.column)?;
+                            let inner_source = traced.source as usize;
+                            let source = result.add_source(
+                                &map.sources[inner_source],
+                                map.sources_content[inner_source].as_deref(),
+                            );
+                            // The name as written beats a renamed one
+                            let name = traced.name.map(|n| map.names[n as usize].as_str()).or(name);
+                            Some(Original {
+                                source,
+                                line: traced.line,
+                                column: traced.column,
+                                name: name.map(|name| result.add_name(name)),
+                            })
+                        }
+                        None => Some(Original {
+                            source: result.add_source(
+                                &self.sources[source],
+                                self.sources_content[source].as_deref(),
+                            ),
+                            name: name.map(|name| result.add_name(name)),
+                            ..original
+                        }),
+                    }
+                });
+                result.push(line as u32, segment.column, original);
+            }
+        }
+        result
+    }
+
+    /// Decode a map inlined as a `data:` URL, as [`Self::to_data_url`]
+    /// writes it.
+    pub fn from_data_url(url: &str) -> Result<Self, SourceMapError> {
+        let (header, data) = url
+            .strip_prefix("data:")
+            .and_then(|url| url.split_once(','))
+            .ok_or(SourceMapError::DataUrl)?;
+        if !header.starts_with("application/json") || !header.ends_with(";base64") {
+            return Err(SourceMapError::DataUrl);
+        }
+        let json = base64_decode(data).ok_or(SourceMapError::DataUrl)?;
+        Self::parse(std::str::from_utf8(&json).map_err(|_| SourceMapError::DataUrl)?)
+    }
+
+    /// The `//# sourceMappingURL=` value that inlines this map.
+    #[must_use]
+    pub fn to_data_url(&self) -> String {
+        inline_url(&self.to_json())
+    }
+}
+
+/// Compose a chain of maps, given in the order their transforms ran: each
+/// map's generated code is the next one's source. Returns `None` for an
+/// empty chain.
+#[must_use]
+pub fn compose(maps: &[&SourceMap]) -> Option<SourceMap> {
+    let (first, rest) = maps.split_first()?;
+    let mut composed = (*first).clone();
+    for map in rest {
+        composed = map.remap(|_| Some(&composed));
+    }
+    Some(composed)
+}
+
+/// A `data:` URL holding a source map, for `//# sourceMappingURL=`.
+#[must_use]
+pub fn inline_url(json: &str) -> String {
+    format!(
+        "data:application/json;charset=utf-8;base64,{}",
+        base64_encode(json.as_bytes())
+    )
+}
+
+/// Base64 encoding (standard alphabet, padded).
+pub(crate) fn base64_encode(data: &[u8]) -> String {
+    let mut result = String::with_capacity(data.len().div_ceil(3) * 4);
+
+    for chunk in data.chunks(3) {
+        let mut buffer = [0u8; 3];
+        buffer[..chunk.len()].copy_from_slice(chunk);
+
+        let n = u32::from(buffer[0]) << 16 | u32::from(buffer[1]) << 8 | u32::from(buffer[2]);
+
+        result.push(BASE64[(n >> 18 & 0x3F) as usize] as char);
+        result.push(BASE64[(n >> 12 & 0x3F) as usize] as char);
+
+        if chunk.len() > 1 {
+            result.push(BASE64[(n >> 6 & 0x3F) as usize] as char);
+        } else {
+            result.push('=');
+        }
+
+        if chunk.len() > 2 {
+            result.push(BASE64[(n & 0x3F) as usize] as char);
+        } else {
+            result.push('=');
+        }
+    }
+
+    result
+}
+
+/// Base64 decoding (standard alphabet, padding optional).
+fn base64_decode(data: &str) -> Option<Vec<u8>> {
+    let data = data.trim_end_matches('=').as_bytes();
+    let mut result = Vec::with_capacity(data.len() * 3 / 4);
+    let mut buffer = 0u32;
+    let mut bits = 0;
+    for &c in data {
+        buffer = (buffer << 6 | base64_value(c)?) & 0xFFFF;
+        bits += 6;
+        if bits >= 8 {
+            bits -= 8;
+            result.push((buffer >> bits).to_le_bytes()[0]);
+        }
+    }
+    Some(result)
+}
+
+/// The value of a base64 digit.
+fn base64_value(byte: u8) -> Option<u32> {
+    let value = BASE64.iter().position(|&b| b == byte)?;
+    u32::try_from(value).ok()
+}
+
+const BASE64: &[u8] = b"ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789+/";
+
+/// Byte offsets of line starts, to turn offsets into line and column.
+struct LineIndex {
+    starts: Vec<usize>,
+}
+
+impl LineIndex {
+    fn new(source: &str) -> Self {
+        let starts = std::iter::once(0)
+            .chain(source.match_indices('\n').map(|(i, _)| i + 1))
+            .collect();
+        Self { starts }
+    }
+
+    /// Line and UTF-16 column of a byte offset.
+    fn position(&self, source: &str, offset: usize) -> (u32, u32) {
+        let offset = offset.min(source.len());
+        let line = self.starts.partition_point(|&start| start <= offset) - 1;
+        let text = &source[self.starts[line]..];
+        (line as u32, utf16_column(text, offset - self.starts[line]))
+    }
+}
+
+/// UTF-16 length of the first `byte_column` bytes of `line`.
+fn utf16_column(line: &str, byte_column: usize) -> u32 {
+    let mut end = byte_column.min(line.len());
+    while !line.is_char_boundary(end) {
+        end -= 1;
+    }
+    line[..end].encode_utf16().count() as u32
+}
+
+/// Append a VLQ-encoded signed integer.
+pub(crate) fn vlq_encode(value: i64, out: &mut String) {
+    #[allow(clippy::cast_sign_loss)]
+    let mut v = (if value < 0 {
+        ((-value) << 1) | 1
+    } else {
+        value << 1
+    }) as u64;
+    loop {
+        let mut digit = (v & 0x1f) as u8;
+        v >>= 5;
+        if v > 0 {
+            digit |= 0x20; // continuation bit
+        }
+        out.push(BASE64[digit as usize] as char);
+        if v == 0 {
+            break;
+        }
+    }
+}
+
+/// Read one VLQ-encoded signed integer.
+fn vlq_decode(bytes: &mut std::iter::Peekable<std::str::Bytes<'_>>) -> Result<i64, SourceMapError> {
+    let mut value: i64 = 0;
+    let mut shift = 0;
+    loop {
+        let byte = bytes
+            .next()
+            .ok_or(SourceMapError::Mappings("truncated segment"))?;
+        let digit =
+            i64::from(base64_value(byte).ok_or(SourceMapError::Mappings("invalid base64 digit"))?);
+        if shift > 60 {
+            return Err(SourceMapError::Mappings("value out of range"));
+        }
+        value |= (digit & 0x1f) << shift;
+        shift += 5;
+        if digit & 0x20 == 0 {
+            break;
+        }
+    }
+    Ok(if value & 1 == 1 {
+        -(value >> 1)
+    } else {
+        value >> 1
+    })
+}
+
+fn decode_mappings(
+    mappings: &str,
+    sources: usize,
+    names: usize,
+) -> Result<Vec<Vec<Segment>>, SourceMapError> {
+    let mut lines = Vec::new();
+    let (mut source, mut line, mut column, mut name) = (0i64, 0i64, 0i64, 0i64);
+    let as_index = |value: i64, len: usize, what: &'static str| {
+        u32::try_from(value)
+            .ok()
+            .filter(|&v| (v as usize) < len)
+            .ok_or(SourceMapError::Mappings(what))
+    };
+    let as_position = |value: i64| {
+        u32::try_from(value).map_err(|_| SourceMapError::Mappings("negative position"))
+    };
+
+    for text in mappings.split(';') {
+        let mut segments = Vec::new();
+        let mut generated_column = 0i64;
+        for segment in text.split(',').filter(|s| !s.is_empty()) {
+            let mut bytes = segment.bytes().peekable();
+            let mut fields = Vec::with_capacity(5);
+            while bytes.peek().is_some() {
+                fields.push(vlq_decode(&mut bytes)?);
+            }
+            generated_column += fields[0];
+            let original = match fields.len() {
+                1 => None,
+                4 | 5 => {
+                    source += fields[1];
+                    line += fields[2];
+                    column += fields[3];
+                    let name = if let Some(&delta) = fields.get(4) {
+                        name += delta;
+                        Some(as_index(name, names, "name index out of range")?)
+                    } else {
+                        None
+                    };
+                    Some(Original {
+                        source: as_index(source, sources, "source index out of range")?,
+                        line: as_position(line)?,
+                        column: as_position(column)?,
+                        name,
+                    })
+                }
+                _ => return Err(SourceMapError::Mappings("segment has a wrong field count")),
+            };
+            segments.push(Segment {
+                column: as_position(generated_column)?,
+                original,
+            });
+        }
+        segments.sort_by_key(|s| s.column);
+        lines.push(segments);
+    }
+    Ok(lines)
+}
+
+fn encode_mappings(lines: &[Vec<Segment>]) -> String {
+    let mut out = String::new();
+    let (mut source, mut line, mut column, mut name) = (0i64, 0i64, 0i64, 0i64);
+    for (i, segments) in lines.iter().enumerate() {
+        if i > 0 {
+            out.push(';');
+        }
+        let mut generated_column = 0i64;
+        for (j, segment) in segments.iter().enumerate() {
+            if j > 0 {
+                out.push(',');
+            }
+            vlq_encode(i64::from(segment.column) - generated_column, &mut out);
+            generated_column = i64::from(segment.column);
+            if let Some(original) = segment.original {
+                vlq_encode(i64::from(original.source) - source, &mut out);
+                vlq_encode(i64::from(original.line) - line, &mut out);
+                vlq_encode(i64::from(original.column) - column, &mut out);
+                source = i64::from(original.source);
+                line = i64::from(original.line);
+                column = i64::from(original.column);
+                if let Some(n) = original.name {
+                    vlq_encode(i64::from(n) - name, &mut out);
+                    name = i64::from(n);
+                }
+            }
+        }
+    }
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[allow(clippy::unnecessary_wraps)]
+    fn original(source: u32, line: u32, column: u32) -> Option<Original> {
+        Some(Original {
+            source,
+            line,
+            column,
+            name: None,
+        })
+    }
+
+    #[test]
+    fn test_roundtrip() {
+        let json = r#"{"version":3,"file":"out.js","sources":["a.ts","b.ts"],"sourcesContent":["let a","let b"],"names":["a"],"mappings":"AAAA,IAAIA;;ACCJ,GAAG"}"#;
+        let map = SourceMap::parse(json).unwrap();
+        assert_eq!(map.lines.len(), 3);
+        assert_eq!(map.lines[0][1].column, 4);
+        assert_eq!(map.lines[0][1].original.unwrap().name, Some(0));
+        assert_eq!(map.lines[2][0].original, original(1, 1, 0));
+        assert_eq!(map.lines[2][1].column, 3);
+        assert_eq!(SourceMap::parse(&map.to_json()).unwrap(), map);
+        assert_eq!(map.to_json(), json);
+    }
+
+    #[test]
+    fn test_parse_errors() {
+        assert!(SourceMap::parse("{").is_err());
+        assert!(matches!(
+            SourceMap::parse(r#"{"version":2,"sources":[],"mappings":""}"#),
+            Err(SourceMapError::Version(2))
+        ));
+        assert!(SourceMap::parse(r#"{"version":3,"sources":[],"mappings":"AACA"}"#).is_err());
+        assert!(SourceMap::parse(r#"{"version":3,"sources":["a"],"mappings":"AA"}"#).is_err());
+    }
+
+    #[test]
+    fn test_lookup() {
+        let mut map = SourceMap::new();
+        map.add_source("a.js", None);
+        map.push(0, 2, original(0, 5, 0));
+        map.push(0, 10, original(0, 5, 8));
+        assert_eq!(map.lookup(0, 12), original(0, 5, 8));
+        assert_eq!(map.lookup(0, 4), original(0, 5, 0));
+        // Before the first segment, as in a line-level map
+        assert_eq!(map.lookup(0, 0), original(0, 5, 0));
+        assert_eq!(map.lookup(1, 0), None);
+    }
+
+    #[test]
+    fn test_from_codegen_utf16_columns() {
+        use howth_parser::{Codegen, CodegenOptions, Parser, ParserOptions};
+
+        let source = "const s: string = 'é';\nlet n: number = 1;\n";
+        let options = ParserOptions {
+            typescript: true,
+            ..Default::default()
+        };
+        let ast = Parser::new(source, options).parse().unwrap();
+        let codegen = CodegenOptions {
+            source_map: true,
+            ..Default::default()
+        };
+        let (code, mappings) = Codegen::new(&ast, codegen).generate_with_source_map();
+        let map = SourceMap::from_codegen("a.ts", source, &code, &mappings);
+
+        assert_eq!(map.sources, vec!["a.ts"]);
+        assert_eq!(map.sources_content, vec![Some(source.to_string())]);
+        assert_eq!(map.lookup(0, 0), original(0, 0, 0));
+        assert_eq!(map.lookup(1, 0), original(0, 1, 0));
+    }
+
+    #[test]
+    fn test_identity_lines() {
+        let map = SourceMap::identity_lines("a.js", "let a;\n  let b;\n");
+        assert_eq!(map.lines.len(), 3);
+        assert_eq!(map.lookup(1, 2), original(0, 1, 0));
+    }
+
+    #[test]
+    fn test_insert() {
+        let mut bundle = SourceMap::new();
+        bundle.add_source("a.js", Some("a"));
+        bundle.push(0, 0, original(0, 0, 0));
+
+        let mut module = SourceMap::new();
+        module.add_source("b.js", Some("b"));
+        module.push(0, 2, original(0, 1, 0));
+        module.push(1, 0, original(0, 2, 0));
+
+        bundle.insert(&module, 1, 10);
+        assert_eq!(bundle.sources, vec!["a.js", "b.js"]);
+        assert_eq!(bundle.lines[1][0].column, 12);
+        assert_eq!(bundle.lookup(1, 12), original(1, 1, 0));
+        assert_eq!(bundle.lookup(2, 0), original(1, 2, 0));
+    }
+
+    #[test]
+    fn test_compose_chain() {
+        // plugin: source.ts line 3 -> plugin output line 1
+        let mut plugin = SourceMap::new();
+        plugin.add_source("src/app.ts", Some("// header\n\n\nlet a: number = 1;"));
+        plugin.push(1, 0, original(0, 3, 0));
+        plugin.push(1, 4, original(0, 3, 4));
+        // transpile: plugin output line 1 -> line 0
+        let mut transpile = SourceMap::new();
+        transpile.add_source("src/app.ts", None);
+        transpile.push(0, 0, original(0, 1, 0));
+        transpile.push(0, 4, original(0, 1, 4));
+        // minify: line 0 -> line 0 at column 10
+        let mut minify = SourceMap::new();
+        minify.add_source("bundle.js", None);
+        minify.push(0, 10, original(0, 0, 4));
+        minify.push(0, 20, None);
+
+        let composed = compose(&[&plugin, &transpile, &minify]).unwrap();
+        assert_eq!(composed.sources, vec!["src/app.ts"]);
+        assert!(composed.sources_content[0].is_some());
+        assert_eq!(composed.lookup(0, 10), original(0, 3, 4));
+        assert_eq!(composed.lines[0][1].original, None);
+        assert!(compose(&[]).is_none());
+    }
+
+    #[test]
+    fn test_remap_keeps_unmapped_sources() {
+        let mut bundle = SourceMap::new();
+        bundle.add_source("a.js", Some("a"));
+        bundle.add_source("b.js", Some("b"));
+        bundle.push(0, 0, original(0, 0, 0));
+        bundle.push(1, 0, original(1, 4, 0));
+
+        let mut a = SourceMap::new();
+        a.add_source("a.ts", Some("a: 1"));
+        a.push(0, 0, original(0, 2, 6));
+
+        let remapped = bundle.remap(|source| (source == "a.js").then_some(&a));
+        assert_eq!(remapped.sources, vec!["a.ts", "b.js"]);
+        assert_eq!(remapped.lookup(0, 0), original(0, 2, 6));
+        assert_eq!(remapped.lookup(1, 0), original(1, 4, 0));
+    }
+
+    #[test]
+    fn test_inline_url() {
+        assert_eq!(
+            inline_url("{}"),
+            "data:application/json;charset=utf-8;base64,e30="
+        );
+
+        let map = SourceMap::identity_lines("a.js", "a\nb");
+        assert_eq!(SourceMap::from_data_url(&map.to_data_url()).unwrap(), map);
+        assert!(SourceMap::from_data_url("data:text/plain,{}").is_err());
+    }
+}
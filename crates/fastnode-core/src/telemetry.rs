@@ -0,0 +1,204 @@
+//! Configuration for opt-in OpenTelemetry trace export.
+//!
+//! Export stays off unless an OTLP endpoint is configured, either through the
+//! standard OpenTelemetry environment variables or under `howth.telemetry`
+//! in `package.json` (the nearest one that has it, so a workspace root can
+//! configure every member):
+//!
+//! ```json
+//! {
+//!   "howth": {
+//!     "telemetry": {
+//!       "endpoint": "http://collector:4318",
+//!       "serviceName": "web-build",
+//!       "headers": { "x-api-key": "..." }
+//!     }
+//!   }
+//! }
+//! ```
+//!
+//! Environment variables take precedence:
+//! - `OTEL_EXPORTER_OTLP_TRACES_ENDPOINT`: full traces URL, used as-is
+//! - `OTEL_EXPORTER_OTLP_ENDPOINT`: base URL, `/v1/traces` is appended
+//! - `OTEL_SERVICE_NAME`: service name (default `howth`)
+//! - `OTEL_SDK_DISABLED=true`: disables export even if configured
+//!
+//! A `package.json` endpoint is a base URL like `OTEL_EXPORTER_OTLP_ENDPOINT`.
+
+use serde_json::Value;
+use std::collections::BTreeMap;
+use std::path::Path;
+
+/// Full OTLP/HTTP traces URL.
+pub const TRACES_ENDPOINT_ENV: &str = "OTEL_EXPORTER_OTLP_TRACES_ENDPOINT";
+
+/// Base OTLP/HTTP URL shared by all signals.
+pub const ENDPOINT_ENV: &str = "OTEL_EXPORTER_OTLP_ENDPOINT";
+
+/// Service name reported on exported spans.
+pub const SERVICE_NAME_ENV: &str = "OTEL_SERVICE_NAME";
+
+/// Set to `true` to disable export.
+pub const SDK_DISABLED_ENV: &str = "OTEL_SDK_DISABLED";
+
+/// Service name used when none is configured.
+pub const DEFAULT_SERVICE_NAME: &str = "howth";
+
+/// Path appended to base endpoints for trace export.
+const TRACES_PATH: &str = "/v1/traces";
+
+/// Resolved trace export settings.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TelemetryConfig {
+    /// OTLP/HTTP URL spans are posted to.
+    pub endpoint: String,
+    /// `service.name` resource attribute.
+    pub service_name: String,
+    /// Extra request headers from `package.json`.
+    pub headers: BTreeMap<String, String>,
+}
+
+impl TelemetryConfig {
+    /// Resolve settings for a command run in `cwd` from the process
+    /// environment and `package.json`. Returns `None` when export is off.
+    #[must_use]
+    pub fn load(cwd: &Path) -> Option<Self> {
+        let project = project_telemetry(cwd);
+        Self::resolve(|name| std::env::var(name).ok(), project.as_ref())
+    }
+
+    /// Resolve settings from an environment lookup and the project's
+    /// `howth.telemetry` object.
+    fn resolve(env: impl Fn(&str) -> Option<String>, project: Option<&Value>) -> Option<Self> {
+        let env = |name: &str| env(name).filter(|value| !value.trim().is_empty());
+        if env(SDK_DISABLED_ENV).is_some_and(|v| v.trim().eq_ignore_ascii_case("true")) {
+            return None;
+        }
+
+        let project_str = |key: &str| {
+            project
+                .and_then(|p| p.get(key))
+                .and_then(Value::as_str)
+                .filter(|value| !value.trim().is_empty())
+                .map(str::to_string)
+        };
+
+        let endpoint = env(TRACES_ENDPOINT_ENV).or_else(|| {
+            env(ENDPOINT_ENV)
+                .or_else(|| project_str("endpoint"))
+                .map(|base| format!("{}{TRACES_PATH}", base.trim_end_matches('/')))
+        })?;
+
+        let service_name = env(SERVICE_NAME_ENV)
+            .or_else(|| project_str("serviceName"))
+            .unwrap_or_else(|| DEFAULT_SERVICE_NAME.to_string());
+
+        let headers = project
+            .and_then(|p| p.get("headers"))
+            .and_then(Value::as_object)
+            .map(|headers| {
+                headers
+                    .iter()
+                    .filter_map(|(name, value)| Some((name.clone(), value.as_str()?.to_string())))
+                    .collect()
+            })
+            .unwrap_or_default();
+
+        Some(Self {
+            endpoint,
+            service_name,
+            headers,
+        })
+    }
+}
+
+/// `howth.telemetry` from the nearest `package.json` above `cwd` that has it.
+fn project_telemetry(cwd: &Path) -> Option<Value> {
+    cwd.ancestors().find_map(|dir| {
+        let content = std::fs::read_to_string(dir.join("package.json")).ok()?;
+        let pkg: Value = serde_json::from_str(&content).ok()?;
+        pkg.get("howth")?.get("telemetry").cloned()
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+    use std::collections::HashMap;
+
+    fn resolve(env: &[(&str, &str)], project: Option<Value>) -> Option<TelemetryConfig> {
+        let env: HashMap<String, String> = env
+            .iter()
+            .map(|(k, v)| ((*k).to_string(), (*v).to_string()))
+            .collect();
+        TelemetryConfig::resolve(|name| env.get(name).cloned(), project.as_ref())
+    }
+
+    #[test]
+    fn test_disabled_without_endpoint() {
+        assert_eq!(resolve(&[], None), None);
+        assert_eq!(resolve(&[(SERVICE_NAME_ENV, "ci")], Some(json!({}))), None);
+    }
+
+    #[test]
+    fn test_env_endpoints() {
+        let config = resolve(&[(ENDPOINT_ENV, "http://collector:4318/")], None).unwrap();
+        assert_eq!(config.endpoint, "http://collector:4318/v1/traces");
+        assert_eq!(config.service_name, DEFAULT_SERVICE_NAME);
+
+        let config = resolve(
+            &[
+                (ENDPOINT_ENV, "http://collector:4318"),
+                (TRACES_ENDPOINT_ENV, "http://traces:4318/custom"),
+            ],
+            None,
+        )
+        .unwrap();
+        assert_eq!(config.endpoint, "http://traces:4318/custom");
+    }
+
+    #[test]
+    fn test_project_config_and_env_precedence() {
+        let project = json!({
+            "endpoint": "http://collector:4318",
+            "serviceName": "web-build",
+            "headers": { "x-api-key": "secret", "ignored": 1 }
+        });
+
+        let config = resolve(&[], Some(project.clone())).unwrap();
+        assert_eq!(config.endpoint, "http://collector:4318/v1/traces");
+        assert_eq!(config.service_name, "web-build");
+        assert_eq!(
+            config.headers,
+            BTreeMap::from([("x-api-key".to_string(), "secret".to_string())])
+        );
+
+        let config = resolve(
+            &[(ENDPOINT_ENV, "http://env:4318"), (SERVICE_NAME_ENV, "ci")],
+            Some(project.clone()),
+        )
+        .unwrap();
+        assert_eq!(config.endpoint, "http://env:4318/v1/traces");
+        assert_eq!(config.service_name, "ci");
+
+        assert_eq!(resolve(&[(SDK_DISABLED_ENV, "TRUE")], Some(project)), None);
+    }
+
+    #[test]
+    fn test_project_telemetry_uses_nearest_configured_package_json() {
+        let dir = tempfile::tempdir().unwrap();
+        std::fs::write(
+            dir.path().join("package.json"),
+            r#"{"howth": {"telemetry": {"endpoint": "http://root:4318"}}}"#,
+        )
+        .unwrap();
+        let member = dir.path().join("packages/app");
+        std::fs::create_dir_all(&member).unwrap();
+        std::fs::write(member.join("package.json"), r#"{"name": "app"}"#).unwrap();
+
+        let project = project_telemetry(&member);
+        let config = TelemetryConfig::resolve(|_| None, project.as_ref()).unwrap();
+        assert_eq!(config.endpoint, "http://root:4318/v1/traces");
+    }
+}
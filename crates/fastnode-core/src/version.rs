@@ -25,7 +25,7 @@ mod tests {
 
     #[test]
     fn test_version_not_empty() {
-        assert!(!VERSION.is_empty());
+        assert_ne!(VERSION, "");
     }
 
     #[test]
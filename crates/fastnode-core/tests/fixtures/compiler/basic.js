@@ -0,0 +1,31 @@
+import { readFile } from "node:fs/promises";
+export * from "./shared.js";
+
+const cache = new Map();
+
+export async function load(path, { encoding = "utf8", ...rest } = {}) {
+  if (cache.has(path)) return cache.get(path);
+  const text = await readFile(path, encoding);
+  const value = { text, size: text.length, ...rest };
+  cache.set(path, value);
+  return value;
+}
+
+export default class Store {
+  #items = [];
+  static create() {
+    return new Store();
+  }
+  get size() {
+    return this.#items.length;
+  }
+  add(...items) {
+    for (const item of items) this.#items.push(item);
+    return this;
+  }
+}
+
+export const lazy = () => import("./lazy.js");
+label: for (let i = 0; i < 3; i++) {
+  if (i % 2) continue label;
+}
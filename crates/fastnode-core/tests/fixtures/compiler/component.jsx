@@ -0,0 +1,18 @@
+import { useState } from "react";
+
+export function Counter({ start = 0 }) {
+  const [count, setCount] = useState(start);
+  return (
+    <div className="counter">
+      <button onClick={() => setCount(count + 1)}>+</button>
+      <span>{count}</span>
+      <>
+        <Label text="count" />
+      </>
+    </div>
+  );
+}
+
+function Label({ text }) {
+  return <label>{text}</label>;
+}
@@ -0,0 +1,20 @@
+import { memo, type ReactNode } from "react";
+
+interface Props {
+  title: string;
+  children?: ReactNode;
+}
+
+export const Panel = memo(function Panel({ title, children }: Props) {
+  const items: string[] = [title];
+  return (
+    <section title={title as string}>
+      <h2>{items.map((item) => <span key={item}>{item}</span>)}</h2>
+      {children}
+    </section>
+  );
+});
+
+export default function App(): JSX.Element {
+  return <Panel title="Hello" />;
+}
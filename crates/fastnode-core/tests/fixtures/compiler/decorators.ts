@@ -0,0 +1,14 @@
+function sealed<T>(ctor: T): T {
+  return ctor;
+}
+
+function log(_target: unknown, _ctx: unknown) {}
+
+@sealed
+export class Service {
+  @log
+  run(): void {}
+
+  @log
+  name = "svc";
+}
@@ -0,0 +1,13 @@
+export function pick(config, fallback) {
+  const port = config?.server?.port ?? fallback;
+  const handler = config.hooks?.onStart?.(port);
+  config.retries ||= 3;
+  config.timeout ??= 1000;
+  const square = port ** 2;
+  try {
+    JSON.parse(handler);
+  } catch {
+    return { port, square };
+  }
+  return { ...config, port, square };
+}
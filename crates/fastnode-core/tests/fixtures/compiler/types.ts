@@ -0,0 +1,34 @@
+import type { Options } from "./options";
+import { defaults } from "./options";
+
+export interface User {
+  id: number;
+  name: string;
+  tags?: readonly string[];
+}
+
+export type Id = User["id"];
+
+enum Role {
+  Admin = "admin",
+  Guest = "guest",
+}
+
+export abstract class Repo<T extends { id: number }> {
+  protected readonly items = new Map<number, T>();
+  constructor(private readonly options: Options = defaults) {}
+  abstract validate(item: T): item is T;
+  save(item: T): T {
+    this.items.set(item.id, item);
+    return item;
+  }
+}
+
+export function roleOf(user: User): Role {
+  return user.name === "root" ? Role.Admin : Role.Guest;
+}
+
+export const first = <T,>(items: T[]): T | undefined => items[0];
+const count = (first([1, 2]) as number) satisfies number;
+declare const VERSION: string;
+export { count };
@@ -8,9 +8,11 @@ use fastnode_core::resolver::{
     CachedResolveResult, FileStamp, PkgJsonCache, PkgJsonStamp, ResolveResult, ResolveStatus,
     ResolverCache, ResolverCacheKey,
 };
+use fastnode_util::fs as fs_util;
 use serde_json::Value;
 use std::collections::{HashMap, HashSet};
 use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicU64, Ordering};
 use std::sync::RwLock;
 use tracing::debug;
 
@@ -19,8 +21,21 @@ use tracing::debug;
 pub struct DaemonResolverCache {
     /// Cache entries: key -> cached result
     entries: RwLock<HashMap<ResolverCacheKey, CachedResolveResult>>,
-    /// Reverse index: resolved path -> set of cache keys that depend on it
+    /// Reverse index: resolved or tried path -> set of cache keys that depend on it
     reverse_index: RwLock<HashMap<PathBuf, HashSet<ResolverCacheKey>>>,
+    /// Number of entries removed by path invalidation.
+    invalidations: AtomicU64,
+}
+
+/// Paths a cached resolution depends on: the resolved file and every
+/// candidate that was tried, so creating a previously missing candidate
+/// invalidates the entry.
+fn dependent_paths(cached: &CachedResolveResult) -> impl Iterator<Item = PathBuf> + '_ {
+    cached
+        .resolved
+        .iter()
+        .chain(&cached.tried)
+        .map(PathBuf::from)
 }
 
 impl DaemonResolverCache {
@@ -32,7 +47,7 @@ impl DaemonResolverCache {
 
     /// Store a resolver result in the cache.
     ///
-    /// Also updates the reverse index if the result resolved to a file.
+    /// Also indexes the resolved file and the tried candidates.
     pub fn put(&self, key: ResolverCacheKey, result: &ResolveResult) {
         // Create cached result
         let cached = CachedResolveResult {
@@ -57,18 +72,31 @@ impl DaemonResolverCache {
                 .unwrap_or_default(),
         };
 
-        // Update reverse index if resolved to a path
-        if let Some(ref resolved_path) = result.resolved {
-            let mut index = self.reverse_index.write().unwrap();
-            index
-                .entry(resolved_path.clone())
-                .or_default()
-                .insert(key.clone());
+        ResolverCache::set(self, key, cached);
+    }
+
+    /// Add a key to the reverse index under every path its entry depends on.
+    fn index(&self, key: &ResolverCacheKey, cached: &CachedResolveResult) {
+        let mut index = self.reverse_index.write().unwrap();
+        for path in dependent_paths(cached) {
+            index.entry(path).or_default().insert(key.clone());
         }
+    }
 
-        // Store in cache
-        let mut entries = self.entries.write().unwrap();
-        entries.insert(key, cached);
+    /// Remove a key from the reverse index under every path its entry depends on.
+    fn unindex(
+        index: &mut HashMap<PathBuf, HashSet<ResolverCacheKey>>,
+        key: &ResolverCacheKey,
+        cached: &CachedResolveResult,
+    ) {
+        for path in dependent_paths(cached) {
+            if let Some(keys) = index.get_mut(&path) {
+                keys.remove(key);
+                if keys.is_empty() {
+                    index.remove(&path);
+                }
+            }
+        }
     }
 
     /// Remove a cache entry by key.
@@ -76,33 +104,30 @@ impl DaemonResolverCache {
         let mut entries = self.entries.write().unwrap();
         if let Some(cached) = entries.remove(key) {
             // Also remove from reverse index
-            if let Some(ref resolved_path) = cached.resolved {
-                let path = PathBuf::from(resolved_path);
-                let mut index = self.reverse_index.write().unwrap();
-                if let Some(keys) = index.get_mut(&path) {
-                    keys.remove(key);
-                    if keys.is_empty() {
-                        index.remove(&path);
-                    }
-                }
-            }
+            let mut index = self.reverse_index.write().unwrap();
+            Self::unindex(&mut index, key, &cached);
         }
     }
 
-    /// Invalidate all cache entries that depend on a given file path.
+    /// Invalidate all cache entries that depend on a path or anything below it.
+    ///
+    /// An entry depends on its resolved file and on every candidate it tried,
+    /// so both removals and newly created files invalidate it. Passing a
+    /// directory invalidates every entry under that directory.
     ///
     /// Returns the number of entries invalidated.
     pub fn invalidate_path(&self, path: &Path) -> usize {
-        // Try to canonicalize the path for consistent matching
-        let canonical = dunce::canonicalize(path).unwrap_or_else(|_| path.to_path_buf());
+        // Removed paths can't be canonicalized; match both forms
+        let canonical = fs_util::canonicalize(path).unwrap_or_else(|_| path.to_path_buf());
 
         // Get keys to invalidate
-        let keys_to_remove: Vec<ResolverCacheKey> = {
+        let keys_to_remove: HashSet<ResolverCacheKey> = {
             let index = self.reverse_index.read().unwrap();
             index
-                .get(&canonical)
-                .map(|keys| keys.iter().cloned().collect())
-                .unwrap_or_default()
+                .iter()
+                .filter(|(indexed, _)| indexed.starts_with(&canonical) || indexed.starts_with(path))
+                .flat_map(|(_, keys)| keys.iter().cloned())
+                .collect()
         };
 
         let count = keys_to_remove.len();
@@ -110,15 +135,16 @@ impl DaemonResolverCache {
         if count > 0 {
             debug!(path = %canonical.display(), count, "Invalidating cache entries for path");
 
-            // Remove from cache
+            // Remove from cache and reverse index
             let mut entries = self.entries.write().unwrap();
+            let mut index = self.reverse_index.write().unwrap();
             for key in &keys_to_remove {
-                entries.remove(key);
+                if let Some(cached) = entries.remove(key) {
+                    Self::unindex(&mut index, key, &cached);
+                }
             }
-
-            // Remove from reverse index
-            let mut index = self.reverse_index.write().unwrap();
-            index.remove(&canonical);
+            self.invalidations
+                .fetch_add(count as u64, Ordering::Relaxed);
         }
 
         count
@@ -132,6 +158,7 @@ impl DaemonResolverCache {
         CacheStats {
             entry_count: entries.len(),
             reverse_index_paths: index.len(),
+            invalidations: self.invalidations.load(Ordering::Relaxed),
         }
     }
 
@@ -165,12 +192,8 @@ impl ResolverCache for DaemonResolverCache {
     }
 
     fn set(&self, key: ResolverCacheKey, value: CachedResolveResult) {
-        // Update reverse index if resolved to a path
-        if let Some(ref resolved_path) = value.resolved {
-            let path = PathBuf::from(resolved_path);
-            let mut index = self.reverse_index.write().unwrap();
-            index.entry(path).or_default().insert(key.clone());
-        }
+        // Index the resolved file and tried candidates
+        self.index(&key, &value);
 
         // Store in cache
         let mut entries = self.entries.write().unwrap();
@@ -183,6 +206,8 @@ impl ResolverCache for DaemonResolverCache {
 pub struct CacheStats {
     pub entry_count: usize,
     pub reverse_index_paths: usize,
+    /// Entries removed by path invalidation since startup.
+    pub invalidations: u64,
 }
 
 /// Cached package.json entry.
@@ -201,6 +226,8 @@ struct CachedPkgJsonEntry {
 pub struct DaemonPkgJsonCache {
     /// Cache entries: canonical path -> cached entry
     entries: RwLock<HashMap<PathBuf, CachedPkgJsonEntry>>,
+    /// Number of entries removed by path invalidation.
+    invalidations: AtomicU64,
 }
 
 impl DaemonPkgJsonCache {
@@ -214,9 +241,31 @@ impl DaemonPkgJsonCache {
     ///
     /// Returns true if an entry was removed.
     pub fn invalidate(&self, path: &Path) -> bool {
-        let canonical = dunce::canonicalize(path).unwrap_or_else(|_| path.to_path_buf());
+        let canonical = fs_util::canonicalize(path).unwrap_or_else(|_| path.to_path_buf());
+        let mut entries = self.entries.write().unwrap();
+        let removed = entries.remove(&canonical).is_some();
+        if removed {
+            self.invalidations.fetch_add(1, Ordering::Relaxed);
+        }
+        removed
+    }
+
+    /// Invalidate every package.json at or below a path.
+    ///
+    /// Used for directory events, e.g. a package removed from `node_modules`.
+    /// Returns the number of entries removed.
+    pub fn invalidate_prefix(&self, path: &Path) -> usize {
+        // Removed paths can't be canonicalized; match both forms
+        let canonical = fs_util::canonicalize(path).unwrap_or_else(|_| path.to_path_buf());
         let mut entries = self.entries.write().unwrap();
-        entries.remove(&canonical).is_some()
+        let before = entries.len();
+        entries.retain(|cached, _| !cached.starts_with(&canonical) && !cached.starts_with(path));
+        let removed = before - entries.len();
+        if removed > 0 {
+            self.invalidations
+                .fetch_add(removed as u64, Ordering::Relaxed);
+        }
+        removed
     }
 
     /// Clear all cache entries.
@@ -231,13 +280,14 @@ impl DaemonPkgJsonCache {
         let entries = self.entries.read().unwrap();
         PkgJsonCacheStats {
             entry_count: entries.len(),
+            invalidations: self.invalidations.load(Ordering::Relaxed),
         }
     }
 }
 
 impl PkgJsonCache for DaemonPkgJsonCache {
     fn get(&self, path: &Path) -> Option<Value> {
-        let canonical = dunce::canonicalize(path).ok()?;
+        let canonical = fs_util::canonicalize(path).ok()?;
         let entries = self.entries.read().unwrap();
         let entry = entries.get(&canonical)?;
 
@@ -252,7 +302,7 @@ impl PkgJsonCache for DaemonPkgJsonCache {
     }
 
     fn set(&self, path: &Path, value: Value) {
-        let Ok(canonical) = dunce::canonicalize(path) else {
+        let Ok(canonical) = fs_util::canonicalize(path) else {
             return;
         };
 
@@ -268,6 +318,8 @@ impl PkgJsonCache for DaemonPkgJsonCache {
 #[derive(Debug, Clone, Copy)]
 pub struct PkgJsonCacheStats {
     pub entry_count: usize,
+    /// Entries removed by path invalidation since startup.
+    pub invalidations: u64,
 }
 
 /// Daemon build cache with thread-safe access.
@@ -321,7 +373,7 @@ impl DaemonBuildCache {
 
     /// Add a file path to the reverse index for a node.
     pub fn add_file_dependency(&self, node_id: &str, path: &Path) {
-        let canonical = dunce::canonicalize(path).unwrap_or_else(|_| path.to_path_buf());
+        let canonical = fs_util::canonicalize(path).unwrap_or_else(|_| path.to_path_buf());
         let mut index = self.reverse_index.write().unwrap();
         index
             .entry(canonical)
@@ -333,7 +385,7 @@ impl DaemonBuildCache {
     ///
     /// Returns the number of entries invalidated.
     pub fn invalidate_path(&self, path: &Path) -> usize {
-        let canonical = dunce::canonicalize(path).unwrap_or_else(|_| path.to_path_buf());
+        let canonical = fs_util::canonicalize(path).unwrap_or_else(|_| path.to_path_buf());
 
         // Get node IDs to invalidate
         let node_ids: Vec<String> = {
@@ -472,7 +524,7 @@ mod tests {
 
         let cache = DaemonResolverCache::new();
         let key = make_key("./dep");
-        let result = make_resolved_result(dunce::canonicalize(&file).unwrap());
+        let result = make_resolved_result(fs_util::canonicalize(&file).unwrap());
 
         cache.put(key, &result);
 
@@ -486,7 +538,7 @@ mod tests {
         let dir = tempdir().unwrap();
         let file = dir.path().join("dep.js");
         fs::write(&file, "export const x = 1;").unwrap();
-        let canonical = dunce::canonicalize(&file).unwrap();
+        let canonical = fs_util::canonicalize(&file).unwrap();
 
         let cache = DaemonResolverCache::new();
         let key = make_key("./dep");
@@ -514,7 +566,7 @@ mod tests {
         let dir = tempdir().unwrap();
         let file = dir.path().join("shared.js");
         fs::write(&file, "export const x = 1;").unwrap();
-        let canonical = dunce::canonicalize(&file).unwrap();
+        let canonical = fs_util::canonicalize(&file).unwrap();
 
         let cache = DaemonResolverCache::new();
 
@@ -548,6 +600,51 @@ mod tests {
         assert!(cache.get(&key2).is_none());
     }
 
+    #[test]
+    fn test_invalidate_created_candidate() {
+        let dir = tempdir().unwrap();
+        let root = fs_util::canonicalize(dir.path()).unwrap();
+        let missing = root.join("dep.ts");
+
+        let cache = DaemonResolverCache::new();
+        let key = make_key("./dep");
+        let mut result = make_unresolved_result();
+        result.tried = vec![root.join("dep"), missing.clone()];
+        cache.put(key.clone(), &result);
+        assert!(cache.get(&key).is_some());
+
+        // Creating a previously tried candidate invalidates the negative entry
+        fs::write(&missing, "export const x = 1;").unwrap();
+        assert_eq!(cache.invalidate_path(&missing), 1);
+        assert!(cache.get(&key).is_none());
+
+        let stats = cache.stats();
+        assert_eq!(stats.reverse_index_paths, 0);
+        assert_eq!(stats.invalidations, 1);
+    }
+
+    #[test]
+    fn test_invalidate_directory_prefix() {
+        let dir = tempdir().unwrap();
+        let root = fs_util::canonicalize(dir.path()).unwrap();
+        let pkg_dir = root.join("node_modules/foo");
+        fs::create_dir_all(&pkg_dir).unwrap();
+        fs::write(pkg_dir.join("index.js"), "").unwrap();
+
+        let cache = DaemonResolverCache::new();
+        cache.put(
+            make_key("foo"),
+            &make_resolved_result(pkg_dir.join("index.js")),
+        );
+        cache.put(make_key("bar"), &make_resolved_result(root.join("bar.js")));
+
+        // Removing the package directory invalidates entries below it only
+        fs::remove_dir_all(&pkg_dir).unwrap();
+        assert_eq!(cache.invalidate_path(&pkg_dir), 1);
+        assert!(cache.get(&make_key("foo")).is_none());
+        assert_eq!(cache.stats().entry_count, 1);
+    }
+
     #[test]
     fn test_clear() {
         let dir = tempdir().unwrap();
@@ -614,6 +711,27 @@ mod tests {
         assert!(cache.get(&file).is_none());
     }
 
+    #[test]
+    fn test_pkg_json_cache_invalidate_prefix() {
+        let dir = tempdir().unwrap();
+        let pkg_dir = dir.path().join("node_modules/foo");
+        fs::create_dir_all(&pkg_dir).unwrap();
+        let file = pkg_dir.join("package.json");
+        fs::write(&file, r#"{"name": "foo"}"#).unwrap();
+        let root_file = dir.path().join("package.json");
+        fs::write(&root_file, r#"{"name": "root"}"#).unwrap();
+
+        let cache = DaemonPkgJsonCache::new();
+        cache.set(&file, serde_json::json!({"name": "foo"}));
+        cache.set(&root_file, serde_json::json!({"name": "root"}));
+
+        let removed = cache.invalidate_prefix(&fs_util::canonicalize(&pkg_dir).unwrap());
+        assert_eq!(removed, 1);
+        assert!(cache.get(&file).is_none());
+        assert!(cache.get(&root_file).is_some());
+        assert_eq!(cache.stats().invalidations, 1);
+    }
+
     #[test]
     fn test_pkg_json_cache_clear() {
         let dir = tempdir().unwrap();
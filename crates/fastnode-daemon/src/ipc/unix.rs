@@ -1,8 +1,13 @@
 //! Unix domain socket IPC implementation.
+//!
+//! The socket file is owner-only (`0600`), and connections are only
+//! accepted from peers whose credentials show the daemon's own user.
 
 use std::io;
+use std::os::unix::fs::PermissionsExt;
 use std::path::Path;
 use tokio::net::{UnixListener, UnixStream};
+use tracing::warn;
 
 /// Unix domain socket listener.
 pub struct IpcListener {
@@ -20,20 +25,47 @@ impl IpcListener {
             std::fs::create_dir_all(parent)?;
         }
 
+        // The default endpoint's directory is already owner-only (see
+        // `paths::ensure_ipc_dir`), so the socket isn't reachable by others
+        // in the moment before its own mode is tightened.
         let inner = UnixListener::bind(path)?;
+        std::fs::set_permissions(path, std::fs::Permissions::from_mode(0o600))?;
         Ok(Self { inner })
     }
 
-    /// Accept a new connection.
+    /// Accept a new connection from the daemon's own user.
+    ///
+    /// Connections from other users, or whose peer credentials can't be
+    /// read, are dropped. This peer check is the real access gate: the
+    /// socket's mode and its `0700` directory only narrow who can reach it,
+    /// and the override endpoint may live anywhere.
     ///
     /// # Errors
     /// Returns an error if accepting fails.
     pub async fn accept(&self) -> io::Result<IpcStream> {
-        let (stream, _addr) = self.inner.accept().await?;
-        Ok(IpcStream { inner: stream })
+        loop {
+            let (stream, _addr) = self.inner.accept().await?;
+            match peer_uid(&stream) {
+                Some(uid) if uid == current_uid() => return Ok(IpcStream { inner: stream }),
+                Some(uid) => warn!(peer_uid = uid, "rejected connection from another user"),
+                None => warn!("rejected connection without peer credentials"),
+            }
+        }
     }
 }
 
+/// User ID of the connected peer (`SO_PEERCRED` or equivalent), `None`
+/// if it can't be read.
+fn peer_uid(stream: &UnixStream) -> Option<u32> {
+    stream.peer_cred().ok().map(|cred| cred.uid())
+}
+
+/// Effective user ID of the daemon.
+fn current_uid() -> u32 {
+    // SAFETY: geteuid has no preconditions and cannot fail
+    unsafe { libc::geteuid() }
+}
+
 /// Unix domain socket stream.
 pub struct IpcStream {
     inner: UnixStream,
@@ -83,3 +115,36 @@ impl tokio::io::AsyncWrite for IpcStream {
         std::pin::Pin::new(&mut self.inner).poll_shutdown(cx)
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tokio::io::{AsyncReadExt, AsyncWriteExt};
+
+    #[tokio::test]
+    async fn test_socket_is_owner_only() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("howth.sock");
+        let path = path.to_str().unwrap();
+
+        let listener = IpcListener::bind(path).unwrap();
+        let mode = std::fs::metadata(path).unwrap().permissions().mode();
+        assert_eq!(mode & 0o777, 0o600);
+
+        // The daemon's own user gets through
+        let client = tokio::spawn({
+            let path = path.to_string();
+            async move {
+                let mut stream = IpcStream::connect(&path).await.unwrap();
+                stream.write_all(b"hi").await.unwrap();
+            }
+        });
+        let mut server = listener.accept().await.unwrap();
+        let mut buf = [0u8; 2];
+        server.read_exact(&mut buf).await.unwrap();
+        assert_eq!(&buf, b"hi");
+        client.await.unwrap();
+
+        assert_eq!(peer_uid(&server.inner), Some(current_uid()));
+    }
+}
@@ -1,9 +1,13 @@
 //! Windows named pipe IPC implementation.
 //!
-//! Uses Tokio's named pipe support for async IPC on Windows.
+//! Uses Tokio's named pipe support for async IPC on Windows. Pipes only
+//! grant access to their owner and SYSTEM, and reject remote clients.
+//! Clients refuse a pipe whose server runs as another user.
 
 use std::io;
+use std::os::windows::io::{AsRawHandle, FromRawHandle, OwnedHandle, RawHandle};
 use std::pin::Pin;
+use std::ptr;
 use std::sync::Arc;
 use std::task::{Context, Poll};
 use std::time::Duration;
@@ -11,6 +15,18 @@ use tokio::net::windows::named_pipe::{
     ClientOptions, NamedPipeClient, NamedPipeServer, ServerOptions,
 };
 use tokio::sync::Mutex;
+use windows_sys::Win32::Foundation::LocalFree;
+use windows_sys::Win32::Security::Authorization::{
+    ConvertStringSecurityDescriptorToSecurityDescriptorW, SDDL_REVISION_1,
+};
+use windows_sys::Win32::Security::{
+    EqualSid, GetTokenInformation, TokenUser, PSECURITY_DESCRIPTOR, SECURITY_ATTRIBUTES,
+    TOKEN_QUERY, TOKEN_USER,
+};
+use windows_sys::Win32::System::Pipes::GetNamedPipeServerProcessId;
+use windows_sys::Win32::System::Threading::{
+    GetCurrentProcess, OpenProcess, OpenProcessToken, PROCESS_QUERY_LIMITED_INFORMATION,
+};
 
 /// Prefix for Windows named pipes.
 const PIPE_PREFIX: &str = r"\\.\pipe\";
@@ -27,6 +43,10 @@ const RETRY_DELAY: Duration = Duration::from_millis(50);
 /// Maximum retries for busy pipe.
 const MAX_RETRIES: u32 = 20;
 
+/// Protected DACL granting full access to the pipe owner (`OW`) and
+/// SYSTEM (`SY`) only.
+const PIPE_SDDL: &str = "D:P(A;;GA;;;OW)(A;;GA;;;SY)";
+
 /// Normalize a pipe endpoint name.
 ///
 /// If the endpoint starts with `\\.\pipe\`, use it as-is.
@@ -60,10 +80,7 @@ impl IpcListener {
         let endpoint = normalize_endpoint(endpoint);
 
         // Create the first server instance
-        let server = ServerOptions::new()
-            .first_pipe_instance(true)
-            .max_instances(MAX_INSTANCES)
-            .create(&endpoint)?;
+        let server = create_server(&endpoint, true)?;
 
         Ok(Self {
             endpoint,
@@ -91,9 +108,7 @@ impl IpcListener {
         server.connect().await?;
 
         // Create a new server instance for the next accept
-        let next_server = ServerOptions::new()
-            .max_instances(MAX_INSTANCES)
-            .create(&self.endpoint)?;
+        let next_server = create_server(&self.endpoint, false)?;
 
         // Store the new instance
         {
@@ -105,6 +120,61 @@ impl IpcListener {
     }
 }
 
+/// Create an owner-only server instance of the pipe.
+#[allow(clippy::cast_possible_truncation)]
+fn create_server(endpoint: &str, first: bool) -> io::Result<NamedPipeServer> {
+    let descriptor = SecurityDescriptor::from_sddl(PIPE_SDDL)?;
+    let mut attributes = SECURITY_ATTRIBUTES {
+        nLength: std::mem::size_of::<SECURITY_ATTRIBUTES>() as u32,
+        lpSecurityDescriptor: descriptor.0,
+        bInheritHandle: 0,
+    };
+
+    let mut options = ServerOptions::new();
+    // The first instance uses `FILE_FLAG_FIRST_PIPE_INSTANCE`, so binding
+    // fails if someone else already created a pipe with this name.
+    options
+        .first_pipe_instance(first)
+        .max_instances(MAX_INSTANCES)
+        .reject_remote_clients(true);
+    // SAFETY: `attributes` and the descriptor it points to outlive the call
+    unsafe {
+        options.create_with_security_attributes_raw(endpoint, ptr::addr_of_mut!(attributes).cast())
+    }
+}
+
+/// Security descriptor allocated by the system, freed on drop.
+struct SecurityDescriptor(PSECURITY_DESCRIPTOR);
+
+impl SecurityDescriptor {
+    fn from_sddl(sddl: &str) -> io::Result<Self> {
+        let sddl: Vec<u16> = sddl.encode_utf16().chain(std::iter::once(0)).collect();
+        let mut descriptor: PSECURITY_DESCRIPTOR = ptr::null_mut();
+        // SAFETY: `sddl` is NUL-terminated and `descriptor` is a valid out pointer
+        let ok = unsafe {
+            ConvertStringSecurityDescriptorToSecurityDescriptorW(
+                sddl.as_ptr(),
+                SDDL_REVISION_1,
+                ptr::addr_of_mut!(descriptor),
+                ptr::null_mut(),
+            )
+        };
+        if ok == 0 {
+            return Err(io::Error::last_os_error());
+        }
+        Ok(Self(descriptor))
+    }
+}
+
+impl Drop for SecurityDescriptor {
+    fn drop(&mut self) {
+        // SAFETY: the descriptor was allocated with LocalAlloc by the system
+        unsafe {
+            LocalFree(self.0);
+        }
+    }
+}
+
 /// Windows named pipe stream.
 ///
 /// Can be either a server-side or client-side pipe.
@@ -130,7 +200,10 @@ impl IpcStream {
 
             loop {
                 match ClientOptions::new().open(&endpoint) {
-                    Ok(client) => return Ok(IpcStream::Client(client)),
+                    Ok(client) => {
+                        verify_server_user(&client)?;
+                        return Ok(IpcStream::Client(client));
+                    }
                     Err(e) if e.raw_os_error() == Some(231) && retries < MAX_RETRIES => {
                         // ERROR_PIPE_BUSY (231) - all pipe instances are busy
                         retries += 1;
@@ -152,6 +225,81 @@ impl IpcStream {
     }
 }
 
+/// Fail unless the pipe's server process runs as the current user.
+///
+/// Pipe names are predictable, so another user could create ours before the
+/// daemon starts; the pipe's DACL protects the server, not the client.
+fn verify_server_user(client: &NamedPipeClient) -> io::Result<()> {
+    let mut pid = 0u32;
+    // SAFETY: the handle is a live pipe client and `pid` a valid out pointer
+    if unsafe { GetNamedPipeServerProcessId(client.as_raw_handle(), ptr::addr_of_mut!(pid)) } == 0 {
+        return Err(io::Error::last_os_error());
+    }
+    // SAFETY: OpenProcess has no preconditions; the result is checked
+    let process = unsafe { OpenProcess(PROCESS_QUERY_LIMITED_INFORMATION, 0, pid) };
+    if process.is_null() {
+        return Err(io::Error::last_os_error());
+    }
+    // SAFETY: `process` is a valid handle we own
+    let process = unsafe { OwnedHandle::from_raw_handle(process) };
+
+    let server = token_user(process.as_raw_handle())?;
+    // SAFETY: GetCurrentProcess has no preconditions
+    let current = token_user(unsafe { GetCurrentProcess() })?;
+    // SAFETY: both buffers hold a TOKEN_USER with a valid SID
+    let same = unsafe {
+        EqualSid(
+            (*server.as_ptr().cast::<TOKEN_USER>()).User.Sid,
+            (*current.as_ptr().cast::<TOKEN_USER>()).User.Sid,
+        )
+    };
+    if same == 0 {
+        return Err(io::Error::new(
+            io::ErrorKind::PermissionDenied,
+            "daemon pipe is owned by another user",
+        ));
+    }
+    Ok(())
+}
+
+/// The `TOKEN_USER` of `process`, in a buffer aligned for it.
+fn token_user(process: RawHandle) -> io::Result<Vec<u64>> {
+    let mut token = ptr::null_mut();
+    // SAFETY: `process` is a valid process handle and `token` a valid out pointer
+    if unsafe { OpenProcessToken(process, TOKEN_QUERY, ptr::addr_of_mut!(token)) } == 0 {
+        return Err(io::Error::last_os_error());
+    }
+    // SAFETY: `token` is a valid handle we own
+    let token = unsafe { OwnedHandle::from_raw_handle(token) };
+
+    let mut len = 0u32;
+    // SAFETY: a null buffer of length 0 only queries the required length
+    unsafe {
+        GetTokenInformation(
+            token.as_raw_handle(),
+            TokenUser,
+            ptr::null_mut(),
+            0,
+            ptr::addr_of_mut!(len),
+        );
+    }
+    let mut buf = vec![0u64; (len as usize).div_ceil(8)];
+    // SAFETY: `buf` holds at least `len` bytes
+    let ok = unsafe {
+        GetTokenInformation(
+            token.as_raw_handle(),
+            TokenUser,
+            buf.as_mut_ptr().cast(),
+            len,
+            ptr::addr_of_mut!(len),
+        )
+    };
+    if ok == 0 {
+        return Err(io::Error::last_os_error());
+    }
+    Ok(buf)
+}
+
 impl tokio::io::AsyncRead for IpcStream {
     fn poll_read(
         self: Pin<&mut Self>,
@@ -208,6 +356,11 @@ mod tests {
         assert_eq!(normalize_endpoint(endpoint), r"\\.\pipe\fastnode-test");
     }
 
+    #[test]
+    fn test_pipe_sddl_parses() {
+        assert!(SecurityDescriptor::from_sddl(PIPE_SDDL).is_ok());
+    }
+
     #[test]
     fn test_normalize_endpoint_short_name() {
         let endpoint = "test";
@@ -43,6 +43,7 @@ pub mod pkg;
 mod server;
 pub mod state;
 pub mod test_worker;
+pub mod transpile_client;
 #[cfg(feature = "runtime")]
 pub mod v8_test_worker;
 pub mod watch;
@@ -50,22 +51,30 @@ pub mod watch;
 pub use cache::{DaemonPkgJsonCache, DaemonResolverCache};
 pub use server::{run_server, DaemonConfig};
 pub use state::DaemonState;
-pub use watch::{WatchError, WatcherState};
+pub use transpile_client::DaemonCompiler;
+pub use watch::{FileChange, WatchError, WatcherState};
 
 use crate::cache::DaemonBuildCache;
 use fastnode_core::build::{
-    build_graph_from_project, execute_graph_with_backend, ExecOptions, BUILD_RUN_SCHEMA_VERSION,
+    build_graph_from_project, execute_graph_with_progress, BuildProgress, CacheStatus,
+    CommandExecution, ExecOptions, ExecutionBackend, PersistentFileHashCache,
+    BUILD_RUN_SCHEMA_VERSION,
+};
+use fastnode_core::compiler::{
+    backend_from_env, on_demand_spec, CompilerBackend, TranspileCache, TranspileSpec,
 };
-use fastnode_core::compiler::CompilerBackend;
 use fastnode_core::config::Channel;
 use fastnode_core::resolver::{
-    resolve_v0, PkgJsonCache, ResolveContext, ResolverCache, ResolverCacheKey, ResolverConfig,
+    resolve_v0, ExportConditions, PkgJsonCache, ResolveContext, ResolverCache, ResolverCacheKey,
+    ResolverConfig,
 };
 use fastnode_core::{build_run_plan, RunPlanInput, RunPlanOutput};
 use fastnode_proto::{
     codes, BuildCacheStatus, BuildErrorInfo, BuildNodeResult, BuildRunCounts, BuildRunResult,
-    BuildRunSummary, FrameResponse, ImportSpec, Request, ResolvedImport, Response, RunPlan,
-    TestCaseResult, TestRunResult, TestStatus, PROTO_SCHEMA_VERSION, TEST_RUN_SCHEMA_VERSION,
+    BuildRunSummary, FrameResponse, ImportGraphSummary, ImportSpec, Request, ResolvedImport,
+    Response, RunPlan, RunScriptPlan, RunScriptStep, TestCaseResult, TestEnvironment,
+    TestRunResult, TestStatus, UnresolvedImportGroup, UnresolvedImportSite, WatchBackend,
+    WatchBackendStatus, WatchCacheStats, PROTO_SCHEMA_VERSION, TEST_RUN_SCHEMA_VERSION,
 };
 use std::path::{Path, PathBuf};
 use std::sync::Arc;
@@ -97,26 +106,56 @@ pub fn handle_request(
     match request {
         Request::Ping { nonce } => (Response::pong(*nonce), false),
         Request::Shutdown => (Response::ShutdownAck, true),
-        Request::Run { entry, args, cwd } => {
+        Request::Run {
+            entry,
+            args,
+            cwd,
+            graph_depth,
+        } => {
             let cache = state.map(|s| s.cache.clone());
             let pkg_json_cache = state.map(|s| s.pkg_json_cache.clone());
+            let channel = state.map_or_else(Channel::default, |s| s.channel);
             (
-                handle_run(entry, args, cwd.as_deref(), cache, pkg_json_cache),
+                handle_run(
+                    entry,
+                    args,
+                    cwd.as_deref(),
+                    *graph_depth,
+                    channel,
+                    cache,
+                    pkg_json_cache,
+                ),
                 false,
             )
         }
-        Request::WatchStart { roots } => {
+        Request::WatchStart {
+            roots,
+            ignore,
+            backend,
+            poll_interval_ms,
+        } => {
             let watcher = state.map(|s| s.watcher.clone());
-            (handle_watch_start(roots, watcher.as_ref()), false)
+            let options = watch_options(*backend, *poll_interval_ms);
+            (
+                handle_watch_start(roots, ignore, options, watcher.as_ref()),
+                false,
+            )
         }
         Request::WatchStop => {
             let watcher = state.map(|s| s.watcher.clone());
             (handle_watch_stop(watcher.as_ref()), false)
         }
-        Request::WatchStatus => {
-            let watcher = state.map(|s| s.watcher.clone());
-            (handle_watch_status(watcher.as_ref()), false)
-        }
+        Request::WatchStatus => (handle_watch_status(state.map(AsRef::as_ref)), false),
+        // PkgPack can be handled sync (no network I/O)
+        Request::PkgPack {
+            cwd,
+            destination,
+            dry_run,
+            source_date_epoch,
+        } => (
+            pkg::handle_pkg_pack(cwd, destination.as_deref(), *dry_run, *source_date_epoch),
+            false,
+        ),
         // PkgGraph can be handled sync (no network I/O)
         Request::PkgGraph {
             cwd,
@@ -144,19 +183,23 @@ pub fn handle_request(
             parent,
             channel,
             kind,
+            conditions,
+            platform,
         } => {
             let pkg_json_cache = state.map(|s| s.pkg_json_cache.clone());
-            (
-                handle_pkg_explain(
+            let response = match ExportConditions::new(conditions, platform.as_deref()) {
+                Ok(conditions) => handle_pkg_explain(
                     specifier,
                     cwd,
                     parent,
                     channel,
                     kind,
+                    &conditions,
                     pkg_json_cache.as_ref(),
                 ),
-                false,
-            )
+                Err(message) => Response::error(codes::PKG_EXPLAIN_PLATFORM_INVALID, message),
+            };
+            (response, false)
         }
         // PkgWhy can be handled sync (no network I/O)
         Request::PkgWhy {
@@ -169,6 +212,7 @@ pub fn handle_request(
             include_trace,
             trace_kind,
             trace_parent,
+            dependents,
             ..
         } => {
             let pkg_json_cache = state.map(|s| s.pkg_json_cache.clone());
@@ -182,6 +226,7 @@ pub fn handle_request(
                 include_trace: *include_trace,
                 trace_kind: trace_kind.as_deref(),
                 trace_parent: trace_parent.as_deref(),
+                dependents: *dependents,
             };
             (handle_pkg_why(opts, pkg_json_cache.as_ref()), false)
         }
@@ -214,9 +259,12 @@ pub fn handle_request(
             max_parallel,
             profile,
             targets,
+            remote_exec,
+            source_date_epoch,
+            ..
         } => {
             let build_cache = state.map(|s| s.build_cache.clone());
-            let compiler = state.map(|s| s.compiler.clone());
+            let compiler = state.map(|s| s.build_compiler());
             (
                 handle_build(
                     cwd,
@@ -225,12 +273,20 @@ pub fn handle_request(
                     *max_parallel,
                     *profile,
                     targets,
+                    remote_exec.as_deref(),
+                    *source_date_epoch,
+                    None,
                     build_cache,
                     compiler,
+                    None,
                 ),
                 false,
             )
         }
+        // On-demand transpile for the native runtime's module loader
+        Request::Transpile { path, source, spec } => {
+            (handle_transpile(path, source, spec.as_ref(), state), false)
+        }
         // WatchBuild requires streaming handler (v3.0)
         Request::WatchBuild { .. } => (
             Response::error(
@@ -239,6 +295,14 @@ pub fn handle_request(
             ),
             false,
         ),
+        // WatchSubscribe requires streaming handler
+        Request::WatchSubscribe { .. } => (
+            Response::error(
+                codes::INTERNAL_ERROR,
+                "WatchSubscribe requires streaming handler",
+            ),
+            false,
+        ),
         // RunTests needs async handler (tokio mutex + worker I/O)
         Request::RunTests { .. } => (
             Response::error(codes::INTERNAL_ERROR, "RunTests requires async handler"),
@@ -252,6 +316,7 @@ pub fn handle_request(
         | Request::PkgPublish { .. }
         | Request::PkgCacheList { .. }
         | Request::PkgCachePrune { .. }
+        | Request::PkgCacheDedupe { .. }
         | Request::PkgInstall { .. } => (
             Response::error(
                 codes::INTERNAL_ERROR,
@@ -291,8 +356,9 @@ pub async fn handle_request_async(
             cwd,
             channel,
             save_dev,
+            auto_install_peers,
         } => (
-            pkg::handle_pkg_add(specs, cwd, channel, *save_dev).await,
+            pkg::handle_pkg_add(specs, cwd, channel, *save_dev, *auto_install_peers).await,
             false,
         ),
         Request::PkgRemove {
@@ -311,14 +377,24 @@ pub async fn handle_request_async(
         ),
         Request::PkgCacheList { channel } => (pkg::handle_pkg_cache_list(channel), false),
         Request::PkgCachePrune { channel } => (pkg::handle_pkg_cache_prune(channel), false),
+        Request::PkgCacheDedupe { channel } => (pkg::handle_pkg_cache_dedupe(channel), false),
         Request::PkgInstall {
             cwd,
             channel,
             frozen,
             include_dev,
             include_optional,
+            auto_install_peers,
         } => (
-            pkg::handle_pkg_install(cwd, channel, *frozen, *include_dev, *include_optional).await,
+            pkg::handle_pkg_install(
+                cwd,
+                channel,
+                *frozen,
+                *include_dev,
+                *include_optional,
+                *auto_install_peers,
+            )
+            .await,
             false,
         ),
         Request::PkgOutdated { cwd, channel } => {
@@ -331,6 +407,8 @@ pub async fn handle_request_async(
             dry_run,
             tag,
             access,
+            otp,
+            provenance,
         } => (
             pkg::handle_pkg_publish(
                 cwd,
@@ -339,6 +417,8 @@ pub async fn handle_request_async(
                 *dry_run,
                 tag.as_deref(),
                 access.as_deref(),
+                otp.as_deref(),
+                *provenance,
             )
             .await,
             false,
@@ -349,8 +429,20 @@ pub async fn handle_request_async(
             setup,
             timeout_ms,
             force_exit,
+            keep_tmp,
+            environment,
         } => (
-            handle_run_tests(cwd, files, setup.as_ref(), *timeout_ms, *force_exit, _state).await,
+            handle_run_tests(
+                cwd,
+                files,
+                setup.as_ref(),
+                *timeout_ms,
+                *force_exit,
+                *keep_tmp,
+                *environment,
+                _state,
+            )
+            .await,
             false,
         ),
         // Non-async operations - should not reach here, but handle gracefully
@@ -364,13 +456,34 @@ pub async fn handle_request_async(
     }
 }
 
+/// Watcher options for a `WatchStart` request.
+fn watch_options(backend: WatchBackend, poll_interval_ms: Option<u64>) -> watch::WatchOptions {
+    let mut poll = watch::PollConfig::default();
+    if let Some(ms) = poll_interval_ms {
+        poll.interval = std::time::Duration::from_millis(ms.max(1));
+    }
+    watch::WatchOptions {
+        backend: match backend {
+            WatchBackend::Auto => watch::WatchBackend::Auto,
+            WatchBackend::Native => watch::WatchBackend::Native,
+            WatchBackend::Poll => watch::WatchBackend::Poll,
+        },
+        poll,
+    }
+}
+
 /// Handle a `WatchStart` request.
-fn handle_watch_start(roots: &[String], watcher: Option<&Arc<WatcherState>>) -> Response {
+fn handle_watch_start(
+    roots: &[String],
+    ignore: &[String],
+    options: watch::WatchOptions,
+    watcher: Option<&Arc<WatcherState>>,
+) -> Response {
     let Some(watcher) = watcher else {
         return Response::error(codes::WATCH_UNSUPPORTED, "File watcher is not enabled");
     };
 
-    match watcher.start(roots.to_vec()) {
+    match watcher.start(roots.to_vec(), ignore, options) {
         Ok(()) => Response::WatchStarted {
             roots: roots.to_vec(),
         },
@@ -433,6 +546,7 @@ fn handle_pkg_explain(
     parent: &str,
     channel: &str,
     kind: &str,
+    conditions: &ExportConditions,
     pkg_json_cache: Option<&Arc<DaemonPkgJsonCache>>,
 ) -> Response {
     use fastnode_core::resolver::NoPkgJsonCache;
@@ -442,7 +556,7 @@ fn handle_pkg_explain(
     let cache_ref: &dyn PkgJsonCache =
         pkg_json_cache.map_or(&no_cache as &dyn PkgJsonCache, |c| c.as_ref());
 
-    pkg::handle_pkg_explain(specifier, cwd, parent, channel, kind, cache_ref)
+    pkg::handle_pkg_explain(specifier, cwd, parent, channel, kind, conditions, cache_ref)
 }
 
 /// Handle a `PkgWhy` request.
@@ -476,23 +590,101 @@ fn handle_pkg_doctor(
 }
 
 /// Handle a `WatchStatus` request.
-fn handle_watch_status(watcher: Option<&Arc<WatcherState>>) -> Response {
-    let Some(watcher) = watcher else {
+fn handle_watch_status(state: Option<&DaemonState>) -> Response {
+    let Some(state) = state else {
         return Response::WatchStatus {
             roots: Vec::new(),
             running: false,
             last_event_unix_ms: None,
+            ignore: Vec::new(),
+            caches: WatchCacheStats::default(),
+            backend: None,
         };
     };
 
+    let resolver = state.cache.stats();
+    let pkg_json = state.pkg_json_cache.stats();
+    let transpile = state.transpile_cache.stats();
+    let watcher = &state.watcher;
     Response::WatchStatus {
         roots: watcher.roots(),
         running: watcher.is_running(),
         last_event_unix_ms: watcher.last_event_unix_ms(),
+        ignore: watcher.ignore_patterns(),
+        caches: WatchCacheStats {
+            resolver_entries: resolver.entry_count as u64,
+            resolver_invalidations: resolver.invalidations,
+            pkg_json_entries: pkg_json.entry_count as u64,
+            pkg_json_invalidations: pkg_json.invalidations,
+            transpile_entries: transpile.entries as u64,
+            transpile_hits: transpile.hits,
+            transpile_misses: transpile.misses,
+        },
+        backend: watcher.backend_status().map(|status| WatchBackendStatus {
+            backend: match status.backend {
+                watch::WatchBackend::Auto => WatchBackend::Auto,
+                watch::WatchBackend::Native => WatchBackend::Native,
+                watch::WatchBackend::Poll => WatchBackend::Poll,
+            },
+            #[allow(clippy::cast_possible_truncation)]
+            poll_interval_ms: status.poll_interval.map(|d| d.as_millis() as u64),
+            content_hashing: status.content_hashing,
+            fallback_reason: status.fallback_reason,
+        }),
+    }
+}
+
+/// Handle a `Transpile` request with the warm compiler and transpile cache.
+///
+/// `spec` is the client's serialized `TranspileSpec`; without one the module
+/// is compiled for direct execution. Without daemon state (tests), a fresh
+/// backend and cache are used.
+fn handle_transpile(
+    path: &str,
+    source: &str,
+    spec: Option<&serde_json::Value>,
+    state: Option<&Arc<DaemonState>>,
+) -> Response {
+    let spec = match spec {
+        Some(value) => match serde_json::from_value::<TranspileSpec>(value.clone()) {
+            Ok(spec) => spec,
+            Err(e) => {
+                return Response::error(
+                    codes::TRANSPILE_FAILED,
+                    format!("{path}: invalid transpile spec: {e}"),
+                )
+            }
+        },
+        None => on_demand_spec(Path::new(path)),
+    };
+    let result = match state {
+        Some(state) => {
+            state
+                .transpile_cache
+                .get_or_transpile_output(state.compiler.as_ref(), &spec, source)
+        }
+        None => TranspileCache::new().get_or_transpile_output(
+            backend_from_env().as_ref(),
+            &spec,
+            source,
+        ),
+    };
+    match result {
+        Ok((output, cached)) => Response::TranspileResult {
+            code: output.code.clone(),
+            cached,
+            source_map: output.source_map.clone(),
+        },
+        Err(e) => Response::error(codes::TRANSPILE_FAILED, format!("{path}: {e}")),
     }
 }
 
 /// Handle a `Build` request (v2.0, targets v2.1).
+///
+/// When `changed_paths` is set (watch rebuilds), only planned nodes affected
+/// by those paths are executed and the paths are listed in the run notes.
+/// `remote_exec` runs script nodes through that executor command.
+/// `progress` is called as each node starts and finishes.
 fn handle_build(
     cwd: &str,
     force: bool,
@@ -500,8 +692,12 @@ fn handle_build(
     max_parallel: u32,
     _profile: bool,
     targets: &[String],
+    remote_exec: Option<&str>,
+    source_date_epoch: Option<u64>,
+    changed_paths: Option<&[PathBuf]>,
     build_cache: Option<Arc<DaemonBuildCache>>,
     compiler: Option<Arc<dyn CompilerBackend>>,
+    progress: Option<&mut dyn FnMut(BuildProgress<'_>)>,
 ) -> Response {
     // Validate cwd
     let cwd_path = PathBuf::from(cwd);
@@ -551,13 +747,40 @@ fn handle_build(
         }
     };
 
+    // Watch rebuilds only run the planned nodes affected by the changed paths
+    let mut scoped_targets = Vec::new();
+    let mut notes = Vec::new();
+    if let Some(changed) = changed_paths {
+        notes.push(triggered_by_note(changed, &cwd_path));
+        scoped_targets = graph
+            .affected_nodes(changed)
+            .into_iter()
+            .filter(|id| plan.nodes.contains(id))
+            .collect();
+        if scoped_targets.is_empty() {
+            let mut run_result = fastnode_core::build::BuildRunResult::new(cwd);
+            run_result.set_targets(plan.requested_targets);
+            run_result.finalize(graph.nodes.len() as u32);
+            run_result.notes = notes;
+            run_result
+                .notes
+                .push("no nodes affected by the changed files".to_string());
+            return Response::BuildResult {
+                result: convert_build_result(run_result, cwd),
+            };
+        }
+    }
+
     // Set up execution options
     let options = ExecOptions {
         force,
         dry_run,
         max_parallel: max_parallel as usize,
-        profile: false,      // TODO: wire up profiling
-        targets: Vec::new(), // Empty = run all nodes
+        profile: false,          // TODO: wire up profiling
+        targets: scoped_targets, // Empty = run all nodes
+        executor: remote_exec
+            .map(|command| Arc::new(CommandExecution::new(command)) as Arc<dyn ExecutionBackend>),
+        source_date_epoch,
     };
 
     // Create a wrapper cache that implements BuildCache trait
@@ -568,18 +791,43 @@ fn handle_build(
     // Get the compiler backend reference for transpile nodes (v3.1)
     let backend_ref: Option<&dyn CompilerBackend> = compiler.as_ref().map(|c| c.as_ref());
 
+    // Persistent file hashes let unchanged files skip re-hashing across restarts
+    let file_hashes = PersistentFileHashCache::open(&cwd_path);
+
+    let mut no_progress = |_: BuildProgress<'_>| {};
+    let on_progress = progress.unwrap_or(&mut no_progress);
+
     // Execute only the planned nodes (filtered by targets)
     // TODO: Use plan.nodes for filtered execution
     // For now, execute the full graph but set requested_targets
     let result = match wrapper_cache.as_mut() {
-        Some(cache) => execute_graph_with_backend(&graph, Some(cache), &options, backend_ref),
-        None => execute_graph_with_backend(&graph, None, &options, backend_ref),
+        Some(cache) => execute_graph_with_progress(
+            &graph,
+            Some(cache),
+            &options,
+            backend_ref,
+            Some(&file_hashes),
+            on_progress,
+        ),
+        None => execute_graph_with_progress(
+            &graph,
+            None,
+            &options,
+            backend_ref,
+            Some(&file_hashes),
+            on_progress,
+        ),
     };
 
+    if let Err(e) = file_hashes.save() {
+        warn!(error = %e, "failed to save file hash cache");
+    }
+
     match result {
         Ok(mut run_result) => {
             // Set the requested targets (v2.1)
             run_result.set_targets(plan.requested_targets);
+            run_result.notes.extend(notes);
 
             // Register file dependencies with the build cache for invalidation
             if let Some(ref cache) = build_cache {
@@ -593,14 +841,68 @@ fn handle_build(
             }
 
             // Convert to protocol types
-            Response::BuildResult {
-                result: convert_build_result(run_result, cwd),
+            let result = convert_build_result(run_result, cwd);
+            if !result.ok {
+                save_failed_build(&cwd_path, &result);
             }
+            Response::BuildResult { result }
         }
         Err(e) => Response::error(codes::BUILD_HASH_IO_ERROR, e.to_string()),
     }
 }
 
+/// Keep the latest failed build for diagnostics bundles.
+fn save_failed_build(cwd: &Path, result: &BuildRunResult) {
+    let saved = serde_json::to_vec_pretty(result)
+        .map_err(std::io::Error::other)
+        .and_then(|json| {
+            let path = cwd.join(fastnode_core::build::LAST_FAILED_BUILD_PATH);
+            if let Some(parent) = path.parent() {
+                std::fs::create_dir_all(parent)?;
+            }
+            fastnode_util::fs::atomic_write(&path, &json)
+        });
+    if let Err(e) = saved {
+        warn!(error = %e, "failed to save failed build result");
+    }
+}
+
+/// Protocol event for a build progress update.
+fn build_progress_response(progress: BuildProgress<'_>) -> Response {
+    let status = match progress.result {
+        None => "started",
+        Some(result) if result.cache == CacheStatus::Skipped => "skipped",
+        Some(result) if !result.ok => "failed",
+        Some(result) if result.cache == CacheStatus::Hit => "cached",
+        Some(_) => "built",
+    };
+    Response::BuildProgress {
+        node_id: progress.node_id.to_string(),
+        status: status.to_string(),
+        completed: progress.completed,
+        total: progress.total,
+    }
+}
+
+/// Maximum number of changed paths listed in a watch rebuild note.
+const MAX_TRIGGER_PATHS_SHOWN: usize = 10;
+
+/// Describe the paths that triggered a watch rebuild, relative to `cwd`.
+fn triggered_by_note(changed: &[PathBuf], cwd: &Path) -> String {
+    let mut shown: Vec<String> = changed
+        .iter()
+        .take(MAX_TRIGGER_PATHS_SHOWN)
+        .map(|path| path.strip_prefix(cwd).unwrap_or(path).display().to_string())
+        .collect();
+    if changed.len() > MAX_TRIGGER_PATHS_SHOWN {
+        shown.push(format!(
+            "and {} more",
+            changed.len() - MAX_TRIGGER_PATHS_SHOWN
+        ));
+    }
+    format!("triggered by: {}", shown.join(", "))
+}
+
 /// Wrapper to implement BuildCache trait for DaemonBuildCache.
 struct BuildCacheWrapper(Arc<DaemonBuildCache>);
 
@@ -686,7 +988,31 @@ fn convert_build_result(result: fastnode_core::build::BuildRunResult, cwd: &str)
             stderr_truncated: r.stderr_truncated,
             notes: r.notes,
             files_count: r.files_count,
+            changed_count: r.changed_count,
             auto_discovered: r.auto_discovered,
+            diagnostics: r
+                .diagnostics
+                .into_iter()
+                .map(|d| fastnode_proto::BuildDiagnostic {
+                    file: d.file,
+                    line: d.line,
+                    column: d.column,
+                    code: d.code,
+                    severity: match d.severity {
+                        fastnode_core::build::DiagnosticSeverity::Error => {
+                            fastnode_proto::BuildDiagnosticSeverity::Error
+                        }
+                        fastnode_core::build::DiagnosticSeverity::Warning => {
+                            fastnode_proto::BuildDiagnosticSeverity::Warning
+                        }
+                        fastnode_core::build::DiagnosticSeverity::Info => {
+                            fastnode_proto::BuildDiagnosticSeverity::Info
+                        }
+                    },
+                    message: d.message,
+                })
+                .collect(),
+            log_path: r.log_path,
         })
         .collect();
 
@@ -726,9 +1052,11 @@ async fn handle_run_tests(
     setup: Option<&String>,
     timeout_ms: Option<u64>,
     force_exit: bool,
+    keep_tmp: bool,
+    environment: TestEnvironment,
     state: Option<&Arc<DaemonState>>,
 ) -> Response {
-    use crate::test_worker::TranspiledTestFile;
+    use crate::test_worker::{docblock_environment, TranspiledTestFile};
     use fastnode_core::compiler::TranspileSpec;
 
     let cwd_path = PathBuf::from(cwd);
@@ -755,7 +1083,7 @@ async fn handle_run_tests(
 
     // Transpile all files in parallel using rayon
     let compiler = &state.compiler;
-    let results: Vec<Result<TranspiledTestFile, (String, String)>> = {
+    let results: Vec<Result<TranspiledTestFile, (&str, String)>> = {
         use rayon::prelude::*;
         files
             .par_iter()
@@ -771,28 +1099,44 @@ async fn handle_run_tests(
 
                 let source = std::fs::read_to_string(&path).map_err(|e| {
                     (
-                        file_path.clone(),
+                        codes::TEST_TRANSPILE_FAILED,
                         format!("Failed to read {file_path}: {e}"),
                     )
                 })?;
 
+                // A docblock pragma overrides the run's environment
+                let file_environment = match docblock_environment(&source) {
+                    None => environment,
+                    Some(Ok(env)) => env,
+                    Some(Err(name)) => {
+                        return Err((
+                            codes::TEST_ENVIRONMENT_INVALID,
+                            format!(
+                                "Unknown test environment `{name}` in {file_path} (expected `node` or `dom`)"
+                            ),
+                        ));
+                    }
+                };
+
                 if needs_transpile {
                     let out_path = path.with_extension("mjs");
                     let spec = TranspileSpec::new(&path, &out_path);
                     let output = compiler.transpile(&spec, &source).map_err(|e| {
                         (
-                            file_path.clone(),
+                            codes::TEST_TRANSPILE_FAILED,
                             format!("Failed to transpile {file_path}: {e}"),
                         )
                     })?;
                     Ok(TranspiledTestFile {
                         path: file_path.clone(),
                         code: output.code.replace("howth:mocha", &mocha_shim_str),
+                        environment: file_environment,
                     })
                 } else {
                     Ok(TranspiledTestFile {
                         path: file_path.clone(),
                         code: source.replace("howth:mocha", &mocha_shim_str),
+                        environment: file_environment,
                     })
                 }
             })
@@ -804,8 +1148,8 @@ async fn handle_run_tests(
     for result in results {
         match result {
             Ok(file) => transpiled.push(file),
-            Err((_path, msg)) => {
-                return Response::error(codes::TEST_TRANSPILE_FAILED, msg);
+            Err((code, msg)) => {
+                return Response::error(code, msg);
             }
         }
     }
@@ -840,6 +1184,7 @@ async fn handle_run_tests(
                     TranspiledTestFile {
                         path: setup_path.clone(),
                         code,
+                        environment,
                     },
                 );
             }
@@ -860,7 +1205,7 @@ async fn handle_run_tests(
     // Try native V8 test worker first, fall back to Node.js worker
     #[cfg(feature = "runtime")]
     let result = {
-        let v8_result = try_v8_test_worker(state, &transpiled, timeout_ms);
+        let v8_result = try_v8_test_worker(state, &transpiled, timeout_ms, keep_tmp);
         match v8_result {
             Ok(result) => result,
             Err(v8_err) if v8_err.kind() == std::io::ErrorKind::TimedOut => {
@@ -892,6 +1237,8 @@ async fn handle_run_tests(
 
     #[cfg(not(feature = "runtime"))]
     let result = {
+        // test.tmpdir() and the DOM environment only exist in the V8 runtime
+        let _ = keep_tmp;
         match run_tests_node_worker(state, transpiled, timeout_ms, force_exit).await {
             Ok(r) => r,
             Err(e) => {
@@ -923,6 +1270,9 @@ fn worker_response_to_response(cwd: &str, result: crate::test_worker::WorkerResp
             },
             duration_ms: t.duration_ms,
             error: t.error,
+            diff: t.diff,
+            retries: t.retries,
+            flaky: t.flaky,
         })
         .collect();
 
@@ -948,6 +1298,7 @@ fn try_v8_test_worker(
     state: &Arc<DaemonState>,
     files: &[crate::test_worker::TranspiledTestFile],
     timeout_ms: Option<u64>,
+    keep_tmp: bool,
 ) -> Result<crate::test_worker::WorkerResponse, std::io::Error> {
     let mut guard = state
         .v8_test_worker
@@ -972,7 +1323,7 @@ fn try_v8_test_worker(
             .as_millis()
     );
 
-    worker.run_tests(id, files.to_vec(), timeout_ms)
+    worker.run_tests(id, files.to_vec(), timeout_ms, keep_tmp)
 }
 
 /// Run tests via the Node.js test worker (fallback path).
@@ -1002,6 +1353,8 @@ fn handle_run(
     entry: &str,
     args: &[String],
     cwd: Option<&str>,
+    graph_depth: Option<u32>,
+    channel: Channel,
     cache: Option<Arc<DaemonResolverCache>>,
     pkg_json_cache: Option<Arc<DaemonPkgJsonCache>>,
 ) -> Response {
@@ -1024,7 +1377,8 @@ fn handle_run(
         cwd: cwd_path,
         entry: PathBuf::from(entry),
         args: args.to_vec(),
-        channel: Channel::Stable, // TODO: make configurable
+        channel,
+        graph_depth,
     };
 
     match build_run_plan(input.clone()) {
@@ -1136,7 +1490,7 @@ fn resolve_imports_with_cache(
 
 /// Convert core's `RunPlanOutput` to proto's `RunPlan`.
 fn convert_to_proto_plan(output: RunPlanOutput) -> RunPlan {
-    let mut plan = RunPlan::new(
+    let plan = RunPlan::new(
         output.resolved_cwd,
         output.requested_entry,
         output.resolved_entry,
@@ -1167,8 +1521,60 @@ fn convert_to_proto_plan(output: RunPlanOutput) -> RunPlan {
         })
         .collect();
 
-    plan = plan.with_imports(imports, resolved_imports);
-    plan
+    plan.with_imports(imports, resolved_imports)
+        .with_graph(output.graph.map(convert_graph_summary))
+        .with_script(output.script.map(convert_script_plan))
+}
+
+/// Convert core's `ScriptPlanOutput` to proto's `RunScriptPlan`.
+fn convert_script_plan(script: fastnode_core::ScriptPlanOutput) -> RunScriptPlan {
+    RunScriptPlan {
+        name: script.name,
+        package_json: script.package_json,
+        command: script.command,
+        steps: script
+            .steps
+            .into_iter()
+            .map(|step| RunScriptStep {
+                event: step.event,
+                command: step.command,
+            })
+            .collect(),
+        bin_paths: script.bin_paths,
+        shell: script.shell,
+        shadowed_file: script.shadowed_file,
+    }
+}
+
+/// Convert core's `ImportGraphSummary` to proto's.
+fn convert_graph_summary(graph: fastnode_core::ImportGraphSummary) -> ImportGraphSummary {
+    ImportGraphSummary {
+        depth_limit: graph.depth_limit,
+        max_depth: graph.max_depth,
+        files: graph.files,
+        imports: graph.imports,
+        local: graph.local,
+        external: graph.external,
+        unresolved: graph
+            .unresolved
+            .into_iter()
+            .map(|group| UnresolvedImportGroup {
+                reason: group.reason,
+                count: group.count,
+                sites: group
+                    .sites
+                    .into_iter()
+                    .map(|site| UnresolvedImportSite {
+                        from: site.from,
+                        raw: site.raw,
+                        line: site.line,
+                    })
+                    .collect(),
+            })
+            .collect(),
+        cycles: graph.cycles,
+        truncated: graph.truncated,
+    }
 }
 
 /// Create a response frame.
@@ -1226,6 +1632,7 @@ mod tests {
                 entry: "main.js".to_string(),
                 args: vec!["--flag".to_string()],
                 cwd: Some(dir.path().to_string_lossy().into_owned()),
+                graph_depth: None,
             },
             PROTO_SCHEMA_VERSION,
             None,
@@ -1244,6 +1651,131 @@ mod tests {
         }
     }
 
+    #[test]
+    fn test_handle_run_script_plan() {
+        let dir = tempdir().unwrap();
+        std::fs::write(
+            dir.path().join("package.json"),
+            r#"{"scripts":{"test":"vitest","posttest":"echo ok"}}"#,
+        )
+        .unwrap();
+
+        let (resp, _) = handle_request(
+            &Request::Run {
+                entry: "test".to_string(),
+                args: vec![],
+                cwd: Some(dir.path().to_string_lossy().into_owned()),
+                graph_depth: None,
+            },
+            PROTO_SCHEMA_VERSION,
+            None,
+        );
+
+        match resp {
+            Response::RunPlan { plan } => {
+                assert_eq!(plan.entry_kind, "script");
+                let script = plan.script.expect("script plan");
+                assert_eq!(script.command, "vitest");
+                let events: Vec<_> = script.steps.iter().map(|s| s.event.as_str()).collect();
+                assert_eq!(events, ["test", "posttest"]);
+            }
+            _ => panic!("Expected RunPlan"),
+        }
+    }
+
+    #[test]
+    fn test_handle_transpile_uses_warm_cache() {
+        let state = Arc::new(DaemonState::new());
+        let request = Request::Transpile {
+            path: "/app/main.ts".to_string(),
+            source: "const port: number = 3000;".to_string(),
+            spec: None,
+        };
+
+        for expected_cached in [false, true] {
+            let (resp, _) = handle_request(&request, PROTO_SCHEMA_VERSION, Some(&state));
+            match resp {
+                Response::TranspileResult { code, cached, .. } => {
+                    assert!(code.contains("const port = 3000"));
+                    assert_eq!(cached, expected_cached);
+                }
+                _ => panic!("Expected TranspileResult"),
+            }
+        }
+
+        let (resp, _) = handle_request(
+            &Request::Transpile {
+                path: "/app/bad.ts".to_string(),
+                source: "const = ;".to_string(),
+                spec: None,
+            },
+            PROTO_SCHEMA_VERSION,
+            Some(&state),
+        );
+        assert!(matches!(resp, Response::Error { code, .. } if code == codes::TRANSPILE_FAILED));
+    }
+
+    #[test]
+    fn test_handle_transpile_with_client_spec() {
+        use fastnode_core::compiler::SourceMapKind;
+
+        let state = Arc::new(DaemonState::new());
+        let mut spec = TranspileSpec::new("/app/main.ts", "/app/main.ts");
+        spec.sourcemaps = SourceMapKind::External;
+        let request = Request::Transpile {
+            path: "/app/main.ts".to_string(),
+            source: "const port: number = 3000;".to_string(),
+            spec: Some(serde_json::to_value(&spec).unwrap()),
+        };
+
+        for expected_cached in [false, true] {
+            let (resp, _) = handle_request(&request, PROTO_SCHEMA_VERSION, Some(&state));
+            match resp {
+                Response::TranspileResult {
+                    cached, source_map, ..
+                } => {
+                    assert_eq!(cached, expected_cached);
+                    assert!(source_map.is_some());
+                }
+                _ => panic!("Expected TranspileResult"),
+            }
+        }
+
+        // The same source with the on-demand spec is a separate entry
+        let (resp, _) = handle_request(
+            &Request::Transpile {
+                path: "/app/main.ts".to_string(),
+                source: "const port: number = 3000;".to_string(),
+                spec: None,
+            },
+            PROTO_SCHEMA_VERSION,
+            Some(&state),
+        );
+        assert!(matches!(
+            resp,
+            Response::TranspileResult { cached: false, .. }
+        ));
+
+        let (resp, _) = handle_request(&Request::WatchStatus, PROTO_SCHEMA_VERSION, Some(&state));
+        let Response::WatchStatus { caches, .. } = resp else {
+            panic!("Expected WatchStatus");
+        };
+        assert_eq!(caches.transpile_entries, 2);
+        assert_eq!(caches.transpile_hits, 1);
+        assert_eq!(caches.transpile_misses, 2);
+
+        let (resp, _) = handle_request(
+            &Request::Transpile {
+                path: "/app/main.ts".to_string(),
+                source: String::new(),
+                spec: Some(serde_json::json!({ "module": 42 })),
+            },
+            PROTO_SCHEMA_VERSION,
+            Some(&state),
+        );
+        assert!(matches!(resp, Response::Error { code, .. } if code == codes::TRANSPILE_FAILED));
+    }
+
     #[test]
     fn test_handle_run_with_imports() {
         let dir = tempdir().unwrap();
@@ -1260,6 +1792,7 @@ mod tests {
                 entry: "main.js".to_string(),
                 args: vec![],
                 cwd: Some(dir.path().to_string_lossy().into_owned()),
+                graph_depth: None,
             },
             PROTO_SCHEMA_VERSION,
             Some(&state),
@@ -1283,6 +1816,7 @@ mod tests {
                 entry: "main.js".to_string(),
                 args: vec![],
                 cwd: Some(dir.path().to_string_lossy().into_owned()),
+                graph_depth: None,
             },
             PROTO_SCHEMA_VERSION,
             Some(&state),
@@ -1297,6 +1831,39 @@ mod tests {
         }
     }
 
+    #[test]
+    fn test_handle_run_with_graph_depth() {
+        let dir = tempdir().unwrap();
+        std::fs::write(dir.path().join("main.js"), "import './a.js';").unwrap();
+        std::fs::write(
+            dir.path().join("a.js"),
+            "import './main.js';\nimport './gone.js';",
+        )
+        .unwrap();
+
+        let (resp, _) = handle_request(
+            &Request::Run {
+                entry: "main.js".to_string(),
+                args: vec![],
+                cwd: Some(dir.path().to_string_lossy().into_owned()),
+                graph_depth: Some(u32::MAX),
+            },
+            PROTO_SCHEMA_VERSION,
+            None,
+        );
+
+        match resp {
+            Response::RunPlan { plan } => {
+                let graph = plan.graph.unwrap();
+                assert_eq!(graph.files, 2);
+                assert_eq!(graph.unresolved[0].reason, "NOT_FOUND");
+                assert_eq!(graph.unresolved[0].sites[0].from, "a.js");
+                assert_eq!(graph.cycles, vec![vec!["a.js", "main.js"]]);
+            }
+            _ => panic!("Expected RunPlan"),
+        }
+    }
+
     #[test]
     fn test_handle_run_missing_entry() {
         let dir = tempdir().unwrap();
@@ -1306,6 +1873,7 @@ mod tests {
                 entry: "nonexistent.js".to_string(),
                 args: vec![],
                 cwd: Some(dir.path().to_string_lossy().into_owned()),
+                graph_depth: None,
             },
             PROTO_SCHEMA_VERSION,
             None,
@@ -1327,6 +1895,7 @@ mod tests {
                 entry: "main.js".to_string(),
                 args: vec![],
                 cwd: Some("/nonexistent/path/that/does/not/exist".to_string()),
+                graph_depth: None,
             },
             PROTO_SCHEMA_VERSION,
             None,
@@ -1351,10 +1920,16 @@ mod tests {
                 roots,
                 running,
                 last_event_unix_ms,
+                ignore,
+                caches,
+                backend,
             } => {
-                assert!(roots.is_empty());
+                assert_eq!(roots, Vec::<String>::new());
                 assert!(!running);
                 assert!(last_event_unix_ms.is_none());
+                assert_eq!(ignore, Vec::<String>::new());
+                assert_eq!(caches, WatchCacheStats::default());
+                assert!(backend.is_none());
             }
             _ => panic!("Expected WatchStatus"),
         }
@@ -1365,6 +1940,9 @@ mod tests {
         let (resp, shutdown) = handle_request(
             &Request::WatchStart {
                 roots: vec!["/tmp".to_string()],
+                ignore: Vec::new(),
+                backend: WatchBackend::Auto,
+                poll_interval_ms: None,
             },
             PROTO_SCHEMA_VERSION,
             None,
@@ -1405,12 +1983,103 @@ mod tests {
                 roots,
                 running,
                 last_event_unix_ms,
+                ignore,
+                caches,
+                backend,
             } => {
-                assert!(roots.is_empty());
+                assert_eq!(roots, Vec::<String>::new());
                 assert!(!running);
                 assert!(last_event_unix_ms.is_none());
+                assert!(backend.is_none());
+                // Built-in ignores are always reported
+                assert!(ignore.contains(&"node_modules/".to_string()));
+                assert_eq!(caches.resolver_entries, 0);
+                assert_eq!(caches.pkg_json_invalidations, 0);
             }
             _ => panic!("Expected WatchStatus"),
         }
     }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_handle_build_with_remote_exec() {
+        let dir = tempdir().unwrap();
+        std::fs::write(
+            dir.path().join("package.json"),
+            r#"{"scripts":{"build":"echo built"}}"#,
+        )
+        .unwrap();
+
+        let resp = handle_build(
+            &dir.path().to_string_lossy(),
+            false,
+            false,
+            1,
+            false,
+            &[],
+            Some(r#"test -f "$HOWTH_REMOTE_JOB" && sh -c "$HOWTH_REMOTE_COMMAND""#),
+            None,
+            None,
+            None,
+            None,
+            None,
+        );
+
+        let Response::BuildResult { result } = resp else {
+            panic!("Expected BuildResult, got {resp:?}");
+        };
+        assert!(result.ok);
+        let node = &result.results[0];
+        assert!(
+            node.notes
+                .iter()
+                .any(|note| note.starts_with("remote: uploaded")),
+            "{:?}",
+            node.notes
+        );
+    }
+
+    #[test]
+    fn test_handle_build_reports_progress() {
+        let dir = tempdir().unwrap();
+        std::fs::write(
+            dir.path().join("package.json"),
+            r#"{"scripts":{"build":"echo built"}}"#,
+        )
+        .unwrap();
+
+        let mut events = Vec::new();
+        let resp = handle_build(
+            &dir.path().to_string_lossy(),
+            false,
+            false,
+            1,
+            false,
+            &[],
+            None,
+            None,
+            None,
+            None,
+            None,
+            Some(&mut |progress| events.push(build_progress_response(progress))),
+        );
+
+        assert!(matches!(resp, Response::BuildResult { .. }), "{resp:?}");
+        let statuses: Vec<(String, u32, u32)> = events
+            .into_iter()
+            .map(|event| match event {
+                Response::BuildProgress {
+                    status,
+                    completed,
+                    total,
+                    ..
+                } => (status, completed, total),
+                other => panic!("Expected BuildProgress, got {other:?}"),
+            })
+            .collect();
+        assert_eq!(
+            statuses,
+            [("started".to_string(), 0, 1), ("built".to_string(), 1, 1)]
+        );
+    }
 }
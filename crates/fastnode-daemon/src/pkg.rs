@@ -1,74 +1,49 @@
 //! Package manager handlers for the daemon.
 //!
-//! Handles `PkgAdd`, `PkgCacheList`, `PkgCachePrune`, `PkgGraph`, `PkgExplain`, `PkgWhy`, `PkgDoctor`,
-//! and `PkgInstall` requests.
+//! Handles `PkgAdd`, `PkgCacheList`, `PkgCachePrune`, `PkgCacheDedupe`, `PkgGraph`, `PkgExplain`,
+//! `PkgWhy`, `PkgDoctor`, and `PkgInstall` requests.
 
 use fastnode_core::config::Channel;
 use fastnode_core::pkg::{
-    add_dependency_to_package_json, build_doctor_report, build_pkg_graph, detect_workspaces,
-    download_tarball, extract_tgz_atomic, find_workspace_root, format_pnpm_key, get_tarball_url,
-    link_into_node_modules, link_into_node_modules_direct, link_into_node_modules_with_version,
-    link_package_binaries, link_package_dependencies, lockfile_content_hash, read_package_deps,
-    remove_dependency_from_package_json, resolve_dependencies, resolve_version, version_satisfies,
-    why_from_graph, write_lockfile, DoctorOptions, DoctorSeverity, GraphOptions, LockPackage,
-    Lockfile, PackageCache, PackageSpec, PkgError, PkgWhyResult as CorePkgWhyResult,
-    RegistryClient, ResolveOptions, WhyOptions, LOCKFILE_NAME, MAX_TARBALL_SIZE,
+    add_dependency_to_package_json, build_doctor_report, build_pkg_graph, detect_node_version,
+    detect_workspaces, extract_tgz_atomic_with_store, find_workspace_root, format_pnpm_key,
+    get_tarball_url, is_valid_range, link_hoisted_package, link_into_node_modules,
+    link_into_node_modules_direct, link_into_node_modules_with_version, link_package_binaries,
+    link_package_dependencies, link_pnpm_top_level, load_npmrc_files, lockfile_codes,
+    read_package_deps, remove_dependency_from_package_json, remove_installed_package,
+    resolve_dependencies, resolve_version, why_from_graph, write_lockfile, DoctorOptions,
+    DoctorSeverity, GraphOptions, LockPackage, Lockfile, NodeLinker, NodeModulesLayout,
+    PackageCache, PackageSpec, PkgError, PkgWhyResult as CorePkgWhyResult, RegistryClient,
+    ResolveOptions, WhyOptions, LOCKFILE_NAME, MAX_TARBALL_SIZE,
 };
 use fastnode_core::resolver::{
-    resolve_with_trace, PkgJsonCache, ResolutionKind, ResolveContext, ResolverConfig,
+    resolve_with_trace, ExportConditions, PkgJsonCache, ResolutionKind, ResolveContext,
+    ResolverConfig,
 };
 use fastnode_proto::{
-    codes, CachedPackage, DoctorCounts, DoctorFinding, DoctorSummary, GraphDepEdge, GraphErrorInfo,
-    GraphPackageId, GraphPackageNode, InstallPackageError, InstallPackageInfo, InstallSummary,
-    InstalledPackage, PackageGraph, PkgDoctorReport, PkgErrorInfo, PkgExplainResult,
-    PkgExplainTraceStep, PkgExplainWarning, PkgInstallResult, PkgWhyChain, PkgWhyErrorInfo,
-    PkgWhyLink, PkgWhyResult, PkgWhyTarget, Response, UpdatedPackage, PKG_DOCTOR_SCHEMA_VERSION,
-    PKG_EXPLAIN_SCHEMA_VERSION, PKG_GRAPH_SCHEMA_VERSION, PKG_INSTALL_SCHEMA_VERSION,
-    PKG_WHY_SCHEMA_VERSION,
+    codes, CachedPackage, DoctorCounts, DoctorFinding, DoctorSummary, FileStoreInfo, GraphDepEdge,
+    GraphErrorInfo, GraphPackageId, GraphPackageNode, InstallPackageError, InstallPackageInfo,
+    InstallSummary, InstalledPackage, PackFileInfo, PackageGraph, PkgDoctorReport, PkgErrorInfo,
+    PkgExplainResult, PkgExplainTraceStep, PkgExplainWarning, PkgInstallResult, PkgWhyChain,
+    PkgWhyDependent, PkgWhyDependents, PkgWhyErrorInfo, PkgWhyLink, PkgWhyResult, PkgWhyTarget,
+    Response, UpdatedPackage, PKG_DOCTOR_SCHEMA_VERSION, PKG_EXPLAIN_SCHEMA_VERSION,
+    PKG_GRAPH_SCHEMA_VERSION, PKG_INSTALL_SCHEMA_VERSION, PKG_WHY_SCHEMA_VERSION,
 };
+use fastnode_util::fs as fs_util;
 use std::path::Path;
 use tracing::{debug, warn};
 
-/// Find the best matching version for a dependency in the lockfile.
-///
-/// When multiple versions of the same package exist (e.g. `entities@4.5.0` and
-/// `entities@6.0.1`), uses the semver range to pick the correct one. Falls back
-/// to the first name match if range parsing fails.
-fn find_best_match(lockfile: &Lockfile, dep_name: &str, dep_range: &str) -> Option<String> {
-    let candidates: Vec<&LockPackage> = lockfile
-        .packages
-        .iter()
-        .filter_map(|(k, pkg)| {
-            k.rsplit_once('@')
-                .and_then(|(n, _)| if n == dep_name { Some(pkg) } else { None })
-        })
-        .collect();
-
-    if candidates.is_empty() {
-        return None;
-    }
-    if candidates.len() == 1 {
-        return Some(candidates[0].version.clone());
-    }
-
-    // Multiple versions — use semver to find the right one
-    for pkg in &candidates {
-        if version_satisfies(&pkg.version, dep_range) {
-            return Some(pkg.version.clone());
-        }
-    }
-
-    // Fallback: return first match (legacy behavior)
-    Some(candidates[0].version.clone())
+/// Parse a channel string to Channel enum, defaulting to stable.
+fn parse_channel(channel: &str) -> Channel {
+    channel.parse().unwrap_or_default()
 }
 
-/// Parse a channel string to Channel enum.
-fn parse_channel(channel: &str) -> Channel {
-    match channel.to_lowercase().as_str() {
-        "dev" => Channel::Dev,
-        "nightly" => Channel::Nightly,
-        _ => Channel::Stable,
-    }
+/// Whether resolution should install missing peers: the request flag wins,
+/// then `.npmrc` (`auto-install-peers` / `legacy-peer-deps`), then npm 7+'s
+/// default of installing them.
+fn auto_install_peers(project_root: &Path, flag: Option<bool>) -> bool {
+    flag.or_else(|| load_npmrc_files(project_root).auto_install_peers)
+        .unwrap_or(true)
 }
 
 /// Handle a PkgAdd request.
@@ -77,6 +52,7 @@ pub async fn handle_pkg_add(
     cwd: &str,
     channel: &str,
     save_dev: bool,
+    auto_install_peers_flag: Option<bool>,
 ) -> Response {
     let project_root = Path::new(cwd);
     let package_json_path = project_root.join("package.json");
@@ -150,10 +126,20 @@ pub async fn handle_pkg_add(
         let resolve_opts = ResolveOptions {
             include_dev: true,
             include_optional: false,
+            auto_install_peers: auto_install_peers(project_root, auto_install_peers_flag),
         };
 
         match resolve_dependencies(project_root, &registry, &resolve_opts).await {
             Ok(result) => {
+                // Peer conflicts are reported even though the packages were added
+                for peer in result.lockfile.peer_conflicts() {
+                    errors.push(PkgErrorInfo {
+                        spec: peer.package.clone(),
+                        code: lockfile_codes::PKG_LOCK_CONFLICT.to_string(),
+                        message: peer.message(),
+                    });
+                }
+
                 if let Err(e) = write_lockfile(project_root, &result.lockfile) {
                     warn!(error = %e, "Failed to write lockfile");
                     // Don't add to errors - packages were installed successfully
@@ -199,8 +185,12 @@ async fn add_single_package(
     debug!(name = %spec.name, version = %version, "Resolved version");
 
     // Determine version range for package.json
-    // If user specified a range, use it; otherwise use "^{resolved_version}"
-    let version_range = spec.range.clone().unwrap_or_else(|| format!("^{version}"));
+    // If user specified a range, use it; for no range or a dist-tag use "^{resolved_version}"
+    let version_range = spec
+        .range
+        .clone()
+        .filter(|range| is_valid_range(range))
+        .unwrap_or_else(|| format!("^{version}"));
 
     // Check if already cached
     let package_dir = cache.package_dir(&spec.name, &version);
@@ -214,21 +204,32 @@ async fn add_single_package(
             PkgError::download_failed(format!("No tarball URL for {}@{}", spec.name, version))
         })?;
 
+        let integrity = packument
+            .get("versions")
+            .and_then(|v| v.get(&version))
+            .and_then(|v| v.get("dist"))
+            .and_then(|d| d.get("integrity").or_else(|| d.get("shasum")))
+            .and_then(|v| v.as_str());
+
         debug!(url = %tarball_url, "Downloading tarball");
 
-        // Download tarball (with auth token for scoped registries)
-        let auth_token = registry.auth_token_for(&spec.name);
-        let bytes =
-            download_tarball(registry.http(), tarball_url, MAX_TARBALL_SIZE, auth_token).await?;
+        // Download tarball from a mirror or the registry and verify it
+        let tarball = registry
+            .download_verified_tarball(&spec.name, tarball_url, integrity, MAX_TARBALL_SIZE)
+            .await?;
+        let bytes = tarball.bytes;
 
-        debug!(size = bytes.len(), "Downloaded tarball");
+        debug!(size = bytes.len(), source = %tarball.source.label(), "Downloaded tarball");
 
         // Extract to cache (offload CPU-bound decompression to thread pool)
         let extract_bytes = bytes.clone();
         let extract_dest = package_dir.clone();
-        tokio::task::spawn_blocking(move || extract_tgz_atomic(&extract_bytes, &extract_dest))
-            .await
-            .map_err(|e| PkgError::extract_failed(format!("Extraction task failed: {e}")))??;
+        let store = cache.file_store();
+        tokio::task::spawn_blocking(move || {
+            extract_tgz_atomic_with_store(&extract_bytes, &extract_dest, store.as_ref())
+        })
+        .await
+        .map_err(|e| PkgError::extract_failed(format!("Extraction task failed: {e}")))??;
 
         debug!(path = %package_dir.display(), "Extracted to cache");
     }
@@ -297,7 +298,7 @@ pub async fn handle_pkg_remove(packages: &[String], cwd: &str, channel: &str) ->
                     // Remove from node_modules
                     let pkg_path = node_modules.join(pkg_name);
                     if pkg_path.exists() {
-                        if let Err(e) = std::fs::remove_dir_all(&pkg_path) {
+                        if let Err(e) = fastnode_util::fs::remove_within(&node_modules, &pkg_path) {
                             warn!(name = %pkg_name, error = %e, "Failed to remove from node_modules");
                             // Don't fail the whole operation, package.json was updated
                         } else {
@@ -331,6 +332,7 @@ pub async fn handle_pkg_remove(packages: &[String], cwd: &str, channel: &str) ->
         let resolve_opts = ResolveOptions {
             include_dev: true,
             include_optional: false,
+            auto_install_peers: auto_install_peers(project_root, None),
         };
 
         match resolve_dependencies(project_root, &registry, &resolve_opts).await {
@@ -400,6 +402,20 @@ pub async fn handle_pkg_update(
         }
     };
 
+    // Remember which dependencies are devDependencies so --latest keeps them there
+    let dev_names: std::collections::HashSet<String> = match (
+        read_package_deps(&package_json_path, false, false),
+        read_package_deps(&package_json_path, true, false),
+    ) {
+        (Ok(prod), Ok(with_dev)) => with_dev
+            .deps
+            .into_iter()
+            .map(|(name, _)| name)
+            .filter(|name| !prod.deps.iter().any(|(p, _)| p == name))
+            .collect(),
+        _ => std::collections::HashSet::new(),
+    };
+
     // Filter to specific packages if provided
     let deps_to_check: Vec<(String, String)> = if packages.is_empty() {
         all_deps
@@ -457,7 +473,7 @@ pub async fn handle_pkg_update(
                                     &package_json_path,
                                     &name,
                                     &new_range,
-                                    false, // We don't know if it was dev, but add to deps
+                                    dev_names.contains(&name),
                                 ) {
                                     warn!(error = %e, "Failed to update package.json");
                                 }
@@ -498,6 +514,7 @@ pub async fn handle_pkg_update(
         let resolve_opts = ResolveOptions {
             include_dev: true,
             include_optional: false,
+            auto_install_peers: auto_install_peers(project_root, None),
         };
 
         match resolve_dependencies(project_root, &registry, &resolve_opts).await {
@@ -528,9 +545,101 @@ pub async fn handle_pkg_update(
     }
 }
 
+/// Maximum number of packuments fetched concurrently by `pkg outdated`.
+const MAX_CONCURRENT_OUTDATED_FETCHES: usize = 16;
+
+/// A dependency declared by the root or a workspace package.
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct DeclaredDep {
+    /// Name as declared (the alias for `npm:` aliases).
+    name: String,
+    /// Registry package name.
+    package: String,
+    /// Declared semver range.
+    range: String,
+    /// Dependency type: "dep", "dev" or "optional".
+    dep_type: &'static str,
+    /// Declaring workspace package (`None` for the root).
+    workspace: Option<String>,
+}
+
+/// Check that a declared range refers to a registry version.
+///
+/// Protocol specs (`workspace:`, `file:`, `link:`, git and tarball URLs) and
+/// GitHub shorthands have no wanted/latest version.
+fn is_registry_range(range: &str) -> bool {
+    !range.contains(':') && !range.contains('/')
+}
+
+/// Read the registry dependencies declared in a package.json, classified by
+/// section. `dependencies` wins over `devDependencies`, which wins over
+/// `optionalDependencies`.
+fn read_declared_deps(
+    package_json_path: &Path,
+    workspace: Option<&str>,
+) -> Result<Vec<DeclaredDep>, PkgError> {
+    let prod = read_package_deps(package_json_path, false, false)?;
+    let with_dev = read_package_deps(package_json_path, true, false)?;
+    let with_optional = read_package_deps(package_json_path, false, true)?;
+
+    let prod_names: std::collections::HashSet<&str> =
+        prod.deps.iter().map(|(n, _)| n.as_str()).collect();
+    let sections = [
+        (&prod, "dep"),
+        (&with_dev, "dev"),
+        (&with_optional, "optional"),
+    ];
+
+    let mut declared = Vec::new();
+    for (deps, dep_type) in sections {
+        for (name, range) in &deps.deps {
+            if dep_type != "dep" && prod_names.contains(name.as_str()) {
+                continue;
+            }
+            if !is_registry_range(range) {
+                continue;
+            }
+            declared.push(DeclaredDep {
+                name: name.clone(),
+                package: deps.aliases.get(name).unwrap_or(name).clone(),
+                range: range.clone(),
+                dep_type,
+                workspace: workspace.map(str::to_string),
+            });
+        }
+    }
+    Ok(declared)
+}
+
+/// Installed version of a dependency: the lockfile entry, falling back to the
+/// package.json in `node_modules` (workspace first, then root).
+fn installed_version(
+    name: &str,
+    lockfile: Option<&Lockfile>,
+    search_roots: &[&Path],
+) -> Option<String> {
+    let locked = lockfile
+        .and_then(|lf| lf.dependencies.get(name))
+        .and_then(|dep| dep.resolved.split('@').next_back().map(str::to_string));
+    locked.or_else(|| {
+        search_roots.iter().find_map(|root| {
+            let content =
+                std::fs::read_to_string(root.join("node_modules").join(name).join("package.json"))
+                    .ok()?;
+            let pkg: serde_json::Value = serde_json::from_str(&content).ok()?;
+            pkg.get("version")?.as_str().map(str::to_string)
+        })
+    })
+}
+
 /// Handle a PkgOutdated request.
+///
+/// Checks the root package and, in a monorepo, every workspace package.
+/// Packuments are fetched concurrently, once per package name.
 pub async fn handle_pkg_outdated(cwd: &str, channel: &str) -> Response {
     use fastnode_proto::OutdatedPackage;
+    use futures::stream::{self, StreamExt};
+    use std::collections::HashMap;
 
     let project_root = Path::new(cwd);
     let package_json_path = project_root.join("package.json");
@@ -562,75 +671,95 @@ pub async fn handle_pkg_outdated(cwd: &str, channel: &str) -> Response {
         );
     };
 
-    // Read dependencies from package.json
-    let deps_result = read_package_deps(&package_json_path, true, true);
-    let (all_deps, dev_deps) = match deps_result {
-        Ok(result) => {
-            // Also read dev deps to classify them
-            let dev_deps_result = read_package_deps(&package_json_path, true, false);
-            let dev_names: std::collections::HashSet<String> = dev_deps_result
-                .map(|r| r.deps.into_iter().map(|(n, _)| n).collect())
-                .unwrap_or_default();
-            (result.deps, dev_names)
-        }
+    // Collect declared dependencies from the root and workspace packages
+    let mut declared = match read_declared_deps(&package_json_path, None) {
+        Ok(deps) => deps,
         Err(e) => {
             return Response::error(e.code().to_string(), e.to_string());
         }
     };
+    let workspaces = detect_workspaces(project_root);
+    let mut workspace_dirs: HashMap<String, std::path::PathBuf> = HashMap::new();
+    if let Some(config) = &workspaces {
+        let mut packages: Vec<_> = config.packages.values().collect();
+        packages.sort_by(|a, b| a.name.cmp(&b.name));
+        for ws in packages {
+            match read_declared_deps(&ws.path.join("package.json"), Some(&ws.name)) {
+                Ok(deps) => declared.extend(deps),
+                Err(e) => warn!(workspace = %ws.name, error = %e, "Failed to read workspace deps"),
+            }
+            workspace_dirs.insert(ws.name.clone(), ws.path.clone());
+        }
+        // Dependencies on sibling workspace packages are linked, not fetched
+        declared.retain(|dep| !config.is_workspace_package(&dep.package));
+    }
+
+    // Fetch each packument once, concurrently
+    let mut names: Vec<String> = declared.iter().map(|d| d.package.clone()).collect();
+    names.sort();
+    names.dedup();
+    let packuments: HashMap<String, serde_json::Value> = stream::iter(names)
+        .map(|name| {
+            let registry = &registry;
+            async move {
+                let result = registry.fetch_packument(&name).await;
+                (name, result)
+            }
+        })
+        .buffer_unordered(MAX_CONCURRENT_OUTDATED_FETCHES)
+        .filter_map(|(name, result)| async move {
+            match result {
+                Ok(packument) => Some((name, packument)),
+                Err(e) => {
+                    warn!(name = %name, error = %e, "Failed to fetch packument for outdated check");
+                    None
+                }
+            }
+        })
+        .collect()
+        .await;
 
     let mut outdated = Vec::new();
     let mut up_to_date_count = 0u32;
 
-    for (name, range) in all_deps {
-        // Get current installed version from lockfile
-        let current_version = lockfile
-            .as_ref()
-            .and_then(|lf| lf.dependencies.get(&name))
-            .and_then(|dep| dep.resolved.split('@').next_back().map(|s| s.to_string()));
-
-        let current = current_version.unwrap_or_else(|| "none".to_string());
-
-        // Fetch packument to check for updates
-        match registry.fetch_packument(&name).await {
-            Ok(packument) => {
-                // Resolve wanted version (within semver range)
-                let wanted = resolve_version(&packument, Some(&range))
-                    .map(|v| v.to_string())
-                    .unwrap_or_else(|_| current.clone());
-
-                // Resolve latest version (any version)
-                let latest = resolve_version(&packument, None)
-                    .map(|v| v.to_string())
-                    .unwrap_or_else(|_| current.clone());
-
-                // Determine dep type
-                let dep_type = if dev_deps.contains(&name) {
-                    "dev"
-                } else {
-                    "dep"
-                };
+    for dep in declared {
+        let Some(packument) = packuments.get(&dep.package) else {
+            continue;
+        };
 
-                // Check if outdated
-                if current != wanted || current != latest {
-                    outdated.push(OutdatedPackage {
-                        name,
-                        current,
-                        wanted,
-                        latest,
-                        dep_type: dep_type.to_string(),
-                    });
-                } else {
-                    up_to_date_count += 1;
-                }
-            }
-            Err(e) => {
-                warn!(name = %name, error = %e, "Failed to fetch packument for outdated check");
-            }
+        let workspace_dir = dep.workspace.as_ref().and_then(|ws| workspace_dirs.get(ws));
+        let search_roots: Vec<&Path> = workspace_dir
+            .map(std::path::PathBuf::as_path)
+            .into_iter()
+            .chain(std::iter::once(project_root))
+            .collect();
+        let current = installed_version(&dep.name, lockfile.as_ref(), &search_roots)
+            .unwrap_or_else(|| "none".to_string());
+
+        // Resolve wanted version (max satisfying the declared range)
+        let wanted =
+            resolve_version(packument, Some(&dep.range)).unwrap_or_else(|_| current.clone());
+
+        // Resolve latest version (dist-tags.latest)
+        let latest = resolve_version(packument, None).unwrap_or_else(|_| current.clone());
+
+        // Check if outdated
+        if current != wanted || current != latest {
+            outdated.push(OutdatedPackage {
+                name: dep.name,
+                current,
+                wanted,
+                latest,
+                dep_type: dep.dep_type.to_string(),
+                workspace: dep.workspace,
+            });
+        } else {
+            up_to_date_count += 1;
         }
     }
 
-    // Suppress unused variable warning
-    let _ = channel;
+    // Root dependencies first, then by workspace and name
+    outdated.sort_by(|a, b| (&a.workspace, &a.name).cmp(&(&b.workspace, &b.name)));
 
     Response::PkgOutdatedResult {
         outdated,
@@ -638,9 +767,66 @@ pub async fn handle_pkg_outdated(cwd: &str, channel: &str) -> Response {
     }
 }
 
+/// Convert packed files to their protocol form.
+fn pack_file_infos(files: &[fastnode_core::pkg::PackFile]) -> Vec<PackFileInfo> {
+    files
+        .iter()
+        .map(|f| PackFileInfo {
+            path: f.path.clone(),
+            size: f.size,
+        })
+        .collect()
+}
+
+/// Check npm output for a one-time password challenge.
+fn is_otp_challenge(output: &str) -> bool {
+    output.contains("EOTP") || output.contains("one-time password")
+}
+
+/// Handle a PkgPack request.
+pub fn handle_pkg_pack(
+    cwd: &str,
+    destination: Option<&str>,
+    dry_run: bool,
+    source_date_epoch: Option<u64>,
+) -> Response {
+    match fastnode_core::pkg::pack(
+        Path::new(cwd),
+        destination.map(Path::new),
+        dry_run,
+        source_date_epoch,
+    ) {
+        Ok(result) => Response::PkgPackResult {
+            ok: true,
+            files: pack_file_infos(&result.files),
+            name: result.name,
+            version: result.version,
+            filename: result.filename,
+            path: result
+                .tarball_path
+                .map(|p| p.to_string_lossy().into_owned()),
+            unpacked_size: result.unpacked_size,
+            tarball_size: result.tarball_size,
+            error: None,
+        },
+        Err(e) => Response::PkgPackResult {
+            ok: false,
+            name: String::new(),
+            version: String::new(),
+            filename: String::new(),
+            path: None,
+            files: Vec::new(),
+            unpacked_size: 0,
+            tarball_size: 0,
+            error: Some(e.to_string()),
+        },
+    }
+}
+
 /// Handle a PkgPublish request.
 ///
-/// Uses npm CLI under the hood for reliable publishing.
+/// Packs the tarball with the same file rules as `pkg pack`, then hands it to
+/// the npm CLI for the registry upload. Dry runs stop after packing.
 pub async fn handle_pkg_publish(
     cwd: &str,
     registry_url: Option<&str>,
@@ -648,81 +834,74 @@ pub async fn handle_pkg_publish(
     dry_run: bool,
     tag: Option<&str>,
     access: Option<&str>,
+    otp: Option<&str>,
+    provenance: bool,
 ) -> Response {
     use std::process::Command;
 
     let project_root = Path::new(cwd);
-    let package_json_path = project_root.join("package.json");
+    let registry = registry_url.unwrap_or("https://registry.npmjs.org");
+    let tag = tag.unwrap_or("latest");
 
-    // Read package.json
-    let package_json_content = match std::fs::read_to_string(&package_json_path) {
-        Ok(c) => c,
-        Err(e) => {
-            return Response::PkgPublishResult {
-                ok: false,
-                name: String::new(),
-                version: String::new(),
-                registry: String::new(),
-                tag: String::new(),
-                tarball_size: 0,
-                files_count: 0,
-                error: Some(format!("Failed to read package.json: {e}")),
-            };
+    let failed = |name: String, version: String, error: String, otp_required: bool| {
+        Response::PkgPublishResult {
+            ok: false,
+            name,
+            version,
+            registry: registry.to_string(),
+            tag: tag.to_string(),
+            tarball_size: 0,
+            files_count: 0,
+            error: Some(error),
+            files: Vec::new(),
+            unpacked_size: 0,
+            otp_required,
         }
     };
 
-    let package_json: serde_json::Value = match serde_json::from_str(&package_json_content) {
-        Ok(v) => v,
+    // Pack into a temp dir so npm publishes exactly what we report
+    let tmp = match tempfile::tempdir() {
+        Ok(t) => t,
         Err(e) => {
-            return Response::PkgPublishResult {
-                ok: false,
-                name: String::new(),
-                version: String::new(),
-                registry: String::new(),
-                tag: String::new(),
-                tarball_size: 0,
-                files_count: 0,
-                error: Some(format!("Failed to parse package.json: {e}")),
-            };
+            return failed(
+                String::new(),
+                String::new(),
+                format!("Failed to create temp dir: {e}"),
+                false,
+            );
         }
     };
+    let dest = if dry_run { None } else { Some(tmp.path()) };
+    let packed = match fastnode_core::pkg::pack(project_root, dest, dry_run, None) {
+        Ok(p) => p,
+        Err(e) => return failed(String::new(), String::new(), e.to_string(), false),
+    };
 
-    let name = package_json
-        .get("name")
-        .and_then(|v| v.as_str())
-        .unwrap_or("")
-        .to_string();
-    let version = package_json
-        .get("version")
-        .and_then(|v| v.as_str())
-        .unwrap_or("")
-        .to_string();
-
-    if name.is_empty() || version.is_empty() {
-        return Response::PkgPublishResult {
-            ok: false,
-            name,
-            version,
-            registry: String::new(),
-            tag: String::new(),
-            tarball_size: 0,
-            files_count: 0,
-            error: Some("package.json must have name and version fields".to_string()),
-        };
-    }
+    let files = pack_file_infos(&packed.files);
+    #[allow(clippy::cast_possible_truncation)]
+    let files_count = files.len() as u32;
+    let succeeded = |packed: fastnode_core::pkg::PackResult, files| Response::PkgPublishResult {
+        ok: true,
+        name: packed.name,
+        version: packed.version,
+        registry: registry.to_string(),
+        tag: tag.to_string(),
+        tarball_size: packed.tarball_size,
+        files_count,
+        error: None,
+        files,
+        unpacked_size: packed.unpacked_size,
+        otp_required: false,
+    };
 
-    let registry = registry_url.unwrap_or("https://registry.npmjs.org");
-    let tag = tag.unwrap_or("latest");
+    let Some(tarball_path) = packed.tarball_path.clone() else {
+        return succeeded(packed, files);
+    };
 
     // Build npm publish command
     let mut cmd = Command::new("npm");
-    cmd.arg("publish");
+    cmd.arg("publish").arg(&tarball_path);
     cmd.current_dir(project_root);
-
-    if dry_run {
-        cmd.arg("--dry-run");
-    }
-
     cmd.arg("--tag").arg(tag);
 
     if let Some(reg) = registry_url {
@@ -733,84 +912,46 @@ pub async fn handle_pkg_publish(
         cmd.arg("--access").arg(acc);
     }
 
+    if let Some(code) = otp {
+        cmd.arg("--otp").arg(code);
+    }
+
+    if provenance {
+        cmd.arg("--provenance");
+    }
+
     // Set token via environment if provided
     if let Some(tok) = token {
         cmd.env("NPM_TOKEN", tok);
     }
 
     debug!(
-        name = %name,
-        version = %version,
-        dry_run = dry_run,
+        name = %packed.name,
+        version = %packed.version,
+        tarball = %tarball_path.display(),
         "Running npm publish"
     );
 
     match cmd.output() {
+        Ok(output) if output.status.success() => succeeded(packed, files),
         Ok(output) => {
             let stdout = String::from_utf8_lossy(&output.stdout);
             let stderr = String::from_utf8_lossy(&output.stderr);
-
-            if output.status.success() {
-                // Try to parse npm pack output for file count/size (best effort)
-                let files_count = stdout
-                    .lines()
-                    .find(|l| l.contains("files:"))
-                    .and_then(|l| l.split_whitespace().last())
-                    .and_then(|s| s.parse().ok())
-                    .unwrap_or(0u32);
-
-                let tarball_size = stdout
-                    .lines()
-                    .find(|l| l.contains("size:") || l.contains("unpacked size"))
-                    .and_then(|l| {
-                        l.split_whitespace()
-                            .find(|s| s.chars().all(|c| c.is_ascii_digit()))
-                    })
-                    .and_then(|s| s.parse().ok())
-                    .unwrap_or(0u64);
-
-                Response::PkgPublishResult {
-                    ok: true,
-                    name,
-                    version,
-                    registry: registry.to_string(),
-                    tag: tag.to_string(),
-                    tarball_size,
-                    files_count,
-                    error: None,
-                }
-            } else {
-                Response::PkgPublishResult {
-                    ok: false,
-                    name,
-                    version,
-                    registry: registry.to_string(),
-                    tag: tag.to_string(),
-                    tarball_size: 0,
-                    files_count: 0,
-                    error: Some(format!(
-                        "npm publish failed: {}",
-                        if stderr.is_empty() {
-                            stdout.to_string()
-                        } else {
-                            stderr.to_string()
-                        }
-                    )),
-                }
-            }
+            let otp_required = is_otp_challenge(&stderr) || is_otp_challenge(&stdout);
+            let detail = if stderr.is_empty() { stdout } else { stderr };
+            failed(
+                packed.name,
+                packed.version,
+                format!("npm publish failed: {detail}"),
+                otp_required,
+            )
         }
-        Err(e) => Response::PkgPublishResult {
-            ok: false,
-            name,
-            version,
-            registry: registry.to_string(),
-            tag: tag.to_string(),
-            tarball_size: 0,
-            files_count: 0,
-            error: Some(format!(
-                "Failed to run npm publish (is npm installed?): {e}"
-            )),
-        },
+        Err(e) => failed(
+            packed.name,
+            packed.version,
+            format!("Failed to run npm publish (is npm installed?): {e}"),
+            false,
+        ),
     }
 }
 
@@ -839,9 +980,21 @@ pub fn handle_pkg_cache_list(channel: &str) -> Response {
                 })
                 .collect();
 
+            // Files shared through the store are only counted once on disk
+            let file_store = cache.file_store().map(|store| {
+                let usage = store.usage();
+                FileStoreInfo {
+                    files: usage.files,
+                    size_bytes: usage.size_bytes,
+                }
+            });
+            let disk_size_bytes = cache.disk_size().unwrap_or(total_size_bytes);
+
             Response::PkgCacheListResult {
                 packages: cached_packages,
                 total_size_bytes,
+                disk_size_bytes,
+                file_store,
             }
         }
         Err(e) => {
@@ -849,6 +1002,8 @@ pub fn handle_pkg_cache_list(channel: &str) -> Response {
             Response::PkgCacheListResult {
                 packages: Vec::new(),
                 total_size_bytes: 0,
+                disk_size_bytes: 0,
+                file_store: None,
             }
         }
     }
@@ -865,6 +1020,43 @@ pub fn handle_pkg_cache_prune(channel: &str) -> Response {
     }
 }
 
+/// Handle a PkgCacheDedupe request.
+///
+/// Migrates the cache to the content-addressed file store: identical files
+/// across cached packages become hard links to one stored copy.
+pub fn handle_pkg_cache_dedupe(channel: &str) -> Response {
+    let cache = PackageCache::new(parse_channel(channel));
+
+    match cache.dedupe() {
+        Ok((packages, stats)) => {
+            debug!(
+                packages,
+                files = stats.files,
+                linked = stats.linked,
+                saved_bytes = stats.saved_bytes,
+                "Deduplicated package cache"
+            );
+            Response::PkgCacheDedupeResult {
+                packages,
+                files: stats.files,
+                linked_files: stats.linked,
+                saved_bytes: stats.saved_bytes,
+                error: None,
+            }
+        }
+        Err(e) => {
+            warn!(error = %e, "Failed to deduplicate cache");
+            Response::PkgCacheDedupeResult {
+                packages: 0,
+                files: 0,
+                linked_files: 0,
+                saved_bytes: 0,
+                error: Some(e.to_string()),
+            }
+        }
+    }
+}
+
 /// Handle a PkgInstall request (v1.9).
 ///
 /// Installs packages from the lockfile (`howth.lock`).
@@ -875,21 +1067,32 @@ pub async fn handle_pkg_install(
     frozen: bool,
     include_dev: bool,
     include_optional: bool,
+    auto_install_peers_flag: Option<bool>,
 ) -> Response {
-    handle_pkg_install_with_progress(cwd, channel, frozen, include_dev, include_optional, None)
-        .await
+    handle_pkg_install_with_progress(
+        cwd,
+        channel,
+        frozen,
+        include_dev,
+        include_optional,
+        auto_install_peers_flag,
+        None,
+    )
+    .await
 }
 
 /// Handle a PkgInstall request with optional streaming progress.
 ///
 /// When `progress_tx` is `Some`, sends `PkgInstallProgress` events as each
 /// package completes. The final `PkgInstallResult` is always returned.
+#[tracing::instrument(name = "pkg.install", skip_all, fields(cwd = %cwd, frozen))]
 pub async fn handle_pkg_install_with_progress(
     cwd: &str,
     channel: &str,
     frozen: bool,
     include_dev: bool,
     include_optional: bool,
+    auto_install_peers_flag: Option<bool>,
     progress_tx: Option<tokio::sync::mpsc::Sender<Response>>,
 ) -> Response {
     use std::path::PathBuf;
@@ -897,7 +1100,7 @@ pub async fn handle_pkg_install_with_progress(
     let project_root = PathBuf::from(cwd);
 
     // Canonicalize the path
-    let project_root = match dunce::canonicalize(&project_root) {
+    let project_root = match fs_util::canonicalize(&project_root) {
         Ok(p) => p,
         Err(e) => {
             return Response::error(
@@ -938,6 +1141,7 @@ pub async fn handle_pkg_install_with_progress(
         let resolve_opts = ResolveOptions {
             include_dev,
             include_optional,
+            auto_install_peers: auto_install_peers(&project_root, auto_install_peers_flag),
         };
 
         match resolve_dependencies(&project_root, &registry, &resolve_opts).await {
@@ -1025,6 +1229,7 @@ pub async fn handle_pkg_install_with_progress(
             let resolve_opts = ResolveOptions {
                 include_dev,
                 include_optional,
+                auto_install_peers: auto_install_peers(&project_root, auto_install_peers_flag),
             };
 
             match resolve_dependencies(&project_root, &registry, &resolve_opts).await {
@@ -1057,37 +1262,6 @@ pub async fn handle_pkg_install_with_progress(
         "Using lockfile"
     );
 
-    // Check if node_modules is already up-to-date with the lockfile
-    let content_hash = lockfile_content_hash(&lockfile);
-    let state_file = project_root.join("node_modules/.howth-state");
-    let pnpm_dir = project_root.join("node_modules/.pnpm");
-
-    if pnpm_dir.is_dir() {
-        if let Ok(stored_hash) = std::fs::read_to_string(&state_file) {
-            if stored_hash.trim() == content_hash {
-                debug!("node_modules is up-to-date, skipping install");
-                return Response::PkgInstallResult {
-                    result: PkgInstallResult {
-                        schema_version: PKG_INSTALL_SCHEMA_VERSION,
-                        cwd: project_root.to_string_lossy().into_owned(),
-                        ok: true,
-                        summary: InstallSummary {
-                            total_packages: 0,
-                            downloaded: 0,
-                            cached: 0,
-                            linked: 0,
-                            failed: 0,
-                            workspace_linked: 0,
-                        },
-                        installed: Vec::new(),
-                        errors: Vec::new(),
-                        notes: vec!["already up-to-date".to_string()],
-                    },
-                };
-            }
-        }
-    }
-
     // Detect workspaces for local package linking
     let workspace_root = find_workspace_root(&project_root);
     let workspace_config = workspace_root
@@ -1102,6 +1276,68 @@ pub async fn handle_pkg_install_with_progress(
         );
     }
 
+    // The layout is fully determined by the lockfile, so an identical
+    // recorded layout means node_modules is already up-to-date
+    let node_linker = load_npmrc_files(&project_root)
+        .node_linker
+        .unwrap_or_default();
+    let mut layout =
+        NodeModulesLayout::compute(&lockfile, node_linker, include_dev, include_optional);
+    layout.channel = Some(chan);
+    if let Some(ref config) = workspace_config {
+        layout.link_workspace_packages(config);
+    }
+    let previous_layout = NodeModulesLayout::read_from(&project_root);
+
+    // Peer conflicts recorded during resolution fail the install
+    let peer_errors: Vec<InstallPackageError> = lockfile
+        .peer_conflicts()
+        .map(|peer| InstallPackageError {
+            name: peer.name.clone(),
+            version: peer.range.clone(),
+            code: lockfile_codes::PKG_LOCK_CONFLICT.to_string(),
+            message: peer.message(),
+        })
+        .collect();
+    if previous_layout.as_ref() == Some(&layout) {
+        debug!("node_modules is up-to-date, skipping install");
+        return Response::PkgInstallResult {
+            result: PkgInstallResult {
+                schema_version: PKG_INSTALL_SCHEMA_VERSION,
+                cwd: project_root.to_string_lossy().into_owned(),
+                ok: peer_errors.is_empty(),
+                summary: InstallSummary {
+                    total_packages: 0,
+                    downloaded: 0,
+                    cached: 0,
+                    linked: 0,
+                    failed: 0,
+                    workspace_linked: 0,
+                },
+                installed: Vec::new(),
+                errors: peer_errors,
+                notes: vec!["already up-to-date".to_string()],
+            },
+        };
+    }
+
+    // Remove packages the previous layout installed elsewhere
+    if let Some(previous) = &previous_layout {
+        for install_dir in previous.packages.keys() {
+            if layout.packages.contains_key(install_dir) {
+                continue;
+            }
+            if let Err(e) = remove_installed_package(&project_root, install_dir) {
+                warn!(dir = %install_dir, error = %e, "Failed to remove stale package");
+            }
+        }
+    }
+    if node_linker == NodeLinker::Hoisted {
+        if let Err(e) = remove_installed_package(&project_root, "node_modules/.pnpm") {
+            warn!(error = %e, "Failed to remove .pnpm directory");
+        }
+    }
+
     use futures::stream::{self, StreamExt};
 
     let mut installed = Vec::new();
@@ -1112,8 +1348,24 @@ pub async fn handle_pkg_install_with_progress(
     let mut workspace_linked = 0u32;
     let mut completed = 0u32;
 
+    // First directory the hoisted layout installs each package in
+    let mut hoisted_dirs: std::collections::HashMap<&str, &str> = std::collections::HashMap::new();
+    if node_linker == NodeLinker::Hoisted {
+        for (install_dir, key) in &layout.packages {
+            hoisted_dirs
+                .entry(key.as_str())
+                .or_insert(install_dir.as_str());
+        }
+    }
+    let mut fetched = std::collections::HashMap::new();
+    let mut workspace_names = std::collections::HashSet::new();
+
     // Count total packages to install (for progress reporting)
-    let total_packages = lockfile.packages.len() as u32;
+    let total_packages = if node_linker == NodeLinker::Hoisted {
+        hoisted_dirs.len() as u32
+    } else {
+        lockfile.packages.len() as u32
+    };
 
     // Separate workspace packages from registry packages
     // Workspace packages are linked locally (fast), registry packages need download (parallelized)
@@ -1135,6 +1387,9 @@ pub async fn handle_pkg_install_with_progress(
                 }
             }
         }
+        if node_linker == NodeLinker::Hoisted && !hoisted_dirs.contains_key(key.as_str()) {
+            continue;
+        }
 
         // Check if this is a workspace package
         if let Some(ref config) = workspace_config {
@@ -1156,6 +1411,7 @@ pub async fn handle_pkg_install_with_progress(
                                 debug!(bin = %bin.display(), "Linked workspace binary");
                             }
                         }
+                        workspace_names.insert(name);
                         workspace_linked += 1;
                         linked += 1;
                         completed += 1;
@@ -1166,6 +1422,7 @@ pub async fn handle_pkg_install_with_progress(
                             link_path: link_path.to_string_lossy().into_owned(),
                             cache_path: ws_pkg.path.to_string_lossy().into_owned(),
                             is_workspace: true,
+                            served_by: None,
                         });
 
                         // Send progress event
@@ -1197,20 +1454,34 @@ pub async fn handle_pkg_install_with_progress(
         }
 
         // Collect registry packages for parallel download
-        registry_packages.push((name.to_string(), lock_pkg.clone()));
+        let hoisted_dir = hoisted_dirs.get(key.as_str()).map(|dir| dir.to_string());
+        registry_packages.push((name.to_string(), lock_pkg.clone(), hoisted_dir));
     }
 
     // Install registry packages in parallel
     const MAX_CONCURRENT_DOWNLOADS: usize = 32;
+    // Packages listed individually when they skipped a mirror
+    const MAX_FALLBACK_NOTES: usize = 10;
+
+    // Tarballs per serving mirror/registry, and packages that fell back
+    let mut served_counts = std::collections::BTreeMap::new();
+    let mut mirror_fallbacks = Vec::new();
 
     let mut stream = stream::iter(registry_packages)
-        .map(|(name, lock_pkg)| {
+        .map(|(name, lock_pkg, hoisted_dir)| {
             let project_root = project_root.clone();
             let cache = cache.clone();
             let registry = registry.clone();
             async move {
-                let result =
-                    install_from_lockfile(&name, &lock_pkg, &project_root, &cache, &registry).await;
+                let result = install_from_lockfile(
+                    &name,
+                    &lock_pkg,
+                    hoisted_dir.as_deref(),
+                    &project_root,
+                    &cache,
+                    &registry,
+                )
+                .await;
                 (name, lock_pkg.version.clone(), result)
             }
         })
@@ -1219,8 +1490,17 @@ pub async fn handle_pkg_install_with_progress(
     // Process results one at a time, sending progress for each
     while let Some((name, version, result)) = stream.next().await {
         match result {
-            Ok((pkg_info, from_cache)) => {
+            Ok((pkg_info, from_cache, skipped)) => {
                 let status = if from_cache { "cached" } else { "downloaded" };
+                if let Some(ref source) = pkg_info.served_by {
+                    *served_counts.entry(source.clone()).or_insert(0u32) += 1;
+                    if !skipped.is_empty() {
+                        mirror_fallbacks.push(format!(
+                            "{name}@{version}: {}; served by {source}",
+                            skipped.join("; ")
+                        ));
+                    }
+                }
                 if from_cache {
                     cached += 1;
                 } else {
@@ -1242,6 +1522,12 @@ pub async fn handle_pkg_install_with_progress(
                         .await;
                 }
 
+                if node_linker == NodeLinker::Hoisted {
+                    fetched.insert(
+                        Lockfile::package_key(&name, &version),
+                        PathBuf::from(&pkg_info.cache_path),
+                    );
+                }
                 installed.push(pkg_info);
             }
             Err(e) => {
@@ -1256,69 +1542,136 @@ pub async fn handle_pkg_install_with_progress(
         }
     }
 
-    // Phase 2: Link package dependencies (pnpm-style)
+    // Hoisted layout: copy packages into the directories the layout picked.
+    // Parents go before the packages nested in them, since reinstalling a
+    // package replaces its directory.
+    if node_linker == NodeLinker::Hoisted {
+        for (install_dir, key) in &layout.packages {
+            let Some(package_dir) = fetched.get(key) else {
+                continue;
+            };
+            let name = key.rsplit_once('@').map_or(key.as_str(), |(n, _)| n);
+            match link_hoisted_package(&project_root, install_dir, package_dir) {
+                Ok(dest) => {
+                    if *install_dir == format!("node_modules/{name}") {
+                        let _ =
+                            link_package_binaries(&project_root, name, package_dir, Some(&dest));
+                    }
+                }
+                Err(e) => errors.push(InstallPackageError {
+                    name: name.to_string(),
+                    version: key[name.len() + 1..].to_string(),
+                    code: e.code().to_string(),
+                    message: e.to_string(),
+                }),
+            }
+        }
+    }
+
+    // Phase 2: Link package dependencies (pnpm-style). The hoisted layout
+    // needs no links: Node.js finds dependencies in parent directories.
     // This must happen after all packages are installed so the targets exist.
     // First resolve all dependency versions from the lockfile (cheap, single-threaded),
     // then create symlinks in parallel via rayon.
-    debug!("Linking package dependencies (pnpm layout)");
-
-    // Collect work items: (name, version, resolved_deps)
-    let mut link_work: Vec<(String, String, std::collections::BTreeMap<String, String>)> =
-        Vec::new();
-    for (key, lock_pkg) in &lockfile.packages {
-        if lock_pkg.dependencies.is_empty() && lock_pkg.peer_dependencies.is_empty() {
-            continue;
-        }
+    if node_linker == NodeLinker::Isolated {
+        debug!("Linking package dependencies (pnpm layout)");
+
+        // Collect work items: (name, version, resolved_deps)
+        let mut link_work: Vec<(String, String, std::collections::BTreeMap<String, String>)> =
+            Vec::new();
+        for (key, lock_pkg) in &lockfile.packages {
+            if lock_pkg.dependencies.is_empty() && lock_pkg.peer_dependencies.is_empty() {
+                continue;
+            }
 
-        let name = key.rsplit_once('@').map_or(key.as_str(), |(n, _)| n);
-        let version = &lock_pkg.version;
+            let name = key.rsplit_once('@').map_or(key.as_str(), |(n, _)| n);
+            let version = &lock_pkg.version;
 
-        let mut resolved_deps = std::collections::BTreeMap::new();
-        for (dep_name, dep_range) in &lock_pkg.dependencies {
-            if let Some(version) = find_best_match(&lockfile, dep_name, dep_range) {
-                resolved_deps.insert(dep_name.clone(), version);
-            } else {
-                debug!(
-                    pkg = %name,
-                    dep = %dep_name,
-                    range = %dep_range,
-                    "Dependency not found in lockfile, skipping"
-                );
+            let mut resolved_deps = std::collections::BTreeMap::new();
+            for (dep_name, dep_range) in &lock_pkg.dependencies {
+                if let Some(version) = lockfile
+                    .best_match(dep_name, dep_range)
+                    .map(|pkg| pkg.version.clone())
+                {
+                    resolved_deps.insert(dep_name.clone(), version);
+                } else {
+                    debug!(
+                        pkg = %name,
+                        dep = %dep_name,
+                        range = %dep_range,
+                        "Dependency not found in lockfile, skipping"
+                    );
+                }
             }
-        }
 
-        for (dep_name, dep_range) in &lock_pkg.peer_dependencies {
-            if resolved_deps.contains_key(dep_name) {
-                continue;
+            for (dep_name, dep_range) in &lock_pkg.peer_dependencies {
+                if resolved_deps.contains_key(dep_name) {
+                    continue;
+                }
+                if let Some(version) = lockfile
+                    .best_match(dep_name, dep_range)
+                    .map(|pkg| pkg.version.clone())
+                {
+                    resolved_deps.insert(dep_name.clone(), version);
+                }
             }
-            if let Some(version) = find_best_match(&lockfile, dep_name, dep_range) {
-                resolved_deps.insert(dep_name.clone(), version);
+
+            if !resolved_deps.is_empty() {
+                link_work.push((name.to_string(), version.clone(), resolved_deps));
             }
         }
 
-        if !resolved_deps.is_empty() {
-            link_work.push((name.to_string(), version.clone(), resolved_deps));
-        }
-    }
+        // Execute symlink creation in parallel
+        {
+            let project_root = project_root.clone();
+            let link_errors: Vec<String> = tokio::task::block_in_place(|| {
+                use rayon::prelude::*;
+                link_work
+                    .par_iter()
+                    .filter_map(|(name, version, resolved_deps)| {
+                        match link_package_dependencies(&project_root, name, version, resolved_deps)
+                        {
+                            Ok(()) => None,
+                            Err(e) => Some(format!("{name}@{version}: {e}")),
+                        }
+                    })
+                    .collect()
+            });
 
-    // Execute symlink creation in parallel
-    {
-        let project_root = project_root.clone();
-        let link_errors: Vec<String> = tokio::task::block_in_place(|| {
-            use rayon::prelude::*;
-            link_work
-                .par_iter()
-                .filter_map(|(name, version, resolved_deps)| {
-                    match link_package_dependencies(&project_root, name, version, resolved_deps) {
-                        Ok(()) => None,
-                        Err(e) => Some(format!("{name}@{version}: {e}")),
-                    }
-                })
-                .collect()
-        });
+            for msg in &link_errors {
+                warn!(error = %msg, "Failed to link package dependencies");
+            }
+        }
 
-        for msg in &link_errors {
-            warn!(error = %msg, "Failed to link package dependencies");
+        // Every version is in .pnpm now; point each top-level name at the
+        // version the layout picked, whatever order the downloads finished in
+        for (name, _, key) in layout.top_level() {
+            if workspace_names.contains(name) {
+                continue;
+            }
+            let Some(lock_pkg) = lockfile.packages.get(key) else {
+                continue;
+            };
+            let version = &lock_pkg.version;
+            let pnpm_pkg_dir = project_root
+                .join("node_modules/.pnpm")
+                .join(format_pnpm_key(name, version))
+                .join("node_modules")
+                .join(name);
+            if !pnpm_pkg_dir.exists() {
+                continue;
+            }
+            if let Err(e) = link_pnpm_top_level(&project_root, name, version) {
+                warn!(package = %key, error = %e, "Failed to link top-level package");
+                continue;
+            }
+            let fetch_name = lock_pkg.alias_for.as_deref().unwrap_or(name);
+            let _ = link_package_binaries(
+                &project_root,
+                name,
+                &cache.package_dir(fetch_name, version),
+                Some(&pnpm_pkg_dir),
+            );
         }
     }
 
@@ -1334,13 +1687,20 @@ pub async fn handle_pkg_install_with_progress(
         "Install completed"
     );
 
-    // Write sentinel so the next install can skip if nothing changed
+    // Record the layout so the next install can skip if nothing changed,
+    // and doctor can verify the tree
     if ok {
-        if let Err(e) = std::fs::write(&state_file, &content_hash) {
-            warn!(error = %e, "Failed to write .howth-state sentinel");
+        let written = std::fs::create_dir_all(project_root.join("node_modules"))
+            .and_then(|()| layout.write_to(&project_root));
+        if let Err(e) = written {
+            warn!(error = %e, "Failed to record node_modules layout");
         }
     }
 
+    let failed = errors.len() as u32;
+    errors.extend(peer_errors);
+    let ok = errors.is_empty();
+
     let mut notes = vec![];
     if workspace_linked > 0 {
         notes.push(format!(
@@ -1348,6 +1708,29 @@ pub async fn handle_pkg_install_with_progress(
             workspace_linked
         ));
     }
+    if !registry.mirrors().is_empty() {
+        for (source, count) in &served_counts {
+            notes.push(format!("{count} package(s) served by {source}"));
+        }
+        for fallback in mirror_fallbacks.iter().take(MAX_FALLBACK_NOTES) {
+            notes.push(fallback.clone());
+        }
+        if mirror_fallbacks.len() > MAX_FALLBACK_NOTES {
+            notes.push(format!(
+                "... and {} more package(s) skipped a mirror",
+                mirror_fallbacks.len() - MAX_FALLBACK_NOTES
+            ));
+        }
+    }
+    let stats = registry.download_stats();
+    if stats.retries() > 0 {
+        notes.push(format!(
+            "{} download retr{} ({} resumed)",
+            stats.retries(),
+            if stats.retries() == 1 { "y" } else { "ies" },
+            stats.resumed()
+        ));
+    }
 
     Response::PkgInstallResult {
         result: PkgInstallResult {
@@ -1359,7 +1742,7 @@ pub async fn handle_pkg_install_with_progress(
                 downloaded,
                 cached,
                 linked,
-                failed: errors.len() as u32,
+                failed,
                 workspace_linked,
             },
             installed,
@@ -1369,14 +1752,20 @@ pub async fn handle_pkg_install_with_progress(
     }
 }
 
-/// Install a single package from lockfile.
+/// Install a single package from lockfile. Returns (info, was_cached,
+/// mirror sources skipped before the tarball was served).
+///
+/// With the hoisted layout, the package is only fetched; the caller copies
+/// it into the layout's directories, `hoisted_dir` being the first of them.
+/// Otherwise it is linked into `.pnpm`.
 async fn install_from_lockfile(
     name: &str,
     lock_pkg: &LockPackage,
+    hoisted_dir: Option<&str>,
     project_root: &Path,
     cache: &PackageCache,
     registry: &RegistryClient,
-) -> Result<(InstallPackageInfo, bool), PkgError> {
+) -> Result<(InstallPackageInfo, bool, Vec<String>), PkgError> {
     let version = &lock_pkg.version;
     // For npm: aliases, use the real package name for registry/cache operations
     let fetch_name = lock_pkg.alias_for.as_deref().unwrap_or(name);
@@ -1386,6 +1775,8 @@ async fn install_from_lockfile(
     // Check if already cached (use real package name for cache)
     let package_dir = cache.package_dir(fetch_name, version);
     let was_cached = cache.is_cached(fetch_name, version);
+    let mut served_by = None;
+    let mut skipped = Vec::new();
 
     if was_cached {
         debug!(path = %package_dir.display(), "Using cached package");
@@ -1408,45 +1799,58 @@ async fn install_from_lockfile(
 
         debug!(url = %tarball_url, "Downloading tarball");
 
-        // Download tarball (with auth token for scoped registries)
-        let auth_token = registry.auth_token_for(fetch_name);
-        let bytes =
-            download_tarball(registry.http(), &tarball_url, MAX_TARBALL_SIZE, auth_token).await?;
-
-        // TODO: Verify integrity hash matches lock_pkg.integrity
-        // For now, just extract
+        // Download tarball from a mirror or the registry, checked against
+        // the lockfile's integrity hash
+        let integrity = Some(lock_pkg.integrity.as_str()).filter(|i| !i.is_empty());
+        let tarball = registry
+            .download_verified_tarball(fetch_name, &tarball_url, integrity, MAX_TARBALL_SIZE)
+            .await?;
+        let bytes = tarball.bytes;
+        served_by = Some(tarball.source.label());
+        skipped = tarball.failures;
 
-        debug!(size = bytes.len(), "Downloaded tarball");
+        debug!(size = bytes.len(), source = ?served_by, "Downloaded tarball");
 
         // Extract to cache (offload CPU-bound decompression to thread pool)
         let extract_bytes = bytes.clone();
         let extract_dest = package_dir.clone();
-        tokio::task::spawn_blocking(move || extract_tgz_atomic(&extract_bytes, &extract_dest))
-            .await
-            .map_err(|e| PkgError::extract_failed(format!("Extraction task failed: {e}")))??;
+        let store = cache.file_store();
+        tokio::task::spawn_blocking(move || {
+            extract_tgz_atomic_with_store(&extract_bytes, &extract_dest, store.as_ref())
+        })
+        .await
+        .map_err(|e| PkgError::extract_failed(format!("Extraction task failed: {e}")))??;
 
         debug!(path = %package_dir.display(), "Extracted to cache");
     }
 
-    // Link into node_modules using pnpm-style layout
-    // Use the alias name so the module is accessible under the alias
-    let link_path = link_into_node_modules_with_version(project_root, name, version, &package_dir)?;
-
-    // Derive the .pnpm content path so binary symlinks resolve transitive deps
-    let pnpm_pkg_dir = project_root
-        .join("node_modules/.pnpm")
-        .join(format_pnpm_key(name, version))
-        .join("node_modules")
-        .join(name);
-
-    // Link binaries into .bin
-    if let Ok(binaries) =
-        link_package_binaries(project_root, name, &package_dir, Some(&pnpm_pkg_dir))
-    {
-        for bin in &binaries {
-            debug!(bin = %bin.display(), "Linked binary");
+    let link_path = match hoisted_dir {
+        // Hoisted layout: copied into place once every package is fetched
+        Some(hoisted_dir) => project_root.join(hoisted_dir),
+        None => {
+            // Link into node_modules using pnpm-style layout
+            // Use the alias name so the module is accessible under the alias
+            let link_path =
+                link_into_node_modules_with_version(project_root, name, version, &package_dir)?;
+
+            // Derive the .pnpm content path so binary symlinks resolve transitive deps
+            let pnpm_pkg_dir = project_root
+                .join("node_modules/.pnpm")
+                .join(format_pnpm_key(name, version))
+                .join("node_modules")
+                .join(name);
+
+            // Link binaries into .bin
+            if let Ok(binaries) =
+                link_package_binaries(project_root, name, &package_dir, Some(&pnpm_pkg_dir))
+            {
+                for bin in &binaries {
+                    debug!(bin = %bin.display(), "Linked binary");
+                }
+            }
+            link_path
         }
-    }
+    };
 
     Ok((
         InstallPackageInfo {
@@ -1456,8 +1860,10 @@ async fn install_from_lockfile(
             link_path: link_path.to_string_lossy().into_owned(),
             cache_path: package_dir.to_string_lossy().into_owned(),
             is_workspace: false,
+            served_by,
         },
         was_cached,
+        skipped,
     ))
 }
 
@@ -1472,7 +1878,7 @@ pub fn handle_pkg_graph(
     let project_root = Path::new(cwd);
 
     // Canonicalize the path
-    let project_root = match dunce::canonicalize(project_root) {
+    let project_root = match fs_util::canonicalize(project_root) {
         Ok(p) => p,
         Err(e) => {
             return Response::error(
@@ -1585,6 +1991,7 @@ pub fn handle_pkg_explain(
     parent: &str,
     channel: &str,
     kind: &str,
+    conditions: &ExportConditions,
     pkg_json_cache: &dyn PkgJsonCache,
 ) -> Response {
     use std::path::PathBuf;
@@ -1605,7 +2012,7 @@ pub fn handle_pkg_explain(
             format!("Working directory does not exist: {}", cwd),
         );
     }
-    let cwd_canonical = match dunce::canonicalize(&cwd_path) {
+    let cwd_canonical = match fs_util::canonicalize(&cwd_path) {
         Ok(p) => p,
         Err(e) => {
             return Response::error(
@@ -1623,7 +2030,7 @@ pub fn handle_pkg_explain(
             format!("Parent directory does not exist: {}", parent),
         );
     }
-    let parent_canonical = match dunce::canonicalize(&parent_path) {
+    let parent_canonical = match fs_util::canonicalize(&parent_path) {
         Ok(p) => p,
         Err(e) => {
             return Response::error(
@@ -1654,11 +2061,15 @@ pub fn handle_pkg_explain(
         cwd = %cwd_canonical.display(),
         parent = %parent_canonical.display(),
         kind = ?resolution_kind,
+        conditions = ?conditions,
         "Explaining module resolution"
     );
 
     // Create resolver context
-    let config = ResolverConfig::default();
+    let config = ResolverConfig {
+        conditions: conditions.clone(),
+        ..ResolverConfig::default()
+    };
     let ctx = ResolveContext {
         cwd: cwd_canonical.clone(),
         parent: parent_canonical.clone(),
@@ -1681,6 +2092,7 @@ pub fn handle_pkg_explain(
             detail: step.detail,
             path: step.path.map(|p| p.to_string_lossy().into_owned()),
             condition: step.condition,
+            conditions: step.conditions,
             key: step.key,
             target: step.target,
             notes: step.notes,
@@ -1734,6 +2146,15 @@ pub fn handle_pkg_explain(
         error_message,
         kind: kind_str.to_string(),
         parent: parent_canonical.to_string_lossy().into_owned(),
+        conditions: if conditions.is_empty() {
+            Vec::new()
+        } else {
+            conditions
+                .active(resolution_kind)
+                .into_iter()
+                .map(String::from)
+                .collect()
+        },
         trace: trace_steps,
         warnings,
         tried,
@@ -1751,6 +2172,7 @@ pub fn handle_pkg_explain(
 }
 
 /// Options for why request.
+#[allow(clippy::struct_excessive_bools)]
 pub struct WhyRequestOptions<'a> {
     pub arg: &'a str,
     pub cwd: &'a str,
@@ -1761,6 +2183,7 @@ pub struct WhyRequestOptions<'a> {
     pub include_trace: bool,
     pub trace_kind: Option<&'a str>,
     pub trace_parent: Option<&'a str>,
+    pub dependents: bool,
 }
 
 /// Handle a PkgWhy request.
@@ -1789,7 +2212,7 @@ pub fn handle_pkg_why(opts: WhyRequestOptions<'_>, pkg_json_cache: &dyn PkgJsonC
             format!("Working directory does not exist: {}", cwd),
         );
     }
-    let cwd_canonical = match dunce::canonicalize(&cwd_path) {
+    let cwd_canonical = match fs_util::canonicalize(&cwd_path) {
         Ok(p) => p,
         Err(e) => {
             return Response::error(
@@ -1830,6 +2253,7 @@ pub fn handle_pkg_why(opts: WhyRequestOptions<'_>, pkg_json_cache: &dyn PkgJsonC
     let why_opts = WhyOptions {
         max_chains,
         prefer_shortest: true,
+        dependents: opts.dependents,
     };
 
     let core_result = why_from_graph(&core_graph, arg, &why_opts);
@@ -1857,7 +2281,7 @@ pub fn handle_pkg_why(opts: WhyRequestOptions<'_>, pkg_json_cache: &dyn PkgJsonC
             }
         });
 
-        let trace_parent_canonical = dunce::canonicalize(&trace_parent).unwrap_or(trace_parent);
+        let trace_parent_canonical = fs_util::canonicalize(&trace_parent).unwrap_or(trace_parent);
         let trace_kind = opts.trace_kind.unwrap_or("auto");
 
         // Call explain for the trace
@@ -1867,6 +2291,7 @@ pub fn handle_pkg_why(opts: WhyRequestOptions<'_>, pkg_json_cache: &dyn PkgJsonC
             &trace_parent_canonical.to_string_lossy(),
             "stable",
             trace_kind,
+            &ExportConditions::default(),
             pkg_json_cache,
         );
 
@@ -1887,7 +2312,9 @@ pub fn handle_pkg_why(opts: WhyRequestOptions<'_>, pkg_json_cache: &dyn PkgJsonC
         "Why query completed"
     );
 
-    Response::PkgWhyResult { result }
+    Response::PkgWhyResult {
+        result: Box::new(result),
+    }
 }
 
 /// Convert core why result to protocol types.
@@ -1932,6 +2359,22 @@ fn convert_why_result_to_proto(core: CorePkgWhyResult) -> PkgWhyResult {
             })
             .collect(),
         trace: None, // Set by caller if trace is requested
+        dependents: core.dependents.map(|d| PkgWhyDependents {
+            direct: d
+                .direct
+                .into_iter()
+                .map(|dep| PkgWhyDependent {
+                    name: dep.name,
+                    version: dep.version,
+                    path: dep.path,
+                    req: dep.req,
+                    kind: dep.kind,
+                    resolved_version: dep.resolved_version,
+                })
+                .collect(),
+            transitive_count: u32::try_from(d.transitive_count).unwrap_or(u32::MAX),
+            root_dependency: d.root_dependency,
+        }),
     }
 }
 
@@ -1962,7 +2405,7 @@ pub fn handle_pkg_doctor(
             format!("Working directory does not exist: {}", cwd),
         );
     }
-    let cwd_canonical = match dunce::canonicalize(&cwd_path) {
+    let cwd_canonical = match fs_util::canonicalize(&cwd_path) {
         Ok(p) => p,
         Err(e) => {
             return Response::error(
@@ -2022,6 +2465,7 @@ pub fn handle_pkg_doctor(
         max_depth: opts.max_depth as usize,
         max_items,
         min_severity,
+        node_version: detect_node_version(),
     };
 
     let core_report =
@@ -2083,6 +2527,82 @@ mod tests {
     use super::*;
     use tempfile::tempdir;
 
+    #[test]
+    fn test_read_declared_deps_classifies_sections() {
+        let dir = tempfile::tempdir().unwrap();
+        let pkg = dir.path().join("package.json");
+        std::fs::write(
+            &pkg,
+            r#"{
+                "dependencies": { "react": "^18.0.0", "shared": "^1.0.0", "local": "file:../local" },
+                "devDependencies": { "vitest": "^1.0.0", "shared": "^1.0.0" },
+                "optionalDependencies": { "fsevents": "^2.0.0" },
+                "peerDependencies": { "typescript": "^5.0.0" }
+            }"#,
+        )
+        .unwrap();
+
+        let deps = read_declared_deps(&pkg, Some("app")).unwrap();
+        let summary: Vec<(&str, &str)> =
+            deps.iter().map(|d| (d.name.as_str(), d.dep_type)).collect();
+        assert_eq!(
+            summary,
+            vec![
+                ("react", "dep"),
+                ("shared", "dep"),
+                ("vitest", "dev"),
+                ("fsevents", "optional"),
+            ]
+        );
+        assert!(deps.iter().all(|d| d.workspace.as_deref() == Some("app")));
+    }
+
+    #[test]
+    fn test_is_otp_challenge() {
+        assert!(is_otp_challenge("npm ERR! code EOTP"));
+        assert!(is_otp_challenge(
+            "This operation requires a one-time password from your authenticator."
+        ));
+        assert!(!is_otp_challenge("npm ERR! code E403"));
+    }
+
+    #[test]
+    fn test_handle_pkg_pack_dry_run() {
+        let dir = tempfile::tempdir().unwrap();
+        std::fs::write(
+            dir.path().join("package.json"),
+            r#"{"name":"demo","version":"1.0.0"}"#,
+        )
+        .unwrap();
+        std::fs::write(dir.path().join("index.js"), "module.exports = 1;").unwrap();
+
+        let response = handle_pkg_pack(&dir.path().to_string_lossy(), None, true, None);
+        let Response::PkgPackResult {
+            ok,
+            filename,
+            path,
+            files,
+            ..
+        } = response
+        else {
+            panic!("expected PkgPackResult");
+        };
+        assert!(ok);
+        assert_eq!(filename, "demo-1.0.0.tgz");
+        assert!(path.is_none());
+        assert_eq!(files.len(), 2);
+        assert!(!dir.path().join("demo-1.0.0.tgz").exists());
+    }
+
+    #[test]
+    fn test_is_registry_range() {
+        assert!(is_registry_range("^1.2.3"));
+        assert!(is_registry_range(">=1 <2 || 3.x"));
+        assert!(!is_registry_range("workspace:*"));
+        assert!(!is_registry_range("github:user/repo"));
+        assert!(!is_registry_range("user/repo"));
+    }
+
     #[test]
     fn test_calculate_dir_size() {
         let dir = tempdir().unwrap();
@@ -2104,6 +2624,7 @@ mod tests {
             Response::PkgCacheListResult {
                 packages,
                 total_size_bytes,
+                ..
             } => {
                 // Result is valid regardless of package count
                 let _ = packages.len();
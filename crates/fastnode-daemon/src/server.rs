@@ -2,15 +2,23 @@
 
 use crate::ipc::{cleanup_socket, IpcListener, IpcStream};
 use crate::state::DaemonState;
-use crate::{handle_build, handle_request, handle_request_async, make_response_frame};
-use fastnode_proto::{codes, encode_frame, Frame, Request, Response};
+use crate::{
+    build_progress_response, handle_build, handle_request, handle_request_async,
+    make_response_frame,
+};
+use fastnode_core::build::BuildProgress;
+use fastnode_core::config::Channel;
+use fastnode_proto::{
+    codes, encode_frame, Frame, Request, Response, WatchFileEvent, WatchFileEventKind,
+};
+use std::collections::BTreeSet;
 use std::io;
 use std::path::PathBuf;
 use std::sync::atomic::{AtomicBool, Ordering};
 use std::sync::Arc;
 use tokio::io::{AsyncReadExt, AsyncWriteExt};
 use tokio::sync::mpsc;
-use tracing::{debug, error, info, warn};
+use tracing::{debug, error, info, info_span, warn, Instrument};
 
 /// Maximum frame size for sanity checking (16 MiB).
 const MAX_FRAME_SIZE: usize = 16 * 1024 * 1024;
@@ -20,6 +28,8 @@ const MAX_FRAME_SIZE: usize = 16 * 1024 * 1024;
 pub struct DaemonConfig {
     /// IPC endpoint (socket path on Unix, pipe name on Windows).
     pub endpoint: String,
+    /// Channel the daemon serves.
+    pub channel: Channel,
 }
 
 /// Run the daemon server.
@@ -34,13 +44,13 @@ pub async fn run_server(config: DaemonConfig) -> io::Result<()> {
 
     // Bind to the endpoint
     let listener = IpcListener::bind(&config.endpoint)?;
-    info!(endpoint = %config.endpoint, "daemon listening");
+    info!(endpoint = %config.endpoint, channel = %config.channel, "daemon listening");
 
     // Shutdown flag
     let shutdown = Arc::new(AtomicBool::new(false));
 
     // Create daemon state (cache + watcher)
-    let state = Arc::new(DaemonState::new());
+    let state = Arc::new(DaemonState::with_channel(config.channel));
 
     // Wire caches to watcher for invalidation
     state.watcher.set_cache(state.cache.clone());
@@ -100,6 +110,7 @@ fn is_async_request(request: &Request) -> bool {
             | Request::PkgPublish { .. }
             | Request::PkgCacheList { .. }
             | Request::PkgCachePrune { .. }
+            | Request::PkgCacheDedupe { .. }
             | Request::RunTests { .. }
     )
 }
@@ -109,11 +120,21 @@ fn is_watch_build(request: &Request) -> bool {
     matches!(request, Request::WatchBuild { .. })
 }
 
+/// Check if a request is a watch subscription (requires streaming).
+fn is_watch_subscribe(request: &Request) -> bool {
+    matches!(request, Request::WatchSubscribe { .. })
+}
+
 /// Check if a request is a pkg install (requires streaming for progress).
 fn is_pkg_install(request: &Request) -> bool {
     matches!(request, Request::PkgInstall { .. })
 }
 
+/// Check if a request is a build that streams progress events.
+fn is_build_with_progress(request: &Request) -> bool {
+    matches!(request, Request::Build { progress: true, .. })
+}
+
 /// Handle watch build with streaming responses (v3.0).
 async fn handle_watch_build_streaming(
     mut stream: IpcStream,
@@ -164,8 +185,8 @@ async fn handle_watch_build_streaming(
     stream.write_all(&encoded).await?;
     stream.flush().await?;
 
-    // Create a channel for file change notifications
-    let (tx, mut rx) = mpsc::channel::<()>(16);
+    // Create a channel for file change notifications (changed paths)
+    let (tx, mut rx) = mpsc::channel::<Vec<PathBuf>>(16);
 
     // Subscribe watcher to the cwd
     if let Err(e) = state.watcher.watch_for_build(&cwd_path, tx) {
@@ -178,10 +199,11 @@ async fn handle_watch_build_streaming(
         return Ok(());
     }
 
-    // Helper to run a build and send result
-    let run_build = || {
+    // Helper to run a build and send result; `changed` scopes the build to
+    // the nodes affected by those paths
+    let run_build = |changed: Option<&[PathBuf]>| {
         let build_cache = Some(state.build_cache.clone());
-        let compiler = Some(state.compiler.clone());
+        let compiler = Some(state.build_compiler());
         handle_build(
             &cwd,
             false,
@@ -189,13 +211,17 @@ async fn handle_watch_build_streaming(
             max_parallel,
             false,
             &targets,
+            None,
+            None,
+            changed,
             build_cache,
             compiler,
+            None,
         )
     };
 
     // Run initial build
-    let initial_result = run_build();
+    let initial_result = run_build(None);
     let response = make_response_frame(initial_result);
     let encoded = encode_frame(&response)?;
     stream.write_all(&encoded).await?;
@@ -208,16 +234,18 @@ async fn handle_watch_build_streaming(
     loop {
         // Wait for file change notification or connection close
         tokio::select! {
-            _ = rx.recv() => {
+            Some(paths) = rx.recv() => {
                 // File changed - debounce
                 debug!("file change detected, debouncing...");
+                let mut changed: BTreeSet<PathBuf> = paths.into_iter().collect();
 
                 // Drain any additional events during debounce period
                 let deadline = tokio::time::Instant::now() + debounce_duration;
                 loop {
                     tokio::select! {
-                        _ = rx.recv() => {
+                        Some(paths) = rx.recv() => {
                             // More events, keep debouncing
+                            changed.extend(paths);
                         }
                         _ = tokio::time::sleep_until(deadline) => {
                             break;
@@ -225,13 +253,12 @@ async fn handle_watch_build_streaming(
                     }
                 }
 
-                debug!("debounce complete, rebuilding...");
+                debug!(count = changed.len(), "debounce complete, rebuilding...");
 
-                // Invalidate build cache for this cwd
-                state.build_cache.clear();
-
-                // Run build
-                let result = run_build();
+                // Run build for the nodes affected by the changed paths.
+                // Node hashes cover their inputs, so the cache stays valid.
+                let changed: Vec<PathBuf> = changed.into_iter().collect();
+                let result = run_build(Some(&changed));
                 let response = make_response_frame(result);
                 match encode_frame(&response) {
                     Ok(encoded) => {
@@ -272,31 +299,48 @@ async fn handle_watch_build_streaming(
     Ok(())
 }
 
-/// Handle pkg install with streaming progress responses.
-async fn handle_pkg_install_streaming(
+/// Convert a watcher change into its wire representation.
+fn to_wire_event(change: &crate::watch::FileChange) -> WatchFileEvent {
+    use crate::watch::WatchEventKind;
+
+    WatchFileEvent {
+        path: change.path.display().to_string(),
+        kind: match change.kind {
+            WatchEventKind::Create => WatchFileEventKind::Create,
+            WatchEventKind::Modify => WatchFileEventKind::Modify,
+            WatchEventKind::Remove => WatchFileEventKind::Remove,
+            WatchEventKind::Rename => WatchFileEventKind::Rename,
+            WatchEventKind::Other => WatchFileEventKind::Other,
+        },
+        timestamp_unix_ms: change.unix_ms,
+    }
+}
+
+/// Handle a watch subscription with streaming event batches.
+async fn handle_watch_subscribe_streaming(
     mut stream: IpcStream,
     frame: Frame,
-    _state: Arc<DaemonState>,
+    state: Arc<DaemonState>,
 ) -> io::Result<()> {
-    // Extract install parameters
-    let (cwd, channel, frozen, include_dev, include_optional) = match &frame.request {
-        Request::PkgInstall {
-            cwd,
-            channel,
-            frozen,
-            include_dev,
-            include_optional,
-        } => (
-            cwd.clone(),
-            channel.clone(),
-            *frozen,
-            *include_dev,
-            *include_optional,
-        ),
+    let filter: Vec<PathBuf> = match &frame.request {
+        Request::WatchSubscribe { paths } => paths.iter().map(PathBuf::from).collect(),
         _ => {
             let response = make_response_frame(Response::error(
                 codes::INTERNAL_ERROR,
-                "Expected PkgInstall request",
+                "Expected WatchSubscribe request",
+            ));
+            let encoded = encode_frame(&response)?;
+            stream.write_all(&encoded).await?;
+            return Ok(());
+        }
+    };
+
+    let mut rx = match state.watcher.subscribe() {
+        Ok(rx) => rx,
+        Err(e) => {
+            let response = make_response_frame(Response::error(
+                codes::WATCH_NOT_RUNNING,
+                format!("{e}; start it with `howth watch start`"),
             ));
             let encoded = encode_frame(&response)?;
             stream.write_all(&encoded).await?;
@@ -304,6 +348,98 @@ async fn handle_pkg_install_streaming(
         }
     };
 
+    info!(filter = ?filter, "watch subscriber connected");
+
+    let subscribed = make_response_frame(Response::WatchSubscribed {
+        roots: state.watcher.roots(),
+    });
+    let encoded = encode_frame(&subscribed)?;
+    stream.write_all(&encoded).await?;
+    stream.flush().await?;
+
+    let mut read_buf = [0u8; 1];
+    loop {
+        tokio::select! {
+            batch = rx.recv() => {
+                let Some(batch) = batch else {
+                    let response = make_response_frame(Response::WatchUnsubscribed {
+                        reason: "Watcher stopped".to_string(),
+                    });
+                    let encoded = encode_frame(&response)?;
+                    stream.write_all(&encoded).await?;
+                    stream.flush().await?;
+                    break;
+                };
+
+                let events: Vec<WatchFileEvent> = batch
+                    .iter()
+                    .filter(|c| filter.is_empty() || filter.iter().any(|f| c.path.starts_with(f)))
+                    .map(to_wire_event)
+                    .collect();
+                if events.is_empty() {
+                    continue;
+                }
+
+                let response = make_response_frame(Response::WatchEvents { events });
+                let encoded = encode_frame(&response)?;
+                if stream.write_all(&encoded).await.is_err() || stream.flush().await.is_err() {
+                    info!("watch subscriber disconnected");
+                    break;
+                }
+            }
+            // Check if stream is still open by trying to read
+            result = stream.read(&mut read_buf) => {
+                match result {
+                    Ok(0) | Err(_) => {
+                        info!("watch subscriber disconnected");
+                        break;
+                    }
+                    Ok(_) => {
+                        // Unexpected data - ignore
+                    }
+                }
+            }
+        }
+    }
+
+    Ok(())
+}
+
+/// Handle pkg install with streaming progress responses.
+async fn handle_pkg_install_streaming(
+    mut stream: IpcStream,
+    frame: Frame,
+    _state: Arc<DaemonState>,
+) -> io::Result<()> {
+    // Extract install parameters
+    let (cwd, channel, frozen, include_dev, include_optional, auto_install_peers) =
+        match &frame.request {
+            Request::PkgInstall {
+                cwd,
+                channel,
+                frozen,
+                include_dev,
+                include_optional,
+                auto_install_peers,
+            } => (
+                cwd.clone(),
+                channel.clone(),
+                *frozen,
+                *include_dev,
+                *include_optional,
+                *auto_install_peers,
+            ),
+            _ => {
+                let response = make_response_frame(Response::error(
+                    codes::INTERNAL_ERROR,
+                    "Expected PkgInstall request",
+                ));
+                let encoded = encode_frame(&response)?;
+                stream.write_all(&encoded).await?;
+                return Ok(());
+            }
+        };
+
     info!(cwd = %cwd, frozen, "starting streaming pkg install");
 
     // Create progress channel
@@ -317,6 +453,7 @@ async fn handle_pkg_install_streaming(
             frozen,
             include_dev,
             include_optional,
+            auto_install_peers,
             Some(tx),
         )
         .await
@@ -345,6 +482,81 @@ async fn handle_pkg_install_streaming(
     Ok(())
 }
 
+/// Handle a build with streaming progress responses.
+async fn handle_build_streaming(
+    mut stream: IpcStream,
+    frame: Frame,
+    state: Arc<DaemonState>,
+) -> io::Result<()> {
+    let Request::Build {
+        cwd,
+        force,
+        dry_run,
+        max_parallel,
+        profile,
+        targets,
+        remote_exec,
+        source_date_epoch,
+        ..
+    } = frame.request
+    else {
+        let response = make_response_frame(Response::error(
+            codes::INTERNAL_ERROR,
+            "Expected Build request",
+        ));
+        let encoded = encode_frame(&response)?;
+        stream.write_all(&encoded).await?;
+        return Ok(());
+    };
+
+    let (tx, mut rx) = mpsc::channel::<Response>(64);
+
+    // Builds are blocking; progress is forwarded from the build thread
+    let build_cache = Some(state.build_cache.clone());
+    let compiler = Some(state.build_compiler());
+    let span = tracing::Span::current();
+    let build_handle = tokio::task::spawn_blocking(move || {
+        let _span = span.enter();
+        let mut send_progress = |progress: BuildProgress<'_>| {
+            // A closed channel means the client went away; the build still finishes
+            let _ = tx.blocking_send(build_progress_response(progress));
+        };
+        handle_build(
+            &cwd,
+            force,
+            dry_run,
+            max_parallel,
+            profile,
+            &targets,
+            remote_exec.as_deref(),
+            source_date_epoch,
+            None,
+            build_cache,
+            compiler,
+            Some(&mut send_progress),
+        )
+    });
+
+    while let Some(progress) = rx.recv().await {
+        let response_frame = make_response_frame(progress);
+        let encoded = encode_frame(&response_frame)?;
+        stream.write_all(&encoded).await?;
+        stream.flush().await?;
+    }
+
+    let final_response = match build_handle.await {
+        Ok(response) => response,
+        Err(e) => Response::error(codes::INTERNAL_ERROR, format!("Build task panicked: {e}")),
+    };
+
+    let response_frame = make_response_frame(final_response);
+    let encoded = encode_frame(&response_frame)?;
+    stream.write_all(&encoded).await?;
+    stream.flush().await?;
+
+    Ok(())
+}
+
 /// Handle a single connection.
 async fn handle_connection(
     mut stream: IpcStream,
@@ -393,16 +605,39 @@ async fn handle_connection(
         "handling request"
     );
 
+    let span = info_span!("daemon.request", request = %frame.request.kind());
+    dispatch_frame(stream, frame, shutdown, state)
+        .instrument(span)
+        .await
+}
+
+/// Handle a decoded request frame and write the response(s).
+async fn dispatch_frame(
+    mut stream: IpcStream,
+    frame: Frame,
+    shutdown: Arc<AtomicBool>,
+    state: Arc<DaemonState>,
+) -> io::Result<()> {
     // v3.0: Watch build requires streaming handler
     if is_watch_build(&frame.request) {
         return handle_watch_build_streaming(stream, frame, state).await;
     }
 
+    // Watch subscriptions stream event batches until disconnect
+    if is_watch_subscribe(&frame.request) {
+        return handle_watch_subscribe_streaming(stream, frame, state).await;
+    }
+
     // Streaming progress for pkg install
     if is_pkg_install(&frame.request) {
         return handle_pkg_install_streaming(stream, frame, state).await;
     }
 
+    // Streaming progress for builds that ask for it
+    if is_build_with_progress(&frame.request) {
+        return handle_build_streaming(stream, frame, state).await;
+    }
+
     // Handle request - use async handler for pkg/test operations
     let (response, should_shutdown) = if is_async_request(&frame.request) {
         handle_request_async(
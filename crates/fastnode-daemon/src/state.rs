@@ -9,13 +9,15 @@ use crate::test_worker::NodeTestWorker;
 #[cfg(feature = "runtime")]
 use crate::v8_test_worker::V8TestWorker;
 use crate::watch::WatcherState;
-use fastnode_core::compiler::{CompilerBackend, SwcBackend};
+use fastnode_core::compiler::{backend_from_env, CachedBackend, CompilerBackend, TranspileCache};
 use fastnode_core::config::Channel;
 use fastnode_core::pkg::{PackageCache, RegistryClient};
 use std::sync::Arc;
 
 /// Shared daemon state containing cache and watcher.
 pub struct DaemonState {
+    /// Channel this daemon serves (selects its package cache).
+    pub channel: Channel,
     /// Resolver cache for import resolution.
     pub cache: Arc<DaemonResolverCache>,
     /// File watcher for cache invalidation.
@@ -28,6 +30,9 @@ pub struct DaemonState {
     pub build_cache: Arc<DaemonBuildCache>,
     /// Compiler backend for transpilation (v3.1).
     pub compiler: Arc<dyn CompilerBackend>,
+    /// Transpile results keyed by path, content hash and options, shared by
+    /// `Transpile` requests and builds.
+    pub transpile_cache: Arc<TranspileCache>,
     /// Shared registry client with persistent packument cache.
     pub registry: Arc<RegistryClient>,
     /// Warm Node.js test worker (lazy-started on first test run, fallback).
@@ -41,12 +46,14 @@ pub struct DaemonState {
 impl std::fmt::Debug for DaemonState {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         let d = &mut f.debug_struct("DaemonState");
-        d.field("cache", &self.cache)
+        d.field("channel", &self.channel)
+            .field("cache", &self.cache)
             .field("watcher", &self.watcher)
             .field("pkg_cache", &self.pkg_cache)
             .field("pkg_json_cache", &self.pkg_json_cache)
             .field("build_cache", &self.build_cache)
             .field("compiler", &self.compiler.name())
+            .field("transpile_cache", &self.transpile_cache.stats())
             .field("registry", &"RegistryClient")
             .field("test_worker", &"<Mutex>");
         #[cfg(feature = "runtime")]
@@ -59,7 +66,7 @@ impl DaemonState {
     /// Create new daemon state with empty cache and stopped watcher.
     #[must_use]
     pub fn new() -> Self {
-        Self::with_channel(Channel::Stable)
+        Self::with_channel(Channel::default())
     }
 
     /// Create new daemon state with the given channel.
@@ -70,7 +77,7 @@ impl DaemonState {
         let pkg_cache = Arc::new(PackageCache::new(channel));
         let pkg_json_cache = Arc::new(DaemonPkgJsonCache::new());
         let build_cache = Arc::new(DaemonBuildCache::new());
-        let compiler: Arc<dyn CompilerBackend> = Arc::new(SwcBackend::new());
+        let compiler = backend_from_env();
 
         // Create shared registry client with persistent packument cache
         let registry =
@@ -79,12 +86,14 @@ impl DaemonState {
             });
 
         Self {
+            channel,
             cache,
             watcher,
             pkg_cache,
             pkg_json_cache,
             build_cache,
             compiler,
+            transpile_cache: Arc::new(TranspileCache::new()),
             registry: Arc::new(registry),
             test_worker: tokio::sync::Mutex::new(None),
             #[cfg(feature = "runtime")]
@@ -95,11 +104,12 @@ impl DaemonState {
     /// Create daemon state with the given cache for invalidation.
     #[must_use]
     pub fn with_cache(cache: Arc<DaemonResolverCache>) -> Self {
+        let channel = Channel::default();
         let watcher = Arc::new(WatcherState::new());
-        let pkg_cache = Arc::new(PackageCache::new(Channel::Stable));
+        let pkg_cache = Arc::new(PackageCache::new(channel));
         let pkg_json_cache = Arc::new(DaemonPkgJsonCache::new());
         let build_cache = Arc::new(DaemonBuildCache::new());
-        let compiler: Arc<dyn CompilerBackend> = Arc::new(SwcBackend::new());
+        let compiler = backend_from_env();
 
         // Create shared registry client with persistent packument cache
         let registry =
@@ -108,12 +118,14 @@ impl DaemonState {
             });
 
         Self {
+            channel,
             cache,
             watcher,
             pkg_cache,
             pkg_json_cache,
             build_cache,
             compiler,
+            transpile_cache: Arc::new(TranspileCache::new()),
             registry: Arc::new(registry),
             test_worker: tokio::sync::Mutex::new(None),
             #[cfg(feature = "runtime")]
@@ -124,9 +136,10 @@ impl DaemonState {
     /// Create daemon state with a custom compiler backend.
     #[must_use]
     pub fn with_compiler(compiler: Arc<dyn CompilerBackend>) -> Self {
+        let channel = Channel::default();
         let cache = Arc::new(DaemonResolverCache::new());
         let watcher = Arc::new(WatcherState::new());
-        let pkg_cache = Arc::new(PackageCache::new(Channel::Stable));
+        let pkg_cache = Arc::new(PackageCache::new(channel));
         let pkg_json_cache = Arc::new(DaemonPkgJsonCache::new());
         let build_cache = Arc::new(DaemonBuildCache::new());
 
@@ -137,12 +150,14 @@ impl DaemonState {
             });
 
         Self {
+            channel,
             cache,
             watcher,
             pkg_cache,
             pkg_json_cache,
             build_cache,
             compiler,
+            transpile_cache: Arc::new(TranspileCache::new()),
             registry: Arc::new(registry),
             test_worker: tokio::sync::Mutex::new(None),
             #[cfg(feature = "runtime")]
@@ -151,6 +166,18 @@ impl DaemonState {
     }
 }
 
+impl DaemonState {
+    /// Compiler for builds: the warm backend behind the shared transpile
+    /// cache, so build nodes whose specs match earlier requests are hits.
+    #[must_use]
+    pub fn build_compiler(&self) -> Arc<dyn CompilerBackend> {
+        Arc::new(CachedBackend::new(
+            Arc::clone(&self.compiler),
+            Arc::clone(&self.transpile_cache),
+        ))
+    }
+}
+
 impl Default for DaemonState {
     fn default() -> Self {
         Self::new()
@@ -3,6 +3,7 @@
 //! Keeps a long-running Node.js child process that executes tests via `node:test`.
 //! Communication is newline-delimited JSON over stdin/stdout pipes.
 
+use fastnode_proto::{TestDiff, TestEnvironment};
 use serde::{Deserialize, Serialize};
 use std::io;
 use std::path::{Path, PathBuf};
@@ -20,6 +21,8 @@ pub struct TranspiledTestFile {
     pub path: String,
     /// Transpiled JavaScript code.
     pub code: String,
+    /// Globals the file runs with.
+    pub environment: TestEnvironment,
 }
 
 /// Message sent to the worker via stdin.
@@ -57,6 +60,12 @@ pub struct WorkerTestCase {
     #[serde(default)]
     pub duration_ms: f64,
     pub error: Option<String>,
+    #[serde(default)]
+    pub diff: Option<TestDiff>,
+    #[serde(default)]
+    pub retries: u32,
+    #[serde(default)]
+    pub flaky: bool,
 }
 
 /// Manages a warm Node.js child process for running tests.
@@ -145,7 +154,7 @@ impl NodeTestWorker {
     }
 
     /// Respawn the worker if it has died.
-    async fn ensure_alive(&mut self) -> io::Result<()> {
+    fn ensure_alive(&mut self) -> io::Result<()> {
         if !self.is_alive() {
             warn!("test worker died, respawning");
             let (child, stdin, stdout, stderr_drain) = Self::spawn_node(&self.worker_script_path)?;
@@ -172,7 +181,7 @@ impl NodeTestWorker {
         timeout_ms: Option<u64>,
         force_exit: bool,
     ) -> io::Result<WorkerResponse> {
-        self.ensure_alive().await?;
+        self.ensure_alive()?;
 
         self.next_id += 1;
         let id = format!("t{}", self.next_id);
@@ -301,3 +310,64 @@ pub fn cleanup_stale_temp_files(file_paths: &[String]) {
         }
     }
 }
+
+/// Docblock pragmas naming a file's test environment.
+const ENVIRONMENT_PRAGMAS: &[&str] = &[
+    "@howth-environment",
+    "@jest-environment",
+    "@vitest-environment",
+];
+
+/// Read the test environment from the docblock at the top of `source`.
+///
+/// Returns `None` without a docblock pragma, and `Err` with the name when
+/// the pragma names an unknown environment.
+pub fn docblock_environment(source: &str) -> Option<Result<TestEnvironment, String>> {
+    let mut rest = source.trim_start_matches('\u{feff}');
+    if rest.starts_with("#!") {
+        rest = rest.split_once('\n').map_or("", |(_, after)| after);
+    }
+    let comment = rest.trim_start().strip_prefix("/*")?;
+    let comment = &comment[..comment.find("*/")?];
+
+    let mut words = comment
+        .split_whitespace()
+        .map(|word| word.trim_start_matches('*'));
+    while let Some(word) = words.next() {
+        if ENVIRONMENT_PRAGMAS.contains(&word) {
+            let name = words.next().unwrap_or_default();
+            return Some(TestEnvironment::from_name(name).ok_or_else(|| name.to_string()));
+        }
+    }
+    None
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_docblock_environment() {
+        let source = "/**\n * @jest-environment jsdom\n */\nimport x from 'x';\n";
+        assert_eq!(docblock_environment(source), Some(Ok(TestEnvironment::Dom)));
+
+        let source = "#!/usr/bin/env howth\n/* @howth-environment node */\n";
+        assert_eq!(
+            docblock_environment(source),
+            Some(Ok(TestEnvironment::Node))
+        );
+
+        let source = "/** @vitest-environment browser */";
+        assert_eq!(
+            docblock_environment(source),
+            Some(Err("browser".to_string()))
+        );
+
+        // Only the leading comment counts
+        assert_eq!(docblock_environment("/** setup */"), None);
+        assert_eq!(
+            docblock_environment("const a = 1;\n/** @jest-environment jsdom */"),
+            None
+        );
+    }
+}
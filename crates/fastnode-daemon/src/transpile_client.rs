@@ -0,0 +1,173 @@
+//! Blocking client for the daemon's `Transpile` request.
+//!
+//! `DaemonCompiler` is a `CompilerBackend` that forwards each module to a
+//! running daemon so the native runtime and dev servers reuse its warm
+//! compiler and transpile cache. The full spec is sent, so the daemon's
+//! cache key covers the client's options. When the daemon cannot be reached it falls back to an
+//! in-process backend and stops trying for the rest of the process.
+
+use fastnode_core::compiler::{
+    CompilerBackend, CompilerError, HowthParserBackend, TranspileOutput, TranspileSpec,
+};
+use fastnode_core::VERSION;
+use fastnode_proto::{codes, read_frame, write_frame, Frame, FrameResponse, Request, Response};
+use std::io;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+
+/// Compiler backend that transpiles through the daemon.
+pub struct DaemonCompiler {
+    endpoint: String,
+    fallback: Arc<dyn CompilerBackend>,
+    available: AtomicBool,
+}
+
+impl std::fmt::Debug for DaemonCompiler {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("DaemonCompiler")
+            .field("endpoint", &self.endpoint)
+            .field("fallback", &self.fallback.name())
+            .field("available", &self.available.load(Ordering::Relaxed))
+            .finish()
+    }
+}
+
+impl DaemonCompiler {
+    /// Create a client for the daemon at `endpoint`, falling back to the
+    /// native in-process backend.
+    #[must_use]
+    pub fn new(endpoint: impl Into<String>) -> Self {
+        Self::with_fallback(endpoint, Arc::new(HowthParserBackend::new()))
+    }
+
+    /// Create a client with a custom fallback backend.
+    #[must_use]
+    pub fn with_fallback(endpoint: impl Into<String>, fallback: Arc<dyn CompilerBackend>) -> Self {
+        Self {
+            endpoint: endpoint.into(),
+            fallback,
+            available: AtomicBool::new(true),
+        }
+    }
+
+    /// Whether requests are still being sent to the daemon.
+    #[must_use]
+    pub fn is_available(&self) -> bool {
+        self.available.load(Ordering::Relaxed)
+    }
+
+    /// Send a `Transpile` request and wait for the response.
+    fn request(&self, spec: &TranspileSpec, source: &str) -> io::Result<Response> {
+        let frame = Frame::new(
+            VERSION,
+            Request::Transpile {
+                path: spec.input_path.to_string_lossy().into_owned(),
+                source: source.to_string(),
+                spec: serde_json::to_value(spec).ok(),
+            },
+        );
+        let mut stream = connect(&self.endpoint)?;
+        write_frame(&mut stream, &frame)?;
+        let response: FrameResponse = read_frame(&mut stream)?;
+        Ok(response.response)
+    }
+}
+
+impl CompilerBackend for DaemonCompiler {
+    fn name(&self) -> &'static str {
+        "daemon"
+    }
+
+    fn transpile(
+        &self,
+        spec: &TranspileSpec,
+        source: &str,
+    ) -> Result<TranspileOutput, CompilerError> {
+        if self.is_available() {
+            match self.request(spec, source) {
+                Ok(Response::TranspileResult {
+                    code, source_map, ..
+                }) => {
+                    let output = TranspileOutput::new(code);
+                    return Ok(match source_map {
+                        Some(map) => output.with_source_map(map),
+                        None => output,
+                    });
+                }
+                Ok(Response::Error { code, message }) if code == codes::TRANSPILE_FAILED => {
+                    return Err(CompilerError::transform_error(message));
+                }
+                // Unreachable daemon, protocol mismatch or an unexpected
+                // response: stop asking and compile locally from now on.
+                Ok(_) | Err(_) => self.available.store(false, Ordering::Relaxed),
+            }
+        }
+        self.fallback.transpile(spec, source)
+    }
+}
+
+#[cfg(unix)]
+fn connect(endpoint: &str) -> io::Result<std::os::unix::net::UnixStream> {
+    std::os::unix::net::UnixStream::connect(endpoint)
+}
+
+#[cfg(windows)]
+fn connect(endpoint: &str) -> io::Result<std::fs::File> {
+    std::fs::OpenOptions::new()
+        .read(true)
+        .write(true)
+        .open(endpoint)
+}
+
+#[cfg(all(test, unix))]
+mod tests {
+    use super::*;
+    use std::os::unix::net::UnixListener;
+
+    #[test]
+    fn test_falls_back_when_daemon_is_down() {
+        let dir = tempfile::tempdir().unwrap();
+        let endpoint = dir.path().join("missing.sock");
+        let compiler = DaemonCompiler::new(endpoint.to_string_lossy());
+
+        let spec = TranspileSpec::new("/app/main.ts", "/app/main.ts");
+        let output = compiler.transpile(&spec, "let a: number = 1;").unwrap();
+        assert!(output.code.contains("let a = 1"));
+        assert!(!compiler.is_available());
+    }
+
+    #[test]
+    fn test_forwards_to_daemon() {
+        let dir = tempfile::tempdir().unwrap();
+        let endpoint = dir.path().join("daemon.sock");
+        let listener = UnixListener::bind(&endpoint).unwrap();
+
+        let server = std::thread::spawn(move || {
+            let (mut stream, _) = listener.accept().unwrap();
+            let frame: Frame = read_frame(&mut stream).unwrap();
+            let Request::Transpile { path, spec, .. } = frame.request else {
+                panic!("Expected Transpile");
+            };
+            let spec: TranspileSpec = serde_json::from_value(spec.unwrap()).unwrap();
+            let response = FrameResponse::new(
+                VERSION,
+                Response::TranspileResult {
+                    code: format!("// from daemon: {path}\n"),
+                    cached: false,
+                    source_map: spec.react_refresh.then(|| "{}".to_string()),
+                },
+            );
+            write_frame(&mut stream, &response).unwrap();
+        });
+
+        let compiler = DaemonCompiler::new(endpoint.to_string_lossy());
+        let mut spec = TranspileSpec::new("/app/main.ts", "/app/main.ts");
+        spec.react_refresh = true;
+        let output = compiler.transpile(&spec, "let a = 1;").unwrap();
+        server.join().unwrap();
+
+        assert_eq!(output.code, "// from daemon: /app/main.ts\n");
+        assert_eq!(output.source_map.as_deref(), Some("{}"));
+        assert!(compiler.is_available());
+    }
+}
@@ -57,6 +57,7 @@ fn js_string_literal(value: &str) -> String {
 struct V8Request {
     id: String,
     files: Vec<TranspiledTestFile>,
+    keep_tmp: bool,
     reply: mpsc::Sender<io::Result<WorkerResponse>>,
 }
 
@@ -109,6 +110,7 @@ impl V8TestWorker {
         id: String,
         files: Vec<TranspiledTestFile>,
         timeout_ms: Option<u64>,
+        keep_tmp: bool,
     ) -> io::Result<WorkerResponse> {
         let (reply_tx, reply_rx) = mpsc::channel();
 
@@ -116,6 +118,7 @@ impl V8TestWorker {
             .send(V8Request {
                 id,
                 files,
+                keep_tmp,
                 reply: reply_tx,
             })
             .map_err(|_| io::Error::new(io::ErrorKind::BrokenPipe, "V8 worker thread died"))?;
@@ -178,6 +181,7 @@ async fn v8_worker_loop(rx: mpsc::Receiver<V8Request>, temp_dir: &std::path::Pat
             &mut runtime,
             &req.id,
             &req.files,
+            req.keep_tmp,
             temp_dir,
             &virtual_modules,
         )
@@ -193,6 +197,7 @@ async fn run_tests_in_v8(
     runtime: &mut fastnode_runtime::Runtime,
     id: &str,
     files: &[TranspiledTestFile],
+    keep_tmp: bool,
     temp_dir: &std::path::Path,
     virtual_modules: &Rc<RefCell<HashMap<String, String>>>,
 ) -> io::Result<WorkerResponse> {
@@ -213,6 +218,11 @@ async fn run_tests_in_v8(
         runner_code.push_str(&js_string_literal(root));
         runner_code.push_str(");\n");
     }
+    runner_code.push_str(&format!("globalThis.__howth_keep_tmp = {keep_tmp};\n"));
+    // Jest-style global expect, unless a test setup already installed one
+    runner_code.push_str(
+        "if (typeof globalThis.expect === 'undefined') globalThis.expect = globalThis.__howth_modules[\"node:test\"].expect;\n",
+    );
     runner_code.push_str("try {\n");
     for (i, file) in files.iter().enumerate() {
         let file_dir = PathBuf::from(&file.path)
@@ -223,6 +233,7 @@ async fn run_tests_in_v8(
         runner_code.push_str(&format!(
             concat!(
                 "  console.error(\"[howth] loading [{i}/{total}] {short_name}\");\n",
+                "  globalThis.__howth_begin_test_file({path}, \"{environment}\");\n",
                 "  globalThis.__howth_main_module_path = {path};\n",
                 "  globalThis.__filename = {path};\n",
                 "  globalThis.__dirname = {dir};\n",
@@ -233,6 +244,7 @@ async fn run_tests_in_v8(
             total = files.len(),
             short_name = short_name,
             path = js_string_literal(&file.path),
+            environment = file.environment.as_str(),
             dir = js_string_literal(&file_dir),
             source = js_string_literal(&file.code),
         ));
@@ -246,6 +258,9 @@ async fn run_tests_in_v8(
     tests: [{ name: "test-runner", status: "fail", duration_ms: 0, error: String(e && e.stack || e) }],
   });
 } finally {
+  // Fake timers and DOM globals never outlive a run, even one that failed while loading
+  globalThis.__howth_fake_clock.uninstall();
+  globalThis.__howth_dom.uninstall();
   // Close any Sequelize connections so db:drop works on the next run
   try {
     const _m = globalThis.__howth_modules;
@@ -286,6 +301,9 @@ async fn run_tests_in_v8(
                 status: "fail".to_string(),
                 duration_ms: 0.0,
                 error: Some(format!("Failed to execute: {e}")),
+                diff: None,
+                retries: 0,
+                flaky: false,
             }],
             diagnostics: String::new(),
         });
@@ -345,6 +363,17 @@ async fn run_tests_in_v8(
                             e.as_str().map(String::from)
                         }
                     }),
+                    diff: t
+                        .get("diff")
+                        .and_then(|d| serde_json::from_value(d.clone()).ok()),
+                    retries: t
+                        .get("retries")
+                        .and_then(serde_json::Value::as_u64)
+                        .map_or(0, |r| u32::try_from(r).unwrap_or(u32::MAX)),
+                    flaky: t
+                        .get("flaky")
+                        .and_then(serde_json::Value::as_bool)
+                        .unwrap_or(false),
                 })
                 .collect()
         })
@@ -0,0 +1,239 @@
+//! Normalization of raw notify events.
+//!
+//! Editors rarely write a file in place. They write a temp file and rename
+//! it over the original (VS Code, most atomic writers), or move the original
+//! aside to a backup and write a new one (vim, `JetBrains` "safe write").
+//! Reported naively, one save becomes a delete and a create, often in
+//! different batches, and triggers two rebuilds. [`EventNormalizer`] pairs
+//! the two sides of each rename and turns a temp file renamed over a source
+//! file into a single modify. Editor temp files themselves (see
+//! [`is_editor_temp`]) are never reported. A delete followed by a create of
+//! the same path is coalesced into a modify by
+//! [`WatchEventKind::coalesce`](super::WatchEventKind::coalesce).
+
+use super::{should_process_event, WatchEvent, WatchEventKind};
+use notify::event::{ModifyKind, RenameMode};
+use notify::{Event, EventKind};
+use std::path::{Path, PathBuf};
+
+/// Number of vim's "can we create files here" probe file.
+const VIM_PROBE_FILE: u32 = 4913;
+
+/// Check whether a path is a temp, swap or backup file written by an editor.
+#[must_use]
+pub fn is_editor_temp(path: &Path) -> bool {
+    let Some(name) = path.file_name().and_then(|n| n.to_str()) else {
+        return false;
+    };
+    // Backups (`index.ts~`), vim swap files, and `*.tmp`
+    name.ends_with('~')
+        || [".swp", ".swo", ".swx", ".tmp"]
+            .iter()
+            .any(|ext| name.ends_with(ext))
+        // Emacs lock files and auto-saves
+        || name.starts_with(".#")
+        || (name.len() > 1 && name.starts_with('#') && name.ends_with('#'))
+        // JetBrains safe write
+        || name.ends_with("___jb_tmp___")
+        || name.ends_with("___jb_old___")
+        // vim probes 4913, then 4913 + 123, ... until a name is free
+        || name
+            .parse::<u32>()
+            .is_ok_and(|n| n >= VIM_PROBE_FILE && (n - VIM_PROBE_FILE).rem_euclid(123) == 0)
+}
+
+/// Turns raw notify events into watch events, pairing the two sides of
+/// renames.
+///
+/// Every backend reports the old side of a rename immediately before the
+/// new one (inotify and Windows as `From`/`To`, `FSEvents` as two `Any`
+/// events told apart by whether the path still exists).
+#[derive(Debug, Default)]
+pub struct EventNormalizer {
+    /// Old side of the rename in progress.
+    rename_from: Option<PathBuf>,
+}
+
+impl EventNormalizer {
+    /// Normalize one raw event. Editor temp paths are still included; the
+    /// caller filters them with the other ignored paths.
+    pub fn normalize(&mut self, event: &Event) -> Vec<WatchEvent> {
+        if let EventKind::Modify(ModifyKind::Name(mode)) = event.kind {
+            return self.rename(mode, &event.paths);
+        }
+
+        self.rename_from = None;
+        if !should_process_event(event) {
+            return Vec::new();
+        }
+        vec![WatchEvent {
+            paths: event.paths.clone(),
+            kind: WatchEventKind::from(&event.kind),
+        }]
+    }
+
+    fn rename(&mut self, mode: RenameMode, paths: &[PathBuf]) -> Vec<WatchEvent> {
+        let Some(path) = paths.first() else {
+            return Vec::new();
+        };
+        let arrived = match mode {
+            RenameMode::From => false,
+            RenameMode::To => true,
+            // inotify repeats a paired From/To as one event
+            RenameMode::Both => return Vec::new(),
+            RenameMode::Any | RenameMode::Other => path.exists(),
+        };
+
+        if !arrived {
+            // Reported as removed unless the new side pairs with it
+            self.rename_from = Some(path.clone());
+            return vec![WatchEvent {
+                paths: vec![path.clone()],
+                kind: WatchEventKind::Remove,
+            }];
+        }
+
+        let (paths, kind) = match self.rename_from.take() {
+            // The original moved aside to a backup; the new file is coming
+            _ if is_editor_temp(path) => return Vec::new(),
+            // A temp file renamed over the original: an atomic save
+            Some(from) if is_editor_temp(&from) => (vec![path.clone()], WatchEventKind::Modify),
+            Some(from) => (vec![from, path.clone()], WatchEventKind::Rename),
+            // Moved in from outside the watched tree
+            None => (vec![path.clone()], WatchEventKind::Create),
+        };
+        vec![WatchEvent { paths, kind }]
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use notify::event::{CreateKind, DataChange, RemoveKind};
+
+    fn raw(kind: EventKind, paths: &[&Path]) -> Event {
+        paths.iter().fold(Event::new(kind), |event, path| {
+            event.add_path(path.to_path_buf())
+        })
+    }
+
+    fn name(mode: RenameMode, paths: &[&Path]) -> Event {
+        raw(EventKind::Modify(ModifyKind::Name(mode)), paths)
+    }
+
+    /// Normalize a sequence, then coalesce per path as the event loop does.
+    fn coalesced(events: &[Event]) -> Vec<(PathBuf, WatchEventKind)> {
+        let mut normalizer = EventNormalizer::default();
+        let mut changes: Vec<(PathBuf, WatchEventKind)> = Vec::new();
+        for event in events.iter().flat_map(|e| normalizer.normalize(e)) {
+            for path in event.paths.into_iter().filter(|p| !is_editor_temp(p)) {
+                match changes.iter_mut().find(|(p, _)| *p == path) {
+                    Some((_, kind)) => *kind = kind.coalesce(event.kind),
+                    None => changes.push((path, event.kind)),
+                }
+            }
+        }
+        changes
+    }
+
+    #[test]
+    fn test_is_editor_temp() {
+        for temp in [
+            "index.ts~",
+            ".index.ts.swp",
+            ".index.ts.swx",
+            "4913",
+            "5036",
+            ".#index.ts",
+            "#index.ts#",
+            "index.ts___jb_tmp___",
+            "index.ts___jb_old___",
+            "index.ts.tmp",
+        ] {
+            assert!(is_editor_temp(Path::new(temp)), "{temp}");
+        }
+        for source in ["index.ts", "4914", "404", "#", "swp.ts", "README.md"] {
+            assert!(!is_editor_temp(Path::new(source)), "{source}");
+        }
+    }
+
+    #[test]
+    fn test_temp_renamed_over_original_is_modify() {
+        let dir = Path::new("/proj/src");
+        let (tmp, target) = (dir.join("index.ts.tmp"), dir.join("index.ts"));
+        // inotify: create and write the temp file, then rename it
+        let events = [
+            raw(EventKind::Create(CreateKind::File), &[&tmp]),
+            raw(
+                EventKind::Modify(ModifyKind::Data(DataChange::Any)),
+                &[&tmp],
+            ),
+            name(RenameMode::From, &[&tmp]),
+            name(RenameMode::To, &[&target]),
+            name(RenameMode::Both, &[&tmp, &target]),
+        ];
+        assert_eq!(coalesced(&events), [(target, WatchEventKind::Modify)]);
+    }
+
+    #[test]
+    fn test_backup_and_rewrite_is_modify() {
+        // vim: probe, move the original aside, write a new one, drop the backup
+        let dir = Path::new("/proj/src");
+        let (target, backup, probe) = (dir.join("a.ts"), dir.join("a.ts~"), dir.join("4913"));
+        let events = [
+            raw(EventKind::Create(CreateKind::File), &[&probe]),
+            raw(EventKind::Remove(RemoveKind::File), &[&probe]),
+            name(RenameMode::From, &[&target]),
+            name(RenameMode::To, &[&backup]),
+            raw(EventKind::Create(CreateKind::File), &[&target]),
+            raw(
+                EventKind::Modify(ModifyKind::Data(DataChange::Any)),
+                &[&target],
+            ),
+            raw(EventKind::Remove(RemoveKind::File), &[&backup]),
+        ];
+        assert_eq!(coalesced(&events), [(target, WatchEventKind::Modify)]);
+    }
+
+    #[test]
+    fn test_rename_sides_are_paired() {
+        let dir = tempfile::tempdir().unwrap();
+        let (from, to) = (dir.path().join("a.ts"), dir.path().join("b.ts"));
+        let events = [
+            name(RenameMode::From, &[&from]),
+            name(RenameMode::To, &[&to]),
+            name(RenameMode::Both, &[&from, &to]),
+        ];
+        assert_eq!(
+            coalesced(&events),
+            [
+                (from.clone(), WatchEventKind::Rename),
+                (to.clone(), WatchEventKind::Rename)
+            ]
+        );
+
+        // FSEvents: unpaired sides, told apart by existence
+        std::fs::write(&to, "").unwrap();
+        let events = [
+            name(RenameMode::Any, &[&from]),
+            name(RenameMode::Any, &[&to]),
+        ];
+        assert_eq!(
+            coalesced(&events),
+            [
+                (from.clone(), WatchEventKind::Rename),
+                (to.clone(), WatchEventKind::Rename)
+            ]
+        );
+
+        // Moved out of and into the watched tree
+        assert_eq!(
+            coalesced(&[name(RenameMode::From, &[&from])]),
+            [(from, WatchEventKind::Remove)]
+        );
+        assert_eq!(
+            coalesced(&[name(RenameMode::To, &[&to])]),
+            [(to, WatchEventKind::Create)]
+        );
+    }
+}
@@ -0,0 +1,272 @@
+//! Ignore rules for the file watcher.
+//!
+//! Combines built-in ignores (`node_modules`, `.git`, `dist`), the `.gitignore`
+//! file at each watch root, and user-configured globs for the watch session.
+//! Paths matched by the project's `.howthignore` (see
+//! [`fastnode_core::howthignore`]) are always ignored.
+//! Rules follow gitignore semantics, using the same matcher as
+//! `.howthignore` ([`fastnode_core::howthignore::IgnoreRule`]).
+
+use fastnode_core::howthignore::{is_ignored_by, HowthIgnore, IgnoreRule};
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+
+/// Directories that are always ignored unless re-included by a later rule.
+pub const BUILTIN_IGNORES: &[&str] = &["node_modules/", ".git/", "dist/"];
+
+/// A parsed rule and the watch root it belongs to.
+#[derive(Debug, Clone)]
+struct ScopedRule {
+    /// Root the rule is relative to. `None` applies to every watch root.
+    base: Option<PathBuf>,
+    rule: IgnoreRule,
+}
+
+/// Effective ignore rules for a watch session.
+#[derive(Debug, Clone)]
+pub struct IgnoreRules {
+    /// Watch roots (used to relativize event paths).
+    roots: Vec<PathBuf>,
+    /// Parsed rules in evaluation order.
+    rules: Vec<ScopedRule>,
+    /// Effective pattern list, for status reporting.
+    patterns: Vec<String>,
+    /// `.howthignore` rules that apply to the watch roots.
+    howthignores: Vec<Arc<HowthIgnore>>,
+}
+
+impl Default for IgnoreRules {
+    fn default() -> Self {
+        Self::new(&[], &[])
+    }
+}
+
+impl IgnoreRules {
+    /// Build ignore rules for the given roots.
+    ///
+    /// Rules are evaluated in order: built-ins, each root's `.gitignore`, then
+    /// the user-configured globs (which apply to every root).
+    #[must_use]
+    pub fn new(roots: &[PathBuf], user_globs: &[String]) -> Self {
+        let mut rules = Self {
+            roots: Vec::new(),
+            rules: Vec::new(),
+            patterns: Vec::new(),
+            howthignores: Vec::new(),
+        };
+
+        for pattern in BUILTIN_IGNORES {
+            rules.push(pattern);
+        }
+        for root in roots {
+            rules.add_root(root);
+        }
+        for glob in user_globs {
+            rules.push(glob);
+        }
+
+        rules
+    }
+
+    /// Add a watch root, loading its `.gitignore` and the project's
+    /// `.howthignore` if present.
+    ///
+    /// Rules from the new `.gitignore` are evaluated before user globs so that
+    /// user configuration keeps the final say.
+    pub fn add_root(&mut self, root: &Path) {
+        if self.roots.iter().any(|r| r == root) {
+            return;
+        }
+        self.roots.push(root.to_path_buf());
+
+        let howthignore = HowthIgnore::load(root);
+        if !howthignore.is_empty()
+            && !self
+                .howthignores
+                .iter()
+                .any(|h| h.root() == howthignore.root())
+        {
+            for pattern in howthignore.patterns() {
+                if !self.patterns.contains(pattern) {
+                    self.patterns.push(pattern.clone());
+                }
+            }
+            self.howthignores.push(howthignore);
+        }
+
+        let Ok(content) = std::fs::read_to_string(root.join(".gitignore")) else {
+            return;
+        };
+
+        let insert_at = self
+            .rules
+            .iter()
+            .rposition(|r| r.base.is_some())
+            .map_or(BUILTIN_IGNORES.len(), |i| i + 1);
+        let mut offset = 0;
+        for line in content.lines() {
+            if let Some(rule) = IgnoreRule::parse(line) {
+                let base = Some(root.to_path_buf());
+                self.rules
+                    .insert(insert_at + offset, ScopedRule { base, rule });
+                offset += 1;
+                let line = line.trim_end().to_string();
+                if !self.patterns.contains(&line) {
+                    self.patterns.push(line);
+                }
+            }
+        }
+    }
+
+    /// Append a rule that applies to every root.
+    fn push(&mut self, pattern: &str) {
+        if let Some(rule) = IgnoreRule::parse(pattern) {
+            self.rules.push(ScopedRule { base: None, rule });
+            let pattern = pattern.trim_end().to_string();
+            if !self.patterns.contains(&pattern) {
+                self.patterns.push(pattern);
+            }
+        }
+    }
+
+    /// The effective ignore patterns, in evaluation order.
+    #[must_use]
+    pub fn patterns(&self) -> &[String] {
+        &self.patterns
+    }
+
+    /// Check whether an event path should be ignored.
+    ///
+    /// Paths outside every watch root are matched relative to nothing, so only
+    /// unanchored rules apply to them.
+    #[must_use]
+    pub fn is_ignored(&self, path: &Path) -> bool {
+        self.check(path, false)
+    }
+
+    /// Check whether a directory (and so everything below it) is ignored.
+    #[must_use]
+    pub fn is_ignored_dir(&self, path: &Path) -> bool {
+        self.check(path, true)
+    }
+
+    fn check(&self, path: &Path, is_dir: bool) -> bool {
+        if self.howthignores.iter().any(|h| h.is_ignored(path, is_dir)) {
+            return true;
+        }
+
+        let root = self
+            .roots
+            .iter()
+            .filter(|r| path.starts_with(r))
+            .max_by_key(|r| r.as_os_str().len());
+        let rel = root.map_or(path, |r| path.strip_prefix(r).unwrap_or(path));
+
+        let rules = self
+            .rules
+            .iter()
+            .filter(|r| r.base.as_ref().is_none_or(|base| root == Some(base)))
+            .map(|r| &r.rule);
+        is_ignored_by(rules, rel, is_dir)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_builtin_ignores() {
+        let root = PathBuf::from("/proj");
+        let rules = IgnoreRules::new(std::slice::from_ref(&root), &[]);
+        assert!(rules.is_ignored(&root.join("node_modules/react/index.js")));
+        assert!(rules.is_ignored(&root.join("packages/a/node_modules/x.js")));
+        assert!(rules.is_ignored(&root.join(".git/HEAD")));
+        assert!(rules.is_ignored(&root.join("dist/index.js")));
+        assert!(!rules.is_ignored(&root.join("src/index.ts")));
+        // A file named like a built-in directory is not ignored
+        assert!(!rules.is_ignored(&root.join("src/dist")));
+        assert!(rules.is_ignored_dir(&root.join("src/dist")));
+        assert!(!rules.is_ignored_dir(&root.join("src")));
+    }
+
+    #[test]
+    fn test_gitignore_rules() {
+        let dir = tempfile::tempdir().unwrap();
+        let root = dir.path().to_path_buf();
+        std::fs::write(
+            root.join(".gitignore"),
+            "# comment\n*.log\n/coverage\nbuild/\n!important.log\n",
+        )
+        .unwrap();
+
+        let rules = IgnoreRules::new(std::slice::from_ref(&root), &[]);
+        assert!(rules.is_ignored(&root.join("debug.log")));
+        assert!(rules.is_ignored(&root.join("src/nested/debug.log")));
+        assert!(!rules.is_ignored(&root.join("important.log")));
+        assert!(rules.is_ignored(&root.join("coverage/lcov.info")));
+        assert!(!rules.is_ignored(&root.join("src/coverage/x.ts")));
+        assert!(rules.is_ignored(&root.join("src/build/out.js")));
+        assert!(!rules.is_ignored(&root.join("src/index.ts")));
+        assert!(rules.patterns().contains(&"*.log".to_string()));
+        assert!(!rules.patterns().iter().any(|p| p.starts_with('#')));
+    }
+
+    #[test]
+    fn test_user_globs_and_negation() {
+        let root = PathBuf::from("/proj");
+        let rules = IgnoreRules::new(
+            std::slice::from_ref(&root),
+            &[
+                "*.snap".to_string(),
+                "!dist/".to_string(),
+                "tmp/**".to_string(),
+            ],
+        );
+        assert!(rules.is_ignored(&root.join("src/__snapshots__/a.snap")));
+        assert!(rules.is_ignored(&root.join("tmp/a/b.txt")));
+        assert!(
+            IgnoreRules::new(std::slice::from_ref(&root), &["*.{log,tmp}".to_string()])
+                .is_ignored(&root.join("src/x.tmp"))
+        );
+        // User globs can re-include built-ins
+        assert!(!rules.is_ignored(&root.join("dist/index.js")));
+        assert_eq!(
+            rules.patterns(),
+            [
+                "node_modules/",
+                ".git/",
+                "dist/",
+                "*.snap",
+                "!dist/",
+                "tmp/**"
+            ]
+        );
+    }
+
+    #[test]
+    fn test_gitignore_scoped_to_root() {
+        let a = tempfile::tempdir().unwrap();
+        let b = tempfile::tempdir().unwrap();
+        std::fs::write(a.path().join(".gitignore"), "*.gen.ts\n").unwrap();
+
+        let mut rules = IgnoreRules::new(&[a.path().to_path_buf()], &[]);
+        rules.add_root(b.path());
+        assert!(rules.is_ignored(&a.path().join("x.gen.ts")));
+        assert!(!rules.is_ignored(&b.path().join("x.gen.ts")));
+    }
+
+    #[test]
+    fn test_howthignore_applies_below_its_directory() {
+        let dir = tempfile::tempdir().unwrap();
+        let root = dir.path().join("packages/app");
+        std::fs::create_dir_all(&root).unwrap();
+        std::fs::write(dir.path().join(".howthignore"), "generated/\n").unwrap();
+
+        let rules = IgnoreRules::new(std::slice::from_ref(&root), &["!generated/".to_string()]);
+        assert!(rules.is_ignored(&root.join("src/generated/api.ts")));
+        assert!(rules.is_ignored_dir(&root.join("generated")));
+        assert!(!rules.is_ignored(&root.join("src/api.ts")));
+        assert!(rules.patterns().contains(&"generated/".to_string()));
+    }
+}
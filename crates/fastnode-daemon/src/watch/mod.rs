@@ -1,13 +1,35 @@
 //! File watcher for cache invalidation.
 //!
 //! Watches directories for file changes and invalidates resolver cache entries.
+//! Events under ignored paths (see [`IgnoreRules`]) and editor temp files are
+//! dropped before processing, and the events of an atomic save are reduced to
+//! a single modify (see [`coalesce`]).
+//!
+//! Events come from native OS notifications or, where those are unreliable
+//! (network filesystems, Docker volumes) or fail to start, from a polling
+//! backend that rescans the roots (see [`WatchBackend`]).
+//!
+//! Roots and changed paths are handled in their plain spelling (see
+//! [`fastnode_util::fs::simplified`]), so a root given as a `\\?\` or UNC
+//! path invalidates the same cache entries as the resolver created.
+
+pub mod coalesce;
+mod ignore;
+mod poll;
+
+pub use coalesce::is_editor_temp;
+pub use ignore::{IgnoreRules, BUILTIN_IGNORES};
+pub use poll::{PollConfig, DEFAULT_HASH_LIMIT, DEFAULT_POLL_INTERVAL};
 
 use crate::cache::{DaemonBuildCache, DaemonPkgJsonCache, DaemonResolverCache};
+use coalesce::EventNormalizer;
+use fastnode_util::fs as fs_util;
 use notify::{
-    event::{CreateKind, ModifyKind, RemoveKind, RenameMode},
+    event::{CreateKind, ModifyKind, RemoveKind},
     Config, Event, EventKind, RecommendedWatcher, RecursiveMode, Watcher as NotifyWatcher,
 };
-use std::collections::HashSet;
+use poll::PollWatcher;
+use std::collections::HashMap;
 use std::path::PathBuf;
 use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
 use std::sync::{Arc, Mutex, RwLock};
@@ -18,6 +40,10 @@ use tracing::{debug, error, info, warn};
 /// Event coalescing window.
 const COALESCE_WINDOW_MS: u64 = 50;
 
+/// How long a removal is held back in case the file is recreated (an
+/// editor's atomic save), so the pair is delivered as one modify.
+const ATOMIC_SAVE_WINDOW_MS: u64 = 150;
+
 /// Watcher state.
 #[derive(Debug)]
 pub struct WatcherState {
@@ -25,11 +51,17 @@ pub struct WatcherState {
     roots: RwLock<Vec<String>>,
     /// Whether the watcher is running.
     running: AtomicBool,
+    /// Ignore rules for the current watch session.
+    ignore: Arc<RwLock<IgnoreRules>>,
     /// Timestamp of last invalidation event (ms since Unix epoch).
     /// Updated AFTER invalidation is applied.
     last_event_unix_ms: Arc<AtomicU64>,
     /// The actual watcher handle (when running).
-    watcher: Mutex<Option<RecommendedWatcher>>,
+    watcher: Mutex<Option<Backend>>,
+    /// Backend selection for the current watch session.
+    options: RwLock<WatchOptions>,
+    /// Why the native backend failed, if polling was selected automatically.
+    fallback_reason: RwLock<Option<String>>,
     /// Event sender for async processing.
     event_tx: Mutex<Option<mpsc::UnboundedSender<WatchEvent>>>,
     /// Optional reference to resolver cache for invalidation.
@@ -38,10 +70,57 @@ pub struct WatcherState {
     pkg_json_cache: Mutex<Option<Arc<DaemonPkgJsonCache>>>,
     /// Optional reference to build cache for invalidation.
     build_cache: Mutex<Option<Arc<DaemonBuildCache>>>,
-    /// Build watch subscribers (v3.0): directory path -> notification senders.
-    build_watchers: Arc<Mutex<Vec<(PathBuf, mpsc::Sender<()>)>>>,
+    /// Build watch subscribers (v3.0): directory path -> senders receiving
+    /// the changed paths under that directory.
+    build_watchers: Arc<Mutex<Vec<(PathBuf, mpsc::Sender<Vec<PathBuf>>)>>>,
+    /// Event subscribers: receive every coalesced batch of file changes.
+    event_subscribers: Arc<Mutex<Vec<mpsc::Sender<Vec<FileChange>>>>>,
+}
+
+/// Which backend produces file events.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum WatchBackend {
+    /// Native notifications, falling back to polling if they fail.
+    #[default]
+    Auto,
+    /// Native notifications only.
+    Native,
+    /// Periodic rescans only.
+    Poll,
+}
+
+/// Backend options for a watch session.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct WatchOptions {
+    /// Requested backend.
+    pub backend: WatchBackend,
+    /// Polling configuration, used when polling.
+    pub poll: PollConfig,
+}
+
+/// The backend a running watcher uses.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct BackendStatus {
+    /// [`WatchBackend::Native`] or [`WatchBackend::Poll`].
+    pub backend: WatchBackend,
+    /// Rescan interval, when polling.
+    pub poll_interval: Option<Duration>,
+    /// Whether the last rescan compared file contents.
+    pub content_hashing: bool,
+    /// Why the native backend failed, if polling was selected automatically.
+    pub fallback_reason: Option<String>,
 }
 
+/// A running watcher backend.
+#[derive(Debug)]
+enum Backend {
+    Native(RecommendedWatcher),
+    Poll(PollWatcher),
+}
+
+/// Capacity of each event subscriber channel (in batches).
+const SUBSCRIBER_CHANNEL_CAPACITY: usize = 64;
+
 /// Watcher event for internal processing.
 #[derive(Debug, Clone)]
 pub struct WatchEvent {
@@ -51,6 +130,17 @@ pub struct WatchEvent {
     pub kind: WatchEventKind,
 }
 
+/// A coalesced file change delivered to event subscribers.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct FileChange {
+    /// Path that changed.
+    pub path: PathBuf,
+    /// Kind of change, coalesced over the batch window.
+    pub kind: WatchEventKind,
+    /// When the last event for this path was observed (ms since Unix epoch).
+    pub unix_ms: u64,
+}
+
 /// Kind of watch event.
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub enum WatchEventKind {
@@ -61,10 +151,27 @@ pub enum WatchEventKind {
     Other,
 }
 
+impl WatchEventKind {
+    /// Merge a later event for the same path into this one.
+    ///
+    /// A file created and then written is still a create, and a file removed
+    /// and then recreated (an atomic save) is a modify. Otherwise the later
+    /// event wins.
+    #[must_use]
+    pub fn coalesce(self, next: Self) -> Self {
+        match (self, next) {
+            (Self::Create, Self::Modify | Self::Other) => Self::Create,
+            (Self::Remove, Self::Create) => Self::Modify,
+            (_, next) => next,
+        }
+    }
+}
+
 impl From<&EventKind> for WatchEventKind {
     fn from(kind: &EventKind) -> Self {
         match kind {
             EventKind::Create(_) => Self::Create,
+            EventKind::Modify(ModifyKind::Name(_)) => Self::Rename,
             EventKind::Modify(_) => Self::Modify,
             EventKind::Remove(_) => Self::Remove,
             EventKind::Other => Self::Other,
@@ -86,13 +193,17 @@ impl WatcherState {
         Self {
             roots: RwLock::new(Vec::new()),
             running: AtomicBool::new(false),
+            ignore: Arc::new(RwLock::new(IgnoreRules::default())),
             last_event_unix_ms: Arc::new(AtomicU64::new(0)),
             watcher: Mutex::new(None),
+            options: RwLock::new(WatchOptions::default()),
+            fallback_reason: RwLock::new(None),
             event_tx: Mutex::new(None),
             cache: Mutex::new(None),
             pkg_json_cache: Mutex::new(None),
             build_cache: Mutex::new(None),
             build_watchers: Arc::new(Mutex::new(Vec::new())),
+            event_subscribers: Arc::new(Mutex::new(Vec::new())),
         }
     }
 
@@ -123,6 +234,12 @@ impl WatcherState {
         self.roots.read().unwrap().clone()
     }
 
+    /// Get the effective ignore patterns for the current watch session.
+    #[must_use]
+    pub fn ignore_patterns(&self) -> Vec<String> {
+        self.ignore.read().unwrap().patterns().to_vec()
+    }
+
     /// Get the last event timestamp.
     #[must_use]
     pub fn last_event_unix_ms(&self) -> Option<u64> {
@@ -134,14 +251,44 @@ impl WatcherState {
         }
     }
 
+    /// Get the backend in use, if running.
+    #[must_use]
+    pub fn backend_status(&self) -> Option<BackendStatus> {
+        let watcher = self.watcher.lock().unwrap();
+        Some(match watcher.as_ref()? {
+            Backend::Native(_) => BackendStatus {
+                backend: WatchBackend::Native,
+                poll_interval: None,
+                content_hashing: false,
+                fallback_reason: None,
+            },
+            Backend::Poll(poller) => BackendStatus {
+                backend: WatchBackend::Poll,
+                poll_interval: Some(poller.interval()),
+                content_hashing: poller.content_hashing(),
+                fallback_reason: self.fallback_reason.read().unwrap().clone(),
+            },
+        })
+    }
+
     /// Start watching the given roots.
     ///
+    /// `ignore` holds user-configured globs for this session; they are applied
+    /// after the built-in ignores and each root's `.gitignore`. With
+    /// [`WatchBackend::Auto`], a native backend that fails to start is
+    /// replaced by polling.
+    ///
     /// # Errors
     /// Returns an error if:
     /// - The watcher is already running
     /// - A root path is invalid
     /// - The watcher cannot be created
-    pub fn start(&self, roots: Vec<String>) -> Result<(), WatchError> {
+    pub fn start(
+        &self,
+        roots: Vec<String>,
+        ignore: &[String],
+        options: WatchOptions,
+    ) -> Result<(), WatchError> {
         // Check if already running
         if self.running.load(Ordering::Relaxed) {
             return Err(WatchError::AlreadyRunning);
@@ -157,52 +304,24 @@ impl WatcherState {
             if !path.is_dir() {
                 return Err(WatchError::InvalidRoot(root.clone()));
             }
-            validated_roots.push(path);
+            validated_roots.push(fs_util::simplified(&path));
         }
 
+        // Load ignore rules for this session
+        *self.ignore.write().unwrap() = IgnoreRules::new(&validated_roots, ignore);
+
         // Create event channel
         let (tx, mut rx) = mpsc::unbounded_channel::<WatchEvent>();
 
         // Create the watcher
-        let tx_clone = tx.clone();
-
-        let watcher = RecommendedWatcher::new(
-            move |res: Result<Event, notify::Error>| {
-                match res {
-                    Ok(event) => {
-                        // Filter events we care about
-                        if should_process_event(&event) {
-                            let watch_event = WatchEvent {
-                                paths: event.paths.clone(),
-                                kind: WatchEventKind::from(&event.kind),
-                            };
-
-                            if let Err(e) = tx_clone.send(watch_event) {
-                                warn!(error = %e, "Failed to send watch event");
-                            }
-                        }
-                    }
-                    Err(e) => {
-                        error!(error = %e, "Watch error");
-                    }
-                }
-            },
-            Config::default().with_poll_interval(Duration::from_secs(2)),
-        )
-        .map_err(|e| WatchError::WatcherFailed(e.to_string()))?;
-
-        // Watch each root
-        let mut watcher = watcher;
-        for root in &validated_roots {
-            watcher
-                .watch(root, RecursiveMode::Recursive)
-                .map_err(|e| WatchError::WatcherFailed(e.to_string()))?;
-            info!(root = %root.display(), "Watching directory");
-        }
+        let (backend, fallback_reason) =
+            open_backend(&validated_roots, options, &self.ignore, &tx)?;
 
         // Store state
         *self.roots.write().unwrap() = roots;
-        *self.watcher.lock().unwrap() = Some(watcher);
+        *self.watcher.lock().unwrap() = Some(backend);
+        *self.options.write().unwrap() = options;
+        *self.fallback_reason.write().unwrap() = fallback_reason;
         *self.event_tx.lock().unwrap() = Some(tx);
         self.running.store(true, Ordering::Relaxed);
 
@@ -212,6 +331,7 @@ impl WatcherState {
         let build_cache = self.build_cache.lock().unwrap().clone();
         let last_event_store = self.last_event_unix_ms.clone();
         let build_watchers = self.build_watchers.clone();
+        let event_subscribers = self.event_subscribers.clone();
 
         // Spawn event processor
         tokio::spawn(async move {
@@ -222,6 +342,7 @@ impl WatcherState {
                 build_cache.as_ref(),
                 &last_event_store,
                 &build_watchers,
+                &event_subscribers,
             )
             .await;
         });
@@ -244,6 +365,10 @@ impl WatcherState {
 
         // Clear state
         self.roots.write().unwrap().clear();
+        *self.ignore.write().unwrap() = IgnoreRules::default();
+        *self.fallback_reason.write().unwrap() = None;
+        // Dropping the senders ends every subscription
+        self.event_subscribers.lock().unwrap().clear();
         self.running.store(false, Ordering::Relaxed);
 
         info!("File watcher stopped");
@@ -251,16 +376,46 @@ impl WatcherState {
         Ok(())
     }
 
+    /// Subscribe to coalesced batches of file changes.
+    ///
+    /// The subscription ends (the receiver yields `None`) when the watcher is
+    /// stopped. Dropping the receiver unsubscribes.
+    ///
+    /// # Errors
+    /// Returns an error if the watcher is not running.
+    pub fn subscribe(&self) -> Result<mpsc::Receiver<Vec<FileChange>>, WatchError> {
+        if !self.running.load(Ordering::Relaxed) {
+            return Err(WatchError::NotRunning);
+        }
+
+        let (tx, rx) = mpsc::channel(SUBSCRIBER_CHANNEL_CAPACITY);
+        self.event_subscribers.lock().unwrap().push(tx);
+        Ok(rx)
+    }
+
+    /// Number of active event subscribers.
+    #[must_use]
+    pub fn subscriber_count(&self) -> usize {
+        let mut subscribers = self.event_subscribers.lock().unwrap();
+        subscribers.retain(|tx| !tx.is_closed());
+        subscribers.len()
+    }
+
     /// Watch a directory for build mode (v3.0).
-    /// Notifications are sent to the provided channel when files change.
+    /// The changed paths under the directory are sent to the provided channel.
     ///
     /// # Errors
     /// Returns an error if the path is invalid or watcher cannot be set up.
-    pub fn watch_for_build(&self, path: &PathBuf, tx: mpsc::Sender<()>) -> Result<(), WatchError> {
+    pub fn watch_for_build(
+        &self,
+        path: &PathBuf,
+        tx: mpsc::Sender<Vec<PathBuf>>,
+    ) -> Result<(), WatchError> {
         // Validate path
         if !path.exists() || !path.is_dir() {
             return Err(WatchError::InvalidRoot(path.display().to_string()));
         }
+        let path = &fs_util::simplified(path);
 
         // Add subscriber
         {
@@ -270,19 +425,49 @@ impl WatcherState {
 
         // If watcher not running, start it for this path
         if !self.running.load(Ordering::Relaxed) {
-            self.start(vec![path.display().to_string()])?;
+            self.start(
+                vec![path.display().to_string()],
+                &[],
+                WatchOptions::default(),
+            )?;
         } else {
             // Add path to existing watcher if not already watching
             let mut roots = self.roots.write().unwrap();
             let path_str = path.display().to_string();
             if !roots.contains(&path_str) {
-                if let Some(watcher) = self.watcher.lock().unwrap().as_mut() {
-                    watcher
-                        .watch(path, RecursiveMode::Recursive)
-                        .map_err(|e| WatchError::WatcherFailed(e.to_string()))?;
-                    roots.push(path_str);
-                    info!(root = %path.display(), "Added directory to watcher");
+                let mut backend = self.watcher.lock().unwrap();
+                match backend.as_mut() {
+                    Some(Backend::Native(watcher)) => {
+                        if let Err(e) = watcher.watch(path, RecursiveMode::Recursive) {
+                            let options = *self.options.read().unwrap();
+                            if options.backend != WatchBackend::Auto {
+                                return Err(WatchError::WatcherFailed(e.to_string()));
+                            }
+                            // Move every root over to polling
+                            warn!(error = %e, "Native watcher failed, falling back to polling");
+                            let tx = self.event_tx.lock().unwrap().clone();
+                            let Some(tx) = tx else {
+                                return Err(WatchError::NotRunning);
+                            };
+                            let mut all_roots: Vec<PathBuf> =
+                                roots.iter().map(PathBuf::from).collect();
+                            all_roots.push(path.clone());
+                            self.ignore.write().unwrap().add_root(path);
+                            *backend = Some(Backend::Poll(PollWatcher::new(
+                                &all_roots,
+                                options.poll,
+                                self.ignore.clone(),
+                                tx,
+                            )));
+                            *self.fallback_reason.write().unwrap() = Some(e.to_string());
+                        }
+                    }
+                    Some(Backend::Poll(poller)) => poller.watch(path),
+                    None => return Ok(()),
                 }
+                roots.push(path_str);
+                self.ignore.write().unwrap().add_root(path);
+                info!(root = %path.display(), "Added directory to watcher");
             }
         }
 
@@ -292,24 +477,100 @@ impl WatcherState {
     /// Stop watching a specific directory (v3.0).
     pub fn unwatch(&self, path: &PathBuf) {
         // Remove subscriber
+        let plain = fs_util::simplified(path);
         {
             let mut watchers = self.build_watchers.lock().unwrap();
-            watchers.retain(|(p, _)| p != path);
+            watchers.retain(|(p, _)| *p != plain);
         }
 
         // Optionally unwatch from file system if no other subscribers for this path
         let has_other_subscribers = {
             let watchers = self.build_watchers.lock().unwrap();
-            watchers.iter().any(|(p, _)| p == path)
+            watchers.iter().any(|(p, _)| *p == plain)
         };
 
         if !has_other_subscribers {
-            if let Some(watcher) = self.watcher.lock().unwrap().as_mut() {
-                let _ = watcher.unwatch(path);
-                info!(root = %path.display(), "Removed directory from watcher");
+            match self.watcher.lock().unwrap().as_mut() {
+                Some(Backend::Native(watcher)) => {
+                    let _ = watcher.unwatch(path);
+                }
+                Some(Backend::Poll(poller)) => poller.unwatch(&plain),
+                None => return,
+            }
+            info!(root = %path.display(), "Removed directory from watcher");
+        }
+    }
+}
+
+/// Open the backend for `roots`, returning it and why the native backend
+/// was abandoned (if it was).
+fn open_backend(
+    roots: &[PathBuf],
+    options: WatchOptions,
+    ignore: &Arc<RwLock<IgnoreRules>>,
+    tx: &mpsc::UnboundedSender<WatchEvent>,
+) -> Result<(Backend, Option<String>), WatchError> {
+    let mut fallback_reason = None;
+    if options.backend != WatchBackend::Poll {
+        match open_native(roots, ignore.clone(), tx.clone()) {
+            Ok(watcher) => return Ok((Backend::Native(watcher), None)),
+            Err(e) if options.backend == WatchBackend::Auto => {
+                warn!(error = %e, "Native watcher failed, falling back to polling");
+                fallback_reason = Some(e.to_string());
             }
+            Err(e) => return Err(WatchError::WatcherFailed(e.to_string())),
         }
     }
+
+    let poller = PollWatcher::new(roots, options.poll, ignore.clone(), tx.clone());
+    for root in roots {
+        info!(root = %root.display(), interval = ?poller.interval(), "Polling directory");
+    }
+    Ok((Backend::Poll(poller), fallback_reason))
+}
+
+/// Create a native watcher over `roots`.
+fn open_native(
+    roots: &[PathBuf],
+    ignore_rules: Arc<RwLock<IgnoreRules>>,
+    tx: mpsc::UnboundedSender<WatchEvent>,
+) -> notify::Result<RecommendedWatcher> {
+    let mut normalizer = EventNormalizer::default();
+    let mut watcher = RecommendedWatcher::new(
+        move |res: Result<Event, notify::Error>| {
+            match res {
+                Ok(event) => {
+                    // Filter events we care about, pairing renames
+                    for mut watch_event in normalizer.normalize(&event) {
+                        {
+                            let rules = ignore_rules.read().unwrap();
+                            watch_event
+                                .paths
+                                .retain(|p| !rules.is_ignored(p) && !is_editor_temp(p));
+                        }
+                        if watch_event.paths.is_empty() {
+                            continue;
+                        }
+
+                        if let Err(e) = tx.send(watch_event) {
+                            warn!(error = %e, "Failed to send watch event");
+                        }
+                    }
+                }
+                Err(e) => {
+                    error!(error = %e, "Watch error");
+                }
+            }
+        },
+        Config::default(),
+    )?;
+
+    // Watch each root
+    for root in roots {
+        watcher.watch(root, RecursiveMode::Recursive)?;
+        info!(root = %root.display(), "Watching directory");
+    }
+    Ok(watcher)
 }
 
 /// Process events with coalescing.
@@ -319,9 +580,11 @@ async fn process_events(
     pkg_json_cache: Option<&Arc<DaemonPkgJsonCache>>,
     build_cache: Option<&Arc<DaemonBuildCache>>,
     last_event_store: &Arc<AtomicU64>,
-    build_watchers: &Arc<Mutex<Vec<(PathBuf, mpsc::Sender<()>)>>>,
+    build_watchers: &Arc<Mutex<Vec<(PathBuf, mpsc::Sender<Vec<PathBuf>>)>>>,
+    event_subscribers: &Arc<Mutex<Vec<mpsc::Sender<Vec<FileChange>>>>>,
 ) {
-    let mut pending_paths: HashSet<PathBuf> = HashSet::new();
+    // Pending changes: path -> (coalesced kind, last event time)
+    let mut pending_paths: HashMap<PathBuf, (WatchEventKind, u64)> = HashMap::new();
     let mut last_event_time = std::time::Instant::now();
 
     loop {
@@ -330,9 +593,17 @@ async fn process_events(
 
         match timeout {
             Ok(Some(event)) => {
-                // Accumulate paths
+                // Accumulate paths, coalescing kinds per path. Paths are
+                // keyed without `\\?\` so they match the caches' keys.
+                let now = unix_ms_now();
                 for path in event.paths {
-                    pending_paths.insert(path);
+                    pending_paths
+                        .entry(fs_util::simplified(&path))
+                        .and_modify(|(kind, ts)| {
+                            *kind = kind.coalesce(event.kind);
+                            *ts = now;
+                        })
+                        .or_insert((event.kind, now));
                 }
                 last_event_time = std::time::Instant::now();
             }
@@ -342,8 +613,15 @@ async fn process_events(
                 break;
             }
             Err(_) => {
-                // Timeout - process pending if we have any and enough time has passed
+                // Timeout - process pending if we have any and enough time has passed,
+                // holding back recent removals that may be half of an atomic save
+                let now = unix_ms_now();
+                let awaiting_recreate = pending_paths.values().any(|(kind, ts)| {
+                    *kind == WatchEventKind::Remove
+                        && now.saturating_sub(*ts) < ATOMIC_SAVE_WINDOW_MS
+                });
                 if !pending_paths.is_empty()
+                    && !awaiting_recreate
                     && last_event_time.elapsed() >= Duration::from_millis(COALESCE_WINDOW_MS)
                 {
                     // Process coalesced events
@@ -356,7 +634,7 @@ async fn process_events(
                     let mut pkg_json_invalidated = 0;
                     let mut build_invalidated = 0;
 
-                    for path in &pending_paths {
+                    for path in pending_paths.keys() {
                         debug!(path = %path.display(), "File changed");
 
                         // Invalidate resolver cache entries for this path
@@ -365,11 +643,10 @@ async fn process_events(
                             total_invalidated += count;
                         }
 
-                        // Invalidate package.json cache if this is a package.json file
+                        // Invalidate package.json cache entries at or below this path
+                        // (a changed package.json, or a removed package directory)
                         if let Some(pkg_cache) = pkg_json_cache {
-                            if is_package_json(path) && pkg_cache.invalidate(path) {
-                                pkg_json_invalidated += 1;
-                            }
+                            pkg_json_invalidated += pkg_cache.invalidate_prefix(path);
                         }
 
                         // Invalidate build cache entries for this path
@@ -396,23 +673,43 @@ async fn process_events(
                     }
 
                     // Update timestamp AFTER invalidation is applied
-                    #[allow(clippy::cast_possible_truncation)]
-                    let now = std::time::SystemTime::now()
-                        .duration_since(std::time::UNIX_EPOCH)
-                        .map(|d| d.as_millis() as u64)
-                        .unwrap_or(0);
-                    last_event_store.store(now, Ordering::Relaxed);
+                    last_event_store.store(unix_ms_now(), Ordering::Relaxed);
 
                     // Notify build watchers (v3.0)
                     {
                         let watchers = build_watchers.lock().unwrap();
                         for (watch_path, tx) in watchers.iter() {
-                            // Check if any changed path is under this watch path
-                            for changed in &pending_paths {
-                                if changed.starts_with(watch_path) {
-                                    // Send notification (non-blocking)
-                                    let _ = tx.try_send(());
-                                    break; // Only need to notify once per watcher
+                            // Forward the changed paths under this watch path
+                            let mut changed: Vec<PathBuf> = pending_paths
+                                .keys()
+                                .filter(|changed| changed.starts_with(watch_path))
+                                .cloned()
+                                .collect();
+                            if !changed.is_empty() {
+                                changed.sort();
+                                // Send notification (non-blocking)
+                                let _ = tx.try_send(changed);
+                            }
+                        }
+                    }
+
+                    // Deliver the batch to event subscribers
+                    {
+                        let mut subscribers = event_subscribers.lock().unwrap();
+                        subscribers.retain(|tx| !tx.is_closed());
+                        if !subscribers.is_empty() {
+                            let mut batch: Vec<FileChange> = pending_paths
+                                .iter()
+                                .map(|(path, (kind, unix_ms))| FileChange {
+                                    path: path.clone(),
+                                    kind: *kind,
+                                    unix_ms: *unix_ms,
+                                })
+                                .collect();
+                            batch.sort_by(|a, b| a.path.cmp(&b.path));
+                            for tx in subscribers.iter() {
+                                if tx.try_send(batch.clone()).is_err() {
+                                    warn!("Watch subscriber is lagging, dropping event batch");
                                 }
                             }
                         }
@@ -425,26 +722,28 @@ async fn process_events(
     }
 }
 
-/// Check if a path is a package.json file.
-fn is_package_json(path: &std::path::Path) -> bool {
-    path.file_name()
-        .map(|n| n == "package.json")
-        .unwrap_or(false)
+/// Current time in milliseconds since the Unix epoch.
+#[allow(clippy::cast_possible_truncation)]
+fn unix_ms_now() -> u64 {
+    std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .map(|d| d.as_millis() as u64)
+        .unwrap_or(0)
 }
 
-/// Check if we should process this event.
+/// Check if we should process this event. Renames are handled by
+/// [`EventNormalizer`].
 fn should_process_event(event: &Event) -> bool {
     match &event.kind {
         // File creation
         EventKind::Create(CreateKind::File) => true,
         // File modification
         EventKind::Modify(ModifyKind::Data(_)) => true,
-        EventKind::Modify(ModifyKind::Name(RenameMode::To)) => true,
-        EventKind::Modify(ModifyKind::Name(RenameMode::From)) => true,
-        EventKind::Modify(ModifyKind::Name(RenameMode::Both)) => true,
         // File removal
         EventKind::Remove(RemoveKind::File) => true,
-        // Ignore directories, metadata changes, and other events
+        // Directory removal: invalidates cache entries below it by prefix
+        EventKind::Remove(RemoveKind::Folder) => true,
+        // Ignore directory creation, metadata changes, and other events
         _ => false,
     }
 }
@@ -474,12 +773,13 @@ impl std::error::Error for WatchError {}
 #[cfg(test)]
 mod tests {
     use super::*;
+    use notify::event::RenameMode;
 
     #[test]
     fn test_watcher_state_new() {
         let state = WatcherState::new();
         assert!(!state.is_running());
-        assert!(state.roots().is_empty());
+        assert_eq!(state.roots(), Vec::<String>::new());
         assert!(state.last_event_unix_ms().is_none());
     }
 
@@ -500,5 +800,175 @@ mod tests {
             WatchEventKind::from(&EventKind::Remove(RemoveKind::File)),
             WatchEventKind::Remove
         );
+        assert_eq!(
+            WatchEventKind::from(&EventKind::Modify(ModifyKind::Name(RenameMode::Both))),
+            WatchEventKind::Rename
+        );
+    }
+
+    #[test]
+    fn test_watch_event_kind_coalesce() {
+        use WatchEventKind::{Create, Modify, Remove, Rename};
+        assert_eq!(Create.coalesce(Modify), Create);
+        assert_eq!(Create.coalesce(Remove), Remove);
+        assert_eq!(Modify.coalesce(Remove), Remove);
+        // An atomic save
+        assert_eq!(Remove.coalesce(Create), Modify);
+        assert_eq!(Modify.coalesce(Rename), Rename);
+    }
+
+    #[test]
+    fn test_subscribe_requires_running_watcher() {
+        let state = WatcherState::new();
+        assert!(matches!(state.subscribe(), Err(WatchError::NotRunning)));
+        assert_eq!(state.subscriber_count(), 0);
+    }
+
+    #[tokio::test]
+    async fn test_subscriber_receives_coalesced_batch() {
+        let dir = tempfile::tempdir().unwrap();
+        let root = fs_util::canonicalize(dir.path()).unwrap();
+        let state = WatcherState::new();
+        state
+            .start(
+                vec![root.display().to_string()],
+                &[],
+                WatchOptions::default(),
+            )
+            .unwrap();
+
+        let mut rx = state.subscribe().unwrap();
+        assert_eq!(state.subscriber_count(), 1);
+
+        // Inject events directly rather than relying on OS notification timing
+        let tx = state.event_tx.lock().unwrap().clone().unwrap();
+        let file = root.join("index.ts");
+        for kind in [WatchEventKind::Create, WatchEventKind::Modify] {
+            tx.send(WatchEvent {
+                paths: vec![file.clone()],
+                kind,
+            })
+            .unwrap();
+        }
+
+        let batch = tokio::time::timeout(Duration::from_secs(5), async {
+            loop {
+                let batch = rx.recv().await.expect("subscription ended");
+                if batch.iter().any(|c| c.path == file) {
+                    return batch;
+                }
+            }
+        })
+        .await
+        .unwrap();
+        let change = batch.iter().find(|c| c.path == file).unwrap();
+        assert_eq!(change.kind, WatchEventKind::Create);
+        assert!(change.unix_ms > 0);
+
+        // Stopping the watcher ends the subscription
+        state.stop().unwrap();
+        assert!(rx.recv().await.is_none());
+    }
+
+    #[tokio::test]
+    async fn test_remove_then_recreate_is_one_modify() {
+        let dir = tempfile::tempdir().unwrap();
+        let root = fs_util::canonicalize(dir.path()).unwrap();
+        let state = WatcherState::new();
+        state
+            .start(
+                vec![root.display().to_string()],
+                &[],
+                WatchOptions::default(),
+            )
+            .unwrap();
+        let mut rx = state.subscribe().unwrap();
+
+        // The recreate lands after the coalescing window but within the
+        // atomic-save window
+        let tx = state.event_tx.lock().unwrap().clone().unwrap();
+        let file = root.join("index.ts");
+        let send = |kind| {
+            tx.send(WatchEvent {
+                paths: vec![file.clone()],
+                kind,
+            })
+            .unwrap();
+        };
+        send(WatchEventKind::Remove);
+        tokio::time::sleep(Duration::from_millis(COALESCE_WINDOW_MS * 2)).await;
+        send(WatchEventKind::Create);
+
+        let batch = tokio::time::timeout(Duration::from_secs(5), rx.recv())
+            .await
+            .unwrap()
+            .unwrap();
+        let change = batch.iter().find(|c| c.path == file).unwrap();
+        assert_eq!(change.kind, WatchEventKind::Modify);
+        state.stop().unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_poll_backend_reports_changes() {
+        let dir = tempfile::tempdir().unwrap();
+        let root = fs_util::canonicalize(dir.path()).unwrap();
+        std::fs::write(root.join("index.ts"), "a").unwrap();
+
+        let state = WatcherState::new();
+        let options = WatchOptions {
+            backend: WatchBackend::Poll,
+            poll: PollConfig {
+                interval: Duration::from_millis(20),
+                ..PollConfig::default()
+            },
+        };
+        state
+            .start(vec![root.display().to_string()], &[], options)
+            .unwrap();
+        let status = state.backend_status().unwrap();
+        assert_eq!(status.backend, WatchBackend::Poll);
+        assert_eq!(status.poll_interval, Some(Duration::from_millis(20)));
+        assert!(status.content_hashing);
+        assert_eq!(status.fallback_reason, None);
+
+        let mut rx = state.subscribe().unwrap();
+        std::fs::write(root.join("index.ts"), "b").unwrap();
+        let batch = tokio::time::timeout(Duration::from_secs(5), rx.recv())
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(batch.len(), 1);
+        assert_eq!(batch[0].path, root.join("index.ts"));
+        assert_eq!(batch[0].kind, WatchEventKind::Modify);
+
+        state.stop().unwrap();
+        assert!(state.backend_status().is_none());
+    }
+
+    #[tokio::test]
+    async fn test_auto_backend_falls_back_to_polling() {
+        // The native backend can't watch a missing root; polling can
+        let missing = tempfile::tempdir().unwrap().path().join("missing");
+        let ignore = Arc::new(RwLock::new(IgnoreRules::default()));
+        let (tx, _rx) = mpsc::unbounded_channel();
+
+        let (backend, reason) = open_backend(
+            std::slice::from_ref(&missing),
+            WatchOptions::default(),
+            &ignore,
+            &tx,
+        )
+        .unwrap();
+        assert!(matches!(backend, Backend::Poll(_)));
+        assert!(reason.is_some());
+
+        let native = WatchOptions {
+            backend: WatchBackend::Native,
+            ..WatchOptions::default()
+        };
+        assert!(matches!(
+            open_backend(&[missing], native, &ignore, &tx),
+            Err(WatchError::WatcherFailed(_))
+        ));
     }
 }
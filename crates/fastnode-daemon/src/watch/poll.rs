@@ -0,0 +1,324 @@
+//! Polling backend for the file watcher.
+//!
+//! Native notifications are unreliable on network filesystems and Docker
+//! volumes, and inotify fails outright once the watch limit is exhausted.
+//! The poller instead rescans the watch roots every interval and diffs the
+//! result against the previous scan. Ignored directories are never entered,
+//! so `node_modules` costs nothing to poll.
+//!
+//! Trees with at most [`PollConfig::hash_limit`] files are compared by
+//! content hash rather than size and mtime. That catches edits that keep
+//! both (mtimes on network mounts are often second-granular or cached), and
+//! skips files that were touched without changing.
+
+use super::{is_editor_temp, IgnoreRules, WatchEvent, WatchEventKind};
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::mpsc::RecvTimeoutError;
+use std::sync::{Arc, Mutex, RwLock};
+use std::time::{Duration, SystemTime};
+use tokio::sync::mpsc;
+use tracing::debug;
+
+/// Default rescan interval.
+pub const DEFAULT_POLL_INTERVAL: Duration = Duration::from_secs(1);
+
+/// Default maximum number of files for content hashing.
+pub const DEFAULT_HASH_LIMIT: usize = 2_000;
+
+/// Polling backend configuration.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct PollConfig {
+    /// Time between rescans.
+    pub interval: Duration,
+    /// Compare file contents when the watched trees hold at most this many
+    /// files (`0` disables hashing).
+    pub hash_limit: usize,
+}
+
+impl Default for PollConfig {
+    fn default() -> Self {
+        Self {
+            interval: DEFAULT_POLL_INTERVAL,
+            hash_limit: DEFAULT_HASH_LIMIT,
+        }
+    }
+}
+
+/// State of one file as seen by a scan.
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct FileState {
+    modified: Option<SystemTime>,
+    len: u64,
+    /// Content hash, when the scan hashed contents.
+    hash: Option<String>,
+}
+
+impl FileState {
+    fn changed(&self, next: &Self) -> bool {
+        match (&self.hash, &next.hash) {
+            (Some(a), Some(b)) => a != b,
+            _ => self.modified != next.modified || self.len != next.len,
+        }
+    }
+}
+
+/// Files under each watch root.
+type Snapshot = HashMap<PathBuf, HashMap<PathBuf, FileState>>;
+
+/// A watcher that rescans its roots on a background thread.
+///
+/// Dropping it stops the thread.
+#[derive(Debug)]
+pub struct PollWatcher {
+    roots: Arc<Mutex<Vec<PathBuf>>>,
+    config: PollConfig,
+    hashing: Arc<AtomicBool>,
+    /// Dropped to stop the scan thread.
+    _stop: std::sync::mpsc::Sender<()>,
+}
+
+impl PollWatcher {
+    /// Scan `roots` once as the baseline and start polling them.
+    ///
+    /// Changes found by later scans are sent to `tx`, filtered by `ignore`.
+    pub fn new(
+        roots: &[PathBuf],
+        config: PollConfig,
+        ignore: Arc<RwLock<IgnoreRules>>,
+        tx: mpsc::UnboundedSender<WatchEvent>,
+    ) -> Self {
+        let roots = Arc::new(Mutex::new(roots.to_vec()));
+        let hashing = Arc::new(AtomicBool::new(false));
+        let (stop_tx, stop_rx) = std::sync::mpsc::channel::<()>();
+
+        let mut previous = {
+            let rules = ignore.read().unwrap();
+            let (snapshot, hashed) = scan(&roots.lock().unwrap(), &rules, config.hash_limit);
+            hashing.store(hashed, Ordering::Relaxed);
+            snapshot
+        };
+
+        let thread_roots = roots.clone();
+        let thread_hashing = hashing.clone();
+        // Nothing is ever sent on the stop channel; it disconnects on drop
+        std::thread::spawn(move || {
+            while let Err(RecvTimeoutError::Timeout) = stop_rx.recv_timeout(config.interval) {
+                let roots = thread_roots.lock().unwrap().clone();
+                let (next, hashed) = scan(&roots, &ignore.read().unwrap(), config.hash_limit);
+                thread_hashing.store(hashed, Ordering::Relaxed);
+
+                for event in diff(&previous, &next) {
+                    if tx.send(event).is_err() {
+                        return;
+                    }
+                }
+                previous = next;
+            }
+        });
+
+        Self {
+            roots,
+            config,
+            hashing,
+            _stop: stop_tx,
+        }
+    }
+
+    /// Start polling another root. Its files become the baseline on the
+    /// next scan, so they aren't reported as created.
+    pub fn watch(&self, root: &Path) {
+        let mut roots = self.roots.lock().unwrap();
+        if !roots.iter().any(|r| r == root) {
+            roots.push(root.to_path_buf());
+        }
+    }
+
+    /// Stop polling a root.
+    pub fn unwatch(&self, root: &Path) {
+        self.roots.lock().unwrap().retain(|r| r != root);
+    }
+
+    /// Time between rescans.
+    #[must_use]
+    pub fn interval(&self) -> Duration {
+        self.config.interval
+    }
+
+    /// Whether the last scan compared file contents.
+    #[must_use]
+    pub fn content_hashing(&self) -> bool {
+        self.hashing.load(Ordering::Relaxed)
+    }
+}
+
+/// Scan every root, returning the snapshot and whether contents were hashed.
+fn scan(roots: &[PathBuf], ignore: &IgnoreRules, hash_limit: usize) -> (Snapshot, bool) {
+    let mut snapshot: Snapshot = roots
+        .iter()
+        .map(|root| (root.clone(), scan_root(root, ignore)))
+        .collect();
+
+    let total: usize = snapshot.values().map(HashMap::len).sum();
+    let hashed = total <= hash_limit;
+    if hashed {
+        for (path, state) in snapshot.values_mut().flatten() {
+            state.hash = fastnode_util::hash::blake3_file(path).ok();
+        }
+    }
+    debug!(files = total, hashed, "Polled watch roots");
+    (snapshot, hashed)
+}
+
+/// Collect the metadata of every non-ignored file below `root`.
+fn scan_root(root: &Path, ignore: &IgnoreRules) -> HashMap<PathBuf, FileState> {
+    let mut files = HashMap::new();
+    let mut dirs = vec![root.to_path_buf()];
+    while let Some(dir) = dirs.pop() {
+        let Ok(entries) = std::fs::read_dir(&dir) else {
+            continue;
+        };
+        for entry in entries.flatten() {
+            let path = entry.path();
+            let Ok(file_type) = entry.file_type() else {
+                continue;
+            };
+            // Symlinked directories are not followed
+            if file_type.is_dir() {
+                if !ignore.is_ignored_dir(&path) {
+                    dirs.push(path);
+                }
+                continue;
+            }
+            if ignore.is_ignored(&path) || is_editor_temp(&path) {
+                continue;
+            }
+            let Ok(metadata) = std::fs::metadata(&path) else {
+                continue;
+            };
+            if metadata.is_file() {
+                files.insert(
+                    path,
+                    FileState {
+                        modified: metadata.modified().ok(),
+                        len: metadata.len(),
+                        hash: None,
+                    },
+                );
+            }
+        }
+    }
+    files
+}
+
+/// Events turning `previous` into `next`. Roots missing from `previous`
+/// were just added and report nothing.
+fn diff(previous: &Snapshot, next: &Snapshot) -> Vec<WatchEvent> {
+    let mut created = Vec::new();
+    let mut modified = Vec::new();
+    let mut removed = Vec::new();
+
+    for (root, files) in next {
+        let Some(old) = previous.get(root) else {
+            continue;
+        };
+        for (path, state) in files {
+            match old.get(path) {
+                None => created.push(path.clone()),
+                Some(old_state) if old_state.changed(state) => modified.push(path.clone()),
+                Some(_) => {}
+            }
+        }
+        removed.extend(old.keys().filter(|p| !files.contains_key(*p)).cloned());
+    }
+
+    [
+        (created, WatchEventKind::Create),
+        (modified, WatchEventKind::Modify),
+        (removed, WatchEventKind::Remove),
+    ]
+    .into_iter()
+    .filter(|(paths, _)| !paths.is_empty())
+    .map(|(mut paths, kind)| {
+        paths.sort();
+        WatchEvent { paths, kind }
+    })
+    .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn rescan(root: &Path, hash_limit: usize) -> Snapshot {
+        let rules = IgnoreRules::new(&[root.to_path_buf()], &[]);
+        scan(&[root.to_path_buf()], &rules, hash_limit).0
+    }
+
+    fn kinds(events: &[WatchEvent], root: &Path) -> Vec<(WatchEventKind, Vec<String>)> {
+        events
+            .iter()
+            .map(|e| {
+                let paths = e
+                    .paths
+                    .iter()
+                    .map(|p| p.strip_prefix(root).unwrap().display().to_string())
+                    .collect();
+                (e.kind, paths)
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_diff_detects_create_modify_remove() {
+        let dir = tempfile::tempdir().unwrap();
+        let root = dir.path();
+        std::fs::write(root.join("a.ts"), "a").unwrap();
+        std::fs::write(root.join("b.ts"), "b").unwrap();
+        std::fs::create_dir_all(root.join("node_modules/x")).unwrap();
+        let before = rescan(root, DEFAULT_HASH_LIMIT);
+
+        // Same length, so only the content hash can tell
+        std::fs::write(root.join("a.ts"), "A").unwrap();
+        std::fs::remove_file(root.join("b.ts")).unwrap();
+        std::fs::write(root.join("c.ts"), "c").unwrap();
+        std::fs::write(root.join("node_modules/x/index.js"), "x").unwrap();
+        let after = rescan(root, DEFAULT_HASH_LIMIT);
+
+        assert_eq!(
+            kinds(&diff(&before, &after), root),
+            [
+                (WatchEventKind::Create, vec!["c.ts".to_string()]),
+                (WatchEventKind::Modify, vec!["a.ts".to_string()]),
+                (WatchEventKind::Remove, vec!["b.ts".to_string()]),
+            ]
+        );
+        assert!(diff(&after, &rescan(root, DEFAULT_HASH_LIMIT)).is_empty());
+    }
+
+    #[test]
+    fn test_hashing_only_for_small_trees() {
+        let dir = tempfile::tempdir().unwrap();
+        for i in 0..3 {
+            std::fs::write(dir.path().join(format!("{i}.ts")), "x").unwrap();
+        }
+        let rules = IgnoreRules::new(&[dir.path().to_path_buf()], &[]);
+        let roots = [dir.path().to_path_buf()];
+        assert!(scan(&roots, &rules, 3).1);
+        let (snapshot, hashed) = scan(&roots, &rules, 2);
+        assert!(!hashed);
+        assert!(snapshot.values().flatten().all(|(_, s)| s.hash.is_none()));
+    }
+
+    #[test]
+    fn test_added_root_is_baseline() {
+        let a = tempfile::tempdir().unwrap();
+        let b = tempfile::tempdir().unwrap();
+        std::fs::write(b.path().join("x.ts"), "x").unwrap();
+        let before = rescan(a.path(), DEFAULT_HASH_LIMIT);
+        let mut after = before.clone();
+        after.extend(rescan(b.path(), DEFAULT_HASH_LIMIT));
+        assert!(diff(&before, &after).is_empty());
+    }
+}
@@ -11,6 +11,18 @@
 //! Messages use length-prefixed JSON:
 //! - 4-byte little-endian u32 length prefix
 //! - JSON payload bytes
+//!
+//! ## Schema versions
+//! Every machine-readable result carries a `schema_version` field. It is bumped
+//! only on breaking changes (removed/renamed fields or changed meaning); new
+//! optional fields do not bump it.
+//! - [`RunPlan`] uses [`RUNPLAN_SCHEMA_VERSION`] (`howth run --dry-run --json`)
+//! - [`TestRunResult`] uses [`TEST_RUN_SCHEMA_VERSION`] (`howth test --json`)
+//! - [`ExecResult`] uses [`EXEC_RESULT_SCHEMA_VERSION`] (`howth exec --json`)
+//! - [`ScriptRunResult`] uses [`SCRIPT_RUN_SCHEMA_VERSION`] (`howth run <script> --json`)
+//! - [`BuildRunResult`] uses [`BUILD_RUN_SCHEMA_VERSION`] (`howth build --json`)
+//! - [`PkgInstallResult`] uses [`PKG_INSTALL_SCHEMA_VERSION`] (`howth install --json`)
+//! - Package graph/explain/why/doctor reports use their `PKG_*_SCHEMA_VERSION`
 
 use serde::{Deserialize, Serialize};
 use std::io::{self, Read, Write};
@@ -42,6 +54,12 @@ pub const PKG_INSTALL_SCHEMA_VERSION: u32 = 1;
 /// Test run schema version.
 pub const TEST_RUN_SCHEMA_VERSION: u32 = 1;
 
+/// Exec result schema version.
+pub const EXEC_RESULT_SCHEMA_VERSION: u32 = 1;
+
+/// Script run result schema version.
+pub const SCRIPT_RUN_SCHEMA_VERSION: u32 = 1;
+
 /// Build graph schema version (v2.0).
 pub const BUILD_GRAPH_SCHEMA_VERSION: u32 = 1;
 
@@ -83,6 +101,9 @@ pub mod codes {
     pub const PKG_PACKAGE_JSON_INVALID: &str = "PKG_PACKAGE_JSON_INVALID";
     pub const PKG_DEP_RANGE_INVALID: &str = "PKG_DEP_RANGE_INVALID";
 
+    // pack/publish error codes
+    pub const PKG_PACK_FAILED: &str = "PKG_PACK_FAILED";
+
     // v1.4: pkg graph error codes
     pub const PKG_GRAPH_NODE_MODULES_NOT_FOUND: &str = "PKG_GRAPH_NODE_MODULES_NOT_FOUND";
     pub const PKG_GRAPH_PACKAGE_JSON_INVALID: &str = "PKG_GRAPH_PACKAGE_JSON_INVALID";
@@ -95,6 +116,7 @@ pub mod codes {
     pub const PKG_EXPLAIN_KIND_INVALID: &str = "PKG_EXPLAIN_KIND_INVALID";
     pub const PKG_EXPLAIN_CWD_INVALID: &str = "PKG_EXPLAIN_CWD_INVALID";
     pub const PKG_EXPLAIN_PARENT_INVALID: &str = "PKG_EXPLAIN_PARENT_INVALID";
+    pub const PKG_EXPLAIN_PLATFORM_INVALID: &str = "PKG_EXPLAIN_PLATFORM_INVALID";
 
     // v1.6: pkg why error codes
     pub const PKG_WHY_ARGS_INVALID: &str = "PKG_WHY_ARGS_INVALID";
@@ -140,6 +162,10 @@ pub mod codes {
     pub const TEST_TRANSPILE_FAILED: &str = "TEST_TRANSPILE_FAILED";
     pub const TEST_WORKER_FAILED: &str = "TEST_WORKER_FAILED";
     pub const TEST_WORKER_TIMEOUT: &str = "TEST_WORKER_TIMEOUT";
+    pub const TEST_ENVIRONMENT_INVALID: &str = "TEST_ENVIRONMENT_INVALID";
+
+    // On-demand transpile error codes
+    pub const TRANSPILE_FAILED: &str = "TRANSPILE_FAILED";
 }
 
 /// Resolver reason codes for unresolved imports.
@@ -209,12 +235,26 @@ pub enum Request {
         args: Vec<String>,
         /// Working directory (optional; daemon uses its own logic if omitted).
         cwd: Option<String>,
+        /// Walk transitive imports up to this depth and include a graph
+        /// summary in the plan (`u32::MAX` = whole graph).
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        graph_depth: Option<u32>,
     },
 
     /// Start watching directories for file changes.
     WatchStart {
         /// Root directories to watch.
         roots: Vec<String>,
+        /// Extra ignore globs for this watch session (gitignore syntax).
+        /// Applied after built-in ignores and each root's `.gitignore`.
+        #[serde(default)]
+        ignore: Vec<String>,
+        /// Watcher backend to use.
+        #[serde(default)]
+        backend: WatchBackend,
+        /// Rescan interval for the polling backend (milliseconds).
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        poll_interval_ms: Option<u64>,
     },
 
     /// Stop watching for file changes.
@@ -233,6 +273,9 @@ pub enum Request {
         channel: String,
         /// Save as devDependency instead of dependency.
         save_dev: bool,
+        /// Install missing peer dependencies (`None` defers to `.npmrc`).
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        auto_install_peers: Option<bool>,
     },
 
     /// Remove packages from the project.
@@ -279,6 +322,26 @@ pub enum Request {
         tag: Option<String>,
         /// Allow publishing with public access for scoped packages.
         access: Option<String>,
+        /// One-time password for registries requiring 2FA.
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        otp: Option<String>,
+        /// Publish with a signed provenance statement (CI only).
+        #[serde(default)]
+        provenance: bool,
+    },
+
+    /// Pack a package into a tarball, as `npm pack` does.
+    PkgPack {
+        /// Working directory (package root).
+        cwd: String,
+        /// Directory to write the tarball to (defaults to `cwd`).
+        destination: Option<String>,
+        /// Report contents without writing the tarball.
+        dry_run: bool,
+        /// Timestamp for tarball entries (`SOURCE_DATE_EPOCH`), instead of
+        /// npm's fixed date.
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        source_date_epoch: Option<u64>,
     },
 
     /// List cached packages.
@@ -293,6 +356,12 @@ pub enum Request {
         channel: String,
     },
 
+    /// Move cached package files into the content-addressed file store.
+    PkgCacheDedupe {
+        /// Channel for cache directory.
+        channel: String,
+    },
+
     /// Get the package dependency graph.
     PkgGraph {
         /// Working directory (project root).
@@ -321,6 +390,13 @@ pub enum Request {
         channel: String,
         /// Resolution kind: "import", "require", or "auto".
         kind: String,
+        /// Extra export conditions to match (e.g. "development"), highest
+        /// priority first.
+        #[serde(default, skip_serializing_if = "Vec::is_empty")]
+        conditions: Vec<String>,
+        /// Platform condition to simulate: "node" or "browser".
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        platform: Option<String>,
     },
 
     /// Explain why a package is installed (dependency chain).
@@ -352,6 +428,9 @@ pub enum Request {
         /// Output format: "tree" or "list".
         #[serde(default = "default_why_format")]
         format: String,
+        /// List dependents of the target instead of chains from the root.
+        #[serde(default)]
+        dependents: bool,
     },
 
     /// Run package health diagnostics.
@@ -392,6 +471,9 @@ pub enum Request {
         /// Include optionalDependencies.
         #[serde(default = "default_install_include_optional")]
         include_optional: bool,
+        /// Install missing peer dependencies (`None` defers to `.npmrc`).
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        auto_install_peers: Option<bool>,
     },
 
     /// Execute a build (v2.0, targets in v2.1).
@@ -413,6 +495,16 @@ pub enum Request {
         /// Target nodes to build (v2.1). Empty = use defaults.
         #[serde(default)]
         targets: Vec<String>,
+        /// Stream `BuildProgress` events before the final result.
+        #[serde(default)]
+        progress: bool,
+        /// Executor command that runs script nodes remotely. `None` runs
+        /// them locally.
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        remote_exec: Option<String>,
+        /// `SOURCE_DATE_EPOCH` of the caller, exported to script nodes.
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        source_date_epoch: Option<u64>,
     },
 
     /// Run tests via warm Node worker pool.
@@ -430,6 +522,12 @@ pub enum Request {
         /// Force exit after tests complete (like mocha --exit).
         #[serde(default)]
         force_exit: bool,
+        /// Keep `test.tmpdir()` directories instead of removing them.
+        #[serde(default)]
+        keep_tmp: bool,
+        /// Environment for files without an `@jest-environment` docblock.
+        #[serde(default)]
+        environment: TestEnvironment,
     },
 
     /// Watch for file changes and rebuild (v3.0).
@@ -447,6 +545,28 @@ pub enum Request {
         #[serde(default = "default_build_max_parallel")]
         max_parallel: u32,
     },
+
+    /// Subscribe to file events from the running watcher.
+    /// Streams `WatchEvents` batches until the client disconnects.
+    WatchSubscribe {
+        /// Only report events under these paths. Empty = all watched roots.
+        #[serde(default)]
+        paths: Vec<String>,
+    },
+
+    /// Transpile one module with the daemon's warm compiler.
+    /// Used by the native runtime's module loader for on-the-fly TS/TSX
+    /// and by dev servers for the modules they serve.
+    Transpile {
+        /// Absolute path of the module (selects TS/JSX parsing).
+        path: String,
+        /// Module source text.
+        source: String,
+        /// The client's serialized `TranspileSpec`. When absent the module
+        /// is compiled for direct execution (ESM, no source map).
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        spec: Option<serde_json::Value>,
+    },
 }
 
 fn default_max_chains() -> u32 {
@@ -480,8 +600,7 @@ fn default_install_include_optional() -> bool {
 #[allow(clippy::cast_possible_truncation)]
 fn default_build_max_parallel() -> u32 {
     std::thread::available_parallelism()
-        .map(|n| n.get() as u32)
-        .unwrap_or(1)
+        .map_or(1, |n| n.get() as u32)
         .clamp(1, 64)
 }
 
@@ -605,7 +724,7 @@ pub struct RunPlan {
     pub requested_entry: String,
     /// Canonicalized absolute path if entry exists.
     pub resolved_entry: Option<String>,
-    /// Entry kind: "file", "dir", "missing", or "unknown".
+    /// Entry kind: "file", "script", "dir", "missing", or "unknown".
     pub entry_kind: String,
     /// Arguments to pass to the script.
     pub args: Vec<String>,
@@ -619,6 +738,93 @@ pub struct RunPlan {
     pub resolved_imports: Vec<ResolvedImport>,
     /// Resolver configuration info.
     pub resolver: ResolverInfo,
+    /// Transitive import graph summary (when requested).
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub graph: Option<ImportGraphSummary>,
+    /// The `package.json` script run instead of a file (entry kind "script").
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub script: Option<RunScriptPlan>,
+}
+
+/// A `package.json` script chosen as the run entry.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+pub struct RunScriptPlan {
+    /// Script name.
+    pub name: String,
+    /// The `package.json` defining the script.
+    pub package_json: String,
+    /// Script command as defined, without args.
+    pub command: String,
+    /// Steps in run order: `pre<name>`, `<name>` (with args appended) and
+    /// `post<name>`, hooks only when defined.
+    pub steps: Vec<RunScriptStep>,
+    /// `node_modules/.bin` directories prepended to `PATH`, nearest first.
+    pub bin_paths: Vec<String>,
+    /// Shell the steps run in.
+    pub shell: String,
+    /// File with the same name as the script, which is not run.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub shadowed_file: Option<String>,
+}
+
+/// One lifecycle step of a planned script.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+pub struct RunScriptStep {
+    /// Lifecycle event (e.g., `prebuild`, `build`).
+    pub event: String,
+    /// Shell command.
+    pub command: String,
+}
+
+/// Summary of an entry's transitive import graph.
+///
+/// Only local modules are walked; imports of packages and Node builtins are
+/// counted as external. Paths are relative to the working directory.
+#[derive(Debug, Clone, Default, Serialize, Deserialize, PartialEq, Eq)]
+pub struct ImportGraphSummary {
+    /// Depth limit of the walk (`None` = whole graph).
+    pub depth_limit: Option<u32>,
+    /// Deepest import level reached (the entry is level 0).
+    pub max_depth: u32,
+    /// Local modules visited, including the entry.
+    pub files: u32,
+    /// Import specifiers scanned across all visited modules.
+    pub imports: u32,
+    /// Imports resolved to local modules.
+    pub local: u32,
+    /// Imports of packages and Node builtins.
+    pub external: u32,
+    /// Unresolved imports grouped by reason code, sorted by reason.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub unresolved: Vec<UnresolvedImportGroup>,
+    /// Import cycles, each listed in import order.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub cycles: Vec<Vec<String>>,
+    /// Whether the walk stopped at its module limit.
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub truncated: bool,
+}
+
+/// Unresolved imports sharing a reason code.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+pub struct UnresolvedImportGroup {
+    /// Reason code (e.g., `NOT_FOUND`).
+    pub reason: String,
+    /// Number of unresolved imports with this reason.
+    pub count: u32,
+    /// First import sites with this reason.
+    pub sites: Vec<UnresolvedImportSite>,
+}
+
+/// Location of an unresolved import.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+pub struct UnresolvedImportSite {
+    /// Importing module.
+    pub from: String,
+    /// Specifier as written.
+    pub raw: String,
+    /// Line number (1-indexed, best-effort).
+    pub line: Option<u32>,
 }
 
 impl RunPlan {
@@ -646,6 +852,8 @@ impl RunPlan {
             imports: Vec::new(),
             resolved_imports: Vec::new(),
             resolver: ResolverInfo::default(),
+            graph: None,
+            script: None,
         }
     }
 
@@ -667,6 +875,20 @@ impl RunPlan {
         self.resolver = resolver;
         self
     }
+
+    /// Set the import graph summary.
+    #[must_use]
+    pub fn with_graph(mut self, graph: Option<ImportGraphSummary>) -> Self {
+        self.graph = graph;
+        self
+    }
+
+    /// Set the script run instead of a file.
+    #[must_use]
+    pub fn with_script(mut self, script: Option<RunScriptPlan>) -> Self {
+        self.script = script;
+        self
+    }
 }
 
 /// Information about an installed package.
@@ -706,6 +928,18 @@ pub struct OutdatedPackage {
     pub latest: String,
     /// Dependency type: "dep", "dev", "optional".
     pub dep_type: String,
+    /// Workspace package declaring the dependency (`None` for the root).
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub workspace: Option<String>,
+}
+
+/// A file included in a package tarball.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+pub struct PackFileInfo {
+    /// Path relative to the package root.
+    pub path: String,
+    /// File size in bytes.
+    pub size: u64,
 }
 
 /// Information about a cached package.
@@ -721,6 +955,15 @@ pub struct CachedPackage {
     pub path: String,
 }
 
+/// Content-addressed file store of the package cache.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+pub struct FileStoreInfo {
+    /// Number of stored files.
+    pub files: u64,
+    /// Total size of stored files in bytes.
+    pub size_bytes: u64,
+}
+
 /// Error information for a package operation.
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
 pub struct PkgErrorInfo {
@@ -815,6 +1058,10 @@ pub struct PkgExplainTraceStep {
     /// Export/import condition used (e.g., "import", "require", "default").
     #[serde(skip_serializing_if = "Option::is_none")]
     pub condition: Option<String>,
+    /// Conditions matched at each level of a conditional target, outermost
+    /// first.
+    #[serde(skip_serializing_if = "Vec::is_empty", default)]
+    pub conditions: Vec<String>,
     /// Package.json exports/imports key matched.
     #[serde(skip_serializing_if = "Option::is_none")]
     pub key: Option<String>,
@@ -857,6 +1104,10 @@ pub struct PkgExplainResult {
     pub kind: String,
     /// Parent directory used for resolution.
     pub parent: String,
+    /// Active export conditions in priority order, when conditions or a
+    /// platform were requested.
+    #[serde(skip_serializing_if = "Vec::is_empty", default)]
+    pub conditions: Vec<String>,
     /// Resolution trace steps.
     pub trace: Vec<PkgExplainTraceStep>,
     /// Warnings generated during resolution.
@@ -913,6 +1164,35 @@ pub struct PkgWhyChain {
     pub links: Vec<PkgWhyLink>,
 }
 
+/// A package that directly depends on the why target.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+pub struct PkgWhyDependent {
+    /// Dependent package name.
+    pub name: String,
+    /// Dependent package version.
+    pub version: String,
+    /// Dependent package root path.
+    pub path: String,
+    /// Version range it requires.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub req: Option<String>,
+    /// Dependency kind: "dep", "dev", "optional", "peer".
+    pub kind: String,
+    /// Version of the target it resolves to.
+    pub resolved_version: String,
+}
+
+/// Everything that depends on the why target.
+#[derive(Debug, Clone, Default, Serialize, Deserialize, PartialEq, Eq)]
+pub struct PkgWhyDependents {
+    /// Direct dependents.
+    pub direct: Vec<PkgWhyDependent>,
+    /// Number of packages depending on the target at any depth.
+    pub transitive_count: u32,
+    /// Whether the root package.json declares the target.
+    pub root_dependency: bool,
+}
+
 /// Error information for why operations.
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
 pub struct PkgWhyErrorInfo {
@@ -950,6 +1230,9 @@ pub struct PkgWhyResult {
     /// Optional resolver trace (if `include_trace` was true).
     #[serde(skip_serializing_if = "Option::is_none", default)]
     pub trace: Option<PkgExplainResult>,
+    /// Dependents of the target (if `dependents` was requested).
+    #[serde(skip_serializing_if = "Option::is_none", default)]
+    pub dependents: Option<PkgWhyDependents>,
 }
 
 // =============================================================================
@@ -1066,6 +1349,10 @@ pub struct InstallPackageInfo {
     /// Whether this is a workspace package (local symlink).
     #[serde(default)]
     pub is_workspace: bool,
+    /// Mirror or registry the tarball was downloaded from (downloaded
+    /// packages only).
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub served_by: Option<String>,
 }
 
 /// Error for a specific package during install.
@@ -1189,9 +1476,18 @@ pub struct BuildNodeResult {
     /// Number of files processed (for batch transpile nodes, v3.1.2).
     #[serde(skip_serializing_if = "Option::is_none")]
     pub files_count: Option<u32>,
+    /// Number of files actually re-emitted (for batch transpile nodes).
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub changed_count: Option<u32>,
     /// Whether this node was auto-discovered (v3.1.2).
     #[serde(skip_serializing_if = "std::ops::Not::not", default)]
     pub auto_discovered: bool,
+    /// Structured diagnostics (e.g. type errors from typecheck nodes).
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub diagnostics: Vec<BuildDiagnostic>,
+    /// Log file with the full output of this run, relative to the project root.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub log_path: Option<String>,
 }
 
 /// Error information for a build failure.
@@ -1206,6 +1502,36 @@ pub struct BuildErrorInfo {
     pub detail: Option<String>,
 }
 
+/// Severity of a build diagnostic.
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum BuildDiagnosticSeverity {
+    /// An error.
+    Error,
+    /// A warning.
+    Warning,
+    /// An informational message or suggestion.
+    Info,
+}
+
+/// A structured diagnostic (e.g. a type error) reported by a build node.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+pub struct BuildDiagnostic {
+    /// File path as reported by the tool. `None` for global diagnostics.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub file: Option<String>,
+    /// 1-based line number (0 when there is no location).
+    pub line: u32,
+    /// 1-based column number (0 when there is no location).
+    pub column: u32,
+    /// Tool-specific code (e.g. "TS2322").
+    pub code: String,
+    /// Severity.
+    pub severity: BuildDiagnosticSeverity,
+    /// Message text.
+    pub message: String,
+}
+
 /// Summary of a build run.
 #[derive(Debug, Clone, Default, Serialize, Deserialize, PartialEq, Eq)]
 pub struct BuildRunSummary {
@@ -1252,6 +1578,98 @@ pub struct BuildRunResult {
     pub notes: Vec<String>,
 }
 
+// =============================================================================
+// Watch types
+// =============================================================================
+
+/// Kind of a file event reported to watch subscribers.
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum WatchFileEventKind {
+    /// File was created.
+    Create,
+    /// File contents changed.
+    Modify,
+    /// File was removed.
+    Remove,
+    /// File was renamed (reported for both the old and new path).
+    Rename,
+    /// Any other change.
+    Other,
+}
+
+/// A single file event reported to watch subscribers.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+pub struct WatchFileEvent {
+    /// Absolute path of the changed file.
+    pub path: String,
+    /// Kind of change (coalesced over the batch window).
+    pub kind: WatchFileEventKind,
+    /// When the last event for this path was observed (milliseconds since Unix epoch).
+    pub timestamp_unix_ms: u64,
+}
+
+/// File watcher backend.
+#[derive(Debug, Clone, Copy, Default, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum WatchBackend {
+    /// Native OS notifications, falling back to polling if they fail.
+    #[default]
+    Auto,
+    /// Native OS notifications (inotify, `FSEvents`, `ReadDirectoryChangesW`).
+    Native,
+    /// Periodic rescans, for network filesystems and Docker volumes.
+    Poll,
+}
+
+/// Watcher backend reported by watch status.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+pub struct WatchBackendStatus {
+    /// Backend in use (`native` or `poll`).
+    pub backend: WatchBackend,
+    /// Rescan interval of the polling backend (milliseconds).
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub poll_interval_ms: Option<u64>,
+    /// Whether the last rescan compared file contents (small trees only).
+    #[serde(default)]
+    pub content_hashing: bool,
+    /// Why the native backend failed, if polling was selected automatically.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub fallback_reason: Option<String>,
+}
+
+/// Daemon cache statistics reported by watch status.
+#[derive(Debug, Clone, Copy, Default, Serialize, Deserialize, PartialEq, Eq)]
+pub struct WatchCacheStats {
+    /// Cached resolver results.
+    pub resolver_entries: u64,
+    /// Resolver results invalidated by file events since startup.
+    pub resolver_invalidations: u64,
+    /// Cached package.json files.
+    pub pkg_json_entries: u64,
+    /// package.json entries invalidated by file events since startup.
+    pub pkg_json_invalidations: u64,
+    /// Cached transpile outputs, shared by runtimes, dev servers and builds.
+    #[serde(default)]
+    pub transpile_entries: u64,
+    /// Transpiles served from the cache since startup.
+    #[serde(default)]
+    pub transpile_hits: u64,
+    /// Transpiles that ran the compiler since startup.
+    #[serde(default)]
+    pub transpile_misses: u64,
+}
+
+impl WatchCacheStats {
+    /// Share of transpiles served from the cache, if any were requested.
+    #[must_use]
+    #[allow(clippy::cast_precision_loss)]
+    pub fn transpile_hit_rate(&self) -> Option<f64> {
+        let total = self.transpile_hits + self.transpile_misses;
+        (total > 0).then(|| self.transpile_hits as f64 / total as f64)
+    }
+}
+
 // =============================================================================
 // Test Run types
 // =============================================================================
@@ -1268,6 +1686,39 @@ pub enum TestStatus {
     Skip,
 }
 
+/// Globals a test file runs with.
+#[derive(Debug, Clone, Copy, Default, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum TestEnvironment {
+    /// Node.js globals only.
+    #[default]
+    Node,
+    /// A DOM-lite `window` and `document` for component tests.
+    Dom,
+}
+
+impl TestEnvironment {
+    /// Parse an environment name, accepting Jest's `jsdom` and `happy-dom`
+    /// as aliases for `dom`.
+    #[must_use]
+    pub fn from_name(name: &str) -> Option<Self> {
+        match name {
+            "node" => Some(Self::Node),
+            "dom" | "jsdom" | "happy-dom" => Some(Self::Dom),
+            _ => None,
+        }
+    }
+
+    /// The canonical name (`node` or `dom`).
+    #[must_use]
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Self::Node => "node",
+            Self::Dom => "dom",
+        }
+    }
+}
+
 /// Result of a single test case.
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
 pub struct TestCaseResult {
@@ -1282,6 +1733,26 @@ pub struct TestCaseResult {
     /// Error message if failed.
     #[serde(skip_serializing_if = "Option::is_none")]
     pub error: Option<String>,
+    /// Expected/actual values of a failed `expect()` comparison.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub diff: Option<TestDiff>,
+    /// Times the test was re-run after failing (its `retry` option).
+    #[serde(default)]
+    pub retries: u32,
+    /// Whether the test failed before passing on a retry.
+    #[serde(default)]
+    pub flaky: bool,
+}
+
+/// Values compared by a failed assertion, pretty-printed for diffing.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+pub struct TestDiff {
+    /// Matcher that failed (e.g. `toEqual`).
+    pub matcher: String,
+    /// Expected value, formatted one property per line.
+    pub expected: String,
+    /// Received value, formatted the same way.
+    pub actual: String,
 }
 
 /// Result of a test run.
@@ -1311,6 +1782,92 @@ pub struct TestRunResult {
     pub diagnostics: String,
 }
 
+// =============================================================================
+// Exec / script run types
+// =============================================================================
+
+/// Error information for a failed exec or script run.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+pub struct RunErrorInfo {
+    /// Stable error code.
+    pub code: String,
+    /// Human-readable message.
+    pub message: String,
+}
+
+/// Result of `howth exec`.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct ExecResult {
+    /// Schema version.
+    pub schema_version: u32,
+    /// Whether the binary ran and exited with code 0.
+    pub ok: bool,
+    /// Binary name as requested.
+    pub binary: String,
+    /// Resolved binary path, if found.
+    pub resolved_path: Option<String>,
+    /// Arguments passed to the binary.
+    #[serde(default)]
+    pub args: Vec<String>,
+    /// Working directory.
+    pub cwd: String,
+    /// Exit code (`None` if the process didn't run or was killed by a signal).
+    pub exit_code: Option<i32>,
+    /// Wall-clock duration in milliseconds.
+    pub duration_ms: f64,
+    /// Captured stdout (only with `--capture`).
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub stdout: Option<String>,
+    /// Captured stderr (only with `--capture`).
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub stderr: Option<String>,
+    /// Error details when the binary could not be run.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub error: Option<RunErrorInfo>,
+}
+
+/// Result of one lifecycle step (`pre<name>`, `<name>`, `post<name>`) of a script run.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct ScriptStepResult {
+    /// Lifecycle event name (e.g. "prebuild").
+    pub event: String,
+    /// Shell command that was run.
+    pub command: String,
+    /// Exit code (`None` if the process didn't run or was killed by a signal).
+    pub exit_code: Option<i32>,
+    /// Wall-clock duration in milliseconds.
+    pub duration_ms: f64,
+    /// Captured stdout (only with `--capture`).
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub stdout: Option<String>,
+    /// Captured stderr (only with `--capture`).
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub stderr: Option<String>,
+}
+
+/// Result of running a package.json script.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct ScriptRunResult {
+    /// Schema version.
+    pub schema_version: u32,
+    /// Whether every step exited with code 0.
+    pub ok: bool,
+    /// Script name.
+    pub script: String,
+    /// Working directory.
+    pub cwd: String,
+    /// Exit code of the last step run (`None` if no step ran).
+    pub exit_code: Option<i32>,
+    /// Total wall-clock duration in milliseconds.
+    pub duration_ms: f64,
+    /// Steps in execution order. Stops at the first failing step.
+    #[serde(default)]
+    pub steps: Vec<ScriptStepResult>,
+    /// Error details when the script could not be run.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub error: Option<RunErrorInfo>,
+}
+
 /// A response from daemon to client.
 #[derive(Debug, Clone, Serialize, Deserialize)]
 #[serde(tag = "type", rename_all = "snake_case")]
@@ -1357,6 +1914,15 @@ pub enum Response {
         running: bool,
         /// Timestamp of last file event (milliseconds since Unix epoch).
         last_event_unix_ms: Option<u64>,
+        /// Effective ignore patterns (built-ins, `.gitignore`, user globs).
+        #[serde(default)]
+        ignore: Vec<String>,
+        /// Resolver and package.json cache statistics.
+        #[serde(default)]
+        caches: WatchCacheStats,
+        /// Backend in use while running.
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        backend: Option<WatchBackendStatus>,
     },
 
     /// Result of package add operation.
@@ -1413,6 +1979,37 @@ pub enum Response {
         files_count: u32,
         /// Error message if failed.
         error: Option<String>,
+        /// Tarball contents.
+        #[serde(default)]
+        files: Vec<PackFileInfo>,
+        /// Sum of packed file sizes in bytes.
+        #[serde(default)]
+        unpacked_size: u64,
+        /// Publish was rejected because a one-time password is required.
+        #[serde(default)]
+        otp_required: bool,
+    },
+
+    /// Result of package pack operation.
+    PkgPackResult {
+        /// Whether packing succeeded.
+        ok: bool,
+        /// Package name.
+        name: String,
+        /// Package version.
+        version: String,
+        /// Tarball file name.
+        filename: String,
+        /// Path the tarball was written to (`None` for dry runs).
+        path: Option<String>,
+        /// Tarball contents.
+        files: Vec<PackFileInfo>,
+        /// Sum of packed file sizes in bytes.
+        unpacked_size: u64,
+        /// Compressed tarball size in bytes.
+        tarball_size: u64,
+        /// Error message if failed.
+        error: Option<String>,
     },
 
     /// Result of cache list operation.
@@ -1421,6 +2018,12 @@ pub enum Response {
         packages: Vec<CachedPackage>,
         /// Total size in bytes.
         total_size_bytes: u64,
+        /// Bytes used on disk, counting files shared through the file store once.
+        #[serde(default)]
+        disk_size_bytes: u64,
+        /// Content-addressed file store, if enabled.
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        file_store: Option<FileStoreInfo>,
     },
 
     /// Result of cache prune operation.
@@ -1431,6 +2034,20 @@ pub enum Response {
         freed_bytes: u64,
     },
 
+    /// Result of cache dedupe operation.
+    PkgCacheDedupeResult {
+        /// Number of cached packages processed.
+        packages: u32,
+        /// Number of files visited.
+        files: u64,
+        /// Files replaced by a link to an identical stored file.
+        linked_files: u64,
+        /// Bytes no longer stored twice.
+        saved_bytes: u64,
+        /// Error message if failed.
+        error: Option<String>,
+    },
+
     /// Result of package graph request.
     PkgGraphResult {
         /// The dependency graph.
@@ -1445,8 +2062,8 @@ pub enum Response {
 
     /// Result of package why request.
     PkgWhyResult {
-        /// The why result.
-        result: PkgWhyResult,
+        /// The why result (boxed to reduce enum size).
+        result: Box<PkgWhyResult>,
     },
 
     /// Result of package doctor request.
@@ -1475,6 +2092,19 @@ pub enum Response {
         result: PkgInstallResult,
     },
 
+    /// Progress event during a build (streamed before the final result when
+    /// the request sets `progress`).
+    BuildProgress {
+        /// Node ID.
+        node_id: String,
+        /// "started", or how the node finished: "cached", "built", "failed" or "skipped".
+        status: String,
+        /// Running count of finished nodes.
+        completed: u32,
+        /// Total nodes in this run.
+        total: u32,
+    },
+
     /// Result of build request (v2.0).
     BuildResult {
         /// The build result.
@@ -1504,6 +2134,47 @@ pub enum Response {
         /// Reason for stopping.
         reason: String,
     },
+
+    /// Watch subscription established.
+    /// After this, `WatchEvents` batches are streamed as files change.
+    WatchSubscribed {
+        /// Root directories being watched.
+        roots: Vec<String>,
+    },
+
+    /// A debounced, coalesced batch of file events.
+    WatchEvents {
+        /// Events in the batch, sorted by path.
+        events: Vec<WatchFileEvent>,
+    },
+
+    /// Watch subscription ended (e.g. the watcher was stopped).
+    WatchUnsubscribed {
+        /// Reason for ending the subscription.
+        reason: String,
+    },
+
+    /// Result of a transpile request.
+    TranspileResult {
+        /// Transpiled JavaScript.
+        code: String,
+        /// Whether the daemon served the code from its transpile cache.
+        cached: bool,
+        /// Source map JSON, when the spec asked for one.
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        source_map: Option<String>,
+    },
+}
+
+impl Request {
+    /// Wire name of the request (its `type` tag), e.g. `"pkg_install"`.
+    #[must_use]
+    pub fn kind(&self) -> String {
+        serde_json::to_value(self)
+            .ok()
+            .and_then(|value| value.get("type")?.as_str().map(str::to_string))
+            .unwrap_or_default()
+    }
 }
 
 impl Response {
@@ -1682,6 +2353,12 @@ mod tests {
         }
     }
 
+    #[test]
+    fn test_request_kind() {
+        assert_eq!(Request::Ping { nonce: 1 }.kind(), "ping");
+        assert_eq!(Request::Shutdown.kind(), "shutdown");
+    }
+
     #[test]
     fn test_frame_response_roundtrip() {
         let frame = FrameResponse::new("0.1.0", Response::pong(42));
@@ -1807,11 +2484,13 @@ mod tests {
             entry: "main.js".to_string(),
             args: vec!["--flag".to_string(), "value".to_string()],
             cwd: Some("/home/user/project".to_string()),
+            graph_depth: None,
         };
         let json = serde_json::to_string(&req).unwrap();
         assert!(json.contains("run"));
         assert!(json.contains("main.js"));
         assert!(json.contains("--flag"));
+        assert!(!json.contains("graph_depth"));
     }
 
     #[test]
@@ -1822,6 +2501,7 @@ mod tests {
                 entry: "src/index.ts".to_string(),
                 args: vec!["arg1".to_string()],
                 cwd: Some("/tmp".to_string()),
+                graph_depth: Some(3),
             },
         );
 
@@ -1829,10 +2509,16 @@ mod tests {
         let decoded: Frame = decode_frame(&encoded[4..]).unwrap();
 
         match decoded.request {
-            Request::Run { entry, args, cwd } => {
+            Request::Run {
+                entry,
+                args,
+                cwd,
+                graph_depth,
+            } => {
                 assert_eq!(entry, "src/index.ts");
                 assert_eq!(args, vec!["arg1"]);
                 assert_eq!(cwd, Some("/tmp".to_string()));
+                assert_eq!(graph_depth, Some(3));
             }
             _ => panic!("Expected Run"),
         }
@@ -1852,11 +2538,49 @@ mod tests {
         assert_eq!(plan.schema_version, RUNPLAN_SCHEMA_VERSION);
         assert_eq!(plan.schema_version, 2);
         // New fields should be present but empty
-        assert!(plan.imports.is_empty());
-        assert!(plan.resolved_imports.is_empty());
+        assert_eq!(plan.imports, []);
+        assert_eq!(plan.resolved_imports, []);
         assert_eq!(plan.resolver.schema_version, 1);
     }
 
+    #[test]
+    fn test_runplan_graph_roundtrip() {
+        let graph = ImportGraphSummary {
+            depth_limit: Some(4),
+            max_depth: 2,
+            files: 3,
+            imports: 5,
+            local: 3,
+            external: 1,
+            unresolved: vec![UnresolvedImportGroup {
+                reason: "NOT_FOUND".to_string(),
+                count: 1,
+                sites: vec![UnresolvedImportSite {
+                    from: "src/a.ts".to_string(),
+                    raw: "./gone".to_string(),
+                    line: Some(2),
+                }],
+            }],
+            cycles: vec![vec!["src/a.ts".to_string(), "src/b.ts".to_string()]],
+            truncated: false,
+        };
+        let plan = RunPlan::new(
+            "/p".to_string(),
+            "main.ts".to_string(),
+            None,
+            "file".to_string(),
+            vec![],
+            "dev".to_string(),
+            vec![],
+        )
+        .with_graph(Some(graph.clone()));
+
+        let json = serde_json::to_string(&plan).unwrap();
+        assert!(!json.contains("truncated"));
+        let decoded: RunPlan = serde_json::from_str(&json).unwrap();
+        assert_eq!(decoded.graph, Some(graph));
+    }
+
     #[test]
     fn test_response_runplan_roundtrip() {
         let plan = RunPlan::new(
@@ -1886,13 +2610,52 @@ mod tests {
                 assert_eq!(plan.args, vec!["--port", "3000"]);
                 assert_eq!(plan.channel, "dev");
                 // New fields present
-                assert!(plan.imports.is_empty());
-                assert!(plan.resolved_imports.is_empty());
+                assert_eq!(plan.imports, []);
+                assert_eq!(plan.resolved_imports, []);
+                assert!(plan.graph.is_none());
+                assert!(plan.script.is_none());
             }
             _ => panic!("Expected RunPlan"),
         }
     }
 
+    #[test]
+    fn test_runplan_script_roundtrip() {
+        let script = RunScriptPlan {
+            name: "build".to_string(),
+            package_json: "/p/package.json".to_string(),
+            command: "tsc".to_string(),
+            steps: vec![
+                RunScriptStep {
+                    event: "prebuild".to_string(),
+                    command: "rm -rf dist".to_string(),
+                },
+                RunScriptStep {
+                    event: "build".to_string(),
+                    command: "tsc".to_string(),
+                },
+            ],
+            bin_paths: vec!["/p/node_modules/.bin".to_string()],
+            shell: "sh".to_string(),
+            shadowed_file: None,
+        };
+        let plan = RunPlan::new(
+            "/p".to_string(),
+            "build".to_string(),
+            None,
+            "script".to_string(),
+            vec![],
+            "stable".to_string(),
+            vec![],
+        )
+        .with_script(Some(script.clone()));
+
+        let json = serde_json::to_string(&plan).unwrap();
+        assert!(!json.contains("shadowed_file"));
+        let decoded: RunPlan = serde_json::from_str(&json).unwrap();
+        assert_eq!(decoded.script, Some(script));
+    }
+
     #[test]
     fn test_runplan_with_imports_roundtrip() {
         let imports = vec![
@@ -1929,9 +2692,27 @@ mod tests {
     fn test_watch_start_serialization() {
         let req = Request::WatchStart {
             roots: vec!["/home/user/project".to_string()],
+            ignore: vec!["*.log".to_string()],
+            backend: WatchBackend::Poll,
+            poll_interval_ms: Some(500),
         };
         let json = serde_json::to_string(&req).unwrap();
         assert!(json.contains("watch_start"));
+        assert!(json.contains(r#""backend":"poll""#));
+        assert!(json.contains(r#""poll_interval_ms":500"#));
+
+        // Older clients don't send a backend
+        let req: Request =
+            serde_json::from_str(r#"{"type":"watch_start","roots":["/p"]}"#).unwrap();
+        assert!(matches!(
+            req,
+            Request::WatchStart {
+                backend: WatchBackend::Auto,
+                poll_interval_ms: None,
+                ..
+            }
+        ));
+        assert!(json.contains("*.log"));
         assert!(json.contains("/home/user/project"));
     }
 
@@ -1972,13 +2753,142 @@ mod tests {
             roots: vec!["/home/user/project".to_string()],
             running: true,
             last_event_unix_ms: Some(1_234_567_890),
+            ignore: vec!["node_modules/".to_string()],
+            caches: WatchCacheStats {
+                resolver_invalidations: 3,
+                ..WatchCacheStats::default()
+            },
+            backend: Some(WatchBackendStatus {
+                backend: WatchBackend::Poll,
+                poll_interval_ms: Some(1000),
+                content_hashing: true,
+                fallback_reason: Some("inotify watch limit reached".to_string()),
+            }),
         };
         let json = serde_json::to_string(&resp).unwrap();
         assert!(json.contains("watch_status"));
+        assert!(json.contains(r#""backend":"poll""#));
+        assert!(json.contains("inotify watch limit reached"));
+        assert!(json.contains(r#""resolver_invalidations":3"#));
+        assert!(json.contains(r#""transpile_hits":0"#));
+        assert!(json.contains("node_modules/"));
         assert!(json.contains("running"));
         assert!(json.contains("1234567890"));
     }
 
+    #[test]
+    fn test_watch_cache_stats_transpile_hit_rate() {
+        assert_eq!(WatchCacheStats::default().transpile_hit_rate(), None);
+        let stats = WatchCacheStats {
+            transpile_hits: 3,
+            transpile_misses: 1,
+            ..WatchCacheStats::default()
+        };
+        assert_eq!(stats.transpile_hit_rate(), Some(0.75));
+
+        // Older daemons don't report transpile counters
+        let stats: WatchCacheStats = serde_json::from_str(
+            r#"{"resolver_entries":1,"resolver_invalidations":0,"pkg_json_entries":0,"pkg_json_invalidations":0}"#,
+        )
+        .unwrap();
+        assert_eq!(stats.transpile_entries, 0);
+    }
+
+    #[test]
+    fn test_watch_subscribe_roundtrip() {
+        let req: Request = serde_json::from_str(r#"{"type":"watch_subscribe"}"#).unwrap();
+        assert!(matches!(req, Request::WatchSubscribe { paths } if paths.is_empty()));
+
+        let event = WatchFileEvent {
+            path: "/home/user/project/src/index.ts".to_string(),
+            kind: WatchFileEventKind::Modify,
+            timestamp_unix_ms: 1_234_567_890,
+        };
+        let resp = Response::WatchEvents {
+            events: vec![event.clone()],
+        };
+        let json = serde_json::to_string(&resp).unwrap();
+        assert!(json.contains("watch_events"));
+        assert!(json.contains(r#""kind":"modify""#));
+        assert!(json.contains("1234567890"));
+
+        match serde_json::from_str::<Response>(&json).unwrap() {
+            Response::WatchEvents { events } => assert_eq!(events, vec![event]),
+            _ => panic!("Expected WatchEvents"),
+        }
+    }
+
+    #[test]
+    fn test_build_progress_roundtrip() {
+        // Older clients don't send `progress`
+        let req: Request = serde_json::from_str(r#"{"type":"build","cwd":"/p"}"#).unwrap();
+        assert!(matches!(
+            req,
+            Request::Build {
+                progress: false,
+                remote_exec: None,
+                source_date_epoch: None,
+                ..
+            }
+        ));
+
+        let resp = Response::BuildProgress {
+            node_id: "script:build".to_string(),
+            status: "cached".to_string(),
+            completed: 1,
+            total: 3,
+        };
+        let json = serde_json::to_string(&resp).unwrap();
+        assert!(json.contains("build_progress"));
+
+        match serde_json::from_str::<Response>(&json).unwrap() {
+            Response::BuildProgress {
+                node_id,
+                status,
+                completed,
+                total,
+            } => {
+                assert_eq!(node_id, "script:build");
+                assert_eq!(status, "cached");
+                assert_eq!((completed, total), (1, 3));
+            }
+            _ => panic!("Expected BuildProgress"),
+        }
+    }
+
+    #[test]
+    fn test_transpile_roundtrip() {
+        let req: Request = serde_json::from_str(
+            r#"{"type":"transpile","path":"/app/main.ts","source":"let a: number = 1;"}"#,
+        )
+        .unwrap();
+        assert!(matches!(
+            req,
+            Request::Transpile { ref path, spec: None, .. } if path == "/app/main.ts"
+        ));
+
+        let resp = Response::TranspileResult {
+            code: "let a = 1;".to_string(),
+            cached: true,
+            source_map: None,
+        };
+        let json = serde_json::to_string(&resp).unwrap();
+        assert!(json.contains("transpile_result"));
+        assert!(!json.contains("source_map"));
+        match serde_json::from_str::<Response>(&json).unwrap() {
+            Response::TranspileResult {
+                code,
+                cached,
+                source_map,
+            } => {
+                assert_eq!(code, "let a = 1;");
+                assert!(cached);
+                assert!(source_map.is_none());
+            }
+            _ => panic!("Expected TranspileResult"),
+        }
+    }
+
     #[test]
     fn test_watch_codes_are_uppercase() {
         let watch_codes = [
@@ -2029,9 +2939,11 @@ mod tests {
             cwd: "/home/user/project".to_string(),
             channel: "stable".to_string(),
             save_dev: false,
+            auto_install_peers: None,
         };
         let json = serde_json::to_string(&req).unwrap();
         assert!(json.contains("pkg_add"));
+        assert!(!json.contains("auto_install_peers"));
         assert!(json.contains("react"));
         assert!(json.contains("lodash@^4.17.0"));
     }
@@ -2124,11 +3036,38 @@ mod tests {
                 path: "/cache/lodash/4.17.21/package".to_string(),
             }],
             total_size_bytes: 102_400,
+            disk_size_bytes: 102_400,
+            file_store: None,
         };
         let json = serde_json::to_string(&resp).unwrap();
         assert!(json.contains("pkg_cache_list_result"));
         assert!(json.contains("lodash"));
         assert!(json.contains("total_size_bytes"));
+        assert!(!json.contains("file_store"));
+    }
+
+    #[test]
+    fn test_pkg_cache_dedupe_roundtrip() {
+        let req = Request::PkgCacheDedupe {
+            channel: "stable".to_string(),
+        };
+        let json = serde_json::to_string(&req).unwrap();
+        assert!(json.contains("pkg_cache_dedupe"));
+
+        let resp = Response::PkgCacheDedupeResult {
+            packages: 2,
+            files: 10,
+            linked_files: 4,
+            saved_bytes: 2048,
+            error: None,
+        };
+        let json = serde_json::to_string(&resp).unwrap();
+        assert!(json.contains("pkg_cache_dedupe_result"));
+        assert!(json.contains("linked_files"));
+        match serde_json::from_str(&json).unwrap() {
+            Response::PkgCacheDedupeResult { saved_bytes, .. } => assert_eq!(saved_bytes, 2048),
+            _ => panic!("Expected PkgCacheDedupeResult"),
+        }
     }
 
     #[test]
@@ -2152,6 +3091,7 @@ mod tests {
                 cwd: "/tmp/project".to_string(),
                 channel: "dev".to_string(),
                 save_dev: true,
+                auto_install_peers: Some(false),
             },
         );
 
@@ -2164,11 +3104,13 @@ mod tests {
                 cwd,
                 channel,
                 save_dev,
+                auto_install_peers,
             } => {
                 assert_eq!(specs, vec!["react@^18.0.0"]);
                 assert_eq!(cwd, "/tmp/project");
                 assert_eq!(channel, "dev");
                 assert!(save_dev);
+                assert_eq!(auto_install_peers, Some(false));
             }
             _ => panic!("Expected PkgAdd"),
         }
@@ -2440,6 +3382,61 @@ mod tests {
         }
     }
 
+    #[test]
+    fn test_run_result_schema_versions_are_stable() {
+        assert_eq!(TEST_RUN_SCHEMA_VERSION, 1);
+        assert_eq!(EXEC_RESULT_SCHEMA_VERSION, 1);
+        assert_eq!(SCRIPT_RUN_SCHEMA_VERSION, 1);
+    }
+
+    #[test]
+    fn test_exec_result_omits_uncaptured_output() {
+        let result = ExecResult {
+            schema_version: EXEC_RESULT_SCHEMA_VERSION,
+            ok: true,
+            binary: "tsc".to_string(),
+            resolved_path: Some("/p/node_modules/.bin/tsc".to_string()),
+            args: vec!["--noEmit".to_string()],
+            cwd: "/p".to_string(),
+            exit_code: Some(0),
+            duration_ms: 12.5,
+            stdout: None,
+            stderr: None,
+            error: None,
+        };
+        let json = serde_json::to_value(&result).unwrap();
+        assert_eq!(json["schema_version"], 1);
+        assert!(json.get("stdout").is_none());
+        assert!(json.get("error").is_none());
+
+        let decoded: ExecResult = serde_json::from_value(json).unwrap();
+        assert_eq!(decoded, result);
+    }
+
+    #[test]
+    fn test_script_run_result_roundtrip() {
+        let result = ScriptRunResult {
+            schema_version: SCRIPT_RUN_SCHEMA_VERSION,
+            ok: false,
+            script: "build".to_string(),
+            cwd: "/p".to_string(),
+            exit_code: Some(2),
+            duration_ms: 40.0,
+            steps: vec![ScriptStepResult {
+                event: "prebuild".to_string(),
+                command: "exit 2".to_string(),
+                exit_code: Some(2),
+                duration_ms: 40.0,
+                stdout: Some(String::new()),
+                stderr: Some("boom".to_string()),
+            }],
+            error: None,
+        };
+        let json = serde_json::to_string(&result).unwrap();
+        let decoded: ScriptRunResult = serde_json::from_str(&json).unwrap();
+        assert_eq!(decoded, result);
+    }
+
     // v1.9: PkgInstall tests
 
     #[test]
@@ -2473,6 +3470,7 @@ mod tests {
             frozen: true,
             include_dev: true,
             include_optional: false,
+            auto_install_peers: None,
         };
         let json = serde_json::to_string(&req).unwrap();
         assert!(json.contains("pkg_install"));
@@ -2490,6 +3488,7 @@ mod tests {
                 frozen: true,
                 include_dev: false,
                 include_optional: true,
+                auto_install_peers: Some(true),
             },
         );
 
@@ -2503,12 +3502,14 @@ mod tests {
                 frozen,
                 include_dev,
                 include_optional,
+                auto_install_peers,
             } => {
                 assert_eq!(cwd, "/tmp/project");
                 assert_eq!(channel, "stable");
                 assert!(frozen);
                 assert!(!include_dev);
                 assert!(include_optional);
+                assert_eq!(auto_install_peers, Some(true));
             }
             _ => panic!("Expected PkgInstall"),
         }
@@ -2539,6 +3540,7 @@ mod tests {
             link_path: "/project/node_modules/lodash".to_string(),
             cache_path: "/cache/lodash".to_string(),
             is_workspace: false,
+            served_by: None,
         };
         let json = serde_json::to_string(&info).unwrap();
         assert!(json.contains("lodash"));
@@ -2580,6 +3582,7 @@ mod tests {
                 link_path: "/project/node_modules/react".to_string(),
                 cache_path: "/cache/react".to_string(),
                 is_workspace: false,
+                served_by: None,
             }],
             errors: vec![],
             notes: vec!["All packages installed successfully".to_string()],
@@ -2607,4 +3610,58 @@ mod tests {
         assert!(json.contains("pkg_install_result"));
         assert!(json.contains("schema_version"));
     }
+
+    #[test]
+    fn test_test_case_result_diff() {
+        let case = TestCaseResult {
+            name: "adds".to_string(),
+            file: "math.test.js".to_string(),
+            status: TestStatus::Fail,
+            duration_ms: 1.0,
+            error: Some("expect(received).toBe(expected)".to_string()),
+            diff: Some(TestDiff {
+                matcher: "toBe".to_string(),
+                expected: "3".to_string(),
+                actual: "4".to_string(),
+            }),
+            retries: 0,
+            flaky: false,
+        };
+        let json = serde_json::to_value(&case).unwrap();
+        assert_eq!(json["diff"]["matcher"], "toBe");
+        let back: TestCaseResult = serde_json::from_value(json).unwrap();
+        assert_eq!(back, case);
+
+        // Results from older daemons have no diff
+        let json = r#"{"name":"a","file":"f","status":"pass","duration_ms":0.5}"#;
+        let parsed: TestCaseResult = serde_json::from_str(json).unwrap();
+        assert!(parsed.diff.is_none());
+        assert_eq!(parsed.retries, 0);
+        assert!(!parsed.flaky);
+        assert!(!serde_json::to_string(&parsed).unwrap().contains("diff"));
+    }
+
+    #[test]
+    fn test_test_environment() {
+        assert_eq!(
+            TestEnvironment::from_name("jsdom"),
+            Some(TestEnvironment::Dom)
+        );
+        assert_eq!(
+            TestEnvironment::from_name("node"),
+            Some(TestEnvironment::Node)
+        );
+        assert_eq!(TestEnvironment::from_name("browser"), None);
+        assert_eq!(
+            serde_json::to_string(&TestEnvironment::Dom).unwrap(),
+            r#""dom""#
+        );
+
+        // Requests from older CLIs have no environment
+        let json = r#"{"type":"run_tests","cwd":"/p","files":["a.test.js"]}"#;
+        let Request::RunTests { environment, .. } = serde_json::from_str(json).unwrap() else {
+            panic!("expected RunTests");
+        };
+        assert_eq!(environment, TestEnvironment::Node);
+    }
 }
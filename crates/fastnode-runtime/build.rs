@@ -15,6 +15,15 @@ fn main() {
     let path = std::path::Path::new(&out_dir).join("SNAPSHOT.bin");
     std::fs::write(&path, &*snapshot).unwrap();
 
+    // Native addons resolve napi_* symbols from the host process, so test
+    // binaries that load them must export those symbols like the CLI does.
+    let target_os = std::env::var("CARGO_CFG_TARGET_OS").unwrap_or_default();
+    if target_os == "macos" {
+        println!("cargo:rustc-link-arg=-Wl,-export_dynamic");
+    } else if target_os == "linux" {
+        println!("cargo:rustc-link-arg=-Wl,--export-dynamic");
+    }
+
     println!("cargo:rerun-if-changed=src/bootstrap.js");
     println!("cargo:rerun-if-changed=build.rs");
 }
@@ -17,7 +17,9 @@
         op_howth_cwd: () => '/',
         op_howth_env_get: () => null,
         op_howth_hrtime: () => 0,
-        op_howth_stdin_is_tty: () => false,
+        op_howth_isatty: () => false,
+        op_howth_tty_window_size: () => null,
+        op_howth_tty_set_raw_mode: () => {},
         op_howth_print: () => {},
         op_howth_print_error: () => {},
         op_howth_fs_exists: () => false,
@@ -124,6 +126,72 @@
     },
   };
 
+  /**
+   * Color depth (bits) for a terminal stream, following Node's
+   * tty.WriteStream#getColorDepth environment checks.
+   */
+  function terminalColorDepth(fd) {
+    const env = (name) => ops.op_howth_env_get(name);
+    const force = env("FORCE_COLOR");
+    if (force !== null && force !== undefined) {
+      return { "": 4, "0": 1, "1": 4, "2": 8, "3": 24, "true": 4, "false": 1 }[force] ?? 4;
+    }
+    if (env("NODE_DISABLE_COLORS") !== null || env("NO_COLOR") !== null) return 1;
+    if (!ops.op_howth_isatty(fd)) return 1;
+    const term = env("TERM") || "";
+    if (term === "dumb") return 1;
+    const colorterm = env("COLORTERM") || "";
+    if (colorterm === "truecolor" || colorterm === "24bit") return 24;
+    if (/-256(colou?r)?$/i.test(term)) return 8;
+    return 4;
+  }
+
+  /**
+   * process.stdout / process.stderr. TTY state is queried on access so it
+   * reflects redirection and terminal resizes.
+   */
+  function createStdioWriter(fd, print) {
+    const size = () => {
+      const dims = ops.op_howth_tty_window_size(fd);
+      if (dims) return dims;
+      // Not a terminal: fall back to what the shell exported, like libuv
+      const columns = parseInt(ops.op_howth_env_get("COLUMNS"), 10);
+      const rows = parseInt(ops.op_howth_env_get("LINES"), 10);
+      return [columns > 0 ? columns : 80, rows > 0 ? rows : 24];
+    };
+    return {
+      fd,
+      write(data, encoding, callback) {
+        print(String(data));
+        if (typeof encoding === "function") callback = encoding;
+        if (typeof callback === "function") process.nextTick(callback);
+        return true;
+      },
+      get isTTY() {
+        return ops.op_howth_isatty(fd);
+      },
+      get columns() {
+        return size()[0];
+      },
+      get rows() {
+        return size()[1];
+      },
+      getWindowSize() {
+        return size();
+      },
+      getColorDepth() {
+        return terminalColorDepth(fd);
+      },
+      hasColors(count = 16) {
+        return count <= 2 ** terminalColorDepth(fd);
+      },
+    };
+  }
+
+  // process.stdin, built by createStdinStream (defined with the tty module)
+  let stdinStream = null;
+  let createStdinStream;
+
   // Process implementation (Node.js compatibility)
   // Event emitter functionality for process
   const processListeners = new Map();
@@ -384,22 +452,19 @@
       throw new Error(`No such module: ${name}`);
     },
     // Standard streams (minimal implementation)
-    stdout: {
-      write(data) {
-        ops.op_howth_print(String(data));
-        return true;
-      },
-      isTTY: false,
-    },
-    stderr: {
-      write(data) {
-        ops.op_howth_print_error(String(data));
-        return true;
-      },
-      isTTY: false,
+    stdout: createStdioWriter(1, (data) => ops.op_howth_print(data)),
+    stderr: createStdioWriter(2, (data) => ops.op_howth_print_error(data)),
+    // stdin is a Readable, so it's created on first use (the stream classes
+    // live in the lazily initialized module block)
+    get stdin() {
+      if (stdinStream === null) {
+        globalThis.__howth_modules["node:tty"];
+        stdinStream = createStdinStream();
+      }
+      return stdinStream;
     },
-    stdin: {
-      isTTY: false,
+    set stdin(value) {
+      stdinStream = value;
     },
     // Memory usage stub
     memoryUsage() {
@@ -419,10 +484,9 @@
     uptime() {
       return 0;
     },
-    // dlopen - load native addons (.node files)
-    dlopen(module, filename) {
+    // dlopen - load native addons (.node files); flags are not supported
+    dlopen(module, filename, _flags) {
       Module._extensions[".node"](module, filename);
-      return module;
     },
   };
 
@@ -445,7 +509,139 @@
   const timers = new Map();
   let timerId = 0;
 
-  globalThis.setTimeout = (callback, delay, ...args) => {
+  // Fake clock behind howth:test's useFakeTimers(). While installed, new
+  // timers wait in `fakeClock.timers` until the test advances the clock and
+  // Date reads the fake system time. The test runner keeps real timers
+  // through realSetTimeout and realClearTimeout.
+  const RealDate = Date;
+  const realDateNow = RealDate.now;
+  const MAX_FAKE_TIMER_RUNS = 100000;
+  const fakeClock = {
+    installed: false,
+    now: 0, // milliseconds advanced on the fake timeline
+    offset: 0, // Date.now() minus `now`
+    seq: 0, // orders timers due at the same time
+    timers: new Map(), // id -> { callback, args, at, interval, seq }
+  };
+
+  function fakeDateNow() {
+    return fakeClock.now + fakeClock.offset;
+  }
+
+  // new Date() and Date() read the fake clock; everything else is Date itself
+  const FakeDate = new Proxy(RealDate, {
+    construct(target, args, newTarget) {
+      return Reflect.construct(target, args.length === 0 ? [fakeDateNow()] : args, newTarget);
+    },
+    apply() {
+      return new RealDate(fakeDateNow()).toString();
+    },
+  });
+
+  function timeValue(time) {
+    return time instanceof RealDate ? time.getTime() : Number(time);
+  }
+
+  function addFakeTimer(callback, args, delay, repeat) {
+    const id = ++timerId;
+    const ms = Math.max(0, Number(delay) || 0);
+    fakeClock.timers.set(id, {
+      callback,
+      args,
+      at: fakeClock.now + ms,
+      interval: repeat ? Math.max(1, ms) : null,
+      seq: ++fakeClock.seq,
+    });
+    return id;
+  }
+
+  // Earliest timer due at or before `limit` (ties run in creation order)
+  function nextFakeTimer(limit, only) {
+    let next = null;
+    for (const entry of fakeClock.timers) {
+      const timer = entry[1];
+      if (timer.at > limit || (only && !only.has(entry[0]))) continue;
+      if (!next || timer.at < next[1].at || (timer.at === next[1].at && timer.seq < next[1].seq)) {
+        next = entry;
+      }
+    }
+    return next;
+  }
+
+  function runFakeTimer([id, timer]) {
+    fakeClock.now = Math.max(fakeClock.now, timer.at);
+    if (timer.interval === null) {
+      fakeClock.timers.delete(id);
+    } else {
+      timer.at += timer.interval;
+      timer.seq = ++fakeClock.seq;
+    }
+    timer.callback(...timer.args);
+  }
+
+  function runFakeTimers(limit, only) {
+    let runs = 0;
+    let next;
+    while ((next = nextFakeTimer(limit, only))) {
+      if (++runs > MAX_FAKE_TIMER_RUNS) {
+        throw new Error("Aborting after running " + MAX_FAKE_TIMER_RUNS + " timers, assuming an infinite loop!");
+      }
+      if (only) only.delete(next[0]);
+      runFakeTimer(next);
+    }
+  }
+
+  globalThis.__howth_fake_clock = {
+    get installed() {
+      return fakeClock.installed;
+    },
+    // Start faking (or restart with a fresh timer queue) at `now`
+    install(now) {
+      if (!fakeClock.installed) {
+        fakeClock.installed = true;
+        RealDate.now = fakeDateNow;
+        globalThis.Date = FakeDate;
+      }
+      fakeClock.timers.clear();
+      fakeClock.now = 0;
+      fakeClock.offset = now === undefined ? realDateNow() : timeValue(now);
+    },
+    // Back to real timers; pending fake timers never run
+    uninstall() {
+      if (!fakeClock.installed) return;
+      fakeClock.installed = false;
+      fakeClock.timers.clear();
+      RealDate.now = realDateNow;
+      globalThis.Date = RealDate;
+    },
+    advance(ms) {
+      const target = fakeClock.now + Math.max(0, Number(ms) || 0);
+      runFakeTimers(target, null);
+      fakeClock.now = target;
+    },
+    advanceToNext() {
+      const next = nextFakeTimer(Infinity, null);
+      if (next) runFakeTimer(next);
+    },
+    runAll() {
+      runFakeTimers(Infinity, null);
+    },
+    // Run the timers pending now, but not ones they schedule
+    runPending() {
+      runFakeTimers(Infinity, new Set(fakeClock.timers.keys()));
+    },
+    // Move Date without firing timers
+    setSystemTime(time) {
+      fakeClock.offset = timeValue(time) - fakeClock.now;
+    },
+    count() {
+      return fakeClock.timers.size;
+    },
+  };
+
+  // The test runner times tests and hooks through realSetTimeout and
+  // realClearTimeout, which stay in place when node:timers loads.
+  function realSetTimeout(callback, delay, ...args) {
     const id = ++timerId;
     const handle = core.queueUserTimer(
       core.getTimerDepth() + 1,
@@ -461,14 +657,30 @@
     );
     timers.set(id, handle);
     return id;
+  }
+
+  // Cancels the underlying timer too, so a cleared timeout doesn't keep the
+  // event loop alive until it would have fired
+  function realClearTimeout(id) {
+    const handle = timers.get(id);
+    if (handle === undefined) return;
+    timers.delete(id);
+    core.cancelTimer(handle);
+  }
+
+  globalThis.setTimeout = (callback, delay, ...args) => {
+    if (fakeClock.installed) return addFakeTimer(callback, args, delay, false);
+    return realSetTimeout(callback, delay, ...args);
   };
 
   globalThis.clearTimeout = (id) => {
     // Just delete from map - the callback will check if still present
     timers.delete(id);
+    fakeClock.timers.delete(id);
   };
 
   globalThis.setInterval = (callback, delay, ...args) => {
+    if (fakeClock.installed) return addFakeTimer(callback, args, delay, true);
     const id = ++timerId;
     const tick = () => {
       callback(...args);
@@ -494,6 +706,7 @@
 
   globalThis.clearInterval = (id) => {
     timers.delete(id);
+    fakeClock.timers.delete(id);
   };
 
   // TextEncoder implementation
@@ -1032,13 +1245,81 @@
   };
 
   // DOMException - must be before AbortSignal
+  const DOM_EXCEPTION_CODES = {
+    IndexSizeError: 1,
+    HierarchyRequestError: 3,
+    WrongDocumentError: 4,
+    InvalidCharacterError: 5,
+    NoModificationAllowedError: 7,
+    NotFoundError: 8,
+    NotSupportedError: 9,
+    InUseAttributeError: 10,
+    InvalidStateError: 11,
+    SyntaxError: 12,
+    InvalidModificationError: 13,
+    NamespaceError: 14,
+    InvalidAccessError: 15,
+    TypeMismatchError: 17,
+    SecurityError: 18,
+    NetworkError: 19,
+    AbortError: 20,
+    URLMismatchError: 21,
+    QuotaExceededError: 22,
+    TimeoutError: 23,
+    InvalidNodeTypeError: 24,
+    DataCloneError: 25,
+  };
+
+  // Stored under symbols rather than private fields: V8 may format the stack
+  // (and so read `name`) while the Error super constructor is still running.
+  const kDOMExceptionName = Symbol("DOMException.name");
+  const kDOMExceptionCode = Symbol("DOMException.code");
+
   globalThis.DOMException = class DOMException extends Error {
-    constructor(message, name = "Error") {
-      super(message);
-      this.name = name;
+    // `options` may be a name string or `{ name, cause }` (Node.js extension).
+    constructor(message = "", options = "Error") {
+      let name = "Error";
+      if (options !== null && typeof options === "object") {
+        super(String(message), "cause" in options ? { cause: options.cause } : undefined);
+        if ("name" in options) name = String(options.name);
+      } else {
+        super(String(message));
+        name = String(options);
+      }
+      Object.defineProperty(this, kDOMExceptionName, { value: name });
+      Object.defineProperty(this, kDOMExceptionCode, { value: DOM_EXCEPTION_CODES[name] ?? 0 });
+    }
+
+    get name() {
+      return this[kDOMExceptionName] ?? "Error";
+    }
+
+    get code() {
+      return this[kDOMExceptionCode] ?? 0;
+    }
+
+    get [Symbol.toStringTag]() {
+      return "DOMException";
     }
   };
 
+  // Legacy error code constants (DOMException.ABORT_ERR === 20, etc.)
+  for (const [name, code] of Object.entries({
+    INDEX_SIZE_ERR: 1, DOMSTRING_SIZE_ERR: 2, HIERARCHY_REQUEST_ERR: 3,
+    WRONG_DOCUMENT_ERR: 4, INVALID_CHARACTER_ERR: 5, NO_DATA_ALLOWED_ERR: 6,
+    NO_MODIFICATION_ALLOWED_ERR: 7, NOT_FOUND_ERR: 8, NOT_SUPPORTED_ERR: 9,
+    INUSE_ATTRIBUTE_ERR: 10, INVALID_STATE_ERR: 11, SYNTAX_ERR: 12,
+    INVALID_MODIFICATION_ERR: 13, NAMESPACE_ERR: 14, INVALID_ACCESS_ERR: 15,
+    VALIDATION_ERR: 16, TYPE_MISMATCH_ERR: 17, SECURITY_ERR: 18,
+    NETWORK_ERR: 19, ABORT_ERR: 20, URL_MISMATCH_ERR: 21,
+    QUOTA_EXCEEDED_ERR: 22, TIMEOUT_ERR: 23, INVALID_NODE_TYPE_ERR: 24,
+    DATA_CLONE_ERR: 25,
+  })) {
+    const desc = { value: code, enumerable: true };
+    Object.defineProperty(DOMException, name, desc);
+    Object.defineProperty(DOMException.prototype, name, desc);
+  }
+
   // crypto implementation - Web Crypto API
   globalThis.crypto = {
     getRandomValues(array) {
@@ -1227,9 +1508,22 @@
   };
 
   // AbortController / AbortSignal
+  // Token that lets internal code construct signals; `new AbortSignal()` throws.
+  const kCreateAbortSignal = Symbol("createAbortSignal");
+
   globalThis.AbortSignal = class AbortSignal extends EventTarget {
     #aborted = false;
     #reason = undefined;
+    #onabort = null;
+    // Signals created by AbortSignal.any() that follow this one.
+    #dependents = new Set();
+
+    constructor(token) {
+      if (token !== kCreateAbortSignal) {
+        throw new TypeError("Illegal constructor");
+      }
+      super();
+    }
 
     get aborted() {
       return this.#aborted;
@@ -1239,6 +1533,24 @@
       return this.#reason;
     }
 
+    get onabort() {
+      return this.#onabort;
+    }
+
+    set onabort(handler) {
+      if (this.#onabort) {
+        this.removeEventListener("abort", this.#onabort);
+      }
+      this.#onabort = typeof handler === "function" ? handler : null;
+      if (this.#onabort) {
+        this.addEventListener("abort", this.#onabort);
+      }
+    }
+
+    get [Symbol.toStringTag]() {
+      return "AbortSignal";
+    }
+
     throwIfAborted() {
       if (this.#aborted) {
         throw this.#reason;
@@ -1246,19 +1558,46 @@
     }
 
     static abort(reason) {
-      const signal = new AbortSignal();
+      const signal = new AbortSignal(kCreateAbortSignal);
       signal.#aborted = true;
-      signal.#reason = reason ?? new DOMException("signal is aborted without reason", "AbortError");
+      signal.#reason = reason !== undefined
+        ? reason
+        : new DOMException("This operation was aborted", "AbortError");
       return signal;
     }
 
     static timeout(ms) {
-      const signal = new AbortSignal();
-      setTimeout(() => {
+      const delay = Number(ms);
+      if (!Number.isFinite(delay) || delay < 0 || delay > Number.MAX_SAFE_INTEGER) {
+        throw new TypeError(`The "ms" argument must be a non-negative finite number. Received ${ms}`);
+      }
+      const signal = new AbortSignal(kCreateAbortSignal);
+      const timer = setTimeout(() => {
+        signal._abort(new DOMException("The operation was aborted due to timeout", "TimeoutError"));
+      }, delay);
+      // A pending timeout signal must not keep the process alive.
+      timer?.unref?.();
+      return signal;
+    }
+
+    static any(signals) {
+      const signal = new AbortSignal(kCreateAbortSignal);
+      const sources = [];
+      for (const source of signals) {
+        if (!(source instanceof AbortSignal)) {
+          throw new TypeError("The \"signals\" argument must contain only AbortSignal instances");
+        }
+        sources.push(source);
+      }
+      const aborted = sources.find((source) => source.aborted);
+      if (aborted) {
         signal.#aborted = true;
-        signal.#reason = new DOMException("signal timed out", "TimeoutError");
-        signal.dispatchEvent(new Event("abort"));
-      }, ms);
+        signal.#reason = aborted.reason;
+        return signal;
+      }
+      for (const source of sources) {
+        source.#dependents.add(signal);
+      }
       return signal;
     }
 
@@ -1266,13 +1605,32 @@
     _abort(reason) {
       if (this.#aborted) return;
       this.#aborted = true;
-      this.#reason = reason ?? new DOMException("signal is aborted without reason", "AbortError");
+      this.#reason = reason !== undefined
+        ? reason
+        : new DOMException("This operation was aborted", "AbortError");
+
+      // Mark dependents aborted before any event fires so listeners observe
+      // a consistent state, then dispatch in order.
+      const dependents = [];
+      for (const dependent of this.#dependents) {
+        if (!dependent.#aborted) {
+          dependent.#aborted = true;
+          dependent.#reason = this.#reason;
+          dependents.push(dependent);
+        }
+      }
+      this.#dependents.clear();
+
       this.dispatchEvent(new Event("abort"));
+      for (const dependent of dependents) {
+        dependent.#dependents.clear();
+        dependent.dispatchEvent(new Event("abort"));
+      }
     }
   };
 
   globalThis.AbortController = class AbortController {
-    #signal = new AbortSignal();
+    #signal = new AbortSignal(kCreateAbortSignal);
 
     get signal() {
       return this.#signal;
@@ -2015,7 +2373,8 @@
 
   globalThis.performance = {
     now() {
-      return Date.now() - performanceStart;
+      // Real time even while fake timers mock Date
+      return realDateNow() - performanceStart;
     },
     timeOrigin: performanceStart,
 
@@ -2145,128 +2504,55 @@
     },
   };
 
-  // structuredClone - proper implementation supporting Buffer, TypedArrays, etc.
-  globalThis.structuredClone = (value, options) => {
-    const seen = new Map();
-
-    function clone(val) {
-      // Primitives
-      if (val === null || val === undefined) return val;
-      if (typeof val === 'boolean' || typeof val === 'number' || typeof val === 'string') return val;
-      if (typeof val === 'bigint') return val;
-      if (typeof val === 'symbol') throw new DOMException('Symbol cannot be cloned', 'DataCloneError');
-      if (typeof val === 'function') throw new DOMException('Function cannot be cloned', 'DataCloneError');
-
-      // Check circular reference
-      if (seen.has(val)) return seen.get(val);
-
-      // Date
-      if (val instanceof Date) {
-        const cloned = new Date(val.getTime());
-        seen.set(val, cloned);
-        return cloned;
-      }
-
-      // RegExp
-      if (val instanceof RegExp) {
-        const cloned = new RegExp(val.source, val.flags);
-        seen.set(val, cloned);
-        return cloned;
-      }
-
-      // Error types
-      if (val instanceof Error) {
-        const ErrorConstructor = val.constructor || Error;
-        const cloned = new ErrorConstructor(val.message);
-        cloned.name = val.name;
-        cloned.stack = val.stack;
-        seen.set(val, cloned);
-        return cloned;
-      }
-
-      // ArrayBuffer
-      if (val instanceof ArrayBuffer) {
-        const cloned = val.slice(0);
-        seen.set(val, cloned);
-        return cloned;
-      }
-
-      // TypedArrays
-      if (ArrayBuffer.isView(val) && !(val instanceof DataView)) {
-        const TypedArrayConstructor = val.constructor;
-        const cloned = new TypedArrayConstructor(val);
-        seen.set(val, cloned);
-        return cloned;
-      }
+  // structuredClone - backed by V8's ValueSerializer (the same wire format as
+  // v8.serialize), so Maps, Sets, Dates, RegExps, errors, typed arrays and
+  // cycles round-trip exactly as they do in Node.js.
+  const throwDataCloneError = (message) => {
+    throw new DOMException(message, "DataCloneError");
+  };
 
-      // DataView
-      if (val instanceof DataView) {
-        const cloned = new DataView(val.buffer.slice(0), val.byteOffset, val.byteLength);
-        seen.set(val, cloned);
-        return cloned;
-      }
+  globalThis.structuredClone = function structuredClone(value, options) {
+    if (arguments.length === 0) {
+      throw new TypeError("The \"value\" argument must be specified");
+    }
 
-      // Buffer (Node.js specific)
-      if (typeof Buffer !== 'undefined' && Buffer.isBuffer(val)) {
-        const cloned = Buffer.from(val);
-        seen.set(val, cloned);
-        return cloned;
+    let transfer = [];
+    if (options !== undefined && options !== null) {
+      if (typeof options !== "object") {
+        throw new TypeError("The \"options\" argument must be of type object");
       }
-
-      // Map
-      if (val instanceof Map) {
-        const cloned = new Map();
-        seen.set(val, cloned);
-        for (const [k, v] of val) {
-          cloned.set(clone(k), clone(v));
+      if (options.transfer !== undefined) {
+        if (options.transfer === null || typeof options.transfer[Symbol.iterator] !== "function") {
+          throw new TypeError("The \"options.transfer\" property must be iterable");
         }
-        return cloned;
+        transfer = [...options.transfer];
       }
+    }
 
-      // Set
-      if (val instanceof Set) {
-        const cloned = new Set();
-        seen.set(val, cloned);
-        for (const v of val) {
-          cloned.add(clone(v));
-        }
-        return cloned;
+    const seen = new Set();
+    for (const buffer of transfer) {
+      if (!(buffer instanceof ArrayBuffer)) {
+        throwDataCloneError("Value not transferable");
       }
-
-      // Array
-      if (Array.isArray(val)) {
-        const cloned = [];
-        seen.set(val, cloned);
-        for (let i = 0; i < val.length; i++) {
-          cloned[i] = clone(val[i]);
-        }
-        return cloned;
+      if (seen.has(buffer)) {
+        throwDataCloneError("ArrayBuffer occurs more than once in the transfer list");
       }
-
-      // Plain object
-      if (Object.getPrototypeOf(val) === Object.prototype || Object.getPrototypeOf(val) === null) {
-        const cloned = Object.create(Object.getPrototypeOf(val));
-        seen.set(val, cloned);
-        for (const key of Object.keys(val)) {
-          cloned[key] = clone(val[key]);
-        }
-        return cloned;
+      if (buffer.detached) {
+        throwDataCloneError("An ArrayBuffer is detached and could not be cloned");
       }
+      seen.add(buffer);
+    }
 
-      // Fallback for other objects - try to clone as plain object
-      const cloned = {};
-      seen.set(val, cloned);
-      for (const key of Object.keys(val)) {
-        try {
-          cloned[key] = clone(val[key]);
-        } catch (e) {
-          // Skip non-cloneable properties
-        }
-      }
-      return cloned;
+    const serialized = core.serialize(value, undefined, throwDataCloneError);
+    const cloned = core.deserialize(serialized);
+
+    // The clone owns copies of the data; detach the originals so the
+    // transfer is observable to the caller as it is in Node.js.
+    for (const buffer of transfer) {
+      buffer.transfer();
     }
 
-    return clone(value);
+    return cloned;
   };
 
   // sleep helper (non-standard but useful)
@@ -5525,41 +5811,59 @@
       module.exports = JSON.parse(content);
     },
     '.node': (module, filename) => {
-      if (typeof Deno.core.ops.op_napi_open === 'function') {
-        try {
-          module.exports = Deno.core.ops.op_napi_open(
-            filename,
-            globalThis,
-            globalThis.Buffer,
-            (err) => { console.error('[howth] NAPI error:', err); },
-          );
-          return;
-        } catch (e) {
-          // If dlopen fails (e.g. non-NAPI addon using V8 C++ internals),
-          // return a lazy proxy that throws on actual use
-          const errMsg = `Native addon failed to load: ${filename}: ${e.message || e}`;
-          console.warn(`[howth] ${errMsg}`);
-          const thrower = () => { throw new Error(errMsg); };
-          const lazyTrap = {
-            get(_, prop) {
-              if (typeof prop === 'symbol' || prop === 'inspect' ||
-                  prop === 'toString' || prop === 'valueOf' ||
-                  prop === '__esModule' || prop === 'default') return undefined;
-              // Return a callable proxy so `new mod.Foo()` or `mod.bar()` defer the error
-              return new Proxy(thrower, {
-                construct: thrower,
-                apply: thrower,
-                get: lazyTrap.get,
-              });
-            },
-            construct: thrower,
-            apply: thrower,
-          };
-          module.exports = new Proxy(thrower, lazyTrap);
-          return;
-        }
+      const dlopenError = (message) => {
+        const err = new Error(message);
+        err.code = 'ERR_DLOPEN_FAILED';
+        return err;
+      };
+      if (typeof Deno.core.ops.op_napi_open !== 'function') {
+        throw dlopenError(`Native addons are not supported: ${filename}`);
+      }
+      try {
+        // dlopen the addon and run its napi_register_module_v1 (or the
+        // module it registered with napi_module_register) on a fresh exports
+        // object; the napi_* functions it calls are exported by this binary.
+        module.exports = Deno.core.ops.op_napi_open(
+          filename,
+          globalThis,
+          globalThis.Buffer,
+          (err) => {
+            if (process.listenerCount('uncaughtException') > 0) {
+              process.emit('uncaughtException', err);
+            } else {
+              console.error('[howth] NAPI error:', err);
+            }
+          },
+        );
+      } catch (e) {
+        const errMsg = `Native addon failed to load: ${filename}: ${e.message || e}`;
+        // A library that opened but registered no Node-API module is a V8
+        // C++ addon howth can't host. Return a lazy proxy that throws on
+        // actual use so packages that only touch it optionally keep working.
+        if (!String(e.message || e).includes('Unable to find register Node-API module')) {
+          // Missing file, wrong architecture, unresolved symbols: throw like
+          // Node so `try { require(addon) } catch {}` fallbacks kick in
+          throw dlopenError(errMsg);
+        }
+        console.warn(`[howth] ${errMsg}`);
+        const thrower = () => { throw new Error(errMsg); };
+        const lazyTrap = {
+          get(_, prop) {
+            if (typeof prop === 'symbol' || prop === 'inspect' ||
+                prop === 'toString' || prop === 'valueOf' ||
+                prop === '__esModule' || prop === 'default') return undefined;
+            // Return a callable proxy so `new mod.Foo()` or `mod.bar()` defer the error
+            return new Proxy(thrower, {
+              construct: thrower,
+              apply: thrower,
+              get: lazyTrap.get,
+            });
+          },
+          construct: thrower,
+          apply: thrower,
+        };
+        module.exports = new Proxy(thrower, lazyTrap);
       }
-      throw new Error(`Native addon not supported: ${filename}`);
     },
   };
 
@@ -6219,82 +6523,188 @@
     "node:readline", "readline", "node:readline/promises", "readline/promises",
   ], () => {
 
-  class _EventEmitterImpl {
-    constructor() {
-      if (!this._events) {
-        this._events = new Map();
+  const kCapture = Symbol("kCapture");
+  const kMaxListenersWarned = Symbol("kMaxListenersWarned");
+  const captureRejectionSymbol = Symbol.for("nodejs.rejection");
+  const errorMonitor = Symbol("events.errorMonitor");
+  const kDispose = Symbol.dispose ?? Symbol.for("nodejs.dispose");
+  let defaultMaxListeners = 10;
+  let defaultCaptureRejections = false;
+
+  function checkListener(listener) {
+    if (typeof listener !== 'function') {
+      throw new TypeError(`The "listener" argument must be of type function. Received ${listener === null ? "null" : typeof listener}`);
+    }
+  }
+
+  // Shared by the constructor and the ES5-style `EventEmitter.call(this, opts)` path.
+  function initEventEmitter(emitter, opts) {
+    if (!emitter._events || emitter._events === Object.getPrototypeOf(emitter)?._events) {
+      emitter._events = new Map();
+      emitter._eventsCount = 0;
+    }
+    emitter._maxListeners = emitter._maxListeners || undefined;
+    if (opts?.captureRejections !== undefined) {
+      if (typeof opts.captureRejections !== 'boolean') {
+        throw new TypeError('The "options.captureRejections" property must be of type boolean');
       }
+      emitter[kCapture] = opts.captureRejections;
+    } else if (emitter[kCapture] === undefined) {
+      emitter[kCapture] = defaultCaptureRejections;
+    }
+    return emitter;
+  }
+
+  class _EventEmitterImpl {
+    constructor(opts) {
+      initEventEmitter(this, opts);
     }
 
     _initEvents() {
       if (!this._events) {
         this._events = new Map();
+        this._eventsCount = 0;
       }
     }
 
-    on(event, listener) {
-      if (typeof listener !== 'function') {
-        throw new TypeError(`"listener" argument must be a function. Received ${typeof listener}`);
-      }
+    _addListener(event, listener, prepend) {
+      checkListener(listener);
       this._initEvents();
-      if (!this._events.has(event)) {
-        this._events.set(event, []);
+      // Emitted before the listener is added, so a 'newListener' handler
+      // registering the same event runs first.
+      if (this._events.has('newListener')) {
+        this.emit('newListener', event, listener.listener ?? listener);
+      }
+      let list = this._events.get(event);
+      if (!list) {
+        list = [];
+        this._events.set(event, list);
+        this._eventsCount = this._events.size;
+      }
+      if (prepend) list.unshift(listener);
+      else list.push(listener);
+
+      const max = this.getMaxListeners();
+      if (max > 0 && list.length > max && !list[kMaxListenersWarned]) {
+        list[kMaxListenersWarned] = true;
+        const name = typeof event === 'symbol' ? event.toString() : event;
+        ops.op_howth_print_error(
+          `(node:${process.pid}) MaxListenersExceededWarning: Possible EventEmitter memory leak detected. ` +
+          `${list.length} ${name} listeners added to [${this.constructor?.name || 'EventEmitter'}]. ` +
+          `MaxListeners is ${max}. Use emitter.setMaxListeners() to increase limit\n`
+        );
       }
-      this._events.get(event).push(listener);
       return this;
     }
 
+    _onceWrapper(event, listener) {
+      const state = { fired: false, event, listener, target: this };
+      const wrapper = function (...args) {
+        if (state.fired) return undefined;
+        state.fired = true;
+        state.target.removeListener(state.event, wrapper);
+        return listener.apply(state.target, args);
+      };
+      wrapper.listener = listener;
+      return wrapper;
+    }
+
+    on(event, listener) {
+      return this._addListener(event, listener, false);
+    }
+
     addListener(event, listener) {
-      return this.on(event, listener);
+      return this._addListener(event, listener, false);
+    }
+
+    prependListener(event, listener) {
+      return this._addListener(event, listener, true);
     }
 
     once(event, listener) {
-      if (typeof listener !== 'function') {
-        throw new TypeError(`"listener" argument must be a function. Received ${typeof listener}`);
-      }
-      const wrapper = (...args) => {
-        this.off(event, wrapper);
-        listener.apply(this, args);
-      };
-      wrapper.listener = listener;
-      return this.on(event, wrapper);
+      checkListener(listener);
+      return this._addListener(event, this._onceWrapper(event, listener), false);
+    }
+
+    prependOnceListener(event, listener) {
+      checkListener(listener);
+      return this._addListener(event, this._onceWrapper(event, listener), true);
     }
 
     off(event, listener) {
+      return this.removeListener(event, listener);
+    }
+
+    removeListener(event, listener) {
+      checkListener(listener);
       this._initEvents();
-      const listeners = this._events.get(event);
-      if (listeners) {
-        const index = listeners.findIndex(
-          (l) => l === listener || l.listener === listener
-        );
-        if (index !== -1) {
-          listeners.splice(index, 1);
+      const list = this._events.get(event);
+      if (!list) return this;
+      // Node removes the most recently added matching listener.
+      let index = -1;
+      for (let i = list.length - 1; i >= 0; i--) {
+        if (list[i] === listener || list[i].listener === listener) {
+          index = i;
+          break;
         }
       }
+      if (index === -1) return this;
+      const [removed] = list.splice(index, 1);
+      if (list.length === 0) {
+        this._events.delete(event);
+        this._eventsCount = this._events.size;
+      }
+      if (this._events.has('removeListener')) {
+        this.emit('removeListener', event, removed.listener ?? removed);
+      }
       return this;
     }
 
-    removeListener(event, listener) {
-      return this.off(event, listener);
-    }
-
     removeAllListeners(event) {
       this._initEvents();
+      const notify = this._events.has('removeListener');
       if (event !== undefined) {
+        const list = this._events.get(event);
+        if (!list) return this;
+        if (notify && event !== 'removeListener') {
+          for (let i = list.length - 1; i >= 0; i--) this.removeListener(event, list[i]);
+        }
         this._events.delete(event);
       } else {
+        if (notify) {
+          for (const name of [...this._events.keys()]) {
+            if (name !== 'removeListener') this.removeAllListeners(name);
+          }
+        }
         this._events.clear();
       }
+      this._eventsCount = this._events.size;
       return this;
     }
 
     emit(event, ...args) {
       this._initEvents();
+      if (event === 'error' && this._events.has(errorMonitor)) {
+        this.emit(errorMonitor, ...args);
+      }
+
       const listeners = this._events.get(event);
-      if (!listeners || listeners.length === 0) return false;
+      if (!listeners || listeners.length === 0) {
+        if (event === 'error') {
+          const er = args[0];
+          if (er instanceof Error) throw er;
+          const err = new Error(`Unhandled error. (${inspect(er)})`);
+          err.code = 'ERR_UNHANDLED_ERROR';
+          err.context = er;
+          throw err;
+        }
+        return false;
+      }
+
       for (const listener of [...listeners]) {
-        if (typeof listener === 'function') {
-          listener.apply(this, args);
+        const result = listener.apply(this, args);
+        if (this[kCapture] && result !== undefined && result !== null && typeof result.then === 'function') {
+          addCatch(this, result, event, args);
         }
       }
       return true;
@@ -6307,10 +6717,17 @@
       return list.map((l) => l.listener || l);
     }
 
-    listenerCount(event) {
+    rawListeners(event) {
+      this._initEvents();
+      return [...(this._events.get(event) || [])];
+    }
+
+    listenerCount(event, listener) {
       this._initEvents();
       const list = this._events.get(event);
-      return list ? list.length : 0;
+      if (!list) return 0;
+      if (listener === undefined) return list.length;
+      return list.filter((l) => l === listener || l.listener === listener).length;
     }
 
     eventNames() {
@@ -6318,43 +6735,41 @@
       return [...this._events.keys()];
     }
 
-    prependListener(event, listener) {
-      if (typeof listener !== 'function') {
-        throw new TypeError(`"listener" argument must be a function. Received ${typeof listener}`);
-      }
-      this._initEvents();
-      if (!this._events.has(event)) {
-        this._events.set(event, []);
+    setMaxListeners(n) {
+      if (typeof n !== 'number' || n < 0 || Number.isNaN(n)) {
+        throw new RangeError(`The value of "n" is out of range. It must be a non-negative number. Received ${n}`);
       }
-      this._events.get(event).unshift(listener);
-      return this;
-    }
-
-    prependOnceListener(event, listener) {
-      if (typeof listener !== 'function') {
-        throw new TypeError(`"listener" argument must be a function. Received ${typeof listener}`);
-      }
-      const wrapper = (...args) => {
-        this.off(event, wrapper);
-        listener.apply(this, args);
-      };
-      wrapper.listener = listener;
-      return this.prependListener(event, wrapper);
-    }
-
-    setMaxListeners(n) {
-      // No-op for compatibility
+      this._maxListeners = n;
       return this;
     }
 
     getMaxListeners() {
-      return 10; // Default Node.js value
+      return this._maxListeners === undefined ? defaultMaxListeners : this._maxListeners;
     }
+  }
 
-    rawListeners(event) {
-      this._initEvents();
-      return this._events.get(event) || [];
-    }
+  // Report as "EventEmitter" in inspect() output and leak warnings, as Node does.
+  Object.defineProperty(_EventEmitterImpl, 'name', { value: 'EventEmitter' });
+
+  // A rejected promise from a listener is routed to the emitter's
+  // Symbol.for('nodejs.rejection') hook, or to its 'error' event.
+  function addCatch(emitter, promise, event, args) {
+    promise.then(undefined, (err) => {
+      process.nextTick(() => {
+        if (typeof emitter[captureRejectionSymbol] === 'function') {
+          emitter[captureRejectionSymbol](err, event, ...args);
+          return;
+        }
+        const prev = emitter[kCapture];
+        try {
+          // Don't capture again if the 'error' handler itself rejects.
+          emitter[kCapture] = false;
+          emitter.emit('error', err);
+        } finally {
+          emitter[kCapture] = prev;
+        }
+      });
+    });
   }
 
   // Wrap _EventEmitterImpl so it can be called without 'new' (ES5 inheritance compat)
@@ -6365,14 +6780,9 @@
       // Do NOT change the prototype — callers like ioredis use this as a mixin
       // and expect their own prototype chain to remain intact.
       if (thisArg && typeof thisArg === 'object') {
-        if (!thisArg._events) {
-          thisArg._events = new Map();
-        }
-        thisArg._eventsCount = 0;
-        thisArg._maxListeners = undefined;
-        return thisArg;
+        return initEventEmitter(thisArg, args[0]);
       }
-      return new _EventEmitterImpl();
+      return new _EventEmitterImpl(args[0]);
     },
   });
 
@@ -6381,6 +6791,196 @@
     return emitter.listenerCount(event);
   };
 
+  Object.defineProperty(EventEmitter, 'defaultMaxListeners', {
+    enumerable: true,
+    get() {
+      return defaultMaxListeners;
+    },
+    set(n) {
+      if (typeof n !== 'number' || n < 0 || Number.isNaN(n)) {
+        throw new RangeError(`The value of "defaultMaxListeners" is out of range. It must be a non-negative number. Received ${n}`);
+      }
+      defaultMaxListeners = n;
+    },
+  });
+
+  Object.defineProperty(EventEmitter, 'captureRejections', {
+    enumerable: true,
+    get() {
+      return defaultCaptureRejections;
+    },
+    set(value) {
+      if (typeof value !== 'boolean') {
+        throw new TypeError('The "EventEmitter.captureRejections" property must be of type boolean');
+      }
+      defaultCaptureRejections = value;
+    },
+  });
+
+  EventEmitter.errorMonitor = errorMonitor;
+  EventEmitter.captureRejectionSymbol = captureRejectionSymbol;
+
+  function abortError(signal) {
+    const err = new DOMException("The operation was aborted", "AbortError");
+    if (signal?.reason !== undefined) {
+      Object.defineProperty(err, 'cause', { value: signal.reason, configurable: true, writable: true });
+    }
+    return err;
+  }
+
+  function validateAbortSignal(signal) {
+    if (signal !== undefined && (signal === null || typeof signal !== 'object' || !('aborted' in signal))) {
+      throw new TypeError('The "options.signal" property must be an instance of AbortSignal');
+    }
+  }
+
+  /**
+   * Resolve with the arguments of the next `name` event. Rejects if 'error'
+   * is emitted first (unless waiting for 'error') or if the signal aborts.
+   */
+  EventEmitter.once = function once(emitter, name, options = {}) {
+    const signal = options?.signal;
+    validateAbortSignal(signal);
+    if (signal?.aborted) return Promise.reject(abortError(signal));
+
+    return new Promise((resolve, reject) => {
+      if (typeof emitter.addEventListener === 'function' && typeof emitter.on !== 'function') {
+        const listener = (...args) => {
+          signal?.removeEventListener('abort', onAbort);
+          resolve(args);
+        };
+        const onAbort = () => {
+          emitter.removeEventListener(name, listener);
+          reject(abortError(signal));
+        };
+        emitter.addEventListener(name, listener, { once: true });
+        signal?.addEventListener('abort', onAbort, { once: true });
+        return;
+      }
+
+      const cleanup = () => {
+        emitter.removeListener(name, resolver);
+        if (name !== 'error') emitter.removeListener('error', errorListener);
+        signal?.removeEventListener('abort', onAbort);
+      };
+      const resolver = (...args) => {
+        cleanup();
+        resolve(args);
+      };
+      const errorListener = (err) => {
+        cleanup();
+        reject(err);
+      };
+      const onAbort = () => {
+        cleanup();
+        reject(abortError(signal));
+      };
+      emitter.once(name, resolver);
+      if (name !== 'error') emitter.once('error', errorListener);
+      signal?.addEventListener('abort', onAbort, { once: true });
+    });
+  };
+
+  /**
+   * Async iterator over `event` emissions; throws on 'error'.
+   */
+  EventEmitter.on = function on(emitter, event, options = {}) {
+    const signal = options?.signal;
+    validateAbortSignal(signal);
+    if (signal?.aborted) throw abortError(signal);
+
+    const unconsumedEvents = [];
+    const unconsumedPromises = [];
+    let error = null;
+    let finished = false;
+
+    const eventHandler = (...args) => {
+      const pending = unconsumedPromises.shift();
+      if (pending) pending.resolve({ value: args, done: false });
+      else unconsumedEvents.push(args);
+    };
+    const errorHandler = (err) => {
+      finished = true;
+      const pending = unconsumedPromises.shift();
+      if (pending) pending.reject(err);
+      else error = err;
+      iterator.return();
+    };
+    const abortListener = () => errorHandler(abortError(signal));
+
+    const iterator = {
+      next() {
+        const value = unconsumedEvents.shift();
+        if (value) return Promise.resolve({ value, done: false });
+        if (error) {
+          const p = Promise.reject(error);
+          error = null;
+          return p;
+        }
+        if (finished) return Promise.resolve({ value: undefined, done: true });
+        return new Promise((resolve, reject) => unconsumedPromises.push({ resolve, reject }));
+      },
+      return() {
+        emitter.removeListener(event, eventHandler);
+        emitter.removeListener('error', errorHandler);
+        signal?.removeEventListener('abort', abortListener);
+        finished = true;
+        for (const pending of unconsumedPromises) pending.resolve({ value: undefined, done: true });
+        unconsumedPromises.length = 0;
+        return Promise.resolve({ value: undefined, done: true });
+      },
+      throw(err) {
+        error = err;
+        emitter.removeListener(event, eventHandler);
+        emitter.removeListener('error', errorHandler);
+        return Promise.reject(err);
+      },
+      [Symbol.asyncIterator]() {
+        return this;
+      },
+    };
+
+    emitter.on(event, eventHandler);
+    if (event !== 'error') emitter.on('error', errorHandler);
+    signal?.addEventListener('abort', abortListener, { once: true });
+    return iterator;
+  };
+
+  EventEmitter.getEventListeners = function getEventListeners(emitterOrTarget, name) {
+    if (typeof emitterOrTarget?.listeners === 'function') return emitterOrTarget.listeners(name);
+    return [];
+  };
+
+  EventEmitter.getMaxListeners = function getMaxListeners(emitterOrTarget) {
+    if (typeof emitterOrTarget?.getMaxListeners === 'function') return emitterOrTarget.getMaxListeners();
+    return defaultMaxListeners;
+  };
+
+  EventEmitter.setMaxListeners = function setMaxListeners(n = defaultMaxListeners, ...targets) {
+    if (targets.length === 0) {
+      EventEmitter.defaultMaxListeners = n;
+      return;
+    }
+    for (const target of targets) {
+      if (typeof target?.setMaxListeners === 'function') target.setMaxListeners(n);
+    }
+  };
+
+  EventEmitter.addAbortListener = function addAbortListener(signal, listener) {
+    validateAbortSignal(signal);
+    checkListener(listener);
+    if (signal.aborted) {
+      queueMicrotask(() => listener());
+      return { [kDispose]() {} };
+    }
+    signal.addEventListener('abort', listener, { once: true });
+    return {
+      [kDispose]() {
+        signal.removeEventListener('abort', listener);
+      },
+    };
+  };
+
   const eventsModule = EventEmitter;
   eventsModule.EventEmitter = EventEmitter;
 
@@ -6391,161 +6991,611 @@
   // util module
   // ============================================================================
 
+  /**
+   * ANSI color codes and per-type styles used by `inspect(..., { colors: true })`.
+   */
+  const inspectColors = {
+    reset: [0, 0], bold: [1, 22], dim: [2, 22], italic: [3, 23], underline: [4, 24],
+    inverse: [7, 27], hidden: [8, 28], strikethrough: [9, 29],
+    black: [30, 39], red: [31, 39], green: [32, 39], yellow: [33, 39], blue: [34, 39],
+    magenta: [35, 39], cyan: [36, 39], white: [37, 39], gray: [90, 39],
+    redBright: [91, 39], greenBright: [92, 39], yellowBright: [93, 39], blueBright: [94, 39],
+    magentaBright: [95, 39], cyanBright: [96, 39], whiteBright: [97, 39],
+  };
+  inspectColors.grey = inspectColors.gray;
+
+  const inspectStyles = {
+    special: "cyan", number: "yellow", bigint: "yellow", boolean: "yellow",
+    undefined: "grey", null: "bold", string: "green", symbol: "green",
+    date: "magenta", regexp: "red", module: "underline",
+  };
+
+  const identifierRe = /^[a-zA-Z_$][a-zA-Z_$0-9]*$/;
+
+  /**
+   * Quote a string the way Node.js does: prefer single quotes, fall back to
+   * double quotes or backticks to avoid escaping, and escape control characters.
+   */
+  function quoteString(str) {
+    let quote = "'";
+    if (str.includes("'")) {
+      if (!str.includes('"')) quote = '"';
+      else if (!str.includes("`") && !str.includes("${")) quote = "`";
+    }
+    let out = "";
+    for (const ch of str) {
+      const code = ch.charCodeAt(0);
+      if (ch === quote || ch === "\\") out += "\\" + ch;
+      else if (ch === "\n") out += "\\n";
+      else if (ch === "\t") out += "\\t";
+      else if (ch === "\r") out += "\\r";
+      else if (ch === "\b") out += "\\b";
+      else if (ch === "\f") out += "\\f";
+      else if (ch === "\v") out += "\\v";
+      else if (code < 0x20 || code === 0x7f) out += `\\x${code.toString(16).padStart(2, "0").toUpperCase()}`;
+      else out += ch;
+    }
+    return quote + out + quote;
+  }
+
   /**
    * Inspect a value and return a string representation.
+   *
+   * Supports Node's options object as well as the legacy
+   * `inspect(obj, showHidden, depth, colors)` signature, honors
+   * `[util.inspect.custom]`, and breaks long output across lines.
    */
-  function inspect(obj, options = {}) {
-    const {
-      depth = 2,
-      colors = false,
-      showHidden = false,
-      maxArrayLength = 100,
-      maxStringLength = 10000,
-      sorted = false,
-      getters = false,
-    } = typeof options === "boolean" ? { showHidden: options } : options;
-
-    const seen = new WeakSet();
-
-    function inspectValue(value, currentDepth) {
-      if (value === null) return colors ? "\x1b[1mnull\x1b[22m" : "null";
-      if (value === undefined) return colors ? "\x1b[90mundefined\x1b[39m" : "undefined";
-
-      const type = typeof value;
-
-      if (type === "string") {
-        const truncated = value.length > maxStringLength ? value.slice(0, maxStringLength) + "..." : value;
-        const escaped = JSON.stringify(truncated);
-        return colors ? `\x1b[32m${escaped}\x1b[39m` : escaped;
-      }
-      if (type === "number") {
-        const str = Object.is(value, -0) ? "-0" : String(value);
-        return colors ? `\x1b[33m${str}\x1b[39m` : str;
-      }
-      if (type === "bigint") {
-        const str = `${value}n`;
-        return colors ? `\x1b[33m${str}\x1b[39m` : str;
-      }
-      if (type === "boolean") {
-        return colors ? `\x1b[33m${value}\x1b[39m` : String(value);
-      }
-      if (type === "symbol") {
-        const str = value.toString();
-        return colors ? `\x1b[32m${str}\x1b[39m` : str;
-      }
-      if (type === "function") {
-        const name = value.name || "(anonymous)";
-        const str = `[Function: ${name}]`;
-        return colors ? `\x1b[36m${str}\x1b[39m` : str;
-      }
-
-      if (type === "object") {
-        if (seen.has(value)) return colors ? "\x1b[36m[Circular]\x1b[39m" : "[Circular]";
-        if (currentDepth > depth) return Array.isArray(value) ? "[Array]" : "[Object]";
-        seen.add(value);
-
-        if (value instanceof Date) return colors ? `\x1b[35m${value.toISOString()}\x1b[39m` : value.toISOString();
-        if (value instanceof RegExp) return colors ? `\x1b[31m${value}\x1b[39m` : value.toString();
-        if (value instanceof Error) return value.stack || value.toString();
-        if (value instanceof Map) {
-          if (value.size === 0) return "Map(0) {}";
-          const entries = [...value.entries()].slice(0, maxArrayLength)
-            .map(([k, v]) => `${inspectValue(k, currentDepth + 1)} => ${inspectValue(v, currentDepth + 1)}`).join(", ");
-          return `Map(${value.size}) { ${entries} }`;
-        }
-        if (value instanceof Set) {
-          if (value.size === 0) return "Set(0) {}";
-          const entries = [...value.values()].slice(0, maxArrayLength).map((v) => inspectValue(v, currentDepth + 1)).join(", ");
-          return `Set(${value.size}) { ${entries} }`;
-        }
-        if (value instanceof WeakMap) return "WeakMap { <items unknown> }";
-        if (value instanceof WeakSet) return "WeakSet { <items unknown> }";
-        if (ArrayBuffer.isView(value) && !(value instanceof DataView)) {
-          const name = value.constructor.name;
-          const len = value.length;
-          if (len === 0) return `${name}(0) []`;
-          const items = [...value].slice(0, maxArrayLength).map(String).join(", ");
-          const suffix = len > maxArrayLength ? `, ... ${len - maxArrayLength} more items` : "";
-          return `${name}(${len}) [ ${items}${suffix} ]`;
-        }
-        if (value instanceof ArrayBuffer) return `ArrayBuffer { byteLength: ${value.byteLength} }`;
-        if (value instanceof Promise) return "Promise { <pending> }";
-
-        if (Array.isArray(value)) {
-          if (value.length === 0) return "[]";
-          const items = value.slice(0, maxArrayLength).map((v) => inspectValue(v, currentDepth + 1));
-          const suffix = value.length > maxArrayLength ? `, ... ${value.length - maxArrayLength} more items` : "";
-          return `[ ${items.join(", ")}${suffix} ]`;
-        }
-
-        let keys = Object.keys(value);
-        if (sorted) keys = keys.sort();
-        if (showHidden) keys = keys.concat(Object.getOwnPropertySymbols(value));
-        if (keys.length === 0) return "{}";
-
-        const entries = keys.map((key) => {
-          const desc = Object.getOwnPropertyDescriptor(value, key);
-          let val;
-          if (desc.get && !getters) val = "[Getter]";
-          else if (desc.set && !desc.get) val = "[Setter]";
-          else val = inspectValue(value[key], currentDepth + 1);
-          return `${typeof key === "symbol" ? key.toString() : key}: ${val}`;
-        });
-        return `{ ${entries.join(", ")} }`;
+  function inspect(obj, opts, ...legacy) {
+    const ctx = { ...inspect.defaultOptions };
+    if (typeof opts === "boolean") {
+      ctx.showHidden = opts;
+      if (legacy.length >= 1 && legacy[0] !== undefined) ctx.depth = legacy[0];
+      if (legacy.length >= 2 && legacy[1] !== undefined) ctx.colors = legacy[1];
+    } else if (opts !== null && typeof opts === "object") {
+      Object.assign(ctx, opts);
+    }
+    if (ctx.depth === null) ctx.depth = Infinity;
+    if (ctx.maxArrayLength === null) ctx.maxArrayLength = Infinity;
+    if (ctx.maxStringLength === null) ctx.maxStringLength = Infinity;
+    ctx.stylize = ctx.colors ? stylizeWithColor : (str) => str;
+    ctx.currentDepth = 0;
+
+    const ancestors = [];
+    const circular = new Map();
+
+    function stylizeWithColor(str, styleType) {
+      const style = inspect.styles[styleType];
+      const color = style !== undefined ? inspect.colors[style] : undefined;
+      return color ? `\x1b[${color[0]}m${str}\x1b[${color[1]}m` : str;
+    }
+
+    function formatPrimitive(value) {
+      switch (typeof value) {
+        case "string": {
+          let str = value;
+          let trailer = "";
+          if (str.length > ctx.maxStringLength) {
+            const remaining = str.length - ctx.maxStringLength;
+            str = str.slice(0, ctx.maxStringLength);
+            trailer = `... ${remaining} more character${remaining > 1 ? "s" : ""}`;
+          }
+          return ctx.stylize(quoteString(str), "string") + trailer;
+        }
+        case "number":
+          return ctx.stylize(Object.is(value, -0) ? "-0" : String(value), "number");
+        case "bigint":
+          return ctx.stylize(`${value}n`, "bigint");
+        case "boolean":
+          return ctx.stylize(String(value), "boolean");
+        case "undefined":
+          return ctx.stylize("undefined", "undefined");
+        case "symbol":
+          return ctx.stylize(value.toString(), "symbol");
+      }
+      return ctx.stylize("null", "null");
+    }
+
+    function formatKey(key, desc) {
+      let name;
+      if (typeof key === "symbol") name = `[${ctx.stylize(key.toString(), "symbol")}]`;
+      else if (identifierRe.test(key)) name = key;
+      else name = ctx.stylize(quoteString(key), "string");
+      if (desc && !desc.enumerable) name = `[${name}]`;
+      return name;
+    }
+
+    function formatProperty(value, key, depth, desc) {
+      desc = desc || Object.getOwnPropertyDescriptor(value, key) || { value: value[key], enumerable: true };
+      let str;
+      if (desc.value !== undefined || !("get" in desc || "set" in desc)) {
+        str = formatValue(desc.value, depth + 1);
+      } else if (desc.get !== undefined) {
+        const label = desc.set !== undefined ? "Getter/Setter" : "Getter";
+        if (ctx.getters) {
+          try {
+            str = `${ctx.stylize(`[${label}:`, "special")} ${formatValue(desc.get.call(value), depth + 1)}${ctx.stylize("]", "special")}`;
+          } catch (err) {
+            str = ctx.stylize(`[${label}: <Inspection threw (${err.message})>]`, "special");
+          }
+        } else {
+          str = ctx.stylize(`[${label}]`, "special");
+        }
+      } else {
+        str = ctx.stylize(desc.set !== undefined ? "[Setter]" : "undefined", desc.set !== undefined ? "special" : "undefined");
+      }
+      return `${formatKey(key, desc)}: ${str}`;
+    }
+
+    function ownKeys(value, filter) {
+      let keys = ctx.showHidden ? Object.getOwnPropertyNames(value) : Object.keys(value);
+      const symbols = Object.getOwnPropertySymbols(value);
+      keys = keys.concat(ctx.showHidden ? symbols : symbols.filter((s) => Object.prototype.propertyIsEnumerable.call(value, s)));
+      if (filter) keys = keys.filter(filter);
+      if (ctx.sorted) {
+        const byName = (a, b) => (String(a) < String(b) ? -1 : String(a) > String(b) ? 1 : 0);
+        keys.sort(typeof ctx.sorted === "function" ? ctx.sorted : byName);
+      }
+      return keys;
+    }
+
+    function isInstanceof(value, ctor) {
+      try {
+        return value instanceof ctor;
+      } catch {
+        return false;
+      }
+    }
+
+    function constructorName(value) {
+      let proto = value;
+      while (proto !== null) {
+        const desc = Object.getOwnPropertyDescriptor(proto, "constructor");
+        if (desc && typeof desc.value === "function" && desc.value.name !== "" && isInstanceof(value, desc.value)) {
+          return desc.value.name;
+        }
+        proto = Object.getPrototypeOf(proto);
+      }
+      return null;
+    }
+
+    function prefix(ctor, tag, fallback, size) {
+      const sizeStr = size !== undefined ? `(${size})` : "";
+      if (ctor === null) return `[${fallback}${sizeStr}: null prototype] `;
+      if (tag !== "" && tag !== ctor) return `${ctor}${sizeStr} [${tag}] `;
+      return `${ctor}${sizeStr} `;
+    }
+
+    function moreItems(count) {
+      return `... ${count} more item${count > 1 ? "s" : ""}`;
+    }
+
+    function formatArrayLike(value, depth) {
+      const output = [];
+      const max = Math.min(ctx.maxArrayLength, value.length);
+      let holes = 0;
+      let i = 0;
+      for (; i < value.length && output.length < max; i++) {
+        if (!Object.prototype.hasOwnProperty.call(value, i)) {
+          holes++;
+          continue;
+        }
+        if (holes > 0) {
+          output.push(ctx.stylize(`<${holes} empty item${holes > 1 ? "s" : ""}>`, "undefined"));
+          holes = 0;
+          if (output.length >= max) break;
+        }
+        output.push(formatValue(value[i], depth + 1));
+      }
+      if (holes > 0 && output.length < max) {
+        output.push(ctx.stylize(`<${holes} empty item${holes > 1 ? "s" : ""}>`, "undefined"));
+      }
+      if (i < value.length) output.push(moreItems(value.length - i));
+      return output;
+    }
+
+    function isBelowBreakLength(output, start, base) {
+      let totalLength = output.length + start;
+      if (totalLength + output.length > ctx.breakLength) return false;
+      for (const entry of output) {
+        totalLength += ctx.colors ? stripVTControlCharacters(entry).length : entry.length;
+        if (totalLength > ctx.breakLength) return false;
+      }
+      return base === "" || !base.includes("\n");
+    }
+
+    // Lay out long arrays of short entries as aligned columns, like Node.js.
+    function groupArrayElements(output, value, depth) {
+      const indentationLvl = depth * 2;
+      let totalLength = 0;
+      let maxLength = 0;
+      let outputLength = output.length;
+      if (ctx.maxArrayLength < output.length) outputLength--; // skip "... more items"
+      const separatorSpace = 2;
+      const dataLen = new Array(outputLength);
+      for (let i = 0; i < outputLength; i++) {
+        const len = ctx.colors ? stripVTControlCharacters(output[i]).length : output[i].length;
+        dataLen[i] = len;
+        totalLength += len + separatorSpace;
+        if (maxLength < len) maxLength = len;
+      }
+      const actualMax = maxLength + separatorSpace;
+      if (actualMax * 3 + indentationLvl < ctx.breakLength &&
+          (totalLength / actualMax > 5 || maxLength <= 6)) {
+        const averageBias = Math.sqrt(actualMax - totalLength / output.length);
+        const biasedMax = Math.max(actualMax - 3 - averageBias, 1);
+        const columns = Math.min(
+          Math.round(Math.sqrt(2.5 * biasedMax * outputLength) / biasedMax),
+          Math.floor((ctx.breakLength - indentationLvl) / actualMax),
+          ctx.compact * 4,
+          15,
+        );
+        if (columns <= 1) return output;
+        const maxLineLength = [];
+        for (let i = 0; i < columns; i++) {
+          let lineLength = 0;
+          for (let j = i; j < output.length; j += columns) {
+            if (dataLen[j] > lineLength) lineLength = dataLen[j];
+          }
+          maxLineLength.push(lineLength + separatorSpace);
+        }
+        let padStart = true;
+        for (let i = 0; i < output.length; i++) {
+          if (typeof value[i] !== "number" && typeof value[i] !== "bigint") {
+            padStart = false;
+            break;
+          }
+        }
+        const grouped = [];
+        for (let i = 0; i < outputLength; i += columns) {
+          const max = Math.min(i + columns, outputLength);
+          let str = "";
+          let j = i;
+          for (; j < max - 1; j++) {
+            const padding = maxLineLength[j - i] + output[j].length - dataLen[j];
+            str += padStart ? `${output[j]}, `.padStart(padding, " ") : `${output[j]}, `.padEnd(padding, " ");
+          }
+          if (padStart) {
+            const padding = maxLineLength[j - i] + output[j].length - dataLen[j] - separatorSpace;
+            str += output[j].padStart(padding, " ");
+          } else {
+            str += output[j];
+          }
+          grouped.push(str);
+        }
+        if (ctx.maxArrayLength < output.length) grouped.push(output[outputLength]);
+        return grouped;
+      }
+      return output;
+    }
+
+    function reduceToSingleString(output, base, braces, depth, value, isArrayLike) {
+      const indentation = `\n${"  ".repeat(depth)}`;
+      if (ctx.compact !== true) {
+        if (typeof ctx.compact === "number" && ctx.compact >= 1) {
+          const entries = output.length;
+          if (isArrayLike && entries > 6) output = groupArrayElements(output, value, depth);
+          // Only the innermost `compact` levels of nesting may share a line.
+          if (ctx.currentDepth - depth < ctx.compact && entries === output.length) {
+            const start = output.length + depth * 2 + braces[0].length + base.length + 10;
+            if (isBelowBreakLength(output, start, base)) {
+              const joined = output.join(", ");
+              if (!joined.includes("\n")) {
+                return `${base ? `${base} ` : ""}${braces[0]} ${joined} ${braces[1]}`;
+              }
+            }
+          }
+        }
+        return `${base ? `${base} ` : ""}${braces[0]}${indentation}  ${output.join(`,${indentation}  `)}${indentation}${braces[1]}`;
+      }
+      if (isBelowBreakLength(output, 0, base)) {
+        return `${braces[0]}${base ? ` ${base}` : ""} ${output.join(", ")} ${braces[1]}`;
+      }
+      const ln = base === "" && braces[0].length === 1 ? " " : `${base ? ` ${base}` : ""}${indentation}  `;
+      return `${braces[0]}${ln}${output.join(`,${indentation}  `)} ${braces[1]}`;
+    }
+
+    function formatFunctionBase(value, ctor) {
+      const source = Function.prototype.toString.call(value);
+      if (source.startsWith("class") && /^class\s*[\s{A-Za-z_$]/.test(source)) {
+        let base = `[class ${value.name || "(anonymous)"}`;
+        const superCtor = Object.getPrototypeOf(value);
+        if (superCtor && superCtor.name) base += ` extends ${superCtor.name}`;
+        return base + "]";
+      }
+      let type = "Function";
+      const tag = value[Symbol.toStringTag];
+      if (tag === "AsyncFunction" || tag === "GeneratorFunction" || tag === "AsyncGeneratorFunction") type = tag;
+      let base = `[${type}`;
+      base += value.name ? `: ${value.name}]` : " (anonymous)]";
+      if (ctor === null) base += " [null prototype]";
+      return base;
+    }
+
+    function formatValue(value, depth) {
+      if (value === null || (typeof value !== "object" && typeof value !== "function")) {
+        return formatPrimitive(value);
+      }
+
+      if (ctx.customInspect !== false) {
+        const custom = value[inspect.custom];
+        if (typeof custom === "function" && custom !== inspect) {
+          const childOptions = { ...ctx, depth: ctx.depth - depth };
+          const result = custom.call(value, ctx.depth - depth, childOptions, inspect);
+          if (result !== value) {
+            return typeof result === "string" ? result : formatValue(result, depth);
+          }
+        }
+      }
+
+      if (ancestors.includes(value)) {
+        if (!circular.has(value)) circular.set(value, circular.size + 1);
+        return ctx.stylize(`[Circular *${circular.get(value)}]`, "special");
+      }
+
+      ancestors.push(value);
+      let result;
+      try {
+        result = formatRaw(value, depth);
+      } finally {
+        ancestors.pop();
+      }
+      if (circular.has(value)) {
+        result = `${ctx.stylize(`<ref *${circular.get(value)}>`, "special")} ${result}`;
+      }
+      return result;
+    }
+
+    function formatRaw(value, depth) {
+      const ctor = constructorName(value);
+      let tag = value[Symbol.toStringTag];
+      if (typeof tag !== "string") tag = "";
+
+      let base = "";
+      let braces = ["{", "}"];
+      let keys;
+      let formatter = () => [];
+      let noEntries = false;
+
+      if (typeof value === "function") {
+        base = ctx.stylize(formatFunctionBase(value, ctor), "special");
+        keys = ownKeys(value);
+        if (keys.length === 0) return base;
+      } else if (Array.isArray(value)) {
+        const pre = ctor !== "Array" || tag !== "" ? prefix(ctor, tag, "Array", value.length) : "";
+        keys = ownKeys(value, (key) => typeof key === "symbol" || !/^(0|[1-9][0-9]*)$/.test(key));
+        braces = [`${pre}[`, "]"];
+        if (value.length === 0 && keys.length === 0) return `${braces[0]}]`;
+        formatter = () => formatArrayLike(value, depth);
+      } else if (value instanceof Map) {
+        keys = ownKeys(value);
+        braces = [`${prefix(ctor, tag === "Map" ? "" : tag, "Map", value.size)}{`, "}"];
+        if (value.size === 0 && keys.length === 0) return `${braces[0]}}`;
+        formatter = () => {
+          const output = [];
+          let remaining = value.size;
+          for (const [k, v] of value) {
+            if (output.length >= ctx.maxArrayLength) break;
+            output.push(`${formatValue(k, depth + 1)} => ${formatValue(v, depth + 1)}`);
+            remaining--;
+          }
+          if (remaining > 0) output.push(moreItems(remaining));
+          return output;
+        };
+      } else if (value instanceof Set) {
+        keys = ownKeys(value);
+        braces = [`${prefix(ctor, tag === "Set" ? "" : tag, "Set", value.size)}{`, "}"];
+        if (value.size === 0 && keys.length === 0) return `${braces[0]}}`;
+        formatter = () => {
+          const output = [];
+          let remaining = value.size;
+          for (const v of value) {
+            if (output.length >= ctx.maxArrayLength) break;
+            output.push(formatValue(v, depth + 1));
+            remaining--;
+          }
+          if (remaining > 0) output.push(moreItems(remaining));
+          return output;
+        };
+      } else if (typeof Buffer !== "undefined" && Buffer.isBuffer(value)) {
+        // Matches Buffer.prototype[inspect.custom] in Node (INSPECT_MAX_BYTES = 50).
+        const hex = [...value.subarray(0, 50)].map((b) => b.toString(16).padStart(2, "0")).join(" ");
+        const more = value.length > 50 ? ` ... ${value.length - 50} more bytes` : "";
+        return `<${ctor || "Buffer"}${hex ? ` ${hex}` : ""}${more}>`;
+      } else if (ArrayBuffer.isView(value) && !(value instanceof DataView)) {
+        keys = ownKeys(value, (key) => typeof key === "symbol" || !/^(0|[1-9][0-9]*)$/.test(key));
+        braces = [`${prefix(ctor, tag === ctor ? "" : tag, tag || "TypedArray", value.length)}[`, "]"];
+        if (value.length === 0 && keys.length === 0) return `${braces[0]}]`;
+        formatter = () => {
+          const max = Math.min(ctx.maxArrayLength, value.length);
+          const output = [];
+          for (let i = 0; i < max; i++) output.push(formatPrimitive(value[i]));
+          if (value.length > max) output.push(moreItems(value.length - max));
+          return output;
+        };
+      } else {
+        keys = ownKeys(value);
+        if (value instanceof Date) {
+          base = ctx.stylize(Number.isNaN(value.getTime()) ? "Invalid Date" : value.toISOString(), "date");
+          if (keys.length === 0) return base;
+        } else if (value instanceof RegExp) {
+          base = ctx.stylize(RegExp.prototype.toString.call(value), "regexp");
+          if (keys.length === 0) return base;
+        } else if (value instanceof Error) {
+          base = value.stack || Error.prototype.toString.call(value);
+          keys = keys.filter((key) => key !== "stack" && key !== "message");
+          if ("cause" in value && !keys.includes("cause")) keys.push("cause");
+          if (keys.length === 0) return base;
+        } else if (value instanceof WeakMap || value instanceof WeakSet) {
+          return `${prefix(ctor, tag === ctor ? "" : tag, tag)}{ ${ctx.stylize("<items unknown>", "special")} }`;
+        } else if (value instanceof Promise) {
+          braces = [`${prefix(ctor, tag === "Promise" ? "" : tag, "Promise")}{`, "}"];
+          formatter = () => {
+            const details = core.getPromiseDetails?.(value);
+            if (!details || details[0] === 0) return [ctx.stylize("<pending>", "special")];
+            const result = formatValue(details[1], depth + 1);
+            return [details[0] === 2 ? `${ctx.stylize("<rejected>", "special")} ${result}` : result];
+          };
+        } else if (value instanceof ArrayBuffer || (typeof SharedArrayBuffer !== "undefined" && value instanceof SharedArrayBuffer)) {
+          braces = [`${prefix(ctor, "", tag)}{`, "}"];
+          formatter = () => {
+            const bytes = [...new Uint8Array(value, 0, Math.min(ctx.maxArrayLength, value.byteLength))]
+              .map((b) => b.toString(16).padStart(2, "0")).join(" ");
+            const more = value.byteLength > ctx.maxArrayLength ? ` ... ${value.byteLength - ctx.maxArrayLength} more bytes` : "";
+            return [`[Uint8Contents]: <${bytes}${more}>`, `byteLength: ${formatPrimitive(value.byteLength)}`];
+          };
+        } else if (value instanceof Number || value instanceof String || value instanceof Boolean ||
+                   value instanceof BigInt || value instanceof Symbol) {
+          const primitive = value.valueOf();
+          const type = typeof primitive;
+          const name = type[0].toUpperCase() + type.slice(1);
+          base = `[${name}${ctor !== name ? ` (${ctor})` : ""}: ${formatPrimitive(primitive)}]`;
+          if (type === "string") keys = keys.filter((key) => !/^(0|[1-9][0-9]*)$/.test(key) && key !== "length");
+          if (keys.length === 0) return base;
+        } else {
+          if (ctor === "Object" && tag === "") {
+            noEntries = keys.length === 0;
+          } else {
+            braces[0] = `${prefix(ctor, tag, "Object")}{`;
+            noEntries = keys.length === 0;
+          }
+          if (noEntries) return `${braces[0]}}`;
+        }
+      }
+
+      if (depth > ctx.depth) {
+        const name = Array.isArray(value) ? "Array" : (ctor || tag || "Object");
+        return ctx.stylize(`[${name}]`, "special");
       }
-      return String(value);
+
+      ctx.currentDepth = depth;
+      const output = formatter();
+      for (const key of keys) output.push(formatProperty(value, key, depth));
+      const isArrayLike = Array.isArray(value) || (ArrayBuffer.isView(value) && !(value instanceof DataView));
+      return reduceToSingleString(output, base, braces, depth, value, isArrayLike);
     }
-    return inspectValue(obj, 0);
+
+    return formatValue(obj, 0);
   }
 
   inspect.custom = Symbol.for("nodejs.util.inspect.custom");
-  inspect.defaultOptions = { showHidden: false, depth: 2, colors: false, maxArrayLength: 100 };
+  inspect.colors = inspectColors;
+  inspect.styles = inspectStyles;
+  inspect.defaultOptions = {
+    showHidden: false,
+    depth: 2,
+    colors: false,
+    customInspect: true,
+    maxArrayLength: 100,
+    maxStringLength: 10000,
+    breakLength: 80,
+    compact: 3,
+    sorted: false,
+    getters: false,
+  };
 
   /**
    * Format a string with printf-style formatting.
    */
-  function format(fmt, ...args) {
-    if (typeof fmt !== "string") return [fmt, ...args].map((v) => inspect(v)).join(" ");
-    let i = 0;
-    let str = fmt.replace(/%([sdifjoOc%])/g, (match, spec) => {
-      if (spec === "%") return "%";
-      if (i >= args.length) return match;
-      const arg = args[i++];
-      switch (spec) {
-        case "s": return String(arg);
-        case "d": case "i": return Number.parseInt(arg, 10).toString();
-        case "f": return Number.parseFloat(arg).toString();
-        case "j": try { return JSON.stringify(arg); } catch { return "[Circular]"; }
-        case "o": case "O": return inspect(arg);
-        case "c": return "";
-        default: return match;
-      }
-    });
-    while (i < args.length) str += " " + inspect(args[i++]);
-    return str;
+  function format(...args) {
+    return formatWithOptions(undefined, ...args);
   }
 
-  function formatWithOptions(inspectOptions, fmt, ...args) {
-    if (typeof fmt !== "string") return [fmt, ...args].map((v) => inspect(v, inspectOptions)).join(" ");
-    let i = 0;
-    let str = fmt.replace(/%([sdifjoOc%])/g, (match, spec) => {
-      if (spec === "%") return "%";
-      if (i >= args.length) return match;
-      const arg = args[i++];
-      switch (spec) {
-        case "s": return String(arg);
-        case "d": case "i": return Number.parseInt(arg, 10).toString();
-        case "f": return Number.parseFloat(arg).toString();
-        case "j": try { return JSON.stringify(arg); } catch { return "[Circular]"; }
-        case "o": case "O": return inspect(arg, inspectOptions);
-        case "c": return "";
-        default: return match;
+  function formatWithOptions(inspectOptions, ...args) {
+    const opts = inspectOptions || {};
+    const first = args[0];
+    let str = "";
+    let a = 1;
+
+    if (typeof first === "string") {
+      if (args.length === 1) return first;
+      let lastPos = 0;
+      for (let i = 0; i < first.length - 1; i++) {
+        if (first.charCodeAt(i) !== 37) continue; // '%'
+        const spec = first[i + 1];
+        if (spec === "%") {
+          str += first.slice(lastPos, i) + "%";
+          lastPos = i + 2;
+          i++;
+          continue;
+        }
+        if (a >= args.length || !"sdifjoOc".includes(spec)) continue;
+        const arg = args[a];
+        let piece;
+        switch (spec) {
+          case "s":
+            if (typeof arg === "bigint") piece = `${arg}n`;
+            else if (typeof arg === "number") piece = Object.is(arg, -0) ? "-0" : String(arg);
+            else if (typeof arg === "symbol") piece = arg.toString();
+            else if (arg !== null && typeof arg === "object" && !hasUserToString(arg)) {
+              piece = inspect(arg, { ...opts, depth: 0, colors: false, compact: 3 });
+            } else piece = String(arg);
+            break;
+          case "d": {
+            if (typeof arg === "bigint") piece = `${arg}n`;
+            else if (typeof arg === "symbol") piece = "NaN";
+            else {
+              const n = Number(arg);
+              piece = Object.is(n, -0) ? "-0" : String(n);
+            }
+            break;
+          }
+          case "i":
+            if (typeof arg === "bigint") piece = `${arg}n`;
+            else if (typeof arg === "symbol") piece = "NaN";
+            else piece = String(Number.parseInt(arg, 10));
+            break;
+          case "f":
+            piece = typeof arg === "symbol" ? "NaN" : String(Number.parseFloat(arg));
+            break;
+          case "j":
+            try {
+              piece = JSON.stringify(arg);
+            } catch (err) {
+              if (!(err instanceof TypeError) || !/circular/i.test(err.message)) throw err;
+              piece = "[Circular]";
+            }
+            break;
+          case "o":
+            piece = inspect(arg, { ...opts, showHidden: true, showProxy: true, depth: 4 });
+            break;
+          case "O":
+            piece = inspect(arg, opts);
+            break;
+          case "c":
+            piece = "";
+            break;
+        }
+        str += first.slice(lastPos, i) + piece;
+        lastPos = i + 2;
+        i++;
+        a++;
       }
-    });
-    while (i < args.length) str += " " + inspect(args[i++], inspectOptions);
+      str += first.slice(lastPos);
+    } else {
+      a = 0;
+    }
+
+    for (; a < args.length; a++) {
+      const value = args[a];
+      const piece = typeof value === "string" ? value : inspect(value, opts);
+      str += (str.length > 0 || a > 0 ? " " : "") + piece;
+    }
     return str;
   }
 
+  // `%s` only calls toString() when a user-defined class (not a built-in such
+  // as Object, Array or Error) provides one; everything else is inspected.
+  function hasUserToString(obj) {
+    let proto = obj;
+    while (proto !== null) {
+      const ctor = Object.getOwnPropertyDescriptor(proto, "constructor")?.value;
+      if (typeof ctor === "function" && globalThis[ctor.name] === ctor) return false;
+      if (Object.prototype.hasOwnProperty.call(proto, "toString")) return true;
+      proto = Object.getPrototypeOf(proto);
+    }
+    return false;
+  }
+
   /**
    * Convert a callback-style function to a Promise-returning function.
    */
@@ -6554,18 +7604,18 @@
     if (original[promisify.custom]) {
       const fn = original[promisify.custom];
       if (typeof fn !== "function") throw new TypeError('The "util.promisify.custom" property must be of type Function');
-      return fn;
+      return Object.defineProperty(fn, promisify.custom, { value: fn, enumerable: false, writable: false, configurable: true });
     }
     function fn(...args) {
       return new Promise((resolve, reject) => {
-        original.call(this, ...args, (err, ...values) => {
+        Reflect.apply(original, this, [...args, (err, ...values) => {
           if (err) reject(err);
-          else if (values.length === 1) resolve(values[0]);
-          else resolve(values);
-        });
+          else resolve(values[0]);
+        }]);
       });
     }
     Object.setPrototypeOf(fn, Object.getPrototypeOf(original));
+    Object.defineProperty(fn, promisify.custom, { value: fn, enumerable: false, writable: false, configurable: true });
     return Object.defineProperties(fn, Object.getOwnPropertyDescriptors(original));
   }
   promisify.custom = Symbol.for("nodejs.util.promisify.custom");
@@ -6578,13 +7628,40 @@
     function callbackified(...args) {
       const callback = args.pop();
       if (typeof callback !== "function") throw new TypeError("The last argument must be of type Function");
-      Promise.resolve(original.apply(this, args)).then(
+      Reflect.apply(original, this, args).then(
         (value) => process.nextTick(callback, null, value),
-        (err) => { if (!err) { err = new Error("Promise rejected with falsy value"); err.reason = err; } process.nextTick(callback, err); }
+        (reason) => {
+          if (!reason) {
+            const err = new Error("Promise was rejected with falsy value");
+            err.code = "ERR_FALSY_VALUE_REJECTION";
+            err.reason = reason;
+            reason = err;
+          }
+          process.nextTick(callback, reason);
+        }
       );
     }
+    const descriptors = Object.getOwnPropertyDescriptors(original);
+    if (typeof descriptors.length?.value === "number") descriptors.length.value++;
+    if (typeof descriptors.name?.value === "string") descriptors.name.value += "Callbackified";
     Object.setPrototypeOf(callbackified, Object.getPrototypeOf(original));
-    return Object.defineProperties(callbackified, Object.getOwnPropertyDescriptors(original));
+    return Object.defineProperties(callbackified, descriptors);
+  }
+
+  /**
+   * Remove ANSI escape codes from a string.
+   */
+  function stripVTControlCharacters(str) {
+    if (typeof str !== "string") throw new TypeError('The "str" argument must be of type string');
+    // eslint-disable-next-line no-control-regex
+    return str.replace(/[\u001B\u009B][[\]()#;?]*(?:(?:(?:(?:;[-a-zA-Z\d\/#&.:=?%@~_]+)*|[a-zA-Z\d]+(?:;[-a-zA-Z\d\/#&.:=?%@~_]*)*)?\u0007)|(?:(?:\d{1,4}(?:;\d{0,4})*)?[\dA-PR-TZcf-ntqry=><~]))/g, "");
+  }
+
+  /**
+   * Replace lone surrogates with U+FFFD.
+   */
+  function toUSVString(str) {
+    return String(str).toWellFormed ? String(str).toWellFormed() : String(str).replace(/[\uD800-\uDBFF](?![\uDC00-\uDFFF])|(?<![\uD800-\uDBFF])[\uDC00-\uDFFF]/g, "�");
   }
 
   /**
@@ -6657,7 +7734,7 @@
     isNativeError: (v) => v instanceof Error,
     isNumberObject: (v) => v instanceof Number,
     isPromise: (v) => v instanceof Promise,
-    isProxy: () => false,
+    isProxy: (v) => (typeof v === "object" || typeof v === "function") && v !== null && core.getProxyDetails?.(v) != null,
     isRegExp: (v) => v instanceof RegExp,
     isSet: (v) => v instanceof Set,
     isSetIterator: (v) => v?.[Symbol.toStringTag] === "Set Iterator",
@@ -6710,7 +7787,7 @@
 
   const utilModule = {
     format, formatWithOptions, inspect, promisify, callbackify, deprecate, inherits, debuglog,
-    debug: debuglog, isDeepStrictEqual, types,
+    debug: debuglog, isDeepStrictEqual, types, stripVTControlCharacters, toUSVString,
     // Legacy type checking
     isArray: Array.isArray,
     isBoolean: (v) => typeof v === "boolean",
@@ -8441,6 +9518,38 @@
   // child_process module
   // ============================================================================
 
+  /**
+   * Normalize Node's `stdio` option to one mode per standard fd:
+   * "pipe", "inherit" or "ignore". Passing the parent's own fd (or
+   * process.stdout etc.) inherits it.
+   * @param {string|Array|undefined} stdio
+   * @returns {string[]}
+   */
+  function normalizeStdio(stdio) {
+    const modes = typeof stdio === "string" ? [stdio, stdio, stdio] : (stdio || []);
+    const result = [];
+    for (let fd = 0; fd < 3; fd++) {
+      const mode = modes[fd];
+      if (mode === "inherit" || mode === "ignore") {
+        result.push(mode);
+      } else if (typeof mode === "number" || (mode && typeof mode.fd === "number")) {
+        const n = typeof mode === "number" ? mode : mode.fd;
+        result.push(n >= 0 && n <= 2 ? "inherit" : "pipe");
+      } else {
+        // "pipe", "overlapped", null and undefined all get a pipe
+        result.push("pipe");
+      }
+    }
+    return result;
+  }
+
+  /**
+   * Options as the spawn ops expect them (stdio normalized).
+   */
+  function spawnOpOptions(options) {
+    return { ...(options || {}), stdio: normalizeStdio(options && options.stdio) };
+  }
+
   /**
    * Execute a command synchronously in a shell.
    * @param {string} command - The command to run
@@ -8448,7 +9557,10 @@
    * @returns {Buffer|string} - stdout output
    */
   function execSync(command, options = {}) {
-    const result = ops.op_howth_exec_sync(command, options);
+    // Node's execSync shows the child's stderr unless stdio says otherwise
+    const opOptions = spawnOpOptions(options);
+    if (options.stdio === undefined) opOptions.stdio[2] = "inherit";
+    const result = ops.op_howth_exec_sync(command, opOptions);
 
     if (result.error) {
       const err = new Error(result.error);
@@ -8488,7 +9600,7 @@
     }
 
     const result = ops.op_howth_spawn_sync(file, args, {
-      ...options,
+      ...spawnOpOptions(options),
       shell: false,
     });
 
@@ -8529,13 +9641,17 @@
       args = [];
     }
 
-    const result = ops.op_howth_spawn_sync(command, args, options);
+    const stdio = normalizeStdio(options.stdio);
+    const result = ops.op_howth_spawn_sync(command, args, { ...options, stdio });
+    // Streams that weren't piped have no captured output
+    const stdout = stdio[1] === "pipe" ? Buffer.from(result.stdout) : null;
+    const stderr = stdio[2] === "pipe" ? Buffer.from(result.stderr) : null;
 
     return {
       pid: 0, // We don't have the real PID in sync mode
-      output: [null, Buffer.from(result.stdout), Buffer.from(result.stderr)],
-      stdout: Buffer.from(result.stdout),
-      stderr: Buffer.from(result.stderr),
+      output: [null, stdout, stderr],
+      stdout,
+      stderr,
       status: result.status,
       signal: null,
       error: result.error ? new Error(result.error) : undefined,
@@ -8558,7 +9674,7 @@
     // Run synchronously but call callback asynchronously to match Node.js behavior
     process.nextTick(() => {
       try {
-        const result = ops.op_howth_exec_sync(command, options || {});
+        const result = ops.op_howth_exec_sync(command, spawnOpOptions(options));
 
         if (result.error) {
           const err = new Error(result.error);
@@ -8622,7 +9738,7 @@
     process.nextTick(() => {
       try {
         const result = ops.op_howth_spawn_sync(file, args || [], {
-          ...(options || {}),
+          ...spawnOpOptions(options),
           shell: false,
         });
 
@@ -8869,10 +9985,12 @@
       return stream;
     }
 
-    // Create streams immediately (they will wait for childId via promise)
-    const stdoutStream = createReadableStream(ops.op_howth_spawn_read_stdout);
-    const stderrStream = createReadableStream(ops.op_howth_spawn_read_stderr);
-    const stdinStream = createWritableStream();
+    // Create streams immediately (they will wait for childId via promise).
+    // Only piped fds get a stream; inherited/ignored ones are null as in Node.
+    const stdio = normalizeStdio(options.stdio);
+    const stdoutStream = stdio[1] === "pipe" ? createReadableStream(ops.op_howth_spawn_read_stdout) : null;
+    const stderrStream = stdio[2] === "pipe" ? createReadableStream(ops.op_howth_spawn_read_stderr) : null;
+    const stdinStream = stdio[0] === "pipe" ? createWritableStream() : null;
 
     // The ChildProcess object
     const child = {
@@ -8941,7 +10059,7 @@
     // Spawn the process asynchronously
     (async () => {
       try {
-        const result = await ops.op_howth_spawn_async(command, args, options);
+        const result = await ops.op_howth_spawn_async(command, args, { ...options, stdio });
 
         if (result.error) {
           const err = new Error(result.error);
@@ -9293,27 +10411,34 @@
     },
   };
 
+  /**
+   * Host information from the native side; re-read on every call since
+   * memory, load and uptime change over the life of the process.
+   */
+  function osInfo() {
+    try {
+      return ops.op_howth_os_info();
+    } catch {
+      return { cpus: [], availableParallelism: 1 };
+    }
+  }
+
   /**
    * Get CPU information.
    */
   function cpus() {
-    // Return a basic CPU info array
-    const numCpus = (typeof navigator !== 'undefined' && navigator.hardwareConcurrency) || 4;
-    const cpuInfo = [];
-    for (let i = 0; i < numCpus; i++) {
-      cpuInfo.push({
-        model: "Unknown CPU",
-        speed: 2400, // MHz (placeholder)
-        times: {
-          user: 0,
-          nice: 0,
-          sys: 0,
-          idle: 0,
-          irq: 0,
-        },
-      });
-    }
-    return cpuInfo;
+    return osInfo().cpus.map((cpu) => ({
+      model: cpu.model,
+      speed: cpu.speed,
+      times: { ...cpu.times },
+    }));
+  }
+
+  /**
+   * Get an estimate of the default amount of parallelism a program should use.
+   */
+  function availableParallelism() {
+    return osInfo().availableParallelism;
   }
 
   /**
@@ -9329,30 +10454,30 @@
    * Get the amount of free system memory in bytes.
    */
   function freemem() {
-    // Placeholder - would need native op
-    return 4 * 1024 * 1024 * 1024; // 4GB placeholder
+    return osInfo().freemem ?? 0;
   }
 
   /**
    * Get the home directory of the current user.
    */
   function homedir() {
-    return process.env.HOME || process.env.USERPROFILE || "/";
+    const fromEnv = process.platform === "win32" ? process.env.USERPROFILE : process.env.HOME;
+    return fromEnv || osInfo().user?.homedir || "";
   }
 
   /**
    * Get the hostname.
    */
   function hostname() {
-    return process.env.HOSTNAME || "localhost";
+    return osInfo().hostname ?? "localhost";
   }
 
   /**
    * Get system load averages.
    */
   function loadavg() {
-    // Placeholder - would need native op
-    return [0.0, 0.0, 0.0];
+    // Node.js reports zeros on Windows, where load averages don't exist.
+    return osInfo().loadavg ?? [0, 0, 0];
   }
 
   /**
@@ -9374,34 +10499,38 @@
    * Get the operating system release.
    */
   function release() {
-    // Placeholder - would need native op
-    return "0.0.0";
+    return osInfo().release ?? "";
   }
 
   /**
    * Get the operating system temporary directory.
    */
   function tmpdir() {
-    return (
-      process.env.TMPDIR ||
-      process.env.TMP ||
-      process.env.TEMP ||
-      (process.platform === "win32" ? "C:\\Windows\\Temp" : "/tmp")
-    );
-  }
+    let path;
+    if (process.platform === "win32") {
+      path = process.env.TEMP || process.env.TMP ||
+        `${process.env.SystemRoot || process.env.windir || "C:\\Windows"}\\temp`;
+      if (path.length > 1 && path.endsWith("\\") && !path.endsWith(":\\")) path = path.slice(0, -1);
+    } else {
+      path = process.env.TMPDIR || process.env.TMP || process.env.TEMP || "/tmp";
+      if (path.length > 1 && path.endsWith("/")) path = path.slice(0, -1);
+    }
+    return path;
+  }
 
   /**
    * Get the total amount of system memory in bytes.
    */
   function totalmem() {
-    // Placeholder - would need native op
-    return 8 * 1024 * 1024 * 1024; // 8GB placeholder
+    return osInfo().totalmem ?? 0;
   }
 
   /**
    * Get the operating system type.
    */
   function type() {
+    const sysname = osInfo().sysname;
+    if (sysname) return sysname;
     const p = process.platform;
     if (p === "darwin") return "Darwin";
     if (p === "win32") return "Windows_NT";
@@ -9414,35 +10543,41 @@
    * Get system uptime in seconds.
    */
   function uptime() {
-    // Placeholder - would need native op
-    return 0;
+    return osInfo().uptime ?? 0;
   }
 
   /**
    * Get user info.
    */
   function userInfo(options = {}) {
-    return {
-      uid: -1,
-      gid: -1,
-      username: process.env.USER || process.env.USERNAME || "unknown",
-      homedir: homedir(),
-      shell: process.env.SHELL || null,
-    };
+    const user = osInfo().user;
+    if (!user) {
+      const err = new Error("A system error occurred: uv_os_get_passwd returned ENOENT");
+      err.code = "ERR_SYSTEM_ERROR";
+      throw err;
+    }
+    const info = { ...user };
+    if (options?.encoding === "buffer") {
+      for (const key of ["username", "homedir", "shell"]) {
+        if (typeof info[key] === "string") info[key] = Buffer.from(info[key]);
+      }
+    }
+    return info;
   }
 
   /**
    * Get OS version.
    */
   function version() {
-    // Placeholder - would need native op
-    return "";
+    return osInfo().version ?? "";
   }
 
   /**
    * Get machine type.
    */
   function machine() {
+    const reported = osInfo().machine;
+    if (reported) return reported;
     // Map to common machine names
     const a = arch();
     if (a === "x64") return "x86_64";
@@ -9459,6 +10594,7 @@
 
   const osModule = {
     arch,
+    availableParallelism,
     constants: osConstants,
     cpus,
     devNull,
@@ -9621,7 +10757,7 @@
   const _originalClearTimeout = globalThis.clearTimeout;
   const _originalSetInterval = globalThis.setInterval;
   const _originalClearInterval = globalThis.clearInterval;
-  globalThis.__nativeSetTimeout = _originalSetTimeout;
+  globalThis.__nativeSetTimeout = realSetTimeout;
   globalThis.__nativeClearTimeout = _originalClearTimeout;
 
   // Wrap setTimeout to return Timeout object
@@ -10537,28 +11673,78 @@
    * Check if a file descriptor refers to a TTY.
    */
   function isatty(fd) {
-    // In a native runtime, we'd check the actual fd
-    // For now, assume stdin/stdout/stderr might be TTYs
-    return fd >= 0 && fd <= 2;
+    return Number.isInteger(fd) && fd >= 0 && fd <= 2147483647 && ops.op_howth_isatty(fd);
+  }
+
+  const kPendingRead = Symbol("kPendingRead");
+
+  /**
+   * process.stdin when it isn't a terminal. Chunks come from the stdin
+   * reader op; a paused stream unrefs its pending read so it doesn't keep
+   * the process alive (readline's close() pauses stdin).
+   */
+  class StdinStream extends Readable {
+    constructor() {
+      super();
+      this.fd = 0;
+      this[kPendingRead] = null;
+    }
+
+    _read() {
+      if (this[kPendingRead] || this._readableState.ended) return;
+      const promise = ops.op_howth_stdin_read();
+      this[kPendingRead] = promise;
+      if (this._readableState.flowing === false) core.unrefOpPromise(promise);
+      promise.then((bytes) => {
+        this[kPendingRead] = null;
+        const state = this._readableState;
+        this.push(bytes === null ? null : Buffer.from(bytes));
+        if (!state.flowing) {
+          this.emit("readable");
+        } else if (bytes !== null) {
+          this._read();
+        }
+      }, (err) => {
+        this[kPendingRead] = null;
+        this.destroy(err);
+      });
+    }
+
+    pause() {
+      super.pause();
+      if (this[kPendingRead]) core.unrefOpPromise(this[kPendingRead]);
+      this.emit("pause");
+      return this;
+    }
+
+    resume() {
+      super.resume();
+      if (this[kPendingRead]) core.refOpPromise(this[kPendingRead]);
+      this.emit("resume");
+      return this;
+    }
   }
 
   /**
-   * ReadStream class for TTY input.
+   * ReadStream class for TTY input. Only fd 0 can be read from.
    */
-  class ReadStream extends EventEmitter {
+  class ReadStream extends StdinStream {
     constructor(fd) {
       super();
       this.fd = fd;
-      this.isTTY = isatty(fd);
+      this.isTTY = true;
       this.isRaw = false;
     }
 
     setRawMode(mode) {
-      this.isRaw = mode;
+      ops.op_howth_tty_set_raw_mode(this.fd, !!mode);
+      this.isRaw = !!mode;
       return this;
     }
   }
 
+  createStdinStream = () => (isatty(0) ? new ReadStream(0) : new StdinStream());
+
   /**
    * WriteStream class for TTY output.
    */
@@ -10566,9 +11752,10 @@
     constructor(fd) {
       super();
       this.fd = fd;
-      this.isTTY = isatty(fd);
-      this.columns = 80;
-      this.rows = 24;
+      this.isTTY = true;
+      const size = ops.op_howth_tty_window_size(fd);
+      this.columns = size ? size[0] : 80;
+      this.rows = size ? size[1] : 24;
     }
 
     clearLine(dir, callback) {
@@ -10588,11 +11775,11 @@
     }
 
     getColorDepth() {
-      return 8;
+      return terminalColorDepth(this.fd);
     }
 
     hasColors(count = 16) {
-      return count <= 256;
+      return count <= 2 ** this.getColorDepth();
     }
 
     getWindowSize() {
@@ -14351,332 +15538,4010 @@
   // ═══════════════════════════════════════════════════════════════
   //  readline
   // ═══════════════════════════════════════════════════════════════
-  const readlineModule = {
-    createInterface(opts) {
-      const EventEmitter = globalThis.__howth_modules["events"];
-      const rl = Object.create(EventEmitter.prototype);
-      EventEmitter.call(rl);
-
-      let buffer = "";
-      let closed = false;
-      const lineQueue = [];
-      let lineResolve = null;
-      let done = false;
-
-      function pushLine(line) {
-        rl.emit("line", line);
-        if (lineResolve) {
-          const resolve = lineResolve;
-          lineResolve = null;
-          resolve({ value: line, done: false });
+  const kHistorySize = 30;
+  const kMinCrlfDelay = 100;
+  const kKeypressDecoder = Symbol("kKeypressDecoder");
+  const lineEnding = /\r?\n|\r(?!\n)/;
+  const lineEndingGlobal = /\r?\n|\r(?!\n)/g;
+
+  // Key names for CSI (ESC [) and SS3 (ESC O) sequences, keyed by the code
+  // with any modifier parameter removed
+  const escapeKeyNames = {
+    "[A": "up", "[B": "down", "[C": "right", "[D": "left", "[E": "clear", "[F": "end", "[H": "home",
+    "OA": "up", "OB": "down", "OC": "right", "OD": "left", "OE": "clear", "OF": "end", "OH": "home",
+    "[1~": "home", "[2~": "insert", "[3~": "delete", "[4~": "end",
+    "[5~": "pageup", "[6~": "pagedown", "[7~": "home", "[8~": "end",
+    "OP": "f1", "OQ": "f2", "OR": "f3", "OS": "f4",
+    "[11~": "f1", "[12~": "f2", "[13~": "f3", "[14~": "f4", "[15~": "f5",
+    "[17~": "f6", "[18~": "f7", "[19~": "f8", "[20~": "f9", "[21~": "f10",
+    "[23~": "f11", "[24~": "f12", "[Z": "tab",
+    "[200~": "paste-start", "[201~": "paste-end",
+  };
+
+  function useAfterClose() {
+    const err = new Error("readline was closed");
+    err.code = "ERR_USE_AFTER_CLOSE";
+    return err;
+  }
+
+  /**
+   * Fill in name/ctrl/shift for a single character, as Node's emitKeys does.
+   */
+  function describeChar(ch, key) {
+    if (ch === "\r") {
+      key.name = "return";
+    } else if (ch === "\n") {
+      key.name = "enter";
+    } else if (ch === "\t") {
+      key.name = "tab";
+    } else if (ch === "\b" || ch === "\x7f") {
+      key.name = "backspace";
+    } else if (ch === " ") {
+      key.name = "space";
+    } else if (ch <= "\x1a") {
+      // Ctrl+letter
+      key.name = String.fromCharCode(ch.charCodeAt(0) + 96);
+      key.ctrl = true;
+    } else if (/^[0-9A-Za-z]$/.test(ch)) {
+      key.name = ch.toLowerCase();
+      key.shift = /^[A-Z]$/.test(ch);
+    }
+  }
+
+  /**
+   * Split terminal input into [sequence, key] pairs. The sequence is
+   * undefined for escape sequences, which never insert text.
+   */
+  function decodeKeys(str) {
+    const keys = [];
+    let i = 0;
+    while (i < str.length) {
+      const start = i;
+      const key = { sequence: "", name: undefined, ctrl: false, meta: false, shift: false };
+      let ch = String.fromCodePoint(str.codePointAt(i));
+      i += ch.length;
+      let escaped = false;
+
+      if (ch === "\x1b" && i < str.length) {
+        escaped = true;
+        ch = str[i];
+        const match = (ch === "[" || ch === "O")
+          ? /^(\d*)(?:;(\d+))?([~A-Za-z])/.exec(str.slice(i + 1))
+          : null;
+        if (match) {
+          const [sequence, number, modifier, final] = match;
+          i += 1 + sequence.length;
+          key.code = ch + (final === "~" ? `${number}~` : final);
+          key.name = escapeKeyNames[key.code];
+          const mods = (parseInt(modifier || "1", 10) || 1) - 1;
+          key.shift = !!(mods & 1) || key.code === "[Z";
+          key.meta = !!(mods & 10);
+          key.ctrl = !!(mods & 4);
         } else {
-          lineQueue.push(line);
-        }
-      }
-
-      function finish() {
-        if (closed) return;
-        closed = true;
-        // Flush remaining buffer (no trailing newline)
-        if (buffer.length > 0) {
-          pushLine(buffer);
-          buffer = "";
-        }
-        done = true;
-        rl.emit("close");
-        if (lineResolve) {
-          const resolve = lineResolve;
-          lineResolve = null;
-          resolve({ value: undefined, done: true });
-        }
-      }
-
-      const input = opts && (opts.input || opts);
-      if (input && typeof input.on === "function") {
-        const crlfDelay = opts && opts.crlfDelay;
-        input.on("data", (chunk) => {
-          if (closed) return;
-          buffer += (typeof chunk === "string" ? chunk : chunk.toString("utf8"));
-          let idx;
-          while ((idx = buffer.indexOf("\n")) !== -1) {
-            let line = buffer.slice(0, idx);
-            // Strip trailing \r for CRLF
-            if (line.length > 0 && line.charCodeAt(line.length - 1) === 13) {
-              line = line.slice(0, -1);
-            }
-            buffer = buffer.slice(idx + 1);
-            pushLine(line);
-          }
-        });
-        input.on("end", finish);
-        input.on("error", (err) => { rl.emit("error", err); finish(); });
-        // Ensure stream starts flowing
-        if (typeof input.resume === "function") input.resume();
+          // ESC followed by a character is Meta+character
+          ch = String.fromCodePoint(str.codePointAt(i));
+          i += ch.length;
+          describeChar(ch, key);
+          key.meta = true;
+        }
+      } else if (ch === "\x1b") {
+        key.name = "escape";
+      } else {
+        describeChar(ch, key);
       }
 
-      const output = opts && opts.output;
-      let currentPrompt = opts && opts.prompt || "> ";
-      let paused = false;
+      key.sequence = str.slice(start, i);
+      keys.push([escaped && key.name !== undefined ? undefined : key.sequence, key]);
+    }
+    return keys;
+  }
 
-      // question() - prompt user and read line from stdin
-      rl.question = (query, options, cb) => {
-        // Handle (query, callback) signature
-        if (typeof options === "function") {
-          cb = options;
-          options = {};
-        }
-        options = options || {};
+  /**
+   * Make `stream` emit 'keypress' events for the data it reads. Decoding only
+   * runs while someone listens for 'keypress'.
+   */
+  function emitKeypressEvents(stream, iface = {}) {
+    if (stream[kKeypressDecoder]) return;
+    stream[kKeypressDecoder] = new StringDecoder("utf8");
 
-        // Write the query/prompt to output
-        if (output && typeof output.write === "function") {
-          output.write(query);
-        } else if (typeof query === "string") {
-          // Fallback to console
-          globalThis.process.stdout.write(query);
+    function onData(input) {
+      if (stream.listenerCount("keypress") > 0) {
+        const string = typeof input === "string" ? input : stream[kKeypressDecoder].write(input);
+        for (const [sequence, key] of decodeKeys(string)) {
+          stream.emit("keypress", sequence, key);
         }
+      } else {
+        // Nobody is listening any more
+        stream.removeListener("data", onData);
+        stream.on("newListener", onNewListener);
+      }
+    }
 
-        // If we have an input stream providing data, wait for next line
-        if (input && typeof input.on === "function" && !ops.op_howth_stdin_is_tty()) {
-          // Use the async iterator / line queue mechanism
-          const waitForLine = () => {
-            if (lineQueue.length > 0) {
-              const line = lineQueue.shift();
-              if (cb) cb(line);
-              return;
-            }
-            if (done) {
-              if (cb) cb("");
-              return;
-            }
-            // Set up one-time listener for next line
-            const onLine = (line) => {
-              rl.removeListener("line", onLine);
-              if (cb) cb(line);
-            };
-            rl.once("line", onLine);
-          };
-          waitForLine();
-          return;
-        }
+    function onNewListener(event) {
+      if (event === "keypress") {
+        stream.on("data", onData);
+        stream.removeListener("newListener", onNewListener);
+      }
+    }
 
-        // Use native stdin reading for interactive TTY input
-        (async () => {
-          try {
-            const line = await ops.op_howth_stdin_read_line();
-            if (cb) cb(line || "");
-          } catch (e) {
-            if (cb) cb("");
-          }
-        })();
-      };
+    if (stream.listenerCount("keypress") > 0) {
+      stream.on("data", onData);
+    } else {
+      stream.on("newListener", onNewListener);
+    }
+  }
 
-      rl.close = () => { finish(); };
+  // Escape sequences shared by the cursor functions and promises.Readline
+  function cursorToSequence(x, y) {
+    if (typeof y === "number") return `\x1b[${y + 1};${x + 1}H`;
+    return `\x1b[${x + 1}G`;
+  }
 
-      rl.prompt = (preserveCursor) => {
-        if (output && typeof output.write === "function") {
-          output.write(currentPrompt);
-        } else {
-          globalThis.process.stdout.write(currentPrompt);
-        }
-      };
+  function moveCursorSequence(dx, dy) {
+    let seq = "";
+    if (dx < 0) seq += `\x1b[${-dx}D`;
+    else if (dx > 0) seq += `\x1b[${dx}C`;
+    if (dy < 0) seq += `\x1b[${-dy}A`;
+    else if (dy > 0) seq += `\x1b[${dy}B`;
+    return seq;
+  }
 
-      rl.write = (data, key) => {
-        // Write data to output if available
-        if (data && output && typeof output.write === "function") {
-          output.write(data);
-        }
-      };
+  function clearLineSequence(dir) {
+    // dir: -1 = before cursor, 0 = entire line, 1 = after cursor
+    return `\x1b[${dir < 0 ? 1 : dir > 0 ? 0 : 2}K`;
+  }
 
-      rl.pause = () => { paused = true; return rl; };
-      rl.resume = () => { paused = false; return rl; };
-      rl.setPrompt = (prompt) => { currentPrompt = prompt; };
-      rl[Symbol.asyncIterator] = function() {
-        return {
-          next() {
-            if (lineQueue.length > 0) {
-              return Promise.resolve({ value: lineQueue.shift(), done: false });
-            }
-            if (done) {
-              return Promise.resolve({ value: undefined, done: true });
-            }
-            return new Promise((resolve) => { lineResolve = resolve; });
-          },
-          return() {
-            rl.close();
-            return Promise.resolve({ value: undefined, done: true });
-          },
-        };
+  const clearScreenDownSequence = "\x1b[0J";
+
+  function charLengthLeft(str, i) {
+    if (i <= 0) return 0;
+    return i > 1 && str.codePointAt(i - 2) >= 0x10000 ? 2 : 1;
+  }
+
+  function charLengthAt(str, i) {
+    if (str.length <= i) return 1;
+    return str.codePointAt(i) >= 0x10000 ? 2 : 1;
+  }
+
+  function commonPrefix(strings) {
+    if (strings.length === 0) return "";
+    let prefix = strings[0];
+    for (const s of strings) {
+      let i = 0;
+      while (i < prefix.length && i < s.length && prefix[i] === s[i]) i++;
+      prefix = prefix.slice(0, i);
+    }
+    return prefix;
+  }
+
+  /**
+   * readline.Interface. Without a terminal, lines are split from the input
+   * stream's data. With one, the input is put in raw mode and the line is
+   * edited here (cursor keys, word deletion, history, tab completion).
+   */
+  class Interface extends EventEmitter {
+    constructor(input, output, completer, terminal) {
+      super();
+      let history;
+      let historySize;
+      let removeHistoryDuplicates = false;
+      let crlfDelay;
+      let prompt = "> ";
+      let signal;
+      let tabSize;
+
+      if (input && input.input) {
+        // createInterface({ input, output, ... })
+        const options = input;
+        output = options.output;
+        completer = options.completer;
+        terminal = options.terminal;
+        history = options.history;
+        historySize = options.historySize;
+        removeHistoryDuplicates = options.removeHistoryDuplicates === true;
+        crlfDelay = options.crlfDelay;
+        tabSize = options.tabSize;
+        signal = options.signal;
+        if (options.prompt !== undefined) prompt = options.prompt;
+        input = options.input;
+      }
+
+      if (completer !== undefined && typeof completer !== "function") {
+        const err = new TypeError(`The argument 'completer' is invalid. Received ${completer}`);
+        err.code = "ERR_INVALID_ARG_VALUE";
+        throw err;
+      }
+      if (historySize === undefined) historySize = kHistorySize;
+      if (typeof historySize !== "number" || Number.isNaN(historySize) || historySize < 0) {
+        const err = new RangeError(`The argument 'historySize' is invalid. Received ${historySize}`);
+        err.code = "ERR_INVALID_ARG_VALUE";
+        throw err;
+      }
+      if (history !== undefined && !Array.isArray(history)) {
+        const err = new TypeError('The "history" argument must be an instance of Array');
+        err.code = "ERR_INVALID_ARG_TYPE";
+        throw err;
+      }
+      validateAbortSignal(signal);
+
+      // Default to terminal mode when writing to a TTY
+      if (terminal === undefined && output !== undefined && output !== null) {
+        terminal = !!output.isTTY;
+      }
+
+      this.input = input;
+      this.output = output;
+      this.completer = completer;
+      this.terminal = !!terminal;
+      this.line = "";
+      this.cursor = 0;
+      this.history = history ? [...history] : [];
+      this.historyIndex = -1;
+      this.historySize = historySize;
+      this.removeHistoryDuplicates = removeHistoryDuplicates;
+      this.crlfDelay = crlfDelay ? Math.max(kMinCrlfDelay, crlfDelay) : kMinCrlfDelay;
+      this.tabSize = tabSize || 8;
+      this.closed = false;
+      this.paused = false;
+      this.prevRows = 0;
+      this._prompt = prompt;
+      this._oldPrompt = prompt;
+      this._questionCallback = null;
+      this._lineBuffer = null;
+      this._sawReturnAt = 0;
+      this._savedLine = "";
+      this._previousKey = null;
+      this._decoder = new StringDecoder("utf8");
+
+      this._onData = (data) => this._normalWrite(data);
+      this._onEnd = () => {
+        if (typeof this._lineBuffer === "string" && this._lineBuffer.length > 0) {
+          this.emit("line", this._lineBuffer);
+        }
+        this.close();
       };
-      return rl;
-    },
-    // Cursor manipulation functions using ANSI escape codes
-    cursorTo(stream, x, y, callback) {
-      if (typeof y === "function") {
-        callback = y;
-        y = undefined;
+      this._onKeypress = (s, key) => this._ttyWrite(s, key);
+      this._onTermEnd = () => {
+        if (this.line.length > 0) this.emit("line", this.line);
+        this.close();
+      };
+      this._onError = (err) => this.emit("error", err);
+
+      if (input) {
+        input.on("error", this._onError);
+        if (this.terminal) {
+          emitKeypressEvents(input, this);
+          input.on("keypress", this._onKeypress);
+          input.on("end", this._onTermEnd);
+          this._setRawMode(true);
+        } else {
+          input.on("data", this._onData);
+          input.on("end", this._onEnd);
+        }
       }
-      if (stream && typeof stream.write === "function") {
-        if (typeof y === "number") {
-          // Move to absolute position (x, y) - ESC[y;xH
-          stream.write(`\x1b[${y + 1};${x + 1}H`);
-        } else if (typeof x === "number") {
-          // Move to column x - ESC[xG
-          stream.write(`\x1b[${x + 1}G`);
+
+      if (signal) {
+        if (signal.aborted) {
+          process.nextTick(() => this.close());
+        } else {
+          signal.addEventListener("abort", () => this.close(), { once: true });
         }
       }
-      if (typeof callback === "function") callback();
-      return true;
-    },
 
-    moveCursor(stream, dx, dy, callback) {
-      if (stream && typeof stream.write === "function") {
-        let seq = "";
-        if (dx < 0) seq += `\x1b[${-dx}D`; // Left
-        else if (dx > 0) seq += `\x1b[${dx}C`; // Right
-        if (dy < 0) seq += `\x1b[${-dy}A`; // Up
-        else if (dy > 0) seq += `\x1b[${dy}B`; // Down
-        if (seq) stream.write(seq);
+      if (input && typeof input.resume === "function") input.resume();
+    }
+
+    get columns() {
+      return this.output && this.output.columns ? this.output.columns : Infinity;
+    }
+
+    setPrompt(prompt) {
+      this._prompt = prompt;
+    }
+
+    getPrompt() {
+      return this._prompt;
+    }
+
+    prompt(preserveCursor) {
+      if (this.paused) this.resume();
+      if (this.terminal) {
+        if (!preserveCursor) this.cursor = 0;
+        this._refreshLine();
+      } else {
+        this._writeToOutput(this._prompt);
       }
-      if (typeof callback === "function") callback();
-      return true;
-    },
+    }
 
-    clearLine(stream, dir, callback) {
-      if (typeof dir === "function") {
-        callback = dir;
-        dir = 0;
+    question(query, options, cb) {
+      if (typeof options === "function") {
+        cb = options;
+        options = {};
       }
-      if (stream && typeof stream.write === "function") {
-        // dir: -1 = before cursor, 0 = entire line, 1 = after cursor
-        const code = dir === -1 ? 1 : dir === 1 ? 0 : 2;
-        stream.write(`\x1b[${code}K`);
+      if (this.closed) throw useAfterClose();
+      const signal = options && options.signal;
+      if (signal) {
+        validateAbortSignal(signal);
+        if (signal.aborted) return;
+        const onAbort = () => this._questionCancel();
+        signal.addEventListener("abort", onAbort, { once: true });
+        const callback = cb;
+        cb = (answer) => {
+          signal.removeEventListener("abort", onAbort);
+          callback(answer);
+        };
       }
-      if (typeof callback === "function") callback();
-      return true;
-    },
+      if (typeof cb === "function") this._question(query, cb);
+    }
 
-    clearScreenDown(stream, callback) {
-      if (stream && typeof stream.write === "function") {
-        stream.write("\x1b[0J"); // Clear from cursor to end of screen
+    _question(query, cb) {
+      if (this._questionCallback) {
+        this.prompt();
+        return;
       }
-      if (typeof callback === "function") callback();
-      return true;
-    },
+      this._oldPrompt = this._prompt;
+      this.setPrompt(query);
+      this._questionCallback = cb;
+      this.prompt();
+    }
 
-    // Additional commonly used function
-    emitKeypressEvents(stream, interface_) {
-      // This sets up keypress event handling on a stream
-      // For now, just ensure stream is in raw mode if possible
-      if (stream && stream.isTTY && typeof stream.setRawMode === "function") {
-        // Raw mode setup would go here
+    _questionCancel() {
+      if (this._questionCallback) {
+        this._questionCallback = null;
+        this.setPrompt(this._oldPrompt);
+        this.clearLine();
       }
-    },
-  };
-  // readline/promises - creates interfaces where question() returns a Promise
-  readlineModule.promises = {
-    createInterface(opts) {
-      const rl = readlineModule.createInterface(opts);
+    }
 
-      // Wrap the callback-based question with a promise-based one
-      const originalQuestion = rl.question.bind(rl);
-      rl.question = (query, options) => {
-        return new Promise((resolve, reject) => {
-          try {
-            originalQuestion(query, options, (answer) => {
-              resolve(answer);
-            });
-          } catch (e) {
-            reject(e);
-          }
-        });
-      };
+    _onLine(line) {
+      if (this._questionCallback) {
+        const cb = this._questionCallback;
+        this._questionCallback = null;
+        this.setPrompt(this._oldPrompt);
+        cb(line);
+      } else {
+        this.emit("line", line);
+      }
+    }
 
-      return rl;
-    },
-  };
-  globalThis.__howth_modules["node:readline"] = readlineModule;
-  globalThis.__howth_modules["readline"] = readlineModule;
-  globalThis.__howth_modules["node:readline/promises"] = readlineModule.promises;
-  globalThis.__howth_modules["readline/promises"] = readlineModule.promises;
+    _writeToOutput(s) {
+      if (this.output !== null && this.output !== undefined) this.output.write(s);
+    }
 
-  return globalThis.__howth_modules["node:events"];
-  }); // end heavy lazy block
+    _setRawMode(mode) {
+      const wasRaw = this.input.isRaw;
+      if (typeof this.input.setRawMode === "function") this.input.setRawMode(mode);
+      return wasRaw;
+    }
 
-  // Set up unhandled promise rejection handler using Deno core API
-  core.setUnhandledPromiseRejectionHandler((promise, reason) => {
-    // Check if process has handlers for 'unhandledRejection'
-    const listeners = processListeners.get('unhandledRejection');
-    if (listeners && listeners.length > 0) {
-      for (const listener of [...listeners]) {
-        listener(reason, promise);
+    pause() {
+      if (this.paused) return this;
+      if (this.input && typeof this.input.pause === "function") this.input.pause();
+      this.paused = true;
+      this.emit("pause");
+      return this;
+    }
+
+    resume() {
+      if (!this.paused) return this;
+      if (this.input && typeof this.input.resume === "function") this.input.resume();
+      this.paused = false;
+      this.emit("resume");
+      return this;
+    }
+
+    write(data, key) {
+      if (this.closed) throw useAfterClose();
+      if (this.paused) this.resume();
+      if (this.terminal) {
+        this._ttyWrite(data, key);
+      } else {
+        this._normalWrite(data);
       }
-      return true; // handled
     }
-    // Log and swallow the error to prevent crash (Node.js behavior: warn but don't crash by default)
-    if (reason instanceof Error) {
-      console.error('[howth] UnhandledPromiseRejection:', reason.stack || reason.message);
-    } else {
-      console.error('[howth] UnhandledPromiseRejection:', reason);
+
+    close() {
+      if (this.closed) return;
+      this.pause();
+      if (this.terminal) this._setRawMode(false);
+      this.closed = true;
+      if (this.input) {
+        this.input.removeListener("error", this._onError);
+        this.input.removeListener("data", this._onData);
+        this.input.removeListener("end", this._onEnd);
+        this.input.removeListener("keypress", this._onKeypress);
+        this.input.removeListener("end", this._onTermEnd);
+      }
+      this.emit("close");
     }
-    return true; // prevent crash
-  });
 
-  // Set up exception callback for better error reporting
-  core.setReportExceptionCallback((err) => {
-    const listeners = processListeners.get('uncaughtException');
-    if (listeners && listeners.length > 0) {
-      for (const listener of [...listeners]) {
-        listener(err);
+    getCursorPos() {
+      const columns = this.columns;
+      const width = stripVTControlCharacters(this._prompt + this.line.slice(0, this.cursor)).length;
+      if (columns === Infinity) return { rows: 0, cols: width };
+      return { rows: Math.floor(width / columns), cols: width % columns };
+    }
+
+    clearLine() {
+      this._moveCursor(+Infinity);
+      this._writeToOutput("\r\n");
+      this.line = "";
+      this.cursor = 0;
+      this.prevRows = 0;
+    }
+
+    // Lines from a non-terminal stream: split on \n, \r\n or a lone \r
+    _normalWrite(data) {
+      if (data === undefined || data === null) return;
+      let string = typeof data === "string" ? data : this._decoder.write(data);
+      if (this._sawReturnAt && Date.now() - this._sawReturnAt <= this.crlfDelay) {
+        if (string.codePointAt(0) === 10) string = string.slice(1);
+        this._sawReturnAt = 0;
+      }
+      const containsEnding = lineEnding.test(string);
+      if (this._lineBuffer) {
+        string = this._lineBuffer + string;
+        this._lineBuffer = null;
+      }
+      if (containsEnding) {
+        this._sawReturnAt = string.endsWith("\r") ? Date.now() : 0;
+        const lines = string.split(lineEndingGlobal);
+        this._lineBuffer = lines.pop();
+        for (const line of lines) this._onLine(line);
+      } else if (string) {
+        this._lineBuffer = string;
+      }
+    }
+
+    _refreshLine() {
+      const line = this._prompt + this.line;
+      const columns = this.columns;
+      const width = stripVTControlCharacters(line).length;
+      const lineRows = columns === Infinity ? 0 : Math.floor(width / columns);
+      const cursorPos = this.getCursorPos();
+
+      let seq = "";
+      if (this.prevRows > 0) seq += moveCursorSequence(0, -this.prevRows);
+      seq += cursorToSequence(0) + clearScreenDownSequence + line;
+      // A full last row leaves the cursor past the edge; wrap it explicitly
+      if (columns !== Infinity && width > 0 && width % columns === 0) seq += "\r\n";
+      seq += cursorToSequence(cursorPos.cols);
+      const rowsBelow = lineRows - cursorPos.rows;
+      if (rowsBelow > 0) seq += moveCursorSequence(0, -rowsBelow);
+      this._writeToOutput(seq);
+      this.prevRows = cursorPos.rows;
+    }
+
+    _insertString(c) {
+      if (!c) return;
+      if (this.cursor < this.line.length) {
+        this.line = this.line.slice(0, this.cursor) + c + this.line.slice(this.cursor);
+        this.cursor += c.length;
+        this._refreshLine();
+      } else {
+        this.line += c;
+        this.cursor += c.length;
+        if (this.getCursorPos().cols === 0) {
+          this._refreshLine();
+        } else {
+          this._writeToOutput(c);
+        }
       }
-      return;
     }
-    if (err instanceof Error) {
-      console.error('[howth] Uncaught exception:', err.stack || err.message);
-    } else {
-      console.error('[howth] Uncaught exception:', err);
+
+    _moveCursor(dx) {
+      if (dx === 0) return;
+      const oldPos = this.getCursorPos();
+      this.cursor = Math.max(0, Math.min(this.line.length, this.cursor + dx));
+      const newPos = this.getCursorPos();
+      if (oldPos.rows === newPos.rows) {
+        const seq = moveCursorSequence(newPos.cols - oldPos.cols, 0);
+        if (seq) this._writeToOutput(seq);
+      } else {
+        this._refreshLine();
+      }
     }
-  });
 
-  // node:test module — lightweight test runner for howth's native V8 executor.
-  // Implements test(), describe(), it(), before(), after(), beforeEach(), afterEach().
-  // Results are collected in globalThis.__howth_test_results after __howth_run_tests().
-  (function() {
-    const suiteStack = []; // stack of { name, before, after, beforeEach, afterEach, children }
-    let rootSuite = { name: '<root>', before: [], after: [], beforeEach: [], afterEach: [], children: [] };
-    let currentSuite = rootSuite;
-    let hasOnly = false; // tracks whether any .only() was registered
+    _wordLeft() {
+      if (this.cursor > 0) {
+        const reversed = [...this.line.slice(0, this.cursor)].reverse().join("");
+        const match = reversed.match(/^\s*(?:[^\w\s]+|\w+)?/);
+        this._moveCursor(-match[0].length);
+      }
+    }
 
-    function pushSuite(name) {
-      const suite = { name, before: [], after: [], beforeEach: [], afterEach: [], children: [] };
-      currentSuite.children.push({ type: 'suite', suite });
-      suiteStack.push(currentSuite);
-      currentSuite = suite;
-      return suite;
+    _wordRight() {
+      if (this.cursor < this.line.length) {
+        const match = this.line.slice(this.cursor).match(/^(?:\s+|[^\w\s]+|\w+)\s*/);
+        this._moveCursor(match[0].length);
+      }
     }
 
-    function popSuite() {
-      currentSuite = suiteStack.pop() || rootSuite;
+    _deleteLeft() {
+      if (this.cursor > 0 && this.line.length > 0) {
+        const n = charLengthLeft(this.line, this.cursor);
+        this.line = this.line.slice(0, this.cursor - n) + this.line.slice(this.cursor);
+        this.cursor -= n;
+        this._refreshLine();
+      }
     }
 
-    // test(name, [options], fn)
-    function test(name, optionsOrFn, maybeFn) {
-      let fn, options;
-      if (typeof optionsOrFn === 'function') {
+    _deleteRight() {
+      if (this.cursor < this.line.length) {
+        const n = charLengthAt(this.line, this.cursor);
+        this.line = this.line.slice(0, this.cursor) + this.line.slice(this.cursor + n);
+        this._refreshLine();
+      }
+    }
+
+    _deleteWordLeft() {
+      if (this.cursor > 0) {
+        let leading = this.line.slice(0, this.cursor);
+        const reversed = [...leading].reverse().join("");
+        const match = reversed.match(/^\s*(?:[^\w\s]+|\w+)?/);
+        leading = leading.slice(0, leading.length - match[0].length);
+        this.line = leading + this.line.slice(this.cursor);
+        this.cursor = leading.length;
+        this._refreshLine();
+      }
+    }
+
+    _deleteWordRight() {
+      if (this.cursor < this.line.length) {
+        const trailing = this.line.slice(this.cursor);
+        const match = trailing.match(/^(?:\s+|\W+|\w+)\s*/);
+        this.line = this.line.slice(0, this.cursor) + trailing.slice(match[0].length);
+        this._refreshLine();
+      }
+    }
+
+    _deleteLineLeft() {
+      this.line = this.line.slice(this.cursor);
+      this.cursor = 0;
+      this._refreshLine();
+    }
+
+    _deleteLineRight() {
+      this.line = this.line.slice(0, this.cursor);
+      this._refreshLine();
+    }
+
+    // Record the current line in history (most recent first)
+    _addHistory() {
+      if (this.line.length === 0) return "";
+      if (this.historySize === 0) return this.line;
+      if (this.line.trim().length === 0) return this.line;
+
+      if (this.history.length === 0 || this.history[0] !== this.line) {
+        if (this.removeHistoryDuplicates) {
+          const dupIndex = this.history.indexOf(this.line);
+          if (dupIndex !== -1) this.history.splice(dupIndex, 1);
+        }
+        this.history.unshift(this.line);
+        if (this.history.length > this.historySize) this.history.pop();
+      }
+      this.historyIndex = -1;
+      const line = this.history[0];
+      this.emit("history", this.history);
+      return line;
+    }
+
+    _historyPrev() {
+      if (this.historyIndex + 1 < this.history.length) {
+        if (this.historyIndex === -1) this._savedLine = this.line;
+        this.historyIndex++;
+        this.line = this.history[this.historyIndex];
+        this.cursor = this.line.length;
+        this._refreshLine();
+      }
+    }
+
+    _historyNext() {
+      if (this.historyIndex >= 0) {
+        this.historyIndex--;
+        this.line = this.historyIndex === -1 ? this._savedLine : this.history[this.historyIndex];
+        this.cursor = this.line.length;
+        this._refreshLine();
+      }
+    }
+
+    _line() {
+      const line = this._addHistory();
+      this.clearLine();
+      this._onLine(line);
+    }
+
+    _tabComplete(lastKeypressWasTab) {
+      this.pause();
+      const done = (err, value) => {
+        this.resume();
+        if (err) {
+          this._writeToOutput(`\r\nTab completion error: ${err && err.message ? err.message : err}\r\n`);
+          this.prevRows = 0;
+          this._refreshLine();
+          return;
+        }
+        const [completions, completeOn = ""] = value || [];
+        if (!completions || completions.length === 0) return;
+        const candidates = completions.filter((c) => c);
+        const prefix = commonPrefix(candidates);
+        if (prefix.length > completeOn.length) {
+          this._insertString(prefix.slice(completeOn.length));
+          return;
+        }
+        if (!lastKeypressWasTab || candidates.length < 2) return;
+        // Second tab with nothing left to insert: list the candidates
+        this._writeToOutput(`\r\n${candidates.join("  ")}\r\n`);
+        this.prevRows = 0;
+        this._refreshLine();
+      };
+
+      const string = this.line.slice(0, this.cursor);
+      if (this.completer.length === 2) {
+        this.completer(string, done);
+        return;
+      }
+      try {
+        const value = this.completer(string);
+        if (value && typeof value.then === "function") {
+          value.then((v) => done(null, v), done);
+        } else {
+          done(null, value);
+        }
+      } catch (err) {
+        done(err);
+      }
+    }
+
+    // Handle one keypress in terminal mode
+    _ttyWrite(s, key) {
+      const previousKey = this._previousKey;
+      key = key || {};
+      this._previousKey = key;
+
+      if (key.name === "escape") return;
+
+      if (key.ctrl && key.shift) {
+        if (key.name === "backspace") this._deleteLineLeft();
+        else if (key.name === "delete") this._deleteLineRight();
+      } else if (key.ctrl) {
+        switch (key.name) {
+          case "c":
+            if (this.listenerCount("SIGINT") > 0) {
+              this.emit("SIGINT");
+            } else {
+              this.close();
+            }
+            break;
+          case "h":
+            this._deleteLeft();
+            break;
+          case "d":
+            if (this.cursor === 0 && this.line.length === 0) {
+              this.close();
+            } else {
+              this._deleteRight();
+            }
+            break;
+          case "u":
+            this._deleteLineLeft();
+            break;
+          case "k":
+            this._deleteLineRight();
+            break;
+          case "a":
+            this._moveCursor(-Infinity);
+            break;
+          case "e":
+            this._moveCursor(+Infinity);
+            break;
+          case "b":
+            this._moveCursor(-charLengthLeft(this.line, this.cursor));
+            break;
+          case "f":
+            this._moveCursor(+charLengthAt(this.line, this.cursor));
+            break;
+          case "l":
+            this._writeToOutput(cursorToSequence(0, 0) + clearScreenDownSequence);
+            this.prevRows = 0;
+            this._refreshLine();
+            break;
+          case "n":
+            this._historyNext();
+            break;
+          case "p":
+            this._historyPrev();
+            break;
+          case "w":
+          case "backspace":
+            this._deleteWordLeft();
+            break;
+          case "delete":
+            this._deleteWordRight();
+            break;
+          case "left":
+            this._wordLeft();
+            break;
+          case "right":
+            this._wordRight();
+            break;
+        }
+      } else if (key.meta) {
+        switch (key.name) {
+          case "b":
+            this._wordLeft();
+            break;
+          case "f":
+            this._wordRight();
+            break;
+          case "d":
+          case "delete":
+            this._deleteWordRight();
+            break;
+          case "backspace":
+            this._deleteWordLeft();
+            break;
+        }
+      } else {
+        // \r\n arrives as return + enter; only the first ends the line
+        if (this._sawReturnAt && key.name !== "enter") this._sawReturnAt = 0;
+
+        switch (key.name) {
+          case "return":
+            this._sawReturnAt = Date.now();
+            this._line();
+            break;
+          case "enter":
+            if (this._sawReturnAt === 0 || Date.now() - this._sawReturnAt > this.crlfDelay) {
+              this._line();
+            }
+            this._sawReturnAt = 0;
+            break;
+          case "backspace":
+            this._deleteLeft();
+            break;
+          case "delete":
+            this._deleteRight();
+            break;
+          case "left":
+            this._moveCursor(-charLengthLeft(this.line, this.cursor));
+            break;
+          case "right":
+            this._moveCursor(+charLengthAt(this.line, this.cursor));
+            break;
+          case "home":
+            this._moveCursor(-Infinity);
+            break;
+          case "end":
+            this._moveCursor(+Infinity);
+            break;
+          case "up":
+            this._historyPrev();
+            break;
+          case "down":
+            this._historyNext();
+            break;
+          case "tab":
+            if (typeof this.completer === "function") {
+              this._tabComplete(previousKey !== null && previousKey.name === "tab");
+              break;
+            }
+          // falls through
+          default:
+            if (typeof s === "string" && s) {
+              // Pasted text may contain several lines
+              const lines = s.split(/\r\n|\n|\r/);
+              for (let i = 0; i < lines.length; i++) {
+                if (i > 0) this._line();
+                this._insertString(lines[i]);
+              }
+            }
+        }
+      }
+    }
+
+    [Symbol.asyncIterator]() {
+      if (this._lineIterator === undefined) {
+        const queue = [];
+        const waiting = [];
+        let finished = this.closed;
+        this.on("line", (line) => {
+          if (waiting.length > 0) waiting.shift()({ value: line, done: false });
+          else queue.push(line);
+        });
+        this.on("close", () => {
+          finished = true;
+          for (const resolve of waiting.splice(0)) resolve({ value: undefined, done: true });
+        });
+        this._lineIterator = {
+          next: () => {
+            if (queue.length > 0) return Promise.resolve({ value: queue.shift(), done: false });
+            if (finished) return Promise.resolve({ value: undefined, done: true });
+            return new Promise((resolve) => waiting.push(resolve));
+          },
+          return: () => {
+            this.close();
+            return Promise.resolve({ value: undefined, done: true });
+          },
+          [Symbol.asyncIterator]() {
+            return this;
+          },
+        };
+      }
+      return this._lineIterator;
+    }
+  }
+
+  const readlineModule = {
+    Interface,
+    createInterface(input, output, completer, terminal) {
+      return new Interface(input, output, completer, terminal);
+    },
+    // Cursor manipulation functions using ANSI escape codes
+    cursorTo(stream, x, y, callback) {
+      if (typeof y === "function") {
+        callback = y;
+        y = undefined;
+      }
+      if (stream && typeof stream.write === "function" && typeof x === "number") {
+        stream.write(cursorToSequence(x, y));
+      }
+      if (typeof callback === "function") callback();
+      return true;
+    },
+
+    moveCursor(stream, dx, dy, callback) {
+      if (stream && typeof stream.write === "function") {
+        const seq = moveCursorSequence(dx, dy);
+        if (seq) stream.write(seq);
+      }
+      if (typeof callback === "function") callback();
+      return true;
+    },
+
+    clearLine(stream, dir, callback) {
+      if (typeof dir === "function") {
+        callback = dir;
+        dir = 0;
+      }
+      if (stream && typeof stream.write === "function") {
+        stream.write(clearLineSequence(dir));
+      }
+      if (typeof callback === "function") callback();
+      return true;
+    },
+
+    clearScreenDown(stream, callback) {
+      if (stream && typeof stream.write === "function") {
+        stream.write(clearScreenDownSequence);
+      }
+      if (typeof callback === "function") callback();
+      return true;
+    },
+
+    emitKeypressEvents,
+  };
+
+  // readline/promises - question() returns a Promise
+  class PromisesInterface extends Interface {
+    question(query, options) {
+      return new Promise((resolve, reject) => {
+        if (this.closed) {
+          reject(useAfterClose());
+          return;
+        }
+        let cb = resolve;
+        const signal = options && options.signal;
+        if (signal) {
+          validateAbortSignal(signal);
+          if (signal.aborted) {
+            reject(abortError(signal));
+            return;
+          }
+          const onAbort = () => {
+            this._questionCancel();
+            reject(abortError(signal));
+          };
+          signal.addEventListener("abort", onAbort, { once: true });
+          cb = (answer) => {
+            signal.removeEventListener("abort", onAbort);
+            resolve(answer);
+          };
+        }
+        this._question(query, cb);
+      });
+    }
+  }
+
+  /**
+   * readline/promises Readline: batches cursor actions until commit().
+   */
+  class Readline {
+    constructor(stream, options = {}) {
+      this._stream = stream;
+      this._autoCommit = !!options.autoCommit;
+      this._todo = [];
+    }
+
+    _queue(seq) {
+      if (this._autoCommit) {
+        process.nextTick(() => this._stream.write(seq));
+      } else {
+        this._todo.push(seq);
+      }
+      return this;
+    }
+
+    cursorTo(x, y) {
+      return this._queue(cursorToSequence(x, y));
+    }
+
+    moveCursor(dx, dy) {
+      return dx || dy ? this._queue(moveCursorSequence(dx, dy)) : this;
+    }
+
+    clearLine(dir) {
+      return this._queue(clearLineSequence(dir));
+    }
+
+    clearScreenDown() {
+      return this._queue(clearScreenDownSequence);
+    }
+
+    commit() {
+      const data = this._todo.join("");
+      this._todo = [];
+      return new Promise((resolve) => {
+        if (data) this._stream.write(data, resolve);
+        else resolve();
+      });
+    }
+
+    rollback() {
+      this._todo = [];
+      return this;
+    }
+  }
+
+  readlineModule.promises = {
+    Interface: PromisesInterface,
+    Readline,
+    createInterface(input, output, completer, terminal) {
+      return new PromisesInterface(input, output, completer, terminal);
+    },
+  };
+  globalThis.__howth_modules["node:readline"] = readlineModule;
+  globalThis.__howth_modules["readline"] = readlineModule;
+  globalThis.__howth_modules["node:readline/promises"] = readlineModule.promises;
+  globalThis.__howth_modules["readline/promises"] = readlineModule.promises;
+
+  return globalThis.__howth_modules["node:events"];
+  }); // end heavy lazy block
+
+  // Set up unhandled promise rejection handler using Deno core API
+  core.setUnhandledPromiseRejectionHandler((promise, reason) => {
+    // Check if process has handlers for 'unhandledRejection'
+    const listeners = processListeners.get('unhandledRejection');
+    if (listeners && listeners.length > 0) {
+      for (const listener of [...listeners]) {
+        listener(reason, promise);
+      }
+      return true; // handled
+    }
+    // Log and swallow the error to prevent crash (Node.js behavior: warn but don't crash by default)
+    if (reason instanceof Error) {
+      console.error('[howth] UnhandledPromiseRejection:', reason.stack || reason.message);
+    } else {
+      console.error('[howth] UnhandledPromiseRejection:', reason);
+    }
+    return true; // prevent crash
+  });
+
+  // Set up exception callback for better error reporting
+  core.setReportExceptionCallback((err) => {
+    const listeners = processListeners.get('uncaughtException');
+    if (listeners && listeners.length > 0) {
+      for (const listener of [...listeners]) {
+        listener(err);
+      }
+      return;
+    }
+    if (err instanceof Error) {
+      console.error('[howth] Uncaught exception:', err.stack || err.message);
+    } else {
+      console.error('[howth] Uncaught exception:', err);
+    }
+  });
+
+  // ============================================================================
+  // howth:dom - DOM-lite environment for component tests
+  // ============================================================================
+
+  // A small DOM (nodes, events, selectors, innerHTML) that `howth test` installs
+  // as globals for files run with `--environment dom` or an `@jest-environment
+  // jsdom` docblock. It covers what React and Vue render and what Testing
+  // Library queries; there is no layout or CSS cascade, so sizes are zero and
+  // getComputedStyle() only sees inline styles.
+  (function() {
+    const HTML_NS = "http://www.w3.org/1999/xhtml";
+    const SVG_NS = "http://www.w3.org/2000/svg";
+    const DEFAULT_URL = "http://localhost/";
+    const VOID_ELEMENTS = new Set([
+      "area", "base", "br", "col", "embed", "hr", "img", "input", "link", "meta",
+      "source", "track", "wbr",
+    ]);
+    const RAW_TEXT_ELEMENTS = new Set(["script", "style"]);
+    const ESCAPABLE_RAW_TEXT_ELEMENTS = new Set(["textarea", "title"]);
+    const FORM_CONTROLS = new Set(["button", "fieldset", "input", "optgroup", "option", "select", "textarea"]);
+    const FOCUSABLE_ELEMENTS = new Set(["a", "button", "iframe", "input", "select", "summary", "textarea"]);
+    // Open elements closed by the start tag of another, as the HTML parser does
+    const IMPLIED_END_TAGS = {
+      li: ["li"], dt: ["dt", "dd"], dd: ["dt", "dd"], option: ["option"], optgroup: ["option", "optgroup"],
+      tr: ["tr", "td", "th"], td: ["td", "th"], th: ["td", "th"], thead: ["tbody", "tfoot"],
+      tbody: ["thead", "tbody", "tfoot"], tfoot: ["thead", "tbody"],
+    };
+    for (const tag of ["p", "div", "ul", "ol", "dl", "table", "form", "pre", "section", "article",
+      "header", "footer", "nav", "aside", "h1", "h2", "h3", "h4", "h5", "h6", "blockquote", "hr"]) {
+      IMPLIED_END_TAGS[tag] = ["p"];
+    }
+    const ENTITIES = {
+      amp: "&", lt: "<", gt: ">", quot: "\"", apos: "'", nbsp: " ", copy: "©", reg: "®",
+      trade: "™", hellip: "…", mdash: "—", ndash: "–", lsquo: "‘", rsquo: "’",
+      ldquo: "“", rdquo: "”", laquo: "«", raquo: "»", middot: "·", bull: "•",
+      times: "×", deg: "°", euro: "€",
+    };
+    const EVENT_HANDLERS = [
+      "abort", "animationend", "animationiteration", "animationstart", "beforeinput", "blur", "cancel",
+      "change", "click", "close", "compositionend", "compositionstart", "compositionupdate", "contextmenu",
+      "copy", "cut", "dblclick", "drag", "dragend", "dragenter", "dragleave", "dragover", "dragstart",
+      "drop", "error", "focus", "focusin", "focusout", "hashchange", "input", "invalid", "keydown",
+      "keypress", "keyup", "load", "message", "mousedown", "mouseenter", "mouseleave", "mousemove",
+      "mouseout", "mouseover", "mouseup", "paste", "pointercancel", "pointerdown", "pointerenter",
+      "pointerleave", "pointermove", "pointerout", "pointerover", "pointerup", "popstate", "reset",
+      "resize", "scroll", "select", "storage", "submit", "toggle", "touchcancel", "touchend",
+      "touchmove", "touchstart", "transitionend", "wheel",
+    ];
+
+    let domWindow = null; // the window the globals currently read through to
+    const windowTargets = new WeakMap(); // window proxy -> the Window behind it
+    let installed = null; // global name -> original property descriptor
+    let observerCount = 0;
+    let reportingException = false;
+
+    // --- Events ---------------------------------------------------------------
+
+    class Event {
+      constructor(type, init) {
+        if (arguments.length === 0) {
+          throw new TypeError("Failed to construct 'Event': 1 argument required, but only 0 present.");
+        }
+        init = init || {};
+        this.type = String(type);
+        this.bubbles = !!init.bubbles;
+        this.cancelable = !!init.cancelable;
+        this.composed = !!init.composed;
+        this.defaultPrevented = false;
+        this.isTrusted = false;
+        this.timeStamp = performance.now();
+        this.target = null;
+        this.currentTarget = null;
+        this.eventPhase = 0;
+        this._stop = false;
+        this._stopNow = false;
+        this._passive = false;
+        this._path = [];
+      }
+
+      get srcElement() { return this.target; }
+      get returnValue() { return !this.defaultPrevented; }
+      set returnValue(value) { if (!value) this.preventDefault(); }
+      get cancelBubble() { return this._stop; }
+      set cancelBubble(value) { if (value) this._stop = true; }
+
+      composedPath() { return this.eventPhase === 0 ? [] : this._path.slice(); }
+      preventDefault() { if (this.cancelable && !this._passive) this.defaultPrevented = true; }
+      stopPropagation() { this._stop = true; }
+      stopImmediatePropagation() { this._stop = true; this._stopNow = true; }
+
+      initEvent(type, bubbles, cancelable) {
+        this.type = String(type);
+        this.bubbles = !!bubbles;
+        this.cancelable = !!cancelable;
+      }
+    }
+    Object.assign(Event, { NONE: 0, CAPTURING_PHASE: 1, AT_TARGET: 2, BUBBLING_PHASE: 3 });
+
+    class CustomEvent extends Event {
+      constructor(type, init) {
+        super(type, init);
+        this.detail = init && init.detail !== undefined ? init.detail : null;
+      }
+
+      initCustomEvent(type, bubbles, cancelable, detail) {
+        this.initEvent(type, bubbles, cancelable);
+        this.detail = detail;
+      }
+    }
+
+    class UIEvent extends Event {
+      constructor(type, init) {
+        super(type, init);
+        this.view = (init && init.view) || null;
+        this.detail = (init && init.detail) || 0;
+      }
+    }
+
+    const MODIFIER_KEYS = { Alt: "altKey", Control: "ctrlKey", Meta: "metaKey", Shift: "shiftKey" };
+
+    function initModifiers(event, init) {
+      event.altKey = !!init.altKey;
+      event.ctrlKey = !!init.ctrlKey;
+      event.metaKey = !!init.metaKey;
+      event.shiftKey = !!init.shiftKey;
+    }
+
+    class MouseEvent extends UIEvent {
+      constructor(type, init) {
+        super(type, init);
+        init = init || {};
+        initModifiers(this, init);
+        this.screenX = init.screenX || 0;
+        this.screenY = init.screenY || 0;
+        this.clientX = init.clientX || 0;
+        this.clientY = init.clientY || 0;
+        this.button = init.button || 0;
+        this.buttons = init.buttons || 0;
+        this.relatedTarget = init.relatedTarget || null;
+      }
+
+      get pageX() { return this.clientX; }
+      get pageY() { return this.clientY; }
+      get x() { return this.clientX; }
+      get y() { return this.clientY; }
+      getModifierState(key) { return !!this[MODIFIER_KEYS[key]]; }
+    }
+
+    class PointerEvent extends MouseEvent {
+      constructor(type, init) {
+        super(type, init);
+        init = init || {};
+        this.pointerId = init.pointerId || 0;
+        this.pointerType = init.pointerType || "";
+        this.isPrimary = !!init.isPrimary;
+        this.width = init.width || 1;
+        this.height = init.height || 1;
+        this.pressure = init.pressure || 0;
+      }
+    }
+
+    class WheelEvent extends MouseEvent {
+      constructor(type, init) {
+        super(type, init);
+        init = init || {};
+        this.deltaX = init.deltaX || 0;
+        this.deltaY = init.deltaY || 0;
+        this.deltaZ = init.deltaZ || 0;
+        this.deltaMode = init.deltaMode || 0;
+      }
+    }
+
+    class KeyboardEvent extends UIEvent {
+      constructor(type, init) {
+        super(type, init);
+        init = init || {};
+        initModifiers(this, init);
+        this.key = init.key || "";
+        this.code = init.code || "";
+        this.location = init.location || 0;
+        this.repeat = !!init.repeat;
+        this.isComposing = !!init.isComposing;
+        this.charCode = init.charCode || 0;
+        this.keyCode = init.keyCode || 0;
+        this.which = init.which || this.keyCode || this.charCode;
+      }
+
+      getModifierState(key) { return !!this[MODIFIER_KEYS[key]]; }
+    }
+
+    class FocusEvent extends UIEvent {
+      constructor(type, init) {
+        super(type, init);
+        this.relatedTarget = (init && init.relatedTarget) || null;
+      }
+    }
+
+    class InputEvent extends UIEvent {
+      constructor(type, init) {
+        super(type, init);
+        init = init || {};
+        this.data = init.data === undefined ? null : init.data;
+        this.inputType = init.inputType || "";
+        this.isComposing = !!init.isComposing;
+      }
+    }
+
+    class CompositionEvent extends UIEvent {
+      constructor(type, init) {
+        super(type, init);
+        this.data = (init && init.data) || "";
+      }
+    }
+
+    class SubmitEvent extends Event {
+      constructor(type, init) {
+        super(type, init);
+        this.submitter = (init && init.submitter) || null;
+      }
+    }
+
+    class ErrorEvent extends Event {
+      constructor(type, init) {
+        super(type, init);
+        init = init || {};
+        this.message = init.message || "";
+        this.filename = init.filename || "";
+        this.lineno = init.lineno || 0;
+        this.colno = init.colno || 0;
+        this.error = init.error;
+      }
+    }
+
+    class HashChangeEvent extends Event {
+      constructor(type, init) {
+        super(type, init);
+        this.oldURL = (init && init.oldURL) || "";
+        this.newURL = (init && init.newURL) || "";
+      }
+    }
+
+    class PopStateEvent extends Event {
+      constructor(type, init) {
+        super(type, init);
+        this.state = init && init.state !== undefined ? init.state : null;
+      }
+    }
+
+    const EVENT_INTERFACES = {
+      Event, Events: Event, HTMLEvents: Event, CustomEvent, UIEvent, UIEvents: UIEvent, MouseEvent,
+      MouseEvents: MouseEvent, PointerEvent, WheelEvent, KeyboardEvent, FocusEvent, InputEvent,
+      CompositionEvent, SubmitEvent, ErrorEvent, HashChangeEvent, PopStateEvent,
+    };
+
+    class EventTarget {
+      constructor() {
+        this._listeners = null;
+        this._handlers = null;
+      }
+
+      addEventListener(type, callback, options) {
+        if (!callback) return;
+        const capture = typeof options === "boolean" ? options : !!(options && options.capture);
+        const flags = options && typeof options === "object" ? options : {};
+        if (flags.signal && flags.signal.aborted) return;
+        if (!this._listeners) this._listeners = new Map();
+        let list = this._listeners.get(type);
+        if (!list) this._listeners.set(type, list = []);
+        if (list.some((l) => l.callback === callback && l.capture === capture)) return;
+        list.push({ callback, capture, once: !!flags.once, passive: !!flags.passive, removed: false });
+        if (flags.signal) {
+          flags.signal.addEventListener("abort", () => this.removeEventListener(type, callback, capture));
+        }
+      }
+
+      removeEventListener(type, callback, options) {
+        const capture = typeof options === "boolean" ? options : !!(options && options.capture);
+        const list = this._listeners && this._listeners.get(type);
+        if (!list) return;
+        const index = list.findIndex((l) => l.callback === callback && l.capture === capture);
+        if (index !== -1) {
+          list[index].removed = true;
+          list.splice(index, 1);
+        }
+      }
+
+      dispatchEvent(event) {
+        if (!event || typeof event.type !== "string") {
+          throw new TypeError("Failed to execute 'dispatchEvent' on 'EventTarget': parameter 1 is not of type 'Event'.");
+        }
+        return dispatch(this, event);
+      }
+
+      _eventParent() {
+        return null;
+      }
+    }
+
+    for (const name of EVENT_HANDLERS) {
+      Object.defineProperty(EventTarget.prototype, "on" + name, {
+        get() { return (this._handlers && this._handlers[name]) || null; },
+        set(value) {
+          if (!this._handlers) this._handlers = {};
+          this._handlers[name] = typeof value === "function" ? value : null;
+        },
+        configurable: true,
+        enumerable: true,
+      });
+    }
+
+    // Capture, target and bubble phases, plus the activation behaviour of a
+    // click (toggling checkboxes, submitting forms, forwarding label clicks).
+    function dispatch(target, event) {
+      const path = [];
+      for (let node = target; node; node = node._eventParent()) path.push(node);
+      event.target = target;
+      event._path = path;
+
+      let activator = null;
+      let activationState;
+      if (event.type === "click" && event instanceof MouseEvent) {
+        activator = path.find((node) => node._activate && !isDisabled(node)) || null;
+        if (activator && activator._preActivate) activationState = activator._preActivate();
+      }
+
+      for (let i = path.length - 1; i > 0 && !event._stop; i--) invokeListeners(path[i], event, 1);
+      if (!event._stop) invokeListeners(target, event, 2);
+      if (event.bubbles) {
+        for (let i = 1; i < path.length && !event._stop; i++) invokeListeners(path[i], event, 3);
+      }
+      event.eventPhase = 0;
+      event.currentTarget = null;
+      event._stop = false;
+      event._stopNow = false;
+
+      if (activator) {
+        if (event.defaultPrevented) {
+          if (activator._cancelActivate) activator._cancelActivate(activationState);
+        } else {
+          activator._activate(event, activationState);
+        }
+      }
+      return !event.defaultPrevented;
+    }
+
+    function invokeListeners(node, event, phase) {
+      event.currentTarget = node;
+      event.eventPhase = phase;
+      const list = node._listeners && node._listeners.get(event.type);
+      if (list) {
+        for (const listener of list.slice()) {
+          if (listener.removed) continue;
+          if (phase === 1 && !listener.capture) continue;
+          if (phase === 3 && listener.capture) continue;
+          if (listener.once) node.removeEventListener(event.type, listener.callback, listener.capture);
+          callListener(node, event, listener.callback, listener.passive);
+          if (event._stopNow) return;
+        }
+      }
+      const handler = phase !== 1 && node._handlers && node._handlers[event.type];
+      if (handler && callListener(node, event, handler, false) === false) event.preventDefault();
+    }
+
+    function callListener(node, event, callback, passive) {
+      event._passive = passive;
+      try {
+        return typeof callback === "function" ? callback.call(node, event) : callback.handleEvent(event);
+      } catch (error) {
+        reportException(error);
+      } finally {
+        event._passive = false;
+      }
+    }
+
+    // Errors thrown by listeners go to the window's "error" event, as in a
+    // browser, and are logged unless a listener cancels it.
+    function reportException(error) {
+      if (domWindow && !reportingException) {
+        reportingException = true;
+        try {
+          const event = new ErrorEvent("error", {
+            cancelable: true,
+            message: error && error.message ? error.message : String(error),
+            error,
+          });
+          if (!domWindow.dispatchEvent(event)) return;
+        } finally {
+          reportingException = false;
+        }
+      }
+      console.error("Error: Uncaught [" + (error && error.stack ? error.stack : String(error)) + "]");
+    }
+
+    // --- Mutation observers ---------------------------------------------------
+
+    class MutationObserver {
+      constructor(callback) {
+        if (typeof callback !== "function") {
+          throw new TypeError("Failed to construct 'MutationObserver': parameter 1 is not of type 'Function'.");
+        }
+        this._callback = callback;
+        this._targets = [];
+        this._records = [];
+      }
+
+      observe(target, options) {
+        options = Object.assign({}, options);
+        if (options.attributeOldValue || options.attributeFilter) options.attributes = true;
+        if (options.characterDataOldValue) options.characterData = true;
+        if (!options.childList && !options.attributes && !options.characterData) {
+          throw new TypeError("Failed to execute 'observe' on 'MutationObserver': The options object must set at least one of 'attributes', 'characterData', or 'childList' to true.");
+        }
+        if (!target._observers) target._observers = [];
+        const existing = target._observers.find((r) => r.observer === this);
+        if (existing) {
+          existing.options = options;
+        } else {
+          target._observers.push({ observer: this, options });
+          this._targets.push(target);
+          observerCount++;
+        }
+      }
+
+      disconnect() {
+        for (const target of this._targets) {
+          target._observers = target._observers.filter((r) => r.observer !== this);
+          observerCount--;
+        }
+        this._targets = [];
+        this._records = [];
+      }
+
+      takeRecords() {
+        const records = this._records;
+        this._records = [];
+        return records;
+      }
+    }
+
+    const pendingObservers = new Set();
+    let mutationsQueued = false;
+
+    function queueMutation(target, type, details) {
+      if (observerCount === 0) return;
+      let interested = null;
+      for (let node = target; node; node = node.parentNode) {
+        if (!node._observers) continue;
+        for (const { observer, options } of node._observers) {
+          if (node !== target && !options.subtree) continue;
+          if (type === "childList" && !options.childList) continue;
+          if (type === "characterData" && !options.characterData) continue;
+          if (type === "attributes") {
+            if (!options.attributes) continue;
+            if (options.attributeFilter && !options.attributeFilter.includes(details.attributeName)) continue;
+          }
+          const oldValue = type === "attributes" ? options.attributeOldValue
+            : type === "characterData" ? options.characterDataOldValue : false;
+          if (!interested) interested = new Map();
+          interested.set(observer, interested.get(observer) || !!oldValue);
+        }
+      }
+      if (!interested) return;
+      for (const [observer, withOldValue] of interested) {
+        observer._records.push({
+          type,
+          target,
+          addedNodes: NodeList.of(...(details.addedNodes || [])),
+          removedNodes: NodeList.of(...(details.removedNodes || [])),
+          previousSibling: details.previousSibling || null,
+          nextSibling: details.nextSibling || null,
+          attributeName: details.attributeName || null,
+          attributeNamespace: null,
+          oldValue: withOldValue && details.oldValue !== undefined ? details.oldValue : null,
+        });
+        pendingObservers.add(observer);
+      }
+      if (!mutationsQueued) {
+        mutationsQueued = true;
+        queueMicrotask(deliverMutations);
+      }
+    }
+
+    function deliverMutations() {
+      mutationsQueued = false;
+      for (const observer of [...pendingObservers]) {
+        pendingObservers.delete(observer);
+        const records = observer.takeRecords();
+        if (records.length === 0) continue;
+        try {
+          observer._callback.call(observer, records, observer);
+        } catch (error) {
+          reportException(error);
+        }
+      }
+    }
+
+    // --- Nodes ----------------------------------------------------------------
+
+    class NodeList extends Array {
+      static get [Symbol.species]() { return Array; }
+      item(index) { return this[index] || null; }
+    }
+
+    class HTMLCollection extends Array {
+      static get [Symbol.species]() { return Array; }
+      item(index) { return this[index] || null; }
+      namedItem(name) {
+        return this.find((el) => el.getAttribute("id") === name || el.getAttribute("name") === name) || null;
+      }
+    }
+
+    class NamedNodeMap extends Array {
+      static get [Symbol.species]() { return Array; }
+      item(index) { return this[index] || null; }
+      getNamedItem(name) { return this.find((attr) => attr.name === name) || null; }
+    }
+
+    const NODE_CONSTANTS = {
+      ELEMENT_NODE: 1, ATTRIBUTE_NODE: 2, TEXT_NODE: 3, CDATA_SECTION_NODE: 4,
+      PROCESSING_INSTRUCTION_NODE: 7, COMMENT_NODE: 8, DOCUMENT_NODE: 9, DOCUMENT_TYPE_NODE: 10,
+      DOCUMENT_FRAGMENT_NODE: 11, DOCUMENT_POSITION_DISCONNECTED: 1, DOCUMENT_POSITION_PRECEDING: 2,
+      DOCUMENT_POSITION_FOLLOWING: 4, DOCUMENT_POSITION_CONTAINS: 8, DOCUMENT_POSITION_CONTAINED_BY: 16,
+      DOCUMENT_POSITION_IMPLEMENTATION_SPECIFIC: 32,
+    };
+
+    class Node extends EventTarget {
+      constructor() {
+        super();
+        this.ownerDocument = domWindow ? domWindow.document : null;
+        this.parentNode = null;
+        this.childNodes = new NodeList();
+        this._observers = null;
+      }
+
+      get parentElement() {
+        const parent = this.parentNode;
+        return parent && parent.nodeType === 1 ? parent : null;
+      }
+      get firstChild() { return this.childNodes[0] || null; }
+      get lastChild() { return this.childNodes[this.childNodes.length - 1] || null; }
+      get previousSibling() {
+        const parent = this.parentNode;
+        return parent ? parent.childNodes[parent.childNodes.indexOf(this) - 1] || null : null;
+      }
+      get nextSibling() {
+        const parent = this.parentNode;
+        return parent ? parent.childNodes[parent.childNodes.indexOf(this) + 1] || null : null;
+      }
+      get isConnected() { return this.getRootNode().nodeType === 9; }
+      get nodeValue() { return null; }
+      set nodeValue(value) {}
+      get textContent() { return textOf(this); }
+      set textContent(value) {
+        const text = value === null || value === undefined ? "" : String(value);
+        replaceAllChildren(this, text ? this.ownerDocument.createTextNode(text) : null);
+      }
+      get baseURI() { return this.ownerDocument ? this.ownerDocument.baseURI : DEFAULT_URL; }
+
+      hasChildNodes() { return this.childNodes.length > 0; }
+      getRootNode() {
+        let node = this;
+        while (node.parentNode) node = node.parentNode;
+        return node;
+      }
+      contains(other) {
+        for (let node = other; node; node = node.parentNode) {
+          if (node === this) return true;
+        }
+        return false;
+      }
+      appendChild(node) { return insertNode(this, node, null); }
+      insertBefore(node, child) { return insertNode(this, node, child || null); }
+      removeChild(child) {
+        if (!child || child.parentNode !== this) {
+          throw new DOMException("Failed to execute 'removeChild' on 'Node': The node to be removed is not a child of this node.", "NotFoundError");
+        }
+        removeNode(child);
+        return child;
+      }
+      replaceChild(node, child) {
+        if (!child || child.parentNode !== this) {
+          throw new DOMException("Failed to execute 'replaceChild' on 'Node': The node to be replaced is not a child of this node.", "NotFoundError");
+        }
+        if (node === child) return child;
+        let reference = child.nextSibling;
+        if (reference === node) reference = node.nextSibling;
+        removeNode(child);
+        insertNode(this, node, reference);
+        return child;
+      }
+      cloneNode(deep) {
+        const copy = this._cloneShallow();
+        if (deep) {
+          for (const child of this.childNodes) {
+            const childCopy = child.cloneNode(true);
+            childCopy.parentNode = copy;
+            copy.childNodes.push(childCopy);
+          }
+        }
+        return copy;
+      }
+      isSameNode(other) { return other === this; }
+      isEqualNode(other) {
+        return !!other && other.nodeType === this.nodeType && serializeNode(other) === serializeNode(this);
+      }
+      compareDocumentPosition(other) {
+        if (other === this) return 0;
+        if (this.contains(other)) return 20;
+        if (other.contains(this)) return 10;
+        const ancestors = (node) => {
+          const chain = [];
+          for (; node; node = node.parentNode) chain.unshift(node);
+          return chain;
+        };
+        const a = ancestors(this);
+        const b = ancestors(other);
+        if (a[0] !== b[0]) return 37;
+        let i = 0;
+        while (a[i] === b[i]) i++;
+        const siblings = a[i - 1].childNodes;
+        return siblings.indexOf(b[i]) < siblings.indexOf(a[i]) ? 2 : 4;
+      }
+      normalize() {
+        for (const child of this.childNodes.slice()) {
+          if (child.nodeType === 3) {
+            const previous = child.previousSibling;
+            if (child._data === "") removeNode(child);
+            else if (previous && previous.nodeType === 3) {
+              previous.data += child._data;
+              removeNode(child);
+            }
+          } else {
+            child.normalize();
+          }
+        }
+      }
+      _eventParent() {
+        return this.parentNode;
+      }
+    }
+    Object.assign(Node, NODE_CONSTANTS);
+    Object.assign(Node.prototype, NODE_CONSTANTS);
+
+    function textOf(node) {
+      let text = "";
+      for (const child of node.childNodes) {
+        if (child.nodeType === 3) text += child._data;
+        else if (child.nodeType === 1 || child.nodeType === 11) text += textOf(child);
+      }
+      return text;
+    }
+
+    function adopt(node, doc) {
+      if (node.ownerDocument === doc || node.nodeType === 9) return;
+      node.ownerDocument = doc;
+      for (const child of node.childNodes) adopt(child, doc);
+    }
+
+    function focusFixup(node) {
+      const doc = node.ownerDocument;
+      if (doc && doc._focused && node.contains(doc._focused)) doc._focused = null;
+    }
+
+    function insertNode(parent, node, reference) {
+      if (!(node instanceof Node)) {
+        throw new TypeError("Failed to execute 'appendChild' on 'Node': parameter 1 is not of type 'Node'.");
+      }
+      if (reference && reference.parentNode !== parent) {
+        throw new DOMException("Failed to execute 'insertBefore' on 'Node': The node before which the new node is to be inserted is not a child of this node.", "NotFoundError");
+      }
+      if (node.contains(parent)) {
+        throw new DOMException("Failed to execute 'appendChild' on 'Node': The new child element contains the parent.", "HierarchyRequestError");
+      }
+      let nodes;
+      if (node.nodeType === 11) {
+        nodes = node.childNodes.slice();
+        for (const child of nodes) removeNode(child);
+      } else {
+        nodes = [node];
+        if (reference === node) reference = node.nextSibling;
+        if (node.parentNode) removeNode(node);
+      }
+      const doc = parent.nodeType === 9 ? parent : parent.ownerDocument;
+      let index = reference ? parent.childNodes.indexOf(reference) : parent.childNodes.length;
+      const previousSibling = parent.childNodes[index - 1] || null;
+      for (const child of nodes) {
+        parent.childNodes.splice(index++, 0, child);
+        child.parentNode = parent;
+        adopt(child, doc);
+      }
+      if (nodes.length) {
+        queueMutation(parent, "childList", { addedNodes: nodes, previousSibling, nextSibling: reference });
+      }
+      return node;
+    }
+
+    function removeNode(node) {
+      const parent = node.parentNode;
+      const index = parent.childNodes.indexOf(node);
+      focusFixup(node);
+      parent.childNodes.splice(index, 1);
+      node.parentNode = null;
+      queueMutation(parent, "childList", {
+        removedNodes: [node],
+        previousSibling: parent.childNodes[index - 1] || null,
+        nextSibling: parent.childNodes[index] || null,
+      });
+    }
+
+    function replaceAllChildren(parent, node) {
+      const removed = parent.childNodes.slice();
+      for (const child of removed) {
+        focusFixup(child);
+        child.parentNode = null;
+      }
+      parent.childNodes.length = 0;
+      let added = [];
+      if (node) {
+        added = node.nodeType === 11 ? node.childNodes.slice() : [node];
+        if (node.nodeType === 11) node.childNodes.length = 0;
+        else if (node.parentNode) removeNode(node);
+        const doc = parent.nodeType === 9 ? parent : parent.ownerDocument;
+        for (const child of added) {
+          parent.childNodes.push(child);
+          child.parentNode = parent;
+          adopt(child, doc);
+        }
+      }
+      if (removed.length || added.length) {
+        queueMutation(parent, "childList", { addedNodes: added, removedNodes: removed });
+      }
+    }
+
+    // Nodes and strings passed to append() and friends, as one node
+    function nodesToNode(doc, nodes) {
+      if (nodes.length === 1 && nodes[0] instanceof Node) return nodes[0];
+      const fragment = doc.createDocumentFragment();
+      for (const node of nodes) {
+        fragment.appendChild(node instanceof Node ? node : doc.createTextNode(String(node)));
+      }
+      return fragment;
+    }
+
+    function elementChildren(node) {
+      const children = new HTMLCollection();
+      for (const child of node.childNodes) {
+        if (child.nodeType === 1) children.push(child);
+      }
+      return children;
+    }
+
+    function walkElements(node, visit) {
+      for (const child of node.childNodes) {
+        if (child.nodeType !== 1) continue;
+        if (visit(child) === false || walkElements(child, visit) === false) return false;
+      }
+      return true;
+    }
+
+    // Methods shared by elements, documents and fragments
+    class ParentNode {
+      get children() { return elementChildren(this); }
+      get childElementCount() { return elementChildren(this).length; }
+      get firstElementChild() { return this.childNodes.find((n) => n.nodeType === 1) || null; }
+      get lastElementChild() { return this.childNodes.findLast((n) => n.nodeType === 1) || null; }
+
+      append(...nodes) { this.appendChild(nodesToNode(this.ownerDocument || this, nodes)); }
+      prepend(...nodes) { this.insertBefore(nodesToNode(this.ownerDocument || this, nodes), this.firstChild); }
+      replaceChildren(...nodes) {
+        replaceAllChildren(this, nodes.length ? nodesToNode(this.ownerDocument || this, nodes) : null);
+      }
+      querySelector(selectors) {
+        const list = parseSelector(selectors);
+        const scope = this.nodeType === 1 ? this : null;
+        let found = null;
+        walkElements(this, (el) => {
+          if (matchesSelectorList(el, list, scope)) {
+            found = el;
+            return false;
+          }
+        });
+        return found;
+      }
+      querySelectorAll(selectors) {
+        const list = parseSelector(selectors);
+        const scope = this.nodeType === 1 ? this : null;
+        const found = new NodeList();
+        walkElements(this, (el) => {
+          if (matchesSelectorList(el, list, scope)) found.push(el);
+        });
+        return found;
+      }
+      getElementsByTagName(name) {
+        const lower = String(name).toLowerCase();
+        const found = new HTMLCollection();
+        walkElements(this, (el) => {
+          if (name === "*" || el.localName.toLowerCase() === lower) found.push(el);
+        });
+        return found;
+      }
+      getElementsByClassName(names) {
+        const wanted = String(names).split(/\s+/).filter(Boolean);
+        const found = new HTMLCollection();
+        walkElements(this, (el) => {
+          const classes = (el.getAttribute("class") || "").split(/\s+/);
+          if (wanted.length && wanted.every((name) => classes.includes(name))) found.push(el);
+        });
+        return found;
+      }
+    }
+
+    // Methods shared by elements and character data
+    class ChildNode {
+      get previousElementSibling() {
+        for (let node = this.previousSibling; node; node = node.previousSibling) {
+          if (node.nodeType === 1) return node;
+        }
+        return null;
+      }
+      get nextElementSibling() {
+        for (let node = this.nextSibling; node; node = node.nextSibling) {
+          if (node.nodeType === 1) return node;
+        }
+        return null;
+      }
+
+      before(...nodes) {
+        if (this.parentNode) this.parentNode.insertBefore(nodesToNode(this.ownerDocument, nodes), this);
+      }
+      after(...nodes) {
+        if (this.parentNode) this.parentNode.insertBefore(nodesToNode(this.ownerDocument, nodes), this.nextSibling);
+      }
+      replaceWith(...nodes) {
+        if (this.parentNode) this.parentNode.replaceChild(nodesToNode(this.ownerDocument, nodes), this);
+      }
+      remove() {
+        if (this.parentNode) removeNode(this);
+      }
+    }
+
+    function mixin(target, source) {
+      for (const key of Object.getOwnPropertyNames(source.prototype)) {
+        if (key !== "constructor") {
+          Object.defineProperty(target.prototype, key, Object.getOwnPropertyDescriptor(source.prototype, key));
+        }
+      }
+    }
+
+    class CharacterData extends Node {
+      constructor(data) {
+        super();
+        this._data = data === undefined ? "" : String(data);
+      }
+
+      get data() { return this._data; }
+      set data(value) {
+        const oldValue = this._data;
+        this._data = value === null || value === undefined ? "" : String(value);
+        queueMutation(this, "characterData", { oldValue });
+      }
+      get nodeValue() { return this._data; }
+      set nodeValue(value) { this.data = value; }
+      get textContent() { return this._data; }
+      set textContent(value) { this.data = value; }
+      get length() { return this._data.length; }
+
+      appendData(data) { this.data = this._data + data; }
+      insertData(offset, data) { this.data = this._data.slice(0, offset) + data + this._data.slice(offset); }
+      deleteData(offset, count) { this.data = this._data.slice(0, offset) + this._data.slice(offset + count); }
+      replaceData(offset, count, data) {
+        this.data = this._data.slice(0, offset) + data + this._data.slice(offset + count);
+      }
+      substringData(offset, count) { return this._data.substr(offset, count); }
+    }
+    mixin(CharacterData, ChildNode);
+
+    class Text extends CharacterData {
+      get nodeType() { return 3; }
+      get nodeName() { return "#text"; }
+      get wholeText() { return this._data; }
+
+      splitText(offset) {
+        const rest = this.ownerDocument.createTextNode(this._data.slice(offset));
+        this.data = this._data.slice(0, offset);
+        if (this.parentNode) this.parentNode.insertBefore(rest, this.nextSibling);
+        return rest;
+      }
+      _cloneShallow() { return this.ownerDocument.createTextNode(this._data); }
+    }
+
+    class Comment extends CharacterData {
+      get nodeType() { return 8; }
+      get nodeName() { return "#comment"; }
+      _cloneShallow() { return this.ownerDocument.createComment(this._data); }
+    }
+
+    class DocumentFragment extends Node {
+      get nodeType() { return 11; }
+      get nodeName() { return "#document-fragment"; }
+
+      getElementById(id) { return findById(this, id); }
+      _cloneShallow() { return this.ownerDocument.createDocumentFragment(); }
+    }
+    mixin(DocumentFragment, ParentNode);
+
+    function findById(root, id) {
+      let found = null;
+      walkElements(root, (el) => {
+        if (el.getAttribute("id") === id) {
+          found = el;
+          return false;
+        }
+      });
+      return found;
+    }
+
+    // --- Elements -------------------------------------------------------------
+
+    class Attr {
+      constructor(name, value, ownerElement, namespaceURI) {
+        this.name = name;
+        this.value = value;
+        this.ownerElement = ownerElement;
+        this.namespaceURI = namespaceURI || null;
+      }
+
+      get localName() { return this.name.slice(this.name.indexOf(":") + 1); }
+      get prefix() {
+        const colon = this.name.indexOf(":");
+        return colon === -1 ? null : this.name.slice(0, colon);
+      }
+      get nodeType() { return 2; }
+      get nodeName() { return this.name; }
+      get nodeValue() { return this.value; }
+      get textContent() { return this.value; }
+      get specified() { return true; }
+    }
+
+    const VALID_NAME = /^[^\s"'>/=\x00-\x1f]+$/;
+
+    class Element extends Node {
+      constructor() {
+        super();
+        this.namespaceURI = HTML_NS;
+        this.prefix = null;
+        this.localName = "";
+        this.scrollTop = 0;
+        this.scrollLeft = 0;
+        this._attrs = [];
+        this._classList = null;
+        this._style = null;
+        this._dataset = null;
+      }
+
+      get nodeType() { return 1; }
+      get tagName() {
+        const name = this.prefix ? this.prefix + ":" + this.localName : this.localName;
+        return this.namespaceURI === HTML_NS ? name.toUpperCase() : name;
+      }
+      get nodeName() { return this.tagName; }
+      get attributes() { return NamedNodeMap.from(this._attrs); }
+      get classList() { return this._classList || (this._classList = new DOMTokenList(this, "class")); }
+      get style() { return this._style || (this._style = createStyle(this)); }
+      set style(value) { this.style.cssText = value; }
+      get dataset() { return this._dataset || (this._dataset = createDataset(this)); }
+      get innerHTML() { return this._contents().childNodes.map(serializeNode).join(""); }
+      set innerHTML(html) { replaceAllChildren(this._contents(), parseFragment(String(html), this)); }
+      get outerHTML() { return serializeNode(this); }
+      set outerHTML(html) {
+        const parent = this.parentNode;
+        if (!parent) return;
+        const context = parent.nodeType === 1 ? parent : this.ownerDocument.body;
+        parent.replaceChild(parseFragment(String(html), context), this);
+      }
+      get innerText() { return this.textContent; }
+      set innerText(value) { this.textContent = value; }
+      get clientTop() { return 0; }
+      get clientLeft() { return 0; }
+      get clientWidth() { return 0; }
+      get clientHeight() { return 0; }
+      get scrollWidth() { return 0; }
+      get scrollHeight() { return 0; }
+      get tabIndex() {
+        const value = parseInt(this.getAttribute("tabindex"), 10);
+        if (!isNaN(value)) return value;
+        return FOCUSABLE_ELEMENTS.has(this.localName) && this.namespaceURI === HTML_NS ? 0 : -1;
+      }
+      set tabIndex(value) { this.setAttribute("tabindex", String(value | 0)); }
+
+      _attrName(name) {
+        name = String(name);
+        return this.namespaceURI === HTML_NS ? name.toLowerCase() : name;
+      }
+      _attr(name) {
+        name = this._attrName(name);
+        return this._attrs.find((attr) => attr.name === name) || null;
+      }
+      _contents() {
+        return this;
+      }
+
+      getAttribute(name) {
+        const attr = this._attr(name);
+        return attr ? attr.value : null;
+      }
+      getAttributeNode(name) { return this._attr(name); }
+      getAttributeNames() { return this._attrs.map((attr) => attr.name); }
+      hasAttribute(name) { return this._attr(name) !== null; }
+      hasAttributes() { return this._attrs.length > 0; }
+      setAttribute(name, value) {
+        if (!VALID_NAME.test(String(name))) {
+          throw new DOMException("Failed to execute 'setAttribute' on 'Element': '" + name + "' is not a valid attribute name.", "InvalidCharacterError");
+        }
+        setAttributeValue(this, this._attrName(name), String(value), null);
+      }
+      removeAttribute(name) {
+        const attr = this._attr(name);
+        if (!attr) return;
+        this._attrs.splice(this._attrs.indexOf(attr), 1);
+        queueMutation(this, "attributes", { attributeName: attr.name, oldValue: attr.value });
+      }
+      toggleAttribute(name, force) {
+        const present = this.hasAttribute(name);
+        if (present && force !== true) {
+          this.removeAttribute(name);
+          return false;
+        }
+        if (!present && force !== false) this.setAttribute(name, "");
+        return present ? true : force !== false;
+      }
+      getAttributeNS(namespace, localName) {
+        const attr = this._attrs.find((a) => a.namespaceURI === (namespace || null) && a.localName === localName);
+        return attr ? attr.value : null;
+      }
+      setAttributeNS(namespace, qualifiedName, value) {
+        setAttributeValue(this, String(qualifiedName), String(value), namespace || null);
+      }
+      removeAttributeNS(namespace, localName) {
+        const attr = this._attrs.find((a) => a.namespaceURI === (namespace || null) && a.localName === localName);
+        if (attr) this.removeAttribute(attr.name);
+      }
+      hasAttributeNS(namespace, localName) { return this.getAttributeNS(namespace, localName) !== null; }
+
+      matches(selectors) { return matchesSelectorList(this, parseSelector(selectors), this); }
+      webkitMatchesSelector(selectors) { return this.matches(selectors); }
+      closest(selectors) {
+        const list = parseSelector(selectors);
+        for (let el = this; el; el = el.parentElement) {
+          if (matchesSelectorList(el, list, this)) return el;
+        }
+        return null;
+      }
+
+      insertAdjacentElement(position, element) {
+        return insertAdjacent(this, position, element);
+      }
+      insertAdjacentText(position, text) {
+        insertAdjacent(this, position, this.ownerDocument.createTextNode(String(text)));
+      }
+      insertAdjacentHTML(position, html) {
+        const inside = /^(afterbegin|beforeend)$/i.test(position);
+        const context = inside ? this : this.parentElement || this.ownerDocument.body;
+        insertAdjacent(this, position, parseFragment(String(html), context));
+      }
+
+      focus() { if (isFocusable(this)) focusElement(this.ownerDocument, this); }
+      blur() { if (this.ownerDocument && this.ownerDocument._focused === this) focusElement(this.ownerDocument, null); }
+      click() {
+        if (isDisabled(this)) return;
+        this.dispatchEvent(new MouseEvent("click", {
+          bubbles: true, cancelable: true, composed: true, detail: 1, view: domWindow,
+        }));
+      }
+      getBoundingClientRect() { return new DOMRect(); }
+      getClientRects() { return []; }
+      scrollIntoView() {}
+      scrollTo() {}
+      scroll() {}
+      scrollBy() {}
+
+      _cloneShallow() {
+        const copy = createElementFor(this.ownerDocument, this.namespaceURI, this.localName, this.prefix);
+        copy._attrs = this._attrs.map((attr) => new Attr(attr.name, attr.value, copy, attr.namespaceURI));
+        return copy;
+      }
+    }
+    mixin(Element, ParentNode);
+    mixin(Element, ChildNode);
+
+    function setAttributeValue(el, name, value, namespaceURI) {
+      const attr = el._attrs.find((a) => a.name === name);
+      const oldValue = attr ? attr.value : null;
+      if (attr) attr.value = value;
+      else el._attrs.push(new Attr(name, value, el, namespaceURI));
+      queueMutation(el, "attributes", { attributeName: name, oldValue });
+    }
+
+    function insertAdjacent(el, position, node) {
+      switch (String(position).toLowerCase()) {
+        case "beforebegin":
+          if (!el.parentNode) return null;
+          el.parentNode.insertBefore(node, el);
+          return node;
+        case "afterbegin":
+          el.insertBefore(node, el.firstChild);
+          return node;
+        case "beforeend":
+          el.appendChild(node);
+          return node;
+        case "afterend":
+          if (!el.parentNode) return null;
+          el.parentNode.insertBefore(node, el.nextSibling);
+          return node;
+        default:
+          throw new DOMException("Failed to execute 'insertAdjacent' on 'Element': The value provided ('" + position + "') is not one of 'beforeBegin', 'afterBegin', 'beforeEnd', or 'afterEnd'.", "SyntaxError");
+      }
+    }
+
+    class DOMRect {
+      constructor(x = 0, y = 0, width = 0, height = 0) {
+        this.x = x;
+        this.y = y;
+        this.width = width;
+        this.height = height;
+      }
+
+      get top() { return this.y; }
+      get left() { return this.x; }
+      get right() { return this.x + this.width; }
+      get bottom() { return this.y + this.height; }
+      toJSON() {
+        const { x, y, width, height, top, right, bottom, left } = this;
+        return { x, y, width, height, top, right, bottom, left };
+      }
+    }
+
+    function reflectString(proto, property, attribute) {
+      Object.defineProperty(proto, property, {
+        get() { return this.getAttribute(attribute) ?? ""; },
+        set(value) { this.setAttribute(attribute, String(value)); },
+        configurable: true,
+        enumerable: true,
+      });
+    }
+
+    function reflectBoolean(proto, property, attribute) {
+      Object.defineProperty(proto, property, {
+        get() { return this.hasAttribute(attribute); },
+        set(value) { this.toggleAttribute(attribute, !!value); },
+        configurable: true,
+        enumerable: true,
+      });
+    }
+
+    function reflectUrl(proto, property, attribute) {
+      Object.defineProperty(proto, property, {
+        get() {
+          const value = this.getAttribute(attribute);
+          if (value === null) return "";
+          try {
+            return resolveUrl(value, this.baseURI).href;
+          } catch {
+            return value;
+          }
+        },
+        set(value) { this.setAttribute(attribute, String(value)); },
+        configurable: true,
+        enumerable: true,
+      });
+    }
+
+    reflectString(Element.prototype, "id", "id");
+    reflectString(Element.prototype, "className", "class");
+    reflectString(Element.prototype, "slot", "slot");
+
+    class HTMLElement extends Element {
+      get offsetParent() { return null; }
+      get offsetTop() { return 0; }
+      get offsetLeft() { return 0; }
+      get offsetWidth() { return 0; }
+      get offsetHeight() { return 0; }
+      get isContentEditable() { return this.getAttribute("contenteditable") === "true"; }
+      get contentEditable() { return this.getAttribute("contenteditable") ?? "inherit"; }
+      set contentEditable(value) { this.setAttribute("contenteditable", String(value)); }
+    }
+    for (const name of ["title", "lang", "dir", "accessKey"]) {
+      reflectString(HTMLElement.prototype, name, name.toLowerCase());
+    }
+    reflectBoolean(HTMLElement.prototype, "hidden", "hidden");
+
+    class SVGElement extends Element {}
+    class SVGSVGElement extends SVGElement {}
+
+    function isDisabled(el) {
+      if (el.nodeType !== 1 || el.namespaceURI !== HTML_NS || !FORM_CONTROLS.has(el.localName)) return false;
+      if (el.hasAttribute("disabled")) return true;
+      if (el.localName === "option") {
+        const group = el.parentElement;
+        return !!group && group.localName === "optgroup" && group.hasAttribute("disabled");
+      }
+      for (let parent = el.parentElement; parent; parent = parent.parentElement) {
+        if (parent.localName === "fieldset" && parent.hasAttribute("disabled")) {
+          const legend = parent.children.find((child) => child.localName === "legend");
+          return !(legend && legend.contains(el));
+        }
+      }
+      return false;
+    }
+
+    function isFocusable(el) {
+      if (!el.isConnected || isDisabled(el)) return false;
+      if (el.hasAttribute("tabindex") || el.isContentEditable) return true;
+      if (el.namespaceURI !== HTML_NS || !FOCUSABLE_ELEMENTS.has(el.localName)) return false;
+      if (el.localName === "a") return el.hasAttribute("href");
+      return !(el.localName === "input" && el.type === "hidden");
+    }
+
+    function focusElement(doc, el) {
+      const previous = doc._focused;
+      if (previous === el) return;
+      doc._focused = null;
+      if (previous) {
+        previous.dispatchEvent(new FocusEvent("blur", { relatedTarget: el, view: domWindow }));
+        previous.dispatchEvent(new FocusEvent("focusout", { bubbles: true, relatedTarget: el, view: domWindow }));
+      }
+      if (el) {
+        doc._focused = el;
+        el.dispatchEvent(new FocusEvent("focus", { relatedTarget: previous, view: domWindow }));
+        el.dispatchEvent(new FocusEvent("focusin", { bubbles: true, relatedTarget: previous, view: domWindow }));
+      }
+    }
+
+    function fireFormEvent(el, type) {
+      el.dispatchEvent(new Event(type, { bubbles: true, composed: type === "input" }));
+    }
+
+    function formOf(el) {
+      const id = el.getAttribute("form");
+      if (id !== null) {
+        const form = el.ownerDocument.getElementById(id);
+        return form && form.localName === "form" ? form : null;
+      }
+      return el.closest("form");
+    }
+
+    function labelsOf(el) {
+      const labels = new NodeList();
+      const root = el.getRootNode();
+      walkElements(root, (label) => {
+        if (label.localName === "label" && label.control === el) labels.push(label);
+      });
+      return labels;
+    }
+
+    // Form-associated elements share name, form, labels and validity stubs
+    class FormControl {
+      get form() { return formOf(this); }
+      get labels() { return labelsOf(this); }
+      get validity() { return { valid: true }; }
+      get validationMessage() { return ""; }
+      get willValidate() { return !isDisabled(this); }
+      checkValidity() { return true; }
+      reportValidity() { return true; }
+      setCustomValidity() {}
+    }
+
+    const INPUT_TYPES = new Set([
+      "button", "checkbox", "color", "date", "datetime-local", "email", "file", "hidden", "image",
+      "month", "number", "password", "radio", "range", "reset", "search", "submit", "tel", "text",
+      "time", "url", "week",
+    ]);
+
+    class HTMLInputElement extends HTMLElement {
+      constructor() {
+        super();
+        this._value = "";
+        this._dirtyValue = false;
+        this._checked = false;
+        this._dirtyChecked = false;
+        this.indeterminate = false;
+        this.selectionStart = 0;
+        this.selectionEnd = 0;
+        this.selectionDirection = "none";
+      }
+
+      get type() {
+        const type = (this.getAttribute("type") || "").toLowerCase();
+        return INPUT_TYPES.has(type) ? type : "text";
+      }
+      set type(value) { this.setAttribute("type", String(value)); }
+      get value() {
+        if (this.type === "checkbox" || this.type === "radio") return this.getAttribute("value") ?? "on";
+        return this._dirtyValue ? this._value : this.getAttribute("value") ?? "";
+      }
+      set value(value) {
+        value = value === null || value === undefined ? "" : String(value);
+        if (this.type === "checkbox" || this.type === "radio") {
+          this.setAttribute("value", value);
+          return;
+        }
+        this._value = value;
+        this._dirtyValue = true;
+        this.selectionStart = this.selectionEnd = value.length;
+      }
+      get valueAsNumber() { return this.value === "" ? NaN : Number(this.value); }
+      set valueAsNumber(value) { this.value = String(value); }
+      get defaultValue() { return this.getAttribute("value") ?? ""; }
+      set defaultValue(value) { this.setAttribute("value", String(value)); }
+      get checked() { return this._dirtyChecked ? this._checked : this.hasAttribute("checked"); }
+      set checked(value) {
+        this._checked = !!value;
+        this._dirtyChecked = true;
+        if (this._checked && this.type === "radio") uncheckRadioGroup(this);
+      }
+      get defaultChecked() { return this.hasAttribute("checked"); }
+      set defaultChecked(value) { this.toggleAttribute("checked", !!value); }
+      get files() { return this.type === "file" ? [] : null; }
+
+      select() {
+        this.selectionStart = 0;
+        this.selectionEnd = this.value.length;
+      }
+      setSelectionRange(start, end, direction) {
+        this.selectionStart = start;
+        this.selectionEnd = end;
+        this.selectionDirection = direction || "none";
+      }
+
+      get _activate() {
+        return ["checkbox", "radio", "submit", "image", "reset"].includes(this.type) ? activateInput : undefined;
+      }
+      _preActivate() {
+        if (this.type === "checkbox") {
+          const checked = this.checked;
+          this.checked = !checked;
+          return { checked };
+        }
+        if (this.type === "radio") {
+          const previous = radioGroup(this).find((input) => input.checked) || null;
+          const checked = this.checked;
+          this.checked = true;
+          return { checked, previous };
+        }
+        return undefined;
+      }
+      _cancelActivate(state) {
+        if (!state) return;
+        this.checked = state.checked;
+        if (state.previous) state.previous.checked = true;
+      }
+      _cloneShallow() {
+        const copy = super._cloneShallow();
+        Object.assign(copy, {
+          _value: this._value,
+          _dirtyValue: this._dirtyValue,
+          _checked: this._checked,
+          _dirtyChecked: this._dirtyChecked,
+        });
+        return copy;
+      }
+    }
+    mixin(HTMLInputElement, FormControl);
+
+    function activateInput(event, state) {
+      if (this.type === "checkbox" || this.type === "radio") {
+        if (state && state.checked !== this.checked) {
+          fireFormEvent(this, "input");
+          fireFormEvent(this, "change");
+        }
+      } else if (this.form) {
+        if (this.type === "reset") this.form.reset();
+        else this.form.requestSubmit(this);
+      }
+    }
+
+    function radioGroup(input) {
+      const name = input.getAttribute("name");
+      if (!name) return [input];
+      const form = input.form;
+      const group = [];
+      walkElements(form || input.getRootNode(), (el) => {
+        if (el.localName === "input" && el.type === "radio" && el.getAttribute("name") === name && el.form === form) {
+          group.push(el);
+        }
+      });
+      return group;
+    }
+
+    function uncheckRadioGroup(input) {
+      for (const other of radioGroup(input)) {
+        if (other !== input) {
+          other._checked = false;
+          other._dirtyChecked = true;
+        }
+      }
+    }
+
+    class HTMLTextAreaElement extends HTMLElement {
+      constructor() {
+        super();
+        this._value = "";
+        this._dirtyValue = false;
+        this.selectionStart = 0;
+        this.selectionEnd = 0;
+        this.selectionDirection = "none";
+      }
+
+      get type() { return "textarea"; }
+      get value() { return this._dirtyValue ? this._value : this.textContent; }
+      set value(value) {
+        this._value = value === null || value === undefined ? "" : String(value);
+        this._dirtyValue = true;
+        this.selectionStart = this.selectionEnd = this._value.length;
+      }
+      get defaultValue() { return this.textContent; }
+      set defaultValue(value) { this.textContent = value; }
+      get textLength() { return this.value.length; }
+
+      select() {
+        this.selectionStart = 0;
+        this.selectionEnd = this.value.length;
+      }
+      setSelectionRange(start, end, direction) {
+        this.selectionStart = start;
+        this.selectionEnd = end;
+        this.selectionDirection = direction || "none";
+      }
+    }
+    mixin(HTMLTextAreaElement, FormControl);
+
+    class HTMLSelectElement extends HTMLElement {
+      get type() { return this.multiple ? "select-multiple" : "select-one"; }
+      get options() {
+        const options = new HTMLCollection();
+        walkElements(this, (el) => {
+          if (el.localName === "option") options.push(el);
+        });
+        return options;
+      }
+      get length() { return this.options.length; }
+      get selectedOptions() { return HTMLCollection.from(this.options.filter((option) => option.selected)); }
+      get selectedIndex() { return this.options.findIndex((option) => option.selected); }
+      set selectedIndex(index) {
+        this.options.forEach((option, i) => {
+          option._selected = i === Number(index);
+        });
+      }
+      get value() {
+        const option = this.options.find((o) => o.selected);
+        return option ? option.value : "";
+      }
+      set value(value) {
+        let found = false;
+        for (const option of this.options) {
+          option._selected = !found && option.value === String(value);
+          if (option._selected) found = true;
+        }
+      }
+
+      item(index) { return this.options[index] || null; }
+      namedItem(name) { return this.options.namedItem(name); }
+      add(option, before) {
+        const reference = typeof before === "number" ? this.options[before] : before;
+        if (reference) reference.parentNode.insertBefore(option, reference);
+        else this.appendChild(option);
+      }
+      remove(index) {
+        if (index === undefined) {
+          if (this.parentNode) removeNode(this);
+        } else if (this.options[index]) {
+          this.options[index].remove();
+        }
+      }
+    }
+    mixin(HTMLSelectElement, FormControl);
+    reflectBoolean(HTMLSelectElement.prototype, "multiple", "multiple");
+
+    // Selectedness follows the HTML rules: set state wins over the `selected`
+    // attribute, and a single select with none shows its first enabled option.
+    class HTMLOptionElement extends HTMLElement {
+      constructor() {
+        super();
+        this._selected = null;
+      }
+
+      get value() { return this.getAttribute("value") ?? this.text; }
+      set value(value) { this.setAttribute("value", String(value)); }
+      get text() { return this.textContent.replace(/\s+/g, " ").trim(); }
+      set text(value) { this.textContent = value; }
+      get label() { return this.getAttribute("label") ?? this.text; }
+      get defaultSelected() { return this.hasAttribute("selected"); }
+      set defaultSelected(value) { this.toggleAttribute("selected", !!value); }
+      get index() {
+        const select = this._select();
+        return select ? select.options.indexOf(this) : 0;
+      }
+      get form() {
+        const select = this._select();
+        return select ? select.form : null;
+      }
+      get selected() {
+        const select = this._select();
+        if (!select || select.multiple) return this._explicitlySelected();
+        const options = select.options;
+        const chosen = options.findLast((option) => option._explicitlySelected());
+        if (chosen) return chosen === this;
+        return options.find((option) => !isDisabled(option)) === this;
+      }
+      set selected(value) {
+        const select = this._select();
+        if (value && select && !select.multiple) {
+          for (const option of select.options) option._selected = false;
+        }
+        this._selected = !!value;
+      }
+
+      _explicitlySelected() {
+        return this._selected === null ? this.hasAttribute("selected") : this._selected;
+      }
+      _select() {
+        for (let parent = this.parentElement; parent; parent = parent.parentElement) {
+          if (parent.localName === "select") return parent;
+          if (parent.localName !== "optgroup") return null;
+        }
+        return null;
+      }
+    }
+
+    class HTMLButtonElement extends HTMLElement {
+      get type() {
+        const type = (this.getAttribute("type") || "").toLowerCase();
+        return type === "reset" || type === "button" ? type : "submit";
+      }
+      set type(value) { this.setAttribute("type", String(value)); }
+
+      _activate() {
+        const form = this.form;
+        if (!form || this.type === "button") return;
+        if (this.type === "reset") form.reset();
+        else form.requestSubmit(this);
+      }
+    }
+    mixin(HTMLButtonElement, FormControl);
+    reflectString(HTMLButtonElement.prototype, "value", "value");
+
+    for (const cls of [HTMLInputElement, HTMLTextAreaElement, HTMLSelectElement, HTMLButtonElement]) {
+      reflectString(cls.prototype, "name", "name");
+      reflectBoolean(cls.prototype, "disabled", "disabled");
+      reflectBoolean(cls.prototype, "autofocus", "autofocus");
+    }
+    for (const cls of [HTMLInputElement, HTMLTextAreaElement]) {
+      reflectString(cls.prototype, "placeholder", "placeholder");
+      reflectBoolean(cls.prototype, "readOnly", "readonly");
+    }
+    for (const cls of [HTMLInputElement, HTMLTextAreaElement, HTMLSelectElement]) {
+      reflectBoolean(cls.prototype, "required", "required");
+    }
+    reflectBoolean(HTMLInputElement.prototype, "multiple", "multiple");
+    for (const name of ["accept", "alt", "autocomplete", "max", "min", "pattern", "step"]) {
+      reflectString(HTMLInputElement.prototype, name, name.toLowerCase());
+    }
+    reflectBoolean(HTMLOptionElement.prototype, "disabled", "disabled");
+
+    class HTMLFormElement extends HTMLElement {
+      get elements() {
+        const elements = new HTMLCollection();
+        walkElements(this.getRootNode(), (el) => {
+          if (["button", "fieldset", "input", "select", "textarea"].includes(el.localName) && el.form === this) {
+            elements.push(el);
+          }
+        });
+        return elements;
+      }
+      get length() { return this.elements.length; }
+
+      submit() {}
+      requestSubmit(submitter) {
+        this.dispatchEvent(new SubmitEvent("submit", { bubbles: true, cancelable: true, submitter: submitter || null }));
+      }
+      reset() {
+        if (!this.dispatchEvent(new Event("reset", { bubbles: true, cancelable: true }))) return;
+        for (const el of this.elements) {
+          el._dirtyValue = false;
+          el._dirtyChecked = false;
+          if (el.localName === "select") {
+            for (const option of el.options) option._selected = null;
+          }
+        }
+      }
+      checkValidity() { return true; }
+      reportValidity() { return true; }
+    }
+    for (const name of ["name", "method", "target", "enctype"]) {
+      reflectString(HTMLFormElement.prototype, name, name);
+    }
+    reflectUrl(HTMLFormElement.prototype, "action", "action");
+    reflectBoolean(HTMLFormElement.prototype, "noValidate", "novalidate");
+
+    class HTMLLabelElement extends HTMLElement {
+      get control() {
+        const id = this.getAttribute("for");
+        const control = id !== null
+          ? this.ownerDocument.getElementById(id)
+          : this.querySelector("button, input:not([type=hidden]), select, textarea");
+        return control && ["button", "input", "select", "textarea"].includes(control.localName) ? control : null;
+      }
+      get form() {
+        const control = this.control;
+        return control ? control.form : null;
+      }
+
+      _activate(event) {
+        const control = this.control;
+        if (control && !control.contains(event.target)) control.click();
+      }
+    }
+    reflectString(HTMLLabelElement.prototype, "htmlFor", "for");
+
+    class HTMLAnchorElement extends HTMLElement {
+      get text() { return this.textContent; }
+      set text(value) { this.textContent = value; }
+    }
+    reflectUrl(HTMLAnchorElement.prototype, "href", "href");
+    for (const name of ["target", "rel", "download", "hreflang", "type"]) {
+      reflectString(HTMLAnchorElement.prototype, name, name);
+    }
+
+    class HTMLImageElement extends HTMLElement {
+      get complete() { return true; }
+      get naturalWidth() { return 0; }
+      get naturalHeight() { return 0; }
+    }
+    reflectUrl(HTMLImageElement.prototype, "src", "src");
+    reflectString(HTMLImageElement.prototype, "alt", "alt");
+
+    class HTMLTemplateElement extends HTMLElement {
+      get content() {
+        if (!this._content) this._content = this.ownerDocument.createDocumentFragment();
+        return this._content;
+      }
+
+      _contents() {
+        return this.content;
+      }
+      cloneNode(deep) {
+        const copy = super.cloneNode(false);
+        if (deep) copy.content.appendChild(this.content.cloneNode(true));
+        return copy;
+      }
+    }
+
+    class HTMLIFrameElement extends HTMLElement {
+      get contentWindow() { return null; }
+      get contentDocument() { return null; }
+    }
+    reflectUrl(HTMLIFrameElement.prototype, "src", "src");
+
+    class HTMLCanvasElement extends HTMLElement {
+      getContext() { return null; }
+      toDataURL() { return "data:,"; }
+    }
+
+    class HTMLScriptElement extends HTMLElement {
+      get text() { return this.textContent; }
+      set text(value) { this.textContent = value; }
+    }
+    reflectUrl(HTMLScriptElement.prototype, "src", "src");
+    reflectString(HTMLScriptElement.prototype, "type", "type");
+
+    const HTML_ELEMENT_CLASSES = new Map(Object.entries({
+      a: HTMLAnchorElement, button: HTMLButtonElement, canvas: HTMLCanvasElement, form: HTMLFormElement,
+      iframe: HTMLIFrameElement, img: HTMLImageElement, input: HTMLInputElement, label: HTMLLabelElement,
+      option: HTMLOptionElement, script: HTMLScriptElement, select: HTMLSelectElement,
+      template: HTMLTemplateElement, textarea: HTMLTextAreaElement,
+    }));
+    const SIMPLE_ELEMENT_CLASSES = {
+      HTMLBodyElement: ["body"], HTMLBRElement: ["br"], HTMLDivElement: ["div"], HTMLDListElement: ["dl"],
+      HTMLFieldSetElement: ["fieldset"], HTMLHeadElement: ["head"], HTMLHeadingElement: ["h1", "h2", "h3", "h4", "h5", "h6"],
+      HTMLHRElement: ["hr"], HTMLHtmlElement: ["html"], HTMLLegendElement: ["legend"], HTMLLIElement: ["li"],
+      HTMLLinkElement: ["link"], HTMLMetaElement: ["meta"], HTMLOListElement: ["ol"], HTMLOptGroupElement: ["optgroup"],
+      HTMLParagraphElement: ["p"], HTMLPreElement: ["pre"], HTMLSpanElement: ["span"], HTMLStyleElement: ["style"],
+      HTMLTableCellElement: ["td", "th"], HTMLTableElement: ["table"], HTMLTableRowElement: ["tr"],
+      HTMLTableSectionElement: ["thead", "tbody", "tfoot"], HTMLTitleElement: ["title"], HTMLUListElement: ["ul"],
+    };
+    const ELEMENT_INTERFACES = {
+      HTMLAnchorElement, HTMLButtonElement, HTMLCanvasElement, HTMLFormElement, HTMLIFrameElement,
+      HTMLImageElement, HTMLInputElement, HTMLLabelElement, HTMLOptionElement, HTMLScriptElement,
+      HTMLSelectElement, HTMLTemplateElement, HTMLTextAreaElement,
+    };
+    for (const [name, tags] of Object.entries(SIMPLE_ELEMENT_CLASSES)) {
+      const cls = ({ [name]: class extends HTMLElement {} })[name];
+      ELEMENT_INTERFACES[name] = cls;
+      for (const tag of tags) HTML_ELEMENT_CLASSES.set(tag, cls);
+    }
+    reflectBoolean(ELEMENT_INTERFACES.HTMLFieldSetElement.prototype, "disabled", "disabled");
+    reflectBoolean(ELEMENT_INTERFACES.HTMLOptGroupElement.prototype, "disabled", "disabled");
+    reflectString(ELEMENT_INTERFACES.HTMLOptGroupElement.prototype, "label", "label");
+    reflectUrl(ELEMENT_INTERFACES.HTMLLinkElement.prototype, "href", "href");
+    reflectString(ELEMENT_INTERFACES.HTMLLinkElement.prototype, "rel", "rel");
+
+    function createElementFor(doc, namespaceURI, localName, prefix) {
+      const cls = namespaceURI === HTML_NS ? HTML_ELEMENT_CLASSES.get(localName) || HTMLElement
+        : namespaceURI === SVG_NS ? (localName === "svg" ? SVGSVGElement : SVGElement)
+          : Element;
+      const el = new cls();
+      el.ownerDocument = doc;
+      el.namespaceURI = namespaceURI;
+      el.localName = localName;
+      el.prefix = prefix || null;
+      return el;
+    }
+
+    // --- classList, style and dataset -----------------------------------------
+
+    class DOMTokenList {
+      constructor(element, attribute) {
+        this._element = element;
+        this._attribute = attribute;
+      }
+
+      _tokens() {
+        return [...new Set((this._element.getAttribute(this._attribute) || "").split(/\s+/).filter(Boolean))];
+      }
+      _update(tokens) {
+        if (!this._element.hasAttribute(this._attribute) && tokens.length === 0) return;
+        this._element.setAttribute(this._attribute, tokens.join(" "));
+      }
+
+      get length() { return this._tokens().length; }
+      get value() { return this._element.getAttribute(this._attribute) || ""; }
+      set value(value) { this._element.setAttribute(this._attribute, String(value)); }
+
+      item(index) { return this._tokens()[index] ?? null; }
+      contains(token) { return this._tokens().includes(String(token)); }
+      add(...tokens) {
+        const list = this._tokens();
+        for (const token of tokens.map(validToken)) {
+          if (!list.includes(token)) list.push(token);
+        }
+        this._update(list);
+      }
+      remove(...tokens) {
+        const remove = tokens.map(validToken);
+        this._update(this._tokens().filter((token) => !remove.includes(token)));
+      }
+      toggle(token, force) {
+        token = validToken(token);
+        const present = this.contains(token);
+        if (present && force !== true) {
+          this.remove(token);
+          return false;
+        }
+        if (!present && force !== false) this.add(token);
+        return present || force !== false;
+      }
+      replace(token, newToken) {
+        const list = this._tokens();
+        const index = list.indexOf(validToken(token));
+        if (index === -1) return false;
+        list[index] = validToken(newToken);
+        this._update([...new Set(list)]);
+        return true;
+      }
+      supports() { return true; }
+      forEach(callback, thisArg) { this._tokens().forEach((token, i) => callback.call(thisArg, token, i, this)); }
+      toString() { return this.value; }
+      [Symbol.iterator]() { return this._tokens()[Symbol.iterator](); }
+    }
+
+    function validToken(token) {
+      token = String(token);
+      if (token === "") {
+        throw new DOMException("The token provided must not be empty.", "SyntaxError");
+      }
+      if (/\s/.test(token)) {
+        throw new DOMException("The token provided ('" + token + "') contains HTML space characters, which are not valid in tokens.", "InvalidCharacterError");
+      }
+      return token;
+    }
+
+    // Inline styles live in the `style` attribute; a detached declaration
+    // (from getComputedStyle) keeps its own copy.
+    class CSSStyleDeclaration {
+      constructor(element) {
+        this._element = element || null;
+        this._detached = element ? null : new Map();
+        this._cacheText = null;
+        this._cache = null;
+      }
+
+      _props() {
+        if (this._detached) return this._detached;
+        const text = this._element.getAttribute("style") || "";
+        if (text !== this._cacheText) {
+          this._cacheText = text;
+          this._cache = parseStyle(text);
+        }
+        return new Map(this._cache);
+      }
+      _commit(props) {
+        if (this._detached) this._detached = props;
+        else this._element.setAttribute("style", serializeStyle(props));
+      }
+
+      get cssText() { return serializeStyle(this._props()); }
+      set cssText(value) { this._commit(parseStyle(String(value ?? ""))); }
+      get length() { return this._props().size; }
+      get cssFloat() { return this.getPropertyValue("float"); }
+      set cssFloat(value) { this.setProperty("float", value); }
+
+      item(index) { return [...this._props().keys()][index] || ""; }
+      getPropertyValue(name) {
+        const entry = this._props().get(normalizeCssName(name));
+        return entry ? entry.value : "";
+      }
+      getPropertyPriority(name) {
+        const entry = this._props().get(normalizeCssName(name));
+        return entry ? entry.priority : "";
+      }
+      setProperty(name, value, priority) {
+        if (value === null || value === undefined || value === "") {
+          this.removeProperty(name);
+          return;
+        }
+        const props = this._props();
+        props.set(normalizeCssName(name), { value: String(value).trim(), priority: priority ? "important" : "" });
+        this._commit(props);
+      }
+      removeProperty(name) {
+        const props = this._props();
+        const key = normalizeCssName(name);
+        const entry = props.get(key);
+        if (!entry) return "";
+        props.delete(key);
+        this._commit(props);
+        return entry.value;
+      }
+    }
+
+    const STYLE_HANDLER = {
+      get(target, property) {
+        if (typeof property !== "string" || property in target) {
+          const value = Reflect.get(target, property);
+          return typeof value === "function" ? value.bind(target) : value;
+        }
+        if (/^\d+$/.test(property)) return target.item(Number(property));
+        return target.getPropertyValue(cssPropertyName(property));
+      },
+      set(target, property, value) {
+        if (typeof property !== "string" || property in target) return Reflect.set(target, property, value);
+        target.setProperty(cssPropertyName(property), value);
+        return true;
+      },
+      has(target, property) {
+        return property in target || (typeof property === "string" && target._props().has(cssPropertyName(property)));
+      },
+    };
+
+    function createStyle(element) {
+      return new Proxy(new CSSStyleDeclaration(element), STYLE_HANDLER);
+    }
+
+    function normalizeCssName(name) {
+      name = String(name).trim();
+      return name.startsWith("--") ? name : name.toLowerCase();
+    }
+
+    // `backgroundColor` -> `background-color`, `WebkitTransform` -> `-webkit-transform`
+    function cssPropertyName(property) {
+      if (property.startsWith("--")) return property;
+      if (property === "cssFloat") return "float";
+      const name = property.replace(/[A-Z]/g, (c) => "-" + c.toLowerCase());
+      return name.startsWith("ms-") ? "-" + name : name;
+    }
+
+    function parseStyle(text) {
+      const props = new Map();
+      let depth = 0;
+      let quote = null;
+      let start = 0;
+      const declarations = [];
+      for (let i = 0; i <= text.length; i++) {
+        const c = text[i];
+        if (quote) {
+          if (c === quote) quote = null;
+        } else if (c === "\"" || c === "'") {
+          quote = c;
+        } else if (c === "(") {
+          depth++;
+        } else if (c === ")") {
+          depth--;
+        } else if ((c === ";" && depth === 0) || i === text.length) {
+          declarations.push(text.slice(start, i));
+          start = i + 1;
+        }
+      }
+      for (const declaration of declarations) {
+        const colon = declaration.indexOf(":");
+        if (colon === -1) continue;
+        const name = normalizeCssName(declaration.slice(0, colon));
+        let value = declaration.slice(colon + 1).trim();
+        let priority = "";
+        const important = /\s*!\s*important$/i.exec(value);
+        if (important) {
+          value = value.slice(0, important.index);
+          priority = "important";
+        }
+        if (name && value) props.set(name, { value, priority });
+      }
+      return props;
+    }
+
+    function serializeStyle(props) {
+      return [...props].map(([name, { value, priority }]) => name + ": " + value + (priority ? " !important" : "") + ";").join(" ");
+    }
+
+    function createDataset(element) {
+      const attributeName = (property) => "data-" + property.replace(/[A-Z]/g, (c) => "-" + c.toLowerCase());
+      const propertyName = (attribute) => attribute.slice(5).replace(/-([a-z])/g, (_, c) => c.toUpperCase());
+      return new Proxy({}, {
+        get(_, property) {
+          if (typeof property !== "string") return undefined;
+          return element.getAttribute(attributeName(property)) ?? undefined;
+        },
+        set(_, property, value) {
+          element.setAttribute(attributeName(property), String(value));
+          return true;
+        },
+        has(_, property) {
+          return typeof property === "string" && element.hasAttribute(attributeName(property));
+        },
+        deleteProperty(_, property) {
+          element.removeAttribute(attributeName(property));
+          return true;
+        },
+        ownKeys() {
+          return element.getAttributeNames().filter((name) => name.startsWith("data-")).map(propertyName);
+        },
+        getOwnPropertyDescriptor(_, property) {
+          const value = element.getAttribute(attributeName(String(property)));
+          return value === null ? undefined : { value, writable: true, enumerable: true, configurable: true };
+        },
+      });
+    }
+
+    // --- Selectors --------------------------------------------------------------
+
+    const selectorCache = new Map();
+    const IDENT = /(?:--|-?(?:[A-Za-z_ -￿]|\\.))(?:[\w\- -￿]|\\.)*/y;
+    const NTH = /^([+-]?\d*)n\s*(?:([+-])\s*(\d+))?$|^([+-]?\d+)$/;
+    const PSEUDO_CLASSES = new Set([
+      "active", "any-link", "checked", "defined", "disabled", "empty", "enabled", "first-child",
+      "first-of-type", "focus", "focus-visible", "focus-within", "has", "hover", "indeterminate", "is",
+      "last-child", "last-of-type", "link", "not", "nth-child", "nth-last-child", "nth-last-of-type",
+      "nth-of-type", "only-child", "only-of-type", "optional", "placeholder-shown", "read-only",
+      "read-write", "required", "root", "scope", "target", "visited", "where",
+    ]);
+
+    function parseSelector(text) {
+      text = String(text);
+      let parsed = selectorCache.get(text);
+      if (!parsed) {
+        parsed = parseSelectorList(text);
+        if (selectorCache.size >= 1000) selectorCache.clear();
+        selectorCache.set(text, parsed);
+      }
+      return parsed;
+    }
+
+    // Selector lists parse to arrays of complex selectors; each is a list of
+    // compound selectors with the combinator that precedes them.
+    function parseSelectorList(text) {
+      let i = 0;
+      const fail = () => {
+        throw new DOMException("'" + text + "' is not a valid selector", "SyntaxError");
+      };
+      const skipSpace = () => {
+        while (i < text.length && /\s/.test(text[i])) i++;
+      };
+      const ident = () => {
+        IDENT.lastIndex = i;
+        const match = IDENT.exec(text);
+        if (!match) fail();
+        i = IDENT.lastIndex;
+        return match[0].replace(/\\(.)/g, "$1");
+      };
+      const string = () => {
+        const quote = text[i++];
+        let value = "";
+        while (i < text.length && text[i] !== quote) {
+          if (text[i] === "\\") i++;
+          value += text[i++];
+        }
+        if (text[i] !== quote) fail();
+        i++;
+        return value;
+      };
+
+      function list() {
+        const selectors = [];
+        for (;;) {
+          skipSpace();
+          selectors.push(complex());
+          skipSpace();
+          if (text[i] !== ",") return selectors;
+          i++;
+        }
+      }
+
+      function complex() {
+        const parts = [{ combinator: null, compound: compound() }];
+        for (;;) {
+          const start = i;
+          skipSpace();
+          if (i >= text.length || text[i] === "," || text[i] === ")") return parts;
+          let combinator = " ";
+          if (text[i] === ">" || text[i] === "+" || text[i] === "~") {
+            combinator = text[i++];
+            skipSpace();
+          } else if (i === start) {
+            fail();
+          }
+          parts.push({ combinator, compound: compound() });
+        }
+      }
+
+      function compound() {
+        const simple = [];
+        let universal = false;
+        if (text[i] === "*") {
+          universal = true;
+          i++;
+        } else if (/[A-Za-z_\\ -￿-]/.test(text[i] || "")) {
+          simple.push({ type: "tag", name: ident().toLowerCase() });
+        }
+        for (;;) {
+          const c = text[i];
+          if (c === "#") {
+            i++;
+            simple.push({ type: "id", name: ident() });
+          } else if (c === ".") {
+            i++;
+            simple.push({ type: "class", name: ident() });
+          } else if (c === "[") {
+            i++;
+            skipSpace();
+            const attribute = { type: "attr", name: ident().toLowerCase(), op: null, value: null, insensitive: false };
+            skipSpace();
+            if (text[i] !== "]") {
+              const op = /[~|^$*]?=/y;
+              op.lastIndex = i;
+              const match = op.exec(text);
+              if (!match) fail();
+              attribute.op = match[0];
+              i = op.lastIndex;
+              skipSpace();
+              attribute.value = text[i] === "\"" || text[i] === "'" ? string() : ident();
+              skipSpace();
+              if (/[iIsS]/.test(text[i] || "")) {
+                attribute.insensitive = text[i].toLowerCase() === "i";
+                i++;
+                skipSpace();
+              }
+            }
+            if (text[i] !== "]") fail();
+            i++;
+            simple.push(attribute);
+          } else if (c === ":") {
+            i++;
+            if (text[i] === ":") {
+              // Pseudo-elements never match an element
+              i++;
+              ident();
+              simple.push({ type: "never" });
+              continue;
+            }
+            const name = ident().toLowerCase();
+            if (!PSEUDO_CLASSES.has(name)) fail();
+            let arg = null;
+            if (text[i] === "(") {
+              i++;
+              skipSpace();
+              if (name.startsWith("nth-")) {
+                const end = text.indexOf(")", i);
+                if (end === -1) fail();
+                arg = parseNth(text.slice(i, end).trim()) || fail();
+                i = end;
+              } else if (["not", "is", "where", "has"].includes(name)) {
+                arg = list();
+              } else {
+                fail();
+              }
+              skipSpace();
+              if (text[i] !== ")") fail();
+              i++;
+            } else if (name.startsWith("nth-") || ["not", "is", "where", "has"].includes(name)) {
+              fail();
+            }
+            simple.push({ type: "pseudo", name, arg });
+          } else {
+            break;
+          }
+        }
+        if (!universal && simple.length === 0) fail();
+        return simple;
+      }
+
+      const selectors = list();
+      if (i < text.length) fail();
+      return selectors;
+    }
+
+    function parseNth(text) {
+      if (/^odd$/i.test(text)) return { a: 2, b: 1 };
+      if (/^even$/i.test(text)) return { a: 2, b: 0 };
+      const match = NTH.exec(text.replace(/\s+/g, (s, offset) => (offset ? s : "")));
+      if (!match) return null;
+      if (match[4] !== undefined) return { a: 0, b: Number(match[4]) };
+      const a = match[1] === "" || match[1] === "+" ? 1 : match[1] === "-" ? -1 : Number(match[1]);
+      const b = match[3] ? Number(match[2] + match[3]) : 0;
+      return { a, b };
+    }
+
+    function matchesSelectorList(el, list, scope) {
+      return list.some((parts) => matchesComplex(el, parts, parts.length - 1, scope));
+    }
+
+    function matchesComplex(el, parts, index, scope) {
+      if (!parts[index].compound.every((simple) => matchesSimple(el, simple, scope))) return false;
+      if (index === 0) return true;
+      switch (parts[index].combinator) {
+        case ">": {
+          const parent = el.parentElement;
+          return !!parent && matchesComplex(parent, parts, index - 1, scope);
+        }
+        case "+": {
+          const sibling = el.previousElementSibling;
+          return !!sibling && matchesComplex(sibling, parts, index - 1, scope);
+        }
+        case "~":
+          for (let sibling = el.previousElementSibling; sibling; sibling = sibling.previousElementSibling) {
+            if (matchesComplex(sibling, parts, index - 1, scope)) return true;
+          }
+          return false;
+        default:
+          for (let parent = el.parentElement; parent; parent = parent.parentElement) {
+            if (matchesComplex(parent, parts, index - 1, scope)) return true;
+          }
+          return false;
+      }
+    }
+
+    function matchesSimple(el, simple, scope) {
+      switch (simple.type) {
+        case "tag":
+          return el.localName.toLowerCase() === simple.name;
+        case "id":
+          return el.getAttribute("id") === simple.name;
+        case "class":
+          return (el.getAttribute("class") || "").split(/\s+/).includes(simple.name);
+        case "attr": {
+          const attr = el._attrs.find((a) => a.name.toLowerCase() === simple.name);
+          if (!attr) return false;
+          if (!simple.op) return true;
+          let value = attr.value;
+          let wanted = simple.value;
+          if (simple.insensitive) {
+            value = value.toLowerCase();
+            wanted = wanted.toLowerCase();
+          }
+          switch (simple.op) {
+            case "=": return value === wanted;
+            case "~=": return value.split(/\s+/).includes(wanted);
+            case "|=": return value === wanted || value.startsWith(wanted + "-");
+            case "^=": return wanted !== "" && value.startsWith(wanted);
+            case "$=": return wanted !== "" && value.endsWith(wanted);
+            default: return wanted !== "" && value.includes(wanted);
+          }
+        }
+        case "pseudo":
+          return matchesPseudo(el, simple, scope);
+        default:
+          return false;
+      }
+    }
+
+    function matchesPseudo(el, { name, arg }, scope) {
+      const doc = el.ownerDocument;
+      switch (name) {
+        case "not": return !matchesSelectorList(el, arg, scope);
+        case "is": case "where": return matchesSelectorList(el, arg, scope);
+        case "has": return el.querySelectorAll("*").some((d) => matchesSelectorList(d, arg, el));
+        case "scope": return scope ? el === scope : el.parentNode && el.parentNode.nodeType === 9;
+        case "root": return !!el.parentNode && el.parentNode.nodeType === 9;
+        case "empty": return !el.childNodes.some((n) => n.nodeType === 1 || (n.nodeType === 3 && n._data !== ""));
+        case "first-child": return !el.previousElementSibling;
+        case "last-child": return !el.nextElementSibling;
+        case "only-child": return !el.previousElementSibling && !el.nextElementSibling;
+        case "first-of-type": return nthPosition(el, true, false) === 1;
+        case "last-of-type": return nthPosition(el, true, true) === 1;
+        case "only-of-type": return nthPosition(el, true, false) === 1 && nthPosition(el, true, true) === 1;
+        case "nth-child": return nthMatches(arg, nthPosition(el, false, false));
+        case "nth-last-child": return nthMatches(arg, nthPosition(el, false, true));
+        case "nth-of-type": return nthMatches(arg, nthPosition(el, true, false));
+        case "nth-last-of-type": return nthMatches(arg, nthPosition(el, true, true));
+        case "checked":
+          return (el.localName === "input" && (el.type === "checkbox" || el.type === "radio") && el.checked)
+            || (el.localName === "option" && el.selected);
+        case "indeterminate": return el.localName === "input" && !!el.indeterminate;
+        case "disabled": return isDisabled(el);
+        case "enabled": return FORM_CONTROLS.has(el.localName) && !isDisabled(el);
+        case "required": return isRequirable(el) && el.hasAttribute("required");
+        case "optional": return isRequirable(el) && !el.hasAttribute("required");
+        case "read-only": return !isEditable(el);
+        case "read-write": return isEditable(el);
+        case "placeholder-shown": return el.hasAttribute("placeholder") && el.value === "";
+        case "focus": case "focus-visible": return !!doc && doc._focused === el;
+        case "focus-within": return !!doc && !!doc._focused && el.contains(doc._focused);
+        case "link": case "any-link": return (el.localName === "a" || el.localName === "area") && el.hasAttribute("href");
+        case "defined": return true;
+        default: return false;
+      }
+    }
+
+    function isRequirable(el) {
+      return el.namespaceURI === HTML_NS && ["input", "select", "textarea"].includes(el.localName);
+    }
+
+    function isEditable(el) {
+      if (el.localName === "textarea" || (el.localName === "input" && !["checkbox", "radio", "button", "submit", "reset", "image", "hidden", "file", "color", "range"].includes(el.type))) {
+        return !el.hasAttribute("readonly") && !isDisabled(el);
+      }
+      return el.isContentEditable === true;
+    }
+
+    function nthPosition(el, ofType, fromEnd) {
+      const parent = el.parentNode;
+      if (!parent) return 1;
+      let siblings = parent.childNodes.filter((n) => n.nodeType === 1);
+      if (ofType) siblings = siblings.filter((n) => n.localName === el.localName);
+      const index = siblings.indexOf(el);
+      return fromEnd ? siblings.length - index : index + 1;
+    }
+
+    function nthMatches({ a, b }, position) {
+      if (a === 0) return position === b;
+      const n = (position - b) / a;
+      return Number.isInteger(n) && n >= 0;
+    }
+
+    // --- HTML parsing and serialization ---------------------------------------
+
+    function decodeEntities(text) {
+      if (!text.includes("&")) return text;
+      return text.replace(/&(#[xX][0-9a-fA-F]+|#[0-9]+|[A-Za-z][A-Za-z0-9]*);?/g, (match, name) => {
+        if (name[0] === "#") {
+          const code = name[1] === "x" || name[1] === "X" ? parseInt(name.slice(2), 16) : parseInt(name.slice(1), 10);
+          return code > 0 && code <= 0x10ffff ? String.fromCodePoint(code) : "�";
+        }
+        return Object.prototype.hasOwnProperty.call(ENTITIES, name) ? ENTITIES[name] : match;
+      });
+    }
+
+    const START_TAG = /<([A-Za-z][^\s/>]*)/y;
+    const END_TAG = /<\/([A-Za-z][^\s/>]*)[^>]*>?/y;
+    const ATTRIBUTE = /\s*([^\s"'>/=]+)(?:\s*=\s*(?:"([^"]*)"?|'([^']*)'?|([^\s>]+)))?/y;
+
+    // Parse `html` as the children of `context`, like innerHTML does: start
+    // and end tags, attributes, comments, entities, void and raw text elements,
+    // implied end tags and SVG. Table fixups and the like are not done.
+    function parseFragment(html, context) {
+      const doc = context.nodeType === 9 ? context : context.ownerDocument;
+      const fragment = doc.createDocumentFragment();
+      const stack = [fragment];
+      const inSvg = () => {
+        const top = stack[stack.length - 1];
+        const el = top === fragment ? context : top;
+        return el.namespaceURI === SVG_NS && el.localName !== "foreignObject";
+      };
+      const container = () => {
+        const top = stack[stack.length - 1];
+        return top.localName === "template" && top.namespaceURI === HTML_NS ? top.content : top;
+      };
+      const appendText = (text) => {
+        if (!text) return;
+        const parent = container();
+        const last = parent.lastChild;
+        if (last && last.nodeType === 3) last._data += text;
+        else parent.appendChild(doc.createTextNode(text));
+      };
+
+      let i = 0;
+      while (i < html.length) {
+        const lt = html.indexOf("<", i);
+        if (lt === -1) {
+          appendText(decodeEntities(html.slice(i)));
+          break;
+        }
+        appendText(decodeEntities(html.slice(i, lt)));
+        i = lt;
+
+        if (html.startsWith("<!--", i)) {
+          const end = html.indexOf("-->", i + 4);
+          container().appendChild(doc.createComment(html.slice(i + 4, end === -1 ? html.length : end)));
+          i = end === -1 ? html.length : end + 3;
+          continue;
+        }
+        if (html[i + 1] === "!" || html[i + 1] === "?") {
+          const end = html.indexOf(">", i);
+          i = end === -1 ? html.length : end + 1;
+          continue;
+        }
+        if (html[i + 1] === "/") {
+          END_TAG.lastIndex = i;
+          const match = END_TAG.exec(html);
+          if (!match) {
+            appendText("<");
+            i++;
+            continue;
+          }
+          i = END_TAG.lastIndex;
+          const name = match[1].toLowerCase();
+          for (let depth = stack.length - 1; depth > 0; depth--) {
+            if (stack[depth].localName.toLowerCase() === name) {
+              stack.length = depth;
+              break;
+            }
+          }
+          continue;
+        }
+
+        START_TAG.lastIndex = i;
+        const match = START_TAG.exec(html);
+        if (!match) {
+          appendText("<");
+          i++;
+          continue;
+        }
+        i = START_TAG.lastIndex;
+        const svg = inSvg() || match[1].toLowerCase() === "svg";
+        const localName = svg ? match[1] : match[1].toLowerCase();
+        const attributes = [];
+        let selfClosing = false;
+        for (;;) {
+          while (i < html.length && /[\s/]/.test(html[i])) {
+            selfClosing = html[i] === "/";
+            i++;
+          }
+          if (i >= html.length || html[i] === ">") {
+            i++;
+            break;
+          }
+          selfClosing = false;
+          ATTRIBUTE.lastIndex = i;
+          const attribute = ATTRIBUTE.exec(html);
+          if (!attribute) {
+            i++;
+            continue;
+          }
+          i = ATTRIBUTE.lastIndex;
+          const name = svg ? attribute[1] : attribute[1].toLowerCase();
+          const value = attribute[2] ?? attribute[3] ?? attribute[4] ?? "";
+          if (!attributes.some((a) => a.name === name)) attributes.push({ name, value: decodeEntities(value) });
+        }
+
+        if (!svg) {
+          const closes = IMPLIED_END_TAGS[localName];
+          while (closes && stack.length > 1 && closes.includes(stack[stack.length - 1].localName)) stack.pop();
+        }
+        const el = createElementFor(doc, svg ? SVG_NS : HTML_NS, localName, null);
+        for (const { name, value } of attributes) {
+          const colon = name.indexOf(":");
+          const namespace = colon !== -1 && name.slice(0, colon) === "xlink" ? "http://www.w3.org/1999/xlink" : null;
+          el._attrs.push(new Attr(name, value, el, namespace));
+        }
+        container().appendChild(el);
+
+        if (svg ? selfClosing : VOID_ELEMENTS.has(localName)) continue;
+        if (!svg && (RAW_TEXT_ELEMENTS.has(localName) || ESCAPABLE_RAW_TEXT_ELEMENTS.has(localName))) {
+          const close = html.toLowerCase().indexOf("</" + localName, i);
+          const end = close === -1 ? html.length : close;
+          const text = html.slice(i, end);
+          if (text) el.appendChild(doc.createTextNode(RAW_TEXT_ELEMENTS.has(localName) ? text : decodeEntities(text)));
+          const gt = close === -1 ? -1 : html.indexOf(">", close);
+          i = gt === -1 ? html.length : gt + 1;
+          continue;
+        }
+        stack.push(el);
+      }
+      return fragment;
+    }
+
+    function escapeText(text) {
+      return text.replace(/[&<> ]/g, (c) => (c === "&" ? "&amp;" : c === "<" ? "&lt;" : c === ">" ? "&gt;" : "&nbsp;"));
+    }
+
+    function escapeAttribute(value) {
+      return value.replace(/[&" ]/g, (c) => (c === "&" ? "&amp;" : c === "\"" ? "&quot;" : "&nbsp;"));
+    }
+
+    function serializeNode(node) {
+      switch (node.nodeType) {
+        case 1: {
+          const name = node.prefix ? node.prefix + ":" + node.localName : node.localName;
+          let html = "<" + name;
+          for (const attr of node._attrs) html += " " + attr.name + "=\"" + escapeAttribute(attr.value) + "\"";
+          html += ">";
+          if (node.namespaceURI === HTML_NS && VOID_ELEMENTS.has(node.localName)) return html;
+          return html + node._contents().childNodes.map(serializeNode).join("") + "</" + name + ">";
+        }
+        case 3: {
+          const parent = node.parentNode;
+          const raw = parent && parent.nodeType === 1 && parent.namespaceURI === HTML_NS && RAW_TEXT_ELEMENTS.has(parent.localName);
+          return raw ? node._data : escapeText(node._data);
+        }
+        case 8:
+          return "<!--" + node._data + "-->";
+        case 9:
+        case 11:
+          return node.childNodes.map(serializeNode).join("");
+        default:
+          return "";
+      }
+    }
+
+    // --- Document -------------------------------------------------------------
+
+    class Document extends Node {
+      constructor() {
+        super();
+        this.ownerDocument = null;
+        this._window = null;
+        this._focused = null;
+        this._cookies = new Map();
+      }
+
+      get nodeType() { return 9; }
+      get nodeName() { return "#document"; }
+      get textContent() { return null; }
+      set textContent(value) {}
+      get documentElement() { return this.childNodes.find((n) => n.nodeType === 1) || null; }
+      get head() { return this._html("head"); }
+      get body() { return this._html("body"); }
+      get title() {
+        const title = this.querySelector("title");
+        return title ? title.textContent.replace(/\s+/g, " ").trim() : "";
+      }
+      set title(value) {
+        let title = this.querySelector("title");
+        if (!title && this.head) title = this.head.appendChild(this.createElement("title"));
+        if (title) title.textContent = value;
+      }
+      get defaultView() { return this._window; }
+      get activeElement() {
+        return this._focused && this._focused.isConnected ? this._focused : this.body;
+      }
+      get location() { return this._window ? this._window.location : null; }
+      set location(value) { if (this._window) this._window.location.href = value; }
+      get URL() { return this._window ? this._window.location.href : "about:blank"; }
+      get documentURI() { return this.URL; }
+      get baseURI() { return this.URL; }
+      get domain() { return this._window ? this._window.location.hostname : ""; }
+      get referrer() { return ""; }
+      get readyState() { return "complete"; }
+      get visibilityState() { return "visible"; }
+      get hidden() { return false; }
+      get characterSet() { return "UTF-8"; }
+      get charset() { return "UTF-8"; }
+      get contentType() { return "text/html"; }
+      get compatMode() { return "CSS1Compat"; }
+      get doctype() { return null; }
+      get forms() { return this.getElementsByTagName("form"); }
+      get images() { return this.getElementsByTagName("img"); }
+      get cookie() {
+        return [...this._cookies].map(([name, value]) => (name ? name + "=" + value : value)).join("; ");
+      }
+      set cookie(text) {
+        const [pair, ...attributes] = String(text).split(";");
+        const eq = pair.indexOf("=");
+        const name = eq === -1 ? "" : pair.slice(0, eq).trim();
+        const value = (eq === -1 ? pair : pair.slice(eq + 1)).trim();
+        const expired = attributes.some((attribute) => {
+          const [key, val = ""] = attribute.split("=");
+          const lower = key.trim().toLowerCase();
+          return (lower === "max-age" && Number(val) <= 0) || (lower === "expires" && Date.parse(val) <= Date.now());
+        });
+        if (expired) this._cookies.delete(name);
+        else this._cookies.set(name, value);
+      }
+      get implementation() {
+        return {
+          createHTMLDocument: (title) => {
+            const doc = createDocument(null);
+            if (title !== undefined) doc.title = title;
+            return doc;
+          },
+          hasFeature: () => true,
+        };
+      }
+
+      _html(name) {
+        const root = this.documentElement;
+        return root ? root.childNodes.find((n) => n.localName === name) || null : null;
+      }
+      _eventParent() {
+        return this._window;
+      }
+
+      createElement(name) {
+        name = String(name);
+        if (!/^[A-Za-z][^\s/>]*$/.test(name)) {
+          throw new DOMException("Failed to execute 'createElement' on 'Document': The tag name provided ('" + name + "') is not a valid name.", "InvalidCharacterError");
+        }
+        return createElementFor(this, HTML_NS, name.toLowerCase(), null);
+      }
+      createElementNS(namespaceURI, qualifiedName) {
+        const colon = String(qualifiedName).indexOf(":");
+        const prefix = colon === -1 ? null : qualifiedName.slice(0, colon);
+        return createElementFor(this, namespaceURI || null, qualifiedName.slice(colon + 1), prefix);
+      }
+      createTextNode(data) {
+        const node = new Text(data);
+        node.ownerDocument = this;
+        return node;
+      }
+      createComment(data) {
+        const node = new Comment(data);
+        node.ownerDocument = this;
+        return node;
+      }
+      createDocumentFragment() {
+        const node = new DocumentFragment();
+        node.ownerDocument = this;
+        return node;
+      }
+      createEvent(name) {
+        const cls = EVENT_INTERFACES[name];
+        if (!cls) {
+          throw new DOMException("Failed to execute 'createEvent' on 'Document': The provided event type ('" + name + "') is invalid.", "NotSupportedError");
+        }
+        return new cls("");
+      }
+      createRange() {
+        return new Range(this);
+      }
+      createTreeWalker(root, whatToShow, filter) {
+        return new TreeWalker(root, whatToShow, filter);
+      }
+      getElementById(id) { return findById(this, String(id)); }
+      getElementsByName(name) {
+        const found = new NodeList();
+        walkElements(this, (el) => {
+          if (el.getAttribute("name") === name) found.push(el);
+        });
+        return found;
+      }
+      importNode(node, deep) {
+        const copy = node.cloneNode(deep);
+        adopt(copy, this);
+        return copy;
+      }
+      adoptNode(node) {
+        if (node.parentNode) removeNode(node);
+        adopt(node, this);
+        return node;
+      }
+      hasFocus() { return true; }
+      elementFromPoint() { return null; }
+      getSelection() { return this._window ? this._window.getSelection() : null; }
+      execCommand() { return false; }
+      queryCommandSupported() { return false; }
+      _cloneShallow() { return createDocument(null); }
+    }
+    mixin(Document, ParentNode);
+
+    function createDocument(win) {
+      const doc = new Document();
+      doc._window = win;
+      const html = doc.createElement("html");
+      html.appendChild(doc.createElement("head"));
+      html.appendChild(doc.createElement("body"));
+      doc.appendChild(html);
+      return doc;
+    }
+
+    class Range {
+      constructor(doc) {
+        doc = doc || (domWindow && domWindow.document);
+        this.startContainer = doc;
+        this.startOffset = 0;
+        this.endContainer = doc;
+        this.endOffset = 0;
+      }
+
+      get collapsed() { return this.startContainer === this.endContainer && this.startOffset === this.endOffset; }
+      get commonAncestorContainer() {
+        for (let node = this.startContainer; node; node = node.parentNode) {
+          if (node.contains(this.endContainer)) return node;
+        }
+        return null;
+      }
+
+      setStart(node, offset) { this.startContainer = node; this.startOffset = offset; }
+      setEnd(node, offset) { this.endContainer = node; this.endOffset = offset; }
+      setStartBefore(node) { this.setStart(node.parentNode, node.parentNode.childNodes.indexOf(node)); }
+      setStartAfter(node) { this.setStart(node.parentNode, node.parentNode.childNodes.indexOf(node) + 1); }
+      setEndBefore(node) { this.setEnd(node.parentNode, node.parentNode.childNodes.indexOf(node)); }
+      setEndAfter(node) { this.setEnd(node.parentNode, node.parentNode.childNodes.indexOf(node) + 1); }
+      selectNode(node) {
+        this.setStartBefore(node);
+        this.setEndAfter(node);
+      }
+      selectNodeContents(node) {
+        this.setStart(node, 0);
+        this.setEnd(node, node.nodeType === 3 || node.nodeType === 8 ? node.length : node.childNodes.length);
+      }
+      collapse(toStart) {
+        if (toStart) this.setEnd(this.startContainer, this.startOffset);
+        else this.setStart(this.endContainer, this.endOffset);
+      }
+      cloneRange() {
+        const range = new Range(this.startContainer.ownerDocument);
+        range.setStart(this.startContainer, this.startOffset);
+        range.setEnd(this.endContainer, this.endOffset);
+        return range;
+      }
+      createContextualFragment(html) {
+        const start = this.startContainer;
+        const context = start.nodeType === 1 ? start : start.parentElement || (start.ownerDocument || start).body;
+        return parseFragment(String(html), context);
+      }
+      getBoundingClientRect() { return new DOMRect(); }
+      getClientRects() { return []; }
+      detach() {}
+      toString() {
+        if (this.startContainer === this.endContainer && this.startContainer.nodeType === 3) {
+          return this.startContainer._data.slice(this.startOffset, this.endOffset);
+        }
+        return "";
+      }
+    }
+
+    const NodeFilter = {
+      FILTER_ACCEPT: 1, FILTER_REJECT: 2, FILTER_SKIP: 3, SHOW_ALL: 0xffffffff, SHOW_ELEMENT: 0x1,
+      SHOW_ATTRIBUTE: 0x2, SHOW_TEXT: 0x4, SHOW_COMMENT: 0x80, SHOW_DOCUMENT: 0x100,
+      SHOW_DOCUMENT_FRAGMENT: 0x400,
+    };
+
+    class TreeWalker {
+      constructor(root, whatToShow, filter) {
+        this.root = root;
+        this.whatToShow = whatToShow === undefined ? NodeFilter.SHOW_ALL : whatToShow >>> 0;
+        this.filter = filter || null;
+        this.currentNode = root;
+      }
+
+      _accept(node) {
+        if (!(this.whatToShow & (1 << (node.nodeType - 1)))) return NodeFilter.FILTER_SKIP;
+        if (!this.filter) return NodeFilter.FILTER_ACCEPT;
+        return typeof this.filter === "function" ? this.filter(node) : this.filter.acceptNode(node);
+      }
+      _following(node, skipChildren) {
+        if (!skipChildren && node.firstChild) return node.firstChild;
+        for (; node && node !== this.root; node = node.parentNode) {
+          if (node.nextSibling) return node.nextSibling;
+        }
+        return null;
+      }
+
+      nextNode() {
+        let node = this.currentNode;
+        let result = NodeFilter.FILTER_ACCEPT;
+        for (;;) {
+          node = this._following(node, result === NodeFilter.FILTER_REJECT);
+          if (!node) return null;
+          result = this._accept(node);
+          if (result === NodeFilter.FILTER_ACCEPT) return (this.currentNode = node);
+        }
+      }
+      parentNode() {
+        for (let node = this.currentNode; node && node !== this.root;) {
+          node = node.parentNode;
+          if (node && this._accept(node) === NodeFilter.FILTER_ACCEPT) return (this.currentNode = node);
+        }
+        return null;
+      }
+    }
+
+    // --- Window ---------------------------------------------------------------
+
+    function resolveUrl(value, base) {
+      const text = String(value);
+      const current = new URL(base);
+      if (text.startsWith("#")) return new URL(current.origin + current.pathname + current.search + text);
+      if (text.startsWith("?")) return new URL(current.origin + current.pathname + text);
+      return new URL(text, base);
+    }
+
+    class Location {
+      constructor(win) {
+        this._window = win;
+        this._url = new URL(DEFAULT_URL);
+      }
+
+      get href() { return this._url.href; }
+      set href(value) { this.assign(value); }
+      get origin() { return this._url.origin; }
+      get protocol() { return this._url.protocol; }
+      get host() { return this._url.host; }
+      get hostname() { return this._url.hostname; }
+      get port() { return this._url.port; }
+      get pathname() { return this._url.pathname; }
+      set pathname(value) {
+        const path = String(value);
+        this.assign(this.origin + (path.startsWith("/") ? path : "/" + path) + this.search + this.hash);
+      }
+      get search() { return this._url.search; }
+      set search(value) {
+        const search = String(value);
+        this.assign(this.origin + this.pathname + (search && !search.startsWith("?") ? "?" + search : search) + this.hash);
+      }
+      get hash() { return this._url.hash; }
+      set hash(value) {
+        const hash = String(value);
+        this.assign("#" + hash.replace(/^#/, ""));
+      }
+
+      // Navigation only changes the URL; a new fragment fires "hashchange"
+      assign(url) {
+        const previous = this._url;
+        this._url = resolveUrl(url, previous.href);
+        const sameDocument = previous.href.split("#")[0] === this._url.href.split("#")[0];
+        if (sameDocument && previous.hash !== this._url.hash) {
+          const init = { oldURL: previous.href, newURL: this._url.href };
+          queueMicrotask(() => this._window.dispatchEvent(new HashChangeEvent("hashchange", init)));
+        }
+      }
+      replace(url) { this.assign(url); }
+      reload() {}
+      toString() { return this.href; }
+    }
+
+    class History {
+      constructor(win) {
+        this._window = win;
+        this._entries = [{ state: null, url: DEFAULT_URL }];
+        this._index = 0;
+        this.scrollRestoration = "auto";
+      }
+
+      get length() { return this._entries.length; }
+      get state() { return this._entries[this._index].state; }
+
+      pushState(state, title, url) {
+        this._entries.length = this._index + 1;
+        this._entries.push({ state: state ?? null, url: this._resolve(url) });
+        this._index++;
+      }
+      replaceState(state, title, url) {
+        this._entries[this._index] = { state: state ?? null, url: this._resolve(url) };
+      }
+      back() { this.go(-1); }
+      forward() { this.go(1); }
+      go(delta) {
+        const index = this._index + (Number(delta) || 0);
+        if (index === this._index || index < 0 || index >= this._entries.length) return;
+        this._index = index;
+        const entry = this._entries[index];
+        this._window.location._url = new URL(entry.url);
+        queueMicrotask(() => this._window.dispatchEvent(new PopStateEvent("popstate", { state: entry.state })));
+      }
+
+      _resolve(url) {
+        const location = this._window.location;
+        if (url !== undefined && url !== null) location._url = resolveUrl(url, location.href);
+        return location.href;
+      }
+    }
+
+    class Storage {
+      constructor() {
+        this._items = new Map();
+      }
+
+      get length() { return this._items.size; }
+      key(index) { return [...this._items.keys()][index] ?? null; }
+      getItem(key) { return this._items.get(String(key)) ?? null; }
+      setItem(key, value) { this._items.set(String(key), String(value)); }
+      removeItem(key) { this._items.delete(String(key)); }
+      clear() { this._items.clear(); }
+    }
+
+    class Window extends EventTarget {
+      get location() { return this._location; }
+      set location(value) { this._location.assign(value); }
+    }
+
+    // The window falls back to the real global for anything it doesn't
+    // define, so window.setTimeout and window.JSON work as in a browser.
+    const WINDOW_HANDLER = {
+      get(target, property) {
+        return property in target ? target[property] : globalThis[property];
+      },
+      set(target, property, value) {
+        if (property in target) target[property] = value;
+        else globalThis[property] = value;
+        return true;
+      },
+      has(target, property) {
+        return property in target || property in globalThis;
+      },
+    };
+
+    const DOM_INTERFACES = {
+      Attr, CharacterData, Comment, CSSStyleDeclaration, Document, DocumentFragment, DOMRect, DOMTokenList,
+      Element, EventTarget, History, HTMLCollection, HTMLElement, Location, MutationObserver, NamedNodeMap,
+      Node, NodeFilter, NodeList, Range, Storage, SVGElement, SVGSVGElement, Text, TreeWalker, Window,
+      ...ELEMENT_INTERFACES,
+    };
+    for (const [name, cls] of Object.entries(EVENT_INTERFACES)) {
+      if (cls.name === name) DOM_INTERFACES[name] = cls;
+    }
+
+    // A fresh window and document. install() creates the one tests see.
+    function createWindow() {
+      const target = new Window();
+      const win = new Proxy(target, WINDOW_HANDLER);
+      const selection = {
+        rangeCount: 0, anchorNode: null, anchorOffset: 0, focusNode: null, focusOffset: 0,
+        isCollapsed: true, type: "None",
+        addRange() {}, removeAllRanges() {}, removeRange() {}, collapse() {}, extend() {},
+        getRangeAt() { throw new DOMException("The index provided (0) is not less than the range count (0).", "IndexSizeError"); },
+        toString() { return ""; },
+      };
+      Object.assign(target, DOM_INTERFACES, {
+        window: win, self: win, top: win, parent: win, frames: win, frameElement: null,
+        document: createDocument(win),
+        _location: new Location(win),
+        history: new History(win),
+        navigator: {
+          userAgent: "Mozilla/5.0 (" + process.platform + ") AppleWebKit/537.36 (KHTML, like Gecko) howth",
+          language: "en-US", languages: ["en-US", "en"], platform: "", vendor: "", onLine: true,
+          cookieEnabled: true, maxTouchPoints: 0, hardwareConcurrency: 4,
+        },
+        screen: { width: 1024, height: 768, availWidth: 1024, availHeight: 768, colorDepth: 24, pixelDepth: 24 },
+        localStorage: new Storage(),
+        sessionStorage: new Storage(),
+        name: "", closed: false, origin: "http://localhost", devicePixelRatio: 1,
+        innerWidth: 1024, innerHeight: 768, outerWidth: 1024, outerHeight: 768,
+        scrollX: 0, scrollY: 0, pageXOffset: 0, pageYOffset: 0, screenX: 0, screenY: 0,
+        getComputedStyle(element) {
+          const style = new CSSStyleDeclaration(null);
+          style._detached = new Map(element.style._props());
+          if (element.hidden && !style._detached.has("display")) style._detached.set("display", { value: "none", priority: "" });
+          return new Proxy(style, STYLE_HANDLER);
+        },
+        matchMedia(media) {
+          return {
+            matches: false, media: String(media), onchange: null,
+            addListener() {}, removeListener() {}, addEventListener() {}, removeEventListener() {},
+            dispatchEvent() { return true; },
+          };
+        },
+        requestAnimationFrame(callback) {
+          return setTimeout(() => callback(performance.now()), 16);
+        },
+        cancelAnimationFrame(id) { clearTimeout(id); },
+        getSelection() { return selection; },
+        scrollTo() {}, scroll() {}, scrollBy() {},
+        alert() {}, confirm() { return false; }, prompt() { return null; }, print() {},
+        open() { return null; }, close() {}, focus() {}, blur() {},
+      });
+      windowTargets.set(win, target);
+      return win;
+    }
+
+    // Define the window's properties as globals that read through to it, so
+    // tests can replace `window.matchMedia` and code calling `matchMedia()`
+    // sees the replacement. Without a window this creates a fresh one; the
+    // runner passes a file's own window back in when that file's tests run.
+    function install(win) {
+      domWindow = win || createWindow();
+      if (installed) return domWindow;
+      installed = new Map();
+      const names = Object.keys(DOM_INTERFACES).concat(
+        Object.keys(windowTargets.get(domWindow)).filter((name) => !name.startsWith("_")),
+        ["location", "addEventListener", "removeEventListener", "dispatchEvent"],
+        EVENT_HANDLERS.map((name) => "on" + name),
+      );
+      for (const name of new Set(names)) {
+        installed.set(name, Object.getOwnPropertyDescriptor(globalThis, name));
+        const bound = name === "addEventListener" || name === "removeEventListener" || name === "dispatchEvent";
+        Object.defineProperty(globalThis, name, {
+          get() {
+            const value = domWindow[name];
+            return bound ? value.bind(domWindow) : value;
+          },
+          set(value) { domWindow[name] = value; },
+          configurable: true,
+        });
+      }
+      return domWindow;
+    }
+
+    function uninstall() {
+      if (!installed) return;
+      for (const [name, descriptor] of installed) {
+        if (descriptor) Object.defineProperty(globalThis, name, descriptor);
+        else delete globalThis[name];
+      }
+      installed = null;
+    }
+
+    globalThis.__howth_dom = {
+      install,
+      uninstall,
+      get installed() { return installed !== null; },
+    };
+
+    // howth:dom - the DOM-lite classes, plus createWindow() for a separate
+    // window and document
+    globalThis.__howth_modules["howth:dom"] = { createWindow, ...DOM_INTERFACES };
+  })();
+
+  // node:test module — lightweight test runner for howth's native V8 executor.
+  // Implements test(), describe(), it(), before()/beforeAll(), after()/afterAll(),
+  // beforeEach() and afterEach(), with hooks ordered as in Jest across nested suites,
+  // a Jest-style expect(), and fake timers (howth:test).
+  // Results are collected in globalThis.__howth_test_results after __howth_run_tests().
+  (function() {
+    const suiteStack = []; // stack of { name, before, after, beforeEach, afterEach, children }
+    let rootSuite = { name: '<root>', before: [], after: [], beforeEach: [], afterEach: [], children: [] };
+    let currentSuite = rootSuite;
+    let hasOnly = false; // tracks whether any .only() was registered
+    let currentFile = null; // { type: 'file', path, fakeTimers } marker of the file being loaded
+    let running = false; // true while __howth_run_tests() executes tests
+
+    function pushSuite(name) {
+      const suite = { name, before: [], after: [], beforeEach: [], afterEach: [], children: [] };
+      currentSuite.children.push({ type: 'suite', suite });
+      suiteStack.push(currentSuite);
+      currentSuite = suite;
+      return suite;
+    }
+
+    function popSuite() {
+      currentSuite = suiteStack.pop() || rootSuite;
+    }
+
+    // Default timeouts for tests and each-hooks, and for all-hooks.
+    const DEFAULT_TEST_TIMEOUT = 30000;
+    const DEFAULT_ALL_HOOK_TIMEOUT = 120000;
+    const MAX_CONCURRENCY = 5;
+
+    // Timeout from a number (Jest: `fn, 5000`) or `{ timeout }` (node:test)
+    function timeoutOption(option) {
+      if (typeof option === 'number') return option;
+      return option && typeof option.timeout === 'number' ? option.timeout : undefined;
+    }
+
+    // test(name, [options], fn) or test(name, fn, [timeout | options]).
+    // Options: skip, todo, only, timeout, retry, concurrent.
+    function test(name, optionsOrFn, maybeFn) {
+      let fn, options;
+      if (typeof optionsOrFn === 'function') {
         fn = optionsOrFn;
-        options = {};
+        options = typeof maybeFn === 'number' ? { timeout: maybeFn } : (maybeFn || {});
       } else {
         options = optionsOrFn || {};
         fn = maybeFn;
       }
       const skip = options.skip === true || (typeof options.skip === 'string');
       const todo = options.todo === true || (typeof options.todo === 'string');
-      const entry = { type: 'test', name, fn, skip: skip || todo, only: !!options.only };
+      const entry = {
+        type: 'test',
+        name,
+        fn,
+        skip: skip || todo,
+        only: !!options.only,
+        concurrent: !!options.concurrent,
+        _timeout: timeoutOption(options),
+        _retry: typeof options.retry === 'number' ? options.retry : undefined,
+      };
       currentSuite.children.push(entry);
       if (options.only) hasOnly = true;
       // Return chainable object for Mocha compatibility (e.g. it("x", fn).timeout(5000))
       return {
         timeout(ms) { entry._timeout = ms; return this; },
-        retries(n) { return this; },
+        retries(n) { entry._retry = n; return this; },
         slow(ms) { return this; },
       };
     }
@@ -14710,39 +19575,161 @@
       constructor() { super("skip"); this.name = "__HowthSkipError"; }
     }
 
-    // Mocha-compatible context object for describe/before/it callbacks
-    function _makeMochaContext(suite) {
+    // Mocha-compatible context object for describe/before/it callbacks.
+    // `scope` holds the fixtures of the running test or all-hook.
+    function _makeMochaContext(suite, scope) {
       return {
         timeout(ms) { if (suite) suite._timeout = ms; return this; },
-        retries(n) { return this; },
+        retries(n) { if (suite) suite._retries = n; return this; },
         slow(ms) { return this; },
         skip() { throw new __HowthSkipError(); },
+        tmpdir() { return scopeTmpdir(scope || activeScope('this.tmpdir')); },
+        __howth_scope: scope,
       };
     }
 
+    // Fixture scopes: one per test attempt, and one per suite for its
+    // beforeAll/afterAll hooks. Releasing a scope runs its cleanups (last
+    // registered first) and removes its temp dir, whether or not it passed.
+    const activeScopes = new Set();
+
+    function openScope(label) {
+      const scope = { label, tmpdir: null, cleanups: [], fixtures: new Map() };
+      activeScopes.add(scope);
+      return scope;
+    }
+
+    // The scope of whatever is running, for calls made without a test context
+    function activeScope(name) {
+      if (activeScopes.size === 1) return activeScopes.values().next().value;
+      throw new Error(activeScopes.size === 0
+        ? name + '() must be called while a test or hook runs'
+        : name + '() is ambiguous while concurrent tests run; use this.tmpdir() or pass `this`');
+    }
+
+    function scopeTmpdir(scope) {
+      if (!scope.tmpdir) scope.tmpdir = ops.op_howth_test_tmpdir_create(scope.label);
+      return scope.tmpdir;
+    }
+
+    // Returns the first cleanup error, if any
+    async function releaseScope(scope) {
+      activeScopes.delete(scope);
+      let error = null;
+      for (const cleanup of scope.cleanups.reverse()) {
+        try {
+          await callWithTimeout(() => cleanup(), null, DEFAULT_TEST_TIMEOUT, 'Fixture cleanup');
+        } catch (e) {
+          if (!error) error = e;
+        }
+      }
+      if (scope.tmpdir) {
+        if (globalThis.__howth_keep_tmp) {
+          console.error("[howth] kept temp dir: " + scope.tmpdir);
+        } else {
+          try {
+            ops.op_howth_test_tmpdir_remove(scope.tmpdir);
+          } catch (e) {
+            if (!error) error = e;
+          }
+        }
+      }
+      return error;
+    }
+
+    // test.tmpdir(): an empty directory private to the running test (or
+    // beforeAll/afterAll hook), removed when it ends
+    test.tmpdir = function tmpdir(ctx) {
+      return scopeTmpdir((ctx && ctx.__howth_scope) || activeScope('test.tmpdir'));
+    };
+
+    // test.fixture(factory): returns use([ctx]), which calls
+    // factory({ tmpdir, cleanup }) once per test and returns its value.
+    // Functions passed to cleanup() run when the test ends.
+    test.fixture = function fixture(factory) {
+      function use(ctx) {
+        const scope = (ctx && ctx.__howth_scope) || activeScope('fixture');
+        if (!scope.fixtures.has(use)) {
+          scope.fixtures.set(use, factory({
+            tmpdir: () => scopeTmpdir(scope),
+            cleanup: (fn) => { scope.cleanups.push(fn); },
+          }));
+        }
+        return scope.fixtures.get(use);
+      }
+      return use;
+    };
+
+    // test.concurrent(): runs alongside the neighbouring concurrent tests of
+    // its suite, at most MAX_CONCURRENCY at a time
+    test.concurrent = function concurrent(name, optionsOrFn, maybeFn) {
+      if (typeof optionsOrFn === 'function') {
+        const options = typeof maybeFn === 'number' ? { timeout: maybeFn } : (maybeFn || {});
+        return test(name, optionsOrFn, { ...options, concurrent: true });
+      }
+      return test(name, { ...optionsOrFn, concurrent: true }, maybeFn);
+    };
+
     // it() is an alias for test()
     const it = test;
 
-    function before(fn) {
+    // Hook names used in failure messages
+    const HOOK_LABELS = { before: 'beforeAll', after: 'afterAll', beforeEach: 'beforeEach', afterEach: 'afterEach' };
+
+    // Hooks take an optional timeout: a number (Jest) or `{ timeout }` (node:test)
+    function addHook(kind, fn, option) {
       const suite = currentSuite;
-      // Wrap fn to be called with Mocha context
-      const wrapped = function() { return fn.call(_makeMochaContext(suite)); };
-      suite.before.push(wrapped);
+      suite[kind].push({ kind, fn, suite, timeout: timeoutOption(option) });
     }
-    function after(fn) {
-      const suite = currentSuite;
-      const wrapped = function() { return fn.call(_makeMochaContext(suite)); };
-      suite.after.push(wrapped);
+    function before(fn, options) { addHook('before', fn, options); }
+    function after(fn, options) { addHook('after', fn, options); }
+    function beforeEach(fn, options) { addHook('beforeEach', fn, options); }
+    function afterEach(fn, options) { addHook('afterEach', fn, options); }
+
+    // Jest/Vitest names for the suite-level hooks
+    const beforeAll = before;
+    const afterAll = after;
+
+    // Call fn (sync, promise-returning or done-callback style) with a timeout.
+    // A timeout aborts `controller`, so code watching its signal can stop.
+    function callWithTimeout(fn, ctx, timeoutMs, label, controller) {
+      return new Promise((resolve, reject) => {
+        let settled = false;
+        const timer = realSetTimeout(() => {
+          const err = new Error(label + ' timed out after ' + timeoutMs + 'ms');
+          if (controller) controller.abort(err);
+          finish(err);
+        }, timeoutMs);
+        function finish(err) {
+          if (settled) return;
+          settled = true;
+          realClearTimeout(timer);
+          if (err) reject(err); else resolve();
+        }
+        try {
+          if (fn.length > 0) {
+            fn.call(ctx, (err) => finish(err));
+          } else {
+            const r = fn.call(ctx);
+            if (r && typeof r.then === 'function') {
+              r.then(() => finish(), (e) => finish(e || new Error(label + ' rejected')));
+            } else {
+              finish();
+            }
+          }
+        } catch (e) {
+          finish(e);
+        }
+      });
     }
-    function beforeEach(fn) {
-      const suite = currentSuite;
-      const wrapped = function() { return fn.call(_makeMochaContext(suite)); };
-      suite.beforeEach.push(wrapped);
+
+    function runHook(hook, defaultTimeout, scope) {
+      const timeout = hook.timeout || hook.suite._timeout || defaultTimeout;
+      return callWithTimeout(hook.fn, _makeMochaContext(hook.suite, scope), timeout, HOOK_LABELS[hook.kind] + ' hook');
     }
-    function afterEach(fn) {
-      const suite = currentSuite;
-      const wrapped = function() { return fn.call(_makeMochaContext(suite)); };
-      suite.afterEach.push(wrapped);
+
+    function errorText(e) {
+      return (e && (e.stack || e.message)) || String(e);
     }
 
     // Execute all registered tests and return results
@@ -14751,117 +19738,206 @@
       const results = [];
       const startTime = performance.now();
 
-      async function runSuite(suite, parentBeforeEach, parentAfterEach, prefix) {
-        if (suite.skip) {
-          // Mark all children as skipped
-          function skipAll(s, pfx) {
-            for (const child of s.children) {
-              if (child.type === 'test') {
-                results.push({ name: pfx ? pfx + ' > ' + child.name : child.name, status: 'skip', duration_ms: 0, error: null });
-              } else if (child.type === 'suite') {
-                skipAll(child.suite, pfx ? pfx + ' > ' + child.suite.name : child.suite.name);
-              }
-            }
+      // Record every test under `suite` with `status` (skipped tests stay skipped)
+      function markAll(suite, prefix, status, error) {
+        for (const child of suite.children) {
+          if (child.type === 'test') {
+            const fullName = prefix ? prefix + ' > ' + child.name : child.name;
+            const skipped = child.skip || suite.skip || status === 'skip';
+            results.push({ name: fullName, status: skipped ? 'skip' : status, duration_ms: 0, error: skipped ? null : error });
+          } else if (child.type === 'suite') {
+            const childPrefix = prefix ? prefix + ' > ' + child.suite.name : child.suite.name;
+            markAll(child.suite, childPrefix, child.suite.skip ? 'skip' : status, error);
           }
-          skipAll(suite, prefix);
-          return;
         }
+      }
 
-        const allBeforeEach = parentBeforeEach.concat(suite.beforeEach);
-        const allAfterEach = suite.afterEach.concat(parentAfterEach);
+      // Run one test, re-running it up to its retry count while it fails.
+      // A test that passes on a retry is reported as flaky.
+      async function runTest(child, suite, beforeEachHooks, afterEachHooks, fullName, suiteTimeout, suiteRetries) {
+        if (child.skip) {
+          return { name: fullName, status: 'skip', duration_ms: 0, error: null };
+        }
+
+        const maxRetries = child._retry !== undefined ? child._retry : suiteRetries || 0;
+        const t0 = performance.now();
+        let attempt = await runAttempt(child, suite, beforeEachHooks, afterEachHooks, fullName, suiteTimeout);
+        let retries = 0;
+        while (attempt.status === 'fail' && retries < maxRetries) {
+          retries++;
+          console.error("[howth] retrying test (" + retries + "/" + maxRetries + "): " + fullName);
+          attempt = await runAttempt(child, suite, beforeEachHooks, afterEachHooks, fullName, suiteTimeout);
+        }
+
+        const duration_ms = performance.now() - t0;
+        const result = { name: fullName, status: attempt.status, duration_ms, error: attempt.error };
+        if (attempt.diff) result.diff = attempt.diff;
+        if (retries > 0) {
+          result.retries = retries;
+          result.flaky = attempt.status === 'pass';
+        }
+        return result;
+      }
 
-        // Run suite-level before hooks
-        for (let _hi = 0; _hi < suite.before.length; _hi++) {
-          const hook = suite.before[_hi];
-          console.error("[howth] running before hook " + _hi + " for suite: " + (prefix || "(root)"));
+      // One attempt: beforeEach hooks (outermost first), the test, then
+      // afterEach hooks (innermost first). afterEach hooks always run; the
+      // first failure is attributed to the test.
+      async function runAttempt(child, suite, beforeEachHooks, afterEachHooks, fullName, suiteTimeout) {
+        let status = 'pass';
+        let error = null;
+        let diff = null;
+        function fail(e, what) {
+          if (e instanceof __HowthSkipError) {
+            if (status === 'pass') status = 'skip';
+            return;
+          }
+          if (status === 'fail') return;
+          status = 'fail';
+          error = what ? what + ': ' + errorText(e) : errorText(e);
+          if (e && e.__howth_diff) diff = e.__howth_diff;
+        }
+
+        console.error("[howth] running test: " + fullName);
+        const scope = openScope(fullName);
+        const eachTimeout = suiteTimeout || DEFAULT_TEST_TIMEOUT;
+        let ready = true;
+        for (const hook of beforeEachHooks) {
           try {
-            const _hr = hook();
-            if (_hr && typeof _hr.then === 'function') {
-              const _ht = suite._timeout || 120000;
-              await Promise.race([_hr, new Promise((_, rej) => globalThis.__nativeSetTimeout(() => rej(new Error("before hook timed out after " + _ht + "ms")), _ht))]);
-            }
-            console.error("[howth] before hook " + _hi + " completed for: " + (prefix || "(root)"));
+            await runHook(hook, eachTimeout, scope);
           } catch (e) {
-            // If before hook fails, mark all tests in this suite (recursively) as failed
-            function failAll(s, pfx) {
-              for (const child of s.children) {
-                if (child.type === 'test') {
-                  const fullName = pfx ? pfx + ' > ' + child.name : child.name;
-                  results.push({ name: fullName, status: 'fail', duration_ms: 0, error: 'before hook failed: ' + (e.stack || e.message || String(e)) });
-                } else if (child.type === 'suite') {
-                  failAll(child.suite, pfx ? pfx + ' > ' + child.suite.name : child.suite.name);
-                }
-              }
-            }
-            failAll(suite, prefix);
-            return;
+            fail(e, 'beforeEach hook failed');
+            ready = false;
+            break;
           }
         }
 
-        for (const child of suite.children) {
-          if (child.type === 'test') {
-            const fullName = prefix ? prefix + ' > ' + child.name : child.name;
-            if (child.skip) {
-              results.push({ name: fullName, status: 'skip', duration_ms: 0, error: null });
-              continue;
-            }
+        if (ready && child.fn) {
+          const timeout = child._timeout || suiteTimeout || DEFAULT_TEST_TIMEOUT;
+          // this.signal aborts when the test times out
+          const controller = new AbortController();
+          const ctx = _makeMochaContext(suite, scope);
+          ctx.signal = controller.signal;
+          try {
+            await callWithTimeout(child.fn, ctx, timeout, 'Test', controller);
+          } catch (e) {
+            fail(e, null);
+          }
+        }
 
-            const t0 = performance.now();
-            let status = 'pass';
-            let error = null;
+        for (const hook of afterEachHooks) {
+          try {
+            await runHook(hook, eachTimeout, scope);
+          } catch (e) {
+            fail(e, 'afterEach hook failed');
+          }
+        }
 
-            console.error("[howth] running test: " + fullName);
-            try {
-              for (const hook of allBeforeEach) {
-                const _r = hook();
-                if (_r && typeof _r.then === 'function') await Promise.race([_r, new Promise((_, rej) => globalThis.__nativeSetTimeout(() => rej(new Error("beforeEach timed out after 30s")), 30000))]);
-              }
-              if (child.fn) {
-                const ctx = _makeMochaContext(suite);
-                let result;
-                if (child.fn.length > 0) {
-                  result = new Promise((resolve, reject) => {
-                    const _timeout = globalThis.__nativeSetTimeout(() => reject(new Error("Test timed out after 30s")), 30000);
-                    child.fn.call(ctx, (err) => { globalThis.__nativeClearTimeout(_timeout); err ? reject(err) : resolve(); });
-                  });
-                } else {
-                  result = child.fn.call(ctx);
-                }
-                if (result && typeof result.then === 'function') {
-                  const _testTimeout = new Promise((_, rej) => globalThis.__nativeSetTimeout(() => rej(new Error("Test timed out after 30s")), 30000));
-                  await Promise.race([result, _testTimeout]);
-                }
-              }
-            } catch (e) {
-              if (e instanceof __HowthSkipError) {
-                status = 'skip';
-              } else {
-                status = 'fail';
-                error = e && (e.stack || e.message || String(e));
-              }
+        const cleanupError = await releaseScope(scope);
+        if (cleanupError) fail(cleanupError, 'cleanup failed');
+
+        return { status, error, diff };
+      }
+
+      // Run async tasks with at most MAX_CONCURRENCY in flight; results keep task order
+      async function runConcurrently(tasks) {
+        const out = new Array(tasks.length);
+        let next = 0;
+        async function worker() {
+          while (next < tasks.length) {
+            const i = next++;
+            out[i] = await tasks[i]();
+          }
+        }
+        const workers = [];
+        for (let i = 0; i < Math.min(MAX_CONCURRENCY, tasks.length); i++) workers.push(worker());
+        await Promise.all(workers);
+        return out;
+      }
+
+      async function runSuite(suite, parentBeforeEach, parentAfterEach, prefix, parentTimeout, parentRetries) {
+        if (suite.skip) {
+          markAll(suite, prefix, 'skip', null);
+          return;
+        }
+
+        const suiteTimeout = suite._timeout || parentTimeout;
+        const suiteRetries = suite._retries !== undefined ? suite._retries : parentRetries;
+        const allBeforeEach = parentBeforeEach.concat(suite.beforeEach);
+        const allAfterEach = suite.afterEach.concat(parentAfterEach);
+
+        // beforeAll hooks; a failure fails every test in this suite and its
+        // nested suites, which then don't run
+        let setupStatus = null;
+        let setupError = null;
+        const scope = openScope(prefix || 'root');
+        for (const hook of suite.before) {
+          try {
+            await runHook(hook, DEFAULT_ALL_HOOK_TIMEOUT, scope);
+          } catch (e) {
+            if (e instanceof __HowthSkipError) {
+              setupStatus = 'skip';
+            } else {
+              setupStatus = 'fail';
+              setupError = 'beforeAll hook failed: ' + errorText(e);
             }
+            break;
+          }
+        }
 
-            try {
-              for (const hook of allAfterEach) await hook();
-            } catch (e) {
-              if (status === 'pass') {
-                status = 'fail';
-                error = 'afterEach hook failed: ' + (e.message || String(e));
+        // Tests use their own scopes; the suite's comes back for afterAll
+        activeScopes.delete(scope);
+        if (setupStatus) {
+          markAll(suite, prefix, setupStatus, setupError);
+        } else {
+          const testName = (child) => (prefix ? prefix + ' > ' + child.name : child.name);
+          const children = suite.children;
+          for (let i = 0; i < children.length; i++) {
+            const child = children[i];
+            if (child.type === 'test' && child.concurrent) {
+              // Neighbouring concurrent tests run together
+              const group = [];
+              while (i < children.length && children[i].type === 'test' && children[i].concurrent) {
+                group.push(children[i++]);
               }
+              i--;
+              const groupResults = await runConcurrently(group.map((c) => () =>
+                runTest(c, suite, allBeforeEach, allAfterEach, testName(c), suiteTimeout, suiteRetries)));
+              results.push(...groupResults);
+            } else if (child.type === 'test') {
+              results.push(await runTest(child, suite, allBeforeEach, allAfterEach, testName(child), suiteTimeout, suiteRetries));
+            } else if (child.type === 'suite') {
+              const childPrefix = prefix ? prefix + ' > ' + child.suite.name : child.suite.name;
+              await runSuite(child.suite, allBeforeEach, allAfterEach, childPrefix, suiteTimeout, suiteRetries);
+            } else if (child.type === 'file') {
+              // Each file starts with real timers, or the fake ones it asked for at load,
+              // and with its own environment
+              globalThis.__howth_fake_clock.uninstall();
+              if (child.fakeTimers) globalThis.__howth_fake_clock.install(child.fakeTimers.now);
+              if (child.window) globalThis.__howth_dom.install(child.window);
+              else globalThis.__howth_dom.uninstall();
             }
-
-            const duration_ms = performance.now() - t0;
-            results.push({ name: fullName, status, duration_ms, error });
-          } else if (child.type === 'suite') {
-            const childPrefix = prefix ? prefix + ' > ' + child.suite.name : child.suite.name;
-            await runSuite(child.suite, allBeforeEach, allAfterEach, childPrefix);
           }
         }
 
-        // Run suite-level after hooks
+        // afterAll hooks run even when setup failed. Their tests already have
+        // results, so a failure is reported as its own entry for the suite.
+        const hookFailed = (error) => results.push({
+          name: (prefix ? prefix + ' > ' : '') + '"afterAll" hook',
+          status: 'fail',
+          duration_ms: 0,
+          error,
+        });
+        activeScopes.add(scope);
         for (const hook of suite.after) {
-          try { await hook(); } catch (e) { /* after hook errors are swallowed */ }
+          try {
+            await runHook(hook, DEFAULT_ALL_HOOK_TIMEOUT, scope);
+          } catch (e) {
+            if (e instanceof __HowthSkipError) continue;
+            hookFailed('afterAll hook failed: ' + errorText(e));
+          }
         }
+        const cleanupError = await releaseScope(scope);
+        if (cleanupError) hookFailed('cleanup failed: ' + errorText(cleanupError));
       }
 
       // If any .only was registered, mark non-only tests/suites as skipped
@@ -14891,7 +19967,14 @@
         applyOnly(rootSuite);
       }
 
-      await runSuite(rootSuite, [], [], '');
+      running = true;
+      try {
+        await runSuite(rootSuite, [], [], '', undefined, undefined);
+      } finally {
+        running = false;
+        globalThis.__howth_fake_clock.uninstall();
+        globalThis.__howth_dom.uninstall();
+      }
 
       const totalDuration = performance.now() - startTime;
       let passed = 0, failed = 0, skipped = 0;
@@ -14918,6 +20001,7 @@
       currentSuite = rootSuite;
       suiteStack.length = 0;
       hasOnly = false;
+      currentFile = null;
 
       return report;
     }
@@ -14938,6 +20022,455 @@
       restoreAll() {},
     };
 
+    // expect() — Jest-style assertions. Failed comparisons throw an
+    // AssertionError whose __howth_diff ({ matcher, expected, actual }) the
+    // runner copies into the test result for the CLI to render as a diff.
+    class HowthAssertionError extends Error {
+      constructor(message, diff) {
+        super(message);
+        this.name = 'AssertionError';
+        if (diff) this.__howth_diff = diff;
+      }
+    }
+
+    // Asymmetric matchers (expect.any(), expect.objectContaining(), ...)
+    class AsymmetricMatcher {
+      constructor(name, sample, match, describe) {
+        this.name = name;
+        this.sample = sample;
+        this.match = match;
+        this.describe = describe;
+      }
+      asymmetricMatch(other) { return this.match(other); }
+      toString() { return this.describe(); }
+    }
+
+    function isAsymmetric(value) {
+      return value instanceof AsymmetricMatcher
+        || (value !== null && typeof value === 'object' && typeof value.asymmetricMatch === 'function');
+    }
+
+    function typeName(ctor) {
+      return (ctor && ctor.name) || 'Anonymous';
+    }
+
+    function anyMatches(ctor, other) {
+      if (ctor === String) return typeof other === 'string' || other instanceof String;
+      if (ctor === Number) return typeof other === 'number' || other instanceof Number;
+      if (ctor === Boolean) return typeof other === 'boolean' || other instanceof Boolean;
+      if (ctor === BigInt) return typeof other === 'bigint';
+      if (ctor === Symbol) return typeof other === 'symbol';
+      if (ctor === Function) return typeof other === 'function';
+      if (ctor === Object) return other !== null && typeof other === 'object';
+      return other instanceof ctor;
+    }
+
+    // Pretty-print a value for failure messages and diffs, one property per
+    // line so nested values diff line by line.
+    function formatValue(value, indent, seen) {
+      indent = indent || '';
+      seen = seen || [];
+      const inner = indent + '  ';
+      if (isAsymmetric(value)) return String(value);
+      if (value === null) return 'null';
+      if (value === undefined) return 'undefined';
+      switch (typeof value) {
+        case 'string': return JSON.stringify(value);
+        case 'number': return Object.is(value, -0) ? '-0' : String(value);
+        case 'boolean': return String(value);
+        case 'bigint': return value + 'n';
+        case 'symbol': return value.toString();
+        case 'function': return '[Function ' + (value.name || 'anonymous') + ']';
+      }
+      if (seen.includes(value)) return '[Circular]';
+      if (value instanceof Date) return isNaN(value.getTime()) ? 'Date { NaN }' : value.toISOString();
+      if (value instanceof RegExp) return String(value);
+      if (value instanceof Error) return '[' + value.name + ': ' + value.message + ']';
+
+      const nested = seen.concat([value]);
+      if (Array.isArray(value)) {
+        if (value.length === 0) return '[]';
+        const items = value.map((item) => inner + formatValue(item, inner, nested) + ',');
+        return '[\n' + items.join('\n') + '\n' + indent + ']';
+      }
+      if (value instanceof Map) {
+        if (value.size === 0) return 'Map {}';
+        const items = [...value].map(([k, v]) => inner + formatValue(k, inner, nested) + ' => ' + formatValue(v, inner, nested) + ',');
+        return 'Map {\n' + items.join('\n') + '\n' + indent + '}';
+      }
+      if (value instanceof Set) {
+        if (value.size === 0) return 'Set {}';
+        const items = [...value].map((item) => inner + formatValue(item, inner, nested) + ',');
+        return 'Set {\n' + items.join('\n') + '\n' + indent + '}';
+      }
+
+      const proto = Object.getPrototypeOf(value);
+      const prefix = proto && proto !== Object.prototype && proto.constructor && proto.constructor.name
+        ? proto.constructor.name + ' '
+        : '';
+      const keys = Object.keys(value).sort();
+      if (keys.length === 0) return prefix + '{}';
+      const items = keys.map((key) => inner + JSON.stringify(key) + ': ' + formatValue(value[key], inner, nested) + ',');
+      return prefix + '{\n' + items.join('\n') + '\n' + indent + '}';
+    }
+
+    // Deep equality for toEqual (strict = false) and toStrictEqual (strict = true)
+    function equals(a, b, strict, seen) {
+      if (isAsymmetric(a)) return a.asymmetricMatch(b);
+      if (isAsymmetric(b)) return b.asymmetricMatch(a);
+      if (Object.is(a, b)) return true;
+      if (typeof a !== 'object' || typeof b !== 'object' || a === null || b === null) return false;
+
+      seen = seen || [];
+      for (const [x, y] of seen) {
+        if (x === a && y === b) return true;
+      }
+      seen = seen.concat([[a, b]]);
+
+      if (Object.prototype.toString.call(a) !== Object.prototype.toString.call(b)) return false;
+      if (strict && Object.getPrototypeOf(a) !== Object.getPrototypeOf(b)) return false;
+      if (a instanceof Date) return a.getTime() === b.getTime();
+      if (a instanceof RegExp) return a.source === b.source && a.flags === b.flags;
+      if (a instanceof Error && (a.message !== b.message || a.name !== b.name)) return false;
+
+      if (Array.isArray(a)) {
+        if (a.length !== b.length) return false;
+        for (let i = 0; i < a.length; i++) {
+          if (strict && (i in a) !== (i in b)) return false;
+          if (!equals(a[i], b[i], strict, seen)) return false;
+        }
+        return true;
+      }
+      if (a instanceof Map) {
+        if (a.size !== b.size) return false;
+        for (const [key, value] of a) {
+          if (!b.has(key) || !equals(value, b.get(key), strict, seen)) return false;
+        }
+        return true;
+      }
+      if (a instanceof Set) {
+        if (a.size !== b.size) return false;
+        for (const value of a) {
+          if (!b.has(value) && ![...b].some((other) => equals(value, other, strict, seen))) return false;
+        }
+        return true;
+      }
+
+      // toEqual ignores properties that are undefined
+      const keysOf = (obj) => Object.keys(obj).filter((key) => strict || obj[key] !== undefined);
+      const aKeys = keysOf(a);
+      const bKeys = keysOf(b);
+      if (aKeys.length !== bKeys.length) return false;
+      for (const key of aKeys) {
+        if (!Object.prototype.hasOwnProperty.call(b, key)) return false;
+        if (!equals(a[key], b[key], strict, seen)) return false;
+      }
+      return true;
+    }
+
+    // Recursive subset match for toMatchObject and expect.objectContaining
+    function matchesObject(received, expected) {
+      if (isAsymmetric(expected)) return expected.asymmetricMatch(received);
+      if (typeof expected !== 'object' || expected === null) return equals(received, expected, false);
+      if (typeof received !== 'object' || received === null) return false;
+      if (Array.isArray(expected)) {
+        return Array.isArray(received)
+          && received.length === expected.length
+          && expected.every((item, i) => matchesObject(received[i], item));
+      }
+      if (expected instanceof Date || expected instanceof RegExp || expected instanceof Map || expected instanceof Set) {
+        return equals(received, expected, false);
+      }
+      return Object.keys(expected).every((key) =>
+        key in received && matchesObject(received[key], expected[key]));
+    }
+
+    // Split 'a.b[0].c' into ['a', 'b', '0', 'c']
+    function propertyPath(path) {
+      if (Array.isArray(path)) return path.map(String);
+      return String(path).replace(/\[(\w+)\]/g, '.$1').split('.').filter((part) => part !== '');
+    }
+
+    // Calls recorded by mock.fn() ({ arguments }) or a Jest-style mock (arrays)
+    function mockCalls(received) {
+      if (typeof received !== 'function' || !received.mock || !Array.isArray(received.mock.calls)) {
+        throw new HowthAssertionError('received value must be a mock function\n\nReceived: ' + formatValue(received));
+      }
+      return received.mock.calls.map((call) => Array.isArray(call) ? call : call.arguments);
+    }
+
+    function thrownBy(received, fromPromise) {
+      if (fromPromise) return { threw: true, error: received };
+      if (typeof received !== 'function') {
+        throw new HowthAssertionError('received value must be a function\n\nReceived: ' + formatValue(received));
+      }
+      try {
+        received();
+      } catch (e) {
+        return { threw: true, error: e };
+      }
+      return { threw: false, error: undefined };
+    }
+
+    // Matchers return { pass, message, diff? } and are called with the
+    // assertion context ({ isNot, mode, fromPromise }) as `this`. `diff`
+    // asks for expected/actual to be attached to the failure.
+    const matchers = {
+      toBe(received, expected) {
+        const pass = Object.is(received, expected);
+        const deep = !pass && equals(received, expected, true);
+        return {
+          pass,
+          diff: { expected, actual: received },
+          message: deep ? 'values have no visual difference; use toStrictEqual for deep equality' : '',
+        };
+      },
+      toEqual(received, expected) {
+        return { pass: equals(received, expected, false), diff: { expected, actual: received }, hint: '// deep equality' };
+      },
+      toStrictEqual(received, expected) {
+        return { pass: equals(received, expected, true), diff: { expected, actual: received }, hint: '// deep equality' };
+      },
+      toMatchObject(received, expected) {
+        return { pass: matchesObject(received, expected), diff: { expected, actual: received } };
+      },
+      toBeTruthy(received) {
+        return { pass: !!received, message: 'Received: ' + formatValue(received) };
+      },
+      toBeFalsy(received) {
+        return { pass: !received, message: 'Received: ' + formatValue(received) };
+      },
+      toBeNull(received) {
+        return { pass: received === null, message: 'Received: ' + formatValue(received) };
+      },
+      toBeUndefined(received) {
+        return { pass: received === undefined, message: 'Received: ' + formatValue(received) };
+      },
+      toBeDefined(received) {
+        return { pass: received !== undefined, message: 'Received: ' + formatValue(received) };
+      },
+      toBeNaN(received) {
+        return { pass: Number.isNaN(received), message: 'Received: ' + formatValue(received) };
+      },
+      toBeGreaterThan(received, expected) {
+        return { pass: received > expected, message: 'Expected: > ' + formatValue(expected) + '\nReceived:   ' + formatValue(received) };
+      },
+      toBeGreaterThanOrEqual(received, expected) {
+        return { pass: received >= expected, message: 'Expected: >= ' + formatValue(expected) + '\nReceived:    ' + formatValue(received) };
+      },
+      toBeLessThan(received, expected) {
+        return { pass: received < expected, message: 'Expected: < ' + formatValue(expected) + '\nReceived:   ' + formatValue(received) };
+      },
+      toBeLessThanOrEqual(received, expected) {
+        return { pass: received <= expected, message: 'Expected: <= ' + formatValue(expected) + '\nReceived:    ' + formatValue(received) };
+      },
+      toBeCloseTo(received, expected, digits) {
+        const precision = digits === undefined ? 2 : digits;
+        const pass = (received === Infinity && expected === Infinity)
+          || (received === -Infinity && expected === -Infinity)
+          || Math.abs(expected - received) < Math.pow(10, -precision) / 2;
+        return { pass, message: 'Expected: ' + formatValue(expected) + ' (precision ' + precision + ')\nReceived: ' + formatValue(received) };
+      },
+      toBeInstanceOf(received, expected) {
+        const actual = received !== null && received !== undefined && received.constructor ? received.constructor.name : formatValue(received);
+        return { pass: received instanceof expected, message: 'Expected constructor: ' + typeName(expected) + '\nReceived constructor: ' + actual };
+      },
+      toContain(received, expected) {
+        let pass;
+        if (typeof received === 'string') pass = received.includes(String(expected));
+        else if (received && typeof received[Symbol.iterator] === 'function') pass = [...received].some((item) => item === expected);
+        else pass = false;
+        return { pass, message: 'Expected value: ' + formatValue(expected) + '\nReceived: ' + formatValue(received) };
+      },
+      toContainEqual(received, expected) {
+        const pass = !!received && typeof received[Symbol.iterator] === 'function'
+          && [...received].some((item) => equals(item, expected, false));
+        return { pass, message: 'Expected value: ' + formatValue(expected) + '\nReceived: ' + formatValue(received) };
+      },
+      toHaveLength(received, expected) {
+        const length = received !== null && received !== undefined ? received.length : undefined;
+        return { pass: length === expected, message: 'Expected length: ' + expected + '\nReceived length: ' + formatValue(length) };
+      },
+      toHaveProperty(received, path, ...rest) {
+        let current = received;
+        let found = current !== null && current !== undefined;
+        for (const key of propertyPath(path)) {
+          if (current === null || current === undefined || !(Object(current) instanceof Object) || !(key in Object(current))) {
+            found = false;
+            break;
+          }
+          current = current[key];
+        }
+        const checkValue = rest.length > 0;
+        const pass = found && (!checkValue || equals(current, rest[0], false));
+        return {
+          pass,
+          diff: checkValue && found ? { expected: rest[0], actual: current } : null,
+          message: found ? '' : 'Expected path: ' + formatValue(path) + '\nReceived: ' + formatValue(received),
+        };
+      },
+      toMatch(received, expected) {
+        const pass = typeof received === 'string'
+          && (expected instanceof RegExp ? expected.test(received) : received.includes(expected));
+        return { pass, message: 'Expected pattern: ' + formatValue(expected) + '\nReceived string:  ' + formatValue(received) };
+      },
+      toThrow(received, expected) {
+        const { threw, error } = thrownBy(received, this.fromPromise);
+        const message = error && typeof error === 'object' && 'message' in error ? error.message : error;
+        let pass = threw;
+        if (threw && expected !== undefined) {
+          if (typeof expected === 'function') pass = error instanceof expected;
+          else if (expected instanceof RegExp) pass = expected.test(String(message));
+          else if (typeof expected === 'string') pass = String(message).includes(expected);
+          else if (isAsymmetric(expected)) pass = expected.asymmetricMatch(error);
+          else if (expected && typeof expected === 'object') pass = message === expected.message;
+        }
+        const wanted = expected === undefined ? 'a thrown error'
+          : typeof expected === 'function' ? 'an instance of ' + typeName(expected)
+          : formatValue(expected && typeof expected === 'object' && !(expected instanceof RegExp) && !isAsymmetric(expected) ? expected.message : expected);
+        return {
+          pass,
+          message: 'Expected: ' + wanted + '\n' + (threw ? 'Received error: ' + formatValue(error) : 'Received function did not throw'),
+        };
+      },
+      toHaveBeenCalled(received) {
+        const calls = mockCalls(received);
+        return { pass: calls.length > 0, message: 'Received number of calls: ' + calls.length };
+      },
+      toHaveBeenCalledTimes(received, expected) {
+        const calls = mockCalls(received);
+        return { pass: calls.length === expected, message: 'Expected number of calls: ' + expected + '\nReceived number of calls: ' + calls.length };
+      },
+      toHaveBeenCalledWith(received, ...expected) {
+        const calls = mockCalls(received);
+        const pass = calls.some((args) => equals(args, expected, false));
+        const last = calls.length > 0 ? calls[calls.length - 1] : undefined;
+        return {
+          pass,
+          diff: last ? { expected, actual: last } : null,
+          message: calls.length === 0 ? 'Number of calls: 0' : '',
+        };
+      },
+    };
+    matchers.toThrowError = matchers.toThrow;
+    matchers.toBeCalled = matchers.toHaveBeenCalled;
+    matchers.toBeCalledTimes = matchers.toHaveBeenCalledTimes;
+    matchers.toBeCalledWith = matchers.toHaveBeenCalledWith;
+
+    function runMatcher(name, received, args, ctx) {
+      const result = matchers[name].call(ctx, received, ...args);
+      if (result.pass !== ctx.isNot) return;
+
+      const argList = args.length > 0 ? 'expected' : '';
+      let header = 'expect(received).' + (ctx.mode ? ctx.mode + '.' : '') + (ctx.isNot ? 'not.' : '') + name + '(' + argList + ')';
+      if (result.hint) header += ' ' + result.hint;
+
+      // A negated comparison failed because the values match, so a diff shows nothing
+      let diff = null;
+      const lines = [header];
+      if (result.diff && !ctx.isNot) {
+        diff = { matcher: name, expected: formatValue(result.diff.expected), actual: formatValue(result.diff.actual) };
+      } else if (result.diff && ctx.isNot) {
+        lines.push('', 'Expected: not ' + formatValue(result.diff.expected));
+      }
+      if (result.message) lines.push('', result.message);
+      throw new HowthAssertionError(lines.join('\n'), diff);
+    }
+
+    function makeAssertions(received, isNot, mode) {
+      const assertions = {};
+      for (const name of Object.keys(matchers)) {
+        assertions[name] = (...args) => {
+          if (!mode) return runMatcher(name, received, args, { isNot, mode, fromPromise: false });
+          return Promise.resolve(received).then(
+            (value) => {
+              if (mode === 'rejects') {
+                throw new HowthAssertionError('expect(received).rejects.' + name + '(' + (args.length > 0 ? 'expected' : '') + ')\n\nReceived promise resolved instead of rejected\nResolved to value: ' + formatValue(value));
+              }
+              return runMatcher(name, value, args, { isNot, mode, fromPromise: false });
+            },
+            (reason) => {
+              if (mode === 'resolves') {
+                throw new HowthAssertionError('expect(received).resolves.' + name + '(' + (args.length > 0 ? 'expected' : '') + ')\n\nReceived promise rejected instead of resolved\nRejected to value: ' + formatValue(reason));
+              }
+              return runMatcher(name, reason, args, { isNot, mode, fromPromise: true });
+            },
+          );
+        };
+      }
+      return assertions;
+    }
+
+    function expect(received) {
+      const assertions = makeAssertions(received, false, null);
+      assertions.not = makeAssertions(received, true, null);
+      assertions.resolves = makeAssertions(received, false, 'resolves');
+      assertions.resolves.not = makeAssertions(received, true, 'resolves');
+      assertions.rejects = makeAssertions(received, false, 'rejects');
+      assertions.rejects.not = makeAssertions(received, true, 'rejects');
+      return assertions;
+    }
+
+    expect.anything = () => new AsymmetricMatcher('anything', undefined,
+      (other) => other !== null && other !== undefined, () => 'Anything');
+    expect.any = (ctor) => new AsymmetricMatcher('any', ctor,
+      (other) => anyMatches(ctor, other), () => 'Any<' + typeName(ctor) + '>');
+    expect.objectContaining = (sample) => new AsymmetricMatcher('objectContaining', sample,
+      (other) => matchesObject(other, sample), () => 'ObjectContaining ' + formatValue(sample));
+    expect.arrayContaining = (sample) => new AsymmetricMatcher('arrayContaining', sample,
+      (other) => Array.isArray(other) && sample.every((item) => other.some((o) => equals(o, item, false))),
+      () => 'ArrayContaining ' + formatValue(sample));
+    expect.stringContaining = (sample) => new AsymmetricMatcher('stringContaining', sample,
+      (other) => typeof other === 'string' && other.includes(sample), () => 'StringContaining ' + JSON.stringify(sample));
+    expect.stringMatching = (sample) => new AsymmetricMatcher('stringMatching', sample,
+      (other) => typeof other === 'string' && (sample instanceof RegExp ? sample.test(other) : other.includes(sample)),
+      () => 'StringMatching ' + String(sample));
+    expect.closeTo = (sample, digits) => new AsymmetricMatcher('closeTo', sample,
+      (other) => typeof other === 'number' && Math.abs(sample - other) < Math.pow(10, -(digits === undefined ? 2 : digits)) / 2,
+      () => 'NumberCloseTo ' + sample);
+
+    // The runner calls this before loading each test file. Fake timers are
+    // reset at every file boundary, both while loading and while running, and
+    // a `dom` file gets a fresh window and document (howth:dom).
+    function __howth_begin_test_file(path, environment) {
+      currentFile = { type: 'file', path, fakeTimers: null, window: null };
+      rootSuite.children.push(currentFile);
+      globalThis.__howth_fake_clock.uninstall();
+      if (environment === 'dom') currentFile.window = globalThis.__howth_dom.install();
+      else globalThis.__howth_dom.uninstall();
+    }
+
+    // Fake timers (howth:test). useFakeTimers() at the top of a file applies
+    // to that file's tests.
+    function useFakeTimers(options) {
+      const now = options && options.now !== undefined ? options.now : undefined;
+      if (!running && currentFile) currentFile.fakeTimers = { now };
+      globalThis.__howth_fake_clock.install(now);
+    }
+
+    function useRealTimers() {
+      if (!running && currentFile) currentFile.fakeTimers = null;
+      globalThis.__howth_fake_clock.uninstall();
+    }
+
+    function fakeClock(name) {
+      const clock = globalThis.__howth_fake_clock;
+      if (!clock.installed) {
+        throw new Error(name + '() requires fake timers; call useFakeTimers() first');
+      }
+      return clock;
+    }
+
+    function advanceTimersByTime(ms) { fakeClock('advanceTimersByTime').advance(ms); }
+    function advanceTimersToNextTimer() { fakeClock('advanceTimersToNextTimer').advanceToNext(); }
+    function runAllTimers() { fakeClock('runAllTimers').runAll(); }
+    function runOnlyPendingTimers() { fakeClock('runOnlyPendingTimers').runPending(); }
+    function setSystemTime(time) { fakeClock('setSystemTime').setSystemTime(time); }
+    function getTimerCount() {
+      const clock = globalThis.__howth_fake_clock;
+      return clock.installed ? clock.count() : 0;
+    }
+
     const testModule = test;
     // Attach named exports as properties
     testModule.test = test;
@@ -14947,11 +20480,38 @@
     testModule.after = after;
     testModule.beforeEach = beforeEach;
     testModule.afterEach = afterEach;
+    testModule.beforeAll = beforeAll;
+    testModule.afterAll = afterAll;
+    testModule.expect = expect;
     testModule.mock = mock;
 
     globalThis.__howth_modules["node:test"] = testModule;
     globalThis.__howth_modules["test"] = testModule;
     globalThis.__howth_run_tests = __howth_run_tests;
+    globalThis.__howth_begin_test_file = __howth_begin_test_file;
+
+    // howth:test — node:test plus Jest-style fake timers
+    globalThis.__howth_modules["howth:test"] = {
+      test,
+      describe,
+      it,
+      before,
+      after,
+      beforeEach,
+      afterEach,
+      beforeAll,
+      afterAll,
+      expect,
+      mock,
+      useFakeTimers,
+      useRealTimers,
+      advanceTimersByTime,
+      advanceTimersToNextTimer,
+      runAllTimers,
+      runOnlyPendingTimers,
+      setSystemTime,
+      getTimerCount,
+    };
 
     // howth:mocha — mocha-compatible API wrapping the same test infrastructure
     (function() {
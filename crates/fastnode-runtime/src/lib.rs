@@ -25,8 +25,10 @@ pub mod napi;
 mod ops;
 mod runtime;
 
-pub use module_loader::{HowthModuleLoader, VirtualModuleMap};
-pub use runtime::{create_local_server_future, Runtime, RuntimeError, RuntimeOptions};
+pub use module_loader::{HowthModuleLoader, ModuleTranspiler, VirtualModuleMap};
+pub use runtime::{
+    create_local_server_future, restore_terminal, Runtime, RuntimeError, RuntimeOptions,
+};
 
 /// Run a JavaScript file and return the exit code.
 pub async fn run_file(path: &std::path::Path) -> Result<i32, RuntimeError> {
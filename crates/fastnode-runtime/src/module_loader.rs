@@ -8,11 +8,16 @@ use deno_core::{
     ModuleLoadResponse, ModuleLoader, ModuleSource, ModuleSourceCode, ModuleSpecifier, ModuleType,
     RequestedModuleType, ResolutionKind,
 };
+use fastnode_core::bundler::builtin_name;
+use fastnode_core::compiler::{
+    on_demand_spec, CompilerBackend, HowthParserBackend, TranspileCache,
+};
 use serde::Deserialize;
 use std::cell::RefCell;
 use std::collections::HashMap;
 use std::path::{Path, PathBuf};
 use std::rc::Rc;
+use std::sync::Arc;
 
 /// Minimal package.json structure for module resolution.
 #[derive(Debug, Deserialize, Default)]
@@ -32,12 +37,61 @@ struct PackageJson {
 /// Modules in this map are served from memory without disk I/O.
 pub type VirtualModuleMap = Rc<RefCell<HashMap<String, String>>>;
 
+/// Transpiles TS/TSX/JSX modules as they are loaded.
+///
+/// Results are cached in memory by content hash, so a module imported from
+/// several places (or re-imported after an unrelated edit) is compiled once.
+/// The backend defaults to the in-process howth-parser transforms; `howth run`
+/// passes the daemon's warm compiler when one is running.
+#[derive(Clone)]
+pub struct ModuleTranspiler {
+    backend: Arc<dyn CompilerBackend>,
+    cache: Arc<TranspileCache>,
+}
+
+impl ModuleTranspiler {
+    /// Create a transpiler using the given compiler backend.
+    pub fn new(backend: Arc<dyn CompilerBackend>) -> Self {
+        Self {
+            backend,
+            cache: Arc::new(TranspileCache::new()),
+        }
+    }
+
+    /// Transpile a module's source, reusing cached output for unchanged content.
+    pub fn transpile(&self, source: &str, path: &Path) -> Result<String, AnyError> {
+        let spec = on_demand_spec(path);
+        let (code, _cached) = self
+            .cache
+            .get_or_transpile(self.backend.as_ref(), &spec, source)
+            .map_err(|e| AnyError::msg(format!("Transpilation failed: {}", e.message)))?;
+        Ok(code.to_string())
+    }
+}
+
+impl Default for ModuleTranspiler {
+    fn default() -> Self {
+        Self::new(Arc::new(HowthParserBackend::new()))
+    }
+}
+
+impl std::fmt::Debug for ModuleTranspiler {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("ModuleTranspiler")
+            .field("backend", &self.backend.name())
+            .field("cache", &self.cache.stats())
+            .finish()
+    }
+}
+
 /// Howth's custom module loader.
 pub struct HowthModuleLoader {
     /// Base directory for resolving relative imports.
     cwd: PathBuf,
     /// Virtual modules that live in memory (no disk I/O needed).
     virtual_modules: Option<VirtualModuleMap>,
+    /// On-demand transpiler for TS/TSX/JSX modules.
+    transpiler: ModuleTranspiler,
 }
 
 impl HowthModuleLoader {
@@ -46,6 +100,7 @@ impl HowthModuleLoader {
         Self {
             cwd,
             virtual_modules: None,
+            transpiler: ModuleTranspiler::default(),
         }
     }
 
@@ -54,9 +109,17 @@ impl HowthModuleLoader {
         Self {
             cwd,
             virtual_modules: Some(virtual_modules),
+            transpiler: ModuleTranspiler::default(),
         }
     }
 
+    /// Use the given transpiler for TS/TSX/JSX modules.
+    #[must_use]
+    pub fn with_transpiler(mut self, transpiler: ModuleTranspiler) -> Self {
+        self.transpiler = transpiler;
+        self
+    }
+
     /// Resolve a module specifier to a file path.
     fn resolve_path(
         &self,
@@ -432,6 +495,9 @@ impl HowthModuleLoader {
     /// Load and optionally transpile a module.
     fn load_module(&self, path: &Path) -> Result<(String, ModuleType), AnyError> {
         let source = std::fs::read_to_string(path)?;
+        // CLI scripts start with `#!/usr/bin/env node`, which is only valid at
+        // the very start of a file (not inside the CommonJS wrapper)
+        let source = strip_shebang(source);
 
         // Determine if transpilation is needed
         let ext = path.extension().and_then(|e| e.to_str()).unwrap_or("");
@@ -662,18 +728,9 @@ export default __howth_result__;
         declarations
     }
 
-    /// Transpile TypeScript/JSX to JavaScript using howth-parser.
+    /// Transpile TypeScript/JSX to JavaScript with the loader's transpiler.
     fn transpile(&self, source: &str, path: &Path) -> Result<String, AnyError> {
-        let ext = path.extension().and_then(|e| e.to_str()).unwrap_or("");
-
-        let (code, _imports) = match ext.to_lowercase().as_str() {
-            "tsx" => fastnode_core::compiler::transform_tsx(source),
-            "jsx" => fastnode_core::compiler::transform_jsx(source),
-            _ => fastnode_core::compiler::transform_ts(source),
-        }
-        .map_err(|e| AnyError::msg(format!("Transpilation failed: {}", e.message)))?;
-
-        Ok(code)
+        self.transpiler.transpile(source, path)
     }
 }
 
@@ -689,6 +746,11 @@ impl ModuleLoader for HowthModuleLoader {
             return ModuleSpecifier::parse(&format!("howth-builtin:///{}", specifier))
                 .map_err(|e| AnyError::msg(format!("Invalid builtin module: {}", e)));
         }
+        // Bare built-in names (`fs`) win over node_modules, as in Node
+        if let Some(name) = builtin_name(specifier) {
+            return ModuleSpecifier::parse(&format!("howth-builtin:///node:{}", name))
+                .map_err(|e| AnyError::msg(format!("Invalid builtin module: {}", e)));
+        }
 
         // Parse referrer as URL
         let referrer_url = if referrer == "." || referrer.is_empty() {
@@ -711,7 +773,11 @@ impl ModuleLoader for HowthModuleLoader {
         // Resolve the specifier to a path
         let resolved_path = self.resolve_path(specifier, &referrer_url)?;
 
-        // Convert back to file:// URL
+        // Convert back to file:// URL. The real path gives each module one URL
+        // (its `import.meta.url`) however it was reached, e.g. via `..` or a
+        // symlinked package; virtual modules only exist in memory.
+        let resolved_path = dunce::canonicalize(&resolved_path)
+            .unwrap_or_else(|_| Self::normalize_path(&resolved_path));
         ModuleSpecifier::from_file_path(&resolved_path)
             .map_err(|_| AnyError::msg(format!("Invalid path: {}", resolved_path.display())))
     }
@@ -726,6 +792,7 @@ impl ModuleLoader for HowthModuleLoader {
         let specifier = module_specifier.clone();
         let cwd = self.cwd.clone();
         let virtual_modules = self.virtual_modules.clone();
+        let transpiler = self.transpiler.clone();
 
         ModuleLoadResponse::Async(
             async move {
@@ -759,7 +826,7 @@ impl ModuleLoader for HowthModuleLoader {
                     }
                 }
 
-                let loader = HowthModuleLoader::new(cwd);
+                let loader = HowthModuleLoader::new(cwd).with_transpiler(transpiler);
 
                 let (code, module_type) = loader.load_module(&path)?;
 
@@ -808,7 +875,13 @@ impl HowthModuleLoader {
                 "readFile, writeFile, appendFile, mkdir, rmdir, rm, unlink, rename, copyFile, readdir, stat, lstat, realpath, chmod, access"
             }
             "node:test" | "test" => {
-                "test, describe, it, before, after, beforeEach, afterEach, mock"
+                "test, describe, it, before, after, beforeEach, afterEach, beforeAll, afterAll, expect, mock"
+            }
+            "howth:test" => {
+                "test, describe, it, before, after, beforeEach, afterEach, beforeAll, afterAll, expect, mock, useFakeTimers, useRealTimers, advanceTimersByTime, advanceTimersToNextTimer, runAllTimers, runOnlyPendingTimers, setSystemTime, getTimerCount"
+            }
+            "howth:dom" => {
+                "createWindow, Window, Document, DocumentFragment, Node, Element, HTMLElement, Text, Comment, Event, CustomEvent, MouseEvent, KeyboardEvent, FocusEvent, InputEvent, EventTarget, MutationObserver"
             }
             "howth:mocha" => {
                 "describe, context, it, specify, before, after, beforeEach, afterEach"
@@ -860,6 +933,41 @@ impl HowthModuleLoader {
     }
 }
 
+/// `import.meta.resolve()`: resolve like a dynamic import from the module,
+/// reporting built-ins as `node:` specifiers. As in Node, relative and
+/// absolute specifiers resolve to a URL even if no file exists there.
+pub(crate) fn import_meta_resolve(
+    loader: &dyn ModuleLoader,
+    specifier: String,
+    referrer: String,
+) -> Result<ModuleSpecifier, AnyError> {
+    match loader.resolve(&specifier, &referrer, ResolutionKind::DynamicImport) {
+        Ok(resolved) if resolved.scheme() == "howth-builtin" => Ok(ModuleSpecifier::parse(
+            resolved.path().trim_start_matches('/'),
+        )?),
+        Ok(resolved) => Ok(resolved),
+        Err(e) => {
+            let is_path = specifier.starts_with("./")
+                || specifier.starts_with("../")
+                || specifier.starts_with('/');
+            match ModuleSpecifier::parse(&referrer) {
+                Ok(base) if is_path => Ok(base.join(&specifier)?),
+                _ => Err(e),
+            }
+        }
+    }
+}
+
+/// Blank out a leading shebang line, keeping the newline so line numbers in
+/// errors and stack traces still match the file.
+fn strip_shebang(source: String) -> String {
+    if !source.starts_with("#!") {
+        return source;
+    }
+    let end = source.find('\n').unwrap_or(source.len());
+    source[end..].to_string()
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -919,6 +1027,71 @@ mod tests {
         fs::write(scoped.join("package.json"), r#"{"name": "@scope/pkg"}"#).unwrap();
     }
 
+    #[test]
+    fn test_resolve_returns_real_file_urls() {
+        let temp = TempDir::new().unwrap();
+        setup_node_modules(&temp);
+        let root = dunce::canonicalize(temp.path()).unwrap();
+        fs::create_dir_all(root.join("src/lib")).unwrap();
+        fs::write(root.join("src/util.js"), "export {};").unwrap();
+        let loader = HowthModuleLoader::new(root.clone());
+        let referrer = ModuleSpecifier::from_file_path(root.join("src/lib/a.js")).unwrap();
+
+        let url = loader
+            .resolve("../util.js", referrer.as_str(), ResolutionKind::Import)
+            .unwrap();
+        assert_eq!(
+            url,
+            ModuleSpecifier::from_file_path(root.join("src/util.js")).unwrap()
+        );
+
+        let fs_url = loader
+            .resolve("fs", referrer.as_str(), ResolutionKind::Import)
+            .unwrap();
+        assert_eq!(fs_url.as_str(), "howth-builtin:///node:fs");
+    }
+
+    #[test]
+    fn test_import_meta_resolve() {
+        let temp = TempDir::new().unwrap();
+        setup_node_modules(&temp);
+        let root = dunce::canonicalize(temp.path()).unwrap();
+        let loader = HowthModuleLoader::new(root.clone());
+        let referrer = ModuleSpecifier::from_file_path(root.join("main.js"))
+            .unwrap()
+            .to_string();
+
+        let resolve = |spec: &str| import_meta_resolve(&loader, spec.to_string(), referrer.clone());
+        assert_eq!(resolve("node:fs").unwrap().as_str(), "node:fs");
+        assert_eq!(resolve("path").unwrap().as_str(), "node:path");
+        assert_eq!(
+            resolve("simple-pkg").unwrap(),
+            ModuleSpecifier::from_file_path(root.join("node_modules/simple-pkg/index.js")).unwrap()
+        );
+        // Missing files still resolve to a URL; missing packages don't
+        assert_eq!(
+            resolve("./assets/logo.png").unwrap(),
+            ModuleSpecifier::from_file_path(root.join("assets/logo.png")).unwrap()
+        );
+        assert!(resolve("not-installed").is_err());
+    }
+
+    #[test]
+    fn test_load_module_strips_shebang() {
+        let temp = TempDir::new().unwrap();
+        let loader = HowthModuleLoader::new(temp.path().to_path_buf());
+
+        let esm = temp.path().join("cli.mjs");
+        fs::write(&esm, "#!/usr/bin/env node\nexport const x = 1;\n").unwrap();
+        let (code, _) = loader.load_module(&esm).unwrap();
+        assert_eq!(code, "\nexport const x = 1;\n");
+
+        let cjs = temp.path().join("cli.js");
+        fs::write(&cjs, "#!/usr/bin/env node\nmodule.exports = 1;\n").unwrap();
+        let (code, _) = loader.load_module(&cjs).unwrap();
+        assert!(!code.contains("#!"));
+    }
+
     #[test]
     fn test_parse_bare_specifier() {
         let loader = HowthModuleLoader::new(PathBuf::from("/tmp"));
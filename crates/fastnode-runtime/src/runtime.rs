@@ -1,6 +1,6 @@
 //! Runtime implementation using deno_core.
 
-use crate::module_loader::HowthModuleLoader;
+use crate::module_loader::{import_meta_resolve, HowthModuleLoader};
 use deno_core::{
     extension, op2, JsBuffer, JsRuntime, ModuleSpecifier, RuntimeOptions as DenoRuntimeOptions,
 };
@@ -117,6 +117,8 @@ pub struct RuntimeOptions {
     pub args: Option<Vec<String>>,
     /// Virtual modules served from memory instead of disk.
     pub virtual_modules: Option<crate::module_loader::VirtualModuleMap>,
+    /// Transpiler for TS/TSX/JSX modules (defaults to in-process howth-parser).
+    pub transpiler: Option<crate::module_loader::ModuleTranspiler>,
 }
 
 /// Thread-local storage for script arguments (set before runtime creation).
@@ -147,6 +149,7 @@ extension!(
         op_howth_chdir,
         op_howth_platform,
         op_howth_arch,
+        op_howth_os_info,
         op_howth_env_get,
         op_howth_env_set,
         op_howth_exit,
@@ -198,6 +201,9 @@ extension!(
         op_howth_fs_watch_start,
         op_howth_fs_watch_poll,
         op_howth_fs_watch_close,
+        // Test runner ops
+        op_howth_test_tmpdir_create,
+        op_howth_test_tmpdir_remove,
         // Zlib compression ops
         op_howth_zlib_gzip,
         op_howth_zlib_gunzip,
@@ -246,8 +252,10 @@ extension!(
         op_howth_dns_resolve_ptr,
         op_howth_dns_reverse,
         // Stdin ops
-        op_howth_stdin_read_line,
-        op_howth_stdin_is_tty,
+        op_howth_stdin_read,
+        op_howth_isatty,
+        op_howth_tty_window_size,
+        op_howth_tty_set_raw_mode,
         // Child process ops (sync)
         op_howth_spawn_sync,
         op_howth_exec_sync,
@@ -530,6 +538,65 @@ fn op_howth_fs_rmdir(
     }
 }
 
+/// Create a fresh directory under the test temp root for `test.tmpdir()`.
+///
+/// `label` (the test name) is sanitized into the directory name so kept
+/// directories are easy to match to their tests.
+#[op2]
+#[string]
+fn op_howth_test_tmpdir_create(
+    #[string] label: &str,
+) -> Result<String, deno_core::error::AnyError> {
+    static NEXT_TMPDIR: AtomicU64 = AtomicU64::new(0);
+
+    let root = fastnode_core::paths::test_tmp_dir();
+    std::fs::create_dir_all(&root)
+        .map_err(|e| format_fs_error(e, "mkdir", &root.to_string_lossy()))?;
+
+    let label: String = label
+        .chars()
+        .map(|c| {
+            if c.is_ascii_alphanumeric() || c == '-' || c == '_' {
+                c
+            } else {
+                '-'
+            }
+        })
+        .take(40)
+        .collect();
+    loop {
+        let n = NEXT_TMPDIR.fetch_add(1, Ordering::Relaxed);
+        let dir = root.join(format!("{}-{n}-{label}", std::process::id()));
+        match std::fs::create_dir(&dir) {
+            Ok(()) => return Ok(dir.to_string_lossy().into_owned()),
+            Err(e) if e.kind() == ErrorKind::AlreadyExists => {}
+            Err(e) => return Err(format_fs_error(e, "mkdir", &dir.to_string_lossy())),
+        }
+    }
+}
+
+/// Remove a directory made by `op_howth_test_tmpdir_create`.
+///
+/// Paths outside the test temp root are refused, so a test can't use this
+/// to delete arbitrary directories.
+#[op2(fast)]
+fn op_howth_test_tmpdir_remove(#[string] path: &str) -> Result<(), deno_core::error::AnyError> {
+    let dir = std::path::Path::new(path);
+    if dir
+        .components()
+        .any(|c| c == std::path::Component::ParentDir)
+        || !dir.starts_with(fastnode_core::paths::test_tmp_dir())
+    {
+        return Err(deno_core::error::AnyError::msg(format!(
+            "refusing to remove {path}: not a test temp directory"
+        )));
+    }
+    match std::fs::remove_dir_all(dir) {
+        Err(e) if e.kind() != ErrorKind::NotFound => Err(format_fs_error(e, "rmdir", path)),
+        _ => Ok(()),
+    }
+}
+
 /// Rename/move a file or directory.
 #[op2(fast)]
 fn op_howth_fs_rename(
@@ -1515,6 +1582,7 @@ fn run_worker_script(
         // Create runtime options
         let options = DenoRuntimeOptions {
             module_loader: Some(module_loader),
+            import_meta_resolve_callback: Some(Box::new(import_meta_resolve)),
             extensions: vec![ext, napi_ext],
             create_params,
             startup_snapshot: Some(SNAPSHOT),
@@ -2128,49 +2196,106 @@ async fn op_howth_dns_reverse(
 // Stdin Operations
 // ============================================================================
 
-use std::io::BufRead;
+/// A pending stdin read: the reader thread answers on the oneshot.
+type StdinRequest = tokio::sync::oneshot::Sender<std::io::Result<Vec<u8>>>;
 
-/// Read a line from stdin (blocking)
-#[op2(async)]
-#[string]
-async fn op_howth_stdin_read_line() -> Result<Option<String>, deno_core::error::AnyError> {
-    // Run blocking stdin read in a separate thread
-    tokio::task::spawn_blocking(|| {
-        let stdin = std::io::stdin();
-        let mut handle = stdin.lock();
-        let mut line = String::new();
-        match handle.read_line(&mut line) {
-            Ok(0) => Ok(None), // EOF
-            Ok(_) => {
-                // Remove trailing newline
-                if line.ends_with('\n') {
-                    line.pop();
-                    if line.ends_with('\r') {
-                        line.pop();
-                    }
+lazy_static::lazy_static! {
+    /// Sender for the stdin reader thread.
+    ///
+    /// Reads happen on a dedicated thread rather than `spawn_blocking` so a
+    /// read that never completes (an idle terminal) doesn't hold up runtime
+    /// shutdown. The thread only reads when asked, so stdin isn't consumed
+    /// while `process.stdin` is paused (e.g. before spawning a child with
+    /// `stdio: "inherit"`).
+    static ref STDIN_READER: std::sync::Mutex<std::sync::mpsc::Sender<StdinRequest>> = {
+        let (tx, rx) = std::sync::mpsc::channel::<StdinRequest>();
+        std::thread::Builder::new()
+            .name("howth-stdin".to_string())
+            .spawn(move || {
+                use std::io::Read;
+                for reply in rx {
+                    let mut buf = vec![0u8; 64 * 1024];
+                    let result = loop {
+                        match std::io::stdin().lock().read(&mut buf) {
+                            Err(e) if e.kind() == std::io::ErrorKind::Interrupted => {}
+                            other => break other,
+                        }
+                    };
+                    let _ = reply.send(result.map(|n| {
+                        buf.truncate(n);
+                        buf
+                    }));
                 }
-                Ok(Some(line))
-            }
-            Err(e) => Err(deno_core::error::generic_error(format!(
-                "stdin read error: {}",
-                e
-            ))),
-        }
-    })
-    .await
-    .map_err(|e| deno_core::error::generic_error(format!("spawn_blocking error: {}", e)))?
+            })
+            .expect("failed to spawn stdin reader thread");
+        std::sync::Mutex::new(tx)
+    };
 }
 
-/// Check if stdin is a TTY
+/// Read the next chunk of stdin (`process.stdin`). Returns `None` at EOF.
+#[op2(async)]
+#[serde]
+async fn op_howth_stdin_read() -> Result<Option<Vec<u8>>, deno_core::error::AnyError> {
+    let (reply, response) = tokio::sync::oneshot::channel();
+    STDIN_READER
+        .lock()
+        .unwrap_or_else(std::sync::PoisonError::into_inner)
+        .send(reply)
+        .map_err(|_| deno_core::error::generic_error("stdin reader is gone"))?;
+    let chunk = response
+        .await
+        .map_err(|_| deno_core::error::generic_error("stdin reader is gone"))?
+        .map_err(|e| deno_core::error::generic_error(format!("stdin read error: {e}")))?;
+    Ok((!chunk.is_empty()).then_some(chunk))
+}
+
+/// Check if a file descriptor is a TTY (`tty.isatty`).
 #[op2(fast)]
-fn op_howth_stdin_is_tty() -> bool {
-    #[cfg(unix)]
-    {
-        unsafe { libc::isatty(libc::STDIN_FILENO) != 0 }
+fn op_howth_isatty(fd: i32) -> bool {
+    fastnode_util::tty::is_terminal(fd)
+}
+
+/// Terminal size of a file descriptor as `[columns, rows]`.
+#[op2]
+#[serde]
+fn op_howth_tty_window_size(fd: i32) -> Option<[u16; 2]> {
+    fastnode_util::tty::window_size(fd).map(|(columns, rows)| [columns, rows])
+}
+
+lazy_static::lazy_static! {
+    /// Attributes of terminals put into raw mode, keyed by fd, so they can be
+    /// restored by `setRawMode(false)` or when the script exits.
+    static ref RAW_MODE_TERMINALS: std::sync::Mutex<HashMap<i32, fastnode_util::tty::TerminalState>> =
+        std::sync::Mutex::new(HashMap::new());
+}
+
+/// Toggle raw mode on a TTY (`process.stdin.setRawMode`).
+#[op2(fast)]
+fn op_howth_tty_set_raw_mode(fd: i32, enable: bool) -> Result<(), deno_core::error::AnyError> {
+    let mut terminals = RAW_MODE_TERMINALS
+        .lock()
+        .unwrap_or_else(std::sync::PoisonError::into_inner);
+    if enable {
+        if !terminals.contains_key(&fd) {
+            let previous = fastnode_util::tty::enable_raw_mode(fd)?;
+            terminals.insert(fd, previous);
+        }
+    } else if let Some(previous) = terminals.remove(&fd) {
+        previous.restore()?;
     }
-    #[cfg(not(unix))]
-    {
-        false
+    Ok(())
+}
+
+/// Restore every terminal a script put into raw mode.
+///
+/// Called on `process.exit()` and by the CLI once a script finishes, so a
+/// crashed interactive tool doesn't leave the shell without echo.
+pub fn restore_terminal() {
+    let mut terminals = RAW_MODE_TERMINALS
+        .lock()
+        .unwrap_or_else(std::sync::PoisonError::into_inner);
+    for (_, previous) in terminals.drain() {
+        let _ = previous.restore();
     }
 }
 
@@ -2196,6 +2321,28 @@ pub struct SpawnOptions {
     pub encoding: Option<String>,
     pub timeout: Option<u64>,
     pub max_buffer: Option<usize>,
+    /// Per-fd mode for stdin/stdout/stderr: "pipe", "inherit" or "ignore".
+    /// Normalized from Node's `stdio` option on the JS side.
+    pub stdio: Option<Vec<String>>,
+}
+
+impl SpawnOptions {
+    /// The `stdio` mode for `fd`; "pipe" unless the caller said otherwise.
+    fn stdio_mode(&self, fd: usize) -> &str {
+        self.stdio
+            .as_ref()
+            .and_then(|modes| modes.get(fd))
+            .map_or("pipe", String::as_str)
+    }
+
+    /// `Stdio` for `fd`, so "inherit" hands the child our terminal.
+    fn stdio(&self, fd: usize) -> std::process::Stdio {
+        match self.stdio_mode(fd) {
+            "inherit" => std::process::Stdio::inherit(),
+            "ignore" => std::process::Stdio::null(),
+            _ => std::process::Stdio::piped(),
+        }
+    }
 }
 
 /// Spawn a process synchronously and wait for completion.
@@ -2206,7 +2353,7 @@ fn op_howth_spawn_sync(
     #[serde] args: Vec<String>,
     #[serde] options: Option<SpawnOptions>,
 ) -> SpawnSyncResult {
-    use std::process::{Command, Stdio};
+    use std::process::Command;
 
     let opts = options.unwrap_or_default();
     let use_shell = opts.shell.unwrap_or(false);
@@ -2239,18 +2386,22 @@ fn op_howth_spawn_sync(
     };
 
     // Set working directory
-    if let Some(cwd) = opts.cwd {
+    if let Some(cwd) = &opts.cwd {
         cmd.current_dir(cwd);
     }
 
     // Set environment variables
-    if let Some(env) = opts.env {
+    if let Some(env) = &opts.env {
         cmd.envs(env);
     }
 
-    // Capture stdout and stderr
-    cmd.stdout(Stdio::piped());
-    cmd.stderr(Stdio::piped());
+    // Piped stdin has no input to feed in sync mode, so only an explicit
+    // "inherit"/"ignore" changes it from output()'s default
+    if opts.stdio_mode(0) != "pipe" {
+        cmd.stdin(opts.stdio(0));
+    }
+    cmd.stdout(opts.stdio(1));
+    cmd.stderr(opts.stdio(2));
 
     match cmd.output() {
         Ok(output) => {
@@ -2281,7 +2432,7 @@ fn op_howth_exec_sync(
     #[string] command: &str,
     #[serde] options: Option<SpawnOptions>,
 ) -> SpawnSyncResult {
-    use std::process::{Command, Stdio};
+    use std::process::Command;
 
     let opts = options.unwrap_or_default();
 
@@ -2300,18 +2451,20 @@ fn op_howth_exec_sync(
     };
 
     // Set working directory
-    if let Some(cwd) = opts.cwd {
+    if let Some(cwd) = &opts.cwd {
         cmd.current_dir(cwd);
     }
 
     // Set environment variables
-    if let Some(env) = opts.env {
+    if let Some(env) = &opts.env {
         cmd.envs(env);
     }
 
-    // Capture stdout and stderr
-    cmd.stdout(Stdio::piped());
-    cmd.stderr(Stdio::piped());
+    if opts.stdio_mode(0) != "pipe" {
+        cmd.stdin(opts.stdio(0));
+    }
+    cmd.stdout(opts.stdio(1));
+    cmd.stderr(opts.stdio(2));
 
     match cmd.output() {
         Ok(output) => {
@@ -2390,8 +2543,6 @@ async fn op_howth_spawn_async(
     #[serde] args: Vec<String>,
     #[serde] options: Option<SpawnOptions>,
 ) -> SpawnAsyncResult {
-    use std::process::Stdio;
-
     let opts = options.unwrap_or_default();
     let use_shell = opts.shell.unwrap_or(false);
 
@@ -2423,19 +2574,19 @@ async fn op_howth_spawn_async(
     };
 
     // Set working directory
-    if let Some(cwd) = opts.cwd {
+    if let Some(cwd) = &opts.cwd {
         cmd.current_dir(cwd);
     }
 
     // Set environment variables
-    if let Some(env) = opts.env {
+    if let Some(env) = &opts.env {
         cmd.envs(env);
     }
 
-    // Configure stdio for piping
-    cmd.stdin(Stdio::piped());
-    cmd.stdout(Stdio::piped());
-    cmd.stderr(Stdio::piped());
+    // Piped fds get handles below; inherited ones share our terminal
+    cmd.stdin(opts.stdio(0));
+    cmd.stdout(opts.stdio(1));
+    cmd.stderr(opts.stdio(2));
 
     match cmd.spawn() {
         Ok(mut child) => {
@@ -5264,6 +5415,13 @@ fn op_howth_arch() -> &'static str {
     }
 }
 
+/// Snapshot of host information for `node:os`.
+#[op2]
+#[serde]
+fn op_howth_os_info() -> fastnode_compat::os::OsInfo {
+    fastnode_compat::os::info()
+}
+
 /// Get environment variable.
 #[op2]
 #[string]
@@ -5280,6 +5438,7 @@ fn op_howth_env_set(#[string] key: &str, #[string] value: &str) {
 /// Exit the process.
 #[op2(fast)]
 fn op_howth_exit(code: i32) {
+    restore_terminal();
     std::process::exit(code);
 }
 
@@ -6095,10 +6254,14 @@ impl Runtime {
             .clone()
             .unwrap_or_else(|| std::env::current_dir().unwrap_or_else(|_| PathBuf::from(".")));
 
-        let module_loader = Rc::new(if let Some(vm) = options.virtual_modules {
+        let module_loader = if let Some(vm) = options.virtual_modules {
             HowthModuleLoader::new_with_virtual_modules(cwd.clone(), vm)
         } else {
             HowthModuleLoader::new(cwd.clone())
+        };
+        let module_loader = Rc::new(match options.transpiler {
+            Some(transpiler) => module_loader.with_transpiler(transpiler),
+            None => module_loader,
         });
 
         let mut js_runtime = JsRuntime::new(DenoRuntimeOptions {
@@ -6107,6 +6270,7 @@ impl Runtime {
                 deno_napi::deno_napi::init_ops::<HowthNapiPermissions>(),
             ],
             module_loader: Some(module_loader),
+            import_meta_resolve_callback: Some(Box::new(import_meta_resolve)),
             startup_snapshot: Some(SNAPSHOT),
             ..Default::default()
         });
@@ -6166,11 +6330,12 @@ impl Runtime {
 
     /// Execute an ES module from a file path.
     pub async fn execute_module(&mut self, path: &std::path::Path) -> Result<(), RuntimeError> {
-        let specifier = ModuleSpecifier::from_file_path(path)
-            .map_err(|_| RuntimeError::Io(format!("Invalid path: {}", path.display())))?;
-
         // Set up the main module context for require
         let abs_path = dunce::canonicalize(path).unwrap_or_else(|_| path.to_path_buf());
+        // Same URL the loader gives the file when it's imported, so
+        // `import.meta.url` is absolute and the entry isn't loaded twice
+        let specifier = ModuleSpecifier::from_file_path(&abs_path)
+            .map_err(|_| RuntimeError::Io(format!("Invalid path: {}", path.display())))?;
         let main_module_path = abs_path.to_string_lossy().to_string();
         let main_module_dir = abs_path
             .parent()
@@ -6662,6 +6827,65 @@ mod tests {
             .unwrap();
     }
 
+    #[tokio::test]
+    async fn test_structured_clone_v8_types() {
+        let mut runtime = Runtime::new(RuntimeOptions::default()).unwrap();
+        runtime
+            .execute_script(
+                r#"
+                const src = { m: new Map([[1, new Set([2])]]), d: new Date(0), r: /x/g, e: new RangeError('boom') };
+                src.self = src;
+                const clone = structuredClone(src);
+                if (clone.self !== clone) throw new Error('cycle not preserved');
+                if (!(clone.m.get(1) instanceof Set)) throw new Error('map/set lost');
+                if (!(clone.d instanceof Date) || !(clone.r instanceof RegExp)) throw new Error('date/regexp lost');
+                if (!(clone.e instanceof RangeError) || clone.e.message !== 'boom') throw new Error('error lost');
+
+                const buf = new Uint8Array([1, 2, 3]).buffer;
+                const moved = structuredClone(buf, { transfer: [buf] });
+                if (moved.byteLength !== 3 || buf.byteLength !== 0) throw new Error('transfer did not detach');
+
+                try { structuredClone(() => {}); throw new Error('should throw'); }
+                catch (e) { if (!(e instanceof DOMException) || e.name !== 'DataCloneError' || e.code !== 25) throw e; }
+                "#,
+            )
+            .await
+            .unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_abort_signal() {
+        let mut runtime = Runtime::new(RuntimeOptions::default()).unwrap();
+        runtime
+            .execute_script(
+                r#"
+                try { new AbortSignal(); throw new Error('should throw'); }
+                catch (e) { if (!(e instanceof TypeError)) throw e; }
+
+                const a = new AbortController();
+                const b = new AbortController();
+                const any = AbortSignal.any([a.signal, b.signal]);
+                let fired = 0;
+                any.onabort = () => { fired++; };
+                b.abort('first');
+                a.abort('second');
+                if (!any.aborted || any.reason !== 'first' || fired !== 1) throw new Error('any() broken');
+
+                const aborted = AbortSignal.abort();
+                if (aborted.reason.name !== 'AbortError' || aborted.reason.code !== DOMException.ABORT_ERR) {
+                    throw new Error('abort() reason');
+                }
+                try { AbortSignal.timeout(-1); throw new Error('should throw'); }
+                catch (e) { if (!(e instanceof TypeError)) throw e; }
+
+                const ex = new DOMException('m', { name: 'TimeoutError', cause: 'c' });
+                if (ex.code !== 23 || ex.cause !== 'c' || String(ex) !== 'TimeoutError: m') throw new Error('DOMException');
+                "#,
+            )
+            .await
+            .unwrap();
+    }
+
     #[tokio::test]
     async fn test_process_hrtime() {
         let mut runtime = Runtime::new(RuntimeOptions::default()).unwrap();
@@ -6836,6 +7060,45 @@ mod tests {
         runtime.execute_module(&main_file).await.unwrap();
     }
 
+    #[tokio::test]
+    async fn test_import_meta() {
+        use std::fs;
+        let temp = tempfile::TempDir::new().unwrap();
+        fs::create_dir_all(temp.path().join("lib")).unwrap();
+        fs::write(
+            temp.path().join("lib/dep.js"),
+            r#"
+            if (import.meta.main) throw new Error('dep is not main');
+            export const url = import.meta.url;
+        "#,
+        )
+        .unwrap();
+
+        let main_file = temp.path().join("main.js");
+        fs::write(
+            &main_file,
+            r#"
+            import { url as depUrl } from './lib/../lib/dep.js';
+            if (!import.meta.main) throw new Error('main module not marked main');
+            if (!import.meta.url.startsWith('file:///')) throw new Error('bad url ' + import.meta.url);
+            if (depUrl !== new URL('./lib/dep.js', import.meta.url).href) throw new Error('bad dep url ' + depUrl);
+            if (import.meta.resolve('./lib/dep.js') !== depUrl) throw new Error('resolve mismatch');
+            if (import.meta.resolve('fs') !== 'node:fs') throw new Error('builtin resolve');
+            if (!import.meta.filename.endsWith('main.js')) throw new Error('bad filename');
+        "#,
+        )
+        .unwrap();
+
+        let mut runtime = Runtime::new(RuntimeOptions {
+            cwd: Some(temp.path().to_path_buf()),
+            main_module: Some(main_file.clone()),
+            ..Default::default()
+        })
+        .unwrap();
+
+        runtime.execute_module(&main_file).await.unwrap();
+    }
+
     #[tokio::test]
     async fn test_scoped_package_import() {
         use std::fs;
@@ -7597,6 +7860,547 @@ mod tests {
         runtime.execute_module(&main_file).await.unwrap();
     }
 
+    #[tokio::test]
+    async fn test_commonjs_os_and_util() {
+        use std::fs;
+        let temp = tempfile::TempDir::new().unwrap();
+
+        let main_file = temp.path().join("main.js");
+        fs::write(
+            &main_file,
+            r#"
+            const os = require('node:os');
+            const util = require('node:util');
+
+            if (os.cpus().length < 1 || os.availableParallelism() < 1) throw new Error('os.cpus');
+            if (os.totalmem() <= 0 || typeof os.hostname() !== 'string') throw new Error('os host info');
+            if (os.EOL !== (process.platform === 'win32' ? '
+' : '
+')) throw new Error('os.EOL');
+            if (!os.homedir() || !os.tmpdir()) throw new Error('os dirs');
+
+            const nested = util.inspect({ a: { b: { c: { d: 1 } } }, s: "it's" });
+            if (nested !== `{ a: { b: { c: [Object] } }, s: "it's" }`) throw new Error(nested);
+            if (util.inspect({ a: { b: { c: { d: 1 } } } }, { depth: null }) !== '{\n  a: { b: { c: { d: 1 } } }\n}') {
+                throw new Error('depth: null');
+            }
+            const colored = util.inspect(1, { colors: true });
+            if (colored !== '\x1b[33m1\x1b[39m') throw new Error('colors');
+            const custom = { [util.inspect.custom]: () => 'custom!' };
+            if (util.inspect([custom]) !== '[ custom! ]') throw new Error('inspect.custom');
+            if (util.format('%s=%d %j', 'n', 42, { x: 1 }, 'rest') !== 'n=42 {"x":1} rest') throw new Error('format');
+
+            const add = util.promisify((a, b, cb) => cb(null, a + b));
+            const back = util.callbackify(async (x) => x * 2);
+            add(1, 2).then((sum) => {
+                if (sum !== 3) throw new Error('promisify');
+                back(2, (err, doubled) => {
+                    if (err || doubled !== 4) throw new Error('callbackify');
+                });
+            });
+            if (!util.types.isProxy(new Proxy({}, {}))) throw new Error('types.isProxy');
+            if (new util.TextDecoder().decode(new util.TextEncoder().encode('hé')) !== 'hé') throw new Error('TextEncoder');
+        "#,
+        )
+        .unwrap();
+
+        let mut runtime = Runtime::new(RuntimeOptions {
+            cwd: Some(temp.path().to_path_buf()),
+            main_module: Some(main_file.clone()),
+            ..Default::default()
+        })
+        .unwrap();
+
+        runtime.execute_module(&main_file).await.unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_commonjs_events() {
+        use std::fs;
+        let temp = tempfile::TempDir::new().unwrap();
+
+        let main_file = temp.path().join("main.js");
+        fs::write(
+            &main_file,
+            r#"
+            const EventEmitter = require('node:events');
+            const { once, errorMonitor } = EventEmitter;
+
+            const e = new EventEmitter();
+            const order = [];
+            e.on('x', () => order.push('b'));
+            e.prependListener('x', () => order.push('a'));
+            e.once('x', () => order.push('once'));
+            e.emit('x');
+            e.emit('x');
+            if (order.join() !== 'a,b,once,a,b') throw new Error(order.join());
+
+            let threw = false;
+            try { e.emit('error', new Error('boom')); } catch (err) { threw = err.message === 'boom'; }
+            if (!threw) throw new Error('unhandled error event should throw');
+
+            let monitored = false;
+            e.on(errorMonitor, () => { monitored = true; });
+            e.on('error', () => {});
+            e.emit('error', new Error('handled'));
+            if (!monitored) throw new Error('errorMonitor');
+
+            e.removeAllListeners('x');
+            if (e.listenerCount('x') !== 0) throw new Error('removeAllListeners');
+            e.setMaxListeners(1);
+            if (e.getMaxListeners() !== 1) throw new Error('setMaxListeners');
+
+            const captured = new EventEmitter({ captureRejections: true });
+            captured.on('error', (err) => { globalThis.__capturedError = err.message; });
+            captured.on('job', async () => { throw new Error('async failure'); });
+            captured.emit('job');
+
+            once(e, 'ready').then(([value]) => {
+                if (value !== 42) throw new Error('events.once');
+            });
+            e.emit('ready', 42);
+        "#,
+        )
+        .unwrap();
+
+        let mut runtime = Runtime::new(RuntimeOptions {
+            cwd: Some(temp.path().to_path_buf()),
+            main_module: Some(main_file.clone()),
+            ..Default::default()
+        })
+        .unwrap();
+
+        runtime.execute_module(&main_file).await.unwrap();
+        runtime
+            .execute_script("if (globalThis.__capturedError !== 'async failure') throw new Error('captureRejections');")
+            .await
+            .unwrap();
+    }
+
+    #[cfg(unix)]
+    #[tokio::test]
+    async fn test_commonjs_tty_and_stdio_inherit() {
+        use std::fs;
+        let temp = tempfile::TempDir::new().unwrap();
+
+        let main_file = temp.path().join("main.js");
+        fs::write(
+            &main_file,
+            r#"
+            const tty = require('node:tty');
+            const { spawnSync, execSync } = require('node:child_process');
+
+            if (tty.isatty(-1) || tty.isatty(1.5)) throw new Error('isatty on invalid fd');
+            if (typeof process.stdout.isTTY !== 'boolean') throw new Error('stdout.isTTY');
+            if (!(process.stdout.columns > 0)) throw new Error('stdout.columns');
+            if (process.stdin.isTTY && typeof process.stdin.setRawMode !== 'function') throw new Error('stdin.setRawMode');
+
+            const inherited = spawnSync('echo', ['hi'], { stdio: 'inherit' });
+            if (inherited.status !== 0 || inherited.stdout !== null) throw new Error('inherit stdout');
+
+            const piped = spawnSync('echo', ['hi'], { stdio: ['ignore', 'pipe', 'inherit'] });
+            if (piped.stdout.toString() !== 'hi\n' || piped.stderr !== null) throw new Error('mixed stdio');
+
+            if (execSync('echo ok').toString() !== 'ok\n') throw new Error('execSync');
+        "#,
+        )
+        .unwrap();
+
+        let mut runtime = Runtime::new(RuntimeOptions {
+            cwd: Some(temp.path().to_path_buf()),
+            main_module: Some(main_file.clone()),
+            ..Default::default()
+        })
+        .unwrap();
+
+        runtime.execute_module(&main_file).await.unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_commonjs_readline() {
+        use std::fs;
+        let temp = tempfile::TempDir::new().unwrap();
+
+        let main_file = temp.path().join("main.js");
+        fs::write(
+            &main_file,
+            r#"
+            const readline = require('node:readline');
+            const EventEmitter = require('node:events');
+
+            function fakeStream() {
+              const stream = new EventEmitter();
+              stream.pause = () => stream;
+              stream.resume = () => stream;
+              return stream;
+            }
+
+            // Plain line splitting; question() takes the next line
+            const input = fakeStream();
+            const rl = readline.createInterface({ input });
+            const lines = [];
+            rl.on('line', (line) => lines.push(line));
+            rl.question('name? ', (answer) => lines.push('answer:' + answer));
+            input.emit('data', 'bob\r\nfoo\nba');
+            input.emit('data', 'r\rlast');
+            input.emit('end');
+            if (lines.join() !== 'answer:bob,foo,bar,last') throw new Error(lines.join());
+            if (!rl.closed) throw new Error('end should close');
+
+            // Terminal mode: line editing and history
+            const tin = fakeStream();
+            let written = '';
+            const out = { write(s) { written += s; return true; }, columns: 80 };
+            const trl = readline.createInterface({ input: tin, output: out, terminal: true, history: ['old'] });
+            const entered = [];
+            trl.on('line', (line) => entered.push(line));
+            let historyEvents = 0;
+            trl.on('history', () => historyEvents++);
+            tin.emit('data', 'one\r');
+            tin.emit('data', 'ab\x1b[Dx\r');
+            tin.emit('data', '\x1b[A\x1b[A\r');
+            tin.emit('data', 'hello world\x17\r');
+            if (entered.join() !== 'one,axb,one,hello ') throw new Error(entered.join());
+            if (trl.history.join() !== 'hello ,one,axb,one,old') throw new Error(trl.history.join());
+            if (historyEvents !== 4) throw new Error('history events');
+            trl.write(null, { name: 'up' });
+            if (trl.line !== 'hello ') throw new Error('history navigation');
+            trl.close();
+
+            const completing = readline.createInterface({
+              input: fakeStream(), output: out, terminal: true,
+              completer: (line) => [['help', 'hello'].filter((c) => c.startsWith(line)), line],
+            });
+            completing.write('h');
+            completing.write('\t', { name: 'tab' });
+            if (completing.line !== 'hel') throw new Error('completion: ' + completing.line);
+            completing.close();
+
+            const prl = require('node:readline/promises').createInterface({ input: fakeStream() });
+            prl.question('q? ').then((answer) => { globalThis.__answer = answer; });
+            prl.input.emit('data', 'yes\n');
+        "#,
+        )
+        .unwrap();
+
+        let mut runtime = Runtime::new(RuntimeOptions {
+            cwd: Some(temp.path().to_path_buf()),
+            main_module: Some(main_file.clone()),
+            ..Default::default()
+        })
+        .unwrap();
+
+        runtime.execute_module(&main_file).await.unwrap();
+        runtime
+            .execute_script(
+                "if (globalThis.__answer !== 'yes') throw new Error('promises question');",
+            )
+            .await
+            .unwrap();
+    }
+
+    /// Run `source` as the main CommonJS module and drain the event loop.
+    async fn run_main(source: &str) -> Runtime {
+        let temp = tempfile::TempDir::new().unwrap();
+        let main_file = temp.path().join("main.js");
+        std::fs::write(&main_file, source).unwrap();
+
+        let mut runtime = Runtime::new(RuntimeOptions {
+            cwd: Some(temp.path().to_path_buf()),
+            main_module: Some(main_file.clone()),
+            ..Default::default()
+        })
+        .unwrap();
+        runtime.execute_module(&main_file).await.unwrap();
+        runtime.run_event_loop().await.unwrap();
+        runtime
+    }
+
+    #[tokio::test]
+    async fn test_readline_lines_prompts_and_iteration() {
+        let mut runtime = run_main(
+            r#"
+            const readline = require('node:readline');
+            const EventEmitter = require('node:events');
+
+            function fakeStream() {
+              const stream = new EventEmitter();
+              stream.pause = () => stream;
+              stream.resume = () => stream;
+              return stream;
+            }
+            function fakeOutput() {
+              return { written: '', write(s) { this.written += s; return true; } };
+            }
+            const log = (globalThis.__log = []);
+
+            // for await yields each line until the input ends; chunks may split a
+            // character or a \r\n
+            const input = fakeStream();
+            const rl = readline.createInterface({ input });
+            rl.on('close', () => log.push('close'));
+            (async () => {
+              for await (const line of rl) log.push(line);
+              log.push('done');
+            })();
+            input.emit('data', Buffer.from([0x63, 0x61, 0x66, 0xc3]));
+            input.emit('data', Buffer.from([0xa9, 0x0d]));
+            input.emit('data', Buffer.from('\nnext\n'));
+            input.emit('data', 'tail');
+            input.emit('end');
+
+            // Breaking out of the loop closes the interface
+            const early = readline.createInterface({ input: fakeStream() });
+            (async () => {
+              for await (const line of early) {
+                globalThis.__first = line;
+                break;
+              }
+              globalThis.__earlyClosed = early.closed;
+            })();
+            early.input.emit('data', 'one\ntwo\n');
+
+            // Non-terminal prompts go to the output as-is; pause/resume are reported once
+            const output = fakeOutput();
+            const prompting = readline.createInterface({ input: fakeStream(), output, prompt: '$ ' });
+            if (prompting.terminal) throw new Error('a plain output is not a terminal');
+            const events = [];
+            prompting.on('pause', () => events.push('pause'));
+            prompting.on('resume', () => events.push('resume'));
+            prompting.prompt();
+            prompting.setPrompt('% ');
+            if (prompting.getPrompt() !== '% ') throw new Error('getPrompt');
+            prompting.pause();
+            prompting.pause();
+            prompting.prompt();
+            prompting.question('name? ', (answer) => events.push('answer:' + answer));
+            if (prompting.getPrompt() !== 'name? ') throw new Error('question prompt');
+            prompting.input.emit('data', 'ann\nafter\n');
+            if (prompting.getPrompt() !== '% ') throw new Error('prompt restored');
+            prompting.on('line', (line) => events.push('line:' + line));
+            prompting.write('typed\n');
+            prompting.close();
+            prompting.close();
+            if (output.written !== '$ % name? ') throw new Error(JSON.stringify(output.written));
+            if (events.join() !== 'pause,resume,answer:ann,line:typed,pause') throw new Error(events.join());
+
+            // An abort signal closes the interface
+            const controller = new AbortController();
+            const aborted = readline.createInterface({ input: fakeStream(), signal: controller.signal });
+            controller.abort();
+            if (!aborted.closed) throw new Error('signal should close');
+        "#,
+        )
+        .await;
+
+        assert_eq!(
+            eval_json(
+                &mut runtime,
+                "JSON.stringify([globalThis.__log, globalThis.__first, globalThis.__earlyClosed])"
+            ),
+            serde_json::json!([["close", "café", "next", "tail", "done"], "one", true])
+        );
+    }
+
+    #[tokio::test]
+    async fn test_readline_errors() {
+        let mut runtime = run_main(
+            r#"
+            const readline = require('node:readline');
+            const readlinePromises = require('node:readline/promises');
+            const EventEmitter = require('node:events');
+
+            function fakeStream() {
+              const stream = new EventEmitter();
+              stream.pause = () => stream;
+              stream.resume = () => stream;
+              return stream;
+            }
+            function code(fn) {
+              try {
+                fn();
+              } catch (err) {
+                return err.code || err.name;
+              }
+              return 'no error';
+            }
+            const results = (globalThis.__results = {});
+
+            // Invalid options
+            results.completer = code(() => readline.createInterface({ input: fakeStream(), completer: 'x' }));
+            results.historySize = code(() => readline.createInterface({ input: fakeStream(), historySize: -1 }));
+            results.history = code(() => readline.createInterface({ input: fakeStream(), history: 'x' }));
+            results.signal = code(() => readline.createInterface({ input: fakeStream(), signal: {} }));
+
+            // A closed interface rejects use; data after close is ignored
+            const input = fakeStream();
+            const rl = readline.createInterface({ input });
+            const lines = [];
+            rl.on('line', (line) => lines.push(line));
+            rl.close();
+            input.emit('data', 'ignored\n');
+            results.closedLines = lines.length;
+            results.question = code(() => rl.question('q? ', () => {}));
+            results.write = code(() => rl.write('x'));
+
+            // Input errors surface on the interface
+            const failing = readline.createInterface({ input: fakeStream() });
+            failing.on('error', (err) => { results.inputError = err.message; });
+            failing.input.emit('error', new Error('broken pipe'));
+
+            // An aborted question never answers; its line goes to 'line' instead
+            const asking = readline.createInterface({ input: fakeStream() });
+            const asked = [];
+            asking.on('line', (line) => asked.push('line:' + line));
+            const controller = new AbortController();
+            asking.question('q? ', { signal: controller.signal }, (answer) => asked.push('answer:' + answer));
+            controller.abort();
+            asking.question('q? ', { signal: controller.signal }, (answer) => asked.push('late:' + answer));
+            asking.input.emit('data', 'x\n');
+            results.aborted = asked.join();
+            results.abortedPrompt = asking.getPrompt();
+
+            // Tab completion errors are written to the output
+            let written = '';
+            const completing = readline.createInterface({
+              input: fakeStream(),
+              output: { write(s) { written += s; return true; }, columns: 80 },
+              terminal: true,
+              completer: () => { throw new Error('boom'); },
+            });
+            completing.write('\t', { name: 'tab' });
+            results.completionError = written.includes('Tab completion error: boom');
+            completing.close();
+
+            // readline/promises rejects instead of throwing
+            const closed = readlinePromises.createInterface({ input: fakeStream() });
+            closed.close();
+            closed.question('q? ').catch((err) => { results.promiseClosed = err.code; });
+            const preAborted = readlinePromises.createInterface({ input: fakeStream() });
+            preAborted.question('q? ', { signal: AbortSignal.abort() })
+              .catch((err) => { results.promisePreAborted = err.name; });
+            const pending = readlinePromises.createInterface({ input: fakeStream() });
+            const pendingController = new AbortController();
+            pending.question('q? ', { signal: pendingController.signal })
+              .catch((err) => { results.promiseAborted = err.name; });
+            pendingController.abort();
+            pending.on('line', (line) => { results.promiseLine = line; });
+            pending.input.emit('data', 'later\n');
+        "#,
+        )
+        .await;
+
+        assert_eq!(
+            eval_json(&mut runtime, "JSON.stringify(globalThis.__results)"),
+            serde_json::json!({
+                "completer": "ERR_INVALID_ARG_VALUE",
+                "historySize": "ERR_INVALID_ARG_VALUE",
+                "history": "ERR_INVALID_ARG_TYPE",
+                "signal": "TypeError",
+                "closedLines": 0,
+                "question": "ERR_USE_AFTER_CLOSE",
+                "write": "ERR_USE_AFTER_CLOSE",
+                "inputError": "broken pipe",
+                "aborted": "line:x",
+                "abortedPrompt": "> ",
+                "completionError": true,
+                "promiseClosed": "ERR_USE_AFTER_CLOSE",
+                "promisePreAborted": "AbortError",
+                "promiseAborted": "AbortError",
+                "promiseLine": "later",
+            })
+        );
+    }
+
+    #[tokio::test]
+    async fn test_readline_terminal_keys_and_cursor() {
+        let mut runtime = run_main(
+            r#"
+            const readline = require('node:readline');
+            const { Readline } = require('node:readline/promises');
+            const EventEmitter = require('node:events');
+
+            function fakeStream() {
+              const stream = new EventEmitter();
+              stream.pause = () => stream;
+              stream.resume = () => stream;
+              return stream;
+            }
+            function fakeOutput() {
+              return { written: '', columns: 80, write(s, cb) { this.written += s; if (cb) cb(); return true; } };
+            }
+
+            // Raw mode is switched on for the interface's lifetime
+            const rawModes = [];
+            const rawInput = fakeStream();
+            rawInput.setRawMode = (mode) => rawModes.push(mode);
+            readline.createInterface({ input: rawInput, output: fakeOutput(), terminal: true }).close();
+            if (rawModes.join() !== 'true,false') throw new Error('raw mode: ' + rawModes.join());
+
+            // Ctrl+C goes to a SIGINT listener, or closes; Ctrl+D closes an empty line
+            const sigint = readline.createInterface({ input: fakeStream(), output: fakeOutput(), terminal: true });
+            let interrupts = 0;
+            sigint.on('SIGINT', () => interrupts++);
+            sigint.input.emit('data', '\x03');
+            if (interrupts !== 1 || sigint.closed) throw new Error('SIGINT listener');
+            sigint.input.emit('data', 'ab\x1b[D\x04');
+            if (sigint.line !== 'a' || sigint.closed) throw new Error('Ctrl+D deletes under the cursor');
+            sigint.input.emit('data', '\x15\x04');
+            if (!sigint.closed) throw new Error('Ctrl+D on an empty line closes');
+            const plain = readline.createInterface({ input: fakeStream(), output: fakeOutput(), terminal: true });
+            plain.input.emit('data', '\x03');
+            if (!plain.closed) throw new Error('Ctrl+C without a listener closes');
+
+            // History is capped and can drop duplicates
+            const hist = readline.createInterface({
+              input: fakeStream(), output: fakeOutput(), terminal: true,
+              historySize: 2, removeHistoryDuplicates: true,
+            });
+            hist.input.emit('data', 'a\rb\ra\rc\r');
+            if (hist.history.join() !== 'c,a') throw new Error('history: ' + hist.history.join());
+            // The last line is emitted when the input ends
+            const ended = [];
+            hist.on('line', (line) => ended.push(line));
+            hist.input.emit('data', 'unfinished');
+            hist.input.emit('end');
+            if (ended.join() !== 'unfinished' || !hist.closed) throw new Error('end: ' + ended.join());
+
+            // Keypress decoding
+            const keys = fakeStream();
+            readline.emitKeypressEvents(keys);
+            const pressed = [];
+            keys.on('keypress', (s, key) => pressed.push(
+              `${key.name}${key.ctrl ? '+ctrl' : ''}${key.meta ? '+meta' : ''}${key.shift ? '+shift' : ''}:${s === undefined ? '' : JSON.stringify(s)}`));
+            keys.emit('data', 'A\x1b[1;5C\x1bb\x03\x1b[Z');
+            if (pressed.join(' ') !== 'a+shift:"A" right+ctrl: b+meta: c+ctrl:"\\u0003" tab+shift:')
+              throw new Error(pressed.join(' '));
+
+            // Cursor helpers write ANSI sequences
+            const out = fakeOutput();
+            readline.cursorTo(out, 3);
+            readline.cursorTo(out, 1, 2);
+            readline.moveCursor(out, -1, 2);
+            readline.clearLine(out, -1);
+            readline.clearScreenDown(out);
+            if (out.written !== '\x1b[4G\x1b[3;2H\x1b[1D\x1b[2B\x1b[1K\x1b[0J') throw new Error(JSON.stringify(out.written));
+
+            // readline/promises Readline batches until commit(); rollback() drops the batch
+            const batched = fakeOutput();
+            const batch = new Readline(batched);
+            batch.cursorTo(0).clearLine(0);
+            if (batched.written !== '') throw new Error('written before commit');
+            batch.rollback().moveCursor(0, 0).clearScreenDown();
+            batch.commit().then(() => { globalThis.__committed = batched.written; });
+        "#,
+        )
+        .await;
+
+        assert_eq!(
+            runtime.eval_to_string("globalThis.__committed").unwrap(),
+            "\x1b[0J"
+        );
+    }
+
     #[tokio::test]
     async fn test_commonjs_module_caching() {
         use std::fs;
@@ -7643,5 +8447,679 @@ mod tests {
 
         runtime.execute_module(&main_file).await.unwrap();
     }
+
+    /// Compile the fixture addon in `tests/fixtures/napi_hello.c` with the
+    /// system C compiler. Returns `None` when no compiler is available.
+    #[cfg(unix)]
+    fn build_fixture_addon(out: &std::path::Path) -> Option<std::path::PathBuf> {
+        let source =
+            std::path::Path::new(env!("CARGO_MANIFEST_DIR")).join("tests/fixtures/napi_hello.c");
+        let addon = out.join("hello.node");
+        let mut cmd =
+            std::process::Command::new(std::env::var("CC").unwrap_or_else(|_| "cc".to_string()));
+        cmd.args(["-shared", "-fPIC", "-o"])
+            .arg(&addon)
+            .arg(&source);
+        if cfg!(target_os = "macos") {
+            // napi_* symbols are resolved from the host binary at load time
+            cmd.args(["-undefined", "dynamic_lookup"]);
+        }
+        match cmd.status() {
+            Ok(status) if status.success() => Some(addon),
+            _ => {
+                eprintln!("C compiler not available, skipping native addon test");
+                None
+            }
+        }
+    }
+
+    #[tokio::test]
+    #[cfg(unix)]
+    async fn test_require_native_addon() {
+        use std::fs;
+        let temp = tempfile::TempDir::new().unwrap();
+        let release = temp.path().join("build/Release");
+        fs::create_dir_all(&release).unwrap();
+        if build_fixture_addon(&release).is_none() {
+            return;
+        }
+
+        let main_file = temp.path().join("main.js");
+        fs::write(
+            &main_file,
+            r#"
+            const addon = require('./build/Release/hello.node');
+            if (addon.hello() !== 'world') throw new Error('hello: ' + addon.hello());
+            if (addon.add(2, 3) !== 5) throw new Error('add: ' + addon.add(2, 3));
+            if (require('./build/Release/hello') !== addon) throw new Error('not cached');
+
+            const m = { exports: {} };
+            process.dlopen(m, require('path').join(__dirname, 'build/Release/hello.node'));
+            if (m.exports.add(1, 1) !== 2) throw new Error('dlopen');
+
+            let code;
+            try {
+                require('./missing.node');
+            } catch (e) {
+                code = e.code;
+            }
+            if (code !== 'MODULE_NOT_FOUND') throw new Error('missing: ' + code);
+
+            require('fs').writeFileSync(__dirname + '/broken.node', 'not a library');
+            try {
+                require('./broken.node');
+            } catch (e) {
+                code = e.code;
+            }
+            if (code !== 'ERR_DLOPEN_FAILED') throw new Error('broken: ' + code);
+        "#,
+        )
+        .unwrap();
+
+        let mut runtime = Runtime::new(RuntimeOptions {
+            cwd: Some(temp.path().to_path_buf()),
+            main_module: Some(main_file.clone()),
+            ..Default::default()
+        })
+        .unwrap();
+
+        runtime.execute_module(&main_file).await.unwrap();
+    }
+
+    /// Run test files through the `howth test` runner the way the V8 test
+    /// worker loads them, each as `(path, environment, source)`, and return
+    /// the runner's report.
+    async fn run_test_files(
+        runtime: &mut Runtime,
+        files: &[(&str, &str, &str)],
+    ) -> serde_json::Value {
+        let mut code = String::new();
+        for (path, environment, source) in files {
+            code.push_str(&format!(
+                "globalThis.__howth_begin_test_file({path:?}, {environment:?});\n(function () {{\n{source}\n}})();\n"
+            ));
+        }
+        code.push_str(
+            "globalThis.__howth_run_tests().then((report) => { globalThis.__report = JSON.stringify(report); });",
+        );
+        runtime.execute_script(&code).await.unwrap();
+        runtime.run_event_loop().await.unwrap();
+        serde_json::from_str(&runtime.eval_to_string("globalThis.__report").unwrap()).unwrap()
+    }
+
+    /// Run a single test file in the node environment and return the
+    /// runner's report.
+    async fn run_tests(runtime: &mut Runtime, source: &str) -> serde_json::Value {
+        run_test_files(runtime, &[("/virtual/test.js", "node", source)]).await
+    }
+
+    /// The result for the test named `name` in a runner report.
+    fn test_case<'a>(report: &'a serde_json::Value, name: &str) -> &'a serde_json::Value {
+        report["tests"]
+            .as_array()
+            .unwrap()
+            .iter()
+            .find(|case| case["name"] == name)
+            .unwrap_or_else(|| panic!("no test {name:?} in {report:#}"))
+    }
+
+    /// Evaluate `code` and parse the JSON it produces.
+    fn eval_json(runtime: &mut Runtime, code: &str) -> serde_json::Value {
+        serde_json::from_str(&runtime.eval_to_string(code).unwrap()).unwrap()
+    }
+
+    #[tokio::test]
+    async fn test_runner_hook_order() {
+        let mut runtime = Runtime::new(RuntimeOptions::default()).unwrap();
+        let report = run_tests(
+            &mut runtime,
+            r#"
+            const { describe, test, beforeEach, afterEach } = globalThis.__howth_modules["node:test"];
+            const order = (globalThis.__order = []);
+            beforeEach(() => order.push("outer beforeEach"));
+            afterEach(() => order.push("outer afterEach"));
+            describe("suite", () => {
+                beforeEach(() => order.push("inner beforeEach"));
+                afterEach(() => order.push("inner afterEach"));
+                test("works", () => order.push("test"));
+            });
+        "#,
+        )
+        .await;
+
+        assert_eq!(test_case(&report, "suite > works")["status"], "pass");
+        // beforeEach runs outermost first, afterEach innermost first
+        assert_eq!(
+            eval_json(&mut runtime, "JSON.stringify(globalThis.__order)"),
+            serde_json::json!([
+                "outer beforeEach",
+                "inner beforeEach",
+                "test",
+                "inner afterEach",
+                "outer afterEach",
+            ])
+        );
+    }
+
+    #[tokio::test]
+    async fn test_runner_failing_before_all_fails_its_suite() {
+        let mut runtime = Runtime::new(RuntimeOptions::default()).unwrap();
+        let report = run_tests(
+            &mut runtime,
+            r#"
+            const { describe, test, beforeAll } = globalThis.__howth_modules["node:test"];
+            const ran = (globalThis.__ran = []);
+            describe("db", () => {
+                beforeAll(() => { throw new Error("db down"); });
+                test("reads", () => ran.push("reads"));
+                describe("nested", () => {
+                    test("writes", () => ran.push("writes"));
+                });
+            });
+            test("outside", () => ran.push("outside"));
+        "#,
+        )
+        .await;
+
+        for name in ["db > reads", "db > nested > writes"] {
+            let case = test_case(&report, name);
+            assert_eq!(case["status"], "fail", "{name}");
+            assert!(
+                case["error"]
+                    .as_str()
+                    .unwrap()
+                    .starts_with("beforeAll hook failed: Error: db down"),
+                "{case:#}"
+            );
+        }
+        assert_eq!(test_case(&report, "outside")["status"], "pass");
+        // Tests under the failed hook never ran
+        assert_eq!(
+            eval_json(&mut runtime, "JSON.stringify(globalThis.__ran)"),
+            serde_json::json!(["outside"])
+        );
+    }
+
+    #[tokio::test]
+    async fn test_runner_failing_after_all_is_reported_separately() {
+        let mut runtime = Runtime::new(RuntimeOptions::default()).unwrap();
+        let report = run_tests(
+            &mut runtime,
+            r#"
+            const { describe, test, afterAll } = globalThis.__howth_modules["node:test"];
+            describe("server", () => {
+                afterAll(() => { throw new Error("port still open"); });
+                test("responds", () => {});
+            });
+        "#,
+        )
+        .await;
+
+        assert_eq!(report["total"], 2);
+        assert_eq!(test_case(&report, "server > responds")["status"], "pass");
+        let hook = test_case(&report, r#"server > "afterAll" hook"#);
+        assert_eq!(hook["status"], "fail");
+        assert!(
+            hook["error"]
+                .as_str()
+                .unwrap()
+                .starts_with("afterAll hook failed: Error: port still open"),
+            "{hook:#}"
+        );
+    }
+
+    #[tokio::test]
+    async fn test_runner_hook_timeout() {
+        let mut runtime = Runtime::new(RuntimeOptions::default()).unwrap();
+        let report = run_tests(
+            &mut runtime,
+            r#"
+            const { describe, test, beforeEach } = globalThis.__howth_modules["node:test"];
+            const ran = (globalThis.__ran = []);
+            describe("slow", () => {
+                beforeEach(() => new Promise(() => {}), 50);
+                test("never runs", () => ran.push("never runs"));
+            });
+        "#,
+        )
+        .await;
+
+        let case = test_case(&report, "slow > never runs");
+        assert_eq!(case["status"], "fail");
+        assert!(
+            case["error"]
+                .as_str()
+                .unwrap()
+                .starts_with("beforeEach hook failed: Error: beforeEach hook timed out after 50ms"),
+            "{case:#}"
+        );
+        assert_eq!(
+            eval_json(&mut runtime, "JSON.stringify(globalThis.__ran)"),
+            serde_json::json!([])
+        );
+    }
+
+    /// Fail with the whole report unless every test in it passed.
+    fn assert_all_passed(report: &serde_json::Value) {
+        assert_eq!(report["failed"], 0, "{report:#}");
+        assert!(report["passed"].as_u64().unwrap() > 0, "{report:#}");
+    }
+
+    #[tokio::test]
+    async fn test_fake_timers_advance_in_order() {
+        let mut runtime = Runtime::new(RuntimeOptions::default()).unwrap();
+        let report = run_tests(
+            &mut runtime,
+            r#"
+            const { test, expect, useFakeTimers, advanceTimersByTime } = globalThis.__howth_modules["howth:test"];
+            useFakeTimers();
+            test("advance", () => {
+                const fired = [];
+                setTimeout(() => fired.push("b"), 20);
+                setTimeout(() => fired.push("a"), 10);
+                setTimeout(() => fired.push("c"), 20);
+                setTimeout(() => fired.push("late"), 100);
+                advanceTimersByTime(19);
+                expect(fired).toEqual(["a"]);
+                advanceTimersByTime(1);
+                expect(fired).toEqual(["a", "b", "c"]);
+            });
+        "#,
+        )
+        .await;
+
+        assert_all_passed(&report);
+    }
+
+    #[tokio::test]
+    async fn test_fake_timers_run_only_pending() {
+        let mut runtime = Runtime::new(RuntimeOptions::default()).unwrap();
+        let report = run_tests(
+            &mut runtime,
+            r#"
+            const { test, expect, useFakeTimers, runOnlyPendingTimers, getTimerCount } = globalThis.__howth_modules["howth:test"];
+            useFakeTimers();
+            test("pending", () => {
+                const fired = [];
+                setTimeout(() => {
+                    fired.push("first");
+                    setTimeout(() => fired.push("scheduled by first"), 0);
+                }, 5);
+                runOnlyPendingTimers();
+                expect(fired).toEqual(["first"]);
+                expect(getTimerCount()).toBe(1);
+                runOnlyPendingTimers();
+                expect(fired).toEqual(["first", "scheduled by first"]);
+                expect(getTimerCount()).toBe(0);
+            });
+        "#,
+        )
+        .await;
+
+        assert_all_passed(&report);
+    }
+
+    #[tokio::test]
+    async fn test_fake_timers_set_system_time() {
+        let mut runtime = Runtime::new(RuntimeOptions::default()).unwrap();
+        let report = run_tests(
+            &mut runtime,
+            r#"
+            const { test, expect, useFakeTimers, setSystemTime, advanceTimersByTime } = globalThis.__howth_modules["howth:test"];
+            useFakeTimers();
+            test("date", () => {
+                setSystemTime(new Date("2020-01-01T00:00:00Z"));
+                expect(Date.now()).toBe(1577836800000);
+                expect(new Date().getTime()).toBe(1577836800000);
+                advanceTimersByTime(1500);
+                expect(Date.now()).toBe(1577836801500);
+                expect(new Date().toISOString()).toBe("2020-01-01T00:00:01.500Z");
+            });
+        "#,
+        )
+        .await;
+
+        assert_all_passed(&report);
+    }
+
+    #[tokio::test]
+    async fn test_fake_timers_reset_at_file_boundary() {
+        let mut runtime = Runtime::new(RuntimeOptions::default()).unwrap();
+        let report = run_test_files(
+            &mut runtime,
+            &[
+                (
+                    "/virtual/fake.test.js",
+                    "node",
+                    r#"
+                    const { test, expect, useFakeTimers } = globalThis.__howth_modules["howth:test"];
+                    useFakeTimers({ now: 1000 });
+                    test("fake", () => {
+                        expect(Date.now()).toBe(1000);
+                        setTimeout(() => { globalThis.__leaked = true; }, 10);
+                    });
+                    "#,
+                ),
+                (
+                    "/virtual/real.test.js",
+                    "node",
+                    r#"
+                    const { test, expect } = globalThis.__howth_modules["howth:test"];
+                    globalThis.__fakeWhileLoading = globalThis.__howth_fake_clock.installed;
+                    test("real", async () => {
+                        expect(Date.now()).toBeGreaterThan(1000);
+                        await new Promise((resolve) => setTimeout(resolve, 1));
+                    }, 1000);
+                    "#,
+                ),
+            ],
+        )
+        .await;
+
+        assert_all_passed(&report);
+        // The next file loads and runs with real timers; the fake timer left
+        // pending by the first file never fires
+        assert_eq!(
+            runtime
+                .eval_to_string("String(globalThis.__fakeWhileLoading)")
+                .unwrap(),
+            "false"
+        );
+        assert_eq!(
+            runtime
+                .eval_to_string("String(globalThis.__leaked)")
+                .unwrap(),
+            "undefined"
+        );
+        assert_eq!(
+            runtime
+                .eval_to_string("String(globalThis.__howth_fake_clock.installed)")
+                .unwrap(),
+            "false"
+        );
+    }
+
+    #[tokio::test]
+    async fn test_runner_timeout_aborts_signal() {
+        let mut runtime = Runtime::new(RuntimeOptions::default()).unwrap();
+        let report = run_tests(
+            &mut runtime,
+            r#"
+            const { test } = globalThis.__howth_modules["howth:test"];
+            test("hangs", function () {
+                globalThis.__signal = this.signal;
+                this.signal.addEventListener("abort", () => { globalThis.__abortReason = this.signal.reason.message; });
+                return new Promise(() => {});
+            }, 50);
+        "#,
+        )
+        .await;
+
+        let case = test_case(&report, "hangs");
+        assert_eq!(case["status"], "fail");
+        assert!(
+            case["error"]
+                .as_str()
+                .unwrap()
+                .starts_with("Error: Test timed out after 50ms"),
+            "{case:#}"
+        );
+        assert_eq!(
+            runtime
+                .eval_to_string("String(globalThis.__signal.aborted)")
+                .unwrap(),
+            "true"
+        );
+        assert_eq!(
+            runtime.eval_to_string("globalThis.__abortReason").unwrap(),
+            "Test timed out after 50ms"
+        );
+    }
+
+    #[tokio::test]
+    async fn test_runner_retry_reports_flaky() {
+        let mut runtime = Runtime::new(RuntimeOptions::default()).unwrap();
+        let report = run_tests(
+            &mut runtime,
+            r#"
+            const { test } = globalThis.__howth_modules["howth:test"];
+            let attempts = 0;
+            test("eventually", () => {
+                attempts++;
+                if (attempts < 3) throw new Error("attempt " + attempts);
+            }, { retry: 2 });
+            test("never", () => { throw new Error("nope"); }, { retry: 1 });
+        "#,
+        )
+        .await;
+
+        let eventually = test_case(&report, "eventually");
+        assert_eq!(eventually["status"], "pass");
+        assert_eq!(eventually["retries"], 2);
+        assert_eq!(eventually["flaky"], true);
+
+        let never = test_case(&report, "never");
+        assert_eq!(never["status"], "fail");
+        assert_eq!(never["retries"], 1);
+        assert_eq!(never["flaky"], false);
+    }
+
+    #[tokio::test]
+    async fn test_runner_concurrent_tests_overlap() {
+        let mut runtime = Runtime::new(RuntimeOptions::default()).unwrap();
+        let report = run_tests(
+            &mut runtime,
+            r#"
+            const { test } = globalThis.__howth_modules["howth:test"];
+            let active = 0;
+            globalThis.__maxActive = 0;
+            for (const name of ["a", "b", "c"]) {
+                test.concurrent(name, async () => {
+                    active++;
+                    globalThis.__maxActive = Math.max(globalThis.__maxActive, active);
+                    await new Promise((resolve) => setTimeout(resolve, 20));
+                    active--;
+                });
+            }
+        "#,
+        )
+        .await;
+
+        assert_all_passed(&report);
+        assert_eq!(
+            runtime
+                .eval_to_string("String(globalThis.__maxActive)")
+                .unwrap(),
+            "3"
+        );
+        // Results keep declaration order
+        let names: Vec<_> = report["tests"]
+            .as_array()
+            .unwrap()
+            .iter()
+            .map(|case| case["name"].as_str().unwrap())
+            .collect();
+        assert_eq!(names, ["a", "b", "c"]);
+    }
+
+    /// Tests using `test.tmpdir()` and `test.fixture()`; each records the
+    /// directories it used in `globalThis.__dirs`.
+    const TMPDIR_TESTS: &str = r#"
+                const { test, expect } = globalThis.__howth_modules["howth:test"];
+                const fs = globalThis.__howth_modules["node:fs"];
+                const dirs = (globalThis.__dirs = []);
+                const cleaned = (globalThis.__cleaned = []);
+                const useStore = test.fixture(({ tmpdir, cleanup }) => {
+                    const dir = tmpdir();
+                    cleanup(() => cleaned.push(dir));
+                    return { dir };
+                });
+                test("passes", () => {
+                    const dir = test.tmpdir();
+                    dirs.push(dir);
+                    expect(fs.existsSync(dir)).toBe(true);
+                    expect(test.tmpdir()).toBe(dir);
+                });
+                test("fails", function () {
+                    dirs.push(this.tmpdir());
+                    throw new Error("boom");
+                });
+                test("fixture", () => {
+                    const store = useStore();
+                    expect(useStore()).toBe(store);
+                    expect(fs.existsSync(store.dir)).toBe(true);
+                    dirs.push(store.dir);
+                });
+            "#;
+
+    /// Paths a test file stored as a JSON array in `global`.
+    fn eval_paths(runtime: &mut Runtime, global: &str) -> Vec<PathBuf> {
+        serde_json::from_value(eval_json(runtime, &format!("JSON.stringify({global})"))).unwrap()
+    }
+
+    #[tokio::test]
+    async fn test_runner_tmpdir_removed_after_test() {
+        let mut runtime = Runtime::new(RuntimeOptions::default()).unwrap();
+        let report = run_tests(&mut runtime, TMPDIR_TESTS).await;
+
+        assert_eq!(test_case(&report, "passes")["status"], "pass");
+        assert_eq!(test_case(&report, "fails")["status"], "fail");
+        assert_eq!(test_case(&report, "fixture")["status"], "pass");
+
+        // Each test had its own directory, which existed while it ran (the
+        // tests check) and is gone now, whether the test passed or failed
+        let dirs = eval_paths(&mut runtime, "globalThis.__dirs");
+        assert_eq!(dirs.len(), 3);
+        for dir in &dirs {
+            assert!(
+                dir.starts_with(fastnode_core::paths::test_tmp_dir()),
+                "{dir:?}"
+            );
+            assert!(!dir.exists(), "{dir:?} was not removed");
+        }
+        assert_ne!(dirs[0], dirs[1]);
+        // The fixture's cleanup ran
+        assert_eq!(
+            eval_paths(&mut runtime, "globalThis.__cleaned"),
+            [dirs[2].clone()]
+        );
+    }
+
+    #[tokio::test]
+    async fn test_runner_keep_tmp_keeps_tmpdirs() {
+        let mut runtime = Runtime::new(RuntimeOptions::default()).unwrap();
+        runtime
+            .execute_script("globalThis.__howth_keep_tmp = true;")
+            .await
+            .unwrap();
+        let report = run_tests(&mut runtime, TMPDIR_TESTS).await;
+
+        assert_eq!(report["failed"], 1);
+
+        let dirs = eval_paths(&mut runtime, "globalThis.__dirs");
+        assert_eq!(dirs.len(), 3);
+        for dir in &dirs {
+            assert!(dir.is_dir(), "{dir:?} was not kept");
+            std::fs::remove_dir_all(dir).unwrap();
+        }
+        // Cleanups still run
+        assert_eq!(
+            eval_paths(&mut runtime, "globalThis.__cleaned"),
+            [dirs[2].clone()]
+        );
+    }
+
+    #[tokio::test]
+    async fn test_tmpdir_remove_refuses_other_directories() {
+        let outside = tempfile::TempDir::new().unwrap();
+        let escape = fastnode_core::paths::test_tmp_dir()
+            .join("..")
+            .join(outside.path().file_name().unwrap());
+        let mut runtime = Runtime::new(RuntimeOptions::default()).unwrap();
+        for path in [outside.path(), escape.as_path()] {
+            runtime
+                .execute_script(&format!(
+                    r#"{{
+                        let refused = false;
+                        try {{
+                            Deno.core.ops.op_howth_test_tmpdir_remove({:?});
+                        }} catch (e) {{
+                            refused = String(e).includes("not a test temp directory");
+                        }}
+                        if (!refused) throw new Error("removed a directory outside the test temp root");
+                    }}"#,
+                    path.to_string_lossy()
+                ))
+                .await
+                .unwrap();
+        }
+        assert!(outside.path().is_dir());
+    }
+
+    /// One test per DOM-lite area: selectors, attributes and `classList`,
+    /// event dispatch, and `innerHTML`/`textContent`.
+    const DOM_TESTS: &str = r##"
+                const { test, expect } = globalThis.__howth_modules["howth:test"];
+                test("querySelector", () => {
+                    document.body.innerHTML = '<ul id="list"><li class="item">one</li><li class="item active">two</li></ul><p>three</p>';
+                    expect(document.querySelector("#list .active").textContent).toBe("two");
+                    expect(document.querySelectorAll("li.item").length).toBe(2);
+                    expect(document.querySelector("ul > li:first-child").textContent).toBe("one");
+                    expect(document.querySelector("span")).toBe(null);
+                });
+                test("attributes and classList", () => {
+                    const el = document.createElement("div");
+                    el.setAttribute("data-id", "7");
+                    expect(el.getAttribute("data-id")).toBe("7");
+                    expect(el.dataset.id).toBe("7");
+                    el.classList.add("a", "b");
+                    el.classList.remove("a");
+                    el.classList.toggle("c");
+                    expect(el.className).toBe("b c");
+                    expect(el.classList.contains("a")).toBe(false);
+                    el.removeAttribute("data-id");
+                    expect(el.hasAttribute("data-id")).toBe(false);
+                });
+                test("events bubble and can be prevented", () => {
+                    document.body.innerHTML = '<div id="outer"><button id="btn">go</button></div>';
+                    const button = document.getElementById("btn");
+                    const seen = [];
+                    document.getElementById("outer").addEventListener("click", (e) => {
+                        seen.push("outer:" + e.target.id);
+                        e.preventDefault();
+                    });
+                    button.addEventListener("click", () => seen.push("button"));
+                    const click = new Event("click", { bubbles: true, cancelable: true });
+                    expect(button.dispatchEvent(click)).toBe(false);
+                    expect(click.defaultPrevented).toBe(true);
+                    expect(seen).toEqual(["button", "outer:btn"]);
+                    seen.length = 0;
+                    button.dispatchEvent(new Event("click"));
+                    expect(seen).toEqual(["button"]);
+                });
+                test("innerHTML and textContent round-trip", () => {
+                    const el = document.createElement("div");
+                    el.innerHTML = '<p class="x">a &amp; b</p><br><span>c</span>';
+                    expect(el.innerHTML).toBe('<p class="x">a &amp; b</p><br><span>c</span>');
+                    expect(el.textContent).toBe("a & bc");
+                    el.textContent = "<b>raw</b>";
+                    expect(el.children.length).toBe(0);
+                    expect(el.innerHTML).toBe("&lt;b&gt;raw&lt;/b&gt;");
+                });
+            "##;
+
+    #[tokio::test]
+    async fn test_dom_environment() {
+        let mut runtime = Runtime::new(RuntimeOptions::default()).unwrap();
+        let report =
+            run_test_files(&mut runtime, &[("/virtual/dom.test.js", "dom", DOM_TESTS)]).await;
+        assert_all_passed(&report);
+        assert_eq!(report["passed"], 4);
+        // The DOM globals only exist while the file's tests run
+        assert_eq!(
+            runtime.eval_to_string("typeof document").unwrap(),
+            "undefined"
+        );
+    }
 }
 // Force rebuild Wed Jan 28 13:43:43 IST 2026
@@ -0,0 +1,63 @@
+/*
+ * Minimal Node-API addon used by the runtime's native addon tests.
+ *
+ * Declares the handful of napi_* functions it uses instead of including
+ * node_api.h, so it builds with any C compiler and no Node.js headers. The
+ * symbols are resolved from the host process when the addon is loaded.
+ */
+
+#include <stddef.h>
+#include <stdint.h>
+
+typedef struct napi_env__ *napi_env;
+typedef struct napi_value__ *napi_value;
+typedef struct napi_callback_info__ *napi_callback_info;
+typedef int napi_status;
+typedef napi_value (*napi_callback)(napi_env env, napi_callback_info info);
+
+#define NAPI_AUTO_LENGTH SIZE_MAX
+
+extern napi_status napi_create_function(napi_env env, const char *name, size_t length,
+                                        napi_callback cb, void *data, napi_value *result);
+extern napi_status napi_set_named_property(napi_env env, napi_value object,
+                                           const char *name, napi_value value);
+extern napi_status napi_get_cb_info(napi_env env, napi_callback_info info, size_t *argc,
+                                    napi_value *argv, napi_value *this_arg, void **data);
+extern napi_status napi_get_value_int32(napi_env env, napi_value value, int32_t *result);
+extern napi_status napi_create_int32(napi_env env, int32_t value, napi_value *result);
+extern napi_status napi_create_string_utf8(napi_env env, const char *str, size_t length,
+                                           napi_value *result);
+
+static napi_value hello(napi_env env, napi_callback_info info) {
+    napi_value result;
+    (void)info;
+    napi_create_string_utf8(env, "world", NAPI_AUTO_LENGTH, &result);
+    return result;
+}
+
+static napi_value add(napi_env env, napi_callback_info info) {
+    size_t argc = 2;
+    napi_value argv[2];
+    int32_t a = 0, b = 0;
+    napi_value result;
+
+    napi_get_cb_info(env, info, &argc, argv, NULL, NULL);
+    if (argc == 2) {
+        napi_get_value_int32(env, argv[0], &a);
+        napi_get_value_int32(env, argv[1], &b);
+    }
+    napi_create_int32(env, a + b, &result);
+    return result;
+}
+
+static void export_function(napi_env env, napi_value exports, const char *name, napi_callback cb) {
+    napi_value fn;
+    napi_create_function(env, name, NAPI_AUTO_LENGTH, cb, NULL, &fn);
+    napi_set_named_property(env, exports, name, fn);
+}
+
+napi_value napi_register_module_v1(napi_env env, napi_value exports) {
+    export_function(env, exports, "hello", hello);
+    export_function(env, exports, "add", add);
+    return exports;
+}
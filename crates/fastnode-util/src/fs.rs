@@ -1,6 +1,152 @@
 use std::fs::{self, File};
 use std::io::{self, Write};
-use std::path::Path;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Mutex, PoisonError};
+use std::time::Duration;
+
+/// How many times [`retry_on_busy`] retries a busy operation.
+const BUSY_RETRIES: u32 = 8;
+
+/// Delay before the first retry; doubles on each further attempt.
+const BUSY_BACKOFF: Duration = Duration::from_millis(10);
+
+/// Distinguishes temp files of concurrent [`atomic_write`] calls in one process.
+static TEMP_COUNTER: AtomicU64 = AtomicU64::new(0);
+
+/// Pairs of filesystems (source and destination device ids) that cannot
+/// clone files between each other.
+static NO_REFLINK: Mutex<Vec<(u64, u64)>> = Mutex::new(Vec::new());
+
+/// Prefix of Windows extended-length ("verbatim") paths.
+const VERBATIM_PREFIX: &str = r"\\?\";
+
+/// Prefix of verbatim UNC paths (`\\?\UNC\server\share`).
+const VERBATIM_UNC_PREFIX: &str = r"\\?\UNC\";
+
+/// Extended-length form of `path` for Windows file APIs.
+///
+/// Deep `node_modules` trees easily exceed `MAX_PATH` (260 characters),
+/// beyond which the plain Win32 APIs fail. An absolute path is normalized
+/// (`/` becomes `\`, `.` and `..` are resolved lexically, since verbatim
+/// paths are passed to the filesystem as is) and prefixed with `\\?\`;
+/// UNC paths (`\\server\share\...`) become `\\?\UNC\server\share\...`.
+/// Relative and already-verbatim paths are returned unchanged, as is every
+/// path on other platforms.
+#[must_use]
+pub fn long_path(path: &Path) -> PathBuf {
+    #[cfg(windows)]
+    if let Some(verbatim) = path.to_str().and_then(to_verbatim) {
+        return PathBuf::from(verbatim);
+    }
+    path.to_path_buf()
+}
+
+/// `path` without the Windows `\\?\` prefix, for display and for
+/// comparing paths.
+///
+/// `\\?\C:\x` becomes `C:\x` and `\\?\UNC\server\share\x` becomes
+/// `\\server\share\x`. Unlike `dunce::simplified`, long paths are
+/// simplified too (the standard library re-adds the prefix itself when
+/// needed), so the same file always has the same spelling. Paths that
+/// can't be written without the prefix are returned unchanged.
+#[must_use]
+pub fn simplified(path: &Path) -> PathBuf {
+    #[cfg(windows)]
+    if let Some(simple) = path.to_str().and_then(strip_verbatim) {
+        return PathBuf::from(simple);
+    }
+    path.to_path_buf()
+}
+
+/// Canonicalize `path` and return it [`simplified`].
+///
+/// Use this rather than [`fs::canonicalize`] for paths that are compared
+/// or used as cache keys, so project roots on network shares and files deep
+/// in `node_modules` are spelled the same way everywhere.
+///
+/// # Errors
+/// Returns an error if the path doesn't exist or cannot be resolved.
+pub fn canonicalize(path: &Path) -> io::Result<PathBuf> {
+    fs::canonicalize(path).map(|path| simplified(&path))
+}
+
+/// Extended-length spelling of an absolute Windows path, or `None` if it
+/// isn't absolute or already has a `\\?\` or `\\.\` prefix.
+#[cfg_attr(not(windows), allow(dead_code))]
+fn to_verbatim(path: &str) -> Option<String> {
+    let is_sep = |c: char| c == '\\' || c == '/';
+    let bytes = path.as_bytes();
+    let (mut result, rest, min_parts) =
+        if bytes.len() >= 3 && bytes[0].is_ascii_alphabetic() && bytes[1] == b':' {
+            if !is_sep(char::from(bytes[2])) {
+                // Drive-relative (`C:foo`)
+                return None;
+            }
+            (format!("{VERBATIM_PREFIX}{}", &path[..2]), &path[3..], 0)
+        } else if path.len() > 2 && path.starts_with(is_sep) && path[1..].starts_with(is_sep) {
+            let rest = &path[2..];
+            if rest.starts_with(['?', '.']) && rest[1..].starts_with(is_sep) {
+                return None;
+            }
+            // `server` and `share` can't be popped by `..`
+            (
+                VERBATIM_UNC_PREFIX.trim_end_matches('\\').to_string(),
+                rest,
+                2,
+            )
+        } else {
+            return None;
+        };
+
+    let mut parts: Vec<&str> = Vec::new();
+    for part in rest.split(is_sep) {
+        match part {
+            "" | "." => {}
+            ".." => {
+                if parts.len() > min_parts {
+                    parts.pop();
+                }
+            }
+            part => parts.push(part),
+        }
+    }
+    if parts.len() < min_parts {
+        return None;
+    }
+    result.push('\\');
+    result.push_str(&parts.join("\\"));
+    Some(result)
+}
+
+/// Plain spelling of a `\\?\` path, or `None` if it has no such prefix or
+/// can't be written without it.
+#[cfg_attr(not(windows), allow(dead_code))]
+fn strip_verbatim(path: &str) -> Option<String> {
+    let (prefix, rest) = if let Some(rest) = path.strip_prefix(VERBATIM_UNC_PREFIX) {
+        ("\\\\", rest)
+    } else {
+        let rest = path.strip_prefix(VERBATIM_PREFIX)?;
+        let bytes = rest.as_bytes();
+        // `\\?\C:` alone would become the drive-relative `C:`
+        let is_drive = bytes.len() >= 3
+            && bytes[0].is_ascii_alphabetic()
+            && bytes[1] == b':'
+            && bytes[2] == b'\\';
+        if !is_drive {
+            return None;
+        }
+        ("", rest)
+    };
+    // Without the prefix, `/` would be a separator and `.`/`..` would be
+    // resolved; trailing dots and spaces would be trimmed
+    let representable = !rest.contains('/')
+        && rest
+            .split('\\')
+            .skip(1)
+            .all(|part| part != "." && part != ".." && !part.ends_with(['.', ' ']));
+    representable.then(|| format!("{prefix}{rest}"))
+}
 
 /// Read a file to string, replacing invalid UTF-8 sequences with the replacement character.
 ///
@@ -14,43 +160,358 @@ pub fn read_to_string_lossy(path: &Path) -> io::Result<String> {
 /// Atomically write bytes to a file by writing to a temp file then renaming.
 ///
 /// This provides crash-safety: the file will either have the old contents or
-/// the new contents, never a partial write.
+/// the new contents, never a partial write. The temp file is fsynced before
+/// the rename and, on Unix, the parent directory after it so the rename
+/// itself survives a crash. A rename blocked by another process holding the
+/// target open (common on Windows) is retried, see [`retry_on_busy`].
+/// Paths longer than `MAX_PATH` are written through [`long_path`].
 ///
 /// # Errors
 /// Returns an error if the write or rename fails.
 pub fn atomic_write(path: &Path, bytes: &[u8]) -> io::Result<()> {
-    let parent = path.parent().unwrap_or(Path::new("."));
+    let path = &long_path(path);
+    let parent = match path.parent() {
+        Some(p) if !p.as_os_str().is_empty() => p,
+        _ => Path::new("."),
+    };
 
     // Create temp file in the same directory to ensure same filesystem for rename
-    let mut temp_path = parent.to_path_buf();
-    temp_path.push(format!(
-        ".{}.tmp.{}",
+    let temp_path = parent.join(format!(
+        ".{}.tmp.{}.{}",
         path.file_name().and_then(|n| n.to_str()).unwrap_or("file"),
-        std::process::id()
+        std::process::id(),
+        TEMP_COUNTER.fetch_add(1, Ordering::Relaxed)
     ));
 
-    // Write to temp file
+    let result = (|| {
+        {
+            let mut file = File::create(&temp_path)?;
+            file.write_all(bytes)?;
+            file.sync_all()?;
+        }
+        retry_on_busy(|| fs::rename(&temp_path, path))
+    })();
+    if let Err(e) = result {
+        let _ = fs::remove_file(&temp_path);
+        return Err(e);
+    }
+
+    #[cfg(unix)]
+    if let Ok(dir) = File::open(parent) {
+        let _ = dir.sync_all();
+    }
+    Ok(())
+}
+
+/// Whether `err` means another process temporarily holds the file: `EBUSY`
+/// on Unix; sharing/lock violations or access denied (typically antivirus
+/// or the search indexer) on Windows.
+#[must_use]
+pub fn is_busy(err: &io::Error) -> bool {
+    #[cfg(windows)]
     {
-        let mut file = File::create(&temp_path)?;
-        file.write_all(bytes)?;
-        file.sync_all()?;
-    }
-
-    // Try atomic rename
-    match fs::rename(&temp_path, path) {
-        Ok(()) => Ok(()),
-        Err(e) => {
-            // On Windows, rename can fail if target exists. Try copy + remove as fallback.
-            if cfg!(windows) {
-                fs::copy(&temp_path, path)?;
-                let _ = fs::remove_file(&temp_path);
-                Ok(())
-            } else {
-                let _ = fs::remove_file(&temp_path);
-                Err(e)
+        // ERROR_ACCESS_DENIED, ERROR_SHARING_VIOLATION, ERROR_LOCK_VIOLATION
+        matches!(err.raw_os_error(), Some(5 | 32 | 33))
+    }
+    #[cfg(unix)]
+    {
+        err.raw_os_error() == Some(libc::EBUSY)
+    }
+    #[cfg(not(any(unix, windows)))]
+    {
+        let _ = err;
+        false
+    }
+}
+
+/// Run `op`, retrying with exponential backoff while it fails with a
+/// [busy](is_busy) error. Other errors are returned immediately.
+///
+/// # Errors
+/// Returns the last error from `op`.
+pub fn retry_on_busy<T>(mut op: impl FnMut() -> io::Result<T>) -> io::Result<T> {
+    let mut delay = BUSY_BACKOFF;
+    let mut attempt = 0;
+    loop {
+        match op() {
+            Err(e) if attempt < BUSY_RETRIES && is_busy(&e) => {
+                std::thread::sleep(delay);
+                delay *= 2;
+                attempt += 1;
+            }
+            result => return result,
+        }
+    }
+}
+
+/// Remove `path` (file, directory tree, symlink or junction), refusing to
+/// touch anything that is not strictly inside `root`.
+///
+/// Links are removed themselves, never followed. `..` components and
+/// symlinked parents are resolved before the check, so neither can be used
+/// to escape `root`. Removing a path that doesn't exist succeeds.
+///
+/// # Errors
+/// Returns [`io::ErrorKind::InvalidInput`] if `path` is not inside `root`,
+/// or an error if removal fails.
+pub fn remove_within(root: &Path, path: &Path) -> io::Result<()> {
+    let root = root.canonicalize()?;
+    let path = if path.is_absolute() {
+        path.to_path_buf()
+    } else {
+        std::env::current_dir()?.join(path)
+    };
+    let (Some(parent), Some(name)) = (path.parent(), path.file_name()) else {
+        return Err(outside_root(&path, &root));
+    };
+    let parent = match parent.canonicalize() {
+        Ok(parent) => parent,
+        Err(e) if e.kind() == io::ErrorKind::NotFound => return Ok(()),
+        Err(e) => return Err(e),
+    };
+    let target = parent.join(name);
+    if !parent.starts_with(&root) || target == root {
+        return Err(outside_root(&target, &root));
+    }
+
+    let Ok(metadata) = fs::symlink_metadata(&target) else {
+        return Ok(());
+    };
+    if is_link(&metadata) {
+        retry_on_busy(|| remove_link(&target))
+    } else if metadata.is_dir() {
+        retry_on_busy(|| fs::remove_dir_all(&target))
+    } else {
+        retry_on_busy(|| fs::remove_file(&target))
+    }
+}
+
+fn outside_root(path: &Path, root: &Path) -> io::Error {
+    io::Error::new(
+        io::ErrorKind::InvalidInput,
+        format!(
+            "refusing to remove {}: not inside {}",
+            path.display(),
+            root.display()
+        ),
+    )
+}
+
+/// Whether `metadata` (from [`fs::symlink_metadata`]) is a symlink or, on
+/// Windows, any reparse point such as a junction.
+#[must_use]
+pub fn is_link(metadata: &fs::Metadata) -> bool {
+    #[cfg(windows)]
+    {
+        use std::os::windows::fs::MetadataExt;
+        // FILE_ATTRIBUTE_REPARSE_POINT
+        metadata.file_type().is_symlink() || metadata.file_attributes() & 0x400 != 0
+    }
+    #[cfg(not(windows))]
+    {
+        metadata.file_type().is_symlink()
+    }
+}
+
+/// Remove a symlink or junction without touching what it points to.
+///
+/// # Errors
+/// Returns an error if the link cannot be removed.
+pub fn remove_link(path: &Path) -> io::Result<()> {
+    #[cfg(windows)]
+    {
+        // Directory links (junctions, dir symlinks) are removed as directories
+        if fs::symlink_metadata(path)?.is_dir() {
+            return fs::remove_dir(path);
+        }
+    }
+    fs::remove_file(path)
+}
+
+/// How [`symlink_dir`] ended up linking a directory.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum LinkKind {
+    /// A symbolic link.
+    Symlink,
+    /// An NTFS junction (Windows without symlink privileges).
+    Junction,
+    /// A recursive copy (no link support).
+    Copy,
+}
+
+/// Make `link` point at the directory `target`.
+///
+/// Unix creates a symlink. Windows tries a directory symlink (requires
+/// Developer Mode or admin), then a junction. Where neither works the
+/// directory is copied. A relative `target` is resolved against `link`'s
+/// parent, as it would be for a symlink.
+///
+/// # Errors
+/// Returns the error of the last attempted method.
+pub fn symlink_dir(target: &Path, link: &Path) -> io::Result<LinkKind> {
+    #[cfg(unix)]
+    {
+        std::os::unix::fs::symlink(target, link).map(|()| LinkKind::Symlink)
+    }
+    #[cfg(windows)]
+    {
+        if std::os::windows::fs::symlink_dir(target, link).is_ok() {
+            return Ok(LinkKind::Symlink);
+        }
+        // Junctions need an absolute target
+        let absolute = link.parent().unwrap_or(Path::new(".")).join(target);
+        if junction::create(&absolute, link).is_ok() {
+            return Ok(LinkKind::Junction);
+        }
+        copy_dir_all(&absolute, link).map(|()| LinkKind::Copy)
+    }
+    #[cfg(not(any(unix, windows)))]
+    {
+        let absolute = link.parent().unwrap_or(Path::new(".")).join(target);
+        copy_dir_all(&absolute, link).map(|()| LinkKind::Copy)
+    }
+}
+
+/// How [`clone_or_copy`] copied a file.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CopyKind {
+    /// A copy-on-write clone sharing the source's blocks (`clonefile` on
+    /// APFS, `FICLONE` on btrfs and XFS).
+    Reflink,
+    /// A byte-for-byte copy.
+    Copy,
+}
+
+/// Clone `src` to `dst` without copying its data: both files share the same
+/// blocks until one of them is written.
+///
+/// # Errors
+/// Returns an error if the filesystem cannot clone (or the platform has no
+/// clone API, [`io::ErrorKind::Unsupported`]), if `src` and `dst` are on
+/// different filesystems, or if either cannot be opened.
+pub fn reflink(src: &Path, dst: &Path) -> io::Result<()> {
+    #[cfg(any(target_os = "linux", target_os = "android"))]
+    {
+        use std::os::unix::io::AsRawFd;
+        let src_file = File::open(src)?;
+        let dst_file = File::create(dst)?;
+        // SAFETY: both descriptors stay open for the duration of the call
+        if unsafe { libc::ioctl(dst_file.as_raw_fd(), libc::FICLONE, src_file.as_raw_fd()) } == -1 {
+            let err = io::Error::last_os_error();
+            drop(dst_file);
+            let _ = fs::remove_file(dst);
+            return Err(err);
+        }
+        dst_file.set_permissions(src_file.metadata()?.permissions())
+    }
+    #[cfg(target_vendor = "apple")]
+    {
+        use std::ffi::CString;
+        use std::os::unix::ffi::OsStrExt;
+        let src_c = CString::new(src.as_os_str().as_bytes())?;
+        let dst_c = CString::new(dst.as_os_str().as_bytes())?;
+        // clonefile refuses to replace an existing file
+        match fs::remove_file(dst) {
+            Ok(()) => {}
+            Err(e) if e.kind() == io::ErrorKind::NotFound => {}
+            Err(e) => return Err(e),
+        }
+        // SAFETY: both are valid NUL-terminated paths
+        if unsafe { libc::clonefile(src_c.as_ptr(), dst_c.as_ptr(), 0) } == 0 {
+            Ok(())
+        } else {
+            Err(io::Error::last_os_error())
+        }
+    }
+    #[cfg(not(any(target_os = "linux", target_os = "android", target_vendor = "apple")))]
+    {
+        let _ = (src, dst);
+        Err(io::Error::from(io::ErrorKind::Unsupported))
+    }
+}
+
+/// Copy `src` to `dst`, as a [`reflink`] where the filesystem supports it
+/// and byte for byte otherwise.
+///
+/// A clone costs a metadata update instead of reading and writing the whole
+/// file, and takes no extra space. Support is detected on the first attempt
+/// per pair of filesystems and remembered for the rest of the process, so
+/// ext4, tmpfs or a copy across filesystems pay for one failed attempt only.
+/// Like [`fs::copy`], an existing `dst` is overwritten and permissions are
+/// copied.
+///
+/// # Errors
+/// Returns an error if `src` cannot be read or `dst` cannot be written.
+pub fn clone_or_copy(src: &Path, dst: &Path) -> io::Result<CopyKind> {
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::MetadataExt;
+        let dst_dir = match dst.parent() {
+            Some(p) if !p.as_os_str().is_empty() => p,
+            _ => Path::new("."),
+        };
+        let devices = (fs::metadata(src)?.dev(), fs::metadata(dst_dir)?.dev());
+        let known_unsupported = NO_REFLINK
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner)
+            .contains(&devices);
+        if !known_unsupported {
+            match reflink(src, dst) {
+                Ok(()) => return Ok(CopyKind::Reflink),
+                Err(e) if is_reflink_unsupported(&e) => {
+                    NO_REFLINK
+                        .lock()
+                        .unwrap_or_else(PoisonError::into_inner)
+                        .push(devices);
+                }
+                // Anything else is reported by the copy if it persists
+                Err(_) => {}
             }
         }
     }
+    fs::copy(src, dst).map(|_| CopyKind::Copy)
+}
+
+/// Whether a [`reflink`] error means clones don't work between the two
+/// filesystems at all.
+#[cfg(unix)]
+fn is_reflink_unsupported(err: &io::Error) -> bool {
+    err.kind() == io::ErrorKind::Unsupported
+        || err.raw_os_error().is_some_and(|code| {
+            [
+                libc::EOPNOTSUPP,
+                libc::ENOTSUP,
+                libc::EXDEV,
+                libc::EINVAL,
+                libc::ENOTTY,
+                libc::ENOSYS,
+            ]
+            .contains(&code)
+        })
+}
+
+/// Recursively copy the directory `src` to `dst`, following symlinks.
+/// Files are cloned where possible (see [`clone_or_copy`]). Works on trees
+/// deeper than `MAX_PATH` on Windows.
+///
+/// # Errors
+/// Returns an error if any entry cannot be read or written.
+pub fn copy_dir_all(src: &Path, dst: &Path) -> io::Result<()> {
+    copy_dir_recursive(&long_path(src), &long_path(dst))
+}
+
+fn copy_dir_recursive(src: &Path, dst: &Path) -> io::Result<()> {
+    fs::create_dir_all(dst)?;
+    for entry in fs::read_dir(src)? {
+        let entry = entry?;
+        let target = dst.join(entry.file_name());
+        if entry.path().is_dir() {
+            copy_dir_recursive(&entry.path(), &target)?;
+        } else {
+            clone_or_copy(&entry.path(), &target)?;
+        }
+    }
+    Ok(())
 }
 
 #[cfg(test)]
@@ -110,4 +571,224 @@ mod tests {
             "test.txt"
         );
     }
+
+    #[test]
+    fn test_retry_on_busy() {
+        let mut calls = 0;
+        let result = retry_on_busy(|| {
+            calls += 1;
+            if calls < 3 {
+                Err(busy_error())
+            } else {
+                Ok(calls)
+            }
+        });
+        assert_eq!(result.unwrap(), 3);
+
+        // Other errors are not retried
+        let mut calls = 0;
+        let result: io::Result<()> = retry_on_busy(|| {
+            calls += 1;
+            Err(io::Error::from(io::ErrorKind::NotFound))
+        });
+        assert!(result.is_err());
+        assert_eq!(calls, 1);
+    }
+
+    #[cfg(unix)]
+    fn busy_error() -> io::Error {
+        io::Error::from_raw_os_error(libc::EBUSY)
+    }
+
+    #[cfg(windows)]
+    fn busy_error() -> io::Error {
+        io::Error::from_raw_os_error(32)
+    }
+
+    #[test]
+    fn test_remove_within() {
+        let dir = tempdir().unwrap();
+        let root = dir.path().join("root");
+        fs::create_dir_all(root.join("out/sub")).unwrap();
+        fs::write(root.join("out/sub/file.js"), "x").unwrap();
+        fs::write(dir.path().join("outside.txt"), "keep").unwrap();
+
+        remove_within(&root, &root.join("out")).unwrap();
+        assert!(!root.join("out").exists());
+
+        // Missing paths are fine
+        remove_within(&root, &root.join("out")).unwrap();
+
+        // Escaping the root is refused
+        let err = remove_within(&root, &root.join("../outside.txt")).unwrap_err();
+        assert_eq!(err.kind(), io::ErrorKind::InvalidInput);
+        assert!(remove_within(&root, &root).is_err());
+        assert!(dir.path().join("outside.txt").exists());
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_remove_within_does_not_follow_links() {
+        let dir = tempdir().unwrap();
+        let root = dir.path().join("root");
+        let target = dir.path().join("target");
+        fs::create_dir_all(&root).unwrap();
+        fs::create_dir_all(&target).unwrap();
+        fs::write(target.join("keep.txt"), "keep").unwrap();
+
+        // A link inside the root is removed, not its target
+        assert_eq!(
+            symlink_dir(&target, &root.join("link")).unwrap(),
+            LinkKind::Symlink
+        );
+        assert!(root.join("link/keep.txt").exists());
+        remove_within(&root, &root.join("link")).unwrap();
+        assert!(fs::symlink_metadata(root.join("link")).is_err());
+        assert!(target.join("keep.txt").exists());
+
+        // A path reached through a symlinked parent is outside the root
+        std::os::unix::fs::symlink(&target, root.join("escape")).unwrap();
+        assert!(remove_within(&root, &root.join("escape/keep.txt")).is_err());
+        assert!(target.join("keep.txt").exists());
+    }
+
+    #[test]
+    fn test_to_verbatim() {
+        for (path, expected) in [
+            (r"C:\proj\node_modules", r"\\?\C:\proj\node_modules"),
+            ("c:/proj/./a/../b/", r"\\?\c:\proj\b"),
+            (r"C:\", r"\\?\C:\"),
+            (r"C:\..\x", r"\\?\C:\x"),
+            (r"\\server\share\proj", r"\\?\UNC\server\share\proj"),
+            ("//server/share/a/../../..", r"\\?\UNC\server\share"),
+        ] {
+            assert_eq!(to_verbatim(path).as_deref(), Some(expected), "{path}");
+        }
+        for path in [
+            r"\\?\C:\proj",
+            r"\\.\pipe\howth",
+            r"\\server",
+            "C:proj",
+            r"proj\src",
+            "/usr/lib",
+        ] {
+            assert_eq!(to_verbatim(path), None, "{path}");
+        }
+    }
+
+    #[test]
+    fn test_strip_verbatim() {
+        for (path, expected) in [
+            (r"\\?\C:\proj\index.js", r"C:\proj\index.js"),
+            (r"\\?\C:\", r"C:\"),
+            (r"\\?\UNC\server\share\proj", r"\\server\share\proj"),
+        ] {
+            assert_eq!(strip_verbatim(path).as_deref(), Some(expected), "{path}");
+            assert_eq!(to_verbatim(expected).as_deref(), Some(path));
+        }
+        for path in [
+            r"C:\proj",
+            r"\\?\C:",
+            r"\\?\Volume{1234}\proj",
+            r"\\?\C:\proj\a/b",
+            r"\\?\C:\proj\trailing.",
+            r"\\?\C:\proj\..\x",
+        ] {
+            assert_eq!(strip_verbatim(path), None, "{path}");
+        }
+    }
+
+    #[cfg(windows)]
+    #[test]
+    fn test_long_paths_on_windows() {
+        let dir = tempdir().unwrap();
+        // Well past MAX_PATH
+        let deep = (0..30).fold(dir.path().to_path_buf(), |path, i| {
+            path.join(format!("node_modules_{i:02}"))
+        });
+        assert!(deep.as_os_str().len() > 260);
+
+        let long = long_path(&deep);
+        assert!(long.to_str().unwrap().starts_with(VERBATIM_PREFIX));
+        assert_eq!(simplified(&long), deep);
+        fs::create_dir_all(&long).unwrap();
+
+        let file = deep.join("index.js");
+        atomic_write(&file, b"x").unwrap();
+        copy_dir_all(
+            &dir.path().join("node_modules_00"),
+            &dir.path().join("copy"),
+        )
+        .unwrap();
+        assert_eq!(
+            canonicalize(&file).unwrap(),
+            simplified(&fs::canonicalize(&file).unwrap())
+        );
+        assert!(!canonicalize(&file)
+            .unwrap()
+            .to_str()
+            .unwrap()
+            .starts_with(VERBATIM_PREFIX));
+        remove_within(dir.path(), &dir.path().join("node_modules_00")).unwrap();
+        assert!(!dir.path().join("node_modules_00").exists());
+    }
+
+    #[cfg(windows)]
+    #[test]
+    fn test_unc_paths_on_windows() {
+        let share = Path::new(r"\\server\share\proj");
+        assert_eq!(long_path(share), Path::new(r"\\?\UNC\server\share\proj"));
+        assert_eq!(simplified(&long_path(share)), share);
+        // The administrative share of the local machine is a real UNC root
+        let dir = tempdir().unwrap();
+        let drive = dir.path().to_str().unwrap();
+        let unc = format!(r"\\localhost\{}${}", &drive[..1], &drive[2..]);
+        if let Ok(canonical) = canonicalize(Path::new(&unc)) {
+            assert!(canonical.to_str().unwrap().starts_with(r"\\"));
+            assert!(!canonical.to_str().unwrap().starts_with(VERBATIM_PREFIX));
+        }
+    }
+
+    #[test]
+    fn test_clone_or_copy() {
+        let dir = tempdir().unwrap();
+        let (src, dst) = (dir.path().join("a.js"), dir.path().join("b.js"));
+        fs::write(&src, "module.exports = 1;").unwrap();
+        fs::write(&dst, "stale").unwrap();
+
+        let kind = clone_or_copy(&src, &dst).unwrap();
+        assert_eq!(fs::read_to_string(&dst).unwrap(), "module.exports = 1;");
+        // Clones are independent copies
+        fs::write(&dst, "changed").unwrap();
+        assert_eq!(fs::read_to_string(&src).unwrap(), "module.exports = 1;");
+
+        #[cfg(unix)]
+        {
+            use std::os::unix::fs::MetadataExt;
+            let dev = fs::metadata(dir.path()).unwrap().dev();
+            let remembered = NO_REFLINK.lock().unwrap().contains(&(dev, dev));
+            // A filesystem without clones is remembered after the first try
+            assert_eq!(kind == CopyKind::Copy, remembered);
+            if remembered {
+                assert!(reflink(&src, &dir.path().join("c.js")).is_err());
+                assert!(!dir.path().join("c.js").exists());
+            }
+        }
+        #[cfg(not(unix))]
+        assert_eq!(kind, CopyKind::Copy);
+
+        assert!(clone_or_copy(&dir.path().join("missing.js"), &dst).is_err());
+    }
+
+    #[test]
+    fn test_copy_dir_all() {
+        let dir = tempdir().unwrap();
+        fs::create_dir_all(dir.path().join("src/a")).unwrap();
+        fs::write(dir.path().join("src/a/b.txt"), "b").unwrap();
+        copy_dir_all(&dir.path().join("src"), &dir.path().join("dst")).unwrap();
+        assert_eq!(
+            fs::read_to_string(dir.path().join("dst/a/b.txt")).unwrap(),
+            "b"
+        );
+    }
 }
@@ -0,0 +1,720 @@
+//! Glob matching and directory expansion.
+//!
+//! Patterns match `/`-separated paths relative to a root:
+//! - `*` matches any run of characters within a segment, `?` a single one
+//! - `**` as a whole segment matches zero or more segments
+//! - `[abc]`, `[a-z]` and `[!a-z]` (or `[^a-z]`) match one character
+//! - `{a,b}` expands to alternatives and may nest: `src/**/*.{ts,tsx}`
+//! - `?(a|b)`, `*(a|b)`, `+(a|b)`, `@(a|b)` and `!(a|b)` are extglobs
+//!   matching zero or one, zero or more, one or more, exactly one, or
+//!   anything but the alternatives, within a segment
+//! - `\` escapes the next character
+//!
+//! Wildcards match leading dots, so `*` includes dotfiles.
+//!
+//! A [`GlobSet`] combines include patterns with negative (`!pattern`) and
+//! ignore patterns. Excludes follow gitignore semantics: a path is excluded
+//! when it or any of its ancestor directories matches, and [`expand`] never
+//! descends into an excluded directory.
+
+use std::collections::BTreeMap;
+use std::fmt;
+use std::io;
+use std::path::{Component, Path, PathBuf};
+use walkdir::WalkDir;
+
+/// Upper bound on brace alternatives per pattern.
+const MAX_ALTERNATIVES: usize = 1024;
+
+/// Error for a malformed glob pattern.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct GlobError {
+    /// The pattern as written.
+    pub pattern: String,
+    /// What is wrong with it.
+    pub message: &'static str,
+}
+
+impl fmt::Display for GlobError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "invalid glob `{}`: {}", self.pattern, self.message)
+    }
+}
+
+impl std::error::Error for GlobError {}
+
+impl From<GlobError> for io::Error {
+    fn from(err: GlobError) -> Self {
+        Self::new(io::ErrorKind::InvalidInput, err)
+    }
+}
+
+/// Extglob operator.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum ExtKind {
+    /// `?(...)`
+    ZeroOrOne,
+    /// `*(...)`
+    ZeroOrMore,
+    /// `+(...)`
+    OneOrMore,
+    /// `@(...)`
+    One,
+    /// `!(...)`
+    Not,
+}
+
+/// One element of a segment pattern.
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum Token {
+    Char(char),
+    /// `?`
+    One,
+    /// `*`
+    Any,
+    /// `[...]`
+    Class {
+        negated: bool,
+        ranges: Vec<(char, char)>,
+    },
+    Ext(ExtKind, Vec<Vec<Token>>),
+}
+
+/// A path segment pattern.
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum Segment {
+    /// `**`
+    Globstar,
+    Tokens(Vec<Token>),
+}
+
+impl Segment {
+    /// The segment's text if it has no wildcards.
+    fn literal(&self) -> Option<String> {
+        match self {
+            Self::Globstar => None,
+            Self::Tokens(tokens) => tokens
+                .iter()
+                .map(|token| match token {
+                    Token::Char(c) => Some(*c),
+                    _ => None,
+                })
+                .collect(),
+        }
+    }
+}
+
+/// A compiled glob pattern.
+#[derive(Debug, Clone)]
+pub struct Glob {
+    pattern: String,
+    /// One segment list per brace alternative.
+    alternatives: Vec<Vec<Segment>>,
+}
+
+impl Glob {
+    /// Compile a pattern.
+    ///
+    /// # Errors
+    /// Returns an error for an unclosed `{`, `[` or extglob `(`, a trailing
+    /// `\`, or a brace expansion with too many alternatives.
+    pub fn new(pattern: &str) -> Result<Self, GlobError> {
+        let error = |message| GlobError {
+            pattern: pattern.to_string(),
+            message,
+        };
+        let alternatives = expand_braces(pattern)
+            .map_err(error)?
+            .iter()
+            .map(|alternative| parse_segments(alternative))
+            .collect::<Result<_, _>>()
+            .map_err(error)?;
+        Ok(Self {
+            pattern: pattern.to_string(),
+            alternatives,
+        })
+    }
+
+    /// The pattern as written.
+    #[must_use]
+    pub fn as_str(&self) -> &str {
+        &self.pattern
+    }
+
+    /// Whether a `/`-separated relative path matches.
+    #[must_use]
+    pub fn is_match(&self, path: &str) -> bool {
+        let segments: Vec<Vec<char>> = split_path(path)
+            .map(|segment| segment.chars().collect())
+            .collect();
+        self.alternatives
+            .iter()
+            .any(|alternative| match_segments(alternative, &segments))
+    }
+}
+
+/// Include patterns minus negated and ignored ones.
+#[derive(Debug, Clone, Default)]
+pub struct GlobSet {
+    include: Vec<Glob>,
+    exclude: Vec<Glob>,
+}
+
+impl GlobSet {
+    /// Compile include patterns; a leading `!` (other than an `!(...)`
+    /// extglob) makes a pattern exclude instead.
+    ///
+    /// # Errors
+    /// Returns the first invalid pattern.
+    pub fn new<I, S>(patterns: I) -> Result<Self, GlobError>
+    where
+        I: IntoIterator<Item = S>,
+        S: AsRef<str>,
+    {
+        let mut set = Self::default();
+        for pattern in patterns {
+            let pattern = pattern.as_ref();
+            match pattern
+                .strip_prefix('!')
+                .filter(|rest| !rest.starts_with('('))
+            {
+                Some(negated) => set.exclude.push(Glob::new(negated)?),
+                None => set.include.push(Glob::new(pattern)?),
+            }
+        }
+        Ok(set)
+    }
+
+    /// Add ignore patterns, which exclude like negated patterns.
+    ///
+    /// # Errors
+    /// Returns the first invalid pattern.
+    pub fn with_ignore<I, S>(mut self, patterns: I) -> Result<Self, GlobError>
+    where
+        I: IntoIterator<Item = S>,
+        S: AsRef<str>,
+    {
+        for pattern in patterns {
+            self.exclude.push(Glob::new(pattern.as_ref())?);
+        }
+        Ok(self)
+    }
+
+    /// Whether a relative path matches an include pattern and is not excluded.
+    #[must_use]
+    pub fn is_match(&self, path: &str) -> bool {
+        self.include.iter().any(|glob| glob.is_match(path)) && !self.is_excluded(path)
+    }
+
+    /// Whether a relative path or one of its ancestors matches an exclude.
+    #[must_use]
+    pub fn is_excluded(&self, path: &str) -> bool {
+        if self.exclude.is_empty() {
+            return false;
+        }
+        let segments: Vec<&str> = split_path(path).collect();
+        (1..=segments.len()).any(|len| {
+            let prefix = segments[..len].join("/");
+            self.exclude.iter().any(|glob| glob.is_match(&prefix))
+        })
+    }
+
+    /// Directory to start walking from and how deep to go below it.
+    fn walk_scope(&self) -> (Vec<String>, Option<usize>) {
+        let mut base: Option<Vec<String>> = None;
+        let mut unbounded = false;
+        let mut deepest = 0;
+        for segments in self.include.iter().flat_map(|glob| &glob.alternatives) {
+            unbounded |= segments.contains(&Segment::Globstar);
+            deepest = deepest.max(segments.len());
+            // The last segment names the file, so it never extends the base
+            let literal: Vec<String> = segments[..segments.len().saturating_sub(1)]
+                .iter()
+                .map_while(Segment::literal)
+                .collect();
+            base = Some(match base {
+                None => literal,
+                Some(base) => base
+                    .into_iter()
+                    .zip(literal)
+                    .take_while(|(a, b)| a == b)
+                    .map(|(a, _)| a)
+                    .collect(),
+            });
+        }
+        let base = base.unwrap_or_default();
+        let depth = (!unbounded).then(|| deepest.saturating_sub(base.len()));
+        (base, depth)
+    }
+}
+
+/// Expand a glob set under `root`, returning matching files sorted by their
+/// relative path.
+///
+/// Symlinks are followed; links that loop back to an ancestor directory and
+/// dangling links are skipped.
+///
+/// # Errors
+/// Returns an error if a directory can't be read.
+pub fn expand(root: &Path, set: &GlobSet) -> io::Result<Vec<PathBuf>> {
+    expand_filtered(root, set, |_, _| false)
+}
+
+/// Like [`expand`], also skipping paths for which `skip(path, is_dir)` is
+/// true. Skipped directories are not descended into.
+///
+/// # Errors
+/// Returns an error if a directory can't be read.
+pub fn expand_filtered(
+    root: &Path,
+    set: &GlobSet,
+    skip: impl Fn(&Path, bool) -> bool,
+) -> io::Result<Vec<PathBuf>> {
+    let (base, max_depth) = set.walk_scope();
+    let start = base.iter().fold(root.to_path_buf(), |dir, s| dir.join(s));
+    let mut walker = WalkDir::new(start).follow_links(true);
+    if let Some(depth) = max_depth {
+        walker = walker.max_depth(depth);
+    }
+
+    let mut files = BTreeMap::new();
+    let entries = walker.into_iter().filter_entry(|entry| {
+        let is_dir = entry.file_type().is_dir();
+        !(is_dir && set.is_excluded(&relative(root, entry.path())))
+            && (entry.depth() == 0 || !skip(entry.path(), is_dir))
+    });
+    for entry in entries {
+        let entry = match entry {
+            Ok(entry) => entry,
+            Err(e) if e.loop_ancestor().is_some() => continue,
+            Err(e) if e.io_error().map(io::Error::kind) == Some(io::ErrorKind::NotFound) => {
+                continue
+            }
+            Err(e) => return Err(e.into()),
+        };
+        if entry.file_type().is_file() {
+            let rel = relative(root, entry.path());
+            if set.is_match(&rel) {
+                files.insert(rel, entry.into_path());
+            }
+        }
+    }
+    Ok(files.into_values().collect())
+}
+
+/// `path` relative to `root` with `/` separators.
+fn relative(root: &Path, path: &Path) -> String {
+    let rel = path.strip_prefix(root).unwrap_or(path);
+    let parts: Vec<_> = rel
+        .components()
+        .filter_map(|c| match c {
+            Component::Normal(part) => Some(part.to_string_lossy()),
+            _ => None,
+        })
+        .collect();
+    parts.join("/")
+}
+
+/// Non-empty segments of a relative path, ignoring `.` segments.
+fn split_path(path: &str) -> impl Iterator<Item = &str> {
+    path.split('/').filter(|s| !s.is_empty() && *s != ".")
+}
+
+/// Expand `{a,b}` alternatives, innermost choices included.
+fn expand_braces(pattern: &str) -> Result<Vec<String>, &'static str> {
+    let chars: Vec<char> = pattern.chars().collect();
+    let mut i = 0;
+    while i < chars.len() {
+        match chars[i] {
+            '\\' => i += 1,
+            '{' => {
+                let mut depth = 0;
+                let mut commas = Vec::new();
+                let mut close = None;
+                let mut j = i;
+                while j < chars.len() {
+                    match chars[j] {
+                        '\\' => j += 1,
+                        '{' => depth += 1,
+                        '}' => {
+                            depth -= 1;
+                            if depth == 0 {
+                                close = Some(j);
+                                break;
+                            }
+                        }
+                        ',' if depth == 1 => commas.push(j),
+                        _ => {}
+                    }
+                    j += 1;
+                }
+                let close = close.ok_or("unclosed `{`")?;
+                if commas.is_empty() {
+                    // `{a}` is literal, but may contain expandable braces
+                    i += 1;
+                    continue;
+                }
+
+                let prefix: String = chars[..i].iter().collect();
+                let suffix: String = chars[close + 1..].iter().collect();
+                let bounds = std::iter::once(i)
+                    .chain(commas.iter().copied())
+                    .zip(commas.iter().copied().chain(std::iter::once(close)));
+                let mut out = Vec::new();
+                for (start, end) in bounds {
+                    let choice: String = chars[start + 1..end].iter().collect();
+                    out.extend(expand_braces(&format!("{prefix}{choice}{suffix}"))?);
+                    if out.len() > MAX_ALTERNATIVES {
+                        return Err("too many brace alternatives");
+                    }
+                }
+                return Ok(out);
+            }
+            _ => {}
+        }
+        i += 1;
+    }
+    Ok(vec![pattern.to_string()])
+}
+
+/// Split a brace-free pattern into segments and parse each one.
+fn parse_segments(pattern: &str) -> Result<Vec<Segment>, &'static str> {
+    let pattern = pattern.strip_prefix("./").unwrap_or(pattern);
+    let mut raw = Vec::new();
+    let mut current = String::new();
+    let mut depth = 0usize;
+    let mut chars = pattern.chars();
+    while let Some(c) = chars.next() {
+        match c {
+            '\\' => {
+                current.push(c);
+                current.push(chars.next().ok_or("trailing `\\`")?);
+            }
+            '(' => {
+                depth += 1;
+                current.push(c);
+            }
+            ')' => {
+                depth = depth.saturating_sub(1);
+                current.push(c);
+            }
+            '/' if depth == 0 => raw.push(std::mem::take(&mut current)),
+            _ => current.push(c),
+        }
+    }
+    raw.push(current);
+
+    raw.iter()
+        .filter(|s| !s.is_empty() && s.as_str() != ".")
+        .map(|s| {
+            if s == "**" {
+                return Ok(Segment::Globstar);
+            }
+            let chars: Vec<char> = s.chars().collect();
+            let mut pos = 0;
+            parse_tokens(&chars, &mut pos, false).map(Segment::Tokens)
+        })
+        .collect()
+}
+
+/// Parse tokens up to the end, or up to `|`/`)` inside an extglob.
+fn parse_tokens(chars: &[char], pos: &mut usize, nested: bool) -> Result<Vec<Token>, &'static str> {
+    let mut tokens = Vec::new();
+    while let Some(&c) = chars.get(*pos) {
+        if nested && (c == '|' || c == ')') {
+            return Ok(tokens);
+        }
+        *pos += 1;
+        let ext = match c {
+            '?' => Some(ExtKind::ZeroOrOne),
+            '*' => Some(ExtKind::ZeroOrMore),
+            '+' => Some(ExtKind::OneOrMore),
+            '@' => Some(ExtKind::One),
+            '!' => Some(ExtKind::Not),
+            _ => None,
+        }
+        .filter(|_| chars.get(*pos) == Some(&'('));
+
+        if let Some(kind) = ext {
+            *pos += 1;
+            let mut alternatives = Vec::new();
+            loop {
+                alternatives.push(parse_tokens(chars, pos, true)?);
+                match chars.get(*pos) {
+                    Some('|') => *pos += 1,
+                    Some(')') => {
+                        *pos += 1;
+                        break;
+                    }
+                    _ => return Err("unclosed `(`"),
+                }
+            }
+            tokens.push(Token::Ext(kind, alternatives));
+            continue;
+        }
+
+        match c {
+            '\\' => {
+                let escaped = *chars.get(*pos).ok_or("trailing `\\`")?;
+                *pos += 1;
+                tokens.push(Token::Char(escaped));
+            }
+            '*' => {
+                if tokens.last() != Some(&Token::Any) {
+                    tokens.push(Token::Any);
+                }
+            }
+            '?' => tokens.push(Token::One),
+            '[' => tokens.push(parse_class(chars, pos)?),
+            c => tokens.push(Token::Char(c)),
+        }
+    }
+    if nested {
+        Err("unclosed `(`")
+    } else {
+        Ok(tokens)
+    }
+}
+
+/// Parse a character class; `pos` is just past the `[`.
+fn parse_class(chars: &[char], pos: &mut usize) -> Result<Token, &'static str> {
+    let negated = matches!(chars.get(*pos), Some('!' | '^'));
+    if negated {
+        *pos += 1;
+    }
+    let mut ranges = Vec::new();
+    let mut first = true;
+    loop {
+        let mut c = *chars.get(*pos).ok_or("unclosed `[`")?;
+        *pos += 1;
+        if c == ']' && !first {
+            return Ok(Token::Class { negated, ranges });
+        }
+        first = false;
+        if c == '\\' {
+            c = *chars.get(*pos).ok_or("unclosed `[`")?;
+            *pos += 1;
+        }
+        let is_range =
+            chars.get(*pos) == Some(&'-') && chars.get(*pos + 1).is_some_and(|&e| e != ']');
+        if is_range {
+            let end = chars[*pos + 1];
+            *pos += 2;
+            ranges.push((c, end));
+        } else {
+            ranges.push((c, c));
+        }
+    }
+}
+
+fn match_segments(pattern: &[Segment], path: &[Vec<char>]) -> bool {
+    match pattern.split_first() {
+        None => path.is_empty(),
+        Some((Segment::Globstar, rest)) => {
+            (0..=path.len()).any(|skip| match_segments(rest, &path[skip..]))
+        }
+        Some((Segment::Tokens(tokens), rest)) => path
+            .split_first()
+            .is_some_and(|(first, tail)| match_tokens(tokens, first) && match_segments(rest, tail)),
+    }
+}
+
+fn match_tokens(tokens: &[Token], s: &[char]) -> bool {
+    let Some((token, rest)) = tokens.split_first() else {
+        return s.is_empty();
+    };
+    match token {
+        Token::Char(c) => s.first() == Some(c) && match_tokens(rest, &s[1..]),
+        Token::One => !s.is_empty() && match_tokens(rest, &s[1..]),
+        Token::Any => (0..=s.len()).any(|i| match_tokens(rest, &s[i..])),
+        Token::Class { negated, ranges } => {
+            s.first()
+                .is_some_and(|c| ranges.iter().any(|&(lo, hi)| (lo..=hi).contains(c)) != *negated)
+                && match_tokens(rest, &s[1..])
+        }
+        Token::Ext(kind, alternatives) => (0..=s.len()).any(|i| {
+            let (head, tail) = s.split_at(i);
+            let matched = match kind {
+                ExtKind::ZeroOrOne => head.is_empty() || match_any(alternatives, head),
+                ExtKind::ZeroOrMore => match_repeated(alternatives, head),
+                ExtKind::OneOrMore => {
+                    match_any(alternatives, head)
+                        || (!head.is_empty() && match_repeated(alternatives, head))
+                }
+                ExtKind::One => match_any(alternatives, head),
+                ExtKind::Not => !match_any(alternatives, head),
+            };
+            matched && match_tokens(rest, tail)
+        }),
+    }
+}
+
+fn match_any(alternatives: &[Vec<Token>], s: &[char]) -> bool {
+    alternatives.iter().any(|tokens| match_tokens(tokens, s))
+}
+
+/// Whether `s` is a concatenation of zero or more alternatives.
+fn match_repeated(alternatives: &[Vec<Token>], s: &[char]) -> bool {
+    s.is_empty()
+        || (1..=s.len())
+            .any(|i| match_any(alternatives, &s[..i]) && match_repeated(alternatives, &s[i..]))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::fs;
+
+    fn matches(pattern: &str, path: &str) -> bool {
+        Glob::new(pattern).unwrap().is_match(path)
+    }
+
+    #[test]
+    fn test_wildcards_and_globstar() {
+        assert!(matches("*.ts", "index.ts"));
+        assert!(!matches("*.ts", "src/index.ts"));
+        assert!(matches("src/**/*.ts", "src/index.ts"));
+        assert!(matches("src/**/*.ts", "src/a/b/c.ts"));
+        assert!(!matches("src/**/*.ts", "lib/a.ts"));
+        assert!(matches("**", "a/b/c"));
+        assert!(matches("node_modules/**", "node_modules"));
+        assert!(matches("file?.[jt]s", "file1.ts"));
+        assert!(!matches("file?.[!jt]s", "file1.ts"));
+        assert!(matches("[a-c]*", "beta"));
+        assert!(matches("*", ".env"));
+        assert!(matches("\\*.md", "*.md"));
+        assert!(!matches("\\*.md", "a.md"));
+    }
+
+    #[test]
+    fn test_braces() {
+        assert!(matches("src/**/*.{ts,tsx}", "src/a/App.tsx"));
+        assert!(matches("src/**/*.{ts,tsx}", "src/a.ts"));
+        assert!(!matches("src/**/*.{ts,tsx}", "src/a.js"));
+        assert!(matches("{src,test}/**/*.{m,c,}js", "test/x.cjs"));
+        assert!(matches("{src,test}/**/*.{m,c,}js", "src/x.js"));
+        assert!(matches("a{b,c{d,e}}", "ace"));
+        assert!(matches("{a}", "{a}"));
+    }
+
+    #[test]
+    fn test_extglob() {
+        assert!(matches("!(*.d).ts", "index.ts"));
+        assert!(!matches("!(*.d).ts", "index.d.ts"));
+        assert!(matches("@(foo|bar).js", "bar.js"));
+        assert!(!matches("@(foo|bar).js", "foobar.js"));
+        assert!(matches("+(ab).js", "ababab.js"));
+        assert!(!matches("+(ab).js", ".js"));
+        assert!(matches("*(ab).js", ".js"));
+        assert!(matches("?(x)y", "y"));
+        assert!(matches("?(x)y", "xy"));
+        assert!(!matches("?(x)y", "xxy"));
+        assert!(matches("src/!(vendor)/*.ts", "src/app/main.ts"));
+        assert!(!matches("src/!(vendor)/*.ts", "src/vendor/main.ts"));
+    }
+
+    #[test]
+    fn test_invalid_patterns() {
+        for pattern in ["src/{a,b", "[abc", "@(a|b", "a\\"] {
+            let err = Glob::new(pattern).unwrap_err();
+            assert_eq!(err.pattern, pattern);
+        }
+    }
+
+    #[test]
+    fn test_glob_set_negation_and_ignore() {
+        let set = GlobSet::new(["src/**/*.ts", "!**/*.test.ts", "!(x).md"])
+            .unwrap()
+            .with_ignore(["src/generated"])
+            .unwrap();
+        assert!(set.is_match("src/app.ts"));
+        assert!(!set.is_match("src/app.test.ts"));
+        assert!(!set.is_match("src/generated/api.ts"));
+        assert!(set.is_match("README.md"));
+        assert!(!set.is_match("x.md"));
+        assert!(set.is_excluded("src/generated"));
+        assert!(!GlobSet::default().is_match("a"));
+    }
+
+    #[test]
+    fn test_walk_scope() {
+        let scope = |patterns: &[&str]| GlobSet::new(patterns).unwrap().walk_scope();
+        assert_eq!(scope(&["src/**/*.ts"]), (vec!["src".to_string()], None));
+        assert_eq!(
+            scope(&["src/*.ts", "src/lib/*.ts"]),
+            (vec!["src".to_string()], Some(2))
+        );
+        assert_eq!(scope(&["{a,b}/x.ts"]), (vec![], Some(2)));
+        assert_eq!(scope(&["package.json"]), (vec![], Some(1)));
+    }
+
+    #[test]
+    fn test_expand_sorted_with_ignores() {
+        let dir = tempfile::tempdir().unwrap();
+        let root = dir.path();
+        for file in [
+            "src/b.ts",
+            "src/a.tsx",
+            "src/a/c.ts",
+            "src/a/c.test.ts",
+            "src/d.js",
+        ] {
+            let path = root.join(file);
+            fs::create_dir_all(path.parent().unwrap()).unwrap();
+            fs::write(path, "").unwrap();
+        }
+        fs::create_dir_all(root.join("node_modules/x")).unwrap();
+        fs::write(root.join("node_modules/x/i.ts"), "").unwrap();
+
+        let set = GlobSet::new(["**/*.{ts,tsx}", "!**/*.test.ts"])
+            .unwrap()
+            .with_ignore(["node_modules/**"])
+            .unwrap();
+        let files = expand(root, &set).unwrap();
+        assert_eq!(
+            files,
+            ["src/a.tsx", "src/a/c.ts", "src/b.ts"].map(|f| root.join(f))
+        );
+    }
+
+    #[test]
+    fn test_expand_filtered_prunes_skipped_dirs() {
+        let dir = tempfile::tempdir().unwrap();
+        let root = dir.path();
+        for file in ["src/a.ts", "src/vendor/b.ts", "src/c.gen.ts"] {
+            let path = root.join(file);
+            fs::create_dir_all(path.parent().unwrap()).unwrap();
+            fs::write(path, "").unwrap();
+        }
+
+        let set = GlobSet::new(["**/*.ts"]).unwrap();
+        let files = expand_filtered(root, &set, |path, is_dir| {
+            let name = path.file_name().unwrap().to_string_lossy();
+            (is_dir && name == "vendor") || name.ends_with(".gen.ts")
+        })
+        .unwrap();
+        assert_eq!(files, [root.join("src/a.ts")]);
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_expand_follows_symlinks_without_looping() {
+        let dir = tempfile::tempdir().unwrap();
+        let root = dir.path();
+        fs::create_dir_all(root.join("src")).unwrap();
+        fs::write(root.join("src/a.ts"), "").unwrap();
+        std::os::unix::fs::symlink(root.join("src"), root.join("src/self")).unwrap();
+        std::os::unix::fs::symlink(root.join("src/a.ts"), root.join("linked.ts")).unwrap();
+        std::os::unix::fs::symlink(root.join("missing.ts"), root.join("dangling.ts")).unwrap();
+
+        let files = expand(root, &GlobSet::new(["**/*.ts"]).unwrap()).unwrap();
+        assert_eq!(files, [root.join("linked.ts"), root.join("src/a.ts")]);
+    }
+
+    #[test]
+    fn test_expand_missing_base() {
+        let dir = tempfile::tempdir().unwrap();
+        let set = GlobSet::new(["src/**/*.ts"]).unwrap();
+        assert_eq!(expand(dir.path(), &set).unwrap(), Vec::<PathBuf>::new());
+    }
+}
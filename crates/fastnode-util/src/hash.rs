@@ -2,29 +2,76 @@ use std::fs::File;
 use std::io::{self, BufReader, Read};
 use std::path::Path;
 
+/// Identifier of the content hash algorithm.
+///
+/// Persisted caches and fingerprints record it so that a future change of
+/// algorithm invalidates them instead of comparing digests across algorithms.
+pub const HASH_ALGORITHM: &str = "blake3";
+
+/// Files at least this large are hashed in parallel chunks.
+pub const LARGE_FILE_THRESHOLD: u64 = 1024 * 1024;
+
+/// Read size for large files; each chunk is hashed across threads.
+const CHUNK_SIZE: usize = 4 * 1024 * 1024;
+
 /// Compute the BLAKE3 hash of a file, returning the hex-encoded digest.
 ///
-/// Streams the file content to minimize memory usage.
+/// Streams the file content to minimize memory usage. Files of at least
+/// [`LARGE_FILE_THRESHOLD`] bytes are read in large chunks that are hashed
+/// on multiple threads; the digest is the same either way.
 ///
 /// # Errors
 /// Returns an error if the file cannot be opened or read.
 pub fn blake3_file(path: &Path) -> io::Result<String> {
     let file = File::open(path)?;
-    let mut reader = BufReader::new(file);
+    let len = file.metadata()?.len();
     let mut hasher = blake3::Hasher::new();
-    let mut buffer = [0u8; 8192];
 
-    loop {
-        let bytes_read = reader.read(&mut buffer)?;
-        if bytes_read == 0 {
-            break;
+    if len >= LARGE_FILE_THRESHOLD {
+        let mut reader = file;
+        let mut buffer = vec![0u8; CHUNK_SIZE];
+        loop {
+            let filled = read_full(&mut reader, &mut buffer)?;
+            if filled == 0 {
+                break;
+            }
+            hasher.update_rayon(&buffer[..filled]);
+        }
+    } else {
+        let mut reader = BufReader::new(file);
+        let mut buffer = [0u8; 8192];
+        loop {
+            let bytes_read = reader.read(&mut buffer)?;
+            if bytes_read == 0 {
+                break;
+            }
+            hasher.update(&buffer[..bytes_read]);
         }
-        hasher.update(&buffer[..bytes_read]);
     }
 
     Ok(hasher.finalize().to_hex().to_string())
 }
 
+/// Fill `buffer` as far as possible, returning the number of bytes read.
+fn read_full(reader: &mut impl Read, buffer: &mut [u8]) -> io::Result<usize> {
+    let mut filled = 0;
+    while filled < buffer.len() {
+        match reader.read(&mut buffer[filled..]) {
+            Ok(0) => break,
+            Ok(n) => filled += n,
+            Err(e) if e.kind() == io::ErrorKind::Interrupted => {}
+            Err(e) => return Err(e),
+        }
+    }
+    Ok(filled)
+}
+
+/// Prefix a hex digest with [`HASH_ALGORITHM`], e.g. `blake3:d749...`.
+#[must_use]
+pub fn tagged(hex: &str) -> String {
+    format!("{HASH_ALGORITHM}:{hex}")
+}
+
 /// Compute the BLAKE3 hash of a byte slice, returning the hex-encoded digest.
 #[must_use]
 pub fn blake3_bytes(data: &[u8]) -> String {
@@ -61,6 +108,25 @@ mod tests {
         );
     }
 
+    #[test]
+    fn test_blake3_file_large_matches_bytes() {
+        // Spans several chunks plus a partial one
+        #[allow(clippy::cast_possible_truncation)]
+        let data: Vec<u8> = (0..CHUNK_SIZE * 2 + 12345)
+            .map(|i| (i % 251) as u8)
+            .collect();
+        let mut file = NamedTempFile::new().unwrap();
+        file.write_all(&data).unwrap();
+        file.flush().unwrap();
+
+        assert_eq!(blake3_file(file.path()).unwrap(), blake3_bytes(&data));
+    }
+
+    #[test]
+    fn test_tagged() {
+        assert_eq!(tagged("abc"), "blake3:abc");
+    }
+
     #[test]
     fn test_blake3_file_not_found() {
         let result = blake3_file(Path::new("/nonexistent/file"));
@@ -8,4 +8,8 @@
 //! Logging is handled by the CLI crate to keep this library lightweight.
 
 pub mod fs;
+pub mod glob;
 pub mod hash;
+pub mod process;
+pub mod sign;
+pub mod tty;
@@ -0,0 +1,406 @@
+//! Supervision for child processes run on the user's behalf.
+//!
+//! On Unix the child leads its own process group so everything it starts
+//! can be signalled together:
+//! - SIGINT, SIGTERM, SIGHUP and SIGQUIT received while it runs are
+//!   forwarded to the whole group.
+//! - When stdin is the controlling terminal and we are in the foreground,
+//!   the group becomes the terminal's foreground group, so Ctrl+C and job
+//!   control reach it directly. The terminal is handed back afterwards,
+//!   with the attributes it had before the child started (so a child that
+//!   dies in raw mode doesn't leave the shell without echo).
+//! - Once the child exits, processes it left running in its group get
+//!   SIGTERM (SIGKILL after [`ORPHAN_GRACE`]) and are reaped.
+//!
+//! On Windows, console Ctrl+C already reaches every attached process, so
+//! children are spawned as-is.
+
+use std::io;
+use std::process::{Child, Command, ExitStatus, Output, Stdio};
+use std::time::Duration;
+
+/// How long orphaned group members get to exit after SIGTERM.
+pub const ORPHAN_GRACE: Duration = Duration::from_secs(2);
+
+/// A spawned child whose signals and leftovers are being managed.
+///
+/// Signal forwarding stays active until the child is waited on (or the
+/// value is dropped), at which point leftovers are cleaned up.
+#[derive(Debug)]
+pub struct Supervised {
+    child: Child,
+    #[cfg(unix)]
+    guard: unix::Guard,
+}
+
+impl Supervised {
+    /// The child's process id (also its process group id on Unix).
+    #[must_use]
+    pub fn id(&self) -> u32 {
+        self.child.id()
+    }
+
+    /// Wait for the child to exit, then clean up its process group.
+    ///
+    /// # Errors
+    /// Returns an error if waiting on the child fails.
+    pub fn wait(mut self) -> io::Result<ExitStatus> {
+        self.child.wait()
+    }
+
+    /// Collect the child's piped output and wait for it to exit, then clean
+    /// up its process group.
+    ///
+    /// # Errors
+    /// Returns an error if reading output or waiting on the child fails.
+    pub fn wait_with_output(self) -> io::Result<Output> {
+        #[cfg(unix)]
+        let _guard = self.guard;
+        self.child.wait_with_output()
+    }
+}
+
+/// Spawn `cmd` under supervision.
+///
+/// # Errors
+/// Returns an error if the process can't be spawned.
+pub fn spawn(cmd: &mut Command) -> io::Result<Supervised> {
+    #[cfg(unix)]
+    {
+        let foreground = unix::prepare(cmd);
+        // Taken before the child can change the terminal's mode
+        let terminal = if foreground {
+            crate::tty::TerminalState::save(0).ok()
+        } else {
+            None
+        };
+        let child = cmd.spawn()?;
+        let guard = unix::Guard::install(child.id(), foreground, terminal);
+        Ok(Supervised { child, guard })
+    }
+    #[cfg(not(unix))]
+    {
+        Ok(Supervised {
+            child: cmd.spawn()?,
+        })
+    }
+}
+
+/// Run `cmd` under supervision and wait for it, like [`Command::status`].
+///
+/// # Errors
+/// Returns an error if the process can't be spawned or waited on.
+pub fn status(cmd: &mut Command) -> io::Result<ExitStatus> {
+    spawn(cmd)?.wait()
+}
+
+/// Run `cmd` under supervision and capture stdout/stderr, like
+/// [`Command::output`].
+///
+/// # Errors
+/// Returns an error if the process can't be spawned or waited on.
+pub fn output(cmd: &mut Command) -> io::Result<Output> {
+    cmd.stdout(Stdio::piped()).stderr(Stdio::piped());
+    spawn(cmd)?.wait_with_output()
+}
+
+/// Shell-style exit code for `status`: the child's code, or `128 + signal`
+/// if it was killed by a signal.
+#[must_use]
+pub fn exit_code(status: ExitStatus) -> i32 {
+    #[cfg(unix)]
+    {
+        use std::os::unix::process::ExitStatusExt;
+        if let Some(signal) = status.signal() {
+            return 128 + signal;
+        }
+    }
+    status.code().unwrap_or(1)
+}
+
+/// Exit the current process mirroring `status`.
+///
+/// A child killed by a signal is mirrored by re-raising that signal with its
+/// default action, so a parent shell sees the same termination (e.g. stops a
+/// loop on Ctrl+C). Falls back to [`exit_code`].
+pub fn exit_with(status: ExitStatus) -> ! {
+    #[cfg(unix)]
+    {
+        use std::os::unix::process::ExitStatusExt;
+        if let Some(signal) = status.signal() {
+            unix::raise_default(signal);
+        }
+    }
+    std::process::exit(exit_code(status))
+}
+
+#[cfg(unix)]
+mod unix {
+    use super::ORPHAN_GRACE;
+    use crate::tty::TerminalState;
+    use libc::c_int;
+    use std::os::unix::process::CommandExt;
+    use std::process::Command;
+    use std::sync::atomic::{AtomicI32, Ordering};
+    use std::time::{Duration, Instant};
+
+    /// Signals forwarded to the supervised process group.
+    const FORWARDED: [c_int; 4] = [libc::SIGINT, libc::SIGTERM, libc::SIGHUP, libc::SIGQUIT];
+
+    /// Process group that [`forward`] delivers to, or 0 when none is running.
+    static CHILD_PGID: AtomicI32 = AtomicI32::new(0);
+
+    extern "C" fn forward(signal: c_int) {
+        let pgid = CHILD_PGID.load(Ordering::SeqCst);
+        if pgid > 0 {
+            // SAFETY: kill is async-signal-safe; a negative pid targets the group
+            unsafe {
+                libc::kill(-pgid, signal);
+            }
+        }
+    }
+
+    /// Whether stdin is a terminal we currently own as its foreground group.
+    fn owns_terminal() -> bool {
+        // SAFETY: plain queries on fd 0 with no memory arguments
+        unsafe { libc::isatty(0) == 1 && libc::tcgetpgrp(0) == libc::getpgrp() }
+    }
+
+    /// Make `pgid` the terminal's foreground group. SIGTTOU is ignored for
+    /// the call since a background group may not otherwise change it.
+    ///
+    /// # Safety
+    /// Only async-signal-safe calls, so this may run between fork and exec.
+    unsafe fn set_foreground(pgid: libc::pid_t) {
+        let previous = libc::signal(libc::SIGTTOU, libc::SIG_IGN);
+        libc::tcsetpgrp(0, pgid);
+        libc::signal(libc::SIGTTOU, previous);
+    }
+
+    /// Put the child in its own process group, handing it the terminal if
+    /// we own it. Returns whether the terminal is being handed over.
+    pub(super) fn prepare(cmd: &mut Command) -> bool {
+        let foreground = owns_terminal();
+        // SAFETY: the hook only makes async-signal-safe calls
+        unsafe {
+            cmd.pre_exec(move || {
+                if libc::setpgid(0, 0) != 0 {
+                    return Err(std::io::Error::last_os_error());
+                }
+                if foreground {
+                    set_foreground(libc::getpid());
+                }
+                Ok(())
+            });
+        }
+        foreground
+    }
+
+    /// Forwarding handlers and terminal ownership for a running child.
+    /// Dropping it restores both and cleans up the child's group.
+    #[derive(Debug)]
+    pub(super) struct Guard {
+        pgid: libc::pid_t,
+        foreground: bool,
+        terminal: Option<TerminalState>,
+        previous: Vec<(c_int, libc::sigaction)>,
+    }
+
+    impl Guard {
+        pub(super) fn install(
+            child_pid: u32,
+            foreground: bool,
+            terminal: Option<TerminalState>,
+        ) -> Self {
+            let pgid = libc::pid_t::try_from(child_pid).unwrap_or(0);
+            become_subreaper();
+            CHILD_PGID.store(pgid, Ordering::SeqCst);
+
+            let mut previous = Vec::with_capacity(FORWARDED.len());
+            for signal in FORWARDED {
+                // SAFETY: zeroed sigaction is a valid empty mask/flags value,
+                // and `forward` is an extern "C" handler that is async-signal-safe
+                unsafe {
+                    let mut action: libc::sigaction = std::mem::zeroed();
+                    action.sa_sigaction = forward as extern "C" fn(c_int) as usize;
+                    action.sa_flags = libc::SA_RESTART;
+                    libc::sigemptyset(&raw mut action.sa_mask);
+                    let mut old: libc::sigaction = std::mem::zeroed();
+                    if libc::sigaction(signal, &raw const action, &raw mut old) == 0 {
+                        previous.push((signal, old));
+                    }
+                }
+            }
+
+            if foreground {
+                // Also set from the parent: whichever runs first wins the race
+                // against the child reading the terminal
+                // SAFETY: pgid is our child's group
+                unsafe {
+                    libc::setpgid(pgid, pgid);
+                    set_foreground(pgid);
+                }
+            }
+
+            Self {
+                pgid,
+                foreground,
+                terminal,
+                previous,
+            }
+        }
+    }
+
+    impl Drop for Guard {
+        fn drop(&mut self) {
+            if self.foreground {
+                // SAFETY: hands the terminal back to our own group
+                unsafe { set_foreground(libc::getpgrp()) };
+                // Only possible once we're the foreground group again
+                if let Some(terminal) = &self.terminal {
+                    let _ = terminal.restore();
+                }
+            }
+            // Keep forwarding while leftovers are being shut down
+            reap_group(self.pgid, ORPHAN_GRACE);
+            CHILD_PGID.store(0, Ordering::SeqCst);
+            for (signal, old) in &self.previous {
+                // SAFETY: restores the action saved in `install`
+                unsafe {
+                    libc::sigaction(*signal, old, std::ptr::null_mut());
+                }
+            }
+        }
+    }
+
+    /// Have orphaned descendants reparented to us rather than init, so they
+    /// can be reaped. Linux only; elsewhere init reaps them.
+    fn become_subreaper() {
+        #[cfg(target_os = "linux")]
+        // SAFETY: prctl with PR_SET_CHILD_SUBREAPER takes a plain integer
+        unsafe {
+            libc::prctl(libc::PR_SET_CHILD_SUBREAPER, 1, 0, 0, 0);
+        }
+    }
+
+    /// Whether any process (including zombies) remains in group `pgid`.
+    fn group_alive(pgid: libc::pid_t) -> bool {
+        // SAFETY: signal 0 only checks for existence
+        unsafe { libc::kill(-pgid, 0) == 0 }
+    }
+
+    /// Collect any exited members of group `pgid` that were reparented to us.
+    fn collect(pgid: libc::pid_t) {
+        let mut status = 0;
+        // SAFETY: waitpid writes only to `status`
+        while unsafe { libc::waitpid(-pgid, &raw mut status, libc::WNOHANG) } > 0 {}
+    }
+
+    /// Terminate and reap whatever is left of group `pgid`: SIGTERM first,
+    /// SIGKILL if members are still alive after `grace`.
+    pub(super) fn reap_group(pgid: libc::pid_t, grace: Duration) {
+        if pgid <= 0 {
+            return;
+        }
+        collect(pgid);
+        if !group_alive(pgid) {
+            return;
+        }
+
+        // SAFETY: a negative pid targets only the child's group
+        unsafe {
+            libc::kill(-pgid, libc::SIGTERM);
+        }
+        let deadline = Instant::now() + grace;
+        while Instant::now() < deadline {
+            collect(pgid);
+            if !group_alive(pgid) {
+                return;
+            }
+            std::thread::sleep(Duration::from_millis(20));
+        }
+
+        // SAFETY: as above
+        unsafe {
+            libc::kill(-pgid, libc::SIGKILL);
+        }
+        let deadline = Instant::now() + grace;
+        while Instant::now() < deadline {
+            collect(pgid);
+            if !group_alive(pgid) {
+                return;
+            }
+            std::thread::sleep(Duration::from_millis(20));
+        }
+    }
+
+    /// Terminate ourselves with `signal` under its default action.
+    pub(super) fn raise_default(signal: c_int) {
+        // SAFETY: resetting a disposition and signalling ourselves
+        unsafe {
+            libc::signal(signal, libc::SIG_DFL);
+            libc::kill(libc::getpid(), signal);
+        }
+    }
+
+    #[cfg(test)]
+    pub(super) fn forward_to(pgid: libc::pid_t, signal: c_int) {
+        CHILD_PGID.store(pgid, Ordering::SeqCst);
+        forward(signal);
+    }
+}
+
+#[cfg(all(test, unix))]
+mod tests {
+    use super::*;
+    use std::time::Instant;
+
+    fn sh(script: &str) -> Command {
+        let mut cmd = Command::new("sh");
+        cmd.arg("-c").arg(script).stdin(Stdio::null());
+        cmd
+    }
+
+    #[test]
+    fn test_exit_code_mapping() {
+        assert_eq!(exit_code(status(&mut sh("exit 3")).unwrap()), 3);
+        assert_eq!(exit_code(status(&mut sh("kill -TERM $$")).unwrap()), 143);
+        assert_eq!(exit_code(status(&mut sh("kill -INT $$")).unwrap()), 130);
+    }
+
+    #[test]
+    fn test_output_is_captured() {
+        let out = output(&mut sh("echo out; echo err >&2; exit 4")).unwrap();
+        assert_eq!(out.stdout, b"out\n");
+        assert_eq!(out.stderr, b"err\n");
+        assert_eq!(exit_code(out.status), 4);
+    }
+
+    #[test]
+    fn test_signal_forwarded_to_group() {
+        let child = spawn(&mut sh("trap 'exit 7' TERM; while :; do sleep 0.05; done")).unwrap();
+        std::thread::sleep(Duration::from_millis(200));
+        unix::forward_to(libc::pid_t::try_from(child.id()).unwrap(), libc::SIGTERM);
+        assert_eq!(exit_code(child.wait().unwrap()), 7);
+    }
+
+    #[test]
+    fn test_orphans_are_reaped() {
+        let dir = tempfile::tempdir().unwrap();
+        let pid_file = dir.path().join("pid");
+        let script = format!("sleep 30 & echo $! > '{}'; exit 0", pid_file.display());
+
+        let started = Instant::now();
+        let status = status(&mut sh(&script)).unwrap();
+        assert!(status.success());
+        assert!(started.elapsed() < Duration::from_secs(10));
+
+        let orphan: libc::pid_t = std::fs::read_to_string(&pid_file)
+            .unwrap()
+            .trim()
+            .parse()
+            .unwrap();
+        // SAFETY: signal 0 only checks for existence
+        assert_ne!(unsafe { libc::kill(orphan, 0) }, 0, "orphan still running");
+    }
+}
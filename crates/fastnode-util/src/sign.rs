@@ -0,0 +1,214 @@
+//! Ed25519 keys for signing shared build cache entries.
+//!
+//! Keys and signatures are stored as lowercase hex: a secret key file holds
+//! the 32-byte seed, a public key file the 32-byte verifying key, each on a
+//! single line.
+
+use ed25519_dalek::{Signature, Signer, Verifier};
+use std::fs;
+use std::io::{self, Write};
+use std::path::Path;
+
+/// A secret key that signs messages.
+#[derive(Clone)]
+pub struct SigningKey(ed25519_dalek::SigningKey);
+
+/// A public key that verifies signatures.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct PublicKey(ed25519_dalek::VerifyingKey);
+
+impl std::fmt::Debug for SigningKey {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        // Never print the secret
+        f.debug_tuple("SigningKey")
+            .field(&self.public_key().key_id())
+            .finish()
+    }
+}
+
+impl SigningKey {
+    /// Generate a new random key.
+    ///
+    /// # Errors
+    /// Returns an error if the OS random number generator fails.
+    pub fn generate() -> io::Result<Self> {
+        let mut seed = [0u8; 32];
+        getrandom::getrandom(&mut seed).map_err(io::Error::other)?;
+        Ok(Self(ed25519_dalek::SigningKey::from_bytes(&seed)))
+    }
+
+    /// Parse a hex-encoded 32-byte seed.
+    ///
+    /// # Errors
+    /// Returns an error if `hex` is not 64 hex digits.
+    pub fn from_hex(hex: &str) -> io::Result<Self> {
+        Ok(Self(ed25519_dalek::SigningKey::from_bytes(&decode_key(
+            hex,
+        )?)))
+    }
+
+    /// The seed as hex.
+    #[must_use]
+    pub fn to_hex(&self) -> String {
+        encode(self.0.as_bytes())
+    }
+
+    /// Read a secret key file.
+    ///
+    /// # Errors
+    /// Returns an error if the file cannot be read or holds no valid key.
+    pub fn read(path: &Path) -> io::Result<Self> {
+        Self::from_hex(&fs::read_to_string(path)?)
+    }
+
+    /// Write the key to a new file, readable only by the owner on Unix.
+    ///
+    /// # Errors
+    /// Returns an error if `path` already exists or cannot be written.
+    pub fn write(&self, path: &Path) -> io::Result<()> {
+        let mut options = fs::OpenOptions::new();
+        options.write(true).create_new(true);
+        #[cfg(unix)]
+        {
+            use std::os::unix::fs::OpenOptionsExt;
+            options.mode(0o600);
+        }
+        let mut file = options.open(path)?;
+        writeln!(file, "{}", self.to_hex())
+    }
+
+    /// The matching public key.
+    #[must_use]
+    pub fn public_key(&self) -> PublicKey {
+        PublicKey(self.0.verifying_key())
+    }
+
+    /// Sign `message`, returning the signature as hex.
+    #[must_use]
+    pub fn sign(&self, message: &[u8]) -> String {
+        encode(&self.0.sign(message).to_bytes())
+    }
+}
+
+impl PublicKey {
+    /// Parse a hex-encoded 32-byte public key.
+    ///
+    /// # Errors
+    /// Returns an error if `hex` is not 64 hex digits or not a valid key.
+    pub fn from_hex(hex: &str) -> io::Result<Self> {
+        ed25519_dalek::VerifyingKey::from_bytes(&decode_key(hex)?)
+            .map(Self)
+            .map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e))
+    }
+
+    /// The key as hex.
+    #[must_use]
+    pub fn to_hex(&self) -> String {
+        encode(self.0.as_bytes())
+    }
+
+    /// Read a public key file.
+    ///
+    /// # Errors
+    /// Returns an error if the file cannot be read or holds no valid key.
+    pub fn read(path: &Path) -> io::Result<Self> {
+        Self::from_hex(&fs::read_to_string(path)?)
+    }
+
+    /// Write the key to `path`.
+    ///
+    /// # Errors
+    /// Returns an error if the file cannot be written.
+    pub fn write(&self, path: &Path) -> io::Result<()> {
+        fs::write(path, format!("{}\n", self.to_hex()))
+    }
+
+    /// Short identifier of the key (16 hex digits of its BLAKE3 hash), for
+    /// naming the key that signed something.
+    #[must_use]
+    pub fn key_id(&self) -> String {
+        blake3::hash(self.0.as_bytes()).to_hex()[..16].to_string()
+    }
+
+    /// Check a hex `signature` of `message`.
+    #[must_use]
+    pub fn verify(&self, message: &[u8], signature: &str) -> bool {
+        let Some(bytes) = decode(signature.trim()).and_then(|b| <[u8; 64]>::try_from(b).ok())
+        else {
+            return false;
+        };
+        self.0
+            .verify(message, &Signature::from_bytes(&bytes))
+            .is_ok()
+    }
+}
+
+fn encode(bytes: &[u8]) -> String {
+    use std::fmt::Write as _;
+    bytes
+        .iter()
+        .fold(String::with_capacity(bytes.len() * 2), |mut hex, b| {
+            let _ = write!(hex, "{b:02x}");
+            hex
+        })
+}
+
+fn decode(hex: &str) -> Option<Vec<u8>> {
+    hex.as_bytes()
+        .chunks(2)
+        .map(|pair| {
+            let pair = std::str::from_utf8(pair).ok().filter(|p| p.len() == 2)?;
+            u8::from_str_radix(pair, 16).ok()
+        })
+        .collect()
+}
+
+fn decode_key(hex: &str) -> io::Result<[u8; 32]> {
+    decode(hex.trim())
+        .and_then(|bytes| bytes.try_into().ok())
+        .ok_or_else(|| {
+            io::Error::new(
+                io::ErrorKind::InvalidData,
+                "expected a 32-byte key as 64 hex digits",
+            )
+        })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_sign_and_verify() {
+        let key = SigningKey::generate().unwrap();
+        let public = key.public_key();
+        let signature = key.sign(b"entry");
+
+        assert!(public.verify(b"entry", &signature));
+        assert!(!public.verify(b"tampered", &signature));
+        assert!(!public.verify(b"entry", "not hex"));
+        let other = SigningKey::generate().unwrap().public_key();
+        assert!(!other.verify(b"entry", &signature));
+        assert_ne!(public.key_id(), other.key_id());
+    }
+
+    #[test]
+    fn test_key_files_roundtrip() {
+        let dir = tempfile::tempdir().unwrap();
+        let key = SigningKey::generate().unwrap();
+        key.write(&dir.path().join("cache.key")).unwrap();
+        key.public_key()
+            .write(&dir.path().join("cache.pub"))
+            .unwrap();
+
+        let read = SigningKey::read(&dir.path().join("cache.key")).unwrap();
+        assert_eq!(read.to_hex(), key.to_hex());
+        let public = PublicKey::read(&dir.path().join("cache.pub")).unwrap();
+        assert_eq!(public, key.public_key());
+
+        // Existing keys are never overwritten
+        assert!(key.write(&dir.path().join("cache.key")).is_err());
+        assert!(PublicKey::from_hex("abcd").is_err());
+        assert!(!format!("{key:?}").contains(&key.to_hex()));
+    }
+}
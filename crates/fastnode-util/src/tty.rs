@@ -0,0 +1,154 @@
+//! Terminal queries and mode changes for the standard streams.
+//!
+//! Used by the runtime's `tty`/`process.stdout` bindings and by process
+//! supervision, which restores the terminal if a child leaves it in raw
+//! mode. File descriptors are the Node.js ones: 0, 1 and 2.
+
+use std::io;
+
+/// Whether `fd` refers to a terminal.
+#[must_use]
+pub fn is_terminal(fd: i32) -> bool {
+    #[cfg(unix)]
+    {
+        // SAFETY: isatty only inspects the descriptor
+        unsafe { libc::isatty(fd) == 1 }
+    }
+    #[cfg(not(unix))]
+    {
+        use std::io::IsTerminal;
+        match fd {
+            0 => io::stdin().is_terminal(),
+            1 => io::stdout().is_terminal(),
+            2 => io::stderr().is_terminal(),
+            _ => false,
+        }
+    }
+}
+
+/// Terminal size of `fd` as `(columns, rows)`, or `None` if it isn't a
+/// terminal (or the size is unknown).
+#[must_use]
+pub fn window_size(fd: i32) -> Option<(u16, u16)> {
+    #[cfg(unix)]
+    {
+        // SAFETY: zeroed winsize is a valid out-parameter for TIOCGWINSZ
+        let mut size: libc::winsize = unsafe { std::mem::zeroed() };
+        // SAFETY: TIOCGWINSZ writes only to `size`
+        let rc = unsafe { libc::ioctl(fd, libc::TIOCGWINSZ, &raw mut size) };
+        (rc == 0 && size.ws_col > 0).then_some((size.ws_col, size.ws_row))
+    }
+    #[cfg(not(unix))]
+    {
+        let _ = fd;
+        None
+    }
+}
+
+/// Saved terminal attributes, restored with [`TerminalState::restore`].
+#[derive(Clone, Copy)]
+pub struct TerminalState {
+    fd: i32,
+    #[cfg(unix)]
+    termios: libc::termios,
+}
+
+impl std::fmt::Debug for TerminalState {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("TerminalState")
+            .field("fd", &self.fd)
+            .finish_non_exhaustive()
+    }
+}
+
+impl TerminalState {
+    /// Capture the current attributes of `fd`.
+    ///
+    /// # Errors
+    /// Returns an error if `fd` is not a terminal.
+    pub fn save(fd: i32) -> io::Result<Self> {
+        #[cfg(unix)]
+        {
+            // SAFETY: zeroed termios is a valid out-parameter for tcgetattr
+            let mut termios: libc::termios = unsafe { std::mem::zeroed() };
+            // SAFETY: tcgetattr writes only to `termios`
+            if unsafe { libc::tcgetattr(fd, &raw mut termios) } != 0 {
+                return Err(io::Error::last_os_error());
+            }
+            Ok(Self { fd, termios })
+        }
+        #[cfg(not(unix))]
+        {
+            Err(unsupported(fd))
+        }
+    }
+
+    /// Put the saved attributes back.
+    ///
+    /// # Errors
+    /// Returns an error if the terminal rejects the attributes.
+    pub fn restore(&self) -> io::Result<()> {
+        #[cfg(unix)]
+        {
+            // SAFETY: termios came from tcgetattr on the same descriptor
+            if unsafe { libc::tcsetattr(self.fd, libc::TCSADRAIN, &raw const self.termios) } != 0 {
+                return Err(io::Error::last_os_error());
+            }
+            Ok(())
+        }
+        #[cfg(not(unix))]
+        {
+            Err(unsupported(self.fd))
+        }
+    }
+}
+
+/// Switch `fd` into raw mode (no line buffering, echo or signal keys), as
+/// `process.stdin.setRawMode(true)` does. Returns the previous state so the
+/// caller can restore it.
+///
+/// # Errors
+/// Returns an error if `fd` is not a terminal.
+pub fn enable_raw_mode(fd: i32) -> io::Result<TerminalState> {
+    let previous = TerminalState::save(fd)?;
+    #[cfg(unix)]
+    {
+        let mut raw = previous.termios;
+        // Same flags libuv's UV_TTY_MODE_RAW clears/sets
+        raw.c_iflag &= !(libc::BRKINT | libc::ICRNL | libc::INPCK | libc::ISTRIP | libc::IXON);
+        raw.c_oflag |= libc::ONLCR;
+        raw.c_cflag |= libc::CS8;
+        raw.c_lflag &= !(libc::ECHO | libc::ICANON | libc::IEXTEN | libc::ISIG);
+        raw.c_cc[libc::VMIN] = 1;
+        raw.c_cc[libc::VTIME] = 0;
+        // SAFETY: raw is a valid termios derived from tcgetattr
+        if unsafe { libc::tcsetattr(fd, libc::TCSADRAIN, &raw const raw) } != 0 {
+            return Err(io::Error::last_os_error());
+        }
+    }
+    Ok(previous)
+}
+
+#[cfg(not(unix))]
+fn unsupported(fd: i32) -> io::Error {
+    io::Error::new(
+        io::ErrorKind::Unsupported,
+        format!("terminal modes are not supported for fd {fd} on this platform"),
+    )
+}
+
+#[cfg(all(test, unix))]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_non_terminal_fd() {
+        let file = tempfile::tempfile().unwrap();
+        let fd = std::os::unix::io::AsRawFd::as_raw_fd(&file);
+
+        assert!(!is_terminal(fd));
+        assert_eq!(window_size(fd), None);
+        assert!(TerminalState::save(fd).is_err());
+        assert!(enable_raw_mode(fd).is_err());
+    }
+}
@@ -12,15 +12,100 @@ pub struct Ast {
     pub stmts: Vec<Stmt>,
     /// Source code (for error messages and codegen).
     pub source: String,
+    /// Comments in source order. Only collected by
+    /// [`Parser::parse_with_comments`](crate::Parser::parse_with_comments).
+    pub comments: Vec<Comment>,
+    /// Start offsets of expressions annotated `/* @__PURE__ */` (or
+    /// `#__PURE__`), sorted. Always collected.
+    pub pure_annotations: Vec<u32>,
 }
 
 impl Ast {
     /// Create a new AST.
     pub fn new(stmts: Vec<Stmt>, source: String) -> Self {
-        Self { stmts, source }
+        Self {
+            stmts,
+            source,
+            comments: Vec::new(),
+            pure_annotations: Vec::new(),
+        }
+    }
+
+    /// Whether `expr` is a call or `new` annotated `/* @__PURE__ */`: it can
+    /// be removed when its result is unused (its arguments still run).
+    pub fn is_pure_call(&self, expr: &Expr) -> bool {
+        matches!(expr.kind, ExprKind::Call { .. } | ExprKind::New { .. })
+            && self
+                .pure_annotations
+                .binary_search(&expr.span.start)
+                .is_ok()
+    }
+
+    /// The comments directly before the node starting at `start`, with
+    /// only whitespace between them and the node (JSDoc, pragmas).
+    pub fn leading_comments(&self, start: u32) -> &[Comment] {
+        let end = self.comments.partition_point(|c| c.span.end <= start);
+        let mut first = end;
+        let mut next = start as usize;
+        while first > 0 {
+            let comment = &self.comments[first - 1];
+            let gap = self.source.get(comment.span.end as usize..next);
+            if !gap.is_some_and(|gap| gap.trim().is_empty()) {
+                break;
+            }
+            first -= 1;
+            next = comment.span.start as usize;
+        }
+        &self.comments[first..end]
+    }
+}
+
+/// A comment in the source.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Comment {
+    pub kind: CommentKind,
+    /// Span including the `//` or `/* */` delimiters.
+    pub span: Span,
+}
+
+impl Comment {
+    /// The comment's text without its delimiters.
+    pub fn text<'s>(&self, source: &'s str) -> &'s str {
+        let raw = &source[self.span.start as usize..self.span.end as usize];
+        match self.kind {
+            CommentKind::Line => &raw[2..],
+            CommentKind::Block => &raw[2..raw.len().saturating_sub(2).max(2)],
+        }
+    }
+
+    /// Whether this is a `/** ... */` doc comment.
+    pub fn is_jsdoc(&self, source: &str) -> bool {
+        self.kind == CommentKind::Block
+            && source[self.span.start as usize..].starts_with("/**")
+            && self.span.end - self.span.start > 4
+    }
+
+    /// The pragma this comment starts with, as `(name, argument)`:
+    /// `// @ts-ignore` is `("ts-ignore", "")` and `/** @jsx h */` is
+    /// `("jsx", "h")`.
+    pub fn pragma<'s>(&self, source: &'s str) -> Option<(&'s str, &'s str)> {
+        let text = self.text(source).trim_start_matches('*').trim();
+        let rest = text.strip_prefix('@')?;
+        let name_len = rest.find(|c: char| c.is_whitespace()).unwrap_or(rest.len());
+        let (name, argument) = rest.split_at(name_len);
+        (!name.is_empty()).then(|| (name, argument.trim()))
     }
 }
 
+/// Comment kinds.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CommentKind {
+    /// `// ...`
+    Line,
+    /// `/* ... */`
+    Block,
+}
+
 // =============================================================================
 // Expressions
 // =============================================================================
@@ -544,6 +629,9 @@ pub struct Param {
     pub default: Option<Expr>,
     pub rest: bool,
     pub span: Span,
+    /// TypeScript optional parameter: `x?: T`
+    #[cfg(feature = "typescript")]
+    pub optional: bool,
 }
 
 /// Class node.
@@ -552,6 +640,8 @@ pub struct Class {
     pub name: Option<String>,
     pub super_class: Option<Box<Expr>>,
     pub body: Vec<ClassMember>,
+    /// Class decorators (`@dec class Foo {}`), in source order.
+    pub decorators: Vec<Expr>,
     pub span: Span,
     #[cfg(feature = "typescript")]
     pub type_params: Option<Vec<TsTypeParam>>,
@@ -563,6 +653,8 @@ pub struct Class {
 #[derive(Debug, Clone, PartialEq)]
 pub struct ClassMember {
     pub kind: ClassMemberKind,
+    /// Member decorators (`@dec method() {}`), in source order.
+    pub decorators: Vec<Expr>,
     pub span: Span,
 }
 
@@ -599,6 +691,10 @@ pub enum ClassMemberKind {
         is_abstract: bool,
         #[cfg(feature = "typescript")]
         is_override: bool,
+        /// `foo?: T`
+        #[cfg(feature = "typescript")]
+        optional: bool,
+        /// `foo!: T`
         #[cfg(feature = "typescript")]
         definite: bool,
     },
@@ -7,6 +7,8 @@ use crate::ast::*;
 use crate::span::Span;
 use std::collections::HashMap;
 
+mod format;
+
 /// Code generation options.
 #[derive(Debug, Clone, Default)]
 pub struct CodegenOptions {
@@ -16,12 +18,28 @@ pub struct CodegenOptions {
     pub source_map: bool,
     /// Indent string (default: "  ").
     pub indent: Option<String>,
-    /// Target ECMAScript version.
+    /// Target ECMAScript version. Syntax newer than the target is lowered.
     pub target: Target,
+    /// How class and member decorators are compiled.
+    pub decorators: DecoratorMode,
 }
 
-/// ECMAScript target version.
+/// Decorator semantics used when compiling `@dec` on classes and members.
 #[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum DecoratorMode {
+    /// Standard (TC39 2023) decorators: `dec(value, context)`.
+    #[default]
+    Tc39,
+    /// TypeScript `experimentalDecorators`: `dec(target, key, descriptor)`.
+    Legacy,
+}
+
+/// ECMAScript target version.
+///
+/// Lowering starts at ES2015: `**` (ES2016), object spread (ES2018),
+/// optional catch bindings (ES2019), `??` and `?.` (ES2020) and logical
+/// assignment (ES2021) are rewritten for older targets.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, PartialOrd, Ord)]
 pub enum Target {
     /// ES5 (IE11 compatible)
     ES5,
@@ -64,8 +82,40 @@ pub struct Codegen<'a> {
     needs_semicolon: bool,
     /// Source map mappings (if enabled).
     mappings: Vec<SourceMapping>,
+    /// Source offset to map the next emitted token to.
+    pending_mapping: Option<u32>,
+    /// Output length, line and line start already scanned for mappings.
+    mapped_len: usize,
+    mapped_line: u32,
+    mapped_line_start: usize,
     /// Identifier renames (for scope hoisting).
     renames: HashMap<String, String>,
+    /// Runtime helpers referenced by lowered syntax, prepended on output.
+    helpers: Helpers,
+    /// Format mode: print the source back as written (see `codegen/format.rs`).
+    format: bool,
+    /// Index of the next comment in `ast.comments` to place (format mode).
+    next_comment: usize,
+    /// Source offset just past the last placed node or comment (format mode).
+    last_pos: u32,
+    /// Whether the next line break starts a list (no blank line before it).
+    at_list_start: bool,
+    /// Parenthesize the leftmost `function`/`class`/object of the next
+    /// expression (statement and arrow-body position).
+    wrap_leftmost: bool,
+    /// Why the source can't be formatted without losing something.
+    refused: Option<(Span, &'static str)>,
+}
+
+/// Runtime helpers needed by the generated code.
+#[derive(Debug, Clone, Copy, Default)]
+struct Helpers {
+    /// `__tmp`/`__ctx` scratch variables for `??` and `?.` lowering.
+    temps: bool,
+    /// TypeScript-style `__decorate`.
+    legacy_decorate: bool,
+    /// `__decorateElement` and `__runInitializers` for TC39 decorators.
+    tc39_decorate: bool,
 }
 
 /// A source map mapping.
@@ -92,7 +142,18 @@ impl<'a> Codegen<'a> {
             needs_space: false,
             needs_semicolon: false,
             mappings: Vec::new(),
+            pending_mapping: None,
+            mapped_len: 0,
+            mapped_line: 0,
+            mapped_line_start: 0,
             renames: HashMap::new(),
+            helpers: Helpers::default(),
+            format: false,
+            next_comment: 0,
+            last_pos: 0,
+            at_list_start: false,
+            wrap_leftmost: false,
+            refused: None,
         }
     }
 
@@ -112,7 +173,18 @@ impl<'a> Codegen<'a> {
             needs_space: false,
             needs_semicolon: false,
             mappings: Vec::new(),
+            pending_mapping: None,
+            mapped_len: 0,
+            mapped_line: 0,
+            mapped_line_start: 0,
             renames,
+            helpers: Helpers::default(),
+            format: false,
+            next_comment: 0,
+            last_pos: 0,
+            at_list_start: false,
+            wrap_leftmost: false,
+            refused: None,
         }
     }
 
@@ -125,32 +197,71 @@ impl<'a> Codegen<'a> {
     }
 
     /// Generate JavaScript source code.
-    pub fn generate(mut self) -> String {
-        for stmt in &self.ast.stmts {
-            self.emit_stmt(stmt);
-            if !self.options.minify {
-                self.emit_newline();
-            }
-        }
-        self.output
+    pub fn generate(self) -> String {
+        self.generate_with_source_map().0
     }
 
     /// Generate JavaScript source code with source map.
     pub fn generate_with_source_map(mut self) -> (String, Vec<SourceMapping>) {
+        let mut prelude_at = None;
         for stmt in &self.ast.stmts {
+            // Helpers go after the directive prologue ("use strict").
+            let is_directive = matches!(
+                &stmt.kind,
+                StmtKind::Expr(Expr {
+                    kind: ExprKind::String(_),
+                    ..
+                })
+            );
+            if prelude_at.is_none() && !is_directive {
+                prelude_at = Some(self.output.len());
+            }
             self.emit_stmt(stmt);
             if !self.options.minify {
                 self.emit_newline();
             }
         }
+        let prelude = self.helper_prelude();
+        if !prelude.is_empty() {
+            let at = prelude_at.unwrap_or(self.output.len());
+            let shifted_line = self.output[..at].matches('\n').count() as u32;
+            let added_lines = prelude.matches('\n').count() as u32;
+            for mapping in &mut self.mappings {
+                if mapping.gen_line >= shifted_line {
+                    mapping.gen_line += added_lines;
+                }
+            }
+            self.output.insert_str(at, &prelude);
+        }
         (self.output, self.mappings)
     }
 
+    /// Runtime helper definitions required by the emitted code.
+    fn helper_prelude(&self) -> String {
+        let mut prelude = String::new();
+        if self.helpers.temps {
+            prelude.push_str("var __tmp, __ctx;\n");
+        }
+        if self.helpers.legacy_decorate {
+            prelude.push_str(LEGACY_DECORATE_HELPER);
+        }
+        if self.helpers.tc39_decorate {
+            prelude.push_str(TC39_DECORATE_HELPERS);
+        }
+        prelude
+    }
+
+    /// Whether the target natively supports syntax introduced in `version`.
+    fn supports(&self, version: Target) -> bool {
+        self.format || self.options.target >= version
+    }
+
     // =========================================================================
     // Output Helpers
     // =========================================================================
 
     fn emit(&mut self, s: &str) {
+        self.wrap_leftmost = false;
         if self.needs_semicolon {
             self.output.push(';');
             self.needs_semicolon = false;
@@ -162,9 +273,31 @@ impl<'a> Codegen<'a> {
             }
             self.needs_space = false;
         }
+        // Keep `a - -b`, `a + ++b` and `a / /re/` from running together into
+        // `--`, `+++` and a `//` comment
+        if let Some(first @ ('+' | '-' | '/')) = s.chars().next() {
+            if self.output.ends_with(first) {
+                self.output.push(' ');
+            }
+        }
+        if let Some(offset) = self.pending_mapping.take() {
+            self.add_mapping(offset);
+        }
         self.output.push_str(s);
     }
 
+    /// Keep a call's `/* @__PURE__ */` annotation so later tools can still
+    /// drop it. Format mode places it with the other comments.
+    fn emit_pure_annotation(&mut self, expr: &Expr) {
+        if !self.format && self.ast.is_pure_call(expr) {
+            if self.options.minify {
+                self.emit("/*@__PURE__*/");
+            } else {
+                self.emit("/* @__PURE__ */ ");
+            }
+        }
+    }
+
     fn emit_space(&mut self) {
         if !self.options.minify {
             self.output.push(' ');
@@ -196,18 +329,26 @@ impl<'a> Codegen<'a> {
         }
     }
 
-    fn emit_with_mapping(&mut self, s: &str, span: Span) {
-        if self.options.source_map {
-            let lines: Vec<&str> = self.output.split('\n').collect();
-            let gen_line = (lines.len() - 1) as u32;
-            let gen_col = lines.last().map(|l| l.len() as u32).unwrap_or(0);
-            self.mappings.push(SourceMapping {
-                gen_line,
-                gen_col,
-                orig_offset: span.start,
-            });
+    /// Map the next emitted token to the start of `span` (source maps only).
+    fn map_next(&mut self, span: Span) {
+        if self.options.source_map && !self.format {
+            self.pending_mapping = Some(span.start);
+        }
+    }
+
+    /// Map the current output position to `offset` in the source.
+    fn add_mapping(&mut self, offset: u32) {
+        let scanned = &self.output[self.mapped_len..];
+        if let Some(last) = scanned.rfind('\n') {
+            self.mapped_line += scanned.matches('\n').count() as u32;
+            self.mapped_line_start = self.mapped_len + last + 1;
         }
-        self.emit(s);
+        self.mapped_len = self.output.len();
+        self.mappings.push(SourceMapping {
+            gen_line: self.mapped_line,
+            gen_col: (self.output.len() - self.mapped_line_start) as u32,
+            orig_offset: offset,
+        });
     }
 
     fn indent(&mut self) {
@@ -223,6 +364,10 @@ impl<'a> Codegen<'a> {
     // =========================================================================
 
     fn emit_stmt(&mut self, stmt: &Stmt) {
+        if self.format && self.emit_formatted_stmt(stmt) {
+            return;
+        }
+        self.map_next(stmt.span);
         match &stmt.kind {
             StmtKind::Var { kind, decls } => {
                 self.emit_var_decl(*kind, decls);
@@ -230,6 +375,14 @@ impl<'a> Codegen<'a> {
             StmtKind::Function(func) => {
                 self.emit_function(func, true);
             }
+            StmtKind::Class(class) if has_decorators(class) => {
+                let name = self.rename(class.name.as_deref().unwrap_or("_class"));
+                self.emit("let ");
+                self.emit(&name);
+                self.emit_op("=");
+                self.emit_decorated_class(class, &name);
+                self.emit_semicolon();
+            }
             StmtKind::Class(class) => {
                 self.emit_class(class, true);
             }
@@ -251,6 +404,8 @@ impl<'a> Codegen<'a> {
                 if let Some(alt) = alternate {
                     self.emit_space();
                     self.emit("else");
+                    // `else return` must not become `elsereturn`
+                    self.needs_space = self.options.minify;
                     self.emit_space();
                     self.emit_stmt(alt);
                 }
@@ -355,6 +510,7 @@ impl<'a> Codegen<'a> {
             }
             StmtKind::DoWhile { body, test } => {
                 self.emit("do");
+                self.needs_space = self.options.minify;
                 self.emit_space();
                 self.emit_stmt(body);
                 self.emit_space();
@@ -411,6 +567,10 @@ impl<'a> Codegen<'a> {
                         self.emit("(");
                         self.emit_binding(param);
                         self.emit(")");
+                    } else if !self.supports(Target::ES2019) {
+                        // Optional catch binding is ES2019
+                        self.emit_space();
+                        self.emit("(_unused)");
                     }
                     self.emit_space();
                     self.emit_block(&catch.body);
@@ -428,6 +588,14 @@ impl<'a> Codegen<'a> {
                 self.emit_space();
                 self.emit_stmt(body);
             }
+            // `(function() {})()` and `({ a } = b)` would otherwise read as a
+            // declaration and a block
+            StmtKind::Expr(expr) if starts_with_declaration(expr) => {
+                self.emit("(");
+                self.emit_expr(expr);
+                self.emit(")");
+                self.emit_semicolon();
+            }
             StmtKind::Expr(expr) => {
                 self.emit_expr(expr);
                 self.emit_semicolon();
@@ -479,6 +647,10 @@ impl<'a> Codegen<'a> {
     }
 
     fn emit_block(&mut self, stmts: &[Stmt]) {
+        if self.format {
+            self.emit_formatted_block(stmts, None);
+            return;
+        }
         self.emit("{");
         if !stmts.is_empty() {
             self.indent();
@@ -546,6 +718,10 @@ impl<'a> Codegen<'a> {
     }
 
     fn emit_binding(&mut self, binding: &Binding) {
+        if self.format {
+            self.emit_formatted_binding(binding, false);
+            return;
+        }
         match &binding.kind {
             BindingKind::Ident { name, .. } => {
                 let renamed = self.rename(name);
@@ -604,6 +780,10 @@ impl<'a> Codegen<'a> {
     }
 
     fn emit_function(&mut self, func: &Function, is_declaration: bool) {
+        if self.format {
+            self.emit_formatted_function(func, is_declaration);
+            return;
+        }
         if func.is_async {
             self.emit("async");
             self.emit(" ");
@@ -625,6 +805,10 @@ impl<'a> Codegen<'a> {
     }
 
     fn emit_arrow(&mut self, arrow: &ArrowFunction) {
+        if self.format {
+            self.emit_formatted_arrow(arrow);
+            return;
+        }
         if arrow.is_async {
             self.emit("async");
             self.emit(" ");
@@ -648,8 +832,8 @@ impl<'a> Codegen<'a> {
 
         match &arrow.body {
             ArrowBody::Expr(expr) => {
-                // Object literal needs parens
-                if matches!(expr.kind, ExprKind::Object(_)) {
+                // Object literals and sequences need parens
+                if matches!(expr.kind, ExprKind::Object(_) | ExprKind::Sequence(_)) {
                     self.emit("(");
                     self.emit_expr(expr);
                     self.emit(")");
@@ -664,6 +848,10 @@ impl<'a> Codegen<'a> {
     }
 
     fn emit_params(&mut self, params: &[Param]) {
+        if self.format {
+            self.emit_formatted_params(params);
+            return;
+        }
         self.emit("(");
         for (i, param) in params.iter().enumerate() {
             if i > 0 {
@@ -685,6 +873,17 @@ impl<'a> Codegen<'a> {
     }
 
     fn emit_class(&mut self, class: &Class, is_declaration: bool) {
+        self.emit_class_with(class, is_declaration, false);
+    }
+
+    /// Emit a class. With `tc39_fields`, decorated fields read their value
+    /// through `__initN` and instance initializers run first (see
+    /// [`Self::emit_decorated_class`]).
+    fn emit_class_with(&mut self, class: &Class, is_declaration: bool, tc39_fields: bool) {
+        if self.format {
+            self.emit_formatted_class(class, is_declaration);
+            return;
+        }
         self.emit("class");
         if let Some(name) = &class.name {
             self.emit(" ");
@@ -700,9 +899,37 @@ impl<'a> Codegen<'a> {
         self.emit_space();
         self.emit("{");
         self.indent();
+        let mut field_index = 0;
+        if tc39_fields && decorated_members(class).any(|(_, decoration)| !decoration.is_static) {
+            self.emit_newline();
+            self.emit("#__runInits");
+            self.emit_op("=");
+            self.emit("__runInitializers(this,");
+            self.emit_space();
+            self.emit("__inits)");
+            self.emit_semicolon();
+        }
         for member in &class.body {
             self.emit_newline();
-            self.emit_class_member(member);
+            match (&member.kind, member_decoration(member)) {
+                (ClassMemberKind::Property { key, value, .. }, Some(decoration)) if tc39_fields => {
+                    if decoration.is_static {
+                        self.emit("static ");
+                    }
+                    self.emit_property_key(key);
+                    self.emit_op("=");
+                    self.emit(&format!("__init{field_index}(this,"));
+                    self.emit_space();
+                    match value {
+                        Some(value) => self.emit_expr_with_prec(value, 2),
+                        None => self.emit("void 0"),
+                    }
+                    self.emit(")");
+                    self.emit_semicolon();
+                    field_index += 1;
+                }
+                _ => self.emit_class_member(member),
+            }
         }
         self.dedent();
         if !class.body.is_empty() {
@@ -711,13 +938,208 @@ impl<'a> Codegen<'a> {
         self.emit("}");
     }
 
+    /// Emit a decorated class as an expression that evaluates to the
+    /// decorated class, bound to `binding` inside:
+    ///
+    /// ```text
+    /// (() => {
+    ///   let C = class C { ... };
+    ///   __decorate([dec], C.prototype, "method", null);   // legacy
+    ///   C = __decorate([classDec], C);
+    ///   return C;
+    /// })()
+    /// ```
+    ///
+    /// TC39 mode decorates fields before the class exists (their
+    /// initializers are read through `__initN`), then methods and accessors,
+    /// then the class, and finally runs static/class `addInitializer`
+    /// callbacks. Decorators on `#private` members are not supported and
+    /// are dropped.
+    fn emit_decorated_class(&mut self, class: &Class, binding: &str) {
+        let legacy = self.options.decorators == DecoratorMode::Legacy;
+        if legacy {
+            self.helpers.legacy_decorate = true;
+        } else {
+            self.helpers.tc39_decorate = true;
+        }
+
+        self.emit("(()");
+        self.emit_op("=>");
+        self.emit("{");
+        self.indent();
+
+        if !legacy {
+            let fields: Vec<_> = decorated_members(class)
+                .filter(|(_, decoration)| decoration.kind == "field")
+                .collect();
+            self.emit_newline();
+            self.emit("let __inits");
+            self.emit_op("=");
+            self.emit("[],");
+            self.emit_space();
+            self.emit("__staticInits");
+            self.emit_op("=");
+            self.emit("[]");
+            for i in 0..fields.len() {
+                self.emit(",");
+                self.emit_space();
+                self.emit(&format!("__init{i}"));
+            }
+            self.emit_semicolon();
+            for (i, (member, decoration)) in fields.into_iter().enumerate() {
+                self.emit_newline();
+                self.emit(&format!("__init{i}"));
+                self.emit_op("=");
+                self.emit("__decorateElement(null,");
+                self.emit_space();
+                self.emit_decorator_args(member, &decoration);
+                self.emit(")");
+                self.emit_semicolon();
+            }
+        }
+
+        self.emit_newline();
+        self.emit("let ");
+        self.emit(binding);
+        self.emit_op("=");
+        self.emit_class_with(class, false, !legacy);
+        self.emit_semicolon();
+
+        for (member, decoration) in decorated_members(class) {
+            if !legacy && decoration.kind == "field" {
+                continue;
+            }
+            self.emit_newline();
+            if legacy {
+                self.emit("__decorate(");
+                self.emit_decorator_array(&member.decorators);
+                self.emit(",");
+                self.emit_space();
+                self.emit(binding);
+                if !decoration.is_static {
+                    self.emit(".prototype");
+                }
+                self.emit(",");
+                self.emit_space();
+                self.emit_member_key_value(decoration.key);
+                self.emit(",");
+                self.emit_space();
+                self.emit(if decoration.kind == "field" {
+                    "void 0"
+                } else {
+                    "null"
+                });
+            } else {
+                self.emit("__decorateElement(");
+                self.emit(binding);
+                self.emit(",");
+                self.emit_space();
+                self.emit_decorator_args(member, &decoration);
+            }
+            self.emit(")");
+            self.emit_semicolon();
+        }
+
+        if !class.decorators.is_empty() {
+            self.emit_newline();
+            self.emit(binding);
+            self.emit_op("=");
+            if legacy {
+                self.emit("__decorate(");
+                self.emit_decorator_array(&class.decorators);
+                self.emit(",");
+                self.emit_space();
+                self.emit(binding);
+            } else {
+                self.emit("__decorateElement(");
+                self.emit(binding);
+                self.emit(",");
+                self.emit_space();
+                self.emit("\"class\",");
+                self.emit_space();
+                self.emit("void 0,");
+                self.emit_space();
+                self.emit_decorator_array(&class.decorators);
+                self.emit(",");
+                self.emit_space();
+                self.emit("false,");
+                self.emit_space();
+                self.emit("__staticInits");
+            }
+            self.emit(")");
+            self.emit_semicolon();
+        }
+        if !legacy {
+            self.emit_newline();
+            self.emit("__runInitializers(");
+            self.emit(binding);
+            self.emit(",");
+            self.emit_space();
+            self.emit("__staticInits)");
+            self.emit_semicolon();
+        }
+
+        self.emit_newline();
+        self.emit("return ");
+        self.emit(binding);
+        self.emit_semicolon();
+        self.dedent();
+        self.emit_newline();
+        self.emit("})()");
+    }
+
+    /// `kind, key, [decorators], isStatic, inits` for `__decorateElement`.
+    fn emit_decorator_args(&mut self, member: &ClassMember, decoration: &MemberDecoration<'_>) {
+        self.emit(&format!("\"{}\",", decoration.kind));
+        self.emit_space();
+        self.emit_member_key_value(decoration.key);
+        self.emit(",");
+        self.emit_space();
+        self.emit_decorator_array(&member.decorators);
+        self.emit(",");
+        self.emit_space();
+        if decoration.is_static {
+            self.emit("true,");
+            self.emit_space();
+            self.emit("__staticInits");
+        } else {
+            self.emit("false,");
+            self.emit_space();
+            self.emit("__inits");
+        }
+    }
+
+    fn emit_decorator_array(&mut self, decorators: &[Expr]) {
+        self.emit("[");
+        for (i, decorator) in decorators.iter().enumerate() {
+            if i > 0 {
+                self.emit(",");
+                self.emit_space();
+            }
+            self.emit_expr_with_prec(decorator, 2);
+        }
+        self.emit("]");
+    }
+
+    /// A member key as a runtime value (`foo` -> `"foo"`).
+    fn emit_member_key_value(&mut self, key: &PropertyKey) {
+        match key {
+            PropertyKey::Ident(name) | PropertyKey::String(name) => {
+                self.emit("\"");
+                self.emit(&escape_string(name));
+                self.emit("\"");
+            }
+            PropertyKey::Number(n) => self.emit(&format_number(*n)),
+            PropertyKey::Computed(expr) => self.emit_expr_with_prec(expr, 2),
+        }
+    }
+
     fn emit_class_member(&mut self, member: &ClassMember) {
         match &member.kind {
             ClassMemberKind::Method {
                 key,
                 value,
                 kind,
-                computed,
                 is_static,
                 ..
             } => {
@@ -735,13 +1157,7 @@ impl<'a> Codegen<'a> {
                 if value.is_generator {
                     self.emit("*");
                 }
-                if *computed {
-                    self.emit("[");
-                }
                 self.emit_property_key(key);
-                if *computed {
-                    self.emit("]");
-                }
                 self.emit_params(&value.params);
                 self.emit_space();
                 self.emit_block(&value.body);
@@ -749,20 +1165,13 @@ impl<'a> Codegen<'a> {
             ClassMemberKind::Property {
                 key,
                 value,
-                computed,
                 is_static,
                 ..
             } => {
                 if *is_static {
                     self.emit("static ");
                 }
-                if *computed {
-                    self.emit("[");
-                }
                 self.emit_property_key(key);
-                if *computed {
-                    self.emit("]");
-                }
                 if let Some(value) = value {
                     self.emit_space();
                     self.emit("=");
@@ -783,6 +1192,10 @@ impl<'a> Codegen<'a> {
     }
 
     fn emit_import(&mut self, decl: &ImportDecl) {
+        if self.format {
+            self.emit_formatted_import(decl);
+            return;
+        }
         // TypeScript: skip type-only imports entirely
         #[cfg(feature = "typescript")]
         if decl.is_type_only {
@@ -881,6 +1294,10 @@ impl<'a> Codegen<'a> {
     }
 
     fn emit_export(&mut self, decl: &ExportDecl) {
+        if self.format {
+            self.emit_formatted_export(decl);
+            return;
+        }
         match decl {
             ExportDecl::Named {
                 specifiers,
@@ -939,6 +1356,21 @@ impl<'a> Codegen<'a> {
                 }
                 self.emit_semicolon();
             }
+            ExportDecl::Default { expr, .. } if matches!(&expr.kind, ExprKind::Class(class) if class.name.is_some() && has_decorators(class)) =>
+            {
+                // Keep the local binding: `let C = ...; export { C as default };`
+                let ExprKind::Class(class) = &expr.kind else {
+                    unreachable!()
+                };
+                let stmt = Stmt::new(StmtKind::Class(class.clone()), expr.span);
+                self.emit_stmt(&stmt);
+                self.emit_newline();
+                let name = self.rename(class.name.as_deref().unwrap_or_default());
+                self.emit("export { ");
+                self.emit(&name);
+                self.emit(" as default }");
+                self.emit_semicolon();
+            }
             ExportDecl::Default { expr, .. } => {
                 self.emit("export default ");
                 self.emit_expr(expr);
@@ -973,6 +1405,9 @@ impl<'a> Codegen<'a> {
     }
 
     fn emit_expr_with_prec(&mut self, expr: &Expr, min_prec: u8) {
+        if self.format && self.emit_formatted_expr(expr, min_prec) {
+            return;
+        }
         match &expr.kind {
             ExprKind::Null => self.emit("null"),
             ExprKind::Bool(b) => self.emit(if *b { "true" } else { "false" }),
@@ -1000,10 +1435,7 @@ impl<'a> Codegen<'a> {
                 self.emit("\"");
             }
             ExprKind::Regex { pattern, flags } => {
-                self.emit("/");
-                self.emit(pattern);
-                self.emit("/");
-                self.emit(flags);
+                self.emit(&format!("/{pattern}/{flags}"));
             }
             ExprKind::TemplateNoSub(s) => {
                 self.emit("`");
@@ -1041,6 +1473,11 @@ impl<'a> Codegen<'a> {
                 }
                 self.emit("]");
             }
+            ExprKind::Object(properties)
+                if !self.supports(Target::ES2018) && properties.iter().any(is_spread_property) =>
+            {
+                self.emit_object_assign(properties);
+            }
             ExprKind::Object(properties) => {
                 self.emit("{");
                 for (i, prop) in properties.iter().enumerate() {
@@ -1065,6 +1502,10 @@ impl<'a> Codegen<'a> {
                     self.emit(")");
                 }
             }
+            ExprKind::Class(class) if has_decorators(class) => {
+                let name = self.rename(class.name.as_deref().unwrap_or("_class"));
+                self.emit_decorated_class(class, &name);
+            }
             ExprKind::Class(class) => {
                 self.emit_class(class, false);
             }
@@ -1078,8 +1519,30 @@ impl<'a> Codegen<'a> {
                     UnaryOp::Void => "void ",
                     UnaryOp::Delete => "delete ",
                 };
+                // `(-1).toFixed()`, `(!a)()`
+                let needs_parens = min_prec > 15;
+                if needs_parens {
+                    self.emit("(");
+                }
                 self.emit(op_str);
                 self.emit_expr_with_prec(arg, 15); // Unary precedence
+                if needs_parens {
+                    self.emit(")");
+                }
+            }
+            ExprKind::Binary {
+                op: BinaryOp::Pow,
+                left,
+                right,
+            } if !self.supports(Target::ES2016) => {
+                self.emit_math_pow(left, right);
+            }
+            ExprKind::Binary {
+                op: BinaryOp::NullishCoalesce,
+                left,
+                right,
+            } if !self.supports(Target::ES2020) => {
+                self.emit_lowered_nullish(left, right, None, min_prec);
             }
             ExprKind::Binary { op, left, right } => {
                 let (prec, op_str) = binary_op_info(*op);
@@ -1089,8 +1552,16 @@ impl<'a> Codegen<'a> {
                 }
                 self.emit_expr_with_prec(left, prec);
                 self.emit_space();
+                // `a in b` and `a instanceof B` keep their spaces in minify
+                let is_keyword = op_str.starts_with(char::is_alphabetic);
+                if is_keyword && self.options.minify {
+                    self.needs_space = true;
+                }
                 self.emit(op_str);
                 self.emit_space();
+                if is_keyword {
+                    self.needs_space = self.options.minify;
+                }
                 // Right side needs higher precedence for left-associative ops
                 let right_prec = if is_right_associative(*op) {
                     prec
@@ -1102,6 +1573,9 @@ impl<'a> Codegen<'a> {
                     self.emit(")");
                 }
             }
+            ExprKind::Assign { op, left, right } if self.needs_assign_lowering(*op) => {
+                self.emit_lowered_assign(*op, left, right, min_prec);
+            }
             ExprKind::Assign { op, left, right } => {
                 if min_prec > 2 {
                     self.emit("(");
@@ -1150,6 +1624,9 @@ impl<'a> Codegen<'a> {
                 }
             }
             ExprKind::Sequence(exprs) => {
+                if min_prec > 1 {
+                    self.emit("(");
+                }
                 for (i, expr) in exprs.iter().enumerate() {
                     if i > 0 {
                         self.emit(",");
@@ -1157,6 +1634,17 @@ impl<'a> Codegen<'a> {
                     }
                     self.emit_expr_with_prec(expr, 1);
                 }
+                if min_prec > 1 {
+                    self.emit(")");
+                }
+            }
+            ExprKind::Member { .. }
+            | ExprKind::OptionalMember { .. }
+            | ExprKind::Call { .. }
+            | ExprKind::OptionalCall { .. }
+                if !self.supports(Target::ES2020) && has_optional_link(expr) =>
+            {
+                self.emit_lowered_chain(expr);
             }
             ExprKind::Member {
                 object,
@@ -1189,6 +1677,7 @@ impl<'a> Codegen<'a> {
                 }
             }
             ExprKind::Call { callee, args } => {
+                self.emit_pure_annotation(expr);
                 self.emit_expr_with_prec(callee, 18);
                 self.emit("(");
                 for (i, arg) in args.iter().enumerate() {
@@ -1213,19 +1702,20 @@ impl<'a> Codegen<'a> {
                 self.emit(")");
             }
             ExprKind::New { callee, args } => {
+                self.emit_pure_annotation(expr);
                 self.emit("new ");
                 self.emit_expr_with_prec(callee, 17);
-                if !args.is_empty() {
-                    self.emit("(");
-                    for (i, arg) in args.iter().enumerate() {
-                        if i > 0 {
-                            self.emit(",");
-                            self.emit_space();
-                        }
-                        self.emit_expr_with_prec(arg, 2);
+                // Always emit the arguments: `new Foo.bar` would read as `new (Foo.bar)`
+                // when the new object is the object of a member expression
+                self.emit("(");
+                for (i, arg) in args.iter().enumerate() {
+                    if i > 0 {
+                        self.emit(",");
+                        self.emit_space();
                     }
-                    self.emit(")");
+                    self.emit_expr_with_prec(arg, 2);
                 }
+                self.emit(")");
             }
             ExprKind::TaggedTemplate { tag, quasi } => {
                 self.emit_expr_with_prec(tag, 18);
@@ -1291,6 +1781,254 @@ impl<'a> Codegen<'a> {
         }
     }
 
+    // =========================================================================
+    // Syntax Lowering
+    // =========================================================================
+
+    /// Emit ` op ` (spaces dropped when minifying).
+    fn emit_op(&mut self, op: &str) {
+        self.emit_space();
+        self.emit(op);
+        self.emit_space();
+    }
+
+    /// `a ** b` as `Math.pow(a, b)`.
+    fn emit_math_pow(&mut self, left: &Expr, right: &Expr) {
+        self.emit("Math.pow(");
+        self.emit_expr_with_prec(left, 2);
+        self.emit(",");
+        self.emit_space();
+        self.emit_expr_with_prec(right, 2);
+        self.emit(")");
+    }
+
+    /// `a ?? b` as `(__tmp = a) != null ? __tmp : b`.
+    ///
+    /// With `assign_to`, the fallback assigns `b` to it (for `a ??= b`).
+    fn emit_lowered_nullish(
+        &mut self,
+        left: &Expr,
+        right: &Expr,
+        assign_to: Option<&Expr>,
+        min_prec: u8,
+    ) {
+        self.helpers.temps = true;
+        if min_prec > 3 {
+            self.emit("(");
+        }
+        self.emit("(__tmp");
+        self.emit_op("=");
+        self.emit_expr_with_prec(left, 2);
+        self.emit(")");
+        self.emit_op("!=");
+        self.emit("null");
+        self.emit_op("?");
+        self.emit("__tmp");
+        self.emit_op(":");
+        if let Some(target) = assign_to {
+            self.emit("(");
+            self.emit_expr_with_prec(target, 3);
+            self.emit_op("=");
+            self.emit_expr_with_prec(right, 2);
+            self.emit(")");
+        } else {
+            self.emit_expr_with_prec(right, 2);
+        }
+        if min_prec > 3 {
+            self.emit(")");
+        }
+    }
+
+    /// Whether an assignment operator is newer than the target.
+    fn needs_assign_lowering(&self, op: AssignOp) -> bool {
+        match op {
+            AssignOp::PowAssign => !self.supports(Target::ES2016),
+            AssignOp::AndAssign | AssignOp::OrAssign | AssignOp::NullishAssign => {
+                !self.supports(Target::ES2021)
+            }
+            _ => false,
+        }
+    }
+
+    /// Lower `**=` and logical assignment.
+    ///
+    /// `a.b ||= c` becomes `a.b || (a.b = c)`; the target expression is
+    /// evaluated twice, so member targets with side effects run them twice.
+    fn emit_lowered_assign(&mut self, op: AssignOp, left: &Expr, right: &Expr, min_prec: u8) {
+        if op == AssignOp::NullishAssign && !self.supports(Target::ES2020) {
+            self.emit_lowered_nullish(left, right, Some(left), min_prec);
+            return;
+        }
+        if min_prec > 2 {
+            self.emit("(");
+        }
+        if op == AssignOp::PowAssign {
+            self.emit_expr_with_prec(left, 3);
+            self.emit_op("=");
+            self.emit_math_pow(left, right);
+        } else {
+            let (prec, op_str) = match op {
+                AssignOp::AndAssign => binary_op_info(BinaryOp::And),
+                AssignOp::OrAssign => binary_op_info(BinaryOp::Or),
+                _ => binary_op_info(BinaryOp::NullishCoalesce),
+            };
+            self.emit_expr_with_prec(left, prec + 1);
+            self.emit_op(op_str);
+            self.emit("(");
+            self.emit_expr_with_prec(left, 3);
+            self.emit_op("=");
+            self.emit_expr_with_prec(right, 2);
+            self.emit(")");
+        }
+        if min_prec > 2 {
+            self.emit(")");
+        }
+    }
+
+    /// `{ a, ...b, c }` as `Object.assign({ a }, b, { c })`.
+    fn emit_object_assign(&mut self, properties: &[Property]) {
+        self.emit("Object.assign(");
+        let mut first = true;
+        if properties.first().is_some_and(is_spread_property) {
+            self.emit("{}");
+            first = false;
+        }
+        let mut rest = properties;
+        while !rest.is_empty() {
+            if !first {
+                self.emit(",");
+                self.emit_space();
+            }
+            first = false;
+            if let ExprKind::Spread(arg) = &rest[0].value.kind {
+                self.emit_expr_with_prec(arg, 2);
+                rest = &rest[1..];
+                continue;
+            }
+            let end = rest
+                .iter()
+                .position(is_spread_property)
+                .unwrap_or(rest.len());
+            self.emit("{");
+            for (i, prop) in rest[..end].iter().enumerate() {
+                if i > 0 {
+                    self.emit(",");
+                    self.emit_space();
+                }
+                self.emit_object_property(prop);
+            }
+            self.emit("}");
+            rest = &rest[end..];
+        }
+        self.emit(")");
+    }
+
+    /// Lower an optional chain (`a?.b.c`, `a.b?.()`) to conditionals.
+    ///
+    /// `a?.b.c` becomes `(__tmp = a) == null ? void 0 : __tmp.b.c`, so a
+    /// nullish link short-circuits the rest of the chain. Optional calls
+    /// of a member keep their `this` through `__ctx`.
+    fn emit_lowered_chain(&mut self, expr: &Expr) {
+        let (base, links) = flatten_chain(expr);
+        self.helpers.temps = true;
+        self.emit("(");
+        self.emit_chain_segment(ChainHead::Expr(base), &links);
+        self.emit(")");
+    }
+
+    fn emit_chain_segment(&mut self, head: ChainHead<'_>, links: &[ChainLink<'_>]) {
+        let Some(optional) = links.iter().position(|link| link.optional) else {
+            self.emit_chain_head(head);
+            self.emit_chain_links(links);
+            return;
+        };
+        let (before, rest) = links.split_at(optional);
+        let this_member = matches!(rest[0].kind, ChainLinkKind::Call(_))
+            && matches!(
+                before.last(),
+                Some(ChainLink {
+                    kind: ChainLinkKind::Member { .. },
+                    ..
+                })
+            );
+
+        self.emit("(__tmp");
+        self.emit_op("=");
+        if this_member {
+            let (object_links, member) = before.split_at(before.len() - 1);
+            self.emit("(__ctx");
+            self.emit_op("=");
+            self.emit_chain_head(head);
+            self.emit_chain_links(object_links);
+            self.emit(")");
+            self.emit_chain_links(member);
+        } else {
+            self.emit_chain_head(head);
+            self.emit_chain_links(before);
+        }
+        self.emit(")");
+        self.emit_op("==");
+        self.emit("null");
+        self.emit_op("?");
+        self.emit("void 0");
+        self.emit_op(":");
+
+        let next = match rest[0].kind {
+            ChainLinkKind::Call(args) if this_member => ChainHead::BoundCall(args),
+            _ => ChainHead::Temp(&rest[0]),
+        };
+        self.emit_chain_segment(next, &rest[1..]);
+    }
+
+    fn emit_chain_head(&mut self, head: ChainHead<'_>) {
+        match head {
+            ChainHead::Expr(expr) => self.emit_expr_with_prec(expr, 18),
+            ChainHead::Temp(link) => {
+                self.emit("__tmp");
+                self.emit_chain_links(std::slice::from_ref(link));
+            }
+            ChainHead::BoundCall(args) => {
+                self.emit("__tmp.call(__ctx");
+                for arg in args {
+                    self.emit(",");
+                    self.emit_space();
+                    self.emit_expr_with_prec(arg, 2);
+                }
+                self.emit(")");
+            }
+        }
+    }
+
+    fn emit_chain_links(&mut self, links: &[ChainLink<'_>]) {
+        for link in links {
+            match link.kind {
+                ChainLinkKind::Member {
+                    property,
+                    computed: true,
+                } => {
+                    self.emit("[");
+                    self.emit_expr(property);
+                    self.emit("]");
+                }
+                ChainLinkKind::Member { property, .. } => {
+                    self.emit(".");
+                    self.emit_expr(property);
+                }
+                ChainLinkKind::Call(args) => {
+                    self.emit("(");
+                    for (i, arg) in args.iter().enumerate() {
+                        if i > 0 {
+                            self.emit(",");
+                            self.emit_space();
+                        }
+                        self.emit_expr_with_prec(arg, 2);
+                    }
+                    self.emit(")");
+                }
+            }
+        }
+    }
+
     // =========================================================================
     // JSX Code Generation
     // =========================================================================
@@ -1581,6 +2319,15 @@ impl<'a> Codegen<'a> {
     }
 
     fn emit_object_property(&mut self, prop: &Property) {
+        if self.format {
+            self.emit_formatted_property(prop);
+            return;
+        }
+        if let ExprKind::Spread(arg) = &prop.value.kind {
+            self.emit("...");
+            self.emit_expr_with_prec(arg, 2);
+            return;
+        }
         if prop.shorthand {
             if let PropertyKey::Ident(name) = &prop.key {
                 self.emit(name);
@@ -1594,13 +2341,8 @@ impl<'a> Codegen<'a> {
             _ => {}
         }
 
-        if prop.computed {
-            self.emit("[");
-        }
+        // Computed keys carry their own brackets
         self.emit_property_key(&prop.key);
-        if prop.computed {
-            self.emit("]");
-        }
 
         match prop.kind {
             PropertyKind::Method => {
@@ -1652,6 +2394,46 @@ impl<'a> Codegen<'a> {
 // Helper Functions
 // =============================================================================
 
+/// `experimentalDecorators` runtime, matching TypeScript's `__decorate`.
+const LEGACY_DECORATE_HELPER: &str = r#"var __decorate = function (decorators, target, key, desc) {
+  var c = arguments.length, r = c < 3 ? target : desc === null ? desc = Object.getOwnPropertyDescriptor(target, key) : desc, d;
+  if (typeof Reflect === "object" && typeof Reflect.decorate === "function") r = Reflect.decorate(decorators, target, key, desc);
+  else for (var i = decorators.length - 1; i >= 0; i--) if (d = decorators[i]) r = (c < 3 ? d(r) : c > 3 ? d(target, key, r) : d(target, key)) || r;
+  return c > 3 && r && Object.defineProperty(target, key, r), r;
+};
+"#;
+
+/// Standard decorator runtime.
+///
+/// `__decorateElement` applies decorators innermost-first and installs the
+/// result; for fields it returns a function that runs the initializers.
+/// `addInitializer` callbacks are collected in `inits` and run by
+/// `__runInitializers` (per instance for instance members, once for the
+/// class otherwise).
+const TC39_DECORATE_HELPERS: &str = r#"var __decorateElement = function (target, kind, name, decorators, isStatic, inits) {
+  var isClass = kind === "class", isField = kind === "field";
+  var home = isClass || isField ? null : isStatic ? target : target.prototype;
+  var key = kind === "getter" ? "get" : kind === "setter" ? "set" : "value";
+  var desc = home ? Object.getOwnPropertyDescriptor(home, name) : null;
+  var value = isClass ? target : desc ? desc[key] : void 0;
+  var fieldInits = [];
+  for (var i = decorators.length - 1; i >= 0; i--) {
+    var context = { kind: kind, name: isClass ? target.name : name, addInitializer: function (fn) { inits.push(fn); } };
+    if (!isClass) { context.static = isStatic; context.private = false; }
+    var result = decorators[i](value, context);
+    if (isField) { if (typeof result === "function") fieldInits.unshift(result); }
+    else if (result !== void 0) value = result;
+  }
+  if (isClass) return value;
+  if (isField) return function (self, init) { for (var j = 0; j < fieldInits.length; j++) init = fieldInits[j].call(self, init); return init; };
+  desc[key] = value;
+  Object.defineProperty(home, name, desc);
+};
+var __runInitializers = function (self, inits) {
+  for (var i = 0; i < inits.length; i++) inits[i].call(self);
+};
+"#;
+
 fn format_number(n: f64) -> String {
     if n.fract() == 0.0 && n.abs() < 1e15 {
         format!("{:.0}", n)
@@ -1729,6 +2511,164 @@ fn binary_op_info(op: BinaryOp) -> (u8, &'static str) {
     }
 }
 
+/// What a member decorator applies to.
+struct MemberDecoration<'a> {
+    /// `field`, `method`, `getter` or `setter` (the TC39 context kind).
+    kind: &'static str,
+    key: &'a PropertyKey,
+    is_static: bool,
+}
+
+/// The decoration target of a member, if it has supported decorators.
+fn member_decoration(member: &ClassMember) -> Option<MemberDecoration<'_>> {
+    if member.decorators.is_empty() {
+        return None;
+    }
+    let (kind, key, is_static) = match &member.kind {
+        ClassMemberKind::Method {
+            key,
+            kind,
+            is_static,
+            ..
+        } => {
+            let kind = match kind {
+                MethodKind::Method => "method",
+                MethodKind::Get => "getter",
+                MethodKind::Set => "setter",
+                MethodKind::Constructor => return None,
+            };
+            (kind, key, *is_static)
+        }
+        ClassMemberKind::Property { key, is_static, .. } => ("field", key, *is_static),
+        ClassMemberKind::StaticBlock(_) | ClassMemberKind::Empty => return None,
+    };
+    if matches!(key, PropertyKey::Ident(name) if name.starts_with('#')) {
+        return None;
+    }
+    Some(MemberDecoration {
+        kind,
+        key,
+        is_static,
+    })
+}
+
+fn decorated_members(class: &Class) -> impl Iterator<Item = (&ClassMember, MemberDecoration<'_>)> {
+    class
+        .body
+        .iter()
+        .filter_map(|member| member_decoration(member).map(|decoration| (member, decoration)))
+}
+
+/// Whether a class needs decorator lowering.
+/// Whether an expression statement would start with `function`, `class` or
+/// `{` when printed.
+fn starts_with_declaration(expr: &Expr) -> bool {
+    match &expr.kind {
+        ExprKind::Function(_) | ExprKind::Class(_) | ExprKind::Object(_) => true,
+        ExprKind::Call { callee: inner, .. }
+        | ExprKind::OptionalCall { callee: inner, .. }
+        | ExprKind::Member { object: inner, .. }
+        | ExprKind::OptionalMember { object: inner, .. }
+        | ExprKind::TaggedTemplate { tag: inner, .. }
+        | ExprKind::Binary { left: inner, .. }
+        | ExprKind::Assign { left: inner, .. }
+        | ExprKind::Conditional { test: inner, .. }
+        | ExprKind::Update {
+            prefix: false,
+            arg: inner,
+            ..
+        } => starts_with_declaration(inner),
+        ExprKind::Sequence(exprs) => exprs.first().is_some_and(starts_with_declaration),
+        #[cfg(feature = "typescript")]
+        ExprKind::TsAs { expr: inner, .. }
+        | ExprKind::TsSatisfies { expr: inner, .. }
+        | ExprKind::TsNonNull(inner) => starts_with_declaration(inner),
+        _ => false,
+    }
+}
+
+fn has_decorators(class: &Class) -> bool {
+    !class.decorators.is_empty() || decorated_members(class).next().is_some()
+}
+
+fn is_spread_property(prop: &Property) -> bool {
+    matches!(prop.value.kind, ExprKind::Spread(_))
+}
+
+/// Start of a (partially lowered) optional chain.
+#[derive(Clone, Copy)]
+enum ChainHead<'e> {
+    /// The original base expression.
+    Expr(&'e Expr),
+    /// `__tmp` followed by one link.
+    Temp(&'e ChainLink<'e>),
+    /// `__tmp.call(__ctx, ...args)`.
+    BoundCall(&'e [Expr]),
+}
+
+/// One member access or call in a chain.
+struct ChainLink<'e> {
+    kind: ChainLinkKind<'e>,
+    optional: bool,
+}
+
+#[derive(Clone, Copy)]
+enum ChainLinkKind<'e> {
+    Member { property: &'e Expr, computed: bool },
+    Call(&'e [Expr]),
+}
+
+/// Split a member/call chain into its base and links, innermost first.
+fn flatten_chain(mut expr: &Expr) -> (&Expr, Vec<ChainLink<'_>>) {
+    let mut links = Vec::new();
+    loop {
+        let (next, kind, optional) = match &expr.kind {
+            ExprKind::Member {
+                object,
+                property,
+                computed,
+            } => (
+                object,
+                ChainLinkKind::Member {
+                    property,
+                    computed: *computed,
+                },
+                false,
+            ),
+            ExprKind::OptionalMember {
+                object,
+                property,
+                computed,
+            } => (
+                object,
+                ChainLinkKind::Member {
+                    property,
+                    computed: *computed,
+                },
+                true,
+            ),
+            ExprKind::Call { callee, args } => (callee, ChainLinkKind::Call(args), false),
+            ExprKind::OptionalCall { callee, args } => (callee, ChainLinkKind::Call(args), true),
+            // `a?.b!.c` is still one chain
+            #[cfg(feature = "typescript")]
+            ExprKind::TsNonNull(inner) => {
+                expr = inner;
+                continue;
+            }
+            _ => break,
+        };
+        links.push(ChainLink { kind, optional });
+        expr = next;
+    }
+    links.reverse();
+    (expr, links)
+}
+
+/// Whether a member/call chain contains `?.`.
+fn has_optional_link(expr: &Expr) -> bool {
+    flatten_chain(expr).1.iter().any(|link| link.optional)
+}
+
 fn is_right_associative(op: BinaryOp) -> bool {
     matches!(op, BinaryOp::Pow)
 }
@@ -1779,6 +2719,12 @@ mod tests {
         assert!(output.contains("return a + b"));
     }
 
+    #[test]
+    fn test_new_expression() {
+        assert!(roundtrip("new Foo().bar();").contains("new Foo().bar()"));
+        assert!(roundtrip("new Foo.Bar;").contains("new Foo.Bar()"));
+    }
+
     #[test]
     fn test_minify() {
         let ast = Parser::new("let x = 1;\nlet y = 2;", ParserOptions::default())
@@ -1795,6 +2741,55 @@ mod tests {
         assert!(!output.contains('\n'));
     }
 
+    #[test]
+    fn test_source_map_statements() {
+        let source = "let a = 1;\n\nfunction f() {\n  return a;\n}\n";
+        let ast = Parser::new(source, ParserOptions::default())
+            .parse()
+            .unwrap();
+        let (output, mappings) = Codegen::new(
+            &ast,
+            CodegenOptions {
+                source_map: true,
+                ..Default::default()
+            },
+        )
+        .generate_with_source_map();
+
+        let positions: Vec<(u32, u32, u32)> = mappings
+            .iter()
+            .map(|m| (m.gen_line, m.gen_col, m.orig_offset))
+            .collect();
+        let lines: Vec<&str> = output.lines().collect();
+        assert_eq!(lines[positions[1].0 as usize].trim(), "function f() {");
+        assert_eq!(positions[0], (0, 0, 0));
+        assert_eq!(&source[positions[1].2 as usize..][..8], "function");
+        // `return a;` is indented in the output and the source
+        assert_eq!(positions[2].1, 2);
+        assert_eq!(&source[positions[2].2 as usize..][..6], "return");
+        assert_eq!(positions.len(), 3);
+    }
+
+    #[test]
+    fn test_minify_keyword_operators() {
+        let ast = Parser::new(
+            "x = e instanceof Error;\ny = k in o;",
+            ParserOptions::default(),
+        )
+        .parse()
+        .unwrap();
+        let output = Codegen::new(
+            &ast,
+            CodegenOptions {
+                minify: true,
+                ..Default::default()
+            },
+        )
+        .generate();
+        assert!(output.contains("x=e instanceof Error"));
+        assert!(output.contains("y=k in o"));
+    }
+
     #[cfg(feature = "typescript")]
     fn ts_strip(source: &str) -> String {
         let opts = ParserOptions {
@@ -1914,4 +2909,130 @@ mod tests {
         assert!(out.contains("bar"), "runtime specifier preserved");
         assert!(!out.contains("Foo"), "type specifier stripped");
     }
+
+    fn generate_with(source: &str, options: CodegenOptions) -> String {
+        let opts = ParserOptions {
+            module: true,
+            ..Default::default()
+        };
+        let ast = Parser::new(source, opts).parse().unwrap();
+        Codegen::new(&ast, options).generate()
+    }
+
+    fn lowered(source: &str, target: Target) -> String {
+        generate_with(
+            source,
+            CodegenOptions {
+                target,
+                ..Default::default()
+            },
+        )
+    }
+
+    #[test]
+    fn test_computed_keys_and_object_spread() {
+        let out = roundtrip("const o = {...a, [k]: 1}; class A { [m]() {} }");
+        assert!(out.contains("{...a, [k]: 1}"));
+        assert!(out.contains("  [m]() {}"));
+    }
+
+    #[test]
+    fn test_target_lowering() {
+        let source =
+            "x = a ** b; y = a ?? b; z = a?.b.c; w.n ||= 1; o = {...p, q}; try {} catch {}";
+
+        let out = lowered(source, Target::ESNext);
+        assert!(out.contains("a ** b"));
+        assert!(out.contains("a?.b.c"));
+        assert!(!out.contains("__tmp"));
+
+        let out = lowered(source, Target::ES2020);
+        assert!(out.contains("a ?? b"));
+        assert!(out.contains("w.n || (w.n = 1)"));
+
+        let out = lowered(source, Target::ES2015);
+        assert!(out.starts_with("var __tmp, __ctx;\n"));
+        assert!(out.contains("x = Math.pow(a, b)"));
+        assert!(out.contains("y = (__tmp = a) != null ? __tmp : b"));
+        assert!(out.contains("z = ((__tmp = a) == null ? void 0 : __tmp.b.c)"));
+        assert!(out.contains("o = Object.assign({}, p, {q})"));
+        assert!(out.contains("catch (_unused)"));
+    }
+
+    #[test]
+    fn test_optional_call_keeps_this() {
+        let out = lowered("a.b?.(1);", Target::ES2019);
+        assert!(out.contains("((__tmp = (__ctx = a).b) == null ? void 0 : __tmp.call(__ctx, 1))"));
+    }
+
+    #[test]
+    fn test_helpers_follow_directives() {
+        let out = lowered("\"use strict\";\nx = a?.b;", Target::ES2015);
+        assert!(out.starts_with("\"use strict\";\nvar __tmp, __ctx;\n"));
+    }
+
+    #[test]
+    fn test_legacy_decorators() {
+        let out = generate_with(
+            "@sealed export class C { @log m() {} @field static x = 1; }",
+            CodegenOptions {
+                decorators: DecoratorMode::Legacy,
+                ..Default::default()
+            },
+        );
+        assert!(out.starts_with("var __decorate = "));
+        assert!(out.contains("export let C = (() => {"));
+        assert!(out.contains("let C = class C {"));
+        assert!(out.contains("__decorate([log], C.prototype, \"m\", null);"));
+        assert!(out.contains("__decorate([field], C, \"x\", void 0);"));
+        assert!(out.contains("C = __decorate([sealed], C);"));
+        assert!(!out.contains('@'));
+    }
+
+    #[test]
+    fn test_tc39_decorators() {
+        let out = generate_with(
+            "export default @register class C { @observe x = 1; @bound m() {} }",
+            CodegenOptions::default(),
+        );
+        assert!(out.contains("var __decorateElement = "));
+        assert!(out.contains(
+            "__init0 = __decorateElement(null, \"field\", \"x\", [observe], false, __inits);"
+        ));
+        assert!(out.contains("#__runInits = __runInitializers(this, __inits);"));
+        assert!(out.contains("x = __init0(this, 1);"));
+        assert!(out.contains("__decorateElement(C, \"method\", \"m\", [bound], false, __inits);"));
+        assert!(out.contains(
+            "C = __decorateElement(C, \"class\", void 0, [register], false, __staticInits);"
+        ));
+        assert!(out.contains("export { C as default };"));
+    }
+
+    #[test]
+    fn test_pure_annotation_and_statement_parens() {
+        assert_eq!(
+            roundtrip("x = /* @__PURE__ */ f();"),
+            "x = /* @__PURE__ */ f();\n"
+        );
+        assert_eq!(
+            roundtrip("/*#__PURE__*/ (function() {})();"),
+            "(/* @__PURE__ */ function() {}());\n"
+        );
+        assert_eq!(roundtrip("({ a } = b);"), "({a} = b);\n");
+
+        let ast = Parser::new(
+            "x = a / /re\\//g / /*@__PURE__*/ f();",
+            ParserOptions::default(),
+        )
+        .parse()
+        .unwrap();
+        let options = CodegenOptions {
+            minify: true,
+            ..Default::default()
+        };
+        assert_eq!(
+            Codegen::new(&ast, options).generate(),
+            "x=a/ /re\\//g/ /*@__PURE__*/f()"
+        );
+    }
 }
@@ -0,0 +1,1582 @@
+//! Format mode: print a module back as source in one canonical layout.
+//!
+//! The code generator runs with lowering disabled and with literals,
+//! templates, JSX and TypeScript types taken verbatim from the source, so
+//! only whitespace, quotes, parentheses, semicolons and trailing commas
+//! change. Comments are placed between statements, class members and the
+//! items of bracketed lists; a comment anywhere else refuses the file rather
+//! than moving or dropping it.
+
+use super::{escape_string, Codegen};
+use crate::ast::*;
+use crate::span::Span;
+
+/// Refusal reason for a comment that can't be placed.
+const MISPLACED_COMMENT: &str = "comment in a position the formatter can't preserve";
+/// Refusal reason for syntax the AST doesn't fully capture.
+const UNSUPPORTED_SYNTAX: &str = "syntax the formatter can't preserve";
+
+/// Class member modifiers, printed in source order.
+const MEMBER_MODIFIERS: &[&str] = &[
+    "public",
+    "private",
+    "protected",
+    "declare",
+    "static",
+    "abstract",
+    "override",
+    "readonly",
+    "accessor",
+];
+
+impl<'a> Codegen<'a> {
+    /// Print the program in format mode.
+    ///
+    /// Fails with the location and reason when the source can't be printed
+    /// without losing a comment or syntax.
+    pub(crate) fn generate_formatted(mut self) -> Result<String, (Span, &'static str)> {
+        self.format = true;
+        let ast = self.ast;
+        let stmts = non_empty(&ast.stmts);
+        let end = ast.source.len() as u32;
+        self.emit_list(
+            &stmts,
+            Some(end),
+            "",
+            false,
+            |stmt| stmt.span,
+            |this, stmt| this.emit_stmt(stmt),
+        );
+        if let Some(refused) = self.refused {
+            return Err(refused);
+        }
+        let body = self.output.trim_start_matches('\n').trim_end();
+        if body.is_empty() {
+            return Ok(String::new());
+        }
+        Ok(format!("{body}\n"))
+    }
+
+    // =========================================================================
+    // Source Helpers
+    // =========================================================================
+
+    fn source_slice(&self, start: u32, end: u32) -> &'a str {
+        let ast = self.ast;
+        &ast.source[start as usize..end as usize]
+    }
+
+    fn byte_at(&self, pos: u32) -> Option<u8> {
+        self.ast.source.as_bytes().get(pos as usize).copied()
+    }
+
+    /// Offset just past the last token before `pos`, skipping whitespace and
+    /// comments (node spans often extend to the next token).
+    fn content_end(&self, mut pos: u32) -> u32 {
+        let ast = self.ast;
+        let bytes = ast.source.as_bytes();
+        loop {
+            while pos > 0 && bytes[pos as usize - 1].is_ascii_whitespace() {
+                pos -= 1;
+            }
+            let i = ast.comments.partition_point(|c| c.span.end < pos);
+            match ast.comments.get(i) {
+                Some(comment) if comment.span.end == pos => pos = comment.span.start,
+                _ => return pos,
+            }
+        }
+    }
+
+    /// Offset of the first token at or after `pos`.
+    fn next_token(&self, mut pos: u32) -> u32 {
+        let ast = self.ast;
+        let bytes = ast.source.as_bytes();
+        loop {
+            while (pos as usize) < bytes.len() && bytes[pos as usize].is_ascii_whitespace() {
+                pos += 1;
+            }
+            let i = ast.comments.partition_point(|c| c.span.start < pos);
+            match ast.comments.get(i) {
+                Some(comment) if comment.span.start == pos => pos = comment.span.end,
+                _ => return pos,
+            }
+        }
+    }
+
+    /// Offset of the `}` ending `span`, if it ends with one.
+    fn closing_brace(&self, span: Span) -> Option<u32> {
+        let end = self.content_end(span.end);
+        (end > 0 && self.byte_at(end - 1) == Some(b'}')).then(|| end - 1)
+    }
+
+    fn has_comment_between(&self, start: u32, end: u32) -> bool {
+        let comments = &self.ast.comments;
+        let i = comments.partition_point(|c| c.span.start < start);
+        comments.get(i).is_some_and(|c| c.span.start < end)
+    }
+
+    /// The source text of `span` without trailing whitespace and comments.
+    fn node_text(&self, span: Span) -> &'a str {
+        let end = self.content_end(span.end).max(span.start);
+        self.source_slice(span.start, end)
+    }
+
+    fn refuse(&mut self, span: Span, reason: &'static str) {
+        if self.refused.is_none() {
+            self.refused = Some((span, reason));
+        }
+    }
+
+    // =========================================================================
+    // Comments and Lists
+    // =========================================================================
+
+    /// Start a new line, keeping one blank line where the source had any.
+    fn emit_line_break(&mut self, pos: u32) {
+        let gap = self.source_slice(self.last_pos.min(pos), pos);
+        if !self.at_list_start && gap.matches('\n').count() >= 2 {
+            self.output.push('\n');
+        }
+        self.at_list_start = false;
+        self.emit_newline();
+    }
+
+    /// Emit the comments before `pos` on their own lines. Returns whether the
+    /// last one was a block comment followed by code on the same line.
+    fn emit_leading_comments(&mut self, pos: u32) -> bool {
+        let ast = self.ast;
+        let mut inline = false;
+        while let Some(comment) = ast
+            .comments
+            .get(self.next_comment)
+            .filter(|c| c.span.start < pos)
+        {
+            self.next_comment += 1;
+            if comment.span.start < self.last_pos {
+                self.refuse(comment.span, MISPLACED_COMMENT);
+                continue;
+            }
+            if !inline {
+                self.emit_line_break(comment.span.start);
+            }
+            self.emit_comment(comment);
+            self.last_pos = comment.span.end;
+            let next = ast
+                .comments
+                .get(self.next_comment)
+                .map_or(pos, |c| c.span.start.min(pos));
+            inline = comment.kind == CommentKind::Block
+                && !self.source_slice(comment.span.end, next).contains('\n');
+            if inline {
+                self.output.push(' ');
+            }
+        }
+        inline
+    }
+
+    /// Refuse comments inside the node just printed, then attach comments
+    /// that follow it on the same line.
+    fn emit_trailing_comments(&mut self) {
+        let ast = self.ast;
+        while let Some(comment) = ast
+            .comments
+            .get(self.next_comment)
+            .filter(|c| c.span.start < self.last_pos)
+        {
+            self.next_comment += 1;
+            self.refuse(comment.span, MISPLACED_COMMENT);
+        }
+        while let Some(comment) = ast.comments.get(self.next_comment) {
+            let gap = self.source_slice(self.last_pos, comment.span.start);
+            if !gap.chars().all(|c| matches!(c, ' ' | '\t' | ',' | ';')) {
+                break;
+            }
+            self.next_comment += 1;
+            self.output.push(' ');
+            self.emit_comment(comment);
+            self.last_pos = comment.span.end;
+        }
+    }
+
+    /// Emit a comment, re-indenting the ` *` lines of doc comments.
+    fn emit_comment(&mut self, comment: &Comment) {
+        let text = self.source_slice(comment.span.start, comment.span.end);
+        let mut lines = text.lines();
+        let is_doc = comment.kind == CommentKind::Block
+            && text.contains('\n')
+            && lines
+                .clone()
+                .skip(1)
+                .all(|l| l.trim_start().starts_with('*'));
+        if !is_doc {
+            self.output.push_str(text.trim_end());
+            return;
+        }
+        self.output
+            .push_str(lines.next().unwrap_or_default().trim_end());
+        for line in lines {
+            self.emit_newline();
+            self.output.push(' ');
+            self.output.push_str(line.trim());
+        }
+    }
+
+    /// Skip the comments inside a node printed verbatim.
+    fn skip_comments_within(&mut self, start: u32, end: u32) {
+        let ast = self.ast;
+        while let Some(comment) = ast
+            .comments
+            .get(self.next_comment)
+            .filter(|c| c.span.start < end)
+        {
+            self.next_comment += 1;
+            if comment.span.start < start {
+                self.refuse(comment.span, MISPLACED_COMMENT);
+            }
+        }
+    }
+
+    /// Emit `items` one per line with their comments. The caller emits the
+    /// brackets and indentation; `close` is the offset of the closing
+    /// bracket, before which dangling comments are emitted.
+    fn emit_list<T>(
+        &mut self,
+        items: &[T],
+        close: Option<u32>,
+        sep: &str,
+        trailing_sep: bool,
+        span_of: impl Fn(&T) -> Span,
+        mut emit_item: impl FnMut(&mut Self, &T),
+    ) {
+        if let Some(pos) = items.first().map(|item| span_of(item).start).or(close) {
+            self.last_pos = self.last_pos.max(self.content_end(pos));
+        }
+        self.at_list_start = true;
+        for (i, item) in items.iter().enumerate() {
+            let span = span_of(item);
+            if !self.emit_leading_comments(span.start) {
+                self.emit_line_break(span.start);
+            }
+            emit_item(self, item);
+            if trailing_sep || i + 1 < items.len() {
+                self.emit(sep);
+            }
+            self.last_pos = self.last_pos.max(self.content_end(span.end));
+            self.emit_trailing_comments();
+        }
+        if let Some(close) = close {
+            if self.emit_leading_comments(close) {
+                self.output.pop();
+            }
+            self.last_pos = self.last_pos.max(close);
+        }
+        self.at_list_start = false;
+    }
+
+    /// Emit `open items close` on one line, or one item per line with a
+    /// trailing comma when the source broke the line after `open` or has
+    /// comments between the items. Objects (`pad`) also expand when an item
+    /// spans lines. `close_hint` locates the closing bracket of an empty
+    /// list.
+    #[allow(clippy::too_many_arguments)]
+    fn emit_bracketed<T>(
+        &mut self,
+        (open, close): (&str, &str),
+        pad: bool,
+        items: &[T],
+        close_hint: Option<u32>,
+        trailing_comma: bool,
+        span_of: impl Fn(&T) -> Span,
+        mut emit_item: impl FnMut(&mut Self, &T),
+    ) {
+        let close_pos = match items.last() {
+            Some(last) => {
+                let mut pos = self.next_token(self.content_end(span_of(last).end));
+                if self.byte_at(pos) == Some(b',') {
+                    pos = self.next_token(pos + 1);
+                }
+                Some(pos)
+            }
+            None => close_hint,
+        }
+        .filter(|&pos| self.byte_at(pos) == close.bytes().next());
+        let open_pos = match items.first() {
+            Some(first) => self.content_end(span_of(first).start).checked_sub(1),
+            None => close_pos.and_then(|pos| self.content_end(pos).checked_sub(1)),
+        }
+        .filter(|&pos| self.byte_at(pos) == open.bytes().last());
+        let expanded = match (open_pos, close_pos) {
+            (Some(open_pos), Some(close_pos)) => {
+                let mut gaps = Vec::with_capacity(items.len() + 1);
+                let mut from = open_pos + 1;
+                for item in items {
+                    let span = span_of(item);
+                    gaps.push((from, span.start));
+                    from = self.content_end(span.end);
+                }
+                gaps.push((from, close_pos));
+                items.first().is_some_and(|first| {
+                    self.source_slice(open_pos, span_of(first).start)
+                        .contains('\n')
+                }) || gaps
+                    .iter()
+                    .any(|&(start, end)| self.has_comment_between(start, end))
+            }
+            _ => false,
+        };
+
+        self.emit(open);
+        if !expanded {
+            let snapshot = self.snapshot();
+            if pad && !items.is_empty() {
+                self.emit(" ");
+            }
+            for (i, item) in items.iter().enumerate() {
+                if i > 0 {
+                    self.emit(", ");
+                }
+                emit_item(self, item);
+            }
+            if !(pad && self.output[snapshot.output_len..].contains('\n')) {
+                if pad && !items.is_empty() {
+                    self.emit(" ");
+                }
+                self.emit(close);
+                return;
+            }
+            self.restore(snapshot);
+        }
+        self.indent();
+        self.emit_list(items, close_pos, ",", trailing_comma, span_of, emit_item);
+        self.dedent();
+        self.emit_newline();
+        self.emit(close);
+    }
+
+    fn snapshot(&self) -> Snapshot {
+        Snapshot {
+            output_len: self.output.len(),
+            indent_level: self.indent_level,
+            next_comment: self.next_comment,
+            last_pos: self.last_pos,
+            at_list_start: self.at_list_start,
+            refused: self.refused,
+        }
+    }
+
+    fn restore(&mut self, snapshot: Snapshot) {
+        self.output.truncate(snapshot.output_len);
+        self.indent_level = snapshot.indent_level;
+        self.next_comment = snapshot.next_comment;
+        self.last_pos = snapshot.last_pos;
+        self.at_list_start = snapshot.at_list_start;
+        self.refused = snapshot.refused;
+    }
+
+    /// Emit source text as written, re-basing the indentation of later lines
+    /// onto the current indentation (unless it holds a template literal,
+    /// whose whitespace is significant).
+    fn emit_verbatim(&mut self, span: Span) {
+        let text = self.node_text(span);
+        self.skip_comments_within(span.start, span.start + text.len() as u32);
+        if !text.contains('\n') || text.contains('`') {
+            self.emit(text);
+            return;
+        }
+        let source = self.source_slice(0, span.start);
+        let line_start = source.rfind('\n').map_or(0, |i| i + 1);
+        let line = &source[line_start..];
+        let base = &line[..line.len() - line.trim_start().len()];
+        let mut lines = text.split('\n');
+        self.emit(lines.next().unwrap_or_default());
+        for line in lines {
+            if line.trim().is_empty() {
+                self.output.push('\n');
+                continue;
+            }
+            match line.strip_prefix(base) {
+                Some(rest) => {
+                    self.emit_newline();
+                    self.output.push_str(rest);
+                }
+                None => {
+                    self.output.push('\n');
+                    self.output.push_str(line);
+                }
+            }
+        }
+    }
+
+    // =========================================================================
+    // Statements
+    // =========================================================================
+
+    /// Format-mode statement printing. Returns false to fall back to the
+    /// shared code generator.
+    pub(super) fn emit_formatted_stmt(&mut self, stmt: &Stmt) -> bool {
+        match &stmt.kind {
+            StmtKind::Block(stmts) => {
+                self.emit_formatted_block(stmts, self.closing_brace(stmt.span));
+            }
+            StmtKind::If {
+                test,
+                consequent,
+                alternate,
+            } => {
+                self.emit("if (");
+                self.emit_expr(test);
+                self.emit(") ");
+                self.emit_stmt(consequent);
+                if let Some(alternate) = alternate {
+                    if matches!(consequent.kind, StmtKind::Block(_)) {
+                        self.emit(" ");
+                    } else {
+                        self.emit_newline();
+                    }
+                    self.emit("else ");
+                    self.emit_stmt(alternate);
+                }
+            }
+            StmtKind::Switch {
+                discriminant,
+                cases,
+            } => {
+                self.emit("switch (");
+                self.emit_expr(discriminant);
+                self.emit(") {");
+                self.indent();
+                self.emit_list(
+                    cases,
+                    self.closing_brace(stmt.span),
+                    "",
+                    false,
+                    |case| case.span,
+                    Self::emit_switch_case,
+                );
+                self.dedent();
+                self.emit_newline();
+                self.emit("}");
+            }
+            StmtKind::Try {
+                block,
+                handler,
+                finalizer,
+            } => {
+                self.emit("try ");
+                let block_close = handler.as_ref().and_then(|handler| {
+                    let catch_end = self.content_end(handler.span.start);
+                    self.closing_brace(Span::new(stmt.span.start, catch_end.saturating_sub(5)))
+                });
+                self.emit_formatted_block(block, block_close);
+                if let Some(handler) = handler {
+                    self.emit(" catch ");
+                    if let Some(param) = &handler.param {
+                        self.emit("(");
+                        self.emit_binding(param);
+                        self.emit(") ");
+                    }
+                    self.emit_formatted_block(&handler.body, self.closing_brace(handler.span));
+                }
+                if let Some(finalizer) = finalizer {
+                    self.emit(" finally ");
+                    self.emit_formatted_block(finalizer, self.closing_brace(stmt.span));
+                }
+            }
+            StmtKind::Expr(expr) => {
+                if matches!(&expr.kind, ExprKind::Assign { left, .. } if matches!(left.kind, ExprKind::Object(_)))
+                {
+                    self.emit("(");
+                    self.emit_expr(expr);
+                    self.emit(")");
+                } else {
+                    self.wrap_leftmost = true;
+                    self.emit_expr(expr);
+                }
+                self.emit_semicolon();
+            }
+            StmtKind::Class(class) => self.emit_formatted_class(class, true),
+            #[cfg(feature = "typescript")]
+            StmtKind::TsTypeAlias(_)
+            | StmtKind::TsInterface(_)
+            | StmtKind::TsEnum(_)
+            | StmtKind::TsDeclare(_) => self.emit_verbatim(stmt.span),
+            #[cfg(feature = "typescript")]
+            StmtKind::TsNamespace(ns) => {
+                let close = self.closing_brace(stmt.span);
+                let open = match ns.body.first() {
+                    Some(first) => self.content_end(first.span.start),
+                    None => close.map_or(stmt.span.start, |close| self.content_end(close)),
+                }
+                .saturating_sub(1)
+                .max(stmt.span.start);
+                let header = self.source_slice(stmt.span.start, open);
+                self.emit(&header.split_whitespace().collect::<Vec<_>>().join(" "));
+                self.emit(" ");
+                self.emit_formatted_block(&ns.body, close);
+            }
+            _ => return false,
+        }
+        true
+    }
+
+    fn emit_switch_case(&mut self, case: &SwitchCase) {
+        match &case.test {
+            Some(test) => {
+                self.emit("case ");
+                self.emit_expr(test);
+                self.emit(":");
+            }
+            None => self.emit("default:"),
+        }
+        let stmts = non_empty(&case.consequent);
+        if let [Stmt {
+            kind: StmtKind::Block(body),
+            span,
+        }] = stmts.as_slice()
+        {
+            self.emit(" ");
+            self.emit_formatted_block(body, self.closing_brace(*span));
+            return;
+        }
+        self.indent();
+        self.emit_list(
+            &stmts,
+            None,
+            "",
+            false,
+            |stmt| stmt.span,
+            |this, stmt| this.emit_stmt(stmt),
+        );
+        self.dedent();
+    }
+
+    /// Emit a `{ ... }` statement list. `close` locates the `}` of an empty
+    /// block so comments inside it are kept.
+    pub(super) fn emit_formatted_block(&mut self, stmts: &[Stmt], close: Option<u32>) {
+        let stmts = non_empty(stmts);
+        let close = match stmts.last() {
+            Some(last) => {
+                let pos = self.next_token(self.content_end(last.span.end));
+                (self.byte_at(pos) == Some(b'}')).then_some(pos)
+            }
+            None => close,
+        };
+        self.emit("{");
+        let has_comments =
+            close.is_some_and(|close| self.has_comment_between(self.content_end(close), close));
+        if stmts.is_empty() && !has_comments {
+            self.emit("}");
+            return;
+        }
+        self.indent();
+        self.emit_list(
+            &stmts,
+            close,
+            "",
+            false,
+            |stmt| stmt.span,
+            |this, stmt| this.emit_stmt(stmt),
+        );
+        self.dedent();
+        self.emit_newline();
+        self.emit("}");
+    }
+
+    // =========================================================================
+    // Functions and Bindings
+    // =========================================================================
+
+    pub(super) fn emit_formatted_binding(&mut self, binding: &Binding, optional: bool) {
+        match &binding.kind {
+            BindingKind::Ident { name, .. } => {
+                self.emit(name);
+                let end = self.content_end(binding.span.end);
+                if self.byte_at(end) == Some(b'!')
+                    && self.byte_at(self.next_token(end + 1)) == Some(b':')
+                {
+                    self.emit("!");
+                }
+            }
+            BindingKind::Array { elements, .. } => {
+                self.emit("[");
+                for (i, element) in elements.iter().enumerate() {
+                    if i > 0 {
+                        self.emit(", ");
+                    }
+                    if let Some(element) = element {
+                        if element.rest {
+                            self.emit("...");
+                        }
+                        self.emit_binding(&element.binding);
+                        if let Some(default) = &element.default {
+                            self.emit(" = ");
+                            self.emit_expr_with_prec(default, 2);
+                        }
+                    }
+                }
+                if matches!(elements.last(), Some(None)) {
+                    self.emit(",");
+                }
+                self.emit("]");
+            }
+            BindingKind::Object { properties, .. } => {
+                self.emit("{");
+                for (i, prop) in properties.iter().enumerate() {
+                    self.emit(if i > 0 { ", " } else { " " });
+                    if prop.rest {
+                        self.emit("...");
+                    } else if !prop.shorthand {
+                        self.emit_property_key(&prop.key);
+                        self.emit(": ");
+                    }
+                    self.emit_binding(&prop.value);
+                    if let Some(default) = &prop.default {
+                        self.emit(" = ");
+                        self.emit_expr_with_prec(default, 2);
+                    }
+                }
+                if !properties.is_empty() {
+                    self.emit(" ");
+                }
+                self.emit("}");
+            }
+        }
+        if optional {
+            self.emit("?");
+        }
+        if let Some(ty) = binding_type(binding) {
+            self.emit(": ");
+            self.emit_verbatim(ty);
+        }
+    }
+
+    pub(super) fn emit_formatted_params(&mut self, params: &[Param]) {
+        let trailing_comma = !params.last().is_some_and(|param| param.rest);
+        self.emit_bracketed(
+            ("(", ")"),
+            false,
+            params,
+            None,
+            trailing_comma,
+            |param| param.span,
+            Self::emit_formatted_param,
+        );
+    }
+
+    fn emit_formatted_param(&mut self, param: &Param) {
+        // Decorators and parameter-property modifiers aren't in the AST.
+        if param.span.start < param.binding.span.start {
+            let prefix = self
+                .source_slice(param.span.start, param.binding.span.start)
+                .trim();
+            let prefix = if param.rest {
+                prefix.strip_suffix("...").unwrap_or(prefix).trim_end()
+            } else {
+                prefix
+            };
+            if !prefix.is_empty() {
+                self.emit(prefix);
+                self.emit(" ");
+            }
+        }
+        if param.rest {
+            self.emit("...");
+        }
+        self.emit_formatted_binding(&param.binding, param_optional(param));
+        if let Some(default) = &param.default {
+            self.emit(" = ");
+            self.emit_expr_with_prec(default, 2);
+        }
+    }
+
+    pub(super) fn emit_formatted_function(&mut self, func: &Function, _is_declaration: bool) {
+        if func.is_async {
+            self.emit("async ");
+        }
+        self.emit("function");
+        if func.is_generator {
+            self.emit("*");
+        }
+        self.emit(" ");
+        if let Some(name) = &func.name {
+            self.emit(name);
+        }
+        self.emit_signature(func);
+        self.emit_function_body(func);
+    }
+
+    /// Type parameters, parameters and return type.
+    fn emit_signature(&mut self, func: &Function) {
+        let (type_params, return_type) = function_types(func);
+        self.emit_type_params(&type_params);
+        self.emit_params(&func.params);
+        if let Some(ty) = return_type {
+            self.emit(": ");
+            self.emit_verbatim(ty);
+        }
+    }
+
+    /// ` { ... }`, or `;` for overloads and abstract methods.
+    fn emit_function_body(&mut self, func: &Function) {
+        let close = self.closing_brace(func.span);
+        let has_body = !func.body.is_empty()
+            || close.is_some_and(|close| {
+                self.byte_at(self.content_end(close).saturating_sub(1)) == Some(b'{')
+            });
+        if has_body {
+            self.emit(" ");
+            self.emit_formatted_block(&func.body, close);
+        } else {
+            self.emit(";");
+        }
+    }
+
+    fn emit_type_params(&mut self, params: &[Span]) {
+        let Some(last) = params.last() else {
+            return;
+        };
+        self.emit("<");
+        for (i, param) in params.iter().enumerate() {
+            if i > 0 {
+                self.emit(", ");
+            }
+            self.emit_verbatim(*param);
+        }
+        // `<T,>` keeps a TSX arrow from parsing as an element
+        if self.byte_at(self.next_token(self.content_end(last.end))) == Some(b',') {
+            self.emit(",");
+        }
+        self.emit(">");
+    }
+
+    pub(super) fn emit_formatted_arrow(&mut self, arrow: &ArrowFunction) {
+        if arrow.is_async {
+            self.emit("async ");
+        }
+        let (type_params, return_type) = arrow_types(arrow);
+        self.emit_type_params(&type_params);
+        self.emit_params(&arrow.params);
+        if let Some(ty) = return_type {
+            self.emit(": ");
+            self.emit_verbatim(ty);
+        }
+        self.emit(" => ");
+        match &arrow.body {
+            ArrowBody::Expr(expr) => {
+                self.wrap_leftmost = true;
+                self.emit_expr_with_prec(expr, 2);
+            }
+            ArrowBody::Block(stmts) => {
+                self.emit_formatted_block(stmts, self.closing_brace(arrow.span));
+            }
+        }
+    }
+
+    // =========================================================================
+    // Classes
+    // =========================================================================
+
+    pub(super) fn emit_formatted_class(&mut self, class: &Class, is_declaration: bool) {
+        for decorator in &class.decorators {
+            self.emit("@");
+            self.emit_expr_with_prec(decorator, 18);
+            if is_declaration {
+                self.emit_newline();
+            } else {
+                self.emit(" ");
+            }
+        }
+        self.emit_class_tail(class);
+    }
+
+    /// A class without its decorators.
+    fn emit_class_tail(&mut self, class: &Class) {
+        let before = self.content_end(class.span.start);
+        if self.node_text(class.span).starts_with("abstract")
+            || self.source_slice(0, before).ends_with("abstract")
+        {
+            self.emit("abstract ");
+        }
+        self.emit("class");
+        if let Some(name) = &class.name {
+            self.emit(" ");
+            self.emit(name);
+        }
+        let (type_params, implements) = class_types(class);
+        self.emit_type_params(&type_params);
+        if let Some(super_class) = &class.super_class {
+            self.emit(" extends ");
+            self.emit_expr_with_prec(super_class, 18);
+            let after = self.content_end(super_class.span.end);
+            if let Some(args) = self.type_args_at(after) {
+                self.emit(args);
+            }
+        }
+        if !implements.is_empty() {
+            self.emit(" implements ");
+            for (i, ty) in implements.iter().enumerate() {
+                if i > 0 {
+                    self.emit(", ");
+                }
+                self.emit_verbatim(*ty);
+            }
+        }
+        self.emit(" {");
+        let members: Vec<&ClassMember> = class
+            .body
+            .iter()
+            .filter(|member| !matches!(member.kind, ClassMemberKind::Empty))
+            .collect();
+        let close = self.closing_brace(class.span);
+        let has_comments =
+            close.is_some_and(|close| self.has_comment_between(self.content_end(close), close));
+        if members.is_empty() && !has_comments {
+            self.emit("}");
+            return;
+        }
+        self.indent();
+        self.emit_list(
+            &members,
+            close,
+            "",
+            false,
+            |member| member_span(member),
+            |this, member| this.emit_formatted_member(member),
+        );
+        self.dedent();
+        self.emit_newline();
+        self.emit("}");
+    }
+
+    fn emit_formatted_member(&mut self, member: &ClassMember) {
+        for (i, decorator) in member.decorators.iter().enumerate() {
+            self.emit("@");
+            self.emit_expr_with_prec(decorator, 18);
+            let next = member
+                .decorators
+                .get(i + 1)
+                .map_or(member.span.start, |next| next.span.start);
+            let gap = self.source_slice(self.content_end(decorator.span.end), next);
+            if gap.contains('\n') {
+                self.emit_newline();
+            } else {
+                self.emit(" ");
+            }
+        }
+        if self.is_index_signature(member) {
+            self.emit_verbatim(member.span);
+            if !self.output.ends_with(';') {
+                self.emit(";");
+            }
+            return;
+        }
+        for modifier in self.member_modifiers(member).0 {
+            self.emit(modifier);
+            self.emit(" ");
+        }
+        match &member.kind {
+            ClassMemberKind::Method {
+                key, value, kind, ..
+            } => {
+                match kind {
+                    MethodKind::Get => self.emit("get "),
+                    MethodKind::Set => self.emit("set "),
+                    MethodKind::Method | MethodKind::Constructor => {}
+                }
+                if value.is_async {
+                    self.emit("async ");
+                }
+                if value.is_generator {
+                    self.emit("*");
+                }
+                self.emit_property_key(key);
+                self.emit_signature(value);
+                self.emit_function_body(value);
+            }
+            ClassMemberKind::Property { key, value, .. } => {
+                self.emit_property_key(key);
+                let (optional, definite, type_ann) = property_types(member);
+                if optional {
+                    self.emit("?");
+                }
+                if definite {
+                    self.emit("!");
+                }
+                if let Some(ty) = type_ann {
+                    self.emit(": ");
+                    self.emit_verbatim(ty);
+                }
+                if let Some(value) = value {
+                    self.emit(" = ");
+                    self.emit_expr_with_prec(value, 2);
+                }
+                self.emit(";");
+            }
+            ClassMemberKind::StaticBlock(stmts) => {
+                self.emit("static ");
+                self.emit_formatted_block(stmts, self.closing_brace(member.span));
+            }
+            ClassMemberKind::Empty => {}
+        }
+    }
+
+    /// Modifier keywords at the start of a member, in source order, and the
+    /// offset after them. `static` also appears in the AST; `declare` and
+    /// `accessor` only in the source.
+    fn member_modifiers(&self, member: &ClassMember) -> (Vec<&'a str>, u32) {
+        let mut modifiers = Vec::new();
+        let mut pos = member.span.start;
+        let end = self.content_end(member.span.end).max(pos);
+        loop {
+            let rest = self.source_slice(pos, end);
+            let len = rest
+                .find(|c: char| !c.is_ascii_alphabetic())
+                .unwrap_or(rest.len());
+            let word = &rest[..len];
+            if !MEMBER_MODIFIERS.contains(&word) {
+                break;
+            }
+            let after = self.next_token(pos + len as u32);
+            // `static() {}` and `readonly = 1` name the member
+            let names_member = after == pos + len as u32
+                || matches!(
+                    self.byte_at(after),
+                    Some(b'(' | b'=' | b';' | b':' | b'?' | b'!' | b'<' | b'}') | None
+                );
+            if names_member {
+                break;
+            }
+            modifiers.push(word);
+            pos = after;
+        }
+        (modifiers, pos)
+    }
+
+    /// `[key: string]: T`, which the parser keeps as a property named
+    /// `__index`.
+    fn is_index_signature(&self, member: &ClassMember) -> bool {
+        matches!(
+            &member.kind,
+            ClassMemberKind::Property { key: PropertyKey::Ident(name), .. } if name == "__index"
+        ) && self.byte_at(self.member_modifiers(member).1) == Some(b'[')
+    }
+
+    // =========================================================================
+    // Modules
+    // =========================================================================
+
+    pub(super) fn emit_formatted_import(&mut self, decl: &ImportDecl) {
+        self.emit("import ");
+        if import_type_only(decl) {
+            self.emit("type ");
+        }
+        let mut named = Vec::new();
+        let mut needs_comma = false;
+        for spec in &decl.specifiers {
+            match spec {
+                ImportSpecifier::Default { local, .. } => {
+                    self.emit(local);
+                    needs_comma = true;
+                }
+                ImportSpecifier::Namespace { local, .. } => {
+                    if needs_comma {
+                        self.emit(", ");
+                    }
+                    self.emit("* as ");
+                    self.emit(local);
+                    needs_comma = true;
+                }
+                ImportSpecifier::Named { .. } => named.push(spec),
+            }
+        }
+        if !named.is_empty() {
+            if needs_comma {
+                self.emit(", ");
+            }
+            self.emit_bracketed(
+                ("{", "}"),
+                true,
+                &named,
+                None,
+                true,
+                |spec| match spec {
+                    ImportSpecifier::Named { span, .. }
+                    | ImportSpecifier::Default { span, .. }
+                    | ImportSpecifier::Namespace { span, .. } => *span,
+                },
+                |this, spec| {
+                    if let ImportSpecifier::Named {
+                        imported, local, ..
+                    } = spec
+                    {
+                        if import_specifier_is_type(spec) {
+                            this.emit("type ");
+                        }
+                        this.emit(imported);
+                        if imported != local {
+                            this.emit(" as ");
+                            this.emit(local);
+                        }
+                    }
+                },
+            );
+        }
+        if !decl.specifiers.is_empty() {
+            self.emit(" from ");
+        }
+        self.emit_module_source(decl.span, &decl.source);
+        self.emit_semicolon();
+    }
+
+    pub(super) fn emit_formatted_export(&mut self, decl: &ExportDecl) {
+        match decl {
+            ExportDecl::Named {
+                specifiers,
+                source,
+                span,
+                ..
+            } => {
+                self.emit("export ");
+                if export_type_only(decl) {
+                    self.emit("type ");
+                }
+                self.emit_bracketed(
+                    ("{", "}"),
+                    true,
+                    specifiers,
+                    self.closing_brace(Span::new(span.start, self.export_braces_end(*span))),
+                    true,
+                    |spec| spec.span,
+                    |this, spec| {
+                        if export_specifier_is_type(spec) {
+                            this.emit("type ");
+                        }
+                        this.emit(&spec.local);
+                        if spec.local != spec.exported {
+                            this.emit(" as ");
+                            this.emit(&spec.exported);
+                        }
+                    },
+                );
+                if let Some(source) = source {
+                    self.emit(" from ");
+                    self.emit_module_source(*span, source);
+                }
+                self.emit_semicolon();
+            }
+            ExportDecl::Default { expr, .. } => {
+                self.emit("export default ");
+                self.emit_expr_with_prec(expr, 2);
+                let is_declaration = match &expr.kind {
+                    ExprKind::Function(_) => true,
+                    ExprKind::Class(class) => class.decorators.is_empty(),
+                    _ => false,
+                };
+                if !is_declaration {
+                    self.emit_semicolon();
+                }
+            }
+            ExportDecl::Decl { decl, span } => match &decl.kind {
+                // `@dec export class C {}` keeps its decorators in front
+                StmtKind::Class(class)
+                    if class
+                        .decorators
+                        .first()
+                        .is_some_and(|d| d.span.start < span.start) =>
+                {
+                    for decorator in &class.decorators {
+                        self.emit("@");
+                        self.emit_expr_with_prec(decorator, 18);
+                        self.emit_newline();
+                    }
+                    self.emit("export ");
+                    self.emit_class_tail(class);
+                }
+                _ => {
+                    self.emit("export ");
+                    self.emit_stmt(decl);
+                }
+            },
+            ExportDecl::All {
+                exported,
+                source,
+                span,
+            } => {
+                self.emit("export * ");
+                if let Some(exported) = exported {
+                    self.emit("as ");
+                    self.emit(exported);
+                    self.emit(" ");
+                }
+                self.emit("from ");
+                self.emit_module_source(*span, source);
+                self.emit_semicolon();
+            }
+        }
+    }
+
+    /// End of an `export { ... }` clause: before ` from "..."` if present.
+    fn export_braces_end(&self, span: Span) -> u32 {
+        let text = self.node_text(span);
+        text.rfind('}')
+            .map_or(span.end, |i| span.start + i as u32 + 1)
+    }
+
+    /// Emit the module specifier ending the declaration at `span`, keeping
+    /// the source escapes. Anything after it (import attributes) is refused.
+    fn emit_module_source(&mut self, span: Span, source: &str) {
+        let text = self.node_text(span);
+        let text = text.strip_suffix(';').unwrap_or(text).trim_end();
+        let raw = text.chars().last().and_then(|quote| {
+            let body = &text[..text.len() - 1];
+            let start = body.rfind(quote)?;
+            (quote == '"' || quote == '\'').then(|| &text[start..])
+        });
+        match raw {
+            Some(raw) => self.emit(&requote(raw)),
+            None => {
+                self.refuse(span, UNSUPPORTED_SYNTAX);
+                self.emit("\"");
+                self.emit(&escape_string(source));
+                self.emit("\"");
+            }
+        }
+    }
+
+    // =========================================================================
+    // Expressions
+    // =========================================================================
+
+    /// Format-mode expression printing. Returns false to fall back to the
+    /// shared code generator.
+    pub(super) fn emit_formatted_expr(&mut self, expr: &Expr, min_prec: u8) -> bool {
+        match &expr.kind {
+            // `(function () {})()`, `({}).x` at the start of a statement
+            ExprKind::Function(_) | ExprKind::Class(_) | ExprKind::Object(_)
+                if self.wrap_leftmost =>
+            {
+                self.emit("(");
+                self.emit_expr(expr);
+                self.emit(")");
+            }
+            ExprKind::Number(_) | ExprKind::BigInt(_) => {
+                let text = self.node_text(expr.span);
+                self.emit(text);
+            }
+            ExprKind::String(_) => {
+                let text = requote(self.node_text(expr.span));
+                self.emit(&text);
+            }
+            ExprKind::TemplateNoSub(_) | ExprKind::Template { .. } => {
+                self.emit_verbatim(expr.span);
+            }
+            #[cfg(feature = "jsx")]
+            ExprKind::JsxElement(_) | ExprKind::JsxFragment(_) => {
+                self.emit_verbatim(expr.span);
+            }
+            ExprKind::Array(elements) if elements.iter().all(Option::is_some) => {
+                let close = self.content_end(expr.span.end).saturating_sub(1);
+                self.emit_bracketed(
+                    ("[", "]"),
+                    false,
+                    elements,
+                    Some(close),
+                    true,
+                    |element| element.as_ref().map_or(expr.span, |e| e.span),
+                    |this, element| {
+                        if let Some(element) = element {
+                            this.emit_expr_with_prec(element, 2);
+                        }
+                    },
+                );
+            }
+            ExprKind::Array(elements) => {
+                self.emit("[");
+                for (i, element) in elements.iter().enumerate() {
+                    if i > 0 {
+                        self.emit(", ");
+                    }
+                    if let Some(element) = element {
+                        self.emit_expr_with_prec(element, 2);
+                    }
+                }
+                if matches!(elements.last(), Some(None)) {
+                    self.emit(",");
+                }
+                self.emit("]");
+            }
+            ExprKind::Object(properties) => {
+                self.emit_bracketed(
+                    ("{", "}"),
+                    true,
+                    properties,
+                    self.closing_brace(expr.span),
+                    true,
+                    |prop| prop.span,
+                    Self::emit_object_property,
+                );
+            }
+            ExprKind::Class(class) => self.emit_formatted_class(class, false),
+            ExprKind::Arrow(arrow) => {
+                if min_prec > 2 {
+                    self.emit("(");
+                }
+                self.emit_formatted_arrow(arrow);
+                if min_prec > 2 {
+                    self.emit(")");
+                }
+            }
+            ExprKind::Sequence(exprs) => {
+                if min_prec > 1 {
+                    self.emit("(");
+                }
+                for (i, expr) in exprs.iter().enumerate() {
+                    if i > 0 {
+                        self.emit(", ");
+                    }
+                    self.emit_expr_with_prec(expr, 2);
+                }
+                if min_prec > 1 {
+                    self.emit(")");
+                }
+            }
+            ExprKind::Call { callee, args }
+            | ExprKind::OptionalCall { callee, args }
+            | ExprKind::New { callee, args } => {
+                let is_new = matches!(expr.kind, ExprKind::New { .. });
+                if is_new {
+                    self.emit("new ");
+                    self.emit_expr_with_prec(callee, 17);
+                } else {
+                    self.emit_expr_with_prec(callee, 18);
+                }
+                if let Some(type_args) = self.type_args_at(self.content_end(callee.span.end)) {
+                    self.emit(type_args);
+                }
+                if matches!(expr.kind, ExprKind::OptionalCall { .. }) {
+                    self.emit("?.");
+                }
+                let end = self.content_end(expr.span.end);
+                let close = (end > 0 && self.byte_at(end - 1) == Some(b')')).then(|| end - 1);
+                self.emit_bracketed(
+                    ("(", ")"),
+                    false,
+                    args,
+                    close,
+                    true,
+                    |arg| arg.span,
+                    |this, arg| this.emit_expr_with_prec(arg, 2),
+                );
+            }
+            #[cfg(feature = "typescript")]
+            ExprKind::TsAs { expr: inner, ty } | ExprKind::TsSatisfies { expr: inner, ty } => {
+                let keyword = if matches!(expr.kind, ExprKind::TsAs { .. }) {
+                    " as "
+                } else {
+                    " satisfies "
+                };
+                if min_prec > 10 {
+                    self.emit("(");
+                }
+                self.emit_expr_with_prec(inner, 10);
+                self.emit(keyword);
+                self.emit_verbatim(ty.span);
+                if min_prec > 10 {
+                    self.emit(")");
+                }
+            }
+            #[cfg(feature = "typescript")]
+            ExprKind::TsNonNull(inner) => {
+                self.emit_expr_with_prec(inner, 18);
+                self.emit("!");
+            }
+            #[cfg(feature = "typescript")]
+            ExprKind::TsTypeAssertion { ty, expr: inner } => {
+                if min_prec > 15 {
+                    self.emit("(");
+                }
+                self.emit("<");
+                self.emit_verbatim(ty.span);
+                self.emit(">");
+                self.emit_expr_with_prec(inner, 15);
+                if min_prec > 15 {
+                    self.emit(")");
+                }
+            }
+            _ => return false,
+        }
+        true
+    }
+
+    /// Type arguments written at `pos` (`f<T>()`, `extends Base<T>`), which
+    /// the AST doesn't keep.
+    fn type_args_at(&self, pos: u32) -> Option<&'a str> {
+        let start = self.next_token(pos);
+        if self.byte_at(start) != Some(b'<') {
+            return None;
+        }
+        let text = self.source_slice(start, self.ast.source.len() as u32);
+        let mut depth = 0usize;
+        let mut prev = b' ';
+        for (i, byte) in text.bytes().enumerate() {
+            match byte {
+                b'<' => depth += 1,
+                // `=>` inside a function type doesn't close anything
+                b'>' if prev != b'=' => {
+                    depth -= 1;
+                    if depth == 0 {
+                        return Some(&text[..=i]);
+                    }
+                }
+                b';' | b'{' | b'}' => return None,
+                _ => {}
+            }
+            prev = byte;
+        }
+        None
+    }
+
+    pub(super) fn emit_formatted_property(&mut self, prop: &Property) {
+        if let ExprKind::Spread(arg) = &prop.value.kind {
+            self.emit("...");
+            self.emit_expr_with_prec(arg, 2);
+            return;
+        }
+        if prop.shorthand {
+            // `a`, or `a = 1` in an assignment pattern
+            self.emit_expr_with_prec(&prop.value, 2);
+            return;
+        }
+        let func = match &prop.value.kind {
+            ExprKind::Function(func) if prop.kind != PropertyKind::Init => Some(func),
+            _ => None,
+        };
+        match (prop.kind, func) {
+            (PropertyKind::Get, _) => self.emit("get "),
+            (PropertyKind::Set, _) => self.emit("set "),
+            (PropertyKind::Method, Some(func)) => {
+                if func.is_async {
+                    self.emit("async ");
+                }
+                if func.is_generator {
+                    self.emit("*");
+                }
+            }
+            _ => {}
+        }
+        self.emit_property_key(&prop.key);
+        match func {
+            Some(func) => {
+                self.emit_signature(func);
+                self.emit_function_body(func);
+            }
+            None => {
+                self.emit(": ");
+                self.emit_expr_with_prec(&prop.value, 2);
+            }
+        }
+    }
+}
+
+/// Printer state to retry a list in its expanded layout.
+struct Snapshot {
+    output_len: usize,
+    indent_level: usize,
+    next_comment: usize,
+    last_pos: u32,
+    at_list_start: bool,
+    refused: Option<(Span, &'static str)>,
+}
+
+/// Statements other than stray `;`, which the formatter drops.
+fn non_empty(stmts: &[Stmt]) -> Vec<&Stmt> {
+    stmts
+        .iter()
+        .filter(|stmt| !matches!(stmt.kind, StmtKind::Empty))
+        .collect()
+}
+
+/// A member's span including its decorators.
+fn member_span(member: &ClassMember) -> Span {
+    let start = member
+        .decorators
+        .first()
+        .map_or(member.span.start, |d| d.span.start.saturating_sub(1));
+    Span::new(start.min(member.span.start), member.span.end)
+}
+
+/// Re-quote a string literal: double quotes unless the content has more
+/// double than single quotes. Other escapes are kept as written.
+fn requote(raw: &str) -> String {
+    let Some(old) = raw.chars().next().filter(|&c| c == '"' || c == '\'') else {
+        return raw.to_string();
+    };
+    let body = &raw[1..raw.len() - 1];
+    let quote = if body.matches('"').count() > body.matches('\'').count() {
+        '\''
+    } else {
+        '"'
+    };
+    if quote == old {
+        return raw.to_string();
+    }
+    let mut out = String::with_capacity(raw.len() + 2);
+    out.push(quote);
+    let mut chars = body.chars();
+    while let Some(c) = chars.next() {
+        match c {
+            '\\' => match chars.next() {
+                Some(next) if next == old => out.push(next),
+                Some(next) => {
+                    out.push('\\');
+                    out.push(next);
+                }
+                None => out.push('\\'),
+            },
+            c if c == quote => {
+                out.push('\\');
+                out.push(c);
+            }
+            c => out.push(c),
+        }
+    }
+    out.push(quote);
+    out
+}
+
+// =============================================================================
+// TypeScript Accessors
+// =============================================================================
+
+#[cfg(feature = "typescript")]
+fn binding_type(binding: &Binding) -> Option<Span> {
+    match &binding.kind {
+        BindingKind::Ident { type_ann, .. }
+        | BindingKind::Array { type_ann, .. }
+        | BindingKind::Object { type_ann, .. } => type_ann.as_ref().map(|ty| ty.span),
+    }
+}
+
+#[cfg(not(feature = "typescript"))]
+fn binding_type(_binding: &Binding) -> Option<Span> {
+    None
+}
+
+#[cfg(feature = "typescript")]
+fn param_optional(param: &Param) -> bool {
+    param.optional
+}
+
+#[cfg(not(feature = "typescript"))]
+fn param_optional(_param: &Param) -> bool {
+    false
+}
+
+#[cfg(feature = "typescript")]
+fn type_param_spans(params: Option<&Vec<TsTypeParam>>) -> Vec<Span> {
+    params.map_or_else(Vec::new, |params| params.iter().map(|p| p.span).collect())
+}
+
+#[cfg(feature = "typescript")]
+fn function_types(func: &Function) -> (Vec<Span>, Option<Span>) {
+    (
+        type_param_spans(func.type_params.as_ref()),
+        func.return_type.as_ref().map(|ty| ty.span),
+    )
+}
+
+#[cfg(not(feature = "typescript"))]
+fn function_types(_func: &Function) -> (Vec<Span>, Option<Span>) {
+    (Vec::new(), None)
+}
+
+#[cfg(feature = "typescript")]
+fn arrow_types(arrow: &ArrowFunction) -> (Vec<Span>, Option<Span>) {
+    (
+        type_param_spans(arrow.type_params.as_ref()),
+        arrow.return_type.as_ref().map(|ty| ty.span),
+    )
+}
+
+#[cfg(not(feature = "typescript"))]
+fn arrow_types(_arrow: &ArrowFunction) -> (Vec<Span>, Option<Span>) {
+    (Vec::new(), None)
+}
+
+#[cfg(feature = "typescript")]
+fn class_types(class: &Class) -> (Vec<Span>, Vec<Span>) {
+    (
+        type_param_spans(class.type_params.as_ref()),
+        class.implements.iter().map(|ty| ty.span).collect(),
+    )
+}
+
+#[cfg(not(feature = "typescript"))]
+fn class_types(_class: &Class) -> (Vec<Span>, Vec<Span>) {
+    (Vec::new(), Vec::new())
+}
+
+/// `(optional, definite, type annotation)` of a class property.
+#[cfg(feature = "typescript")]
+fn property_types(member: &ClassMember) -> (bool, bool, Option<Span>) {
+    match &member.kind {
+        ClassMemberKind::Property {
+            optional,
+            definite,
+            type_ann,
+            ..
+        } => (*optional, *definite, type_ann.as_ref().map(|ty| ty.span)),
+        _ => (false, false, None),
+    }
+}
+
+#[cfg(not(feature = "typescript"))]
+fn property_types(_member: &ClassMember) -> (bool, bool, Option<Span>) {
+    (false, false, None)
+}
+
+#[cfg(feature = "typescript")]
+fn import_type_only(decl: &ImportDecl) -> bool {
+    decl.is_type_only
+}
+
+#[cfg(not(feature = "typescript"))]
+fn import_type_only(_decl: &ImportDecl) -> bool {
+    false
+}
+
+#[cfg(feature = "typescript")]
+fn import_specifier_is_type(spec: &ImportSpecifier) -> bool {
+    matches!(spec, ImportSpecifier::Named { is_type: true, .. })
+}
+
+#[cfg(not(feature = "typescript"))]
+fn import_specifier_is_type(_spec: &ImportSpecifier) -> bool {
+    false
+}
+
+#[cfg(feature = "typescript")]
+fn export_type_only(decl: &ExportDecl) -> bool {
+    matches!(
+        decl,
+        ExportDecl::Named {
+            is_type_only: true,
+            ..
+        }
+    )
+}
+
+#[cfg(not(feature = "typescript"))]
+fn export_type_only(_decl: &ExportDecl) -> bool {
+    false
+}
+
+#[cfg(feature = "typescript")]
+fn export_specifier_is_type(spec: &ExportSpecifier) -> bool {
+    spec.is_type
+}
+
+#[cfg(not(feature = "typescript"))]
+fn export_specifier_is_type(_spec: &ExportSpecifier) -> bool {
+    false
+}
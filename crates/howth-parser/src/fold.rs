@@ -0,0 +1,1529 @@
+//! Constant folding and dead code elimination.
+//!
+//! Shrinks minified and production output before codegen:
+//!
+//! - Literal arithmetic, comparisons and string concatenation are evaluated
+//!   (`60 * 1000` → `60000`, `"v" + 2` → `"v2"`).
+//! - `typeof` of a literal, or of a function, class or `const` that is
+//!   declared once and never reassigned, becomes a string.
+//! - `&&`, `||`, `??` and `?:` with a constant test keep only the operand
+//!   that's taken, and so do `if` and `while` statements. This removes the
+//!   code `--define` substitution disables: `if ("production" !== "production")`.
+//! - Statements after `return`, `throw`, `break` and `continue` are dropped,
+//!   as are expression statements without side effects.
+//! - Calls annotated `/* @__PURE__ */` count as side-effect free, so an
+//!   unused one is dropped, and so is a local `const x = /* @__PURE__ */ f()`
+//!   that's never read.
+//!
+//! Removed code keeps its `var` declarations (without initializers) since
+//! they're hoisted, and function declarations after a `return` stay.
+//! Directive prologues (`"use strict"`) are kept. Nothing is folded by name
+//! when the program uses `eval` or `with`.
+
+use crate::ast::*;
+use crate::span::Span;
+use std::collections::{HashMap, HashSet};
+
+/// Fold constants and remove dead code in an AST in-place.
+///
+/// Returns whether anything changed, so callers can keep the original
+/// text when it didn't.
+pub fn fold(ast: &mut Ast) -> bool {
+    let mut folder = Folder {
+        collecting: true,
+        pure_calls: ast.pure_annotations.clone(),
+        ..Folder::default()
+    };
+
+    // Phase 1: Collect — declarations and assignments per name
+    folder.stmts(&mut ast.stmts, true);
+
+    // Phase 2: Fold — rewrite expressions and statement lists
+    folder.collecting = false;
+    folder.stmts(&mut ast.stmts, true);
+    folder.changed
+}
+
+// =============================================================================
+// Constant Values
+// =============================================================================
+
+/// A primitive value an expression is known to evaluate to.
+#[derive(Debug, Clone, PartialEq)]
+enum Value {
+    Undefined,
+    Null,
+    Bool(bool),
+    Number(f64),
+    String(String),
+}
+
+impl Value {
+    fn truthy(&self) -> bool {
+        match self {
+            Self::Undefined | Self::Null => false,
+            Self::Bool(b) => *b,
+            Self::Number(n) => *n != 0.0 && !n.is_nan(),
+            Self::String(s) => !s.is_empty(),
+        }
+    }
+
+    fn type_of(&self) -> &'static str {
+        match self {
+            Self::Undefined => "undefined",
+            Self::Null => "object",
+            Self::Bool(_) => "boolean",
+            Self::Number(_) => "number",
+            Self::String(_) => "string",
+        }
+    }
+
+    /// `String(value)`, when it's cheap to get exactly right.
+    fn to_js_string(&self) -> Option<String> {
+        match self {
+            Self::Undefined => Some("undefined".to_string()),
+            Self::Null => Some("null".to_string()),
+            Self::Bool(b) => Some(b.to_string()),
+            Self::Number(n) => number_to_string(*n),
+            Self::String(s) => Some(s.clone()),
+        }
+    }
+
+    /// The literal for this value. `None` for NaN and infinities, whose
+    /// names can be shadowed.
+    fn into_expr(self, span: Span) -> Option<Expr> {
+        let kind = match self {
+            Self::Undefined => ExprKind::Unary {
+                op: UnaryOp::Void,
+                arg: Box::new(Expr::new(ExprKind::Number(0.0), span)),
+            },
+            Self::Null => ExprKind::Null,
+            Self::Bool(b) => ExprKind::Bool(b),
+            Self::Number(n) => return number_expr(n, span),
+            Self::String(s) => ExprKind::String(s),
+        };
+        Some(Expr::new(kind, span))
+    }
+}
+
+/// A number literal. Negative numbers are written as `-n` so they print
+/// correctly in any position (`(-1).toFixed()`, `a - -1`).
+fn number_expr(n: f64, span: Span) -> Option<Expr> {
+    if !n.is_finite() {
+        return None;
+    }
+    let literal = Expr::new(ExprKind::Number(n.abs()), span);
+    if n.is_sign_negative() {
+        Some(Expr::new(
+            ExprKind::Unary {
+                op: UnaryOp::Minus,
+                arg: Box::new(literal),
+            },
+            span,
+        ))
+    } else {
+        Some(literal)
+    }
+}
+
+/// JavaScript's `Number.prototype.toString()` for the range where it
+/// doesn't switch to exponent notation.
+fn number_to_string(n: f64) -> Option<String> {
+    if n == 0.0 {
+        Some("0".to_string())
+    } else if n.is_nan() {
+        Some("NaN".to_string())
+    } else if n.is_infinite() {
+        Some(if n > 0.0 { "Infinity" } else { "-Infinity" }.to_string())
+    } else if (1e-6..1e21).contains(&n.abs()) {
+        Some(format!("{n}"))
+    } else {
+        None
+    }
+}
+
+/// Length of a number as written in output, for deciding whether folding
+/// makes it shorter.
+fn number_len(n: f64) -> usize {
+    number_to_string(n).map_or(usize::MAX, |s| s.len())
+}
+
+fn to_int32(n: f64) -> i32 {
+    if !n.is_finite() {
+        return 0;
+    }
+    #[allow(clippy::cast_possible_truncation)]
+    let wrapped = (n.trunc() % 4_294_967_296.0) as i64;
+    #[allow(clippy::cast_possible_truncation)]
+    let int = wrapped as i32;
+    int
+}
+
+fn strict_equals(left: &Value, right: &Value) -> bool {
+    match (left, right) {
+        (Value::Number(a), Value::Number(b)) => a == b,
+        _ => left == right,
+    }
+}
+
+/// Evaluate a binary operator on two known values.
+fn eval_binary(op: BinaryOp, left: &Value, right: &Value) -> Option<Value> {
+    use Value::{Bool, Null, Number, Undefined};
+
+    let numbers = match (left, right) {
+        (Number(a), Number(b)) => Some((*a, *b)),
+        _ => None,
+    };
+    let value = match op {
+        BinaryOp::Add => match (left, right) {
+            (Number(a), Number(b)) => Number(a + b),
+            (Value::String(a), _) => Value::String(format!("{a}{}", right.to_js_string()?)),
+            (_, Value::String(b)) => Value::String(format!("{}{b}", left.to_js_string()?)),
+            _ => return None,
+        },
+        BinaryOp::Sub => numbers.map(|(a, b)| Number(a - b))?,
+        BinaryOp::Mul => numbers.map(|(a, b)| Number(a * b))?,
+        BinaryOp::Div => numbers.map(|(a, b)| Number(a / b))?,
+        BinaryOp::Mod => numbers.map(|(a, b)| Number(a % b))?,
+        BinaryOp::Pow => numbers.map(|(a, b)| Number(a.powf(b)))?,
+        BinaryOp::BitOr | BinaryOp::BitXor | BinaryOp::BitAnd => {
+            let (a, b) = numbers?;
+            let (a, b) = (to_int32(a), to_int32(b));
+            Number(f64::from(match op {
+                BinaryOp::BitOr => a | b,
+                BinaryOp::BitXor => a ^ b,
+                _ => a & b,
+            }))
+        }
+        BinaryOp::Shl | BinaryOp::Shr | BinaryOp::UShr => {
+            let (a, b) = numbers?;
+            #[allow(clippy::cast_sign_loss)]
+            let shift = (to_int32(b) as u32) & 31;
+            let a = to_int32(a);
+            Number(match op {
+                BinaryOp::Shl => f64::from(a.wrapping_shl(shift)),
+                BinaryOp::Shr => f64::from(a >> shift),
+                #[allow(clippy::cast_sign_loss)]
+                _ => f64::from((a as u32) >> shift),
+            })
+        }
+        BinaryOp::StrictEq => Bool(strict_equals(left, right)),
+        BinaryOp::StrictNotEq => Bool(!strict_equals(left, right)),
+        BinaryOp::Eq | BinaryOp::NotEq => {
+            let equal = match (left, right) {
+                (Null | Undefined, Null | Undefined) => true,
+                (Null | Undefined, _) | (_, Null | Undefined) => false,
+                _ if std::mem::discriminant(left) == std::mem::discriminant(right) => {
+                    strict_equals(left, right)
+                }
+                _ => return None,
+            };
+            Bool(equal == (op == BinaryOp::Eq))
+        }
+        BinaryOp::Lt | BinaryOp::LtEq | BinaryOp::Gt | BinaryOp::GtEq => {
+            let ordering = match (left, right) {
+                (Number(a), Number(b)) => a.partial_cmp(b),
+                // UTF-16 and UTF-8 order only agree for ASCII
+                (Value::String(a), Value::String(b)) if a.is_ascii() && b.is_ascii() => {
+                    Some(a.cmp(b))
+                }
+                _ => return None,
+            };
+            Bool(ordering.is_some_and(|ordering| match op {
+                BinaryOp::Lt => ordering.is_lt(),
+                BinaryOp::LtEq => ordering.is_le(),
+                BinaryOp::Gt => ordering.is_gt(),
+                _ => ordering.is_ge(),
+            }))
+        }
+        _ => return None,
+    };
+
+    // Only fold numbers when the result is no longer than the expression
+    if let (Number(result), Some((a, b))) = (&value, numbers) {
+        if number_len(*result) > number_len(a).saturating_add(number_len(b)) + 1 {
+            return None;
+        }
+    }
+    Some(value)
+}
+
+// =============================================================================
+// Folder
+// =============================================================================
+
+#[derive(Default)]
+struct Folder {
+    /// True during the collect phase, which only records names.
+    collecting: bool,
+    /// Number of binding sites per name, across all scopes.
+    declarations: HashMap<String, u32>,
+    /// `typeof` of names whose declaration fixes it.
+    types: HashMap<String, &'static str>,
+    /// Names that are assigned or updated somewhere.
+    written: HashSet<String>,
+    /// Number of references per name that read it.
+    reads: HashMap<String, u32>,
+    /// [`Ast::pure_annotations`].
+    pure_calls: Vec<u32>,
+    /// Nesting depth of function bodies; top-level names may be read by
+    /// other scripts.
+    function_depth: u32,
+    /// `eval` or `with` is used, so names can change in ways we can't see.
+    dynamic_scope: bool,
+    changed: bool,
+}
+
+impl Folder {
+    fn declare(&mut self, name: &str) {
+        if self.collecting {
+            *self.declarations.entry(name.to_string()).or_default() += 1;
+        }
+    }
+
+    fn declare_typed(&mut self, name: &str, ty: &'static str) {
+        if self.collecting {
+            self.declare(name);
+            self.types.insert(name.to_string(), ty);
+        }
+    }
+
+    /// `typeof name` when it can't change at runtime.
+    fn known_type(&self, name: &str) -> Option<&'static str> {
+        if self.dynamic_scope
+            || self.declarations.get(name) != Some(&1)
+            || self.written.contains(name)
+        {
+            return None;
+        }
+        self.types.get(name).copied()
+    }
+
+    /// Whether `name` refers to the global of that name.
+    fn is_global(&self, name: &str) -> bool {
+        !self.dynamic_scope && !self.declarations.contains_key(name)
+    }
+
+    fn read(&mut self, name: &str) {
+        if self.collecting {
+            *self.reads.entry(name.to_string()).or_default() += 1;
+        }
+    }
+
+    /// Whether a declarator binds a local that's never used and has no
+    /// side effects to keep.
+    fn is_unused(&self, decl: &VarDeclarator) -> bool {
+        let BindingKind::Ident { name, .. } = &decl.binding.kind else {
+            return false;
+        };
+        self.function_depth > 0
+            && !self.dynamic_scope
+            && self.declarations.get(name) == Some(&1)
+            && !self.reads.contains_key(name)
+            && !self.written.contains(name)
+            && decl.init.as_ref().is_none_or(|init| self.is_pure(init))
+    }
+
+    // -------------------------------------------------------------------------
+    // Statements
+    // -------------------------------------------------------------------------
+
+    /// Fold a statement list. `directives` is set for function and program
+    /// bodies, whose leading string statements are directives.
+    fn stmts(&mut self, stmts: &mut Vec<Stmt>, directives: bool) {
+        for stmt in stmts.iter_mut() {
+            self.stmt(stmt);
+        }
+        if self.collecting {
+            return;
+        }
+
+        let mut queue: std::collections::VecDeque<Stmt> = std::mem::take(stmts).into();
+        let mut in_prologue = directives;
+        let mut unreachable = false;
+        let mut hoisted = Vec::new();
+        while let Some(mut stmt) = queue.pop_front() {
+            let is_directive = in_prologue
+                && matches!(
+                    &stmt.kind,
+                    StmtKind::Expr(Expr {
+                        kind: ExprKind::String(_),
+                        ..
+                    })
+                );
+            in_prologue = is_directive;
+
+            if unreachable {
+                if matches!(stmt.kind, StmtKind::Function(_)) || is_bare_var(&stmt) {
+                    stmts.push(stmt);
+                } else {
+                    var_names(&stmt, &mut hoisted);
+                    self.changed = true;
+                }
+                continue;
+            }
+
+            match stmt.kind {
+                StmtKind::Empty => {
+                    self.changed = true;
+                    continue;
+                }
+                // A block without lexical declarations is only braces
+                StmtKind::Block(body) if !body.iter().any(is_lexical) => {
+                    self.changed = true;
+                    for stmt in body.into_iter().rev() {
+                        queue.push_front(stmt);
+                    }
+                    continue;
+                }
+                StmtKind::Expr(ref expr) if !is_directive && self.is_pure(expr) => {
+                    self.changed = true;
+                    continue;
+                }
+                StmtKind::Var { ref mut decls, .. }
+                    if decls.iter().any(|decl| self.is_unused(decl)) =>
+                {
+                    self.changed = true;
+                    decls.retain(|decl| !self.is_unused(decl));
+                    if decls.is_empty() {
+                        continue;
+                    }
+                }
+                _ => {}
+            }
+
+            unreachable = matches!(
+                stmt.kind,
+                StmtKind::Return { .. }
+                    | StmtKind::Throw { .. }
+                    | StmtKind::Break { .. }
+                    | StmtKind::Continue { .. }
+            );
+            stmts.push(stmt);
+        }
+        if !hoisted.is_empty() {
+            let span = stmts.last().map_or(Span::default(), |stmt| stmt.span);
+            stmts.push(var_decl(hoisted, span));
+        }
+    }
+
+    fn stmt(&mut self, stmt: &mut Stmt) {
+        match &mut stmt.kind {
+            StmtKind::Var { kind, decls } => self.var_decls(*kind, decls),
+            StmtKind::Function(func) => {
+                if let Some(name) = &func.name {
+                    self.declare_typed(name, "function");
+                }
+                self.function(func);
+            }
+            StmtKind::Class(class) => {
+                if let Some(name) = &class.name {
+                    self.declare_typed(name, "function");
+                }
+                self.class(class);
+            }
+            StmtKind::Block(stmts) => self.stmts(stmts, false),
+            StmtKind::If {
+                test,
+                consequent,
+                alternate,
+            } => {
+                self.expr(test);
+                self.stmt(consequent);
+                if let Some(alternate) = alternate {
+                    self.stmt(alternate);
+                }
+                if !self.collecting {
+                    self.fold_if(stmt);
+                }
+            }
+            StmtKind::Switch {
+                discriminant,
+                cases,
+            } => {
+                self.expr(discriminant);
+                for case in cases {
+                    if let Some(test) = &mut case.test {
+                        self.expr(test);
+                    }
+                    self.stmts(&mut case.consequent, false);
+                }
+            }
+            StmtKind::For {
+                init,
+                test,
+                update,
+                body,
+            } => {
+                match init {
+                    Some(ForInit::Var { kind, decls }) => self.var_decls(*kind, decls),
+                    Some(ForInit::Expr(expr)) => self.expr(expr),
+                    None => {}
+                }
+                if let Some(test) = test {
+                    self.expr(test);
+                }
+                if let Some(update) = update {
+                    self.expr(update);
+                }
+                self.stmt(body);
+            }
+            StmtKind::ForIn { left, right, body }
+            | StmtKind::ForOf {
+                left, right, body, ..
+            } => {
+                match left {
+                    ForInit::Var { kind, decls } => self.var_decls(*kind, decls),
+                    ForInit::Expr(expr) => self.assign_target(expr),
+                }
+                self.expr(right);
+                self.stmt(body);
+            }
+            StmtKind::While { test, body } => {
+                self.expr(test);
+                self.stmt(body);
+                if !self.collecting && self.truthiness(test) == Some(false) {
+                    let mut names = Vec::new();
+                    var_names(body, &mut names);
+                    *stmt = if names.is_empty() {
+                        Stmt::new(StmtKind::Empty, stmt.span)
+                    } else {
+                        var_decl(names, stmt.span)
+                    };
+                    self.changed = true;
+                }
+            }
+            StmtKind::DoWhile { body, test } => {
+                self.stmt(body);
+                self.expr(test);
+            }
+            StmtKind::Return { arg } => {
+                if let Some(arg) = arg {
+                    self.expr(arg);
+                }
+            }
+            StmtKind::Throw { arg } => self.expr(arg),
+            StmtKind::Expr(expr) => {
+                self.expr(expr);
+                if !self.collecting {
+                    self.drop_unused_values(expr);
+                }
+            }
+            StmtKind::Try {
+                block,
+                handler,
+                finalizer,
+            } => {
+                self.stmts(block, false);
+                if let Some(handler) = handler {
+                    if let Some(param) = &mut handler.param {
+                        self.binding(param);
+                    }
+                    self.stmts(&mut handler.body, false);
+                }
+                if let Some(finalizer) = finalizer {
+                    self.stmts(finalizer, false);
+                }
+            }
+            StmtKind::Labeled { body, .. } => self.stmt(body),
+            StmtKind::With { object, body } => {
+                if self.collecting {
+                    self.dynamic_scope = true;
+                }
+                self.expr(object);
+                self.stmt(body);
+            }
+            StmtKind::Import(import) => {
+                for spec in &import.specifiers {
+                    match spec {
+                        ImportSpecifier::Default { local, .. }
+                        | ImportSpecifier::Namespace { local, .. }
+                        | ImportSpecifier::Named { local, .. } => self.declare(local),
+                    }
+                }
+            }
+            StmtKind::Export(export) => match export.as_mut() {
+                ExportDecl::Default { expr, .. } => self.expr(expr),
+                ExportDecl::Decl { decl, .. } => self.stmt(decl),
+                ExportDecl::Named {
+                    specifiers,
+                    source: None,
+                    ..
+                } => {
+                    for spec in specifiers.iter() {
+                        self.read(&spec.local);
+                    }
+                }
+                ExportDecl::Named { .. } | ExportDecl::All { .. } => {}
+            },
+            StmtKind::Break { .. }
+            | StmtKind::Continue { .. }
+            | StmtKind::Empty
+            | StmtKind::Debugger => {}
+
+            #[cfg(feature = "typescript")]
+            StmtKind::TsEnum(ts_enum) => {
+                self.declare(&ts_enum.name);
+                for member in &mut ts_enum.members {
+                    if let Some(init) = &mut member.init {
+                        self.expr(init);
+                    }
+                }
+            }
+            #[cfg(feature = "typescript")]
+            StmtKind::TsNamespace(namespace) => {
+                self.declare(&namespace.name);
+                self.stmts(&mut namespace.body, false);
+            }
+            #[cfg(feature = "typescript")]
+            StmtKind::TsDeclare(inner) => self.stmt(inner),
+            #[cfg(feature = "typescript")]
+            StmtKind::TsTypeAlias(_) | StmtKind::TsInterface(_) => {}
+        }
+    }
+
+    fn var_decls(&mut self, kind: VarKind, decls: &mut [VarDeclarator]) {
+        for decl in decls {
+            self.binding(&mut decl.binding);
+            if let Some(init) = &mut decl.init {
+                if self.collecting && kind == VarKind::Const {
+                    if let (BindingKind::Ident { name, .. }, Some(ty)) =
+                        (&decl.binding.kind, literal_type(init))
+                    {
+                        self.types.insert(name.clone(), ty);
+                    }
+                }
+                self.expr(init);
+            }
+        }
+    }
+
+    /// Replace an `if` whose test is constant with the branch it takes.
+    fn fold_if(&mut self, stmt: &mut Stmt) {
+        let span = stmt.span;
+        let StmtKind::If {
+            test,
+            consequent,
+            alternate,
+        } = &mut stmt.kind
+        else {
+            return;
+        };
+
+        let Some(truthy) = self.truthiness(test) else {
+            if alternate
+                .as_ref()
+                .is_some_and(|alternate| matches!(alternate.kind, StmtKind::Empty))
+            {
+                *alternate = None;
+                self.changed = true;
+            }
+            return;
+        };
+
+        let consequent = std::mem::replace(&mut **consequent, Stmt::new(StmtKind::Empty, span));
+        let alternate = alternate.take().map(|alternate| *alternate);
+        let (taken, dropped) = if truthy {
+            (Some(consequent), alternate)
+        } else {
+            (alternate, Some(consequent))
+        };
+
+        let mut names = Vec::new();
+        if let Some(dropped) = &dropped {
+            var_names(dropped, &mut names);
+        }
+        let taken = taken.unwrap_or(Stmt::new(StmtKind::Empty, span));
+        *stmt = match (names.is_empty(), &taken.kind) {
+            (true, _) => taken,
+            (false, StmtKind::Empty) => var_decl(names, span),
+            (false, _) => Stmt::new(StmtKind::Block(vec![taken, var_decl(names, span)]), span),
+        };
+        self.changed = true;
+    }
+
+    /// In an expression statement the value is unused, so the parts of a
+    /// sequence without side effects can go.
+    fn drop_unused_values(&mut self, expr: &mut Expr) {
+        let ExprKind::Sequence(exprs) = &mut expr.kind else {
+            return;
+        };
+        let keep: Vec<bool> = exprs.iter().map(|expr| !self.is_pure(expr)).collect();
+        if keep.iter().all(|keep| *keep) || !keep.iter().any(|keep| *keep) {
+            return;
+        }
+        let mut keep = keep.into_iter();
+        exprs.retain(|_| keep.next().unwrap_or(true));
+        if exprs.len() == 1 {
+            *expr = exprs.pop().expect("one expression left");
+        }
+        self.changed = true;
+    }
+
+    // -------------------------------------------------------------------------
+    // Functions, classes and bindings
+    // -------------------------------------------------------------------------
+
+    fn function(&mut self, func: &mut Function) {
+        self.params(&mut func.params);
+        self.function_depth += 1;
+        self.stmts(&mut func.body, true);
+        self.function_depth -= 1;
+    }
+
+    fn params(&mut self, params: &mut [Param]) {
+        for param in params {
+            self.binding(&mut param.binding);
+            if let Some(default) = &mut param.default {
+                self.expr(default);
+            }
+        }
+    }
+
+    fn class(&mut self, class: &mut Class) {
+        for decorator in &mut class.decorators {
+            self.expr(decorator);
+        }
+        if let Some(super_class) = &mut class.super_class {
+            self.expr(super_class);
+        }
+        for member in &mut class.body {
+            for decorator in &mut member.decorators {
+                self.expr(decorator);
+            }
+            match &mut member.kind {
+                ClassMemberKind::Method { key, value, .. } => {
+                    self.property_key(key);
+                    self.function(value);
+                }
+                ClassMemberKind::Property { key, value, .. } => {
+                    self.property_key(key);
+                    if let Some(value) = value {
+                        self.expr(value);
+                    }
+                }
+                ClassMemberKind::StaticBlock(stmts) => self.stmts(stmts, false),
+                ClassMemberKind::Empty => {}
+            }
+        }
+    }
+
+    fn property_key(&mut self, key: &mut PropertyKey) {
+        if let PropertyKey::Computed(expr) = key {
+            self.expr(expr);
+        }
+    }
+
+    fn binding(&mut self, binding: &mut Binding) {
+        match &mut binding.kind {
+            BindingKind::Ident { name, .. } => self.declare(name),
+            BindingKind::Array { elements, .. } => {
+                for element in elements.iter_mut().flatten() {
+                    self.binding(&mut element.binding);
+                    if let Some(default) = &mut element.default {
+                        self.expr(default);
+                    }
+                }
+            }
+            BindingKind::Object { properties, .. } => {
+                for property in properties {
+                    self.property_key(&mut property.key);
+                    self.binding(&mut property.value);
+                    if let Some(default) = &mut property.default {
+                        self.expr(default);
+                    }
+                }
+            }
+        }
+    }
+
+    /// The left side of an assignment, update or `for-in`/`for-of`.
+    fn assign_target(&mut self, target: &mut Expr) {
+        match &mut target.kind {
+            ExprKind::Ident(name) => {
+                if self.collecting {
+                    self.written.insert(name.clone());
+                }
+            }
+            ExprKind::Array(elements) => {
+                for element in elements.iter_mut().flatten() {
+                    self.assign_target(element);
+                }
+            }
+            ExprKind::Object(properties) => {
+                for property in properties {
+                    self.property_key(&mut property.key);
+                    self.assign_target(&mut property.value);
+                }
+            }
+            ExprKind::Assign { left, right, .. } => {
+                self.assign_target(left);
+                self.expr(right);
+            }
+            ExprKind::Spread(inner) => self.assign_target(inner),
+            #[cfg(feature = "typescript")]
+            ExprKind::TsAs { expr, .. }
+            | ExprKind::TsSatisfies { expr, .. }
+            | ExprKind::TsNonNull(expr)
+            | ExprKind::TsTypeAssertion { expr, .. } => self.assign_target(expr),
+            _ => self.expr(target),
+        }
+    }
+
+    // -------------------------------------------------------------------------
+    // Expressions
+    // -------------------------------------------------------------------------
+
+    fn expr(&mut self, expr: &mut Expr) {
+        match &mut expr.kind {
+            ExprKind::Array(elements) => {
+                for element in elements.iter_mut().flatten() {
+                    self.expr(element);
+                }
+            }
+            ExprKind::Object(properties) => {
+                for property in properties {
+                    self.property_key(&mut property.key);
+                    self.expr(&mut property.value);
+                }
+            }
+            ExprKind::Function(func) => {
+                if let Some(name) = &func.name {
+                    self.declare(name);
+                }
+                self.function(func);
+            }
+            ExprKind::Arrow(arrow) => {
+                self.params(&mut arrow.params);
+                match &mut arrow.body {
+                    ArrowBody::Expr(body) => self.expr(body),
+                    ArrowBody::Block(stmts) => {
+                        self.function_depth += 1;
+                        self.stmts(stmts, true);
+                        self.function_depth -= 1;
+                    }
+                }
+            }
+            ExprKind::Class(class) => {
+                if let Some(name) = &class.name {
+                    self.declare(name);
+                }
+                self.class(class);
+            }
+            ExprKind::Unary {
+                op: UnaryOp::Delete,
+                arg,
+            } => self.callee(arg),
+            ExprKind::Unary { arg, .. }
+            | ExprKind::Spread(arg)
+            | ExprKind::Await(arg)
+            | ExprKind::Import(arg) => self.expr(arg),
+            ExprKind::Binary { left, right, .. } => {
+                self.expr(left);
+                self.expr(right);
+            }
+            ExprKind::Assign { left, right, .. } => {
+                self.assign_target(left);
+                self.expr(right);
+            }
+            ExprKind::Update { arg, .. } => self.assign_target(arg),
+            ExprKind::Conditional {
+                test,
+                consequent,
+                alternate,
+            } => {
+                self.expr(test);
+                self.expr(consequent);
+                self.expr(alternate);
+            }
+            ExprKind::Sequence(exprs) => {
+                for expr in exprs {
+                    self.expr(expr);
+                }
+            }
+            ExprKind::Member {
+                object,
+                property,
+                computed,
+            }
+            | ExprKind::OptionalMember {
+                object,
+                property,
+                computed,
+            } => {
+                self.expr(object);
+                if *computed {
+                    self.expr(property);
+                }
+            }
+            ExprKind::Call { callee, args } | ExprKind::OptionalCall { callee, args } => {
+                if self.collecting
+                    && matches!(&callee.kind, ExprKind::Ident(name) if name == "eval")
+                {
+                    self.dynamic_scope = true;
+                }
+                self.callee(callee);
+                for arg in args {
+                    self.expr(arg);
+                }
+            }
+            ExprKind::New { callee, args } => {
+                self.expr(callee);
+                for arg in args {
+                    self.expr(arg);
+                }
+            }
+            ExprKind::TaggedTemplate { tag, quasi } => {
+                self.callee(tag);
+                self.expr(quasi);
+            }
+            ExprKind::Template { exprs, .. } => {
+                for expr in exprs {
+                    self.expr(expr);
+                }
+            }
+            ExprKind::Yield { arg, .. } => {
+                if let Some(arg) = arg {
+                    self.expr(arg);
+                }
+            }
+            ExprKind::Null
+            | ExprKind::Bool(_)
+            | ExprKind::Number(_)
+            | ExprKind::BigInt(_)
+            | ExprKind::String(_)
+            | ExprKind::Regex { .. }
+            | ExprKind::TemplateNoSub(_)
+            | ExprKind::This
+            | ExprKind::Super
+            | ExprKind::MetaProperty { .. } => {}
+            ExprKind::Ident(name) => self.read(name),
+
+            #[cfg(feature = "jsx")]
+            ExprKind::JsxElement(element) => self.jsx_element(element),
+            #[cfg(feature = "jsx")]
+            ExprKind::JsxFragment(fragment) => self.jsx_children(&mut fragment.children),
+
+            #[cfg(feature = "typescript")]
+            ExprKind::TsAs { expr, .. }
+            | ExprKind::TsSatisfies { expr, .. }
+            | ExprKind::TsNonNull(expr)
+            | ExprKind::TsTypeAssertion { expr, .. } => self.expr(expr),
+        }
+        if !self.collecting {
+            self.fold_expr(expr);
+        }
+    }
+
+    /// Fold an expression whose value is called or deleted. Folding
+    /// `(0 || a.b)()` to `a.b()` would change `this`, so a member expression
+    /// that appears by folding is written `(0, a.b)`.
+    fn callee(&mut self, callee: &mut Expr) {
+        let was_member = matches!(
+            callee.kind,
+            ExprKind::Member { .. } | ExprKind::OptionalMember { .. }
+        );
+        self.expr(callee);
+        if !was_member
+            && matches!(
+                callee.kind,
+                ExprKind::Member { .. } | ExprKind::OptionalMember { .. }
+            )
+        {
+            let span = callee.span;
+            let member = std::mem::replace(callee, Expr::new(ExprKind::Null, span));
+            *callee = Expr::new(
+                ExprKind::Sequence(vec![Expr::new(ExprKind::Number(0.0), span), member]),
+                span,
+            );
+        }
+    }
+
+    #[cfg(feature = "jsx")]
+    fn jsx_element(&mut self, element: &mut JsxElement) {
+        match &element.opening.name {
+            JsxElementName::Ident(name) => self.read(name),
+            JsxElementName::MemberExpr(parts) => {
+                if let Some(object) = parts.first() {
+                    self.read(object);
+                }
+            }
+            JsxElementName::NamespacedName { .. } => {}
+        }
+        for attribute in &mut element.opening.attributes {
+            match attribute {
+                JsxAttribute::Attribute {
+                    value: Some(JsxAttrValue::Expr(expr)),
+                    ..
+                }
+                | JsxAttribute::SpreadAttribute { argument: expr, .. } => self.expr(expr),
+                JsxAttribute::Attribute {
+                    value: Some(JsxAttrValue::Element(element)),
+                    ..
+                } => self.jsx_element(element),
+                JsxAttribute::Attribute {
+                    value: Some(JsxAttrValue::Fragment(fragment)),
+                    ..
+                } => self.jsx_children(&mut fragment.children),
+                JsxAttribute::Attribute { .. } => {}
+            }
+        }
+        self.jsx_children(&mut element.children);
+    }
+
+    #[cfg(feature = "jsx")]
+    fn jsx_children(&mut self, children: &mut [JsxChild]) {
+        for child in children {
+            match child {
+                JsxChild::Expr(expr) | JsxChild::Spread(expr) => self.expr(expr),
+                JsxChild::Element(element) => self.jsx_element(element),
+                JsxChild::Fragment(fragment) => self.jsx_children(&mut fragment.children),
+                JsxChild::Text(_) => {}
+            }
+        }
+    }
+
+    /// Rewrite `expr` itself; its children are already folded.
+    fn fold_expr(&mut self, expr: &mut Expr) {
+        let span = expr.span;
+        let folded = match &mut expr.kind {
+            ExprKind::Unary { op, arg } => self.fold_unary(*op, arg, span),
+            ExprKind::Binary { op, left, right } => self.fold_binary(*op, left, right, span),
+            ExprKind::Conditional {
+                test,
+                consequent,
+                alternate,
+            } => self.truthiness(test).map(|truthy| {
+                let taken = if truthy { consequent } else { alternate };
+                std::mem::replace(&mut **taken, Expr::new(ExprKind::Null, span))
+            }),
+            _ => None,
+        };
+        if let Some(folded) = folded {
+            *expr = folded;
+            self.changed = true;
+        }
+    }
+
+    fn fold_unary(&self, op: UnaryOp, arg: &Expr, span: Span) -> Option<Expr> {
+        match op {
+            // `!0` and `!1` are already the shortest booleans
+            UnaryOp::Not if !matches!(arg.kind, ExprKind::Number(_)) => self
+                .truthiness(arg)
+                .map(|truthy| Expr::new(ExprKind::Bool(!truthy), span)),
+            UnaryOp::Typeof => {
+                let ty = match &arg.kind {
+                    ExprKind::Ident(name) => self.known_type(name),
+                    ExprKind::Function(_) | ExprKind::Arrow(_) => Some("function"),
+                    ExprKind::Regex { .. } => Some("object"),
+                    _ => self.value(arg).map(|value| value.type_of()),
+                }?;
+                Some(Expr::new(ExprKind::String(ty.to_string()), span))
+            }
+            UnaryOp::Plus | UnaryOp::Minus | UnaryOp::BitNot => {
+                // `-1` is how negative literals are written
+                if op == UnaryOp::Minus && matches!(arg.kind, ExprKind::Number(_)) {
+                    return None;
+                }
+                let n = match self.value(arg)? {
+                    Value::Number(n) => n,
+                    Value::Bool(b) => f64::from(u8::from(b)),
+                    Value::Null => 0.0,
+                    _ => return None,
+                };
+                number_expr(
+                    match op {
+                        UnaryOp::Plus => n,
+                        UnaryOp::Minus => -n,
+                        _ => f64::from(!to_int32(n)),
+                    },
+                    span,
+                )
+            }
+            UnaryOp::Void
+                if !matches!(arg.kind, ExprKind::Number(n) if n == 0.0) && self.is_pure(arg) =>
+            {
+                Value::Undefined.into_expr(span)
+            }
+            _ => None,
+        }
+    }
+
+    fn fold_binary(
+        &self,
+        op: BinaryOp,
+        left: &mut Box<Expr>,
+        right: &mut Box<Expr>,
+        span: Span,
+    ) -> Option<Expr> {
+        let take = |expr: &mut Box<Expr>| {
+            Some(std::mem::replace(
+                &mut **expr,
+                Expr::new(ExprKind::Null, span),
+            ))
+        };
+        match op {
+            BinaryOp::And => match self.truthiness(left)? {
+                true => take(right),
+                false => take(left),
+            },
+            BinaryOp::Or => match self.truthiness(left)? {
+                true => take(left),
+                false => take(right),
+            },
+            BinaryOp::NullishCoalesce => {
+                let nullish = match self.value(left) {
+                    Some(value) => matches!(value, Value::Null | Value::Undefined),
+                    None if self.truthiness(left) == Some(true) => false,
+                    None => return None,
+                };
+                if nullish {
+                    take(right)
+                } else {
+                    take(left)
+                }
+            }
+            _ => {
+                let value = eval_binary(op, &self.value(left)?, &self.value(right)?)?;
+                value.into_expr(span)
+            }
+        }
+    }
+
+    /// The value `expr` evaluates to, when it's a known primitive and
+    /// evaluating it has no side effects.
+    fn value(&self, expr: &Expr) -> Option<Value> {
+        match &expr.kind {
+            ExprKind::Null => Some(Value::Null),
+            ExprKind::Bool(b) => Some(Value::Bool(*b)),
+            ExprKind::Number(n) => Some(Value::Number(*n)),
+            ExprKind::String(s) => Some(Value::String(s.clone())),
+            // Template text is raw; only use it when there's nothing to cook
+            ExprKind::TemplateNoSub(s) if !s.contains('\\') => Some(Value::String(s.clone())),
+            ExprKind::Unary {
+                op: UnaryOp::Minus,
+                arg,
+            } => match arg.kind {
+                ExprKind::Number(n) => Some(Value::Number(-n)),
+                _ => None,
+            },
+            ExprKind::Unary {
+                op: UnaryOp::Not,
+                arg,
+            } => self.truthiness(arg).map(|truthy| Value::Bool(!truthy)),
+            ExprKind::Unary {
+                op: UnaryOp::Void,
+                arg,
+            } if self.is_pure(arg) => Some(Value::Undefined),
+            ExprKind::Ident(name) if name == "undefined" && self.is_global(name) => {
+                Some(Value::Undefined)
+            }
+            _ => None,
+        }
+    }
+
+    /// Whether `expr` is truthy, when that's known and evaluating it has no
+    /// side effects.
+    fn truthiness(&self, expr: &Expr) -> Option<bool> {
+        if let Some(value) = self.value(expr) {
+            return Some(value.truthy());
+        }
+        match &expr.kind {
+            ExprKind::Function(_) | ExprKind::Arrow(_) | ExprKind::Regex { .. } => Some(true),
+            ExprKind::Array(_) | ExprKind::Object(_) if self.is_pure(expr) => Some(true),
+            // `typeof window !== "undefined" && false`
+            ExprKind::Binary {
+                op: op @ (BinaryOp::And | BinaryOp::Or),
+                left,
+                right,
+            } => {
+                let short_circuit = *op == BinaryOp::Or;
+                match self.truthiness(left) {
+                    Some(truthy) if truthy == short_circuit => Some(truthy),
+                    Some(_) => self.truthiness(right),
+                    None => self
+                        .truthiness(right)
+                        .filter(|truthy| *truthy == short_circuit && self.is_pure(left)),
+                }
+            }
+            _ => None,
+        }
+    }
+
+    /// Whether `expr` is pure and evaluates to a primitive, so operators
+    /// can't call `valueOf` or `toString` on it.
+    fn is_pure_primitive(&self, expr: &Expr) -> bool {
+        let primitive = match &expr.kind {
+            ExprKind::Unary { op, .. } => {
+                matches!(op, UnaryOp::Typeof | UnaryOp::Not | UnaryOp::Void)
+            }
+            ExprKind::Binary { op, .. } => matches!(
+                op,
+                BinaryOp::StrictEq
+                    | BinaryOp::StrictNotEq
+                    | BinaryOp::Eq
+                    | BinaryOp::NotEq
+                    | BinaryOp::Lt
+                    | BinaryOp::LtEq
+                    | BinaryOp::Gt
+                    | BinaryOp::GtEq
+            ),
+            ExprKind::Template { .. } => true,
+            _ => self.value(expr).is_some(),
+        };
+        primitive && self.is_pure(expr)
+    }
+
+    /// Whether evaluating `expr` can't have side effects (or throw).
+    fn is_pure(&self, expr: &Expr) -> bool {
+        match &expr.kind {
+            ExprKind::Null
+            | ExprKind::Bool(_)
+            | ExprKind::Number(_)
+            | ExprKind::BigInt(_)
+            | ExprKind::String(_)
+            | ExprKind::Regex { .. }
+            | ExprKind::TemplateNoSub(_)
+            | ExprKind::This
+            | ExprKind::Function(_)
+            | ExprKind::Arrow(_)
+            | ExprKind::MetaProperty { .. } => true,
+            ExprKind::Ident(name) => {
+                self.declarations.contains_key(name)
+                    || (name == "undefined" && self.is_global(name))
+            }
+            // Interpolating an object could call its `toString`
+            ExprKind::Template { exprs, .. } => exprs.iter().all(|expr| self.value(expr).is_some()),
+            ExprKind::Array(elements) => elements.iter().flatten().all(|element| {
+                !matches!(element.kind, ExprKind::Spread(_)) && self.is_pure(element)
+            }),
+            ExprKind::Object(properties) => properties.iter().all(|property| {
+                let key_pure = match &property.key {
+                    PropertyKey::Computed(key) => self.value(key).is_some(),
+                    _ => true,
+                };
+                key_pure
+                    && !matches!(property.value.kind, ExprKind::Spread(_))
+                    && self.is_pure(&property.value)
+            }),
+            ExprKind::Unary { op, arg } => match op {
+                UnaryOp::Delete => false,
+                // `typeof` never throws for an undeclared name
+                UnaryOp::Typeof => matches!(arg.kind, ExprKind::Ident(_)) || self.is_pure(arg),
+                UnaryOp::Not | UnaryOp::Void => self.is_pure(arg),
+                // Converting an object to a number could call `valueOf`
+                UnaryOp::Minus | UnaryOp::Plus | UnaryOp::BitNot => self.value(arg).is_some(),
+            },
+            ExprKind::Binary { op, left, right } => match op {
+                BinaryOp::And | BinaryOp::Or | BinaryOp::NullishCoalesce => {
+                    self.is_pure(left) && self.is_pure(right)
+                }
+                BinaryOp::In | BinaryOp::Instanceof => false,
+                // Strict equality never converts its operands
+                BinaryOp::StrictEq | BinaryOp::StrictNotEq => {
+                    self.is_pure(left) && self.is_pure(right)
+                }
+                _ => self.is_pure_primitive(left) && self.is_pure_primitive(right),
+            },
+            ExprKind::Conditional {
+                test,
+                consequent,
+                alternate,
+            } => self.is_pure(test) && self.is_pure(consequent) && self.is_pure(alternate),
+            ExprKind::Sequence(exprs) => exprs.iter().all(|expr| self.is_pure(expr)),
+            #[cfg(feature = "typescript")]
+            ExprKind::TsAs { expr, .. }
+            | ExprKind::TsSatisfies { expr, .. }
+            | ExprKind::TsNonNull(expr)
+            | ExprKind::TsTypeAssertion { expr, .. } => self.is_pure(expr),
+            // Annotated calls can go, but their arguments still run
+            ExprKind::Call { args, .. } | ExprKind::New { args, .. }
+                if self.pure_calls.binary_search(&expr.span.start).is_ok() =>
+            {
+                args.iter()
+                    .all(|arg| !matches!(arg.kind, ExprKind::Spread(_)) && self.is_pure(arg))
+            }
+            _ => false,
+        }
+    }
+}
+
+// =============================================================================
+// Helpers
+// =============================================================================
+
+/// `typeof` of a `const` initialized with `init`, when the syntax fixes it.
+fn literal_type(init: &Expr) -> Option<&'static str> {
+    Some(match &init.kind {
+        ExprKind::Null | ExprKind::Regex { .. } | ExprKind::Array(_) | ExprKind::Object(_) => {
+            "object"
+        }
+        ExprKind::Bool(_)
+        | ExprKind::Unary {
+            op: UnaryOp::Not, ..
+        } => "boolean",
+        ExprKind::Number(_) => "number",
+        ExprKind::BigInt(_) => "bigint",
+        ExprKind::String(_)
+        | ExprKind::TemplateNoSub(_)
+        | ExprKind::Template { .. }
+        | ExprKind::Unary {
+            op: UnaryOp::Typeof,
+            ..
+        } => "string",
+        ExprKind::Function(_) | ExprKind::Arrow(_) | ExprKind::Class(_) => "function",
+        ExprKind::Unary {
+            op: UnaryOp::Void, ..
+        } => "undefined",
+        _ => return None,
+    })
+}
+
+/// Whether a statement declares a block-scoped name.
+fn is_lexical(stmt: &Stmt) -> bool {
+    match &stmt.kind {
+        StmtKind::Var { kind, .. } => *kind != VarKind::Var,
+        StmtKind::Function(_) | StmtKind::Class(_) => true,
+        #[cfg(feature = "typescript")]
+        StmtKind::TsEnum(_) | StmtKind::TsNamespace(_) | StmtKind::TsDeclare(_) => true,
+        _ => false,
+    }
+}
+
+/// `var a, b;` — what [`var_decl`] leaves of removed code.
+fn is_bare_var(stmt: &Stmt) -> bool {
+    matches!(
+        &stmt.kind,
+        StmtKind::Var { kind: VarKind::Var, decls }
+            if decls.iter().all(|decl| decl.init.is_none())
+    )
+}
+
+/// Names declared with `var` in `stmt`, outside nested functions.
+fn var_names(stmt: &Stmt, out: &mut Vec<String>) {
+    let var_decls = |decls: &[VarDeclarator], out: &mut Vec<String>| {
+        for decl in decls {
+            binding_names(&decl.binding, out);
+        }
+    };
+    match &stmt.kind {
+        StmtKind::Var {
+            kind: VarKind::Var,
+            decls,
+        } => var_decls(decls, out),
+        StmtKind::Block(stmts) => {
+            for stmt in stmts {
+                var_names(stmt, out);
+            }
+        }
+        StmtKind::If {
+            consequent,
+            alternate,
+            ..
+        } => {
+            var_names(consequent, out);
+            if let Some(alternate) = alternate {
+                var_names(alternate, out);
+            }
+        }
+        StmtKind::For { init, body, .. } => {
+            if let Some(ForInit::Var {
+                kind: VarKind::Var,
+                decls,
+            }) = init
+            {
+                var_decls(decls, out);
+            }
+            var_names(body, out);
+        }
+        StmtKind::ForIn { left, body, .. } | StmtKind::ForOf { left, body, .. } => {
+            if let ForInit::Var {
+                kind: VarKind::Var,
+                decls,
+            } = left
+            {
+                var_decls(decls, out);
+            }
+            var_names(body, out);
+        }
+        StmtKind::While { body, .. }
+        | StmtKind::DoWhile { body, .. }
+        | StmtKind::Labeled { body, .. }
+        | StmtKind::With { body, .. } => var_names(body, out),
+        StmtKind::Switch { cases, .. } => {
+            for stmt in cases.iter().flat_map(|case| &case.consequent) {
+                var_names(stmt, out);
+            }
+        }
+        StmtKind::Try {
+            block,
+            handler,
+            finalizer,
+        } => {
+            let handler = handler.iter().flat_map(|handler| &handler.body);
+            for stmt in block
+                .iter()
+                .chain(handler)
+                .chain(finalizer.iter().flatten())
+            {
+                var_names(stmt, out);
+            }
+        }
+        _ => {}
+    }
+}
+
+fn binding_names(binding: &Binding, out: &mut Vec<String>) {
+    match &binding.kind {
+        BindingKind::Ident { name, .. } => {
+            if !out.contains(name) {
+                out.push(name.clone());
+            }
+        }
+        BindingKind::Array { elements, .. } => {
+            for element in elements.iter().flatten() {
+                binding_names(&element.binding, out);
+            }
+        }
+        BindingKind::Object { properties, .. } => {
+            for property in properties {
+                binding_names(&property.value, out);
+            }
+        }
+    }
+}
+
+/// `var a, b;`
+fn var_decl(names: Vec<String>, span: Span) -> Stmt {
+    let decls = names
+        .into_iter()
+        .map(|name| VarDeclarator {
+            binding: Binding::new(
+                BindingKind::Ident {
+                    name,
+                    #[cfg(feature = "typescript")]
+                    type_ann: None,
+                },
+                span,
+            ),
+            init: None,
+            span,
+        })
+        .collect();
+    Stmt::new(
+        StmtKind::Var {
+            kind: VarKind::Var,
+            decls,
+        },
+        span,
+    )
+}
+
+// =============================================================================
+// Tests
+// =============================================================================
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{Codegen, CodegenOptions, Parser, ParserOptions};
+
+    fn fold_source(source: &str) -> String {
+        let mut ast = Parser::new(source, ParserOptions::default())
+            .parse()
+            .unwrap();
+        fold(&mut ast);
+        let options = CodegenOptions {
+            minify: true,
+            ..Default::default()
+        };
+        Codegen::new(&ast, options).generate()
+    }
+
+    #[test]
+    fn test_fold_literals() {
+        assert_eq!(
+            fold_source("x(60 * 60 * 1000, 'v' + 2, 1 / 3, 2 - 5, 1 << 4, '' + null);"),
+            "x(3600000,\"v2\",1/3,-3,16,\"null\")"
+        );
+        assert_eq!(
+            fold_source("x(1 === 1, 'a' != 'b', null == void 0, 2 > 10, 'a' < 'b');"),
+            "x(true,true,true,false,true)"
+        );
+        assert_eq!(
+            fold_source("x((-1).toFixed(), a - -1);"),
+            "x((-1).toFixed(),a- -1)"
+        );
+    }
+
+    #[test]
+    fn test_fold_typeof() {
+        assert_eq!(
+            fold_source(
+                "function f() {}\nconst n = 1;\nlet m = 1;\nconst r = 1;\nr;\nx(typeof f, typeof n, typeof m, typeof 'a', typeof window);"
+            ),
+            "function f(){}const n=1;let m=1;const r=1;x(\"function\",\"number\",typeof m,\"string\",typeof window)"
+        );
+        // Declared twice or reassigned: unknown
+        assert_eq!(
+            fold_source("const a = 1;\nfunction g(a) { return typeof a; }\nfunction h() {}\nh = 1;\nx(typeof h);"),
+            "const a=1;function g(a){return typeof a;}function h(){}h=1;x(typeof h)"
+        );
+    }
+
+    #[test]
+    fn test_fold_branches() {
+        assert_eq!(
+            fold_source(
+                "if (\"production\" !== \"production\") { dev(); } else { prod(); }\nif (false) { var hoisted = 1; }\nwhile (0) { spin(); }"
+            ),
+            "prod();var hoisted"
+        );
+        assert_eq!(
+            fold_source("x(true ? a : b, null ?? c, 0 || d, 1 && e, !'' );"),
+            "x(a,c,d,e,true)"
+        );
+        assert_eq!(
+            fold_source("if (typeof window < 'u' && false) a(); else b();"),
+            "b()"
+        );
+        // `this` stays undefined when folding leaves a method call
+        assert_eq!(fold_source("(0 || o.m)();"), "(0,o.m)()");
+        assert_eq!(
+            fold_source("if (true) { let scoped = 1; use(scoped); }"),
+            "{let scoped=1;use(scoped);}"
+        );
+    }
+
+    #[test]
+    fn test_drop_dead_code() {
+        assert_eq!(
+            fold_source(
+                "function f() {\n  'use strict';\n  return 1;\n  var a = 2;\n  g();\n  function g() {}\n}"
+            ),
+            "function f(){\"use strict\";return 1;function g(){}var a;}"
+        );
+        assert_eq!(
+            fold_source("let x;\n1;\n'str';\n(function () {});\nx, 2, y();\nundeclared;\n"),
+            "let x;y();undeclared"
+        );
+    }
+
+    #[test]
+    fn test_pure_annotations() {
+        assert_eq!(
+            fold_source(
+                "function f() {\n  const unused = /* @__PURE__ */ create(1);\n  const used = /*#__PURE__*/ create();\n  /* @__PURE__ */ g();\n  /* @__PURE__ */ g(sideEffect());\n  create();\n  return used;\n}\nconst top = /* @__PURE__ */ make();"
+            ),
+            "function f(){const used=/*@__PURE__*/create();/*@__PURE__*/g(sideEffect());create();return used;}const top=/*@__PURE__*/make()"
+        );
+    }
+
+    #[test]
+    fn test_fold_reports_unchanged() {
+        let mut ast = Parser::new("f(a + 1);", ParserOptions::default())
+            .parse()
+            .unwrap();
+        assert!(!fold(&mut ast));
+    }
+}
@@ -0,0 +1,228 @@
+//! Source formatter.
+//!
+//! [`format`] reprints a module in one canonical layout: two-space
+//! indentation, double quotes, semicolons, trailing commas in multi-line
+//! lists and at most one blank line between statements. A bracketed list
+//! stays on one line unless the source broke the line after its opening
+//! bracket. The output is re-parsed and must produce the same AST, tokens
+//! and comments; otherwise the source is left alone and an error says why.
+
+use crate::ast::{Ast, CommentKind, Stmt};
+use crate::codegen::{Codegen, CodegenOptions};
+use crate::lexer::Lexer;
+use crate::parser::{ParseError, Parser, ParserOptions};
+use crate::span::Span;
+use crate::token::TokenKind;
+
+/// A source that can't be formatted.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct FormatError {
+    pub message: String,
+    pub span: Span,
+}
+
+impl std::fmt::Display for FormatError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "{} at {}..{}",
+            self.message, self.span.start, self.span.end
+        )
+    }
+}
+
+impl std::error::Error for FormatError {}
+
+impl From<ParseError> for FormatError {
+    fn from(e: ParseError) -> Self {
+        Self {
+            message: e.message,
+            span: e.span,
+        }
+    }
+}
+
+/// Format `source`. Returns the formatted text, which ends with a single
+/// newline (or is empty).
+pub fn format(source: &str, options: ParserOptions) -> Result<String, FormatError> {
+    let source = source.replace("\r\n", "\n");
+    // Keep a `#!` line, blanked out so offsets stay put
+    let shebang_len = if source.starts_with("#!") {
+        source.find('\n').unwrap_or(source.len())
+    } else {
+        0
+    };
+    let body = format!("{}{}", " ".repeat(shebang_len), &source[shebang_len..]);
+
+    let ast = Parser::new(&body, options.clone()).parse_with_comments()?;
+    let formatted = Codegen::new(&ast, CodegenOptions::default())
+        .generate_formatted()
+        .map_err(|(span, reason)| FormatError {
+            message: format!("can't format: {reason}"),
+            span,
+        })?;
+
+    let reparsed = Parser::new(&formatted, options)
+        .parse_with_comments()
+        .map_err(|e| FormatError {
+            message: format!("formatting produced invalid code ({})", e.message),
+            span: Span::new(0, 0),
+        })?;
+    // The AST doesn't keep every TypeScript detail, so compare tokens too
+    if ast_fingerprint(&ast.stmts) != ast_fingerprint(&reparsed.stmts)
+        || significant_tokens(&body) != significant_tokens(&formatted)
+        || comment_texts(&ast) != comment_texts(&reparsed)
+    {
+        return Err(FormatError {
+            message: "can't format without changing the code".to_string(),
+            span: Span::new(0, 0),
+        });
+    }
+
+    if shebang_len == 0 {
+        return Ok(formatted);
+    }
+    Ok(format!("{}\n{formatted}", &source[..shebang_len]))
+}
+
+/// The AST's debug form without spans and stray `;`, which formatting
+/// changes.
+fn ast_fingerprint(stmts: &[Stmt]) -> String {
+    let debug = format!("{stmts:?}");
+    let mut out = String::with_capacity(debug.len());
+    let mut rest = debug.as_str();
+    while let Some(i) = rest.find("Span { start: ") {
+        out.push_str(&rest[..i]);
+        out.push_str("Span");
+        rest = &rest[i..];
+        rest = &rest[rest.find('}').map_or(rest.len(), |end| end + 1)..];
+    }
+    out.push_str(rest);
+    [
+        "Stmt { kind: Empty, span: Span }",
+        "ClassMember { kind: Empty, decorators: [], span: Span }",
+    ]
+    .iter()
+    .fold(out, |out, empty| {
+        out.replace(&format!("{empty}, "), "")
+            .replace(&format!(", {empty}"), "")
+            .replace(empty, "")
+    })
+}
+
+/// Tokens without the `;`, parentheses and trailing commas formatting adds
+/// or removes.
+fn significant_tokens(source: &str) -> Vec<TokenKind> {
+    let mut lexer = Lexer::new(source);
+    let mut tokens = Vec::new();
+    loop {
+        let token = lexer.next_token();
+        if token.kind == TokenKind::Eof {
+            break;
+        }
+        tokens.push(token.kind);
+    }
+    let mut significant = Vec::with_capacity(tokens.len());
+    for (i, kind) in tokens.iter().enumerate() {
+        let trailing_comma = *kind == TokenKind::Comma
+            && matches!(
+                tokens.get(i + 1),
+                Some(TokenKind::RBrace | TokenKind::RBracket | TokenKind::RParen)
+            );
+        if !trailing_comma
+            && !matches!(
+                kind,
+                TokenKind::Semicolon | TokenKind::LParen | TokenKind::RParen
+            )
+        {
+            significant.push(kind.clone());
+        }
+    }
+    significant
+}
+
+/// Comment texts, with doc-comment lines trimmed (they're re-indented).
+fn comment_texts(ast: &Ast) -> Vec<String> {
+    ast.comments
+        .iter()
+        .map(|comment| {
+            let text = &ast.source[comment.span.start as usize..comment.span.end as usize];
+            match comment.kind {
+                CommentKind::Line => text.trim_end().to_string(),
+                CommentKind::Block => text.lines().map(str::trim).collect::<Vec<_>>().join("\n"),
+            }
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn fmt(source: &str) -> String {
+        format(source, ParserOptions::default()).unwrap()
+    }
+
+    fn fmt_ts(source: &str) -> String {
+        let options = ParserOptions {
+            typescript: true,
+            ..ParserOptions::default()
+        };
+        format(source, options).unwrap()
+    }
+
+    #[test]
+    fn test_format_layout() {
+        assert_eq!(
+            fmt("let a='x'\nif(a){b()}else c()\n\n\n\nfunction f(x,y){return x+y}"),
+            "let a = \"x\";\nif (a) {\n  b();\n} else c();\n\nfunction f(x, y) {\n  return x + y;\n}\n"
+        );
+    }
+
+    #[test]
+    fn test_format_expands_broken_lists() {
+        assert_eq!(fmt("const o = {a: 1, b: 2}"), "const o = { a: 1, b: 2 };\n");
+        assert_eq!(
+            fmt("const o = {\na: 1, b: [1,2]}"),
+            "const o = {\n  a: 1,\n  b: [1, 2],\n};\n"
+        );
+    }
+
+    #[test]
+    fn test_format_keeps_comments() {
+        let out = fmt(
+            "// lead\nlet a = 1 // trailing\n/**\n   * Doc\n   */\nfunction f() { /* inner */ }\n",
+        );
+        assert_eq!(
+            out,
+            "// lead\nlet a = 1; // trailing\n/**\n * Doc\n */\nfunction f() {\n  /* inner */\n}\n"
+        );
+    }
+
+    #[test]
+    fn test_format_is_idempotent() {
+        let source = "#!/usr/bin/env node\nimport {a,b} from './x'\nexport default class A extends B { static x = 1; m() { return (1, 2) } }\n";
+        let once = fmt(source);
+        assert!(once.starts_with("#!/usr/bin/env node\nimport { a, b } from \"./x\";\n"));
+        assert_eq!(fmt(&once), once);
+    }
+
+    #[test]
+    fn test_format_typescript() {
+        let out = fmt_ts("const f = <T,>(x: T): T => x!\nlet v = <any>w\nclass C { private x?: number; constructor(public y: string) {} }");
+        assert_eq!(
+            out,
+            "const f = <T,>(x: T): T => x!;\nlet v = <any>w;\nclass C {\n  private x?: number;\n  constructor(public y: string) {}\n}\n"
+        );
+        assert_eq!(fmt_ts(&out), out);
+        assert_eq!(
+            fmt_ts("function g({a}: P, [b]?: Q) {}"),
+            "function g({ a }: P, [b]?: Q) {}\n"
+        );
+    }
+
+    #[test]
+    fn test_format_refuses_parse_errors() {
+        assert!(format("let = ;", ParserOptions::default()).is_err());
+    }
+}
@@ -4,6 +4,7 @@
 //! It's called on-demand by the parser, not upfront, which enables
 //! context-sensitive tokenization (e.g., regex vs division).
 
+use crate::ast::{Comment, CommentKind};
 use crate::span::Span;
 use crate::token::{keyword_from_str, Token, TokenKind};
 
@@ -21,6 +22,17 @@ pub struct Lexer<'a> {
     pub(crate) allow_regex: bool,
     /// Whether a newline was encountered while skipping whitespace before the current token.
     pub(crate) had_newline: bool,
+    /// Whether skipped comments are recorded in `comments`.
+    pub(crate) collect_comments: bool,
+    /// Comments skipped so far (when `collect_comments` is set). Part of the
+    /// lexer state, so restoring a saved lexer also drops comments re-scanned
+    /// after the save point.
+    pub(crate) comments: Vec<Comment>,
+    /// Start offsets of tokens preceded by a `/* @__PURE__ */` annotation.
+    /// Always collected.
+    pub(crate) pure_annotations: Vec<u32>,
+    /// A pure annotation was skipped and applies to the next token.
+    pending_pure: bool,
 }
 
 impl<'a> Lexer<'a> {
@@ -43,6 +55,10 @@ impl<'a> Lexer<'a> {
             token_start: pos,
             allow_regex: true, // At start of file, regex is allowed
             had_newline: false,
+            collect_comments: false,
+            comments: Vec::new(),
+            pure_annotations: Vec::new(),
+            pending_pure: false,
         }
     }
 
@@ -63,6 +79,11 @@ impl<'a> Lexer<'a> {
         self.skip_whitespace_and_comments();
         self.token_start = self.pos;
         let had_newline = self.had_newline;
+        if self.pending_pure {
+            // `/* @__PURE__ */ (function() {})()` annotates the call inside
+            self.pending_pure = self.current() == b'(';
+            self.pure_annotations.push(self.pos as u32);
+        }
 
         if self.is_eof() {
             return self.make_token(TokenKind::Eof);
@@ -266,13 +287,16 @@ impl<'a> Lexer<'a> {
     }
 
     fn skip_line_comment(&mut self) {
+        let start = self.pos;
         self.advance_n(2); // Skip //
         while !self.is_eof() && self.current() != b'\n' {
             self.advance();
         }
+        self.record_comment(CommentKind::Line, start);
     }
 
     fn skip_block_comment(&mut self) {
+        let start = self.pos;
         self.advance_n(2); // Skip /*
         while !self.is_eof() {
             if self.current() == b'\n' {
@@ -280,6 +304,7 @@ impl<'a> Lexer<'a> {
             }
             if self.current() == b'*' && self.peek_char() == b'/' {
                 self.advance_n(2);
+                self.record_comment(CommentKind::Block, start);
                 return;
             }
             self.advance();
@@ -287,6 +312,23 @@ impl<'a> Lexer<'a> {
         // Unterminated block comment - will be reported as error during parsing
     }
 
+    fn record_comment(&mut self, kind: CommentKind, start: usize) {
+        let comment = Comment {
+            kind,
+            span: Span::new(start as u32, self.pos as u32),
+        };
+        let text = &self.source[start..self.pos];
+        if text
+            .windows(9)
+            .any(|w| w == b"@__PURE__" || w == b"#__PURE__")
+        {
+            self.pending_pure = true;
+        }
+        if self.collect_comments {
+            self.comments.push(comment);
+        }
+    }
+
     // === Token scanning ===
 
     fn scan_identifier(&mut self) -> TokenKind {
@@ -977,6 +1019,26 @@ mod tests {
         );
     }
 
+    #[test]
+    fn test_collect_comments() {
+        let source = "a // line\nb /* block */ c";
+        let mut lexer = Lexer::new(source);
+        lexer.collect_comments = true;
+        while !matches!(lexer.next_token().kind, TokenKind::Eof) {}
+        let comments: Vec<_> = lexer
+            .comments
+            .iter()
+            .map(|c| (c.kind, &source[c.span.start as usize..c.span.end as usize]))
+            .collect();
+        assert_eq!(
+            comments,
+            vec![
+                (CommentKind::Line, "// line"),
+                (CommentKind::Block, "/* block */"),
+            ]
+        );
+    }
+
     #[test]
     fn test_template_literal_no_sub() {
         assert_eq!(
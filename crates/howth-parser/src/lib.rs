@@ -52,11 +52,27 @@ mod typescript;
 mod jsx;
 
 mod codegen;
+pub mod fold;
+mod format;
 pub mod mangle;
+pub mod visit;
+
+/// Parser crate version. Mixed into build cache keys so upgrades invalidate
+/// transpiled outputs.
+pub const VERSION: &str = env!("CARGO_PKG_VERSION");
+
+/// Enabled parser features that change what the parser accepts or emits.
+pub const FEATURES: &[&str] = &[
+    #[cfg(feature = "typescript")]
+    "typescript",
+    #[cfg(feature = "jsx")]
+    "jsx",
+];
 
 // Re-exports
 pub use ast::*;
-pub use codegen::{Codegen, CodegenOptions};
+pub use codegen::{Codegen, CodegenOptions, DecoratorMode, SourceMapping, Target};
+pub use format::{format, FormatError};
 pub use lexer::Lexer;
 pub use parser::{ParseError, Parser, ParserOptions};
 pub use span::Span;
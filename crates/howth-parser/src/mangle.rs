@@ -37,6 +37,25 @@ pub fn mangle(ast: &mut Ast, options: &MangleOptions) {
     rename_stmts(&mut ast.stmts, &ctx);
 }
 
+/// Replace references to names the program doesn't declare.
+///
+/// Bindings that shadow one of the names are left alone. A replacement is
+/// printed as-is, so it can be any expression (`ns.name`); the bundler
+/// uses this to read imports through their module's exports.
+pub fn rename_free(ast: &mut Ast, renames: &HashMap<String, String>) {
+    let options = MangleOptions::default();
+    let mut ctx = MangleContext::new(&options);
+    ctx.collect_stmts(&ast.stmts, ctx.root_scope);
+
+    let root = &mut ctx.scopes[ctx.root_scope];
+    for (name, replacement) in renames {
+        if !root.bindings.contains(name) {
+            root.renames.insert(name.clone(), replacement.clone());
+        }
+    }
+    rename_stmts(&mut ast.stmts, &ctx);
+}
+
 // =============================================================================
 // Scope Tree
 // =============================================================================
@@ -358,10 +377,16 @@ impl<'a> MangleContext<'a> {
     }
 
     fn collect_class(&mut self, c: &Class, parent_scope: ScopeId) {
+        for decorator in &c.decorators {
+            self.collect_expr(decorator, parent_scope);
+        }
         if let Some(super_class) = &c.super_class {
             self.collect_expr(super_class, parent_scope);
         }
         for member in &c.body {
+            for decorator in &member.decorators {
+                self.collect_expr(decorator, parent_scope);
+            }
             match &member.kind {
                 ClassMemberKind::Method { value, .. } => {
                     self.collect_function(value, parent_scope);
@@ -1102,10 +1127,16 @@ impl<'a> Renamer<'a> {
     }
 
     fn rename_class(&mut self, c: &mut Class) {
+        for decorator in &mut c.decorators {
+            self.rename_expr(decorator);
+        }
         if let Some(super_class) = &mut c.super_class {
             self.rename_expr(super_class);
         }
         for member in &mut c.body {
+            for decorator in &mut member.decorators {
+                self.rename_expr(decorator);
+            }
             match &mut member.kind {
                 ClassMemberKind::Method { value, .. } => {
                     self.rename_function(value);
@@ -1707,4 +1738,21 @@ __modules[1] = function(mod, exp, req) {
         // Function params should be mangled (they're in function scope, not top-level)
         assert!(!result.contains("longVariableName"));
     }
+
+    #[test]
+    fn test_rename_free() {
+        let mut ast = Parser::new(
+            "count++; log({ count }, count);\nfunction f(count) { return count; }\n{ let count = 1; use(count); }",
+            ParserOptions::default(),
+        )
+        .parse()
+        .unwrap();
+        let renames = HashMap::from([("count".to_string(), "ns.count".to_string())]);
+        rename_free(&mut ast, &renames);
+        let result = crate::Codegen::new(&ast, crate::CodegenOptions::default()).generate();
+        assert!(result.contains("ns.count++;"));
+        assert!(result.contains("log({count: ns.count}, ns.count);"));
+        assert!(result.contains("function f(count) {\n  return count;"));
+        assert!(result.contains("use(count);"));
+    }
 }
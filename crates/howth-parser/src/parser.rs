@@ -61,6 +61,14 @@ pub struct Parser<'a> {
     pub(crate) source: &'a str,
     /// When false, `in` is not parsed as a binary operator (for-in init).
     pub(crate) allow_in: bool,
+    /// Decorators parsed ahead of a class declaration, attached by `parse_class`.
+    pub(crate) pending_decorators: Vec<Expr>,
+    /// Type parameters parsed ahead of an arrow, attached by `parse_arrow_body`.
+    #[cfg(feature = "typescript")]
+    pub(crate) pending_arrow_type_params: Option<Vec<TsTypeParam>>,
+    /// Return type parsed ahead of an arrow, attached by `parse_arrow_body`.
+    #[cfg(feature = "typescript")]
+    pub(crate) pending_arrow_return_type: Option<Box<TsType>>,
 }
 
 impl<'a> Parser<'a> {
@@ -74,13 +82,34 @@ impl<'a> Parser<'a> {
             options,
             source,
             allow_in: true,
+            pending_decorators: Vec::new(),
+            #[cfg(feature = "typescript")]
+            pending_arrow_type_params: None,
+            #[cfg(feature = "typescript")]
+            pending_arrow_return_type: None,
         }
     }
 
     /// Parse the entire source into an AST.
     pub fn parse(mut self) -> Result<Ast, ParseError> {
         let stmts = self.parse_program()?;
-        Ok(Ast::new(stmts, self.source.to_string()))
+        let mut ast = Ast::new(stmts, self.source.to_string());
+        ast.pure_annotations = std::mem::take(&mut self.lexer.pure_annotations);
+        Ok(ast)
+    }
+
+    /// Parse the entire source into an AST, also collecting its comments
+    /// into [`Ast::comments`].
+    pub fn parse_with_comments(mut self) -> Result<Ast, ParseError> {
+        // Re-lex from the start so comments before the first token count
+        self.lexer = Lexer::new(self.source);
+        self.lexer.collect_comments = true;
+        self.current = self.lexer.next_token();
+        let stmts = self.parse_program()?;
+        let mut ast = Ast::new(stmts, self.source.to_string());
+        ast.comments = std::mem::take(&mut self.lexer.comments);
+        ast.pure_annotations = std::mem::take(&mut self.lexer.pure_annotations);
+        Ok(ast)
     }
 
     // =========================================================================
@@ -188,13 +217,14 @@ impl<'a> Parser<'a> {
 
         // Decorators: @expr (before class or export)
         if self.check(&TokenKind::At) {
-            // Consume all decorators
-            while self.eat(&TokenKind::At) {
-                // Decorator expression: could be @foo, @foo.bar, @foo(), @foo.bar()
-                let _ = self.parse_left_hand_side_expr()?;
-            }
-            // After decorators, expect class, export, or abstract class
-            return self.parse_stmt();
+            // Decorator expression: could be @foo, @foo.bar, @foo(), @foo.bar()
+            let decorators = self.parse_decorators()?;
+            // After decorators, expect class, export, or abstract class;
+            // `parse_class` picks the decorators up.
+            self.pending_decorators = decorators;
+            let stmt = self.parse_stmt();
+            self.pending_decorators.clear();
+            return stmt;
         }
 
         match self.peek() {
@@ -389,14 +419,20 @@ impl<'a> Parser<'a> {
     /// Parse a variable declarator.
     fn parse_var_declarator(&mut self) -> Result<VarDeclarator, ParseError> {
         let start = self.current.span.start;
-        let binding = self.parse_binding()?;
+        #[allow(unused_mut)]
+        let mut binding = self.parse_binding()?;
 
         // TypeScript: definite assignment `!` and type annotation after binding pattern
         #[cfg(feature = "typescript")]
         if self.options.typescript {
             self.eat(&TokenKind::Bang); // definite assignment assertion: let x!: Type
             if self.eat(&TokenKind::Colon) {
-                let _ = self.parse_ts_type()?;
+                let ty = Some(Box::new(self.parse_ts_type()?));
+                match &mut binding.kind {
+                    BindingKind::Ident { type_ann, .. }
+                    | BindingKind::Array { type_ann, .. }
+                    | BindingKind::Object { type_ann, .. } => *type_ann = ty,
+                }
             }
         }
 
@@ -811,17 +847,12 @@ impl<'a> Parser<'a> {
             }
 
             let rest = self.eat(&TokenKind::Spread);
-            let binding = self.parse_binding()?;
+            #[allow(unused_mut)]
+            let mut binding = self.parse_binding()?;
 
             // TypeScript: optional parameter marker `?` and type annotation after it
             #[cfg(feature = "typescript")]
-            if self.options.typescript {
-                self.eat(&TokenKind::Question);
-                // After `?`, there may be a `: type` annotation (e.g. `x?: number`)
-                if self.eat(&TokenKind::Colon) {
-                    let _ = self.parse_ts_type()?;
-                }
-            }
+            let optional = self.options.typescript && self.parse_ts_optional_param(&mut binding)?;
 
             let default = if self.eat(&TokenKind::Eq) {
                 Some(self.parse_assign_expr()?)
@@ -831,6 +862,8 @@ impl<'a> Parser<'a> {
             let end = self.current.span.start;
 
             params.push(Param {
+                #[cfg(feature = "typescript")]
+                optional,
                 binding,
                 default,
                 rest,
@@ -864,14 +897,10 @@ impl<'a> Parser<'a> {
                 }
             }
             let rest = self.eat(&TokenKind::Spread);
-            let binding = self.parse_binding()?;
+            #[allow(unused_mut)]
+            let mut binding = self.parse_binding()?;
             #[cfg(feature = "typescript")]
-            if self.options.typescript {
-                self.eat(&TokenKind::Question);
-                if self.eat(&TokenKind::Colon) {
-                    let _ = self.parse_ts_type()?;
-                }
-            }
+            let optional = self.options.typescript && self.parse_ts_optional_param(&mut binding)?;
             let default = if self.eat(&TokenKind::Eq) {
                 Some(self.parse_assign_expr()?)
             } else {
@@ -879,6 +908,8 @@ impl<'a> Parser<'a> {
             };
             let end = self.current.span.start;
             params.push(Param {
+                #[cfg(feature = "typescript")]
+                optional,
                 binding,
                 default,
                 rest,
@@ -891,6 +922,42 @@ impl<'a> Parser<'a> {
         Ok(params)
     }
 
+    /// Parse a run of decorators: `@a @b.c @d(e) @(f)`.
+    fn parse_decorators(&mut self) -> Result<Vec<Expr>, ParseError> {
+        let mut decorators = Vec::new();
+        while self.eat(&TokenKind::At) {
+            decorators.push(self.parse_decorator_expr()?);
+        }
+        Ok(decorators)
+    }
+
+    /// Parse the expression after `@`.
+    ///
+    /// Only dotted names with an optional call are allowed unparenthesized,
+    /// so `@dec ["key"]() {}` decorates a computed member.
+    fn parse_decorator_expr(&mut self) -> Result<Expr, ParseError> {
+        let start = self.current.span.start;
+        let mut expr = self.parse_primary_expr()?;
+        if matches!(expr.kind, ExprKind::Ident(_)) {
+            while self.eat(&TokenKind::Dot) {
+                let property = self.parse_member_property()?;
+                let end = self.current.span.start;
+                expr = Expr::new(
+                    ExprKind::Member {
+                        object: Box::new(expr),
+                        property: Box::new(property),
+                        computed: false,
+                    },
+                    Span::new(start, end),
+                );
+            }
+            if self.check(&TokenKind::LParen) {
+                expr = self.parse_call_expr(expr)?;
+            }
+        }
+        Ok(expr)
+    }
+
     /// Parse class declaration.
     pub(crate) fn parse_class_decl(&mut self) -> Result<Stmt, ParseError> {
         let start = self.current.span.start;
@@ -905,6 +972,7 @@ impl<'a> Parser<'a> {
     /// Parse a class.
     fn parse_class(&mut self) -> Result<Class, ParseError> {
         let start = self.current.span.start;
+        let decorators = std::mem::take(&mut self.pending_decorators);
 
         self.expect(&TokenKind::Class)?;
 
@@ -968,6 +1036,7 @@ impl<'a> Parser<'a> {
             name,
             super_class,
             body,
+            decorators,
             span: Span::new(start, end),
             #[cfg(feature = "typescript")]
             type_params,
@@ -978,12 +1047,16 @@ impl<'a> Parser<'a> {
 
     /// Parse a class member.
     fn parse_class_member(&mut self) -> Result<ClassMember, ParseError> {
-        let start = self.current.span.start;
-
         // Decorators on class members: @decorator
-        while self.eat(&TokenKind::At) {
-            let _ = self.parse_left_hand_side_expr()?;
-        }
+        let decorators = self.parse_decorators()?;
+        let mut member = self.parse_class_member_body()?;
+        member.decorators = decorators;
+        Ok(member)
+    }
+
+    /// Parse a class member after its decorators.
+    fn parse_class_member_body(&mut self) -> Result<ClassMember, ParseError> {
+        let start = self.current.span.start;
 
         // TypeScript modifiers: accessibility, abstract, readonly, override
         // Only consume these as modifiers when NOT followed by `:`, `=`, `;`, `?`, `!`, `(`
@@ -1055,6 +1128,7 @@ impl<'a> Parser<'a> {
             self.expect(&TokenKind::RBrace)?;
             return Ok(ClassMember {
                 kind: ClassMemberKind::StaticBlock(stmts),
+                decorators: Vec::new(),
                 span: Span::new(start, end),
             });
         }
@@ -1157,10 +1231,13 @@ impl<'a> Parser<'a> {
                         #[cfg(feature = "typescript")]
                         is_override,
                         #[cfg(feature = "typescript")]
+                        optional: false,
+                        #[cfg(feature = "typescript")]
                         definite: false,
                         #[cfg(feature = "typescript")]
                         type_ann: None,
                     },
+                    decorators: Vec::new(),
                     span: Span::new(start, end),
                 });
             }
@@ -1177,11 +1254,10 @@ impl<'a> Parser<'a> {
 
         // TypeScript: optional `?` or definite `!` marker
         #[cfg(feature = "typescript")]
-        let definite = if self.options.typescript {
-            self.eat(&TokenKind::Question);
-            self.eat(&TokenKind::Bang)
+        let (optional, definite) = if self.options.typescript {
+            (self.eat(&TokenKind::Question), self.eat(&TokenKind::Bang))
         } else {
-            false
+            (false, false)
         };
 
         // Method or property?
@@ -1248,6 +1324,7 @@ impl<'a> Parser<'a> {
                     #[cfg(feature = "typescript")]
                     is_override,
                 },
+                decorators: Vec::new(),
                 span: Span::new(start, end),
             })
         } else {
@@ -1285,8 +1362,11 @@ impl<'a> Parser<'a> {
                     #[cfg(feature = "typescript")]
                     is_override,
                     #[cfg(feature = "typescript")]
+                    optional,
+                    #[cfg(feature = "typescript")]
                     definite,
                 },
+                decorators: Vec::new(),
                 span: Span::new(start, end),
             })
         }
@@ -2173,6 +2253,8 @@ impl<'a> Parser<'a> {
                 self.advance(); // eat identifier
                 self.advance(); // eat =>
                 let param = Param {
+                    #[cfg(feature = "typescript")]
+                    optional: false,
                     binding: Binding::new(
                         BindingKind::Ident {
                             name,
@@ -2467,45 +2549,11 @@ impl<'a> Parser<'a> {
     pub(crate) fn parse_left_hand_side_expr(&mut self) -> Result<Expr, ParseError> {
         let start = self.current.span.start;
 
-        // new expression
-        if self.eat(&TokenKind::New) {
-            // new.target meta property
-            if self.eat(&TokenKind::Dot) {
-                if let TokenKind::Identifier(name) = self.peek() {
-                    let name = name.clone();
-                    self.advance();
-                    let end = self.current.span.start;
-                    return Ok(Expr::new(
-                        ExprKind::MetaProperty {
-                            meta: "new".to_string(),
-                            property: name,
-                        },
-                        Span::new(start, end),
-                    ));
-                }
-            }
-            let callee = self.parse_left_hand_side_expr()?;
-            // TypeScript: consume type arguments on new expression (e.g., `new Map<K, V>()`)
-            #[cfg(feature = "typescript")]
-            if self.options.typescript && self.check(&TokenKind::Lt) {
-                let _ = self.parse_ts_type_args_impl()?;
-            }
-            let args = if self.check(&TokenKind::LParen) {
-                self.parse_arguments()?
-            } else {
-                Vec::new()
-            };
-            let end = self.current.span.start;
-            return Ok(Expr::new(
-                ExprKind::New {
-                    callee: Box::new(callee),
-                    args,
-                },
-                Span::new(start, end),
-            ));
-        }
-
-        let mut expr = self.parse_primary_expr()?;
+        let mut expr = if self.check(&TokenKind::New) {
+            self.parse_new_expr()?
+        } else {
+            self.parse_primary_expr()?
+        };
 
         loop {
             match self.peek() {
@@ -2610,7 +2658,8 @@ impl<'a> Parser<'a> {
                 #[cfg(feature = "typescript")]
                 TokenKind::Bang if self.options.typescript && !self.current.had_newline_before => {
                     self.advance();
-                    // Just strip the `!` — expr is already the inner expression
+                    let end = self.current.span.start;
+                    expr = Expr::new(ExprKind::TsNonNull(Box::new(expr)), Span::new(start, end));
                 }
                 _ => break,
             }
@@ -2619,6 +2668,82 @@ impl<'a> Parser<'a> {
         Ok(expr)
     }
 
+    /// Parse `new Callee(args)`, or `new.target`.
+    ///
+    /// The callee is a member expression: a call ends it, so in
+    /// `new URL(a, b).href` the arguments belong to `new` and `.href` is read
+    /// from the new object.
+    fn parse_new_expr(&mut self) -> Result<Expr, ParseError> {
+        let start = self.current.span.start;
+        self.expect(&TokenKind::New)?;
+
+        // new.target meta property
+        if self.eat(&TokenKind::Dot) {
+            if let TokenKind::Identifier(name) = self.peek() {
+                let name = name.clone();
+                self.advance();
+                let end = self.current.span.start;
+                return Ok(Expr::new(
+                    ExprKind::MetaProperty {
+                        meta: "new".to_string(),
+                        property: name,
+                    },
+                    Span::new(start, end),
+                ));
+            }
+        }
+
+        let callee_start = self.current.span.start;
+        let mut callee = if self.check(&TokenKind::New) {
+            self.parse_new_expr()?
+        } else {
+            self.parse_primary_expr()?
+        };
+        loop {
+            let (property, computed) = match self.peek() {
+                TokenKind::Dot => {
+                    self.advance();
+                    (self.parse_member_property()?, false)
+                }
+                TokenKind::LBracket => {
+                    self.advance();
+                    let property = self.parse_expr()?;
+                    self.expect(&TokenKind::RBracket)?;
+                    (property, true)
+                }
+                _ => break,
+            };
+            let end = self.current.span.start;
+            callee = Expr::new(
+                ExprKind::Member {
+                    object: Box::new(callee),
+                    property: Box::new(property),
+                    computed,
+                },
+                Span::new(callee_start, end),
+            );
+        }
+
+        // TypeScript: consume type arguments on new expression (e.g., `new Map<K, V>()`)
+        #[cfg(feature = "typescript")]
+        if self.options.typescript && self.check(&TokenKind::Lt) {
+            let _ = self.parse_ts_type_args_impl()?;
+        }
+        let args = if self.check(&TokenKind::LParen) {
+            self.parse_arguments()?
+        } else {
+            Vec::new()
+        };
+        let end = self.current.span.start;
+        Ok(Expr::new(
+            ExprKind::New {
+                callee: Box::new(callee),
+                args,
+            },
+            Span::new(start, end),
+        ))
+    }
+
     /// Parse a member property after `.` — any identifier or keyword is valid as a property name.
     fn parse_member_property(&mut self) -> Result<Expr, ParseError> {
         let start = self.current.span.start;
@@ -2824,6 +2949,24 @@ impl<'a> Parser<'a> {
                 ))
             }
 
+            // Decorated class expression: `@dec class {}`
+            TokenKind::At => {
+                self.pending_decorators = self.parse_decorators()?;
+                if !self.check(&TokenKind::Class) {
+                    self.pending_decorators.clear();
+                    return Err(ParseError::new(
+                        "Decorators are only valid on classes",
+                        self.current.span,
+                    ));
+                }
+                let class = self.parse_class()?;
+                let end = class.span.end;
+                Ok(Expr::new(
+                    ExprKind::Class(Box::new(class)),
+                    Span::new(start, end),
+                ))
+            }
+
             // Class expression
             TokenKind::Class => {
                 let class = self.parse_class()?;
@@ -2858,6 +3001,8 @@ impl<'a> Parser<'a> {
                         self.advance(); // eat identifier
                         self.advance(); // eat =>
                         let param = Param {
+                            #[cfg(feature = "typescript")]
+                            optional: false,
                             binding: Binding::new(
                                 BindingKind::Ident {
                                     name,
@@ -3094,9 +3239,11 @@ impl<'a> Parser<'a> {
                     };
                     let params = self.parse_params()?;
                     #[cfg(feature = "typescript")]
-                    if self.options.typescript && self.eat(&TokenKind::Colon) {
-                        let _ = self.parse_ts_type()?;
-                    }
+                    let return_type = if self.options.typescript && self.eat(&TokenKind::Colon) {
+                        Some(Box::new(self.parse_ts_type()?))
+                    } else {
+                        None
+                    };
                     self.expect(&TokenKind::LBrace)?;
                     let mut body = Vec::new();
                     while !self.check(&TokenKind::RBrace) && !self.is_eof() {
@@ -3115,7 +3262,7 @@ impl<'a> Parser<'a> {
                         #[cfg(feature = "typescript")]
                         type_params,
                         #[cfg(feature = "typescript")]
-                        return_type: None,
+                        return_type,
                     };
 
                     properties.push(Property {
@@ -3235,7 +3382,7 @@ impl<'a> Parser<'a> {
             // TypeScript: return type annotation
             #[cfg(feature = "typescript")]
             if self.options.typescript && self.eat(&TokenKind::Colon) {
-                let _ = self.parse_ts_type()?;
+                self.pending_arrow_return_type = Some(Box::new(self.parse_ts_type()?));
             }
             if self.check(&TokenKind::Arrow) {
                 self.advance();
@@ -3312,7 +3459,7 @@ impl<'a> Parser<'a> {
                 let params = self.parse_params_inner()?;
                 self.expect(&TokenKind::RParen)?;
                 if self.eat(&TokenKind::Colon) {
-                    let _ = self.parse_ts_type()?;
+                    self.pending_arrow_return_type = Some(Box::new(self.parse_ts_type()?));
                 }
                 self.expect(&TokenKind::Arrow)?;
                 return self.parse_arrow_body(params, is_async, outer_start);
@@ -3347,6 +3494,8 @@ impl<'a> Parser<'a> {
                     }
                     let rest_end = self.current.span.start;
                     params.push(Param {
+                        #[cfg(feature = "typescript")]
+                        optional: false,
                         binding,
                         default: None,
                         rest: true,
@@ -3355,7 +3504,7 @@ impl<'a> Parser<'a> {
                     self.expect(&TokenKind::RParen)?;
                     #[cfg(feature = "typescript")]
                     if self.options.typescript && self.eat(&TokenKind::Colon) {
-                        let _ = self.parse_ts_type()?;
+                        self.pending_arrow_return_type = Some(Box::new(self.parse_ts_type()?));
                     }
                     self.expect(&TokenKind::Arrow)?;
                     return self.parse_arrow_body(params, is_async, outer_start);
@@ -3381,11 +3530,13 @@ impl<'a> Parser<'a> {
                 let saved_lexer = self.lexer.clone();
                 let saved_token = self.current.clone();
                 self.advance();
-                let type_ok = self.parse_ts_type().is_ok();
-                if type_ok && self.check(&TokenKind::Arrow) {
-                    self.advance();
-                    let params = self.exprs_to_params(exprs)?;
-                    return self.parse_arrow_body(params, is_async, outer_start);
+                if let Ok(ty) = self.parse_ts_type() {
+                    if self.check(&TokenKind::Arrow) {
+                        self.advance();
+                        let params = self.exprs_to_params(exprs)?;
+                        self.pending_arrow_return_type = Some(Box::new(ty));
+                        return self.parse_arrow_body(params, is_async, outer_start);
+                    }
                 }
                 self.lexer = saved_lexer;
                 self.current = saved_token;
@@ -3438,11 +3589,13 @@ impl<'a> Parser<'a> {
             let saved_lexer = self.lexer.clone();
             let saved_token = self.current.clone();
             self.advance(); // consume ':'
-            let type_ok = self.parse_ts_type().is_ok();
-            if type_ok && self.check(&TokenKind::Arrow) {
-                self.advance();
-                let params = self.exprs_to_params(vec![first])?;
-                return self.parse_arrow_body(params, is_async, outer_start);
+            if let Ok(ty) = self.parse_ts_type() {
+                if self.check(&TokenKind::Arrow) {
+                    self.advance();
+                    let params = self.exprs_to_params(vec![first])?;
+                    self.pending_arrow_return_type = Some(Box::new(ty));
+                    return self.parse_arrow_body(params, is_async, outer_start);
+                }
             }
             // Not an arrow — restore lexer state
             self.lexer = saved_lexer;
@@ -3488,6 +3641,8 @@ impl<'a> Parser<'a> {
     fn expr_to_param(&self, expr: Expr) -> Result<Param, ParseError> {
         match expr.kind {
             ExprKind::Ident(name) => Ok(Param {
+                #[cfg(feature = "typescript")]
+                optional: false,
                 binding: Binding::new(
                     BindingKind::Ident {
                         name,
@@ -3507,6 +3662,8 @@ impl<'a> Parser<'a> {
             } => {
                 let binding = self.expr_to_binding(*left)?;
                 Ok(Param {
+                    #[cfg(feature = "typescript")]
+                    optional: false,
                     binding,
                     default: Some(*right),
                     rest: false,
@@ -3516,6 +3673,8 @@ impl<'a> Parser<'a> {
             ExprKind::Spread(arg) => {
                 let binding = self.expr_to_binding(*arg)?;
                 Ok(Param {
+                    #[cfg(feature = "typescript")]
+                    optional: false,
                     binding,
                     default: None,
                     rest: true,
@@ -3525,6 +3684,8 @@ impl<'a> Parser<'a> {
             ExprKind::Object(_) | ExprKind::Array(_) => {
                 let binding = self.expr_to_binding(expr.clone())?;
                 Ok(Param {
+                    #[cfg(feature = "typescript")]
+                    optional: false,
                     binding,
                     default: None,
                     rest: false,
@@ -3692,6 +3853,8 @@ impl<'a> Parser<'a> {
                     None
                 };
                 params.push(Param {
+                    #[cfg(feature = "typescript")]
+                    optional: false,
                     span: expr.span,
                     binding,
                     default,
@@ -3709,7 +3872,11 @@ impl<'a> Parser<'a> {
             }
             let param_start = self.current.span.start;
             let rest = self.eat(&TokenKind::Spread);
-            let binding = self.parse_binding()?;
+            #[allow(unused_mut)]
+            let mut binding = self.parse_binding()?;
+            #[cfg(feature = "typescript")]
+            let optional = self.parse_ts_optional_param(&mut binding)?;
+            #[cfg(not(feature = "typescript"))]
             self.eat(&TokenKind::Question);
             let default = if self.eat(&TokenKind::Eq) {
                 Some(self.parse_assign_expr()?)
@@ -3718,6 +3885,8 @@ impl<'a> Parser<'a> {
             };
             let param_end = self.current.span.start;
             params.push(Param {
+                #[cfg(feature = "typescript")]
+                optional,
                 binding,
                 default,
                 rest,
@@ -3730,7 +3899,7 @@ impl<'a> Parser<'a> {
 
         self.expect(&TokenKind::RParen)?;
         if self.eat(&TokenKind::Colon) {
-            let _ = self.parse_ts_type()?;
+            self.pending_arrow_return_type = Some(Box::new(self.parse_ts_type()?));
         }
         self.expect(&TokenKind::Arrow)?;
         self.parse_arrow_body(params, is_async, start)
@@ -3743,6 +3912,10 @@ impl<'a> Parser<'a> {
         is_async: bool,
         start: u32,
     ) -> Result<Expr, ParseError> {
+        #[cfg(feature = "typescript")]
+        let type_params = self.pending_arrow_type_params.take();
+        #[cfg(feature = "typescript")]
+        let return_type = self.pending_arrow_return_type.take();
         let body = if self.check(&TokenKind::LBrace) {
             self.expect(&TokenKind::LBrace)?;
             let mut stmts = Vec::new();
@@ -3763,9 +3936,9 @@ impl<'a> Parser<'a> {
                 is_async,
                 span: Span::new(start, end),
                 #[cfg(feature = "typescript")]
-                type_params: None,
+                type_params,
                 #[cfg(feature = "typescript")]
-                return_type: None,
+                return_type,
             })),
             Span::new(start, end),
         ))
@@ -4008,4 +4181,58 @@ mod tests {
         let ast = parse("class Foo { constructor() {} bar() {} }").unwrap();
         assert_eq!(ast.stmts.len(), 1);
     }
+
+    #[test]
+    fn test_new_expression_arguments() {
+        let ast = parse("new URL(a, b).href;").unwrap();
+        let StmtKind::Expr(expr) = &ast.stmts[0].kind else {
+            panic!("expected expression statement");
+        };
+        let ExprKind::Member { object, .. } = &expr.kind else {
+            panic!("expected member expression, got {:?}", expr.kind);
+        };
+        let ExprKind::New { callee, args } = &object.kind else {
+            panic!("expected new expression, got {:?}", object.kind);
+        };
+        assert!(matches!(&callee.kind, ExprKind::Ident(name) if name == "URL"));
+        assert_eq!(args.len(), 2);
+    }
+
+    #[test]
+    fn test_pure_annotations() {
+        let ast = parse(
+            "a = /* @__PURE__ */ f();\nb = /*#__PURE__*/ (function() {})();\nc = /* pure */ g();",
+        )
+        .unwrap();
+        let value = |i: usize| {
+            let StmtKind::Expr(expr) = &ast.stmts[i].kind else {
+                panic!("expected expression statement");
+            };
+            let ExprKind::Assign { right, .. } = &expr.kind else {
+                panic!("expected assignment, got {:?}", expr.kind);
+            };
+            right.as_ref().clone()
+        };
+        assert!(ast.is_pure_call(&value(0)));
+        assert!(ast.is_pure_call(&value(1)));
+        assert!(!ast.is_pure_call(&value(2)));
+    }
+
+    #[test]
+    fn test_leading_comments_and_pragmas() {
+        let source = "// @ts-ignore\nfoo();\n/** @jsx h */\n\n/** Docs. */\nfunction bar() {}\n";
+        let ast = Parser::new(source, ParserOptions::default())
+            .parse_with_comments()
+            .unwrap();
+        let leading = ast.leading_comments(ast.stmts[0].span.start);
+        assert_eq!(leading.len(), 1);
+        assert_eq!(leading[0].pragma(source), Some(("ts-ignore", "")));
+
+        let leading = ast.leading_comments(ast.stmts[1].span.start);
+        assert_eq!(leading.len(), 2);
+        assert_eq!(leading[0].pragma(source), Some(("jsx", "h")));
+        assert!(leading[1].is_jsdoc(source));
+        assert_eq!(leading[1].text(source), "* Docs. ");
+        assert_eq!(leading[1].pragma(source), None);
+    }
 }
@@ -1843,6 +1843,25 @@ impl<'a> Parser<'a> {
         }
     }
 
+    /// Parse the `?` of an optional parameter and a type annotation the
+    /// binding didn't take (`x?: number`, `{ a }: Props`), attaching the
+    /// type to `binding`. Returns whether the parameter is optional.
+    pub(crate) fn parse_ts_optional_param(
+        &mut self,
+        binding: &mut Binding,
+    ) -> Result<bool, ParseError> {
+        let optional = self.eat(&TokenKind::Question);
+        if self.eat(&TokenKind::Colon) {
+            let ty = Some(Box::new(self.parse_ts_type()?));
+            match &mut binding.kind {
+                BindingKind::Ident { type_ann, .. }
+                | BindingKind::Array { type_ann, .. }
+                | BindingKind::Object { type_ann, .. } => *type_ann = ty,
+            }
+        }
+        Ok(optional)
+    }
+
     /// Try to parse type arguments `<T, U>` before a call expression.
     /// Returns true (and consumes the type args) if successful and followed by `(`.
     /// Returns false (and restores parser state) otherwise.
@@ -1861,12 +1880,15 @@ impl<'a> Parser<'a> {
                 let params = self.parse_params_inner()?;
                 self.expect(&TokenKind::RParen)?;
                 // Optional return type
-                if self.eat(&TokenKind::Colon) {
-                    let _ = self.parse_ts_type()?;
-                }
+                let return_type = if self.eat(&TokenKind::Colon) {
+                    Some(Box::new(self.parse_ts_type()?))
+                } else {
+                    None
+                };
                 if self.check(&TokenKind::Arrow) {
                     self.advance();
-                    let _ = type_params; // type params stripped in codegen
+                    self.pending_arrow_type_params = Some(type_params);
+                    self.pending_arrow_return_type = return_type;
                     return self.parse_arrow_body(params, false, start);
                 }
             }
@@ -1877,13 +1899,18 @@ impl<'a> Parser<'a> {
         self.lexer = saved_lexer;
 
         self.expect(&TokenKind::Lt)?;
-        let _ = self.parse_ts_type_impl()?;
+        let ty = self.parse_ts_type_impl()?;
         self.expect_ts_gt()?;
         // Parse the expression being asserted
         let expr = self.parse_unary_expr()?;
         let end = self.current.span.start;
-        // Return the inner expression (type assertion stripped)
-        Ok(Expr::new(expr.kind, Span::new(start, end)))
+        Ok(Expr::new(
+            ExprKind::TsTypeAssertion {
+                ty: Box::new(ty),
+                expr: Box::new(expr),
+            },
+            Span::new(start, end),
+        ))
     }
 
     pub(crate) fn try_parse_ts_type_args_for_call(&mut self) -> bool {
@@ -0,0 +1,386 @@
+//! Read-only AST traversal.
+//!
+//! [`walk_exprs`] visits every expression of a program, including those in
+//! nested functions, classes, patterns and JSX. Analyses that look for an
+//! expression shape anywhere in a module (e.g. `new URL('./x', import.meta.url)`)
+//! build on it instead of matching each statement kind themselves.
+//!
+//! TypeScript type annotations are not visited.
+
+use crate::ast::*;
+
+/// Call `f` for every expression in `stmts`, each expression before the
+/// expressions nested in it.
+pub fn walk_exprs<'a, F: FnMut(&'a Expr)>(stmts: &'a [Stmt], f: &mut F) {
+    for stmt in stmts {
+        walk_stmt(stmt, f);
+    }
+}
+
+fn walk_stmt<'a, F: FnMut(&'a Expr)>(stmt: &'a Stmt, f: &mut F) {
+    match &stmt.kind {
+        StmtKind::Var { decls, .. } => walk_decls(decls, f),
+        StmtKind::Function(func) => walk_function(func, f),
+        StmtKind::Class(class) => walk_class(class, f),
+        StmtKind::Block(stmts) => walk_exprs(stmts, f),
+        StmtKind::If {
+            test,
+            consequent,
+            alternate,
+        } => {
+            walk_expr(test, f);
+            walk_stmt(consequent, f);
+            if let Some(alternate) = alternate {
+                walk_stmt(alternate, f);
+            }
+        }
+        StmtKind::Switch {
+            discriminant,
+            cases,
+        } => {
+            walk_expr(discriminant, f);
+            for case in cases {
+                if let Some(test) = &case.test {
+                    walk_expr(test, f);
+                }
+                walk_exprs(&case.consequent, f);
+            }
+        }
+        StmtKind::For {
+            init,
+            test,
+            update,
+            body,
+        } => {
+            if let Some(init) = init {
+                walk_for_init(init, f);
+            }
+            for expr in [test, update].into_iter().flatten() {
+                walk_expr(expr, f);
+            }
+            walk_stmt(body, f);
+        }
+        StmtKind::ForIn { left, right, body }
+        | StmtKind::ForOf {
+            left, right, body, ..
+        } => {
+            walk_for_init(left, f);
+            walk_expr(right, f);
+            walk_stmt(body, f);
+        }
+        StmtKind::While { test, body } | StmtKind::DoWhile { body, test } => {
+            walk_expr(test, f);
+            walk_stmt(body, f);
+        }
+        StmtKind::Return { arg } => {
+            if let Some(arg) = arg {
+                walk_expr(arg, f);
+            }
+        }
+        StmtKind::Throw { arg } | StmtKind::Expr(arg) => walk_expr(arg, f),
+        StmtKind::Try {
+            block,
+            handler,
+            finalizer,
+        } => {
+            walk_exprs(block, f);
+            if let Some(handler) = handler {
+                if let Some(param) = &handler.param {
+                    walk_binding(param, f);
+                }
+                walk_exprs(&handler.body, f);
+            }
+            if let Some(finalizer) = finalizer {
+                walk_exprs(finalizer, f);
+            }
+        }
+        StmtKind::Labeled { body, .. } => walk_stmt(body, f),
+        StmtKind::With { object, body } => {
+            walk_expr(object, f);
+            walk_stmt(body, f);
+        }
+        StmtKind::Export(export) => match export.as_ref() {
+            ExportDecl::Default { expr, .. } => walk_expr(expr, f),
+            ExportDecl::Decl { decl, .. } => walk_stmt(decl, f),
+            ExportDecl::Named { .. } | ExportDecl::All { .. } => {}
+        },
+        StmtKind::Break { .. }
+        | StmtKind::Continue { .. }
+        | StmtKind::Empty
+        | StmtKind::Debugger
+        | StmtKind::Import(_) => {}
+        #[cfg(feature = "typescript")]
+        StmtKind::TsEnum(ts_enum) => {
+            for member in &ts_enum.members {
+                if let Some(init) = &member.init {
+                    walk_expr(init, f);
+                }
+            }
+        }
+        #[cfg(feature = "typescript")]
+        StmtKind::TsNamespace(namespace) => walk_exprs(&namespace.body, f),
+        #[cfg(feature = "typescript")]
+        StmtKind::TsDeclare(stmt) => walk_stmt(stmt, f),
+        #[cfg(feature = "typescript")]
+        StmtKind::TsTypeAlias(_) | StmtKind::TsInterface(_) => {}
+    }
+}
+
+fn walk_expr<'a, F: FnMut(&'a Expr)>(expr: &'a Expr, f: &mut F) {
+    f(expr);
+    match &expr.kind {
+        ExprKind::Template { exprs, .. } => {
+            for expr in exprs {
+                walk_expr(expr, f);
+            }
+        }
+        ExprKind::Array(elements) => {
+            for element in elements.iter().flatten() {
+                walk_expr(element, f);
+            }
+        }
+        ExprKind::Object(properties) => {
+            for property in properties {
+                walk_property_key(&property.key, f);
+                walk_expr(&property.value, f);
+            }
+        }
+        ExprKind::Function(func) => walk_function(func, f),
+        ExprKind::Arrow(arrow) => {
+            walk_params(&arrow.params, f);
+            match &arrow.body {
+                ArrowBody::Expr(body) => walk_expr(body, f),
+                ArrowBody::Block(stmts) => walk_exprs(stmts, f),
+            }
+        }
+        ExprKind::Class(class) => walk_class(class, f),
+        ExprKind::Unary { arg, .. }
+        | ExprKind::Update { arg, .. }
+        | ExprKind::Spread(arg)
+        | ExprKind::Await(arg)
+        | ExprKind::Import(arg) => walk_expr(arg, f),
+        ExprKind::Binary { left, right, .. } | ExprKind::Assign { left, right, .. } => {
+            walk_expr(left, f);
+            walk_expr(right, f);
+        }
+        ExprKind::Conditional {
+            test,
+            consequent,
+            alternate,
+        } => {
+            walk_expr(test, f);
+            walk_expr(consequent, f);
+            walk_expr(alternate, f);
+        }
+        ExprKind::Sequence(exprs) => {
+            for expr in exprs {
+                walk_expr(expr, f);
+            }
+        }
+        ExprKind::Member {
+            object,
+            property,
+            computed,
+        }
+        | ExprKind::OptionalMember {
+            object,
+            property,
+            computed,
+        } => {
+            walk_expr(object, f);
+            if *computed {
+                walk_expr(property, f);
+            }
+        }
+        ExprKind::Call { callee, args }
+        | ExprKind::OptionalCall { callee, args }
+        | ExprKind::New { callee, args } => {
+            walk_expr(callee, f);
+            for arg in args {
+                walk_expr(arg, f);
+            }
+        }
+        ExprKind::TaggedTemplate { tag, quasi } => {
+            walk_expr(tag, f);
+            walk_expr(quasi, f);
+        }
+        ExprKind::Yield { arg, .. } => {
+            if let Some(arg) = arg {
+                walk_expr(arg, f);
+            }
+        }
+        ExprKind::Null
+        | ExprKind::Bool(_)
+        | ExprKind::Number(_)
+        | ExprKind::BigInt(_)
+        | ExprKind::String(_)
+        | ExprKind::Regex { .. }
+        | ExprKind::TemplateNoSub(_)
+        | ExprKind::Ident(_)
+        | ExprKind::This
+        | ExprKind::Super
+        | ExprKind::MetaProperty { .. } => {}
+        #[cfg(feature = "jsx")]
+        ExprKind::JsxElement(element) => walk_jsx_element(element, f),
+        #[cfg(feature = "jsx")]
+        ExprKind::JsxFragment(fragment) => walk_jsx_children(&fragment.children, f),
+        #[cfg(feature = "typescript")]
+        ExprKind::TsAs { expr, .. }
+        | ExprKind::TsSatisfies { expr, .. }
+        | ExprKind::TsNonNull(expr)
+        | ExprKind::TsTypeAssertion { expr, .. } => walk_expr(expr, f),
+    }
+}
+
+fn walk_decls<'a, F: FnMut(&'a Expr)>(decls: &'a [VarDeclarator], f: &mut F) {
+    for decl in decls {
+        walk_binding(&decl.binding, f);
+        if let Some(init) = &decl.init {
+            walk_expr(init, f);
+        }
+    }
+}
+
+fn walk_for_init<'a, F: FnMut(&'a Expr)>(init: &'a ForInit, f: &mut F) {
+    match init {
+        ForInit::Var { decls, .. } => walk_decls(decls, f),
+        ForInit::Expr(expr) => walk_expr(expr, f),
+    }
+}
+
+fn walk_function<'a, F: FnMut(&'a Expr)>(func: &'a Function, f: &mut F) {
+    walk_params(&func.params, f);
+    walk_exprs(&func.body, f);
+}
+
+fn walk_params<'a, F: FnMut(&'a Expr)>(params: &'a [Param], f: &mut F) {
+    for param in params {
+        walk_binding(&param.binding, f);
+        if let Some(default) = &param.default {
+            walk_expr(default, f);
+        }
+    }
+}
+
+fn walk_class<'a, F: FnMut(&'a Expr)>(class: &'a Class, f: &mut F) {
+    for decorator in &class.decorators {
+        walk_expr(decorator, f);
+    }
+    if let Some(super_class) = &class.super_class {
+        walk_expr(super_class, f);
+    }
+    for member in &class.body {
+        for decorator in &member.decorators {
+            walk_expr(decorator, f);
+        }
+        match &member.kind {
+            ClassMemberKind::Method { key, value, .. } => {
+                walk_property_key(key, f);
+                walk_function(value, f);
+            }
+            ClassMemberKind::Property { key, value, .. } => {
+                walk_property_key(key, f);
+                if let Some(value) = value {
+                    walk_expr(value, f);
+                }
+            }
+            ClassMemberKind::StaticBlock(stmts) => walk_exprs(stmts, f),
+            ClassMemberKind::Empty => {}
+        }
+    }
+}
+
+fn walk_property_key<'a, F: FnMut(&'a Expr)>(key: &'a PropertyKey, f: &mut F) {
+    if let PropertyKey::Computed(expr) = key {
+        walk_expr(expr, f);
+    }
+}
+
+/// Default values and computed keys in binding patterns.
+fn walk_binding<'a, F: FnMut(&'a Expr)>(binding: &'a Binding, f: &mut F) {
+    match &binding.kind {
+        BindingKind::Ident { .. } => {}
+        BindingKind::Array { elements, .. } => {
+            for element in elements.iter().flatten() {
+                walk_binding(&element.binding, f);
+                if let Some(default) = &element.default {
+                    walk_expr(default, f);
+                }
+            }
+        }
+        BindingKind::Object { properties, .. } => {
+            for property in properties {
+                walk_property_key(&property.key, f);
+                walk_binding(&property.value, f);
+                if let Some(default) = &property.default {
+                    walk_expr(default, f);
+                }
+            }
+        }
+    }
+}
+
+#[cfg(feature = "jsx")]
+fn walk_jsx_element<'a, F: FnMut(&'a Expr)>(element: &'a JsxElement, f: &mut F) {
+    for attribute in &element.opening.attributes {
+        match attribute {
+            JsxAttribute::Attribute {
+                value: Some(value), ..
+            } => match value {
+                JsxAttrValue::Expr(expr) => walk_expr(expr, f),
+                JsxAttrValue::Element(element) => walk_jsx_element(element, f),
+                JsxAttrValue::Fragment(fragment) => walk_jsx_children(&fragment.children, f),
+                JsxAttrValue::String(_) => {}
+            },
+            JsxAttribute::Attribute { value: None, .. } => {}
+            JsxAttribute::SpreadAttribute { argument, .. } => walk_expr(argument, f),
+        }
+    }
+    walk_jsx_children(&element.children, f);
+}
+
+#[cfg(feature = "jsx")]
+fn walk_jsx_children<'a, F: FnMut(&'a Expr)>(children: &'a [JsxChild], f: &mut F) {
+    for child in children {
+        match child {
+            JsxChild::Element(element) => walk_jsx_element(element, f),
+            JsxChild::Fragment(fragment) => walk_jsx_children(&fragment.children, f),
+            JsxChild::Expr(expr) | JsxChild::Spread(expr) => walk_expr(expr, f),
+            JsxChild::Text(_) => {}
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{Parser, ParserOptions};
+
+    fn idents(source: &str) -> Vec<String> {
+        let options = ParserOptions {
+            module: true,
+            ..Default::default()
+        };
+        let ast = Parser::new(source, options).parse().unwrap();
+        let mut names = Vec::new();
+        walk_exprs(&ast.stmts, &mut |expr: &Expr| {
+            if let ExprKind::Ident(name) = &expr.kind {
+                names.push(name.clone());
+            }
+        });
+        names
+    }
+
+    #[test]
+    fn test_walk_nested_expressions() {
+        assert_eq!(
+            idents(
+                "const { x = a } = b;\n\
+                 function f(y = c) { return () => d(e); }\n\
+                 class K extends g { [h] = i; static { j; } }\n\
+                 export default k ? l : [m, ...n];"
+            ),
+            ["a", "b", "c", "d", "e", "g", "h", "i", "j", "k", "l", "m", "n"]
+        );
+    }
+}